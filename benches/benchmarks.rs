@@ -13,8 +13,9 @@ use rxing::oned::{
 use rxing::pdf417::PDF417Reader;
 use rxing::qrcode::QRCodeReader;
 use rxing::{BinaryBitmap, BufferedImageLuminanceSource, Reader};
-use rxing::{FilteredImageReader, MultiFormatReader};
+use rxing::{DecodeHints, DecoderPool, FilteredImageReader, MultiFormatReader};
 use std::path::Path;
+use std::sync::Mutex;
 
 fn get_image(
     path: impl AsRef<Path>,
@@ -228,6 +229,125 @@ fn multi_barcode_complex_benchmark(c: &mut Criterion) {
     });
 }
 
+/// A 3840x2160 frame with a grid of 6 by 4 QR codes of 5 pixel modules
+#[cfg(feature = "parallel")]
+fn many_symbols_4k() -> Vec<u8> {
+    use rxing::{BarcodeFormat, MultiFormatWriter, Writer};
+
+    let (width, height) = (3840, 2160);
+    let mut luma = vec![255u8; width * height];
+    for row in 0..4 {
+        for column in 0..6 {
+            let matrix = MultiFormatWriter
+                .encode(
+                    &format!("symbol {row} {column}"),
+                    &BarcodeFormat::QR_CODE,
+                    0,
+                    0,
+                )
+                .unwrap();
+            let (left, top) = (column * 640 + 80, row * 540 + 80);
+            for y in 0..matrix.getHeight() as usize * 5 {
+                for x in 0..matrix.getWidth() as usize * 5 {
+                    if matrix.get((x / 5) as u32, (y / 5) as u32) {
+                        luma[(top + y) * width + left + x] = 0;
+                    }
+                }
+            }
+        }
+    }
+    luma
+}
+
+#[cfg(feature = "parallel")]
+fn multi_barcode_parallel_benchmark(c: &mut Criterion) {
+    let luma = many_symbols_4k();
+    let mut group = c.benchmark_group("multi_barcode_4k");
+    group.sample_size(10);
+    group.bench_function("sequential", |b| {
+        b.iter(|| rxing::helpers::detect_multiple_in_luma(luma.clone(), 3840, 2160));
+    });
+    for threads in [1, 2, 4, 8] {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .unwrap();
+        group.bench_with_input(
+            criterion::BenchmarkId::new("parallel", threads),
+            &threads,
+            |b, _| {
+                b.iter(|| {
+                    pool.install(|| {
+                        rxing::helpers::detect_multiple_in_luma_parallel(luma.clone(), 3840, 2160)
+                    })
+                });
+            },
+        );
+    }
+    group.finish();
+}
+
+#[cfg(not(feature = "parallel"))]
+fn multi_barcode_parallel_benchmark(_c: &mut Criterion) {}
+
+/// Decodes 32 requests on 8 threads: with a reader built per request, one shared reader behind a
+/// lock, and pools of readers
+fn decoder_pool_benchmark(c: &mut Criterion) {
+    const REQUESTS: usize = 32;
+    const THREADS: usize = 8;
+
+    let picture = image::open("test_resources/blackbox/qrcode-2/1.png").unwrap();
+    let image = || {
+        BinaryBitmap::new(HybridBinarizer::new(BufferedImageLuminanceSource::new(
+            picture.clone(),
+        )))
+    };
+    let serve = |decode: &(dyn Fn() + Sync)| {
+        std::thread::scope(|scope| {
+            for _ in 0..THREADS {
+                scope.spawn(|| {
+                    for _ in 0..REQUESTS / THREADS {
+                        decode();
+                    }
+                });
+            }
+        });
+    };
+
+    let mut group = c.benchmark_group("decoder_pool");
+    group.sample_size(10);
+    group.bench_function("reader_per_request", |b| {
+        b.iter(|| {
+            serve(&|| {
+                let _res = MultiFormatReader::default().decode(&mut image());
+            })
+        });
+    });
+    let shared = Mutex::new(MultiFormatReader::default());
+    group.bench_function("locked_reader", |b| {
+        b.iter(|| {
+            serve(&|| {
+                let _res = shared.lock().unwrap().decode(&mut image());
+            })
+        });
+    });
+    for workers in [1, 2, 4, 8] {
+        let pool = DecoderPool::new(workers, DecodeHints::default());
+        group.bench_with_input(
+            criterion::BenchmarkId::new("pool", workers),
+            &workers,
+            |b, _| {
+                b.iter(|| {
+                    serve(&|| {
+                        let _res = pool.decode(&mut image());
+                    })
+                });
+            },
+        );
+    }
+    group.finish();
+}
+
 criterion_group!(
     benches,
     aztec_benchmark,
@@ -249,5 +369,7 @@ criterion_group!(
     multi_barcode_benchmark,
     multi_barcode_filtered_reader_benchmark,
     multi_barcode_complex_benchmark,
+    multi_barcode_parallel_benchmark,
+    decoder_pool_benchmark,
 );
 criterion_main!(benches);
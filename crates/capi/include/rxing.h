@@ -0,0 +1,345 @@
+#ifndef RXING_H
+#define RXING_H
+
+/* Generated with cbindgen from rxing::capi, do not edit by hand. */
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+/**
+ * Spend more time to find barcodes in difficult images
+ */
+#define RXING_DECODE_FLAG_TRY_HARDER (1 << 0)
+
+/**
+ * The image is a pure, unrotated barcode with little or no border, like a generated one
+ */
+#define RXING_DECODE_FLAG_PURE_BARCODE (1 << 1)
+
+/**
+ * Also look for barcodes with light bars or modules on a dark background
+ */
+#define RXING_DECODE_FLAG_ALSO_INVERTED (1 << 2)
+
+/**
+ * Decode Code 128 barcodes without an FNC1 as GS1
+ */
+#define RXING_DECODE_FLAG_ASSUME_GS1 (1 << 3)
+
+/**
+ * Include the start and end characters in the text of Codabar barcodes
+ */
+#define RXING_DECODE_FLAG_RETURN_CODABAR_START_END (1 << 4)
+
+/**
+ * Treat the last character of Code 39 barcodes as a check digit
+ */
+#define RXING_DECODE_FLAG_ASSUME_CODE39_CHECK_DIGIT (1 << 5)
+
+/**
+ * Decode Telepen barcodes as numeric
+ */
+#define RXING_DECODE_FLAG_TELEPEN_AS_NUMERIC (1 << 6)
+
+/**
+ * The outcome of a C API call
+ */
+typedef enum RxingErrorCode {
+  RXING_ERROR_CODE_OK = 0,
+  /**
+   * No barcode was found in the image
+   */
+  RXING_ERROR_CODE_NOT_FOUND = 1,
+  /**
+   * A barcode was found, but could not be decoded
+   */
+  RXING_ERROR_CODE_FORMAT = 2,
+  /**
+   * A barcode was found, but its check characters did not match
+   */
+  RXING_ERROR_CODE_CHECKSUM = 3,
+  /**
+   * An argument was out of range, or a string was not valid UTF-8
+   */
+  RXING_ERROR_CODE_ILLEGAL_ARGUMENT = 4,
+  /**
+   * The contents could not be encoded in the requested format
+   */
+  RXING_ERROR_CODE_WRITER = 5,
+  /**
+   * The format is not supported for this operation
+   */
+  RXING_ERROR_CODE_UNSUPPORTED = 6,
+  /**
+   * A required pointer was null
+   */
+  RXING_ERROR_CODE_NULL_POINTER = 7,
+  /**
+   * Any other failure
+   */
+  RXING_ERROR_CODE_INTERNAL = 8,
+} RxingErrorCode;
+
+/**
+ * The barcode formats, in the order of `BarcodeFormat::ALL`. Functions take formats as
+ * `uint32_t` holding one of these values.
+ */
+enum RxingFormat
+#if __STDC_VERSION__ >= 202311L
+  : uint32_t
+#endif // __STDC_VERSION__ >= 202311L
+ {
+  RXING_FORMAT_AZTEC = 0,
+  RXING_FORMAT_CODABAR = 1,
+  RXING_FORMAT_CODE39 = 2,
+  RXING_FORMAT_CODE93 = 3,
+  RXING_FORMAT_CODE128 = 4,
+  RXING_FORMAT_DATA_MATRIX = 5,
+  RXING_FORMAT_EAN8 = 6,
+  RXING_FORMAT_EAN13 = 7,
+  RXING_FORMAT_ITF = 8,
+  RXING_FORMAT_MAXICODE = 9,
+  RXING_FORMAT_PDF417 = 10,
+  RXING_FORMAT_QR_CODE = 11,
+  RXING_FORMAT_MICRO_QR_CODE = 12,
+  RXING_FORMAT_RECTANGULAR_MICRO_QR_CODE = 13,
+  RXING_FORMAT_RSS14 = 14,
+  RXING_FORMAT_RSS_EXPANDED = 15,
+  RXING_FORMAT_TELEPEN = 16,
+  RXING_FORMAT_UPC_A = 17,
+  RXING_FORMAT_UPC_E = 18,
+  RXING_FORMAT_UPC_EAN_EXTENSION = 19,
+  RXING_FORMAT_DX_FILM_EDGE = 20,
+  RXING_FORMAT_PHARMACODE = 21,
+  RXING_FORMAT_MSI = 22,
+  /**
+   * Decode any supported format
+   */
+  RXING_FORMAT_ANY = 4294967295,
+};
+#if __STDC_VERSION__ >= 202311L
+typedef enum RxingFormat RxingFormat;
+#else
+typedef uint32_t RxingFormat;
+#endif // __STDC_VERSION__ >= 202311L
+
+/**
+ * The options of [`rxing_decode`] and [`rxing_decode_multi`]. A zeroed struct decodes every
+ * format with the default options.
+ */
+typedef struct RxingDecodeHints {
+  /**
+   * A bit for each format to look for, `1 << format` for an [`RxingFormat`] value, or 0
+   * for all formats
+   */
+  uint64_t formats;
+  /**
+   * `RXING_DECODE_FLAG_*` values or'ed together
+   */
+  uint32_t flags;
+  /**
+   * The character set of text without an ECI, NUL terminated, or null to guess it
+   */
+  const char *character_set;
+} RxingDecodeHints;
+
+typedef struct RxingPoint {
+  float x;
+  float y;
+} RxingPoint;
+
+/**
+ * A decoded barcode. All pointers are owned by the library.
+ */
+typedef struct RxingResult {
+  /**
+   * The decoded text, NUL terminated UTF-8
+   */
+  char *text;
+  uint8_t *raw_bytes;
+  size_t raw_bytes_len;
+  /**
+   * A [`RxingFormat`] value
+   */
+  uint32_t format;
+  struct RxingPoint *points;
+  size_t points_len;
+} RxingResult;
+
+/**
+ * A list of decoded barcodes
+ */
+typedef struct RxingResults {
+  struct RxingResult *results;
+  size_t len;
+} RxingResults;
+
+/**
+ * An 8 bit grayscale image, one byte per pixel, rows stored top to bottom
+ */
+typedef struct RxingImage {
+  uint8_t *data;
+  uint32_t width;
+  uint32_t height;
+} RxingImage;
+
+/**
+ * The NUL terminated version string of the library
+ */
+const char *rxing_version(void);
+
+/**
+ * Describes the last error on the calling thread. The string is owned by the library and
+ * valid until the next call into the library on the same thread.
+ */
+const char *rxing_last_error(void);
+
+/**
+ * Decodes one barcode from an 8 bit grayscale image.
+ *
+ * # Safety
+ * `luma` must point to `width * height` readable bytes and `out` to writable memory for an
+ * [`RxingResult`]. On success `out` must be released with [`rxing_result_free`].
+ */
+enum RxingErrorCode rxing_decode_luma(const uint8_t *luma,
+                                      uint32_t width,
+                                      uint32_t height,
+                                      uint32_t format,
+                                      bool try_harder,
+                                      struct RxingResult *out);
+
+/**
+ * Decodes all barcodes in an 8 bit grayscale image. Finding no barcode is reported as
+ * [`RxingErrorCode::NotFound`].
+ *
+ * # Safety
+ * `luma` must point to `width * height` readable bytes and `out` to writable memory for an
+ * [`RxingResults`]. On success `out` must be released with [`rxing_results_free`].
+ */
+enum RxingErrorCode rxing_decode_multiple_luma(const uint8_t *luma,
+                                               uint32_t width,
+                                               uint32_t height,
+                                               uint32_t format,
+                                               bool try_harder,
+                                               struct RxingResults *out);
+
+/**
+ * Decodes one barcode from an 8 bit grayscale image with the options in `hints`, null for
+ * the defaults.
+ *
+ * # Safety
+ * `luma` must point to `width * height` readable bytes, `hints` must be null or point to an
+ * [`RxingDecodeHints`] and `out` to writable memory for an [`RxingResult`]. On success `out`
+ * must be released with [`rxing_result_free`].
+ */
+enum RxingErrorCode rxing_decode(const uint8_t *luma,
+                                 uint32_t width,
+                                 uint32_t height,
+                                 const struct RxingDecodeHints *hints,
+                                 struct RxingResult *out);
+
+/**
+ * Decodes all barcodes in an 8 bit grayscale image with the options in `hints`, null for
+ * the defaults. Finding no barcode is reported as [`RxingErrorCode::NotFound`].
+ *
+ * # Safety
+ * `luma` must point to `width * height` readable bytes, `hints` must be null or point to an
+ * [`RxingDecodeHints`] and `out` to writable memory for an [`RxingResults`]. On success
+ * `out` must be released with [`rxing_results_free`].
+ */
+enum RxingErrorCode rxing_decode_multi(const uint8_t *luma,
+                                       uint32_t width,
+                                       uint32_t height,
+                                       const struct RxingDecodeHints *hints,
+                                       struct RxingResults *out);
+
+/**
+ * Encodes `contents` into a grayscale image, black modules are 0 and white ones 255.
+ * `width` and `height` are the preferred size, the image may be larger.
+ *
+ * # Safety
+ * `contents` must be a NUL terminated string and `out` must point to writable memory for an
+ * [`RxingImage`]. On success `out` must be released with [`rxing_image_free`].
+ */
+enum RxingErrorCode rxing_encode(const char *contents,
+                                 uint32_t format,
+                                 int32_t width,
+                                 int32_t height,
+                                 struct RxingImage *out);
+
+/**
+ * The result at `index` of `results`, or null if `index` is out of range
+ *
+ * # Safety
+ * `results` must be null or filled in by [`rxing_decode_multi`] and not freed.
+ */
+const struct RxingResult *rxing_results_get(const struct RxingResults *results, size_t index);
+
+/**
+ * The NUL terminated text of `result`, or null if `result` is null
+ *
+ * # Safety
+ * `result` must be null or filled in by [`rxing_decode`] and not freed.
+ */
+const char *rxing_result_text(const struct RxingResult *result);
+
+/**
+ * The raw bytes of `result`, their number stored in `len`. Null if there are none.
+ *
+ * # Safety
+ * `result` must be null or filled in by [`rxing_decode`] and not freed, and `len` must be
+ * null or point to writable memory for a `size_t`.
+ */
+const uint8_t *rxing_result_raw_bytes(const struct RxingResult *result, size_t *len);
+
+/**
+ * The [`RxingFormat`] value of `result`, or [`RxingFormat::Any`] if `result` is null
+ *
+ * # Safety
+ * `result` must be null or filled in by [`rxing_decode`] and not freed.
+ */
+uint32_t rxing_result_format(const struct RxingResult *result);
+
+/**
+ * The number of points of `result`, 0 if `result` is null
+ *
+ * # Safety
+ * `result` must be null or filled in by [`rxing_decode`] and not freed.
+ */
+size_t rxing_result_points_len(const struct RxingResult *result);
+
+/**
+ * Stores the point at `index` of `result` in `out`, false if `index` is out of range
+ *
+ * # Safety
+ * `result` must be null or filled in by [`rxing_decode`] and not freed, and `out` must be
+ * null or point to writable memory for an [`RxingPoint`].
+ */
+bool rxing_result_point(const struct RxingResult *result, size_t index, struct RxingPoint *out);
+
+/**
+ * The NUL terminated name of an [`RxingFormat`] value, such as `"qrcode"`, or null for an
+ * unknown value. The string is owned by the library.
+ */
+const char *rxing_format_name(uint32_t format);
+
+/**
+ * # Safety
+ * `result` must be null or filled in by [`rxing_decode`], and not freed before.
+ */
+void rxing_result_free(struct RxingResult *result);
+
+/**
+ * # Safety
+ * `results` must be null or filled in by [`rxing_decode_multi`], and not freed before.
+ */
+void rxing_results_free(struct RxingResults *results);
+
+/**
+ * # Safety
+ * `image` must be null or filled in by [`rxing_encode`], and not freed before.
+ */
+void rxing_image_free(struct RxingImage *image);
+
+#endif  /* RXING_H */
@@ -0,0 +1,39 @@
+//! Builds the C ABI of [`rxing::capi`] into `librxing_capi`. The matching header is
+//! `include/rxing.h`, regenerate it with `cbindgen --config cbindgen.toml --output include/rxing.h`
+//! from this directory after changing the API.
+
+pub use rxing::capi::*;
+
+#[cfg(test)]
+mod tests {
+    const HEADER: &str = include_str!("../include/rxing.h");
+
+    #[test]
+    fn test_header_declares_api() {
+        for name in [
+            "rxing_version(void)",
+            "rxing_last_error(void)",
+            "rxing_decode_luma(",
+            "rxing_decode_multiple_luma(",
+            "rxing_decode(",
+            "rxing_decode_multi(",
+            "rxing_results_get(",
+            "rxing_result_text(",
+            "rxing_result_raw_bytes(",
+            "rxing_result_format(",
+            "rxing_result_points_len(",
+            "rxing_result_point(",
+            "rxing_format_name(",
+            "typedef struct RxingDecodeHints",
+            "RXING_DECODE_FLAG_TELEPEN_AS_NUMERIC (1 << 6)",
+            "rxing_encode(",
+            "rxing_result_free(",
+            "rxing_results_free(",
+            "rxing_image_free(",
+            "RXING_FORMAT_ANY = 4294967295",
+            "RXING_ERROR_CODE_INTERNAL = 8",
+        ] {
+            assert!(HEADER.contains(name), "{name} missing, regenerate rxing.h");
+        }
+    }
+}
@@ -1,5 +1,5 @@
 use std::{
-    collections::{HashMap, HashSet},
+    collections::HashSet,
     path::PathBuf,
     process::ExitCode,
 };
@@ -51,7 +51,7 @@ enum Commands {
         #[arg(long)]
         raw_bytes: bool,
 
-        /// Save witness data to JSON file (for zero-knowledge proof generation)
+        /// Save witness data to JSON file, or CBOR if the file name ends in .cbor (for zero-knowledge proof generation)
         #[arg(long)]
         save_witness: Option<String>,
 
@@ -347,62 +347,21 @@ fn decode_command(
     raw_bytes: &bool,
     save_witness: &Option<String>,
 ) -> ExitCode {
-    let mut hints: rxing::DecodingHintDictionary = HashMap::new();
-    if let Some(other) = other {
-        hints.insert(
-            rxing::DecodeHintType::OTHER,
-            rxing::DecodeHintValue::Other(other.to_owned()),
-        );
-    }
-    if let Some(pure_barcode) = pure_barcode {
-        hints.insert(
-            rxing::DecodeHintType::PURE_BARCODE,
-            rxing::DecodeHintValue::PureBarcode(*pure_barcode),
-        );
-    }
-
-    if let Some(character_set) = character_set {
-        hints.insert(
-            rxing::DecodeHintType::CHARACTER_SET,
-            rxing::DecodeHintValue::CharacterSet(character_set.to_owned()),
-        );
-    }
-    if let Some(allowed_lengths) = allowed_lengths {
-        hints.insert(
-            rxing::DecodeHintType::ALLOWED_LENGTHS,
-            rxing::DecodeHintValue::AllowedLengths(allowed_lengths.to_vec()),
-        );
-    }
-    if let Some(assume_code_39_check_digit) = assume_code_39_check_digit {
-        hints.insert(
-            rxing::DecodeHintType::ASSUME_CODE_39_CHECK_DIGIT,
-            rxing::DecodeHintValue::AssumeCode39CheckDigit(*assume_code_39_check_digit),
-        );
-    }
-    if let Some(assume_gs1) = assume_gs1 {
-        hints.insert(
-            rxing::DecodeHintType::ASSUME_GS1,
-            rxing::DecodeHintValue::AssumeGs1(*assume_gs1),
-        );
-    }
-    if let Some(return_codabar_start_end) = return_codabar_start_end {
-        hints.insert(
-            rxing::DecodeHintType::RETURN_CODABAR_START_END,
-            rxing::DecodeHintValue::ReturnCodabarStartEnd(*return_codabar_start_end),
-        );
-    }
-    if let Some(allowed_ean_extensions) = allowed_ean_extensions {
-        hints.insert(
-            rxing::DecodeHintType::ALLOWED_EAN_EXTENSIONS,
-            rxing::DecodeHintValue::AllowedEanExtensions(allowed_ean_extensions.to_vec()),
-        );
-    }
-    if let Some(also_inverted) = also_inverted {
-        hints.insert(
-            rxing::DecodeHintType::ALSO_INVERTED,
-            rxing::DecodeHintValue::AlsoInverted(*also_inverted),
-        );
-    }
+    let mut hints = rxing::DecodeHints {
+        Other: other.clone(),
+        PureBarcode: *pure_barcode,
+        CharacterSet: character_set.clone(),
+        AllowedLengths: allowed_lengths.clone(),
+        AssumeCode39CheckDigit: *assume_code_39_check_digit,
+        AssumeGs1: *assume_gs1,
+        ReturnCodabarStartEnd: *return_codabar_start_end,
+        AllowedEanExtensions: allowed_ean_extensions.clone(),
+        AlsoInverted: *also_inverted,
+        PossibleFormats: barcode_types
+            .as_ref()
+            .map(|barcode_types| HashSet::from_iter(barcode_types.iter().cloned())),
+        ..Default::default()
+    };
 
     // println!(
     //     "Decode '{}' with: try_harder: {}, decode_multi: {}, barcode_types: {:?}",
@@ -410,18 +369,7 @@ fn decode_command(
     // );
 
     if !try_harder {
-        hints.insert(
-            rxing::DecodeHintType::TRY_HARDER,
-            rxing::DecodeHintValue::TryHarder(false),
-        );
-    }
-    if let Some(barcode_type) = barcode_types {
-        hints.insert(
-            rxing::DecodeHintType::POSSIBLE_FORMATS,
-            rxing::DecodeHintValue::PossibleFormats(HashSet::from_iter(
-                barcode_type.iter().copied(),
-            )),
-        );
+        hints.TryHarder = Some(false);
     }
 
     let path = PathBuf::from(file_name);
@@ -433,9 +381,9 @@ fn decode_command(
 
     if *decode_multi {
         let results = if extension == "svg" {
-            rxing::helpers::detect_multiple_in_svg_with_hints(file_name, &mut hints.into())
+            rxing::helpers::detect_multiple_in_svg_with_hints(file_name, &mut hints)
         } else {
-            rxing::helpers::detect_multiple_in_file_with_hints(file_name, &mut hints.into())
+            rxing::helpers::detect_multiple_in_file_with_hints(file_name, &mut hints)
         };
         match results {
             Ok(result_array) => {
@@ -468,7 +416,7 @@ fn decode_command(
             decode_with_witness_extraction(
                 file_name,
                 &extension,
-                &mut hints.into(),
+                &mut hints,
                 save_witness.as_ref().unwrap(),
                 detailed_result,
                 detailed_results_json,
@@ -477,9 +425,9 @@ fn decode_command(
             )
         } else {
             let result = if extension == "svg" {
-                rxing::helpers::detect_in_svg_with_hints(file_name, None, &mut hints.into())
+                rxing::helpers::detect_in_svg_with_hints(file_name, None, &mut hints)
             } else {
-                rxing::helpers::detect_in_file_with_hints(file_name, None, &mut hints.into())
+                rxing::helpers::detect_in_file_with_hints(file_name, None, &mut hints)
             };
             match result {
                 Ok(result) => {
@@ -527,18 +475,41 @@ fn decode_with_witness_extraction(
         common::{FixedThresholdBinarizer, Result},
     };
 
-    // Helper to decode from a BinaryBitmap and extract witness data
+    // Helper to decode from a BinaryBitmap and stream its witness data to a file, as JSON or
+    // as CBOR, which packs the images much tighter
     fn decode_and_extract<B: rxing::Binarizer>(
         bitmap: &mut BinaryBitmap<B>,
         hints: &mut rxing::DecodeHints,
-    ) -> Result<(rxing::RXingResult, rxing::WitnessData)> {
+        witness_path: &str,
+    ) -> Result<(rxing::RXingResult, Result<()>)> {
+        use rxing::{Exceptions, WitnessFormat, WitnessWriter};
+        use std::{fs::File, io::BufWriter};
+
         let mut reader = MultiFormatReader::default();
         let result = reader.decode_with_hints(bitmap, hints)?;
-        let witness = rxing::helpers::extract_witness_data(bitmap)?;
-        Ok((result, witness))
+
+        let format = if witness_path.ends_with(".cbor") {
+            WitnessFormat::Cbor
+        } else {
+            WitnessFormat::Json
+        };
+        let io_error = |e: std::io::Error| Exceptions::illegal_argument_with(e.to_string());
+        let saved = File::create(witness_path)
+            .and_then(|file| WitnessWriter::new(BufWriter::new(file), format))
+            .map_err(io_error)
+            .and_then(|mut writer| {
+                rxing::helpers::write_witness_data(bitmap, &result, &mut writer)?;
+                writer.finish().map_err(io_error)?;
+                Ok(())
+            });
+
+        Ok((result, saved))
     }
 
-    let decode_result: Result<(rxing::RXingResult, rxing::WitnessData)> = if extension == "svg" {
+    // The same image has to give the same witness every time
+    hints.Deterministic = Some(true);
+
+    let decode_result = if extension == "svg" {
         use std::{fs::File, io::Read};
         use rxing::{SVGLuminanceSource, common::FixedThresholdBinarizer};
 
@@ -567,7 +538,7 @@ fn decode_with_witness_extraction(
         let binarizer = FixedThresholdBinarizer::new(source);
         let mut bitmap = BinaryBitmap::new(binarizer);
 
-        decode_and_extract(&mut bitmap, hints)
+        decode_and_extract(&mut bitmap, hints, witness_path)
     } else {
         use rxing::BufferedImageLuminanceSource;
 
@@ -583,13 +554,12 @@ fn decode_with_witness_extraction(
         let binarizer = FixedThresholdBinarizer::new(source);
         let mut bitmap = BinaryBitmap::new(binarizer);
 
-        decode_and_extract(&mut bitmap, hints)
+        decode_and_extract(&mut bitmap, hints, witness_path)
     };
 
     match decode_result {
-        Ok((result, witness)) => {
-            // Save witness data to JSON
-            if let Err(e) = witness.save_to_json(witness_path) {
+        Ok((result, saved)) => {
+            if let Err(e) = saved {
                 println!("Error saving witness data to '{}': {}", witness_path, e);
                 return ExitCode::FAILURE;
             }
@@ -671,113 +641,28 @@ fn encode_command(
         return ExitCode::FAILURE;
     };
 
-    let mut hints: rxing::EncodingHintDictionary = HashMap::new();
-
-    if let Some(ec) = error_correction {
-        hints.insert(
-            rxing::EncodeHintType::ERROR_CORRECTION,
-            rxing::EncodeHintValue::ErrorCorrection(ec.to_owned()),
-        );
-    }
-
-    if let Some(character_set) = character_set {
-        hints.insert(
-            rxing::EncodeHintType::CHARACTER_SET,
-            rxing::EncodeHintValue::CharacterSet(character_set.to_owned()),
-        );
-    }
-
-    if let Some(data_matrix_compact) = data_matrix_compact {
-        hints.insert(
-            rxing::EncodeHintType::DATA_MATRIX_COMPACT,
-            rxing::EncodeHintValue::DataMatrixCompact(*data_matrix_compact),
-        );
-    }
-
-    if let Some(margin) = margin {
-        hints.insert(
-            rxing::EncodeHintType::MARGIN,
-            rxing::EncodeHintValue::Margin(margin.to_owned()),
-        );
-    }
-
-    if let Some(pdf_417_compact) = pdf_417_compact {
-        hints.insert(
-            rxing::EncodeHintType::PDF417_COMPACT,
-            rxing::EncodeHintValue::Pdf417Compact(pdf_417_compact.to_string()),
-        );
-    }
-
-    if let Some(pdf_417_compaction) = pdf_417_compaction {
-        hints.insert(
-            rxing::EncodeHintType::PDF417_COMPACTION,
-            rxing::EncodeHintValue::Pdf417Compaction(pdf_417_compaction.to_owned()),
-        );
-    }
-
-    if let Some(pdf_417_auto_eci) = pdf_417_auto_eci {
-        hints.insert(
-            rxing::EncodeHintType::PDF417_AUTO_ECI,
-            rxing::EncodeHintValue::Pdf417AutoEci(pdf_417_auto_eci.to_string()),
-        );
-    }
-
-    if let Some(aztec_layers) = aztec_layers {
-        hints.insert(
-            rxing::EncodeHintType::AZTEC_LAYERS,
-            rxing::EncodeHintValue::AztecLayers(*aztec_layers),
-        );
-    }
-
-    if let Some(qr_version) = qr_version {
-        hints.insert(
-            rxing::EncodeHintType::QR_VERSION,
-            rxing::EncodeHintValue::QrVersion(qr_version.to_owned()),
-        );
-    }
-
-    if let Some(qr_mask_pattern) = qr_mask_pattern {
-        hints.insert(
-            rxing::EncodeHintType::QR_MASK_PATTERN,
-            rxing::EncodeHintValue::QrMaskPattern(qr_mask_pattern.to_owned()),
-        );
-    }
-
-    if let Some(qr_compact) = qr_compact {
+    if qr_compact.is_some() {
         println!("Warning, QRCompact can generate unreadable barcodes");
-        hints.insert(
-            rxing::EncodeHintType::QR_COMPACT,
-            rxing::EncodeHintValue::QrCompact(qr_compact.to_string()),
-        );
     }
 
-    if let Some(gs1_format) = gs1_format {
-        hints.insert(
-            rxing::EncodeHintType::GS1_FORMAT,
-            rxing::EncodeHintValue::Gs1Format(*gs1_format),
-        );
-    }
-
-    if let Some(force_code_set) = force_code_set {
-        hints.insert(
-            rxing::EncodeHintType::FORCE_CODE_SET,
-            rxing::EncodeHintValue::ForceCodeSet(force_code_set.to_owned()),
-        );
-    }
-
-    if let Some(force_c40) = force_c40 {
-        hints.insert(
-            rxing::EncodeHintType::FORCE_C40,
-            rxing::EncodeHintValue::ForceC40(*force_c40),
-        );
-    }
-
-    if let Some(code_128_compact) = code_128_compact {
-        hints.insert(
-            rxing::EncodeHintType::CODE128_COMPACT,
-            rxing::EncodeHintValue::Code128Compact(*code_128_compact),
-        );
-    }
+    let hints = rxing::EncodeHints {
+        ErrorCorrection: error_correction.clone(),
+        CharacterSet: character_set.clone(),
+        DataMatrixCompact: *data_matrix_compact,
+        Margin: margin.clone(),
+        Pdf417Compact: pdf_417_compact.map(|compact| compact.to_string()),
+        Pdf417Compaction: pdf_417_compaction.clone(),
+        Pdf417AutoEci: pdf_417_auto_eci.map(|auto_eci| auto_eci.to_string()),
+        AztecLayers: *aztec_layers,
+        QrVersion: qr_version.clone(),
+        QrMaskPattern: qr_mask_pattern.clone(),
+        QrCompact: qr_compact.map(|compact| compact.to_string()),
+        Gs1Format: *gs1_format,
+        ForceCodeSet: force_code_set.clone(),
+        ForceC40: *force_c40,
+        Code128Compact: *code_128_compact,
+        ..Default::default()
+    };
 
     // println!("Encode: file_name: {}, barcode_type: {}, width: {:?}, height: {:?}, data: '{:?}', data_file: {:?}", file_name, barcode_type, width, height, data, data_file);
 
@@ -787,7 +672,7 @@ fn encode_command(
         barcode_type,
         *width as i32,
         *height as i32,
-        &hints.into(),
+        &hints,
     ) {
         Ok(result) => {
             println!("Encode successful, saving...");
@@ -0,0 +1,48 @@
+from typing import Any, Optional
+
+from typing_extensions import Buffer
+
+__version__: str
+
+class RxingError(Exception): ...
+
+class Barcode:
+    @property
+    def text(self) -> str: ...
+    @property
+    def format(self) -> str: ...
+    @property
+    def raw_bytes(self) -> bytes: ...
+    @property
+    def points(self) -> list[tuple[float, float]]: ...
+
+class EncodedBarcode:
+    @property
+    def width(self) -> int: ...
+    @property
+    def height(self) -> int: ...
+    @property
+    def data(self) -> bytes: ...
+    def to_svg(self) -> str: ...
+    def to_png(self) -> bytes: ...
+
+def decode(
+    image: Buffer,
+    width: Optional[int] = None,
+    height: Optional[int] = None,
+    format: Optional[str] = None,
+    hints: Optional[dict[str, Any]] = None,
+) -> Optional[Barcode]: ...
+def decode_multiple(
+    image: Buffer,
+    width: Optional[int] = None,
+    height: Optional[int] = None,
+    hints: Optional[dict[str, Any]] = None,
+) -> list[Barcode]: ...
+def encode(
+    contents: str,
+    format: str,
+    width: int = 0,
+    height: int = 0,
+    hints: Optional[dict[str, Any]] = None,
+) -> EncodedBarcode: ...
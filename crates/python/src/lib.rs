@@ -0,0 +1,10 @@
+//! Builds [`rxing::python`] into the `rxing` Python extension module. Build a wheel with
+//! `maturin build --release` from this directory, or install into the active virtualenv
+//! with `maturin develop`.
+
+use pyo3::prelude::*;
+
+#[pymodule(name = "rxing")]
+fn rxing_python(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    rxing::python::register(module)
+}
@@ -0,0 +1,47 @@
+import numpy as np
+import pytest
+
+import rxing
+
+
+def test_round_trip_numpy():
+    symbol = rxing.encode("hello", "qrcode", 200, 200)
+    pixels = np.frombuffer(symbol.data, np.uint8).reshape(symbol.height, symbol.width)
+
+    barcode = rxing.decode(pixels, format="qrcode")
+    assert barcode.text == "hello"
+    assert barcode.format == "qrcode"
+    assert len(barcode.points) > 0
+
+
+def test_round_trip_rgb_and_bytes():
+    symbol = rxing.encode("hello", "qrcode", 200, 200)
+    pixels = np.frombuffer(symbol.data, np.uint8).reshape(symbol.height, symbol.width)
+
+    rgb = np.stack([pixels] * 3, axis=-1)
+    assert rxing.decode(rgb).text == "hello"
+    assert rxing.decode(symbol.data, symbol.width, symbol.height).text == "hello"
+
+
+def test_not_found():
+    blank = np.full((100, 100), 255, np.uint8)
+    assert rxing.decode(blank) is None
+    assert rxing.decode_multiple(blank) == []
+
+
+def test_hints():
+    symbol = rxing.encode("12345670", "ean 8", 200, 50)
+    pixels = np.frombuffer(symbol.data, np.uint8).reshape(symbol.height, symbol.width)
+
+    assert rxing.decode(pixels, hints={"PossibleFormats": ["QR_CODE"]}) is None
+    assert rxing.decode(pixels, hints={"PossibleFormats": ["EAN_8"]}).text == "12345670"
+    with pytest.raises(ValueError):
+        rxing.decode(pixels, hints={"TryHarder": "yes"})
+
+
+def test_encode_outputs():
+    symbol = rxing.encode("hello", "qrcode", 100, 100, hints={"ErrorCorrection": "H"})
+    assert symbol.to_svg().startswith("<svg")
+    assert symbol.to_png().startswith(b"\x89PNG")
+    with pytest.raises(ValueError):
+        rxing.encode("hello", "not a format")
@@ -0,0 +1,6 @@
+//! Builds the UniFFI bindings of [`rxing::mobile`] into `librxing_uniffi`. Generate the
+//! Kotlin and Swift sources from the built library, see the README.
+
+pub use rxing::mobile::*;
+
+rxing::uniffi_reexport_scaffolding!();
@@ -0,0 +1,438 @@
+//! Async variants of the [`crate::helpers`] entry points.
+//!
+//! Decoding is CPU bound, so every function here runs the decode on tokio's blocking thread
+//! pool with [`tokio::task::spawn_blocking`]; files are read with [`tokio::fs`]. This keeps
+//! the caller's executor threads free, for example in a web service. A tokio runtime must be
+//! running when these functions are awaited.
+//!
+//! If the hints carry a `Cancellation` token, the future resolves with a `CancelledException`
+//! as soon as the token is cancelled. The decode on the blocking pool stops at the next point
+//! it checks the token.
+
+use std::{future::Future, pin::Pin, task::Poll};
+
+use crate::{
+    common::{HybridBinarizer, Result},
+    helpers, BarcodeFormat, BinaryBitmap, CancellationToken, DecodeHints, Exceptions,
+    LuminanceSource, MultiFormatReader, RXingResult, Reader,
+};
+
+#[cfg(feature = "image")]
+use crate::{
+    common::FixedThresholdBinarizer,
+    helpers::{FileDecodeError, FileDecodeOutcome},
+    multi::{GenericMultipleBarcodeReader, MultipleBarcodeReader},
+    BufferedImageLuminanceSource, MultiUseMultiFormatReader,
+};
+#[cfg(feature = "image")]
+use std::{collections::HashSet, path::PathBuf, time::Instant};
+
+async fn run_blocking<T, F>(cancellation: Option<CancellationToken>, f: F) -> Result<T>
+where
+    F: FnOnce() -> Result<T> + Send + 'static,
+    T: Send + 'static,
+{
+    let task_failed = |e| Exceptions::runtime_with(format!("decode task failed: {e}"));
+    let mut task = tokio::task::spawn_blocking(f);
+    let Some(token) = cancellation else {
+        return task.await.map_err(task_failed)?;
+    };
+
+    // dropping the task detaches it, it ends once the readers see the token
+    let mut cancelled = token.cancelled();
+    std::future::poll_fn(|cx| {
+        if let Poll::Ready(joined) = Pin::new(&mut task).poll(cx) {
+            return Poll::Ready(joined.map_err(task_failed).and_then(|result| result));
+        }
+        Pin::new(&mut cancelled)
+            .poll(cx)
+            .map(|()| Err(Exceptions::CANCELLED))
+    })
+    .await
+}
+
+/**
+ * Decodes `source` with a `MultiFormatReader` on the blocking thread pool. A camera app can
+ * give each frame a `CancellationToken` with `DecodeHints::with_cancellation` and cancel it
+ * when the next frame arrives; the future then resolves at once with a `CancelledException`.
+ */
+pub async fn decode_async<L>(source: L, hints: DecodeHints) -> Result<RXingResult>
+where
+    L: LuminanceSource + Send + 'static,
+{
+    run_blocking(hints.Cancellation.clone(), move || {
+        MultiFormatReader::default()
+            .decode_with_hints(&mut BinaryBitmap::new(HybridBinarizer::new(source)), &hints)
+    })
+    .await
+}
+
+pub async fn detect_in_luma(
+    luma: Vec<u8>,
+    width: u32,
+    height: u32,
+    barcode_type: Option<BarcodeFormat>,
+) -> Result<RXingResult> {
+    detect_in_luma_with_hints(luma, width, height, barcode_type, DecodeHints::default()).await
+}
+
+pub async fn detect_in_luma_with_hints(
+    luma: Vec<u8>,
+    width: u32,
+    height: u32,
+    barcode_type: Option<BarcodeFormat>,
+    mut hints: DecodeHints,
+) -> Result<RXingResult> {
+    run_blocking(hints.Cancellation.clone(), move || {
+        helpers::detect_in_luma_with_hints(luma, width, height, barcode_type, &mut hints)
+    })
+    .await
+}
+
+pub async fn detect_multiple_in_luma(
+    luma: Vec<u8>,
+    width: u32,
+    height: u32,
+) -> Result<Vec<RXingResult>> {
+    detect_multiple_in_luma_with_hints(luma, width, height, DecodeHints::default()).await
+}
+
+pub async fn detect_multiple_in_luma_with_hints(
+    luma: Vec<u8>,
+    width: u32,
+    height: u32,
+    mut hints: DecodeHints,
+) -> Result<Vec<RXingResult>> {
+    run_blocking(hints.Cancellation.clone(), move || {
+        helpers::detect_multiple_in_luma_with_hints(luma, width, height, &mut hints)
+    })
+    .await
+}
+
+/**
+ * Decodes an encoded image (PNG, JPEG, ...) held in memory, such as the body of an HTTP
+ * request or response.
+ */
+#[cfg(feature = "image")]
+pub async fn detect_in_image_bytes(
+    bytes: Vec<u8>,
+    barcode_type: Option<BarcodeFormat>,
+    mut hints: DecodeHints,
+) -> Result<RXingResult> {
+    run_blocking(hints.Cancellation.clone(), move || {
+        decode_image_bytes(&bytes, barcode_type, &mut hints)
+    })
+    .await
+}
+
+#[cfg(feature = "image")]
+pub async fn detect_multiple_in_image_bytes(
+    bytes: Vec<u8>,
+    mut hints: DecodeHints,
+) -> Result<Vec<RXingResult>> {
+    run_blocking(hints.Cancellation.clone(), move || {
+        decode_multiple_image_bytes(&bytes, &mut hints)
+    })
+    .await
+}
+
+/**
+ * Downloads the image at `url` and decodes it, like [`helpers::detect_in_url`]. The body is
+ * read as it arrives and the download stops once it passes the size limit.
+ */
+#[cfg(feature = "url_fetch")]
+pub async fn detect_in_url(url: &str) -> Result<RXingResult> {
+    detect_in_url_with_hints(
+        url,
+        None,
+        &helpers::UrlFetchOptions::default(),
+        DecodeHints::default(),
+    )
+    .await
+}
+
+#[cfg(feature = "url_fetch")]
+pub async fn detect_in_url_with_hints(
+    url: &str,
+    barcode_type: Option<BarcodeFormat>,
+    options: &helpers::UrlFetchOptions,
+    hints: DecodeHints,
+) -> Result<RXingResult> {
+    let fetch_error =
+        |e: reqwest::Error| Exceptions::runtime_with(format!("couldn't fetch {url}: {e}"));
+
+    let client = reqwest::Client::builder()
+        .timeout(options.timeout)
+        .build()
+        .map_err(|e| Exceptions::runtime_with(format!("couldn't create http client: {e}")))?;
+    let mut response = client.get(url).send().await.map_err(fetch_error)?;
+    helpers::check_image_response(
+        url,
+        response.status(),
+        response.headers(),
+        response.content_length(),
+        options,
+    )?;
+
+    let mut bytes = Vec::new();
+    while let Some(chunk) = response.chunk().await.map_err(fetch_error)? {
+        if (bytes.len() + chunk.len()) as u64 > options.max_bytes {
+            return Err(helpers::image_too_large(url, options));
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    detect_in_image_bytes(bytes, barcode_type, hints).await
+}
+
+#[cfg(feature = "image")]
+pub async fn detect_in_file(
+    file_name: impl Into<PathBuf>,
+    barcode_type: Option<BarcodeFormat>,
+) -> Result<RXingResult> {
+    detect_in_file_with_hints(file_name, barcode_type, DecodeHints::default()).await
+}
+
+#[cfg(feature = "image")]
+pub async fn detect_in_file_with_hints(
+    file_name: impl Into<PathBuf>,
+    barcode_type: Option<BarcodeFormat>,
+    hints: DecodeHints,
+) -> Result<RXingResult> {
+    let file_name = file_name.into();
+    let bytes = tokio::fs::read(&file_name).await.map_err(|_| {
+        Exceptions::illegal_argument_with(format!(
+            "file '{}' not found or cannot be opened",
+            file_name.display()
+        ))
+    })?;
+    detect_in_image_bytes(bytes, barcode_type, hints).await
+}
+
+#[cfg(feature = "image")]
+pub async fn detect_multiple_in_file(file_name: impl Into<PathBuf>) -> Result<Vec<RXingResult>> {
+    detect_multiple_in_file_with_hints(file_name, DecodeHints::default()).await
+}
+
+#[cfg(feature = "image")]
+pub async fn detect_multiple_in_file_with_hints(
+    file_name: impl Into<PathBuf>,
+    hints: DecodeHints,
+) -> Result<Vec<RXingResult>> {
+    let file_name = file_name.into();
+    let bytes = tokio::fs::read(&file_name).await.map_err(|e| {
+        Exceptions::runtime_with(format!("couldn't read {}: {e}", file_name.display()))
+    })?;
+    detect_multiple_in_image_bytes(bytes, hints).await
+}
+
+/**
+ * Detects all barcodes in each of `paths`, like [`helpers::detect_multiple_in_files`]. Files
+ * are decoded one after the other, each on the blocking thread pool.
+ */
+#[cfg(feature = "image")]
+pub async fn detect_multiple_in_files(
+    paths: Vec<PathBuf>,
+    hints: DecodeHints,
+) -> Vec<FileDecodeOutcome<Vec<RXingResult>>> {
+    let mut outcomes = Vec::with_capacity(paths.len());
+    for path in paths {
+        let start = Instant::now();
+        let result = match tokio::fs::read(&path).await {
+            Ok(bytes) => {
+                let mut hints = hints.clone();
+                tokio::task::spawn_blocking(move || {
+                    let img = helpers::load_image_from_memory(&bytes)
+                        .map_err(|e| FileDecodeError::Open(e.to_string()))?;
+                    decode_multiple(img, &mut hints).map_err(FileDecodeError::Decode)
+                })
+                .await
+                .unwrap_or_else(|e| {
+                    Err(FileDecodeError::Decode(Exceptions::runtime_with(format!(
+                        "decode task failed: {e}"
+                    ))))
+                })
+            }
+            Err(e) => Err(FileDecodeError::Open(e.to_string())),
+        };
+        outcomes.push(FileDecodeOutcome {
+            path,
+            result,
+            elapsed: start.elapsed(),
+        });
+    }
+    outcomes
+}
+
+#[cfg(feature = "image")]
+fn load_image(bytes: &[u8]) -> Result<image::DynamicImage> {
+    helpers::load_image_from_memory(bytes)
+        .map_err(|e| Exceptions::parse_with(format!("could not read image: {e}")))
+}
+
+#[cfg(feature = "image")]
+fn decode_image_bytes(
+    bytes: &[u8],
+    barcode_type: Option<BarcodeFormat>,
+    hints: &mut DecodeHints,
+) -> Result<RXingResult> {
+    let img = load_image(bytes)?;
+
+    if let Some(bc_type) = barcode_type {
+        hints.PossibleFormats = Some(HashSet::from([bc_type]));
+    }
+
+    hints.TryHarder = hints.TryHarder.or(Some(true));
+
+    MultiFormatReader::default().decode_with_hints(
+        &mut BinaryBitmap::new(FixedThresholdBinarizer::new(
+            BufferedImageLuminanceSource::new(img),
+        )),
+        hints,
+    )
+}
+
+#[cfg(feature = "image")]
+fn decode_multiple_image_bytes(bytes: &[u8], hints: &mut DecodeHints) -> Result<Vec<RXingResult>> {
+    decode_multiple(load_image(bytes)?, hints)
+}
+
+#[cfg(feature = "image")]
+fn decode_multiple(img: image::DynamicImage, hints: &mut DecodeHints) -> Result<Vec<RXingResult>> {
+    hints.TryHarder = hints.TryHarder.or(Some(true));
+
+    GenericMultipleBarcodeReader::new(MultiUseMultiFormatReader::default())
+        .decode_multiple_with_hints(
+            &mut BinaryBitmap::new(FixedThresholdBinarizer::new(
+                BufferedImageLuminanceSource::new(img),
+            )),
+            hints,
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn runtime() -> tokio::runtime::Runtime {
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+    }
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn test_detect_in_file() {
+        let result = runtime()
+            .block_on(detect_in_file(
+                "test_resources/blackbox/qrcode-1/1.png",
+                Some(BarcodeFormat::QR_CODE),
+            ))
+            .unwrap();
+        assert!(result.getText().starts_with("MEBKM:"));
+
+        let bytes = std::fs::read("test_resources/blackbox/qrcode-1/1.png").unwrap();
+        let results = runtime()
+            .block_on(detect_multiple_in_image_bytes(
+                bytes,
+                DecodeHints::default(),
+            ))
+            .unwrap();
+        assert_eq!(result.getText(), results[0].getText());
+
+        let outcomes = runtime().block_on(detect_multiple_in_files(
+            vec![
+                "test_resources/blackbox/qrcode-1/1.png".into(),
+                "test_resources/blackbox/qrcode-1/missing.png".into(),
+                "test_resources/blackbox/qrcode-1/1.txt".into(),
+            ],
+            DecodeHints::default(),
+        ));
+        assert!(outcomes[0].is_ok());
+        assert!(matches!(outcomes[1].result, Err(FileDecodeError::Open(_))));
+        assert!(matches!(outcomes[2].result, Err(FileDecodeError::Open(_))));
+    }
+
+    #[test]
+    fn test_detect_in_luma() {
+        let luma = vec![255; 100 * 100];
+        assert!(matches!(
+            runtime().block_on(detect_in_luma(luma, 100, 100, None)),
+            Err(Exceptions::NotFoundException(_))
+        ));
+    }
+
+    #[test]
+    fn test_cancellation() {
+        let token = CancellationToken::new();
+        let hints = DecodeHints::default().with_cancellation(token.clone());
+        let source = crate::Luma8LuminanceSource::new(vec![255; 100 * 100], 100, 100);
+        token.cancel();
+        assert_eq!(
+            Err(Exceptions::CANCELLED),
+            runtime().block_on(decode_async(source, hints))
+        );
+
+        // the future resolves while the blocking task is still running
+        let token = CancellationToken::new();
+        let (release, released) = std::sync::mpsc::channel::<()>();
+        let canceller = {
+            let token = token.clone();
+            std::thread::spawn(move || {
+                std::thread::sleep(std::time::Duration::from_millis(20));
+                token.cancel();
+            })
+        };
+        let runtime = runtime();
+        let result = runtime.block_on(run_blocking(Some(token), move || {
+            let _ = released.recv();
+            Ok(())
+        }));
+        assert_eq!(Err(Exceptions::CANCELLED), result);
+        // dropping the runtime waits for the blocking task
+        release.send(()).unwrap();
+        drop(runtime);
+        canceller.join().unwrap();
+    }
+
+    #[cfg(feature = "url_fetch")]
+    #[test]
+    fn test_detect_in_url() {
+        use std::io::{BufRead, BufReader, Write};
+
+        // responses without a length, the body ends when the connection closes
+        let png = std::fs::read("test_resources/blackbox/qrcode-1/1.png").unwrap();
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/qr.png", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            for _ in 0..2 {
+                let (stream, _) = listener.accept().unwrap();
+                let mut line = String::new();
+                let mut request = BufReader::new(&stream);
+                while request.read_line(&mut line).unwrap() > 2 {
+                    line.clear();
+                }
+                let head =
+                    "HTTP/1.1 200 OK\r\nContent-Type: image/png\r\nConnection: close\r\n\r\n";
+                let _ = (&stream).write_all(head.as_bytes());
+                let _ = (&stream).write_all(&png);
+            }
+        });
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let result = runtime.block_on(detect_in_url(&url)).unwrap();
+        assert!(result.getText().starts_with("MEBKM:"));
+
+        let options = helpers::UrlFetchOptions {
+            max_bytes: 1024,
+            ..Default::default()
+        };
+        let hints = DecodeHints::default();
+        let fetch = detect_in_url_with_hints(&url, None, &options, hints);
+        let error = runtime.block_on(fetch).unwrap_err();
+        assert!(error.to_string().contains("larger than 1024 bytes"));
+        server.join().unwrap();
+    }
+}
@@ -72,6 +72,37 @@ fn test_high_level_decode() {
     );
 }
 
+#[test]
+fn test_structured_append() {
+    let parse = |b: &str| {
+        let bits = toBooleanArray(&toBitArray(&stripSpace(b)));
+        let mut text = decoder::highLevelDecode(&bits).expect("highLevelDecode Failed");
+        let structured_append = decoder::parse_structured_append(&bits, &mut text);
+        (
+            text,
+            structured_append.map(|sai| (sai.index, sai.count, sai.id)),
+        )
+    };
+
+    // M/L   U/L   ' '   'I'   'D'   ' '   'B'   'C'   'H'   'I'
+    assert_eq!(
+        ("HI".to_owned(), Some((1, 3, "ID".to_owned()))),
+        parse("XXX.X XXX.X ....X .X.X. ..X.X ....X ...XX ..X.. .X..X .X.X.")
+    );
+    // M/L   U/L   'A'   'B'   'H'   'I'
+    assert_eq!(
+        ("HI".to_owned(), Some((0, 2, String::new()))),
+        parse("XXX.X XXX.X ...X. ...XX .X..X .X.X.")
+    );
+    // the index must be below the count
+    assert_eq!(
+        ("CBHI".to_owned(), None),
+        parse("XXX.X XXX.X ..X.. ...XX .X..X .X.X.")
+    );
+    // without the latches the letters are data
+    assert_eq!(("ABHI".to_owned(), None), parse("...X. ...XX .X..X .X.X."));
+}
+
 fn test_high_level_decode_string(expectedString: &str, b: &str) {
     let bits = toBitArray(&stripSpace(b));
     assert_eq!(
@@ -21,8 +21,11 @@ use crate::{
         encoder::HighLevelEncoder,
         shared_test_methods::{stripSpace, toBitArray, toBooleanArray},
     },
-    common::CharacterSet,
-    BarcodeFormat, EncodeHints, Point,
+    common::{
+        reedsolomon::{get_predefined_genericgf, PredefinedGenericGF, ReedSolomonEncoder},
+        BitArray, CharacterSet,
+    },
+    BarcodeFormat, EncodeHintValue, EncodeHints, Point,
 };
 
 use super::{encoder::aztec_encoder, AztecWriter};
@@ -186,6 +189,39 @@ fn testAztecWriter() {
     assert_eq!(&matrix, expected_matrix);
 }
 
+#[test]
+fn test_aztec_writer_compact_eci() {
+    // without a hint, text ISO-8859-1 can't encode goes in the most compact character set
+    for (data, charset) in [
+        ("Привет мир", CharacterSet::ISO8859_5),
+        (
+            "The capital of Japan is named \u{6771}\u{4EAC}.",
+            CharacterSet::Shift_JIS,
+        ),
+    ] {
+        let matrix = AztecWriter {}
+            .encode(data, &BarcodeFormat::AZTEC, 0, 0)
+            .expect("encode");
+        let aztec = aztec_encoder::encode_with_charset(
+            data,
+            aztec_encoder::DEFAULT_EC_PERCENT,
+            aztec_encoder::DEFAULT_AZTEC_LAYERS,
+            charset,
+        )
+        .expect("encode");
+        assert_eq!(&matrix, aztec.getMatrix(), "{data}");
+
+        let r = AztecDetectorRXingResult::new(
+            aztec.getMatrix().clone(),
+            NO_POINTS,
+            aztec.isCompact(),
+            aztec.getCodeWords(),
+            aztec.getLayers(),
+        );
+        assert_eq!(data, decoder::decode(&r).expect("decode").getText());
+    }
+}
+
 // synthetic tests (encode-decode round-trip)
 
 #[test]
@@ -639,6 +675,143 @@ fn testBorderCompact4Case() {
     assert_eq!(4, aztecCode.getLayers());
 }
 
+#[test]
+fn test_compact_hint() {
+    let encode = |data: &str, hints: EncodeHints| {
+        super::aztec_writer::encode_code(data, &hints).expect("encode")
+    };
+    let hints = || EncodeHints::default();
+
+    // full-range symbols of fewer than 4 layers are only encoded when asked for
+    let data = "ABC";
+    let aztec = encode(data, hints().with(EncodeHintValue::AztecCompact(false)));
+    assert!(!aztec.isCompact());
+    assert_eq!(1, aztec.getLayers());
+    let r = AztecDetectorRXingResult::new(
+        aztec.getMatrix().clone(),
+        NO_POINTS,
+        false,
+        aztec.getCodeWords(),
+        1,
+    );
+    assert_eq!(data, decoder::decode(&r).expect("decode").getText());
+    assert!(encode(data, hints()).isCompact());
+
+    // the hint overrides the sign of the layers
+    let aztec = encode(
+        data,
+        hints()
+            .with(EncodeHintValue::AztecLayers(3))
+            .with(EncodeHintValue::AztecCompact(true)),
+    );
+    assert!(aztec.isCompact());
+    assert_eq!(3, aztec.getLayers());
+
+    let long = "ABCDEFGHIJKLMNOPQRSTUVWXYZ".repeat(5);
+    assert!(!encode(&long, hints()).isCompact());
+    assert!(super::aztec_writer::encode_code(
+        &long,
+        &hints().with(EncodeHintValue::AztecCompact(true))
+    )
+    .is_err());
+}
+
+#[test]
+fn test_aztec_runes() {
+    use crate::{
+        test_fixtures::luma_bitmap, RXingResultMetadataType, RXingResultMetadataValue, Reader,
+    };
+
+    let aztec = aztec_encoder::encode_rune(0).expect("encode");
+    assert_eq!((11, 0), (aztec.getSize(), aztec.getLayers()));
+    assert!(super::aztec_writer::encode_code(
+        "256",
+        &EncodeHints::default().with(EncodeHintValue::AztecRune(true))
+    )
+    .is_err());
+
+    let hints = EncodeHints::default()
+        .with(EncodeHintValue::AztecRune(true))
+        .with(EncodeHintValue::Margin(2.to_string()));
+    for value in 0..=255u8 {
+        let contents = value.to_string();
+        let matrix = AztecWriter
+            .encode_with_hints(&contents, &BarcodeFormat::AZTEC, 60, 60, &hints)
+            .expect("encode");
+        let result = super::AztecReader
+            .decode(&mut luma_bitmap(&matrix))
+            .expect("decode");
+        assert_eq!(contents, result.getText());
+        let metadata = result.getRXingResultMetadata();
+        assert_eq!(
+            Some(&RXingResultMetadataValue::AztecRune(value)),
+            metadata.get(&RXingResultMetadataType::AZTEC_RUNE)
+        );
+        assert_eq!(
+            Some(&RXingResultMetadataValue::SymbologyIdentifier(
+                "]zC".to_owned()
+            )),
+            metadata.get(&RXingResultMetadataType::SYMBOLOGY_IDENTIFIER)
+        );
+    }
+}
+
+#[test]
+fn testEncodingWitness() {
+    // 9 layers use 10 bit words
+    let data = "Aztec witness 0123456789";
+    let (aztec, witness) = aztec_encoder::encode_with_witness(data, 25, 9).expect("encode");
+    assert_eq!(BarcodeFormat::AZTEC, witness.format);
+    assert_eq!(9, witness.version);
+    assert_eq!(10, witness.codeword_size);
+    assert_eq!(aztec.getMatrix(), &witness.matrix);
+    assert!(witness.mask.is_none());
+
+    // the data codewords are the stuffed data bits
+    let mut bits = BitArray::new();
+    for i in 0..witness.num_data_bits {
+        bits.appendBit(witness.data_bits[i / 8] & (0x80 >> (i % 8)) != 0);
+    }
+    assert_eq!(
+        data,
+        decoder::highLevelDecode(&toBooleanArray(&bits)).unwrap()
+    );
+    let stuffed = aztec_encoder::stuffBits(&bits, 10).expect("stuff");
+    assert_eq!(stuffed.get_size(), witness.data_codewords.len() * 10);
+    for (i, &word) in witness.data_codewords.iter().enumerate() {
+        for j in 0..10 {
+            assert_eq!(stuffed.get(i * 10 + j), word & (1 << (9 - j)) != 0);
+        }
+    }
+
+    // a single block, its parity following the data
+    let [block] = &witness.blocks[..] else {
+        panic!("{} blocks", witness.blocks.len());
+    };
+    assert_eq!(witness.data_codewords, block.data);
+    let mut words: Vec<i32> = block.data.iter().map(|&word| word as i32).collect();
+    words.resize(witness.codewords.len(), 0);
+    ReedSolomonEncoder::new(get_predefined_genericgf(PredefinedGenericGF::AztecData10))
+        .expect("gf")
+        .encode(&mut words, block.parity.len())
+        .expect("parity");
+    let codewords: Vec<u16> = words.iter().map(|&word| word as u16).collect();
+    assert_eq!(witness.codewords, codewords);
+    assert_eq!(
+        witness.codewords.len() as u32,
+        aztec.getCodeWords() + block.parity.len() as u32
+    );
+
+    let r = AztecDetectorRXingResult::new(
+        witness.matrix.clone(),
+        NO_POINTS,
+        aztec.isCompact(),
+        aztec.getCodeWords(),
+        aztec.getLayers(),
+    );
+    assert_eq!(data, decoder::decode(&r).expect("decode").getText());
+}
+
 // Helper routines
 
 fn testEncode(data: &str, compact: bool, layers: u32, expected: &str) {
@@ -21,8 +21,8 @@
 // import com.google.zxing.common.DetectorRXingResult;
 
 use crate::{
-    common::{BitMatrix, DetectorRXingResult},
-    Point,
+    common::{sample_as_hinted, BitMatrix, DetectorRXingResult, Result, SamplingGrid},
+    DecodeHints, Point,
 };
 
 /**
@@ -37,6 +37,7 @@ pub struct AztecDetectorRXingResult {
     compact: bool,
     nbDatablocks: u32,
     nbLayers: u32,
+    samplingGrid: Option<SamplingGrid>,
 }
 
 impl DetectorRXingResult for AztecDetectorRXingResult {
@@ -47,6 +48,10 @@ impl DetectorRXingResult for AztecDetectorRXingResult {
     fn getPoints(&self) -> &[Point] {
         &self.points
     }
+
+    fn getSamplingGrid(&self) -> Option<&SamplingGrid> {
+        self.samplingGrid.as_ref()
+    }
 }
 
 impl AztecDetectorRXingResult {
@@ -63,9 +68,29 @@ impl AztecDetectorRXingResult {
             compact,
             nbDatablocks,
             nbLayers,
+            samplingGrid: None,
         }
     }
 
+    pub fn with_sampling_grid(mut self, samplingGrid: SamplingGrid) -> Self {
+        self.samplingGrid = Some(samplingGrid);
+        self
+    }
+
+    /// Samples the bits again through the sampling grid if the `FixedPointSampling` or
+    /// `GridSampling` hint asks for it, see `common::sample_as_hinted`
+    pub(crate) fn sample_as_hinted(self, image: &BitMatrix, hints: &DecodeHints) -> Result<Self> {
+        let Some(bits) = self
+            .samplingGrid
+            .as_ref()
+            .and_then(|grid| sample_as_hinted(image, grid, hints))
+            .transpose()?
+        else {
+            return Ok(self);
+        };
+        Ok(Self { bits, ..self })
+    }
+
     pub const fn getNbLayers(&self) -> u32 {
         self.nbLayers
     }
@@ -77,4 +102,9 @@ impl AztecDetectorRXingResult {
     pub const fn isCompact(&self) -> bool {
         self.compact
     }
+
+    /// Whether this is an Aztec Rune, whose value is given as the number of data blocks
+    pub const fn isRune(&self) -> bool {
+        self.nbLayers == 0
+    }
 }
@@ -16,9 +16,11 @@
 
 use crate::{
     common::{DecoderRXingResult, DetectorRXingResult, Result},
+    decode_confidence,
     exceptions::Exceptions,
-    BarcodeFormat, Binarizer, BinaryBitmap, DecodeHints, ImmutableReader, RXingResult,
-    RXingResultMetadataType, RXingResultMetadataValue, Reader,
+    record_witness, symbol_pose, BarcodeFormat, Binarizer, BinaryBitmap, DecodeHints,
+    DecodeWarning, ImmutableReader, RXingResult, RXingResultMetadataType, RXingResultMetadataValue,
+    Reader, WitnessRecording,
 };
 
 use super::{decoder, detector::Detector};
@@ -48,7 +50,9 @@ impl Reader for AztecReader {
         image: &mut BinaryBitmap<B>,
         hints: &DecodeHints,
     ) -> Result<RXingResult> {
-        self.internal_decode_with_hints(image, hints)
+        let mut recording = WitnessRecording::new(hints);
+        self.internal_decode_with_hints(image, hints, &mut recording)
+            .map(|result| record_witness(hints, image, result, &recording))
     }
 }
 
@@ -58,7 +62,9 @@ impl ImmutableReader for AztecReader {
         image: &mut BinaryBitmap<B>,
         hints: &DecodeHints,
     ) -> Result<RXingResult> {
-        self.internal_decode_with_hints(image, hints)
+        let mut recording = WitnessRecording::new(hints);
+        self.internal_decode_with_hints(image, hints, &mut recording)
+            .map(|result| record_witness(hints, image, result, &recording))
     }
 }
 
@@ -67,9 +73,11 @@ impl AztecReader {
         &self,
         image: &mut BinaryBitmap<B>,
         hints: &DecodeHints,
+        recording: &mut WitnessRecording,
     ) -> Result<RXingResult> {
         // let notFoundException = None;
         // let formatException = None;
+        recording.binarization(image);
         let mut detector = Detector::new(image.get_black_matrix());
 
         //  try {
@@ -80,10 +88,17 @@ impl AztecReader {
             det
         } else {
             return Err(Exceptions::NOT_FOUND);
-        };
+        }
+        .sample_as_hinted(image.get_black_matrix(), hints)?;
 
         let points = detectorRXingResult.getPoints();
+        recording.detection(
+            BarcodeFormat::AZTEC,
+            points,
+            detectorRXingResult.getSamplingGrid(),
+        );
         let decoderRXingResult: DecoderRXingResult = decoder::decode(&detectorRXingResult)?;
+        recording.codewords(decoderRXingResult.getCodewords());
         // } catch (NotFoundException e) {
         //   notFoundException = e;
         // } catch (FormatException e) {
@@ -122,6 +137,26 @@ impl AztecReader {
             chrono::Utc::now().timestamp_millis() as u128,
         );
 
+        if let Some(samplingGrid) = detectorRXingResult.getSamplingGrid() {
+            result.putMetadata(
+                RXingResultMetadataType::SAMPLING_GRID,
+                RXingResultMetadataValue::SamplingGrid(samplingGrid.clone()),
+            );
+        }
+
+        if let Some(codewords) = decoderRXingResult.getCodewords() {
+            if codewords.uses_heavy_correction() {
+                result.addWarning(DecodeWarning::HeavyErrorCorrection);
+            }
+            result.putMetadata(
+                RXingResultMetadataType::CODEWORDS,
+                RXingResultMetadataValue::Codewords(codewords.clone()),
+            );
+        }
+
+        decode_confidence::put_confidence(&mut result);
+        symbol_pose::put_pose(&mut result);
+
         let byteSegments = decoderRXingResult.getByteSegments();
         if !byteSegments.is_empty() {
             result.putMetadata(
@@ -136,14 +171,53 @@ impl AztecReader {
                 RXingResultMetadataValue::ErrorCorrectionLevel(ecLevel.to_owned()),
             );
         }
-        result.putMetadata(
-            RXingResultMetadataType::SYMBOLOGY_IDENTIFIER,
-            RXingResultMetadataValue::SymbologyIdentifier(format!(
-                "]z{}",
-                decoderRXingResult.getSymbologyModifier()
-            )),
+        result.putErrorCorrectionMetadata(
+            decoderRXingResult.getErrorsCorrected(),
+            decoderRXingResult.getErasures(),
         );
+        if decoderRXingResult.hasStructuredAppend() {
+            result.putMetadata(
+                RXingResultMetadataType::STRUCTURED_APPEND_SEQUENCE,
+                RXingResultMetadataValue::StructuredAppendSequence(
+                    decoderRXingResult.getStructuredAppendSequenceNumber(),
+                ),
+            );
+            result.putMetadata(
+                RXingResultMetadataType::STRUCTURED_APPEND_PARITY,
+                RXingResultMetadataValue::StructuredAppendParity(
+                    decoderRXingResult.getStructuredAppendParity(),
+                ),
+            );
+            if !decoderRXingResult.getStructuredAppendId().is_empty() {
+                result.putMetadata(
+                    RXingResultMetadataType::STRUCTURED_APPEND_ID,
+                    RXingResultMetadataValue::StructuredAppendId(
+                        decoderRXingResult.getStructuredAppendId().to_owned(),
+                    ),
+                );
+            }
+        }
+        if detectorRXingResult.isRune() {
+            result.putMetadata(
+                RXingResultMetadataType::AZTEC_RUNE,
+                RXingResultMetadataValue::AztecRune(detectorRXingResult.getNbDatablocks() as u8),
+            );
+            // runes have their own modifier
+            result.putMetadata(
+                RXingResultMetadataType::SYMBOLOGY_IDENTIFIER,
+                RXingResultMetadataValue::SymbologyIdentifier("]zC".to_owned()),
+            );
+        } else {
+            result.putMetadata(
+                RXingResultMetadataType::SYMBOLOGY_IDENTIFIER,
+                RXingResultMetadataValue::SymbologyIdentifier(format!(
+                    "]z{}",
+                    decoderRXingResult.getSymbologyModifier()
+                )),
+            );
+        }
 
+        recording.decoded_bits(&mut result);
         Ok(result)
     }
 }
@@ -15,7 +15,7 @@
  */
 
 use crate::{
-    common::{BitMatrix, CharacterSet, Result},
+    common::{most_compact_charset, BitMatrix, CharacterSet, Result},
     exceptions::Exceptions,
     BarcodeFormat, EncodeHints, Writer,
 };
@@ -50,19 +50,10 @@ impl Writer for AztecWriter {
         height: i32,
         hints: &EncodeHints,
     ) -> Result<crate::common::BitMatrix> {
-        let mut charset = None; // Do not add any ECI code by default
-        let mut ecc_percent = aztec_encoder::DEFAULT_EC_PERCENT;
-        let mut layers = aztec_encoder::DEFAULT_AZTEC_LAYERS;
-        if let Some(cset_name) = &hints.CharacterSet {
-            if cset_name.to_lowercase() != "iso-8859-1" {
-                charset = CharacterSet::get_character_set_by_name(cset_name);
-            }
-        }
-        if let Some(ecc_level) = &hints.ErrorCorrection {
-            ecc_percent = ecc_level.parse().unwrap_or(23);
-        }
-        if let Some(az_layers) = hints.AztecLayers {
-            layers = az_layers;
+        if format != &BarcodeFormat::AZTEC {
+            return Err(Exceptions::illegal_argument_with(format!(
+                "can only encode AZTEC, but got {format:?}"
+            )));
         }
 
         let margins = if let Some(margin) = &hints.Margin {
@@ -73,42 +64,42 @@ impl Writer for AztecWriter {
             MARGINS_SIZE
         };
 
-        encode(
-            contents,
-            *format,
-            width as u32,
-            height as u32,
-            margins,
-            charset,
-            ecc_percent,
-            layers,
-        )
+        let aztec = encode_code(contents, hints)?;
+        renderRXingResult(&aztec, width as u32, height as u32, margins)
     }
 }
 
-#[allow(clippy::too_many_arguments)]
-fn encode(
-    contents: &str,
-    format: BarcodeFormat,
-    width: u32,
-    height: u32,
-    margins: u32,
-    charset: Option<CharacterSet>,
-    ecc_percent: u32,
-    layers: i32,
-) -> Result<BitMatrix> {
-    if format != BarcodeFormat::AZTEC {
-        return Err(Exceptions::illegal_argument_with(format!(
-            "can only encode AZTEC, but got {format:?}"
-        )));
+/// Encodes the contents into an Aztec symbol, honoring the character set, error
+/// correction, layer, compact and rune hints.
+pub(crate) fn encode_code(contents: &str, hints: &EncodeHints) -> Result<AztecCode> {
+    if matches!(hints.AztecRune, Some(true)) {
+        let value = contents.parse::<u8>().map_err(|_| {
+            Exceptions::illegal_argument_with(format!(
+                "an Aztec Rune holds a number from 0 to 255, but got '{contents}'"
+            ))
+        })?;
+        return aztec_encoder::encode_rune(value);
     }
-    let aztec = if let Some(cset) = charset {
-        // dbg!(cset.name(), cset.whatwg_name());
-        aztec_encoder::encode_with_charset(contents, ecc_percent, layers, cset)?
-    } else {
-        aztec_encoder::encode(contents, ecc_percent, layers)?
-    };
-    renderRXingResult(&aztec, width, height, margins)
+
+    let mut charset = None; // Do not add any ECI code by default
+    let mut ecc_percent = aztec_encoder::DEFAULT_EC_PERCENT;
+    let mut layers = aztec_encoder::DEFAULT_AZTEC_LAYERS;
+    if let Some(cset_name) = &hints.CharacterSet {
+        if cset_name.to_lowercase() != "iso-8859-1" {
+            charset = CharacterSet::get_character_set_by_name(cset_name);
+        }
+    } else if CharacterSet::ISO8859_1.encode(contents).is_err() {
+        // text beyond the default encoding goes in the most compact character set, with its ECI
+        charset = Some(most_compact_charset(contents));
+    }
+    if let Some(ecc_level) = &hints.ErrorCorrection {
+        ecc_percent = ecc_level.parse().unwrap_or(23);
+    }
+    if let Some(az_layers) = hints.AztecLayers {
+        layers = az_layers;
+    }
+
+    aztec_encoder::encode_with_compact(contents, ecc_percent, layers, hints.AztecCompact, charset)
 }
 
 fn renderRXingResult(code: &AztecCode, width: u32, height: u32, margins: u32) -> Result<BitMatrix> {
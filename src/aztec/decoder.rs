@@ -16,10 +16,12 @@
 
 use crate::{
     common::{
+        cpp_essentials::StructuredAppendInfo,
         reedsolomon::{
             get_predefined_genericgf, GenericGFRef, PredefinedGenericGF, ReedSolomonDecoder,
         },
-        BitMatrix, CharacterSet, DecoderRXingResult, DetectorRXingResult, Eci, Result,
+        BitMatrix, CharacterSet, CodewordBlock, CodewordWitness, DecoderRXingResult,
+        DetectorRXingResult, Eci, Result,
     },
     exceptions::Exceptions,
 };
@@ -74,19 +76,37 @@ const DIGIT_TABLE: [&str; 16] = [
 //   private AztecDetectorRXingResult ddata;
 
 pub fn decode(detectorRXingResult: &AztecDetectorRXingResult) -> Result<DecoderRXingResult> {
+    if detectorRXingResult.isRune() {
+        // the mode message holds all of a rune, its value is the text
+        let value = detectorRXingResult.getNbDatablocks();
+        return Ok(DecoderRXingResult::new(
+            vec![value as u8],
+            value.to_string(),
+            Vec::new(),
+            String::new(),
+        ));
+    }
     //let mut detectorRXingResult = detectorRXingResult.clone();
     let matrix = detectorRXingResult.getBits();
     let rawbits = extract_bits(detectorRXingResult, matrix);
     let corrected_bits = correct_bits(detectorRXingResult, &rawbits)?;
     let raw_bytes = convertBoolArrayToByteArray(&corrected_bits.correct_bits);
-    let result = get_encoded_data(&corrected_bits.correct_bits);
-    let mut decoder_rxing_result = DecoderRXingResult::new(
+    let mut result = get_encoded_data(&corrected_bits.correct_bits)?;
+    // the index of the symbol and the number of symbols are given as its sequence and parity
+    let structured_append =
+        parse_structured_append(&corrected_bits.correct_bits, &mut result).unwrap_or_default();
+    let mut decoder_rxing_result = DecoderRXingResult::with_sa(
         raw_bytes,
-        result?,
+        result,
         Vec::new(),
         format!("{}%", corrected_bits.ec_level),
+        structured_append.index,
+        structured_append.count,
     );
+    decoder_rxing_result.setStructuredAppendId(structured_append.id);
     decoder_rxing_result.setNumBits(corrected_bits.correct_bits.len());
+    decoder_rxing_result.setErrorsCorrected(corrected_bits.codewords.errors_corrected());
+    decoder_rxing_result.setCodewords(Some(corrected_bits.codewords));
 
     Ok(decoder_rxing_result)
 }
@@ -96,6 +116,50 @@ pub fn highLevelDecode(correctedBits: &[bool]) -> Result<String> {
     get_encoded_data(correctedBits)
 }
 
+/**
+ * Reads the structured append header of a symbol, ISO/IEC 24778:2008 8.6, and removes it from the
+ * decoded text. Such a symbol starts with a latch to mixed and back to upper, then the ID of the
+ * message between spaces, if it has one, and two upper case letters for the index of the symbol
+ * and the number of symbols, A standing for the first and for 1.
+ *
+ * @return the index, count and ID, or `None` if the symbol is not part of a message
+ */
+pub fn parse_structured_append(
+    corrected_bits: &[bool],
+    text: &mut String,
+) -> Option<StructuredAppendInfo> {
+    // CTRL_ML in the upper table, CTRL_UL in the mixed one
+    if corrected_bits.len() <= 20
+        || read_code(corrected_bits, 0, 5) != 29
+        || read_code(corrected_bits, 5, 5) != 29
+    {
+        return None;
+    }
+
+    let (id, rest) = match text.strip_prefix(' ') {
+        Some(delimited) => delimited.split_once(' ')?,
+        None => ("", text.as_str()),
+    };
+    let mut letters = rest.chars();
+    let (index, count) = match (letters.next(), letters.next()) {
+        (Some(index @ 'A'..='Z'), Some(count @ 'A'..='Z')) => {
+            (index as i32 - 'A' as i32, count as i32 - 'A' as i32 + 1)
+        }
+        _ => return None,
+    };
+    if count < 2 || index >= count {
+        return None;
+    }
+
+    let info = StructuredAppendInfo {
+        index,
+        count,
+        id: id.to_owned(),
+    };
+    *text = letters.as_str().to_owned();
+    Some(info)
+}
+
 /**
  * Gets the string encoded in the aztec code bits
  *
@@ -301,12 +365,14 @@ fn get_character(table: Table, code: u32) -> Result<&'static str> {
 struct CorrectedBitsRXingResult {
     correct_bits: Vec<bool>,
     ec_level: u32,
+    codewords: CodewordWitness,
 }
 impl CorrectedBitsRXingResult {
-    pub const fn new(correct_bits: Vec<bool>, ec_level: u32) -> Self {
+    pub const fn new(correct_bits: Vec<bool>, ec_level: u32, codewords: CodewordWitness) -> Self {
         Self {
             correct_bits,
             ec_level,
+            codewords,
         }
     }
 }
@@ -355,15 +421,19 @@ fn correct_bits(
         offset += codeword_size;
     }
 
+    let mut codewords = CodewordWitness::new(codeword_size as u32, data_words);
+
     //try {
     let rs_decoder = ReedSolomonDecoder::new(gf);
-    rs_decoder.decode(
-        &mut data_words,
-        (num_codewords - num_data_codewords as usize) as i32,
+    let block = CodewordBlock::correct(
+        &rs_decoder,
+        codewords.raw_codewords.clone(),
+        num_data_codewords as usize,
     )?;
     //} catch (ReedSolomonException ex) {
     //throw FormatException.getFormatInstance(ex);
     //}
+    let data_words = block.data_codewords();
 
     // Now perform the unstuffing operation.
     // First, count how many bits are going to be thrown out as stuffing
@@ -405,9 +475,13 @@ fn correct_bits(
         }
     }
 
+    codewords.corrected_codewords = data_words.to_vec();
+    codewords.blocks.push(block);
+
     Ok(CorrectedBitsRXingResult::new(
         corrected_bits,
         (100 * (num_codewords - num_data_codewords as usize) / num_codewords) as u32,
+        codewords,
     ))
 }
 
@@ -18,7 +18,8 @@ use crate::{
     common::{
         detector::WhiteRectangleDetector,
         reedsolomon::{self, ReedSolomonDecoder},
-        BitMatrix, DefaultGridSampler, GridSampler, Quadrilateral, Result,
+        BitMatrix, DefaultGridSampler, DetectedPattern, GridSampler, PatternKind,
+        PerspectiveTransform, Quadrilateral, Result, SamplingGrid,
     },
     exceptions::Exceptions,
     point, Point,
@@ -33,6 +34,9 @@ const EXPECTED_CORNER_BITS: [u32; 4] = [
     0x707, // 03407 .XX X.. ... XXX
 ];
 
+// Aztec Runes invert every other bit of the compact mode message
+const RUNE_PARAMETER_MASK: u64 = 0xAAAAAAA;
+
 /**
  * Encapsulates logic that can detect an Aztec Code in an image, even if the Aztec Code
  * is rotated or skewed, or partially obscured.
@@ -97,7 +101,12 @@ impl<'a> Detector<'_> {
         );
 
         // 4. Sample the grid
-        let bits = self.sample_grid(self.image, src_quad)?;
+        let (bits, sampling_grid) = self.sample_grid(self.image, src_quad)?;
+        let bulls_eye_center = (bulls_eye_corners[0]
+            + bulls_eye_corners[1]
+            + bulls_eye_corners[2]
+            + bulls_eye_corners[3])
+            / 4.0;
 
         // 5. Get the corners of the matrix.
         let corners = self.get_matrix_corner_points(&bulls_eye_corners);
@@ -108,7 +117,11 @@ impl<'a> Detector<'_> {
             self.compact,
             self.nb_data_blocks,
             self.nb_layers,
-        ))
+        )
+        .with_sampling_grid(sampling_grid.with_patterns(vec![DetectedPattern::new(
+            PatternKind::Finder,
+            bulls_eye_center,
+        )])))
     }
 
     /**
@@ -158,13 +171,21 @@ impl<'a> Detector<'_> {
 
         // Corrects parameter data using RS.  Returns just the data portion
         // without the error correction.
-        let corrected_data = Self::get_corrected_parameter_data(parameter_data, self.compact)?;
+        let corrected_data = Self::get_corrected_parameter_data(parameter_data, self.compact);
 
         if self.compact {
-            // 8 bits:  2 bits layers and 6 bits data blocks
-            self.nb_layers = (corrected_data >> 6) + 1;
-            self.nb_data_blocks = (corrected_data & 0x3F) + 1;
+            if let Ok(corrected_data) = corrected_data {
+                // 8 bits:  2 bits layers and 6 bits data blocks
+                self.nb_layers = (corrected_data >> 6) + 1;
+                self.nb_data_blocks = (corrected_data & 0x3F) + 1;
+            } else {
+                // 8 bits of an Aztec Rune, which has no layers
+                self.nb_layers = 0;
+                self.nb_data_blocks =
+                    Self::get_corrected_parameter_data(parameter_data ^ RUNE_PARAMETER_MASK, true)?;
+            }
         } else {
+            let corrected_data = corrected_data?;
             // 16 bits:  5 bits layers and 11 bits data blocks
             self.nb_layers = (corrected_data >> 11) + 1;
             self.nb_data_blocks = (corrected_data & 0x7FF) + 1;
@@ -455,7 +476,11 @@ impl<'a> Detector<'_> {
      * topLeft, topRight, bottomRight, and bottomLeft are the centers of the squares on the
      * diagonal just outside the bull's eye.
      */
-    fn sample_grid(&self, image: &BitMatrix, quad: Quadrilateral) -> Result<BitMatrix> {
+    fn sample_grid(
+        &self,
+        image: &BitMatrix,
+        quad: Quadrilateral,
+    ) -> Result<(BitMatrix, SamplingGrid)> {
         let sampler = DefaultGridSampler;
         let dimension = self.get_dimension();
 
@@ -469,9 +494,14 @@ impl<'a> Detector<'_> {
             point(low, high),
         );
 
-        let (res, _) = sampler.sample_grid_detailed(image, dimension, dimension, dst, quad)?;
+        let grid = SamplingGrid::with_transform(
+            dimension,
+            dimension,
+            PerspectiveTransform::quadrilateralToQuadrilateral(dst, quad)?,
+        );
+        let (res, _) = sampler.sample_grid(image, dimension, dimension, grid.regions())?;
 
-        Ok(res)
+        Ok((res, grid))
     }
 
     /**
@@ -22,6 +22,7 @@ use crate::{
         BitArray, BitFieldBaseType, BitMatrix, CharacterSet, Result,
     },
     exceptions::Exceptions,
+    BarcodeFormat, EncodingWitness, ParityBlock,
 };
 
 use super::{AztecCode, HighLevelEncoder};
@@ -98,11 +99,73 @@ pub fn encode_with_charset(
         encode_bytes_with_charset(&bytes, minECCPercent, userSpecifiedLayers, charset)
     } else {
         Err(Exceptions::illegal_argument_with(format!(
-            "'{data}' cannot be encoded as ISO_8859_1"
+            "'{data}' cannot be encoded as {}",
+            charset.get_charset_name()
         )))
     }
 }
 
+/**
+ * Encodes the given string content as an Aztec symbol
+ *
+ * @param data input data string
+ * @param minECCPercent minimal percentage of error check words (According to ISO/IEC 24778:2008,
+ *                      a minimum of 23% + 3 words is recommended)
+ * @param userSpecifiedLayers if non-zero, a user-specified value for the number of layers
+ * @param compact if given, whether to encode a compact rather than a full-range symbol,
+ *                overriding the sign of userSpecifiedLayers
+ * @param charset character set in which to encode string using ECI; if none, no ECI code
+ *                will be inserted, and the string must be encodable as ISO/IEC 8859-1
+ * @return Aztec symbol matrix with metadata
+ */
+pub fn encode_with_compact(
+    data: &str,
+    minECCPercent: u32,
+    userSpecifiedLayers: i32,
+    compact: Option<bool>,
+    charset: Option<CharacterSet>,
+) -> Result<AztecCode> {
+    let charset_or_default = charset.unwrap_or(CharacterSet::ISO8859_1);
+    let Ok(bytes) = charset_or_default.encode(data) else {
+        return Err(Exceptions::illegal_argument_with(format!(
+            "'{data}' cannot be encoded as {}",
+            charset_or_default.get_charset_name()
+        )));
+    };
+    encode_symbol(
+        &bytes,
+        minECCPercent,
+        userSpecifiedLayers,
+        compact,
+        charset_or_default,
+    )
+    .map(|symbol| symbol.aztec)
+}
+
+/**
+ * Encodes an Aztec Rune, the 11x11 symbol of ISO/IEC 24778 annex A which holds a single value
+ * in its mode message and has no data layers
+ *
+ * @param value the value of the rune
+ * @return Aztec symbol matrix with metadata, of 0 layers and codewords
+ */
+pub fn encode_rune(value: u8) -> Result<AztecCode> {
+    let mut mode_message = BitArray::new();
+    mode_message.appendBits(value as BitFieldBaseType, 8)?;
+    let mut mode_message = generateCheckWords(&mode_message, 28, 4)?;
+    // every other bit is inverted, which tells runes from compact symbols
+    for i in (0..28).step_by(2) {
+        mode_message.flip(i);
+    }
+
+    let matrixSize = 11;
+    let mut matrix = BitMatrix::with_single_dimension(matrixSize)?;
+    drawModeMessage(&mut matrix, true, matrixSize, mode_message);
+    drawBullsEye(&mut matrix, matrixSize / 2, 5);
+
+    Ok(AztecCode::new(true, matrixSize, 0, 0, matrix))
+}
+
 /**
  * Encodes the given binary content as an Aztec symbol (without ECI code)
  *
@@ -152,6 +215,100 @@ pub fn encode_bytes_with_charset(
     user_specified_layers: i32,
     charset: CharacterSet,
 ) -> Result<AztecCode> {
+    encode_symbol(data, min_eccpercent, user_specified_layers, None, charset)
+        .map(|symbol| symbol.aztec)
+}
+
+/**
+ * Encodes `data` as [`encode`] does, and records the intermediates of each step.
+ *
+ * # Arguments
+ * * `data` - The text to encode, which must be encodable as ISO/IEC 8859-1 (Latin-1)
+ * * `min_eccpercent` - The minimal percentage of error check words
+ * * `user_specified_layers` - If non-zero, the number of layers, negative for a compact symbol
+ *
+ * # Returns
+ * The Aztec symbol and the witness of its encoding
+ */
+pub fn encode_with_witness(
+    data: &str,
+    min_eccpercent: u32,
+    user_specified_layers: i32,
+) -> Result<(AztecCode, EncodingWitness)> {
+    let Ok(bytes) = CharacterSet::ISO8859_1.encode(data) else {
+        return Err(Exceptions::illegal_argument_with(format!(
+            "'{data}' cannot be encoded as ISO_8859_1"
+        )));
+    };
+    let EncodedSymbol {
+        aztec,
+        bits,
+        stuffed_bits,
+        word_size,
+        message_bits,
+    } = encode_symbol(
+        &bytes,
+        min_eccpercent,
+        user_specified_layers,
+        None,
+        CharacterSet::ISO8859_1,
+    )?;
+
+    // the message is padded at the start to fill the layers
+    let start_pad = message_bits.get_size() % word_size;
+    let words = |bits: &BitArray, start: usize| {
+        (start..bits.get_size())
+            .step_by(word_size)
+            .map(|i| (0..word_size).fold(0u16, |word, j| word << 1 | bits.get(i + j) as u16))
+            .collect::<Vec<_>>()
+    };
+    let codewords = words(&message_bits, start_pad);
+    let data_codewords = words(&stuffed_bits, 0);
+    let parity = codewords[data_codewords.len()..].to_vec();
+
+    let num_bytes = bits.getSizeInBytes();
+    let mut data_bits = vec![0u8; num_bytes];
+    bits.toBytes(0, &mut data_bits, 0, num_bytes);
+
+    let witness = EncodingWitness {
+        format: BarcodeFormat::AZTEC,
+        content: data.to_owned(),
+        version: aztec.getLayers(),
+        segments: Vec::new(),
+        data_bits,
+        num_data_bits: bits.get_size(),
+        codeword_size: word_size as u32,
+        data_codewords: data_codewords.clone(),
+        blocks: vec![ParityBlock {
+            data: data_codewords,
+            parity,
+        }],
+        codewords,
+        mask: None,
+        matrix: aztec.getMatrix().clone(),
+    };
+
+    Ok((aztec, witness))
+}
+
+// The Aztec symbol along with the intermediates of encoding it
+struct EncodedSymbol {
+    aztec: AztecCode,
+    // the high-level encoded message, before bit stuffing
+    bits: BitArray,
+    stuffed_bits: BitArray,
+    word_size: usize,
+    // the data and check words, padded at the start to fill the layers
+    message_bits: BitArray,
+}
+
+fn encode_symbol(
+    data: &[u8],
+    min_eccpercent: u32,
+    user_specified_layers: i32,
+    compact_hint: Option<bool>,
+    charset: CharacterSet,
+) -> Result<EncodedSymbol> {
     // High-level encode
     let bits = HighLevelEncoder::with_charset(data.into(), charset).encode()?;
 
@@ -164,7 +321,7 @@ pub fn encode_bytes_with_charset(
     let mut word_size;
     let mut stuffed_bits;
     if user_specified_layers != DEFAULT_AZTEC_LAYERS {
-        compact = user_specified_layers < 0;
+        compact = compact_hint.unwrap_or(user_specified_layers < 0);
         layers = i32::abs(user_specified_layers) as u32;
         if layers
             > (if compact {
@@ -197,20 +354,23 @@ pub fn encode_bytes_with_charset(
         stuffed_bits = BitArray::new();
         // We look at the possible table sizes in the order Compact1, Compact2, Compact3,
         // Compact4, Normal4,...  Normal(i) for i < 4 isn't typically used since Compact(i+1)
-        // is the same size, but has more data.
-        let mut i = 0;
+        // is the same size, but has more data, unless only full-range symbols are wanted.
+        let mut sizes = (1..=MAX_NB_BITS_COMPACT)
+            .map(|layers| (true, layers))
+            .chain((1..=MAX_NB_BITS).map(|layers| (false, layers)))
+            .filter(|&(size_compact, size_layers)| {
+                compact_hint.map_or(size_compact || size_layers >= 4, |c| c == size_compact)
+            });
         loop {
-            // for (int i = 0; ; i++) {
-            if i > MAX_NB_BITS {
+            let Some((size_compact, size_layers)) = sizes.next() else {
                 return Err(Exceptions::illegal_argument_with(
                     "Data too large for an Aztec code",
                 ));
-            }
-            compact = i <= 3;
-            layers = if compact { i + 1 } else { i };
+            };
+            compact = size_compact;
+            layers = size_layers;
             total_bits_in_layer_var = total_bits_in_layer(layers, compact);
             if total_size_bits > total_bits_in_layer_var {
-                i += 1;
                 continue;
             }
             // [Re]stuff the bits if this is the first opportunity, or if the
@@ -223,13 +383,11 @@ pub fn encode_bytes_with_charset(
                 total_bits_in_layer_var - (total_bits_in_layer_var % word_size);
             if compact && stuffed_bits.get_size() as u32 > word_size * 64 {
                 // Compact format only allows 64 data words, though C4 can hold more words than that
-                i += 1;
                 continue;
             }
             if stuffed_bits.get_size() as u32 + ecc_bits <= usable_bits_in_layers {
                 break;
             }
-            i += 1;
         }
     }
     let message_bits = generateCheckWords(
@@ -345,7 +503,13 @@ pub fn encode_bytes_with_charset(
     // aztec.setLayers(layers);
     // aztec.setCodeWords(messageSizeInWords);
     // aztec.setMatrix(matrix);
-    Ok(aztec)
+    Ok(EncodedSymbol {
+        aztec,
+        bits,
+        stuffed_bits,
+        word_size: word_size as usize,
+        message_bits,
+    })
 }
 
 fn drawBullsEye(matrix: &mut BitMatrix, center: u32, size: u32) {
@@ -509,6 +673,6 @@ pub fn stuffBits(bits: &BitArray, word_size: usize) -> Result<BitArray> {
     Ok(out)
 }
 
-fn total_bits_in_layer(layers: u32, compact: bool) -> u32 {
+pub(crate) fn total_bits_in_layer(layers: u32, compact: bool) -> u32 {
     ((if compact { 88 } else { 112 }) + 16 * layers) * layers
 }
@@ -21,13 +21,16 @@ use std::{fmt::Display, str::FromStr};
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
+use crate::common::CharacterSet;
+
 /**
  * Enumerates barcode formats known to this package. Please keep alphabetized.
  *
  * @author Sean Owen
  */
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
 pub enum BarcodeFormat {
     /** Aztec 2D barcode format. */
     AZTEC,
@@ -89,8 +92,20 @@ pub enum BarcodeFormat {
 
     DXFilmEdge,
 
+    /** Laetus Pharmacode 1D format, one-track or two-track. */
+    PHARMACODE,
+
+    /** MSI Plessey 1D format. */
+    MSI,
+
     /// format not supported
     UNSUPORTED_FORMAT,
+
+    /**
+     * A format this package doesn't know, read by a custom reader registered in a
+     * [`crate::ReaderRegistry`], by the name the reader gives it
+     */
+    Other(String),
 }
 
 impl Display for BarcodeFormat {
@@ -120,6 +135,9 @@ impl Display for BarcodeFormat {
                 BarcodeFormat::UPC_E => "upc e",
                 BarcodeFormat::UPC_EAN_EXTENSION => "upc/ean extension",
                 BarcodeFormat::DXFilmEdge => "DXFilmEdge",
+                BarcodeFormat::PHARMACODE => "pharmacode",
+                BarcodeFormat::MSI => "msi",
+                BarcodeFormat::Other(name) => name.as_str(),
                 _ => "unsuported",
             }
         )
@@ -168,6 +186,10 @@ impl From<&str> for BarcodeFormat {
             "upc ean extension" | "upc extension" | "ean extension" | "upc/ean extension"
             | "upc_ean_extension" => BarcodeFormat::UPC_EAN_EXTENSION,
             "DXFilmEdge" | "dxfilmedge" | "dx film edge" => BarcodeFormat::DXFilmEdge,
+            "pharmacode" | "pharma code" | "pharma_code" | "laetus pharmacode" => {
+                BarcodeFormat::PHARMACODE
+            }
+            "msi" | "msi plessey" | "msi_plessey" | "modified plessey" => BarcodeFormat::MSI,
             _ => BarcodeFormat::UNSUPORTED_FORMAT,
         }
     }
@@ -187,3 +209,356 @@ impl FromStr for BarcodeFormat {
         }
     }
 }
+
+/// The check character (or error correction) scheme a format uses to detect misreads
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum ChecksumScheme {
+    /// No check character is defined by the symbology
+    None,
+    /// GS1 modulo 10 check digit (EAN, UPC)
+    Modulo10,
+    /// Optional modulo 43 check character (Code 39)
+    Modulo43,
+    /// Two modulo 47 check characters, "C" and "K" (Code 93)
+    Modulo47,
+    /// Modulo 79 check character over the finder patterns (RSS-14)
+    Modulo79,
+    /// Modulo 103 check character (Code 128)
+    Modulo103,
+    /// Modulo 127 check character (Telepen)
+    Modulo127,
+    /// Modulo 211 check character (RSS Expanded)
+    Modulo211,
+    /// A parity bit (DX film edge)
+    Parity,
+    /// Reed-Solomon error correction codewords (2D symbologies)
+    ReedSolomon,
+}
+
+/**
+ * The maximum amount of data a single symbol can hold, for each kind of data.
+ * Formats which can not hold a kind of data report 0 for it.
+ */
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct DataCapacity {
+    /// Digits
+    pub numeric: usize,
+    /// Upper case letters, digits and the symbology specific punctuation
+    pub alphanumeric: usize,
+    /// Arbitrary bytes (or full ASCII characters for 1D formats)
+    pub bytes: usize,
+}
+
+impl DataCapacity {
+    const fn new(numeric: usize, alphanumeric: usize, bytes: usize) -> Self {
+        Self {
+            numeric,
+            alphanumeric,
+            bytes,
+        }
+    }
+
+    const fn numeric(numeric: usize) -> Self {
+        Self::new(numeric, 0, 0)
+    }
+}
+
+/// The character sets encoders of formats with ECI support accept as `CharacterSet` hint
+const ECI_CHARACTER_SETS: &[CharacterSet] = &[
+    CharacterSet::ISO8859_1,
+    CharacterSet::ISO8859_2,
+    CharacterSet::ISO8859_3,
+    CharacterSet::ISO8859_4,
+    CharacterSet::ISO8859_5,
+    CharacterSet::ISO8859_6,
+    CharacterSet::ISO8859_7,
+    CharacterSet::ISO8859_8,
+    CharacterSet::ISO8859_9,
+    CharacterSet::ISO8859_10,
+    CharacterSet::ISO8859_11,
+    CharacterSet::ISO8859_13,
+    CharacterSet::ISO8859_14,
+    CharacterSet::ISO8859_15,
+    CharacterSet::ISO8859_16,
+    CharacterSet::Shift_JIS,
+    CharacterSet::Cp1250,
+    CharacterSet::Cp1251,
+    CharacterSet::Cp1252,
+    CharacterSet::Cp1256,
+    CharacterSet::UTF16BE,
+    CharacterSet::UTF8,
+    CharacterSet::ASCII,
+    CharacterSet::Big5,
+    CharacterSet::GB18030,
+    CharacterSet::EUC_KR,
+];
+
+impl BarcodeFormat {
+    /// All formats known to this package, excluding `UNSUPORTED_FORMAT`
+    pub const ALL: [BarcodeFormat; 23] = [
+        BarcodeFormat::AZTEC,
+        BarcodeFormat::CODABAR,
+        BarcodeFormat::CODE_39,
+        BarcodeFormat::CODE_93,
+        BarcodeFormat::CODE_128,
+        BarcodeFormat::DATA_MATRIX,
+        BarcodeFormat::EAN_8,
+        BarcodeFormat::EAN_13,
+        BarcodeFormat::ITF,
+        BarcodeFormat::MAXICODE,
+        BarcodeFormat::PDF_417,
+        BarcodeFormat::QR_CODE,
+        BarcodeFormat::MICRO_QR_CODE,
+        BarcodeFormat::RECTANGULAR_MICRO_QR_CODE,
+        BarcodeFormat::RSS_14,
+        BarcodeFormat::RSS_EXPANDED,
+        BarcodeFormat::TELEPEN,
+        BarcodeFormat::UPC_A,
+        BarcodeFormat::UPC_E,
+        BarcodeFormat::UPC_EAN_EXTENSION,
+        BarcodeFormat::DXFilmEdge,
+        BarcodeFormat::PHARMACODE,
+        BarcodeFormat::MSI,
+    ];
+
+    /// Returns true if `MultiFormatWriter` can encode this format
+    pub fn can_encode(&self) -> bool {
+        match self {
+            BarcodeFormat::RSS_EXPANDED => cfg!(feature = "client_support"),
+            _ => matches!(
+                self,
+                BarcodeFormat::AZTEC
+                    | BarcodeFormat::CODABAR
+                    | BarcodeFormat::CODE_39
+                    | BarcodeFormat::CODE_93
+                    | BarcodeFormat::CODE_128
+                    | BarcodeFormat::DATA_MATRIX
+                    | BarcodeFormat::EAN_8
+                    | BarcodeFormat::EAN_13
+                    | BarcodeFormat::ITF
+                    | BarcodeFormat::MAXICODE
+                    | BarcodeFormat::MICRO_QR_CODE
+                    | BarcodeFormat::MSI
+                    | BarcodeFormat::PDF_417
+                    | BarcodeFormat::PHARMACODE
+                    | BarcodeFormat::QR_CODE
+                    | BarcodeFormat::RSS_14
+                    | BarcodeFormat::TELEPEN
+                    | BarcodeFormat::UPC_A
+                    | BarcodeFormat::UPC_E
+            ),
+        }
+    }
+
+    /**
+     * Returns true if `MultiFormatReader` can decode this format, given the enabled features.
+     * `Other` formats are only decoded by the custom readers registered for them.
+     */
+    pub fn can_decode(&self) -> bool {
+        match self {
+            BarcodeFormat::DXFilmEdge => cfg!(feature = "experimental_features"),
+            _ => !matches!(
+                self,
+                BarcodeFormat::UPC_EAN_EXTENSION
+                    | BarcodeFormat::UNSUPORTED_FORMAT
+                    | BarcodeFormat::Other(_)
+            ),
+        }
+    }
+
+    /// Returns true for linear formats, which are read along a single scan line
+    pub fn is_1d(&self) -> bool {
+        matches!(
+            self,
+            BarcodeFormat::CODABAR
+                | BarcodeFormat::CODE_39
+                | BarcodeFormat::CODE_93
+                | BarcodeFormat::CODE_128
+                | BarcodeFormat::EAN_8
+                | BarcodeFormat::EAN_13
+                | BarcodeFormat::ITF
+                | BarcodeFormat::MSI
+                | BarcodeFormat::PHARMACODE
+                | BarcodeFormat::RSS_14
+                | BarcodeFormat::RSS_EXPANDED
+                | BarcodeFormat::TELEPEN
+                | BarcodeFormat::UPC_A
+                | BarcodeFormat::UPC_E
+                | BarcodeFormat::UPC_EAN_EXTENSION
+                | BarcodeFormat::DXFilmEdge
+        )
+    }
+
+    /// Returns true for matrix and stacked formats
+    pub fn is_2d(&self) -> bool {
+        matches!(
+            self,
+            BarcodeFormat::AZTEC
+                | BarcodeFormat::DATA_MATRIX
+                | BarcodeFormat::MAXICODE
+                | BarcodeFormat::PDF_417
+                | BarcodeFormat::QR_CODE
+                | BarcodeFormat::MICRO_QR_CODE
+                | BarcodeFormat::RECTANGULAR_MICRO_QR_CODE
+        )
+    }
+
+    /**
+     * The maximum amount of data a single symbol of this format can hold, at the lowest
+     * error correction level. For formats this package encodes, variable length 1D formats
+     * report the limit enforced by the writer. Returns `None` for formats without a
+     * defined limit.
+     */
+    pub fn max_data_capacity(&self) -> Option<DataCapacity> {
+        let capacity = match self {
+            BarcodeFormat::AZTEC => DataCapacity::new(3832, 3067, 1914),
+            BarcodeFormat::CODE_39 => DataCapacity::new(80, 80, 40),
+            BarcodeFormat::CODE_93 => DataCapacity::new(80, 80, 40),
+            BarcodeFormat::CODE_128 => DataCapacity::new(80, 80, 80),
+            BarcodeFormat::DATA_MATRIX => DataCapacity::new(3116, 2335, 1556),
+            BarcodeFormat::EAN_8 => DataCapacity::numeric(8),
+            BarcodeFormat::EAN_13 => DataCapacity::numeric(13),
+            BarcodeFormat::ITF => DataCapacity::numeric(80),
+            BarcodeFormat::MAXICODE => DataCapacity::new(138, 93, 93),
+            BarcodeFormat::PDF_417 => DataCapacity::new(2710, 1850, 1108),
+            BarcodeFormat::PHARMACODE => DataCapacity::numeric(8),
+            BarcodeFormat::QR_CODE => DataCapacity::new(7089, 4296, 2953),
+            BarcodeFormat::MICRO_QR_CODE => DataCapacity::new(35, 21, 15),
+            BarcodeFormat::RECTANGULAR_MICRO_QR_CODE => DataCapacity::new(361, 219, 150),
+            BarcodeFormat::RSS_14 => DataCapacity::numeric(14),
+            BarcodeFormat::RSS_EXPANDED => DataCapacity::new(74, 41, 41),
+            BarcodeFormat::UPC_A => DataCapacity::numeric(12),
+            BarcodeFormat::UPC_E => DataCapacity::numeric(8),
+            BarcodeFormat::UPC_EAN_EXTENSION => DataCapacity::numeric(5),
+            BarcodeFormat::DXFilmEdge => DataCapacity::numeric(6),
+            BarcodeFormat::CODABAR
+            | BarcodeFormat::MSI
+            | BarcodeFormat::TELEPEN
+            | BarcodeFormat::UNSUPORTED_FORMAT
+            | BarcodeFormat::Other(_) => return None,
+        };
+        Some(capacity)
+    }
+
+    /**
+     * The character sets which can be requested with the `CharacterSet` encode hint.
+     * Formats with ECI support accept any of the common ECI character sets, 1D formats
+     * are limited to (a subset of) ASCII. Numeric only formats return an empty slice.
+     */
+    pub fn supported_character_sets(&self) -> &'static [CharacterSet] {
+        match self {
+            BarcodeFormat::AZTEC
+            | BarcodeFormat::DATA_MATRIX
+            | BarcodeFormat::MAXICODE
+            | BarcodeFormat::PDF_417
+            | BarcodeFormat::QR_CODE
+            | BarcodeFormat::MICRO_QR_CODE
+            | BarcodeFormat::RECTANGULAR_MICRO_QR_CODE => ECI_CHARACTER_SETS,
+            BarcodeFormat::CODABAR
+            | BarcodeFormat::CODE_39
+            | BarcodeFormat::CODE_93
+            | BarcodeFormat::CODE_128
+            | BarcodeFormat::RSS_EXPANDED
+            | BarcodeFormat::TELEPEN => &[CharacterSet::ASCII],
+            _ => &[],
+        }
+    }
+
+    /// The checksum scheme of this format
+    pub fn checksum_scheme(&self) -> ChecksumScheme {
+        match self {
+            BarcodeFormat::AZTEC
+            | BarcodeFormat::DATA_MATRIX
+            | BarcodeFormat::MAXICODE
+            | BarcodeFormat::PDF_417
+            | BarcodeFormat::QR_CODE
+            | BarcodeFormat::MICRO_QR_CODE
+            | BarcodeFormat::RECTANGULAR_MICRO_QR_CODE => ChecksumScheme::ReedSolomon,
+            BarcodeFormat::EAN_8
+            | BarcodeFormat::EAN_13
+            | BarcodeFormat::UPC_A
+            | BarcodeFormat::UPC_E => ChecksumScheme::Modulo10,
+            BarcodeFormat::CODE_39 => ChecksumScheme::Modulo43,
+            BarcodeFormat::CODE_93 => ChecksumScheme::Modulo47,
+            BarcodeFormat::CODE_128 => ChecksumScheme::Modulo103,
+            BarcodeFormat::RSS_14 => ChecksumScheme::Modulo79,
+            BarcodeFormat::RSS_EXPANDED => ChecksumScheme::Modulo211,
+            BarcodeFormat::TELEPEN => ChecksumScheme::Modulo127,
+            BarcodeFormat::DXFilmEdge => ChecksumScheme::Parity,
+            BarcodeFormat::CODABAR
+            | BarcodeFormat::ITF
+            | BarcodeFormat::MSI
+            | BarcodeFormat::PHARMACODE
+            | BarcodeFormat::UPC_EAN_EXTENSION
+            | BarcodeFormat::UNSUPORTED_FORMAT
+            | BarcodeFormat::Other(_) => ChecksumScheme::None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BarcodeFormat, ChecksumScheme, EncodeHints, MultiFormatWriter, Writer};
+
+    #[test]
+    fn test_encode_capability_matches_writer() {
+        for format in BarcodeFormat::ALL {
+            let contents = match format {
+                BarcodeFormat::EAN_13 => "5901234123457",
+                BarcodeFormat::UPC_A => "485963095124",
+                BarcodeFormat::CODABAR => "A123A",
+                BarcodeFormat::PHARMACODE => "1234",
+                BarcodeFormat::RSS_14 => "09506000134352",
+                BarcodeFormat::RSS_EXPANDED => "(01)09506000134352(10)ABC",
+                _ => "12345670",
+            };
+            let writable = MultiFormatWriter
+                .encode_with_hints(contents, &format, 100, 100, &EncodeHints::default())
+                .is_ok();
+            assert_eq!(format.can_encode(), writable, "{format}");
+        }
+    }
+
+    #[test]
+    fn test_dimensionality() {
+        for format in BarcodeFormat::ALL {
+            assert!(format.is_1d() ^ format.is_2d(), "{format}");
+            if format.can_encode() {
+                assert!(format.max_data_capacity().is_some() || format.is_1d());
+            }
+        }
+        assert!(!BarcodeFormat::UNSUPORTED_FORMAT.can_decode());
+        assert!(!BarcodeFormat::Other("square".to_owned()).can_decode());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_other_round_trip() {
+        let format = BarcodeFormat::Other("square".to_owned());
+        let json = serde_json::to_string(&format).unwrap();
+        assert_eq!(
+            format,
+            serde_json::from_str::<BarcodeFormat>(&json).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_checksum_and_capacity() {
+        assert_eq!(
+            ChecksumScheme::ReedSolomon,
+            BarcodeFormat::QR_CODE.checksum_scheme()
+        );
+        assert_eq!(
+            ChecksumScheme::Modulo103,
+            BarcodeFormat::CODE_128.checksum_scheme()
+        );
+        assert_eq!(
+            2953,
+            BarcodeFormat::QR_CODE.max_data_capacity().unwrap().bytes
+        );
+        assert_eq!(0, BarcodeFormat::EAN_13.max_data_capacity().unwrap().bytes);
+        assert!(BarcodeFormat::EAN_13.supported_character_sets().is_empty());
+    }
+}
@@ -0,0 +1,507 @@
+/*
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::borrow::Cow;
+
+use crate::common::Result;
+use crate::{Exceptions, LuminanceSource};
+
+/// The colors of the top left 2x2 pixels of a Bayer color filter array, row by row
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BayerPattern {
+    Rggb,
+    Bggr,
+    Grbg,
+    Gbrg,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Channel {
+    Red,
+    Green,
+    Blue,
+}
+
+impl BayerPattern {
+    fn channel(&self, x: usize, y: usize) -> Channel {
+        let (top, bottom) = match self {
+            BayerPattern::Rggb => (
+                [Channel::Red, Channel::Green],
+                [Channel::Green, Channel::Blue],
+            ),
+            BayerPattern::Bggr => (
+                [Channel::Blue, Channel::Green],
+                [Channel::Green, Channel::Red],
+            ),
+            BayerPattern::Grbg => (
+                [Channel::Green, Channel::Red],
+                [Channel::Blue, Channel::Green],
+            ),
+            BayerPattern::Gbrg => (
+                [Channel::Green, Channel::Blue],
+                [Channel::Red, Channel::Green],
+            ),
+        };
+        if y % 2 == 0 {
+            top[x % 2]
+        } else {
+            bottom[x % 2]
+        }
+    }
+}
+
+/**
+ * How the samples of a row are stored. The 10 and 12 bit formats are packed as MIPI CSI-2
+ * RAW10 and RAW12 are: the high 8 bits of each sample in a byte of their own, followed by the
+ * low bits of the group, so the luminance is read from the high bytes alone.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BayerFormat {
+    /// One byte per sample
+    Raw8,
+    /// 4 samples in 5 bytes: their high 8 bits, then a byte of their low 2 bits
+    Raw10,
+    /// 2 samples in 3 bytes: their high 8 bits, then a byte of their low 4 bits
+    Raw12,
+}
+
+impl BayerFormat {
+    /// The number of bytes holding a row of `width` samples, without padding
+    pub fn row_len(&self, width: usize) -> usize {
+        match self {
+            BayerFormat::Raw8 => width,
+            BayerFormat::Raw10 => width.div_ceil(4) * 5,
+            BayerFormat::Raw12 => width.div_ceil(2) * 3,
+        }
+    }
+
+    // The offset of the high byte of sample `x` in its row
+    fn offset(&self, x: usize) -> usize {
+        match self {
+            BayerFormat::Raw8 => x,
+            BayerFormat::Raw10 => x / 4 * 5 + x % 4,
+            BayerFormat::Raw12 => x / 2 * 3 + x % 2,
+        }
+    }
+}
+
+/// How the colors missing at each pixel are interpolated
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BayerDemosaic {
+    /**
+     * Each missing color is the mean of the nearest samples of that color, and the luminance
+     * the green favouring `(R + 2G + B) / 4`
+     */
+    #[default]
+    Bilinear,
+    /**
+     * The luminance is the green channel alone, interpolated from the four green neighbours
+     * of red and blue pixels. Faster, and enough for black and white symbols.
+     */
+    Green,
+}
+
+/**
+ * A luminance source demosaicing a raw Bayer frame from an image sensor as rows are read, so
+ * frames from industrial cameras can be decoded without a full ISP pass. Rows, columns and
+ * the matrix are computed from the frame on demand; nothing is converted up front.
+ *
+ * Pixels at the edges of the frame are interpolated from the samples mirrored across the
+ * edge. A cropped source interpolates from the samples around the crop, as the full frame
+ * would.
+ *
+ * The frame can be any `AsRef<[u8]>`. Cropping clones it, which costs nothing for a `&[u8]` or
+ * an `Arc<[u8]>`.
+ */
+#[derive(Debug, Clone)]
+pub struct BayerLuminanceSource<D = Vec<u8>> {
+    data: D,
+    pattern: BayerPattern,
+    format: BayerFormat,
+    demosaic: BayerDemosaic,
+    stride: usize,
+    frame_width: usize,
+    frame_height: usize,
+    left: usize,
+    top: usize,
+    width: usize,
+    height: usize,
+    invert: bool,
+}
+
+impl<D: AsRef<[u8]> + Clone> BayerLuminanceSource<D> {
+    /**
+     * Wraps a frame whose rows are packed without padding.
+     *
+     * # Errors
+     * `IllegalArgumentException` if the frame is less than 2 pixels wide or high, or `data` is
+     * shorter than `height` rows of `format`
+     */
+    pub fn new(
+        data: D,
+        pattern: BayerPattern,
+        format: BayerFormat,
+        width: usize,
+        height: usize,
+    ) -> Result<Self> {
+        Self::with_stride(data, pattern, format, width, height, format.row_len(width))
+    }
+
+    /**
+     * Wraps a frame whose rows are `stride` bytes apart, the bytes after the samples of each
+     * being padding.
+     *
+     * # Errors
+     * `IllegalArgumentException` if the frame is less than 2 pixels wide or high, `stride` is
+     * less than `format.row_len(width)`, or `data` is shorter than `height` rows
+     */
+    pub fn with_stride(
+        data: D,
+        pattern: BayerPattern,
+        format: BayerFormat,
+        width: usize,
+        height: usize,
+        stride: usize,
+    ) -> Result<Self> {
+        if width < 2 || height < 2 {
+            return Err(Exceptions::illegal_argument_with(format!(
+                "a {width}x{height} frame does not hold a whole color filter array"
+            )));
+        }
+        let row_len = format.row_len(width);
+        if stride < row_len {
+            return Err(Exceptions::illegal_argument_with(format!(
+                "a stride of {stride} is less than the {row_len} bytes of a {format:?} row"
+            )));
+        }
+        let frame_len = stride * (height - 1) + row_len;
+        if data.as_ref().len() < frame_len {
+            return Err(Exceptions::illegal_argument_with(format!(
+                "a {width}x{height} {format:?} frame with a stride of {stride} needs {frame_len} bytes, got {}",
+                data.as_ref().len()
+            )));
+        }
+
+        Ok(Self {
+            data,
+            pattern,
+            format,
+            demosaic: BayerDemosaic::default(),
+            stride,
+            frame_width: width,
+            frame_height: height,
+            left: 0,
+            top: 0,
+            width,
+            height,
+            invert: false,
+        })
+    }
+
+    /// Interpolate the missing colors with `demosaic`, `Bilinear` by default
+    pub fn with_demosaic(mut self, demosaic: BayerDemosaic) -> Self {
+        self.demosaic = demosaic;
+        self
+    }
+
+    pub fn get_pattern(&self) -> BayerPattern {
+        self.pattern
+    }
+
+    pub fn get_format(&self) -> BayerFormat {
+        self.format
+    }
+
+    pub fn get_stride(&self) -> usize {
+        self.stride
+    }
+
+    // The high 8 bits of the sample at (x, y) of the frame, mirrored across its edges
+    fn sample(&self, x: isize, y: isize) -> u32 {
+        let mirror = |i: isize, size: usize| {
+            let last = size as isize - 1;
+            i.abs().min(2 * last - i) as usize
+        };
+        let (x, y) = (mirror(x, self.frame_width), mirror(y, self.frame_height));
+        self.data.as_ref()[y * self.stride + self.format.offset(x)] as u32
+    }
+
+    // The luminance of pixel (x, y) of the frame
+    fn luminance(&self, x: usize, y: usize) -> u8 {
+        let (xi, yi) = (x as isize, y as isize);
+        let s = |dx: isize, dy: isize| self.sample(xi + dx, yi + dy);
+        let cross = || (s(-1, 0) + s(1, 0) + s(0, -1) + s(0, 1)) / 4;
+        let diagonal = || (s(-1, -1) + s(1, -1) + s(-1, 1) + s(1, 1)) / 4;
+        let horizontal = || (s(-1, 0) + s(1, 0)) / 2;
+        let vertical = || (s(0, -1) + s(0, 1)) / 2;
+
+        let channel = self.pattern.channel(x, y);
+        let luminance = match (self.demosaic, channel) {
+            (BayerDemosaic::Green, Channel::Green) => s(0, 0),
+            (BayerDemosaic::Green, _) => cross(),
+            (BayerDemosaic::Bilinear, Channel::Green) => {
+                // red and blue lie either left and right or above and below
+                let (red, blue) = if self.pattern.channel(x + 1, y) == Channel::Red {
+                    (horizontal(), vertical())
+                } else {
+                    (vertical(), horizontal())
+                };
+                (red + 2 * s(0, 0) + blue) / 4
+            }
+            (BayerDemosaic::Bilinear, _) => (s(0, 0) + 2 * cross() + diagonal()) / 4,
+        };
+
+        if self.invert {
+            255 - luminance as u8
+        } else {
+            luminance as u8
+        }
+    }
+}
+
+impl<D: AsRef<[u8]> + Clone> LuminanceSource for BayerLuminanceSource<D> {
+    const SUPPORTS_CROP: bool = true;
+
+    fn get_row(&self, y: usize) -> Option<Cow<'_, [u8]>> {
+        if y >= self.height {
+            return None;
+        }
+        let row = (0..self.width)
+            .map(|x| self.luminance(self.left + x, self.top + y))
+            .collect();
+        Some(Cow::Owned(row))
+    }
+
+    fn get_column(&self, x: usize) -> Vec<u8> {
+        (0..self.height)
+            .map(|y| self.luminance(self.left + x, self.top + y))
+            .collect()
+    }
+
+    fn get_matrix(&self) -> Vec<u8> {
+        (0..self.height)
+            .flat_map(|y| (0..self.width).map(move |x| (x, y)))
+            .map(|(x, y)| self.luminance(self.left + x, self.top + y))
+            .collect()
+    }
+
+    fn get_width(&self) -> usize {
+        self.width
+    }
+
+    fn get_height(&self) -> usize {
+        self.height
+    }
+
+    fn crop(&self, left: usize, top: usize, width: usize, height: usize) -> Result<Self> {
+        if left + width > self.width || top + height > self.height {
+            return Err(Exceptions::illegal_argument_with(
+                "Crop rectangle does not fit within image data.",
+            ));
+        }
+        Ok(Self {
+            data: self.data.clone(),
+            left: self.left + left,
+            top: self.top + top,
+            width,
+            height,
+            ..*self
+        })
+    }
+
+    fn invert(&mut self) {
+        self.invert = !self.invert;
+    }
+
+    fn get_luma8_point(&self, x: usize, y: usize) -> u8 {
+        self.luminance(self.left + x, self.top + y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        common::HybridBinarizer, qrcode::QRCodeReader, BarcodeFormat, BinaryBitmap,
+        LuminanceSource, MultiFormatWriter, Reader, Writer,
+    };
+
+    use super::{BayerDemosaic, BayerFormat, BayerLuminanceSource, BayerPattern};
+
+    // A frame of `format` with the sample `sample(x, y, rgb)` at each pixel, `rgb` being 1 for
+    // the color of its filter and 0 for the others
+    fn frame(
+        pattern: BayerPattern,
+        format: BayerFormat,
+        width: usize,
+        height: usize,
+        sample: impl Fn(usize, usize, [u8; 3]) -> u8,
+    ) -> Vec<u8> {
+        let row_len = format.row_len(width);
+        let mut data = vec![0xff; row_len * height];
+        for y in 0..height {
+            for x in 0..width {
+                let channel = pattern.channel(x, y) as usize;
+                let mut rgb = [0; 3];
+                rgb[channel] = 1;
+                data[y * row_len + format.offset(x)] = sample(x, y, rgb);
+            }
+        }
+        data
+    }
+
+    #[test]
+    fn test_patterns() {
+        // a red image: only the red samples are lit
+        for pattern in [
+            BayerPattern::Rggb,
+            BayerPattern::Bggr,
+            BayerPattern::Grbg,
+            BayerPattern::Gbrg,
+        ] {
+            let data = frame(pattern, BayerFormat::Raw8, 6, 4, |_, _, rgb| rgb[0] * 200);
+            let source =
+                BayerLuminanceSource::new(&data[..], pattern, BayerFormat::Raw8, 6, 4).unwrap();
+            assert!(source.get_matrix().iter().all(|&l| l == 50), "{pattern:?}");
+            let green = source.with_demosaic(BayerDemosaic::Green);
+            assert!(green.get_matrix().iter().all(|&l| l == 0), "{pattern:?}");
+
+            let data = frame(pattern, BayerFormat::Raw8, 6, 4, |_, _, rgb| rgb[1] * 200);
+            let source =
+                BayerLuminanceSource::new(&data[..], pattern, BayerFormat::Raw8, 6, 4).unwrap();
+            assert!(source.get_matrix().iter().all(|&l| l == 100), "{pattern:?}");
+            let green = source.with_demosaic(BayerDemosaic::Green);
+            assert!(green.get_matrix().iter().all(|&l| l == 200), "{pattern:?}");
+        }
+    }
+
+    #[test]
+    fn test_packed_formats() {
+        // the low bits are set, but only the high bytes are read
+        for format in [BayerFormat::Raw8, BayerFormat::Raw10, BayerFormat::Raw12] {
+            let data = frame(BayerPattern::Grbg, format, 5, 3, |x, y, _| {
+                (10 * (y * 5 + x)) as u8
+            });
+            let source =
+                BayerLuminanceSource::new(&data[..], BayerPattern::Grbg, format, 5, 3).unwrap();
+            let source = source.with_demosaic(BayerDemosaic::Green);
+            // green pixels keep their sample, the others average their neighbours
+            assert_eq!(0, source.get_luma8_point(0, 0), "{format:?}");
+            // (0, 0), (2, 0) and (1, 1) twice, mirrored across the top edge
+            assert_eq!(
+                (20 + 60 + 60) / 4,
+                source.get_luma8_point(1, 0),
+                "{format:?}"
+            );
+            assert_eq!(120, source.get_luma8_point(2, 2), "{format:?}");
+        }
+        assert_eq!(10, BayerFormat::Raw10.row_len(5));
+        assert_eq!(9, BayerFormat::Raw12.row_len(5));
+    }
+
+    #[test]
+    fn test_rows_columns_and_crop() {
+        let data: Vec<u8> = (0..8 * 4).map(|i| (i * 7) as u8).collect();
+        let source = BayerLuminanceSource::with_stride(
+            &data[..],
+            BayerPattern::Rggb,
+            BayerFormat::Raw8,
+            6,
+            4,
+            8,
+        )
+        .unwrap();
+        let matrix = source.get_matrix();
+        assert_eq!(24, matrix.len());
+        assert_eq!(&matrix[6..12], &source.get_row(1).unwrap()[..]);
+        assert!(source.get_row(4).is_none());
+        assert_eq!(
+            (0..4).map(|y| matrix[y * 6 + 2]).collect::<Vec<_>>(),
+            source.get_column(2)
+        );
+
+        // the crop keeps the colors and the neighbours of the full frame
+        let cropped = source.crop(1, 1, 4, 2).unwrap();
+        assert_eq!(&matrix[7..11], &cropped.get_row(0).unwrap()[..]);
+        assert_eq!(matrix[2 * 6 + 4], cropped.get_luma8_point(3, 1));
+        assert!(source.crop(3, 0, 4, 1).is_err());
+
+        let mut inverted = cropped;
+        inverted.invert();
+        assert_eq!(255 - matrix[7], inverted.get_row(0).unwrap()[0]);
+
+        assert!(BayerLuminanceSource::with_stride(
+            &data[..],
+            BayerPattern::Rggb,
+            BayerFormat::Raw10,
+            8,
+            4,
+            8
+        )
+        .is_err());
+        assert!(
+            BayerLuminanceSource::new(&data[..], BayerPattern::Rggb, BayerFormat::Raw8, 6, 6)
+                .is_err()
+        );
+        assert!(
+            BayerLuminanceSource::new(&data[..], BayerPattern::Rggb, BayerFormat::Raw8, 1, 4)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_decode_raw10() {
+        let matrix = MultiFormatWriter
+            .encode("Bayer frame", &BarcodeFormat::QR_CODE, 120, 120)
+            .expect("encode");
+        let (width, height) = (matrix.getWidth() as usize, matrix.getHeight() as usize);
+        // a green tinted print: the red and blue samples are darker
+        let data = frame(
+            BayerPattern::Bggr,
+            BayerFormat::Raw10,
+            width,
+            height,
+            |x, y, rgb| {
+                let level = if matrix.get(x as u32, y as u32) {
+                    20
+                } else {
+                    220
+                };
+                if rgb[1] == 1 {
+                    level
+                } else {
+                    level / 2
+                }
+            },
+        );
+        let data: Arc<[u8]> = data.into();
+
+        for demosaic in [BayerDemosaic::Bilinear, BayerDemosaic::Green] {
+            let source = BayerLuminanceSource::new(
+                data.clone(),
+                BayerPattern::Bggr,
+                BayerFormat::Raw10,
+                width,
+                height,
+            )
+            .expect("frame")
+            .with_demosaic(demosaic);
+            let result = QRCodeReader
+                .decode(&mut BinaryBitmap::new(HybridBinarizer::new(source)))
+                .expect("decode");
+            assert_eq!("Bayer frame", result.getText());
+        }
+    }
+}
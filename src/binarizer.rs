@@ -20,7 +20,7 @@ use std::borrow::Cow;
 
 use crate::{
     common::{BitArray, BitMatrix, LineOrientation, Result},
-    LuminanceSource,
+    BlockThresholds, LuminanceSource,
 };
 
 /**
@@ -88,4 +88,16 @@ pub trait Binarizer {
     fn get_width(&self) -> usize;
 
     fn get_height(&self) -> usize;
+
+    /// The threshold applied to the whole image, pixels darker than it are black. None for
+    /// binarizers that threshold each part of the image differently.
+    fn get_global_threshold(&self) -> Option<u8> {
+        None
+    }
+
+    /// The threshold of each block of the image, for binarizers that threshold blocks
+    /// separately and were asked to keep them. Set once the black matrix is calculated.
+    fn get_block_thresholds(&self) -> Option<&BlockThresholds> {
+        None
+    }
 }
@@ -238,3 +238,69 @@ impl<B: Binarizer> fmt::Display for BinaryBitmap<B> {
         write!(f, "{:?}", self.matrix.borrow())
     }
 }
+
+/// The bitmap the image conversions produce, thresholded as the `helpers` functions do
+#[cfg(feature = "image")]
+pub type ImageBitmap =
+    BinaryBitmap<crate::common::FixedThresholdBinarizer<crate::BufferedImageLuminanceSource>>;
+
+#[cfg(feature = "image")]
+impl From<image::DynamicImage> for ImageBitmap {
+    fn from(img: image::DynamicImage) -> Self {
+        BinaryBitmap::new(crate::common::FixedThresholdBinarizer::new(
+            crate::BufferedImageLuminanceSource::new(img),
+        ))
+    }
+}
+
+#[cfg(feature = "image")]
+impl From<&image::DynamicImage> for ImageBitmap {
+    fn from(img: &image::DynamicImage) -> Self {
+        Self::from(img.clone())
+    }
+}
+
+/**
+ * Reads an encoded image (PNG, JPEG, ...) with `helpers::load_image_from_memory`, so the
+ * image is turned upright as its EXIF orientation says.
+ *
+ * ```
+ * use rxing::{ImageBitmap, MultiFormatReader, Reader};
+ *
+ * let png = std::fs::read("test_resources/blackbox/qrcode-1/1.png").unwrap();
+ * let mut bitmap = ImageBitmap::try_from(png.as_slice()).unwrap();
+ * let result = MultiFormatReader::default().decode(&mut bitmap).unwrap();
+ * assert!(result.getText().starts_with("MEBKM:"));
+ * ```
+ */
+#[cfg(feature = "image")]
+impl TryFrom<&[u8]> for ImageBitmap {
+    type Error = crate::Exceptions;
+
+    fn try_from(bytes: &[u8]) -> Result<Self> {
+        let img = crate::helpers::load_image_from_memory(bytes)
+            .map_err(|e| crate::Exceptions::parse_with(format!("could not read image: {e}")))?;
+        Ok(Self::from(img))
+    }
+}
+
+#[cfg(all(test, feature = "image"))]
+mod tests {
+    use super::ImageBitmap;
+    use crate::{MultiFormatReader, Reader};
+
+    #[test]
+    fn test_image_conversions() {
+        let img = image::open("test_resources/blackbox/qrcode-2/1.png").unwrap();
+        let mut bitmap = ImageBitmap::from(&img);
+        assert_eq!(img.width() as usize, bitmap.get_width());
+        let decoded = MultiFormatReader::default().decode(&mut bitmap).unwrap();
+
+        let png = std::fs::read("test_resources/blackbox/qrcode-2/1.png").unwrap();
+        let mut bitmap = ImageBitmap::try_from(png.as_slice()).unwrap();
+        let result = MultiFormatReader::default().decode(&mut bitmap).unwrap();
+        assert_eq!(decoded.getText(), result.getText());
+
+        assert!(ImageBitmap::try_from(&b"not an image"[..]).is_err());
+    }
+}
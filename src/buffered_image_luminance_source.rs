@@ -19,7 +19,7 @@ use std::borrow::Cow;
 use image::{DynamicImage, GenericImageView, ImageBuffer, Luma, Pixel};
 use imageproc::geometric_transformations::rotate_about_center;
 
-use crate::common::Result;
+use crate::common::{simd, Result};
 use crate::LuminanceSource;
 
 // const MINUS_45_IN_RADIANS: f32 = -0.7853981633974483; // Math.toRadians(-45.0)
@@ -232,26 +232,10 @@ fn build_local_grey_image(source: DynamicImage) -> DynamicImage {
         _ => {
             let img = source.to_rgba8();
 
-            let mut raster: ImageBuffer<_, Vec<_>> =
+            let mut raster: ImageBuffer<Luma<u8>, Vec<_>> =
                 ImageBuffer::new(source.width(), source.height());
-
-            for (x, y, new_pixel) in raster.enumerate_pixels_mut() {
-                let pixel = img.get_pixel(x, y);
-                let [red, green, blue, alpha] = pixel.0;
-                if alpha == 0 {
-                    // white, so we know its luminance is 255
-                    *new_pixel = Luma([0xFF])
-                } else {
-                    // .299R + 0.587G + 0.114B (YUV/YIQ for PAL and NTSC),
-                    // (306*R) >> 10 is approximately equal to R*0.299, and so on.
-                    // 0x200 >> 10 is 0.5, it implements rounding.
-                    *new_pixel = Luma([((306 * (red as u64)
-                        + 601 * (green as u64)
-                        + 117 * (blue as u64)
-                        + 0x200)
-                        >> 10) as u8])
-                }
-            }
+            // .299R + 0.587G + 0.114B (YUV/YIQ for PAL and NTSC), with transparent pixels white
+            simd::rgba_to_luma(img.as_raw(), &mut raster);
             raster
         }
     };
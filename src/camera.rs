@@ -0,0 +1,163 @@
+//! Barcode scanning from a webcam, enabled by the `camera` feature.
+//!
+//! A [`CameraScanner`] opens a camera with [nokhwa](https://docs.rs/nokhwa), captures grayscale
+//! frames and decodes each of them with [`crate::helpers::detect_multiple_in_luma_with_hints`].
+//! A barcode is reported the first time it is seen, so holding a symbol in front of the camera
+//! yields one result rather than one per frame.
+//!
+//! ```no_run
+//! use rxing::camera::CameraScanner;
+//!
+//! for found in CameraScanner::open(0)?.take(1) {
+//!     let found = found?;
+//!     println!("{} at {:?}: {}", found.frame, found.timestamp, found.result);
+//! }
+//! # Ok::<(), rxing::Exceptions>(())
+//! ```
+
+use std::{
+    collections::{HashSet, VecDeque},
+    time::Instant,
+};
+
+use nokhwa::{
+    pixel_format::LumaFormat,
+    utils::{CameraIndex, RequestedFormat, RequestedFormatType},
+    Camera,
+};
+
+use crate::{
+    common::Result,
+    helpers::{self, FrameResult},
+    BarcodeFormat, DecodeHints, Exceptions,
+};
+
+/**
+ * Decodes the frames of a camera as they are captured. Iterating yields each barcode the first
+ * time it is decoded, and an error if a frame can't be captured; frames without a barcode are
+ * skipped, so the iterator blocks until a new barcode is in view.
+ *
+ * The stream is stopped when the scanner is dropped.
+ */
+pub struct CameraScanner {
+    camera: Camera,
+    hints: DecodeHints,
+    started: Instant,
+    frame: usize,
+    seen: HashSet<(BarcodeFormat, String)>,
+    pending: VecDeque<FrameResult>,
+}
+
+impl CameraScanner {
+    /**
+     * Opens the camera at `index`, in the order the platform lists them, and starts its stream
+     * at the highest frame rate it offers.
+     */
+    pub fn open(index: u32) -> Result<Self> {
+        Self::open_with_hints(index, DecodeHints::default())
+    }
+
+    pub fn open_with_hints(index: u32, hints: DecodeHints) -> Result<Self> {
+        let requested =
+            RequestedFormat::new::<LumaFormat>(RequestedFormatType::AbsoluteHighestFrameRate);
+        let mut camera = Camera::new(CameraIndex::Index(index), requested)
+            .map_err(|e| Exceptions::runtime_with(format!("couldn't open camera {index}: {e}")))?;
+        camera
+            .open_stream()
+            .map_err(|e| Exceptions::runtime_with(format!("couldn't start camera {index}: {e}")))?;
+
+        Ok(Self {
+            camera,
+            hints,
+            started: Instant::now(),
+            frame: 0,
+            seen: HashSet::new(),
+            pending: VecDeque::new(),
+        })
+    }
+
+    /**
+     * Captures and decodes one frame.
+     *
+     * # Returns
+     * The barcodes in the frame that were not seen in an earlier one, timestamped from when the
+     * stream was opened
+     */
+    pub fn scan_frame(&mut self) -> Result<Vec<FrameResult>> {
+        let buffer = self
+            .camera
+            .frame()
+            .map_err(|e| Exceptions::runtime_with(format!("couldn't capture frame: {e}")))?;
+        let timestamp = self.started.elapsed();
+        let image = buffer
+            .decode_image::<LumaFormat>()
+            .map_err(|e| Exceptions::runtime_with(format!("couldn't decode frame: {e}")))?;
+        let frame = self.frame;
+        self.frame += 1;
+
+        let (width, height) = image.dimensions();
+        // a frame without a barcode that decodes does not end the scan
+        let found = helpers::detect_multiple_in_luma_with_hints(
+            image.into_raw(),
+            width,
+            height,
+            &mut self.hints,
+        )
+        .unwrap_or_default();
+
+        Ok(found
+            .into_iter()
+            .filter(|result| {
+                self.seen.insert((
+                    result.getBarcodeFormat().clone(),
+                    result.getText().to_owned(),
+                ))
+            })
+            .map(|result| FrameResult {
+                frame,
+                timestamp,
+                result,
+            })
+            .collect())
+    }
+}
+
+impl Iterator for CameraScanner {
+    type Item = Result<FrameResult>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // a frame with several new barcodes is reported one barcode at a time
+        while self.pending.is_empty() {
+            match self.scan_frame() {
+                Ok(found) => self.pending.extend(found),
+                Err(e) => return Some(Err(e)),
+            }
+        }
+        self.pending.pop_front().map(Ok)
+    }
+}
+
+impl Drop for CameraScanner {
+    fn drop(&mut self) {
+        // the stream may already have failed, there is nothing left to do with the error
+        let _ = self.camera.stop_stream();
+    }
+}
+
+/**
+ * Scans the camera at `index` until `on_result` returns false, calling it with each barcode the
+ * first time it is decoded.
+ */
+pub fn scan_camera<F>(index: u32, hints: DecodeHints, mut on_result: F) -> Result<()>
+where
+    F: FnMut(&FrameResult) -> bool,
+{
+    let mut scanner = CameraScanner::open_with_hints(index, hints)?;
+    loop {
+        for found in scanner.scan_frame()? {
+            if !on_result(&found) {
+                return Ok(());
+            }
+        }
+    }
+}
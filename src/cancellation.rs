@@ -0,0 +1,169 @@
+/*
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    task::{Context, Poll, Waker},
+};
+
+use crate::{common::Result, Exceptions};
+
+/**
+ * Abandons a decode from another thread, given to the readers with
+ * `DecodeHints::with_cancellation`. The readers check the token between the rows they scan
+ * and between the formats they try, and fail with `Exceptions::CancelledException` once it
+ * is cancelled, so a camera app can give up on a slow frame when the next one arrives.
+ *
+ * Clones share their state: cancelling any clone cancels them all.
+ */
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    inner: Arc<Inner>,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    cancelled: AtomicBool,
+    wakers: Mutex<Vec<Waker>>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancels every decode the token was given to. Cancelling twice does nothing.
+    pub fn cancel(&self) {
+        if !self.inner.cancelled.swap(true, Ordering::AcqRel) {
+            let wakers = std::mem::take(&mut *self.wakers());
+            for waker in wakers {
+                waker.wake();
+            }
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::Acquire)
+    }
+
+    /// A future that completes once the token is cancelled
+    pub fn cancelled(&self) -> Cancelled<'_> {
+        Cancelled { token: self }
+    }
+
+    /// Fails with `Exceptions::CancelledException` if the token is cancelled
+    pub fn check(&self) -> Result<()> {
+        if self.is_cancelled() {
+            Err(Exceptions::CANCELLED)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn wakers(&self) -> std::sync::MutexGuard<'_, Vec<Waker>> {
+        self.inner
+            .wakers
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// The future returned by [`CancellationToken::cancelled`]
+#[derive(Debug)]
+pub struct Cancelled<'a> {
+    token: &'a CancellationToken,
+}
+
+impl Future for Cancelled<'_> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.token.is_cancelled() {
+            return Poll::Ready(());
+        }
+        let mut wakers = self.token.wakers();
+        // cancel() may have run between the check above and taking the lock
+        if self.token.is_cancelled() {
+            return Poll::Ready(());
+        }
+        if !wakers.iter().any(|waker| waker.will_wake(cx.waker())) {
+            wakers.push(cx.waker().clone());
+        }
+        Poll::Pending
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::HashSet,
+        future::Future,
+        pin::pin,
+        sync::Arc,
+        task::{Context, Poll, Wake},
+    };
+
+    use crate::{
+        BarcodeFormat, BinaryBitmap, CancellationToken, DecodeHints, Exceptions,
+        Luma8LuminanceSource, MultiFormatReader, Reader,
+    };
+
+    struct NoopWaker;
+
+    impl Wake for NoopWaker {
+        fn wake(self: Arc<Self>) {}
+    }
+
+    #[test]
+    fn test_cancelled_future() {
+        let token = CancellationToken::new();
+        let clone = token.clone();
+        let waker = Arc::new(NoopWaker).into();
+        let mut cx = Context::from_waker(&waker);
+        let mut cancelled = pin!(token.cancelled());
+        assert_eq!(Poll::Pending, cancelled.as_mut().poll(&mut cx));
+        assert!(token.check().is_ok());
+
+        clone.cancel();
+        assert!(token.is_cancelled());
+        assert_eq!(Poll::Ready(()), cancelled.as_mut().poll(&mut cx));
+        assert_eq!(Err(Exceptions::CANCELLED), token.check());
+    }
+
+    #[test]
+    fn test_cancelled_decode() {
+        let token = CancellationToken::new();
+        token.cancel();
+        for formats in [None, Some(HashSet::from([BarcodeFormat::QR_CODE]))] {
+            let hints = DecodeHints {
+                PossibleFormats: formats,
+                TryHarder: Some(true),
+                ..Default::default()
+            }
+            .with_cancellation(token.clone());
+            let mut image = BinaryBitmap::new(crate::common::HybridBinarizer::new(
+                Luma8LuminanceSource::new(vec![255; 200 * 200], 200, 200),
+            ));
+            assert_eq!(
+                Err(Exceptions::CANCELLED),
+                MultiFormatReader::default().decode_with_hints(&mut image, &hints)
+            );
+        }
+    }
+}
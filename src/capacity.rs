@@ -0,0 +1,463 @@
+/*
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::fmt;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    aztec::encoder::aztec_encoder,
+    common::Result,
+    datamatrix::encoder::{high_level_encoder, SymbolInfoLookup, PROD_SYMBOLS},
+    pdf417::{encoder::PDF417, DEFAULT_ERROR_CORRECTION_LEVEL},
+    qrcode::{
+        decoder::{ErrorCorrectionLevel, Mode, Version},
+        encoder::qrcode_encoder,
+    },
+    BarcodeFormat, EncodeHints, Exceptions,
+};
+
+/**
+ * The kind of data a capacity is computed for. The capacity of a symbol is the number
+ * of characters it can hold if the whole payload is of this kind.
+ */
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum EncodeMode {
+    /// Digits only
+    Numeric,
+    /// Digits, upper case letters and space
+    Alphanumeric,
+    /// Arbitrary bytes
+    Byte,
+    /// Shift_JIS double byte characters (QR Code only)
+    Kanji,
+}
+
+/// The size of a 2D symbol, in the terms of its symbology
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum SymbolSize {
+    /// QR Code version 1 - 40
+    QrVersion(u32),
+    /// Data Matrix symbol size in modules, including the finder pattern
+    DataMatrix { width: u32, height: u32 },
+    /// Aztec layer count, 1 - 4 for compact and 1 - 32 for full range symbols
+    Aztec { compact: bool, layers: u32 },
+    /// PDF417 data columns (1 - 30) and rows (3 - 90)
+    Pdf417 { columns: u32, rows: u32 },
+}
+
+impl SymbolSize {
+    /// The width and height of the symbol in modules (rows for PDF417), without quiet zone
+    pub fn dimensions(&self) -> (u32, u32) {
+        match *self {
+            SymbolSize::QrVersion(version) => (17 + 4 * version, 17 + 4 * version),
+            SymbolSize::DataMatrix { width, height } => (width, height),
+            SymbolSize::Aztec { compact, layers } => {
+                let base = if compact { 11 } else { 14 } + 4 * layers;
+                let size = if compact {
+                    base
+                } else {
+                    base + 1 + 2 * ((base / 2 - 1) / 15)
+                };
+                (size, size)
+            }
+            SymbolSize::Pdf417 { columns, rows } => (17 * columns + 69, rows),
+        }
+    }
+
+    /// The format this size applies to
+    pub fn format(&self) -> BarcodeFormat {
+        match self {
+            SymbolSize::QrVersion(_) => BarcodeFormat::QR_CODE,
+            SymbolSize::DataMatrix { .. } => BarcodeFormat::DATA_MATRIX,
+            SymbolSize::Aztec { .. } => BarcodeFormat::AZTEC,
+            SymbolSize::Pdf417 { .. } => BarcodeFormat::PDF_417,
+        }
+    }
+}
+
+impl fmt::Display for SymbolSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (width, height) = self.dimensions();
+        match self {
+            SymbolSize::QrVersion(version) => write!(f, "version {version} ({width}x{height})"),
+            SymbolSize::DataMatrix { .. } => write!(f, "{width}x{height}"),
+            SymbolSize::Aztec { compact, layers } => write!(
+                f,
+                "{} {layers} layers ({width}x{height})",
+                if *compact { "compact" } else { "full range" }
+            ),
+            SymbolSize::Pdf417 { columns, rows } => write!(f, "{columns} columns x {rows} rows"),
+        }
+    }
+}
+
+/**
+ * Computes how many characters of the given kind fit into a symbol of the given size.
+ *
+ * `ec_level` has the same meaning as the `ErrorCorrection` encode hint of the format:
+ * `L`, `M`, `Q` or `H` for QR Code (default `L`), a minimum percentage for Aztec
+ * (default 33) and a level 0 - 8 for PDF417 (default 2). Data Matrix has a fixed
+ * error correction, so `ec_level` is ignored.
+ *
+ * Capacities assume a payload that consists entirely of the given kind of data, encoded
+ * in the most compact mode of the symbology. Payloads mixing several kinds of data need
+ * mode switches, use [`min_symbol_for`] to size a concrete payload.
+ */
+pub fn capacity_for(
+    format: BarcodeFormat,
+    size: SymbolSize,
+    ec_level: Option<&str>,
+    mode: EncodeMode,
+) -> Result<usize> {
+    if size.format() != format {
+        return Err(Exceptions::illegal_argument_with(format!(
+            "symbol size {size} does not apply to {format}"
+        )));
+    }
+
+    match size {
+        SymbolSize::QrVersion(version) => qr_capacity(version, qr_ec_level(ec_level)?, mode),
+        SymbolSize::DataMatrix { width, height } => data_matrix_capacity(width, height, mode),
+        SymbolSize::Aztec { compact, layers } => {
+            aztec_capacity(compact, layers, aztec_ec_percent(ec_level)?, mode)
+        }
+        SymbolSize::Pdf417 { columns, rows } => {
+            pdf417_capacity(columns, rows, pdf417_ec_level(ec_level)?, mode)
+        }
+    }
+}
+
+/**
+ * Finds the smallest symbol of the given format which holds `data`, by running the
+ * encoder with its default settings. `ec_level` is interpreted as in [`capacity_for`].
+ */
+pub fn min_symbol_for(
+    format: BarcodeFormat,
+    data: &str,
+    ec_level: Option<&str>,
+) -> Result<SymbolSize> {
+    match format {
+        BarcodeFormat::QR_CODE => {
+            let code = qrcode_encoder::encode_with_hints(
+                data,
+                qr_ec_level(ec_level)?,
+                &EncodeHints::default(),
+            )?;
+            let version = code
+                .getVersion()
+                .ok_or(Exceptions::illegal_state_with("missing qr version"))?;
+            Ok(SymbolSize::QrVersion(version.getVersionNumber()))
+        }
+        BarcodeFormat::DATA_MATRIX => {
+            let encoded = high_level_encoder::encodeHighLevel(data)?;
+            let symbol = SymbolInfoLookup::new()
+                .lookup(encoded.chars().count() as u32)?
+                .ok_or(Exceptions::not_found_with(
+                    "no data matrix symbol fits the data",
+                ))?;
+            Ok(SymbolSize::DataMatrix {
+                width: symbol.getSymbolWidth()?,
+                height: symbol.getSymbolHeight()?,
+            })
+        }
+        BarcodeFormat::AZTEC => {
+            let code = aztec_encoder::encode(data, aztec_ec_percent(ec_level)?, 0)?;
+            Ok(SymbolSize::Aztec {
+                compact: code.isCompact(),
+                layers: code.getLayers(),
+            })
+        }
+        BarcodeFormat::PDF_417 => {
+            let mut encoder = PDF417::new();
+            encoder.generateBarcodeLogic(data, pdf417_ec_level(ec_level)?)?;
+            let matrix = encoder
+                .getBarcodeMatrix()
+                .as_ref()
+                .ok_or(Exceptions::illegal_state_with("missing pdf417 matrix"))?;
+            Ok(SymbolSize::Pdf417 {
+                columns: matrix.getColumnCount() as u32,
+                rows: matrix.getRowCount() as u32,
+            })
+        }
+        _ => Err(Exceptions::illegal_argument_with(format!(
+            "{format} does not have selectable symbol sizes"
+        ))),
+    }
+}
+
+fn qr_ec_level(ec_level: Option<&str>) -> Result<ErrorCorrectionLevel> {
+    ec_level.map_or(Ok(ErrorCorrectionLevel::L), str::parse)
+}
+
+fn aztec_ec_percent(ec_level: Option<&str>) -> Result<u32> {
+    ec_level.map_or(Ok(aztec_encoder::DEFAULT_EC_PERCENT), |ec| {
+        ec.parse()
+            .map_err(|e| Exceptions::parse_with(format!("could not parse {ec}: {e}")))
+    })
+}
+
+fn pdf417_ec_level(ec_level: Option<&str>) -> Result<u32> {
+    let level = ec_level.map_or(Ok(DEFAULT_ERROR_CORRECTION_LEVEL), |ec| {
+        ec.parse()
+            .map_err(|e| Exceptions::parse_with(format!("could not parse {ec}: {e}")))
+    })?;
+    if level > 8 {
+        return Err(Exceptions::illegal_argument_with(format!(
+            "pdf417 error correction level must be 0 - 8, got {level}"
+        )));
+    }
+    Ok(level)
+}
+
+fn qr_capacity(version: u32, ec_level: ErrorCorrectionLevel, mode: EncodeMode) -> Result<usize> {
+    let version = Version::getVersionForNumber(version)?;
+    let qr_mode = match mode {
+        EncodeMode::Numeric => Mode::NUMERIC,
+        EncodeMode::Alphanumeric => Mode::ALPHANUMERIC,
+        EncodeMode::Byte => Mode::BYTE,
+        EncodeMode::Kanji => Mode::KANJI,
+    };
+    let count_bits = qr_mode.getCharacterCountBits(version) as u32;
+    let data_bits = (version.getTotalCodewords()
+        - version.getECBlocksForLevel(ec_level).getTotalECCodewords())
+        * 8;
+    let bits = data_bits.saturating_sub(4 + count_bits) as usize;
+
+    let characters = match mode {
+        // 3 digits in 10 bits, a trailing 1 or 2 digits take 4 or 7 bits
+        EncodeMode::Numeric => {
+            bits / 10 * 3
+                + match bits % 10 {
+                    7.. => 2,
+                    4.. => 1,
+                    _ => 0,
+                }
+        }
+        // 2 characters in 11 bits, a trailing character takes 6 bits
+        EncodeMode::Alphanumeric => bits / 11 * 2 + usize::from(bits % 11 >= 6),
+        EncodeMode::Byte => bits / 8,
+        EncodeMode::Kanji => bits / 13,
+    };
+
+    Ok(characters.min((1 << count_bits) - 1))
+}
+
+fn data_matrix_capacity(width: u32, height: u32, mode: EncodeMode) -> Result<usize> {
+    let symbol = PROD_SYMBOLS
+        .iter()
+        .find(|s| {
+            s.getSymbolWidth().ok() == Some(width) && s.getSymbolHeight().ok() == Some(height)
+        })
+        .ok_or(Exceptions::illegal_argument_with(format!(
+            "{width}x{height} is not a data matrix symbol size"
+        )))?;
+    let codewords = symbol.getDataCapacity() as usize;
+
+    Ok(match mode {
+        // ASCII mode packs two digits into a codeword
+        EncodeMode::Numeric => codewords * 2,
+        // C40 latch, then 3 characters per 2 codewords, a last single codeword holds
+        // one ASCII character
+        EncodeMode::Alphanumeric => (codewords - 1) / 2 * 3 + (codewords - 1) % 2,
+        // Base 256 latch and a length field of "until the end of the symbol"
+        EncodeMode::Byte => codewords - 2,
+        EncodeMode::Kanji => {
+            return Err(Exceptions::illegal_argument_with(
+                "data matrix has no kanji mode",
+            ))
+        }
+    })
+}
+
+fn aztec_capacity(compact: bool, layers: u32, ec_percent: u32, mode: EncodeMode) -> Result<usize> {
+    let max_layers = if compact {
+        aztec_encoder::MAX_NB_BITS_COMPACT
+    } else {
+        aztec_encoder::MAX_NB_BITS
+    };
+    if !(1..=max_layers).contains(&layers) {
+        return Err(Exceptions::illegal_argument_with(format!(
+            "illegal value {layers} for aztec layers"
+        )));
+    }
+
+    let total_bits = aztec_encoder::total_bits_in_layer(layers, compact);
+    let word_size = aztec_encoder::WORD_SIZE[layers as usize];
+    let usable_bits = total_bits - total_bits % word_size;
+    // mirror the encoder: data + data * ec_percent / 100 + 11 must fit, bit stuffing aside
+    let mut data_bits =
+        (usable_bits.saturating_sub(11) as u64 * 100 / (100 + ec_percent as u64)) as usize;
+    if compact {
+        // compact symbols allow at most 64 data words
+        data_bits = data_bits.min(word_size as usize * 64);
+    }
+
+    Ok(match mode {
+        // latch to digit mode, then 4 bits per digit
+        EncodeMode::Numeric => data_bits.saturating_sub(5) / 4,
+        // upper case mode, 5 bits per character
+        EncodeMode::Alphanumeric => data_bits / 5,
+        // binary shift with a 5 bit length, or 5 + 11 bits for more than 31 bytes
+        EncodeMode::Byte => {
+            let short = data_bits.saturating_sub(10) / 8;
+            if short <= 31 {
+                short
+            } else {
+                data_bits.saturating_sub(21) / 8
+            }
+        }
+        EncodeMode::Kanji => {
+            return Err(Exceptions::illegal_argument_with("aztec has no kanji mode"))
+        }
+    })
+}
+
+fn pdf417_capacity(columns: u32, rows: u32, ec_level: u32, mode: EncodeMode) -> Result<usize> {
+    if !(1..=30).contains(&columns) || !(3..=90).contains(&rows) {
+        return Err(Exceptions::illegal_argument_with(format!(
+            "{columns} columns x {rows} rows is not a pdf417 symbol size"
+        )));
+    }
+
+    // at most 928 codewords, minus error correction and the symbol length descriptor
+    let codewords = (columns * rows).min(928) as usize;
+    let data =
+        codewords
+            .checked_sub((2 << ec_level) + 1)
+            .ok_or(Exceptions::illegal_argument_with(
+                "error correction does not fit the symbol",
+            ))?;
+
+    Ok(match mode {
+        // numeric latch, then 44 digits per 15 codewords, a partial group of n
+        // codewords holds 3n - 1 digits
+        EncodeMode::Numeric => {
+            let available = data.saturating_sub(1);
+            available / 15 * 44 + (3 * (available % 15)).saturating_sub(1)
+        }
+        // text compaction is the initial mode, 2 characters per codeword
+        EncodeMode::Alphanumeric => data * 2,
+        // byte latch, then 6 bytes per 5 codewords, trailing bytes take a codeword each
+        EncodeMode::Byte => {
+            let available = data.saturating_sub(1);
+            available / 5 * 6 + available % 5
+        }
+        EncodeMode::Kanji => {
+            return Err(Exceptions::illegal_argument_with(
+                "pdf417 has no kanji mode",
+            ))
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{capacity_for, min_symbol_for, BarcodeFormat, EncodeMode, SymbolSize};
+
+    #[test]
+    fn test_qr_capacity() {
+        let capacity = |version, ec, mode| {
+            capacity_for(
+                BarcodeFormat::QR_CODE,
+                SymbolSize::QrVersion(version),
+                Some(ec),
+                mode,
+            )
+            .unwrap()
+        };
+        // ISO/IEC 18004 table 7
+        assert_eq!(41, capacity(1, "L", EncodeMode::Numeric));
+        assert_eq!(25, capacity(1, "L", EncodeMode::Alphanumeric));
+        assert_eq!(17, capacity(1, "L", EncodeMode::Byte));
+        assert_eq!(10, capacity(1, "L", EncodeMode::Kanji));
+        assert_eq!(7, capacity(1, "H", EncodeMode::Byte));
+        assert_eq!(7089, capacity(40, "L", EncodeMode::Numeric));
+        assert_eq!(4296, capacity(40, "L", EncodeMode::Alphanumeric));
+        assert_eq!(2953, capacity(40, "L", EncodeMode::Byte));
+        assert_eq!(1273, capacity(40, "H", EncodeMode::Byte));
+
+        assert!(capacity_for(
+            BarcodeFormat::AZTEC,
+            SymbolSize::QrVersion(1),
+            None,
+            EncodeMode::Byte
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_other_capacities() {
+        let dm = SymbolSize::DataMatrix {
+            width: 144,
+            height: 144,
+        };
+        assert_eq!(
+            3116,
+            capacity_for(BarcodeFormat::DATA_MATRIX, dm, None, EncodeMode::Numeric).unwrap()
+        );
+        assert_eq!(
+            1556,
+            capacity_for(BarcodeFormat::DATA_MATRIX, dm, None, EncodeMode::Byte).unwrap()
+        );
+        assert!(capacity_for(
+            BarcodeFormat::DATA_MATRIX,
+            SymbolSize::DataMatrix {
+                width: 11,
+                height: 11
+            },
+            None,
+            EncodeMode::Byte
+        )
+        .is_err());
+
+        let pdf = SymbolSize::Pdf417 {
+            columns: 30,
+            rows: 90,
+        };
+        let pdf_capacity = |mode| capacity_for(BarcodeFormat::PDF_417, pdf, Some("0"), mode);
+        assert_eq!(2710, pdf_capacity(EncodeMode::Numeric).unwrap());
+        assert_eq!(1850, pdf_capacity(EncodeMode::Alphanumeric).unwrap());
+        assert_eq!(1108, pdf_capacity(EncodeMode::Byte).unwrap());
+    }
+
+    #[test]
+    fn test_capacity_matches_encoder() {
+        for format in [
+            BarcodeFormat::QR_CODE,
+            BarcodeFormat::DATA_MATRIX,
+            BarcodeFormat::AZTEC,
+            BarcodeFormat::PDF_417,
+        ] {
+            let size = min_symbol_for(format.clone(), "1234567890", None).unwrap();
+            assert_eq!(format, size.format());
+            let capacity = capacity_for(format.clone(), size, None, EncodeMode::Numeric).unwrap();
+            assert!(capacity >= 10, "{format} {size}: {capacity}");
+        }
+
+        assert_eq!(
+            SymbolSize::QrVersion(1),
+            min_symbol_for(BarcodeFormat::QR_CODE, "HELLO WORLD", Some("Q")).unwrap()
+        );
+        assert_eq!(
+            SymbolSize::QrVersion(2),
+            min_symbol_for(BarcodeFormat::QR_CODE, "HELLO WORLD", Some("H")).unwrap()
+        );
+        assert_eq!("version 2 (25x25)", SymbolSize::QrVersion(2).to_string());
+        assert!(min_symbol_for(BarcodeFormat::EAN_13, "5901234123457", None).is_err());
+    }
+}
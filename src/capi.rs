@@ -0,0 +1,946 @@
+//! A C ABI for decoding and encoding, enabled by the `capi` feature.
+//!
+//! The `rxing-capi` crate (in `crates/capi`) builds these functions into a shared and a
+//! static library, together with the C header `include/rxing.h`.
+//!
+//! Conventions:
+//! * Every function returns an [`RxingErrorCode`]; on error, [`rxing_last_error`] describes
+//!   what went wrong on the calling thread.
+//! * A panic inside the library doesn't unwind into the caller, it is reported as
+//!   [`RxingErrorCode::Internal`].
+//! * Formats are passed as [`RxingFormat`] values. [`RxingFormat::Any`] lets the decoder
+//!   try all formats.
+//! * [`rxing_decode`] and [`rxing_decode_multi`] take their options as an
+//!   [`RxingDecodeHints`], null for the defaults.
+//! * The fields of the structs can be read directly, or with the `rxing_result_*`
+//!   accessors from languages that import functions more easily than struct layouts.
+//! * Structs filled in by the library own their memory and must be released with the
+//!   matching `rxing_*_free` function.
+
+use std::{
+    cell::RefCell,
+    collections::HashSet,
+    ffi::{c_char, CStr, CString},
+    panic::{self, AssertUnwindSafe},
+    ptr,
+};
+
+use once_cell::sync::Lazy;
+
+use crate::{
+    common::BitMatrix, decode_no_panic::panic_message, helpers, BarcodeFormat, DecodeHints,
+    EncodeHints, Exceptions, MultiFormatWriter, RXingResult, Writer,
+};
+
+/// The barcode formats, in the order of `BarcodeFormat::ALL`. Functions take formats as
+/// `uint32_t` holding one of these values.
+#[repr(u32)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum RxingFormat {
+    Aztec = 0,
+    Codabar = 1,
+    Code39 = 2,
+    Code93 = 3,
+    Code128 = 4,
+    DataMatrix = 5,
+    Ean8 = 6,
+    Ean13 = 7,
+    Itf = 8,
+    Maxicode = 9,
+    Pdf417 = 10,
+    QrCode = 11,
+    MicroQrCode = 12,
+    RectangularMicroQrCode = 13,
+    Rss14 = 14,
+    RssExpanded = 15,
+    Telepen = 16,
+    UpcA = 17,
+    UpcE = 18,
+    UpcEanExtension = 19,
+    DxFilmEdge = 20,
+    Pharmacode = 21,
+    Msi = 22,
+    /// Decode any supported format
+    Any = 0xFFFF_FFFF,
+}
+
+/// The outcome of a C API call
+#[repr(C)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum RxingErrorCode {
+    Ok = 0,
+    /// No barcode was found in the image
+    NotFound = 1,
+    /// A barcode was found, but could not be decoded
+    Format = 2,
+    /// A barcode was found, but its check characters did not match
+    Checksum = 3,
+    /// An argument was out of range, or a string was not valid UTF-8
+    IllegalArgument = 4,
+    /// The contents could not be encoded in the requested format
+    Writer = 5,
+    /// The format is not supported for this operation
+    Unsupported = 6,
+    /// A required pointer was null
+    NullPointer = 7,
+    /// Any other failure
+    Internal = 8,
+}
+
+impl From<&Exceptions> for RxingErrorCode {
+    fn from(value: &Exceptions) -> Self {
+        match value {
+            Exceptions::NotFoundException(_) => Self::NotFound,
+            Exceptions::FormatException(_)
+            | Exceptions::ReedSolomonException(_)
+            | Exceptions::ReaderDecodeException() => Self::Format,
+            Exceptions::ChecksumException(_) => Self::Checksum,
+            Exceptions::IllegalArgumentException(_) | Exceptions::ParseException(_) => {
+                Self::IllegalArgument
+            }
+            Exceptions::WriterException(_) => Self::Writer,
+            Exceptions::UnsupportedOperationException(_) => Self::Unsupported,
+            _ => Self::Internal,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RxingPoint {
+    pub x: f32,
+    pub y: f32,
+}
+
+/// A decoded barcode. All pointers are owned by the library.
+#[repr(C)]
+#[derive(Debug)]
+pub struct RxingResult {
+    /// The decoded text, NUL terminated UTF-8
+    pub text: *mut c_char,
+    pub raw_bytes: *mut u8,
+    pub raw_bytes_len: usize,
+    /// A [`RxingFormat`] value
+    pub format: u32,
+    pub points: *mut RxingPoint,
+    pub points_len: usize,
+}
+
+/// A list of decoded barcodes
+#[repr(C)]
+#[derive(Debug)]
+pub struct RxingResults {
+    pub results: *mut RxingResult,
+    pub len: usize,
+}
+
+/// An 8 bit grayscale image, one byte per pixel, rows stored top to bottom
+#[repr(C)]
+#[derive(Debug)]
+pub struct RxingImage {
+    pub data: *mut u8,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Spend more time to find barcodes in difficult images
+pub const RXING_DECODE_FLAG_TRY_HARDER: u32 = 1 << 0;
+/// The image is a pure, unrotated barcode with little or no border, like a generated one
+pub const RXING_DECODE_FLAG_PURE_BARCODE: u32 = 1 << 1;
+/// Also look for barcodes with light bars or modules on a dark background
+pub const RXING_DECODE_FLAG_ALSO_INVERTED: u32 = 1 << 2;
+/// Decode Code 128 barcodes without an FNC1 as GS1
+pub const RXING_DECODE_FLAG_ASSUME_GS1: u32 = 1 << 3;
+/// Include the start and end characters in the text of Codabar barcodes
+pub const RXING_DECODE_FLAG_RETURN_CODABAR_START_END: u32 = 1 << 4;
+/// Treat the last character of Code 39 barcodes as a check digit
+pub const RXING_DECODE_FLAG_ASSUME_CODE39_CHECK_DIGIT: u32 = 1 << 5;
+/// Decode Telepen barcodes as numeric
+pub const RXING_DECODE_FLAG_TELEPEN_AS_NUMERIC: u32 = 1 << 6;
+
+const KNOWN_DECODE_FLAGS: u32 = (1 << 7) - 1;
+
+/// The options of [`rxing_decode`] and [`rxing_decode_multi`]. A zeroed struct decodes every
+/// format with the default options.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct RxingDecodeHints {
+    /// A bit for each format to look for, `1 << format` for an [`RxingFormat`] value, or 0
+    /// for all formats
+    pub formats: u64,
+    /// `RXING_DECODE_FLAG_*` values or'ed together
+    pub flags: u32,
+    /// The character set of text without an ECI, NUL terminated, or null to guess it
+    pub character_set: *const c_char,
+}
+
+impl Default for RxingDecodeHints {
+    fn default() -> Self {
+        Self {
+            formats: 0,
+            flags: 0,
+            character_set: ptr::null(),
+        }
+    }
+}
+
+const ANY_FORMAT: u32 = RxingFormat::Any as u32;
+
+static FORMAT_NAMES: Lazy<Vec<CString>> = Lazy::new(|| {
+    BarcodeFormat::ALL
+        .iter()
+        .map(|format| CString::new(format.to_string()).unwrap_or_default())
+        .collect()
+});
+
+thread_local! {
+    static LAST_ERROR: RefCell<CString> = RefCell::new(CString::default());
+}
+
+fn set_last_error(message: &str) {
+    let message = CString::new(message.replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|e| *e.borrow_mut() = message);
+}
+
+fn fail(error: &Exceptions) -> RxingErrorCode {
+    set_last_error(&error.to_string());
+    error.into()
+}
+
+fn fail_with(code: RxingErrorCode, message: &str) -> RxingErrorCode {
+    set_last_error(message);
+    code
+}
+
+// Runs the body of an entry point. Unwinding out of an `extern "C"` function aborts the host
+// process, so a panic is caught here and reported as an error instead.
+fn guard<F: FnOnce() -> RxingErrorCode>(call: F) -> RxingErrorCode {
+    panic::catch_unwind(AssertUnwindSafe(call)).unwrap_or_else(|payload| {
+        fail_with(
+            RxingErrorCode::Internal,
+            &format!("panicked: {}", panic_message(&*payload)),
+        )
+    })
+}
+
+fn format_from_code(code: u32) -> Option<BarcodeFormat> {
+    BarcodeFormat::ALL.get(code as usize).cloned()
+}
+
+fn format_to_code(format: BarcodeFormat) -> u32 {
+    BarcodeFormat::ALL
+        .iter()
+        .position(|f| *f == format)
+        .map_or(ANY_FORMAT, |i| i as u32)
+}
+
+/// The hints of the `rxing_*_luma` functions, which take a single format and a flag
+fn luma_hints(format: u32, try_harder: bool) -> Result<RxingDecodeHints, RxingErrorCode> {
+    let formats = if format == ANY_FORMAT {
+        0
+    } else if format_from_code(format).is_some() {
+        1 << format
+    } else {
+        return Err(fail_with(
+            RxingErrorCode::IllegalArgument,
+            &format!("unknown format code {format}"),
+        ));
+    };
+    Ok(RxingDecodeHints {
+        formats,
+        flags: if try_harder {
+            RXING_DECODE_FLAG_TRY_HARDER
+        } else {
+            0
+        },
+        ..Default::default()
+    })
+}
+
+/// # Safety
+/// `hints` must be null or point to an [`RxingDecodeHints`] whose `character_set` is null or
+/// a NUL terminated string.
+unsafe fn decode_hints(hints: *const RxingDecodeHints) -> Result<DecodeHints, RxingErrorCode> {
+    let Some(hints) = hints.as_ref() else {
+        return Ok(DecodeHints::default());
+    };
+    let unknown_formats = hints.formats & !((1 << BarcodeFormat::ALL.len()) - 1);
+    if unknown_formats != 0 {
+        return Err(fail_with(
+            RxingErrorCode::IllegalArgument,
+            &format!("unknown format bits {unknown_formats:#x}"),
+        ));
+    }
+    if hints.flags & !KNOWN_DECODE_FLAGS != 0 {
+        return Err(fail_with(
+            RxingErrorCode::IllegalArgument,
+            &format!(
+                "unknown decode flags {:#x}",
+                hints.flags & !KNOWN_DECODE_FLAGS
+            ),
+        ));
+    }
+
+    let flag = |flag: u32| (hints.flags & flag != 0).then_some(true);
+    let mut decode_hints = DecodeHints {
+        TryHarder: flag(RXING_DECODE_FLAG_TRY_HARDER),
+        PureBarcode: flag(RXING_DECODE_FLAG_PURE_BARCODE),
+        AlsoInverted: flag(RXING_DECODE_FLAG_ALSO_INVERTED),
+        AssumeGs1: flag(RXING_DECODE_FLAG_ASSUME_GS1),
+        ReturnCodabarStartEnd: flag(RXING_DECODE_FLAG_RETURN_CODABAR_START_END),
+        AssumeCode39CheckDigit: flag(RXING_DECODE_FLAG_ASSUME_CODE39_CHECK_DIGIT),
+        TelepenAsNumeric: flag(RXING_DECODE_FLAG_TELEPEN_AS_NUMERIC),
+        ..Default::default()
+    };
+    if hints.formats != 0 {
+        decode_hints.PossibleFormats = Some(
+            BarcodeFormat::ALL
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| hints.formats & (1 << i) != 0)
+                .map(|(_, format)| format.clone())
+                .collect::<HashSet<_>>(),
+        );
+    }
+    if !hints.character_set.is_null() {
+        let Ok(character_set) = CStr::from_ptr(hints.character_set).to_str() else {
+            return Err(fail_with(
+                RxingErrorCode::IllegalArgument,
+                "character_set is not valid UTF-8",
+            ));
+        };
+        decode_hints.CharacterSet = Some(character_set.to_owned());
+    }
+    Ok(decode_hints)
+}
+
+/// # Safety
+/// `luma` must be null or point to at least `width * height` readable bytes.
+unsafe fn luma_from_raw(
+    luma: *const u8,
+    width: u32,
+    height: u32,
+) -> Result<Vec<u8>, RxingErrorCode> {
+    if luma.is_null() {
+        return Err(fail_with(RxingErrorCode::NullPointer, "luma is null"));
+    }
+    let len = (width as usize)
+        .checked_mul(height as usize)
+        .filter(|len| *len > 0)
+        .ok_or_else(|| fail_with(RxingErrorCode::IllegalArgument, "invalid image size"))?;
+    Ok(std::slice::from_raw_parts(luma, len).to_vec())
+}
+
+fn boxed_slice<T>(values: Vec<T>) -> (*mut T, usize) {
+    let len = values.len();
+    if len == 0 {
+        return (ptr::null_mut(), 0);
+    }
+    (Box::into_raw(values.into_boxed_slice()) as *mut T, len)
+}
+
+/// # Safety
+/// `data` and `len` must come from [`boxed_slice`].
+unsafe fn free_boxed_slice<T>(data: *mut T, len: usize) {
+    if !data.is_null() {
+        drop(Box::from_raw(ptr::slice_from_raw_parts_mut(data, len)));
+    }
+}
+
+fn result_to_c(result: &RXingResult) -> RxingResult {
+    let text = CString::new(result.getText().replace('\0', " ")).unwrap_or_default();
+    let (raw_bytes, raw_bytes_len) = boxed_slice(result.getRawBytes().to_vec());
+    let (points, points_len) = boxed_slice(
+        result
+            .getPoints()
+            .iter()
+            .map(|p| RxingPoint { x: p.x, y: p.y })
+            .collect(),
+    );
+    RxingResult {
+        text: text.into_raw(),
+        raw_bytes,
+        raw_bytes_len,
+        format: format_to_code(result.getBarcodeFormat().clone()),
+        points,
+        points_len,
+    }
+}
+
+/// The NUL terminated version string of the library
+#[no_mangle]
+pub extern "C" fn rxing_version() -> *const c_char {
+    concat!(env!("CARGO_PKG_VERSION"), "\0").as_ptr() as *const c_char
+}
+
+/// Describes the last error on the calling thread. The string is owned by the library and
+/// valid until the next call into the library on the same thread.
+#[no_mangle]
+pub extern "C" fn rxing_last_error() -> *const c_char {
+    LAST_ERROR.with(|e| e.borrow().as_ptr())
+}
+
+/// Decodes one barcode from an 8 bit grayscale image.
+///
+/// # Safety
+/// `luma` must point to `width * height` readable bytes and `out` to writable memory for an
+/// [`RxingResult`]. On success `out` must be released with [`rxing_result_free`].
+#[no_mangle]
+pub unsafe extern "C" fn rxing_decode_luma(
+    luma: *const u8,
+    width: u32,
+    height: u32,
+    format: u32,
+    try_harder: bool,
+    out: *mut RxingResult,
+) -> RxingErrorCode {
+    match luma_hints(format, try_harder) {
+        Ok(hints) => rxing_decode(luma, width, height, &hints, out),
+        Err(code) => code,
+    }
+}
+
+/// Decodes all barcodes in an 8 bit grayscale image. Finding no barcode is reported as
+/// [`RxingErrorCode::NotFound`].
+///
+/// # Safety
+/// `luma` must point to `width * height` readable bytes and `out` to writable memory for an
+/// [`RxingResults`]. On success `out` must be released with [`rxing_results_free`].
+#[no_mangle]
+pub unsafe extern "C" fn rxing_decode_multiple_luma(
+    luma: *const u8,
+    width: u32,
+    height: u32,
+    format: u32,
+    try_harder: bool,
+    out: *mut RxingResults,
+) -> RxingErrorCode {
+    match luma_hints(format, try_harder) {
+        Ok(hints) => rxing_decode_multi(luma, width, height, &hints, out),
+        Err(code) => code,
+    }
+}
+
+/// Decodes one barcode from an 8 bit grayscale image with the options in `hints`, null for
+/// the defaults.
+///
+/// # Safety
+/// `luma` must point to `width * height` readable bytes, `hints` must be null or point to an
+/// [`RxingDecodeHints`] and `out` to writable memory for an [`RxingResult`]. On success `out`
+/// must be released with [`rxing_result_free`].
+#[no_mangle]
+pub unsafe extern "C" fn rxing_decode(
+    luma: *const u8,
+    width: u32,
+    height: u32,
+    hints: *const RxingDecodeHints,
+    out: *mut RxingResult,
+) -> RxingErrorCode {
+    guard(|| {
+        if out.is_null() {
+            return fail_with(RxingErrorCode::NullPointer, "out is null");
+        }
+        let luma = match luma_from_raw(luma, width, height) {
+            Ok(luma) => luma,
+            Err(code) => return code,
+        };
+        let mut hints = match decode_hints(hints) {
+            Ok(hints) => hints,
+            Err(code) => return code,
+        };
+
+        match helpers::detect_in_luma_with_hints(luma, width, height, None, &mut hints) {
+            Ok(result) => {
+                out.write(result_to_c(&result));
+                RxingErrorCode::Ok
+            }
+            Err(e) => fail(&e),
+        }
+    })
+}
+
+/// Decodes all barcodes in an 8 bit grayscale image with the options in `hints`, null for
+/// the defaults. Finding no barcode is reported as [`RxingErrorCode::NotFound`].
+///
+/// # Safety
+/// `luma` must point to `width * height` readable bytes, `hints` must be null or point to an
+/// [`RxingDecodeHints`] and `out` to writable memory for an [`RxingResults`]. On success
+/// `out` must be released with [`rxing_results_free`].
+#[no_mangle]
+pub unsafe extern "C" fn rxing_decode_multi(
+    luma: *const u8,
+    width: u32,
+    height: u32,
+    hints: *const RxingDecodeHints,
+    out: *mut RxingResults,
+) -> RxingErrorCode {
+    guard(|| {
+        if out.is_null() {
+            return fail_with(RxingErrorCode::NullPointer, "out is null");
+        }
+        let luma = match luma_from_raw(luma, width, height) {
+            Ok(luma) => luma,
+            Err(code) => return code,
+        };
+        let mut hints = match decode_hints(hints) {
+            Ok(hints) => hints,
+            Err(code) => return code,
+        };
+
+        match helpers::detect_multiple_in_luma_with_hints(luma, width, height, &mut hints) {
+            Ok(results) if results.is_empty() => fail(&Exceptions::NOT_FOUND),
+            Ok(results) => {
+                let (results, len) = boxed_slice(results.iter().map(result_to_c).collect());
+                out.write(RxingResults { results, len });
+                RxingErrorCode::Ok
+            }
+            Err(e) => fail(&e),
+        }
+    })
+}
+
+/// Encodes `contents` into a grayscale image, black modules are 0 and white ones 255.
+/// `width` and `height` are the preferred size, the image may be larger.
+///
+/// # Safety
+/// `contents` must be a NUL terminated string and `out` must point to writable memory for an
+/// [`RxingImage`]. On success `out` must be released with [`rxing_image_free`].
+#[no_mangle]
+pub unsafe extern "C" fn rxing_encode(
+    contents: *const c_char,
+    format: u32,
+    width: i32,
+    height: i32,
+    out: *mut RxingImage,
+) -> RxingErrorCode {
+    guard(|| {
+        if contents.is_null() || out.is_null() {
+            return fail_with(RxingErrorCode::NullPointer, "contents or out is null");
+        }
+        let Ok(contents) = CStr::from_ptr(contents).to_str() else {
+            return fail_with(
+                RxingErrorCode::IllegalArgument,
+                "contents is not valid UTF-8",
+            );
+        };
+        let Some(format) = format_from_code(format) else {
+            return fail_with(
+                RxingErrorCode::IllegalArgument,
+                &format!("unknown format code {format}"),
+            );
+        };
+
+        match MultiFormatWriter.encode_with_hints(
+            contents,
+            &format,
+            width,
+            height,
+            &EncodeHints::default(),
+        ) {
+            Ok(matrix) => {
+                out.write(image_to_c(&matrix));
+                RxingErrorCode::Ok
+            }
+            Err(e) => fail(&e),
+        }
+    })
+}
+
+fn image_to_c(matrix: &BitMatrix) -> RxingImage {
+    let (data, _) = boxed_slice(matrix.to_luma8());
+    RxingImage {
+        data,
+        width: matrix.getWidth(),
+        height: matrix.getHeight(),
+    }
+}
+
+/// The result at `index` of `results`, or null if `index` is out of range
+///
+/// # Safety
+/// `results` must be null or filled in by [`rxing_decode_multi`] and not freed.
+#[no_mangle]
+pub unsafe extern "C" fn rxing_results_get(
+    results: *const RxingResults,
+    index: usize,
+) -> *const RxingResult {
+    match results.as_ref() {
+        Some(results) if index < results.len => results.results.add(index),
+        _ => ptr::null(),
+    }
+}
+
+/// The NUL terminated text of `result`, or null if `result` is null
+///
+/// # Safety
+/// `result` must be null or filled in by [`rxing_decode`] and not freed.
+#[no_mangle]
+pub unsafe extern "C" fn rxing_result_text(result: *const RxingResult) -> *const c_char {
+    result.as_ref().map_or(ptr::null(), |result| result.text)
+}
+
+/// The raw bytes of `result`, their number stored in `len`. Null if there are none.
+///
+/// # Safety
+/// `result` must be null or filled in by [`rxing_decode`] and not freed, and `len` must be
+/// null or point to writable memory for a `size_t`.
+#[no_mangle]
+pub unsafe extern "C" fn rxing_result_raw_bytes(
+    result: *const RxingResult,
+    len: *mut usize,
+) -> *const u8 {
+    let (bytes, bytes_len) = result.as_ref().map_or((ptr::null_mut(), 0), |result| {
+        (result.raw_bytes, result.raw_bytes_len)
+    });
+    if let Some(len) = len.as_mut() {
+        *len = bytes_len;
+    }
+    bytes
+}
+
+/// The [`RxingFormat`] value of `result`, or [`RxingFormat::Any`] if `result` is null
+///
+/// # Safety
+/// `result` must be null or filled in by [`rxing_decode`] and not freed.
+#[no_mangle]
+pub unsafe extern "C" fn rxing_result_format(result: *const RxingResult) -> u32 {
+    result.as_ref().map_or(ANY_FORMAT, |result| result.format)
+}
+
+/// The number of points of `result`, 0 if `result` is null
+///
+/// # Safety
+/// `result` must be null or filled in by [`rxing_decode`] and not freed.
+#[no_mangle]
+pub unsafe extern "C" fn rxing_result_points_len(result: *const RxingResult) -> usize {
+    result.as_ref().map_or(0, |result| result.points_len)
+}
+
+/// Stores the point at `index` of `result` in `out`, false if `index` is out of range
+///
+/// # Safety
+/// `result` must be null or filled in by [`rxing_decode`] and not freed, and `out` must be
+/// null or point to writable memory for an [`RxingPoint`].
+#[no_mangle]
+pub unsafe extern "C" fn rxing_result_point(
+    result: *const RxingResult,
+    index: usize,
+    out: *mut RxingPoint,
+) -> bool {
+    match (result.as_ref(), out.as_mut()) {
+        (Some(result), Some(out)) if index < result.points_len => {
+            *out = *result.points.add(index);
+            true
+        }
+        _ => false,
+    }
+}
+
+/// The NUL terminated name of an [`RxingFormat`] value, such as `"qrcode"`, or null for an
+/// unknown value. The string is owned by the library.
+#[no_mangle]
+pub extern "C" fn rxing_format_name(format: u32) -> *const c_char {
+    FORMAT_NAMES
+        .get(format as usize)
+        .map_or(ptr::null(), |name| name.as_ptr())
+}
+
+/// # Safety
+/// `result` must be null or filled in by [`rxing_decode`], and not freed before.
+#[no_mangle]
+pub unsafe extern "C" fn rxing_result_free(result: *mut RxingResult) {
+    let Some(result) = result.as_mut() else {
+        return;
+    };
+    if !result.text.is_null() {
+        drop(CString::from_raw(result.text));
+    }
+    free_boxed_slice(result.raw_bytes, result.raw_bytes_len);
+    free_boxed_slice(result.points, result.points_len);
+    *result = RxingResult {
+        text: ptr::null_mut(),
+        raw_bytes: ptr::null_mut(),
+        raw_bytes_len: 0,
+        format: ANY_FORMAT,
+        points: ptr::null_mut(),
+        points_len: 0,
+    };
+}
+
+/// # Safety
+/// `results` must be null or filled in by [`rxing_decode_multi`], and not freed before.
+#[no_mangle]
+pub unsafe extern "C" fn rxing_results_free(results: *mut RxingResults) {
+    let Some(results) = results.as_mut() else {
+        return;
+    };
+    if !results.results.is_null() {
+        for i in 0..results.len {
+            rxing_result_free(results.results.add(i));
+        }
+        free_boxed_slice(results.results, results.len);
+    }
+    results.results = ptr::null_mut();
+    results.len = 0;
+}
+
+/// # Safety
+/// `image` must be null or filled in by [`rxing_encode`], and not freed before.
+#[no_mangle]
+pub unsafe extern "C" fn rxing_image_free(image: *mut RxingImage) {
+    let Some(image) = image.as_mut() else {
+        return;
+    };
+    free_boxed_slice(image.data, image.width as usize * image.height as usize);
+    image.data = ptr::null_mut();
+    image.width = 0;
+    image.height = 0;
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        ffi::{CStr, CString},
+        mem::MaybeUninit,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_encode_decode() {
+        unsafe {
+            let contents = CString::new("capi").unwrap();
+            let mut image = MaybeUninit::<RxingImage>::uninit();
+            assert_eq!(
+                RxingErrorCode::Ok,
+                rxing_encode(
+                    contents.as_ptr(),
+                    RxingFormat::QrCode as u32,
+                    100,
+                    100,
+                    image.as_mut_ptr()
+                )
+            );
+            let mut image = image.assume_init();
+            assert_eq!(100, image.width);
+
+            let mut result = MaybeUninit::<RxingResult>::uninit();
+            assert_eq!(
+                RxingErrorCode::Ok,
+                rxing_decode_luma(
+                    image.data,
+                    image.width,
+                    image.height,
+                    ANY_FORMAT,
+                    true,
+                    result.as_mut_ptr()
+                )
+            );
+            let mut result = result.assume_init();
+            assert_eq!("capi", CStr::from_ptr(result.text).to_str().unwrap());
+            assert_eq!(RxingFormat::QrCode as u32, result.format);
+            assert!(result.points_len >= 3);
+            rxing_result_free(&mut result);
+            assert!(result.text.is_null());
+
+            let mut results = MaybeUninit::<RxingResults>::uninit();
+            assert_eq!(
+                RxingErrorCode::Ok,
+                rxing_decode_multiple_luma(
+                    image.data,
+                    image.width,
+                    image.height,
+                    RxingFormat::QrCode as u32,
+                    true,
+                    results.as_mut_ptr()
+                )
+            );
+            let mut results = results.assume_init();
+            assert_eq!(1, results.len);
+            rxing_results_free(&mut results);
+            rxing_image_free(&mut image);
+        }
+    }
+
+    #[test]
+    fn test_decode_hints() {
+        unsafe {
+            let contents = CString::new("capi hints").unwrap();
+            let mut image = MaybeUninit::<RxingImage>::uninit();
+            assert_eq!(
+                RxingErrorCode::Ok,
+                rxing_encode(
+                    contents.as_ptr(),
+                    RxingFormat::DataMatrix as u32,
+                    100,
+                    100,
+                    image.as_mut_ptr()
+                )
+            );
+            let mut image = image.assume_init();
+
+            let character_set = CString::new("UTF-8").unwrap();
+            let hints = RxingDecodeHints {
+                formats: 1 << RxingFormat::DataMatrix as u32 | 1 << RxingFormat::QrCode as u32,
+                flags: RXING_DECODE_FLAG_TRY_HARDER | RXING_DECODE_FLAG_ALSO_INVERTED,
+                character_set: character_set.as_ptr(),
+            };
+            let converted = decode_hints(&hints).unwrap();
+            assert_eq!(Some(true), converted.AlsoInverted);
+            assert_eq!(None, converted.PureBarcode);
+            assert_eq!(Some("UTF-8".to_owned()), converted.CharacterSet);
+            assert_eq!(
+                Some(HashSet::from([
+                    BarcodeFormat::DATA_MATRIX,
+                    BarcodeFormat::QR_CODE
+                ])),
+                converted.PossibleFormats
+            );
+
+            let mut result = MaybeUninit::<RxingResult>::uninit();
+            assert_eq!(
+                RxingErrorCode::Ok,
+                rxing_decode(
+                    image.data,
+                    image.width,
+                    image.height,
+                    &hints,
+                    result.as_mut_ptr()
+                )
+            );
+            let mut result = result.assume_init();
+            assert_eq!(
+                "capi hints",
+                CStr::from_ptr(rxing_result_text(&result)).to_str().unwrap()
+            );
+            assert_eq!(RxingFormat::DataMatrix as u32, rxing_result_format(&result));
+            assert_eq!(
+                "datamatrix",
+                CStr::from_ptr(rxing_format_name(rxing_result_format(&result)))
+                    .to_str()
+                    .unwrap()
+            );
+            let mut len = 0;
+            assert!(!rxing_result_raw_bytes(&result, &mut len).is_null());
+            assert_eq!(result.raw_bytes_len, len);
+            let mut point = RxingPoint { x: -1.0, y: -1.0 };
+            assert!(rxing_result_point(&result, 0, &mut point));
+            assert!(point.x >= 0.0 && point.y >= 0.0);
+            assert!(!rxing_result_point(
+                &result,
+                rxing_result_points_len(&result),
+                &mut point
+            ));
+            rxing_result_free(&mut result);
+
+            let mut results = MaybeUninit::<RxingResults>::uninit();
+            assert_eq!(
+                RxingErrorCode::Ok,
+                rxing_decode_multi(
+                    image.data,
+                    image.width,
+                    image.height,
+                    ptr::null(),
+                    results.as_mut_ptr()
+                )
+            );
+            let mut results = results.assume_init();
+            assert_eq!(1, results.len);
+            assert!(!rxing_results_get(&results, 0).is_null());
+            assert!(rxing_results_get(&results, 1).is_null());
+            rxing_results_free(&mut results);
+
+            // only looking for QR codes
+            let hints = RxingDecodeHints {
+                formats: 1 << RxingFormat::QrCode as u32,
+                ..hints
+            };
+            let mut result = MaybeUninit::<RxingResult>::uninit();
+            assert_eq!(
+                RxingErrorCode::NotFound,
+                rxing_decode(
+                    image.data,
+                    image.width,
+                    image.height,
+                    &hints,
+                    result.as_mut_ptr()
+                )
+            );
+            rxing_image_free(&mut image);
+        }
+    }
+
+    #[test]
+    fn test_errors() {
+        unsafe {
+            let blank = vec![255_u8; 50 * 50];
+            let mut result = MaybeUninit::<RxingResult>::uninit();
+            assert_eq!(
+                RxingErrorCode::NotFound,
+                rxing_decode_luma(
+                    blank.as_ptr(),
+                    50,
+                    50,
+                    ANY_FORMAT,
+                    false,
+                    result.as_mut_ptr()
+                )
+            );
+            assert_eq!(
+                RxingErrorCode::IllegalArgument,
+                rxing_decode_luma(blank.as_ptr(), 50, 50, 99, false, result.as_mut_ptr())
+            );
+            assert!(CStr::from_ptr(rxing_last_error())
+                .to_str()
+                .unwrap()
+                .contains("99"));
+            assert_eq!(
+                RxingErrorCode::NullPointer,
+                rxing_decode_luma(ptr::null(), 50, 50, ANY_FORMAT, false, result.as_mut_ptr())
+            );
+
+            let contents = CString::new("not digits").unwrap();
+            let mut image = MaybeUninit::<RxingImage>::uninit();
+            assert_ne!(
+                RxingErrorCode::Ok,
+                rxing_encode(
+                    contents.as_ptr(),
+                    RxingFormat::Ean13 as u32,
+                    0,
+                    0,
+                    image.as_mut_ptr()
+                )
+            );
+            assert!(!CStr::from_ptr(rxing_version()).to_bytes().is_empty());
+
+            for hints in [
+                RxingDecodeHints {
+                    formats: 1 << 40,
+                    ..Default::default()
+                },
+                RxingDecodeHints {
+                    flags: 1 << 20,
+                    ..Default::default()
+                },
+            ] {
+                assert_eq!(
+                    RxingErrorCode::IllegalArgument,
+                    rxing_decode(blank.as_ptr(), 50, 50, &hints, result.as_mut_ptr())
+                );
+            }
+            assert!(rxing_format_name(ANY_FORMAT).is_null());
+            assert!(rxing_result_text(ptr::null()).is_null());
+        }
+    }
+
+    #[test]
+    fn test_panic_guard() {
+        assert_eq!(
+            RxingErrorCode::Internal,
+            guard(|| panic!("reader invariant"))
+        );
+        let message = unsafe { CStr::from_ptr(rxing_last_error()) };
+        assert_eq!("panicked: reader invariant", message.to_str().unwrap());
+        assert_eq!(RxingErrorCode::Ok, guard(|| RxingErrorCode::Ok));
+    }
+}
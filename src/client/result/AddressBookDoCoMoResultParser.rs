@@ -25,10 +25,10 @@ use super::{AddressBookParsedRXingResult, ParsedClientResult, ResultParser};
 /**
  * Implements the "MECARD" address book entry format.
  *
- * Supported keys: N, SOUND, TEL, EMAIL, NOTE, ADR, BDAY, URL, plus ORG
- * Unsupported keys: TEL-AV, NICKNAME
+ * Supported keys: N, SOUND, TEL, EMAIL, NOTE, ADR, BDAY, URL, NICKNAME, plus ORG
+ * Unsupported keys: TEL-AV
  *
- * Except for TEL, multiple values for keys are also not supported;
+ * Except for TEL, EMAIL, ADR and URL, multiple values for keys are also not supported;
  * the first one found takes precedence.
  *
  * Our understanding of the MECARD format is based on this document:
@@ -48,6 +48,9 @@ pub fn parse(result: &RXingResult) -> Option<ParsedClientResult> {
     let name = parseName(&rawName[0]);
     let pronunciation = ResultParser::match_single_docomo_prefixed_field("SOUND:", &rawText, true)
         .unwrap_or_default();
+    let nicknames = ResultParser::match_single_docomo_prefixed_field("NICKNAME:", &rawText, true)
+        .map(|nicknames| nicknames.split(',').map(|x| x.trim().to_owned()).collect())
+        .unwrap_or_default();
     let phoneNumbers =
         ResultParser::match_docomo_prefixed_field("TEL:", &rawText).unwrap_or_default();
     let emails = ResultParser::match_docomo_prefixed_field("EMAIL:", &rawText).unwrap_or_default();
@@ -69,7 +72,7 @@ pub fn parse(result: &RXingResult) -> Option<ParsedClientResult> {
 
     if let Ok(new_adb) = AddressBookParsedRXingResult::with_details(
         ResultParser::maybeWrap(Some(name)).unwrap_or_default(),
-        Vec::new(),
+        nicknames,
         pronunciation,
         phoneNumbers,
         Vec::new(),
@@ -45,6 +45,7 @@ pub struct AddressBookParsedRXingResult {
     title: String,
     urls: Vec<String>,
     geo: Vec<String>,
+    photo: String,
 }
 impl ParsedRXingResult for AddressBookParsedRXingResult {
     fn getType(&self) -> super::ParsedRXingResultType {
@@ -152,9 +153,16 @@ impl AddressBookParsedRXingResult {
             title,
             urls,
             geo,
+            photo: String::default(),
         })
     }
 
+    /// Sets the URI of a photo of the contact, such as an `http` or `data` URI
+    pub fn with_photo(mut self, photo: String) -> Self {
+        self.photo = photo;
+        self
+    }
+
     pub fn getNames(&self) -> &Vec<String> {
         &self.names
     }
@@ -242,4 +250,12 @@ impl AddressBookParsedRXingResult {
     pub fn getGeo(&self) -> &Vec<String> {
         &self.geo
     }
+
+    /**
+     * @return URI of a photo of the contact; inline vCard 2.1 and 3.0 photos are returned
+     *  as a `data` URI
+     */
+    pub fn getPhoto(&self) -> &str {
+        &self.photo
+    }
 }
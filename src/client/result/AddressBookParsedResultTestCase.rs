@@ -28,7 +28,10 @@
  */
 // public final class AddressBookParsedRXingResultTestCase extends Assert {
 use crate::{
-    client::result::{ParsedClientResult, ParsedRXingResult, ParsedRXingResultType, ResultParser},
+    client::result::{
+        AddressBookParsedRXingResult, ContactBuilder, ContactFormat, ParsedClientResult,
+        ParsedRXingResult, ParsedRXingResultType, ResultParser,
+    },
     BarcodeFormat, RXingResult,
 };
 
@@ -334,6 +337,157 @@ fn testVCardTypes() {
     );
 }
 
+#[test]
+fn testVCard4() {
+    let contents = "BEGIN:VCARD\r\nVERSION:4.0\r\nFN:Sean Owen\r\nN:Owen;Sean;;;\r\n\
+        TEL;VALUE=uri;TYPE=\"work,voice\";PREF=1:tel:+1-555-555-1212\r\n\
+        item1.TEL;TYPE=cell;TYPE=text:+1-555-555-3434\r\n\
+        EMAIL;TYPE=work:srowen@example.org\r\nEMAIL;TYPE=home:sean@example.com\r\n\
+        ADR;TYPE=home:;;123 Main St;Anytown;;10001;\r\n\
+        PHOTO:http://www.example.com/pub/photos/\r\n sowen.gif\r\n\
+        GEO:geo:37.386013,-122.082932;u=10\r\nNOTE:C:\\\\new\\, folder\r\nEND:VCARD";
+    doTest(
+        contents,
+        "",
+        &["Sean Owen"],
+        "",
+        &["123 Main St\nAnytown\n10001"],
+        &["srowen@example.org", "sean@example.com"],
+        &["+1-555-555-1212", "+1-555-555-3434"],
+        &["work,voice", "cell,text"],
+        "",
+        &Vec::new(),
+        "",
+        "C:\\new, folder",
+    );
+    let contact = parseContact(contents);
+    assert_eq!(&["work", "home"], contact.getEmailTypes().as_slice());
+    assert_eq!(&["home"], contact.getAddressTypes().as_slice());
+    assert_eq!(
+        "http://www.example.com/pub/photos/sowen.gif",
+        contact.getPhoto()
+    );
+    assert_eq!(&["37.386013", "-122.082932"], contact.getGeo().as_slice());
+}
+
+#[test]
+fn testVCardInlinePhoto() {
+    let contact = parseContact(
+        "BEGIN:VCARD\r\nVERSION:3.0\r\nN:Owen;Sean\r\nPHOTO;ENCODING=b;TYPE=JPEG:MIICajCCAdOgAwIBAgICBE\r\n UFMA0GCSqGSIb3DQEBBAUAMHcxCzAJBgNVBAYTAlVTMSww\r\nEND:VCARD",
+    );
+    assert_eq!(
+        "data:image/jpeg;base64,MIICajCCAdOgAwIBAgICBEUFMA0GCSqGSIb3DQEBBAUAMHcxCzAJBgNVBAYTAlVTMSww",
+        contact.getPhoto()
+    );
+    let contact = parseContact(
+        "BEGIN:VCARD\r\nVERSION:3.0\r\nN:Owen;Sean\r\nPHOTO;VALUE=uri:https://example.com/sowen.png\r\nEND:VCARD",
+    );
+    assert_eq!("https://example.com/sowen.png", contact.getPhoto());
+}
+
+#[test]
+fn testMeCardNickname() {
+    let contact = parseContact("MECARD:N:Owen,Sean;NICKNAME:srowen,Sean O;;");
+    assert_eq!(&["Sean Owen"], contact.getNames().as_slice());
+    assert_eq!(&["srowen", "Sean O"], contact.getNicknames().as_slice());
+}
+
+#[test]
+fn testContactBuilderMeCard() {
+    let builder = ContactBuilder::new()
+        .with_name("Sean Owen")
+        .with_pronunciation("shawn")
+        .with_nickname("srowen")
+        .with_phone("+12125551212", "cell")
+        .with_phone("+12125553434", "")
+        .with_email("srowen@example.org", "work")
+        .with_address("123 Main St; Apt 4\nAnytown", "")
+        .with_birthday("1978-09-17")
+        .with_url("https://google.com/")
+        .with_org("Google")
+        .with_note("foo\\bar: baz, qux");
+    let payload = builder.build(ContactFormat::MeCard).unwrap();
+    assert_eq!(
+        "MECARD:N:Sean Owen;SOUND:shawn;NICKNAME:srowen;TEL:+12125551212;TEL:+12125553434;\
+         EMAIL:srowen@example.org;ADR:123 Main St\\; Apt 4\nAnytown;BDAY:19780917;\
+         URL:https\\://google.com/;ORG:Google;NOTE:foo\\\\bar\\: baz\\, qux;;",
+        payload
+    );
+
+    let contact = parseContact(&payload);
+    assert_eq!(&["Sean Owen"], contact.getNames().as_slice());
+    assert_eq!("shawn", contact.getPronunciation());
+    assert_eq!(&["srowen"], contact.getNicknames().as_slice());
+    assert_eq!(
+        &["+12125551212", "+12125553434"],
+        contact.getPhoneNumbers().as_slice()
+    );
+    assert_eq!(&["srowen@example.org"], contact.getEmails().as_slice());
+    assert_eq!(
+        &["123 Main St; Apt 4\nAnytown"],
+        contact.getAddresses().as_slice()
+    );
+    assert_eq!("19780917", contact.getBirthday());
+    assert_eq!(&["https://google.com/"], contact.getURLs().as_slice());
+    assert_eq!("Google", contact.getOrg());
+    assert_eq!("foo\\bar: baz, qux", contact.getNote());
+}
+
+#[test]
+fn testContactBuilderVCardRoundTrip() {
+    let builder = ContactBuilder::new()
+        .with_name("Sean Owen")
+        .with_nickname("srowen")
+        .with_nickname("Sean O")
+        .with_phone("+1-555-555-1212", "work,voice")
+        .with_phone("+1-555-555-3434", "")
+        .with_email("srowen@example.org", "work")
+        .with_address("123 Main St; Apt 4\nAnytown", "home")
+        .with_instant_messenger("xmpp:srowen@example.org")
+        .with_note("line 1\nline 2\\n, done;")
+        .with_org("Google")
+        .with_birthday("1978-09-17")
+        .with_title("Engineer")
+        .with_url("https://google.com/")
+        .with_geo("37.386013", "-122.082932")
+        .with_photo("data:image/png;base64,iVBORw0KGgo=");
+    let payload = builder.build(ContactFormat::VCard).unwrap();
+    assert!(payload.starts_with("BEGIN:VCARD\r\nVERSION:4.0\r\nFN:Sean Owen\r\n"));
+    assert!(payload.contains("\r\nTEL;TYPE=work,voice:+1-555-555-1212\r\n"));
+    assert!(payload.contains("\r\nADR;TYPE=home:;;123 Main St\\; Apt 4\\nAnytown;;;;\r\n"));
+
+    let contact = parseContact(&payload);
+    assert_eq!(builder, ContactBuilder::from(&contact));
+    assert_eq!(
+        payload,
+        ContactBuilder::from(&contact)
+            .build(ContactFormat::VCard)
+            .unwrap()
+    );
+}
+
+#[test]
+fn testContactBuilderErrors() {
+    assert!(ContactBuilder::new()
+        .with_phone("+12125551212", "")
+        .build(ContactFormat::VCard)
+        .is_err());
+    assert!(ContactBuilder::new()
+        .with_name("Sean Owen")
+        .with_birthday("September 17")
+        .build(ContactFormat::MeCard)
+        .is_err());
+}
+
+fn parseContact(contents: &str) -> AddressBookParsedRXingResult {
+    let fakeRXingResult =
+        RXingResult::new(contents, Vec::new(), Vec::new(), BarcodeFormat::QR_CODE);
+    match ResultParser::parseRXingResult(&fakeRXingResult) {
+        ParsedClientResult::AddressBookResult(contact) => contact,
+        _ => panic!("Expected address book result"),
+    }
+}
+
 #[allow(clippy::too_many_arguments)]
 fn doTest(
     contents: &str,
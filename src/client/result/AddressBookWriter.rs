@@ -0,0 +1,353 @@
+/*
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::{
+    common::{BitMatrix, Result},
+    qrcode::QRCodeWriter,
+    BarcodeFormat, EncodeHints, Exceptions, Writer,
+};
+
+use super::AddressBookParsedRXingResult;
+
+static BIRTHDAY: Lazy<Regex> = Lazy::new(|| Regex::new("^\\d{4}-?\\d{2}-?\\d{2}$").unwrap());
+
+/// The payload a [`ContactBuilder`] serializes to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ContactFormat {
+    /// `MECARD:N:...;;`, compact, but without types, title, instant messenger, geo or photo
+    MeCard,
+    /// vCard 4.0, which keeps every field but the pronunciation
+    VCard,
+}
+
+/**
+ * Builds a contact payload in the MeCard or vCard 4.0 format, for encoding in a QR code.
+ * The payload parses back into an [`AddressBookParsedRXingResult`] with the same values.
+ *
+ * ```
+ * use rxing::client::result::{ContactBuilder, ContactFormat};
+ *
+ * let contact = ContactBuilder::new()
+ *     .with_name("Sean Owen")
+ *     .with_phone("+12125551212", "cell")
+ *     .with_email("srowen@example.org", "");
+ * assert_eq!(
+ *     "MECARD:N:Sean Owen;TEL:+12125551212;EMAIL:srowen@example.org;;",
+ *     contact.build(ContactFormat::MeCard)?
+ * );
+ * # Ok::<(), rxing::Exceptions>(())
+ * ```
+ */
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ContactBuilder {
+    names: Vec<String>,
+    nicknames: Vec<String>,
+    pronunciation: String,
+    phones: Vec<(String, String)>,
+    emails: Vec<(String, String)>,
+    addresses: Vec<(String, String)>,
+    instant_messenger: String,
+    note: String,
+    org: String,
+    birthday: String,
+    title: String,
+    urls: Vec<String>,
+    geo: Option<(String, String)>,
+    photo: String,
+}
+
+impl ContactBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a display name, like "Sean Owen"; MeCard keeps only the first
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.names.push(name.to_owned());
+        self
+    }
+
+    pub fn with_nickname(mut self, nickname: &str) -> Self {
+        self.nicknames.push(nickname.to_owned());
+        self
+    }
+
+    /// The phonetic spelling of the name, written only to MeCard (SOUND)
+    pub fn with_pronunciation(mut self, pronunciation: &str) -> Self {
+        pronunciation.clone_into(&mut self.pronunciation);
+        self
+    }
+
+    /// Adds a phone number with a type like "cell" or "work,voice", which may be empty
+    pub fn with_phone(mut self, number: &str, phone_type: &str) -> Self {
+        self.phones.push((number.to_owned(), phone_type.to_owned()));
+        self
+    }
+
+    /// Adds an e-mail address with a type like "work", which may be empty
+    pub fn with_email(mut self, email: &str, email_type: &str) -> Self {
+        self.emails.push((email.to_owned(), email_type.to_owned()));
+        self
+    }
+
+    /// Adds an address, lines separated by `\n`, with a type like "home", which may be empty
+    pub fn with_address(mut self, address: &str, address_type: &str) -> Self {
+        self.addresses
+            .push((address.to_owned(), address_type.to_owned()));
+        self
+    }
+
+    /// An instant messaging URI, like `xmpp:alice@example.com`
+    pub fn with_instant_messenger(mut self, instant_messenger: &str) -> Self {
+        instant_messenger.clone_into(&mut self.instant_messenger);
+        self
+    }
+
+    pub fn with_note(mut self, note: &str) -> Self {
+        note.clone_into(&mut self.note);
+        self
+    }
+
+    pub fn with_org(mut self, org: &str) -> Self {
+        org.clone_into(&mut self.org);
+        self
+    }
+
+    /// The birthday as yyyyMMdd or yyyy-MM-dd
+    pub fn with_birthday(mut self, birthday: &str) -> Self {
+        birthday.clone_into(&mut self.birthday);
+        self
+    }
+
+    pub fn with_title(mut self, title: &str) -> Self {
+        title.clone_into(&mut self.title);
+        self
+    }
+
+    pub fn with_url(mut self, url: &str) -> Self {
+        self.urls.push(url.to_owned());
+        self
+    }
+
+    pub fn with_geo(mut self, latitude: &str, longitude: &str) -> Self {
+        self.geo = Some((latitude.to_owned(), longitude.to_owned()));
+        self
+    }
+
+    /// The URI of a photo, such as an `http` or `data` URI
+    pub fn with_photo(mut self, photo: &str) -> Self {
+        photo.clone_into(&mut self.photo);
+        self
+    }
+
+    /**
+     * Serializes the contact.
+     *
+     * @return the payload, or an error if there is no name or the birthday is not a date
+     */
+    pub fn build(&self, format: ContactFormat) -> Result<String> {
+        if self.names.iter().all(|name| name.is_empty()) {
+            return Err(Exceptions::illegal_argument_with("a contact needs a name"));
+        }
+        if !self.birthday.is_empty() && !BIRTHDAY.is_match(&self.birthday) {
+            return Err(Exceptions::illegal_argument_with(format!(
+                "birthday {} is not a yyyyMMdd date",
+                self.birthday
+            )));
+        }
+        Ok(match format {
+            ContactFormat::MeCard => self.to_mecard(),
+            ContactFormat::VCard => self.to_vcard(),
+        })
+    }
+
+    fn to_mecard(&self) -> String {
+        let mut result = String::from("MECARD:");
+        // N comes first, so that no other key can be mistaken for it
+        if let Some(name) = self.names.iter().find(|name| !name.is_empty()) {
+            append_mecard_field("N", name, &mut result);
+        }
+        append_mecard_field("SOUND", &self.pronunciation, &mut result);
+        let nicknames: Vec<String> = self.nicknames.iter().map(|n| escape_mecard(n)).collect();
+        if !nicknames.is_empty() {
+            result.push_str(&format!("NICKNAME:{};", nicknames.join(",")));
+        }
+        for (number, _) in &self.phones {
+            append_mecard_field("TEL", number, &mut result);
+        }
+        for (email, _) in &self.emails {
+            append_mecard_field("EMAIL", email, &mut result);
+        }
+        for (address, _) in &self.addresses {
+            append_mecard_field("ADR", address, &mut result);
+        }
+        append_mecard_field("BDAY", &self.birthday.replace('-', ""), &mut result);
+        for url in &self.urls {
+            append_mecard_field("URL", url, &mut result);
+        }
+        append_mecard_field("ORG", &self.org, &mut result);
+        append_mecard_field("NOTE", &self.note, &mut result);
+        result.push(';');
+        result
+    }
+
+    fn to_vcard(&self) -> String {
+        let mut result = String::from("BEGIN:VCARD\r\nVERSION:4.0\r\n");
+        for name in self.names.iter().filter(|name| !name.is_empty()) {
+            append_vcard_line("FN", "", &escape_vcard(name), &mut result);
+        }
+        let nicknames: Vec<String> = self.nicknames.iter().map(|n| escape_vcard(n)).collect();
+        append_vcard_line("NICKNAME", "", &nicknames.join(","), &mut result);
+        for (number, phone_type) in &self.phones {
+            append_vcard_line("TEL", phone_type, &escape_vcard(number), &mut result);
+        }
+        for (email, email_type) in &self.emails {
+            append_vcard_line("EMAIL", email_type, &escape_vcard(email), &mut result);
+        }
+        for (address, address_type) in &self.addresses {
+            // the whole address goes in the street component
+            let adr = format!(";;{};;;;", escape_vcard(address));
+            append_vcard_line("ADR", address_type, &adr, &mut result);
+        }
+        append_vcard_line("ORG", "", &escape_vcard(&self.org), &mut result);
+        append_vcard_line("TITLE", "", &escape_vcard(&self.title), &mut result);
+        for url in &self.urls {
+            append_vcard_line("URL", "", url, &mut result);
+        }
+        append_vcard_line("BDAY", "", &self.birthday, &mut result);
+        if let Some((latitude, longitude)) = &self.geo {
+            append_vcard_line(
+                "GEO",
+                "",
+                &format!("geo:{latitude},{longitude}"),
+                &mut result,
+            );
+        }
+        append_vcard_line("IMPP", "", &self.instant_messenger, &mut result);
+        append_vcard_line("PHOTO", "", &self.photo, &mut result);
+        append_vcard_line("NOTE", "", &escape_vcard(&self.note), &mut result);
+        result.push_str("END:VCARD");
+        result
+    }
+}
+
+impl From<&AddressBookParsedRXingResult> for ContactBuilder {
+    fn from(contact: &AddressBookParsedRXingResult) -> Self {
+        let with_types = |values: &[String], types: &[String]| {
+            values
+                .iter()
+                .enumerate()
+                .map(|(i, value)| (value.clone(), types.get(i).cloned().unwrap_or_default()))
+                .collect()
+        };
+        let geo = match contact.getGeo().as_slice() {
+            [latitude, longitude] => Some((latitude.clone(), longitude.clone())),
+            _ => None,
+        };
+        Self {
+            names: contact.getNames().clone(),
+            nicknames: contact.getNicknames().clone(),
+            pronunciation: contact.getPronunciation().to_owned(),
+            phones: with_types(contact.getPhoneNumbers(), contact.getPhoneTypes()),
+            emails: with_types(contact.getEmails(), contact.getEmailTypes()),
+            addresses: with_types(contact.getAddresses(), contact.getAddressTypes()),
+            instant_messenger: contact.getInstantMessenger().to_owned(),
+            note: contact.getNote().to_owned(),
+            org: contact.getOrg().to_owned(),
+            birthday: contact.getBirthday().to_owned(),
+            title: contact.getTitle().to_owned(),
+            urls: contact.getURLs().clone(),
+            geo,
+            photo: contact.getPhoto().to_owned(),
+        }
+    }
+}
+
+/**
+ * Encodes a contact into a QR code as UTF-8, with error correction level M.
+ *
+ * `width` and `height` are the requested output size in pixels; `quiet_zone` is the margin
+ * in modules, at least 4 for a QR code.
+ */
+pub fn encode_contact(
+    contact: &ContactBuilder,
+    format: ContactFormat,
+    width: i32,
+    height: i32,
+    quiet_zone: i32,
+) -> Result<BitMatrix> {
+    let payload = contact.build(format)?;
+    let hints = EncodeHints {
+        CharacterSet: Some("UTF-8".to_owned()),
+        ErrorCorrection: Some("M".to_owned()),
+        Margin: Some(quiet_zone.to_string()),
+        ..Default::default()
+    };
+    QRCodeWriter.encode_with_hints(&payload, &BarcodeFormat::QR_CODE, width, height, &hints)
+}
+
+fn append_mecard_field(key: &str, value: &str, result: &mut String) {
+    if !value.is_empty() {
+        result.push_str(&format!("{key}:{};", escape_mecard(value)));
+    }
+}
+
+/// MeCard ends a value at `;` and finds keys by their `:`, so both are escaped
+fn escape_mecard(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    for c in value.chars().filter(|c| *c != '\r') {
+        if matches!(c, '\\' | ';' | ':' | ',') {
+            result.push('\\');
+        }
+        result.push(c);
+    }
+    result
+}
+
+fn append_vcard_line(name: &str, value_type: &str, value: &str, result: &mut String) {
+    if value.is_empty() || value.chars().all(|c| c == ';') {
+        return;
+    }
+    result.push_str(name);
+    if !value_type.is_empty() {
+        if value_type.contains([':', ';']) {
+            result.push_str(&format!(";TYPE=\"{}\"", value_type.replace('"', "")));
+        } else {
+            result.push_str(&format!(";TYPE={value_type}"));
+        }
+    }
+    result.push(':');
+    result.push_str(value);
+    result.push_str("\r\n");
+}
+
+/// Escapes a vCard text value, as in RFC 6350 section 3.4
+fn escape_vcard(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' | ',' | ';' => {
+                result.push('\\');
+                result.push(c);
+            }
+            '\n' => result.push_str("\\n"),
+            '\r' => {}
+            _ => result.push(c),
+        }
+    }
+    result
+}
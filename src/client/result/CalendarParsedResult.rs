@@ -27,7 +27,7 @@
 // import java.util.regex.Matcher;
 // import java.util.regex.Pattern;
 
-use chrono::{DateTime, NaiveDateTime};
+use chrono::{DateTime, NaiveDateTime, TimeZone};
 use chrono_tz::Tz;
 use once_cell::sync::Lazy;
 use regex::Regex;
@@ -76,6 +76,8 @@ pub struct CalendarParsedRXingResult {
     description: String,
     latitude: f64,
     longitude: f64,
+    timeZone: String,
+    recurrenceRule: String,
 }
 
 impl ParsedRXingResult for CalendarParsedRXingResult {
@@ -126,8 +128,11 @@ impl CalendarParsedRXingResult {
             Self::parseDate(endString.clone())?
         };
 
-        let startAllDay = startString.len() == 8;
-        let endAllDay = !endString.is_empty() && endString.len() == 8;
+        let startAllDay = Self::isDate(&startString);
+        let endAllDay = Self::isDate(&endString);
+        let timeZone = Self::splitTimeZone(&startString)
+            .map(|(_, tz)| tz.to_owned())
+            .unwrap_or_default();
 
         Ok(Self {
             summary,
@@ -141,20 +146,42 @@ impl CalendarParsedRXingResult {
             description,
             latitude,
             longitude,
+            timeZone,
+            recurrenceRule: String::default(),
         })
     }
 
+    /// Sets the RRULE of a recurring event, like `FREQ=WEEKLY;BYDAY=MO`
+    pub fn with_recurrence_rule(mut self, recurrenceRule: String) -> Self {
+        self.recurrenceRule = recurrenceRule;
+        self
+    }
+
+    fn isDate(when: &str) -> bool {
+        Self::splitTimeZone(when).is_some_and(|(date, _)| date.len() == 8)
+    }
+
+    /// Splits a date or date-time into the date-time and the time zone appended to it
+    fn splitTimeZone(when: &str) -> Option<(&str, &str)> {
+        DATE_TIME
+            .find(when)
+            .filter(|m| m.start() == 0)
+            .map(|m| when.split_at(m.end()))
+    }
+
     /**
      * Parses a string as a date. RFC 2445 allows the start and end fields to be of type DATE (e.g. 20081021)
      * or DATE-TIME (e.g. 20081021T123000 for local time, or 20081021T123000Z for UTC).
+     * A local time may be followed by the TZID of its time zone (e.g. 20081021T123000Europe/Vienna);
+     * without one, or with one that is not known, it is read as UTC.
      *
      * @param when The string to parse
      * @throws ParseException if not able to parse as a date
      */
     fn parseDate(when: String) -> Result<i64> {
-        if !DATE_TIME.is_match(&when) {
+        let Some((when, tz_part)) = Self::splitTimeZone(&when) else {
             return Err(Exceptions::parse_with(when));
-        }
+        };
         if when.len() == 8 {
             // Show only year/month/day
             let date_format_string = YMD_THMSZ_FORMAT;
@@ -171,51 +198,26 @@ impl CalendarParsedRXingResult {
             };
         }
         // The when string can be local time, or UTC if it ends with a Z
-        if when.len() == 16
-            && when
-                .chars()
-                .nth(15)
-                .ok_or(Exceptions::INDEX_OUT_OF_BOUNDS)?
-                == 'Z'
-        {
-            return match NaiveDateTime::parse_from_str(&when, YMD_THMSZ_FORMAT) {
+        if when.len() == 16 {
+            return match NaiveDateTime::parse_from_str(when, YMD_THMSZ_FORMAT) {
                 Ok(dtm) => Ok(dtm.and_utc().timestamp()),
                 Err(e) => Err(Exceptions::parse_with(format!(
                     "couldn't parse string: {e}"
                 ))),
             };
         }
-        // Try once more, with weird tz formatting
-        if when.len() > 16 {
-            let time_part = &when[..15];
-            let tz_part = &when[15..];
-            let tz_parsed: Tz = match tz_part.parse() {
-                Ok(time_zone) => time_zone,
-                Err(e) => {
-                    return Err(Exceptions::parse_with(format!(
-                        "couldn't parse timezone '{tz_part}': {e}"
-                    )))
-                }
-            };
-
-            return match NaiveDateTime::parse_from_str(time_part, YMD_THMS_FORMAT) {
-                Ok(dtm) => Ok(dtm.and_utc().with_timezone(&tz_parsed).timestamp()),
-                Err(e) => Err(Exceptions::parse_with(format!(
-                    "couldn't parse string: {e}"
-                ))),
-            };
+        let dtm = NaiveDateTime::parse_from_str(when, YMD_THMS_FORMAT)
+            .map_err(|e| Exceptions::parse_with(format!("couldn't parse local time: {e}")))?;
+        match tz_part.parse::<Tz>() {
+            Ok(time_zone) => time_zone
+                .from_local_datetime(&dtm)
+                .earliest()
+                .map(|local| local.timestamp())
+                .ok_or_else(|| {
+                    Exceptions::parse_with(format!("{when} does not exist in {tz_part}"))
+                }),
+            Err(_) => Ok(dtm.and_utc().timestamp()),
         }
-
-        // Try a final time with an exact length
-        if when.len() == 15 {
-            return match NaiveDateTime::parse_from_str(&when, YMD_THMS_FORMAT) {
-                Ok(dtm) => Ok(dtm.and_utc().timestamp()),
-                Err(e) => Err(Exceptions::parse_with(format!(
-                    "couldn't parse local time: {e}"
-                ))),
-            };
-        }
-        Self::parseDateTimeString(&when)
     }
 
     fn format_event(allDay: bool, date: i64) -> String {
@@ -259,18 +261,6 @@ impl CalendarParsedRXingResult {
         }
     }
 
-    fn parseDateTimeString(dateTimeString: &str) -> Result<i64> {
-        if let Ok(dtm) = DateTime::parse_from_str(dateTimeString, YMD_THMS_FORMAT) {
-            Ok(dtm.timestamp())
-        } else {
-            Err(Exceptions::parse_with(format!(
-                "Couldn't parse {dateTimeString}"
-            )))
-        }
-        // DateFormat format = new SimpleDateFormat("yyyyMMdd'T'HHmmss", Locale.ENGLISH);
-        // return format.parse(dateTimeString).getTime();
-    }
-
     pub fn getSummary(&self) -> &String {
         &self.summary
     }
@@ -328,6 +318,20 @@ impl CalendarParsedRXingResult {
     pub fn getLongitude(&self) -> f64 {
         self.longitude
     }
+
+    /**
+     * @return TZID of the local start time, empty for UTC or floating times
+     */
+    pub fn getTimeZone(&self) -> &str {
+        &self.timeZone
+    }
+
+    /**
+     * @return the RRULE of a recurring event, unparsed, or empty
+     */
+    pub fn getRecurrenceRule(&self) -> &str {
+        &self.recurrenceRule
+    }
 }
 
 impl PartialEq for CalendarParsedRXingResult {
@@ -343,6 +347,8 @@ impl PartialEq for CalendarParsedRXingResult {
             && self.description == other.description
             && self.latitude == other.latitude
             && self.longitude == other.longitude
+            && self.timeZone == other.timeZone
+            && self.recurrenceRule == other.recurrenceRule
     }
 }
 
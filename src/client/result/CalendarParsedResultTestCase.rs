@@ -37,7 +37,9 @@
 use chrono::DateTime;
 
 use crate::{
-    client::result::{ParsedClientResult, ParsedRXingResultType},
+    client::result::{
+        CalendarEventPayload, CalendarParsedRXingResult, ParsedClientResult, ParsedRXingResultType,
+    },
     BarcodeFormat, RXingResult,
 };
 
@@ -159,8 +161,126 @@ fn testVEventEscapes() {
            "Meeting with a friend\nlook at homepage first\n\n\n  \n",
            "Summary line",
            "Location, with, escaped, commas",
-           "20111110T110000Z",
-           "20111110T120000Z");
+           "20111110T100000Z",
+           "20111110T110000Z");
+}
+
+#[test]
+fn testTimeZone() {
+    // TZID is not the last parameter, and is quoted
+    doTestShort(
+        "BEGIN:VEVENT\r\nDTSTART;TZID=\"America/New_York\";VALUE=DATE-TIME:20110710T110000\r\nDTEND;TZID=America/New_York:20110710T120000\r\nEND:VEVENT",
+        "",
+        "",
+        "",
+        "20110710T150000Z",
+        "20110710T160000Z",
+    );
+    // an unknown time zone leaves a floating time
+    doTestShort(
+        "BEGIN:VEVENT\r\nDTSTART;TZID=W. Europe Standard Time:20111110T110000\r\nEND:VEVENT",
+        "",
+        "",
+        "",
+        "20111110T110000Z",
+        "",
+    );
+    let event =
+        parseEvent("BEGIN:VEVENT\r\nDTSTART;TZID=Europe/Vienna;VALUE=DATE:20111110\r\nEND:VEVENT");
+    assert!(event.isStartAllDay());
+    assert_eq!("Europe/Vienna", event.getTimeZone());
+}
+
+#[test]
+fn testRecurrenceRule() {
+    let event = parseEvent(
+        "BEGIN:VEVENT\r\nDTSTART:20080504T123456Z\r\nRRULE:FREQ=WEEKLY;BYDAY=MO,WE;UNTIL=20081231T000000Z\r\nEND:VEVENT",
+    );
+    assert_eq!(
+        "FREQ=WEEKLY;BYDAY=MO,WE;UNTIL=20081231T000000Z",
+        event.getRecurrenceRule()
+    );
+}
+
+#[test]
+fn testAttendeeParameters() {
+    doTest(
+        "BEGIN:VEVENT\r\nDTSTART:20080504T123456Z\r\nORGANIZER;CN=Bob:mailto:bob@example.org\r\nATTENDEE;CN=Alice;ROLE=REQ-PARTICIPANT:mailto:alice@example.org\r\nEND:VEVENT",
+        "", "", "", "20080504T123456Z", "", "bob@example.org",
+        &["alice@example.org"], f64::NAN, f64::NAN);
+}
+
+#[test]
+fn testCalendarEventPayload() {
+    let payload = CalendarEventPayload::new("Review; part 1, 2", 1320919200)
+        .with_end(1320922800)
+        .with_time_zone("Europe/Vienna")
+        .with_location("Room \\3")
+        .with_organizer("bob@example.org")
+        .with_attendee("alice@example.org")
+        .with_description("Bring:\nslides")
+        .with_geo(48.2, 16.37)
+        .with_recurrence_rule("FREQ=WEEKLY;COUNT=3");
+    let contents = payload.build().unwrap();
+    assert_eq!(
+        "BEGIN:VEVENT\r\nSUMMARY:Review\\; part 1\\, 2\r\n\
+         DTSTART;TZID=Europe/Vienna:20111110T110000\r\n\
+         DTEND;TZID=Europe/Vienna:20111110T120000\r\nLOCATION:Room \\\\3\r\n\
+         ORGANIZER:mailto:bob@example.org\r\nATTENDEE:mailto:alice@example.org\r\n\
+         DESCRIPTION:Bring:\\nslides\r\nGEO:48.2;16.37\r\nRRULE:FREQ=WEEKLY;COUNT=3\r\n\
+         END:VEVENT",
+        contents
+    );
+    doTest(
+        &contents,
+        "Bring:\nslides",
+        "Review; part 1, 2",
+        "Room \\3",
+        "20111110T100000Z",
+        "20111110T110000Z",
+        "bob@example.org",
+        &["alice@example.org"],
+        48.2,
+        16.37,
+    );
+    let event = parseEvent(&contents);
+    assert_eq!(payload, CalendarEventPayload::from(&event));
+
+    let all_day = CalendarEventPayload::new("Holiday", 1320883200)
+        .with_all_day(true)
+        .with_duration(2 * 86400)
+        .build()
+        .unwrap();
+    assert_eq!(
+        "BEGIN:VEVENT\r\nSUMMARY:Holiday\r\nDTSTART;VALUE=DATE:20111110\r\nDURATION:P2D\r\nEND:VEVENT",
+        all_day
+    );
+    doTestShort(
+        &all_day,
+        "",
+        "Holiday",
+        "",
+        "20111110T000000Z",
+        "20111112T000000Z",
+    );
+
+    assert!(CalendarEventPayload::new("Nowhere", 0)
+        .with_time_zone("Mars/Olympus_Mons")
+        .build()
+        .is_err());
+    assert!(CalendarEventPayload::new("Backwards", 10)
+        .with_end(0)
+        .build()
+        .is_err());
+}
+
+fn parseEvent(contents: &str) -> CalendarParsedRXingResult {
+    let fakeRXingResult =
+        RXingResult::new(contents, Vec::new(), Vec::new(), BarcodeFormat::QR_CODE);
+    match ResultParser::parseRXingResult(&fakeRXingResult) {
+        ParsedClientResult::CalendarEventResult(event) => event,
+        _ => panic!("Expected Calendar"),
+    }
 }
 
 #[test]
@@ -0,0 +1,271 @@
+/*
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use chrono::DateTime;
+use chrono_tz::Tz;
+
+use crate::{common::Result, Exceptions};
+
+use super::CalendarParsedRXingResult;
+
+/**
+ * Builds an iCalendar `BEGIN:VEVENT` payload, with its text values escaped. Times are UTC
+ * timestamps in seconds, written in UTC or, given a time zone, as local times with a TZID.
+ * The payload parses back into a [`CalendarParsedRXingResult`] with the same values.
+ *
+ * ```
+ * use rxing::client::result::CalendarEventPayload;
+ *
+ * let payload = CalendarEventPayload::new("Stand-up, daily", 1320922800)
+ *     .with_end(1320924600)
+ *     .with_recurrence_rule("FREQ=DAILY;COUNT=5")
+ *     .build()?;
+ * assert_eq!(
+ *     "BEGIN:VEVENT\r\nSUMMARY:Stand-up\\, daily\r\nDTSTART:20111110T110000Z\r\n\
+ *      DTEND:20111110T113000Z\r\nRRULE:FREQ=DAILY;COUNT=5\r\nEND:VEVENT",
+ *     payload
+ * );
+ * # Ok::<(), rxing::Exceptions>(())
+ * ```
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct CalendarEventPayload {
+    summary: String,
+    start: i64,
+    all_day: bool,
+    end: Option<i64>,
+    duration: Option<i64>,
+    time_zone: String,
+    location: String,
+    organizer: String,
+    attendees: Vec<String>,
+    description: String,
+    geo: Option<(f64, f64)>,
+    recurrence_rule: String,
+}
+
+impl CalendarEventPayload {
+    /// An event with a summary that starts at a UTC timestamp, in seconds
+    pub fn new(summary: &str, start: i64) -> Self {
+        Self {
+            summary: summary.to_owned(),
+            start,
+            all_day: false,
+            end: None,
+            duration: None,
+            time_zone: String::default(),
+            location: String::default(),
+            organizer: String::default(),
+            attendees: Vec::new(),
+            description: String::default(),
+            geo: None,
+            recurrence_rule: String::default(),
+        }
+    }
+
+    /// Writes the start and end as dates (VALUE=DATE), the timestamps being midnight UTC
+    pub fn with_all_day(mut self, all_day: bool) -> Self {
+        self.all_day = all_day;
+        self
+    }
+
+    /// The end, a UTC timestamp in seconds
+    pub fn with_end(mut self, end: i64) -> Self {
+        self.end = Some(end);
+        self
+    }
+
+    /// The duration in seconds, written when there is no end
+    pub fn with_duration(mut self, duration: i64) -> Self {
+        self.duration = Some(duration);
+        self
+    }
+
+    /// An IANA time zone, like "Europe/Vienna", in which the start and end are written
+    pub fn with_time_zone(mut self, time_zone: &str) -> Self {
+        time_zone.clone_into(&mut self.time_zone);
+        self
+    }
+
+    pub fn with_location(mut self, location: &str) -> Self {
+        location.clone_into(&mut self.location);
+        self
+    }
+
+    /// The e-mail address of the organizer
+    pub fn with_organizer(mut self, organizer: &str) -> Self {
+        organizer.clone_into(&mut self.organizer);
+        self
+    }
+
+    /// Adds the e-mail address of an attendee
+    pub fn with_attendee(mut self, attendee: &str) -> Self {
+        self.attendees.push(attendee.to_owned());
+        self
+    }
+
+    pub fn with_description(mut self, description: &str) -> Self {
+        description.clone_into(&mut self.description);
+        self
+    }
+
+    pub fn with_geo(mut self, latitude: f64, longitude: f64) -> Self {
+        self.geo = Some((latitude, longitude));
+        self
+    }
+
+    /// An RRULE, like `FREQ=WEEKLY;BYDAY=MO`, written as given
+    pub fn with_recurrence_rule(mut self, recurrence_rule: &str) -> Self {
+        recurrence_rule.clone_into(&mut self.recurrence_rule);
+        self
+    }
+
+    /**
+     * Serializes the event.
+     *
+     * @return the payload, or an error if the time zone is not known, the end is before the
+     *  start or the duration is negative
+     */
+    pub fn build(&self) -> Result<String> {
+        if self.end.is_some_and(|end| end < self.start) {
+            return Err(Exceptions::illegal_argument_with(
+                "the event ends before it starts",
+            ));
+        }
+        if self.duration.is_some_and(|duration| duration < 0) {
+            return Err(Exceptions::illegal_argument_with("negative duration"));
+        }
+
+        let mut result = String::from("BEGIN:VEVENT\r\n");
+        append_line("SUMMARY", &escape_text(&self.summary), &mut result);
+        append_line("DTSTART", &self.format_time(self.start)?, &mut result);
+        if let Some(end) = self.end {
+            append_line("DTEND", &self.format_time(end)?, &mut result);
+        } else if let Some(duration) = self.duration {
+            append_line("DURATION", &format_duration(duration), &mut result);
+        }
+        append_line("LOCATION", &escape_text(&self.location), &mut result);
+        if !self.organizer.is_empty() {
+            append_line(
+                "ORGANIZER",
+                &format!("mailto:{}", self.organizer),
+                &mut result,
+            );
+        }
+        for attendee in &self.attendees {
+            append_line("ATTENDEE", &format!("mailto:{attendee}"), &mut result);
+        }
+        append_line("DESCRIPTION", &escape_text(&self.description), &mut result);
+        if let Some((latitude, longitude)) = self.geo {
+            append_line("GEO", &format!("{latitude};{longitude}"), &mut result);
+        }
+        append_line("RRULE", &self.recurrence_rule, &mut result);
+        result.push_str("END:VEVENT");
+        Ok(result)
+    }
+
+    /// The parameters and value of DTSTART or DTEND, like `;TZID=Europe/Vienna:20111110T110000`
+    fn format_time(&self, timestamp: i64) -> Result<String> {
+        let utc = DateTime::from_timestamp(timestamp, 0)
+            .ok_or_else(|| Exceptions::illegal_argument_with("timestamp out of range"))?;
+        if self.all_day {
+            return Ok(format!(";VALUE=DATE:{}", utc.format("%Y%m%d")));
+        }
+        if self.time_zone.is_empty() {
+            return Ok(format!(":{}", utc.format("%Y%m%dT%H%M%SZ")));
+        }
+        let time_zone: Tz = self.time_zone.parse().map_err(|_| {
+            Exceptions::illegal_argument_with(format!("unknown time zone {}", self.time_zone))
+        })?;
+        Ok(format!(
+            ";TZID={}:{}",
+            self.time_zone,
+            utc.with_timezone(&time_zone).format("%Y%m%dT%H%M%S")
+        ))
+    }
+}
+
+impl From<&CalendarParsedRXingResult> for CalendarEventPayload {
+    fn from(event: &CalendarParsedRXingResult) -> Self {
+        let end = event.getEndTimestamp();
+        Self {
+            summary: event.getSummary().clone(),
+            start: event.getStartTimestamp(),
+            all_day: event.isStartAllDay(),
+            end: (end >= 0).then_some(end),
+            duration: None,
+            time_zone: event.getTimeZone().to_owned(),
+            location: event.getLocation().to_owned(),
+            organizer: event.getOrganizer().to_owned(),
+            attendees: event.getAttendees().clone(),
+            description: event.getDescription().to_owned(),
+            geo: (!event.getLatitude().is_nan())
+                .then_some((event.getLatitude(), event.getLongitude())),
+            recurrence_rule: event.getRecurrenceRule().to_owned(),
+        }
+    }
+}
+
+/// Appends a property, the value starting with its parameters or the colon before it
+fn append_line(name: &str, value: &str, result: &mut String) {
+    if value.is_empty() {
+        return;
+    }
+    result.push_str(name);
+    if !value.starts_with([';', ':']) {
+        result.push(':');
+    }
+    result.push_str(value);
+    result.push_str("\r\n");
+}
+
+/// Escapes a TEXT value, as in RFC 5545 section 3.3.11
+fn escape_text(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' | ',' | ';' => {
+                result.push('\\');
+                result.push(c);
+            }
+            '\n' => result.push_str("\\n"),
+            '\r' => {}
+            _ => result.push(c),
+        }
+    }
+    result
+}
+
+/// Formats seconds as an RFC 5545 duration, like `P1DT2H30M`
+fn format_duration(seconds: i64) -> String {
+    let (days, seconds) = (seconds / 86400, seconds % 86400);
+    let (hours, minutes, seconds) = (seconds / 3600, seconds % 3600 / 60, seconds % 60);
+    let mut result = String::from("P");
+    if days > 0 {
+        result.push_str(&format!("{days}D"));
+    }
+    if hours > 0 || minutes > 0 || seconds > 0 || days == 0 {
+        result.push('T');
+        if hours > 0 {
+            result.push_str(&format!("{hours}H"));
+        }
+        if minutes > 0 {
+            result.push_str(&format!("{minutes}M"));
+        }
+        if seconds > 0 || (hours == 0 && minutes == 0) {
+            result.push_str(&format!("{seconds}S"));
+        }
+    }
+    result
+}
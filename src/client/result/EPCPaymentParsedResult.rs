@@ -0,0 +1,156 @@
+/*
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use super::{ParsedRXingResult, ParsedRXingResultType, ResultParser};
+
+/**
+ * Represents a parsed result that encodes an EPC069-12 SEPA credit transfer
+ * ("GiroCode" / "EPC QR code").
+ *
+ * Amounts are stored in the smallest currency unit (cents) to avoid any
+ * floating point rounding of monetary values.
+ */
+#[derive(PartialEq, Eq, Hash, Debug)]
+pub struct EPCPaymentParsedRXingResult {
+    version: String,
+    character_set: u8,
+    identification: String,
+    bic: String,
+    beneficiary_name: String,
+    iban: String,
+    currency: String,
+    amount_cents: Option<u64>,
+    purpose: String,
+    creditor_reference: String,
+    remittance_information: String,
+    beneficiary_to_originator_information: String,
+}
+
+impl ParsedRXingResult for EPCPaymentParsedRXingResult {
+    fn getType(&self) -> ParsedRXingResultType {
+        ParsedRXingResultType::EpcPayment
+    }
+
+    fn getDisplayRXingResult(&self) -> String {
+        let mut result = String::with_capacity(100);
+        ResultParser::maybe_append_string(&self.beneficiary_name, &mut result);
+        ResultParser::maybe_append_string(&self.iban, &mut result);
+        ResultParser::maybe_append_string(&self.bic, &mut result);
+        if let Some(amount) = self.getAmountString() {
+            ResultParser::maybe_append_string(&format!("{} {amount}", self.currency), &mut result);
+        }
+        ResultParser::maybe_append_string(&self.creditor_reference, &mut result);
+        ResultParser::maybe_append_string(&self.remittance_information, &mut result);
+        ResultParser::maybe_append_string(&self.beneficiary_to_originator_information, &mut result);
+
+        result
+    }
+}
+
+impl EPCPaymentParsedRXingResult {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        version: String,
+        character_set: u8,
+        identification: String,
+        bic: String,
+        beneficiary_name: String,
+        iban: String,
+        currency: String,
+        amount_cents: Option<u64>,
+        purpose: String,
+        creditor_reference: String,
+        remittance_information: String,
+        beneficiary_to_originator_information: String,
+    ) -> Self {
+        Self {
+            version,
+            character_set,
+            identification,
+            bic,
+            beneficiary_name,
+            iban,
+            currency,
+            amount_cents,
+            purpose,
+            creditor_reference,
+            remittance_information,
+            beneficiary_to_originator_information,
+        }
+    }
+
+    /// The EPC QR version, either `001` or `002`
+    pub fn getVersion(&self) -> &str {
+        &self.version
+    }
+
+    /// The EPC character set indicator (1 = UTF-8, 2 = ISO 8859-1, ... 8 = ISO 8859-15)
+    pub fn getCharacterSet(&self) -> u8 {
+        self.character_set
+    }
+
+    /// The identification code, always `SCT` (SEPA credit transfer)
+    pub fn getIdentification(&self) -> &str {
+        &self.identification
+    }
+
+    /// The BIC of the beneficiary bank, empty if not provided (allowed in version `002`)
+    pub fn getBIC(&self) -> &str {
+        &self.bic
+    }
+
+    pub fn getBeneficiaryName(&self) -> &str {
+        &self.beneficiary_name
+    }
+
+    /// The IBAN of the beneficiary with any whitespace removed
+    pub fn getIBAN(&self) -> &str {
+        &self.iban
+    }
+
+    /// The ISO 4217 currency code, `EUR` for all valid EPC payloads
+    pub fn getCurrency(&self) -> &str {
+        &self.currency
+    }
+
+    /// The transfer amount in cents, `None` if the payload leaves the amount to the payer
+    pub fn getAmountInCents(&self) -> Option<u64> {
+        self.amount_cents
+    }
+
+    /// The transfer amount formatted with two decimals, e.g. `12.50`
+    pub fn getAmountString(&self) -> Option<String> {
+        self.amount_cents
+            .map(|cents| format!("{}.{:02}", cents / 100, cents % 100))
+    }
+
+    /// The four letter purpose code, empty if not provided
+    pub fn getPurpose(&self) -> &str {
+        &self.purpose
+    }
+
+    /// The structured (ISO 11649 creditor reference) remittance information
+    pub fn getCreditorReference(&self) -> &str {
+        &self.creditor_reference
+    }
+
+    /// The unstructured remittance information
+    pub fn getRemittanceInformation(&self) -> &str {
+        &self.remittance_information
+    }
+
+    pub fn getBeneficiaryToOriginatorInformation(&self) -> &str {
+        &self.beneficiary_to_originator_information
+    }
+}
@@ -0,0 +1,108 @@
+/*
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+/**
+ * Tests {@link EPCPaymentParsedRXingResult}.
+ */
+use crate::{
+    client::result::{ParsedClientResult, ParsedRXingResult, ParsedRXingResultType},
+    BarcodeFormat, RXingResult,
+};
+
+use super::ResultParser;
+
+#[test]
+fn test_epc_payment() {
+    do_test(
+        "BCD\n002\n1\nSCT\nBFSWDE33BER\nWikimedia Foerdergesellschaft\nDE33100205000001194700\nEUR123.45\n\n\nSpende fuer Wikipedia",
+        "BFSWDE33BER",
+        "Wikimedia Foerdergesellschaft",
+        "DE33100205000001194700",
+        Some(12345),
+        "",
+        "Spende fuer Wikipedia",
+    );
+    do_test(
+        "BCD\r\n001\r\n1\r\nSCT\r\nBPOTBEB1\r\nRed Cross of Belgium\r\nBE72 0000 0000 1616\r\nEUR1\r\nCHAR\r\nRF18539007547034",
+        "BPOTBEB1",
+        "Red Cross of Belgium",
+        "BE72000000001616",
+        Some(100),
+        "RF18539007547034",
+        "",
+    );
+    // version 002 allows omitting both the BIC and the amount
+    do_test(
+        "BCD\n002\n2\nSCT\n\nFrancois D'Alsace S.A.\nFR1420041010050500013M02606",
+        "",
+        "Francois D'Alsace S.A.",
+        "FR1420041010050500013M02606",
+        None,
+        "",
+        "",
+    );
+}
+
+#[test]
+fn test_not_epc_payment() {
+    // bad IBAN checksum
+    do_not_test("BCD\n002\n1\nSCT\n\nName\nDE33100205000001194701\nEUR1.00");
+    // BIC is mandatory in version 001
+    do_not_test("BCD\n001\n1\nSCT\n\nName\nDE33100205000001194700\nEUR1.00");
+    // unknown version, identification and character set
+    do_not_test("BCD\n003\n1\nSCT\n\nName\nDE33100205000001194700");
+    do_not_test("BCD\n002\n1\nSEPA\n\nName\nDE33100205000001194700");
+    do_not_test("BCD\n002\n9\nSCT\n\nName\nDE33100205000001194700");
+    // amount out of range or with bad precision
+    do_not_test("BCD\n002\n1\nSCT\n\nName\nDE33100205000001194700\nEUR0.00");
+    do_not_test("BCD\n002\n1\nSCT\n\nName\nDE33100205000001194700\nEUR1.001");
+    do_not_test("BCD\n002\n1\nSCT\n\nName\nDE33100205000001194700\nUSD1.00");
+    // structured and unstructured remittance are mutually exclusive
+    do_not_test("BCD\n002\n1\nSCT\n\nName\nDE33100205000001194700\nEUR1\n\nRF18539007547034\nText");
+    // missing beneficiary name
+    do_not_test("BCD\n002\n1\nSCT\n\n\nDE33100205000001194700");
+}
+
+fn do_test(
+    contents: &str,
+    bic: &str,
+    name: &str,
+    iban: &str,
+    amount_cents: Option<u64>,
+    reference: &str,
+    remittance: &str,
+) {
+    let fake_rxing_result =
+        RXingResult::new(contents, Vec::new(), Vec::new(), BarcodeFormat::QR_CODE);
+    let result = ResultParser::parseRXingResult(&fake_rxing_result);
+    assert_eq!(ParsedRXingResultType::EpcPayment, result.getType());
+    if let ParsedClientResult::EPCPaymentResult(epc_result) = result {
+        assert_eq!(bic, epc_result.getBIC());
+        assert_eq!(name, epc_result.getBeneficiaryName());
+        assert_eq!(iban, epc_result.getIBAN());
+        assert_eq!("EUR", epc_result.getCurrency());
+        assert_eq!(amount_cents, epc_result.getAmountInCents());
+        assert_eq!(reference, epc_result.getCreditorReference());
+        assert_eq!(remittance, epc_result.getRemittanceInformation());
+    } else {
+        panic!("Expected EPCPaymentResult");
+    }
+}
+
+fn do_not_test(contents: &str) {
+    let fake_rxing_result =
+        RXingResult::new(contents, Vec::new(), Vec::new(), BarcodeFormat::QR_CODE);
+    let result = ResultParser::parseRXingResult(&fake_rxing_result);
+    assert_ne!(ParsedRXingResultType::EpcPayment, result.getType());
+}
@@ -0,0 +1,218 @@
+/*
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use crate::{
+    client::result::{EPCPaymentParsedRXingResult, ParsedClientResult},
+    RXingResult,
+};
+
+use super::ResultParser;
+
+const SERVICE_TAG: &str = "BCD";
+const IDENTIFICATION: &str = "SCT";
+const CURRENCY: &str = "EUR";
+const MAX_PAYLOAD_BYTES: usize = 331;
+const MAX_AMOUNT_CENTS: u64 = 99_999_999_999;
+
+/**
+ * <p>Parses an EPC069-12 "SEPA credit transfer" payload, as printed on invoices
+ * in the form of a QR code. Payloads are line based:</p>
+ *
+ * <pre>
+ * BCD                 service tag
+ * 001|002             version
+ * 1-8                 character set
+ * SCT                 identification
+ * [BIC]               mandatory in version 001
+ * name                beneficiary name, max 70
+ * IBAN                beneficiary account
+ * [EUR12.30]          amount, 0.01 - 999999999.99
+ * [purpose]           4 character purpose code
+ * [reference]         structured creditor reference, max 35
+ * [text]              unstructured remittance, max 140
+ * [information]       beneficiary to originator, max 70
+ * </pre>
+ *
+ * <p>Trailing optional lines may be omitted. Only one of the structured and
+ * unstructured remittance fields may be present. Payloads that violate any of
+ * these rules, or carry an IBAN with an invalid checksum, are not parsed.</p>
+ */
+pub fn parse(result: &RXingResult) -> Option<ParsedClientResult> {
+    let raw_text = ResultParser::getMassagedText(result);
+    if !raw_text.starts_with(SERVICE_TAG) || raw_text.len() > MAX_PAYLOAD_BYTES {
+        return None;
+    }
+
+    let lines: Vec<&str> = raw_text
+        .split('\n')
+        .map(|line| line.strip_suffix('\r').unwrap_or(line))
+        .collect();
+    if lines.len() < 7 || lines.len() > 12 {
+        return None;
+    }
+    let field = |i: usize| lines.get(i).map(|l| l.trim()).unwrap_or_default();
+
+    if field(0) != SERVICE_TAG {
+        return None;
+    }
+
+    let version = field(1);
+    if version != "001" && version != "002" {
+        return None;
+    }
+
+    let character_set = field(2).parse::<u8>().ok()?;
+    if !(1..=8).contains(&character_set) {
+        return None;
+    }
+
+    if field(3) != IDENTIFICATION {
+        return None;
+    }
+
+    let bic = field(4);
+    if (bic.is_empty() && version == "001") || (!bic.is_empty() && !is_valid_bic(bic)) {
+        return None;
+    }
+
+    let name = field(5);
+    if name.is_empty() || name.chars().count() > 70 {
+        return None;
+    }
+
+    let iban: String = field(6).chars().filter(|c| !c.is_whitespace()).collect();
+    if !is_valid_iban(&iban) {
+        return None;
+    }
+
+    let amount_cents = match field(7) {
+        "" => None,
+        amount => Some(parse_amount(amount)?),
+    };
+
+    let purpose = field(8);
+    if !purpose.is_empty()
+        && (purpose.len() != 4 || !purpose.chars().all(|c| c.is_ascii_alphanumeric()))
+    {
+        return None;
+    }
+
+    let creditor_reference = field(9);
+    let remittance_information = field(10);
+    if creditor_reference.chars().count() > 35
+        || remittance_information.chars().count() > 140
+        || (!creditor_reference.is_empty() && !remittance_information.is_empty())
+    {
+        return None;
+    }
+
+    let information = field(11);
+    if information.chars().count() > 70 {
+        return None;
+    }
+
+    Some(ParsedClientResult::EPCPaymentResult(
+        EPCPaymentParsedRXingResult::new(
+            version.to_owned(),
+            character_set,
+            IDENTIFICATION.to_owned(),
+            bic.to_owned(),
+            name.to_owned(),
+            iban,
+            CURRENCY.to_owned(),
+            amount_cents,
+            purpose.to_owned(),
+            creditor_reference.to_owned(),
+            remittance_information.to_owned(),
+            information.to_owned(),
+        ),
+    ))
+}
+
+/// Parses an amount of the form `EUR123.45` into cents.
+fn parse_amount(amount: &str) -> Option<u64> {
+    let value = amount.strip_prefix(CURRENCY)?;
+    let (units, fraction) = match value.split_once('.') {
+        Some((units, fraction)) => (units, fraction),
+        None => (value, ""),
+    };
+    if units.is_empty()
+        || units.len() > 9
+        || fraction.len() > 2
+        || !units.chars().all(|c| c.is_ascii_digit())
+        || !fraction.chars().all(|c| c.is_ascii_digit())
+    {
+        return None;
+    }
+
+    let cents = units.parse::<u64>().ok()? * 100
+        + match fraction.len() {
+            0 => 0,
+            1 => fraction.parse::<u64>().ok()? * 10,
+            _ => fraction.parse::<u64>().ok()?,
+        };
+
+    if cents == 0 || cents > MAX_AMOUNT_CENTS {
+        return None;
+    }
+
+    Some(cents)
+}
+
+/// A BIC is 8 or 11 characters: 4 letter bank code, 2 letter country code,
+/// 2 character location code and an optional 3 character branch code.
+fn is_valid_bic(bic: &str) -> bool {
+    (bic.len() == 8 || bic.len() == 11)
+        && bic
+            .chars()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit())
+        && bic[..6].chars().all(|c| c.is_ascii_uppercase())
+}
+
+/// Validates an IBAN using the ISO 13616 mod 97 checksum.
+pub fn is_valid_iban(iban: &str) -> bool {
+    if iban.len() < 15
+        || iban.len() > 34
+        || !iban.chars().all(|c| c.is_ascii_alphanumeric())
+        || !iban[..2].chars().all(|c| c.is_ascii_uppercase())
+        || !iban[2..4].chars().all(|c| c.is_ascii_digit())
+    {
+        return false;
+    }
+
+    is_mod97_valid(iban)
+}
+
+/// ISO 7064 mod 97-10 check, as used by IBANs and ISO 11649 creditor references:
+/// the first four characters are moved to the end, letters are expanded to two
+/// digits (A = 10 ... Z = 35), and the resulting number must be 1 modulo 97.
+pub fn is_mod97_valid(value: &str) -> bool {
+    if value.len() < 5 || !value.is_ascii() {
+        return false;
+    }
+
+    let mut remainder = 0_u32;
+    for c in value[4..].chars().chain(value[..4].chars()) {
+        let Some(value) = c.to_digit(36) else {
+            return false;
+        };
+        remainder = if value >= 10 {
+            (remainder * 100 + value) % 97
+        } else {
+            (remainder * 10 + value) % 97
+        };
+    }
+
+    remainder == 1
+}
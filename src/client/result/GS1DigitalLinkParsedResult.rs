@@ -0,0 +1,207 @@
+/*
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use super::{ParsedRXingResult, ParsedRXingResultType};
+
+/// The domain of GS1's own resolver, used when a Digital Link is built without one
+pub const GS1_DIGITAL_LINK_DEFAULT_DOMAIN: &str = "https://id.gs1.org";
+
+/// The primary key AIs a Digital Link path can start with, their GS1 Digital Link 1.0
+/// short names, and the key qualifier AIs that may follow them in the path, in order
+const PRIMARY_KEYS: [(&str, &str, &[&str]); 15] = [
+    ("01", "gtin", &["22", "10", "21"]),
+    ("8006", "itip", &["22", "10", "21"]),
+    ("8013", "", &["7040"]),
+    ("00", "sscc", &[]),
+    ("414", "gln", &["254", "7040"]),
+    ("417", "", &["7040"]),
+    ("8003", "grai", &[]),
+    ("8004", "giai", &[]),
+    ("8010", "cpid", &["8011"]),
+    ("8017", "gsrnp", &["8019"]),
+    ("8018", "gsrn", &["8019"]),
+    ("253", "gdti", &[]),
+    ("255", "gcn", &[]),
+    ("401", "ginc", &[]),
+    ("402", "gsin", &[]),
+];
+
+/// The GS1 Digital Link 1.0 short names of key qualifiers
+const QUALIFIER_NAMES: [(&str, &str); 6] = [
+    ("22", "cpv"),
+    ("10", "lot"),
+    ("21", "ser"),
+    ("254", "glnx"),
+    ("8011", "cpsn"),
+    ("8019", "srin"),
+];
+
+/// AIs whose value is a fixed number of digits ending in a GS1 check digit
+const CHECK_DIGIT_LENGTHS: [(&str, usize); 5] = [
+    ("00", 18),
+    ("01", 14),
+    ("402", 17),
+    ("414", 13),
+    ("417", 13),
+];
+
+/**
+ * Represents a parsed result that encodes a GS1 Digital Link URI, a web address carrying
+ * GS1 application identifier (AI) data, such as
+ * `https://id.gs1.org/01/09506000134352/10/ABC123?17=251231`.
+ *
+ * The AIs are kept in the order of the URI: the primary key, its key qualifiers from the
+ * path, then the data attributes from the query string. Query parameters that are not AIs,
+ * such as `linkType`, are kept apart.
+ */
+#[derive(PartialEq, Eq, Hash, Debug, Clone)]
+pub struct GS1DigitalLinkParsedRXingResult {
+    uri: String,
+    domain: String,
+    ais: Vec<(String, String)>,
+    other_parameters: Vec<(String, String)>,
+}
+
+impl ParsedRXingResult for GS1DigitalLinkParsedRXingResult {
+    fn getType(&self) -> ParsedRXingResultType {
+        ParsedRXingResultType::GS1DigitalLink
+    }
+
+    fn getDisplayRXingResult(&self) -> String {
+        self.getElementString()
+    }
+}
+
+impl GS1DigitalLinkParsedRXingResult {
+    pub fn new(
+        uri: String,
+        domain: String,
+        ais: Vec<(String, String)>,
+        other_parameters: Vec<(String, String)>,
+    ) -> Self {
+        Self {
+            uri,
+            domain,
+            ais,
+            other_parameters,
+        }
+    }
+
+    pub fn getURI(&self) -> &str {
+        &self.uri
+    }
+
+    /// The scheme, host and any path before the primary key, e.g. `https://id.gs1.org`
+    pub fn getDomain(&self) -> &str {
+        &self.domain
+    }
+
+    /// The AIs and their values, the primary key first
+    pub fn getAIs(&self) -> &[(String, String)] {
+        &self.ais
+    }
+
+    /// The value of an AI, e.g. `getAI("10")` for the batch or lot number
+    pub fn getAI(&self, ai: &str) -> Option<&str> {
+        self.ais
+            .iter()
+            .find(|(key, _)| key == ai)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// The primary key AI and its value
+    pub fn getPrimaryKey(&self) -> (&str, &str) {
+        self.ais
+            .first()
+            .map(|(ai, value)| (ai.as_str(), value.as_str()))
+            .unwrap_or_default()
+    }
+
+    /// The GTIN, as 14 digits, if the primary key is one
+    pub fn getGTIN(&self) -> Option<&str> {
+        self.getAI("01")
+    }
+
+    /// The query parameters that are not AIs, e.g. `linkType`
+    pub fn getOtherParameters(&self) -> &[(String, String)] {
+        &self.other_parameters
+    }
+
+    /// The AIs as a GS1 element string in human readable form, e.g. `(01)09506000134352(10)ABC`
+    pub fn getElementString(&self) -> String {
+        self.ais
+            .iter()
+            .map(|(ai, value)| format!("({ai}){value}"))
+            .collect()
+    }
+}
+
+/// The AI of a primary key, from the AI itself or its short name, with its key qualifiers
+pub(crate) fn primary_key(segment: &str) -> Option<(&'static str, &'static [&'static str])> {
+    PRIMARY_KEYS
+        .iter()
+        .find(|(ai, name, _)| *ai == segment || (!name.is_empty() && *name == segment))
+        .map(|(ai, _, qualifiers)| (*ai, *qualifiers))
+}
+
+/// The AI of a key qualifier, from the AI itself or its short name
+pub(crate) fn qualifier(segment: &str) -> &str {
+    QUALIFIER_NAMES
+        .iter()
+        .find(|(_, name)| *name == segment)
+        .map_or(segment, |(ai, _)| ai)
+}
+
+/// Whether `key` is written as an AI, 2 to 4 digits
+pub(crate) fn is_ai(key: &str) -> bool {
+    (2..=4).contains(&key.len()) && key.chars().all(|c| c.is_ascii_digit())
+}
+
+/**
+ * Checks the value of an AI, padding a GTIN-8, GTIN-12 or GTIN-13 to 14 digits.
+ *
+ * @return the value as it is carried in the element string, None if it is empty or a key
+ *  with a wrong length or check digit
+ */
+pub(crate) fn normalize_ai_value(ai: &str, value: &str) -> Option<String> {
+    if value.is_empty() {
+        return None;
+    }
+    let value = if ai == "01" && [8, 12, 13].contains(&value.len()) {
+        format!("{value:0>14}")
+    } else {
+        value.to_owned()
+    };
+    match CHECK_DIGIT_LENGTHS.iter().find(|(key, _)| *key == ai) {
+        Some((_, length)) if value.len() != *length || !is_gs1_check_digit_valid(&value) => None,
+        _ => Some(value),
+    }
+}
+
+/// Validates the GS1 mod 10 check digit at the end of a string of digits
+pub fn is_gs1_check_digit_valid(digits: &str) -> bool {
+    if digits.len() < 2 || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return false;
+    }
+    // weighted 3 and 1 alternately from the digit before the check digit leftwards
+    let sum: u32 = digits
+        .bytes()
+        .rev()
+        .skip(1)
+        .enumerate()
+        .map(|(i, digit)| (digit - b'0') as u32 * if i % 2 == 0 { 3 } else { 1 })
+        .sum();
+    let check = (10 - sum % 10) % 10;
+    digits.bytes().last().map(|digit| (digit - b'0') as u32) == Some(check)
+}
@@ -0,0 +1,173 @@
+/*
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+/**
+ * Tests {@link GS1DigitalLinkParsedRXingResult}.
+ */
+use std::collections::HashSet;
+
+use crate::{
+    client::result::{
+        encode_gs1_digital_link, is_gs1_check_digit_valid, GS1DigitalLinkBuilder,
+        ParsedClientResult, ParsedRXingResult, ParsedRXingResultType,
+    },
+    helpers, BarcodeFormat, DecodeHints, RXingResult,
+};
+
+use super::ResultParser;
+
+fn parse(contents: &str) -> ParsedClientResult {
+    let fake_rxing_result =
+        RXingResult::new(contents, Vec::new(), Vec::new(), BarcodeFormat::QR_CODE);
+    ResultParser::parseRXingResult(&fake_rxing_result)
+}
+
+fn pairs(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+    pairs
+        .iter()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect()
+}
+
+#[test]
+fn test_gs1_digital_link() {
+    let uri =
+        "https://id.gs1.org/01/09506000134352/10/ABC%2F1?17=251231&3103=000500&linkType=gs1:pip";
+    let result = parse(uri);
+    assert_eq!(ParsedRXingResultType::GS1DigitalLink, result.getType());
+    let ParsedClientResult::GS1DigitalLinkResult(link) = result else {
+        panic!("Expected GS1DigitalLinkResult");
+    };
+    assert_eq!(uri, link.getURI());
+    assert_eq!("https://id.gs1.org", link.getDomain());
+    assert_eq!(("01", "09506000134352"), link.getPrimaryKey());
+    assert_eq!(Some("09506000134352"), link.getGTIN());
+    assert_eq!(Some("ABC/1"), link.getAI("10"));
+    assert_eq!(pairs(&[("linkType", "gs1:pip")]), link.getOtherParameters());
+    assert_eq!(
+        "(01)09506000134352(10)ABC/1(17)251231(3103)000500",
+        link.getDisplayRXingResult()
+    );
+}
+
+#[test]
+fn test_gs1_digital_link_variants() {
+    // any domain and path prefix, a GTIN-13 padded to 14 digits
+    let ParsedClientResult::GS1DigitalLinkResult(link) =
+        parse("https://example.com/products/01/9506000134352/21/XYZ#top")
+    else {
+        panic!("Expected GS1DigitalLinkResult");
+    };
+    assert_eq!("https://example.com/products", link.getDomain());
+    assert_eq!(
+        pairs(&[("01", "09506000134352"), ("21", "XYZ")]),
+        link.getAIs()
+    );
+
+    // GS1 Digital Link 1.0 short names
+    let ParsedClientResult::GS1DigitalLinkResult(link) =
+        parse("http://example.com/gtin/09506000134352/lot/ABC")
+    else {
+        panic!("Expected GS1DigitalLinkResult");
+    };
+    assert_eq!(Some("ABC"), link.getAI("10"));
+
+    let ParsedClientResult::GS1DigitalLinkResult(link) =
+        parse("https://id.gs1.org/414/9520123456788/254/32a%2Fb")
+    else {
+        panic!("Expected GS1DigitalLinkResult");
+    };
+    assert_eq!(("414", "9520123456788"), link.getPrimaryKey());
+    assert_eq!(Some("32a/b"), link.getAI("254"));
+}
+
+#[test]
+fn test_not_gs1_digital_link() {
+    // wrong check digit
+    let result = parse("https://id.gs1.org/01/09506000134353");
+    assert_eq!(ParsedRXingResultType::Uri, result.getType());
+    // qualifiers out of order
+    let result = parse("https://id.gs1.org/01/09506000134352/21/S/10/L");
+    assert_eq!(ParsedRXingResultType::Uri, result.getType());
+    // no primary key
+    let result = parse("https://example.com/2024/01/05");
+    assert_eq!(ParsedRXingResultType::Uri, result.getType());
+    let result = parse("ftp://id.gs1.org/01/09506000134352");
+    assert_ne!(ParsedRXingResultType::GS1DigitalLink, result.getType());
+}
+
+#[test]
+fn test_build_gs1_digital_link() {
+    let link = GS1DigitalLinkBuilder::new()
+        .with_domain("https://example.com/dl/")
+        .with_ai("21", "S 1")
+        .with_ai("3103", "000500")
+        .with_ai("01", "09506000134352")
+        .with_ai("22", "2A")
+        .with_parameter("linkType", "gs1:pip")
+        .build()
+        .unwrap();
+    assert_eq!(
+        "https://example.com/dl/01/09506000134352/22/2A/21/S%201?3103=000500&linkType=gs1%3Apip",
+        link.getURI()
+    );
+    let ParsedClientResult::GS1DigitalLinkResult(parsed) = parse(link.getURI()) else {
+        panic!("Expected GS1DigitalLinkResult");
+    };
+    assert_eq!(link, parsed);
+
+    let no_key = GS1DigitalLinkBuilder::new().with_ai("10", "ABC").build();
+    assert!(no_key.is_err());
+    let two_keys = GS1DigitalLinkBuilder::new()
+        .with_ai("01", "09506000134352")
+        .with_ai("00", "106141412345678908")
+        .build();
+    assert!(two_keys.is_err());
+    let check_digit = GS1DigitalLinkBuilder::new()
+        .with_ai("01", "09506000134353")
+        .build();
+    assert!(check_digit.is_err());
+    let not_ai = GS1DigitalLinkBuilder::new()
+        .with_ai("01", "09506000134352")
+        .with_ai("lot", "ABC")
+        .build();
+    assert!(not_ai.is_err());
+
+    assert!(is_gs1_check_digit_valid("106141412345678908"));
+    assert!(!is_gs1_check_digit_valid("106141412345678907"));
+}
+
+#[test]
+fn test_encode_gs1_digital_link() {
+    let link = GS1DigitalLinkBuilder::new()
+        .with_ai("01", "09506000134352")
+        .with_ai("10", "ABC123")
+        .build()
+        .unwrap();
+    let matrix = encode_gs1_digital_link(&link, 200, 200, 4).unwrap();
+
+    let mut hints = DecodeHints {
+        PossibleFormats: Some(HashSet::from([BarcodeFormat::QR_CODE])),
+        ..Default::default()
+    };
+    let decoded = helpers::detect_in_luma_with_hints(
+        matrix.to_luma8(),
+        matrix.getWidth(),
+        matrix.getHeight(),
+        Some(BarcodeFormat::QR_CODE),
+        &mut hints,
+    )
+    .unwrap();
+    assert_eq!(link.getURI(), decoded.getText());
+}
@@ -0,0 +1,106 @@
+/*
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use urlencoding::decode;
+
+use crate::{
+    client::result::{GS1DigitalLinkParsedRXingResult, ParsedClientResult},
+    RXingResult,
+};
+
+use super::{
+    GS1DigitalLinkParsedResult::{is_ai, normalize_ai_value, primary_key, qualifier},
+    ResultParser,
+};
+
+/**
+ * <p>Parses a GS1 Digital Link URI, an http or https address whose path holds a GS1
+ * primary key and its key qualifiers, and whose query string holds further AIs:</p>
+ *
+ * <pre>
+ * https://example.com/shop/01/09506000134352/10/ABC123?17=251231&amp;linkType=gs1:pip
+ * \___________________/\__________________/\________/ \_______/ \______________/
+ *        domain            primary key      qualifier    AI      other parameter
+ * </pre>
+ *
+ * <p>Any host and path prefix are allowed. The primary key and qualifiers may also be given
+ * by their GS1 Digital Link 1.0 short names, such as <code>gtin</code> and <code>lot</code>.
+ * A GTIN-8, -12 or -13 is padded to 14 digits. URIs whose qualifiers are out of order, or
+ * whose keys have a wrong check digit, are not parsed.</p>
+ */
+pub fn parse(result: &RXingResult) -> Option<ParsedClientResult> {
+    let raw_text = ResultParser::getMassagedText(result);
+    let (scheme, rest) = raw_text.split_once("://")?;
+    if !scheme.eq_ignore_ascii_case("http") && !scheme.eq_ignore_ascii_case("https") {
+        return None;
+    }
+    let rest = rest.split('#').next().unwrap_or_default();
+    let (rest, query) = rest.split_once('?').unwrap_or((rest, ""));
+    let (authority, path) = rest.split_once('/').unwrap_or((rest, ""));
+    if authority.is_empty() {
+        return None;
+    }
+
+    let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    let (start, mut ais) =
+        (0..segments.len()).find_map(|start| Some((start, parse_path(&segments[start..])?)))?;
+    let prefix: String = segments[..start].iter().map(|s| format!("/{s}")).collect();
+    let domain = format!("{scheme}://{authority}{prefix}");
+
+    let mut other_parameters = Vec::new();
+    for parameter in query.split('&').filter(|p| !p.is_empty()) {
+        let (key, value) = parameter.split_once('=').unwrap_or((parameter, ""));
+        let (key, value) = (decode(key).ok()?, decode(value).ok()?);
+        if is_ai(&key) {
+            if ais.iter().any(|(ai, _)| *ai == key) {
+                return None;
+            }
+            let value = normalize_ai_value(&key, &value)?;
+            ais.push((key.into_owned(), value));
+        } else {
+            other_parameters.push((key.into_owned(), value.into_owned()));
+        }
+    }
+
+    Some(ParsedClientResult::GS1DigitalLinkResult(
+        GS1DigitalLinkParsedRXingResult::new(raw_text, domain, ais, other_parameters),
+    ))
+}
+
+/// Parses a primary key and its qualifiers, the segments of the path that follow the domain
+fn parse_path(segments: &[&str]) -> Option<Vec<(String, String)>> {
+    let (key, value) = match segments {
+        [key, value, ..] => (*key, *value),
+        _ => return None,
+    };
+    let (key, qualifiers) = primary_key(key)?;
+    let mut ais = vec![(
+        key.to_owned(),
+        normalize_ai_value(key, &decode(value).ok()?)?,
+    )];
+
+    let mut allowed = qualifiers;
+    for pair in segments[2..].chunks(2) {
+        let [ai, value] = pair else {
+            return None;
+        };
+        let ai = qualifier(ai);
+        // each qualifier may only follow the ones before it in the order of the key
+        let position = allowed.iter().position(|q| *q == ai)?;
+        allowed = &allowed[position + 1..];
+        ais.push((ai.to_owned(), normalize_ai_value(ai, &decode(value).ok()?)?));
+    }
+
+    Some(ais)
+}
@@ -0,0 +1,188 @@
+/*
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use urlencoding::encode;
+
+use crate::{
+    common::{BitMatrix, Result},
+    qrcode::QRCodeWriter,
+    BarcodeFormat, EncodeHints, Exceptions, Writer,
+};
+
+use super::{
+    GS1DigitalLinkParsedRXingResult,
+    GS1DigitalLinkParsedResult::{is_ai, normalize_ai_value, primary_key},
+    GS1_DIGITAL_LINK_DEFAULT_DOMAIN,
+};
+
+/**
+ * Builds a GS1 Digital Link URI from AI data, for retail QR codes that replace a plain GTIN.
+ *
+ * ```
+ * use rxing::client::result::GS1DigitalLinkBuilder;
+ *
+ * let link = GS1DigitalLinkBuilder::new()
+ *     .with_ai("01", "9506000134352")
+ *     .with_ai("17", "251231")
+ *     .with_ai("10", "ABC/1")
+ *     .build()?;
+ * assert_eq!(
+ *     "https://id.gs1.org/01/09506000134352/10/ABC%2F1?17=251231",
+ *     link.getURI()
+ * );
+ * # Ok::<(), rxing::Exceptions>(())
+ * ```
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GS1DigitalLinkBuilder {
+    domain: String,
+    ais: Vec<(String, String)>,
+    other_parameters: Vec<(String, String)>,
+}
+
+impl Default for GS1DigitalLinkBuilder {
+    fn default() -> Self {
+        Self {
+            domain: GS1_DIGITAL_LINK_DEFAULT_DOMAIN.to_owned(),
+            ais: Vec::new(),
+            other_parameters: Vec::new(),
+        }
+    }
+}
+
+impl GS1DigitalLinkBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The scheme, host and optional path prefix of the URI, `https://id.gs1.org` by default
+    pub fn with_domain(mut self, domain: &str) -> Self {
+        self.domain = domain.trim_end_matches('/').to_owned();
+        self
+    }
+
+    /// Adds an AI, in any order: the primary key and its qualifiers go in the path
+    pub fn with_ai(mut self, ai: &str, value: &str) -> Self {
+        self.ais.push((ai.to_owned(), value.to_owned()));
+        self
+    }
+
+    /// Adds a query parameter that is not an AI, such as `linkType`
+    pub fn with_parameter(mut self, key: &str, value: &str) -> Self {
+        self.other_parameters
+            .push((key.to_owned(), value.to_owned()));
+        self
+    }
+
+    /**
+     * Validates the AIs and builds the URI. The path holds the primary key and its key
+     * qualifiers in the order of GS1 Digital Link, the query the other AIs in the order they
+     * were added, then the other parameters. Values are percent-encoded.
+     *
+     * @return the link, or an error if there is not exactly one primary key, an AI is given
+     *  twice or is not 2 to 4 digits, or a key has a wrong length or check digit
+     */
+    pub fn build(&self) -> Result<GS1DigitalLinkParsedRXingResult> {
+        if !self.domain.starts_with("http://") && !self.domain.starts_with("https://") {
+            return Err(Exceptions::illegal_argument_with(format!(
+                "domain {} is not an http or https URI",
+                self.domain
+            )));
+        }
+
+        let mut ais = Vec::with_capacity(self.ais.len());
+        for (ai, value) in &self.ais {
+            if !is_ai(ai) {
+                return Err(Exceptions::illegal_argument_with(format!(
+                    "{ai} is not an AI"
+                )));
+            }
+            if ais.iter().any(|(key, _): &(String, String)| key == ai) {
+                return Err(Exceptions::illegal_argument_with(format!(
+                    "AI {ai} is given twice"
+                )));
+            }
+            let value = normalize_ai_value(ai, value).ok_or_else(|| {
+                Exceptions::illegal_argument_with(format!("invalid value {value} for AI {ai}"))
+            })?;
+            ais.push((ai.clone(), value));
+        }
+
+        let mut keys = ais.iter().filter_map(|(ai, _)| primary_key(ai));
+        let (key, qualifiers) = match (keys.next(), keys.next()) {
+            (Some(key), None) => key,
+            (None, _) => return Err(Exceptions::illegal_argument_with("no primary key AI")),
+            (Some(_), Some(_)) => {
+                return Err(Exceptions::illegal_argument_with(
+                    "more than one primary key AI",
+                ))
+            }
+        };
+
+        // the primary key, then its qualifiers in their order, then the data attributes
+        let in_path = |ai: &str| ai == key || qualifiers.contains(&ai);
+        let mut path: Vec<_> = ais.iter().filter(|(ai, _)| in_path(ai)).cloned().collect();
+        path.sort_by_key(|(ai, _)| qualifiers.iter().position(|q| q == ai));
+        let query: Vec<_> = ais.iter().filter(|(ai, _)| !in_path(ai)).cloned().collect();
+
+        let mut uri = self.domain.clone();
+        for (ai, value) in &path {
+            uri.push_str(&format!("/{ai}/{}", encode(value)));
+        }
+        let parameters: Vec<String> = query
+            .iter()
+            .chain(&self.other_parameters)
+            .map(|(key, value)| format!("{}={}", encode(key), encode(value)))
+            .collect();
+        if !parameters.is_empty() {
+            uri.push('?');
+            uri.push_str(&parameters.join("&"));
+        }
+
+        path.extend(query);
+        Ok(GS1DigitalLinkParsedRXingResult::new(
+            uri,
+            self.domain.clone(),
+            path,
+            self.other_parameters.clone(),
+        ))
+    }
+}
+
+/**
+ * Encodes a GS1 Digital Link into a QR code with error correction level M, as the GS1
+ * General Specifications recommend.
+ *
+ * `width` and `height` are the requested output size in pixels; `quiet_zone` is the margin
+ * in modules, at least 4 for a QR code.
+ */
+pub fn encode_gs1_digital_link(
+    link: &GS1DigitalLinkParsedRXingResult,
+    width: i32,
+    height: i32,
+    quiet_zone: i32,
+) -> Result<BitMatrix> {
+    let hints = EncodeHints {
+        ErrorCorrection: Some("M".to_owned()),
+        Margin: Some(quiet_zone.to_string()),
+        ..Default::default()
+    };
+    QRCodeWriter.encode_with_hints(
+        link.getURI(),
+        &BarcodeFormat::QR_CODE,
+        width,
+        height,
+        &hints,
+    )
+}
@@ -0,0 +1,614 @@
+/*
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::fmt;
+
+use chrono::{Datelike, NaiveDate, Utc};
+
+use crate::{common::Result, Exceptions};
+
+use super::{is_gs1_check_digit_valid, ParsedRXingResult, ParsedRXingResultType};
+
+/// The group separator that stands for FNC1 after an element of variable length
+pub const GS1_GROUP_SEPARATOR: char = '\u{1d}';
+
+pub use crate::GS1_SYMBOLOGY_IDENTIFIERS;
+
+/**
+ * The AIs of the GS1 General Specifications, their data titles and the format of their values
+ * in the notation of the GS1 syntax dictionary: `N` digits and `X` characters of GS1 AI
+ * encodable character set 82, a fixed number of them or up to `..` a number, each component
+ * optionally checked by `csum` (a GS1 check digit) or `yymmd0` (a date, day 00 meaning the last
+ * day of the month). An `n` in an AI is the position of the decimal point in its value, and
+ * the components of variable length after the first may be left out.
+ */
+const AI_DEFINITIONS: [(&str, &str, &str); 174] = [
+    ("00", "SSCC", "N18,csum"),
+    ("01", "GTIN", "N14,csum"),
+    ("02", "CONTENT", "N14,csum"),
+    ("03", "MTO GTIN", "N14,csum"),
+    ("10", "BATCH/LOT", "X..20"),
+    ("11", "PROD DATE", "N6,yymmd0"),
+    ("12", "DUE DATE", "N6,yymmd0"),
+    ("13", "PACK DATE", "N6,yymmd0"),
+    ("15", "BEST BEFORE or BEST BY", "N6,yymmd0"),
+    ("16", "SELL BY", "N6,yymmd0"),
+    ("17", "USE BY OR EXPIRY", "N6,yymmd0"),
+    ("20", "VARIANT", "N2"),
+    ("21", "SERIAL", "X..20"),
+    ("22", "CPV", "X..20"),
+    ("235", "TPX", "X..28"),
+    ("240", "ADDITIONAL ID", "X..30"),
+    ("241", "CUST. PART No.", "X..30"),
+    ("242", "MTO VARIANT", "N..6"),
+    ("243", "PCN", "X..20"),
+    ("250", "SECONDARY SERIAL", "X..30"),
+    ("251", "REF. TO SOURCE", "X..30"),
+    ("253", "GDTI", "N13,csum+X..17"),
+    ("254", "GLN EXTENSION COMPONENT", "X..20"),
+    ("255", "GCN", "N13,csum+N..12"),
+    ("30", "VAR. COUNT", "N..8"),
+    ("310n", "NET WEIGHT (kg)", "N6"),
+    ("311n", "LENGTH (m)", "N6"),
+    ("312n", "WIDTH (m)", "N6"),
+    ("313n", "HEIGHT (m)", "N6"),
+    ("314n", "AREA (m²)", "N6"),
+    ("315n", "NET VOLUME (l)", "N6"),
+    ("316n", "NET VOLUME (m³)", "N6"),
+    ("320n", "NET WEIGHT (lb)", "N6"),
+    ("321n", "LENGTH (in)", "N6"),
+    ("322n", "LENGTH (ft)", "N6"),
+    ("323n", "LENGTH (yd)", "N6"),
+    ("324n", "WIDTH (in)", "N6"),
+    ("325n", "WIDTH (ft)", "N6"),
+    ("326n", "WIDTH (yd)", "N6"),
+    ("327n", "HEIGHT (in)", "N6"),
+    ("328n", "HEIGHT (ft)", "N6"),
+    ("329n", "HEIGHT (yd)", "N6"),
+    ("330n", "GROSS WEIGHT (kg)", "N6"),
+    ("331n", "LENGTH (m), log", "N6"),
+    ("332n", "WIDTH (m), log", "N6"),
+    ("333n", "HEIGHT (m), log", "N6"),
+    ("334n", "AREA (m²), log", "N6"),
+    ("335n", "VOLUME (l), log", "N6"),
+    ("336n", "VOLUME (m³), log", "N6"),
+    ("337n", "KG PER m²", "N6"),
+    ("340n", "GROSS WEIGHT (lb)", "N6"),
+    ("341n", "LENGTH (in), log", "N6"),
+    ("342n", "LENGTH (ft), log", "N6"),
+    ("343n", "LENGTH (yd), log", "N6"),
+    ("344n", "WIDTH (in), log", "N6"),
+    ("345n", "WIDTH (ft), log", "N6"),
+    ("346n", "WIDTH (yd), log", "N6"),
+    ("347n", "HEIGHT (in), log", "N6"),
+    ("348n", "HEIGHT (ft), log", "N6"),
+    ("349n", "HEIGHT (yd), log", "N6"),
+    ("350n", "AREA (in²)", "N6"),
+    ("351n", "AREA (ft²)", "N6"),
+    ("352n", "AREA (yd²)", "N6"),
+    ("353n", "AREA (in²), log", "N6"),
+    ("354n", "AREA (ft²), log", "N6"),
+    ("355n", "AREA (yd²), log", "N6"),
+    ("356n", "NET WEIGHT (t oz)", "N6"),
+    ("357n", "NET VOLUME (oz)", "N6"),
+    ("360n", "NET VOLUME (qt)", "N6"),
+    ("361n", "NET VOLUME (gal.)", "N6"),
+    ("362n", "VOLUME (qt), log", "N6"),
+    ("363n", "VOLUME (gal.), log", "N6"),
+    ("364n", "VOLUME (in³)", "N6"),
+    ("365n", "VOLUME (ft³)", "N6"),
+    ("366n", "VOLUME (yd³)", "N6"),
+    ("367n", "VOLUME (in³), log", "N6"),
+    ("368n", "VOLUME (ft³), log", "N6"),
+    ("369n", "VOLUME (yd³), log", "N6"),
+    ("37", "COUNT", "N..8"),
+    ("390n", "AMOUNT", "N..15"),
+    ("391n", "AMOUNT", "N3+N..15"),
+    ("392n", "PRICE", "N..15"),
+    ("393n", "PRICE", "N3+N..15"),
+    ("394n", "PRCNT OFF", "N4"),
+    ("395n", "PRICE/UoM", "N6"),
+    ("400", "ORDER NUMBER", "X..30"),
+    ("401", "GINC", "X..30"),
+    ("402", "GSIN", "N17,csum"),
+    ("403", "ROUTE", "X..30"),
+    ("410", "SHIP TO LOC", "N13,csum"),
+    ("411", "BILL TO", "N13,csum"),
+    ("412", "PURCHASE FROM", "N13,csum"),
+    ("413", "SHIP FOR LOC", "N13,csum"),
+    ("414", "LOC No.", "N13,csum"),
+    ("415", "PAY TO", "N13,csum"),
+    ("416", "PROD/SERV LOC", "N13,csum"),
+    ("417", "PARTY", "N13,csum"),
+    ("420", "SHIP TO POST", "X..20"),
+    ("421", "SHIP TO POST", "N3+X..9"),
+    ("422", "ORIGIN", "N3"),
+    ("423", "COUNTRY - INITIAL PROCESS", "N3+N..12"),
+    ("424", "COUNTRY - PROCESS", "N3"),
+    ("425", "COUNTRY - DISASSEMBLY", "N3+N..12"),
+    ("426", "COUNTRY - FULL PROCESS", "N3"),
+    ("427", "ORIGIN SUBDIVISION", "X..3"),
+    ("4300", "SHIP TO COMP", "X..35"),
+    ("4301", "SHIP TO NAME", "X..35"),
+    ("4302", "SHIP TO ADD1", "X..70"),
+    ("4303", "SHIP TO ADD2", "X..70"),
+    ("4304", "SHIP TO SUB", "X..70"),
+    ("4305", "SHIP TO LOC", "X..70"),
+    ("4306", "SHIP TO REG", "X..70"),
+    ("4307", "SHIP TO COUNTRY", "X2"),
+    ("4308", "SHIP TO PHONE", "X..30"),
+    ("4309", "SHIP TO GEO", "N20"),
+    ("4310", "RTN TO COMP", "X..35"),
+    ("4311", "RTN TO NAME", "X..35"),
+    ("4312", "RTN TO ADD1", "X..70"),
+    ("4313", "RTN TO ADD2", "X..70"),
+    ("4314", "RTN TO SUB", "X..70"),
+    ("4315", "RTN TO LOC", "X..70"),
+    ("4316", "RTN TO REG", "X..70"),
+    ("4317", "RTN TO COUNTRY", "X2"),
+    ("4318", "RTN TO POST", "X..20"),
+    ("4319", "RTN TO PHONE", "X..30"),
+    ("4320", "SRV DESCRIPTION", "X..35"),
+    ("4321", "DANGEROUS GOODS", "N1"),
+    ("4322", "AUTH LEAVE", "N1"),
+    ("4323", "SIG REQUIRED", "N1"),
+    ("4324", "NBEF DEL DT", "N6,yymmd0+N4"),
+    ("4325", "NAFT DEL DT", "N6,yymmd0+N4"),
+    ("4326", "REL DATE", "N6,yymmd0"),
+    ("7001", "NSN", "N13"),
+    ("7002", "MEAT CUT", "X..30"),
+    ("7003", "EXPIRY TIME", "N6,yymmd0+N4"),
+    ("7004", "ACTIVE POTENCY", "N..4"),
+    ("7005", "CATCH AREA", "X..12"),
+    ("7006", "FIRST FREEZE DATE", "N6,yymmd0"),
+    ("7007", "HARVEST DATE", "N6,yymmd0+N..6"),
+    ("7008", "AQUATIC SPECIES", "X..3"),
+    ("7009", "FISHING GEAR TYPE", "X..10"),
+    ("7010", "PROD METHOD", "X..2"),
+    ("7020", "REFURB LOT", "X..20"),
+    ("7021", "FUNC STAT", "X..20"),
+    ("7022", "REV STAT", "X..20"),
+    ("7023", "GIAI - ASSEMBLY", "X..30"),
+    ("703n", "PROCESSOR #", "N3+X..27"),
+    ("7040", "UIC+EXT", "N1+X3"),
+    ("710", "NHRN PZN", "X..20"),
+    ("711", "NHRN CIP", "X..20"),
+    ("712", "NHRN CN", "X..20"),
+    ("713", "NHRN DRN", "X..20"),
+    ("714", "NHRN AIM", "X..20"),
+    ("8001", "DIMENSIONS", "N14"),
+    ("8002", "CMT No.", "X..20"),
+    ("8003", "GRAI", "N14,csum+X..16"),
+    ("8004", "GIAI", "X..30"),
+    ("8005", "PRICE PER UNIT", "N6"),
+    ("8006", "ITIP", "N14,csum+N4"),
+    ("8007", "IBAN", "X..34"),
+    ("8008", "PROD TIME", "N8+N..4"),
+    ("8009", "OPTSEN", "X..50"),
+    ("8010", "CPID", "X..30"),
+    ("8011", "CPID SERIAL", "N..12"),
+    ("8012", "VERSION", "X..20"),
+    ("8013", "GMN", "X..25"),
+    ("8017", "GSRN - PROVIDER", "N18,csum"),
+    ("8018", "GSRN - RECIPIENT", "N18,csum"),
+    ("8019", "SRIN", "N..10"),
+    ("8020", "REF No.", "X..25"),
+    ("8026", "ITIP CONTENT", "N14,csum+N4"),
+    ("8200", "PRODUCT URL", "X..70"),
+    ("90", "INTERNAL", "X..30"),
+    ("9n", "INTERNAL", "X..90"),
+];
+
+/**
+ * A number of an AI whose last digit is the position of the decimal point, such as the net
+ * weight of AI 3103 or the price of AI 3922.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GS1Decimal {
+    /// The digits of the number, without the decimal point
+    pub value: u64,
+    /// The number of digits after the decimal point
+    pub decimals: u32,
+}
+
+impl GS1Decimal {
+    pub fn as_f64(&self) -> f64 {
+        self.value as f64 / 10f64.powi(self.decimals as i32)
+    }
+}
+
+impl fmt::Display for GS1Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let scale = 10u64.pow(self.decimals);
+        if self.decimals == 0 {
+            write!(f, "{}", self.value)
+        } else {
+            write!(
+                f,
+                "{}.{:0width$}",
+                self.value / scale,
+                self.value % scale,
+                width = self.decimals as usize
+            )
+        }
+    }
+}
+
+/**
+ * Represents a parsed result that holds GS1 element strings, the application identifier (AI)
+ * data of GS1-128, GS1 DataBar, GS1 Data Matrix and GS1 QR Code symbols, such as
+ * `(01)09506000134352(17)251231(10)ABC123`.
+ *
+ * The AIs are kept in the order of the data, with their values checked against the format of
+ * their AI: their length, characters, check digits and dates.
+ */
+#[derive(PartialEq, Eq, Hash, Debug, Clone)]
+pub struct GS1ParsedRXingResult {
+    ais: Vec<(String, String)>,
+}
+
+impl ParsedRXingResult for GS1ParsedRXingResult {
+    fn getType(&self) -> ParsedRXingResultType {
+        ParsedRXingResultType::GS1
+    }
+
+    fn getDisplayRXingResult(&self) -> String {
+        self.getElementString()
+    }
+}
+
+impl GS1ParsedRXingResult {
+    pub fn new(ais: Vec<(String, String)>) -> Self {
+        Self { ais }
+    }
+
+    /**
+     * Parses GS1 element strings, either as they are encoded, AIs and values one after the
+     * other and elements of variable length ended by a group separator (FNC1), or in human
+     * readable form, the AIs in parentheses. A leading GS1 symbology identifier is skipped.
+     *
+     * # Errors
+     * `FormatException` if an AI is unknown or a value does not fit the format of its AI
+     */
+    pub fn parse(data: &str) -> Result<Self> {
+        let data = GS1_SYMBOLOGY_IDENTIFIERS
+            .iter()
+            .find_map(|identifier| data.strip_prefix(identifier))
+            .unwrap_or(data);
+        let data = data.strip_prefix(GS1_GROUP_SEPARATOR).unwrap_or(data);
+        if data.is_empty() {
+            return Err(Exceptions::format_with("no GS1 element strings"));
+        }
+        let ais = if data.starts_with('(') {
+            parse_human_readable(data)?
+        } else {
+            parse_element_strings(data)?
+        };
+        Ok(Self::new(ais))
+    }
+
+    /// The AIs and their values, in the order of the data
+    pub fn getAIs(&self) -> &[(String, String)] {
+        &self.ais
+    }
+
+    /// The value of an AI, e.g. `getAI("10")` for the batch or lot number
+    pub fn getAI(&self, ai: &str) -> Option<&str> {
+        self.ais
+            .iter()
+            .find(|(key, _)| key == ai)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// The GTIN, as 14 digits
+    pub fn getGTIN(&self) -> Option<&str> {
+        self.getAI("01")
+    }
+
+    /// The SSCC, as 18 digits
+    pub fn getSSCC(&self) -> Option<&str> {
+        self.getAI("00")
+    }
+
+    /// The batch or lot number
+    pub fn getBatch(&self) -> Option<&str> {
+        self.getAI("10")
+    }
+
+    /// The serial number
+    pub fn getSerial(&self) -> Option<&str> {
+        self.getAI("21")
+    }
+
+    /// The expiration date, AI 17
+    pub fn getExpiry(&self) -> Option<NaiveDate> {
+        self.getDate("17")
+    }
+
+    /// The best before date, AI 15
+    pub fn getBestBefore(&self) -> Option<NaiveDate> {
+        self.getDate("15")
+    }
+
+    /**
+     * The date of an AI whose value starts with one, such as the production date of AI 11. A day
+     * of 00 stands for the last day of the month, and the century is the one that puts the date
+     * within 49 years before and 50 years after the current year.
+     */
+    pub fn getDate(&self, ai: &str) -> Option<NaiveDate> {
+        let value = self.getAI(ai)?;
+        let (_, _, format) = definition(ai)?;
+        if !format.starts_with("N6,yymmd0") {
+            return None;
+        }
+        gs1_date(&value[..6], Utc::now().year())
+    }
+
+    /**
+     * The number of an AI whose last digit is the position of the decimal point, such as
+     * `getDecimal("310")` for the net weight in kilograms.
+     */
+    pub fn getDecimal(&self, ai_prefix: &str) -> Option<GS1Decimal> {
+        if ai_prefix.len() != 3 {
+            return None;
+        }
+        let (ai, value) = self
+            .ais
+            .iter()
+            .find(|(ai, _)| ai.len() == 4 && ai.starts_with(ai_prefix))?;
+        let (_, _, format) = definition(ai)?;
+        // the amounts and prices with a currency start with its ISO 4217 code
+        let digits = if format.starts_with("N3+") {
+            &value[3..]
+        } else {
+            value.as_str()
+        };
+        Some(GS1Decimal {
+            value: digits.parse().ok()?,
+            decimals: (ai.as_bytes()[3] - b'0') as u32,
+        })
+    }
+
+    /// The net weight, and its unit, `kg` or `lb`
+    pub fn getNetWeight(&self) -> Option<(GS1Decimal, &'static str)> {
+        self.getDecimal("310")
+            .map(|weight| (weight, "kg"))
+            .or_else(|| self.getDecimal("320").map(|weight| (weight, "lb")))
+    }
+
+    /// The gross weight, and its unit, `kg` or `lb`
+    pub fn getGrossWeight(&self) -> Option<(GS1Decimal, &'static str)> {
+        self.getDecimal("330")
+            .map(|weight| (weight, "kg"))
+            .or_else(|| self.getDecimal("340").map(|weight| (weight, "lb")))
+    }
+
+    /// The data title of an AI, e.g. `BATCH/LOT` for AI 10
+    pub fn getDataTitle(ai: &str) -> Option<&'static str> {
+        definition(ai).map(|&(_, title, _)| title)
+    }
+
+    /// The AIs as a GS1 element string in human readable form, e.g. `(01)09506000134352(10)ABC`
+    pub fn getElementString(&self) -> String {
+        self.ais
+            .iter()
+            .map(|(ai, value)| format!("({ai}){value}"))
+            .collect()
+    }
+
+    /// The AIs as a GS1 element string as it is encoded, each element of variable length but
+    /// the last ended by `separator`, e.g. the FNC1 escape of a writer or [`GS1_GROUP_SEPARATOR`]
+    pub fn getEncodedElementString(&self, separator: char) -> String {
+        let mut encoded = String::new();
+        for (index, (ai, value)) in self.ais.iter().enumerate() {
+            encoded.push_str(ai);
+            encoded.push_str(value);
+            if index + 1 < self.ais.len() && !is_predefined_length(ai) {
+                encoded.push(separator);
+            }
+        }
+        encoded
+    }
+}
+
+/// The definition of the AI that `ai` is, matching the decimal position `n` of an AI
+fn definition(ai: &str) -> Option<&'static (&'static str, &'static str, &'static str)> {
+    AI_DEFINITIONS.iter().find(|(pattern, _, _)| {
+        pattern.len() == ai.len()
+            && pattern
+                .bytes()
+                .zip(ai.bytes())
+                .all(|(p, a)| p == a || (p == b'n' && a.is_ascii_digit()))
+    })
+}
+
+/// The definition of the AI that `data` starts with
+fn definition_at(data: &str) -> Option<&'static (&'static str, &'static str, &'static str)> {
+    (2..=4)
+        .filter(|length| {
+            data.len() >= *length && data.as_bytes()[..*length].iter().all(u8::is_ascii_digit)
+        })
+        .find_map(|length| definition(&data[..length]))
+}
+
+/// Whether an element string of `ai` ends where its value ends, without a group separator
+fn is_predefined_length(ai: &str) -> bool {
+    [
+        "00", "01", "02", "03", "04", "11", "12", "13", "14", "15", "16", "17", "18", "19", "20",
+        "31", "32", "33", "34", "35", "36", "41",
+    ]
+    .contains(&&ai[..2])
+}
+
+/// The least and most characters of a value of `format`
+fn value_length(format: &str) -> (usize, usize) {
+    format
+        .split('+')
+        .map(|component| component.split(',').next().unwrap_or_default())
+        .enumerate()
+        .fold((0, 0), |(min, max), (i, component)| {
+            let (variable, length) = match component[1..].strip_prefix("..") {
+                Some(length) => (true, length),
+                None => (false, &component[1..]),
+            };
+            let length: usize = length.parse().unwrap_or_default();
+            // components of variable length after the first are optional
+            let least = match (i, variable) {
+                (_, false) => length,
+                (0, true) => 1,
+                _ => 0,
+            };
+            (min + least, max + length)
+        })
+}
+
+/// Checks `value` against the format of `ai`
+fn validate(ai: &str, format: &str, value: &str) -> Result<()> {
+    let invalid = || Exceptions::format_with(format!("invalid value for AI {ai}: {value}"));
+    let (min, max) = value_length(format);
+    if !value.is_ascii() || value.len() < min || value.len() > max {
+        return Err(invalid());
+    }
+
+    let mut rest = value;
+    for component in format.split('+') {
+        if rest.is_empty() {
+            break;
+        }
+        let mut parts = component.split(',');
+        let kind = parts.next().unwrap_or_default();
+        let length = match kind[1..].strip_prefix("..") {
+            Some(length) => length.parse().unwrap_or_default(),
+            None => kind[1..].parse().unwrap_or_default(),
+        };
+        let (part, remainder) = rest.split_at(rest.len().min(length));
+        rest = remainder;
+
+        let characters_valid = if kind.starts_with('N') {
+            part.bytes().all(|b| b.is_ascii_digit())
+        } else {
+            part.chars().all(is_encodable)
+        };
+        let check_valid = match parts.next() {
+            Some("csum") => is_gs1_check_digit_valid(part),
+            Some("yymmd0") => gs1_date(part, 2000).is_some(),
+            _ => true,
+        };
+        if !characters_valid || !check_valid {
+            return Err(invalid());
+        }
+    }
+    if !rest.is_empty() {
+        return Err(invalid());
+    }
+    Ok(())
+}
+
+/// Whether `c` is in GS1 AI encodable character set 82
+fn is_encodable(c: char) -> bool {
+    c.is_ascii_alphanumeric() || "!\"%&'()*+,-./:;<=>?_".contains(c)
+}
+
+/// The date of a `YYMMDD` value, a day of 00 standing for the last day of the month
+fn gs1_date(yymmdd: &str, current_year: i32) -> Option<NaiveDate> {
+    if yymmdd.len() != 6 || !yymmdd.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let yy: i32 = yymmdd[..2].parse().ok()?;
+    let month: u32 = yymmdd[2..4].parse().ok()?;
+    let day: u32 = yymmdd[4..].parse().ok()?;
+    if month == 0 || month > 12 {
+        return None;
+    }
+
+    // GS1 General Specifications 7.12
+    let century = current_year - current_year.rem_euclid(100);
+    let year = match yy - current_year.rem_euclid(100) {
+        51..=99 => century - 100 + yy,
+        -99..=-50 => century + 100 + yy,
+        _ => century + yy,
+    };
+    if day == 0 {
+        let (next_year, next_month) = if month == 12 {
+            (year + 1, 1)
+        } else {
+            (year, month + 1)
+        };
+        NaiveDate::from_ymd_opt(next_year, next_month, 1)?.pred_opt()
+    } else {
+        NaiveDate::from_ymd_opt(year, month, day)
+    }
+}
+
+/// Parses element strings as they are encoded, variable length values ended by a group separator
+fn parse_element_strings(data: &str) -> Result<Vec<(String, String)>> {
+    let mut ais = Vec::new();
+    let mut rest = data;
+    while !rest.is_empty() {
+        let (pattern, _, format) = definition_at(rest).ok_or_else(|| {
+            Exceptions::format_with(format!("unknown AI at {}", rest.escape_debug()))
+        })?;
+        let ai = &rest[..pattern.len()];
+        rest = &rest[pattern.len()..];
+
+        let (min, max) = value_length(format);
+        let end = if min == max && is_predefined_length(ai) {
+            max.min(rest.len())
+        } else {
+            rest.find(GS1_GROUP_SEPARATOR).unwrap_or(rest.len())
+        };
+        // a fixed length counts bytes, which may end inside a character that isn't ascii
+        let value = rest.get(..end).ok_or_else(|| {
+            Exceptions::format_with(format!(
+                "invalid value for AI {ai}: {}",
+                rest.escape_debug()
+            ))
+        })?;
+        validate(ai, format, value)?;
+        ais.push((ai.to_owned(), value.to_owned()));
+
+        rest = &rest[end..];
+        rest = rest.strip_prefix(GS1_GROUP_SEPARATOR).unwrap_or(rest);
+    }
+    Ok(ais)
+}
+
+/// Parses element strings in human readable form, `(01)09506000134352(10)ABC`
+fn parse_human_readable(data: &str) -> Result<Vec<(String, String)>> {
+    let invalid = || Exceptions::format_with(format!("invalid GS1 element strings: {data}"));
+    let mut ais = Vec::new();
+    let mut rest = data;
+    while let Some(element) = rest.strip_prefix('(') {
+        let (ai, remainder) = element.split_once(')').ok_or_else(invalid)?;
+        let (_, _, format) = definition(ai).ok_or_else(invalid)?;
+        // the value ends where the next AI in parentheses starts
+        let end = remainder
+            .match_indices('(')
+            .map(|(i, _)| i)
+            .find(|i| {
+                remainder[i + 1..]
+                    .split_once(')')
+                    .is_some_and(|(next, _)| definition(next).is_some())
+            })
+            .unwrap_or(remainder.len());
+        let value = &remainder[..end];
+        validate(ai, format, value)?;
+        ais.push((ai.to_owned(), value.to_owned()));
+        rest = &remainder[end..];
+    }
+    if !rest.is_empty() || ais.is_empty() {
+        return Err(invalid());
+    }
+    Ok(ais)
+}
@@ -0,0 +1,158 @@
+/*
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+/**
+ * Tests {@link GS1ParsedRXingResult}.
+ */
+use chrono::NaiveDate;
+
+use crate::{
+    client::result::{
+        GS1Decimal, GS1ParsedRXingResult, ParsedClientResult, ParsedRXingResult,
+        ParsedRXingResultType,
+    },
+    BarcodeFormat, RXingResult, RXingResultMetadataType, RXingResultMetadataValue,
+};
+
+use super::ResultParser;
+
+fn parse(
+    contents: &str,
+    format: BarcodeFormat,
+    symbology_identifier: Option<&str>,
+) -> ParsedClientResult {
+    let mut fake_rxing_result = RXingResult::new(contents, Vec::new(), Vec::new(), format);
+    if let Some(identifier) = symbology_identifier {
+        fake_rxing_result.putMetadata(
+            RXingResultMetadataType::SYMBOLOGY_IDENTIFIER,
+            RXingResultMetadataValue::SymbologyIdentifier(identifier.to_owned()),
+        );
+    }
+    ResultParser::parseRXingResult(&fake_rxing_result)
+}
+
+fn gs1(
+    contents: &str,
+    format: BarcodeFormat,
+    symbology_identifier: Option<&str>,
+) -> GS1ParsedRXingResult {
+    let result = parse(contents, format, symbology_identifier);
+    assert_eq!(ParsedRXingResultType::GS1, result.getType());
+    let ParsedClientResult::GS1Result(gs1) = result else {
+        panic!("Expected GS1Result");
+    };
+    gs1
+}
+
+#[test]
+fn test_element_strings() {
+    let gs1 = gs1(
+        "01095040000591012112345678p901\u{1d}101234567p\u{1d}171411208200http://www.gs1.org/demo/",
+        BarcodeFormat::DATA_MATRIX,
+        Some("]d2"),
+    );
+    assert_eq!(Some("09504000059101"), gs1.getGTIN());
+    assert_eq!(Some("12345678p901"), gs1.getSerial());
+    assert_eq!(Some("1234567p"), gs1.getBatch());
+    assert_eq!(NaiveDate::from_ymd_opt(2014, 11, 20), gs1.getExpiry());
+    assert_eq!(Some("http://www.gs1.org/demo/"), gs1.getAI("8200"));
+    assert_eq!(None, gs1.getSSCC());
+    assert_eq!(None, gs1.getBestBefore());
+    assert_eq!(
+        "(01)09504000059101(21)12345678p901(10)1234567p(17)141120(8200)http://www.gs1.org/demo/",
+        gs1.getDisplayRXingResult()
+    );
+}
+
+#[test]
+fn test_symbology_identifier_prefix() {
+    let gs1 = gs1("]C10109506000134352\u{1d}", BarcodeFormat::CODE_128, None);
+    assert_eq!(Some("09506000134352"), gs1.getGTIN());
+    assert_eq!(1, gs1.getAIs().len());
+}
+
+#[test]
+fn test_human_readable() {
+    let gs1 = gs1(
+        "(01)09506000134352(17)251231(3103)001750(10)ABC",
+        BarcodeFormat::QR_CODE,
+        None,
+    );
+    assert_eq!(Some("09506000134352"), gs1.getGTIN());
+    assert_eq!(NaiveDate::from_ymd_opt(2025, 12, 31), gs1.getExpiry());
+    assert_eq!(Some("ABC"), gs1.getBatch());
+    let (weight, unit) = gs1.getNetWeight().unwrap();
+    assert_eq!(
+        GS1Decimal {
+            value: 1750,
+            decimals: 3
+        },
+        weight
+    );
+    assert_eq!("kg", unit);
+    assert_eq!("1.750", weight.to_string());
+    assert_eq!(1.75, weight.as_f64());
+    assert_eq!(None, gs1.getGrossWeight());
+    assert_eq!(
+        "010950600013435217251231310300175010ABC",
+        gs1.getEncodedElementString('\u{1d}')
+    );
+    let batch_first = GS1ParsedRXingResult::parse("(10)ABC(21)42(01)09506000134352").unwrap();
+    assert_eq!(
+        "10ABC|2142|0109506000134352",
+        batch_first.getEncodedElementString('|')
+    );
+}
+
+#[test]
+fn test_day_zero() {
+    let gs1 = gs1("(15)240200", BarcodeFormat::QR_CODE, None);
+    assert_eq!(NaiveDate::from_ymd_opt(2024, 2, 29), gs1.getBestBefore());
+}
+
+#[test]
+fn test_rss_14() {
+    let gs1 = gs1("04412345678909", BarcodeFormat::RSS_14, None);
+    assert_eq!(Some("04412345678909"), gs1.getGTIN());
+}
+
+#[test]
+fn test_invalid() {
+    // Bad check digit
+    assert!(GS1ParsedRXingResult::parse("(01)09506000134353").is_err());
+    // Unknown AI
+    assert!(GS1ParsedRXingResult::parse("(23)1234").is_err());
+    // Value too short for a fixed length AI
+    assert!(GS1ParsedRXingResult::parse("0109506000134352\u{1d}1725123").is_err());
+    // Invalid date
+    assert!(GS1ParsedRXingResult::parse("(17)251340").is_err());
+    // Fixed length value ending inside a character that is not ascii
+    assert!(GS1ParsedRXingResult::parse("011234567890123é").is_err());
+    // Not a GS1 symbology identifier, or not GS1 data
+    assert_eq!(
+        ParsedRXingResultType::Text,
+        parse("0109506000134352", BarcodeFormat::CODE_128, None).getType()
+    );
+    assert_eq!(
+        ParsedRXingResultType::Text,
+        parse("168901", BarcodeFormat::CODE_128, Some("]C1")).getType()
+    );
+}
+
+#[test]
+fn test_data_title() {
+    assert_eq!(Some("BATCH/LOT"), GS1ParsedRXingResult::getDataTitle("10"));
+    assert_eq!(Some("GTIN"), GS1ParsedRXingResult::getDataTitle("01"));
+    assert_eq!(None, GS1ParsedRXingResult::getDataTitle("23"));
+}
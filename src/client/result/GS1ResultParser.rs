@@ -0,0 +1,53 @@
+/*
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use crate::{BarcodeFormat, RXingResult, RXingResultMetadataType, RXingResultMetadataValue};
+
+use super::{GS1ParsedRXingResult, ParsedClientResult, ResultParser, GS1_SYMBOLOGY_IDENTIFIERS};
+
+/**
+ * <p>Parses the GS1 element strings of symbols that carry them: those whose symbology
+ * identifier says so, such as <code>]d2</code> for GS1 Data Matrix or <code>]C1</code> for
+ * GS1-128, whether it is reported as metadata or starts the text, and GS1 DataBar, whose
+ * text is a GTIN. Element strings in human readable form, with the AIs in parentheses, are
+ * parsed too.</p>
+ *
+ * <p>Data with an unknown AI or a value that does not fit the format of its AI is not
+ * parsed.</p>
+ */
+pub fn parse(result: &RXingResult) -> Option<ParsedClientResult> {
+    let raw_text = ResultParser::getMassagedText(result);
+    let data = if *result.getBarcodeFormat() == BarcodeFormat::RSS_14 {
+        format!("01{raw_text}")
+    } else if raw_text.starts_with('(')
+        || GS1_SYMBOLOGY_IDENTIFIERS
+            .iter()
+            .any(|identifier| raw_text.starts_with(identifier))
+        || matches!(
+            result
+                .getRXingResultMetadata()
+                .get(&RXingResultMetadataType::SYMBOLOGY_IDENTIFIER),
+            Some(RXingResultMetadataValue::SymbologyIdentifier(identifier))
+                if GS1_SYMBOLOGY_IDENTIFIERS.contains(&identifier.as_str())
+        )
+    {
+        raw_text
+    } else {
+        return None;
+    };
+
+    GS1ParsedRXingResult::parse(&data)
+        .ok()
+        .map(ParsedClientResult::GS1Result)
+}
@@ -36,5 +36,9 @@ pub enum ParsedRXingResultType {
     Wifi,
     Isbn,
     Vin,
+    EpcPayment,
+    SwissQRBill,
+    GS1DigitalLink,
+    GS1,
     Other,
 }
@@ -37,9 +37,10 @@ use crate::{common::Result, exceptions::Exceptions, RXingResult};
 
 use super::{
     AddressBookAUResultParser, AddressBookDoCoMoResultParser, BizcardResultParser,
-    BookmarkDoCoMoResultParser, EmailAddressResultParser, EmailDoCoMoResultParser,
-    ExpandedProductResultParser, GeoResultParser, ISBNResultParser, ParsedClientResult,
-    ProductResultParser, SMSMMSResultParser, SMSTOMMSTOResultParser, SMTPResultParser,
+    BookmarkDoCoMoResultParser, EPCPaymentResultParser, EmailAddressResultParser,
+    EmailDoCoMoResultParser, ExpandedProductResultParser, GS1DigitalLinkResultParser,
+    GS1ResultParser, GeoResultParser, ISBNResultParser, ParsedClientResult, ProductResultParser,
+    SMSMMSResultParser, SMSTOMMSTOResultParser, SMTPResultParser, SwissQRBillResultParser,
     TelResultParser, TextParsedRXingResult, URIResultParser, URLTOResultParser, VCardResultParser,
     VEventResultParser, VINResultParser, WifiResultParser,
 };
@@ -133,7 +134,7 @@ pub fn parse_result_with_parser<F: Fn(&RXingResult) -> Option<ParsedClientResult
 }
 
 pub fn parseRXingResult(the_rxing_result: &RXingResult) -> ParsedClientResult {
-    let PARSERS: [&ParserFunction; 20] = [
+    let PARSERS: [&ParserFunction; 24] = [
         &BookmarkDoCoMoResultParser::parse,
         &AddressBookDoCoMoResultParser::parse,
         &EmailDoCoMoResultParser::parse,
@@ -148,11 +149,15 @@ pub fn parseRXingResult(the_rxing_result: &RXingResult) -> ParsedClientResult {
         &SMSTOMMSTOResultParser::parse,
         &GeoResultParser::parse,
         &WifiResultParser::parse,
+        &EPCPaymentResultParser::parse,
+        &SwissQRBillResultParser::parse,
+        &GS1DigitalLinkResultParser::parse,
         &URLTOResultParser::parse,
         &URIResultParser::parse,
         &ISBNResultParser::parse,
         &ProductResultParser::parse,
         &ExpandedProductResultParser::parse,
+        &GS1ResultParser::parse,
         &VINResultParser::parse,
     ];
 
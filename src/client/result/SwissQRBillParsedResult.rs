@@ -0,0 +1,465 @@
+/*
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::fmt;
+
+use crate::{common::Result, Exceptions};
+
+use super::{EPCPaymentResultParser, ParsedRXingResult, ParsedRXingResultType, ResultParser};
+
+pub const SWISS_QR_BILL_QR_TYPE: &str = "SPC";
+pub const SWISS_QR_BILL_VERSION: &str = "0200";
+pub const SWISS_QR_BILL_CODING_TYPE: &str = "1";
+pub const SWISS_QR_BILL_TRAILER: &str = "EPD";
+
+/// Maximum number of characters of a Swiss QR-bill payload
+pub const SWISS_QR_BILL_MAX_LENGTH: usize = 997;
+
+/**
+ * The address of a creditor or debtor on a Swiss QR-bill.
+ *
+ * Structured addresses (type `S`) carry street, building number, postal code and
+ * town in separate fields. Combined addresses (type `K`) carry two free address
+ * lines, where the second line holds postal code and town.
+ */
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Default)]
+pub struct SwissQRBillAddress {
+    pub address_type: SwissQRBillAddressType,
+    pub name: String,
+    pub street_or_address_line_1: String,
+    pub building_number_or_address_line_2: String,
+    pub postal_code: String,
+    pub town: String,
+    pub country: String,
+}
+
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy, Default)]
+pub enum SwissQRBillAddressType {
+    #[default]
+    Structured,
+    Combined,
+}
+
+impl SwissQRBillAddress {
+    pub fn structured(
+        name: &str,
+        street: &str,
+        building_number: &str,
+        postal_code: &str,
+        town: &str,
+        country: &str,
+    ) -> Self {
+        Self {
+            address_type: SwissQRBillAddressType::Structured,
+            name: name.to_owned(),
+            street_or_address_line_1: street.to_owned(),
+            building_number_or_address_line_2: building_number.to_owned(),
+            postal_code: postal_code.to_owned(),
+            town: town.to_owned(),
+            country: country.to_owned(),
+        }
+    }
+
+    pub fn combined(name: &str, address_line_1: &str, address_line_2: &str, country: &str) -> Self {
+        Self {
+            address_type: SwissQRBillAddressType::Combined,
+            name: name.to_owned(),
+            street_or_address_line_1: address_line_1.to_owned(),
+            building_number_or_address_line_2: address_line_2.to_owned(),
+            postal_code: String::default(),
+            town: String::default(),
+            country: country.to_owned(),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.name.is_empty()
+            && self.street_or_address_line_1.is_empty()
+            && self.building_number_or_address_line_2.is_empty()
+            && self.postal_code.is_empty()
+            && self.town.is_empty()
+            && self.country.is_empty()
+    }
+
+    fn validate(&self, role: &str) -> Result<()> {
+        check_length(role, "name", &self.name, 1, 70)?;
+        check_length(role, "country", &self.country, 2, 2)?;
+        if !self.country.chars().all(|c| c.is_ascii_uppercase()) {
+            return Err(Exceptions::format_with(format!(
+                "{role} country must be an ISO 3166 alpha-2 code"
+            )));
+        }
+        match self.address_type {
+            SwissQRBillAddressType::Structured => {
+                check_length(role, "street", &self.street_or_address_line_1, 0, 70)?;
+                check_length(
+                    role,
+                    "building number",
+                    &self.building_number_or_address_line_2,
+                    0,
+                    16,
+                )?;
+                check_length(role, "postal code", &self.postal_code, 1, 16)?;
+                check_length(role, "town", &self.town, 1, 35)?;
+            }
+            SwissQRBillAddressType::Combined => {
+                check_length(
+                    role,
+                    "address line 1",
+                    &self.street_or_address_line_1,
+                    0,
+                    70,
+                )?;
+                check_length(
+                    role,
+                    "address line 2",
+                    &self.building_number_or_address_line_2,
+                    1,
+                    70,
+                )?;
+                if !self.postal_code.is_empty() || !self.town.is_empty() {
+                    return Err(Exceptions::format_with(format!(
+                        "{role} combined address must not have postal code or town"
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn push_lines(&self, lines: &mut Vec<String>) {
+        if self.is_empty() {
+            lines.extend(std::iter::repeat_n(String::default(), 7));
+            return;
+        }
+        lines.push(self.address_type.to_string());
+        lines.push(self.name.clone());
+        lines.push(self.street_or_address_line_1.clone());
+        lines.push(self.building_number_or_address_line_2.clone());
+        lines.push(self.postal_code.clone());
+        lines.push(self.town.clone());
+        lines.push(self.country.clone());
+    }
+}
+
+impl fmt::Display for SwissQRBillAddressType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SwissQRBillAddressType::Structured => write!(f, "S"),
+            SwissQRBillAddressType::Combined => write!(f, "K"),
+        }
+    }
+}
+
+/**
+ * The kind of payment reference carried by a Swiss QR-bill.
+ */
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy, Default)]
+pub enum SwissQRBillReferenceType {
+    /// 27 digit QR reference, only allowed (and required) with a QR-IBAN
+    QRR,
+    /// ISO 11649 creditor reference
+    SCOR,
+    /// No reference
+    #[default]
+    NON,
+}
+
+impl fmt::Display for SwissQRBillReferenceType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SwissQRBillReferenceType::QRR => write!(f, "QRR"),
+            SwissQRBillReferenceType::SCOR => write!(f, "SCOR"),
+            SwissQRBillReferenceType::NON => write!(f, "NON"),
+        }
+    }
+}
+
+/**
+ * Represents a parsed Swiss QR-bill ("Swiss Payments Code", `SPC`) payload as
+ * defined by the Swiss Implementation Guidelines for the QR-bill, version 2.
+ *
+ * The same type is used to build payloads: populate the fields, then call
+ * [`SwissQRBillParsedRXingResult::to_payload`] (which validates the bill) or hand
+ * it to `encode_swiss_qr_bill` to get a QR code with the Swiss cross applied.
+ *
+ * Amounts are stored in cents (Rappen) to avoid floating point rounding.
+ */
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Default)]
+pub struct SwissQRBillParsedRXingResult {
+    pub iban: String,
+    pub creditor: SwissQRBillAddress,
+    pub amount_cents: Option<u64>,
+    pub currency: String,
+    pub debtor: Option<SwissQRBillAddress>,
+    pub reference_type: SwissQRBillReferenceType,
+    pub reference: String,
+    pub unstructured_message: String,
+    pub billing_information: String,
+    pub alternative_procedures: Vec<String>,
+}
+
+impl ParsedRXingResult for SwissQRBillParsedRXingResult {
+    fn getType(&self) -> ParsedRXingResultType {
+        ParsedRXingResultType::SwissQRBill
+    }
+
+    fn getDisplayRXingResult(&self) -> String {
+        let mut result = String::with_capacity(100);
+        ResultParser::maybe_append_string(&self.creditor.name, &mut result);
+        ResultParser::maybe_append_string(&self.iban, &mut result);
+        if let Some(amount) = self.getAmountString() {
+            ResultParser::maybe_append_string(&format!("{} {amount}", self.currency), &mut result);
+        }
+        ResultParser::maybe_append_string(&self.reference, &mut result);
+        ResultParser::maybe_append_string(&self.unstructured_message, &mut result);
+        if let Some(debtor) = &self.debtor {
+            ResultParser::maybe_append_string(&debtor.name, &mut result);
+        }
+
+        result
+    }
+}
+
+impl SwissQRBillParsedRXingResult {
+    pub fn new(iban: &str, creditor: SwissQRBillAddress, currency: &str) -> Self {
+        Self {
+            iban: iban.chars().filter(|c| !c.is_whitespace()).collect(),
+            creditor,
+            currency: currency.to_owned(),
+            ..Default::default()
+        }
+    }
+
+    pub fn getIBAN(&self) -> &str {
+        &self.iban
+    }
+
+    pub fn getCreditor(&self) -> &SwissQRBillAddress {
+        &self.creditor
+    }
+
+    /// The amount in cents, `None` if the amount is left blank for the payer to fill in
+    pub fn getAmountInCents(&self) -> Option<u64> {
+        self.amount_cents
+    }
+
+    /// The amount formatted with two decimals, e.g. `1949.75`
+    pub fn getAmountString(&self) -> Option<String> {
+        self.amount_cents
+            .map(|cents| format!("{}.{:02}", cents / 100, cents % 100))
+    }
+
+    /// The currency, either `CHF` or `EUR`
+    pub fn getCurrency(&self) -> &str {
+        &self.currency
+    }
+
+    pub fn getDebtor(&self) -> Option<&SwissQRBillAddress> {
+        self.debtor.as_ref()
+    }
+
+    pub fn getReferenceType(&self) -> SwissQRBillReferenceType {
+        self.reference_type
+    }
+
+    pub fn getReference(&self) -> &str {
+        &self.reference
+    }
+
+    pub fn getUnstructuredMessage(&self) -> &str {
+        &self.unstructured_message
+    }
+
+    pub fn getBillingInformation(&self) -> &str {
+        &self.billing_information
+    }
+
+    pub fn getAlternativeProcedures(&self) -> &[String] {
+        &self.alternative_procedures
+    }
+
+    /// Returns true if the IBAN is a QR-IBAN (institution id 30000 - 31999)
+    pub fn isQRIBAN(&self) -> bool {
+        self.iban.len() == 21
+            && self
+                .iban
+                .get(4..9)
+                .and_then(|iid| iid.parse::<u32>().ok())
+                .is_some_and(|iid| (30000..=31999).contains(&iid))
+    }
+
+    /**
+     * Checks all field rules of the implementation guidelines: IBAN country and
+     * checksum, address formats, amount range, currency, reference type and
+     * check digits, and field / payload lengths.
+     */
+    pub fn validate(&self) -> Result<()> {
+        if self.iban.len() != 21
+            || !(self.iban.starts_with("CH") || self.iban.starts_with("LI"))
+            || !EPCPaymentResultParser::is_valid_iban(&self.iban)
+        {
+            return Err(Exceptions::format_with("invalid CH / LI IBAN"));
+        }
+
+        self.creditor.validate("creditor")?;
+        if let Some(debtor) = &self.debtor {
+            debtor.validate("debtor")?;
+        }
+
+        if let Some(cents) = self.amount_cents {
+            if !(1..=99_999_999_999).contains(&cents) {
+                return Err(Exceptions::format_with(
+                    "amount must be between 0.01 and 999999999.99",
+                ));
+            }
+        }
+
+        if self.currency != "CHF" && self.currency != "EUR" {
+            return Err(Exceptions::format_with("currency must be CHF or EUR"));
+        }
+
+        match self.reference_type {
+            SwissQRBillReferenceType::QRR => {
+                if !self.isQRIBAN() {
+                    return Err(Exceptions::format_with(
+                        "QR reference can only be used with a QR-IBAN",
+                    ));
+                }
+                if !is_valid_qr_reference(&self.reference) {
+                    return Err(Exceptions::format_with("invalid QR reference"));
+                }
+            }
+            SwissQRBillReferenceType::SCOR => {
+                if self.isQRIBAN() {
+                    return Err(Exceptions::format_with("a QR-IBAN requires a QR reference"));
+                }
+                if !is_valid_creditor_reference(&self.reference) {
+                    return Err(Exceptions::format_with("invalid creditor reference"));
+                }
+            }
+            SwissQRBillReferenceType::NON => {
+                if self.isQRIBAN() {
+                    return Err(Exceptions::format_with("a QR-IBAN requires a QR reference"));
+                }
+                if !self.reference.is_empty() {
+                    return Err(Exceptions::format_with(
+                        "reference must be empty for reference type NON",
+                    ));
+                }
+            }
+        }
+
+        if self.unstructured_message.chars().count() + self.billing_information.chars().count()
+            > 140
+        {
+            return Err(Exceptions::format_with(
+                "message and billing information exceed 140 characters",
+            ));
+        }
+
+        if self.alternative_procedures.len() > 2
+            || self
+                .alternative_procedures
+                .iter()
+                .any(|p| p.chars().count() > 100)
+        {
+            return Err(Exceptions::format_with(
+                "at most two alternative procedures of 100 characters each",
+            ));
+        }
+
+        Ok(())
+    }
+
+    /**
+     * Validates the bill and serializes it to the newline separated payload to be
+     * encoded in the QR code.
+     */
+    pub fn to_payload(&self) -> Result<String> {
+        self.validate()?;
+
+        let mut lines = vec![
+            SWISS_QR_BILL_QR_TYPE.to_owned(),
+            SWISS_QR_BILL_VERSION.to_owned(),
+            SWISS_QR_BILL_CODING_TYPE.to_owned(),
+            self.iban.clone(),
+        ];
+        self.creditor.push_lines(&mut lines);
+        // ultimate creditor, reserved for future use and must be empty
+        SwissQRBillAddress::default().push_lines(&mut lines);
+        lines.push(self.getAmountString().unwrap_or_default());
+        lines.push(self.currency.clone());
+        self.debtor
+            .clone()
+            .unwrap_or_default()
+            .push_lines(&mut lines);
+        lines.push(self.reference_type.to_string());
+        lines.push(self.reference.clone());
+        lines.push(self.unstructured_message.clone());
+        lines.push(SWISS_QR_BILL_TRAILER.to_owned());
+        if !self.billing_information.is_empty() || !self.alternative_procedures.is_empty() {
+            lines.push(self.billing_information.clone());
+        }
+        lines.extend(self.alternative_procedures.iter().cloned());
+
+        let payload = lines.join("\n");
+        if payload.chars().count() > SWISS_QR_BILL_MAX_LENGTH {
+            return Err(Exceptions::format_with(format!(
+                "payload exceeds {SWISS_QR_BILL_MAX_LENGTH} characters"
+            )));
+        }
+
+        Ok(payload)
+    }
+}
+
+fn check_length(role: &str, field: &str, value: &str, min: usize, max: usize) -> Result<()> {
+    let len = value.chars().count();
+    if len < min || len > max {
+        return Err(Exceptions::format_with(format!(
+            "{role} {field} must be between {min} and {max} characters"
+        )));
+    }
+    Ok(())
+}
+
+/// A QR reference is 27 digits, the last being a recursive mod 10 check digit.
+pub fn is_valid_qr_reference(reference: &str) -> bool {
+    const TABLE: [usize; 10] = [0, 9, 4, 6, 8, 2, 7, 1, 3, 5];
+
+    if reference.len() != 27 || !reference.chars().all(|c| c.is_ascii_digit()) {
+        return false;
+    }
+    let mut carry = 0;
+    for c in reference[..26].bytes() {
+        carry = TABLE[(carry + (c - b'0') as usize) % 10];
+    }
+
+    (10 - carry) % 10 == (reference.as_bytes()[26] - b'0') as usize
+}
+
+/// An ISO 11649 creditor reference is `RF`, two check digits and up to 21 characters,
+/// validated with the same mod 97 scheme as IBANs.
+pub fn is_valid_creditor_reference(reference: &str) -> bool {
+    if reference.len() < 5
+        || reference.len() > 25
+        || !reference.starts_with("RF")
+        || !reference.chars().all(|c| c.is_ascii_alphanumeric())
+    {
+        return false;
+    }
+
+    EPCPaymentResultParser::is_mod97_valid(reference)
+}
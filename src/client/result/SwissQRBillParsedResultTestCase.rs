@@ -0,0 +1,166 @@
+/*
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+/**
+ * Tests {@link SwissQRBillParsedRXingResult}.
+ */
+use std::collections::HashSet;
+
+use crate::{
+    client::result::{
+        encode_swiss_qr_bill, swiss_cross_area, ParsedClientResult, ParsedRXingResult,
+        ParsedRXingResultType, SwissQRBillAddress, SwissQRBillAddressType,
+        SwissQRBillParsedRXingResult, SwissQRBillReferenceType,
+    },
+    helpers, BarcodeFormat, DecodeHints, RXingResult,
+};
+
+use super::ResultParser;
+
+const EXAMPLE: &str = "SPC\n0200\n1\nCH4431999123000889012\nS\nRobert Schneider AG\nRue du Lac\n1268\n2501\nBiel\nCH\n\n\n\n\n\n\n\n1949.75\nCHF\nS\nPia-Maria Rutschmann-Schnyder\nGrosse Marktgasse\n28\n9400\nRorschach\nCH\nQRR\n210000000003139471430009017\nOrder of 15 June 2020\nEPD\n//S1/10/10201409/11/200701/20/140.000-53/30/102673831/31/200615/32/7.7/33/7.7:10/40/0:30\nName AV1: UV;UltraPay005;12345\nName AV2: XY;XYService;54321";
+
+fn parse(contents: &str) -> ParsedClientResult {
+    let fake_rxing_result =
+        RXingResult::new(contents, Vec::new(), Vec::new(), BarcodeFormat::QR_CODE);
+    ResultParser::parseRXingResult(&fake_rxing_result)
+}
+
+fn example_bill() -> SwissQRBillParsedRXingResult {
+    let mut bill = SwissQRBillParsedRXingResult::new(
+        "CH58 0079 1123 0008 8901 2",
+        SwissQRBillAddress::structured(
+            "Robert Schneider AG",
+            "Rue du Lac",
+            "1268",
+            "2501",
+            "Biel",
+            "CH",
+        ),
+        "CHF",
+    );
+    bill.amount_cents = Some(19975);
+    bill.reference_type = SwissQRBillReferenceType::SCOR;
+    bill.reference = "RF18539007547034".to_owned();
+    bill.debtor = Some(SwissQRBillAddress::combined(
+        "Pia Rutschmann",
+        "Marktgasse 28",
+        "9400 Rorschach",
+        "CH",
+    ));
+    bill
+}
+
+#[test]
+fn test_swiss_qr_bill() {
+    let result = parse(EXAMPLE);
+    assert_eq!(ParsedRXingResultType::SwissQRBill, result.getType());
+    let ParsedClientResult::SwissQRBillResult(bill) = result else {
+        panic!("Expected SwissQRBillResult");
+    };
+    assert_eq!("CH4431999123000889012", bill.getIBAN());
+    assert!(bill.isQRIBAN());
+    assert_eq!("Robert Schneider AG", bill.getCreditor().name);
+    assert_eq!(
+        SwissQRBillAddressType::Structured,
+        bill.getCreditor().address_type
+    );
+    assert_eq!("2501", bill.getCreditor().postal_code);
+    assert_eq!(Some(194975), bill.getAmountInCents());
+    assert_eq!("CHF", bill.getCurrency());
+    assert_eq!("Rorschach", bill.getDebtor().unwrap().town);
+    assert_eq!(SwissQRBillReferenceType::QRR, bill.getReferenceType());
+    assert_eq!("210000000003139471430009017", bill.getReference());
+    assert_eq!("Order of 15 June 2020", bill.getUnstructuredMessage());
+    assert_eq!(2, bill.getAlternativeProcedures().len());
+
+    // the payload survives a serialization round trip unchanged
+    assert_eq!(EXAMPLE, bill.to_payload().unwrap());
+}
+
+#[test]
+fn test_iban_not_ascii() {
+    // 21 bytes, with the institution id ending inside the "é"
+    let iban = "CH443199é23000889012";
+    let mut bill = example_bill();
+    bill.iban = iban.to_owned();
+    assert!(!bill.isQRIBAN());
+    assert!(bill.validate().is_err());
+
+    let contents = EXAMPLE.replace("CH4431999123000889012", iban);
+    assert_eq!(ParsedRXingResultType::Text, parse(&contents).getType());
+}
+
+#[test]
+fn test_not_swiss_qr_bill() {
+    // QR reference with an ordinary IBAN
+    let bad = EXAMPLE.replace("CH4431999123000889012", "CH5800791123000889012");
+    assert_ne!(ParsedRXingResultType::SwissQRBill, parse(&bad).getType());
+    // broken QR reference check digit
+    let bad = EXAMPLE.replace("210000000003139471430009017", "210000000003139471430009018");
+    assert_ne!(ParsedRXingResultType::SwissQRBill, parse(&bad).getType());
+    // non CHF/EUR currency
+    let bad = EXAMPLE.replace("\nCHF\n", "\nUSD\n");
+    assert_ne!(ParsedRXingResultType::SwissQRBill, parse(&bad).getType());
+    // missing trailer
+    let bad = EXAMPLE.replace("\nEPD\n", "\nEND\n");
+    assert_ne!(ParsedRXingResultType::SwissQRBill, parse(&bad).getType());
+}
+
+#[test]
+fn test_build_swiss_qr_bill() {
+    let bill = example_bill();
+    let payload = bill.to_payload().unwrap();
+    let ParsedClientResult::SwissQRBillResult(parsed) = parse(&payload) else {
+        panic!("Expected SwissQRBillResult");
+    };
+    assert_eq!(bill, parsed);
+
+    let mut invalid = example_bill();
+    invalid.currency = "USD".to_owned();
+    assert!(invalid.to_payload().is_err());
+
+    let mut invalid = example_bill();
+    invalid.reference_type = SwissQRBillReferenceType::NON;
+    assert!(invalid.to_payload().is_err());
+
+    let mut invalid = example_bill();
+    invalid.creditor.postal_code = String::default();
+    assert!(invalid.to_payload().is_err());
+}
+
+#[test]
+fn test_encode_swiss_qr_bill() {
+    let bill = example_bill();
+    let matrix = encode_swiss_qr_bill(&bill, 400, 400, 4).unwrap();
+    let symbol = matrix.getEnclosingRectangle().unwrap();
+    let [left, top, side, _] = swiss_cross_area(symbol);
+    assert_eq!(symbol[2] * 7 / 46, side);
+    // white border around the cross, black square inside
+    assert!(!matrix.get(left, top));
+    assert!(matrix.get(left + side / 7, top + side / 7));
+
+    let mut hints = DecodeHints {
+        PossibleFormats: Some(HashSet::from([BarcodeFormat::QR_CODE])),
+        ..Default::default()
+    };
+    let decoded = helpers::detect_in_luma_with_hints(
+        matrix.to_luma8(),
+        matrix.getWidth(),
+        matrix.getHeight(),
+        Some(BarcodeFormat::QR_CODE),
+        &mut hints,
+    )
+    .unwrap();
+    assert_eq!(bill.to_payload().unwrap(), decoded.getText());
+}
@@ -0,0 +1,140 @@
+/*
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use crate::{
+    client::result::{
+        ParsedClientResult, SwissQRBillAddress, SwissQRBillAddressType,
+        SwissQRBillParsedRXingResult, SwissQRBillReferenceType, SWISS_QR_BILL_CODING_TYPE,
+        SWISS_QR_BILL_QR_TYPE, SWISS_QR_BILL_TRAILER,
+    },
+    RXingResult,
+};
+
+use super::ResultParser;
+
+/**
+ * <p>Parses a Swiss QR-bill payload. The payload is a fixed sequence of lines:
+ * header (<code>SPC</code>, version, coding type), the creditor account and address,
+ * an empty ultimate creditor block, amount and currency, the optional ultimate debtor,
+ * the payment reference, an unstructured message, the <code>EPD</code> trailer and
+ * optionally billing information and up to two alternative procedures.</p>
+ *
+ * <p>Only version 2 payloads (<code>02xx</code>) are accepted. The parsed bill must
+ * pass {@link SwissQRBillParsedRXingResult#validate}, otherwise the payload is not
+ * treated as a QR-bill.</p>
+ */
+pub fn parse(result: &RXingResult) -> Option<ParsedClientResult> {
+    let raw_text = ResultParser::getMassagedText(result);
+    if !raw_text.starts_with(SWISS_QR_BILL_QR_TYPE) {
+        return None;
+    }
+
+    let lines: Vec<&str> = raw_text
+        .split('\n')
+        .map(|line| line.strip_suffix('\r').unwrap_or(line))
+        .collect();
+    if lines.len() < 31 || lines.len() > 34 {
+        return None;
+    }
+    let field = |i: usize| lines.get(i).map(|l| l.trim()).unwrap_or_default();
+
+    if field(0) != SWISS_QR_BILL_QR_TYPE
+        || !field(1).starts_with("02")
+        || field(1).len() != 4
+        || field(2) != SWISS_QR_BILL_CODING_TYPE
+        || field(30) != SWISS_QR_BILL_TRAILER
+    {
+        return None;
+    }
+
+    let creditor = parse_address(&lines[4..11])??;
+    // the ultimate creditor is reserved for future use and must be empty
+    if parse_address(&lines[11..18])?.is_some() {
+        return None;
+    }
+
+    let amount_cents = match field(18) {
+        "" => None,
+        amount => Some(parse_amount(amount)?),
+    };
+
+    let reference_type = match field(27) {
+        "QRR" => SwissQRBillReferenceType::QRR,
+        "SCOR" => SwissQRBillReferenceType::SCOR,
+        "NON" => SwissQRBillReferenceType::NON,
+        _ => return None,
+    };
+
+    let bill = SwissQRBillParsedRXingResult {
+        iban: field(3).chars().filter(|c| !c.is_whitespace()).collect(),
+        creditor,
+        amount_cents,
+        currency: field(19).to_owned(),
+        debtor: parse_address(&lines[20..27])?,
+        reference_type,
+        reference: field(28).chars().filter(|c| !c.is_whitespace()).collect(),
+        unstructured_message: field(29).to_owned(),
+        billing_information: field(31).to_owned(),
+        alternative_procedures: lines.iter().skip(32).map(|l| l.trim().to_owned()).collect(),
+    };
+
+    bill.validate().ok()?;
+
+    Some(ParsedClientResult::SwissQRBillResult(bill))
+}
+
+/// Parses a block of 7 address lines. Returns `Some(None)` for an empty block and
+/// `None` for an invalid one.
+fn parse_address(lines: &[&str]) -> Option<Option<SwissQRBillAddress>> {
+    if lines.iter().all(|l| l.trim().is_empty()) {
+        return Some(None);
+    }
+    let address_type = match lines[0].trim() {
+        "S" => SwissQRBillAddressType::Structured,
+        "K" => SwissQRBillAddressType::Combined,
+        _ => return None,
+    };
+
+    Some(Some(SwissQRBillAddress {
+        address_type,
+        name: lines[1].trim().to_owned(),
+        street_or_address_line_1: lines[2].trim().to_owned(),
+        building_number_or_address_line_2: lines[3].trim().to_owned(),
+        postal_code: lines[4].trim().to_owned(),
+        town: lines[5].trim().to_owned(),
+        country: lines[6].trim().to_owned(),
+    }))
+}
+
+/// Parses an amount of the form `1949.75` into cents.
+fn parse_amount(amount: &str) -> Option<u64> {
+    let (units, fraction) = amount.split_once('.').unwrap_or((amount, ""));
+    if units.is_empty()
+        || units.len() > 9
+        || fraction.len() > 2
+        || !units.chars().all(|c| c.is_ascii_digit())
+        || !fraction.chars().all(|c| c.is_ascii_digit())
+    {
+        return None;
+    }
+
+    Some(
+        units.parse::<u64>().ok()? * 100
+            + match fraction.len() {
+                0 => 0,
+                1 => fraction.parse::<u64>().ok()? * 10,
+                _ => fraction.parse::<u64>().ok()?,
+            },
+    )
+}
@@ -0,0 +1,127 @@
+/*
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use crate::{
+    common::{BitMatrix, Result},
+    qrcode::{decoder::ErrorCorrectionLevel, encoder::qrcode_encoder, QRCodeWriter},
+    EncodeHints, Exceptions,
+};
+
+use super::SwissQRBillParsedRXingResult;
+
+/// The QR code of a QR-bill is printed 46 x 46 mm (without quiet zone)
+pub const SWISS_QR_BILL_SYMBOL_SIZE_MM: u32 = 46;
+/// The Swiss cross in the center of the QR code is printed 7 x 7 mm
+pub const SWISS_CROSS_SIZE_MM: u32 = 7;
+/// The largest QR version allowed for a QR-bill
+pub const SWISS_QR_BILL_MAX_VERSION: u32 = 25;
+
+/**
+ * Encodes a Swiss QR-bill into a QR code.
+ *
+ * The bill is validated and serialized with [`SwissQRBillParsedRXingResult::to_payload`],
+ * then encoded as UTF-8 with error correction level M, as mandated by the
+ * implementation guidelines. Symbols that would need a version larger than 25
+ * are rejected. The Swiss cross is drawn over the center of the symbol, in the
+ * area returned by [`swiss_cross_area`].
+ *
+ * `width` and `height` are the requested output size in pixels; `quiet_zone` is the
+ * margin in modules (the guidelines require 5 mm, which is ~4 modules at version 25).
+ */
+pub fn encode_swiss_qr_bill(
+    bill: &SwissQRBillParsedRXingResult,
+    width: i32,
+    height: i32,
+    quiet_zone: i32,
+) -> Result<BitMatrix> {
+    let payload = bill.to_payload()?;
+
+    let hints = EncodeHints {
+        CharacterSet: Some("UTF-8".to_owned()),
+        ..Default::default()
+    };
+    let code = qrcode_encoder::encode_with_hints(&payload, ErrorCorrectionLevel::M, &hints)?;
+    let version = code
+        .getVersion()
+        .ok_or(Exceptions::illegal_state_with("missing qr version"))?
+        .getVersionNumber();
+    if version > SWISS_QR_BILL_MAX_VERSION {
+        return Err(Exceptions::writer_with(format!(
+            "QR-bill payload requires version {version}, the maximum is {SWISS_QR_BILL_MAX_VERSION}"
+        )));
+    }
+
+    let mut matrix = QRCodeWriter::renderRXingResult(&code, width, height, quiet_zone)?;
+    let symbol = matrix
+        .getEnclosingRectangle()
+        .ok_or(Exceptions::illegal_state_with("empty qr code"))?;
+    draw_swiss_cross(&mut matrix, swiss_cross_area(symbol))?;
+
+    Ok(matrix)
+}
+
+/**
+ * Computes the square area `[left, top, width, height]` covered by the Swiss cross,
+ * given the enclosing rectangle `[left, top, width, height]` of the QR symbol
+ * (without quiet zone). The cross covers 7/46 of the symbol and is centered on it.
+ */
+pub fn swiss_cross_area(symbol: [u32; 4]) -> [u32; 4] {
+    let [left, top, width, height] = symbol;
+    let side = (width.min(height) * SWISS_CROSS_SIZE_MM + SWISS_QR_BILL_SYMBOL_SIZE_MM / 2)
+        / SWISS_QR_BILL_SYMBOL_SIZE_MM;
+
+    [
+        left + (width - side) / 2,
+        top + (height - side) / 2,
+        side,
+        side,
+    ]
+}
+
+/// Draws the Swiss cross: a white border, a black square, and a white cross with
+/// the proportions of the Swiss flag (arms 6/32 wide and 20/32 long).
+fn draw_swiss_cross(matrix: &mut BitMatrix, area: [u32; 4]) -> Result<()> {
+    let [left, top, side, _] = area;
+
+    for y in top..top + side {
+        for x in left..left + side {
+            matrix.unset(x, y);
+        }
+    }
+
+    let border = (side / 14).max(1);
+    let inner = side.saturating_sub(2 * border);
+    if inner == 0 {
+        return Ok(());
+    }
+    matrix.setRegion(left + border, top + border, inner, inner)?;
+
+    let arm_width = (inner * 6 / 32).max(1);
+    let arm_length = inner * 20 / 32;
+    let center = inner / 2;
+    let (inner_left, inner_top) = (left + border, top + border);
+    for y in 0..inner {
+        for x in 0..inner {
+            let in_vertical =
+                x.abs_diff(center) * 2 < arm_width && y.abs_diff(center) * 2 < arm_length;
+            let in_horizontal =
+                y.abs_diff(center) * 2 < arm_width && x.abs_diff(center) * 2 < arm_length;
+            if in_vertical || in_horizontal {
+                matrix.unset(inner_left + x, inner_top + y);
+            }
+        }
+    }
+
+    Ok(())
+}
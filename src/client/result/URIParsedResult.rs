@@ -16,7 +16,10 @@
 
 // package com.google.zxing.client.result;
 
-use super::{ParsedRXingResult, ParsedRXingResultType, ResultParser, URIResultParser};
+use super::{
+    check_uri_safety, uri_host, uri_scheme, ParsedRXingResult, ParsedRXingResultType, ResultParser,
+    URIClassification, URIResultParser, URISafetyReport, URISchemePolicy,
+};
 
 /**
  * A simple result type encapsulating a URI that has no further interpretation.
@@ -56,6 +59,36 @@ impl URIParsedRXingResult {
         &self.title
     }
 
+    /// The scheme of the URI, e.g. `https`
+    pub fn getScheme(&self) -> Option<&str> {
+        uri_scheme(&self.uri)
+    }
+
+    /// The host of the URI without user info and port, if it has an authority
+    pub fn getHost(&self) -> Option<&str> {
+        uri_host(&self.uri)
+    }
+
+    /// Classifies the URI by its scheme (web link, deep link into an app, script, ...)
+    pub fn classify(&self) -> URIClassification {
+        self.getScheme()
+            .map(URIClassification::from_scheme)
+            .unwrap_or(URIClassification::Other)
+    }
+
+    /// Returns true if opening the URI would hand control to another application
+    pub fn is_deep_link(&self) -> bool {
+        self.classify() == URIClassification::DeepLink
+    }
+
+    /**
+     * Checks the URI against the scheme whitelist in `policy` and for homograph,
+     * punycode and user info tricks, so applications can warn before opening it.
+     */
+    pub fn check_safety(&self, policy: &URISchemePolicy) -> URISafetyReport {
+        check_uri_safety(&self.uri, policy)
+    }
+
     /**
      * @return true if the URI contains suspicious patterns that may suggest it intends to
      *  mislead the user about its true nature
@@ -28,7 +28,10 @@
  */
 // public final class URIParsedRXingResultTestCase extends Assert {
 use crate::{
-    client::result::{ParsedClientResult, ParsedRXingResult, ParsedRXingResultType, ResultParser},
+    client::result::{
+        parse_uri_with_scheme_policy, ParsedClientResult, ParsedRXingResult, ParsedRXingResultType,
+        ResultParser, URIClassification, URIParsedRXingResult, URISchemePolicy,
+    },
     BarcodeFormat, RXingResult,
 };
 
@@ -131,6 +134,100 @@ fn test_exotic() {
     );
 }
 
+#[test]
+fn test_classification() {
+    let classify = |uri: &str| URIParsedRXingResult::new(uri.to_owned(), String::default());
+    assert_eq!(URIClassification::Web, classify("google.com").classify());
+    assert_eq!(
+        URIClassification::Web,
+        classify("HTTPS://google.com").classify()
+    );
+    assert_eq!(URIClassification::Mail, classify("mailto:a@b.c").classify());
+    assert_eq!(
+        URIClassification::Telephone,
+        classify("tel:+1234").classify()
+    );
+    assert_eq!(
+        URIClassification::Script,
+        classify("javascript:alert(1)").classify()
+    );
+    assert_eq!(
+        URIClassification::Data,
+        classify("data:text/html,hi").classify()
+    );
+    assert!(classify("otpauth://totp/foo?secret=bar").is_deep_link());
+    assert!(classify("intent://scan/#Intent;scheme=zxing;end").is_deep_link());
+    assert!(!classify("https://example.com").is_deep_link());
+    assert_eq!(
+        Some("example.com"),
+        classify("https://user@example.com:8080/x").getHost()
+    );
+}
+
+#[test]
+fn test_safety() {
+    let policy = URISchemePolicy::default();
+    let check = |uri: &str| {
+        URIParsedRXingResult::new(uri.to_owned(), String::default()).check_safety(&policy)
+    };
+
+    let report = check("https://example.com/path");
+    assert!(!report.is_suspicious());
+    assert!(!report.punycode_host);
+
+    let report = check("javascript:alert(1)");
+    assert!(report.disallowed_scheme);
+    assert!(report.dangerous_scheme);
+
+    let report = check("myapp://open");
+    assert!(report.disallowed_scheme);
+    assert!(!check("myapp://open").dangerous_scheme);
+    assert!(
+        !URIParsedRXingResult::new("myapp://open".to_owned(), String::default())
+            .check_safety(&URISchemePolicy::default().allow("MyApp"))
+            .disallowed_scheme
+    );
+
+    assert!(check("http://yourbank.com@phisher.com").user_info_in_authority);
+    assert!(check("http://192.168.0.1/login").ip_address_host);
+
+    // "аpple.com" with a cyrillic "а"
+    let report = check("https://xn--pple-43d.com");
+    assert!(report.punycode_host);
+    assert!(report.non_ascii_host);
+    assert!(report.possible_homograph);
+    assert_eq!(Some("аpple.com".to_owned()), report.unicode_host);
+
+    // "аррӏе.com", entirely cyrillic
+    let report = check("https://xn--80ak6aa92e.com");
+    assert!(report.possible_homograph);
+
+    // a genuine internationalized domain is flagged as punycode but not as homograph
+    let report = check("https://xn--mnchen-3ya.de");
+    assert!(report.punycode_host);
+    assert!(!report.possible_homograph);
+    assert_eq!(Some("münchen.de".to_owned()), report.unicode_host);
+
+    // a host that is not ascii to begin with, with a multi-byte character in its first 4 bytes
+    let report = check("http://café.example/");
+    assert!(!report.punycode_host);
+    assert!(report.non_ascii_host);
+}
+
+#[test]
+fn test_scheme_policy_parser() {
+    let policy = URISchemePolicy::new(&["https"]);
+    let parse = |text: &str| {
+        parse_uri_with_scheme_policy(
+            &RXingResult::new(text, Vec::new(), Vec::new(), BarcodeFormat::QR_CODE),
+            &policy,
+        )
+    };
+    assert!(parse("https://example.com").is_some());
+    assert!(parse("http://example.com").is_none());
+    assert!(parse("otpauth://totp/foo").is_none());
+}
+
 fn do_test(contents: &str, uri: &str, title: &str) {
     let fake_rxing_result =
         RXingResult::new(contents, Vec::new(), Vec::new(), BarcodeFormat::QR_CODE);
@@ -0,0 +1,370 @@
+/*
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::collections::HashSet;
+
+use crate::RXingResult;
+
+use super::{ParsedClientResult, URIResultParser};
+
+/**
+ * Broad category of a URI, derived from its scheme.
+ */
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+pub enum URIClassification {
+    /// `http` and `https`
+    Web,
+    /// `mailto`
+    Mail,
+    /// `tel`
+    Telephone,
+    /// `sms`, `smsto`, `mms`, `mmsto`
+    Messaging,
+    /// `geo`
+    Location,
+    /// A custom application scheme (`myapp://...`) or an Android `intent:` URI,
+    /// which hands control to an installed application
+    DeepLink,
+    /// `javascript` and `vbscript`, which execute code when opened
+    Script,
+    /// `data` and `blob`, which embed or reference content inline
+    Data,
+    /// `file`, which references the local file system
+    File,
+    /// Any other scheme
+    Other,
+}
+
+impl URIClassification {
+    pub fn from_scheme(scheme: &str) -> Self {
+        match scheme.to_ascii_lowercase().as_str() {
+            "http" | "https" => URIClassification::Web,
+            "mailto" => URIClassification::Mail,
+            "tel" => URIClassification::Telephone,
+            "sms" | "smsto" | "mms" | "mmsto" => URIClassification::Messaging,
+            "geo" => URIClassification::Location,
+            "javascript" | "vbscript" => URIClassification::Script,
+            "data" | "blob" => URIClassification::Data,
+            "file" => URIClassification::File,
+            "intent" => URIClassification::DeepLink,
+            "ftp" | "ftps" | "sftp" | "ws" | "wss" | "urn" | "magnet" | "bitcoin" | "wifi"
+            | "market" => URIClassification::Other,
+            _ => URIClassification::DeepLink,
+        }
+    }
+
+    /// Script, data and file URIs are never expected in barcodes and should not be opened
+    pub fn is_dangerous(&self) -> bool {
+        matches!(
+            self,
+            URIClassification::Script | URIClassification::Data | URIClassification::File
+        )
+    }
+}
+
+/**
+ * The set of URI schemes a scanning application is willing to open. Schemes are
+ * compared case insensitively.
+ *
+ * The default policy allows `http`, `https`, `mailto`, `tel`, `sms`, `smsto`,
+ * `mms`, `mmsto` and `geo`.
+ */
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct URISchemePolicy {
+    allowed_schemes: HashSet<String>,
+}
+
+impl Default for URISchemePolicy {
+    fn default() -> Self {
+        Self::new(&[
+            "http", "https", "mailto", "tel", "sms", "smsto", "mms", "mmsto", "geo",
+        ])
+    }
+}
+
+impl URISchemePolicy {
+    pub fn new(allowed_schemes: &[&str]) -> Self {
+        Self {
+            allowed_schemes: allowed_schemes
+                .iter()
+                .map(|s| s.to_ascii_lowercase())
+                .collect(),
+        }
+    }
+
+    /// Allows an additional scheme, e.g. the deep link scheme of your own app
+    pub fn allow(mut self, scheme: &str) -> Self {
+        self.allowed_schemes.insert(scheme.to_ascii_lowercase());
+        self
+    }
+
+    pub fn is_allowed(&self, scheme: &str) -> bool {
+        self.allowed_schemes.contains(&scheme.to_ascii_lowercase())
+    }
+}
+
+/**
+ * Findings about a URI which scanning applications may want to warn about before
+ * opening it. None of the flags make the URI invalid, they only describe it.
+ */
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Default)]
+pub struct URISafetyReport {
+    /// The scheme is not in the [`URISchemePolicy`] used for the check
+    pub disallowed_scheme: bool,
+    /// The scheme is known to execute code or access local content
+    pub dangerous_scheme: bool,
+    /// At least one host label is punycode encoded (`xn--`)
+    pub punycode_host: bool,
+    /// The host contains non-ASCII characters, either directly or after punycode decoding
+    pub non_ascii_host: bool,
+    /// A host label mixes scripts (e.g. Latin and Cyrillic), or uses characters that
+    /// are visually confusable with Latin letters, the typical homograph attack
+    pub possible_homograph: bool,
+    /// The authority contains user info (`https://yourbank.com@phisher.com`)
+    pub user_info_in_authority: bool,
+    /// The host is a literal IP address instead of a domain name
+    pub ip_address_host: bool,
+    /// The host name decoded to unicode, when it contained punycode labels
+    pub unicode_host: Option<String>,
+}
+
+impl URISafetyReport {
+    /// Returns true if any of the findings suggest the user should be warned
+    pub fn is_suspicious(&self) -> bool {
+        self.disallowed_scheme
+            || self.dangerous_scheme
+            || self.possible_homograph
+            || self.user_info_in_authority
+            || self.ip_address_host
+    }
+}
+
+/**
+ * Parses the result like the URI parser does, but only accepts URIs whose scheme is
+ * allowed by `policy`. Useful as one of the parsers given to
+ * `parse_result_with_parsers`, or to refuse anything but web links outright.
+ */
+pub fn parse_uri_with_scheme_policy(
+    result: &RXingResult,
+    policy: &URISchemePolicy,
+) -> Option<ParsedClientResult> {
+    let parsed = URIResultParser::parse(result)?;
+    let ParsedClientResult::URIResult(uri) = &parsed else {
+        return None;
+    };
+    if uri
+        .getScheme()
+        .is_some_and(|scheme| policy.is_allowed(scheme))
+    {
+        Some(parsed)
+    } else {
+        None
+    }
+}
+
+/// Returns the scheme of the URI, if it has one.
+pub fn uri_scheme(uri: &str) -> Option<&str> {
+    let end = uri.find(':')?;
+    let scheme = &uri[..end];
+    let mut chars = scheme.chars();
+    if chars.next()?.is_ascii_alphabetic()
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '+' || c == '-' || c == '.')
+    {
+        Some(scheme)
+    } else {
+        None
+    }
+}
+
+/// Returns the authority (`user@host:port`) of a hierarchical URI.
+pub fn uri_authority(uri: &str) -> Option<&str> {
+    let scheme = uri_scheme(uri)?;
+    let rest = uri[scheme.len() + 1..].strip_prefix("//")?;
+    let end = rest.find(['/', '?', '#']).unwrap_or(rest.len());
+    Some(&rest[..end])
+}
+
+/// Returns the host of a hierarchical URI, without user info and port.
+pub fn uri_host(uri: &str) -> Option<&str> {
+    let authority = uri_authority(uri)?;
+    let host = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host)| host);
+    if host.starts_with('[') {
+        // IPv6 literal
+        return host.find(']').map(|end| &host[..=end]);
+    }
+    Some(host.rsplit_once(':').map_or(host, |(host, _)| host))
+}
+
+/// Inspects the URI for the findings described in [`URISafetyReport`].
+pub fn check_uri_safety(uri: &str, policy: &URISchemePolicy) -> URISafetyReport {
+    let mut report = URISafetyReport::default();
+
+    match uri_scheme(uri) {
+        Some(scheme) => {
+            report.disallowed_scheme = !policy.is_allowed(scheme);
+            report.dangerous_scheme = URIClassification::from_scheme(scheme).is_dangerous();
+        }
+        None => report.disallowed_scheme = true,
+    }
+
+    if let Some(authority) = uri_authority(uri) {
+        report.user_info_in_authority = authority.contains('@');
+    }
+
+    let Some(host) = uri_host(uri) else {
+        return report;
+    };
+
+    report.ip_address_host = host.starts_with('[')
+        || (host.split('.').count() == 4
+            && host
+                .split('.')
+                .all(|p| !p.is_empty() && p.parse::<u8>().is_ok()));
+
+    let mut decoded_labels = Vec::new();
+    for label in host.split('.') {
+        let decoded = if label.len() > 4
+            && label
+                .get(..4)
+                .is_some_and(|p| p.eq_ignore_ascii_case("xn--"))
+        {
+            report.punycode_host = true;
+            decode_punycode(&label[4..]).unwrap_or_else(|| label.to_owned())
+        } else {
+            label.to_owned()
+        };
+        if !decoded.is_ascii() {
+            report.non_ascii_host = true;
+            if is_possible_homograph(&decoded) {
+                report.possible_homograph = true;
+            }
+        }
+        decoded_labels.push(decoded);
+    }
+    if report.punycode_host {
+        report.unicode_host = Some(decoded_labels.join("."));
+    }
+
+    report
+}
+
+#[derive(PartialEq, Eq, Hash, Clone, Copy)]
+enum Script {
+    Latin,
+    Greek,
+    Cyrillic,
+    Other,
+}
+
+fn script_of(c: char) -> Option<Script> {
+    match c {
+        '0'..='9' | '-' => None,
+        'a'..='z' | 'A'..='Z' | '\u{00C0}'..='\u{024F}' => Some(Script::Latin),
+        '\u{0370}'..='\u{03FF}' => Some(Script::Greek),
+        '\u{0400}'..='\u{052F}' => Some(Script::Cyrillic),
+        _ => Some(Script::Other),
+    }
+}
+
+/// Cyrillic and Greek letters which are rendered (almost) identically to Latin letters
+const LATIN_CONFUSABLES: &str = "аеорсухіјѕԁһӏԛԝАВЕКМНОРСТХУІЈЅαοντΑΒΕΖΗΙΚΜΝΟΡΤΥΧ";
+
+/// A label is a possible homograph if it mixes Latin, Greek and Cyrillic letters,
+/// or if it consists entirely of letters confusable with Latin ones.
+fn is_possible_homograph(label: &str) -> bool {
+    let scripts: HashSet<_> = label.chars().filter_map(script_of).collect();
+    let confusable_scripts = scripts
+        .iter()
+        .filter(|s| matches!(s, Script::Latin | Script::Greek | Script::Cyrillic))
+        .count();
+    if confusable_scripts > 1 {
+        return true;
+    }
+
+    !scripts.contains(&Script::Latin)
+        && label
+            .chars()
+            .filter(|c| script_of(*c).is_some())
+            .all(|c| LATIN_CONFUSABLES.contains(c))
+}
+
+/// Decodes the punycode (RFC 3492) part of an IDNA label, without the `xn--` prefix.
+fn decode_punycode(input: &str) -> Option<String> {
+    const BASE: u32 = 36;
+    const T_MIN: u32 = 1;
+    const T_MAX: u32 = 26;
+    const SKEW: u32 = 38;
+    const DAMP: u32 = 700;
+
+    fn adapt(mut delta: u32, num_points: u32, first_time: bool) -> u32 {
+        delta /= if first_time { DAMP } else { 2 };
+        delta += delta / num_points;
+        let mut k = 0;
+        while delta > ((BASE - T_MIN) * T_MAX) / 2 {
+            delta /= BASE - T_MIN;
+            k += BASE;
+        }
+        k + (((BASE - T_MIN + 1) * delta) / (delta + SKEW))
+    }
+
+    let (basic, extended) = match input.rfind('-') {
+        Some(pos) => (&input[..pos], &input[pos + 1..]),
+        None => ("", input),
+    };
+    if !basic.is_ascii() {
+        return None;
+    }
+    let mut output: Vec<char> = basic.chars().collect();
+
+    let mut n: u32 = 128;
+    let mut i: u32 = 0;
+    let mut bias: u32 = 72;
+    let mut digits = extended.chars().peekable();
+    while digits.peek().is_some() {
+        let old_i = i;
+        let mut weight: u32 = 1;
+        let mut k = BASE;
+        loop {
+            let digit = match digits.next()? {
+                c @ 'a'..='z' => c as u32 - 'a' as u32,
+                c @ 'A'..='Z' => c as u32 - 'A' as u32,
+                c @ '0'..='9' => c as u32 - '0' as u32 + 26,
+                _ => return None,
+            };
+            i = i.checked_add(digit.checked_mul(weight)?)?;
+            let t = if k <= bias {
+                T_MIN
+            } else if k >= bias + T_MAX {
+                T_MAX
+            } else {
+                k - bias
+            };
+            if digit < t {
+                break;
+            }
+            weight = weight.checked_mul(BASE - t)?;
+            k += BASE;
+        }
+        let length = output.len() as u32 + 1;
+        bias = adapt(i - old_i, length, old_i == 0);
+        n = n.checked_add(i / length)?;
+        i %= length;
+        output.insert(i as usize, char::from_u32(n)?);
+        i += 1;
+    }
+
+    Some(output.into_iter().collect())
+}
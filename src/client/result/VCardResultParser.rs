@@ -29,8 +29,6 @@ use super::{AddressBookParsedRXingResult, ParsedClientResult, ResultParser};
 static BEGIN_VCARD: Lazy<Regex> = Lazy::new(|| Regex::new("(?i:BEGIN:VCARD)").unwrap());
 static VCARD_LIKE_DATE: Lazy<Regex> = Lazy::new(|| Regex::new("\\d{4}-?\\d{2}-?\\d{2}").unwrap());
 static CR_LF_SPACE_TAB: Lazy<Regex> = Lazy::new(|| Regex::new("\r\n[ \t]").unwrap());
-static NEWLINE_ESCAPE: Lazy<Regex> = Lazy::new(|| Regex::new("\\\\[nN]").unwrap());
-static VCARD_ESCAPE: Lazy<Regex> = Lazy::new(|| Regex::new("\\\\([,;\\\\])").unwrap());
 static EQUALS: Lazy<Regex> = Lazy::new(|| Regex::new("=").unwrap());
 static UNESCAPED_SEMICOLONS: Lazy<fancy_regex::Regex> =
     Lazy::new(|| fancy_regex::Regex::new("(?<!\\\\);+").unwrap());
@@ -45,11 +43,15 @@ static SEMICOLON_OR_COMMA: Lazy<Regex> = Lazy::new(|| Regex::new("[;,]").unwrap(
 const SEMICOLON: &str = ";";
 // const UNESCAPED_SEMICOLONS: &'static str = "(?<!\\\\);+";
 const COMMA: &str = ",";
+// URI schemes whose scheme-specific part is used as the value of a VALUE=uri property
+const URI_VALUE_SCHEMES: [&str; 3] = ["tel", "mailto", "sip"];
 // const SEMICOLON_OR_COMMA: &'static str = "[;,]";
 
 /**
- * Parses contact information formatted according to the VCard (2.1) format. This is not a complete
- * implementation but should parse information as commonly encoded in 2D barcodes.
+ * Parses contact information formatted according to the VCard (2.1, 3.0 and 4.0) format. This is
+ * not a complete implementation but should parse information as commonly encoded in 2D barcodes.
+ * Properties may carry a group prefix (`item1.TEL`), parameter values may be quoted, and
+ * several TYPE parameters of a property are joined with commas.
  *
  * @author Sean Owen
  */
@@ -115,8 +117,16 @@ pub fn parse(result: &RXingResult) -> Option<ParsedClientResult> {
     let instantMessenger = matchSingleVCardPrefixedField("IMPP", &rawText, true, false);
     let geoString = matchSingleVCardPrefixedField("GEO", &rawText, true, false);
     let geo = if let Some(geo_string) = geoString {
+        let geo_string = &geo_string[0];
+        // vCard 4.0 writes a geo URI, "geo:37.386013,-122.082932;u=10"
+        let geo_string = match geo_string.get(..4) {
+            Some(scheme) if scheme.eq_ignore_ascii_case("geo:") => {
+                geo_string[4..].split(SEMICOLON).next().unwrap_or_default()
+            }
+            _ => geo_string,
+        };
         SEMICOLON_OR_COMMA
-            .split(&geo_string[0])
+            .split(geo_string)
             .map(|x| x.to_owned())
             .collect()
         // SEMICOLON_OR_COMMA.split(geoString.unwrap()[0])
@@ -126,6 +136,10 @@ pub fn parse(result: &RXingResult) -> Option<ParsedClientResult> {
     // if geo.len() != 2 {
     //   geo = null;
     // }
+    let photo = matchVCardPrefixedField("PHOTO", &rawText, true, false)
+        .and_then(|photos| photos.into_iter().next())
+        .map(toPhotoURI)
+        .unwrap_or_default();
     if let Ok(adb) = AddressBookParsedRXingResult::with_details(
         toPrimaryValues(Some(names)),
         nicknames,
@@ -144,7 +158,7 @@ pub fn parse(result: &RXingResult) -> Option<ParsedClientResult> {
         toPrimaryValues(urls),
         geo,
     ) {
-        Some(ParsedClientResult::AddressBookResult(adb))
+        Some(ParsedClientResult::AddressBookResult(adb.with_photo(photo)))
     } else {
         None
     }
@@ -168,9 +182,12 @@ pub fn matchVCardPrefixedField(
     // let newline_esc = Regex::new(NEWLINE_ESCAPE).unwrap();
     // let vcard_esc = Regex::new(VCARD_ESCAPES).unwrap();
 
-    // At start or after newline, match prefix, followed by optional metadata
-    // (led by ;) ultimately ending in colon
-    let matcher_primary = Regex::new(&format!("(?:^|\\n)(?i:{prefix})(?:;([^:]*))?:")).unwrap();
+    // At start or after newline, match prefix after an optional group name, followed by
+    // optional metadata (led by ;, values possibly quoted) ultimately ending in colon
+    let matcher_primary = Regex::new(&format!(
+        "(?:^|\\n)(?:[0-9A-Za-z-]+\\.)?(?i:{prefix})(?:;((?:[^:\"]|\"[^\"]*\")*))?:"
+    ))
+    .unwrap();
     // let matcher_primary = Regex::new(&format!("(?:^|\n){}(.*)", prefix)).unwrap();
 
     //let lower_case_raw_text = rawText.to_lowercase();
@@ -193,18 +210,19 @@ pub fn matchVCardPrefixedField(
         let metadataString = matcher.get(1); // group 1 = metadata substring
         let mut metadata: Vec<String> = Vec::new();
         let mut quotedPrintable = false;
-        let mut quotedPrintableCharset = "";
-        let mut valueType = "";
+        let mut quotedPrintableCharset = String::new();
+        let mut valueType = String::new();
         if metadataString.is_some() {
             // let mds = metadataString?.as_str().split(SEMICOLON).collect();
-            for metadatum in metadataString?.as_str().split(SEMICOLON) {
+            for metadatum in splitParameters(metadataString?.as_str()) {
                 // for (String metadatum : SEMICOLON.split(metadataString)) {
                 // if (metadata == null) {
                 //   metadata = new ArrayList<>(1);
                 // }
-                metadata.push(metadatum.to_owned());
+                let metadatum = metadatum.replace('"', "");
+                metadata.push(metadatum.clone());
 
-                let metadatumTokens = EQUALS.splitn(metadatum, 2).collect::<Vec<&str>>();
+                let metadatumTokens = EQUALS.splitn(&metadatum, 2).collect::<Vec<&str>>();
                 if metadatumTokens.len() > 1 {
                     let key = metadatumTokens[0];
                     let value = metadatumTokens[1];
@@ -213,9 +231,9 @@ pub fn matchVCardPrefixedField(
                     {
                         quotedPrintable = true;
                     } else if "CHARSET" == key.to_uppercase() {
-                        quotedPrintableCharset = value;
+                        quotedPrintableCharset = value.to_owned();
                     } else if "VALUE" == key.to_uppercase() {
-                        valueType = value;
+                        valueType = value.to_owned();
                     }
                 }
             }
@@ -258,7 +276,7 @@ pub fn matchVCardPrefixedField(
             }
 
             if quotedPrintable {
-                element = decodeQuotedPrintable(&element, quotedPrintableCharset);
+                element = decodeQuotedPrintable(&element, &quotedPrintableCharset);
                 if parseFieldDivider {
                     element = UNESCAPED_SEMICOLONS
                         .replace_all(&element, "\n")
@@ -280,11 +298,7 @@ pub fn matchVCardPrefixedField(
                     .replace_all(&element, "")
                     .to_mut()
                     .to_owned();
-                element = NEWLINE_ESCAPE
-                    .replace_all(&element, "\n")
-                    .to_mut()
-                    .to_owned();
-                element = VCARD_ESCAPE.replace_all(&element, "$1").to_mut().to_owned();
+                element = unescapeVCardText(&element);
                 // element = CR_LF_SPACE_TAB.matcher(element).replaceAll("");
                 // element = NEWLINE_ESCAPE.matcher(element).replaceAll("\n");
                 // element = VCARD_ESCAPES.matcher(element).replaceAll("$1");
@@ -292,9 +306,11 @@ pub fn matchVCardPrefixedField(
             // Only handle VALUE=uri specially
             if "uri" == valueType.to_lowercase() {
                 // Don't actually support dereferencing URIs, but use scheme-specific part not URI
-                // as value, to support tel: and mailto:
+                // as value, to support tel: and mailto:. Other URIs, like a PHOTO, are kept whole.
                 if let Ok(uri) = URI::try_from(element.as_str()) {
-                    element = uri.path().to_string();
+                    if URI_VALUE_SCHEMES.contains(&uri.scheme().as_str().to_lowercase().as_str()) {
+                        element = uri.path().to_string();
+                    }
                 }
                 // try {
                 //   element = URI.create(element).getSchemeSpecificPart();
@@ -322,6 +338,52 @@ pub fn matchVCardPrefixedField(
     }
 }
 
+/**
+ * Splits property parameters at the semicolons that are not inside a quoted value.
+ */
+fn splitParameters(parameters: &str) -> Vec<&str> {
+    let mut result = Vec::new();
+    let mut start = 0;
+    let mut quoted = false;
+    for (i, c) in parameters.char_indices() {
+        if c == '"' {
+            quoted = !quoted;
+        } else if c == ';' && !quoted {
+            result.push(&parameters[start..i]);
+            start = i + 1;
+        }
+    }
+    result.push(&parameters[start..]);
+    result
+}
+
+/**
+ * Undoes the escaping of text values in a single pass: `\n` or `\N` is a newline and
+ * `\,`, `\;` and `\\` are the literal character. Other backslashes are kept.
+ */
+fn unescapeVCardText(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut chars = value.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.peek() {
+            Some('n') | Some('N') => {
+                result.push('\n');
+                chars.next();
+            }
+            Some(&escaped @ (',' | ';' | '\\')) => {
+                result.push(escaped);
+                chars.next();
+            }
+            _ => result.push(c),
+        }
+    }
+    result
+}
+
 fn decodeQuotedPrintable(value: &str, charset: &str) -> String {
     let length = value.len();
     let mut result = String::with_capacity(length);
@@ -428,6 +490,40 @@ fn toPrimaryValues(lists: Option<Vec<Vec<String>>>) -> Vec<String> {
     result
 }
 
+/**
+ * Returns the URI of a PHOTO property. An inline, base64 encoded photo of vCard 2.1 or 3.0
+ * (ENCODING=b or ENCODING=BASE64) becomes a data URI.
+ */
+fn toPhotoURI(photo: Vec<String>) -> String {
+    let Some((value, metadata)) = photo.split_last() else {
+        return String::default();
+    };
+    let mut inline = false;
+    let mut image_type = "";
+    for metadatum in metadata {
+        if let Some((key, param)) = metadatum.split_once('=') {
+            if key.eq_ignore_ascii_case("ENCODING") {
+                inline = param.eq_ignore_ascii_case("b") || param.eq_ignore_ascii_case("BASE64");
+            } else if key.eq_ignore_ascii_case("TYPE") {
+                image_type = param;
+            }
+        } else if metadatum.eq_ignore_ascii_case("BASE64") {
+            inline = true;
+        } else {
+            image_type = metadatum;
+        }
+    }
+    if !inline {
+        return value.clone();
+    }
+    let data: String = value.chars().filter(|c| !c.is_whitespace()).collect();
+    if image_type.is_empty() {
+        format!("data:;base64,{data}")
+    } else {
+        format!("data:image/{};base64,{data}", image_type.to_lowercase())
+    }
+}
+
 fn toTypes(lists: Option<Vec<Vec<String>>>) -> Vec<String> {
     let local_lists = lists.unwrap_or_default();
     if local_lists.is_empty() {
@@ -438,24 +534,28 @@ fn toTypes(lists: Option<Vec<Vec<String>>>) -> Vec<String> {
         // for (List<String> list : lists) {
         if let Some(value) = list.first() {
             if !value.is_empty() {
-                let mut v_type = String::new();
                 let final_value = list.last().unwrap_or(&String::default()).clone();
                 if !final_value.is_empty() {
-                    for i in 0..list.len() - 1 {
+                    let mut types: Vec<&str> = Vec::new();
+                    let mut label = "";
+                    for metadatum in &list[..list.len() - 1] {
                         // for (int i = 1; i < list.size(); i++) {
-                        let metadatum = list.get(i).unwrap_or(&String::default()).clone();
                         if let Some(equals) = metadatum.find('=') {
                             if "TYPE" == (metadatum[0..equals]).to_uppercase() {
-                                metadatum[equals + 1..].clone_into(&mut v_type);
-                                break;
+                                types.push(&metadatum[equals + 1..]);
                             }
-                        } else {
+                        } else if label.is_empty() {
                             // if (equals < 0) {
                             // take the whole thing as a usable label
-                            metadatum.clone_into(&mut v_type);
-                            break;
+                            label = metadatum;
                         }
                     }
+                    // vCard 4.0 may repeat TYPE, as in TEL;TYPE=work;TYPE=voice
+                    let v_type = if types.is_empty() {
+                        label.to_owned()
+                    } else {
+                        types.join(COMMA)
+                    };
                     result.push(v_type);
                 }
             }
@@ -27,6 +27,8 @@ use super::{CalendarParsedRXingResult, ParsedClientResult, ResultParser, VCardRe
 /**
  * Partially implements the iCalendar format's "VEVENT" format for specifying a
  * calendar event. See RFC 2445. This supports SUMMARY, LOCATION, GEO, DTSTART and DTEND fields.
+ * A TZID parameter on DTSTART or DTEND places a local time in that time zone, and RRULE is
+ * passed through unparsed.
  *
  * @author Sean Owen
  */
@@ -58,6 +60,7 @@ pub fn parse(result: &RXingResult) -> Option<ParsedClientResult> {
         }
     }
     let description = matchSingleVCardPrefixedField("DESCRIPTION", &rawText);
+    let recurrenceRule = matchSingleVCardPrefixedField("RRULE", &rawText);
 
     let geoString = matchSingleVCardPrefixedField("GEO", &rawText);
     let latitude;
@@ -92,7 +95,9 @@ pub fn parse(result: &RXingResult) -> Option<ParsedClientResult> {
         latitude,
         longitude,
     ) {
-        Some(ParsedClientResult::CalendarEventResult(cpr))
+        Some(ParsedClientResult::CalendarEventResult(
+            cpr.with_recurrence_rule(recurrenceRule),
+        ))
     } else {
         None
     }
@@ -120,19 +125,16 @@ fn matchSingleVCardPrefixedField(prefix: &str, rawText: &str) -> String {
         if values.is_empty() {
             String::default()
         } else {
-            let tz_mod = if values.len() > 1 {
-                if let Some(v) = values.get(values.len() - 2) {
-                    if let Some(tz_loc) = v.find("TZID=") {
-                        v[tz_loc + 5..].to_owned()
-                    } else {
-                        String::default()
-                    }
-                } else {
-                    String::default()
-                }
-            } else {
-                String::default()
-            };
+            // The time zone of a local time is appended to it, whatever the position of TZID
+            // among the parameters
+            let tz_mod = values[..values.len() - 1]
+                .iter()
+                .find_map(|v| {
+                    v.split_once('=')
+                        .filter(|(key, _)| key.eq_ignore_ascii_case("TZID"))
+                        .map(|(_, tz)| tz.to_owned())
+                })
+                .unwrap_or_default();
             let root_time = values.last().unwrap().clone();
             format!("{root_time}{tz_mod}")
         }
@@ -152,7 +154,8 @@ fn matchVCardPrefixedField(prefix: &str, rawText: &str) -> Vec<String> {
             for (i, res) in result.iter_mut().enumerate().take(size) {
                 // for i in 0..size {
                 // for (int i = 0; i < size; i++) {
-                res.clone_from(values.get(i).unwrap().first().unwrap());
+                // the value comes after the parameters, as in ATTENDEE;CN=Bob:mailto:bob@example.org
+                res.clone_from(values.get(i).unwrap().last().unwrap());
             }
             result
         }
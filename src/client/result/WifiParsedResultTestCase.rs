@@ -28,7 +28,10 @@
  */
 // public final class WifiParsedRXingResultTestCase extends Assert {
 use crate::{
-    client::result::{ParsedClientResult, ParsedRXingResult, ParsedRXingResultType},
+    client::result::{
+        ParsedClientResult, ParsedRXingResult, ParsedRXingResultType, WifiParsedRXingResult,
+        WifiPayload,
+    },
     BarcodeFormat, RXingResult,
 };
 
@@ -143,6 +146,68 @@ fn testEscape() {
     );
 }
 
+#[test]
+fn testKeyInValue() {
+    doTest(
+        "WIFI:S:NotP\\:here;T:WPA;P:secretS:;;",
+        "NotP:here",
+        "secretS:",
+        "WPA",
+    );
+    // the last field may lack its semicolon
+    doTest("WIFI:T:WPA;S:Net;P:pass", "Net", "pass", "WPA");
+}
+
+#[test]
+fn testHidden() {
+    assert!(parseWifi("WIFI:S:Net;T:nopass;H:True;;").isHidden());
+    assert!(!parseWifi("WIFI:S:Net;T:nopass;H:false;;").isHidden());
+}
+
+#[test]
+fn testWifiPayload() {
+    let payload = WifiPayload::new("My \"Net\"; 5GHz, 2")
+        .with_network_encryption("WPA")
+        .with_password("p\\ss:w;rd")
+        .with_hidden(true);
+    let contents = payload.build().unwrap();
+    assert_eq!(
+        "WIFI:T:WPA;S:My \\\"Net\\\"\\; 5GHz\\, 2;P:p\\\\ss\\:w\\;rd;H:true;;",
+        contents
+    );
+    doTest(&contents, "My \"Net\"; 5GHz, 2", "p\\ss:w;rd", "WPA");
+    assert_eq!(payload, WifiPayload::from(&parseWifi(&contents)));
+
+    let enterprise = WifiPayload::new("Corp")
+        .with_network_encryption("WPA2-EAP")
+        .with_eap_method("TTLS", "MSCHAPV2")
+        .with_anonymous_identity("anon")
+        .with_identity("user")
+        .with_password("secret");
+    let contents = enterprise.build().unwrap();
+    assert_eq!(
+        "WIFI:T:WPA2-EAP;S:Corp;E:TTLS;PH2:MSCHAPV2;A:anon;I:user;P:secret;;",
+        contents
+    );
+    assert_eq!(enterprise, WifiPayload::from(&parseWifi(&contents)));
+
+    assert_eq!(
+        "WIFI:T:nopass;S:Open;;",
+        WifiPayload::new("Open").build().unwrap()
+    );
+    assert!(WifiPayload::new("").build().is_err());
+    assert!(WifiPayload::new("Open").with_password("x").build().is_err());
+}
+
+fn parseWifi(contents: &str) -> WifiParsedRXingResult {
+    let fakeRXingResult =
+        RXingResult::new(contents, Vec::new(), Vec::new(), BarcodeFormat::QR_CODE);
+    match ResultParser::parseRXingResult(&fakeRXingResult) {
+        ParsedClientResult::WiFiResult(wifi) => wifi,
+        _ => panic!("Expected WIFI"),
+    }
+}
+
 /**
  * Given the string contents for the barcode, check that it matches our expectations
  */
@@ -29,7 +29,9 @@ use super::ResultParser;
  *
  * <p>"EAP method" can e.g. be "TTLS" or "PWD" or one of the other fields in <a href="https://developer.android.com/reference/android/net/wifi/WifiEnterpriseConfig.Eap.html">WifiEnterpriseConfig.Eap</a> and "Phase 2 method" can e.g. be "MSCHAPV2" or any of the other fields in <a href="https://developer.android.com/reference/android/net/wifi/WifiEnterpriseConfig.Phase2.html">WifiEnterpriseConfig.Phase2</a></p>
  *
- * <p>The fields can appear in any order. Only "S:" is required.</p>
+ * <p>The fields can appear in any order. Only "S:" is required. A field is only recognized at the
+ * start of a value, so a value may contain another key, like "P:" in an SSID, as long as its
+ * semicolons are escaped.</p>
  *
  * @author Vikram Aggarwal
  * @author Sean Owen
@@ -42,40 +44,40 @@ pub fn parse(theRXingResult: &crate::RXingResult) -> Option<super::ParsedClientR
     if !rawText_unstripped.starts_with(WIFI_TEST) {
         return None;
     }
-    let rawText = rawText_unstripped[WIFI_TEST.len()..].to_owned();
-    let ssid =
-        ResultParser::matchSinglePrefixedField("S:", &rawText, ';', false).unwrap_or_default();
+    let fields = parseFields(&rawText_unstripped[WIFI_TEST.len()..]);
+    let field = |key: &str| {
+        fields
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, value)| value.clone())
+    };
+    let ssid = field("S").unwrap_or_default();
 
     if ssid.is_empty() {
         return None;
     }
 
-    let pass =
-        ResultParser::matchSinglePrefixedField("P:", &rawText, ';', false).unwrap_or_default();
+    let pass = field("P").unwrap_or_default();
 
-    let n_type = ResultParser::matchSinglePrefixedField("T:", &rawText, ';', false)
-        .unwrap_or(String::from("nopass"));
+    let n_type = field("T").unwrap_or(String::from("nopass"));
 
     // Unfortunately, in the past, H: was not just used for boolean 'hidden', but 'phase 2 method'.
     // To try to retain backwards compatibility, we set one or the other based on whether the string
     // is 'true' or 'false':
     let mut hidden = false;
-    let mut phase2Method = ResultParser::matchSinglePrefixedField("PH2:", &rawText, ';', false);
-    if let Some(hv) = ResultParser::matchSinglePrefixedField("H:", &rawText, ';', false) {
+    let mut phase2Method = field("PH2");
+    if let Some(hv) = field("H") {
         // If PH2 was specified separately, or if the value is clearly boolean, interpret it as 'hidden'
         if phase2Method.is_some() || "true" == hv.to_lowercase() || "false" == hv.to_lowercase() {
-            hidden = hv.parse().ok()?;
+            hidden = "true" == hv.to_lowercase();
         } else {
             phase2Method = Some(hv);
         }
     };
 
-    let identity =
-        ResultParser::matchSinglePrefixedField("I:", &rawText, ';', false).unwrap_or_default();
-    let anonymousIdentity =
-        ResultParser::matchSinglePrefixedField("A:", &rawText, ';', false).unwrap_or_default();
-    let eapMethod =
-        ResultParser::matchSinglePrefixedField("E:", &rawText, ';', false).unwrap_or_default();
+    let identity = field("I").unwrap_or_default();
+    let anonymousIdentity = field("A").unwrap_or_default();
+    let eapMethod = field("E").unwrap_or_default();
 
     Some(ParsedClientResult::WiFiResult(
         WifiParsedRXingResult::with_details(
@@ -91,3 +93,28 @@ pub fn parse(theRXingResult: &crate::RXingResult) -> Option<super::ParsedClientR
     ))
 }
 // }
+
+/**
+ * Splits the fields after "WIFI:" at unescaped semicolons into their key, before the first
+ * colon, and unescaped value. Fields with an empty value are left out.
+ */
+fn parseFields(rawText: &str) -> Vec<(String, String)> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut escaped = false;
+    // a last field without its semicolon still counts
+    for c in rawText.chars().chain(std::iter::once(';')) {
+        if c == ';' && !escaped {
+            if let Some((key, value)) = field.split_once(':') {
+                if !value.is_empty() {
+                    fields.push((key.to_owned(), ResultParser::unescapeBackslash(value)));
+                }
+            }
+            field.clear();
+        } else {
+            field.push(c);
+        }
+        escaped = c == '\\' && !escaped;
+    }
+    fields
+}
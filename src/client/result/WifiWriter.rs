@@ -0,0 +1,155 @@
+/*
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use crate::{common::Result, Exceptions};
+
+use super::WifiParsedRXingResult;
+
+/**
+ * Builds a `WIFI:` network configuration payload, with its special characters escaped. The
+ * payload parses back into a [`WifiParsedRXingResult`] with the same values.
+ *
+ * ```
+ * use rxing::client::result::WifiPayload;
+ *
+ * let payload = WifiPayload::new("Home; 5GHz")
+ *     .with_network_encryption("WPA")
+ *     .with_password("p@ss:word")
+ *     .build()?;
+ * assert_eq!("WIFI:T:WPA;S:Home\\; 5GHz;P:p@ss\\:word;;", payload);
+ * # Ok::<(), rxing::Exceptions>(())
+ * ```
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WifiPayload {
+    ssid: String,
+    network_encryption: String,
+    password: String,
+    hidden: bool,
+    identity: String,
+    anonymous_identity: String,
+    eap_method: String,
+    phase2_method: String,
+}
+
+impl WifiPayload {
+    pub fn new(ssid: &str) -> Self {
+        Self {
+            ssid: ssid.to_owned(),
+            network_encryption: "nopass".to_owned(),
+            password: String::default(),
+            hidden: false,
+            identity: String::default(),
+            anonymous_identity: String::default(),
+            eap_method: String::default(),
+            phase2_method: String::default(),
+        }
+    }
+
+    /// The authentication type, like "WEP", "WPA", "SAE", "WPA2-EAP" or "nopass" (the default)
+    pub fn with_network_encryption(mut self, network_encryption: &str) -> Self {
+        network_encryption.clone_into(&mut self.network_encryption);
+        self
+    }
+
+    pub fn with_password(mut self, password: &str) -> Self {
+        password.clone_into(&mut self.password);
+        self
+    }
+
+    pub fn with_hidden(mut self, hidden: bool) -> Self {
+        self.hidden = hidden;
+        self
+    }
+
+    /// The EAP method, like "TTLS" or "PEAP", and phase 2 method, like "MSCHAPV2", of WPA2-EAP
+    pub fn with_eap_method(mut self, eap_method: &str, phase2_method: &str) -> Self {
+        eap_method.clone_into(&mut self.eap_method);
+        phase2_method.clone_into(&mut self.phase2_method);
+        self
+    }
+
+    /// The user name of WPA2-EAP
+    pub fn with_identity(mut self, identity: &str) -> Self {
+        identity.clone_into(&mut self.identity);
+        self
+    }
+
+    pub fn with_anonymous_identity(mut self, anonymous_identity: &str) -> Self {
+        anonymous_identity.clone_into(&mut self.anonymous_identity);
+        self
+    }
+
+    /**
+     * Serializes the network configuration.
+     *
+     * @return the payload, or an error if the SSID is empty or an open network has a password
+     */
+    pub fn build(&self) -> Result<String> {
+        if self.ssid.is_empty() {
+            return Err(Exceptions::illegal_argument_with("the SSID is empty"));
+        }
+        let open = self.network_encryption.is_empty()
+            || self.network_encryption.eq_ignore_ascii_case("nopass");
+        if open && !self.password.is_empty() {
+            return Err(Exceptions::illegal_argument_with(
+                "an open network has no password",
+            ));
+        }
+
+        let mut result = String::from("WIFI:");
+        append_field("T", &self.network_encryption, &mut result);
+        append_field("S", &self.ssid, &mut result);
+        append_field("E", &self.eap_method, &mut result);
+        append_field("PH2", &self.phase2_method, &mut result);
+        append_field("A", &self.anonymous_identity, &mut result);
+        append_field("I", &self.identity, &mut result);
+        append_field("P", &self.password, &mut result);
+        if self.hidden {
+            append_field("H", "true", &mut result);
+        }
+        result.push(';');
+        Ok(result)
+    }
+}
+
+impl From<&WifiParsedRXingResult> for WifiPayload {
+    fn from(wifi: &WifiParsedRXingResult) -> Self {
+        Self {
+            ssid: wifi.getSsid().to_owned(),
+            network_encryption: wifi.getNetworkEncryption().to_owned(),
+            password: wifi.getPassword().to_owned(),
+            hidden: wifi.isHidden(),
+            identity: wifi.getIdentity().to_owned(),
+            anonymous_identity: wifi.getAnonymousIdentity().to_owned(),
+            eap_method: wifi.getEapMethod().to_owned(),
+            phase2_method: wifi.getPhase2Method().to_owned(),
+        }
+    }
+}
+
+fn append_field(key: &str, value: &str, result: &mut String) {
+    if value.is_empty() {
+        return;
+    }
+    result.push_str(key);
+    result.push(':');
+    for c in value.chars() {
+        if matches!(c, '\\' | ';' | ',' | ':' | '"') {
+            result.push('\\');
+        }
+        result.push(c);
+    }
+    result.push(';');
+}
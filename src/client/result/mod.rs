@@ -2,14 +2,23 @@ mod AbstractDoCoMoResultParser;
 mod AddressBookAUResultParser;
 mod AddressBookDoCoMoResultParser;
 mod AddressBookParsedResult;
+mod AddressBookWriter;
 mod BizcardResultParser;
 mod BookmarkDoCoMoResultParser;
 mod CalendarParsedResult;
+mod CalendarWriter;
+mod EPCPaymentParsedResult;
+mod EPCPaymentResultParser;
 mod EmailAddressParsedResult;
 mod EmailAddressResultParser;
 mod EmailDoCoMoResultParser;
 mod ExpandedProductParsedResult;
 mod ExpandedProductResultParser;
+mod GS1DigitalLinkParsedResult;
+mod GS1DigitalLinkResultParser;
+mod GS1DigitalLinkWriter;
+mod GS1ParsedResult;
+mod GS1ResultParser;
 mod GeoParsedResult;
 mod GeoResultParser;
 mod ISBNParsedResult;
@@ -23,11 +32,15 @@ mod SMSMMSResultParser;
 mod SMSParsedResult;
 mod SMSTOMMSTOResultParser;
 mod SMTPResultParser;
+mod SwissQRBillParsedResult;
+mod SwissQRBillResultParser;
+mod SwissQRBillWriter;
 mod TelParsedResult;
 mod TelResultParser;
 mod TextParsedResult;
 mod URIParsedResult;
 mod URIResultParser;
+mod URISafety;
 mod URLTOResultParser;
 mod VCardResultParser;
 mod VEventResultParser;
@@ -35,6 +48,7 @@ mod VINParsedResult;
 mod VINResultParser;
 mod WifiParsedResult;
 mod WifiResultParser;
+mod WifiWriter;
 
 use std::fmt;
 
@@ -47,16 +61,26 @@ pub use TextParsedResult::*;
 pub use ISBNParsedResult::*;
 // pub use ISBNResultParser::*;
 pub use WifiParsedResult::*;
+pub use WifiWriter::*;
 // pub use WifiResultParser::*;
 pub use GeoParsedResult::*;
 // pub use GeoResultParser::*;
 pub use AddressBookParsedResult::*;
+pub use AddressBookWriter::*;
 pub use CalendarParsedResult::*;
+pub use CalendarWriter::*;
+pub use EPCPaymentParsedResult::*;
 pub use EmailAddressParsedResult::*;
 pub use ExpandedProductParsedResult::*;
+pub use GS1DigitalLinkParsedResult::*;
+pub use GS1DigitalLinkWriter::*;
+pub use GS1ParsedResult::*;
 pub use ProductParsedResult::*;
 pub use SMSParsedResult::*;
+pub use SwissQRBillParsedResult::*;
+pub use SwissQRBillWriter::*;
 pub use URIParsedResult::*;
+pub use URISafety::*;
 pub use VINParsedResult::*;
 
 mod other_parsed_result;
@@ -67,10 +91,16 @@ mod AddressBookParsedResultTestCase;
 #[cfg(test)]
 mod CalendarParsedResultTestCase;
 #[cfg(test)]
+mod EPCPaymentParsedResultTestCase;
+#[cfg(test)]
 mod EmailAddressParsedResultTestCase;
 #[cfg(test)]
 mod ExpandedProductParsedResultTestCase;
 #[cfg(test)]
+mod GS1DigitalLinkParsedResultTestCase;
+#[cfg(test)]
+mod GS1ParsedResultTestCase;
+#[cfg(test)]
 mod GeoParsedResultTestCase;
 #[cfg(test)]
 mod ISBNParsedResultTestCase;
@@ -81,6 +111,8 @@ mod ProductParsedResultTestCase;
 #[cfg(test)]
 mod SMSMMSParsedResultTestCase;
 #[cfg(test)]
+mod SwissQRBillParsedResultTestCase;
+#[cfg(test)]
 mod TelParsedResultTestCase;
 #[cfg(test)]
 mod URIParsedResultTestCase;
@@ -104,6 +136,10 @@ pub enum ParsedClientResult {
     AddressBookResult(AddressBookParsedRXingResult),
     CalendarEventResult(CalendarParsedRXingResult),
     ExpandedProductResult(ExpandedProductParsedRXingResult),
+    EPCPaymentResult(EPCPaymentParsedRXingResult),
+    SwissQRBillResult(SwissQRBillParsedRXingResult),
+    GS1DigitalLinkResult(GS1DigitalLinkParsedRXingResult),
+    GS1Result(GS1ParsedRXingResult),
     Other(OtherParsedResult),
 }
 
@@ -123,6 +159,10 @@ impl ParsedRXingResult for ParsedClientResult {
             ParsedClientResult::AddressBookResult(a) => a.getType(),
             ParsedClientResult::CalendarEventResult(a) => a.getType(),
             ParsedClientResult::ExpandedProductResult(a) => a.getType(),
+            ParsedClientResult::EPCPaymentResult(a) => a.getType(),
+            ParsedClientResult::SwissQRBillResult(a) => a.getType(),
+            ParsedClientResult::GS1DigitalLinkResult(a) => a.getType(),
+            ParsedClientResult::GS1Result(a) => a.getType(),
             ParsedClientResult::Other(a) => a.getType(),
         }
     }
@@ -142,6 +182,10 @@ impl ParsedRXingResult for ParsedClientResult {
             ParsedClientResult::AddressBookResult(a) => a.getDisplayRXingResult(),
             ParsedClientResult::CalendarEventResult(a) => a.getDisplayRXingResult(),
             ParsedClientResult::ExpandedProductResult(a) => a.getDisplayRXingResult(),
+            ParsedClientResult::EPCPaymentResult(a) => a.getDisplayRXingResult(),
+            ParsedClientResult::SwissQRBillResult(a) => a.getDisplayRXingResult(),
+            ParsedClientResult::GS1DigitalLinkResult(a) => a.getDisplayRXingResult(),
+            ParsedClientResult::GS1Result(a) => a.getDisplayRXingResult(),
             ParsedClientResult::Other(a) => a.getDisplayRXingResult(),
         }
     }
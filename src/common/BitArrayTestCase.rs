@@ -26,7 +26,7 @@
 //  */
 // public final class BitArrayTestCase extends Assert {
 
-use crate::common::BIT_FIELD_BASE_BITS;
+use crate::common::{BitFieldBaseType, BIT_FIELD_BASE_BITS};
 
 use super::{BitArray, BIT_FIELD_SHIFT_BITS};
 use rand::Rng;
@@ -211,12 +211,12 @@ fn test_get_array() {
     if BIT_FIELD_BASE_BITS == 64 {
         assert_eq!(
             ints,
-            &[9223372036854775809_usize.try_into().unwrap_or_default()]
+            &[BitFieldBaseType::try_from(9223372036854775809_usize).unwrap_or_default()]
         )
     } else if BIT_FIELD_BASE_BITS == 128 {
         assert_eq!(
             ints,
-            &[9223372036854775809_u128.try_into().unwrap_or_default()]
+            &[BitFieldBaseType::try_from(9223372036854775809_u128).unwrap_or_default()]
         )
     } else {
         assert_eq!(1, ints[0]);
@@ -281,6 +281,29 @@ fn reverse_test_2() {
     }
 }
 
+#[test]
+fn reverse_from_test() {
+    let oldBits: Vec<super::BitFieldBaseType> = vec![128, 256, 512, 6453324, 50934953];
+    let mut newBitArray = BitArray::new();
+    for size in 1..160 {
+        let newBitsOriginal = reverse_original(&oldBits.clone(), size);
+        let oldBitArray = BitArray::with_initial_values(oldBits.clone(), size);
+        newBitArray.reverse_from(&oldBitArray);
+        assert_eq!(size, newBitArray.get_size());
+        let newBitsNew = newBitArray.getBitArray();
+        assert!(
+            arrays_are_equal(&newBitsOriginal, newBitsNew, size / BIT_FIELD_BASE_BITS + 1),
+            "size: ({size}) : {newBitsOriginal:?}/{newBitsNew:?}"
+        );
+    }
+    // reversing twice gives back the original
+    let oldBitArray = BitArray::with_initial_values(oldBits, 100);
+    let mut reversed = BitArray::new();
+    reversed.reverse_from(&oldBitArray);
+    newBitArray.reverse_from(&reversed);
+    assert!((0..100).all(|i| oldBitArray.get(i) == newBitArray.get(i)));
+}
+
 #[test]
 fn test_clone() {
     let array = BitArray::with_size(32);
@@ -412,6 +412,26 @@ impl BitArray {
         // first we save off the current version as the reversed version
         self.reversed = Some(self.bits.clone());
 
+        self.reverse_bits();
+    }
+
+    /**
+     * Makes this array the reverse of `other`, reusing its storage instead of allocating a
+     * new array for every row that is read backwards.
+     */
+    pub fn reverse_from(&mut self, other: &BitArray) {
+        self.bits.clone_from(&other.bits);
+        self.size = other.size;
+        self.read_offset = 0;
+        self.reversed = None;
+        self.reverse_bits();
+    }
+
+    fn reverse_bits(&mut self) {
+        if self.size == 0 {
+            return;
+        }
+
         // reverse all int's first
         let len = (self.size - 1) / BASE_BITS;
         let oldBitsLen = len + 1;
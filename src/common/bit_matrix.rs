@@ -333,9 +333,17 @@ impl BitMatrix {
      * <p>Flips every bit in the matrix.</p>
      */
     pub fn flip_self(&mut self) {
-        let max = self.bits.len();
-        for bit_set in self.bits.iter_mut().take(max) {
-            *bit_set = !*bit_set;
+        // the bits past the width in the last word of a row stay clear, or they would be
+        // found by getEnclosingRectangle() and getBottomRightOnBit()
+        let padding = self.row_size * BASE_BITS - self.width as usize;
+        let last_word_mask = BaseType::MAX >> padding;
+        for row in self.bits.chunks_exact_mut(self.row_size.max(1)) {
+            for bit_set in row.iter_mut() {
+                *bit_set = !*bit_set;
+            }
+            if let Some(last) = row.last_mut() {
+                *last &= last_word_mask;
+            }
         }
     }
 
@@ -346,23 +354,37 @@ impl BitMatrix {
      * @param mask XOR mask
      */
     pub fn xor(&mut self, mask: &BitMatrix) -> Result<()> {
-        if self.width != mask.width || self.height != mask.height || self.row_size != mask.row_size
+        self.combine(mask, |a, b| a ^ b)
+    }
+
+    /**
+     * Conjunction (AND): Clear the bit in this {@code BitMatrix} if the corresponding
+     * bit of `other` is clear.
+     */
+    pub fn and(&mut self, other: &BitMatrix) -> Result<()> {
+        self.combine(other, |a, b| a & b)
+    }
+
+    /**
+     * Disjunction (OR): Set the bit in this {@code BitMatrix} if the corresponding
+     * bit of `other` is set.
+     */
+    pub fn or(&mut self, other: &BitMatrix) -> Result<()> {
+        self.combine(other, |a, b| a | b)
+    }
+
+    /// Combines the words of this matrix with those of an equally sized one, in place
+    fn combine(&mut self, other: &BitMatrix, op: fn(BaseType, BaseType) -> BaseType) -> Result<()> {
+        if self.width != other.width
+            || self.height != other.height
+            || self.row_size != other.row_size
         {
             return Err(Exceptions::illegal_argument_with(
                 "input matrix dimensions do not match",
             ));
         }
-        // let mut rowArray = BitArray::with_size(self.width as usize);
-        for y in 0..self.height {
-            //for (int y = 0; y < height; y++) {
-            let offset = y as usize * self.row_size;
-            let rowArray = mask.getRow(y);
-            let row = rowArray.getBitArray();
-            for (x, row_x) in row.iter().enumerate().take(self.row_size) {
-                // for x in 0..self.row_size {
-                //for (int x = 0; x < rowSize; x++) {
-                self.bits[offset + x] ^= *row_x;
-            }
+        for (word, other) in self.bits.iter_mut().zip(&other.bits) {
+            *word = op(*word, *other);
         }
         Ok(())
     }
@@ -451,6 +473,136 @@ impl BitMatrix {
         //System.arraycopy(row.getBitArray(), 0, self.bits, y * self.rowSize, self.rowSize);
     }
 
+    /**
+     * The words holding row `y`, least significant bit first, like {@link #getRow(int)} without
+     * the copy. The bits past the width in the last word are clear.
+     */
+    #[inline(always)]
+    pub fn row_slice(&self, y: u32) -> &[BaseType] {
+        let offset = y as usize * self.row_size;
+        &self.bits[offset..offset + self.row_size]
+    }
+
+    /// A view of row `y` that reads its bits without computing the offset of the row each time
+    #[inline(always)]
+    pub fn row(&self, y: u32) -> BitRow<'_> {
+        BitRow {
+            words: self.row_slice(y),
+            width: self.width,
+        }
+    }
+
+    /// A view of column `x`, the counterpart of [`BitMatrix::row`] for walking up and down
+    #[inline(always)]
+    pub fn column(&self, x: u32) -> BitColumn<'_> {
+        let word = (x as usize / BASE_BITS).min(self.row_size.saturating_sub(1));
+        BitColumn {
+            words: &self.bits[word..],
+            row_size: self.row_size,
+            shift: x as usize & BASE_SHIFT,
+            height: if x < self.width { self.height } else { 0 },
+        }
+    }
+
+    /**
+     * Copies all of `source` into this matrix with its top left corner at (`left`, `top`),
+     * a word at a time. The bits of this matrix under `source` are replaced, the others kept.
+     */
+    pub fn set_region_from(&mut self, left: u32, top: u32, source: &BitMatrix) -> Result<()> {
+        if left as u64 + source.width as u64 > self.width as u64
+            || top as u64 + source.height as u64 > self.height as u64
+        {
+            return Err(Exceptions::illegal_argument_with(
+                "the region must fit inside the matrix",
+            ));
+        }
+        for y in 0..source.height {
+            let offset = (top + y) as usize * self.row_size;
+            for (k, &word) in source.row_slice(y).iter().enumerate() {
+                let bits = (source.width as usize - k * BASE_BITS).min(BASE_BITS);
+                let mask = BaseType::MAX >> (BASE_BITS - bits);
+                let word = word & mask;
+                let position = left as usize + k * BASE_BITS;
+                let (index, shift) = (offset + position / BASE_BITS, position & BASE_SHIFT);
+                self.bits[index] = (self.bits[index] & !(mask << shift)) | (word << shift);
+                if shift > 0 {
+                    // the part of the word that spills into the next one
+                    let high_mask = mask >> (BASE_BITS - shift);
+                    if high_mask != 0 {
+                        self.bits[index + 1] =
+                            (self.bits[index + 1] & !high_mask) | (word >> (BASE_BITS - shift));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The number of set bits in the matrix
+    pub fn count_ones(&self) -> u32 {
+        self.bits.iter().map(|word| word.count_ones()).sum()
+    }
+
+    /**
+     * The number of set bits in a rectangular region, counted a word at a time.
+     *
+     * @param left The horizontal position to begin at (inclusive)
+     * @param top The vertical position to begin at (inclusive)
+     * @param width The width of the region
+     * @param height The height of the region
+     */
+    pub fn count_ones_in(&self, left: u32, top: u32, width: u32, height: u32) -> Result<u32> {
+        if left as u64 + width as u64 > self.width as u64
+            || top as u64 + height as u64 > self.height as u64
+        {
+            return Err(Exceptions::illegal_argument_with(
+                "the region must fit inside the matrix",
+            ));
+        }
+        if width == 0 {
+            return Ok(0);
+        }
+        let (first, last) = (left as usize, (left + width - 1) as usize);
+        let (first_word, last_word) = (first / BASE_BITS, last / BASE_BITS);
+        let first_mask = BaseType::MAX << (first & BASE_SHIFT);
+        let last_mask = BaseType::MAX >> (BASE_SHIFT - (last & BASE_SHIFT));
+        let mut count = 0;
+        for y in top..top + height {
+            let row = &self.row_slice(y)[first_word..=last_word];
+            count += if first_word == last_word {
+                (row[0] & first_mask & last_mask).count_ones()
+            } else {
+                (row[0] & first_mask).count_ones()
+                    + row[1..row.len() - 1]
+                        .iter()
+                        .map(|word| word.count_ones())
+                        .sum::<u32>()
+                    + (row[row.len() - 1] & last_mask).count_ones()
+            };
+        }
+        Ok(count)
+    }
+
+    /// The (x, y) positions of the set bits, row by row, skipping clear words
+    pub fn iter_set_bits(&self) -> impl Iterator<Item = (u32, u32)> + '_ {
+        self.bits
+            .chunks_exact(self.row_size.max(1))
+            .enumerate()
+            .flat_map(|(y, row)| {
+                row.iter().enumerate().flat_map(move |(k, &word)| {
+                    let mut word = word;
+                    std::iter::from_fn(move || {
+                        if word == 0 {
+                            return None;
+                        }
+                        let bit = word.trailing_zeros() as usize;
+                        word &= word - 1;
+                        Some(((k * BASE_BITS + bit) as u32, y as u32))
+                    })
+                })
+            })
+    }
+
     /**
      * Modifies this {@code BitMatrix} to represent the same but rotated the given degrees (0, 90, 180, 270)
      *
@@ -663,6 +815,17 @@ impl BitMatrix {
     //   return hash;
     // }
 
+    /// The matrix as 8 bit grayscale pixels, row by row, 0 for a set bit and 255 for an unset one
+    pub fn to_luma8(&self) -> Vec<u8> {
+        let mut luma = Vec::with_capacity(self.width as usize * self.height as usize);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                luma.push(if self.get(x, y) { 0 } else { u8::MAX });
+            }
+        }
+        luma
+    }
+
     /**
      * @param setString representation of a set bit
      * @param unsetString representation of an unset bit
@@ -735,6 +898,58 @@ impl BitMatrix {
     }
 }
 
+/// A row of a [`BitMatrix`], see [`BitMatrix::row`]
+#[derive(Debug, Clone, Copy)]
+pub struct BitRow<'a> {
+    words: &'a [BaseType],
+    width: u32,
+}
+
+impl BitRow<'_> {
+    /// The bit in column `x`, false past the end of the row
+    #[inline(always)]
+    pub fn get(&self, x: u32) -> bool {
+        x < self.width && (self.words[x as usize / BASE_BITS] >> (x as usize & BASE_SHIFT)) & 1 != 0
+    }
+
+    #[inline(always)]
+    pub fn len(&self) -> u32 {
+        self.width
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.width == 0
+    }
+}
+
+/// A column of a [`BitMatrix`], see [`BitMatrix::column`]
+#[derive(Debug, Clone, Copy)]
+pub struct BitColumn<'a> {
+    words: &'a [BaseType],
+    row_size: usize,
+    shift: usize,
+    height: u32,
+}
+
+impl BitColumn<'_> {
+    /// The bit in row `y`, false past the end of the column
+    #[inline(always)]
+    pub fn get(&self, y: u32) -> bool {
+        y < self.height && (self.words[y as usize * self.row_size] >> self.shift) & 1 != 0
+    }
+
+    #[inline(always)]
+    pub fn len(&self) -> u32 {
+        self.height
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.height == 0
+    }
+}
+
 impl fmt::Display for BitMatrix {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.toString("X ", "  "))
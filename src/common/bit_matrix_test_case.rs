@@ -300,6 +300,21 @@ fn test_unset() {
     assert_eq!(emptyMatrix, matrix);
 }
 
+#[test]
+fn test_flip() {
+    let mut matrix = BitMatrix::new(70, 3).unwrap();
+    matrix.setRegion(2, 1, 3, 1).expect("must set");
+    matrix.flip_self();
+    assert!(!matrix.get(2, 1));
+    assert!(matrix.get(69, 2));
+    assert_eq!(vec![0, 0, 70, 3], matrix.getEnclosingRectangle().unwrap());
+    assert_eq!(point(69.0, 2.0), matrix.getBottomRightOnBit().unwrap());
+    matrix.flip_self();
+    let mut expected = BitMatrix::new(70, 3).unwrap();
+    expected.setRegion(2, 1, 3, 1).expect("must set");
+    assert_eq!(expected, matrix);
+}
+
 #[test]
 fn test_xor_case() {
     let emptyMatrix = BitMatrix::new(3, 3).unwrap();
@@ -344,6 +359,133 @@ fn test_xor_case() {
     // }
 }
 
+#[test]
+fn test_and_or() {
+    let mut left = BitMatrix::new(70, 3).unwrap();
+    left.setRegion(0, 0, 40, 3).expect("must set");
+    let mut right = BitMatrix::new(70, 3).unwrap();
+    right.setRegion(30, 1, 40, 2).expect("must set");
+
+    let mut both = left.clone();
+    both.and(&right).expect("must combine");
+    let mut either = left.clone();
+    either.or(&right).expect("must combine");
+    for y in 0..3 {
+        for x in 0..70 {
+            assert_eq!(
+                left.get(x, y) && right.get(x, y),
+                both.get(x, y),
+                "({x},{y})"
+            );
+            assert_eq!(
+                left.get(x, y) || right.get(x, y),
+                either.get(x, y),
+                "({x},{y})"
+            );
+        }
+    }
+    assert_eq!(2 * 10, both.count_ones());
+    assert!(left.and(&BitMatrix::new(71, 3).unwrap()).is_err());
+    assert!(left.or(&BitMatrix::new(70, 4).unwrap()).is_err());
+}
+
+#[test]
+fn test_row_and_column() {
+    let mut matrix = BitMatrix::new(130, 5).unwrap();
+    for (x, y) in [(0, 0), (63, 1), (64, 1), (129, 4), (100, 2)] {
+        matrix.set(x, y);
+    }
+    for y in 0..5 {
+        let row = matrix.row(y);
+        assert_eq!(130, row.len());
+        assert_eq!(matrix.getRowSize(), matrix.row_slice(y).len());
+        for x in 0..131 {
+            assert_eq!(matrix.get(x, y) && x < 130, row.get(x), "({x},{y})");
+        }
+    }
+    for x in 0..130 {
+        let column = matrix.column(x);
+        assert_eq!(5, column.len());
+        for y in 0..6 {
+            assert_eq!(y < 5 && matrix.get(x, y), column.get(y), "({x},{y})");
+        }
+    }
+    assert!(matrix.column(130).is_empty());
+    assert!(!matrix.column(130).get(0));
+}
+
+#[test]
+fn test_set_region_from() {
+    let mut source = BitMatrix::new(70, 3).unwrap();
+    for (x, y) in [(0, 0), (31, 0), (32, 1), (63, 1), (64, 2), (69, 2)] {
+        source.set(x, y);
+    }
+    for left in [0, 1, 5, 31, 32, 60] {
+        let mut matrix = BitMatrix::new(140, 6).unwrap();
+        matrix.setRegion(0, 0, 140, 6).expect("must set");
+        matrix.set_region_from(left, 2, &source).expect("must fit");
+        for y in 0..6 {
+            for x in 0..140 {
+                let inside = (left..left + 70).contains(&x) && (2..5).contains(&y);
+                let expected = !inside || source.get(x - left, y - 2);
+                assert_eq!(expected, matrix.get(x, y), "{left}: ({x},{y})");
+            }
+        }
+    }
+    let mut small = BitMatrix::new(70, 3).unwrap();
+    assert!(small.set_region_from(1, 0, &source).is_err());
+    small.set_region_from(0, 0, &source).expect("must fit");
+    assert_eq!(source, small);
+}
+
+#[test]
+fn test_count_ones_in() {
+    let mut matrix = BitMatrix::new(200, 4).unwrap();
+    matrix.setRegion(10, 1, 150, 2).expect("must set");
+    matrix.set(199, 3);
+    assert_eq!(301, matrix.count_ones());
+    for (left, top, width, height) in [
+        (0, 0, 200, 4),
+        (10, 1, 150, 2),
+        (60, 0, 4, 4),
+        (63, 2, 2, 1),
+        (150, 0, 50, 4),
+        (0, 0, 10, 4),
+        (5, 2, 0, 2),
+    ] {
+        let mut expected = 0;
+        for y in top..top + height {
+            for x in left..left + width {
+                expected += matrix.get(x, y) as u32;
+            }
+        }
+        assert_eq!(
+            expected,
+            matrix.count_ones_in(left, top, width, height).unwrap(),
+            "{left} {top} {width} {height}"
+        );
+    }
+    assert!(matrix.count_ones_in(190, 0, 11, 1).is_err());
+}
+
+#[test]
+fn test_iter_set_bits() {
+    let mut matrix = BitMatrix::new(100, 3).unwrap();
+    let bits = [(5, 0), (63, 0), (64, 0), (0, 1), (99, 2)];
+    for (x, y) in bits {
+        matrix.set(x, y);
+    }
+    assert_eq!(bits.to_vec(), matrix.iter_set_bits().collect::<Vec<_>>());
+    matrix.flip_self();
+    assert_eq!(300 - 5, matrix.iter_set_bits().count());
+}
+
+#[test]
+fn test_to_luma8() {
+    let matrix = BitMatrix::parse_strings("x  \n xx\n", "x", " ").unwrap();
+    assert_eq!(vec![0, 255, 255, 255, 0, 0], matrix.to_luma8());
+}
+
 pub fn matrix_to_string(result: &BitMatrix) -> String {
     assert_eq!(1, result.getHeight());
     let mut builder = String::with_capacity(result.getWidth().try_into().unwrap());
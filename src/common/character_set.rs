@@ -19,12 +19,16 @@ use encoding::EncodingRef;
 use crate::common::Result;
 use crate::Exceptions;
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
 /**
  * Encapsulates a Character Set ECI, according to "Extended Channel Interpretations" 5.3.1.1
  * of ISO 18004.
  *
  * @author Sean Owen
  */
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum CharacterSet {
     // Enum name is a Java encoding valid for java.lang and java.io
@@ -266,12 +270,14 @@ impl CharacterSet {
     // }
 
     /**
-     * @param name character set ECI encoding name
+     * @param name character set ECI encoding name, or any other label of the encoding such as
+     *   "latin2", "sjis" or "cp1251"
      * @return CharacterSetECI representing ECI for character encoding, or null if it is legal
      *   but unsupported
      */
     pub fn get_character_set_by_name(name: &str) -> Option<CharacterSet> {
-        match name.to_lowercase().as_str() {
+        let lower = name.to_lowercase();
+        match lower.as_str() {
             "cp437" => Some(CharacterSet::Cp437),
             "iso-8859-1" => Some(CharacterSet::ISO8859_1),
             "iso-8859-2" => Some(CharacterSet::ISO8859_2),
@@ -299,12 +305,20 @@ impl CharacterSet {
             "big5" => Some(CharacterSet::Big5),
             "gb2312" => Some(CharacterSet::GB2312),
             "gb18030" => Some(CharacterSet::GB18030),
+            // GB18030 is a superset of GBK
+            "gbk" => Some(CharacterSet::GB18030),
             "euc-kr" => Some(CharacterSet::EUC_KR),
             "utf-32be" => Some(CharacterSet::UTF32BE),
             "utf-32le" => Some(CharacterSet::UTF32LE),
             "binary" => Some(CharacterSet::Binary),
             "unknown" => Some(CharacterSet::Unknown),
-            _ => None,
+            _ => {
+                let encoding = encoding::label::encoding_from_whatwg_label(&lower)?;
+                match encoding.whatwg_name()? {
+                    canonical if canonical != lower => Self::get_character_set_by_name(canonical),
+                    _ => None,
+                }
+            }
         }
     }
 
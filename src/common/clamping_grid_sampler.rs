@@ -0,0 +1,103 @@
+/*
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use crate::common::Result;
+use crate::{point, Exceptions, Point};
+
+use super::{default_grid_sampler::project_corners, BitMatrix, GridSampler, SamplerControl};
+
+/**
+ * Samples every module at its center like the [`DefaultGridSampler`](super::DefaultGridSampler),
+ * but reads a center that falls outside the image from the nearest pixel on its edge instead
+ * of failing.
+ *
+ * A symbol printed up to the edge of a label, or a grid fitted a little too large, then
+ * samples its outermost modules from the edge of the image rather than not at all, which
+ * the error correction can usually absorb.
+ */
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ClampingGridSampler;
+
+impl GridSampler for ClampingGridSampler {
+    fn sample_grid(
+        &self,
+        image: &BitMatrix,
+        dimensionX: u32,
+        dimensionY: u32,
+        controls: &[SamplerControl],
+    ) -> Result<(BitMatrix, [Point; 4])> {
+        if dimensionX == 0 || dimensionY == 0 || image.getWidth() == 0 || image.getHeight() == 0 {
+            return Err(Exceptions::NOT_FOUND);
+        }
+        if controls.iter().any(|control| !control.transform.isValid()) {
+            return Err(Exceptions::NOT_FOUND);
+        }
+
+        let max_x = (image.getWidth() - 1) as f32;
+        let max_y = (image.getHeight() - 1) as f32;
+        let mut bits = BitMatrix::new(dimensionX, dimensionY)?;
+        for SamplerControl { p0, p1, transform } in controls {
+            for y in (p0.y as u32)..(p1.y as u32) {
+                for x in (p0.x as u32)..(p1.x as u32) {
+                    let p = transform.transform_point(Point::from((x, y)).centered());
+                    if !p.x.is_finite() || !p.y.is_finite() {
+                        return Err(Exceptions::NOT_FOUND);
+                    }
+                    if image.get_point(point(p.x.clamp(0.0, max_x), p.y.clamp(0.0, max_y))) {
+                        bits.set(x, y);
+                    }
+                }
+            }
+        }
+
+        Ok((bits, project_corners(dimensionX, dimensionY, controls)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ClampingGridSampler;
+    use crate::common::{
+        BitMatrix, DefaultGridSampler, GridSampler, PerspectiveTransform, Quadrilateral,
+        SamplerControl,
+    };
+
+    #[test]
+    fn test_clamps_to_the_edge() {
+        // modules of 2 pixels, the last column and row of which lie outside the image
+        let mut image = BitMatrix::new(18, 18).unwrap();
+        for y in 0..18 {
+            image.set(17, y);
+        }
+        let transform = PerspectiveTransform::quadrilateralToQuadrilateral(
+            Quadrilateral::rectangle_from_xy(0.0, 10.0, 0.0, 10.0, Some(0.0)),
+            Quadrilateral::rectangle_from_xy(0.0, 20.0, 0.0, 20.0, Some(0.0)),
+        )
+        .unwrap();
+        let controls = [SamplerControl::new(10, 10, transform)];
+
+        assert!(DefaultGridSampler
+            .sample_grid(&image, 10, 10, &controls)
+            .is_err());
+        let (bits, _) = ClampingGridSampler
+            .sample_grid(&image, 10, 10, &controls)
+            .unwrap();
+        for y in 0..10 {
+            for x in 0..10 {
+                // the column outside the image repeats the black edge of the one before
+                assert_eq!(x >= 8, bits.get(x, y), "({x}, {y})");
+            }
+        }
+    }
+}
@@ -0,0 +1,153 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::{
+    reedsolomon::{ReedSolomonCorrection, ReedSolomonDecoder},
+    Result,
+};
+
+/**
+ * One Reed-Solomon block of a symbol: its codewords as read from the symbol and how they were
+ * corrected.
+ */
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CodewordBlock {
+    /// The data and error-correction codewords of the block, as read from the symbol
+    pub received: Vec<i32>,
+
+    /// How many of the codewords are data, the rest are error-correction codewords
+    pub num_data_codewords: usize,
+
+    /// The syndromes and the errors found in `received`, indexed into `received`
+    pub correction: ReedSolomonCorrection,
+
+    /// `received` with the errors corrected
+    pub corrected: Vec<i32>,
+}
+
+impl CodewordBlock {
+    /**
+     * Error corrects the codewords of one block.
+     *
+     * # Arguments
+     * * `decoder` - The Reed-Solomon decoder for the field of the symbology
+     * * `received` - The data and error-correction codewords, as read from the symbol
+     * * `num_data_codewords` - How many of the codewords are data
+     */
+    pub fn correct(
+        decoder: &ReedSolomonDecoder,
+        received: Vec<i32>,
+        num_data_codewords: usize,
+    ) -> Result<Self> {
+        let mut corrected = received.clone();
+        let correction = decoder
+            .decode_with_correction(&mut corrected, (received.len() - num_data_codewords) as i32)?;
+
+        Ok(Self {
+            received,
+            num_data_codewords,
+            correction,
+            corrected,
+        })
+    }
+
+    /**
+     * Like `correct`, but with the indices into `received` of codewords that couldn't be read,
+     * which are corrected as erasures.
+     */
+    pub fn correct_with_erasures(
+        decoder: &ReedSolomonDecoder,
+        received: Vec<i32>,
+        num_data_codewords: usize,
+        erasures: &[usize],
+    ) -> Result<Self> {
+        let mut corrected = received.clone();
+        let correction = decoder.decode_with_erasures(
+            &mut corrected,
+            (received.len() - num_data_codewords) as i32,
+            erasures,
+        )?;
+
+        Ok(Self {
+            received,
+            num_data_codewords,
+            correction,
+            corrected,
+        })
+    }
+
+    /// The corrected data codewords of the block
+    pub fn data_codewords(&self) -> &[i32] {
+        &self.corrected[..self.num_data_codewords]
+    }
+}
+
+/**
+ * The codewords of a 2D symbol from the order they were read in, through error correction, to
+ * the data codewords handed to the bit stream parser.
+ *
+ * Together with the sampled modules of a `DetectionWitness` this ties the decoded text back to
+ * the image: `raw_codewords` follow from the modules, each block's `received` is a fixed
+ * permutation of `raw_codewords`, and `corrected_codewords` is the concatenation (or
+ * interleaving, depending on the symbology) of the blocks' corrected data codewords.
+ */
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CodewordWitness {
+    /// The size of a codeword in bits, 8 for QR codes and Data Matrix, 6 to 12 for Aztec
+    pub codeword_size: u32,
+
+    /// All codewords in the order they were read from the symbol
+    pub raw_codewords: Vec<i32>,
+
+    /// The Reed-Solomon blocks the codewords were split into
+    pub blocks: Vec<CodewordBlock>,
+
+    /// The corrected data codewords, in the order they are decoded
+    pub corrected_codewords: Vec<i32>,
+}
+
+impl CodewordWitness {
+    pub fn new(codeword_size: u32, raw_codewords: Vec<i32>) -> Self {
+        Self {
+            codeword_size,
+            raw_codewords,
+            blocks: Vec::new(),
+            corrected_codewords: Vec::new(),
+        }
+    }
+
+    /// The number of codewords corrected over all blocks, erasures included
+    pub fn errors_corrected(&self) -> usize {
+        self.blocks
+            .iter()
+            .map(|block| {
+                block.correction.errors_corrected() + block.correction.erasures_corrected()
+            })
+            .sum()
+    }
+
+    /// The number of erased codewords filled in over all blocks
+    pub fn erasures_corrected(&self) -> usize {
+        self.blocks
+            .iter()
+            .map(|block| block.correction.erasures_corrected())
+            .sum()
+    }
+
+    /**
+     * Whether a block used up more than half of its error correction, where an error costs two
+     * error-correction codewords and an erasure one
+     */
+    pub fn uses_heavy_correction(&self) -> bool {
+        self.blocks.iter().any(|block| {
+            let ec_codewords = block.received.len() - block.num_data_codewords;
+            let used =
+                2 * block.correction.errors_corrected() + block.correction.erasures_corrected();
+            used * 2 > ec_codewords
+        })
+    }
+}
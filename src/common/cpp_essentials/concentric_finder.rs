@@ -77,6 +77,26 @@ pub fn ReadSymmetricPattern<const N: usize, Cursor: BitMatrixCursorTrait>(
     Some(res)
 }
 
+/**
+ * Shortens the outermost runs of `runs` to the width the inner runs give them in `pattern`, for
+ * a pattern whose outer bars may run into dark surroundings where there is no quiet zone.
+ */
+pub fn ClampOuterRuns(runs: &mut [PatternType], pattern: &[PatternType]) {
+    let n = runs.len();
+    if n < 3 || pattern.len() != n {
+        return;
+    }
+    let inner: u32 = runs[1..n - 1].iter().map(|&r| r as u32).sum();
+    let modules: u32 = pattern[1..n - 1].iter().map(|&m| m as u32).sum();
+    if modules == 0 {
+        return;
+    }
+    for i in [0, n - 1] {
+        let width = (inner * pattern[i] as u32).div_ceil(modules).max(1);
+        runs[i] = runs[i].min(width as PatternType);
+    }
+}
+
 // default for RELAXED_THRESHOLD should be false
 pub fn CheckSymmetricPattern<
     const E2E: bool,
@@ -88,6 +108,7 @@ pub fn CheckSymmetricPattern<
     pattern: &Pattern<LEN>,
     range: i32,
     updatePosition: bool,
+    noQuietZone: bool,
 ) -> i32 {
     let mut range = range;
 
@@ -111,7 +132,11 @@ pub fn CheckSymmetricPattern<
     range -= res[s_2] as i32;
 
     let mut next = |cur: &mut FastEdgeToEdgeCounter, i: isize| {
-        let v = cur.stepToNextEdge(range as u32) as i32;
+        let mut v = cur.stepToNextEdge(range as u32) as i32;
+        // without a quiet zone the outer bar may not end within range, it is clamped below
+        if v == 0 && noQuietZone && i.unsigned_abs() == s_2 {
+            v = 1;
+        }
         res[(s_2 as isize + i) as usize] = v as u16;
         range -= v;
 
@@ -124,6 +149,11 @@ pub fn CheckSymmetricPattern<
             return 0;
         }
     }
+    if noQuietZone {
+        let mut runs: Pattern<LEN> = std::array::from_fn(|i| res[i]);
+        ClampOuterRuns(&mut runs, pattern);
+        res = PatternRow::new(runs.to_vec());
+    }
 
     if IsPattern::<E2E, LEN, SUM, false>(
         &PatternView::new(&res),
@@ -559,6 +589,7 @@ pub fn LocateConcentricPattern<const E2E: bool, const LEN: usize, const SUM: usi
     pattern: &Pattern<LEN>,
     center: Point,
     range: i32,
+    noQuietZone: bool,
 ) -> Option<ConcentricPattern> {
     let mut cur = EdgeTracer::new(image, center.floor(), Point::default());
     let mut minSpread = image.getWidth() as i32;
@@ -571,7 +602,8 @@ pub fn LocateConcentricPattern<const E2E: bool, const LEN: usize, const SUM: usi
         // for (auto d : {PointI{0, 1}, {1, 0}}) {
         cur.setDirection(d); // THIS COULD POSSIBLY BE WRONG, WE MIGHT MEAN TO CLONE cur EACH RUN?
 
-        let spread = CheckSymmetricPattern::<E2E, LEN, SUM, _>(&mut cur, pattern, range, true);
+        let spread =
+            CheckSymmetricPattern::<E2E, LEN, SUM, _>(&mut cur, pattern, range, true, noQuietZone);
         if spread != 0 {
             UpdateMinMax(&mut minSpread, &mut maxSpread, spread);
         } else {
@@ -586,7 +618,13 @@ pub fn LocateConcentricPattern<const E2E: bool, const LEN: usize, const SUM: usi
     for d in [point(1.0, 1.0), point(1.0, -1.0)] {
         // for (auto d : {PointI{1, 1}, {1, -1}}) {
         cur.setDirection(d); // THIS COULD POSSIBLY BE WRONG, WE MIGHT MEAN TO CLONE cur EACH RUN?
-        let spread = CheckSymmetricPattern::<E2E, LEN, SUM, _>(&mut cur, pattern, range * 2, false);
+        let spread = CheckSymmetricPattern::<E2E, LEN, SUM, _>(
+            &mut cur,
+            pattern,
+            range * 2,
+            false,
+            noQuietZone,
+        );
         if spread != 0 {
             UpdateMinMax(&mut minSpread, &mut maxSpread, spread);
         } else {
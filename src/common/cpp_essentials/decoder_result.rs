@@ -1,6 +1,9 @@
 use std::sync::Arc;
 
-use crate::{common::ECIStringBuilder, Exceptions};
+use crate::{
+    common::{CodewordWitness, ECIStringBuilder},
+    Exceptions, SymbolVersion,
+};
 
 use super::StructuredAppendInfo;
 
@@ -20,6 +23,8 @@ where
     //std::shared_ptr<CustomData> _extra;
     error: Option<Exceptions>,
     extra: Arc<T>,
+    codewords: Option<CodewordWitness>,
+    symbolVersion: Option<SymbolVersion>,
 }
 
 impl<T> Default for DecoderResult<T>
@@ -37,6 +42,8 @@ where
             readerInit: false,
             error: None,
             extra: Default::default(),
+            codewords: None,
+            symbolVersion: None,
         }
     }
 }
@@ -157,6 +164,28 @@ where
         self
     }
 
+    pub fn codewords(&self) -> Option<&CodewordWitness> {
+        self.codewords.as_ref()
+    }
+    pub fn setCodewords(&mut self, codewords: Option<CodewordWitness>) {
+        self.codewords = codewords
+    }
+    pub fn withCodewords(mut self, codewords: Option<CodewordWitness>) -> DecoderResult<T> {
+        self.setCodewords(codewords);
+        self
+    }
+
+    pub fn symbolVersion(&self) -> Option<SymbolVersion> {
+        self.symbolVersion
+    }
+    pub fn setSymbolVersion(&mut self, symbol_version: Option<SymbolVersion>) {
+        self.symbolVersion = symbol_version
+    }
+    pub fn withSymbolVersion(mut self, symbol_version: Option<SymbolVersion>) -> DecoderResult<T> {
+        self.setSymbolVersion(symbol_version);
+        self
+    }
+
     pub fn withIsModel1(mut self, is_model_1: bool) -> DecoderResult<T> {
         if is_model_1 {
             self.content.symbology.modifier = 48
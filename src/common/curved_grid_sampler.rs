@@ -0,0 +1,315 @@
+/*
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::common::Result;
+use crate::{point, Exceptions, Point};
+
+use super::{
+    default_grid_sampler::project_corners, BitMatrix, GridSampler, PerspectiveTransform,
+    Quadrilateral, SamplerControl, SamplingGrid,
+};
+
+/// The direction of the axis of the cylinder a symbol is printed on, in the symbol's grid
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CylinderAxis {
+    /// Along the columns, as on an upright bottle: the rows are bowed
+    Vertical,
+    /// Along the rows, as on a cable: the columns are bowed
+    Horizontal,
+}
+
+/**
+ * How a symbol wrapped around a cylinder departs from a flat one, as seen face on.
+ *
+ * Across the axis, the modules are spaced by the sine of the angle they are wrapped around
+ * the cylinder, so they crowd towards the edges. Along the axis the middle of the symbol is
+ * nearer the camera than its edges, and appears stretched by `bow` more.
+ */
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CylindricalDistortion {
+    pub axis: CylinderAxis,
+    /// The angle in radians the symbol wraps around the cylinder either side of its middle
+    pub wrap: f32,
+    /// How much more the middle of the symbol is stretched along the axis than its edges
+    pub bow: f32,
+}
+
+impl CylindricalDistortion {
+    /// The angles the symbol is fitted with by `fit_cylinder`
+    const WRAPS: [f32; 6] = [0.0, 0.25, 0.5, 0.75, 1.0, 1.25];
+    /// The bows the symbol is fitted with by `fit_cylinder`
+    const BOWS: [f32; 7] = [-0.15, -0.1, -0.05, 0.0, 0.05, 0.1, 0.15];
+
+    /// No distortion, a flat symbol
+    pub fn flat() -> Self {
+        Self {
+            axis: CylinderAxis::Vertical,
+            wrap: 0.0,
+            bow: 0.0,
+        }
+    }
+
+    /**
+     * Maps a point in the module space of a symbol of `width` by `height` modules to where
+     * it appears, in the module space of the flat symbol seen in its place.
+     */
+    pub fn distort(&self, p: Point, width: u32, height: u32) -> Point {
+        // normalized to -1..1 across the axis (s) and along it (t)
+        let (across, along, across_len, along_len) = match self.axis {
+            CylinderAxis::Vertical => (p.x, p.y, width as f32, height as f32),
+            CylinderAxis::Horizontal => (p.y, p.x, height as f32, width as f32),
+        };
+        let s = 2.0 * across / across_len - 1.0;
+        let t = 2.0 * along / along_len - 1.0;
+
+        let s_flat = if self.wrap > 0.0 {
+            (s * self.wrap).sin() / self.wrap.sin()
+        } else {
+            s
+        };
+        let t_flat = t * (1.0 + self.bow * (1.0 - s * s));
+
+        let across = (s_flat + 1.0) * across_len / 2.0;
+        let along = (t_flat + 1.0) * along_len / 2.0;
+        match self.axis {
+            CylinderAxis::Vertical => point(across, along),
+            CylinderAxis::Horizontal => point(along, across),
+        }
+    }
+
+    fn magnitude(&self) -> f32 {
+        self.wrap + self.bow.abs()
+    }
+
+    fn candidates() -> impl Iterator<Item = Self> {
+        [CylinderAxis::Vertical, CylinderAxis::Horizontal]
+            .into_iter()
+            .flat_map(|axis| {
+                Self::WRAPS.into_iter().flat_map(move |wrap| {
+                    Self::BOWS
+                        .into_iter()
+                        .map(move |bow| Self { axis, wrap, bow })
+                })
+            })
+    }
+}
+
+/**
+ * Samples every module at its center moved by a [`CylindricalDistortion`], for symbols
+ * printed on bottles and cables, which flat perspective transforms sample with their modules
+ * away from the anchors a fraction of a module off. See [`fit_cylinder`] to find the
+ * distortion.
+ *
+ * The anchors are the points, in module space, the flat grid was fitted to, such as the
+ * centers of the finder patterns of a QR Code. Each region of the grid is fitted again so
+ * that the anchors, distorted, are still sampled where the flat grid sampled them.
+ */
+#[derive(Debug, Clone, Copy)]
+pub struct CurvedGridSampler {
+    distortion: CylindricalDistortion,
+    anchors: Quadrilateral,
+}
+
+impl CurvedGridSampler {
+    pub fn new(distortion: CylindricalDistortion, anchors: Quadrilateral) -> Self {
+        Self {
+            distortion,
+            anchors,
+        }
+    }
+
+    pub fn distortion(&self) -> CylindricalDistortion {
+        self.distortion
+    }
+
+    /// The regions of the grid fitted again to the distorted anchors
+    fn curved_controls(
+        &self,
+        dimensionX: u32,
+        dimensionY: u32,
+        controls: &[SamplerControl],
+    ) -> Result<Vec<SamplerControl>> {
+        let distorted = Quadrilateral(
+            self.anchors
+                .0
+                .map(|p| self.distortion.distort(p, dimensionX, dimensionY)),
+        );
+        controls
+            .iter()
+            .map(|control| {
+                let sampled =
+                    Quadrilateral(self.anchors.0.map(|p| control.transform.transform_point(p)));
+                Ok(SamplerControl {
+                    transform: PerspectiveTransform::quadrilateralToQuadrilateral(
+                        distorted, sampled,
+                    )?,
+                    ..*control
+                })
+            })
+            .collect()
+    }
+
+    /// The image position the center of module `(x, y)` is sampled at with the fitted regions
+    fn module_to_image(
+        &self,
+        x: u32,
+        y: u32,
+        dimensionX: u32,
+        dimensionY: u32,
+        curved: &[SamplerControl],
+    ) -> Option<Point> {
+        let module = Point::from((x, y)).centered();
+        let flat = self.distortion.distort(module, dimensionX, dimensionY);
+        curved
+            .iter()
+            .find(|c| {
+                c.p0.x <= module.x && module.x < c.p1.x && c.p0.y <= module.y && module.y < c.p1.y
+            })
+            .map(|c| c.transform.transform_point(flat))
+    }
+}
+
+impl GridSampler for CurvedGridSampler {
+    fn sample_grid(
+        &self,
+        image: &BitMatrix,
+        dimensionX: u32,
+        dimensionY: u32,
+        controls: &[SamplerControl],
+    ) -> Result<(BitMatrix, [Point; 4])> {
+        if dimensionX == 0 || dimensionY == 0 {
+            return Err(Exceptions::NOT_FOUND);
+        }
+        if controls.iter().any(|control| !control.transform.isValid()) {
+            return Err(Exceptions::NOT_FOUND);
+        }
+
+        let curved = self.curved_controls(dimensionX, dimensionY, controls)?;
+        let mut bits = BitMatrix::new(dimensionX, dimensionY)?;
+        for y in 0..dimensionY {
+            for x in 0..dimensionX {
+                let p = self
+                    .module_to_image(x, y, dimensionX, dimensionY, &curved)
+                    .filter(|p| image.is_in(*p))
+                    .ok_or(Exceptions::NOT_FOUND)?;
+                if image.get_point(p) {
+                    bits.set(x, y);
+                }
+            }
+        }
+
+        Ok((bits, project_corners(dimensionX, dimensionY, controls)))
+    }
+}
+
+/**
+ * Fits a cylindrical distortion to a symbol by sampling its timing pattern, or any other
+ * modules whose color is known, with a range of distortions.
+ *
+ * # Arguments
+ * * `image` - The binarized image
+ * * `grid` - The flat grid the detector fitted
+ * * `anchors` - The points in module space the grid was fitted to, see [`CurvedGridSampler`]
+ * * `known` - The modules of known color, as `(x, y, black)`
+ *
+ * # Returns
+ * The distortion that samples the most of the known modules right, or None if none does
+ * better than the flat grid
+ */
+pub fn fit_cylinder(
+    image: &BitMatrix,
+    grid: &SamplingGrid,
+    anchors: Quadrilateral,
+    known: &[(u32, u32, bool)],
+) -> Option<CylindricalDistortion> {
+    let (width, height) = (grid.width(), grid.height());
+    let score = |distortion: CylindricalDistortion| -> Option<usize> {
+        let sampler = CurvedGridSampler::new(distortion, anchors);
+        let curved = sampler
+            .curved_controls(width, height, grid.regions())
+            .ok()?;
+        let mut right = 0;
+        for &(x, y, black) in known {
+            let p = sampler
+                .module_to_image(x, y, width, height, &curved)
+                .filter(|p| image.is_in(*p))?;
+            if image.get_point(p) == black {
+                right += 1;
+            }
+        }
+        Some(right)
+    };
+
+    let flat = score(CylindricalDistortion::flat()).unwrap_or(0);
+    let (best, best_score) = CylindricalDistortion::candidates()
+        .filter_map(|distortion| Some((distortion, score(distortion)?)))
+        // of equally good fits, the least distorted
+        .max_by(|(a, a_score), (b, b_score)| {
+            a_score
+                .cmp(b_score)
+                .then(b.magnitude().total_cmp(&a.magnitude()))
+        })?;
+    (best_score > flat).then_some(best)
+}
+
+/**
+ * Samples the modules of `grid` bent around the cylinder that fits the `known` modules best,
+ * see [`fit_cylinder`] for the arguments.
+ *
+ * # Returns
+ * The modules, or None if no cylinder fits better than the flat grid
+ */
+pub(crate) fn sample_cylinder(
+    image: &BitMatrix,
+    grid: &SamplingGrid,
+    anchors: Quadrilateral,
+    known: &[(u32, u32, bool)],
+) -> Option<BitMatrix> {
+    let distortion = fit_cylinder(image, grid, anchors, known)?;
+    CurvedGridSampler::new(distortion, anchors)
+        .sample_grid(image, grid.width(), grid.height(), grid.regions())
+        .ok()
+        .map(|(bits, _)| bits)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::point;
+
+    use super::{CylinderAxis, CylindricalDistortion};
+
+    #[test]
+    fn test_distort_keeps_the_edges() {
+        let distortion = CylindricalDistortion {
+            axis: CylinderAxis::Vertical,
+            wrap: 1.0,
+            bow: 0.1,
+        };
+        for corner in [point(0.0, 0.0), point(21.0, 0.0), point(21.0, 21.0)] {
+            let distorted = distortion.distort(corner, 21, 21);
+            assert!((distorted.x - corner.x).abs() < 1e-4);
+            assert!((distorted.y - corner.y).abs() < 1e-4);
+        }
+        // the middle column is widest, the top of the middle stretched up
+        let middle = distortion.distort(point(10.5, 0.0), 21, 21);
+        assert!((middle.x - 10.5).abs() < 1e-4);
+        assert!(middle.y < -1.0);
+        assert!(distortion.distort(point(5.0, 10.5), 21, 21).x < 5.0);
+    }
+}
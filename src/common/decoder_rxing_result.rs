@@ -20,6 +20,10 @@
 
 use std::{any::Any, sync::Arc};
 
+use crate::{Content, SymbolVersion};
+
+use super::CodewordWitness;
+
 /**
  * <p>Encapsulates the result of decoding a matrix of bits. This typically
  * applies to 2D barcode formats. For now it contains the raw bytes obtained,
@@ -38,9 +42,14 @@ pub struct DecoderRXingResult {
     other: Option<Arc<dyn Any + Send + Sync>>,
     structuredAppendParity: i32,
     structuredAppendSequenceNumber: i32,
+    structuredAppendId: String,
     symbologyModifier: u32,
     contentType: String,
     isMirrored: bool,
+    readerProgramming: bool,
+    codewords: Option<CodewordWitness>,
+    content: Option<Content>,
+    symbolVersion: Option<SymbolVersion>,
 }
 
 impl DecoderRXingResult {
@@ -128,9 +137,14 @@ impl DecoderRXingResult {
             other: None,
             structuredAppendParity: saParity,
             structuredAppendSequenceNumber: saSequence,
+            structuredAppendId: String::new(),
             symbologyModifier,
             contentType,
             isMirrored,
+            readerProgramming: false,
+            codewords: None,
+            content: None,
+            symbolVersion: None,
         }
     }
 
@@ -223,6 +237,15 @@ impl DecoderRXingResult {
         self.structuredAppendSequenceNumber
     }
 
+    /// The ID of the structured append message, for formats that give one beside the parity
+    pub fn getStructuredAppendId(&self) -> &str {
+        &self.structuredAppendId
+    }
+
+    pub fn setStructuredAppendId(&mut self, structuredAppendId: String) {
+        self.structuredAppendId = structuredAppendId
+    }
+
     pub const fn getSymbologyModifier(&self) -> u32 {
         self.symbologyModifier
     }
@@ -242,4 +265,49 @@ impl DecoderRXingResult {
     pub const fn setIsMirrored(&mut self, is_mirrored: bool) {
         self.isMirrored = is_mirrored
     }
+
+    /**
+     * @return whether the symbol programs the reader rather than carrying data, as flagged by
+     *  its first codeword
+     */
+    pub const fn getReaderProgramming(&self) -> bool {
+        self.readerProgramming
+    }
+
+    pub const fn setReaderProgramming(&mut self, reader_programming: bool) {
+        self.readerProgramming = reader_programming
+    }
+
+    /**
+     * @return the codewords read from the symbol and how they were error corrected, if recorded
+     */
+    pub fn getCodewords(&self) -> Option<&CodewordWitness> {
+        self.codewords.as_ref()
+    }
+
+    pub fn setCodewords(&mut self, codewords: Option<CodewordWitness>) {
+        self.codewords = codewords
+    }
+
+    /**
+     * @return the bytes of the symbol in segments of their ECI character sets, if recorded
+     */
+    pub fn getContent(&self) -> Option<&Content> {
+        self.content.as_ref()
+    }
+
+    pub fn setContent(&mut self, content: Option<Content>) {
+        self.content = content
+    }
+
+    /**
+     * @return the version and structure of the symbol, if the decoder records them
+     */
+    pub fn getSymbolVersion(&self) -> Option<SymbolVersion> {
+        self.symbolVersion
+    }
+
+    pub fn setSymbolVersion(&mut self, symbolVersion: Option<SymbolVersion>) {
+        self.symbolVersion = symbolVersion
+    }
 }
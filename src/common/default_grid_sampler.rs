@@ -84,20 +84,71 @@ impl GridSampler for DefaultGridSampler {
         // dbg!(image.to_string());
         // dbg!(bits.to_string());
 
-        let projectCorner = |p: Point| -> Point {
-            for SamplerControl { p0, p1, transform } in controls {
-                if p0.x <= p.x && p.x <= p1.x && p0.y <= p.y && p.y <= p1.y {
-                    return transform.transform_point(p) + point(0.5, 0.5);
+        Ok((bits, project_corners(dimensionX, dimensionY, controls)))
+    }
+}
+
+impl DefaultGridSampler {
+    /**
+     * Like `sample_grid`, but samples a symbol that lies partly outside of `image` instead of
+     * failing. The modules whose centers fall outside of the image are left unset and are set in
+     * the second matrix returned, so the decoder can treat them as erasures.
+     */
+    pub fn sample_grid_partial(
+        &self,
+        image: &BitMatrix,
+        dimensionX: u32,
+        dimensionY: u32,
+        controls: &[SamplerControl],
+    ) -> Result<(BitMatrix, BitMatrix, [Point; 4])> {
+        if dimensionX == 0 || dimensionY == 0 {
+            return Err(Exceptions::NOT_FOUND);
+        }
+        if controls.iter().any(|control| !control.transform.isValid()) {
+            return Err(Exceptions::NOT_FOUND);
+        }
+
+        let mut bits = BitMatrix::new(dimensionX, dimensionY)?;
+        let mut erased = BitMatrix::new(dimensionX, dimensionY)?;
+        for SamplerControl { p0, p1, transform } in controls {
+            for y in (p0.y as i32)..(p1.y as i32) {
+                for x in (p0.x as i32)..(p1.x as i32) {
+                    let p = transform.transform_point(Point::from((x, y)).centered());
+                    if !p.x.is_finite() || !p.y.is_finite() || !image.is_in(p) {
+                        erased.set(x as u32, y as u32);
+                    } else if image.get_point(p) {
+                        bits.set(x as u32, y as u32);
+                    }
                 }
             }
-            Point::default()
-        };
-
-        let tl = projectCorner(Point::default());
-        let tr = projectCorner(Point::from((dimensionX, 0)));
-        let bl = projectCorner(Point::from((dimensionX, dimensionY)));
-        let br = projectCorner(Point::from((0, dimensionX)));
+        }
 
-        Ok((bits, [tl, tr, bl, br]))
+        Ok((
+            bits,
+            erased,
+            project_corners(dimensionX, dimensionY, controls),
+        ))
     }
 }
+
+pub(super) fn project_corners(
+    dimensionX: u32,
+    dimensionY: u32,
+    controls: &[SamplerControl],
+) -> [Point; 4] {
+    let projectCorner = |p: Point| -> Point {
+        for SamplerControl { p0, p1, transform } in controls {
+            if p0.x <= p.x && p.x <= p1.x && p0.y <= p.y && p.y <= p1.y {
+                return transform.transform_point(p) + point(0.5, 0.5);
+            }
+        }
+        Point::default()
+    };
+
+    let tl = projectCorner(Point::default());
+    let tr = projectCorner(Point::from((dimensionX, 0)));
+    let bl = projectCorner(Point::from((dimensionX, dimensionY)));
+    let br = projectCorner(Point::from((0, dimensionX)));
+
+    [tl, tr, bl, br]
+}
@@ -74,6 +74,32 @@ const ENCODERS: [CharacterSet; 14] = [
     CharacterSet::Cp1256,
 ];
 
+// Multi-byte character sets with an ECI, for text not in any of the single byte ones
+const MULTI_BYTE_ENCODERS: [CharacterSet; 4] = [
+    CharacterSet::GB2312,
+    CharacterSet::Big5,
+    CharacterSet::EUC_KR,
+    CharacterSet::UTF8,
+];
+
+/**
+ * The character set with an ECI that encodes all of `content` in the fewest bytes, ISO-8859-1
+ * where it can, the default encoding of the symbologies, which needs no ECI. Of those encoding
+ * it in as few bytes, the single byte ones are preferred to the multi-byte ones, and UTF-8 is
+ * the last resort.
+ */
+pub fn most_compact_charset(content: &str) -> CharacterSet {
+    if CharacterSet::ISO8859_1.encode(content).is_ok() {
+        return CharacterSet::ISO8859_1;
+    }
+    ENCODERS
+        .iter()
+        .chain(MULTI_BYTE_ENCODERS.iter())
+        .filter_map(|charset| Some((*charset, charset.encode(content).ok()?.len())))
+        .min_by_key(|(_, len)| *len)
+        .map_or(CharacterSet::UTF8, |(charset, _)| charset)
+}
+
 /**
  * Set of CharsetEncoders for a given input string
  *
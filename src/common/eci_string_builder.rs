@@ -28,6 +28,9 @@ use std::{
 
 use crate::DecodeHints;
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
 use super::{string_utils, CharacterSet, Eci};
 
 /**
@@ -311,6 +314,7 @@ impl std::ops::AddAssign<String> for ECIStringBuilder {
     }
 }
 
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum ContentType {
     Text,
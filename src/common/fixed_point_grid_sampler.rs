@@ -0,0 +1,296 @@
+/*
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::common::Result;
+use crate::{Exceptions, Point};
+
+use super::{BitMatrix, GridSampler, PerspectiveTransform, SamplerControl, SamplingGrid};
+
+/**
+ * A perspective transform with integer coefficients, so that mapping a module to its pixel
+ * is exact integer arithmetic.
+ *
+ * The coefficients are those of the floating point transform divided by `a33` and scaled by
+ * `2^frac_bits`, each rounded to the nearest integer (halves away from zero), in the order
+ * of [`PerspectiveTransform::coefficients`]: `a11, a12, a13, a21, a22, a23, a31, a32, a33`.
+ * `a33` is therefore exactly `2^frac_bits`.
+ *
+ * The center of module `(x, y)` is `(u / 2, v / 2)` with `u = 2x + 1` and `v = 2y + 1`, and
+ * is sampled at pixel `(floor(nx / d), floor(ny / d))` where
+ * * `nx = a11 * u + a21 * v + 2 * a31`
+ * * `ny = a12 * u + a22 * v + 2 * a32`
+ * * `d = a13 * u + a23 * v + 2 * a33`
+ *
+ * Sampling fails if `d` is not positive or the pixel is outside the image.
+ */
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FixedPointTransform {
+    pub frac_bits: u32,
+    pub coefficients: [i64; 9],
+}
+
+impl FixedPointTransform {
+    /// The most fractional bits a transform may have
+    pub const MAX_FRAC_BITS: u32 = 48;
+
+    /**
+     * Converts `transform` to fixed point.
+     *
+     * # Arguments
+     * * `transform` - The floating point transform
+     * * `frac_bits` - The number of fractional bits of the coefficients, at most `MAX_FRAC_BITS`
+     *
+     * # Returns
+     * The fixed-point transform, or an error if `frac_bits` is too large, `a33` is zero or a
+     * coefficient does not fit in 64 bits
+     */
+    pub fn new(transform: &PerspectiveTransform, frac_bits: u32) -> Result<Self> {
+        if frac_bits > Self::MAX_FRAC_BITS {
+            return Err(Exceptions::illegal_argument_with(format!(
+                "at most {} fractional bits are supported, not {frac_bits}",
+                Self::MAX_FRAC_BITS
+            )));
+        }
+
+        let floats = transform.coefficients();
+        let a33 = floats[8] as f64;
+        if a33 == 0.0 || !a33.is_finite() {
+            return Err(Exceptions::illegal_argument_with(
+                "the transform cannot be normalized, a33 is zero",
+            ));
+        }
+
+        let scale = (1u64 << frac_bits) as f64 / a33;
+        let mut coefficients = [0i64; 9];
+        for (fixed, float) in coefficients.iter_mut().zip(floats) {
+            let scaled = (float as f64 * scale).round();
+            if !(i64::MIN as f64..i64::MAX as f64).contains(&scaled) {
+                return Err(Exceptions::illegal_argument_with(
+                    "a coefficient of the transform does not fit in 64 bits",
+                ));
+            }
+            *fixed = scaled as i64;
+        }
+
+        Ok(Self {
+            frac_bits,
+            coefficients,
+        })
+    }
+
+    /**
+     * The pixel the center of module `(x, y)` maps to.
+     *
+     * # Returns
+     * The pixel coordinates, which may be negative, or None if the denominator is not positive
+     */
+    pub fn module_to_pixel(&self, x: u32, y: u32) -> Option<(i128, i128)> {
+        let [a11, a12, a13, a21, a22, a23, a31, a32, a33] = self.coefficients.map(i128::from);
+        let u = 2 * x as i128 + 1;
+        let v = 2 * y as i128 + 1;
+
+        let d = a13 * u + a23 * v + 2 * a33;
+        if d <= 0 {
+            return None;
+        }
+        let nx = a11 * u + a21 * v + 2 * a31;
+        let ny = a12 * u + a22 * v + 2 * a32;
+
+        // d is positive, so the euclidean quotient is the floor
+        Some((nx.div_euclid(d), ny.div_euclid(d)))
+    }
+}
+
+/**
+ * Samples the grid with [`FixedPointTransform`]s instead of floating point, so every sampled
+ * pixel follows from the recorded coefficients by integer arithmetic that an arithmetic
+ * circuit can check exactly. Each region samples its own modules, as with the
+ * [`DefaultGridSampler`](super::DefaultGridSampler).
+ *
+ * With enough fractional bits the sampled modules match the floating point sampler, except
+ * for modules whose center lies within rounding error of a pixel edge.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixedPointGridSampler {
+    frac_bits: u32,
+}
+
+impl Default for FixedPointGridSampler {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_FRAC_BITS)
+    }
+}
+
+impl FixedPointGridSampler {
+    /// Enough fractional bits to keep the small projective coefficients precise
+    pub const DEFAULT_FRAC_BITS: u32 = 32;
+
+    pub fn new(frac_bits: u32) -> Self {
+        Self { frac_bits }
+    }
+
+    pub fn frac_bits(&self) -> u32 {
+        self.frac_bits
+    }
+
+    /// The fixed-point transform of each region, in order
+    pub fn transforms(&self, controls: &[SamplerControl]) -> Result<Vec<FixedPointTransform>> {
+        controls
+            .iter()
+            .map(|control| FixedPointTransform::new(&control.transform, self.frac_bits))
+            .collect()
+    }
+
+    /// Samples the modules of a grid a detector fitted, such as to replace the modules it
+    /// sampled in floating point
+    pub fn sample(&self, image: &BitMatrix, grid: &SamplingGrid) -> Result<BitMatrix> {
+        self.sample_grid(image, grid.width(), grid.height(), grid.regions())
+            .map(|(bits, _)| bits)
+    }
+}
+
+impl GridSampler for FixedPointGridSampler {
+    fn sample_grid(
+        &self,
+        image: &BitMatrix,
+        dimensionX: u32,
+        dimensionY: u32,
+        controls: &[SamplerControl],
+    ) -> Result<(BitMatrix, [Point; 4])> {
+        if dimensionX == 0 || dimensionY == 0 {
+            return Err(Exceptions::NOT_FOUND);
+        }
+
+        let width = image.getWidth() as i128;
+        let height = image.getHeight() as i128;
+        let mut bits = BitMatrix::new(dimensionX, dimensionY)?;
+        for (control, transform) in controls.iter().zip(self.transforms(controls)?) {
+            for y in (control.p0.y as u32)..(control.p1.y as u32) {
+                for x in (control.p0.x as u32)..(control.p1.x as u32) {
+                    let (px, py) = transform
+                        .module_to_pixel(x, y)
+                        .filter(|&(px, py)| (0..width).contains(&px) && (0..height).contains(&py))
+                        .ok_or(Exceptions::NOT_FOUND)?;
+                    if image.get(px as u32, py as u32) {
+                        bits.set(x, y);
+                    }
+                }
+            }
+        }
+
+        let corner = |x: u32, y: u32| {
+            let p = Point::from((x, y));
+            controls
+                .iter()
+                .find(|c| c.p0.x <= p.x && p.x <= c.p1.x && c.p0.y <= p.y && p.y <= c.p1.y)
+                .map_or(Point::default(), |c| c.transform.transform_point(p))
+        };
+
+        Ok((
+            bits,
+            [
+                corner(0, 0),
+                corner(dimensionX, 0),
+                corner(dimensionX, dimensionY),
+                corner(0, dimensionY),
+            ],
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FixedPointGridSampler, FixedPointTransform};
+    use crate::common::{
+        BitMatrix, DefaultGridSampler, GridSampler, PerspectiveTransform, Quadrilateral,
+        SamplerControl,
+    };
+    use crate::point;
+
+    fn skewed_transform() -> PerspectiveTransform {
+        PerspectiveTransform::quadrilateralToQuadrilateral(
+            Quadrilateral::rectangle_from_xy(0.0, 21.0, 0.0, 21.0, Some(0.0)),
+            Quadrilateral([
+                point(10.3, 12.1),
+                point(95.7, 8.4),
+                point(101.2, 99.9),
+                point(6.6, 92.5),
+            ]),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_rounding() {
+        let transform = skewed_transform();
+        let fixed = FixedPointTransform::new(&transform, 16).unwrap();
+        assert_eq!(1 << 16, fixed.coefficients[8]);
+
+        let floats = transform.coefficients();
+        for (fixed, float) in fixed.coefficients.iter().zip(floats) {
+            let exact = float as f64 / floats[8] as f64 * 65536.0;
+            assert!((*fixed as f64 - exact).abs() <= 0.5);
+        }
+
+        assert!(FixedPointTransform::new(&transform, FixedPointTransform::MAX_FRAC_BITS).is_ok());
+        assert!(
+            FixedPointTransform::new(&transform, FixedPointTransform::MAX_FRAC_BITS + 1).is_err()
+        );
+    }
+
+    #[test]
+    fn test_module_to_pixel() {
+        // modules of 4 pixels, starting at (10, 20)
+        let transform = PerspectiveTransform::quadrilateralToQuadrilateral(
+            Quadrilateral::rectangle_from_xy(0.0, 21.0, 0.0, 21.0, Some(0.0)),
+            Quadrilateral::rectangle_from_xy(10.0, 94.0, 20.0, 104.0, Some(0.0)),
+        )
+        .unwrap();
+        let fixed = FixedPointTransform::new(&transform, 32).unwrap();
+        assert_eq!(Some((12, 22)), fixed.module_to_pixel(0, 0));
+        assert_eq!(Some((92, 102)), fixed.module_to_pixel(20, 20));
+    }
+
+    #[test]
+    fn test_matches_float_sampler() {
+        let mut image = BitMatrix::new(110, 110).unwrap();
+        for y in 0..110 {
+            for x in 0..110 {
+                if (x * 7 + y * 3) % 11 < 5 {
+                    image.set(x, y);
+                }
+            }
+        }
+        let controls = [SamplerControl::new(21, 21, skewed_transform())];
+
+        let (float, _) = DefaultGridSampler
+            .sample_grid(&image, 21, 21, &controls)
+            .unwrap();
+        let (fixed, _) = FixedPointGridSampler::default()
+            .sample_grid(&image, 21, 21, &controls)
+            .unwrap();
+        assert_eq!(float, fixed);
+
+        // a grid reaching outside the image cannot be sampled
+        let small = BitMatrix::new(50, 50).unwrap();
+        assert!(FixedPointGridSampler::default()
+            .sample_grid(&small, 21, 21, &controls)
+            .is_err());
+    }
+}
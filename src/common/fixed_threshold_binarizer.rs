@@ -16,6 +16,9 @@ use crate::{Binarizer, LuminanceSource};
 
 use super::{BitArray, BitMatrix, LineOrientation};
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
 const DEFAULT_THRESHOLD: u8 = 128;
 
 /**
@@ -64,12 +67,150 @@ impl<LS: LuminanceSource> FixedThresholdBinarizer<LS> {
         }
     }
 
+    /**
+     * Creates a new FixedThresholdBinarizer with the threshold chosen by Otsu's method: the one
+     * that separates the luminance histogram of the whole image into the two classes with the
+     * greatest variance between them. It is computed once, so the binarization stays as
+     * deterministic as with a given threshold while adapting to the brightness of the image.
+     *
+     * @param source The luminance source
+     */
+    pub fn with_otsu(source: LS) -> Self {
+        let threshold = otsu_threshold(&source.get_matrix());
+        Self::with_threshold(source, threshold)
+    }
+
     /**
      * Get the threshold value used by this binarizer.
      */
     pub fn get_threshold(&self) -> u8 {
         self.threshold
     }
+
+    /**
+     * Binarizes the image once more, keeping for each pixel what a circuit proving the
+     * binarization needs, see `ThresholdColumns`.
+     *
+     * If the black matrix has not been produced yet, it is produced by the same pass over the
+     * image, so a decode after this does not binarize again.
+     */
+    pub fn threshold_columns(&self) -> Result<ThresholdColumns> {
+        let luminance = self.source.get_matrix();
+        let mut bits = vec![0u8; luminance.len().div_ceil(8)];
+        let mut matrix = match self.black_matrix.get() {
+            Some(_) => None,
+            None => Some(BitMatrix::new(self.width as u32, self.height as u32)?),
+        };
+
+        for (i, &value) in luminance.iter().enumerate() {
+            if value < self.threshold {
+                bits[i / 8] |= 0x80 >> (i % 8);
+                if let Some(matrix) = &mut matrix {
+                    matrix.set((i % self.width) as u32, (i / self.width) as u32);
+                }
+            }
+        }
+        if let Some(matrix) = matrix {
+            // another thread may have produced the same matrix meanwhile
+            let _ = self.black_matrix.set(matrix);
+        }
+
+        Ok(ThresholdColumns {
+            width: self.width,
+            height: self.height,
+            threshold: self.threshold,
+            luminance,
+            bits,
+        })
+    }
+}
+
+/**
+ * The threshold by Otsu's method for `luminances`: pixels below it form the dark class. An image
+ * of a single luminance gets the default threshold.
+ */
+fn otsu_threshold(luminances: &[u8]) -> u8 {
+    let mut histogram = [0u64; 256];
+    for &luminance in luminances {
+        histogram[luminance as usize] += 1;
+    }
+    let total = luminances.len() as f64;
+    let sum: f64 = histogram
+        .iter()
+        .enumerate()
+        .map(|(level, &count)| level as f64 * count as f64)
+        .sum();
+
+    let mut best = (0.0, DEFAULT_THRESHOLD);
+    let (mut dark_count, mut dark_sum) = (0.0, 0.0);
+    for threshold in 1..=255u8 {
+        let level = threshold as usize - 1;
+        dark_count += histogram[level] as f64;
+        dark_sum += (level as u64 * histogram[level]) as f64;
+        let light_count = total - dark_count;
+        if dark_count == 0.0 || light_count == 0.0 {
+            continue;
+        }
+        let difference = dark_sum / dark_count - (sum - dark_sum) / light_count;
+        let variance = dark_count * light_count * difference * difference;
+        if variance > best.0 {
+            best = (variance, threshold);
+        }
+    }
+    best.1
+}
+
+/**
+ * The (luminance, threshold, bit) triple of every pixel binarized by a
+ * `FixedThresholdBinarizer`, laid out as columns the way constraint systems assign their
+ * witness: column `i` of each holds pixel `i`, row-major. The bit of a pixel is set exactly
+ * when its luminance is below the threshold.
+ *
+ * The threshold column is the same value in every row, so it is kept once. The bit column is
+ * packed eight pixels to a byte, most significant bit first, across row boundaries, with the
+ * last byte padded with zeros.
+ *
+ * # Fields
+ * * `width` - The width of the image in pixels
+ * * `height` - The height of the image in pixels
+ * * `threshold` - The threshold of every pixel
+ * * `luminance` - The grayscale value of each pixel
+ * * `bits` - Whether each pixel is black, packed
+ */
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ThresholdColumns {
+    pub width: usize,
+    pub height: usize,
+    pub threshold: u8,
+    pub luminance: Vec<u8>,
+    pub bits: Vec<u8>,
+}
+
+impl ThresholdColumns {
+    /// The number of pixels, the length of each column
+    pub fn len(&self) -> usize {
+        self.luminance.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.luminance.is_empty()
+    }
+
+    /// Whether pixel `i` is black
+    pub fn bit(&self, i: usize) -> bool {
+        self.bits[i / 8] & (0x80 >> (i % 8)) != 0
+    }
+
+    /// The (luminance, threshold, bit) triple of pixel `i`
+    pub fn triple(&self, i: usize) -> (u8, u8, bool) {
+        (self.luminance[i], self.threshold, self.bit(i))
+    }
+
+    /// The triples of all pixels, row-major
+    pub fn triples(&self) -> impl Iterator<Item = (u8, u8, bool)> + '_ {
+        (0..self.len()).map(|i| self.triple(i))
+    }
 }
 
 impl<LS: LuminanceSource> Binarizer for FixedThresholdBinarizer<LS> {
@@ -171,6 +312,10 @@ impl<LS: LuminanceSource> Binarizer for FixedThresholdBinarizer<LS> {
     fn get_height(&self) -> usize {
         self.height
     }
+
+    fn get_global_threshold(&self) -> Option<u8> {
+        Some(self.threshold)
+    }
 }
 
 #[cfg(test)]
@@ -224,6 +369,7 @@ mod tests {
         let binarizer = FixedThresholdBinarizer::with_threshold(source, 100);
 
         assert_eq!(binarizer.get_threshold(), 100);
+        assert_eq!(binarizer.get_global_threshold(), Some(100));
 
         let matrix = binarizer.get_black_matrix().unwrap();
 
@@ -233,6 +379,31 @@ mod tests {
         assert_eq!(matrix.get(3, 0), false); // 150 >= 100
     }
 
+    #[test]
+    fn test_otsu_threshold() {
+        // dark pixels around 40 and light ones around 200
+        let luma = vec![30, 40, 50, 45, 190, 200, 210, 205];
+        let source = Luma8LuminanceSource::new(luma, 4, 2);
+        let binarizer = FixedThresholdBinarizer::with_otsu(source);
+
+        assert_eq!(binarizer.get_threshold(), 51);
+        assert_eq!(binarizer.get_global_threshold(), Some(51));
+        let matrix = binarizer.get_black_matrix().unwrap();
+        for x in 0..4 {
+            assert!(matrix.get(x, 0));
+            assert!(!matrix.get(x, 1));
+        }
+
+        // a dim image, whose light pixels the default threshold would make black
+        let luma = vec![10, 20, 90, 100];
+        let binarizer = FixedThresholdBinarizer::with_otsu(Luma8LuminanceSource::new(luma, 4, 1));
+        assert_eq!(binarizer.get_threshold(), 21);
+
+        let binarizer =
+            FixedThresholdBinarizer::with_otsu(Luma8LuminanceSource::new(vec![7; 4], 2, 2));
+        assert_eq!(binarizer.get_threshold(), 128);
+    }
+
     #[test]
     fn test_get_black_row() {
         let luma = vec![0, 127, 128, 255, 50, 100, 150, 200];
@@ -251,4 +422,26 @@ mod tests {
         assert_eq!(row1.get(2), false); // 150 >= 128
         assert_eq!(row1.get(3), false); // 200 >= 128
     }
+
+    #[test]
+    fn test_threshold_columns() {
+        let luma = vec![0, 127, 128, 255, 50, 100, 150, 200, 99, 101];
+        let source = Luma8LuminanceSource::new(luma.clone(), 5, 2);
+        let binarizer = FixedThresholdBinarizer::with_threshold(source, 100);
+
+        let columns = binarizer.threshold_columns().unwrap();
+        assert_eq!((5, 2, 10), (columns.width, columns.height, columns.len()));
+        assert_eq!(luma, columns.luminance);
+        // 0, 50 and 99 are below the threshold
+        assert_eq!(vec![0b1000_1000, 0b1000_0000], columns.bits);
+        assert_eq!((99, 100, true), columns.triple(8));
+        assert_eq!((100, 100, false), columns.triple(5));
+
+        // the pass produced the black matrix, which agrees with the bits
+        let matrix = binarizer.get_black_matrix().unwrap();
+        for (i, (_, _, bit)) in columns.triples().enumerate() {
+            assert_eq!(bit, matrix.get((i % 5) as u32, (i / 5) as u32));
+        }
+        assert_eq!(columns, binarizer.threshold_columns().unwrap());
+    }
 }
@@ -27,7 +27,7 @@ use once_cell::sync::OnceCell;
 use crate::common::Result;
 use crate::{Binarizer, Exceptions, LuminanceSource};
 
-use super::{BitArray, BitMatrix, LineOrientation};
+use super::{simd, BitArray, BitMatrix, LineOrientation};
 
 const LUMINANCE_BITS: usize = 5;
 const LUMINANCE_SHIFT: usize = 8 - LUMINANCE_BITS;
@@ -233,13 +233,14 @@ impl<LS: LuminanceSource> GlobalHistogramBinarizer<LS> {
         // Although we end up reading four rows twice, it is consistent with our motto of
         // "fail quickly" which is necessary for continuous scanning.
         let localLuminances = source.get_matrix();
-        for y in 0..height {
-            let offset = y * width;
-            for x in 0..width {
-                let pixel = localLuminances[offset + x];
-                if (pixel as u32) < blackPoint {
-                    matrix.set(x as u32, y as u32);
-                }
+        if blackPoint > 0 {
+            // pixels below the black point are those no lighter than the level under it
+            let threshold = (blackPoint - 1).min(u8::MAX as u32) as u8;
+            for y in 0..height {
+                let offset = y * width;
+                simd::for_each_at_most(&localLuminances[offset..offset + width], threshold, |x| {
+                    matrix.set(x as u32, y as u32)
+                });
             }
         }
 
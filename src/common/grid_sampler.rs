@@ -18,10 +18,16 @@
 
 // import com.google.zxing.NotFoundException;
 
-use crate::{common::Result, Point};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{common::Result, DecodeHints, Point};
 use crate::{point, Exceptions};
 
-use super::{BitMatrix, PerspectiveTransform, Quadrilateral};
+use super::{
+    BitMatrix, ClampingGridSampler, DefaultGridSampler, FixedPointGridSampler,
+    PerspectiveTransform, Quadrilateral, SamplingGrid, SupersamplingGridSampler,
+};
 
 /**
  * Implementations of this class can, given locations of finder patterns for a QR code in an
@@ -239,6 +245,9 @@ pub trait GridSampler {
     }
 }
 
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct SamplerControl {
     pub p0: Point,
     pub p1: Point,
@@ -254,3 +263,61 @@ impl SamplerControl {
         }
     }
 }
+
+/**
+ * How the modules of 2D symbols are read from the image, set with the `GridSampling` decode
+ * hint.
+ */
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum GridSampling {
+    /// From the pixel at the center of each module, the behavior without the hint
+    #[default]
+    Center,
+    /**
+     * From the majority of a 3×3 grid of points over each module, see
+     * [`SupersamplingGridSampler`]. Slower, but reads symbols whose modules are under 2
+     * pixels wide far more reliably.
+     */
+    Supersample,
+    /**
+     * From the pixel at the center of each module, or the nearest one on the edge of the
+     * image, see [`ClampingGridSampler`]. The QR Code detector then reads a symbol whose grid
+     * reaches a little beyond the image instead of giving up on it.
+     */
+    Clamp,
+}
+
+impl GridSampling {
+    pub fn sampler(self) -> &'static dyn GridSampler {
+        match self {
+            GridSampling::Center => &DefaultGridSampler,
+            GridSampling::Supersample => &SupersamplingGridSampler,
+            GridSampling::Clamp => &ClampingGridSampler,
+        }
+    }
+}
+
+/**
+ * Samples the grid a detector fitted again, with a `FixedPointGridSampler` if the
+ * `FixedPointSampling` hint is set or else with a `SupersamplingGridSampler` if the
+ * `GridSampling` hint asks for it.
+ *
+ * # Returns
+ * The modules sampled again, or None if the hints leave the modules the detector sampled
+ */
+pub(crate) fn sample_as_hinted(
+    image: &BitMatrix,
+    grid: &SamplingGrid,
+    hints: &DecodeHints,
+) -> Option<Result<BitMatrix>> {
+    if matches!(hints.FixedPointSampling, Some(true)) {
+        return Some(FixedPointGridSampler::default().sample(image, grid));
+    }
+    // clamping differs only for grids reaching beyond the image, which detectors don't return
+    matches!(hints.GridSampling, Some(GridSampling::Supersample)).then(|| {
+        SupersamplingGridSampler
+            .sample_grid(image, grid.width(), grid.height(), grid.regions())
+            .map(|(bits, _)| bits)
+    })
+}
@@ -25,9 +25,9 @@ use std::borrow::Cow;
 use once_cell::sync::OnceCell;
 
 use crate::common::Result;
-use crate::{Binarizer, LuminanceSource};
+use crate::{Binarizer, BlockThresholds, LuminanceSource};
 
-use super::{BitArray, BitMatrix, GlobalHistogramBinarizer};
+use super::{simd, BitArray, BitMatrix, GlobalHistogramBinarizer};
 
 /**
  * This class implements a local thresholding algorithm, which while slower than the
@@ -52,6 +52,8 @@ pub struct HybridBinarizer<LS: LuminanceSource> {
     //source: Box<dyn LuminanceSource>,
     ghb: GlobalHistogramBinarizer<LS>,
     black_matrix: OnceCell<BitMatrix>,
+    keep_block_thresholds: bool,
+    block_thresholds: OnceCell<BlockThresholds>,
 }
 impl<LS: LuminanceSource> Binarizer for HybridBinarizer<LS> {
     type Source = LS;
@@ -74,14 +76,19 @@ impl<LS: LuminanceSource> Binarizer for HybridBinarizer<LS> {
      * profiling easier, and not doing heavy lifting when callers don't expect it.
      */
     fn get_black_matrix(&self) -> Result<&BitMatrix> {
-        let matrix = self
-            .black_matrix
-            .get_or_try_init(|| Self::calculateBlackMatrix(&self.ghb))?;
+        let matrix = self.black_matrix.get_or_try_init(|| {
+            let (matrix, thresholds) =
+                Self::calculateBlackMatrix(&self.ghb, self.keep_block_thresholds)?;
+            if let Some(thresholds) = thresholds {
+                let _ = self.block_thresholds.set(thresholds);
+            }
+            Ok(matrix)
+        })?;
         Ok(matrix)
     }
 
     fn create_binarizer(&self, source: LS) -> Self {
-        Self::new(source)
+        Self::new(source).with_block_thresholds(self.keep_block_thresholds)
     }
 
     fn get_width(&self) -> usize {
@@ -99,6 +106,10 @@ impl<LS: LuminanceSource> Binarizer for HybridBinarizer<LS> {
             self.get_black_row(y)
         }
     }
+
+    fn get_block_thresholds(&self) -> Option<&BlockThresholds> {
+        self.block_thresholds.get()
+    }
 }
 
 // This class uses 5x5 blocks to compute local luminance, where each block is 8x8 pixels.
@@ -115,12 +126,25 @@ impl<LS: LuminanceSource> HybridBinarizer<LS> {
         Self {
             black_matrix: OnceCell::new(),
             ghb,
+            keep_block_thresholds: false,
+            block_thresholds: OnceCell::new(),
         }
     }
 
+    /**
+     * Keeps the threshold of each block when the black matrix is calculated, for the
+     * binarization stage of a witness, see `get_block_thresholds`. Images too small for
+     * blocks are binarized with a global histogram and have no block thresholds.
+     */
+    pub fn with_block_thresholds(mut self, keep: bool) -> Self {
+        self.keep_block_thresholds = keep;
+        self
+    }
+
     fn calculateBlackMatrix<LS2: LuminanceSource>(
         ghb: &GlobalHistogramBinarizer<LS2>,
-    ) -> Result<BitMatrix> {
+        keep_thresholds: bool,
+    ) -> Result<(BitMatrix, Option<BlockThresholds>)> {
         // let matrix;
         let source = ghb.get_luminance_source();
         let width = source.get_width();
@@ -146,6 +170,8 @@ impl<LS: LuminanceSource> HybridBinarizer<LS> {
             );
 
             let mut new_matrix = BitMatrix::new(width as u32, height as u32)?;
+            let mut thresholds = keep_thresholds
+                .then(|| BlockThresholds::new(width as u32, height as u32, BLOCK_SIZE as u32));
             Self::calculateThresholdForBlock(
                 &luminances,
                 sub_width as u32,
@@ -154,12 +180,13 @@ impl<LS: LuminanceSource> HybridBinarizer<LS> {
                 height as u32,
                 &black_points,
                 &mut new_matrix,
+                thresholds.as_mut(),
             );
-            Ok(new_matrix)
+            Ok((new_matrix, thresholds))
         } else {
             // If the image is too small, fall back to the global histogram approach.
             let m = ghb.get_black_matrix()?;
-            Ok(m.clone())
+            Ok((m.clone(), None))
         }
     }
 
@@ -168,6 +195,7 @@ impl<LS: LuminanceSource> HybridBinarizer<LS> {
      * of the blocks around it. Also handles the corner cases (fractional blocks are computed based
      * on the last pixels in the row/column which are also used in the previous block).
      */
+    #[allow(clippy::too_many_arguments)]
     fn calculateThresholdForBlock(
         luminances: &[u8],
         sub_width: u32,
@@ -176,6 +204,7 @@ impl<LS: LuminanceSource> HybridBinarizer<LS> {
         height: u32,
         black_points: &[Vec<u32>],
         matrix: &mut BitMatrix,
+        mut thresholds: Option<&mut BlockThresholds>,
     ) {
         let maxYOffset = height - BLOCK_SIZE as u32;
         let maxXOffset = width - BLOCK_SIZE as u32;
@@ -200,6 +229,9 @@ impl<LS: LuminanceSource> HybridBinarizer<LS> {
                         + blackRow[(left + 2) as usize];
                 }
                 let average = sum / 25;
+                if let Some(thresholds) = thresholds.as_deref_mut() {
+                    thresholds.set(x, y, average.min(u8::MAX as u32) as u8);
+                }
                 Self::thresholdBlock(luminances, xoffset, yoffset, average, width, matrix);
             }
         }
@@ -216,17 +248,16 @@ impl<LS: LuminanceSource> HybridBinarizer<LS> {
         stride: u32,
         matrix: &mut BitMatrix,
     ) {
-        let mut offset = yoffset * stride + xoffset;
+        let mut offset = (yoffset * stride + xoffset) as usize;
         for y in 0..BLOCK_SIZE {
             // for (int y = 0, offset = yoffset * stride + xoffset; y < HybridBinarizer::BLOCK_SIZE; y++, offset += stride) {
-            for x in 0..BLOCK_SIZE {
-                //   for (int x = 0; x < HybridBinarizer::BLOCK_SIZE; x++) {
-                // Comparison needs to be <= so that black == 0 pixels are black even if the threshold is 0.
-                if luminances[offset as usize + x] as u32 <= threshold {
-                    matrix.set(xoffset + x as u32, yoffset + y as u32);
-                }
-            }
-            offset += stride;
+            // Comparison needs to be <= so that black == 0 pixels are black even if the threshold is 0.
+            simd::for_each_at_most(
+                &luminances[offset..offset + BLOCK_SIZE],
+                threshold.min(u8::MAX as u32) as u8,
+                |x| matrix.set(xoffset + x as u32, yoffset + y as u32),
+            );
+            offset += stride as usize;
         }
     }
 
@@ -253,37 +284,11 @@ impl<LS: LuminanceSource> HybridBinarizer<LS> {
                 //   for (int x = 0; x < subWidth; x++) {
                 let xoffset = u32::min(x << BLOCK_SIZE_POWER, maxXOffset as u32);
 
-                let mut sum: u32 = 0;
-                let mut min = u8::MAX;
-                let mut max = u8::MIN;
-
-                let mut offset = yoffset * width + xoffset;
-                let mut yy = 0;
-                while yy < BLOCK_SIZE {
-                    for &pixel in &luminances[offset as usize..offset as usize + BLOCK_SIZE] {
-                        sum += pixel as u32;
-                        // still looking for good contrast
-                        min = min.min(pixel);
-                        max = max.max(pixel);
-                    }
-                    // short-circuit min/max tests once dynamic range is met
-                    if (max - min) as usize > MIN_DYNAMIC_RANGE {
-                        // finish the rest of the rows quickly
-                        offset += width;
-                        yy += 1;
-                        while yy < BLOCK_SIZE {
-                            sum += luminances[offset as usize..offset as usize + BLOCK_SIZE]
-                                .iter()
-                                .map(|&b| b as u32)
-                                .sum::<u32>();
-                            yy += 1;
-                            offset += width;
-                        }
-                        break;
-                    }
-                    yy += 1;
-                    offset += width;
-                }
+                let (sum, min, max) = simd::block_stats(
+                    luminances,
+                    (yoffset * width + xoffset) as usize,
+                    width as usize,
+                );
 
                 // The default estimate is the average of the values in the block.
                 let mut average = sum >> (BLOCK_SIZE_POWER * 2);
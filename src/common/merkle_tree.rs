@@ -0,0 +1,357 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/**
+ * The hash function of a `MerkleTree`. Leaves and inner nodes are hashed by separate
+ * functions so a leaf can never be passed off as an inner node or the other way around.
+ */
+pub trait MerkleHasher {
+    type Digest: Clone + PartialEq + std::fmt::Debug;
+
+    /// Hashes the data of one leaf, an empty slice for the leaves padding the tree
+    fn hash_leaf(&mut self, data: &[u8]) -> Self::Digest;
+
+    /// Hashes two sibling nodes into their parent
+    fn hash_nodes(&mut self, left: &Self::Digest, right: &Self::Digest) -> Self::Digest;
+}
+
+/**
+ * A binary Merkle tree over a sequence of byte strings, such as the rows of an image.
+ *
+ * The leaves are padded to a power of two with empty leaves, so every opening has the same
+ * number of siblings, `depth()`, which is what a circuit verifying the openings expects.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MerkleTree<D> {
+    num_leaves: usize,
+    // levels[0] holds the (padded) leaves, the last level holds only the root
+    levels: Vec<Vec<D>>,
+}
+
+impl<D: Clone + PartialEq> MerkleTree<D> {
+    /**
+     * Builds the tree over `leaves`.
+     *
+     * # Arguments
+     * * `hasher` - The hash function of the tree
+     * * `leaves` - The data of each leaf, in order
+     */
+    pub fn new<H, I>(hasher: &mut H, leaves: I) -> Self
+    where
+        H: MerkleHasher<Digest = D>,
+        I: IntoIterator,
+        I::Item: AsRef<[u8]>,
+    {
+        let mut level: Vec<D> = leaves
+            .into_iter()
+            .map(|leaf| hasher.hash_leaf(leaf.as_ref()))
+            .collect();
+        let num_leaves = level.len();
+
+        let empty_leaf = hasher.hash_leaf(&[]);
+        level.resize(num_leaves.max(1).next_power_of_two(), empty_leaf);
+
+        let mut levels = vec![level];
+        while levels[levels.len() - 1].len() > 1 {
+            let parents = levels[levels.len() - 1]
+                .chunks_exact(2)
+                .map(|pair| hasher.hash_nodes(&pair[0], &pair[1]))
+                .collect();
+            levels.push(parents);
+        }
+
+        Self { num_leaves, levels }
+    }
+
+    /// The root committing to all leaves
+    pub fn root(&self) -> &D {
+        &self.levels[self.levels.len() - 1][0]
+    }
+
+    /// The number of leaves the tree was built over, not counting the padding
+    pub fn num_leaves(&self) -> usize {
+        self.num_leaves
+    }
+
+    /// The number of siblings in each opening
+    pub fn depth(&self) -> usize {
+        self.levels.len() - 1
+    }
+
+    /**
+     * The opening of leaf `index`, proving it is part of the tree without the other leaves.
+     *
+     * # Returns
+     * The opening, or None if `index` is not a leaf of the tree
+     */
+    pub fn opening(&self, index: usize) -> Option<MerkleOpening<D>> {
+        if index >= self.num_leaves {
+            return None;
+        }
+
+        let siblings = self.levels[..self.depth()]
+            .iter()
+            .enumerate()
+            .map(|(height, level)| level[(index >> height) ^ 1].clone())
+            .collect();
+
+        Some(MerkleOpening { index, siblings })
+    }
+
+    /// The openings of all leaves, in order
+    pub fn openings(&self) -> impl Iterator<Item = MerkleOpening<D>> + '_ {
+        (0..self.num_leaves).filter_map(|index| self.opening(index))
+    }
+}
+
+/**
+ * The path from one leaf of a `MerkleTree` to its root.
+ *
+ * # Fields
+ * * `index` - The index of the leaf, whose bits tell on which side each sibling is
+ * * `siblings` - The sibling of each node on the path, starting at the leaf
+ */
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MerkleOpening<D> {
+    pub index: usize,
+    pub siblings: Vec<D>,
+}
+
+impl<D: PartialEq> MerkleOpening<D> {
+    /**
+     * Checks that `leaf` is the data of leaf `index` of the tree with root `root`.
+     *
+     * # Arguments
+     * * `hasher` - The hash function the tree was built with
+     * * `leaf` - The data of the leaf
+     * * `root` - The root of the tree
+     */
+    pub fn verify<H: MerkleHasher<Digest = D>>(
+        &self,
+        hasher: &mut H,
+        leaf: &[u8],
+        root: &D,
+    ) -> bool {
+        if self.index >> self.siblings.len() != 0 {
+            return false;
+        }
+
+        let mut node = hasher.hash_leaf(leaf);
+        for (height, sibling) in self.siblings.iter().enumerate() {
+            node = if (self.index >> height) & 1 == 0 {
+                hasher.hash_nodes(&node, sibling)
+            } else {
+                hasher.hash_nodes(sibling, &node)
+            };
+        }
+
+        &node == root
+    }
+}
+
+/**
+ * SHA-256 with the domain separation of RFC 6962: a leaf is hashed as `SHA-256(0x00 || data)`,
+ * an inner node as `SHA-256(0x01 || left || right)`.
+ */
+#[cfg(feature = "sha256")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Sha256MerkleHasher;
+
+#[cfg(feature = "sha256")]
+impl MerkleHasher for Sha256MerkleHasher {
+    type Digest = [u8; 32];
+
+    fn hash_leaf(&mut self, data: &[u8]) -> Self::Digest {
+        use sha2::{Digest, Sha256};
+
+        Sha256::new()
+            .chain_update([0x00])
+            .chain_update(data)
+            .finalize()
+            .into()
+    }
+
+    fn hash_nodes(&mut self, left: &Self::Digest, right: &Self::Digest) -> Self::Digest {
+        use sha2::{Digest, Sha256};
+
+        Sha256::new()
+            .chain_update([0x01])
+            .chain_update(left)
+            .chain_update(right)
+            .finalize()
+            .into()
+    }
+}
+
+/**
+ * Poseidon over the BN254 scalar field with the circomlib parameters, so the tree can be
+ * verified cheaply in circom and other circuits over BN254.
+ *
+ * Digests are field elements in big-endian bytes. An inner node is `Poseidon(left, right)`.
+ * A leaf starts from its length in bytes and absorbs the data 31 bytes at a time, each chunk
+ * read as a big-endian field element: `acc = Poseidon(acc, chunk)`. The empty leaf padding
+ * the tree is therefore the zero element.
+ */
+#[cfg(feature = "poseidon")]
+pub struct PoseidonMerkleHasher(light_poseidon::Poseidon<ark_bn254::Fr>);
+
+#[cfg(feature = "poseidon")]
+impl PoseidonMerkleHasher {
+    pub fn new() -> Self {
+        Self(
+            light_poseidon::Poseidon::<ark_bn254::Fr>::new_circom(2)
+                .expect("circom parameters exist for two inputs"),
+        )
+    }
+
+    fn hash(&mut self, left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+        use light_poseidon::PoseidonBytesHasher;
+
+        // both inputs are below the modulus: digests are field elements, data chunks are
+        // 31 bytes and lengths fit in a usize
+        self.0
+            .hash_bytes_be(&[left, right])
+            .expect("inputs are field elements")
+    }
+}
+
+#[cfg(feature = "poseidon")]
+impl Default for PoseidonMerkleHasher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "poseidon")]
+impl MerkleHasher for PoseidonMerkleHasher {
+    type Digest = [u8; 32];
+
+    fn hash_leaf(&mut self, data: &[u8]) -> Self::Digest {
+        let mut acc = [0u8; 32];
+        acc[24..].copy_from_slice(&(data.len() as u64).to_be_bytes());
+
+        for chunk in data.chunks(31) {
+            let mut element = [0u8; 32];
+            element[32 - chunk.len()..].copy_from_slice(chunk);
+            acc = self.hash(&acc, &element);
+        }
+
+        acc
+    }
+
+    fn hash_nodes(&mut self, left: &Self::Digest, right: &Self::Digest) -> Self::Digest {
+        self.hash(left, right)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::hash::{DefaultHasher, Hash, Hasher};
+
+    use super::{MerkleHasher, MerkleTree};
+
+    // Not collision resistant, but deterministic and enough to check the tree structure
+    struct SipMerkleHasher;
+
+    impl MerkleHasher for SipMerkleHasher {
+        type Digest = u64;
+
+        fn hash_leaf(&mut self, data: &[u8]) -> u64 {
+            let mut hasher = DefaultHasher::new();
+            (0u8, data).hash(&mut hasher);
+            hasher.finish()
+        }
+
+        fn hash_nodes(&mut self, left: &u64, right: &u64) -> u64 {
+            let mut hasher = DefaultHasher::new();
+            (1u8, left, right).hash(&mut hasher);
+            hasher.finish()
+        }
+    }
+
+    #[test]
+    fn test_openings() {
+        let rows: Vec<Vec<u8>> = (0..5u8).map(|y| vec![y; 7]).collect();
+        let tree = MerkleTree::new(&mut SipMerkleHasher, &rows);
+
+        assert_eq!(5, tree.num_leaves());
+        assert_eq!(3, tree.depth());
+        assert!(tree.opening(5).is_none());
+
+        let openings: Vec<_> = tree.openings().collect();
+        assert_eq!(5, openings.len());
+        for (y, opening) in openings.iter().enumerate() {
+            assert_eq!(y, opening.index);
+            assert_eq!(3, opening.siblings.len());
+            assert!(opening.verify(&mut SipMerkleHasher, &rows[y], tree.root()));
+            // the row must match and sit at the index of the opening
+            assert!(!opening.verify(&mut SipMerkleHasher, &rows[(y + 1) % 5], tree.root()));
+        }
+
+        let mut moved = openings[2].clone();
+        moved.index = 3;
+        assert!(!moved.verify(&mut SipMerkleHasher, &rows[2], tree.root()));
+        moved.index = 2 + 8;
+        assert!(!moved.verify(&mut SipMerkleHasher, &rows[2], tree.root()));
+    }
+
+    #[test]
+    fn test_root() {
+        let h = &mut SipMerkleHasher;
+        let rows = [b"ab".as_slice(), b"cd", b"ef"];
+        let tree = MerkleTree::new(h, rows);
+
+        let leaves = [b"ab".as_slice(), b"cd", b"ef", &[]].map(|leaf| h.hash_leaf(leaf));
+        let left = h.hash_nodes(&leaves[0], &leaves[1]);
+        let right = h.hash_nodes(&leaves[2], &leaves[3]);
+        assert_eq!(&h.hash_nodes(&left, &right), tree.root());
+
+        // a tree over a single leaf, or none, is just the leaf
+        let single = MerkleTree::new(h, [b"ab"]);
+        assert_eq!(0, single.depth());
+        assert_eq!(leaves[0], *single.root());
+        assert_eq!(0, MerkleTree::new(h, Vec::<Vec<u8>>::new()).num_leaves());
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn test_sha256_leaf() {
+        use super::Sha256MerkleHasher;
+
+        // SHA-256 of the single byte 0x00, the RFC 6962 hash of an empty leaf
+        let empty = "6e340b9cffb37a989ca544e6bb780a2c78901d3fb33738768511a30617afa01d";
+        let hex: String = Sha256MerkleHasher
+            .hash_leaf(&[])
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect();
+        assert_eq!(empty, hex);
+    }
+
+    #[cfg(feature = "poseidon")]
+    #[test]
+    fn test_poseidon_leaf() {
+        use super::PoseidonMerkleHasher;
+
+        let mut hasher = PoseidonMerkleHasher::new();
+        assert_eq!([0u8; 32], hasher.hash_leaf(&[]));
+
+        // one chunk: Poseidon(length, chunk)
+        let mut length = [0u8; 32];
+        length[31] = 3;
+        let mut chunk = [0u8; 32];
+        chunk[29..].copy_from_slice(&[1, 2, 3]);
+        assert_eq!(hasher.hash(&length, &chunk), hasher.hash_leaf(&[1, 2, 3]));
+
+        // longer data is absorbed 31 bytes at a time
+        let data = [7u8; 40];
+        assert_ne!(hasher.hash_leaf(&data[..31]), hasher.hash_leaf(&data));
+
+        let rows = [[0u8; 40], [255u8; 40], [7u8; 40]];
+        let tree = MerkleTree::new(&mut hasher, rows);
+        for opening in tree.openings() {
+            assert!(opening.verify(&mut hasher, &rows[opening.index], tree.root()));
+        }
+    }
+}
@@ -56,6 +56,11 @@ pub trait DetectorRXingResult {
     fn getBits(&self) -> &BitMatrix;
 
     fn getPoints(&self) -> &[Point];
+
+    /// The grid the bits were sampled with, if the detector reports it
+    fn getSamplingGrid(&self) -> Option<&SamplingGrid> {
+        None
+    }
 }
 
 // pub struct DetectorRXingResult {
@@ -75,9 +80,18 @@ pub use bit_source::*;
 mod perspective_transform;
 pub use perspective_transform::*;
 
+mod sampling_grid;
+pub use sampling_grid::*;
+
 mod decoder_rxing_result;
 pub use decoder_rxing_result::*;
 
+mod codeword_witness;
+pub use codeword_witness::*;
+
+mod merkle_tree;
+pub use merkle_tree::*;
+
 mod bit_source_builder;
 pub use bit_source_builder::*;
 
@@ -87,6 +101,18 @@ pub use grid_sampler::*;
 mod default_grid_sampler;
 pub use default_grid_sampler::*;
 
+mod fixed_point_grid_sampler;
+pub use fixed_point_grid_sampler::*;
+
+mod supersampling_grid_sampler;
+pub use supersampling_grid_sampler::*;
+
+mod clamping_grid_sampler;
+pub use clamping_grid_sampler::*;
+
+mod curved_grid_sampler;
+pub use curved_grid_sampler::*;
+
 mod character_set;
 pub use character_set::*;
 
@@ -105,9 +131,17 @@ pub use global_histogram_binarizer::*;
 mod hybrid_binarizer;
 pub use hybrid_binarizer::*;
 
+pub(crate) mod simd;
+
 mod fixed_threshold_binarizer;
 pub use fixed_threshold_binarizer::*;
 
+mod sauvola_binarizer;
+pub use sauvola_binarizer::*;
+
+mod witness_binarizer;
+pub use witness_binarizer::*;
+
 mod eci;
 pub use eci::*;
 
@@ -18,6 +18,9 @@
 
 use std::ops::Mul;
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
 use crate::{common::Result, point, Exceptions, Point};
 
 use super::Quadrilateral;
@@ -29,6 +32,8 @@ use super::Quadrilateral;
  *
  * @author Sean Owen
  */
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
 #[derive(Debug, Copy, Clone, PartialEq, Default)]
 pub struct PerspectiveTransform {
     a11: f32,
@@ -82,6 +87,47 @@ impl PerspectiveTransform {
         Ok(s_to_q * q_to_s)
     }
 
+    /**
+     * The coefficients `[a11, a12, a13, a21, a22, a23, a31, a32, a33]`. A point `(x, y)` maps to
+     * `((a11 x + a21 y + a31) / d, (a12 x + a22 y + a32) / d)` with `d = a13 x + a23 y + a33`.
+     */
+    pub fn coefficients(&self) -> [f32; 9] {
+        [
+            self.a11, self.a12, self.a13, self.a21, self.a22, self.a23, self.a31, self.a32,
+            self.a33,
+        ]
+    }
+
+    /// This transform followed by scaling its output by `factor`
+    pub fn scaled(&self, factor: f32) -> Self {
+        Self::new(
+            self.a11 * factor,
+            self.a21 * factor,
+            self.a31 * factor,
+            self.a12 * factor,
+            self.a22 * factor,
+            self.a32 * factor,
+            self.a13,
+            self.a23,
+            self.a33,
+        )
+    }
+
+    /// This transform followed by moving its output by `offset`
+    pub fn translated(&self, offset: Point) -> Self {
+        Self::new(
+            self.a11 + offset.x * self.a13,
+            self.a21 + offset.x * self.a23,
+            self.a31 + offset.x * self.a33,
+            self.a12 + offset.y * self.a13,
+            self.a22 + offset.y * self.a23,
+            self.a32 + offset.y * self.a33,
+            self.a13,
+            self.a23,
+            self.a33,
+        )
+    }
+
     pub fn transform_point(&self, point: Point) -> Point {
         let x = point.x;
         let y = point.y;
@@ -340,6 +340,145 @@ fn test_aztec() {
     test_encode_decode_random(azd12, 3072, 1023);
 }
 
+#[test]
+fn test_decode_with_correction() {
+    let qrcf256 = super::get_predefined_genericgf(super::PredefinedGenericGF::QrCodeField256);
+    // Test case from example given in ISO 18004, Annex I
+    let message = [
+        0x10, 0x20, 0x0C, 0x56, 0x61, 0x80, 0xEC, 0x11, 0xEC, 0x11, 0xEC, 0x11, 0xEC, 0x11, 0xEC,
+        0x11, 0xA5, 0x24, 0xD4, 0xC1, 0xED, 0x36, 0xC7, 0x87, 0x2C, 0x55,
+    ];
+    let decoder = ReedSolomonDecoder::new(qrcf256);
+
+    let mut received = message;
+    let correction = decoder
+        .decode_with_correction(&mut received, 10)
+        .expect("decode");
+    assert_eq!(correction.syndromes, vec![0; 10]);
+    assert_eq!(correction.errors_corrected(), 0);
+
+    received[3] ^= 0x42;
+    received[20] ^= 0x07;
+    let mut correction = decoder
+        .decode_with_correction(&mut received, 10)
+        .expect("decode");
+    assert_eq!(received, message);
+    assert_eq!(correction.syndromes.len(), 10);
+    assert!(correction.syndromes.iter().any(|s| *s != 0));
+
+    let mut errors: Vec<(usize, i32)> = correction
+        .error_positions
+        .drain(..)
+        .zip(correction.error_magnitudes.drain(..))
+        .collect();
+    errors.sort();
+    assert_eq!(errors, vec![(3, 0x42), (20, 0x07)]);
+}
+
+#[test]
+fn test_decode_with_erasures() {
+    let qrcf256 = super::get_predefined_genericgf(super::PredefinedGenericGF::QrCodeField256);
+    let message = [
+        0x10, 0x20, 0x0C, 0x56, 0x61, 0x80, 0xEC, 0x11, 0xEC, 0x11, 0xEC, 0x11, 0xEC, 0x11, 0xEC,
+        0x11, 0xA5, 0x24, 0xD4, 0xC1, 0xED, 0x36, 0xC7, 0x87, 0x2C, 0x55,
+    ];
+    let decoder = ReedSolomonDecoder::new(qrcf256);
+
+    // all 10 error-correction codewords fill in 10 erasures, 5 unknown errors are the most
+    // they can correct
+    let mut received = message;
+    let erasures = [0, 2, 4, 6, 8, 10, 12, 14, 16, 25];
+    for e in erasures {
+        received[e] = 0;
+    }
+    assert!(decoder.decode(&mut received.clone(), 10).is_err());
+    let correction = decoder
+        .decode_with_erasures(&mut received, 10, &erasures)
+        .expect("decode");
+    assert_eq!(received, message);
+    assert_eq!(correction.errors_corrected(), 0);
+    assert_eq!(correction.erasure_positions, erasures.to_vec());
+
+    // 2 errors and 6 erasures
+    let mut received = message;
+    received[1] ^= 0x5A;
+    received[21] ^= 0x01;
+    for e in [3, 7, 9, 11, 13, 24] {
+        received[e] ^= 0xFF;
+    }
+    let correction = decoder
+        .decode_with_erasures(&mut received, 10, &[3, 7, 9, 11, 13, 24])
+        .expect("decode");
+    assert_eq!(received, message);
+    let mut errors = correction.error_positions.clone();
+    errors.sort();
+    assert_eq!(errors, vec![1, 21]);
+    assert_eq!(correction.erasures_corrected(), 6);
+
+    // 3 errors and 6 erasures are too many
+    let mut received = message;
+    for e in [1, 5, 21, 3, 7, 9, 11, 13, 24] {
+        received[e] ^= 0x33;
+    }
+    assert!(decoder
+        .decode_with_erasures(&mut received, 10, &[3, 7, 9, 11, 13, 24])
+        .map_or(true, |_| received != message));
+
+    let erasures: Vec<usize> = (0..11).collect();
+    assert!(decoder
+        .decode_with_erasures(&mut message.clone(), 10, &erasures)
+        .is_err());
+}
+
+#[test]
+fn test_decode_with_erasures_random() {
+    let dm256 = super::get_predefined_genericgf(super::PredefinedGenericGF::DataMatrixField256);
+    let qrcf256 = super::get_predefined_genericgf(super::PredefinedGenericGF::QrCodeField256);
+    let mut random = get_pseudo_random();
+    for field in [dm256, qrcf256] {
+        let decoder = ReedSolomonDecoder::new(field);
+        let mut encoder = ReedSolomonEncoder::new(field).expect("new");
+        let (dataSize, ecSize) = (30, 20);
+        for _i in 0..DECODER_TEST_ITERATIONS {
+            let mut message = vec![0; dataSize + ecSize];
+            for data in message.iter_mut().take(dataSize) {
+                *data = random.random_range(0..256);
+            }
+            encoder.encode(&mut message, ecSize).expect("encode");
+            for erasureCount in 0..=ecSize {
+                let errorCount = (ecSize - erasureCount) / 2;
+                let mut received = message.clone();
+                let mut positions: Vec<usize> = (0..received.len()).collect();
+                for i in 0..erasureCount + errorCount {
+                    let j = random.random_range(i..positions.len());
+                    positions.swap(i, j);
+                }
+                let erasures = &positions[..erasureCount];
+                for &e in erasures {
+                    received[e] = random.random_range(0..256);
+                }
+                corrupt_at(
+                    &mut received,
+                    &positions[erasureCount..erasureCount + errorCount],
+                );
+                let correction = decoder
+                    .decode_with_erasures(&mut received, ecSize as i32, erasures)
+                    .unwrap_or_else(|e| {
+                        panic!("{field}: {erasureCount} erasures, {errorCount} errors: {e:?}")
+                    });
+                assert_eq!(received, message);
+                assert_eq!(correction.errors_corrected(), errorCount);
+            }
+        }
+    }
+}
+
+fn corrupt_at(received: &mut [i32], positions: &[usize]) {
+    for (i, &p) in positions.iter().enumerate() {
+        received[p] ^= 1 + (i as i32 % 255);
+    }
+}
+
 pub(crate) fn corrupt(
     received: &mut [i32],
     howMany: i32,
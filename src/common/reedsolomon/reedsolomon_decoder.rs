@@ -21,6 +21,9 @@ use crate::Exceptions;
 
 use super::{GenericGF, GenericGFPoly, GenericGFRef};
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
 /**
  * <p>Implements Reed-Solomon decoding, as the name implies.</p>
  *
@@ -47,6 +50,37 @@ pub struct ReedSolomonDecoder {
     field: GenericGFRef,
 }
 
+/**
+ * The intermediate values of a Reed-Solomon decode, as returned by
+ * [`ReedSolomonDecoder::decode_with_correction`].
+ *
+ * Syndrome `i` is the received polynomial evaluated at `α^(i + generatorBase)`; all of them are
+ * zero if, and only if, the received codewords were already a valid code word. Each error is
+ * given as an index into the received codewords together with the magnitude that was added
+ * (XORed) to the codeword at that index to correct it. Codewords the caller marked as erased
+ * are listed apart from the errors, the same way.
+ */
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct ReedSolomonCorrection {
+    pub syndromes: Vec<i32>,
+    pub error_positions: Vec<usize>,
+    pub error_magnitudes: Vec<i32>,
+    pub erasure_positions: Vec<usize>,
+    pub erasure_magnitudes: Vec<i32>,
+}
+
+impl ReedSolomonCorrection {
+    pub fn errors_corrected(&self) -> usize {
+        self.error_positions.len()
+    }
+
+    pub fn erasures_corrected(&self) -> usize {
+        self.erasure_positions.len()
+    }
+}
+
 impl ReedSolomonDecoder {
     pub const fn new(field: GenericGFRef) -> Self {
         Self { field }
@@ -62,48 +96,150 @@ impl ReedSolomonDecoder {
      * @throws ReedSolomonException if decoding fails for any reason
      */
     pub fn decode(&self, received: &mut [i32], twoS: i32) -> Result<usize> {
-        let poly = GenericGFPoly::new(self.field, received)?;
-        let mut syndromeCoefficients = vec![0; twoS as usize];
-        let mut noError = true;
-        for i in 0..twoS {
-            //for (int i = 0; i < twoS; i++) {
-            let eval = poly.evaluateAt(self.field.exp(i + self.field.getGeneratorBase()) as usize);
-            let len = syndromeCoefficients.len();
-            syndromeCoefficients[len - 1 - i as usize] = eval;
-            if eval != 0 {
-                noError = false;
-            }
+        self.decode_with_correction(received, twoS)
+            .map(|correction| correction.errors_corrected())
+    }
+
+    /**
+     * Like [`ReedSolomonDecoder::decode`], but returns the syndromes and the corrections that
+     * were applied to `received` instead of just the number of errors corrected.
+     *
+     * # Arguments
+     * * `received` - data and error-correction codewords, corrected in-place
+     * * `twoS` - number of error-correction codewords available
+     */
+    pub fn decode_with_correction(
+        &self,
+        received: &mut [i32],
+        twoS: i32,
+    ) -> Result<ReedSolomonCorrection> {
+        self.decode_with_erasures(received, twoS, &[])
+    }
+
+    /**
+     * Like [`ReedSolomonDecoder::decode_with_correction`], but with the positions of codewords
+     * known to be unreliable, such as those of modules that couldn't be read. An erasure costs
+     * one error-correction codeword where an error at an unknown position costs two, so up to
+     * `twoS` erasures can be filled in, or `e` errors and `f` erasures as long as `2e + f` is at
+     * most `twoS`.
+     *
+     * # Arguments
+     * * `received` - data and error-correction codewords, corrected in-place
+     * * `twoS` - number of error-correction codewords available
+     * * `erasures` - indices into `received` of the erased codewords
+     */
+    pub fn decode_with_erasures(
+        &self,
+        received: &mut [i32],
+        twoS: i32,
+        erasures: &[usize],
+    ) -> Result<ReedSolomonCorrection> {
+        let mut erasures = erasures.to_vec();
+        erasures.sort_unstable();
+        erasures.dedup();
+        if erasures.len() > twoS as usize {
+            return Err(Exceptions::reed_solomon_with(
+                "more erasures than error-correction codewords",
+            ));
         }
-        if noError {
-            return Ok(0);
+        if erasures.last().is_some_and(|&e| e >= received.len()) {
+            return Err(Exceptions::reed_solomon_with("Bad erasure location"));
         }
+
+        let syndromes = self.syndromes(received, twoS)?;
+        if syndromes.iter().all(|s| *s == 0) {
+            return Ok(ReedSolomonCorrection {
+                syndromes,
+                ..Default::default()
+            });
+        }
+        let syndromeCoefficients: Vec<i32> = syndromes.iter().rev().copied().collect();
         let Ok(syndrome) = GenericGFPoly::new(self.field, &syndromeCoefficients) else {
             return Err(Exceptions::REED_SOLOMON);
         };
+
+        // the erasure locator has a root at the inverse of the locator of every erasure, the
+        // key equation is then solved for the errors with the syndromes it was multiplied by
+        let erasureLocations: Vec<usize> = erasures
+            .iter()
+            .map(|&e| self.field.exp((received.len() - 1 - e) as i32) as usize)
+            .collect();
+        let mut erasureLocator = syndrome.getOne();
+        for location in &erasureLocations {
+            erasureLocator = erasureLocator
+                .multiply(&GenericGFPoly::new(self.field, &[*location as i32, 1])?)?;
+        }
+        let modified = if erasures.is_empty() {
+            syndrome
+        } else {
+            let product = syndrome.multiply(&erasureLocator)?;
+            let coefficients = product.getCoefficients();
+            let keep = coefficients.len().min(twoS as usize);
+            GenericGFPoly::new(self.field, &coefficients[coefficients.len() - keep..])?
+        };
+
         let sigmaOmega = self.runEuclideanAlgorithm(
             &GenericGF::buildMonomial(self.field, twoS as usize, 1),
-            &syndrome,
-            twoS as usize,
+            &modified,
+            twoS as usize + erasures.len(),
         )?;
         let sigma = &sigmaOmega[0];
         let omega = &sigmaOmega[1];
         let errorLocations = self.findErrorLocations(sigma)?;
-        let errorMagnitudes = self.findErrorMagnitudes(omega, &errorLocations)?;
-        for (error_location, error_magnitude) in errorLocations.iter().zip(errorMagnitudes) {
-            // for i in 0..errorLocations.len() {
-            //for (int i = 0; i < errorLocations.length; i++) {
+        if 2 * errorLocations.len() + erasures.len() > twoS as usize {
+            return Err(Exceptions::reed_solomon_with(
+                "too many errors and erasures",
+            ));
+        }
+        let mut errorPositions = Vec::with_capacity(errorLocations.len());
+        for error_location in &errorLocations {
             let log_value = self.field.log(*error_location as i32)?;
             if log_value > received.len() as i32 - 1 {
                 return Err(Exceptions::reed_solomon_with("Bad error location"));
             }
             let position: isize = received.len() as isize - 1 - log_value as isize;
-            if position < 0 {
+            if position < 0 || erasures.binary_search(&(position as usize)).is_ok() {
                 return Err(Exceptions::reed_solomon_with("Bad error location"));
             }
-            received[position as usize] =
-                GenericGF::addOrSubtract(received[position as usize], error_magnitude);
+            errorPositions.push(position as usize);
         }
-        Ok(errorLocations.len())
+
+        let locations: Vec<usize> = errorLocations
+            .iter()
+            .chain(&erasureLocations)
+            .copied()
+            .collect();
+        let mut magnitudes = self.findErrorMagnitudes(omega, &locations)?;
+        let erasureMagnitudes = magnitudes.split_off(errorLocations.len());
+        for (position, magnitude) in errorPositions
+            .iter()
+            .zip(&magnitudes)
+            .chain(erasures.iter().zip(&erasureMagnitudes))
+        {
+            received[*position] = GenericGF::addOrSubtract(received[*position], *magnitude);
+        }
+        // with erasures, the key equation may have been solved by a polynomial that doesn't
+        // correct anything
+        if !erasures.is_empty() && self.syndromes(received, twoS)?.iter().any(|s| *s != 0) {
+            return Err(Exceptions::reed_solomon_with(
+                "erasures and errors could not be corrected",
+            ));
+        }
+        Ok(ReedSolomonCorrection {
+            syndromes,
+            error_positions: errorPositions,
+            error_magnitudes: magnitudes,
+            erasure_positions: erasures,
+            erasure_magnitudes: erasureMagnitudes,
+        })
+    }
+
+    /// The received polynomial evaluated at `α^(i + generatorBase)` for `i` in `0..twoS`
+    fn syndromes(&self, received: &[i32], twoS: i32) -> Result<Vec<i32>> {
+        let poly = GenericGFPoly::new(self.field, received)?;
+        Ok((0..twoS)
+            .map(|i| poly.evaluateAt(self.field.exp(i + self.field.getGeneratorBase()) as usize))
+            .collect())
     }
 
     fn runEuclideanAlgorithm(
@@ -0,0 +1,322 @@
+/*
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{point, Point};
+
+use super::{BitMatrix, PerspectiveTransform, Quadrilateral, Result, SamplerControl};
+
+/// The kind of a pattern a detector located before fitting its sampling grid, or that
+/// [`crate::locate`] located a symbol by
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PatternKind {
+    /// A finder pattern of a QR code, or the bull's eye of an Aztec code
+    Finder,
+    /// An alignment pattern of a QR code
+    Alignment,
+    /// A corner of the L-shaped finder and the timing pattern of a Data Matrix symbol
+    Corner,
+    /// A start or stop pattern of a linear or PDF417 symbol
+    Guard,
+}
+
+/// A pattern located by a detector, with its center in image pixels
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DetectedPattern {
+    pub kind: PatternKind,
+    pub center: Point,
+}
+
+impl DetectedPattern {
+    pub fn new(kind: PatternKind, center: Point) -> Self {
+        Self { kind, center }
+    }
+}
+
+/**
+ * The sampling grid a detector used to read the modules of a symbol out of the image.
+ *
+ * The grid maps module coordinates to image pixels: module `(x, y)` covers the square
+ * from `(x, y)` to `(x + 1, y + 1)` in module space, and its center was sampled at
+ * [`SamplingGrid::module_center`]. Symbols with alignment patterns may be sampled with
+ * several regions, each with its own transform.
+ *
+ * Detectors that search the image for finder and alignment patterns also record the
+ * [`patterns`](SamplingGrid::patterns) the grid was fitted to. Grids of "pure" images are
+ * derived from the symbol bounds and have none.
+ */
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct SamplingGrid {
+    width: u32,
+    height: u32,
+    regions: Vec<SamplerControl>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    patterns: Vec<DetectedPattern>,
+}
+
+impl Eq for SamplingGrid {}
+
+impl SamplingGrid {
+    pub fn new(width: u32, height: u32, regions: Vec<SamplerControl>) -> Self {
+        Self {
+            width,
+            height,
+            regions,
+            patterns: Vec::new(),
+        }
+    }
+
+    /// Records the patterns the grid was fitted to
+    pub fn with_patterns(mut self, patterns: Vec<DetectedPattern>) -> Self {
+        self.patterns = patterns;
+        self
+    }
+
+    /// A grid sampled with a single transform over the whole symbol
+    pub fn with_transform(width: u32, height: u32, transform: PerspectiveTransform) -> Self {
+        Self::new(
+            width,
+            height,
+            vec![SamplerControl::new(width, height, transform)],
+        )
+    }
+
+    /// An unrotated, undistorted grid, as found in "pure" images
+    pub fn axis_aligned(
+        width: u32,
+        height: u32,
+        left: f32,
+        top: f32,
+        module_size: f32,
+    ) -> Result<Self> {
+        let transform = PerspectiveTransform::quadrilateralToQuadrilateral(
+            Quadrilateral::rectangle_from_xy(0.0, width as f32, 0.0, height as f32, Some(0.0)),
+            Quadrilateral::rectangle_from_xy(
+                left,
+                left + width as f32 * module_size,
+                top,
+                top + height as f32 * module_size,
+                Some(0.0),
+            ),
+        )?;
+        Ok(Self::with_transform(width, height, transform))
+    }
+
+    /// The grid for the image scaled up by `factor`, such as the full resolution image of a
+    /// grid found in a downscaled copy
+    pub fn scaled(&self, factor: f32) -> Self {
+        Self {
+            width: self.width,
+            height: self.height,
+            regions: self
+                .regions
+                .iter()
+                .map(|region| SamplerControl {
+                    transform: region.transform.scaled(factor),
+                    ..*region
+                })
+                .collect(),
+            patterns: self
+                .patterns
+                .iter()
+                .map(|pattern| DetectedPattern::new(pattern.kind, pattern.center * factor))
+                .collect(),
+        }
+    }
+
+    /// The grid for an image in which the one it was found in lies at `offset`, such as the
+    /// full image of a grid found in a crop
+    pub fn translated(&self, offset: Point) -> Self {
+        Self {
+            width: self.width,
+            height: self.height,
+            regions: self
+                .regions
+                .iter()
+                .map(|region| SamplerControl {
+                    transform: region.transform.translated(offset),
+                    ..*region
+                })
+                .collect(),
+            patterns: self
+                .patterns
+                .iter()
+                .map(|pattern| DetectedPattern::new(pattern.kind, pattern.center + offset))
+                .collect(),
+        }
+    }
+
+    /// The number of modules sampled horizontally
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// The number of modules sampled vertically
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn regions(&self) -> &[SamplerControl] {
+        &self.regions
+    }
+
+    pub fn patterns(&self) -> &[DetectedPattern] {
+        &self.patterns
+    }
+
+    /// Maps a point in module space to image pixels
+    pub fn module_to_image(&self, module_point: Point) -> Point {
+        let region = self
+            .regions
+            .iter()
+            .find(|r| {
+                module_point.x >= r.p0.x
+                    && module_point.x < r.p1.x
+                    && module_point.y >= r.p0.y
+                    && module_point.y < r.p1.y
+            })
+            .or(self.regions.first());
+
+        region.map_or(module_point, |r| r.transform.transform_point(module_point))
+    }
+
+    /// The image position the module at `(x, y)` was sampled at
+    pub fn module_center(&self, x: u32, y: u32) -> Point {
+        self.module_to_image(point(x as f32 + 0.5, y as f32 + 0.5))
+    }
+
+    /**
+     * The modules that were likely misread from `image`: those whose center sample disagrees
+     * with at least two of four samples a third of a module diagonally from it, such as
+     * modules with a speck at their center or on a blurred edge. Decoders can treat their
+     * codewords as erasures. Samples outside of the image are ignored.
+     */
+    pub fn uncertain_modules(&self, image: &BitMatrix) -> Result<BitMatrix> {
+        const OFFSETS: [(f32, f32); 4] = [(-1.0, -1.0), (1.0, -1.0), (-1.0, 1.0), (1.0, 1.0)];
+
+        let mut uncertain = BitMatrix::new(self.width, self.height)?;
+        let sample = |x: f32, y: f32| {
+            let p = self.module_to_image(point(x, y));
+            image.is_in(p).then(|| image.get_point(p))
+        };
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let (cx, cy) = (x as f32 + 0.5, y as f32 + 0.5);
+                let Some(center) = sample(cx, cy) else {
+                    continue;
+                };
+                let disagreeing = OFFSETS
+                    .iter()
+                    .filter(|(dx, dy)| {
+                        sample(cx + dx / 3.0, cy + dy / 3.0).is_some_and(|bit| bit != center)
+                    })
+                    .count();
+                if disagreeing >= 2 {
+                    uncertain.set(x, y);
+                }
+            }
+        }
+        Ok(uncertain)
+    }
+
+    /**
+     * The estimated module pitch in pixels: the mean distance between the centers of
+     * horizontally and vertically adjacent modules in the middle of the symbol.
+     */
+    pub fn module_size(&self) -> f32 {
+        let (x, y) = (self.width / 2, self.height / 2);
+        let center = self.module_to_image(point(x as f32, y as f32));
+        let right = self.module_to_image(point(x as f32 + 1.0, y as f32));
+        let below = self.module_to_image(point(x as f32, y as f32 + 1.0));
+
+        (Point::distance(center, right) + Point::distance(center, below)) / 2.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::{
+        common::SamplingGrid, helpers, point, BarcodeFormat, DecodeHints, EncodeHints,
+        MultiFormatWriter, RXingResultMetadataType, RXingResultMetadataValue, Writer,
+    };
+
+    #[test]
+    fn test_axis_aligned() {
+        let grid = SamplingGrid::axis_aligned(21, 21, 10.0, 20.0, 4.0).unwrap();
+        assert_eq!(point(12.0, 22.0), grid.module_center(0, 0));
+        assert_eq!(point(92.0, 102.0), grid.module_center(20, 20));
+        assert!((grid.module_size() - 4.0).abs() < 0.01);
+
+        let moved = grid.translated(point(-10.0, 5.0));
+        assert_eq!(point(2.0, 27.0), moved.module_center(0, 0));
+        assert_eq!(point(82.0, 107.0), moved.module_center(20, 20));
+    }
+
+    #[test]
+    fn test_decode_reports_sampling_grid() {
+        for format in [
+            BarcodeFormat::QR_CODE,
+            BarcodeFormat::DATA_MATRIX,
+            BarcodeFormat::AZTEC,
+        ] {
+            let hints = EncodeHints {
+                Margin: Some("4".to_owned()),
+                ..Default::default()
+            };
+            let matrix = MultiFormatWriter
+                .encode_with_hints("sampling grid", &format, 200, 200, &hints)
+                .unwrap();
+            let luma = matrix.to_luma8();
+            let symbol = matrix.getEnclosingRectangle().unwrap();
+
+            let result = helpers::detect_in_luma_with_hints(
+                luma,
+                matrix.getWidth(),
+                matrix.getHeight(),
+                None,
+                &mut DecodeHints {
+                    PossibleFormats: Some(HashSet::from([format.clone()])),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+            let Some(RXingResultMetadataValue::SamplingGrid(grid)) = result
+                .getRXingResultMetadata()
+                .get(&RXingResultMetadataType::SAMPLING_GRID)
+            else {
+                panic!("no sampling grid for {format}");
+            };
+
+            let expected_module_size = symbol[2] as f32 / grid.width() as f32;
+            assert!(
+                (grid.module_size() - expected_module_size).abs() < 0.5,
+                "{format}: {} vs {expected_module_size}",
+                grid.module_size()
+            );
+            let top_left = grid.module_center(0, 0);
+            assert!((top_left.x - symbol[0] as f32 - expected_module_size / 2.0).abs() < 1.5);
+            assert!((top_left.y - symbol[1] as f32 - expected_module_size / 2.0).abs() < 1.5);
+        }
+    }
+}
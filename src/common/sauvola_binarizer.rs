@@ -0,0 +1,299 @@
+/*
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::borrow::Cow;
+
+use once_cell::sync::OnceCell;
+
+use crate::common::Result;
+use crate::{Binarizer, LuminanceSource};
+
+use super::{BitArray, BitMatrix, LineOrientation};
+
+const DEFAULT_WINDOW_SIZE: usize = 25;
+const DEFAULT_K: f32 = 0.2;
+const DEFAULT_NIBLACK_K: f32 = -0.2;
+// The dynamic range of the standard deviation in Sauvola's formula
+const R: f32 = 128.0;
+
+/// How a `SauvolaBinarizer` derives the threshold of a pixel from the mean `m` and the standard
+/// deviation `s` of the luminance in the window around it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LocalThresholdMethod {
+    /// `m * (1 + k * (s / 128 - 1))`, which leaves flat areas white whatever their brightness
+    Sauvola,
+    /// `m + k * s`, which follows the local mean more closely but turns noise in flat areas
+    /// black. `k` is negative, usually -0.2.
+    Niblack,
+}
+
+/**
+ * A binarizer that thresholds each pixel by the mean and the standard deviation of the
+ * luminance in a square window around it, after Sauvola and Niblack. Unlike the block-wise
+ * `HybridBinarizer` it follows strong illumination gradients and highlights on glossy labels
+ * from pixel to pixel, at the cost of a pass over the image with two integral images.
+ *
+ * The window should be a few modules wide, so that it takes in both dark and light modules.
+ * Pixels darker than their threshold are black.
+ */
+pub struct SauvolaBinarizer<LS: LuminanceSource> {
+    source: LS,
+    window_size: usize,
+    k: f32,
+    method: LocalThresholdMethod,
+    width: usize,
+    height: usize,
+    black_matrix: OnceCell<BitMatrix>,
+}
+
+impl<LS: LuminanceSource> SauvolaBinarizer<LS> {
+    /**
+     * Creates a new SauvolaBinarizer with Sauvola's method, a window of 25 pixels and a k of
+     * 0.2.
+     */
+    pub fn new(source: LS) -> Self {
+        Self::with_parameters(source, DEFAULT_WINDOW_SIZE, DEFAULT_K)
+    }
+
+    /**
+     * Creates a new SauvolaBinarizer with Sauvola's method.
+     *
+     * @param source The luminance source
+     * @param window_size The width and height of the window in pixels, rounded up to be odd
+     * @param k How far below the local mean the threshold falls with little contrast, usually
+     *  between 0.2 and 0.5
+     */
+    pub fn with_parameters(source: LS, window_size: usize, k: f32) -> Self {
+        Self::with_method(source, LocalThresholdMethod::Sauvola, window_size, k)
+    }
+
+    /**
+     * Creates a new SauvolaBinarizer with Niblack's method and a k of -0.2.
+     *
+     * @param source The luminance source
+     * @param window_size The width and height of the window in pixels, rounded up to be odd
+     */
+    pub fn niblack(source: LS, window_size: usize) -> Self {
+        Self::with_method(
+            source,
+            LocalThresholdMethod::Niblack,
+            window_size,
+            DEFAULT_NIBLACK_K,
+        )
+    }
+
+    /**
+     * Creates a new SauvolaBinarizer.
+     *
+     * @param source The luminance source
+     * @param method How the threshold follows from the mean and standard deviation
+     * @param window_size The width and height of the window in pixels, rounded up to be odd
+     * @param k The weight of the standard deviation
+     */
+    pub fn with_method(
+        source: LS,
+        method: LocalThresholdMethod,
+        window_size: usize,
+        k: f32,
+    ) -> Self {
+        Self {
+            width: source.get_width(),
+            height: source.get_height(),
+            source,
+            window_size: window_size.max(1) | 1,
+            k,
+            method,
+            black_matrix: OnceCell::new(),
+        }
+    }
+
+    /// The width and height of the window in pixels
+    pub fn get_window_size(&self) -> usize {
+        self.window_size
+    }
+
+    /// The weight of the standard deviation in the threshold
+    pub fn get_k(&self) -> f32 {
+        self.k
+    }
+
+    pub fn get_method(&self) -> LocalThresholdMethod {
+        self.method
+    }
+
+    /// The threshold of a pixel whose window has the luminance `mean` and `deviation`
+    fn threshold(&self, mean: f32, deviation: f32) -> f32 {
+        match self.method {
+            LocalThresholdMethod::Sauvola => mean * (1.0 + self.k * (deviation / R - 1.0)),
+            LocalThresholdMethod::Niblack => mean + self.k * deviation,
+        }
+    }
+
+    fn build_matrix(&self) -> Result<BitMatrix> {
+        let (width, height) = (self.width, self.height);
+        let luminances = self.source.get_matrix();
+
+        // the sums of the luminance and of its square over the pixels above and to the left,
+        // with a row and a column of zeros in front
+        let stride = width + 1;
+        let mut sums = vec![0u64; stride * (height + 1)];
+        let mut squares = vec![0u64; stride * (height + 1)];
+        for y in 0..height {
+            let (mut row_sum, mut row_square) = (0u64, 0u64);
+            for x in 0..width {
+                let luminance = luminances[y * width + x] as u64;
+                row_sum += luminance;
+                row_square += luminance * luminance;
+                let i = (y + 1) * stride + x + 1;
+                sums[i] = sums[i - stride] + row_sum;
+                squares[i] = squares[i - stride] + row_square;
+            }
+        }
+        let window = |table: &[u64], left: usize, top: usize, right: usize, bottom: usize| {
+            table[bottom * stride + right] + table[top * stride + left]
+                - table[top * stride + right]
+                - table[bottom * stride + left]
+        };
+
+        let radius = self.window_size / 2;
+        let mut matrix = BitMatrix::new(width as u32, height as u32)?;
+        for y in 0..height {
+            let top = y.saturating_sub(radius);
+            let bottom = (y + radius + 1).min(height);
+            for x in 0..width {
+                let left = x.saturating_sub(radius);
+                let right = (x + radius + 1).min(width);
+                let count = ((right - left) * (bottom - top)) as f32;
+                let mean = window(&sums, left, top, right, bottom) as f32 / count;
+                let variance =
+                    window(&squares, left, top, right, bottom) as f32 / count - mean * mean;
+                let threshold = self.threshold(mean, variance.max(0.0).sqrt());
+                if (luminances[y * width + x] as f32) < threshold {
+                    matrix.set(x as u32, y as u32);
+                }
+            }
+        }
+
+        Ok(matrix)
+    }
+}
+
+impl<LS: LuminanceSource> Binarizer for SauvolaBinarizer<LS> {
+    type Source = LS;
+
+    fn get_luminance_source(&self) -> &Self::Source {
+        &self.source
+    }
+
+    fn get_black_row(&self, y: usize) -> Result<Cow<'_, BitArray>> {
+        let matrix = self.get_black_matrix()?;
+        Ok(Cow::Owned(matrix.getRow(y as u32)))
+    }
+
+    fn get_black_line(&self, l: usize, lt: LineOrientation) -> Result<Cow<'_, BitArray>> {
+        let matrix = self.get_black_matrix()?;
+        Ok(Cow::Owned(match lt {
+            LineOrientation::Row => matrix.getRow(l as u32),
+            LineOrientation::Column => matrix.getCol(l as u32),
+        }))
+    }
+
+    fn get_black_matrix(&self) -> Result<&BitMatrix> {
+        self.black_matrix.get_or_try_init(|| self.build_matrix())
+    }
+
+    fn get_black_row_from_matrix(&self, y: usize) -> Result<Cow<'_, BitArray>> {
+        self.get_black_row(y)
+    }
+
+    fn create_binarizer(&self, source: Self::Source) -> Self
+    where
+        Self: Sized,
+    {
+        Self::with_method(source, self.method, self.window_size, self.k)
+    }
+
+    fn get_width(&self) -> usize {
+        self.width
+    }
+
+    fn get_height(&self) -> usize {
+        self.height
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        common::FixedThresholdBinarizer, qrcode::QRCodeReader, BarcodeFormat, Binarizer,
+        BinaryBitmap, DecodeHints, Luma8LuminanceSource, MultiFormatWriter, Reader, Writer,
+    };
+
+    use super::{LocalThresholdMethod, SauvolaBinarizer};
+
+    /// A QR code of 4 pixel modules lit from the right, its white going from 50 to 255 and its
+    /// black from 10 to 60
+    fn gradient() -> Luma8LuminanceSource {
+        let matrix = MultiFormatWriter
+            .encode("illumination gradient", &BarcodeFormat::QR_CODE, 140, 140)
+            .expect("encode");
+        let (width, height) = (matrix.getWidth(), matrix.getHeight());
+        let mut luma = Vec::new();
+        for y in 0..height {
+            for x in 0..width {
+                let light = 50.0 + 205.0 * x as f32 / width as f32;
+                let value = if matrix.get(x, y) {
+                    light * 0.2 + 10.0
+                } else {
+                    light
+                };
+                luma.push(value as u8);
+            }
+        }
+        Luma8LuminanceSource::new(luma, width, height)
+    }
+
+    fn decodes<B: Binarizer>(binarizer: B) -> bool {
+        let hints = DecodeHints::default().with(crate::DecodeHintValue::TryHarder(true));
+        QRCodeReader
+            .decode_with_hints(&mut BinaryBitmap::new(binarizer), &hints)
+            .is_ok_and(|result| result.getText() == "illumination gradient")
+    }
+
+    #[test]
+    fn test_flat_areas() {
+        let luma = vec![30, 30, 30, 30, 200, 200, 200, 200, 200];
+        let binarizer =
+            SauvolaBinarizer::with_parameters(Luma8LuminanceSource::new(luma, 9, 1), 3, 0.2);
+        let row = binarizer.get_black_row(0).unwrap();
+        // only the dark pixel next to the light ones stands out from its window
+        let black: Vec<bool> = (0..9).map(|x| row.get(x)).collect();
+        assert_eq!(
+            vec![false, false, false, true, false, false, false, false, false],
+            black
+        );
+        assert_eq!(3, binarizer.get_window_size());
+        assert_eq!(
+            LocalThresholdMethod::Niblack,
+            SauvolaBinarizer::niblack(Luma8LuminanceSource::new(vec![0], 1, 1), 4).get_method()
+        );
+    }
+
+    #[test]
+    fn test_illumination_gradient() {
+        assert!(!decodes(FixedThresholdBinarizer::new(gradient())));
+        assert!(decodes(SauvolaBinarizer::new(gradient())));
+        assert!(decodes(SauvolaBinarizer::niblack(gradient(), 25)));
+    }
+}
@@ -0,0 +1,578 @@
+/*
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! The inner loops of the binarizers and of the conversion of RGB pixels to luminance. With
+//! the `simd` feature they run on SSE2, or AVX2 where the CPU has it, on x86-64 and on NEON on
+//! AArch64; otherwise, and on other targets, they are plain loops. Either way they give the
+//! same results.
+
+/// The side of the square blocks of `block_stats`
+pub(crate) const BLOCK_SIZE: usize = 8;
+
+/// The spread between the darkest and the lightest pixel of a block above which
+/// `HybridBinarizer` takes the block to have contrast
+const MIN_DYNAMIC_RANGE: u8 = 24;
+
+/**
+ * The sum, the least and the greatest luminance of the 8x8 pixels from `offset` on, whose rows
+ * are `stride` apart. Once the spread of a block is above 24, the plain loop only keeps on
+ * summing, so the least and greatest values are then exact only as far as that spread goes.
+ */
+pub(crate) fn block_stats(luminances: &[u8], offset: usize, stride: usize) -> (u32, u8, u8) {
+    assert!(offset + (BLOCK_SIZE - 1) * stride + BLOCK_SIZE <= luminances.len());
+
+    #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+    // SAFETY: SSE2 is part of x86-64, and the rows of the block lie within `luminances`
+    unsafe {
+        x86::block_stats_sse2(luminances, offset, stride)
+    }
+    #[cfg(all(feature = "simd", target_arch = "aarch64"))]
+    // SAFETY: NEON is part of AArch64, and the rows of the block lie within `luminances`
+    unsafe {
+        neon::block_stats(luminances, offset, stride)
+    }
+    #[cfg(not(all(feature = "simd", any(target_arch = "x86_64", target_arch = "aarch64"))))]
+    scalar::block_stats(luminances, offset, stride)
+}
+
+/// Calls `set` with the index of each pixel of `row` that is no lighter than `threshold`, in
+/// order
+pub(crate) fn for_each_at_most(row: &[u8], threshold: u8, set: impl FnMut(usize)) {
+    #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+    {
+        if std::arch::is_x86_feature_detected!("avx2") {
+            // SAFETY: the CPU has AVX2
+            unsafe { x86::for_each_at_most_avx2(row, threshold, set) }
+        } else {
+            // SAFETY: SSE2 is part of x86-64
+            unsafe { x86::for_each_at_most_sse2(row, threshold, set, 0) }
+        }
+    }
+    #[cfg(all(feature = "simd", target_arch = "aarch64"))]
+    // SAFETY: NEON is part of AArch64
+    unsafe {
+        neon::for_each_at_most(row, threshold, set)
+    }
+    #[cfg(not(all(feature = "simd", any(target_arch = "x86_64", target_arch = "aarch64"))))]
+    scalar::for_each_at_most(row, threshold, set, 0)
+}
+
+/// Converts 0xAARRGGBB pixels to the green-favouring average `(R + 2G + B) / 4`
+pub(crate) fn argb_to_luma(pixels: &[u32], luma: &mut [u8]) {
+    assert_eq!(pixels.len(), luma.len());
+
+    #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+    {
+        if std::arch::is_x86_feature_detected!("avx2") {
+            // SAFETY: the CPU has AVX2
+            unsafe { x86::argb_to_luma_avx2(pixels, luma) }
+        } else {
+            // SAFETY: SSE2 is part of x86-64
+            unsafe { x86::argb_to_luma_sse2(pixels, luma, 0) }
+        }
+    }
+    #[cfg(all(feature = "simd", target_arch = "aarch64"))]
+    // SAFETY: NEON is part of AArch64
+    unsafe {
+        neon::argb_to_luma(pixels, luma)
+    }
+    #[cfg(not(all(feature = "simd", any(target_arch = "x86_64", target_arch = "aarch64"))))]
+    scalar::argb_to_luma(pixels, luma, 0)
+}
+
+/**
+ * Converts RGBA pixels, four bytes each, to their luminance `0.299R + 0.587G + 0.114B`
+ * (BT.601), computed as `(306R + 601G + 117B + 512) >> 10`. Transparent pixels are white.
+ */
+pub(crate) fn rgba_to_luma(pixels: &[u8], luma: &mut [u8]) {
+    assert_eq!(pixels.len(), luma.len() * 4);
+
+    #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+    // SAFETY: SSE2 is part of x86-64
+    unsafe {
+        x86::rgba_to_luma_sse2(pixels, luma)
+    }
+    #[cfg(all(feature = "simd", target_arch = "aarch64"))]
+    // SAFETY: NEON is part of AArch64
+    unsafe {
+        neon::rgba_to_luma(pixels, luma)
+    }
+    #[cfg(not(all(feature = "simd", any(target_arch = "x86_64", target_arch = "aarch64"))))]
+    scalar::rgba_to_luma(pixels, luma, 0)
+}
+
+/// The luminance of one pixel, weighted as in `rgba_to_luma`
+pub(crate) fn luminance(red: u8, green: u8, blue: u8) -> u8 {
+    // (306*R) >> 10 is approximately equal to R*0.299, and so on.
+    // 0x200 >> 10 is 0.5, it implements rounding.
+    ((306 * red as u32 + 601 * green as u32 + 117 * blue as u32 + 0x200) >> 10) as u8
+}
+
+/// The plain loops, which the SIMD paths also use for the pixels left over at the end of a row
+mod scalar {
+    use super::{BLOCK_SIZE, MIN_DYNAMIC_RANGE};
+
+    #[allow(dead_code)]
+    pub(super) fn block_stats(luminances: &[u8], offset: usize, stride: usize) -> (u32, u8, u8) {
+        let mut sum: u32 = 0;
+        let mut min = u8::MAX;
+        let mut max = u8::MIN;
+        let mut offset = offset;
+        let mut yy = 0;
+        while yy < BLOCK_SIZE {
+            for &pixel in &luminances[offset..offset + BLOCK_SIZE] {
+                sum += pixel as u32;
+                // still looking for good contrast
+                min = min.min(pixel);
+                max = max.max(pixel);
+            }
+            // short-circuit min/max tests once dynamic range is met
+            if max - min > MIN_DYNAMIC_RANGE {
+                // finish the rest of the rows quickly
+                offset += stride;
+                yy += 1;
+                while yy < BLOCK_SIZE {
+                    sum += luminances[offset..offset + BLOCK_SIZE]
+                        .iter()
+                        .map(|&b| b as u32)
+                        .sum::<u32>();
+                    yy += 1;
+                    offset += stride;
+                }
+                break;
+            }
+            yy += 1;
+            offset += stride;
+        }
+        (sum, min, max)
+    }
+
+    pub(super) fn for_each_at_most(
+        row: &[u8],
+        threshold: u8,
+        mut set: impl FnMut(usize),
+        from: usize,
+    ) {
+        for (x, &pixel) in row.iter().enumerate().skip(from) {
+            if pixel <= threshold {
+                set(x);
+            }
+        }
+    }
+
+    pub(super) fn argb_to_luma(pixels: &[u32], luma: &mut [u8], from: usize) {
+        for (pixel, luma) in pixels.iter().zip(luma.iter_mut()).skip(from) {
+            let r = (pixel >> 16) & 0xff; // red
+            let g2 = (pixel >> 7) & 0x1fe; // 2 * green
+            let b = pixel & 0xff; // blue
+            *luma = ((r + g2 + b) / 4) as u8;
+        }
+    }
+
+    pub(super) fn rgba_to_luma(pixels: &[u8], luma: &mut [u8], from: usize) {
+        for (pixel, luma) in pixels.chunks_exact(4).zip(luma.iter_mut()).skip(from) {
+            let [red, green, blue, alpha] = [pixel[0], pixel[1], pixel[2], pixel[3]];
+            *luma = if alpha == 0 {
+                // white, so we know its luminance is 255
+                0xFF
+            } else {
+                super::luminance(red, green, blue)
+            };
+        }
+    }
+}
+
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+mod x86 {
+    use std::arch::x86_64::*;
+
+    use super::{scalar, BLOCK_SIZE};
+
+    #[target_feature(enable = "sse2")]
+    pub(super) unsafe fn block_stats_sse2(
+        luminances: &[u8],
+        offset: usize,
+        stride: usize,
+    ) -> (u32, u8, u8) {
+        let base = luminances.as_ptr().add(offset);
+        let zero = _mm_setzero_si128();
+        let mut sum = zero;
+        let mut min = _mm_set1_epi8(-1);
+        let mut max = zero;
+        // two rows of the block to a register
+        for row in (0..BLOCK_SIZE).step_by(2) {
+            let upper = _mm_loadl_epi64(base.add(row * stride) as *const __m128i);
+            let lower = _mm_loadl_epi64(base.add((row + 1) * stride) as *const __m128i);
+            let rows = _mm_unpacklo_epi64(upper, lower);
+            sum = _mm_add_epi64(sum, _mm_sad_epu8(rows, zero));
+            min = _mm_min_epu8(min, rows);
+            max = _mm_max_epu8(max, rows);
+        }
+        let sum = _mm_add_epi64(sum, _mm_srli_si128(sum, 8));
+        (
+            _mm_cvtsi128_si32(sum) as u32,
+            horizontal(min, |a, b| _mm_min_epu8(a, b)),
+            horizontal(max, |a, b| _mm_max_epu8(a, b)),
+        )
+    }
+
+    /// Folds the 16 bytes of `v` with `f`
+    #[target_feature(enable = "sse2")]
+    unsafe fn horizontal(v: __m128i, f: impl Fn(__m128i, __m128i) -> __m128i) -> u8 {
+        let v = f(v, _mm_srli_si128(v, 8));
+        let v = f(v, _mm_srli_si128(v, 4));
+        let v = f(v, _mm_srli_si128(v, 2));
+        let v = f(v, _mm_srli_si128(v, 1));
+        _mm_cvtsi128_si32(v) as u8
+    }
+
+    /// Calls `set` for each bit of `mask`, the lowest bit being pixel `x`
+    #[inline(always)]
+    fn for_each_bit(mut mask: u32, x: usize, set: &mut impl FnMut(usize)) {
+        while mask != 0 {
+            set(x + mask.trailing_zeros() as usize);
+            mask &= mask - 1;
+        }
+    }
+
+    #[target_feature(enable = "sse2")]
+    pub(super) unsafe fn for_each_at_most_sse2(
+        row: &[u8],
+        threshold: u8,
+        mut set: impl FnMut(usize),
+        from: usize,
+    ) {
+        let limit = _mm_set1_epi8(threshold as i8);
+        let mut x = from;
+        while x + 16 <= row.len() {
+            let pixels = _mm_loadu_si128(row.as_ptr().add(x) as *const __m128i);
+            // pixel <= threshold exactly where min(pixel, threshold) == pixel
+            let at_most = _mm_cmpeq_epi8(_mm_min_epu8(pixels, limit), pixels);
+            for_each_bit(_mm_movemask_epi8(at_most) as u32, x, &mut set);
+            x += 16;
+        }
+        if x + 8 <= row.len() {
+            let pixels = _mm_loadl_epi64(row.as_ptr().add(x) as *const __m128i);
+            let at_most = _mm_cmpeq_epi8(_mm_min_epu8(pixels, limit), pixels);
+            for_each_bit(_mm_movemask_epi8(at_most) as u32 & 0xFF, x, &mut set);
+            x += 8;
+        }
+        scalar::for_each_at_most(row, threshold, set, x);
+    }
+
+    #[target_feature(enable = "avx2")]
+    pub(super) unsafe fn for_each_at_most_avx2(
+        row: &[u8],
+        threshold: u8,
+        mut set: impl FnMut(usize),
+    ) {
+        let limit = _mm256_set1_epi8(threshold as i8);
+        let mut x = 0;
+        while x + 32 <= row.len() {
+            let pixels = _mm256_loadu_si256(row.as_ptr().add(x) as *const __m256i);
+            let at_most = _mm256_cmpeq_epi8(_mm256_min_epu8(pixels, limit), pixels);
+            for_each_bit(_mm256_movemask_epi8(at_most) as u32, x, &mut set);
+            x += 32;
+        }
+        for_each_at_most_sse2(row, threshold, set, x);
+    }
+
+    /// `(R + 2G + B) / 4` of four 0xAARRGGBB pixels, one to each 32 bit lane
+    #[target_feature(enable = "sse2")]
+    unsafe fn average_sse2(pixels: __m128i) -> __m128i {
+        let byte = _mm_set1_epi32(0xFF);
+        let r = _mm_and_si128(_mm_srli_epi32(pixels, 16), byte);
+        let g = _mm_and_si128(_mm_srli_epi32(pixels, 8), byte);
+        let b = _mm_and_si128(pixels, byte);
+        let sum = _mm_add_epi32(_mm_add_epi32(r, b), _mm_slli_epi32(g, 1));
+        _mm_srli_epi32(sum, 2)
+    }
+
+    #[target_feature(enable = "sse2")]
+    pub(super) unsafe fn argb_to_luma_sse2(pixels: &[u32], luma: &mut [u8], from: usize) {
+        let mut x = from;
+        while x + 16 <= pixels.len() {
+            let source = pixels.as_ptr().add(x) as *const __m128i;
+            let averages = [0, 1, 2, 3].map(|i| average_sse2(_mm_loadu_si128(source.add(i))));
+            // the averages are at most 255, so packing does not saturate
+            let low = _mm_packs_epi32(averages[0], averages[1]);
+            let high = _mm_packs_epi32(averages[2], averages[3]);
+            _mm_storeu_si128(
+                luma.as_mut_ptr().add(x) as *mut __m128i,
+                _mm_packus_epi16(low, high),
+            );
+            x += 16;
+        }
+        scalar::argb_to_luma(pixels, luma, x);
+    }
+
+    #[target_feature(enable = "avx2")]
+    pub(super) unsafe fn argb_to_luma_avx2(pixels: &[u32], luma: &mut [u8]) {
+        let byte = _mm256_set1_epi32(0xFF);
+        let average = |pixels: __m256i| {
+            let r = _mm256_and_si256(_mm256_srli_epi32(pixels, 16), byte);
+            let g = _mm256_and_si256(_mm256_srli_epi32(pixels, 8), byte);
+            let b = _mm256_and_si256(pixels, byte);
+            let sum = _mm256_add_epi32(_mm256_add_epi32(r, b), _mm256_slli_epi32(g, 1));
+            _mm256_srli_epi32(sum, 2)
+        };
+        // packing works within the 128 bit halves, which leaves the groups of four pixels in
+        // the order 0 2 4 6 1 3 5 7
+        let order = _mm256_setr_epi32(0, 4, 1, 5, 2, 6, 3, 7);
+        let mut x = 0;
+        while x + 32 <= pixels.len() {
+            let source = pixels.as_ptr().add(x) as *const __m256i;
+            let averages = [0, 1, 2, 3].map(|i| average(_mm256_loadu_si256(source.add(i))));
+            let low = _mm256_packs_epi32(averages[0], averages[1]);
+            let high = _mm256_packs_epi32(averages[2], averages[3]);
+            let packed = _mm256_permutevar8x32_epi32(_mm256_packus_epi16(low, high), order);
+            _mm256_storeu_si256(luma.as_mut_ptr().add(x) as *mut __m256i, packed);
+            x += 32;
+        }
+        argb_to_luma_sse2(pixels, luma, x);
+    }
+
+    /// The luminance of four RGBA pixels, one to each 32 bit lane
+    #[target_feature(enable = "sse2")]
+    unsafe fn luminance_sse2(pixels: __m128i) -> __m128i {
+        let zero = _mm_setzero_si128();
+        let weights = _mm_setr_epi16(306, 601, 117, 0, 306, 601, 117, 0);
+        // R*306 + G*601 and B*117 of each pixel, side by side
+        let low = _mm_madd_epi16(_mm_unpacklo_epi8(pixels, zero), weights);
+        let high = _mm_madd_epi16(_mm_unpackhi_epi8(pixels, zero), weights);
+        let low = _mm_add_epi32(low, _mm_srli_epi64(low, 32));
+        let high = _mm_add_epi32(high, _mm_srli_epi64(high, 32));
+        let sums = _mm_unpacklo_epi64(
+            _mm_shuffle_epi32(low, 0b00_00_10_00),
+            _mm_shuffle_epi32(high, 0b00_00_10_00),
+        );
+        let luminance = _mm_srli_epi32(_mm_add_epi32(sums, _mm_set1_epi32(0x200)), 10);
+        // transparent pixels are white
+        let transparent = _mm_cmpeq_epi32(_mm_srli_epi32(pixels, 24), zero);
+        _mm_or_si128(luminance, _mm_and_si128(transparent, _mm_set1_epi32(0xFF)))
+    }
+
+    #[target_feature(enable = "sse2")]
+    pub(super) unsafe fn rgba_to_luma_sse2(pixels: &[u8], luma: &mut [u8]) {
+        let mut x = 0;
+        while x + 16 <= luma.len() {
+            let source = pixels.as_ptr().add(x * 4) as *const __m128i;
+            let luminances = [0, 1, 2, 3].map(|i| luminance_sse2(_mm_loadu_si128(source.add(i))));
+            let low = _mm_packs_epi32(luminances[0], luminances[1]);
+            let high = _mm_packs_epi32(luminances[2], luminances[3]);
+            _mm_storeu_si128(
+                luma.as_mut_ptr().add(x) as *mut __m128i,
+                _mm_packus_epi16(low, high),
+            );
+            x += 16;
+        }
+        scalar::rgba_to_luma(pixels, luma, x);
+    }
+}
+
+#[cfg(all(feature = "simd", target_arch = "aarch64"))]
+mod neon {
+    use std::arch::aarch64::*;
+
+    use super::{scalar, BLOCK_SIZE};
+
+    #[target_feature(enable = "neon")]
+    pub(super) unsafe fn block_stats(
+        luminances: &[u8],
+        offset: usize,
+        stride: usize,
+    ) -> (u32, u8, u8) {
+        let base = luminances.as_ptr().add(offset);
+        let mut sum = 0;
+        let mut min = vdup_n_u8(u8::MAX);
+        let mut max = vdup_n_u8(0);
+        for row in 0..BLOCK_SIZE {
+            let pixels = vld1_u8(base.add(row * stride));
+            sum += vaddlv_u8(pixels) as u32;
+            min = vmin_u8(min, pixels);
+            max = vmax_u8(max, pixels);
+        }
+        (sum, vminv_u8(min), vmaxv_u8(max))
+    }
+
+    #[target_feature(enable = "neon")]
+    pub(super) unsafe fn for_each_at_most(row: &[u8], threshold: u8, mut set: impl FnMut(usize)) {
+        let limit = vdupq_n_u8(threshold);
+        let mut x = 0;
+        while x + 16 <= row.len() {
+            let at_most = vcleq_u8(vld1q_u8(row.as_ptr().add(x)), limit);
+            // NEON has no movemask, so only the lanes of blocks with a dark pixel are looked at
+            if vmaxvq_u8(at_most) != 0 {
+                let mut lanes = [0u8; 16];
+                vst1q_u8(lanes.as_mut_ptr(), at_most);
+                for (i, lane) in lanes.iter().enumerate() {
+                    if *lane != 0 {
+                        set(x + i);
+                    }
+                }
+            }
+            x += 16;
+        }
+        scalar::for_each_at_most(row, threshold, set, x);
+    }
+
+    #[target_feature(enable = "neon")]
+    pub(super) unsafe fn argb_to_luma(pixels: &[u32], luma: &mut [u8]) {
+        let mut x = 0;
+        while x + 16 <= pixels.len() {
+            // little endian 0xAARRGGBB pixels are the bytes B G R A
+            let bgra = vld4q_u8(pixels.as_ptr().add(x) as *const u8);
+            let average = |b: uint8x8_t, g: uint8x8_t, r: uint8x8_t| {
+                vshrn_n_u16(vaddq_u16(vaddl_u8(r, b), vshll_n_u8(g, 1)), 2)
+            };
+            let low = average(
+                vget_low_u8(bgra.0),
+                vget_low_u8(bgra.1),
+                vget_low_u8(bgra.2),
+            );
+            let high = average(
+                vget_high_u8(bgra.0),
+                vget_high_u8(bgra.1),
+                vget_high_u8(bgra.2),
+            );
+            vst1q_u8(luma.as_mut_ptr().add(x), vcombine_u8(low, high));
+            x += 16;
+        }
+        scalar::argb_to_luma(pixels, luma, x);
+    }
+
+    /// The luminance of four pixels from 16 bit red, green and blue
+    #[target_feature(enable = "neon")]
+    unsafe fn luminance(r: uint16x4_t, g: uint16x4_t, b: uint16x4_t) -> uint16x4_t {
+        let sum = vmlal_n_u16(vmlal_n_u16(vmull_n_u16(r, 306), g, 601), b, 117);
+        vmovn_u32(vshrq_n_u32(vaddq_u32(sum, vdupq_n_u32(0x200)), 10))
+    }
+
+    #[target_feature(enable = "neon")]
+    pub(super) unsafe fn rgba_to_luma(pixels: &[u8], luma: &mut [u8]) {
+        let mut x = 0;
+        while x + 16 <= luma.len() {
+            let rgba = vld4q_u8(pixels.as_ptr().add(x * 4));
+            let eight = |r: uint8x8_t, g: uint8x8_t, b: uint8x8_t| {
+                let (r, g, b) = (vmovl_u8(r), vmovl_u8(g), vmovl_u8(b));
+                vmovn_u16(vcombine_u16(
+                    luminance(vget_low_u16(r), vget_low_u16(g), vget_low_u16(b)),
+                    luminance(vget_high_u16(r), vget_high_u16(g), vget_high_u16(b)),
+                ))
+            };
+            let low = eight(
+                vget_low_u8(rgba.0),
+                vget_low_u8(rgba.1),
+                vget_low_u8(rgba.2),
+            );
+            let high = eight(
+                vget_high_u8(rgba.0),
+                vget_high_u8(rgba.1),
+                vget_high_u8(rgba.2),
+            );
+            // transparent pixels are white
+            let transparent = vceqq_u8(rgba.3, vdupq_n_u8(0));
+            vst1q_u8(
+                luma.as_mut_ptr().add(x),
+                vorrq_u8(vcombine_u8(low, high), transparent),
+            );
+            x += 16;
+        }
+        scalar::rgba_to_luma(pixels, luma, x);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng};
+
+    use super::{scalar, BLOCK_SIZE, MIN_DYNAMIC_RANGE};
+
+    fn random(len: usize, seed: u64) -> Vec<u8> {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+        (0..len).map(|_| rng.random()).collect()
+    }
+
+    #[test]
+    fn test_block_stats() {
+        let stride = 21;
+        let mut luminances = random(stride * 10, 1);
+        // a flat block, whose least and greatest values are exact
+        for y in 0..BLOCK_SIZE {
+            for x in 0..BLOCK_SIZE {
+                luminances[y * stride + 12 + x] = 100 + ((x + y) % 5) as u8;
+            }
+        }
+        for offset in [0, 3, stride + 5, 12] {
+            let (sum, min, max) = super::block_stats(&luminances, offset, stride);
+            let (expected_sum, expected_min, expected_max) =
+                scalar::block_stats(&luminances, offset, stride);
+            assert_eq!(expected_sum, sum);
+            if expected_max - expected_min > MIN_DYNAMIC_RANGE {
+                assert!(max - min > MIN_DYNAMIC_RANGE);
+            } else {
+                assert_eq!((expected_min, expected_max), (min, max));
+            }
+        }
+        assert_eq!(
+            (100 * 64 + 128, 100, 104),
+            super::block_stats(&luminances, 12, stride)
+        );
+    }
+
+    #[test]
+    fn test_for_each_at_most() {
+        let row = random(101, 2);
+        for threshold in [0, 1, 127, 128, 254, 255] {
+            for len in [0, 7, 8, 15, 16, 31, 32, 33, 101] {
+                let mut found = Vec::new();
+                super::for_each_at_most(&row[..len], threshold, |x| found.push(x));
+                let mut expected = Vec::new();
+                scalar::for_each_at_most(&row[..len], threshold, |x| expected.push(x), 0);
+                assert_eq!(expected, found, "threshold {threshold}, length {len}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_argb_to_luma() {
+        let pixels: Vec<u32> = random(4 * 75, 3)
+            .chunks_exact(4)
+            .map(|c| u32::from_le_bytes([c[0], c[1], c[2], c[3]]))
+            .chain([0xFFFFFFFF, 0xFF000000, 0x00FF00FF])
+            .collect();
+        let mut luma = vec![0; pixels.len()];
+        super::argb_to_luma(&pixels, &mut luma);
+        let mut expected = vec![0; pixels.len()];
+        scalar::argb_to_luma(&pixels, &mut expected, 0);
+        assert_eq!(expected, luma);
+        assert_eq!([255, 0, 127], luma[75..]);
+    }
+
+    #[test]
+    fn test_rgba_to_luma() {
+        let mut pixels = random(4 * 77, 4);
+        pixels.extend([255, 255, 255, 255, 0, 0, 0, 0, 255, 0, 0, 1]);
+        // some transparent pixels among the SIMD lanes
+        pixels[3] = 0;
+        pixels[4 * 17 + 3] = 0;
+        let mut luma = vec![0; pixels.len() / 4];
+        super::rgba_to_luma(&pixels, &mut luma);
+        let mut expected = vec![0; pixels.len() / 4];
+        scalar::rgba_to_luma(&pixels, &mut expected, 0);
+        assert_eq!(expected, luma);
+        assert_eq!([255, 255, 76], luma[77..]);
+        assert_eq!(255, luma[17]);
+    }
+}
@@ -62,8 +62,13 @@ pub fn guessEncoding(bytes: &[u8], hints: &DecodeHints) -> Option<&'static str>
 }
 
 /**
+ * With the `charset_detection` feature, text that is not ASCII is first given to chardetng,
+ * which can also guess the ISO-8859 and windows code pages. Western European text, text in a
+ * multi byte encoding and text in an encoding without a {@link CharacterSet} is left to the
+ * guesses below.
+ *
  * @param bytes bytes encoding a string, whose encoding should be guessed
- * @param hints decode hints if applicable
+ * @param hints decode hints if applicable, the CharacterSet hint is used without guessing
  * @return Charset of guessed encoding; at the moment will only guess one of:
  *  {@link #SHIFT_JIS_CHARSET}, {@link StandardCharsets#UTF_8},
  *  {@link StandardCharsets#ISO_8859_1}, {@link StandardCharsets#UTF_16},
@@ -85,6 +90,11 @@ pub fn guessCharset(bytes: &[u8], hints: &DecodeHints) -> Option<CharacterSet> {
         }
     }
 
+    #[cfg(feature = "charset_detection")]
+    if let Some(charset) = detect_charset(bytes) {
+        return Some(charset);
+    }
+
     // For now, merely tries to distinguish ISO-8859-1, UTF-8 and Shift_JIS,
     // which should be by far the most common encodings.
     let length = bytes.len();
@@ -233,3 +243,25 @@ pub fn guessCharset(bytes: &[u8], hints: &DecodeHints) -> Option<CharacterSet> {
     // Otherwise, we take a wild guess with platform encoding
     Some(CharacterSet::UTF8)
 }
+
+/**
+ * Guesses the single byte encoding of text with chardetng, from the letters and letter pairs
+ * each encoding it considers would decode the bytes to.
+ *
+ * @return the guess, or None for ASCII, for an encoding without a CharacterSet, for
+ *  windows-1252, which chardetng also falls back to when it can't tell, and for the multi byte
+ *  encodings, which it can't tell apart in the few bytes of a barcode
+ */
+#[cfg(feature = "charset_detection")]
+fn detect_charset(bytes: &[u8]) -> Option<CharacterSet> {
+    if bytes.is_ascii() {
+        return None;
+    }
+    let mut detector = chardetng::EncodingDetector::new();
+    detector.feed(bytes, true);
+    let encoding = detector.guess(None, true);
+    if !encoding.is_single_byte() || encoding.name() == "windows-1252" {
+        return None;
+    }
+    CharacterSet::get_character_set_by_name(encoding.name())
+}
@@ -0,0 +1,132 @@
+/*
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use crate::common::Result;
+use crate::{point, Exceptions, Point};
+
+use super::{default_grid_sampler::project_corners, BitMatrix, GridSampler, SamplerControl};
+
+/**
+ * Samples every module at a 3×3 grid of points spread over it, at a sixth, half and five
+ * sixths of its width and height, and sets it if most of the points that fall inside the
+ * image are black.
+ *
+ * A module under 2 pixels wide covers few pixels, and its center may fall on a pixel that
+ * the binarizer blurred with the neighboring module. The majority of the points across the
+ * module is far less likely to be wrong than the one pixel at its center.
+ */
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SupersamplingGridSampler;
+
+impl SupersamplingGridSampler {
+    /// Where the points are sampled along each side of a module, in module units
+    const OFFSETS: [f32; 3] = [1.0 / 6.0, 0.5, 5.0 / 6.0];
+}
+
+impl GridSampler for SupersamplingGridSampler {
+    fn sample_grid(
+        &self,
+        image: &BitMatrix,
+        dimensionX: u32,
+        dimensionY: u32,
+        controls: &[SamplerControl],
+    ) -> Result<(BitMatrix, [Point; 4])> {
+        if dimensionX == 0 || dimensionY == 0 {
+            return Err(Exceptions::NOT_FOUND);
+        }
+        if controls.iter().any(|control| !control.transform.isValid()) {
+            return Err(Exceptions::NOT_FOUND);
+        }
+
+        let mut bits = BitMatrix::new(dimensionX, dimensionY)?;
+        for SamplerControl { p0, p1, transform } in controls {
+            for y in (p0.y as u32)..(p1.y as u32) {
+                for x in (p0.x as u32)..(p1.x as u32) {
+                    // the center must be in the image, as with the DefaultGridSampler
+                    let center = transform.transform_point(Point::from((x, y)).centered());
+                    if !image.is_in(center) {
+                        return Err(Exceptions::NOT_FOUND);
+                    }
+
+                    let (mut black, mut sampled) = (0, 0);
+                    for dy in Self::OFFSETS {
+                        for dx in Self::OFFSETS {
+                            let p = transform.transform_point(point(x as f32 + dx, y as f32 + dy));
+                            if image.is_in(p) {
+                                sampled += 1;
+                                if image.get_point(p) {
+                                    black += 1;
+                                }
+                            }
+                        }
+                    }
+                    if 2 * black > sampled {
+                        bits.set(x, y);
+                    }
+                }
+            }
+        }
+
+        Ok((bits, project_corners(dimensionX, dimensionY, controls)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SupersamplingGridSampler;
+    use crate::common::{
+        BitMatrix, DefaultGridSampler, GridSampler, PerspectiveTransform, Quadrilateral,
+        SamplerControl,
+    };
+
+    #[test]
+    fn test_majority_vote() {
+        // a checkerboard of modules of 3×3 pixels, with the center pixel of each inverted
+        let mut image = BitMatrix::new(30, 30).unwrap();
+        for y in 0..30 {
+            for x in 0..30 {
+                let black = (x / 3 + y / 3) % 2 == 0;
+                let center = x % 3 == 1 && y % 3 == 1;
+                if black != center {
+                    image.set(x, y);
+                }
+            }
+        }
+        let transform = PerspectiveTransform::quadrilateralToQuadrilateral(
+            Quadrilateral::rectangle_from_xy(0.0, 10.0, 0.0, 10.0, Some(0.0)),
+            Quadrilateral::rectangle_from_xy(0.0, 30.0, 0.0, 30.0, Some(0.0)),
+        )
+        .unwrap();
+        let controls = [SamplerControl::new(10, 10, transform)];
+
+        let (bits, _) = SupersamplingGridSampler
+            .sample_grid(&image, 10, 10, &controls)
+            .unwrap();
+        let (centers, _) = DefaultGridSampler
+            .sample_grid(&image, 10, 10, &controls)
+            .unwrap();
+        for y in 0..10 {
+            for x in 0..10 {
+                assert_eq!((x + y) % 2 == 0, bits.get(x, y));
+                assert_eq!((x + y) % 2 == 1, centers.get(x, y));
+            }
+        }
+
+        // a grid reaching outside the image cannot be sampled
+        let small = BitMatrix::new(20, 20).unwrap();
+        assert!(SupersamplingGridSampler
+            .sample_grid(&small, 10, 10, &controls)
+            .is_err());
+    }
+}
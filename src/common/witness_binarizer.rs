@@ -0,0 +1,241 @@
+/*
+ * Witness Binarizer
+ *
+ * Wraps any binarizer and records what it was given and what it produced, so that a pipeline
+ * gains witness capture by changing the type of its binarizer.
+ */
+
+use std::borrow::Cow;
+use std::time::Duration;
+
+use chrono::Utc;
+use once_cell::sync::OnceCell;
+
+use crate::common::Result;
+use crate::{
+    BinarizationWitness, Binarizer, BlockThresholds, LuminanceSource, LuminanceWitness,
+    PipelineStage, PipelineWitness, WitnessProvenance,
+};
+
+use super::{BitArray, BitMatrix, LineOrientation};
+
+/**
+ * A binarizer that delegates to `B` and records the luminance and binarization stages of a
+ * `PipelineWitness`: the grayscale image, the global threshold if `B` has one, and the
+ * BitMatrix `B` produced. The provenance of the witness names `B` and holds the time it took
+ * to produce the BitMatrix.
+ *
+ * The stages are recorded the first time the black matrix is requested, which 2D readers do
+ * and 1D readers don't, so a witness is only available once a 2D reader has run.
+ *
+ * ```
+ * use rxing::common::{FixedThresholdBinarizer, WitnessBinarizer};
+ * use rxing::{BinaryBitmap, Luma8LuminanceSource};
+ *
+ * let source = Luma8LuminanceSource::new(vec![0, 255, 255, 0], 2, 2);
+ * let bitmap = BinaryBitmap::new(WitnessBinarizer::new(FixedThresholdBinarizer::new(source)));
+ * bitmap.get_black_matrix();
+ *
+ * let witness = bitmap.get_binarizer().witness().unwrap();
+ * assert_eq!(Some(128), witness.binarization().unwrap().threshold);
+ * ```
+ */
+pub struct WitnessBinarizer<B: Binarizer> {
+    binarizer: B,
+    witness: OnceCell<PipelineWitness>,
+}
+
+impl<B: Binarizer> WitnessBinarizer<B> {
+    /**
+     * Wraps `binarizer`, recording its stages once it produces the black matrix.
+     */
+    pub fn new(binarizer: B) -> Self {
+        Self {
+            binarizer,
+            witness: OnceCell::new(),
+        }
+    }
+
+    /**
+     * Returns the wrapped binarizer.
+     */
+    pub fn get_binarizer(&self) -> &B {
+        &self.binarizer
+    }
+
+    /**
+     * Returns the luminance and binarization stages, if the black matrix has been produced.
+     */
+    pub fn witness(&self) -> Option<&PipelineWitness> {
+        self.witness.get()
+    }
+
+    /**
+     * Returns the recorded stages, see `witness`.
+     */
+    pub fn into_witness(self) -> Option<PipelineWitness> {
+        self.witness.into_inner()
+    }
+
+    fn record(&self, matrix: &BitMatrix, elapsed: Duration) {
+        self.witness.get_or_init(|| {
+            let source = self.binarizer.get_luminance_source();
+            let luminance =
+                LuminanceWitness::new(source.get_width(), source.get_height(), source.get_matrix());
+            let binarization = BinarizationWitness {
+                threshold: self.binarizer.get_global_threshold(),
+                block_thresholds: self.binarizer.get_block_thresholds().cloned(),
+                ..BinarizationWitness::new(matrix.clone())
+            };
+
+            let provenance = WitnessProvenance::new()
+                .with_binarizer(&self.binarizer)
+                .with_timing(PipelineStage::Binarization, Duration::ZERO, elapsed);
+
+            PipelineWitness::new()
+                .with_luminance(luminance)
+                .with_binarization(binarization)
+                .with_provenance(provenance)
+        });
+    }
+}
+
+impl<B: Binarizer> Binarizer for WitnessBinarizer<B> {
+    type Source = B::Source;
+
+    fn get_luminance_source(&self) -> &Self::Source {
+        self.binarizer.get_luminance_source()
+    }
+
+    fn get_black_row(&self, y: usize) -> Result<Cow<'_, BitArray>> {
+        self.binarizer.get_black_row(y)
+    }
+
+    fn get_black_row_from_matrix(&self, y: usize) -> Result<Cow<'_, BitArray>> {
+        self.binarizer.get_black_row_from_matrix(y)
+    }
+
+    fn get_black_matrix(&self) -> Result<&BitMatrix> {
+        let started = Utc::now();
+        let matrix = self.binarizer.get_black_matrix()?;
+        self.record(matrix, (Utc::now() - started).to_std().unwrap_or_default());
+        Ok(matrix)
+    }
+
+    fn get_black_line(&self, l: usize, lt: LineOrientation) -> Result<Cow<'_, BitArray>> {
+        self.binarizer.get_black_line(l, lt)
+    }
+
+    fn create_binarizer(&self, source: Self::Source) -> Self
+    where
+        Self: Sized,
+    {
+        Self::new(self.binarizer.create_binarizer(source))
+    }
+
+    fn get_width(&self) -> usize {
+        self.binarizer.get_width()
+    }
+
+    fn get_height(&self) -> usize {
+        self.binarizer.get_height()
+    }
+
+    fn get_global_threshold(&self) -> Option<u8> {
+        self.binarizer.get_global_threshold()
+    }
+
+    fn get_block_thresholds(&self) -> Option<&BlockThresholds> {
+        self.binarizer.get_block_thresholds()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::{FixedThresholdBinarizer, HybridBinarizer};
+    use crate::{
+        test_fixtures::scaled_luma, BarcodeFormat, BinaryBitmap, EncodeHintValue, EncodeHints,
+        Luma8LuminanceSource, MultiFormatReader, MultiFormatWriter, Reader, Writer,
+    };
+
+    #[test]
+    fn test_records_stages() {
+        let luma = vec![0, 64, 127, 128, 129, 192, 50, 255];
+        let source = Luma8LuminanceSource::new(luma.clone(), 4, 2);
+        let binarizer = WitnessBinarizer::new(FixedThresholdBinarizer::new(source));
+
+        // rows don't produce the matrix, so nothing is recorded yet
+        binarizer.get_black_row(0).unwrap();
+        assert!(binarizer.witness().is_none());
+
+        let matrix = binarizer.get_black_matrix().unwrap().clone();
+        let witness = binarizer.into_witness().unwrap();
+        let luminance = witness.luminance().unwrap();
+        assert_eq!((4, 2), (luminance.width, luminance.height));
+        assert_eq!(luma, luminance.image);
+        let binarization = witness.binarization().unwrap();
+        assert_eq!(matrix, binarization.binarized_image);
+        assert_eq!(Some(128), binarization.threshold);
+        assert!(witness.verify().is_valid());
+
+        let provenance = witness.provenance().unwrap();
+        let binarizer = provenance.binarizer.as_ref().unwrap();
+        assert!(binarizer.contains("FixedThresholdBinarizer<"));
+        assert!(!binarizer.contains("WitnessBinarizer<"));
+        assert_eq!(PipelineStage::Binarization, provenance.timings[0].stage);
+    }
+
+    #[test]
+    fn test_decode_through_wrapper() {
+        let hints = EncodeHints::default().with(EncodeHintValue::Margin("4".to_owned()));
+        let format = BarcodeFormat::QR_CODE;
+        let symbol = MultiFormatWriter
+            .encode_with_hints("witness binarizer", &format, 0, 0, &hints)
+            .unwrap();
+        let (luma, width, height) = scaled_luma(&symbol, 3, 0, 255);
+        let source = Luma8LuminanceSource::new(luma, width, height);
+
+        let mut bitmap = BinaryBitmap::new(WitnessBinarizer::new(HybridBinarizer::new(source)));
+        let result = MultiFormatReader::default().decode(&mut bitmap).unwrap();
+        assert_eq!("witness binarizer", result.getText());
+
+        // the hybrid binarizer thresholds locally
+        let witness = bitmap.get_binarizer().witness().unwrap();
+        let binarization = witness.binarization().unwrap();
+        assert_eq!(None, binarization.threshold);
+        assert_eq!(bitmap.get_black_matrix(), &binarization.binarized_image);
+    }
+
+    #[test]
+    fn test_block_thresholds() {
+        // a gradient with noise, sized so the last blocks overlap the ones before them
+        let (width, height) = (61, 45);
+        let luma = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| ((x * 4 + y * 2 + (x * y * 7919) % 37) % 256) as u8)
+            .collect();
+        let source = Luma8LuminanceSource::new(luma, width, height);
+        let binarizer =
+            WitnessBinarizer::new(HybridBinarizer::new(source).with_block_thresholds(true));
+        let matrix = binarizer.get_black_matrix().unwrap().clone();
+
+        let witness = binarizer.into_witness().unwrap();
+        let luminance = witness.luminance().unwrap();
+        let binarization = witness.binarization().unwrap();
+        let thresholds = binarization.block_thresholds.as_ref().unwrap();
+        assert_eq!((8, 6), (thresholds.blocks_wide, thresholds.blocks_high));
+        for y in 0..height {
+            for x in 0..width {
+                let value = luminance.image[(y * width + x) as usize];
+                assert_eq!(matrix.get(x, y), thresholds.is_black(x, y, value));
+            }
+        }
+        assert!(witness.verify().is_valid());
+
+        let mut tampered = binarization.clone();
+        tampered.binarized_image.flip_coords(30, 20);
+        let witness = witness.with_binarization(tampered);
+        assert!(!witness.verify().is_valid());
+    }
+}
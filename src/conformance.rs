@@ -0,0 +1,366 @@
+/*
+ * Conformance self-tests
+ *
+ * Programmatic encode / decode checks against reference vectors, so that
+ * downstream integrators can verify that their build (target, feature set,
+ * compiler flags) produces spec compliant symbols, e.g. from their own CI.
+ */
+
+use std::{collections::HashSet, fmt};
+
+use crate::{helpers, BarcodeFormat, DecodeHints, EncodeHints, MultiFormatWriter, Writer};
+
+/**
+ * A single reference vector. Every vector is checked for a lossless encode / decode
+ * round trip. Vectors with `expected_modules` additionally pin the exact module
+ * pattern the encoder must produce, taken from the symbology specifications and the
+ * ZXing reference implementation.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReferenceVector {
+    pub name: &'static str,
+    pub format: BarcodeFormat,
+    pub contents: &'static str,
+    /// The error correction level to request, for formats which support one
+    pub error_correction: Option<&'static str>,
+    /// The expected symbol, one string of `0` / `1` per row, at one pixel per module.
+    /// 1D symbols include the writer's default quiet zone, 2D symbols have none.
+    pub expected_modules: Option<&'static [&'static str]>,
+}
+
+impl ReferenceVector {
+    const fn round_trip(name: &'static str, format: BarcodeFormat, contents: &'static str) -> Self {
+        Self {
+            name,
+            format,
+            contents,
+            error_correction: None,
+            expected_modules: None,
+        }
+    }
+
+    const fn exact(
+        name: &'static str,
+        format: BarcodeFormat,
+        contents: &'static str,
+        expected_modules: &'static [&'static str],
+    ) -> Self {
+        Self {
+            name,
+            format,
+            contents,
+            error_correction: None,
+            expected_modules: Some(expected_modules),
+        }
+    }
+}
+
+/// The kind of check a [`ConformanceCaseResult`] reports on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ConformanceCheck {
+    /// The encoder output matches the expected module pattern exactly
+    Encode,
+    /// The encoded symbol decodes back to the original contents
+    RoundTrip,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConformanceCaseResult {
+    pub vector: ReferenceVector,
+    pub check: ConformanceCheck,
+    /// `None` if the check passed, otherwise a description of the failure
+    pub failure: Option<String>,
+}
+
+impl ConformanceCaseResult {
+    pub fn passed(&self) -> bool {
+        self.failure.is_none()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ConformanceReport {
+    pub cases: Vec<ConformanceCaseResult>,
+}
+
+impl ConformanceReport {
+    /// Returns true if every case passed
+    pub fn is_success(&self) -> bool {
+        self.cases.iter().all(ConformanceCaseResult::passed)
+    }
+
+    pub fn failures(&self) -> impl Iterator<Item = &ConformanceCaseResult> {
+        self.cases.iter().filter(|c| !c.passed())
+    }
+
+    /// The formats covered by this report
+    pub fn formats(&self) -> HashSet<BarcodeFormat> {
+        self.cases.iter().map(|c| c.vector.format.clone()).collect()
+    }
+}
+
+impl fmt::Display for ConformanceReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let failed = self.failures().count();
+        writeln!(
+            f,
+            "{} of {} conformance checks passed",
+            self.cases.len() - failed,
+            self.cases.len()
+        )?;
+        for case in self.failures() {
+            writeln!(
+                f,
+                "FAILED {:?} {} ({}): {}",
+                case.check,
+                case.vector.name,
+                case.vector.format,
+                case.failure.as_deref().unwrap_or_default()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+const QR_ABCDEF_H: &[&str] = &[
+    "111111101111001111111",
+    "100000100111001000001",
+    "101110100101101011101",
+    "101110101110101011101",
+    "101110100111001011101",
+    "100000100100001000001",
+    "111111101010101111111",
+    "000000000010100000000",
+    "001011101100110001001",
+    "101110010001010000000",
+    "001100101000101010110",
+    "110101011101010000010",
+    "001101111000101011110",
+    "000000001001110101000",
+    "111111100010101100001",
+    "100000101111010111101",
+    "101110101011010100001",
+    "101110100110111101010",
+    "101110101000101011101",
+    "100000100110110100011",
+    "111111100000000010101",
+];
+
+static REFERENCE_VECTORS: &[ReferenceVector] = &[
+    ReferenceVector::exact(
+        "ean-13",
+        BarcodeFormat::EAN_13,
+        "5901234123457",
+        &["00001010001011010011101100110010011011110100111010101011001101101100100001010111001001110100010010100000"],
+    ),
+    ReferenceVector::exact(
+        "ean-8",
+        BarcodeFormat::EAN_8,
+        "96385074",
+        &["0000101000101101011110111101011011101010100111011100101000100101110010100000"],
+    ),
+    ReferenceVector::exact(
+        "upc-a",
+        BarcodeFormat::UPC_A,
+        "485963095124",
+        &["00001010100011011011101100010001011010111101111010101011100101110100100111011001101101100101110010100000"],
+    ),
+    ReferenceVector::exact(
+        "upc-e",
+        BarcodeFormat::UPC_E,
+        "05096893",
+        &["000010101110010100111000101101011110110111001011101010100000"],
+    ),
+    ReferenceVector::exact(
+        "itf",
+        BarcodeFormat::ITF,
+        "00123456789012",
+        &["0000010101010111000111000101110100010101110001110111010001010001110100011100010101000101011100011101011101000111000101110100010101110001110100000"],
+    ),
+    ReferenceVector {
+        name: "qr alphanumeric",
+        format: BarcodeFormat::QR_CODE,
+        contents: "ABCDEF",
+        error_correction: Some("H"),
+        expected_modules: Some(QR_ABCDEF_H),
+    },
+    ReferenceVector::round_trip("qr byte", BarcodeFormat::QR_CODE, "https://example.com/?q=rxing"),
+    ReferenceVector::round_trip("qr numeric", BarcodeFormat::QR_CODE, "01234567890123456789"),
+    ReferenceVector::round_trip("code 39", BarcodeFormat::CODE_39, "CODE39-TEST"),
+    ReferenceVector::round_trip("code 93", BarcodeFormat::CODE_93, "CODE93 TEST"),
+    ReferenceVector::round_trip("code 128", BarcodeFormat::CODE_128, "Code 128 test 1234"),
+    ReferenceVector::round_trip("codabar", BarcodeFormat::CODABAR, "123456"),
+    ReferenceVector::round_trip("data matrix", BarcodeFormat::DATA_MATRIX, "Data Matrix 0123"),
+    ReferenceVector::round_trip("aztec", BarcodeFormat::AZTEC, "Aztec conformance"),
+    ReferenceVector::round_trip("pdf 417", BarcodeFormat::PDF_417, "PDF417 conformance test"),
+];
+
+/// All built in reference vectors
+pub fn reference_vectors() -> &'static [ReferenceVector] {
+    REFERENCE_VECTORS
+}
+
+/// Runs every reference vector of every format
+pub fn run_all() -> ConformanceReport {
+    run_vectors(REFERENCE_VECTORS)
+}
+
+/// Runs the reference vectors of a single format
+pub fn run_format(format: BarcodeFormat) -> ConformanceReport {
+    let vectors: Vec<_> = REFERENCE_VECTORS
+        .iter()
+        .filter(|v| v.format == format)
+        .cloned()
+        .collect();
+    run_vectors(&vectors)
+}
+
+/// Runs the given vectors, which may include vectors defined by the caller
+pub fn run_vectors(vectors: &[ReferenceVector]) -> ConformanceReport {
+    let mut report = ConformanceReport::default();
+    for vector in vectors {
+        if vector.expected_modules.is_some() {
+            report.cases.push(ConformanceCaseResult {
+                vector: vector.clone(),
+                check: ConformanceCheck::Encode,
+                failure: check_encode(vector).err(),
+            });
+        }
+        report.cases.push(ConformanceCaseResult {
+            vector: vector.clone(),
+            check: ConformanceCheck::RoundTrip,
+            failure: check_round_trip(vector).err(),
+        });
+    }
+    report
+}
+
+fn encode_hints(vector: &ReferenceVector, margin: Option<&str>) -> EncodeHints {
+    EncodeHints {
+        ErrorCorrection: vector.error_correction.map(str::to_owned),
+        Margin: margin.map(str::to_owned),
+        ..Default::default()
+    }
+}
+
+fn is_2d(format: &BarcodeFormat) -> bool {
+    matches!(
+        format,
+        BarcodeFormat::QR_CODE
+            | BarcodeFormat::AZTEC
+            | BarcodeFormat::DATA_MATRIX
+            | BarcodeFormat::PDF_417
+    )
+}
+
+fn check_encode(vector: &ReferenceVector) -> Result<(), String> {
+    let expected = vector.expected_modules.unwrap_or_default();
+    let margin = if is_2d(&vector.format) {
+        Some("0")
+    } else {
+        None
+    };
+    let matrix = MultiFormatWriter
+        .encode_with_hints(
+            vector.contents,
+            &vector.format,
+            0,
+            0,
+            &encode_hints(vector, margin),
+        )
+        .map_err(|e| format!("encoding failed: {e}"))?;
+
+    let rows: Vec<String> = (0..matrix.getHeight())
+        .map(|y| {
+            (0..matrix.getWidth())
+                .map(|x| if matrix.get(x, y) { '1' } else { '0' })
+                .collect()
+        })
+        .collect();
+
+    // 1D writers repeat the same row for the full height
+    let matches = if is_2d(&vector.format) {
+        rows.iter().map(String::as_str).eq(expected.iter().copied())
+    } else {
+        expected.len() == 1 && rows.iter().all(|r| r == expected[0])
+    };
+
+    if matches {
+        Ok(())
+    } else {
+        Err(format!(
+            "unexpected modules, got {}x{} symbol:\n{}",
+            matrix.getWidth(),
+            matrix.getHeight(),
+            rows.join("\n")
+        ))
+    }
+}
+
+fn check_round_trip(vector: &ReferenceVector) -> Result<(), String> {
+    // generous quiet zones, some 1D readers reject the minimal default
+    let hints = encode_hints(vector, (!is_2d(&vector.format)).then_some("20"));
+    let (width, height) = if is_2d(&vector.format) {
+        (300, 300)
+    } else {
+        let natural = MultiFormatWriter
+            .encode_with_hints(vector.contents, &vector.format, 0, 0, &hints)
+            .map_err(|e| format!("encoding failed: {e}"))?;
+        (natural.getWidth() as i32 * 4, 60)
+    };
+
+    let matrix = MultiFormatWriter
+        .encode_with_hints(vector.contents, &vector.format, width, height, &hints)
+        .map_err(|e| format!("encoding failed: {e}"))?;
+
+    let result = helpers::detect_in_luma_with_hints(
+        matrix.to_luma8(),
+        matrix.getWidth(),
+        matrix.getHeight(),
+        Some(vector.format.clone()),
+        &mut DecodeHints::default(),
+    )
+    .map_err(|e| format!("decoding failed: {e}"))?;
+
+    if result.getBarcodeFormat() != &vector.format {
+        return Err(format!("decoded as {}", result.getBarcodeFormat()));
+    }
+    if result.getText() != vector.contents {
+        return Err(format!(
+            "decoded '{}' instead of '{}'",
+            result.getText(),
+            vector.contents
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reference_vectors() {
+        let report = run_all();
+        assert!(report.is_success(), "{report}");
+        assert!(report.formats().contains(&BarcodeFormat::QR_CODE));
+    }
+
+    #[test]
+    fn test_detects_non_conforming_vector() {
+        let broken = ReferenceVector::exact(
+            "broken ean-8",
+            BarcodeFormat::EAN_8,
+            "96385074",
+            &["0000101000101101011110111101011011101010100111011100101000100101110010100001"],
+        );
+        let report = run_vectors(&[broken]);
+        assert!(!report.is_success());
+        assert_eq!(1, report.failures().count());
+        assert_eq!(
+            ConformanceCheck::Encode,
+            report.failures().next().unwrap().check
+        );
+    }
+}
@@ -0,0 +1,203 @@
+/*
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::{borrow::Cow, sync::Arc};
+
+use crate::common::Result;
+use crate::{Exceptions, LuminanceSource};
+
+/**
+ * A rectangle of another luminance source, such as the viewfinder area of a camera frame, read
+ * through to the source without copying its pixels. Works for any source, whether or not it
+ * supports cropping itself, and cropping it again only narrows the rectangle.
+ */
+#[derive(Debug)]
+pub struct CroppedLuminanceSource<S> {
+    source: Arc<S>,
+    left: usize,
+    top: usize,
+    width: usize,
+    height: usize,
+    invert: bool,
+}
+
+impl<S> Clone for CroppedLuminanceSource<S> {
+    fn clone(&self) -> Self {
+        Self {
+            source: self.source.clone(),
+            ..*self
+        }
+    }
+}
+
+impl<S: LuminanceSource> CroppedLuminanceSource<S> {
+    /**
+     * Restricts `source` to the `width` by `height` rectangle at (`left`, `top`).
+     *
+     * # Errors
+     * `IllegalArgumentException` if the rectangle does not fit within `source`
+     */
+    pub fn new(source: S, left: usize, top: usize, width: usize, height: usize) -> Result<Self> {
+        Self::from_arc(Arc::new(source), left, top, width, height)
+    }
+
+    /// As [`CroppedLuminanceSource::new`], sharing `source` to crop several rectangles out of it
+    pub fn from_arc(
+        source: Arc<S>,
+        left: usize,
+        top: usize,
+        width: usize,
+        height: usize,
+    ) -> Result<Self> {
+        if left + width > source.get_width() || top + height > source.get_height() {
+            return Err(Exceptions::illegal_argument_with(
+                "Crop rectangle does not fit within image data.",
+            ));
+        }
+        Ok(Self {
+            source,
+            left,
+            top,
+            width,
+            height,
+            invert: false,
+        })
+    }
+
+    pub fn get_source(&self) -> &S {
+        &self.source
+    }
+
+    /// The left and top of the rectangle within the source
+    pub fn get_offset(&self) -> (usize, usize) {
+        (self.left, self.top)
+    }
+
+    fn invert_if_should(&self, luminance: u8) -> u8 {
+        if self.invert {
+            255 - luminance
+        } else {
+            luminance
+        }
+    }
+}
+
+impl<S: LuminanceSource> LuminanceSource for CroppedLuminanceSource<S> {
+    const SUPPORTS_CROP: bool = true;
+
+    fn get_row(&self, y: usize) -> Option<Cow<'_, [u8]>> {
+        if y >= self.height {
+            return None;
+        }
+        let row = match self.source.get_row(self.top + y)? {
+            Cow::Borrowed(row) => Cow::Borrowed(&row[self.left..self.left + self.width]),
+            Cow::Owned(mut row) => {
+                row.truncate(self.left + self.width);
+                row.drain(..self.left);
+                Cow::Owned(row)
+            }
+        };
+        if self.invert {
+            Some(Cow::Owned(self.invert_block_of_bytes(row.into_owned())))
+        } else {
+            Some(row)
+        }
+    }
+
+    fn get_column(&self, x: usize) -> Vec<u8> {
+        (0..self.height)
+            .map(|y| self.get_luma8_point(x, y))
+            .collect()
+    }
+
+    fn get_matrix(&self) -> Vec<u8> {
+        let mut matrix = Vec::with_capacity(self.width * self.height);
+        for y in 0..self.height {
+            if let Some(row) = self.get_row(y) {
+                matrix.extend_from_slice(&row);
+            }
+        }
+        matrix
+    }
+
+    fn get_width(&self) -> usize {
+        self.width
+    }
+
+    fn get_height(&self) -> usize {
+        self.height
+    }
+
+    fn crop(&self, left: usize, top: usize, width: usize, height: usize) -> Result<Self> {
+        if left + width > self.width || top + height > self.height {
+            return Err(Exceptions::illegal_argument_with(
+                "Crop rectangle does not fit within image data.",
+            ));
+        }
+        Ok(Self {
+            source: self.source.clone(),
+            left: self.left + left,
+            top: self.top + top,
+            width,
+            height,
+            invert: self.invert,
+        })
+    }
+
+    fn invert(&mut self) {
+        self.invert = !self.invert;
+    }
+
+    fn get_luma8_point(&self, x: usize, y: usize) -> u8 {
+        // rows are the one thing every source implements
+        let luminance = self
+            .source
+            .get_row(self.top + y)
+            .map_or(0, |row| row[self.left + x]);
+        self.invert_if_should(luminance)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{Luma8LuminanceSource, LuminanceSource};
+
+    use super::CroppedLuminanceSource;
+
+    #[test]
+    fn test_crop() {
+        let source = Arc::new(Luma8LuminanceSource::new((0..20).collect(), 5, 4));
+        let cropped = CroppedLuminanceSource::from_arc(source.clone(), 1, 1, 3, 2).expect("crop");
+        assert_eq!(&[6, 7, 8], &cropped.get_row(0).unwrap()[..]);
+        assert!(cropped.get_row(2).is_none());
+        assert_eq!(vec![8, 13], cropped.get_column(2));
+        assert_eq!(vec![6, 7, 8, 11, 12, 13], cropped.get_matrix());
+        assert_eq!(
+            source.crop(1, 1, 3, 2).unwrap().get_matrix(),
+            cropped.get_matrix()
+        );
+
+        let mut again = cropped.crop(1, 1, 2, 1).expect("crop");
+        assert_eq!((2, 2), again.get_offset());
+        assert_eq!(vec![12, 13], again.get_matrix());
+        again.invert();
+        assert_eq!(&[243, 242], &again.get_row(0).unwrap()[..]);
+        assert_eq!(vec![242], again.get_column(1));
+
+        assert!(cropped.crop(1, 0, 3, 1).is_err());
+        assert!(CroppedLuminanceSource::from_arc(source, 3, 0, 3, 1).is_err());
+    }
+}
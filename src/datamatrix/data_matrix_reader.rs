@@ -15,14 +15,19 @@
  */
 
 use crate::{
-    common::{BitMatrix, DecoderRXingResult, DetectorRXingResult, Result},
-    point, BarcodeFormat, Binarizer, DecodeHints, Exceptions, ImmutableReader, Point, RXingResult,
-    RXingResultMetadataType, RXingResultMetadataValue, Reader,
+    common::{
+        sample_cylinder, BitMatrix, DecoderRXingResult, DetectorRXingResult, Quadrilateral, Result,
+        SamplingGrid,
+    },
+    decode_confidence, point, record_witness, symbol_pose, BarcodeFormat, Binarizer, DecodeHints,
+    DecodeWarning, Exceptions, ImmutableReader, Point, RXingResult, RXingResultMetadataType,
+    RXingResultMetadataValue, Reader, WitnessRecording,
 };
 
 use super::{
     decoder::Decoder,
     detector::{zxing_cpp_detector, Detector},
+    MacroEnvelope,
 };
 
 use once_cell::sync::Lazy;
@@ -70,7 +75,9 @@ impl Reader for DataMatrixReader {
         image: &mut crate::BinaryBitmap<B>,
         hints: &DecodeHints,
     ) -> Result<crate::RXingResult> {
-        self.internal_decode_with_hints(image, hints)
+        let mut recording = WitnessRecording::new(hints);
+        self.internal_decode_with_hints(image, hints, &mut recording)
+            .map(|result| record_witness(hints, image, result, &recording))
     }
 }
 
@@ -80,7 +87,9 @@ impl ImmutableReader for DataMatrixReader {
         image: &mut crate::BinaryBitmap<B>,
         hints: &DecodeHints,
     ) -> Result<RXingResult> {
-        self.internal_decode_with_hints(image, hints)
+        let mut recording = WitnessRecording::new(hints);
+        self.internal_decode_with_hints(image, hints, &mut recording)
+            .map(|result| record_witness(hints, image, result, &recording))
     }
 }
 
@@ -90,8 +99,10 @@ impl DataMatrixReader {
      * which contains only an unrotated, unskewed, image of a code, with some white border
      * around it. This is a specialized method that works exceptionally fast in this special
      * case.
+     *
+     * @return the bits and the axis aligned grid they were sampled with
      */
-    fn extractPureBits(&self, image: &BitMatrix) -> Result<BitMatrix> {
+    fn extractPureBits(&self, image: &BitMatrix) -> Result<(BitMatrix, SamplingGrid)> {
         let Some(leftTopBlack) = image.getTopLeftOnBit() else {
             return Err(Exceptions::NOT_FOUND);
         };
@@ -135,7 +146,14 @@ impl DataMatrixReader {
                 }
             }
         }
-        Ok(bits)
+        let grid = SamplingGrid::axis_aligned(
+            matrixWidth,
+            matrixHeight,
+            leftTopBlack.x,
+            leftTopBlack.y,
+            moduleSize as f32,
+        )?;
+        Ok((bits, grid))
     }
 
     fn moduleSize(leftTopBlack: Point, image: &BitMatrix) -> Result<u32> {
@@ -157,30 +175,72 @@ impl DataMatrixReader {
         Ok(moduleSize)
     }
 
+    /// Samples the symbol on `grid` bent around the cylinder that fits its finder and timing
+    /// patterns best, which lie along its edges
+    fn sample_curved(image: &BitMatrix, grid: &SamplingGrid) -> Option<BitMatrix> {
+        let (width, height) = (grid.width(), grid.height());
+        let mut known = Vec::new();
+        for x in 0..width {
+            known.push((x, height - 1, true));
+            known.push((x, 0, x % 2 == 0));
+        }
+        for y in 0..height {
+            known.push((0, y, true));
+            known.push((width - 1, y, (height - 1 - y) % 2 == 0));
+        }
+        sample_cylinder(
+            image,
+            grid,
+            Quadrilateral::rectangle(width as i32, height as i32, None),
+            &known,
+        )
+    }
+
     fn internal_decode_with_hints<B: Binarizer>(
         &self,
         image: &mut crate::BinaryBitmap<B>,
         hints: &DecodeHints,
+        recording: &mut WitnessRecording,
     ) -> Result<RXingResult> {
         let try_harder = hints.TryHarder.unwrap_or(false);
         let decoderRXingResult;
         let mut points = Vec::new();
+        let mut samplingGrid = None;
+        recording.binarization(image);
         if matches!(hints.PureBarcode, Some(true)) {
-            let bits = self.extractPureBits(image.get_black_matrix())?;
+            let (bits, grid) = self.extractPureBits(image.get_black_matrix())?;
             decoderRXingResult = DECODER.decode(&bits)?;
             points.clear();
+            samplingGrid = Some(grid);
         } else {
             //Result<DatamatrixDetectorResult, Exceptions>
             decoderRXingResult = if let Ok(fnd) = || -> Result<DecoderRXingResult> {
                 let detectorRXingResult =
                     zxing_cpp_detector::detect(image.get_black_matrix(), try_harder, true)?;
                 for symbol in detectorRXingResult {
-                    let decoded = DECODER.decode(symbol.getBits());
-                    if decoded.is_ok() {
-                        points = symbol.getPoints().to_vec();
-                        return decoded;
-                    } else {
+                    let Ok(symbol) = symbol.sample_as_hinted(image.get_black_matrix(), hints)
+                    else {
                         continue;
+                    };
+                    recording.detection(
+                        BarcodeFormat::DATA_MATRIX,
+                        symbol.getPoints(),
+                        symbol.getSamplingGrid(),
+                    );
+                    if let Ok(decoded) = DECODER.decode(symbol.getBits()) {
+                        points = symbol.getPoints().to_vec();
+                        samplingGrid = symbol.getSamplingGrid().cloned();
+                        return Ok(decoded);
+                    }
+                    // a symbol on a bottle or cable may read with its grid bent around it
+                    if let Some(decoded) = symbol
+                        .getSamplingGrid()
+                        .filter(|_| matches!(hints.TryCurved, Some(true)))
+                        .and_then(|grid| Self::sample_curved(image.get_black_matrix(), grid))
+                        .and_then(|bits| DECODER.decode(&bits).ok())
+                    {
+                        points = symbol.getPoints().to_vec();
+                        return Ok(decoded);
                     }
                 }
                 Err(Exceptions::NOT_FOUND)
@@ -188,14 +248,23 @@ impl DataMatrixReader {
                 fnd
             } else if try_harder {
                 if let Ok(fnd) = || -> Result<DecoderRXingResult> {
-                    let detectorRXingResult = Detector::new(image.get_black_matrix())?.detect()?;
+                    let detectorRXingResult = Detector::new(image.get_black_matrix())?
+                        .detect()?
+                        .sample_as_hinted(image.get_black_matrix(), hints)?;
+                    recording.detection(
+                        BarcodeFormat::DATA_MATRIX,
+                        detectorRXingResult.getPoints(),
+                        detectorRXingResult.getSamplingGrid(),
+                    );
                     let decoded = DECODER.decode(detectorRXingResult.getBits())?;
                     points = detectorRXingResult.getPoints().to_vec();
+                    samplingGrid = detectorRXingResult.getSamplingGrid().cloned();
                     Ok(decoded)
                 }() {
                     fnd
                 } else {
-                    let bits = self.extractPureBits(image.get_black_matrix())?;
+                    let (bits, grid) = self.extractPureBits(image.get_black_matrix())?;
+                    samplingGrid = Some(grid);
                     DECODER.decode(&bits)?
                 }
             } else {
@@ -204,6 +273,7 @@ impl DataMatrixReader {
 
             // decoderRXingResult = DECODER.decode(detectorRXingResult.getBits())?;
         }
+        recording.codewords(decoderRXingResult.getCodewords());
 
         let mut result = RXingResult::new(
             decoderRXingResult.getText(),
@@ -211,6 +281,15 @@ impl DataMatrixReader {
             points.clone(),
             BarcodeFormat::DATA_MATRIX,
         );
+        if let Some(content) = decoderRXingResult.getContent() {
+            result.setContent(content.clone());
+        }
+        if let Some(samplingGrid) = samplingGrid {
+            result.putMetadata(
+                RXingResultMetadataType::SAMPLING_GRID,
+                RXingResultMetadataValue::SamplingGrid(samplingGrid),
+            );
+        }
         let byteSegments = decoderRXingResult.getByteSegments();
         if !byteSegments.is_empty() {
             result.putMetadata(
@@ -225,6 +304,27 @@ impl DataMatrixReader {
                 RXingResultMetadataValue::ErrorCorrectionLevel(ecLevel.to_string()),
             );
         }
+        result.putErrorCorrectionMetadata(
+            decoderRXingResult.getErrorsCorrected(),
+            decoderRXingResult.getErasures(),
+        );
+        if let Some(codewords) = decoderRXingResult.getCodewords() {
+            if codewords.uses_heavy_correction() {
+                result.addWarning(DecodeWarning::HeavyErrorCorrection);
+            }
+            result.putMetadata(
+                RXingResultMetadataType::CODEWORDS,
+                RXingResultMetadataValue::Codewords(codewords.clone()),
+            );
+        }
+        if let Some(version) = decoderRXingResult.getSymbolVersion() {
+            result.putMetadata(
+                RXingResultMetadataType::SYMBOL_VERSION,
+                RXingResultMetadataValue::SymbolVersion(version),
+            );
+        }
+        decode_confidence::put_confidence(&mut result);
+        symbol_pose::put_pose(&mut result);
         let other_meta = decoderRXingResult.getOther();
         if let Some(other) = other_meta {
             if let Some(dcr) = other.downcast_ref::<String>() {
@@ -232,8 +332,19 @@ impl DataMatrixReader {
                     RXingResultMetadataType::OTHER,
                     RXingResultMetadataValue::OTHER(dcr.to_owned()),
                 );
+            } else if let Some(envelope) = other.downcast_ref::<MacroEnvelope>() {
+                result.putMetadata(
+                    RXingResultMetadataType::MACRO_ENVELOPE,
+                    RXingResultMetadataValue::MacroEnvelope(envelope.clone()),
+                );
             }
         }
+        if decoderRXingResult.getReaderProgramming() {
+            result.putMetadata(
+                RXingResultMetadataType::READER_PROGRAMMING,
+                RXingResultMetadataValue::ReaderProgramming(true),
+            );
+        }
         let contentType = decoderRXingResult.getContentType();
         if !contentType.is_empty() {
             result.putMetadata(
@@ -244,6 +355,7 @@ impl DataMatrixReader {
 
         let mirrored = decoderRXingResult.getIsMirrored();
         if mirrored {
+            result.addWarning(DecodeWarning::AmbiguousOrientation);
             result.putMetadata(
                 RXingResultMetadataType::IS_MIRRORED,
                 RXingResultMetadataValue::IsMirrored(mirrored),
@@ -258,6 +370,79 @@ impl DataMatrixReader {
             )),
         );
 
+        recording.decoded_bits(&mut result);
         Ok(result)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        common::HybridBinarizer,
+        datamatrix::{encoder::SymbolShapeHint, DataMatrixReader},
+        BarcodeFormat, BinaryBitmap, DecodeHints, EncodeHints, Luma8LuminanceSource,
+        MultiFormatWriter, RXingResultMetadataType, Reader, Writer,
+    };
+
+    #[test]
+    fn test_curved() {
+        let contents = "wrapped around a cable";
+        let matrix = MultiFormatWriter
+            .encode_with_hints(
+                contents,
+                &BarcodeFormat::DATA_MATRIX,
+                0,
+                0,
+                &EncodeHints {
+                    DataMatrixShape: Some(SymbolShapeHint::FORCE_SQUARE),
+                    ..Default::default()
+                },
+            )
+            .expect("encode");
+        let dimension = matrix.getWidth();
+        // the symbol wrapped around a horizontal cylinder, so its columns bow
+        let (wrap, bow) = (0.9f32, 0.05f32);
+        let (module, quiet_zone) = (8, 40);
+        let size = dimension * module + 2 * quiet_zone;
+        let mut luma = Vec::new();
+        for y in 0..size {
+            for x in 0..size {
+                let normalized = |p: u32| {
+                    2.0 * (p as f32 - quiet_zone as f32) / (dimension * module) as f32 - 1.0
+                };
+                let (s, t) = (normalized(y), normalized(x));
+                let s = (s * wrap.sin()).asin() / wrap;
+                let t = t / (1.0 + bow * (1.0 - s * s));
+                let black = (-1.0..1.0).contains(&s)
+                    && (-1.0..1.0).contains(&t)
+                    && matrix.get(
+                        ((t + 1.0) / 2.0 * dimension as f32) as u32,
+                        ((s + 1.0) / 2.0 * dimension as f32) as u32,
+                    );
+                luma.push(if black { 0 } else { 255 });
+            }
+        }
+        let image = || {
+            BinaryBitmap::new(HybridBinarizer::new(Luma8LuminanceSource::new(
+                luma.clone(),
+                size,
+                size,
+            )))
+        };
+
+        assert!(DataMatrixReader
+            .decode_with_hints(&mut image(), &DecodeHints::default())
+            .is_err());
+        let hints = DecodeHints {
+            TryCurved: Some(true),
+            ..Default::default()
+        };
+        let result = DataMatrixReader
+            .decode_with_hints(&mut image(), &hints)
+            .expect("decode");
+        assert_eq!(contents, result.getText());
+        assert!(!result
+            .getRXingResultMetadata()
+            .contains_key(&RXingResultMetadataType::SAMPLING_GRID));
+    }
+}
@@ -75,6 +75,41 @@ impl Writer for DataMatrixWriter {
             )));
         }
 
+        let (encoded, symbolInfo) = Self::encode_data(contents, hints)?;
+
+        //2. step: ECC generation
+        let codewords = error_correction::encodeECC200(&encoded, symbolInfo)?;
+
+        //3. step: Module placement in Matrix
+        let mut placement = DefaultPlacement::new(
+            codewords,
+            symbolInfo.getSymbolDataWidth()? as usize,
+            symbolInfo.getSymbolDataHeight()? as usize,
+        );
+        placement.place()?;
+
+        let margins = if let Some(margin) = &hints.Margin {
+            margin
+                .parse::<u32>()
+                .map_err(|e| Exceptions::parse_with(format!("could not parse {margin}: {e}")))?
+        } else {
+            MARGINS_SIZE
+        };
+
+        //4. step: low-level encoding
+        Self::encodeLowLevel(&placement, symbolInfo, width as u32, height as u32, margins)
+    }
+}
+
+impl DataMatrixWriter {
+    /**
+     * Runs the data encodation step and picks the symbol size, honoring the same hints
+     * as [`Writer::encode_with_hints`].
+     */
+    pub(crate) fn encode_data(
+        contents: &str,
+        hints: &EncodeHints,
+    ) -> Result<(String, &'static SymbolInfo)> {
         // Try to get force shape & min / max size
         let shape = &hints.DataMatrixShape.unwrap_or(SymbolShapeHint::FORCE_NONE);
         let minSize = hints.MinSize;
@@ -124,31 +159,9 @@ impl Writer for DataMatrixWriter {
             return Err(Exceptions::not_found_with("symbol info is bad"));
         };
 
-        //2. step: ECC generation
-        let codewords = error_correction::encodeECC200(&encoded, symbolInfo)?;
-
-        //3. step: Module placement in Matrix
-        let mut placement = DefaultPlacement::new(
-            codewords,
-            symbolInfo.getSymbolDataWidth()? as usize,
-            symbolInfo.getSymbolDataHeight()? as usize,
-        );
-        placement.place()?;
-
-        let margins = if let Some(margin) = &hints.Margin {
-            margin
-                .parse::<u32>()
-                .map_err(|e| Exceptions::parse_with(format!("could not parse {margin}: {e}")))?
-        } else {
-            MARGINS_SIZE
-        };
-
-        //4. step: low-level encoding
-        Self::encodeLowLevel(&placement, symbolInfo, width as u32, height as u32, margins)
+        Ok((encoded, symbolInfo))
     }
-}
 
-impl DataMatrixWriter {
     /**
      * Encode the given symbol info to a bit matrix.
      *
@@ -16,8 +16,9 @@
 
 use crate::common::{
     reedsolomon::{get_predefined_genericgf, PredefinedGenericGF, ReedSolomonDecoder},
-    BitMatrix, DecoderRXingResult, Result,
+    BitMatrix, CodewordBlock, CodewordWitness, DecoderRXingResult, Result,
 };
+use crate::SymbolVersion;
 
 use super::{decoded_bit_stream_parser, BitMatrixParser, DataBlock};
 
@@ -117,65 +118,59 @@ impl Decoder {
             .fold(0, |acc, db| acc + db.getNumDataCodewords());
 
         let mut resultBytes = vec![0u8; totalBytes as usize];
+        let mut codewordWitness =
+            CodewordWitness::new(8, codewords.iter().map(|c| *c as i32).collect());
 
         let dataBlocksCount = dataBlocks.len();
         // Error-correct and copy data blocks together into a stream of bytes
         for j in 0..dataBlocksCount {
             // for (int j = 0; j < dataBlocksCount; j++) {
             let dataBlock = &dataBlocks[j];
-            let mut codewordBytes = dataBlock.getCodewords().to_vec();
             let numDataCodewords = dataBlock.getNumDataCodewords() as usize;
-            let errors_corrected = self.correctErrors(&mut codewordBytes, numDataCodewords as u32);
-            if errors_corrected.is_err() && !fix259 {
-                return self.perform_decode(bits, true, is_flipped);
-            } else if errors_corrected.is_err() {
-                return Err(errors_corrected.err().unwrap());
-            }
-            for i in 0..numDataCodewords {
+            let block = self.correctErrors(dataBlock.getCodewords(), numDataCodewords as u32);
+            let block = match block {
+                Err(_) if !fix259 => return self.perform_decode(bits, true, is_flipped),
+                block => block?,
+            };
+            for (i, codeword) in block.data_codewords().iter().enumerate() {
                 // for (int i = 0; i < numDataCodewords; i++) {
                 // De-interlace data blocks.
-                resultBytes[i * dataBlocksCount + j] = codewordBytes[i];
+                resultBytes[i * dataBlocksCount + j] = *codeword as u8;
             }
+            codewordWitness.blocks.push(block);
         }
+        codewordWitness.corrected_codewords = resultBytes.iter().map(|b| *b as i32).collect();
 
         // Decode the contents of that stream of bytes
-        decoded_bit_stream_parser::decode(&resultBytes, is_flipped)
+        let mut result = decoded_bit_stream_parser::decode(&resultBytes, is_flipped)?;
+        result.setErrorsCorrected(codewordWitness.errors_corrected());
+        result.setCodewords(Some(codewordWitness));
+        result.setSymbolVersion(Some(SymbolVersion::DataMatrix {
+            rows: version.getSymbolSizeRows(),
+            columns: version.getSymbolSizeColumns(),
+            data_region_rows: version.getDataRegionSizeRows(),
+            data_region_columns: version.getDataRegionSizeColumns(),
+        }));
+        Ok(result)
     }
 
     /**
      * <p>Given data and error-correction codewords received, possibly corrupted by errors, attempts to
-     * correct the errors in-place using Reed-Solomon error correction.</p>
+     * correct the errors using Reed-Solomon error correction.</p>
      *
      * @param codewordBytes data and error correction codewords
      * @param numDataCodewords number of codewords that are data bytes
+     * @return the block with its corrected codewords
      * @throws ChecksumException if error correction fails
      */
-    fn correctErrors(&self, codewordBytes: &mut [u8], numDataCodewords: u32) -> Result<()> {
-        let _numCodewords = codewordBytes.len();
-        // First read into an array of ints
-        // let codewordsInts = vec![0;numCodewords];
-        // for i in 0..numCodewords {
-        // // for (int i = 0; i < numCodewords; i++) {
-        //   codewordsInts[i] = codewordBytes[i];
-        // }
-        let mut codewordsInts: Vec<i32> = codewordBytes.iter().map(|x| *x as i32).collect();
+    fn correctErrors(&self, codewordBytes: &[u8], numDataCodewords: u32) -> Result<CodewordBlock> {
+        let codewordsInts: Vec<i32> = codewordBytes.iter().map(|x| *x as i32).collect();
 
         //try {
-        self.0.decode(
-            &mut codewordsInts,
-            codewordBytes.len() as i32 - numDataCodewords as i32,
-        )?;
+        CodewordBlock::correct(&self.0, codewordsInts, numDataCodewords as usize)
         //} catch (ReedSolomonException ignored) {
         //throw ChecksumException.getChecksumInstance();
         //}
-        // Copy back into array of bytes -- only need to worry about the bytes that were data
-        // We don't care about errors in the error-correction codewords
-        for i in 0..numDataCodewords as usize {
-            // for (int i = 0; i < numDataCodewords; i++) {
-            codewordBytes[i] = codewordsInts[i] as u8;
-        }
-        // codewordsInts.into_iter().take(numDataCodewords as usize).map(|x| x as u8).collect::<Vec<u8>>()
-        Ok(())
     }
 }
 
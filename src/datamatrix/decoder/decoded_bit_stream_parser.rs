@@ -14,9 +14,12 @@
  * limitations under the License.
  */
 
+use std::sync::Arc;
+
 use crate::{
     common::{BitSource, CharacterSet, DecoderRXingResult, ECIStringBuilder, Eci, Result},
-    Exceptions,
+    datamatrix::MacroEnvelope,
+    Content, Exceptions,
 };
 
 /**
@@ -184,17 +187,40 @@ pub fn decode(bytes: &[u8], is_flipped: bool) -> Result<DecoderRXingResult> {
         symbologyModifier = 1;
     }
 
+    // Reader Programming must be the first codeword, a macro the first after any structured
+    // append header
+    let firstCodeword = if bytes.first() == Some(&233) {
+        bytes.get(4)
+    } else {
+        bytes.first()
+    };
+    let content = Content::from(&result);
+    let text = result.build_result().to_string();
+    let macroEnvelope = if matches!(firstCodeword, Some(236 | 237)) {
+        MacroEnvelope::parse(&text)
+    } else {
+        None
+    };
+
     let mut result = DecoderRXingResult::with_symbology(
         bytes.to_vec(),
-        result.build_result().to_string(),
+        text,
         byteSegments,
         String::new(),
         symbologyModifier,
     );
+    result.setContent(Some(content));
     if is_gs1 {
         result.setContentType(String::from("GS1"));
     }
 
+    if let Some(macroEnvelope) = macroEnvelope {
+        result.setContentType(String::from("ISO15434"));
+        result.setOther(Some(Arc::new(macroEnvelope)));
+    }
+
+    result.setReaderProgramming(bytes.first() == Some(&234));
+
     if !known_eci {
         result.setContentType(String::from("UnknownECI"));
     }
@@ -285,8 +311,7 @@ fn decodeAsciiSegment(
             }
             234 =>
                 // Reader Programming
-            // Ignore these symbols for now
-            //throw ReaderException.getInstance();
+            // Flagged in the result if it is the first codeword, ignored otherwise
                 {}
             235 =>
             // Upper Shift (shift to Extended ASCII)
@@ -812,7 +837,7 @@ fn unrandomize255State(randomizedBase256Codeword: u32, base256CodewordPosition:
 
 #[cfg(test)]
 mod tests {
-    use crate::datamatrix::decoder::decoded_bit_stream_parser;
+    use crate::datamatrix::{decoder::decoded_bit_stream_parser, MacroEnvelope};
 
     #[test]
     fn testAsciiStandardDecode() {
@@ -845,6 +870,38 @@ mod tests {
         assert_eq!("00019899", decodedString);
     }
 
+    #[test]
+    fn testMacroDecode() {
+        // 06 Macro, then "P1"
+        let bytes = [237, (b'P' + 1), (b'1' + 1)];
+        let result = decoded_bit_stream_parser::decode(&bytes, false).expect("decode");
+        assert_eq!("[)>\u{1E}06\u{1D}P1\u{1E}\u{4}", result.getText());
+        assert_eq!("ISO15434", result.getContentType());
+        let other = result.getOther().expect("envelope");
+        let envelope = other.downcast_ref::<MacroEnvelope>().expect("envelope");
+        assert_eq!("06", envelope.getFormat());
+        assert_eq!(Some("1"), envelope.getDataElement("P"));
+        assert!(!result.getReaderProgramming());
+
+        // a macro that is not the first codeword is expanded, but is no envelope
+        let bytes = [(b'A' + 1), 236, (b'1' + 1)];
+        let result = decoded_bit_stream_parser::decode(&bytes, false).expect("decode");
+        assert_eq!("A[)>\u{1E}05\u{1D}1\u{1E}\u{4}", result.getText());
+        assert!(result.getOther().is_none());
+    }
+
+    #[test]
+    fn testReaderProgrammingDecode() {
+        let bytes = [234, (b'A' + 1), (b'B' + 1)];
+        let result = decoded_bit_stream_parser::decode(&bytes, false).expect("decode");
+        assert_eq!("AB", result.getText());
+        assert!(result.getReaderProgramming());
+
+        let bytes = [(b'A' + 1), 234, (b'B' + 1)];
+        let result = decoded_bit_stream_parser::decode(&bytes, false).expect("decode");
+        assert!(!result.getReaderProgramming());
+    }
+
     // TODO(bbrown): Add test cases for each encoding type
     // TODO(bbrown): Add test cases for switching encoding types
 }
@@ -16,8 +16,8 @@
 
 use crate::{
     common::{
-        detector::WhiteRectangleDetector, BitMatrix, DefaultGridSampler, GridSampler,
-        Quadrilateral, Result,
+        detector::WhiteRectangleDetector, BitMatrix, DefaultGridSampler, DetectedPattern,
+        GridSampler, PatternKind, PerspectiveTransform, Quadrilateral, Result, SamplingGrid,
     },
     point, Exceptions, Point,
 };
@@ -84,7 +84,7 @@ impl<'a> Detector<'_> {
             dimensionRight = dimensionTop.max(dimensionRight);
         }
 
-        let bits = Self::sampleGrid(
+        let (bits, transform) = Self::sampleGrid(
             self.image,
             topLeft,
             bottomLeft,
@@ -94,10 +94,18 @@ impl<'a> Detector<'_> {
             dimensionRight,
         )?;
 
-        Ok(DatamatrixDetectorResult::new(
-            bits,
-            vec![topLeft, bottomLeft, bottomRight, topRight],
-        ))
+        let patterns = [topLeft, bottomLeft, bottomRight, topRight]
+            .into_iter()
+            .map(|p| DetectedPattern::new(PatternKind::Corner, p))
+            .collect();
+
+        Ok(
+            DatamatrixDetectorResult::new(bits, vec![topLeft, bottomLeft, bottomRight, topRight])
+                .with_sampling_grid(
+                    SamplingGrid::with_transform(dimensionTop, dimensionRight, transform)
+                        .with_patterns(patterns),
+                ),
+        )
     }
 
     #[inline]
@@ -329,7 +337,7 @@ impl<'a> Detector<'_> {
         topRight: Point,
         dimensionX: u32,
         dimensionY: u32,
-    ) -> Result<BitMatrix> {
+    ) -> Result<(BitMatrix, PerspectiveTransform)> {
         let sampler = DefaultGridSampler;
 
         let dst = Quadrilateral::new(
@@ -341,7 +349,10 @@ impl<'a> Detector<'_> {
         let src = Quadrilateral::new(topRight, topLeft, bottomRight, bottomLeft);
 
         let (res, _) = sampler.sample_grid_detailed(image, dimensionX, dimensionY, dst, src)?;
-        Ok(res)
+        Ok((
+            res,
+            PerspectiveTransform::quadrilateralToQuadrilateral(dst, src)?,
+        ))
     }
 
     /**
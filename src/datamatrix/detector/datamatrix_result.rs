@@ -1,13 +1,32 @@
 use crate::{
-    common::{BitMatrix, DetectorRXingResult},
-    Point,
+    common::{sample_as_hinted, BitMatrix, DetectorRXingResult, Result, SamplingGrid},
+    DecodeHints, Point,
 };
 
-pub struct DatamatrixDetectorResult(BitMatrix, Vec<Point>);
+pub struct DatamatrixDetectorResult(BitMatrix, Vec<Point>, Option<SamplingGrid>);
 
 impl DatamatrixDetectorResult {
     pub fn new(bits: BitMatrix, points: Vec<Point>) -> Self {
-        Self(bits, points)
+        Self(bits, points, None)
+    }
+
+    pub fn with_sampling_grid(mut self, sampling_grid: SamplingGrid) -> Self {
+        self.2 = Some(sampling_grid);
+        self
+    }
+
+    /// Samples the bits again through the sampling grid if the `FixedPointSampling` or
+    /// `GridSampling` hint asks for it, see `common::sample_as_hinted`
+    pub(crate) fn sample_as_hinted(self, image: &BitMatrix, hints: &DecodeHints) -> Result<Self> {
+        let Some(bits) = self
+            .2
+            .as_ref()
+            .and_then(|grid| sample_as_hinted(image, grid, hints))
+            .transpose()?
+        else {
+            return Ok(self);
+        };
+        Ok(Self(bits, self.1, self.2))
     }
 }
 
@@ -19,4 +38,8 @@ impl DetectorRXingResult for DatamatrixDetectorResult {
     fn getPoints(&self) -> &[Point] {
         &self.1
     }
+
+    fn getSamplingGrid(&self) -> Option<&SamplingGrid> {
+        self.2.as_ref()
+    }
 }
@@ -4,6 +4,11 @@ macro_rules! CHECK {
             continue;
         }
     };
+    ($A:expr, $label:lifetime) => {
+        if (!($A)) {
+            break $label None;
+        }
+    };
 }
 
 /*
@@ -15,12 +20,15 @@ use std::sync::{Arc, RwLock};
 
 use crate::{
     common::{
-        cpp_essentials::RegressionLineTrait, BitMatrix, DefaultGridSampler, GridSampler,
-        Quadrilateral, Result,
+        cpp_essentials::RegressionLineTrait, BitMatrix, DefaultGridSampler, DetectedPattern,
+        GridSampler, PatternKind, PerspectiveTransform, Quadrilateral, Result, SamplingGrid,
     },
-    datamatrix::detector::{
-        zxing_cpp_detector::{util::intersect, BitMatrixCursorTrait},
-        DatamatrixDetectorResult,
+    datamatrix::{
+        decoder::Version,
+        detector::{
+            zxing_cpp_detector::{util::intersect, BitMatrixCursorTrait},
+            DatamatrixDetectorResult,
+        },
     },
     point,
     qrcode::encoder::ByteMatrix,
@@ -108,111 +116,134 @@ fn Scan(
         let lenB = Point::distance(bl, br) - 1.0;
         CHECK!(lenL >= 8.0 && lenB >= 10.0 && lenB >= lenL / 4.0 && lenB <= lenL * 18.0);
 
-        let mut maxStepSize: i32 = (lenB / 5.0 + 1.0) as i32; // datamatrix bottom dim is at least 10
-
-        // at this point we found a plausible L-shape and are now looking for the b/w pattern at the top and right:
-        // follow top row right 'half way' (4 gaps), see traceGaps break condition with 'invalid' line
-        tlTracer.setDirection(right);
-        CHECK!(tlTracer.traceGaps(
-            tlTracer.right(),
-            lineT,
-            maxStepSize,
-            &mut DMRegressionLine::default()
-        )?);
-
-        // let a = lineT.length() as i32 / 3;
-        // let b = (lenL / 5.0) as i32;
-
-        // maxStepSize = std::cmp::min(a,  b) * 2;
-        maxStepSize = std::cmp::min(lineT.length() as i32 / 3, (lenL / 5.0) as i32) * 2;
-
-        // follow up until we reach the top line
-        t.setDirection(up);
-        t.state = 3;
-        CHECK!(t.traceGaps(t.left(), lineR, maxStepSize, lineT)?);
-        CHECK!(t.traceCorner(&mut t.left(), &mut tr)?);
-
-        let lenT = Point::distance(tl, tr) - 1.0;
-        let lenR = Point::distance(tr, br) - 1.0;
-
-        CHECK!(
-            (lenT - lenB).abs() / lenB < 0.5
-                && (lenR - lenL).abs() / lenL < 0.5
-                && lineT.points().len() >= 5
-                && lineR.points().len() >= 5
-        );
-
-        // continue top row right until we cross the right line
-        CHECK!(tlTracer.traceGaps(tlTracer.right(), lineT, maxStepSize, lineR)?);
-
-        // #ifdef PRINT_DEBUG
-        // 		printf("L: %.1f, %.1f ^ %.1f, %.1f > %.1f, %.1f (%d : %d : %d : %d)\n", bl.x, bl.y,
-        // 			   tl.x - bl.x, tl.y - bl.y, br.x - bl.x, br.y - bl.y, (int)lenL, (int)lenB, (int)lenT, (int)lenR);
-        // #endif
+        // at this point we found a plausible L-shape and are now looking for the b/w pattern at the top and right.
+        // when a strongly foreshortened symbol defeats tracing it, the top right corner is searched for instead.
+        let traced = 'traced: {
+            let mut maxStepSize: i32 = (lenB / 5.0 + 1.0) as i32; // datamatrix bottom dim is at least 10
+
+            // follow top row right 'half way' (4 gaps), see traceGaps break condition with 'invalid' line
+            tlTracer.setDirection(right);
+            CHECK!(
+                tlTracer.traceGaps(
+                    tlTracer.right(),
+                    lineT,
+                    maxStepSize,
+                    &mut DMRegressionLine::default()
+                )?,
+                'traced
+            );
+
+            // let a = lineT.length() as i32 / 3;
+            // let b = (lenL / 5.0) as i32;
+
+            // maxStepSize = std::cmp::min(a,  b) * 2;
+            maxStepSize = std::cmp::min(lineT.length() as i32 / 3, (lenL / 5.0) as i32) * 2;
+
+            // follow up until we reach the top line
+            t.setDirection(up);
+            t.state = 3;
+            CHECK!(t.traceGaps(t.left(), lineR, maxStepSize, lineT)?, 'traced);
+            CHECK!(t.traceCorner(&mut t.left(), &mut tr)?, 'traced);
+
+            let lenT = Point::distance(tl, tr) - 1.0;
+            let lenR = Point::distance(tr, br) - 1.0;
+
+            CHECK!(
+                (lenT - lenB).abs() / lenB < 0.5
+                    && (lenR - lenL).abs() / lenL < 0.5
+                    && lineT.points().len() >= 5
+                    && lineR.points().len() >= 5,
+                'traced
+            );
+
+            // continue top row right until we cross the right line
+            CHECK!(tlTracer.traceGaps(tlTracer.right(), lineT, maxStepSize, lineR)?, 'traced);
+
+            // #ifdef PRINT_DEBUG
+            // 		printf("L: %.1f, %.1f ^ %.1f, %.1f > %.1f, %.1f (%d : %d : %d : %d)\n", bl.x, bl.y,
+            // 			   tl.x - bl.x, tl.y - bl.y, br.x - bl.x, br.y - bl.y, (int)lenL, (int)lenB, (int)lenT, (int)lenR);
+            // #endif
 
-        // for l in [lineL, lineB, lineT, lineR] {
-        //     l.evaluate_max_distance(Some(1.0), None);
-        // }
-        lineL.evaluate_max_distance(Some(1.0), None);
-        lineB.evaluate_max_distance(Some(1.0), None);
-        lineT.evaluate_max_distance(Some(1.0), None);
-        lineR.evaluate_max_distance(Some(1.0), None);
-
-        // find the bounding box corners of the code with sub-pixel precision by intersecting the 4 border lines
-        bl = intersect(lineB, lineL)?;
-        tl = intersect(lineT, lineL)?;
-        tr = intersect(lineT, lineR)?;
-        br = intersect(lineB, lineR)?;
-
-        let mut dimT: i32 = 0;
-        let mut dimR: i32 = 0;
-        let mut fracT: f64 = 0.0;
-        let mut fracR: f64 = 0.0;
-        let splitDouble = |d: f64, i: &mut i32, f: &mut f64| {
-            *i = if d.is_normal() { (d + 0.5) as i32 } else { 0 };
-            *f = if d.is_normal() {
-                (d - *i as f64).abs()
-            } else {
-                f64::INFINITY
+            // for l in [lineL, lineB, lineT, lineR] {
+            //     l.evaluate_max_distance(Some(1.0), None);
+            // }
+            lineL.evaluate_max_distance(Some(1.0), None);
+            lineB.evaluate_max_distance(Some(1.0), None);
+            lineT.evaluate_max_distance(Some(1.0), None);
+            lineR.evaluate_max_distance(Some(1.0), None);
+
+            // find the bounding box corners of the code with sub-pixel precision by intersecting the 4 border lines
+            bl = intersect(lineB, lineL)?;
+            tl = intersect(lineT, lineL)?;
+            tr = intersect(lineT, lineR)?;
+            br = intersect(lineB, lineR)?;
+
+            let mut dimT: i32 = 0;
+            let mut dimR: i32 = 0;
+            let mut fracT: f64 = 0.0;
+            let mut fracR: f64 = 0.0;
+            let splitDouble = |d: f64, i: &mut i32, f: &mut f64| {
+                *i = if d.is_normal() { (d + 0.5) as i32 } else { 0 };
+                *f = if d.is_normal() {
+                    (d - *i as f64).abs()
+                } else {
+                    f64::INFINITY
+                };
             };
-        };
-        splitDouble(lineT.modules(tl, tr)?, &mut dimT, &mut fracT);
-        splitDouble(lineR.modules(br, tr)?, &mut dimR, &mut fracR);
+            splitDouble(lineT.modules(tl, tr)?, &mut dimT, &mut fracT);
+            splitDouble(lineR.modules(br, tr)?, &mut dimR, &mut fracR);
 
-        // #ifdef PRINT_DEBUG
-        // 		printf("L: %.1f, %.1f ^ %.1f, %.1f > %.1f, %.1f ^> %.1f, %.1f\n", bl.x, bl.y,
-        // 			   tl.x - bl.x, tl.y - bl.y, br.x - bl.x, br.y - bl.y, tr.x, tr.y);
-        // 		printf("dim: %d x %d\n", dimT, dimR);
-        // #endif
+            // #ifdef PRINT_DEBUG
+            // 		printf("L: %.1f, %.1f ^ %.1f, %.1f > %.1f, %.1f ^> %.1f, %.1f\n", bl.x, bl.y,
+            // 			   tl.x - bl.x, tl.y - bl.y, br.x - bl.x, br.y - bl.y, tr.x, tr.y);
+            // 		printf("dim: %d x %d\n", dimT, dimR);
+            // #endif
 
-        // if we have an almost square (invalid rectangular) data matrix dimension, we try to parse it by assuming a
-        // square. we use the dimension that is closer to an integral value. all valid rectangular symbols differ in
-        // their dimension by at least 10 (here 5, see doubling below). Note: this is currently not required for the
-        // black-box tests to complete.
-        if (dimT - dimR).abs() < 5 {
-            dimR = if fracR < fracT { dimR } else { dimT };
-            dimT = dimR;
-        }
+            // if we have an almost square (invalid rectangular) data matrix dimension, we try to parse it by assuming a
+            // square. we use the dimension that is closer to an integral value. all valid rectangular symbols differ in
+            // their dimension by at least 10 (here 5, see doubling below). Note: this is currently not required for the
+            // black-box tests to complete.
+            if (dimT - dimR).abs() < 5 {
+                dimR = if fracR < fracT { dimR } else { dimT };
+                dimT = dimR;
+            }
 
-        // the dimension is 2x the number of black/white transitions
-        dimT *= 2;
-        dimR *= 2;
+            // the dimension is 2x the number of black/white transitions
+            dimT *= 2;
+            dimR *= 2;
 
-        CHECK!((10..=144).contains(&dimT) && (8..=144).contains(&dimR));
+            Some(([tl, tr, br, bl], dimT, dimR))
+        };
+        let Some(([tl, tr, br, bl], dimT, dimR)) =
+            traced.or_else(|| search_top_right(startTracer.img, lineL, lineB, tl, bl, br))
+        else {
+            continue;
+        };
 
-        let movedTowardsBy = |a: Point, b1: Point, b2: Point, d: f32| -> Point {
-            a + d * Point::normalized(Point::normalized(b1 - a) + Point::normalized(b2 - a))
+        let sourcePoints = source_points(tl, tr, br, bl);
+
+        // the gaps of the traced lines overcount the modules of rotated symbols, whose jagged edges
+        // leave gaps within a module, and miss some of strongly foreshortened ones. so also count the
+        // black/white transitions of the timing pattern itself and switch to the counted dimension
+        // if its finder and timing pattern are matched better by the image.
+        let countedT = count_timing_modules(startTracer.img, sourcePoints, true, dimT, dimR);
+        let countedR = count_timing_modules(startTracer.img, sourcePoints, false, dimT, dimR);
+        let score = |(dimT, dimR): (i32, i32)| {
+            if is_symbol_size(dimT, dimR) {
+                border_score(startTracer.img, sourcePoints, dimT, dimR)
+            } else {
+                0.0
+            }
         };
+        let tracedScore = score((dimT, dimR));
+        let (dimT, dimR) = [(countedT, dimR), (dimT, countedR), (countedT, countedR)]
+            .into_iter()
+            .map(|dims| (dims, score(dims)))
+            .filter(|(_, counted)| *counted >= MIN_BORDER_SCORE && *counted > tracedScore)
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map_or((dimT, dimR), |(dims, _)| dims);
 
-        // shrink shape by half a pixel to go from center of white pixel outside of code to the edge between white and black
-        let sourcePoints = Quadrilateral::with_points(
-            movedTowardsBy(tl, tr, bl, 0.5),
-            // move the tr point a little less because the jagged top and right line tend to be statistically slightly
-            // inclined toward the center anyway.
-            movedTowardsBy(tr, br, tl, 0.3),
-            movedTowardsBy(br, bl, tr, 0.5),
-            movedTowardsBy(bl, tl, br, 0.5),
-        );
+        CHECK!((10..=144).contains(&dimT) && (8..=144).contains(&dimR));
 
         let grid_sampler = DefaultGridSampler;
         // let transform = PerspectiveTransform::quadrilateralToQuadrilateral(x0, y0, x1, y1, x2, y2, x3, y3, x0p, y0p, x1p, y1p, x2p, y2p, x3p, y3p);
@@ -235,15 +266,248 @@ fn Scan(
 
         let (res, _) = res?;
 
-        return Ok(DatamatrixDetectorResult::new(
-            res,
-            sourcePoints.points().to_vec(),
-        ));
+        let transform = PerspectiveTransform::quadrilateralToQuadrilateral(dst, src)?;
+        let patterns = [tl, tr, br, bl]
+            .into_iter()
+            .map(|p| DetectedPattern::new(PatternKind::Corner, p))
+            .collect();
+
+        return Ok(
+            DatamatrixDetectorResult::new(res, sourcePoints.points().to_vec()).with_sampling_grid(
+                SamplingGrid::with_transform(dimT as u32, dimR as u32, transform)
+                    .with_patterns(patterns),
+            ),
+        );
     }
 
     Err(Exceptions::NOT_FOUND)
 }
 
+/// The share of the modules of the 'L' and the timing pattern a symbol needs to match
+const MIN_BORDER_SCORE: f32 = 0.75;
+
+/**
+ * Counts the modules of the top (or right) timing pattern of the symbol with the corners
+ * `corners`, along a line half a module inside its edge. The estimated dimension `dimT` by
+ * `dimR` places that line and the shortest run of pixels that counts as a module.
+ */
+fn count_timing_modules(
+    image: &BitMatrix,
+    corners: Quadrilateral,
+    top: bool,
+    dimT: i32,
+    dimR: i32,
+) -> i32 {
+    let Ok(transform) = PerspectiveTransform::quadrilateralToQuadrilateral(
+        Quadrilateral::rectangle_from_xy(0.0, 1.0, 0.0, 1.0, Some(0.0)),
+        corners,
+    ) else {
+        return 0;
+    };
+    let [tl, tr, br, _] = corners.0;
+    // from the black module at the 'L' to the white one in the top right corner
+    let (from, to, dim, length) = if top {
+        let v = 0.5 / dimR.max(1) as f32;
+        (point(0.0, v), point(1.0, v), dimT, Point::distance(tl, tr))
+    } else {
+        let u = 1.0 - 0.5 / dimT.max(1) as f32;
+        (point(u, 1.0), point(u, 0.0), dimR, Point::distance(br, tr))
+    };
+
+    // two samples per pixel, runs of less than a quarter module are noise
+    let samples = (2.0 * length).ceil() as usize;
+    let minRun = (samples as f32 / dim.max(1) as f32 / 4.0).max(1.0) as usize;
+    let mut runs: Vec<(bool, usize)> = Vec::new();
+    for i in 0..samples {
+        let p = transform.transform_point(from + (i as f32 + 0.5) / samples as f32 * (to - from));
+        let black = image.isIn(p, 0) && image.get_point(p);
+        match runs.last_mut() {
+            Some((color, run)) if *color == black => *run += 1,
+            _ => runs.push((black, 1)),
+        }
+    }
+
+    let mut blackRuns = 0;
+    let mut last = None;
+    for (black, run) in runs {
+        if run < minRun || last == Some(black) {
+            continue;
+        }
+        last = Some(black);
+        if black {
+            blackRuns += 1;
+        }
+    }
+
+    // the dimension is 2x the number of black modules
+    2 * blackRuns
+}
+
+/**
+ * How well the image matches the solid 'L' and the alternating timing pattern at the top and
+ * right of a `dimT` by `dimR` symbol with the corners `corners`: the share of the modules of
+ * either that have their color, whichever is lower. The quiet zone beyond the timing pattern
+ * counts with it, so the inner finder patterns of a symbol with several data regions don't
+ * pass for the border of a smaller one.
+ */
+fn border_score(image: &BitMatrix, corners: Quadrilateral, dimT: i32, dimR: i32) -> f32 {
+    let Ok(transform) = PerspectiveTransform::quadrilateralToQuadrilateral(
+        Quadrilateral::rectangle(dimT, dimR, None),
+        corners,
+    ) else {
+        return 0.0;
+    };
+    let matches = |x: i32, y: i32, black: bool| {
+        let p = transform.transform_point(point(x as f32 + 0.5, y as f32 + 0.5));
+        i32::from(image.isIn(p, 0) && image.get_point(p) == black)
+    };
+
+    let (mut finder, mut timing) = (0, 0);
+    for x in 0..dimT {
+        finder += matches(x, dimR - 1, true);
+        timing += matches(x, 0, x % 2 == 0) + matches(x, -1, false);
+    }
+    for y in 0..dimR {
+        finder += matches(0, y, true);
+        timing += matches(dimT - 1, y, y % 2 == 1) + matches(dimT, y, false);
+    }
+
+    f32::min(
+        finder as f32 / (dimT + dimR) as f32,
+        timing as f32 / (2 * (dimT + dimR)) as f32,
+    )
+}
+
+/**
+ * Searches the top right corner of a symbol whose 'L' was traced from `tl` over `bl` to `br`
+ * when its top and right edge could not be: the edges through `tl` and `br`, parallel to the
+ * 'L' at first, are turned until the finder and timing pattern of the symbol they enclose are
+ * matched best, as they are no longer under strong perspective distortion.
+ *
+ * # Returns
+ * The corners and the dimension of the symbol, or `None` if no symbol fits the 'L'
+ */
+fn search_top_right(
+    image: &BitMatrix,
+    lineL: &mut DMRegressionLine,
+    lineB: &mut DMRegressionLine,
+    tl: Point,
+    bl: Point,
+    br: Point,
+) -> Option<([Point; 4], i32, i32)> {
+    // move the corners of the 'L' onto its border lines, as for a traced symbol
+    let (tl, bl, br) = if lineL.evaluate_max_distance(Some(1.0), None)
+        && lineB.evaluate_max_distance(Some(1.0), None)
+    {
+        (
+            lineL.project(tl),
+            intersect(lineB, lineL).ok()?,
+            lineB.project(br),
+        )
+    } else {
+        (tl, bl, br)
+    };
+
+    let top = Point::normalized(br - bl);
+    let right = Point::normalized(tl - bl);
+    let turned = |d: Point, degrees: f32| {
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        point(d.x * cos - d.y * sin, d.x * sin + d.y * cos)
+    };
+    let symbol = |turnT: f32, turnR: f32, dims: Option<(i32, i32)>| {
+        let tr = intersect(
+            &DMRegressionLine::new(tl, tl + turned(top, turnT)),
+            &DMRegressionLine::new(br, br + turned(right, turnR)),
+        )
+        .ok()?;
+        let corners = source_points(tl, tr, br, bl);
+        let (dimT, dimR) = dims.unwrap_or_else(|| {
+            // count along a line one pixel inside the edges first, which is inside the timing
+            // pattern of any symbol of at least 2 pixels per module
+            let (dimT, dimR) = (
+                (Point::distance(tl, tr) / 2.0) as i32,
+                (Point::distance(br, tr) / 2.0) as i32,
+            );
+            let (dimT, dimR) = (
+                count_timing_modules(image, corners, true, dimT, dimR),
+                count_timing_modules(image, corners, false, dimT, dimR),
+            );
+            (
+                count_timing_modules(image, corners, true, dimT, dimR),
+                count_timing_modules(image, corners, false, dimT, dimR),
+            )
+        });
+
+        // the count is off by a module pair or two where an edge is, so try the sizes around it
+        let near = |dim: i32| {
+            let tolerance = (dim / 16).max(1) * 2;
+            (dim - tolerance..=dim + tolerance).step_by(2)
+        };
+        near(dimT)
+            .flat_map(|dimT| near(dimR).map(move |dimR| (dimT, dimR)))
+            .filter(|(dimT, dimR)| is_symbol_size(*dimT, *dimR))
+            .map(|(dimT, dimR)| (dimT, dimR, border_score(image, corners, dimT, dimR)))
+            .fold(
+                None,
+                |best: Option<(i32, i32, f32)>, candidate| match best {
+                    Some(best) if best.2 >= candidate.2 => Some(best),
+                    _ => Some(candidate),
+                },
+            )
+            .map(|(dimT, dimR, score)| ([tl, tr, br, bl], dimT, dimR, score))
+    };
+
+    // turn the edges up to 20 degrees each way, then refine around the best match. smaller turns
+    // come first and win a tie
+    let mut best: Option<([Point; 4], i32, i32, f32)> = None;
+    let mut center = (0.0, 0.0);
+    for (range, step) in [(20.0, 4.0), (4.0, 0.5)] {
+        let steps = (range / step) as i32;
+        let mut turns: Vec<f32> = (-steps..=steps).map(|i| i as f32 * step).collect();
+        turns.sort_by(|a, b| a.abs().total_cmp(&b.abs()));
+        let dims = best.map(|(_, dimT, dimR, _)| (dimT, dimR));
+        let mut found = None;
+        for &turnT in &turns {
+            for &turnR in &turns {
+                let (turnT, turnR) = (center.0 + turnT, center.1 + turnR);
+                let Some(candidate) = symbol(turnT, turnR, dims) else {
+                    continue;
+                };
+                if best.is_none_or(|best| candidate.3 > best.3) {
+                    best = Some(candidate);
+                    found = Some((turnT, turnR));
+                }
+            }
+        }
+        center = found.unwrap_or(center);
+    }
+
+    best.filter(|(_, _, _, score)| *score >= MIN_BORDER_SCORE)
+        .map(|(corners, dimT, dimR, _)| (corners, dimT, dimR))
+}
+
+/// Whether a Data Matrix symbol is `dimT` modules wide and `dimR` high
+fn is_symbol_size(dimT: i32, dimR: i32) -> bool {
+    dimT > 0 && dimR > 0 && Version::getVersionForDimensions(dimR as u32, dimT as u32).is_ok()
+}
+
+/// Shrinks the symbol with the corners `tl`, `tr`, `br` and `bl` from the center of the white
+/// pixels outside of it to the edge between white and black
+fn source_points(tl: Point, tr: Point, br: Point, bl: Point) -> Quadrilateral {
+    let movedTowardsBy = |a: Point, b1: Point, b2: Point, d: f32| -> Point {
+        a + d * Point::normalized(Point::normalized(b1 - a) + Point::normalized(b2 - a))
+    };
+
+    Quadrilateral::with_points(
+        movedTowardsBy(tl, tr, bl, 0.5),
+        // move the tr point a little less because the jagged top and right line tend to be statistically slightly
+        // inclined toward the center anyway.
+        movedTowardsBy(tr, br, tl, 0.3),
+        movedTowardsBy(br, bl, tr, 0.5),
+        movedTowardsBy(bl, tl, br, 0.5),
+    )
+}
+
 pub fn detect(
     image: &BitMatrix,
     tryHarder: bool,
@@ -363,3 +627,79 @@ pub fn detect(
     }
     // #endif
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        common::{BitMatrix, DetectorRXingResult, PerspectiveTransform, Quadrilateral},
+        datamatrix::{decoder::Decoder, DataMatrixWriter},
+        point, BarcodeFormat, Point, Writer,
+    };
+
+    /// Draws `contents` with a quiet zone of 2 modules onto the quadrilateral `corners`
+    fn render(contents: &str, corners: [Point; 4], width: u32, height: u32) -> BitMatrix {
+        let symbol = DataMatrixWriter
+            .encode(contents, &BarcodeFormat::DATA_MATRIX, 0, 0)
+            .expect("encode");
+        let (columns, rows) = (symbol.getWidth() as i32 + 4, symbol.getHeight() as i32 + 4);
+        let transform = PerspectiveTransform::quadrilateralToQuadrilateral(
+            Quadrilateral(corners),
+            Quadrilateral::rectangle(columns, rows, None),
+        )
+        .expect("transform");
+
+        let mut image = BitMatrix::new(width, height).expect("image");
+        for y in 0..height {
+            for x in 0..width {
+                let module = transform.transform_point(point(x as f32 + 0.5, y as f32 + 0.5));
+                let (column, row) = (module.x.floor() as i32 - 2, module.y.floor() as i32 - 2);
+                if (0..columns - 4).contains(&column)
+                    && (0..rows - 4).contains(&row)
+                    && symbol.get(column as u32, row as u32)
+                {
+                    image.set(x, y);
+                }
+            }
+        }
+        image
+    }
+
+    fn detect_text(image: &BitMatrix) -> Vec<String> {
+        super::detect(image, true, true)
+            .map(|symbols| {
+                symbols
+                    .iter()
+                    .filter_map(|symbol| Decoder::new().decode(symbol.getBits()).ok())
+                    .map(|result| result.getText().to_owned())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn test_rotated() {
+        let contents = "square symbol";
+        // 16x16 modules and the quiet zone, 8 pixels each, turned by 15 degrees about (150, 150)
+        let (sin, cos) = 15f32.to_radians().sin_cos();
+        let corners = [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)].map(|(x, y)| {
+            let (x, y) = (x * 80.0, y * 80.0);
+            point(150.0 + x * cos - y * sin, 150.0 + x * sin + y * cos)
+        });
+        let image = render(contents, corners, 300, 300);
+        assert_eq!(vec![contents.to_owned()], detect_text(&image));
+    }
+
+    #[test]
+    fn test_perspective() {
+        let contents = "Perspective tolerant Data Matrix 0123456789";
+        // the left edge seen at twice the distance of the right one
+        let corners = [
+            point(40.0, 70.0),
+            point(260.0, 20.0),
+            point(260.0, 280.0),
+            point(40.0, 230.0),
+        ];
+        let image = render(contents, corners, 300, 300);
+        assert_eq!(vec![contents.to_owned()], detect_text(&image));
+    }
+}
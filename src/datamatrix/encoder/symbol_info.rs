@@ -21,7 +21,7 @@ use crate::{Dimension, Exceptions};
 
 use super::SymbolShapeHint;
 
-pub(super) const PROD_SYMBOLS: [SymbolInfo; 30] = [
+pub(crate) const PROD_SYMBOLS: [SymbolInfo; 30] = [
     SymbolInfo::new(false, 3, 5, 8, 8, 1),
     SymbolInfo::new(false, 5, 7, 10, 10, 1),
     /*rect*/ SymbolInfo::new(true, 5, 7, 16, 6, 1),
@@ -0,0 +1,169 @@
+/*
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+const HEADER_START: &str = "[)>\u{001E}";
+const GROUP_SEPARATOR: char = '\u{001D}';
+const TRAILER: &str = "\u{001E}\u{0004}";
+
+/**
+ * The ISO/IEC 15434 envelope of a Data Matrix symbol that starts with a Macro 05 or Macro 06
+ * codeword. These stand for the header <code>[)&gt;RS05GS</code> or <code>[)&gt;RS06GS</code>
+ * and the trailer <code>RS EOT</code>, which the text of the result includes.
+ *
+ * The data elements between them are separated by GS. In format 05 they start with GS1
+ * application identifiers, in format 06 with ASC MH10 data identifiers, as on AIAG and
+ * ODETTE automotive labels: <code>P</code> for a part number, <code>Q</code> for a quantity,
+ * <code>1J</code> for a license plate and so on.
+ */
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MacroEnvelope {
+    format: String,
+    data_elements: Vec<String>,
+}
+
+impl MacroEnvelope {
+    /**
+     * Parses a message in the envelope of an ISO/IEC 15434 format.
+     *
+     * @param text the message, header and trailer included
+     * @return the envelope, or `None` if the text does not start with a header or does not end
+     *  with the trailer
+     */
+    pub fn parse(text: &str) -> Option<Self> {
+        let rest = text.strip_prefix(HEADER_START)?;
+        let format = rest
+            .get(..2)
+            .filter(|f| f.bytes().all(|b| b.is_ascii_digit()))?;
+        let data = rest[2..]
+            .strip_prefix(GROUP_SEPARATOR)?
+            .strip_suffix(TRAILER)?;
+        Some(Self {
+            format: format.to_owned(),
+            data_elements: data.split(GROUP_SEPARATOR).map(str::to_owned).collect(),
+        })
+    }
+
+    /// The format indicator, "05" or "06" for the Data Matrix macros
+    pub fn getFormat(&self) -> &str {
+        &self.format
+    }
+
+    /// The header, <code>[)&gt;RS</code>, the format and GS
+    pub fn getHeader(&self) -> String {
+        format!("{HEADER_START}{}{GROUP_SEPARATOR}", self.format)
+    }
+
+    /// The trailer, <code>RS EOT</code>
+    pub fn getTrailer(&self) -> &'static str {
+        TRAILER
+    }
+
+    /// The data elements in the order of the symbol, without their separators
+    pub fn getDataElements(&self) -> &[String] {
+        &self.data_elements
+    }
+
+    /**
+     * Finds a data element of format 06 by its data identifier, which is up to three digits and
+     * an uppercase letter.
+     *
+     * @param identifier the data identifier, such as "P" or "1J"
+     * @return the data of the first element with that identifier, without it
+     */
+    pub fn getDataElement(&self, identifier: &str) -> Option<&str> {
+        self.data_elements.iter().find_map(|element| {
+            let (element_identifier, data) = split_data_identifier(element)?;
+            (element_identifier == identifier).then_some(data)
+        })
+    }
+}
+
+/// Splits an element of format 06 after its data identifier
+fn split_data_identifier(element: &str) -> Option<(&str, &str)> {
+    let digits = element.bytes().take_while(|b| b.is_ascii_digit()).count();
+    let letter = element.as_bytes().get(digits)?;
+    if digits > 3 || !letter.is_ascii_uppercase() {
+        return None;
+    }
+    Some(element.split_at(digits + 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::{
+        datamatrix::DataMatrixWriter, helpers, BarcodeFormat, DecodeHints, RXingResultMetadataType,
+        RXingResultMetadataValue, Writer,
+    };
+
+    use super::MacroEnvelope;
+
+    #[test]
+    fn test_parse() {
+        let envelope = MacroEnvelope::parse(
+            "[)>\u{1E}06\u{1D}P12345AB\u{1D}Q100\u{1D}1JUN123456789\u{1E}\u{4}",
+        )
+        .expect("envelope");
+        assert_eq!("06", envelope.getFormat());
+        assert_eq!("[)>\u{1E}06\u{1D}", envelope.getHeader());
+        assert_eq!("\u{1E}\u{4}", envelope.getTrailer());
+        assert_eq!(
+            ["P12345AB", "Q100", "1JUN123456789"],
+            envelope.getDataElements()
+        );
+        assert_eq!(Some("12345AB"), envelope.getDataElement("P"));
+        assert_eq!(Some("100"), envelope.getDataElement("Q"));
+        assert_eq!(Some("UN123456789"), envelope.getDataElement("1J"));
+        assert_eq!(None, envelope.getDataElement("J"));
+
+        assert_eq!(None, MacroEnvelope::parse("[)>\u{1E}06\u{1D}P12345AB"));
+        assert_eq!(None, MacroEnvelope::parse("P12345AB\u{1E}\u{4}"));
+    }
+
+    #[test]
+    fn test_decode_envelope() {
+        let contents = "[)>\u{1E}05\u{1D}0109506000134352\u{1D}10ABC\u{1E}\u{4}";
+        let matrix = DataMatrixWriter
+            .encode(contents, &BarcodeFormat::DATA_MATRIX, 0, 0)
+            .expect("encode");
+        let luma = matrix.to_luma8();
+        let mut hints = DecodeHints {
+            PossibleFormats: Some(HashSet::from([BarcodeFormat::DATA_MATRIX])),
+            PureBarcode: Some(true),
+            ..Default::default()
+        };
+        let result = helpers::detect_in_luma_with_hints(
+            luma,
+            matrix.getWidth(),
+            matrix.getHeight(),
+            Some(BarcodeFormat::DATA_MATRIX),
+            &mut hints,
+        )
+        .expect("decode");
+        assert_eq!(contents, result.getText());
+        assert_eq!(
+            Some(&RXingResultMetadataValue::MacroEnvelope(
+                MacroEnvelope::parse(contents).unwrap()
+            )),
+            result
+                .getRXingResultMetadata()
+                .get(&RXingResultMetadataType::MACRO_ENVELOPE)
+        );
+    }
+}
@@ -4,5 +4,7 @@ pub mod encoder;
 
 mod data_matrix_reader;
 mod data_matrix_writer;
+mod macro_envelope;
 pub use data_matrix_reader::*;
 pub use data_matrix_writer::*;
+pub use macro_envelope::*;
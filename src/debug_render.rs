@@ -0,0 +1,613 @@
+//! Overlays of what the detectors and decoders found in an image, for working out why a photo
+//! fails to decode without adding print statements to the detectors.
+//!
+//! A [`DebugOverlay`] installs itself in the decode hints as the `NeedResultPointCallback` and
+//! the `WitnessSink`, so it works with any reader and entry point. It collects:
+//!
+//! * the finder and alignment pattern candidates the detectors reported, even when the decode
+//!   failed afterwards
+//! * the regions [`crate::locate`] found symbols in, which are drawn as rejected when no
+//!   symbol was decoded in them
+//! * the outline, patterns and sampling grid of every decoded symbol
+//! * the modules of the codewords error correction fixed, for QR codes
+//!
+//! The collected [`DebugLayers`] are drawn as an SVG document to lay over the image, or with
+//! the `image` feature over the image itself, and saved as a PNG with `image-render`.
+//!
+//! ```
+//! use rxing::{
+//!     common::HybridBinarizer, debug_render::debug_decode, BarcodeFormat, BinaryBitmap,
+//!     DecodeHints, Luma8LuminanceSource, MultiFormatWriter, Writer,
+//! };
+//!
+//! let symbol = MultiFormatWriter.encode("debug me", &BarcodeFormat::QR_CODE, 120, 120)?;
+//! let mut image = BinaryBitmap::new(HybridBinarizer::new(Luma8LuminanceSource::new(
+//!     symbol.to_luma8(),
+//!     120,
+//!     120,
+//! )));
+//! let (result, layers) = debug_decode(&mut image, &DecodeHints::default());
+//! assert_eq!("debug me", result?.getText());
+//! let svg = layers.to_svg(120, 120, Some("photo.png"));
+//! assert!(svg.contains("<image href=\"photo.png\""));
+//! # Ok::<(), rxing::Exceptions>(())
+//! ```
+
+use std::{
+    collections::HashSet,
+    fmt::Write,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+};
+
+use crate::{
+    common::{CodewordWitness, DetectedPattern, PatternKind, Result, SamplingGrid},
+    point,
+    qrcode::decoder::Version,
+    render::{hex_color, number, xml_escape},
+    BarcodeFormat, BinarizationWitness, Binarizer, BinaryBitmap, DecodeHints, DetectionWitness,
+    LocatedSymbol, LuminanceWitness, MultiFormatReader, Point, RXingResult, Reader,
+    SamplingWitness, TextAssemblyWitness, WitnessProvenance, WitnessSink,
+};
+
+const CANDIDATE_COLOR: [u8; 3] = [255, 200, 0];
+const REJECTED_COLOR: [u8; 3] = [220, 20, 60];
+const DECODED_COLOR: [u8; 3] = [0, 200, 0];
+const PATTERN_COLOR: [u8; 3] = [30, 90, 255];
+const GRID_COLOR: [u8; 3] = [0, 190, 230];
+const CORRECTED_COLOR: [u8; 3] = [255, 0, 255];
+
+/// What was found of a decoded symbol
+#[derive(Debug, Clone, PartialEq)]
+pub struct SymbolOverlay {
+    pub format: BarcodeFormat,
+    /// The result points of the symbol, its corners or the ends of a linear symbol
+    pub points: Vec<Point>,
+    /// The finder and alignment patterns the symbol was located by
+    pub patterns: Vec<DetectedPattern>,
+    /// The grid the modules were sampled with, for formats whose detector keeps one
+    pub grid: Option<SamplingGrid>,
+    /// The modules, as `(x, y)` in `grid`, of the codewords error correction fixed
+    pub corrected_modules: Vec<(u32, u32)>,
+}
+
+/// Everything a [`DebugOverlay`] collected during a decode, in image pixels
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DebugLayers {
+    /// The finder and alignment pattern candidates reported by the detectors
+    pub candidates: Vec<Point>,
+    /// The regions symbols were located in, decoded or not
+    pub located: Vec<LocatedSymbol>,
+    /// The symbols that were decoded
+    pub symbols: Vec<SymbolOverlay>,
+}
+
+impl DebugLayers {
+    /// The located regions no symbol was decoded in
+    pub fn rejected(&self) -> Vec<&LocatedSymbol> {
+        self.located
+            .iter()
+            .filter(|located| {
+                let (left, top, width, height) = located.bounds(0.0, usize::MAX, usize::MAX);
+                !self.symbols.iter().any(|symbol| {
+                    let center = centroid(&symbol.points);
+                    (left as f32..=(left + width) as f32).contains(&center.x)
+                        && (top as f32..=(top + height) as f32).contains(&center.y)
+                })
+            })
+            .collect()
+    }
+
+    /**
+     * Draws the layers as an SVG document of `width` by `height` pixels, over the image at
+     * `image_href` if given, such as the path of the photo that was decoded.
+     */
+    pub fn to_svg(&self, width: u32, height: u32, image_href: Option<&str>) -> String {
+        let mut svg = String::new();
+        svg.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        let _ = writeln!(
+            svg,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" version=\"1.1\" width=\"{width}\" height=\"{height}\" viewBox=\"0 0 {width} {height}\">"
+        );
+        if let Some(href) = image_href {
+            let _ = writeln!(
+                svg,
+                "<image href=\"{}\" width=\"{width}\" height=\"{height}\"/>",
+                xml_escape(href)
+            );
+        }
+        for shape in self.shapes() {
+            let points = |points: &[Point]| {
+                points
+                    .iter()
+                    .map(|p| format!("{},{}", number(p.x), number(p.y)))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            };
+            let _ = match shape {
+                Shape::Polygon(corners, color) => writeln!(
+                    svg,
+                    "<polygon points=\"{}\" fill=\"none\" stroke=\"{}\"/>",
+                    points(&corners),
+                    hex_color(color)
+                ),
+                Shape::Polyline(line, color) => writeln!(
+                    svg,
+                    "<polyline points=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"0.5\"/>",
+                    points(&line),
+                    hex_color(color)
+                ),
+                Shape::Circle(center, radius, color) => writeln!(
+                    svg,
+                    "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"none\" stroke=\"{}\"/>",
+                    number(center.x),
+                    number(center.y),
+                    number(radius),
+                    hex_color(color)
+                ),
+                Shape::Cross(center, size, color) => writeln!(
+                    svg,
+                    "<path d=\"M{},{}h{}M{},{}v{}\" stroke=\"{}\"/>",
+                    number(center.x - size),
+                    number(center.y),
+                    number(2.0 * size),
+                    number(center.x),
+                    number(center.y - size),
+                    number(2.0 * size),
+                    hex_color(color)
+                ),
+            };
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    /// Draws the layers over `image`
+    #[cfg(feature = "image")]
+    pub fn draw(&self, image: &mut image::RgbaImage) {
+        use imageproc::drawing::{draw_hollow_circle_mut, draw_line_segment_mut};
+
+        let rgba = |[r, g, b]: [u8; 3]| image::Rgba([r, g, b, u8::MAX]);
+        let segments = |image: &mut image::RgbaImage, points: &[Point], color| {
+            for pair in points.windows(2) {
+                draw_line_segment_mut(
+                    image,
+                    (pair[0].x, pair[0].y),
+                    (pair[1].x, pair[1].y),
+                    rgba(color),
+                );
+            }
+        };
+        for shape in self.shapes() {
+            match shape {
+                Shape::Polygon(mut corners, color) => {
+                    if let Some(first) = corners.first().copied() {
+                        corners.push(first);
+                    }
+                    segments(image, &corners, color);
+                }
+                Shape::Polyline(line, color) => segments(image, &line, color),
+                Shape::Circle(center, radius, color) => draw_hollow_circle_mut(
+                    image,
+                    (center.x.round() as i32, center.y.round() as i32),
+                    radius.round() as i32,
+                    rgba(color),
+                ),
+                Shape::Cross(center, size, color) => {
+                    segments(
+                        image,
+                        &[
+                            point(center.x - size, center.y),
+                            point(center.x + size, center.y),
+                        ],
+                        color,
+                    );
+                    segments(
+                        image,
+                        &[
+                            point(center.x, center.y - size),
+                            point(center.x, center.y + size),
+                        ],
+                        color,
+                    );
+                }
+            }
+        }
+    }
+
+    /// Draws the layers over the grayscale image `source` was read from
+    #[cfg(feature = "image")]
+    pub fn render<L: crate::LuminanceSource>(&self, source: &L) -> image::RgbaImage {
+        let (width, height) = (source.get_width() as u32, source.get_height() as u32);
+        let luma = source.get_matrix();
+        let mut image = image::RgbaImage::from_fn(width, height, |x, y| {
+            let value = luma[(y * width + x) as usize];
+            image::Rgba([value, value, value, u8::MAX])
+        });
+        self.draw(&mut image);
+        image
+    }
+
+    /// Draws the layers over the grayscale image `source` was read from and saves it as a PNG
+    #[cfg(feature = "image-render")]
+    pub fn save_png<L: crate::LuminanceSource>(
+        &self,
+        source: &L,
+        path: impl AsRef<std::path::Path>,
+    ) -> Result<()> {
+        let path = path.as_ref();
+        self.render(source)
+            .save_with_format(path, image::ImageFormat::Png)
+            .map_err(|e| {
+                crate::Exceptions::illegal_argument_with(format!(
+                    "could not write '{}': {e}",
+                    path.display()
+                ))
+            })
+    }
+
+    // Drawn in order, so the finer layers end up on top
+    fn shapes(&self) -> Vec<Shape> {
+        let mut shapes = Vec::new();
+        for located in self.rejected() {
+            shapes.push(Shape::Polygon(located.corners.to_vec(), REJECTED_COLOR));
+        }
+        for symbol in &self.symbols {
+            let module_size = symbol.grid.as_ref().map_or(4.0, SamplingGrid::module_size);
+            if let Some(grid) = &symbol.grid {
+                let at = |x: u32, y: u32| grid.module_to_image(point(x as f32, y as f32));
+                for x in 0..=grid.width() {
+                    let line = (0..=grid.height()).map(|y| at(x, y)).collect();
+                    shapes.push(Shape::Polyline(line, GRID_COLOR));
+                }
+                for y in 0..=grid.height() {
+                    let line = (0..=grid.width()).map(|x| at(x, y)).collect();
+                    shapes.push(Shape::Polyline(line, GRID_COLOR));
+                }
+                for &(x, y) in &symbol.corrected_modules {
+                    let corners = vec![at(x, y), at(x + 1, y), at(x + 1, y + 1), at(x, y + 1)];
+                    shapes.push(Shape::Polygon(corners, CORRECTED_COLOR));
+                }
+            }
+            if symbol.points.len() > 2 {
+                shapes.push(Shape::Polygon(symbol.points.clone(), DECODED_COLOR));
+            } else {
+                shapes.push(Shape::Polyline(symbol.points.clone(), DECODED_COLOR));
+            }
+            for pattern in &symbol.patterns {
+                // finder patterns are 7 modules across, the others 5 or less
+                let modules = if pattern.kind == PatternKind::Finder {
+                    3.5
+                } else {
+                    2.5
+                };
+                shapes.push(Shape::Circle(
+                    pattern.center,
+                    modules * module_size,
+                    PATTERN_COLOR,
+                ));
+            }
+        }
+        for candidate in &self.candidates {
+            shapes.push(Shape::Cross(*candidate, 4.0, CANDIDATE_COLOR));
+        }
+        shapes
+    }
+}
+
+enum Shape {
+    Polygon(Vec<Point>, [u8; 3]),
+    Polyline(Vec<Point>, [u8; 3]),
+    Circle(Point, f32, [u8; 3]),
+    Cross(Point, f32, [u8; 3]),
+}
+
+/**
+ * Collects the candidates, located regions and decoded symbols of decodes run with the hints
+ * returned by [`DebugOverlay::install`]. Symbols are collected as they are decoded, so several
+ * decodes, or a reader of several symbols, add to the same layers.
+ */
+#[derive(Debug, Default)]
+pub struct DebugOverlay {
+    layers: Mutex<DebugLayers>,
+}
+
+impl DebugOverlay {
+    pub fn new() -> Arc<Self> {
+        Arc::default()
+    }
+
+    /**
+     * `hints` with the overlay as the `NeedResultPointCallback` and the `WitnessSink`. A
+     * callback or sink already in the hints is still called.
+     */
+    pub fn install(self: &Arc<Self>, hints: &DecodeHints) -> DecodeHints {
+        let mut hints = hints.clone();
+        let overlay = Arc::clone(self);
+        let previous = hints.NeedResultPointCallback.take();
+        hints.NeedResultPointCallback = Some(Arc::new(move |candidate| {
+            overlay.collected().candidates.push(candidate);
+            if let Some(previous) = &previous {
+                previous(candidate);
+            }
+        }));
+        hints.WitnessSink = Some(Arc::new(ChainedSink {
+            overlay: Arc::clone(self),
+            next: hints.WitnessSink.take(),
+        }));
+        hints
+    }
+
+    /// Locates the symbols in `image`, see [`crate::locate`], to show those that weren't decoded
+    pub fn locate<B: Binarizer>(
+        &self,
+        image: &mut BinaryBitmap<B>,
+        hints: &DecodeHints,
+    ) -> Result<()> {
+        let located = crate::locate(image, hints)?;
+        self.collected().located.extend(located);
+        Ok(())
+    }
+
+    /// What was collected so far
+    pub fn layers(&self) -> DebugLayers {
+        self.collected().clone()
+    }
+
+    // Stages are added as a whole, so a panic while the lock was held leaves none half written
+    fn collected(&self) -> MutexGuard<'_, DebugLayers> {
+        self.layers.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl WitnessSink for DebugOverlay {
+    fn detection(&self, witness: &DetectionWitness) {
+        self.collected().symbols.push(SymbolOverlay {
+            format: witness.format.clone(),
+            points: witness.points.clone(),
+            patterns: witness.patterns.clone(),
+            grid: None,
+            corrected_modules: Vec::new(),
+        });
+    }
+
+    fn sampling(&self, witness: &SamplingWitness) {
+        if let Some(symbol) = self.collected().symbols.last_mut() {
+            symbol.grid = Some(witness.grid.clone());
+        }
+    }
+
+    fn decoding(&self, witness: &CodewordWitness) {
+        if let Some(symbol) = self.collected().symbols.last_mut() {
+            if let (BarcodeFormat::QR_CODE, Some(grid)) = (&symbol.format, &symbol.grid) {
+                symbol.corrected_modules = qr_corrected_modules(grid.width(), witness);
+            }
+        }
+    }
+}
+
+// The overlay in front of a sink that was already in the hints
+struct ChainedSink {
+    overlay: Arc<DebugOverlay>,
+    next: Option<Arc<dyn WitnessSink>>,
+}
+
+impl WitnessSink for ChainedSink {
+    fn luminance(&self, witness: &LuminanceWitness) {
+        if let Some(next) = &self.next {
+            next.luminance(witness);
+        }
+    }
+
+    fn binarization(&self, witness: &BinarizationWitness) {
+        if let Some(next) = &self.next {
+            next.binarization(witness);
+        }
+    }
+
+    fn detection(&self, witness: &DetectionWitness) {
+        self.overlay.detection(witness);
+        if let Some(next) = &self.next {
+            next.detection(witness);
+        }
+    }
+
+    fn sampling(&self, witness: &SamplingWitness) {
+        self.overlay.sampling(witness);
+        if let Some(next) = &self.next {
+            next.sampling(witness);
+        }
+    }
+
+    fn decoding(&self, witness: &CodewordWitness) {
+        self.overlay.decoding(witness);
+        if let Some(next) = &self.next {
+            next.decoding(witness);
+        }
+    }
+
+    fn text_assembly(&self, witness: &TextAssemblyWitness) {
+        if let Some(next) = &self.next {
+            next.text_assembly(witness);
+        }
+    }
+
+    fn provenance(&self, provenance: &WitnessProvenance) {
+        if let Some(next) = &self.next {
+            next.provenance(provenance);
+        }
+    }
+}
+
+/**
+ * Locates the symbols in `image` and decodes it with a `MultiFormatReader`, returning the
+ * result with the layers collected along the way, whether it decoded or not.
+ */
+pub fn debug_decode<B: Binarizer>(
+    image: &mut BinaryBitmap<B>,
+    hints: &DecodeHints,
+) -> (Result<RXingResult>, DebugLayers) {
+    let overlay = DebugOverlay::new();
+    // regions are only drawn, so a failed search leaves the layers without them
+    let _ = overlay.locate(image, hints);
+    let result = MultiFormatReader::default().decode_with_hints(image, &overlay.install(hints));
+    (result, overlay.layers())
+}
+
+fn centroid(points: &[Point]) -> Point {
+    let sum = points
+        .iter()
+        .fold(point(0.0, 0.0), |sum, p| point(sum.x + p.x, sum.y + p.y));
+    let count = points.len().max(1) as f32;
+    point(sum.x / count, sum.y / count)
+}
+
+/**
+ * The modules of the codewords error correction fixed in a QR code of `dimension` modules,
+ * walking the codewords in the order they were placed, as `BitMatrixParser::readCodewords`
+ * reads them. Mirrored symbols were read transposed, so their modules are shown transposed.
+ */
+fn qr_corrected_modules(dimension: u32, codewords: &CodewordWitness) -> Vec<(u32, u32)> {
+    let Ok(function_pattern) = Version::getProvisionalVersionForDimension(dimension)
+        .and_then(|version| version.buildFunctionPattern())
+    else {
+        return Vec::new();
+    };
+
+    // The blocks are interleaved a codeword at a time, data then error correction, the
+    // longer blocks having one more data codeword at the end
+    let blocks = &codewords.blocks;
+    let max_data = blocks.iter().map(|b| b.num_data_codewords).max();
+    let max_ec = blocks
+        .iter()
+        .map(|b| b.received.len() - b.num_data_codewords)
+        .max();
+    let mut order = Vec::new();
+    for i in 0..max_data.unwrap_or(0) {
+        order.extend(
+            (0..blocks.len()).filter_map(|b| (i < blocks[b].num_data_codewords).then_some((b, i))),
+        );
+    }
+    for i in 0..max_ec.unwrap_or(0) {
+        order.extend((0..blocks.len()).map(|b| (b, blocks[b].num_data_codewords + i)));
+    }
+    let corrected: HashSet<usize> = order
+        .iter()
+        .enumerate()
+        .filter(|(_, (b, position))| {
+            let correction = &blocks[*b].correction;
+            correction.error_positions.contains(position)
+                || correction.erasure_positions.contains(position)
+        })
+        .map(|(index, _)| index)
+        .collect();
+
+    let mut modules = Vec::new();
+    let mut bits_read = 0;
+    let mut reading_up = true;
+    let mut j = dimension as i32 - 1;
+    while j > 0 {
+        // skip the vertical timing pattern
+        if j == 6 {
+            j -= 1;
+        }
+        for count in 0..dimension {
+            let y = if reading_up {
+                dimension - 1 - count
+            } else {
+                count
+            };
+            for col in 0..2 {
+                let x = (j - col) as u32;
+                if !function_pattern.get(x, y) {
+                    if corrected.contains(&(bits_read / 8)) {
+                        modules.push((x, y));
+                    }
+                    bits_read += 1;
+                }
+            }
+        }
+        reading_up = !reading_up;
+        j -= 2;
+    }
+    modules
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        common::{BitMatrix, HybridBinarizer},
+        BarcodeFormat, BinaryBitmap, DecodeHints, EncodeHints, Luma8LuminanceSource,
+        MultiFormatWriter, Writer,
+    };
+
+    use super::debug_decode;
+
+    fn image(
+        matrix: &BitMatrix,
+        module: u32,
+    ) -> BinaryBitmap<HybridBinarizer<Luma8LuminanceSource>> {
+        let quiet_zone = 4 * module;
+        let size = matrix.getWidth() * module + 2 * quiet_zone;
+        let mut luma = vec![255u8; (size * size) as usize];
+        for y in 0..matrix.getHeight() * module {
+            for x in 0..matrix.getWidth() * module {
+                if matrix.get(x / module, y / module) {
+                    luma[((y + quiet_zone) * size + x + quiet_zone) as usize] = 0;
+                }
+            }
+        }
+        BinaryBitmap::new(HybridBinarizer::new(Luma8LuminanceSource::new(
+            luma, size, size,
+        )))
+    }
+
+    fn qr_code(contents: &str) -> BitMatrix {
+        let hints = EncodeHints {
+            QrVersion: Some("2".to_owned()),
+            Margin: Some("0".to_owned()),
+            ..Default::default()
+        };
+        MultiFormatWriter
+            .encode_with_hints(contents, &BarcodeFormat::QR_CODE, 0, 0, &hints)
+            .expect("encode")
+    }
+
+    #[test]
+    fn test_corrected_modules() {
+        let mut matrix = qr_code("corrected");
+        // the first module of the first codeword, in the bottom right corner
+        matrix.flip_coords(24, 24);
+        let (result, layers) = debug_decode(&mut image(&matrix, 6), &DecodeHints::default());
+        assert_eq!("corrected", result.expect("decode").getText());
+        assert!(layers.candidates.len() >= 3);
+        assert!(layers.rejected().is_empty());
+        let symbol = &layers.symbols[0];
+        assert_eq!(Some(25), symbol.grid.as_ref().map(|grid| grid.width()));
+        assert_eq!(8, symbol.corrected_modules.len());
+        assert!(symbol.corrected_modules.contains(&(24, 24)));
+
+        let svg = layers.to_svg(198, 198, None);
+        assert_eq!(8, svg.matches("stroke=\"#ff00ff\"").count());
+        assert!(svg.contains("<circle"));
+    }
+
+    #[test]
+    fn test_rejected() {
+        let mut matrix = qr_code("too damaged to read");
+        // wipe out the data right of the finder patterns
+        for y in 9..25 {
+            for x in 9..25 {
+                if (x + y) % 3 == 0 {
+                    matrix.flip_coords(x, y);
+                }
+            }
+        }
+        let (result, layers) = debug_decode(&mut image(&matrix, 6), &DecodeHints::default());
+        assert!(result.is_err());
+        assert!(layers.symbols.is_empty());
+        assert!(layers.candidates.len() >= 3);
+        assert_eq!(1, layers.rejected().len());
+        assert_eq!(BarcodeFormat::QR_CODE, layers.rejected()[0].format);
+        assert!(layers.to_svg(198, 198, None).contains("stroke=\"#dc143c\""));
+    }
+}
@@ -0,0 +1,279 @@
+/*
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    common::{CodewordWitness, PatternKind, SamplingGrid},
+    point, BarcodeFormat, Point, RXingResult, RXingResultMetadataType, RXingResultMetadataValue,
+};
+
+/// The finder pattern fit error, in modules, at which the fit no longer adds to the score
+const MAX_FIT_ERROR: f32 = 2.0;
+
+/**
+ * How sure a reader is of a 2D symbol it read, found in the `CONFIDENCE` metadata of a result.
+ * A clean, flat symbol scores 1, and each measure of damage or distortion lowers the score, so
+ * an application can reject marginal reads with a threshold such as 0.5.
+ *
+ * Each measure is `None` when the reader did not record what it is computed from: the
+ * codewords for the correction load, and the sampling grid for the geometry.
+ */
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DecodeConfidence {
+    /// From 0 for an unreliable read to 1, the product of the factors of the measures below
+    pub score: f32,
+    /**
+     * The largest share of the codewords an error correction block can correct that were
+     * corrected, from 0 for an undamaged symbol to 1 for one that was barely read
+     */
+    pub correction_load: Option<f32>,
+    /**
+     * The mean distance in modules between the finder patterns the detector found and where
+     * the sampling grid puts them
+     */
+    pub finder_fit_error: Option<f32>,
+    /**
+     * The smallest module pitch over the symbol as a share of the largest, 1 for a symbol seen
+     * head on and less the more it is tilted away from the camera
+     */
+    pub module_size_consistency: Option<f32>,
+}
+
+impl Eq for DecodeConfidence {}
+
+impl DecodeConfidence {
+    /// The confidence in a result from its `CODEWORDS` and `SAMPLING_GRID` metadata, `None` if
+    /// it has neither
+    pub fn from_result(result: &RXingResult) -> Option<Self> {
+        let metadata = result.getRXingResultMetadata();
+        let codewords = match metadata.get(&RXingResultMetadataType::CODEWORDS) {
+            Some(RXingResultMetadataValue::Codewords(codewords)) => Some(codewords),
+            _ => None,
+        };
+        let grid = match metadata.get(&RXingResultMetadataType::SAMPLING_GRID) {
+            Some(RXingResultMetadataValue::SamplingGrid(grid)) => Some(grid),
+            _ => None,
+        };
+        if codewords.is_none() && grid.is_none() {
+            return None;
+        }
+
+        let correction_load = codewords.and_then(correction_load);
+        let finder_fit_error =
+            grid.and_then(|grid| finder_fit_error(grid, result.getBarcodeFormat()));
+        let module_size_consistency = grid.map(module_size_consistency);
+        let score = (1.0 - correction_load.unwrap_or(0.0).min(1.0))
+            * (1.0 - finder_fit_error.unwrap_or(0.0).min(MAX_FIT_ERROR) / MAX_FIT_ERROR)
+            * module_size_consistency.unwrap_or(1.0);
+
+        Some(Self {
+            score,
+            correction_load,
+            finder_fit_error,
+            module_size_consistency,
+        })
+    }
+}
+
+/// Adds `CONFIDENCE` metadata to `result` if it has the metadata to compute it from
+pub(crate) fn put_confidence(result: &mut RXingResult) {
+    if let Some(confidence) = DecodeConfidence::from_result(result) {
+        result.putMetadata(
+            RXingResultMetadataType::CONFIDENCE,
+            RXingResultMetadataValue::Confidence(confidence),
+        );
+    }
+}
+
+fn correction_load(codewords: &CodewordWitness) -> Option<f32> {
+    codewords
+        .blocks
+        .iter()
+        .filter_map(|block| {
+            let ec_codewords = block.received.len() - block.num_data_codewords;
+            // an error costs two error-correction codewords, an erasure one
+            let used =
+                2 * block.correction.errors_corrected() + block.correction.erasures_corrected();
+            (ec_codewords > 1).then(|| used as f32 / (ec_codewords / 2 * 2) as f32)
+        })
+        .reduce(f32::max)
+}
+
+/// The centers of the finder patterns of a symbol of `format`, in module space
+fn finder_centers(grid: &SamplingGrid, format: &BarcodeFormat) -> Vec<Point> {
+    let (width, height) = (grid.width() as f32, grid.height() as f32);
+    match format {
+        BarcodeFormat::QR_CODE => vec![
+            point(3.5, 3.5),
+            point(width - 3.5, 3.5),
+            point(3.5, height - 3.5),
+        ],
+        BarcodeFormat::MICRO_QR_CODE => vec![point(3.5, 3.5)],
+        BarcodeFormat::AZTEC => vec![point(width / 2.0, height / 2.0)],
+        _ => Vec::new(),
+    }
+}
+
+fn finder_fit_error(grid: &SamplingGrid, format: &BarcodeFormat) -> Option<f32> {
+    let module_size = grid.module_size();
+    let expected: Vec<Point> = finder_centers(grid, format)
+        .into_iter()
+        .map(|center| grid.module_to_image(center))
+        .collect();
+    let found: Vec<Point> = grid
+        .patterns()
+        .iter()
+        .filter(|pattern| pattern.kind == PatternKind::Finder)
+        .map(|pattern| pattern.center)
+        .collect();
+    if expected.is_empty() || found.len() != expected.len() || module_size <= 0.0 {
+        return None;
+    }
+
+    // the patterns of a mirrored symbol are found in another order, so each is matched with
+    // the nearest place a finder pattern belongs
+    let total: f32 = found
+        .iter()
+        .map(|&center| {
+            expected
+                .iter()
+                .map(|&place| Point::distance(center, place))
+                .fold(f32::MAX, f32::min)
+        })
+        .sum();
+    Some(total / found.len() as f32 / module_size)
+}
+
+fn module_size_consistency(grid: &SamplingGrid) -> f32 {
+    let (width, height) = (grid.width() as f32, grid.height() as f32);
+    if width < 2.0 || height < 2.0 {
+        return 1.0;
+    }
+    let pitch = |x: f32, y: f32| {
+        let center = grid.module_to_image(point(x, y));
+        let right = grid.module_to_image(point(x + 1.0, y));
+        let below = grid.module_to_image(point(x, y + 1.0));
+        (Point::distance(center, right) + Point::distance(center, below)) / 2.0
+    };
+    let pitches = [
+        pitch(0.5, 0.5),
+        pitch(width - 1.5, 0.5),
+        pitch(0.5, height - 1.5),
+        pitch(width - 1.5, height - 1.5),
+        pitch(width / 2.0, height / 2.0),
+    ];
+    let smallest = pitches.iter().copied().fold(f32::MAX, f32::min);
+    let largest = pitches.iter().copied().fold(0.0, f32::max);
+    if largest > 0.0 {
+        smallest / largest
+    } else {
+        1.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::{
+        common::{PerspectiveTransform, Quadrilateral, SamplingGrid},
+        helpers, point,
+        test_fixtures::scaled_luma,
+        BarcodeFormat, DecodeConfidence, DecodeHints, EncodeHints, MultiFormatWriter,
+        RXingResultMetadataType, RXingResultMetadataValue, Writer,
+    };
+
+    use super::module_size_consistency;
+
+    fn confidence(flips: &[(u32, u32)]) -> DecodeConfidence {
+        let hints = EncodeHints {
+            ErrorCorrection: Some("L".to_owned()),
+            Margin: Some("4".to_owned()),
+            ..Default::default()
+        };
+        let mut matrix = MultiFormatWriter
+            .encode_with_hints("confidence", &BarcodeFormat::QR_CODE, 0, 0, &hints)
+            .expect("encode");
+        for &(x, y) in flips {
+            matrix.flip_coords(x, y);
+        }
+        // 4 pixels to a module so the finder patterns are located to a fraction of a module
+        let (luma, width, height) = scaled_luma(&matrix, 4, 0, 255);
+        let mut hints = DecodeHints {
+            PossibleFormats: Some(HashSet::from([BarcodeFormat::QR_CODE])),
+            ..Default::default()
+        };
+        let result = helpers::detect_in_luma_with_hints(
+            luma,
+            width,
+            height,
+            Some(BarcodeFormat::QR_CODE),
+            &mut hints,
+        )
+        .expect("decode");
+        match result
+            .getRXingResultMetadata()
+            .get(&RXingResultMetadataType::CONFIDENCE)
+        {
+            Some(RXingResultMetadataValue::Confidence(confidence)) => *confidence,
+            other => panic!("no confidence: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_clean_symbol() {
+        let confidence = confidence(&[]);
+        assert_eq!(Some(0.0), confidence.correction_load);
+        assert!(confidence.finder_fit_error.unwrap() < 0.25);
+        assert!(confidence.module_size_consistency.unwrap() > 0.99);
+        assert!(confidence.score > 0.85);
+    }
+
+    #[test]
+    fn test_corrected_symbol() {
+        // two of the 19 data and 7 error correction codewords of version 1-L, which corrects
+        // three of them
+        let clean = confidence(&[]);
+        let damaged = confidence(&[(24, 24), (24, 16)]);
+        assert_eq!(Some(2.0 / 3.0), damaged.correction_load);
+        assert!(damaged.score < clean.score / 2.0);
+    }
+
+    #[test]
+    fn test_module_size_consistency() {
+        let flat = SamplingGrid::axis_aligned(21, 21, 10.0, 10.0, 4.0).unwrap();
+        assert_eq!(1.0, module_size_consistency(&flat));
+
+        // the bottom edge is half as wide as the top
+        let tilted = SamplingGrid::with_transform(
+            21,
+            21,
+            PerspectiveTransform::quadrilateralToQuadrilateral(
+                Quadrilateral::rectangle_from_xy(0.0, 21.0, 0.0, 21.0, Some(0.0)),
+                Quadrilateral([
+                    point(0.0, 0.0),
+                    point(84.0, 0.0),
+                    point(63.0, 84.0),
+                    point(21.0, 84.0),
+                ]),
+            )
+            .unwrap(),
+        );
+        let consistency = module_size_consistency(&tilted);
+        assert!(consistency > 0.3 && consistency < 0.8, "{consistency}");
+    }
+}
@@ -16,9 +16,16 @@
 
 //package com.google.zxing;
 
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
 
-use crate::{BarcodeFormat, PointCallback};
+use crate::{
+    common::GridSampling,
+    oned::{CheckDigitPolicy, MSIChecksum, RowSampling},
+    BarcodeFormat, CancellationToken, PointCallback, Strictness, WitnessRecorder, WitnessSink,
+};
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
@@ -60,6 +67,7 @@ pub enum DecodeHintType {
 
     /**
      * Specifies what character encoding to use when decoding, where applicable (type String)
+     * Byte data without an ECI is decoded in this encoding instead of a guessed one.
      */
     CHARACTER_SET,
 
@@ -99,7 +107,8 @@ pub enum DecodeHintType {
      * Maps to an {@code int[]} of the allowed extension lengths, for example [2], [5], or [2, 5].
      * If it is optional to have an extension, do not set this hint. If this is set,
      * and a UPC or EAN barcode is found but an extension is not, then no result will be returned
-     * at all.
+     * at all. The text of the extension is returned in the `UPC_EAN_EXTENSION` metadata and
+     * where it is in the row in `UPC_EAN_EXTENSION_POSITION`.
      */
     ALLOWED_EAN_EXTENSIONS,
 
@@ -126,6 +135,195 @@ pub enum DecodeHintType {
      * Will translate the ASCII values parsed by the Telepen reader into the Telepen Numeric form.
      */
     TELEPEN_AS_NUMERIC,
+
+    /**
+     * How to treat the last character of a Code 39 barcode, which may be a mod 43 check digit.
+     * Takes precedence over {@link #ASSUME_CODE_39_CHECK_DIGIT}, which is the same as
+     * `CheckDigitPolicy::Require`. Maps to a `CheckDigitPolicy`.
+     */
+    CODE_39_CHECK_DIGIT,
+
+    /**
+     * How to treat the last data character of a Codabar barcode, which may be a mod 16 check
+     * digit over all the characters, start and stop included. Maps to a `CheckDigitPolicy`.
+     */
+    CODABAR_CHECK_DIGIT,
+
+    /**
+     * How to treat the last digit of an ITF barcode, which may be a mod 10 check digit as in
+     * GS1 keys. {@link #ALLOWED_LENGTHS} apply to the digits before any is removed. Maps to a
+     * `CheckDigitPolicy`.
+     */
+    ITF_CHECK_DIGIT,
+
+    /**
+     * How closely symbols must follow their specification to be read: quiet zones and bar
+     * widths as the ISO specifications require, as by default, or relaxed for damaged media.
+     * Maps to a `Strictness`.
+     */
+    STRICTNESS,
+
+    /**
+     * If true, return UPC-E barcodes as the 12 digits of the equivalent UPC-A code, with the
+     * zeros UPC-E suppresses put back (see `convertUPCEtoUPCA`). The format is still reported
+     * as UPC-E. Doesn't matter what it maps to; use {@link Boolean#TRUE}.
+     */
+    EXPAND_UPC_E,
+
+    /**
+     * Lengths of ITF barcodes to accept, in digits, for example [14] for ITF-14 only. Unlike
+     * {@link #ALLOWED_LENGTHS}, longer barcodes are rejected too, and a row is rejected before
+     * its digits are decoded if it is too narrow or too wide for all of them. Takes the place of
+     * {@link #ALLOWED_LENGTHS} for ITF, and applies to the digits before any check digit is
+     * removed. Maps to an {@code int[]}.
+     */
+    ITF_LENGTHS,
+
+    /**
+     * If true, decode Code 39 barcodes as Full ASCII Code 39, in which pairs of characters such
+     * as "+A" stand for the ASCII characters Code 39 lacks, and return the characters as read in
+     * the `RAW_TEXT` metadata. If false, return the characters as read. Overrides how the reader
+     * was created. Doesn't matter what it maps to; use {@link Boolean#TRUE}.
+     */
+    CODE_39_EXTENDED_MODE,
+
+    /**
+     * Removes all nondeterminism from the decode, so the same image and hints always produce
+     * the same result and the same witness (see `PipelineWitness`). The formats of
+     * {@link #POSSIBLE_FORMATS} are tried in a fixed order rather than the iteration order of
+     * the set, which differs between runs; this is the order they are tried in without the
+     * hint: QR Code, Micro QR Code, rMQR, Data Matrix, Aztec, PDF417, MaxiCode. No reader
+     * gives up early based on elapsed time, with or without this hint. Doesn't matter what it
+     * maps to; use {@link Boolean#TRUE}.
+     */
+    DETERMINISTIC,
+
+    /**
+     * The caller wants the intermediate stages of the decode (see `PipelineWitness`) streamed
+     * into a sink. The reader that decodes the barcode hands each stage to the sink as soon as
+     * the decode succeeds, in pipeline order; readers that fail send nothing. Maps to a
+     * {@link WitnessSink}.
+     */
+    WITNESS_SINK,
+
+    /**
+     * The caller wants the intermediate stages of the decode recorded while the reader runs.
+     * The reader hands each stage to the recorder as it reaches it, including the stages of
+     * attempts that fail later on. Maps to a {@link WitnessRecorder}.
+     */
+    WITNESS_RECORDER,
+
+    /**
+     * A token another thread can cancel to abandon the decode. The readers check it between
+     * the rows they scan and the formats they try, and fail with a `CancelledException` once
+     * it is cancelled. Maps to a `CancellationToken`.
+     */
+    CANCELLATION,
+
+    /**
+     * The caller wants the stages streamed into the {@link #WITNESS_SINK} to hold only the
+     * region of the image around the symbol, extended by this many pixels on every side, rather
+     * than the full frame. See `PipelineWitness::crop_to_symbol`. Maps to a {@link Integer}.
+     */
+    WITNESS_CROP_MARGIN,
+
+    /**
+     * Samples the modules of QR codes, Data Matrix and Aztec symbols without floating point
+     * arithmetic. Once the detector has fitted its sampling grid, the modules are sampled
+     * again with a `FixedPointGridSampler`, whose integer arithmetic gives the same modules
+     * on every platform, and the sampling stage streamed into the {@link #WITNESS_SINK}
+     * records the fixed-point transforms. Doesn't matter what it maps to; use {@link Boolean#TRUE}.
+     */
+    FIXED_POINT_SAMPLING,
+
+    /**
+     * If true, read Pharmacode as two-track Pharmacode, whose bars stand for digits by
+     * reaching the upper track, the lower track or both. Otherwise it is read as one-track
+     * Pharmacode of narrow and wide bars. Doesn't matter what it maps to; use
+     * {@link Boolean#TRUE}.
+     */
+    PHARMACODE_TWO_TRACK,
+
+    /**
+     * The least number of bars of Pharmacode to accept, 2 if not given. Pharmacode has
+     * neither start and stop patterns nor a check digit, so raising it guards against reading
+     * a few bars of anything else as a barcode. Maps to a {@link Integer}.
+     */
+    PHARMACODE_MIN_BARS,
+
+    /**
+     * The greatest number of bars of Pharmacode to accept, 16 if not given. Maps to a
+     * {@link Integer}.
+     */
+    PHARMACODE_MAX_BARS,
+
+    /**
+     * How to treat the check digits of an MSI Plessey barcode, whose kind is given by
+     * {@link #MSI_CHECKSUM}. Maps to a `CheckDigitPolicy`.
+     */
+    MSI_CHECK_DIGIT,
+
+    /**
+     * The check digits MSI Plessey barcodes carry, mod 10 if not given. Only used if
+     * {@link #MSI_CHECK_DIGIT} checks or removes them. Maps to an `MSIChecksum`.
+     */
+    MSI_CHECKSUM,
+
+    /**
+     * If true, a `PyramidReader` also looks for symbols in copies of the image scaled down
+     * by its downscale factor, smallest first, before the full resolution image. Symbols
+     * whose modules are many pixels wide, or blurry or noisy, are often found more easily
+     * at a lower resolution. Doesn't matter what it maps to; use {@link Boolean#TRUE}.
+     */
+    TRY_DOWNSCALE,
+
+    /**
+     * Which rows 1D readers scan, and how many symbols `OneDReader::decode_rows` collects.
+     * Scanning fewer rows, from a different offset each frame, speeds up reading video.
+     * Maps to a `RowSampling`.
+     */
+    ONE_D_ROW_SAMPLING,
+
+    /**
+     * If true, a `MultiFormatReader` that finds nothing tries again on copies of the image
+     * cleaned up by the filters of `preprocess::RETRY_CHAIN`, one after another: sharpened,
+     * denoised, and with gaps in the bars closed. This helps with faint, low-contrast prints
+     * such as thermal labels, at the cost of slower failures. Doesn't matter what it maps to;
+     * use {@link Boolean#TRUE}.
+     */
+    TRY_PREPROCESSING,
+
+    /**
+     * If true, the QR Code reader also accepts finder patterns whose outer ring runs straight
+     * into dark content next to the symbol, as with codes printed without a quiet zone, framed
+     * by a border or cropped close. Makes false positives in busy images a little more likely.
+     * Doesn't matter what it maps to; use {@link Boolean#TRUE}.
+     */
+    ASSUME_NO_QUIET_ZONE,
+
+    /**
+     * If true, the QR Code reader samples a symbol that runs off the edge of the image instead
+     * of giving up, and leaves the codewords of the modules outside the image to the error
+     * correction as erasures, which costs half as much of it as errors do. Doesn't matter what
+     * it maps to; use {@link Boolean#TRUE}.
+     */
+    ALLOW_PARTIAL_SYMBOL,
+
+    /**
+     * How the QR Code, Data Matrix and Aztec readers read the modules of a symbol from the
+     * image once its detector has fitted the sampling grid, see `GridSampling`.
+     * `Supersample` helps with symbols whose modules are under 2 pixels wide. The
+     * `FixedPointSampling` hint takes precedence. Maps to a `GridSampling`.
+     */
+    GRID_SAMPLING,
+
+    /**
+     * If true, the QR Code and Data Matrix readers retry a symbol that fails to decode with
+     * its sampling grid bent around a cylinder, fitted to the finder and timing patterns, for
+     * symbols printed on bottles and cables. The result then has no `SAMPLING_GRID`, which
+     * would be flat. Doesn't matter what it maps to; use {@link Boolean#TRUE}.
+     */
+    TRY_CURVED,
     /*
      * Data type the hint is expecting.
      * Among the possible values the {@link Void} stands out as being used for
@@ -174,6 +372,7 @@ pub enum DecodeHintValue {
 
     /**
      * Specifies what character encoding to use when decoding, where applicable (type String)
+     * Byte data without an ECI is decoded in this encoding instead of a guessed one.
      */
     CharacterSet(String),
 
@@ -214,7 +413,8 @@ pub enum DecodeHintValue {
      * Maps to an {@code int[]} of the allowed extension lengths, for example [2], [5], or [2, 5].
      * If it is optional to have an extension, do not set this hint. If this is set,
      * and a UPC or EAN barcode is found but an extension is not, then no result will be returned
-     * at all.
+     * at all. The text of the extension is returned in the `UPC_EAN_EXTENSION` metadata and
+     * where it is in the row in `UPC_EAN_EXTENSION_POSITION`.
      */
     AllowedEanExtensions(Vec<u32>),
 
@@ -241,6 +441,198 @@ pub enum DecodeHintValue {
      * Translate the ASCII values parsed by the Telepen reader into the Telepen Numeric form; use {@link Boolean#TRUE}.
      */
     TelepenAsNumeric(bool),
+
+    /**
+     * How to treat the last character of a Code 39 barcode, which may be a mod 43 check digit.
+     * Takes precedence over {@link #ASSUME_CODE_39_CHECK_DIGIT}, which is the same as
+     * `CheckDigitPolicy::Require`. Maps to a `CheckDigitPolicy`.
+     */
+    Code39CheckDigit(CheckDigitPolicy),
+
+    /**
+     * How to treat the last data character of a Codabar barcode, which may be a mod 16 check
+     * digit over all the characters, start and stop included. Maps to a `CheckDigitPolicy`.
+     */
+    CodabarCheckDigit(CheckDigitPolicy),
+
+    /**
+     * How to treat the last digit of an ITF barcode, which may be a mod 10 check digit as in
+     * GS1 keys. {@link #ALLOWED_LENGTHS} apply to the digits before any is removed. Maps to a
+     * `CheckDigitPolicy`.
+     */
+    ITFCheckDigit(CheckDigitPolicy),
+
+    /**
+     * How closely symbols must follow their specification to be read: quiet zones and bar
+     * widths as the ISO specifications require, as by default, or relaxed for damaged media.
+     * Maps to a `Strictness`.
+     */
+    Strictness(Strictness),
+
+    /**
+     * If true, return UPC-E barcodes as the 12 digits of the equivalent UPC-A code, with the
+     * zeros UPC-E suppresses put back (see `convertUPCEtoUPCA`). The format is still reported
+     * as UPC-E. Doesn't matter what it maps to; use {@link Boolean#TRUE}.
+     */
+    ExpandUPCE(bool),
+
+    /**
+     * Lengths of ITF barcodes to accept, in digits, for example [14] for ITF-14 only. Unlike
+     * {@link #ALLOWED_LENGTHS}, longer barcodes are rejected too, and a row is rejected before
+     * its digits are decoded if it is too narrow or too wide for all of them. Takes the place of
+     * {@link #ALLOWED_LENGTHS} for ITF, and applies to the digits before any check digit is
+     * removed. Maps to an {@code int[]}.
+     */
+    ITFLengths(Vec<u32>),
+
+    /**
+     * If true, decode Code 39 barcodes as Full ASCII Code 39, in which pairs of characters such
+     * as "+A" stand for the ASCII characters Code 39 lacks, and return the characters as read in
+     * the `RAW_TEXT` metadata. If false, return the characters as read. Overrides how the reader
+     * was created. Doesn't matter what it maps to; use {@link Boolean#TRUE}.
+     */
+    Code39ExtendedMode(bool),
+
+    /**
+     * Removes all nondeterminism from the decode, so the same image and hints always produce
+     * the same result and the same witness (see `PipelineWitness`). The formats of
+     * {@link #POSSIBLE_FORMATS} are tried in a fixed order rather than the iteration order of
+     * the set, which differs between runs; this is the order they are tried in without the
+     * hint: QR Code, Micro QR Code, rMQR, Data Matrix, Aztec, PDF417, MaxiCode. No reader
+     * gives up early based on elapsed time, with or without this hint. Doesn't matter what it
+     * maps to; use {@link Boolean#TRUE}.
+     */
+    Deterministic(bool),
+
+    /**
+     * The caller wants the intermediate stages of the decode (see `PipelineWitness`) streamed
+     * into a sink. The reader that decodes the barcode hands each stage to the sink as soon as
+     * the decode succeeds, in pipeline order; readers that fail send nothing. Maps to a
+     * {@link WitnessSink}.
+     */
+    #[cfg_attr(feature = "serde", serde(skip_serializing, skip_deserializing))]
+    WitnessSink(Arc<dyn WitnessSink>),
+
+    /**
+     * The caller wants the intermediate stages of the decode recorded while the reader runs.
+     * The reader hands each stage to the recorder as it reaches it, including the stages of
+     * attempts that fail later on. Maps to a {@link WitnessRecorder}.
+     */
+    #[cfg_attr(feature = "serde", serde(skip_serializing, skip_deserializing))]
+    WitnessRecorder(Arc<dyn WitnessRecorder>),
+
+    /**
+     * A token another thread can cancel to abandon the decode. The readers check it between
+     * the rows they scan and the formats they try, and fail with a `CancelledException` once
+     * it is cancelled. Maps to a `CancellationToken`.
+     */
+    #[cfg_attr(feature = "serde", serde(skip_serializing, skip_deserializing))]
+    Cancellation(CancellationToken),
+
+    /**
+     * The caller wants the stages streamed into the {@link #WITNESS_SINK} to hold only the
+     * region of the image around the symbol, extended by this many pixels on every side, rather
+     * than the full frame. See `PipelineWitness::crop_to_symbol`. Maps to a {@link Integer}.
+     */
+    WitnessCropMargin(u32),
+
+    /**
+     * Samples the modules of QR codes, Data Matrix and Aztec symbols without floating point
+     * arithmetic. Once the detector has fitted its sampling grid, the modules are sampled
+     * again with a `FixedPointGridSampler`, whose integer arithmetic gives the same modules
+     * on every platform, and the sampling stage streamed into the {@link #WITNESS_SINK}
+     * records the fixed-point transforms. Doesn't matter what it maps to; use {@link Boolean#TRUE}.
+     */
+    FixedPointSampling(bool),
+
+    /**
+     * If true, read Pharmacode as two-track Pharmacode, whose bars stand for digits by
+     * reaching the upper track, the lower track or both. Otherwise it is read as one-track
+     * Pharmacode of narrow and wide bars. Doesn't matter what it maps to; use
+     * {@link Boolean#TRUE}.
+     */
+    PharmacodeTwoTrack(bool),
+
+    /**
+     * The least number of bars of Pharmacode to accept, 2 if not given. Pharmacode has
+     * neither start and stop patterns nor a check digit, so raising it guards against reading
+     * a few bars of anything else as a barcode. Maps to a {@link Integer}.
+     */
+    PharmacodeMinBars(u32),
+
+    /**
+     * The greatest number of bars of Pharmacode to accept, 16 if not given. Maps to a
+     * {@link Integer}.
+     */
+    PharmacodeMaxBars(u32),
+
+    /**
+     * How to treat the check digits of an MSI Plessey barcode, whose kind is given by
+     * {@link #MSI_CHECKSUM}. Maps to a `CheckDigitPolicy`.
+     */
+    MSICheckDigit(CheckDigitPolicy),
+
+    /**
+     * The check digits MSI Plessey barcodes carry, mod 10 if not given. Only used if
+     * {@link #MSI_CHECK_DIGIT} checks or removes them. Maps to an `MSIChecksum`.
+     */
+    MSIChecksum(MSIChecksum),
+
+    /**
+     * If true, a `PyramidReader` also looks for symbols in copies of the image scaled down
+     * by its downscale factor, smallest first, before the full resolution image. Symbols
+     * whose modules are many pixels wide, or blurry or noisy, are often found more easily
+     * at a lower resolution. Doesn't matter what it maps to; use {@link Boolean#TRUE}.
+     */
+    TryDownscale(bool),
+
+    /**
+     * Which rows 1D readers scan, and how many symbols `OneDReader::decode_rows` collects.
+     * Scanning fewer rows, from a different offset each frame, speeds up reading video.
+     * Maps to a `RowSampling`.
+     */
+    OneDRowSampling(RowSampling),
+
+    /**
+     * If true, a `MultiFormatReader` that finds nothing tries again on copies of the image
+     * cleaned up by the filters of `preprocess::RETRY_CHAIN`, one after another: sharpened,
+     * denoised, and with gaps in the bars closed. This helps with faint, low-contrast prints
+     * such as thermal labels, at the cost of slower failures. Doesn't matter what it maps to;
+     * use {@link Boolean#TRUE}.
+     */
+    TryPreprocessing(bool),
+
+    /**
+     * If true, the QR Code reader also accepts finder patterns whose outer ring runs straight
+     * into dark content next to the symbol, as with codes printed without a quiet zone, framed
+     * by a border or cropped close. Makes false positives in busy images a little more likely.
+     * Doesn't matter what it maps to; use {@link Boolean#TRUE}.
+     */
+    AssumeNoQuietZone(bool),
+
+    /**
+     * If true, the QR Code reader samples a symbol that runs off the edge of the image instead
+     * of giving up, and leaves the codewords of the modules outside the image to the error
+     * correction as erasures, which costs half as much of it as errors do. Doesn't matter what
+     * it maps to; use {@link Boolean#TRUE}.
+     */
+    AllowPartialSymbol(bool),
+
+    /**
+     * How the QR Code, Data Matrix and Aztec readers read the modules of a symbol from the
+     * image once its detector has fitted the sampling grid, see `GridSampling`.
+     * `Supersample` helps with symbols whose modules are under 2 pixels wide. The
+     * `FixedPointSampling` hint takes precedence. Maps to a `GridSampling`.
+     */
+    GridSampling(GridSampling),
+
+    /**
+     * If true, the QR Code and Data Matrix readers retry a symbol that fails to decode with
+     * its sampling grid bent around a cylinder, fitted to the finder and timing patterns, for
+     * symbols printed on bottles and cables. The result then has no `SAMPLING_GRID`, which
+     * would be flat. Doesn't matter what it maps to; use {@link Boolean#TRUE}.
+     */
+    TryCurved(bool),
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -271,6 +663,7 @@ pub struct DecodeHints {
 
     /**
      * Specifies what character encoding to use when decoding, where applicable (type String)
+     * Byte data without an ECI is decoded in this encoding instead of a guessed one.
      */
     pub CharacterSet: Option<String>,
 
@@ -311,14 +704,19 @@ pub struct DecodeHints {
      * Maps to an {@code int[]} of the allowed extension lengths, for example [2], [5], or [2, 5].
      * If it is optional to have an extension, do not set this hint. If this is set,
      * and a UPC or EAN barcode is found but an extension is not, then no result will be returned
-     * at all.
+     * at all. The text of the extension is returned in the `UPC_EAN_EXTENSION` metadata and
+     * where it is in the row in `UPC_EAN_EXTENSION_POSITION`.
      */
     pub AllowedEanExtensions: Option<Vec<u32>>,
 
     /**
      * If true, also tries to decode as inverted image. All configured decoders are simply called a
-     * second time with an inverted image. Doesn't matter what it maps to; use {@link Boolean#TRUE}.
+     * second time with an inverted image, for light symbols on a dark background such as codes
+     * laser etched into metal. Results found this way have `IS_INVERTED` metadata. Also accepted
+     * as `TryInvert`, its name in ZXing-C++, when deserialized. Doesn't matter what it maps to;
+     * use {@link Boolean#TRUE}.
      */
+    #[cfg_attr(feature = "serde", serde(alias = "TryInvert"))]
     pub AlsoInverted: Option<bool>,
 
     /**
@@ -338,8 +736,201 @@ pub struct DecodeHints {
      * Translate the ASCII values parsed by the Telepen reader into the Telepen Numeric form; use {@link Boolean#TRUE}.
      */
     pub TelepenAsNumeric: Option<bool>,
+
+    /**
+     * How to treat the last character of a Code 39 barcode, which may be a mod 43 check digit.
+     * Takes precedence over {@link #ASSUME_CODE_39_CHECK_DIGIT}, which is the same as
+     * `CheckDigitPolicy::Require`. Maps to a `CheckDigitPolicy`.
+     */
+    pub Code39CheckDigit: Option<CheckDigitPolicy>,
+
+    /**
+     * How to treat the last data character of a Codabar barcode, which may be a mod 16 check
+     * digit over all the characters, start and stop included. Maps to a `CheckDigitPolicy`.
+     */
+    pub CodabarCheckDigit: Option<CheckDigitPolicy>,
+
+    /**
+     * How to treat the last digit of an ITF barcode, which may be a mod 10 check digit as in
+     * GS1 keys. {@link #ALLOWED_LENGTHS} apply to the digits before any is removed. Maps to a
+     * `CheckDigitPolicy`.
+     */
+    pub ITFCheckDigit: Option<CheckDigitPolicy>,
+
+    /**
+     * How closely symbols must follow their specification to be read: quiet zones and bar
+     * widths as the ISO specifications require, as by default, or relaxed for damaged media.
+     * Maps to a `Strictness`.
+     */
+    pub Strictness: Option<Strictness>,
+
+    /**
+     * If true, return UPC-E barcodes as the 12 digits of the equivalent UPC-A code, with the
+     * zeros UPC-E suppresses put back (see `convertUPCEtoUPCA`). The format is still reported
+     * as UPC-E. Doesn't matter what it maps to; use {@link Boolean#TRUE}.
+     */
+    pub ExpandUPCE: Option<bool>,
+
+    /**
+     * Lengths of ITF barcodes to accept, in digits, for example [14] for ITF-14 only. Unlike
+     * {@link #ALLOWED_LENGTHS}, longer barcodes are rejected too, and a row is rejected before
+     * its digits are decoded if it is too narrow or too wide for all of them. Takes the place of
+     * {@link #ALLOWED_LENGTHS} for ITF, and applies to the digits before any check digit is
+     * removed. Maps to an {@code int[]}.
+     */
+    pub ITFLengths: Option<Vec<u32>>,
+
+    /**
+     * If true, decode Code 39 barcodes as Full ASCII Code 39, in which pairs of characters such
+     * as "+A" stand for the ASCII characters Code 39 lacks, and return the characters as read in
+     * the `RAW_TEXT` metadata. If false, return the characters as read. Overrides how the reader
+     * was created. Doesn't matter what it maps to; use {@link Boolean#TRUE}.
+     */
+    pub Code39ExtendedMode: Option<bool>,
+
+    /**
+     * Removes all nondeterminism from the decode, so the same image and hints always produce
+     * the same result and the same witness (see `PipelineWitness`). The formats of
+     * {@link #POSSIBLE_FORMATS} are tried in a fixed order rather than the iteration order of
+     * the set, which differs between runs; this is the order they are tried in without the
+     * hint: QR Code, Micro QR Code, rMQR, Data Matrix, Aztec, PDF417, MaxiCode. No reader
+     * gives up early based on elapsed time, with or without this hint. Doesn't matter what it
+     * maps to; use {@link Boolean#TRUE}.
+     */
+    pub Deterministic: Option<bool>,
+
+    /**
+     * The caller wants the intermediate stages of the decode (see `PipelineWitness`) streamed
+     * into a sink. The reader that decodes the barcode hands each stage to the sink as soon as
+     * the decode succeeds, in pipeline order; readers that fail send nothing. Maps to a
+     * {@link WitnessSink}.
+     */
+    #[cfg_attr(feature = "serde", serde(skip_serializing, skip_deserializing))]
+    pub WitnessSink: Option<Arc<dyn WitnessSink>>,
+
+    /**
+     * The caller wants the intermediate stages of the decode recorded while the reader runs.
+     * The reader hands each stage to the recorder as it reaches it, including the stages of
+     * attempts that fail later on. Maps to a {@link WitnessRecorder}.
+     */
+    #[cfg_attr(feature = "serde", serde(skip_serializing, skip_deserializing))]
+    pub WitnessRecorder: Option<Arc<dyn WitnessRecorder>>,
+
+    /**
+     * A token another thread can cancel to abandon the decode. The readers check it between
+     * the rows they scan and the formats they try, and fail with a `CancelledException` once
+     * it is cancelled. Maps to a `CancellationToken`.
+     */
+    #[cfg_attr(feature = "serde", serde(skip_serializing, skip_deserializing))]
+    pub Cancellation: Option<CancellationToken>,
+
+    /**
+     * The caller wants the stages streamed into the {@link #WITNESS_SINK} to hold only the
+     * region of the image around the symbol, extended by this many pixels on every side, rather
+     * than the full frame. See `PipelineWitness::crop_to_symbol`. Maps to a {@link Integer}.
+     */
+    pub WitnessCropMargin: Option<u32>,
+
+    /**
+     * Samples the modules of QR codes, Data Matrix and Aztec symbols without floating point
+     * arithmetic. Once the detector has fitted its sampling grid, the modules are sampled
+     * again with a `FixedPointGridSampler`, whose integer arithmetic gives the same modules
+     * on every platform, and the sampling stage streamed into the {@link #WITNESS_SINK}
+     * records the fixed-point transforms. Doesn't matter what it maps to; use {@link Boolean#TRUE}.
+     */
+    pub FixedPointSampling: Option<bool>,
+
+    /**
+     * If true, read Pharmacode as two-track Pharmacode, whose bars stand for digits by
+     * reaching the upper track, the lower track or both. Otherwise it is read as one-track
+     * Pharmacode of narrow and wide bars. Doesn't matter what it maps to; use
+     * {@link Boolean#TRUE}.
+     */
+    pub PharmacodeTwoTrack: Option<bool>,
+
+    /**
+     * The least number of bars of Pharmacode to accept, 2 if not given. Pharmacode has
+     * neither start and stop patterns nor a check digit, so raising it guards against reading
+     * a few bars of anything else as a barcode. Maps to a {@link Integer}.
+     */
+    pub PharmacodeMinBars: Option<u32>,
+
+    /**
+     * The greatest number of bars of Pharmacode to accept, 16 if not given. Maps to a
+     * {@link Integer}.
+     */
+    pub PharmacodeMaxBars: Option<u32>,
+
+    /**
+     * How to treat the check digits of an MSI Plessey barcode, whose kind is given by
+     * {@link #MSI_CHECKSUM}. Maps to a `CheckDigitPolicy`.
+     */
+    pub MSICheckDigit: Option<CheckDigitPolicy>,
+
+    /**
+     * The check digits MSI Plessey barcodes carry, mod 10 if not given. Only used if
+     * {@link #MSI_CHECK_DIGIT} checks or removes them. Maps to an `MSIChecksum`.
+     */
+    pub MSIChecksum: Option<MSIChecksum>,
+
+    /**
+     * If true, a `PyramidReader` also looks for symbols in copies of the image scaled down
+     * by its downscale factor, smallest first, before the full resolution image. Symbols
+     * whose modules are many pixels wide, or blurry or noisy, are often found more easily
+     * at a lower resolution. Doesn't matter what it maps to; use {@link Boolean#TRUE}.
+     */
+    pub TryDownscale: Option<bool>,
+
+    /**
+     * Which rows 1D readers scan, and how many symbols `OneDReader::decode_rows` collects.
+     * Scanning fewer rows, from a different offset each frame, speeds up reading video.
+     * Maps to a `RowSampling`.
+     */
+    pub OneDRowSampling: Option<RowSampling>,
+
+    /**
+     * If true, a `MultiFormatReader` that finds nothing tries again on copies of the image
+     * cleaned up by the filters of `preprocess::RETRY_CHAIN`, one after another: sharpened,
+     * denoised, and with gaps in the bars closed. This helps with faint, low-contrast prints
+     * such as thermal labels, at the cost of slower failures. Doesn't matter what it maps to;
+     * use {@link Boolean#TRUE}.
+     */
+    pub TryPreprocessing: Option<bool>,
+
+    /**
+     * If true, the QR Code reader also accepts finder patterns whose outer ring runs straight
+     * into dark content next to the symbol, as with codes printed without a quiet zone, framed
+     * by a border or cropped close. Makes false positives in busy images a little more likely.
+     * Doesn't matter what it maps to; use {@link Boolean#TRUE}.
+     */
+    pub AssumeNoQuietZone: Option<bool>,
+
+    /**
+     * If true, the QR Code reader samples a symbol that runs off the edge of the image instead
+     * of giving up, and leaves the codewords of the modules outside the image to the error
+     * correction as erasures, which costs half as much of it as errors do. Doesn't matter what
+     * it maps to; use {@link Boolean#TRUE}.
+     */
+    pub AllowPartialSymbol: Option<bool>,
+
+    /**
+     * How the QR Code, Data Matrix and Aztec readers read the modules of a symbol from the
+     * image once its detector has fitted the sampling grid, see `GridSampling`.
+     * `Supersample` helps with symbols whose modules are under 2 pixels wide. The
+     * `FixedPointSampling` hint takes precedence. Maps to a `GridSampling`.
+     */
+    pub GridSampling: Option<GridSampling>,
+
+    /**
+     * If true, the QR Code and Data Matrix readers retry a symbol that fails to decode with
+     * its sampling grid bent around a cylinder, fitted to the finder and timing patterns, for
+     * symbols printed on bottles and cables. The result then has no `SAMPLING_GRID`, which
+     * would be flat. Doesn't matter what it maps to; use {@link Boolean#TRUE}.
+     */
+    pub TryCurved: Option<bool>,
 }
 
+#[allow(deprecated)]
 impl From<super::DecodingHintDictionary> for DecodeHints {
     fn from(value: super::DecodingHintDictionary) -> Self {
         let mut new_self: Self = Self::default();
@@ -364,6 +955,31 @@ impl From<super::DecodingHintDictionary> for DecodeHints {
                 DecodeHintValue::AllowedEanExtensions(v) => new_self.AllowedEanExtensions = Some(v),
                 DecodeHintValue::AlsoInverted(v) => new_self.AlsoInverted = Some(v),
                 DecodeHintValue::TelepenAsNumeric(v) => new_self.TelepenAsNumeric = Some(v),
+                DecodeHintValue::Code39CheckDigit(v) => new_self.Code39CheckDigit = Some(v),
+                DecodeHintValue::CodabarCheckDigit(v) => new_self.CodabarCheckDigit = Some(v),
+                DecodeHintValue::ITFCheckDigit(v) => new_self.ITFCheckDigit = Some(v),
+                DecodeHintValue::Strictness(v) => new_self.Strictness = Some(v),
+                DecodeHintValue::ExpandUPCE(v) => new_self.ExpandUPCE = Some(v),
+                DecodeHintValue::ITFLengths(v) => new_self.ITFLengths = Some(v),
+                DecodeHintValue::Code39ExtendedMode(v) => new_self.Code39ExtendedMode = Some(v),
+                DecodeHintValue::Deterministic(v) => new_self.Deterministic = Some(v),
+                DecodeHintValue::WitnessSink(v) => new_self.WitnessSink = Some(v),
+                DecodeHintValue::WitnessRecorder(v) => new_self.WitnessRecorder = Some(v),
+                DecodeHintValue::Cancellation(v) => new_self.Cancellation = Some(v),
+                DecodeHintValue::WitnessCropMargin(v) => new_self.WitnessCropMargin = Some(v),
+                DecodeHintValue::FixedPointSampling(v) => new_self.FixedPointSampling = Some(v),
+                DecodeHintValue::PharmacodeTwoTrack(v) => new_self.PharmacodeTwoTrack = Some(v),
+                DecodeHintValue::PharmacodeMinBars(v) => new_self.PharmacodeMinBars = Some(v),
+                DecodeHintValue::PharmacodeMaxBars(v) => new_self.PharmacodeMaxBars = Some(v),
+                DecodeHintValue::MSICheckDigit(v) => new_self.MSICheckDigit = Some(v),
+                DecodeHintValue::MSIChecksum(v) => new_self.MSIChecksum = Some(v),
+                DecodeHintValue::TryDownscale(v) => new_self.TryDownscale = Some(v),
+                DecodeHintValue::OneDRowSampling(v) => new_self.OneDRowSampling = Some(v),
+                DecodeHintValue::TryPreprocessing(v) => new_self.TryPreprocessing = Some(v),
+                DecodeHintValue::AssumeNoQuietZone(v) => new_self.AssumeNoQuietZone = Some(v),
+                DecodeHintValue::AllowPartialSymbol(v) => new_self.AllowPartialSymbol = Some(v),
+                DecodeHintValue::GridSampling(v) => new_self.GridSampling = Some(v),
+                DecodeHintValue::TryCurved(v) => new_self.TryCurved = Some(v),
                 #[cfg(feature = "allow_forced_iso_ied_18004_compliance")]
                 DecodeHintValue::QrAssumeSpecConformInput(v) => {
                     new_self.QrAssumeSpecConformInput = Some(v)
@@ -374,6 +990,7 @@ impl From<super::DecodingHintDictionary> for DecodeHints {
     }
 }
 
+#[allow(deprecated)]
 impl From<DecodeHints> for super::DecodingHintDictionary {
     fn from(value: DecodeHints) -> Self {
         let mut new_self = HashMap::default();
@@ -460,6 +1077,169 @@ impl From<DecodeHints> for super::DecodingHintDictionary {
             );
         }
 
+        if let Some(v) = value.Code39CheckDigit {
+            new_self.insert(
+                DecodeHintType::CODE_39_CHECK_DIGIT,
+                DecodeHintValue::Code39CheckDigit(v),
+            );
+        }
+
+        if let Some(v) = value.CodabarCheckDigit {
+            new_self.insert(
+                DecodeHintType::CODABAR_CHECK_DIGIT,
+                DecodeHintValue::CodabarCheckDigit(v),
+            );
+        }
+
+        if let Some(v) = value.ITFCheckDigit {
+            new_self.insert(
+                DecodeHintType::ITF_CHECK_DIGIT,
+                DecodeHintValue::ITFCheckDigit(v),
+            );
+        }
+
+        if let Some(v) = value.Strictness {
+            new_self.insert(DecodeHintType::STRICTNESS, DecodeHintValue::Strictness(v));
+        }
+
+        if let Some(v) = value.ExpandUPCE {
+            new_self.insert(DecodeHintType::EXPAND_UPC_E, DecodeHintValue::ExpandUPCE(v));
+        }
+
+        if let Some(v) = value.ITFLengths {
+            new_self.insert(DecodeHintType::ITF_LENGTHS, DecodeHintValue::ITFLengths(v));
+        }
+
+        if let Some(v) = value.Code39ExtendedMode {
+            new_self.insert(
+                DecodeHintType::CODE_39_EXTENDED_MODE,
+                DecodeHintValue::Code39ExtendedMode(v),
+            );
+        }
+
+        if let Some(v) = value.Deterministic {
+            new_self.insert(
+                DecodeHintType::DETERMINISTIC,
+                DecodeHintValue::Deterministic(v),
+            );
+        }
+
+        if let Some(v) = value.WitnessSink {
+            new_self.insert(
+                DecodeHintType::WITNESS_SINK,
+                DecodeHintValue::WitnessSink(v),
+            );
+        }
+
+        if let Some(v) = value.WitnessRecorder {
+            new_self.insert(
+                DecodeHintType::WITNESS_RECORDER,
+                DecodeHintValue::WitnessRecorder(v),
+            );
+        }
+
+        if let Some(v) = value.Cancellation {
+            new_self.insert(
+                DecodeHintType::CANCELLATION,
+                DecodeHintValue::Cancellation(v),
+            );
+        }
+
+        if let Some(v) = value.WitnessCropMargin {
+            new_self.insert(
+                DecodeHintType::WITNESS_CROP_MARGIN,
+                DecodeHintValue::WitnessCropMargin(v),
+            );
+        }
+
+        if let Some(v) = value.FixedPointSampling {
+            new_self.insert(
+                DecodeHintType::FIXED_POINT_SAMPLING,
+                DecodeHintValue::FixedPointSampling(v),
+            );
+        }
+
+        if let Some(v) = value.PharmacodeTwoTrack {
+            new_self.insert(
+                DecodeHintType::PHARMACODE_TWO_TRACK,
+                DecodeHintValue::PharmacodeTwoTrack(v),
+            );
+        }
+
+        if let Some(v) = value.PharmacodeMinBars {
+            new_self.insert(
+                DecodeHintType::PHARMACODE_MIN_BARS,
+                DecodeHintValue::PharmacodeMinBars(v),
+            );
+        }
+
+        if let Some(v) = value.PharmacodeMaxBars {
+            new_self.insert(
+                DecodeHintType::PHARMACODE_MAX_BARS,
+                DecodeHintValue::PharmacodeMaxBars(v),
+            );
+        }
+
+        if let Some(v) = value.MSICheckDigit {
+            new_self.insert(
+                DecodeHintType::MSI_CHECK_DIGIT,
+                DecodeHintValue::MSICheckDigit(v),
+            );
+        }
+
+        if let Some(v) = value.MSIChecksum {
+            new_self.insert(
+                DecodeHintType::MSI_CHECKSUM,
+                DecodeHintValue::MSIChecksum(v),
+            );
+        }
+
+        if let Some(v) = value.TryDownscale {
+            new_self.insert(
+                DecodeHintType::TRY_DOWNSCALE,
+                DecodeHintValue::TryDownscale(v),
+            );
+        }
+
+        if let Some(v) = value.OneDRowSampling {
+            new_self.insert(
+                DecodeHintType::ONE_D_ROW_SAMPLING,
+                DecodeHintValue::OneDRowSampling(v),
+            );
+        }
+
+        if let Some(v) = value.TryPreprocessing {
+            new_self.insert(
+                DecodeHintType::TRY_PREPROCESSING,
+                DecodeHintValue::TryPreprocessing(v),
+            );
+        }
+
+        if let Some(v) = value.AssumeNoQuietZone {
+            new_self.insert(
+                DecodeHintType::ASSUME_NO_QUIET_ZONE,
+                DecodeHintValue::AssumeNoQuietZone(v),
+            );
+        }
+
+        if let Some(v) = value.AllowPartialSymbol {
+            new_self.insert(
+                DecodeHintType::ALLOW_PARTIAL_SYMBOL,
+                DecodeHintValue::AllowPartialSymbol(v),
+            );
+        }
+
+        if let Some(v) = value.GridSampling {
+            new_self.insert(
+                DecodeHintType::GRID_SAMPLING,
+                DecodeHintValue::GridSampling(v),
+            );
+        }
+
+        if let Some(v) = value.TryCurved {
+            new_self.insert(DecodeHintType::TRY_CURVED, DecodeHintValue::TryCurved(v));
+        }
+
         #[cfg(feature = "allow_forced_iso_ied_18004_compliance")]
         if let Some(v) = value.QrAssumeSpecConformInput {
             new_self.insert(
@@ -473,6 +1253,37 @@ impl From<DecodeHints> for super::DecodingHintDictionary {
 }
 
 impl DecodeHints {
+    /**
+     * Hints for reproducible witnesses of QR codes: the same image gives the same
+     * intermediate state bit for bit, across runs and platforms.
+     *
+     * Only QR codes are looked for, the decode is `Deterministic` and the modules are
+     * sampled in fixed point (see `FixedPointSampling`). The binarizer is not a hint; use a `FixedThresholdBinarizer`,
+     * whose threshold does not depend on the image, or choose one threshold for the image
+     * up front with `FixedThresholdBinarizer::with_otsu`.
+     */
+    pub fn zk_deterministic() -> Self {
+        Self {
+            PossibleFormats: Some(HashSet::from([BarcodeFormat::QR_CODE])),
+            Deterministic: Some(true),
+            FixedPointSampling: Some(true),
+            ..Default::default()
+        }
+    }
+
+    /// Abandons the decode once `token` is cancelled, see `Cancellation`
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.Cancellation = Some(token);
+        self
+    }
+
+    /// Fails with a `CancelledException` if the `Cancellation` token is cancelled
+    pub(crate) fn check_cancelled(&self) -> crate::common::Result<()> {
+        self.Cancellation
+            .as_ref()
+            .map_or(Ok(()), CancellationToken::check)
+    }
+
     pub fn with(mut self, value: DecodeHintValue) -> Self {
         match value {
             DecodeHintValue::Other(v) => self.Other = Some(v),
@@ -488,6 +1299,31 @@ impl DecodeHints {
             DecodeHintValue::AllowedEanExtensions(v) => self.AllowedEanExtensions = Some(v),
             DecodeHintValue::AlsoInverted(v) => self.AlsoInverted = Some(v),
             DecodeHintValue::TelepenAsNumeric(v) => self.TelepenAsNumeric = Some(v),
+            DecodeHintValue::Code39CheckDigit(v) => self.Code39CheckDigit = Some(v),
+            DecodeHintValue::CodabarCheckDigit(v) => self.CodabarCheckDigit = Some(v),
+            DecodeHintValue::ITFCheckDigit(v) => self.ITFCheckDigit = Some(v),
+            DecodeHintValue::Strictness(v) => self.Strictness = Some(v),
+            DecodeHintValue::ExpandUPCE(v) => self.ExpandUPCE = Some(v),
+            DecodeHintValue::ITFLengths(v) => self.ITFLengths = Some(v),
+            DecodeHintValue::Code39ExtendedMode(v) => self.Code39ExtendedMode = Some(v),
+            DecodeHintValue::Deterministic(v) => self.Deterministic = Some(v),
+            DecodeHintValue::WitnessSink(v) => self.WitnessSink = Some(v),
+            DecodeHintValue::WitnessRecorder(v) => self.WitnessRecorder = Some(v),
+            DecodeHintValue::Cancellation(v) => self.Cancellation = Some(v),
+            DecodeHintValue::WitnessCropMargin(v) => self.WitnessCropMargin = Some(v),
+            DecodeHintValue::FixedPointSampling(v) => self.FixedPointSampling = Some(v),
+            DecodeHintValue::PharmacodeTwoTrack(v) => self.PharmacodeTwoTrack = Some(v),
+            DecodeHintValue::PharmacodeMinBars(v) => self.PharmacodeMinBars = Some(v),
+            DecodeHintValue::PharmacodeMaxBars(v) => self.PharmacodeMaxBars = Some(v),
+            DecodeHintValue::MSICheckDigit(v) => self.MSICheckDigit = Some(v),
+            DecodeHintValue::MSIChecksum(v) => self.MSIChecksum = Some(v),
+            DecodeHintValue::TryDownscale(v) => self.TryDownscale = Some(v),
+            DecodeHintValue::OneDRowSampling(v) => self.OneDRowSampling = Some(v),
+            DecodeHintValue::TryPreprocessing(v) => self.TryPreprocessing = Some(v),
+            DecodeHintValue::AssumeNoQuietZone(v) => self.AssumeNoQuietZone = Some(v),
+            DecodeHintValue::AllowPartialSymbol(v) => self.AllowPartialSymbol = Some(v),
+            DecodeHintValue::GridSampling(v) => self.GridSampling = Some(v),
+            DecodeHintValue::TryCurved(v) => self.TryCurved = Some(v),
             #[cfg(feature = "allow_forced_iso_ied_18004_compliance")]
             DecodeHintValue::QrAssumeSpecConformInput(v) => self.QrAssumeSpecConformInput = Some(v),
         }
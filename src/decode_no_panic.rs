@@ -0,0 +1,267 @@
+/*
+ * A hardened entry point for decoding untrusted input
+ *
+ * A service scanning uploads can't let a malformed or hostile image take the process down,
+ * whether by exhausting memory or by tripping an internal invariant of a reader. These
+ * functions bound the resources spent on an input and turn a panic into an error.
+ */
+
+use std::{
+    any::Any,
+    panic::{self, AssertUnwindSafe},
+};
+
+use thiserror::Error;
+
+use crate::{helpers, DecodeHints, Exceptions, RXingResult};
+#[cfg(feature = "image")]
+use crate::{
+    multi::{GenericMultipleBarcodeReader, MultipleBarcodeReader},
+    ImageBitmap, MultiUseMultiFormatReader,
+};
+
+/// The resources `decode_no_panic` may spend on an input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecodeLimits {
+    /// The largest encoded image read, in bytes
+    pub max_input_bytes: usize,
+    /// The widest image decoded, in pixels
+    pub max_width: u32,
+    /// The tallest image decoded, in pixels
+    pub max_height: u32,
+    /// The most pixels an image may have, bounding the memory its decoded form takes
+    pub max_pixels: u64,
+}
+
+impl Default for DecodeLimits {
+    fn default() -> Self {
+        Self {
+            max_input_bytes: 32 * 1024 * 1024,
+            max_width: 16384,
+            max_height: 16384,
+            max_pixels: 64 * 1024 * 1024,
+        }
+    }
+}
+
+impl DecodeLimits {
+    fn check_dimensions(&self, width: u32, height: u32) -> Result<(), HardenedDecodeError> {
+        if width > self.max_width
+            || height > self.max_height
+            || width as u64 * height as u64 > self.max_pixels
+        {
+            return Err(HardenedDecodeError::LimitExceeded(format!(
+                "image of {width}x{height} pixels is larger than the limits"
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Why `decode_no_panic` returned no barcodes
+#[derive(Error, Debug, PartialEq, Eq, Clone)]
+pub enum HardenedDecodeError {
+    /// The input, or the image in it, is larger than the `DecodeLimits`
+    #[error("limit exceeded: {0}")]
+    LimitExceeded(String),
+    /// The input is not an image that can be read
+    #[error("could not read image: {0}")]
+    UnreadableImage(String),
+    /// The image was read, but decoding failed, `NotFoundException` if it holds no barcode
+    #[error(transparent)]
+    Decode(#[from] Exceptions),
+    /// Reading or decoding the image panicked, with the message of the panic
+    #[error("decoding panicked: {0}")]
+    Panicked(String),
+}
+
+/**
+ * Decodes every barcode in an encoded image (PNG, JPEG, ...) from an untrusted source.
+ *
+ * The input size is checked before anything is read, and the dimensions of the image before
+ * it is decoded, so an image that would inflate to gigabytes is refused from its header. A
+ * panic anywhere in reading or decoding is caught and returned as
+ * `HardenedDecodeError::Panicked`.
+ *
+ * Panics are still reported to the panic hook, which by default prints them to stderr, and
+ * can't be caught in a build with `panic = "abort"`.
+ *
+ * # Arguments
+ * * `bytes` - The encoded image, its format is told from the contents
+ * * `hints` - The hints to decode with, the caller's copy is never modified
+ * * `limits` - The resources the decode may spend
+ */
+#[cfg(feature = "image")]
+pub fn decode_no_panic(
+    bytes: &[u8],
+    hints: &DecodeHints,
+    limits: &DecodeLimits,
+) -> Result<Vec<RXingResult>, HardenedDecodeError> {
+    if bytes.len() > limits.max_input_bytes {
+        return Err(HardenedDecodeError::LimitExceeded(format!(
+            "input of {} bytes is larger than {} bytes",
+            bytes.len(),
+            limits.max_input_bytes
+        )));
+    }
+
+    guard(|| {
+        let img = read_image(bytes, limits)?;
+        let mut hints = hints.clone();
+        hints.TryHarder = hints.TryHarder.or(Some(true));
+        let mut scanner = GenericMultipleBarcodeReader::new(MultiUseMultiFormatReader::default());
+        Ok(scanner.decode_multiple_with_hints(&mut ImageBitmap::from(img), &hints)?)
+    })
+}
+
+// Reads the image as `helpers::load_image_from_memory` does, checking its dimensions first
+#[cfg(feature = "image")]
+fn read_image(
+    bytes: &[u8],
+    limits: &DecodeLimits,
+) -> Result<image::DynamicImage, HardenedDecodeError> {
+    let unreadable = |e: image::ImageError| match e {
+        image::ImageError::Limits(e) => HardenedDecodeError::LimitExceeded(e.to_string()),
+        e => HardenedDecodeError::UnreadableImage(e.to_string()),
+    };
+    let reader = || -> Result<_, HardenedDecodeError> {
+        let mut reader = image::ImageReader::new(std::io::Cursor::new(bytes))
+            .with_guessed_format()
+            .map_err(|e| HardenedDecodeError::UnreadableImage(e.to_string()))?;
+        let mut image_limits = image::Limits::default();
+        image_limits.max_image_width = Some(limits.max_width);
+        image_limits.max_image_height = Some(limits.max_height);
+        reader.limits(image_limits);
+        Ok(reader)
+    };
+
+    helpers::register_image_hooks();
+    let (width, height) = reader()?.into_dimensions().map_err(unreadable)?;
+    limits.check_dimensions(width, height)?;
+    helpers::decode_upright(reader()?).map_err(unreadable)
+}
+
+/**
+ * Like [`decode_no_panic`], for a grayscale image already in memory, one byte per pixel and
+ * row-major.
+ */
+pub fn decode_luma_no_panic(
+    luma: Vec<u8>,
+    width: u32,
+    height: u32,
+    hints: &DecodeHints,
+    limits: &DecodeLimits,
+) -> Result<Vec<RXingResult>, HardenedDecodeError> {
+    limits.check_dimensions(width, height)?;
+    if luma.len() as u64 != width as u64 * height as u64 {
+        let message = format!("{} bytes of luma for a {width}x{height} image", luma.len());
+        return Err(Exceptions::illegal_argument_with(message).into());
+    }
+
+    guard(|| decode_luma(luma, width, height, hints))
+}
+
+fn decode_luma(
+    luma: Vec<u8>,
+    width: u32,
+    height: u32,
+    hints: &DecodeHints,
+) -> Result<Vec<RXingResult>, HardenedDecodeError> {
+    let mut hints = hints.clone();
+    Ok(helpers::detect_multiple_in_luma_with_hints(
+        luma, width, height, &mut hints,
+    )?)
+}
+
+// Runs `decode`, turning a panic into an error. Nothing `decode` touches outlives it but the
+// caller's hints, which are only read, so no broken state can be observed after a panic.
+fn guard<T, F>(decode: F) -> Result<T, HardenedDecodeError>
+where
+    F: FnOnce() -> Result<T, HardenedDecodeError>,
+{
+    panic::catch_unwind(AssertUnwindSafe(decode))
+        .unwrap_or_else(|payload| Err(HardenedDecodeError::Panicked(panic_message(&*payload))))
+}
+
+/// The message a panic was raised with, for a payload caught by [`panic::catch_unwind`]
+pub(crate) fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        (*message).to_owned()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic".to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Exceptions::NotFoundException;
+    use crate::{BarcodeFormat, MultiFormatWriter, Writer};
+    use HardenedDecodeError::*;
+
+    fn qr_luma() -> (Vec<u8>, u32, u32) {
+        let matrix = MultiFormatWriter
+            .encode("hardened", &BarcodeFormat::QR_CODE, 100, 100)
+            .unwrap();
+        (matrix.to_luma8(), matrix.getWidth(), matrix.getHeight())
+    }
+
+    #[test]
+    fn test_decode_luma_no_panic() {
+        let hints = DecodeHints::default();
+        let limits = DecodeLimits::default();
+        let (luma, width, height) = qr_luma();
+        let results = decode_luma_no_panic(luma.clone(), width, height, &hints, &limits).unwrap();
+        assert_eq!("hardened", results[0].getText());
+
+        let small = DecodeLimits {
+            max_pixels: width as u64 * height as u64 - 1,
+            ..limits
+        };
+        let refused = decode_luma_no_panic(luma.clone(), width, height, &hints, &small);
+        assert!(matches!(refused, Err(LimitExceeded(_))));
+        let short = decode_luma_no_panic(luma, width + 1, height, &hints, &limits);
+        assert!(matches!(short, Err(Decode(_))));
+        let blank = vec![255; 10_000];
+        let blank = decode_luma_no_panic(blank, 100, 100, &hints, &limits);
+        assert!(matches!(blank, Err(Decode(NotFoundException(_)))));
+    }
+
+    #[test]
+    fn test_guard() {
+        let caught = guard::<(), _>(|| panic!("broken invariant"));
+        assert_eq!(Err(Panicked("broken invariant".to_owned())), caught);
+        let index = 10;
+        let caught = guard(|| Ok([0u8; 4][index]));
+        assert!(matches!(caught, Err(Panicked(_))));
+    }
+
+    #[cfg(feature = "image_formats")]
+    #[test]
+    fn test_decode_no_panic() {
+        let hints = DecodeHints::default();
+        let limits = DecodeLimits::default();
+        let png = std::fs::read("test_resources/blackbox/qrcode-2/1.png").unwrap();
+        assert_eq!(1, decode_no_panic(&png, &hints, &limits).unwrap().len());
+
+        let small = DecodeLimits {
+            max_input_bytes: png.len() - 1,
+            ..limits
+        };
+        let refused = decode_no_panic(&png, &hints, &small);
+        assert!(matches!(refused, Err(LimitExceeded(_))));
+        let narrow = DecodeLimits {
+            max_width: 10,
+            ..limits
+        };
+        let refused = decode_no_panic(&png, &hints, &narrow);
+        assert!(matches!(refused, Err(LimitExceeded(_))));
+
+        let truncated = decode_no_panic(&png[..png.len() / 2], &hints, &limits);
+        assert!(matches!(truncated, Err(UnreadableImage(_))));
+        let garbage = decode_no_panic(b"not an image", &hints, &limits);
+        assert!(matches!(garbage, Err(UnreadableImage(_))));
+    }
+}
@@ -0,0 +1,207 @@
+use std::collections::HashSet;
+
+use crate::{
+    common::{CharacterSet, GridSampling},
+    oned::RowSampling,
+    BarcodeFormat, CancellationToken, DecodeHintValue, DecodeHints, Strictness,
+};
+
+/**
+ * Builds `DecodeHints` with typed setters, so a hint can't be given a value of the wrong
+ * type:
+ *
+ * ```
+ * use rxing::{common::CharacterSet, BarcodeFormat, DecodeHints, DecodeOptions};
+ *
+ * let hints: DecodeHints = DecodeOptions::new()
+ *     .formats(&[BarcodeFormat::QR_CODE, BarcodeFormat::CODE_128])
+ *     .try_harder(true)
+ *     .character_set(CharacterSet::UTF8)
+ *     .into();
+ * assert_eq!(Some(true), hints.TryHarder);
+ * ```
+ *
+ * Hints without a setter here are set with `hint`.
+ */
+#[derive(Default, Clone)]
+pub struct DecodeOptions {
+    hints: DecodeHints,
+}
+
+impl DecodeOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only look for symbols of these formats, see `PossibleFormats`
+    pub fn formats(mut self, formats: &[BarcodeFormat]) -> Self {
+        self.hints.PossibleFormats = Some(formats.iter().cloned().collect::<HashSet<_>>());
+        self
+    }
+
+    /// Optimize for accuracy, not speed, see `TryHarder`
+    pub fn try_harder(mut self, try_harder: bool) -> Self {
+        self.hints.TryHarder = Some(try_harder);
+        self
+    }
+
+    /// The image is a pure monochrome image of a barcode, see `PureBarcode`
+    pub fn pure_barcode(mut self, pure_barcode: bool) -> Self {
+        self.hints.PureBarcode = Some(pure_barcode);
+        self
+    }
+
+    /// Also look for light symbols on a dark background, see `AlsoInverted`
+    pub fn also_inverted(mut self, also_inverted: bool) -> Self {
+        self.hints.AlsoInverted = Some(also_inverted);
+        self
+    }
+
+    /// Also look in copies of the image scaled down, see `TryDownscale`
+    pub fn try_downscale(mut self, try_downscale: bool) -> Self {
+        self.hints.TryDownscale = Some(try_downscale);
+        self
+    }
+
+    /// Try again on sharpened and denoised copies of the image, see `TryPreprocessing`
+    pub fn try_preprocessing(mut self, try_preprocessing: bool) -> Self {
+        self.hints.TryPreprocessing = Some(try_preprocessing);
+        self
+    }
+
+    /// Accept QR Codes without a quiet zone, see `AssumeNoQuietZone`
+    pub fn assume_no_quiet_zone(mut self, assume_no_quiet_zone: bool) -> Self {
+        self.hints.AssumeNoQuietZone = Some(assume_no_quiet_zone);
+        self
+    }
+
+    /// Decode QR Codes that run off the edge of the image, see `AllowPartialSymbol`
+    pub fn allow_partial_symbol(mut self, allow_partial_symbol: bool) -> Self {
+        self.hints.AllowPartialSymbol = Some(allow_partial_symbol);
+        self
+    }
+
+    /// Read the modules of 2D symbols this way, see `GridSampling`
+    pub fn grid_sampling(mut self, grid_sampling: GridSampling) -> Self {
+        self.hints.GridSampling = Some(grid_sampling);
+        self
+    }
+
+    /// Retry QR Codes and Data Matrix symbols printed on curved surfaces, see `TryCurved`
+    pub fn try_curved(mut self, try_curved: bool) -> Self {
+        self.hints.TryCurved = Some(try_curved);
+        self
+    }
+
+    /// Decode byte data without an ECI in this character set, see `CharacterSet`
+    pub fn character_set(mut self, character_set: CharacterSet) -> Self {
+        self.hints.CharacterSet = Some(character_set.get_charset_name().to_owned());
+        self
+    }
+
+    /// Reject symbols encoding another number of characters, see `AllowedLengths`
+    pub fn allowed_lengths(mut self, lengths: &[u32]) -> Self {
+        self.hints.AllowedLengths = Some(lengths.to_vec());
+        self
+    }
+
+    /// Only accept EAN/UPC symbols with an extension of one of these lengths, see
+    /// `AllowedEanExtensions`
+    pub fn allowed_ean_extensions(mut self, lengths: &[u32]) -> Self {
+        self.hints.AllowedEanExtensions = Some(lengths.to_vec());
+        self
+    }
+
+    /// Treat the symbols as GS1 data, see `AssumeGs1`
+    pub fn assume_gs1(mut self, assume_gs1: bool) -> Self {
+        self.hints.AssumeGs1 = Some(assume_gs1);
+        self
+    }
+
+    /// Keep the start and end characters of Codabar symbols, see `ReturnCodabarStartEnd`
+    pub fn return_codabar_start_end(mut self, return_start_end: bool) -> Self {
+        self.hints.ReturnCodabarStartEnd = Some(return_start_end);
+        self
+    }
+
+    /// How closely symbols must follow their specification, see `Strictness`
+    pub fn strictness(mut self, strictness: Strictness) -> Self {
+        self.hints.Strictness = Some(strictness);
+        self
+    }
+
+    /// Which rows 1D readers scan, see `OneDRowSampling`
+    pub fn row_sampling(mut self, sampling: RowSampling) -> Self {
+        self.hints.OneDRowSampling = Some(sampling);
+        self
+    }
+
+    /// Abandon the decode once `token` is cancelled, see `Cancellation`
+    pub fn cancellation(mut self, token: CancellationToken) -> Self {
+        self.hints.Cancellation = Some(token);
+        self
+    }
+
+    /// Sets any hint, replacing a value set before
+    pub fn hint(mut self, value: DecodeHintValue) -> Self {
+        self.hints = self.hints.with(value);
+        self
+    }
+
+    pub fn hints(&self) -> &DecodeHints {
+        &self.hints
+    }
+}
+
+impl From<DecodeOptions> for DecodeHints {
+    fn from(value: DecodeOptions) -> Self {
+        value.hints
+    }
+}
+
+#[allow(deprecated)]
+impl From<DecodeOptions> for crate::DecodingHintDictionary {
+    fn from(value: DecodeOptions) -> Self {
+        value.hints.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::{
+        common::CharacterSet, BarcodeFormat, DecodeHintType, DecodeHintValue, DecodeHints,
+        DecodeOptions,
+    };
+
+    #[test]
+    fn test_into_hints() {
+        let options = DecodeOptions::new()
+            .formats(&[BarcodeFormat::QR_CODE, BarcodeFormat::CODE_128])
+            .try_harder(true)
+            .character_set(CharacterSet::UTF8)
+            .hint(DecodeHintValue::TelepenAsNumeric(true));
+
+        let hints: DecodeHints = options.clone().into();
+        assert_eq!(
+            Some(HashSet::from([
+                BarcodeFormat::QR_CODE,
+                BarcodeFormat::CODE_128
+            ])),
+            hints.PossibleFormats
+        );
+        assert_eq!(Some(true), hints.TryHarder);
+        assert_eq!(Some("utf-8"), hints.CharacterSet.as_deref());
+        assert_eq!(Some(true), hints.TelepenAsNumeric);
+        assert_eq!(None, hints.PureBarcode);
+
+        #[allow(deprecated)]
+        let dictionary: crate::DecodingHintDictionary = options.into();
+        assert_eq!(4, dictionary.len());
+        assert!(matches!(
+            dictionary.get(&DecodeHintType::TRY_HARDER),
+            Some(DecodeHintValue::TryHarder(true))
+        ));
+    }
+}
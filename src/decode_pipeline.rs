@@ -0,0 +1,262 @@
+/*
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::{collections::HashSet, fmt, sync::Arc};
+
+use crate::{
+    common::{BitMatrix, FixedThresholdBinarizer, Result},
+    BarcodeFormat, BinaryBitmap, DecodeHints, Luma8LuminanceSource, MultiFormatReader, RXingResult,
+    Reader,
+};
+
+/**
+ * A hook into the stages of a [`DecodePipeline`]. All methods default to doing nothing, so
+ * middleware only implements the stages it is interested in. Returning an error from any
+ * of the pre/post binarize or pre decode hooks aborts the decode with that error.
+ */
+pub trait DecodeMiddleware: Send + Sync {
+    /// Called with the grayscale image before it is binarized. The image and hints may be replaced.
+    fn pre_binarize(
+        &self,
+        _source: &mut Luma8LuminanceSource,
+        _hints: &mut DecodeHints,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    /**
+     * Called with the binarized image. Changes to the matrix are seen by the 2D readers;
+     * 1D readers binarize rows on their own.
+     */
+    fn post_binarize(&self, _matrix: &mut BitMatrix, _hints: &DecodeHints) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called right before the readers run, with the final binarized image and hints
+    fn pre_decode(&self, _matrix: &BitMatrix, _hints: &mut DecodeHints) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called with the outcome of the decode, which may be replaced
+    fn post_decode(&self, _result: &mut Result<RXingResult>, _hints: &DecodeHints) {}
+}
+
+/**
+ * The high level decode pipeline (binarize, then run a [`MultiFormatReader`]) with
+ * [`DecodeMiddleware`] hooks around each stage. Middleware runs in the order it was added,
+ * which allows logging, image dumps, witness capture or custom filters without changes to
+ * the readers.
+ */
+#[derive(Default)]
+pub struct DecodePipeline {
+    reader: MultiFormatReader,
+    middleware: Vec<Arc<dyn DecodeMiddleware>>,
+}
+
+impl DecodePipeline {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Uses `reader`, for example one with custom readers registered, instead of the default
+    pub fn with_reader(reader: MultiFormatReader) -> Self {
+        Self {
+            reader,
+            middleware: Vec::new(),
+        }
+    }
+
+    pub fn with_middleware<M: DecodeMiddleware + 'static>(mut self, middleware: M) -> Self {
+        self.add_middleware(middleware);
+        self
+    }
+
+    pub fn add_middleware<M: DecodeMiddleware + 'static>(&mut self, middleware: M) {
+        self.middleware.push(Arc::new(middleware));
+    }
+
+    pub fn middleware_count(&self) -> usize {
+        self.middleware.len()
+    }
+
+    pub fn decode_luma(
+        &mut self,
+        luma: Vec<u8>,
+        width: u32,
+        height: u32,
+        barcode_type: Option<BarcodeFormat>,
+    ) -> Result<RXingResult> {
+        let mut hints = DecodeHints::default();
+        if let Some(bc_type) = barcode_type {
+            hints.PossibleFormats = Some(HashSet::from([bc_type]));
+        }
+        self.decode_with_hints(Luma8LuminanceSource::new(luma, width, height), &hints)
+    }
+
+    /**
+     * Decodes `source` with the same defaults as [`crate::helpers::detect_in_luma_with_hints`]:
+     * a fixed threshold binarizer, and `TryHarder` unless the hints say otherwise.
+     */
+    pub fn decode_with_hints(
+        &mut self,
+        mut source: Luma8LuminanceSource,
+        hints: &DecodeHints,
+    ) -> Result<RXingResult> {
+        let mut hints = hints.clone();
+        hints.TryHarder = hints.TryHarder.or(Some(true));
+
+        for middleware in &self.middleware {
+            middleware.pre_binarize(&mut source, &mut hints)?;
+        }
+
+        let mut bitmap = BinaryBitmap::new(FixedThresholdBinarizer::new(source));
+        for middleware in &self.middleware {
+            middleware.post_binarize(bitmap.get_black_matrix_mut(), &hints)?;
+        }
+
+        for middleware in &self.middleware {
+            middleware.pre_decode(bitmap.get_black_matrix(), &mut hints)?;
+        }
+
+        let mut result = self.reader.decode_with_hints(&mut bitmap, &hints);
+
+        for middleware in &self.middleware {
+            middleware.post_decode(&mut result, &hints);
+        }
+
+        result
+    }
+}
+
+impl fmt::Debug for DecodePipeline {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DecodePipeline")
+            .field("middleware", &self.middleware.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use crate::{
+        common::{BitMatrix, Result},
+        test_fixtures::scaled_luma,
+        BarcodeFormat, DecodeHints, DecodeMiddleware, DecodePipeline, Exceptions,
+        Luma8LuminanceSource, LuminanceSource, MultiFormatWriter, RXingResult, Writer,
+    };
+
+    #[derive(Default, Clone)]
+    struct Recorder(Arc<Mutex<Vec<String>>>);
+
+    impl DecodeMiddleware for Recorder {
+        fn pre_binarize(
+            &self,
+            source: &mut Luma8LuminanceSource,
+            _hints: &mut DecodeHints,
+        ) -> Result<()> {
+            self.0
+                .lock()
+                .unwrap()
+                .push(format!("pre_binarize {}", source.get_width()));
+            Ok(())
+        }
+
+        fn post_binarize(&self, _matrix: &mut BitMatrix, _hints: &DecodeHints) -> Result<()> {
+            self.0.lock().unwrap().push("post_binarize".to_owned());
+            Ok(())
+        }
+
+        fn pre_decode(&self, _matrix: &BitMatrix, _hints: &mut DecodeHints) -> Result<()> {
+            self.0.lock().unwrap().push("pre_decode".to_owned());
+            Ok(())
+        }
+
+        fn post_decode(&self, result: &mut Result<RXingResult>, _hints: &DecodeHints) {
+            self.0
+                .lock()
+                .unwrap()
+                .push(format!("post_decode {}", result.is_ok()));
+        }
+    }
+
+    /// Inverts the binarized image, so light-on-dark symbols can be read
+    struct Invert;
+
+    impl DecodeMiddleware for Invert {
+        fn post_binarize(&self, matrix: &mut BitMatrix, _hints: &DecodeHints) -> Result<()> {
+            matrix.flip_self();
+            Ok(())
+        }
+    }
+
+    struct Reject;
+
+    impl DecodeMiddleware for Reject {
+        fn pre_decode(&self, _matrix: &BitMatrix, _hints: &mut DecodeHints) -> Result<()> {
+            Err(Exceptions::illegal_state_with("rejected"))
+        }
+    }
+
+    fn qr_luma(inverted: bool) -> (Vec<u8>, u32, u32) {
+        let matrix = MultiFormatWriter
+            .encode("pipeline", &BarcodeFormat::QR_CODE, 100, 100)
+            .unwrap();
+        let (dark, light) = if inverted { (255, 0) } else { (0, 255) };
+        scaled_luma(&matrix, 1, dark, light)
+    }
+
+    #[test]
+    fn test_hooks_run_in_order() {
+        let recorder = Recorder::default();
+        let mut pipeline = DecodePipeline::new().with_middleware(recorder.clone());
+        let (luma, width, height) = qr_luma(false);
+        let result = pipeline
+            .decode_luma(luma, width, height, Some(BarcodeFormat::QR_CODE))
+            .unwrap();
+        assert_eq!("pipeline", result.getText());
+        assert_eq!(
+            vec![
+                format!("pre_binarize {width}"),
+                "post_binarize".to_owned(),
+                "pre_decode".to_owned(),
+                "post_decode true".to_owned()
+            ],
+            *recorder.0.lock().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_filter_and_abort() {
+        let (luma, width, height) = qr_luma(true);
+        assert!(DecodePipeline::new()
+            .decode_luma(luma.clone(), width, height, Some(BarcodeFormat::QR_CODE))
+            .is_err());
+        let mut pipeline = DecodePipeline::new().with_middleware(Invert);
+        assert_eq!(
+            "pipeline",
+            pipeline
+                .decode_luma(luma.clone(), width, height, Some(BarcodeFormat::QR_CODE))
+                .unwrap()
+                .getText()
+        );
+
+        pipeline.add_middleware(Reject);
+        assert!(matches!(
+            pipeline.decode_luma(luma, width, height, Some(BarcodeFormat::QR_CODE)),
+            Err(Exceptions::IllegalStateException(_))
+        ));
+    }
+}
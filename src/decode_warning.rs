@@ -0,0 +1,190 @@
+/*
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{LuminanceSource, Point, RXingResult};
+
+/// The symbol contrast in percent below which `LowContrast` is reported, where ISO/IEC 15415
+/// and 15416 grade it D or F
+const LOW_SYMBOL_CONTRAST: f32 = 40.0;
+
+/**
+ * A defect of a symbol that was read anyway, found in `RXingResult::getWarnings`. An
+ * application can accept the result and still flag the label for reprinting.
+ */
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DecodeWarning {
+    /**
+     * A quiet zone of a linear symbol is narrower than its specification requires, or cut off
+     * by the edge of the image, so that `Strictness::Strict` would not have read it.
+     */
+    QuietZoneViolation,
+    /**
+     * The spread between the dark and the light pixels of the symbol is less than 40 percent of
+     * the range of luminance, as measured by `MultiFormatReader` and
+     * `MultiUseMultiFormatReader`.
+     */
+    LowContrast,
+    /**
+     * Error correction used more than half of what it could correct in a block of a 2D symbol.
+     * A little more damage and it would not have been read.
+     */
+    HeavyErrorCorrection,
+    /**
+     * A QR Code or Data Matrix symbol was only read as its mirror image, so it was printed
+     * reversed or seen through the back of the label.
+     */
+    AmbiguousOrientation,
+    /**
+     * The `WitnessRecorder` or `WitnessSink` hint failed to record the whole witness of the
+     * symbol. The symbol was read, but its witness is missing the stages after the failure.
+     */
+    WitnessIncomplete,
+}
+
+/// Adds `DecodeWarning::LowContrast` to `result` if its symbol has a low contrast in `source`
+pub(crate) fn check_contrast<S: LuminanceSource>(source: &S, result: &mut RXingResult) {
+    if is_low_contrast(source, result.getPoints()) {
+        result.addWarning(DecodeWarning::LowContrast);
+    }
+}
+
+/**
+ * Whether the symbol whose result has `points` has a low contrast in `source`. The contrast is
+ * measured within the bounding box of the points, between the darkest and the lightest twentieth
+ * of its pixels.
+ */
+fn is_low_contrast<S: LuminanceSource>(source: &S, points: &[Point]) -> bool {
+    let (width, height) = (source.get_width(), source.get_height());
+    if points.is_empty() || width == 0 || height == 0 {
+        return false;
+    }
+    let clamp = |v: f32, size: usize| (v.max(0.0) as usize).min(size - 1);
+    let left = clamp(points.iter().map(|p| p.x).fold(f32::MAX, f32::min), width);
+    let right = clamp(points.iter().map(|p| p.x).fold(f32::MIN, f32::max), width);
+    let top = clamp(points.iter().map(|p| p.y).fold(f32::MAX, f32::min), height);
+    let bottom = clamp(points.iter().map(|p| p.y).fold(f32::MIN, f32::max), height);
+
+    let mut histogram = [0usize; 256];
+    for y in top..=bottom {
+        let Some(row) = source.get_row(y) else {
+            continue;
+        };
+        for luminance in row.iter().take(right + 1).skip(left) {
+            histogram[*luminance as usize] += 1;
+        }
+    }
+    let total: usize = histogram.iter().sum();
+    if total == 0 {
+        return false;
+    }
+
+    let dark = percentile(&histogram, 0..256, total);
+    let light = percentile(&histogram, (0..256).rev(), total);
+    (light.saturating_sub(dark) as f32) * 100.0 / 255.0 < LOW_SYMBOL_CONTRAST
+}
+
+/// The first of `levels` up to which a twentieth of the `total` pixels of `histogram` are
+fn percentile(
+    histogram: &[usize; 256],
+    levels: impl Iterator<Item = usize>,
+    total: usize,
+) -> usize {
+    let mut count = 0;
+    for level in levels {
+        count += histogram[level];
+        if count * 20 >= total {
+            return level;
+        }
+    }
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::{
+        common::BitMatrix, helpers, point, test_fixtures::scaled_luma, BarcodeFormat, DecodeHints,
+        DecodeWarning, EncodeHints, Luma8LuminanceSource, MultiFormatWriter, RXingResult,
+        RXingResultMetadataType, RXingResultMetadataValue, Writer,
+    };
+
+    use super::is_low_contrast;
+
+    fn source(dark: u8, light: u8) -> Luma8LuminanceSource {
+        let luma = (0..100)
+            .map(|i| if (i / 2) % 2 == 0 { dark } else { light })
+            .collect();
+        Luma8LuminanceSource::new(luma, 10, 10)
+    }
+
+    fn decode(matrix: &BitMatrix, dark: u8, light: u8) -> RXingResult {
+        let (luma, width, height) = scaled_luma(matrix, 1, dark, light);
+        let mut hints = DecodeHints {
+            PossibleFormats: Some(HashSet::from([BarcodeFormat::QR_CODE])),
+            PureBarcode: Some(true),
+            ..Default::default()
+        };
+        helpers::detect_in_luma_with_hints(
+            luma,
+            width,
+            height,
+            Some(BarcodeFormat::QR_CODE),
+            &mut hints,
+        )
+        .expect("decode")
+    }
+
+    #[test]
+    fn test_low_contrast() {
+        let points = [point(1.0, 1.0), point(8.0, 8.0)];
+        assert!(!is_low_contrast(&source(20, 230), &points));
+        assert!(is_low_contrast(&source(100, 160), &points));
+        assert!(!is_low_contrast(&source(100, 160), &[]));
+    }
+
+    #[test]
+    fn test_warnings() {
+        let hints = EncodeHints {
+            ErrorCorrection: Some("L".to_owned()),
+            Margin: Some("4".to_owned()),
+            ..Default::default()
+        };
+        let mut matrix = MultiFormatWriter
+            .encode_with_hints("warnings", &BarcodeFormat::QR_CODE, 0, 0, &hints)
+            .expect("encode");
+        assert!(decode(&matrix, 0, 255).getWarnings().is_empty());
+        assert_eq!(
+            [DecodeWarning::LowContrast],
+            decode(&matrix, 90, 160).getWarnings()
+        );
+
+        // two of the 19 data and 7 error correction codewords of version 1-L, which corrects
+        // three of them
+        matrix.flip_coords(24, 24);
+        matrix.flip_coords(24, 16);
+        let result = decode(&matrix, 0, 255);
+        assert_eq!([DecodeWarning::HeavyErrorCorrection], result.getWarnings());
+        assert_eq!(
+            Some(&RXingResultMetadataValue::ErrorsCorrected(2)),
+            result
+                .getRXingResultMetadata()
+                .get(&RXingResultMetadataType::ERRORS_CORRECTED)
+        );
+    }
+}
@@ -0,0 +1,216 @@
+/*
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::sync::{Condvar, Mutex, PoisonError};
+
+use crate::{
+    common::Result, Binarizer, BinaryBitmap, DecodeHints, MultiFormatReader, RXingResult, Reader,
+    ReaderRegistry,
+};
+
+/**
+ * A set of `MultiFormatReader`s set up with the same hints, to share between threads. A
+ * `MultiFormatReader` keeps state between decodes, so a server would otherwise build one per
+ * request or lock a shared one for the whole decode. The pool lends each call to `decode` a
+ * reader of its own, and a call finding them all busy waits for one to be returned.
+ *
+ * ```
+ * use std::sync::Arc;
+ * use rxing::{common::HybridBinarizer, BinaryBitmap, DecodeHints, DecoderPool, Luma8LuminanceSource};
+ *
+ * let pool = Arc::new(DecoderPool::new(4, DecodeHints::default()));
+ * let handles = (0..8).map(|_| {
+ *     let pool = Arc::clone(&pool);
+ *     std::thread::spawn(move || {
+ *         let mut image = BinaryBitmap::new(HybridBinarizer::new(Luma8LuminanceSource::new(
+ *             vec![255; 100 * 100], 100, 100,
+ *         )));
+ *         pool.decode(&mut image).is_err()
+ *     })
+ * });
+ * assert!(handles.map(|handle| handle.join().unwrap()).all(|blank| blank));
+ * ```
+ */
+pub struct DecoderPool {
+    hints: DecodeHints,
+    workers: usize,
+    idle: Mutex<Vec<MultiFormatReader>>,
+    returned: Condvar,
+}
+
+/// A reader lent by the pool, given back when dropped, also when the decode panics
+struct Lease<'a> {
+    pool: &'a DecoderPool,
+    reader: Option<MultiFormatReader>,
+}
+
+impl Lease<'_> {
+    fn decode<B: Binarizer>(mut self, image: &mut BinaryBitmap<B>) -> Result<RXingResult> {
+        let reader = self.reader.as_mut().expect("a leased reader");
+        let result = reader.decode_with_state(image);
+        reader.reset();
+        result
+    }
+}
+
+impl Drop for Lease<'_> {
+    fn drop(&mut self) {
+        if let Some(reader) = self.reader.take() {
+            self.pool.lock().push(reader);
+            self.pool.returned.notify_one();
+        }
+    }
+}
+
+impl DecoderPool {
+    /// A pool of `workers` readers, at least one, that decode with `hints`
+    pub fn new(workers: usize, hints: DecodeHints) -> Self {
+        Self::with_registry(workers, hints, ReaderRegistry::default())
+    }
+
+    /// A pool of `workers` readers, at least one, that also try the custom readers of `registry`
+    pub fn with_registry(workers: usize, hints: DecodeHints, registry: ReaderRegistry) -> Self {
+        let workers = workers.max(1);
+        let idle = (0..workers)
+            .map(|_| {
+                let mut reader = MultiFormatReader::with_registry(registry.clone());
+                reader.set_hints(&hints);
+                reader
+            })
+            .collect();
+        Self {
+            hints,
+            workers,
+            idle: Mutex::new(idle),
+            returned: Condvar::new(),
+        }
+    }
+
+    /// The number of decodes the pool runs at the same time
+    pub fn workers(&self) -> usize {
+        self.workers
+    }
+
+    pub fn hints(&self) -> &DecodeHints {
+        &self.hints
+    }
+
+    /// Decodes `image` with a reader of the pool, waiting for one if all are busy
+    pub fn decode<B: Binarizer>(&self, image: &mut BinaryBitmap<B>) -> Result<RXingResult> {
+        self.lease().decode(image)
+    }
+
+    /// Decodes `image` with a reader of the pool if one is idle, `None` if all are busy
+    pub fn try_decode<B: Binarizer>(
+        &self,
+        image: &mut BinaryBitmap<B>,
+    ) -> Option<Result<RXingResult>> {
+        let reader = self.lock().pop()?;
+        let lease = Lease {
+            pool: self,
+            reader: Some(reader),
+        };
+        Some(lease.decode(image))
+    }
+
+    fn lease(&self) -> Lease<'_> {
+        let mut idle = self.lock();
+        loop {
+            if let Some(reader) = idle.pop() {
+                return Lease {
+                    pool: self,
+                    reader: Some(reader),
+                };
+            }
+            idle = self
+                .returned
+                .wait(idle)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+    }
+
+    // a decode that panicked left no reader half taken, so the readers can still be used
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<MultiFormatReader>> {
+        self.idle.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::HashSet,
+        sync::{Arc, Barrier},
+    };
+
+    use crate::{
+        common::HybridBinarizer, test_fixtures::luma_bitmap, BarcodeFormat, BinaryBitmap,
+        DecodeHints, Luma8LuminanceSource, MultiFormatWriter, Writer,
+    };
+
+    use super::DecoderPool;
+
+    fn qr_code(contents: &str) -> BinaryBitmap<HybridBinarizer<Luma8LuminanceSource>> {
+        let matrix = MultiFormatWriter
+            .encode(contents, &BarcodeFormat::QR_CODE, 120, 120)
+            .expect("encode");
+        luma_bitmap(&matrix)
+    }
+
+    fn is_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn test_send_sync() {
+        is_send_sync::<DecoderPool>();
+    }
+
+    #[test]
+    fn test_concurrent_decodes() {
+        let hints = DecodeHints {
+            PossibleFormats: Some(HashSet::from([BarcodeFormat::QR_CODE])),
+            ..Default::default()
+        };
+        let pool = Arc::new(DecoderPool::new(2, hints));
+        let barrier = Arc::new(Barrier::new(6));
+        let handles = (0..6)
+            .map(|i| {
+                let pool = Arc::clone(&pool);
+                let barrier = Arc::clone(&barrier);
+                std::thread::spawn(move || {
+                    let contents = format!("request {i}");
+                    let mut image = qr_code(&contents);
+                    barrier.wait();
+                    let result = pool.decode(&mut image).expect("decode");
+                    assert_eq!(contents, result.getText());
+                })
+            })
+            .collect::<Vec<_>>();
+        for handle in handles {
+            handle.join().expect("thread");
+        }
+        assert_eq!(2, pool.lock().len());
+    }
+
+    #[test]
+    fn test_try_decode() {
+        let pool = DecoderPool::new(1, DecodeHints::default());
+        let lease = pool.lease();
+        assert!(pool.try_decode(&mut qr_code("busy")).is_none());
+        drop(lease);
+        let result = pool
+            .try_decode(&mut qr_code("idle"))
+            .expect("an idle reader");
+        assert_eq!("idle", result.expect("decode").getText());
+        assert_eq!(1, DecoderPool::new(0, DecodeHints::default()).workers());
+    }
+}
@@ -18,7 +18,11 @@
 
 #![allow(deprecated)]
 
-use crate::{pdf417::encoder::Dimensions, Dimension};
+use crate::{
+    oned::{ITFBearerBars, MSIChecksum},
+    pdf417::encoder::Dimensions,
+    Dimension,
+};
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
@@ -121,6 +125,21 @@ pub enum EncodeHintType {
      */
     PDF417_AUTO_ECI,
 
+    /**
+     * Specifies whether to encode a MicroPDF417 symbol (ISO/IEC 24728) of 1 to 4 columns instead
+     * of a PDF417 symbol when writing {@link BarcodeFormat#PDF_417} (type {@link Boolean}). Its
+     * size sets the error correction, so {@link #ERROR_CORRECTION} and {@link #PDF417_COMPACT}
+     * are ignored.
+     */
+    PDF417_MICRO,
+
+    /**
+     * Specifies the preferred ratio of the width of a PDF417 symbol to its height (type
+     * {@link Float}), among the sizes {@link #PDF417_DIMENSIONS} allows. It is 3 by default for
+     * PDF417 and the smallest symbol is chosen for MicroPDF417 if not given.
+     */
+    PDF417_ASPECT_RATIO,
+
     /**
      * Specifies the required number of layers for an Aztec code.
      * A negative number (-1, -2, -3, -4) specifies a compact Aztec code.
@@ -130,6 +149,20 @@ pub enum EncodeHintType {
      */
     AZTEC_LAYERS,
 
+    /**
+     * Specifies whether to encode a compact Aztec code of 1 to 4 layers (true) or a full-range one
+     * of 1 to 32 layers (false), rather than the smallest of either (type {@link Boolean}). With
+     * {@link #AZTEC_LAYERS} it overrides the sign of the number of layers.
+     */
+    AZTEC_COMPACT,
+
+    /**
+     * Specifies whether to encode an Aztec Rune (type {@link Boolean}), the 11x11 symbol holding a
+     * single value from 0 to 255, given in decimal as the contents. A rune has no error correction
+     * or layers, so the other Aztec hints are ignored.
+     */
+    AZTEC_RUNE,
+
     /**
      * Specifies the exact version of QR code to be encoded.
      * (Type {@link Integer}, or {@link String} representation of the integer value).
@@ -154,9 +187,83 @@ pub enum EncodeHintType {
      */
     QR_COMPACT,
 
+    /**
+     * Specifies whether the QR code compaction may use the Hanzi mode of GB/T 18284 for Chinese
+     * characters of GB2312, 13 bits each rather than 16 in byte mode (type {@link Boolean}). It
+     * implies {@link #QR_COMPACT}. Hanzi mode is a Chinese extension, so some scanners outside
+     * China do not read it.
+     */
+    QR_HANZI,
+
+    /**
+     * Specifies whether to encode a Micro QR Code (M1 to M4) instead of a QR Code when writing
+     * {@link BarcodeFormat#QR_CODE} (type {@link Boolean}). {@link #QR_VERSION} then selects the
+     * Micro QR version 1 to 4 and {@link #QR_MASK_PATTERN} one of its 4 mask patterns.
+     */
+    MICRO_QR,
+
+    /**
+     * Specifies the MaxiCode mode (type {@link Integer}): 2 or 3 for a structured carrier message of
+     * postcode, country and class of service, 4 for standard and 5 for full error correction. The
+     * default is 4.
+     */
+    MAXICODE_MODE,
+
+    /**
+     * Specifies whether to encode two-track rather than one-track Pharmacode (type {@link Boolean},
+     * or "true" or "false" {@link String} value).
+     */
+    PHARMACODE_TWO_TRACK,
+
+    /**
+     * Specifies whether to encode a two row DataBar (RSS-14) Stacked rather than a DataBar
+     * Omnidirectional symbol (type {@link Boolean}, or "true" or "false" {@link String} value).
+     */
+    RSS_STACKED,
+
+    /**
+     * Specifies the number of segments, data characters and the check character, per row of a
+     * DataBar Expanded Stacked symbol (type {@link Integer}): an even number from 2 to 22. A
+     * DataBar Expanded symbol has a single row if not given.
+     */
+    RSS_EXPANDED_SEGMENTS,
+
+    /**
+     * Specifies the check digits to append to MSI Plessey barcodes (type `MSIChecksum`). None
+     * are appended if not given.
+     */
+    MSI_CHECKSUM,
+
+    /**
+     * Specifies whether to append a mod 10 check digit to ITF barcodes, computed as for GS1 keys
+     * (type {@link Boolean}). The contents then have an odd number of digits, 13 for ITF-14.
+     */
+    ITF_CHECK_DIGIT,
+
+    /**
+     * Specifies the bearer bars to draw around ITF barcodes (type `ITFBearerBars`), as ITF-14
+     * symbols printed on corrugated board have. None are drawn if not given.
+     */
+    ITF_BEARER_BARS,
+
+    /**
+     * Specifies the ratio of the wide to the narrow bars of ITF barcodes (type {@link Float}),
+     * from 2.0 to 3.0. 3.0 if not given.
+     */
+    ITF_WIDE_TO_NARROW_RATIO,
+
+    /**
+     * Specifies a 2 or 5 digit add-on to append to EAN-13 and UPC-A barcodes (type {@link String}),
+     * an issue number or a suggested price. None is appended if not given.
+     */
+    UPC_EAN_EXTENSION,
+
     /**
      * Specifies whether the data should be encoded to the GS1 standard (type {@link Boolean}, or "true" or "false"
      * {@link String } value).
+     * Code-128 contents are then GS1 element strings, in human readable form such as
+     * `(01)09506000134352(10)ABC` or with FNC1 after values of variable length, and are encoded
+     * as GS1-128 with FNC1 where needed.
      */
     GS1_FORMAT,
 
@@ -176,8 +283,9 @@ pub enum EncodeHintType {
     /**
      * Specifies whether to use compact mode for Code-128 code (type {@link Boolean}, or "true" or "false"
      * {@link String } value).
-     * This can yield slightly smaller bar codes. This option and {@link #FORCE_CODE_SET} are mutually
-     * exclusive.
+     * Compact mode switches code sets where it yields the fewest codewords, and is used unless this
+     * is false or {@link #FORCE_CODE_SET} is given; false encodes greedily instead. This option and
+     * {@link #FORCE_CODE_SET} are mutually exclusive.
      */
     CODE128_COMPACT,
 
@@ -279,6 +387,21 @@ pub enum EncodeHintValue {
      */
     Pdf417AutoEci(String),
 
+    /**
+     * Specifies whether to encode a MicroPDF417 symbol (ISO/IEC 24728) of 1 to 4 columns instead
+     * of a PDF417 symbol when writing {@link BarcodeFormat#PDF_417} (type {@link Boolean}). Its
+     * size sets the error correction, so {@link #ERROR_CORRECTION} and {@link #PDF417_COMPACT}
+     * are ignored.
+     */
+    Pdf417Micro(bool),
+
+    /**
+     * Specifies the preferred ratio of the width of a PDF417 symbol to its height (type
+     * {@link Float}), among the sizes {@link #PDF417_DIMENSIONS} allows. It is 3 by default for
+     * PDF417 and the smallest symbol is chosen for MicroPDF417 if not given.
+     */
+    Pdf417AspectRatio(f32),
+
     /**
      * Specifies the required number of layers for an Aztec code.
      * A negative number (-1, -2, -3, -4) specifies a compact Aztec code.
@@ -288,6 +411,20 @@ pub enum EncodeHintValue {
      */
     AztecLayers(i32),
 
+    /**
+     * Specifies whether to encode a compact Aztec code of 1 to 4 layers (true) or a full-range one
+     * of 1 to 32 layers (false), rather than the smallest of either (type {@link Boolean}). With
+     * {@link #AZTEC_LAYERS} it overrides the sign of the number of layers.
+     */
+    AztecCompact(bool),
+
+    /**
+     * Specifies whether to encode an Aztec Rune (type {@link Boolean}), the 11x11 symbol holding a
+     * single value from 0 to 255, given in decimal as the contents. A rune has no error correction
+     * or layers, so the other Aztec hints are ignored.
+     */
+    AztecRune(bool),
+
     /**
      * Specifies the exact version of QR code to be encoded.
      * (Type {@link Integer}, or {@link String} representation of the integer value).
@@ -312,9 +449,83 @@ pub enum EncodeHintValue {
      */
     QrCompact(String),
 
+    /**
+     * Specifies whether the QR code compaction may use the Hanzi mode of GB/T 18284 for Chinese
+     * characters of GB2312, 13 bits each rather than 16 in byte mode (type {@link Boolean}). It
+     * implies {@link #QR_COMPACT}. Hanzi mode is a Chinese extension, so some scanners outside
+     * China do not read it.
+     */
+    QrHanzi(bool),
+
+    /**
+     * Specifies whether to encode a Micro QR Code (M1 to M4) instead of a QR Code when writing
+     * {@link BarcodeFormat#QR_CODE} (type {@link Boolean}). {@link #QR_VERSION} then selects the
+     * Micro QR version 1 to 4 and {@link #QR_MASK_PATTERN} one of its 4 mask patterns.
+     */
+    MicroQr(bool),
+
+    /**
+     * Specifies the MaxiCode mode (type {@link Integer}): 2 or 3 for a structured carrier message of
+     * postcode, country and class of service, 4 for standard and 5 for full error correction. The
+     * default is 4.
+     */
+    MaxiCodeMode(u8),
+
+    /**
+     * Specifies whether to encode two-track rather than one-track Pharmacode (type {@link Boolean},
+     * or "true" or "false" {@link String} value).
+     */
+    PharmacodeTwoTrack(bool),
+
+    /**
+     * Specifies whether to encode a two row DataBar (RSS-14) Stacked rather than a DataBar
+     * Omnidirectional symbol (type {@link Boolean}, or "true" or "false" {@link String} value).
+     */
+    RssStacked(bool),
+
+    /**
+     * Specifies the number of segments, data characters and the check character, per row of a
+     * DataBar Expanded Stacked symbol (type {@link Integer}): an even number from 2 to 22. A
+     * DataBar Expanded symbol has a single row if not given.
+     */
+    RssExpandedSegments(u32),
+
+    /**
+     * Specifies the check digits to append to MSI Plessey barcodes (type `MSIChecksum`). None
+     * are appended if not given.
+     */
+    MSIChecksum(MSIChecksum),
+
+    /**
+     * Specifies whether to append a mod 10 check digit to ITF barcodes, computed as for GS1 keys
+     * (type {@link Boolean}). The contents then have an odd number of digits, 13 for ITF-14.
+     */
+    ITFCheckDigit(bool),
+
+    /**
+     * Specifies the bearer bars to draw around ITF barcodes (type `ITFBearerBars`), as ITF-14
+     * symbols printed on corrugated board have. None are drawn if not given.
+     */
+    ITFBearerBars(ITFBearerBars),
+
+    /**
+     * Specifies the ratio of the wide to the narrow bars of ITF barcodes (type {@link Float}),
+     * from 2.0 to 3.0. 3.0 if not given.
+     */
+    ITFWideToNarrowRatio(f32),
+
+    /**
+     * Specifies a 2 or 5 digit add-on to append to EAN-13 and UPC-A barcodes (type {@link String}),
+     * an issue number or a suggested price. None is appended if not given.
+     */
+    UpcEanExtension(String),
+
     /**
      * Specifies whether the data should be encoded to the GS1 standard (type {@link Boolean}, or "true" or "false"
      * {@link String } value).
+     * Code-128 contents are then GS1 element strings, in human readable form such as
+     * `(01)09506000134352(10)ABC` or with FNC1 after values of variable length, and are encoded
+     * as GS1-128 with FNC1 where needed.
      */
     Gs1Format(bool),
 
@@ -334,8 +545,9 @@ pub enum EncodeHintValue {
     /**
      * Specifies whether to use compact mode for Code-128 code (type {@link Boolean}, or "true" or "false"
      * {@link String } value).
-     * This can yield slightly smaller bar codes. This option and {@link #FORCE_CODE_SET} are mutually
-     * exclusive.
+     * Compact mode switches code sets where it yields the fewest codewords, and is used unless this
+     * is false or {@link #FORCE_CODE_SET} is given; false encodes greedily instead. This option and
+     * {@link #FORCE_CODE_SET} are mutually exclusive.
      */
     Code128Compact(bool),
 
@@ -438,6 +650,21 @@ pub struct EncodeHints {
      */
     pub Pdf417AutoEci: Option<String>,
 
+    /**
+     * Specifies whether to encode a MicroPDF417 symbol (ISO/IEC 24728) of 1 to 4 columns instead
+     * of a PDF417 symbol when writing {@link BarcodeFormat#PDF_417} (type {@link Boolean}). Its
+     * size sets the error correction, so {@link #ERROR_CORRECTION} and {@link #PDF417_COMPACT}
+     * are ignored.
+     */
+    pub Pdf417Micro: Option<bool>,
+
+    /**
+     * Specifies the preferred ratio of the width of a PDF417 symbol to its height (type
+     * {@link Float}), among the sizes {@link #PDF417_DIMENSIONS} allows. It is 3 by default for
+     * PDF417 and the smallest symbol is chosen for MicroPDF417 if not given.
+     */
+    pub Pdf417AspectRatio: Option<f32>,
+
     /**
      * Specifies the required number of layers for an Aztec code.
      * A negative number (-1, -2, -3, -4) specifies a compact Aztec code.
@@ -447,6 +674,20 @@ pub struct EncodeHints {
      */
     pub AztecLayers: Option<i32>,
 
+    /**
+     * Specifies whether to encode a compact Aztec code of 1 to 4 layers (true) or a full-range one
+     * of 1 to 32 layers (false), rather than the smallest of either (type {@link Boolean}). With
+     * {@link #AZTEC_LAYERS} it overrides the sign of the number of layers.
+     */
+    pub AztecCompact: Option<bool>,
+
+    /**
+     * Specifies whether to encode an Aztec Rune (type {@link Boolean}), the 11x11 symbol holding a
+     * single value from 0 to 255, given in decimal as the contents. A rune has no error correction
+     * or layers, so the other Aztec hints are ignored.
+     */
+    pub AztecRune: Option<bool>,
+
     /**
      * Specifies the exact version of QR code to be encoded.
      * (Type {@link Integer}, or {@link String} representation of the integer value).
@@ -471,9 +712,83 @@ pub struct EncodeHints {
      */
     pub QrCompact: Option<String>,
 
+    /**
+     * Specifies whether the QR code compaction may use the Hanzi mode of GB/T 18284 for Chinese
+     * characters of GB2312, 13 bits each rather than 16 in byte mode (type {@link Boolean}). It
+     * implies {@link #QR_COMPACT}. Hanzi mode is a Chinese extension, so some scanners outside
+     * China do not read it.
+     */
+    pub QrHanzi: Option<bool>,
+
+    /**
+     * Specifies whether to encode a Micro QR Code (M1 to M4) instead of a QR Code when writing
+     * {@link BarcodeFormat#QR_CODE} (type {@link Boolean}). {@link #QR_VERSION} then selects the
+     * Micro QR version 1 to 4 and {@link #QR_MASK_PATTERN} one of its 4 mask patterns.
+     */
+    pub MicroQr: Option<bool>,
+
+    /**
+     * Specifies the MaxiCode mode (type {@link Integer}): 2 or 3 for a structured carrier message of
+     * postcode, country and class of service, 4 for standard and 5 for full error correction. The
+     * default is 4.
+     */
+    pub MaxiCodeMode: Option<u8>,
+
+    /**
+     * Specifies whether to encode two-track rather than one-track Pharmacode (type {@link Boolean},
+     * or "true" or "false" {@link String} value).
+     */
+    pub PharmacodeTwoTrack: Option<bool>,
+
+    /**
+     * Specifies whether to encode a two row DataBar (RSS-14) Stacked rather than a DataBar
+     * Omnidirectional symbol (type {@link Boolean}, or "true" or "false" {@link String} value).
+     */
+    pub RssStacked: Option<bool>,
+
+    /**
+     * Specifies the number of segments, data characters and the check character, per row of a
+     * DataBar Expanded Stacked symbol (type {@link Integer}): an even number from 2 to 22. A
+     * DataBar Expanded symbol has a single row if not given.
+     */
+    pub RssExpandedSegments: Option<u32>,
+
+    /**
+     * Specifies the check digits to append to MSI Plessey barcodes (type `MSIChecksum`). None
+     * are appended if not given.
+     */
+    pub MSIChecksum: Option<MSIChecksum>,
+
+    /**
+     * Specifies whether to append a mod 10 check digit to ITF barcodes, computed as for GS1 keys
+     * (type {@link Boolean}). The contents then have an odd number of digits, 13 for ITF-14.
+     */
+    pub ITFCheckDigit: Option<bool>,
+
+    /**
+     * Specifies the bearer bars to draw around ITF barcodes (type `ITFBearerBars`), as ITF-14
+     * symbols printed on corrugated board have. None are drawn if not given.
+     */
+    pub ITFBearerBars: Option<ITFBearerBars>,
+
+    /**
+     * Specifies the ratio of the wide to the narrow bars of ITF barcodes (type {@link Float}),
+     * from 2.0 to 3.0. 3.0 if not given.
+     */
+    pub ITFWideToNarrowRatio: Option<f32>,
+
+    /**
+     * Specifies a 2 or 5 digit add-on to append to EAN-13 and UPC-A barcodes (type {@link String}),
+     * an issue number or a suggested price. None is appended if not given.
+     */
+    pub UpcEanExtension: Option<String>,
+
     /**
      * Specifies whether the data should be encoded to the GS1 standard (type {@link Boolean}, or "true" or "false"
      * {@link String } value).
+     * Code-128 contents are then GS1 element strings, in human readable form such as
+     * `(01)09506000134352(10)ABC` or with FNC1 after values of variable length, and are encoded
+     * as GS1-128 with FNC1 where needed.
      */
     pub Gs1Format: Option<bool>,
 
@@ -493,8 +808,9 @@ pub struct EncodeHints {
     /**
      * Specifies whether to use compact mode for Code-128 code (type {@link Boolean}, or "true" or "false"
      * {@link String } value).
-     * This can yield slightly smaller bar codes. This option and {@link #FORCE_CODE_SET} are mutually
-     * exclusive.
+     * Compact mode switches code sets where it yields the fewest codewords, and is used unless this
+     * is false or {@link #FORCE_CODE_SET} is given; false encodes greedily instead. This option and
+     * {@link #FORCE_CODE_SET} are mutually exclusive.
      */
     pub Code128Compact: Option<bool>,
 
@@ -521,10 +837,25 @@ impl From<super::EncodingHintDictionary> for EncodeHints {
                 EncodeHintValue::Pdf417Compaction(v) => new_self.Pdf417Compaction = Some(v),
                 EncodeHintValue::Pdf417Dimensions(v) => new_self.Pdf417Dimensions = Some(v),
                 EncodeHintValue::Pdf417AutoEci(v) => new_self.Pdf417AutoEci = Some(v),
+                EncodeHintValue::Pdf417Micro(v) => new_self.Pdf417Micro = Some(v),
+                EncodeHintValue::Pdf417AspectRatio(v) => new_self.Pdf417AspectRatio = Some(v),
                 EncodeHintValue::AztecLayers(v) => new_self.AztecLayers = Some(v),
+                EncodeHintValue::AztecCompact(v) => new_self.AztecCompact = Some(v),
+                EncodeHintValue::AztecRune(v) => new_self.AztecRune = Some(v),
                 EncodeHintValue::QrVersion(v) => new_self.QrVersion = Some(v),
                 EncodeHintValue::QrMaskPattern(v) => new_self.QrMaskPattern = Some(v),
                 EncodeHintValue::QrCompact(v) => new_self.QrCompact = Some(v),
+                EncodeHintValue::QrHanzi(v) => new_self.QrHanzi = Some(v),
+                EncodeHintValue::MicroQr(v) => new_self.MicroQr = Some(v),
+                EncodeHintValue::MaxiCodeMode(v) => new_self.MaxiCodeMode = Some(v),
+                EncodeHintValue::PharmacodeTwoTrack(v) => new_self.PharmacodeTwoTrack = Some(v),
+                EncodeHintValue::RssStacked(v) => new_self.RssStacked = Some(v),
+                EncodeHintValue::RssExpandedSegments(v) => new_self.RssExpandedSegments = Some(v),
+                EncodeHintValue::MSIChecksum(v) => new_self.MSIChecksum = Some(v),
+                EncodeHintValue::ITFCheckDigit(v) => new_self.ITFCheckDigit = Some(v),
+                EncodeHintValue::ITFBearerBars(v) => new_self.ITFBearerBars = Some(v),
+                EncodeHintValue::ITFWideToNarrowRatio(v) => new_self.ITFWideToNarrowRatio = Some(v),
+                EncodeHintValue::UpcEanExtension(v) => new_self.UpcEanExtension = Some(v),
                 EncodeHintValue::Gs1Format(v) => new_self.Gs1Format = Some(v),
                 EncodeHintValue::ForceCodeSet(v) => new_self.ForceCodeSet = Some(v),
                 EncodeHintValue::ForceC40(v) => new_self.ForceC40 = Some(v),
@@ -551,10 +882,25 @@ impl EncodeHints {
             EncodeHintValue::Pdf417Compaction(v) => self.Pdf417Compaction = Some(v),
             EncodeHintValue::Pdf417Dimensions(v) => self.Pdf417Dimensions = Some(v),
             EncodeHintValue::Pdf417AutoEci(v) => self.Pdf417AutoEci = Some(v),
+            EncodeHintValue::Pdf417Micro(v) => self.Pdf417Micro = Some(v),
+            EncodeHintValue::Pdf417AspectRatio(v) => self.Pdf417AspectRatio = Some(v),
             EncodeHintValue::AztecLayers(v) => self.AztecLayers = Some(v),
+            EncodeHintValue::AztecCompact(v) => self.AztecCompact = Some(v),
+            EncodeHintValue::AztecRune(v) => self.AztecRune = Some(v),
             EncodeHintValue::QrVersion(v) => self.QrVersion = Some(v),
             EncodeHintValue::QrMaskPattern(v) => self.QrMaskPattern = Some(v),
             EncodeHintValue::QrCompact(v) => self.QrCompact = Some(v),
+            EncodeHintValue::QrHanzi(v) => self.QrHanzi = Some(v),
+            EncodeHintValue::MicroQr(v) => self.MicroQr = Some(v),
+            EncodeHintValue::MaxiCodeMode(v) => self.MaxiCodeMode = Some(v),
+            EncodeHintValue::PharmacodeTwoTrack(v) => self.PharmacodeTwoTrack = Some(v),
+            EncodeHintValue::RssStacked(v) => self.RssStacked = Some(v),
+            EncodeHintValue::RssExpandedSegments(v) => self.RssExpandedSegments = Some(v),
+            EncodeHintValue::MSIChecksum(v) => self.MSIChecksum = Some(v),
+            EncodeHintValue::ITFCheckDigit(v) => self.ITFCheckDigit = Some(v),
+            EncodeHintValue::ITFBearerBars(v) => self.ITFBearerBars = Some(v),
+            EncodeHintValue::ITFWideToNarrowRatio(v) => self.ITFWideToNarrowRatio = Some(v),
+            EncodeHintValue::UpcEanExtension(v) => self.UpcEanExtension = Some(v),
             EncodeHintValue::Gs1Format(v) => self.Gs1Format = Some(v),
             EncodeHintValue::ForceCodeSet(v) => self.ForceCodeSet = Some(v),
             EncodeHintValue::ForceC40(v) => self.ForceC40 = Some(v),
@@ -0,0 +1,215 @@
+use std::fmt::Display;
+
+use crate::{
+    common::CharacterSet, datamatrix::encoder::SymbolShapeHint, pdf417::encoder::Dimensions,
+    qrcode::decoder::ErrorCorrectionLevel, EncodeHintValue, EncodeHints,
+};
+
+/**
+ * Builds `EncodeHints` with typed setters instead of the strings most encode hints hold:
+ *
+ * ```
+ * use rxing::{qrcode::decoder::ErrorCorrectionLevel, EncodeHints, EncodeOptions};
+ *
+ * let hints: EncodeHints = EncodeOptions::new()
+ *     .qr_error_correction(ErrorCorrectionLevel::H)
+ *     .qr_version(5)
+ *     .margin(2)
+ *     .into();
+ * assert_eq!(Some("2"), hints.Margin.as_deref());
+ * ```
+ *
+ * Hints without a setter here are set with `hint`.
+ */
+#[derive(Debug, Default, Clone)]
+pub struct EncodeOptions {
+    hints: EncodeHints,
+}
+
+impl EncodeOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The error correction level of QR codes, see `ErrorCorrection`
+    pub fn qr_error_correction(self, level: ErrorCorrectionLevel) -> Self {
+        self.error_correction(level)
+    }
+
+    /**
+     * The error correction of any format, see `ErrorCorrection`: the minimal percentage of
+     * error correction words for Aztec, or the level from 0 to 8 for PDF417
+     */
+    pub fn error_correction(mut self, error_correction: impl Display) -> Self {
+        self.hints.ErrorCorrection = Some(error_correction.to_string());
+        self
+    }
+
+    /// Encode text in this character set, see `CharacterSet`
+    pub fn character_set(mut self, character_set: CharacterSet) -> Self {
+        self.hints.CharacterSet = Some(character_set.get_charset_name().to_owned());
+        self
+    }
+
+    /// The quiet zone around the symbol, see `Margin`
+    pub fn margin(mut self, margin: u32) -> Self {
+        self.hints.Margin = Some(margin.to_string());
+        self
+    }
+
+    /// Encode the contents as GS1 data, see `Gs1Format`
+    pub fn gs1_format(mut self, gs1_format: bool) -> Self {
+        self.hints.Gs1Format = Some(gs1_format);
+        self
+    }
+
+    /// The exact QR code version from 1 to 40, see `QrVersion`
+    pub fn qr_version(mut self, version: u32) -> Self {
+        self.hints.QrVersion = Some(version.to_string());
+        self
+    }
+
+    /// The QR code mask pattern from 0 to 7, see `QrMaskPattern`
+    pub fn qr_mask_pattern(mut self, mask_pattern: u8) -> Self {
+        self.hints.QrMaskPattern = Some(mask_pattern.to_string());
+        self
+    }
+
+    /// Encode QR codes in the fewest bits, switching modes, see `QrCompact`
+    pub fn qr_compact(mut self, compact: bool) -> Self {
+        self.hints.QrCompact = Some(compact.to_string());
+        self
+    }
+
+    /// Encode Chinese text of QR codes in Hanzi mode where shorter, see `QrHanzi`
+    pub fn qr_hanzi(mut self, hanzi: bool) -> Self {
+        self.hints.QrHanzi = Some(hanzi);
+        self
+    }
+
+    /// Encode a Micro QR code rather than a QR code, see `MicroQr`
+    pub fn micro_qr(mut self, micro_qr: bool) -> Self {
+        self.hints.MicroQr = Some(micro_qr);
+        self
+    }
+
+    /// The shape of Data Matrix symbols, see `DataMatrixShape`
+    pub fn data_matrix_shape(mut self, shape: SymbolShapeHint) -> Self {
+        self.hints.DataMatrixShape = Some(shape);
+        self
+    }
+
+    /// Encode Data Matrix symbols in the fewest codewords, see `DataMatrixCompact`
+    pub fn data_matrix_compact(mut self, compact: bool) -> Self {
+        self.hints.DataMatrixCompact = Some(compact);
+        self
+    }
+
+    /// Encode Code 128 in the fewest codewords, the default, or greedily, see `Code128Compact`
+    pub fn code128_compact(mut self, compact: bool) -> Self {
+        self.hints.Code128Compact = Some(compact);
+        self
+    }
+
+    /// The number of Aztec layers, negative for compact symbols, see `AztecLayers`
+    pub fn aztec_layers(mut self, layers: i32) -> Self {
+        self.hints.AztecLayers = Some(layers);
+        self
+    }
+
+    /// Encode only compact or only full-range Aztec symbols, see `AztecCompact`
+    pub fn aztec_compact(mut self, compact: bool) -> Self {
+        self.hints.AztecCompact = Some(compact);
+        self
+    }
+
+    /// The minimal percentage of Aztec error correction words, see `ErrorCorrection`
+    pub fn aztec_error_correction(self, percent: u32) -> Self {
+        self.error_correction(percent)
+    }
+
+    /// Encode an Aztec Rune of the value given as the contents, see `AztecRune`
+    pub fn aztec_rune(mut self, rune: bool) -> Self {
+        self.hints.AztecRune = Some(rune);
+        self
+    }
+
+    /// Encode compact PDF417 symbols, see `Pdf417Compact`
+    pub fn pdf417_compact(mut self, compact: bool) -> Self {
+        self.hints.Pdf417Compact = Some(compact.to_string());
+        self
+    }
+
+    /// The range of PDF417 rows and columns, see `Pdf417Dimensions`
+    pub fn pdf417_dimensions(mut self, dimensions: Dimensions) -> Self {
+        self.hints.Pdf417Dimensions = Some(dimensions);
+        self
+    }
+
+    /// Encode a MicroPDF417 symbol rather than a PDF417 one, see `Pdf417Micro`
+    pub fn pdf417_micro(mut self, micro: bool) -> Self {
+        self.hints.Pdf417Micro = Some(micro);
+        self
+    }
+
+    /// The preferred width to height ratio of PDF417 symbols, see `Pdf417AspectRatio`
+    pub fn pdf417_aspect_ratio(mut self, ratio: f32) -> Self {
+        self.hints.Pdf417AspectRatio = Some(ratio);
+        self
+    }
+
+    /// Encode a DataBar Stacked symbol rather than an Omnidirectional one, see `RssStacked`
+    pub fn rss_stacked(mut self, stacked: bool) -> Self {
+        self.hints.RssStacked = Some(stacked);
+        self
+    }
+
+    /// The number of segments per row of DataBar Expanded Stacked, see `RssExpandedSegments`
+    pub fn rss_expanded_segments(mut self, segments: u32) -> Self {
+        self.hints.RssExpandedSegments = Some(segments);
+        self
+    }
+
+    /// Sets any hint, replacing a value set before
+    pub fn hint(mut self, value: EncodeHintValue) -> Self {
+        self.hints = self.hints.with(value);
+        self
+    }
+
+    pub fn hints(&self) -> &EncodeHints {
+        &self.hints
+    }
+}
+
+impl From<EncodeOptions> for EncodeHints {
+    fn from(value: EncodeOptions) -> Self {
+        value.hints
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        qrcode::{decoder::ErrorCorrectionLevel, QRCodeWriter},
+        BarcodeFormat, EncodeHintValue, EncodeHints, EncodeOptions, Writer,
+    };
+
+    #[test]
+    fn test_into_hints() {
+        let hints: EncodeHints = EncodeOptions::new()
+            .qr_error_correction(ErrorCorrectionLevel::H)
+            .qr_version(5)
+            .margin(0)
+            .hint(EncodeHintValue::Code128Compact(true))
+            .into();
+        assert_eq!(Some("H"), hints.ErrorCorrection.as_deref());
+        assert_eq!(Some("5"), hints.QrVersion.as_deref());
+        assert_eq!(Some(true), hints.Code128Compact);
+
+        let matrix = QRCodeWriter
+            .encode_with_hints("options", &BarcodeFormat::QR_CODE, 0, 0, &hints)
+            .expect("encode");
+        // version 5 is 37 modules wide
+        assert_eq!(37, matrix.getWidth());
+    }
+}
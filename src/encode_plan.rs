@@ -0,0 +1,336 @@
+/*
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::fmt;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    aztec::{self, encoder::aztec_encoder},
+    common::Result,
+    datamatrix::DataMatrixWriter,
+    pdf417::PDF417Writer,
+    qrcode::{
+        decoder::{ErrorCorrectionLevel, Mode},
+        encoder::qrcode_encoder,
+    },
+    BarcodeFormat, EncodeHints, EncodeMode, Exceptions, MultiFormatWriter, SymbolSize, Writer,
+};
+
+/// A run of characters encoded in a single mode
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct EncodeSegment {
+    pub mode: EncodeMode,
+    /// The number of characters, or bytes for byte mode segments
+    pub characters: usize,
+}
+
+/**
+ * The outcome of a dry-run encode: what symbol the encoder would produce for the
+ * contents and hints, see [`plan_encode`].
+ */
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct EncodePlan {
+    pub format: BarcodeFormat,
+    /// The chosen symbol size, `None` for 1D formats
+    pub symbol_size: Option<SymbolSize>,
+    /// The error correction level in the terms of the `ErrorCorrection` hint, `None` for
+    /// formats without a selectable level
+    pub error_correction: Option<String>,
+    /// The segmentation of the data, only reported for QR Code
+    pub segments: Vec<EncodeSegment>,
+    /// The symbol width in modules, without quiet zone
+    pub width: u32,
+    /// The symbol height in modules (rows for PDF417), 1 for 1D formats
+    pub height: u32,
+}
+
+impl fmt::Display for EncodePlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}x{} {}", self.width, self.height, self.format)?;
+        if let Some(size) = &self.symbol_size {
+            write!(f, ", {size}")?;
+        }
+        if let Some(ec) = &self.error_correction {
+            write!(f, ", ec level {ec}")?;
+        }
+        Ok(())
+    }
+}
+
+/**
+ * Determines the symbol `MultiFormatWriter` would produce for the contents, honoring the
+ * same hints, without error correction, masking or rendering. This is cheap enough to
+ * run on every keystroke, e.g. to show "this will be a 37x37 QR" while the user types.
+ *
+ * Returns the same error the writer would return if the contents can not be encoded.
+ */
+pub fn plan_encode(
+    contents: &str,
+    format: BarcodeFormat,
+    hints: &EncodeHints,
+) -> Result<EncodePlan> {
+    match format {
+        BarcodeFormat::QR_CODE => plan_qr_code(contents, hints),
+        BarcodeFormat::DATA_MATRIX => {
+            let (_, symbol) = DataMatrixWriter::encode_data(contents, hints)?;
+            let size = SymbolSize::DataMatrix {
+                width: symbol.getSymbolWidth()?,
+                height: symbol.getSymbolHeight()?,
+            };
+            Ok(plan_2d(format, size, None, Vec::new()))
+        }
+        BarcodeFormat::AZTEC => {
+            let code = aztec::encode_code(contents, hints)?;
+            let size = SymbolSize::Aztec {
+                compact: code.isCompact(),
+                layers: code.getLayers(),
+            };
+            // runes have no layers and no error correction
+            let ec = (code.getLayers() > 0).then(|| {
+                hints
+                    .ErrorCorrection
+                    .clone()
+                    .unwrap_or_else(|| aztec_encoder::DEFAULT_EC_PERCENT.to_string())
+            });
+            Ok(plan_2d(format, size, ec, Vec::new()))
+        }
+        BarcodeFormat::PDF_417 if matches!(hints.Pdf417Micro, Some(true)) => {
+            // the size of a MicroPDF417 symbol sets its error correction
+            let (mut encoder, auto_eci) = PDF417Writer::microEncoderFromHints(hints)?;
+            encoder.generateBarcodeLogic(contents, auto_eci)?;
+            let matrix = encoder
+                .getBarcodeMatrix()
+                .as_ref()
+                .ok_or(Exceptions::illegal_state_with("missing micropdf417 matrix"))?;
+            let size = SymbolSize::Pdf417 {
+                columns: matrix.getColumnCount() as u32,
+                rows: matrix.getRowCount() as u32,
+            };
+            let width = matrix.getMatrix().first().map_or(0, Vec::len) as u32;
+            Ok(EncodePlan {
+                width,
+                ..plan_2d(format, size, None, Vec::new())
+            })
+        }
+        BarcodeFormat::PDF_417 => {
+            let (mut encoder, ec_level, auto_eci) = PDF417Writer::encoderFromHints(hints)?;
+            encoder.generateBarcodeLogicWithAutoECI(contents, ec_level, auto_eci)?;
+            let matrix = encoder
+                .getBarcodeMatrix()
+                .as_ref()
+                .ok_or(Exceptions::illegal_state_with("missing pdf417 matrix"))?;
+            let size = SymbolSize::Pdf417 {
+                columns: matrix.getColumnCount() as u32,
+                rows: matrix.getRowCount() as u32,
+            };
+            let width = matrix.getMatrix().first().map_or(0, Vec::len) as u32;
+            Ok(EncodePlan {
+                width,
+                ..plan_2d(format, size, Some(ec_level.to_string()), Vec::new())
+            })
+        }
+        _ if format.is_1d() && format.can_encode() => {
+            // 1D symbols are cheap to encode, the single row holds the module count
+            let hints = EncodeHints {
+                Margin: Some("0".to_owned()),
+                ..hints.clone()
+            };
+            let row = MultiFormatWriter.encode_with_hints(contents, &format, 0, 1, &hints)?;
+            Ok(EncodePlan {
+                format,
+                symbol_size: None,
+                error_correction: None,
+                segments: Vec::new(),
+                width: row.getWidth(),
+                height: 1,
+            })
+        }
+        _ => Err(Exceptions::illegal_argument_with(format!(
+            "No encoder available for format {format:?}"
+        ))),
+    }
+}
+
+fn plan_qr_code(contents: &str, hints: &EncodeHints) -> Result<EncodePlan> {
+    let ec_level = if let Some(ec_level) = &hints.ErrorCorrection {
+        ec_level.parse()?
+    } else {
+        ErrorCorrectionLevel::L
+    };
+    let data = qrcode_encoder::encode_data_with_hints(contents, ec_level, hints)?;
+
+    let segments = data
+        .segments
+        .iter()
+        .filter_map(|(mode, characters)| {
+            let mode = match mode {
+                Mode::NUMERIC => EncodeMode::Numeric,
+                Mode::ALPHANUMERIC => EncodeMode::Alphanumeric,
+                Mode::BYTE => EncodeMode::Byte,
+                Mode::KANJI | Mode::HANZI => EncodeMode::Kanji,
+                _ => return None,
+            };
+            Some(EncodeSegment {
+                mode,
+                characters: *characters as usize,
+            })
+        })
+        .collect();
+
+    Ok(plan_2d(
+        BarcodeFormat::QR_CODE,
+        SymbolSize::QrVersion(data.version.getVersionNumber()),
+        Some(ec_level.to_string()),
+        segments,
+    ))
+}
+
+fn plan_2d(
+    format: BarcodeFormat,
+    size: SymbolSize,
+    error_correction: Option<String>,
+    segments: Vec<EncodeSegment>,
+) -> EncodePlan {
+    let (width, height) = size.dimensions();
+    EncodePlan {
+        format,
+        symbol_size: Some(size),
+        error_correction,
+        segments,
+        width,
+        height,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        plan_encode, BarcodeFormat, EncodeHints, EncodeMode, EncodeSegment, MultiFormatWriter,
+        SymbolSize, Writer,
+    };
+
+    #[test]
+    fn test_plan_matches_writer() {
+        let hints = EncodeHints {
+            Margin: Some("0".to_owned()),
+            ..Default::default()
+        };
+        for (format, contents) in [
+            (
+                BarcodeFormat::QR_CODE,
+                "https://example.com/some/path?query=1",
+            ),
+            (BarcodeFormat::DATA_MATRIX, "Data Matrix plan"),
+            (BarcodeFormat::AZTEC, "Aztec plan test"),
+            (BarcodeFormat::EAN_13, "5901234123457"),
+            (BarcodeFormat::CODE_128, "plan 128"),
+        ] {
+            let plan = plan_encode(contents, format.clone(), &hints).unwrap();
+            let matrix = MultiFormatWriter
+                .encode_with_hints(contents, &format, 0, 0, &hints)
+                .unwrap();
+            assert_eq!(matrix.getWidth(), plan.width, "{plan}");
+            if format.is_2d() {
+                assert_eq!(matrix.getHeight(), plan.height, "{plan}");
+            }
+        }
+
+        assert!(plan_encode("not digits", BarcodeFormat::EAN_13, &hints).is_err());
+        assert!(plan_encode("maxicode", BarcodeFormat::MAXICODE, &hints).is_err());
+    }
+
+    #[test]
+    fn test_plan_qr_code() {
+        let plan = plan_encode(
+            "HELLO WORLD",
+            BarcodeFormat::QR_CODE,
+            &EncodeHints::default(),
+        )
+        .unwrap();
+        assert_eq!(Some(SymbolSize::QrVersion(1)), plan.symbol_size);
+        assert_eq!(Some("L"), plan.error_correction.as_deref());
+        assert_eq!(
+            vec![EncodeSegment {
+                mode: EncodeMode::Alphanumeric,
+                characters: 11
+            }],
+            plan.segments
+        );
+        assert_eq!(
+            "21x21 qrcode, version 1 (21x21), ec level L",
+            plan.to_string()
+        );
+
+        // the minimal encoder splits the data into segments
+        let hints = EncodeHints {
+            QrCompact: Some("true".to_owned()),
+            ..Default::default()
+        };
+        let plan = plan_encode("abcdef0123456789012345", BarcodeFormat::QR_CODE, &hints).unwrap();
+        assert_eq!(
+            vec![EncodeMode::Byte, EncodeMode::Numeric],
+            plan.segments.iter().map(|s| s.mode).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_plan_pdf417() {
+        let plan = plan_encode(
+            "PDF417 plan",
+            BarcodeFormat::PDF_417,
+            &EncodeHints::default(),
+        )
+        .unwrap();
+        let Some(SymbolSize::Pdf417 { columns, rows }) = plan.symbol_size else {
+            panic!("expected a pdf417 size");
+        };
+        assert_eq!(17 * columns + 69, plan.width);
+        assert_eq!(rows, plan.height);
+        assert_eq!(Some("2"), plan.error_correction.as_deref());
+    }
+
+    #[test]
+    fn test_plan_aztec_rune() {
+        let hints = EncodeHints::default().with(crate::EncodeHintValue::AztecRune(true));
+        let plan = plan_encode("42", BarcodeFormat::AZTEC, &hints).unwrap();
+        assert_eq!(
+            Some(SymbolSize::Aztec {
+                compact: true,
+                layers: 0
+            }),
+            plan.symbol_size
+        );
+        assert_eq!((11, 11), (plan.width, plan.height));
+        assert_eq!(None, plan.error_correction);
+    }
+
+    #[test]
+    fn test_plan_micro_pdf417() {
+        let hints = EncodeHints::default().with(crate::EncodeHintValue::Pdf417Micro(true));
+        let plan = plan_encode("1234", BarcodeFormat::PDF_417, &hints).unwrap();
+        assert_eq!(
+            Some(SymbolSize::Pdf417 {
+                columns: 4,
+                rows: 4
+            }),
+            plan.symbol_size
+        );
+        assert_eq!(99, plan.width);
+        assert_eq!(None, plan.error_correction);
+    }
+}
@@ -0,0 +1,198 @@
+/*
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use crate::common::BitMatrix;
+use crate::BarcodeFormat;
+
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+/**
+ * Holds the intermediates of each step of an encode, so that a proof can show that a symbol
+ * encodes a given message: the message is segmented, the segments are turned into data
+ * codewords, each block of data codewords gets its parity, the blocks are interleaved and
+ * placed in the symbol, and the data modules are masked. QR codes are recorded by
+ * `qrcode_encoder::encode_with_witness`, Aztec symbols by `aztec_encoder::encode_with_witness`.
+ *
+ * # Fields
+ * * `format` - The format of the symbol
+ * * `content` - The message that was encoded
+ * * `version` - The version of the symbol, or the number of layers of an Aztec symbol
+ * * `segments` - The segments the message was split into, for formats with a character count
+ *   per segment. Aztec symbols latch and shift between modes character by character, which
+ *   `data_bits` holds
+ * * `data_bits` - The bits of the segmented message, mode indicators and character counts
+ *   included, before termination and padding, most significant bit first
+ * * `num_data_bits` - How many bits of `data_bits` are valid
+ * * `codeword_size` - The number of bits in a codeword: 8 for QR codes, from 6 to 12 depending
+ *   on the number of layers for Aztec symbols
+ * * `data_codewords` - The data codewords, terminated and padded to the capacity of the symbol,
+ *   or bit stuffed for Aztec symbols
+ * * `blocks` - The error correction blocks, in the order they are interleaved
+ * * `codewords` - The data and parity codewords, interleaved in placement order
+ * * `mask` - The mask applied to the data modules, for formats that mask
+ * * `matrix` - The final symbol, one bit per module
+ */
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncodingWitness {
+    pub format: BarcodeFormat,
+    pub content: String,
+    pub version: u32,
+    pub segments: Vec<EncodedSegment>,
+    pub data_bits: Vec<u8>,
+    pub num_data_bits: usize,
+    pub codeword_size: u32,
+    pub data_codewords: Vec<u16>,
+    pub blocks: Vec<ParityBlock>,
+    pub codewords: Vec<u16>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub mask: Option<MaskWitness>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(serialize_with = "crate::pipeline_witness::serialize_bitmatrix")
+    )]
+    pub matrix: BitMatrix,
+}
+
+/// A run of the message encoded in a single mode
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct EncodedSegment {
+    /// The name of the mode, such as `NUMERIC` or `BYTE`
+    pub mode: String,
+    /// The number of characters, or of bytes for byte mode
+    pub character_count: u32,
+}
+
+/// The data codewords of an error correction block and the parity computed over them
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ParityBlock {
+    pub data: Vec<u16>,
+    pub parity: Vec<u16>,
+}
+
+/**
+ * How the mask was applied: every module set in `data_modules` is the corresponding module of
+ * `unmasked` flipped by the mask `pattern`, every other module is copied as is.
+ */
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MaskWitness {
+    pub pattern: u32,
+    /// The modules holding codewords, which are the only ones masked
+    #[cfg_attr(
+        feature = "serde",
+        serde(serialize_with = "crate::pipeline_witness::serialize_bitmatrix")
+    )]
+    pub data_modules: BitMatrix,
+    /// The symbol before masking, with function patterns and format information in place
+    #[cfg_attr(
+        feature = "serde",
+        serde(serialize_with = "crate::pipeline_witness::serialize_bitmatrix")
+    )]
+    pub unmasked: BitMatrix,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::qrcode::decoder::{qrcode_decoder, ErrorCorrectionLevel};
+    use crate::qrcode::encoder::{mask_util, qrcode_encoder};
+    use crate::EncodeHints;
+
+    #[test]
+    fn test_qr_encoding_witness() {
+        // version 5-Q has two groups of blocks of different sizes
+        let content = "the encoding witness of a message spread over blocks";
+        let (qr_code, witness) = qrcode_encoder::encode_with_witness(
+            content,
+            ErrorCorrectionLevel::Q,
+            &EncodeHints::default(),
+        )
+        .unwrap();
+        assert_eq!(5, witness.version);
+        assert_eq!(4, witness.blocks.len());
+        assert_ne!(witness.blocks[0].data.len(), witness.blocks[3].data.len());
+        assert_eq!(1, witness.segments.len());
+        assert_eq!("BYTE", witness.segments[0].mode);
+        assert_eq!(content.len() as u32, witness.segments[0].character_count);
+        assert_eq!(8, witness.codeword_size);
+
+        // the segment is the byte mode indicator, the count and the bytes, which the data
+        // codewords start with before the terminator and the padding
+        assert_eq!(4 + 8 + 8 * content.len(), witness.num_data_bits);
+        assert_eq!(0b0100, witness.data_bits[0] >> 4);
+        let data_bytes: Vec<u16> = witness.data_bits.iter().map(|&b| b as u16).collect();
+        assert_eq!(
+            data_bytes[..witness.num_data_bits / 8],
+            witness.data_codewords[..witness.num_data_bits / 8]
+        );
+
+        // the blocks split the data codewords, and each carries its parity
+        let data: Vec<u16> = witness.blocks.iter().flat_map(|b| b.data.clone()).collect();
+        assert_eq!(witness.data_codewords, data);
+        for block in &witness.blocks {
+            let bytes: Vec<u8> = block.data.iter().map(|&b| b as u8).collect();
+            let parity = qrcode_encoder::generateECBytes(&bytes, block.parity.len()).unwrap();
+            let parity: Vec<u16> = parity.into_iter().map(u16::from).collect();
+            assert_eq!(parity, block.parity);
+        }
+
+        // the codewords interleave the blocks, data first
+        let mut interleaved = Vec::new();
+        for i in 0..witness.blocks.iter().map(|b| b.data.len()).max().unwrap() {
+            interleaved.extend(witness.blocks.iter().filter_map(|b| b.data.get(i)));
+        }
+        for i in 0..witness.blocks[0].parity.len() {
+            interleaved.extend(witness.blocks.iter().map(|b| b.parity[i]));
+        }
+        assert_eq!(witness.codewords, interleaved);
+
+        // the mask flips exactly the data modules
+        let mask = witness.mask.as_ref().unwrap();
+        assert_eq!(qr_code.getMaskPattern() as u32, mask.pattern);
+        let byte_matrix = qr_code.getMatrix().as_ref().unwrap();
+        for y in 0..witness.matrix.getHeight() {
+            for x in 0..witness.matrix.getWidth() {
+                let flip = mask.data_modules.get(x, y)
+                    && mask_util::getDataMaskBit(mask.pattern, x, y).unwrap();
+                assert_eq!(mask.unmasked.get(x, y) ^ flip, witness.matrix.get(x, y));
+                assert_eq!(byte_matrix.get(x, y) == 1, witness.matrix.get(x, y));
+            }
+        }
+
+        // and the symbol decodes back to the data codewords and the message
+        let decoded = qrcode_decoder::decode_bitmatrix(&witness.matrix).unwrap();
+        let raw: Vec<u16> = decoded.getRawBytes().iter().map(|&b| b as u16).collect();
+        assert_eq!(witness.data_codewords, raw);
+        assert_eq!(content, decoded.getText());
+    }
+
+    #[test]
+    fn test_mask_hint() {
+        let hints = EncodeHints {
+            QrMaskPattern: Some("3".to_owned()),
+            ..Default::default()
+        };
+        let (qr_code, witness) =
+            qrcode_encoder::encode_with_witness("12345", ErrorCorrectionLevel::L, &hints).unwrap();
+        assert_eq!(3, qr_code.getMaskPattern());
+        assert_eq!(3, witness.mask.unwrap().pattern);
+        assert_eq!("NUMERIC", witness.segments[0].mode);
+        let plain =
+            qrcode_encoder::encode_with_hints("12345", ErrorCorrectionLevel::L, &hints).unwrap();
+        assert_eq!(plain.getMatrix(), qr_code.getMatrix());
+    }
+}
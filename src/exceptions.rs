@@ -31,6 +31,8 @@ pub enum Exceptions {
     RuntimeException(String),
     #[error("ParseException{}", if .0.is_empty() { String::new()  } else { format!(" - {}", .0) })]
     ParseException(String),
+    #[error("CancelledException{}", if .0.is_empty() { String::new()  } else { format!(" - {}", .0) })]
+    CancelledException(String),
     #[error("ReaderDecodeException")]
     ReaderDecodeException(),
 }
@@ -100,4 +102,9 @@ impl Exceptions {
     pub fn parse_with<I: Into<String>>(x: I) -> Self {
         Self::ParseException(x.into())
     }
+
+    pub const CANCELLED: Self = Self::CancelledException(String::new());
+    pub fn cancelled_with<I: Into<String>>(x: I) -> Self {
+        Self::CancelledException(x.into())
+    }
 }
@@ -67,7 +67,7 @@ impl<R: Reader> Reader for FilteredImageReader<R> {
 }
 
 #[derive(Debug, Clone, Default)]
-struct LumImagePyramid {
+pub(crate) struct LumImagePyramid {
     pub layers: Vec<Luma8LuminanceSource>,
 }
 
@@ -0,0 +1,296 @@
+//! GStreamer integration, enabled by the `gstreamer` feature.
+//!
+//! [`luminance_source_from_sample`] turns a GRAY8 or NV12 video frame pulled from an `appsink`
+//! into a [`Luma8LuminanceSource`], and [`BarcodeSink`] wraps an `appsink` that decodes every
+//! frame it receives, so a pipeline ending in it reports barcodes as they come into view:
+//!
+//! ```no_run
+//! use gstreamer::prelude::*;
+//! use rxing::{gstreamer::BarcodeSink, DecodeHints};
+//!
+//! gstreamer::init().unwrap();
+//! let pipeline = gstreamer::parse::launch("v4l2src ! videoconvert ! queue name=tail")
+//!     .unwrap()
+//!     .downcast::<gstreamer::Pipeline>()
+//!     .unwrap();
+//! let sink = BarcodeSink::new(DecodeHints::default(), |found| println!("{}", found.result));
+//! pipeline.add(sink.element()).unwrap();
+//! pipeline.by_name("tail").unwrap().link(sink.element()).unwrap();
+//! pipeline.set_state(gstreamer::State::Playing).unwrap();
+//! ```
+
+use std::{
+    collections::HashSet,
+    sync::mpsc::{self, Receiver},
+    time::Duration,
+};
+
+use ::gstreamer as gst;
+use gst::prelude::*;
+use gstreamer_app::{AppSink, AppSinkCallbacks};
+use gstreamer_video::{prelude::*, VideoCapsBuilder, VideoFormat, VideoFrameRef, VideoInfo};
+
+use crate::{
+    common::Result,
+    helpers::{self, FrameResult},
+    BarcodeFormat, DecodeHints, Exceptions, Luma8LuminanceSource, LuminanceSource,
+};
+
+/// The raw video formats frames can be decoded from
+pub const SUPPORTED_VIDEO_FORMATS: [VideoFormat; 2] = [VideoFormat::Gray8, VideoFormat::Nv12];
+
+/**
+ * Copies the luma of a video frame into a luminance source. Only the Y plane of an NV12 frame
+ * is read, chroma plays no part in decoding.
+ *
+ * # Arguments
+ * * `sample` - A sample with GRAY8 or NV12 video caps, as pulled from an `appsink`
+ *
+ * # Returns
+ * The luminance source, or an error if the caps are not raw video in a supported format or
+ * the buffer can't be mapped
+ */
+pub fn luminance_source_from_sample(sample: &gst::Sample) -> Result<Luma8LuminanceSource> {
+    let Some(caps) = sample.caps() else {
+        return Err(Exceptions::illegal_argument_with("sample has no caps"));
+    };
+    let Some(buffer) = sample.buffer() else {
+        return Err(Exceptions::illegal_argument_with("sample has no buffer"));
+    };
+    let info = VideoInfo::from_caps(caps)
+        .map_err(|e| Exceptions::illegal_argument_with(format!("not raw video: {e}")))?;
+    let frame = VideoFrameRef::from_buffer_ref_readable(buffer, &info)
+        .map_err(|e| Exceptions::runtime_with(format!("couldn't map video frame: {e}")))?;
+
+    luminance_source_from_frame(&frame)
+}
+
+/**
+ * Copies the Y plane of a mapped GRAY8 or NV12 frame into a luminance source, dropping the
+ * padding at the end of each row.
+ */
+pub fn luminance_source_from_frame(
+    frame: &VideoFrameRef<&gst::BufferRef>,
+) -> Result<Luma8LuminanceSource> {
+    if !SUPPORTED_VIDEO_FORMATS.contains(&frame.format()) {
+        return Err(Exceptions::unsupported_operation_with(format!(
+            "video format {} is not supported, GRAY8 and NV12 are",
+            frame.format().to_str()
+        )));
+    }
+
+    let (width, height) = (frame.width() as usize, frame.height() as usize);
+    let stride = frame.plane_stride()[0] as usize;
+    let plane = frame
+        .plane_data(0)
+        .map_err(|e| Exceptions::runtime_with(format!("couldn't read luma plane: {e}")))?;
+    if stride < width || plane.len() < stride * (height - 1) + width {
+        return Err(Exceptions::illegal_argument_with(
+            "luma plane is smaller than the frame",
+        ));
+    }
+
+    let luma = plane
+        .chunks(stride)
+        .take(height)
+        .flat_map(|row| &row[..width])
+        .copied()
+        .collect();
+    Ok(Luma8LuminanceSource::new(luma, width as u32, height as u32))
+}
+
+/**
+ * An `appsink` that decodes every frame it receives and reports each barcode when it comes
+ * into view, that is when it was not decoded in the frame before. A barcode held in front of
+ * the camera is reported once, and again if it is taken away and shown again.
+ *
+ * The sink accepts GRAY8 and NV12, put a `videoconvert` in front of it for other formats. It
+ * keeps only the latest frame and does not synchronize to the clock, so a slow decode drops
+ * frames instead of stalling the pipeline.
+ */
+pub struct BarcodeSink {
+    appsink: AppSink,
+}
+
+impl BarcodeSink {
+    /**
+     * Creates the sink, calling `on_result` on the streaming thread for each barcode as it
+     * comes into view, with the index of the frame and its presentation timestamp.
+     */
+    pub fn new<F>(mut hints: DecodeHints, mut on_result: F) -> Self
+    where
+        F: FnMut(FrameResult) + Send + 'static,
+    {
+        let caps = VideoCapsBuilder::new()
+            .format_list(SUPPORTED_VIDEO_FORMATS)
+            .build();
+        let appsink = AppSink::builder()
+            .caps(&caps)
+            .max_buffers(1)
+            .drop(true)
+            .sync(false)
+            .build();
+
+        let mut frame = 0;
+        let mut in_view = HashSet::<(BarcodeFormat, String)>::new();
+        appsink.set_callbacks(
+            AppSinkCallbacks::builder()
+                .new_sample(move |sink| {
+                    let sample = sink.pull_sample().map_err(|_| gst::FlowError::Eos)?;
+                    let timestamp = sample
+                        .buffer()
+                        .and_then(|buffer| buffer.pts())
+                        .map(|pts| Duration::from_nanos(pts.nseconds()))
+                        .unwrap_or_default();
+
+                    // a frame that can't be read or holds no barcode clears the view
+                    let found = luminance_source_from_sample(&sample)
+                        .and_then(|source| {
+                            let (width, height) = (source.get_width(), source.get_height());
+                            helpers::detect_multiple_in_luma_with_hints(
+                                source.get_matrix(),
+                                width as u32,
+                                height as u32,
+                                &mut hints,
+                            )
+                        })
+                        .unwrap_or_default();
+
+                    let previous = std::mem::take(&mut in_view);
+                    for result in found {
+                        let key = (
+                            result.getBarcodeFormat().clone(),
+                            result.getText().to_owned(),
+                        );
+                        if !previous.contains(&key) {
+                            on_result(FrameResult {
+                                frame,
+                                timestamp,
+                                result,
+                            });
+                        }
+                        in_view.insert(key);
+                    }
+                    frame += 1;
+
+                    Ok(gst::FlowSuccess::Ok)
+                })
+                .build(),
+        );
+
+        Self { appsink }
+    }
+
+    /**
+     * Creates the sink and a channel its barcodes are sent to, see `new`.
+     */
+    pub fn with_channel(hints: DecodeHints) -> (Self, Receiver<FrameResult>) {
+        let (sender, receiver) = mpsc::channel();
+        let sink = Self::new(hints, move |found| {
+            // the receiver may have been dropped, the pipeline keeps running regardless
+            let _ = sender.send(found);
+        });
+        (sink, receiver)
+    }
+
+    /**
+     * Returns the sink as an element, to add to a pipeline and link.
+     */
+    pub fn element(&self) -> &gst::Element {
+        self.appsink.upcast_ref()
+    }
+
+    /**
+     * Returns the wrapped `appsink`.
+     */
+    pub fn appsink(&self) -> &AppSink {
+        &self.appsink
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        common::BitMatrix, test_fixtures::scaled_luma, EncodeHintValue, EncodeHints,
+        MultiFormatWriter, Writer,
+    };
+
+    // A GRAY8 or NV12 frame of a QR code, or of a blank page, with its rows padded to a
+    // multiple of 4 bytes as GStreamer lays them out
+    fn video_sample(
+        contents: Option<&str>,
+        format: VideoFormat,
+        pts: u64,
+    ) -> (Vec<u8>, gst::Sample) {
+        let hints = EncodeHints::default().with(EncodeHintValue::Margin("4".to_owned()));
+        let symbol = match contents {
+            Some(contents) => MultiFormatWriter
+                .encode_with_hints(contents, &BarcodeFormat::QR_CODE, 0, 0, &hints)
+                .unwrap(),
+            None => BitMatrix::with_single_dimension(29).unwrap(),
+        };
+        let (luma, side, _) = scaled_luma(&symbol, 3, 0, 255);
+
+        let info = VideoInfo::builder(format, side, side).build().unwrap();
+        let stride = info.stride()[0] as usize;
+        let mut data = vec![128; info.size()];
+        for (row, pixels) in luma.chunks(side as usize).enumerate() {
+            data[row * stride..row * stride + pixels.len()].copy_from_slice(pixels);
+        }
+        let mut buffer = gst::Buffer::from_mut_slice(data);
+        let pts = gst::ClockTime::from_mseconds(pts);
+        buffer.get_mut().unwrap().set_pts(pts);
+        let caps = info.to_caps().unwrap();
+        let sample = gst::Sample::builder().buffer(&buffer).caps(&caps).build();
+        (luma, sample)
+    }
+
+    #[test]
+    fn test_luminance_source_from_sample() {
+        gst::init().unwrap();
+
+        for format in SUPPORTED_VIDEO_FORMATS {
+            let (luma, sample) = video_sample(Some("gstreamer"), format, 0);
+            let source = luminance_source_from_sample(&sample).unwrap();
+            assert_eq!(luma, source.get_matrix());
+        }
+
+        let (_, sample) = video_sample(Some("gstreamer"), VideoFormat::Rgbx, 0);
+        assert!(luminance_source_from_sample(&sample).is_err());
+    }
+
+    #[test]
+    fn test_barcode_sink() {
+        gst::init().unwrap();
+
+        let (sink, found) = BarcodeSink::with_channel(DecodeHints::default());
+        let appsink = sink.appsink();
+        appsink.set_state(gst::State::Playing).unwrap();
+        let pad = appsink.static_pad("sink").unwrap();
+        pad.send_event(gst::event::StreamStart::new("test"));
+        let segment = gst::FormattedSegment::<gst::ClockTime>::new();
+        pad.send_event(gst::event::Segment::new(&segment));
+
+        // the code is reported when it comes into view and again once it returns
+        let frames = [
+            (Some("kiosk"), VideoFormat::Nv12, 0),
+            (Some("kiosk"), VideoFormat::Nv12, 40),
+            (None, VideoFormat::Gray8, 80),
+            (Some("kiosk"), VideoFormat::Gray8, 120),
+        ];
+        for (contents, format, pts) in frames {
+            let (_, sample) = video_sample(contents, format, pts);
+            pad.send_event(gst::event::Caps::new(&sample.caps_owned().unwrap()));
+            pad.chain(sample.buffer_owned().unwrap()).unwrap();
+        }
+        appsink.set_state(gst::State::Null).unwrap();
+
+        let found: Vec<_> = found.try_iter().collect();
+        assert_eq!(2, found.len());
+        assert_eq!("kiosk", found[0].result.getText());
+        assert_eq!((0, Duration::ZERO), (found[0].frame, found[0].timestamp));
+        let returned = (found[1].frame, found[1].timestamp);
+        assert_eq!((3, Duration::from_millis(120)), returned);
+    }
+}
@@ -1,15 +1,35 @@
-use std::{collections::HashSet, io::Write, path::PathBuf};
+use std::{
+    collections::HashSet,
+    io::{Read, Write},
+    path::PathBuf,
+    sync::{Mutex, PoisonError},
+};
 
 use crate::{
-    BarcodeFormat, Binarizer, BinaryBitmap, DecodeHints, Exceptions, FilteredImageReader,
-    LuminanceSource, Luma8LuminanceSource, MultiFormatReader, MultiUseMultiFormatReader,
-    RXingResult, Reader, WitnessData,
     common::{BitMatrix, FixedThresholdBinarizer, Result},
     multi::{GenericMultipleBarcodeReader, MultipleBarcodeReader},
+    stream_witness, BarcodeFormat, BinarizationWitness, Binarizer, BinaryBitmap, DecodeHints,
+    DecodeOptions, Exceptions, FilteredImageReader, Luma8LuminanceSource, LuminanceSource,
+    LuminanceWitness, MultiFormatReader, MultiUseMultiFormatReader, PipelineWitness, PyramidReader,
+    RXingResult, Reader, WitnessProvenance,
 };
 
+#[cfg(feature = "image-render")]
+use crate::render::{render_to_image, ImageRenderOptions};
+#[cfg(feature = "image")]
+use crate::{point, BufferedImageLuminanceSource, Point};
+#[cfg(feature = "serde")]
+use crate::{
+    DetectionWitness, RXingResultMetadataType, RXingResultMetadataValue, SamplingWitness,
+    TextAssemblyWitness, WitnessWriter,
+};
 #[cfg(feature = "image")]
-use crate::BufferedImageLuminanceSource;
+use std::{
+    path::Path,
+    time::{Duration, Instant},
+};
+#[cfg(feature = "image")]
+use thiserror::Error;
 
 #[cfg(feature = "svg_read")]
 pub fn detect_in_svg(file_name: &str, barcode_type: Option<BarcodeFormat>) -> Result<RXingResult> {
@@ -22,10 +42,84 @@ pub fn detect_in_svg_with_hints(
     barcode_type: Option<BarcodeFormat>,
     hints: &mut DecodeHints,
 ) -> Result<RXingResult> {
-    use std::{fs::File, io::Read};
+    detect_in_svg_at_dpi(file_name, crate::SVG_DEFAULT_DPI, barcode_type, hints)
+}
 
+/**
+ * Rasterizes the SVG at `dpi` pixels per inch and decodes it, see
+ * `SVGLuminanceSource::with_dpi`.
+ */
+#[cfg(feature = "svg_read")]
+pub fn detect_in_svg_at_dpi(
+    file_name: &str,
+    dpi: f32,
+    barcode_type: Option<BarcodeFormat>,
+    hints: &mut DecodeHints,
+) -> Result<RXingResult> {
     use crate::SVGLuminanceSource;
 
+    let svg_data = read_svg(file_name)?;
+
+    let mut multi_format_reader = PyramidReader::new(MultiFormatReader::default());
+
+    if let Some(bc_type) = barcode_type {
+        hints.PossibleFormats = Some(HashSet::from([bc_type]));
+    }
+
+    hints.TryHarder = hints.TryHarder.or(Some(true));
+
+    multi_format_reader.decode_with_hints(
+        &mut BinaryBitmap::new(FixedThresholdBinarizer::new(SVGLuminanceSource::with_dpi(
+            &svg_data, dpi,
+        )?)),
+        hints,
+    )
+}
+
+#[cfg(feature = "svg_read")]
+pub fn detect_multiple_in_svg(file_name: &str) -> Result<Vec<RXingResult>> {
+    detect_multiple_in_svg_with_hints(file_name, &mut DecodeHints::default())
+}
+
+#[cfg(feature = "svg_read")]
+pub fn detect_multiple_in_svg_with_hints(
+    file_name: &str,
+    hints: &mut DecodeHints,
+) -> Result<Vec<RXingResult>> {
+    detect_multiple_in_svg_at_dpi(file_name, crate::SVG_DEFAULT_DPI, hints)
+}
+
+/**
+ * Rasterizes the SVG at `dpi` pixels per inch and decodes every barcode in it, see
+ * `SVGLuminanceSource::with_dpi`.
+ */
+#[cfg(feature = "svg_read")]
+pub fn detect_multiple_in_svg_at_dpi(
+    file_name: &str,
+    dpi: f32,
+    hints: &mut DecodeHints,
+) -> Result<Vec<RXingResult>> {
+    use crate::SVGLuminanceSource;
+
+    let svg_data = read_svg(file_name)?;
+
+    let multi_format_reader = MultiUseMultiFormatReader::default();
+    let mut scanner = GenericMultipleBarcodeReader::new(multi_format_reader);
+
+    hints.TryHarder = hints.TryHarder.or(Some(true));
+
+    scanner.decode_multiple_with_hints(
+        &mut BinaryBitmap::new(FixedThresholdBinarizer::new(SVGLuminanceSource::with_dpi(
+            &svg_data, dpi,
+        )?)),
+        hints,
+    )
+}
+
+#[cfg(feature = "svg_read")]
+fn read_svg(file_name: &str) -> Result<Vec<u8>> {
+    use std::{fs::File, io::Read};
+
     let path = PathBuf::from(file_name);
     if !path.exists() {
         return Err(Exceptions::illegal_argument_with("file does not exist"));
@@ -40,118 +134,1568 @@ pub fn detect_in_svg_with_hints(
         return Err(Exceptions::illegal_argument_with("file cannot be read"));
     }
 
-    let mut multi_format_reader = MultiFormatReader::default();
+    Ok(svg_data)
+}
+
+/**
+ * Detects a barcode in an image file, turned upright as its EXIF orientation says. The result
+ * points are pixels of the file as stored, as if it had not been turned.
+ */
+#[cfg(feature = "image")]
+pub fn detect_in_file(file_name: &str, barcode_type: Option<BarcodeFormat>) -> Result<RXingResult> {
+    detect_in_file_with_hints(file_name, barcode_type, &mut DecodeHints::default())
+}
+
+#[cfg(feature = "image")]
+pub fn detect_in_file_with_hints(
+    file_name: &str,
+    barcode_type: Option<BarcodeFormat>,
+    hints: &mut DecodeHints,
+) -> Result<RXingResult> {
+    detect_in_file_outcome(file_name, barcode_type, hints)
+        .result
+        .map_err(|e| match e {
+            FileDecodeError::Open(_) => Exceptions::illegal_argument_with(format!(
+                "file '{file_name}' not found or cannot be opened"
+            )),
+            FileDecodeError::Decode(e) => e,
+        })
+}
+
+/// Like [`detect_in_file_with_hints`], with the hints built by `options`
+#[cfg(feature = "image")]
+pub fn detect_in_file_with_options(file_name: &str, options: DecodeOptions) -> Result<RXingResult> {
+    detect_in_file_with_hints(file_name, None, &mut options.into())
+}
+
+/**
+ * Opens an image for decoding, telling its format from the contents rather than the extension.
+ * HEIF and HEIC images are read with the `heic` feature, AVIF images with the `avif` feature.
+ *
+ * The image is turned upright as its EXIF orientation says, so a photo taken with the phone
+ * held sideways reads without trying rotations.
+ */
+#[cfg(feature = "image")]
+pub fn open_image<P: AsRef<Path>>(path: P) -> image::ImageResult<image::DynamicImage> {
+    register_image_hooks();
+    decode_upright(image::ImageReader::open(path)?.with_guessed_format()?)
+}
+
+/// Like [`open_image`], for an image already in memory
+#[cfg(feature = "image")]
+pub fn load_image_from_memory(bytes: &[u8]) -> image::ImageResult<image::DynamicImage> {
+    register_image_hooks();
+    decode_upright(image::ImageReader::new(std::io::Cursor::new(bytes)).with_guessed_format()?)
+}
+
+/**
+ * Like [`open_image`], also returning the EXIF orientation the image was turned upright by, so
+ * points found in it can be mapped back to the pixels of the file with [`unorient_point`].
+ */
+#[cfg(feature = "image")]
+pub fn open_image_oriented<P: AsRef<Path>>(
+    path: P,
+) -> image::ImageResult<(image::DynamicImage, image::metadata::Orientation)> {
+    register_image_hooks();
+    decode_oriented(image::ImageReader::open(path)?.with_guessed_format()?)
+}
+
+/**
+ * Maps a point of an image turned upright by `orientation`, `width` by `height` pixels once
+ * upright, back to the pixel of the image as stored, before it was turned.
+ */
+#[cfg(feature = "image")]
+pub fn unorient_point(
+    p: Point,
+    orientation: image::metadata::Orientation,
+    width: u32,
+    height: u32,
+) -> Point {
+    use image::metadata::Orientation;
+
+    let (right, bottom) = (width as f32 - 1.0, height as f32 - 1.0);
+    match orientation {
+        Orientation::NoTransforms => p,
+        Orientation::Rotate90 => point(p.y, right - p.x),
+        Orientation::Rotate180 => point(right - p.x, bottom - p.y),
+        Orientation::Rotate270 => point(bottom - p.y, p.x),
+        Orientation::FlipHorizontal => point(right - p.x, p.y),
+        Orientation::FlipVertical => point(p.x, bottom - p.y),
+        Orientation::Rotate90FlipH => point(p.y, p.x),
+        Orientation::Rotate270FlipH => point(bottom - p.y, right - p.x),
+    }
+}
+
+#[cfg(feature = "image")]
+pub(crate) fn decode_upright<R: std::io::BufRead + std::io::Seek>(
+    reader: image::ImageReader<R>,
+) -> image::ImageResult<image::DynamicImage> {
+    decode_oriented(reader).map(|(img, _)| img)
+}
+
+#[cfg(feature = "image")]
+fn decode_oriented<R: std::io::BufRead + std::io::Seek>(
+    reader: image::ImageReader<R>,
+) -> image::ImageResult<(image::DynamicImage, image::metadata::Orientation)> {
+    use image::ImageDecoder;
+
+    let mut decoder = reader.into_decoder()?;
+    let orientation = decoder.orientation()?;
+    let mut img = image::DynamicImage::from_decoder(decoder)?;
+    img.apply_orientation(orientation);
+    Ok((img, orientation))
+}
+
+// The image crate reaches libheif only through the decoding hooks it registers
+#[cfg(feature = "image")]
+pub(crate) fn register_image_hooks() {
+    #[cfg(feature = "heic")]
+    {
+        static REGISTERED: std::sync::Once = std::sync::Once::new();
+        REGISTERED.call_once(|| {
+            libheif_rs::integration::image::register_heif_decoding_hook();
+            libheif_rs::integration::image::register_heic_decoding_hook();
+        });
+    }
+}
+
+/// Why a file could not be decoded
+#[cfg(feature = "image")]
+#[derive(Error, Debug, PartialEq, Eq, Clone)]
+pub enum FileDecodeError {
+    /// The file could not be read, or is not an image
+    #[error("could not open image: {0}")]
+    Open(String),
+    /// The image was read, but decoding failed
+    #[error(transparent)]
+    Decode(#[from] Exceptions),
+}
+
+/// The outcome of decoding one file, with the time it took to load and decode it
+#[cfg(feature = "image")]
+#[derive(Debug, Clone)]
+pub struct FileDecodeOutcome<T> {
+    pub path: PathBuf,
+    pub result: std::result::Result<T, FileDecodeError>,
+    pub elapsed: Duration,
+}
+
+#[cfg(feature = "image")]
+impl<T> FileDecodeOutcome<T> {
+    // the points are mapped back to the pixels of the file, as stored before turning it upright
+    fn timed<F>(path: &Path, decode: F) -> Self
+    where
+        F: FnOnce(image::DynamicImage) -> Result<T>,
+        T: FilePoints,
+    {
+        let start = Instant::now();
+        let result = open_image_oriented(path)
+            .map_err(|e| FileDecodeError::Open(e.to_string()))
+            .and_then(|(img, orientation)| {
+                let (width, height) = (img.width(), img.height());
+                let mut decoded = decode(img).map_err(FileDecodeError::Decode)?;
+                decoded.map_points(&|p| unorient_point(p, orientation, width, height));
+                Ok(decoded)
+            });
+        Self {
+            path: path.to_path_buf(),
+            result,
+            elapsed: start.elapsed(),
+        }
+    }
+
+    pub fn is_ok(&self) -> bool {
+        self.result.is_ok()
+    }
+}
+
+#[cfg(feature = "image")]
+trait FilePoints {
+    fn map_points(&mut self, map: &dyn Fn(Point) -> Point);
+}
+
+#[cfg(feature = "image")]
+impl FilePoints for RXingResult {
+    fn map_points(&mut self, map: &dyn Fn(Point) -> Point) {
+        for p in self.getPointsMut() {
+            *p = map(*p);
+        }
+    }
+}
+
+#[cfg(feature = "image")]
+impl FilePoints for Vec<RXingResult> {
+    fn map_points(&mut self, map: &dyn Fn(Point) -> Point) {
+        for result in self {
+            result.map_points(map);
+        }
+    }
+}
+
+/// Like [`detect_in_file_with_hints`], but reports the path, a typed error and the timing
+#[cfg(feature = "image")]
+pub fn detect_in_file_outcome<P: AsRef<Path>>(
+    path: P,
+    barcode_type: Option<BarcodeFormat>,
+    hints: &mut DecodeHints,
+) -> FileDecodeOutcome<RXingResult> {
+    if let Some(bc_type) = barcode_type {
+        hints.PossibleFormats = Some(HashSet::from([bc_type]));
+    }
+
+    hints.TryHarder = hints.TryHarder.or(Some(true));
+
+    FileDecodeOutcome::timed(path.as_ref(), |img| {
+        PyramidReader::new(MultiFormatReader::default()).decode_with_hints(
+            &mut BinaryBitmap::new(FixedThresholdBinarizer::new(
+                BufferedImageLuminanceSource::new(img),
+            )),
+            hints,
+        )
+    })
+}
+
+#[cfg(feature = "image")]
+pub fn detect_multiple_in_file(file_name: &str) -> Result<Vec<RXingResult>> {
+    detect_multiple_in_file_with_hints(file_name, &mut DecodeHints::default())
+}
+
+#[cfg(feature = "image")]
+pub fn detect_multiple_in_file_with_hints(
+    file_name: &str,
+    hints: &mut DecodeHints,
+) -> Result<Vec<RXingResult>> {
+    detect_multiple_in_file_outcome(file_name, hints)
+        .result
+        .map_err(|e| match e {
+            FileDecodeError::Open(e) => {
+                Exceptions::runtime_with(format!("couldn't read {file_name}: {e}"))
+            }
+            FileDecodeError::Decode(e) => e,
+        })
+}
+
+/// Like [`detect_multiple_in_file_with_hints`], with the hints built by `options`
+#[cfg(feature = "image")]
+pub fn detect_multiple_in_file_with_options(
+    file_name: &str,
+    options: DecodeOptions,
+) -> Result<Vec<RXingResult>> {
+    detect_multiple_in_file_with_hints(file_name, &mut options.into())
+}
+
+/**
+ * Detects all barcodes in a binary PGM file too large to decode whole, such as a page scanned
+ * at a high resolution. The file is mapped into memory and scanned a tile at a time by a
+ * [`TiledMultipleBarcodeReader`](crate::multi::TiledMultipleBarcodeReader).
+ */
+#[cfg(feature = "mmap")]
+pub fn detect_multiple_in_pgm_tiled(file_name: &str) -> Result<Vec<RXingResult>> {
+    detect_multiple_in_pgm_tiled_with_hints(file_name, &mut DecodeHints::default())
+}
+
+#[cfg(feature = "mmap")]
+pub fn detect_multiple_in_pgm_tiled_with_hints(
+    file_name: &str,
+    hints: &mut DecodeHints,
+) -> Result<Vec<RXingResult>> {
+    hints.TryHarder = hints.TryHarder.or(Some(true));
+
+    let source = crate::TiledLuminanceSource::new(crate::RawLumaStore::map_pgm(file_name)?);
+    crate::multi::TiledMultipleBarcodeReader::new(GenericMultipleBarcodeReader::new(
+        MultiUseMultiFormatReader::default(),
+    ))
+    .decode_multiple_with_hints(
+        &mut BinaryBitmap::new(crate::common::HybridBinarizer::new(source)),
+        hints,
+    )
+}
+
+/// Like [`detect_multiple_in_file_with_hints`], but reports the path, a typed error and the timing
+#[cfg(feature = "image")]
+pub fn detect_multiple_in_file_outcome<P: AsRef<Path>>(
+    path: P,
+    hints: &mut DecodeHints,
+) -> FileDecodeOutcome<Vec<RXingResult>> {
+    hints.TryHarder = hints.TryHarder.or(Some(true));
+
+    FileDecodeOutcome::timed(path.as_ref(), |img| {
+        GenericMultipleBarcodeReader::new(MultiUseMultiFormatReader::default())
+            .decode_multiple_with_hints(
+                &mut BinaryBitmap::new(FixedThresholdBinarizer::new(
+                    BufferedImageLuminanceSource::new(img),
+                )),
+                hints,
+            )
+    })
+}
+
+/**
+ * Detects all barcodes in each of `paths`. A failure on one file does not stop the batch:
+ * every path gets its own outcome, in the order given.
+ */
+#[cfg(feature = "image")]
+pub fn detect_multiple_in_files<P: AsRef<Path>>(
+    paths: &[P],
+) -> Vec<FileDecodeOutcome<Vec<RXingResult>>> {
+    detect_multiple_in_files_with_hints(paths, &DecodeHints::default())
+}
+
+#[cfg(feature = "image")]
+pub fn detect_multiple_in_files_with_hints<P: AsRef<Path>>(
+    paths: &[P],
+    hints: &DecodeHints,
+) -> Vec<FileDecodeOutcome<Vec<RXingResult>>> {
+    paths
+        .iter()
+        .map(|path| detect_multiple_in_file_outcome(path, &mut hints.clone()))
+        .collect()
+}
+
+/// A symbol found by [`detect_multiple_in_image`]
+#[cfg(feature = "image")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct DetectedSymbol {
+    pub format: BarcodeFormat,
+    pub text: String,
+    /**
+     * The top left, top right, bottom right and bottom left of the box around the points the
+     * reader found, turned by `rotation`. The corners of a linear symbol lie on the row it
+     * was read on.
+     */
+    pub corners: [Point; 4],
+    /**
+     * Degrees the symbol is turned clockwise from upright, from 0 to 360. An Aztec symbol
+     * reads in any direction, so its rotation is that of its nearest side.
+     */
+    pub rotation: f32,
+}
+
+#[cfg(feature = "image")]
+impl DetectedSymbol {
+    fn new(result: &RXingResult) -> Self {
+        let points = result.getPoints();
+        let angle = |from: Point, to: Point| (to.y - from.y).atan2(to.x - from.x).to_degrees();
+        let rotation = match (points, result.getBarcodeFormat()) {
+            // linear symbols are found from their start to their end
+            ([start, end], _) => angle(*start, *end),
+            // the corners of an Aztec symbol start top right, those of the others top left
+            ([tr, .., tl], BarcodeFormat::AZTEC) if points.len() == 4 => angle(*tl, *tr),
+            _ => match result
+                .getRXingResultMetadata()
+                .get(&crate::RXingResultMetadataType::ORIENTATION)
+            {
+                Some(crate::RXingResultMetadataValue::Orientation(degrees)) => *degrees as f32,
+                _ if points.len() >= 4 => angle(points[0], points[1]),
+                _ => 0.0,
+            },
+        }
+        .rem_euclid(360.0);
+
+        // the bounding box of the points, in the coordinates of the upright symbol
+        let (sin, cos) = rotation.to_radians().sin_cos();
+        let upright = |p: &Point| point(p.x * cos + p.y * sin, p.y * cos - p.x * sin);
+        let turned = |x: f32, y: f32| point(x * cos - y * sin, x * sin + y * cos);
+        let (left, top, right, bottom) = points.iter().map(upright).fold(
+            (f32::MAX, f32::MAX, f32::MIN, f32::MIN),
+            |(left, top, right, bottom), p| {
+                (left.min(p.x), top.min(p.y), right.max(p.x), bottom.max(p.y))
+            },
+        );
+        let corners = if points.is_empty() {
+            [Point::default(); 4]
+        } else {
+            [
+                turned(left, top),
+                turned(right, top),
+                turned(right, bottom),
+                turned(left, bottom),
+            ]
+        };
+
+        Self {
+            format: result.getBarcodeFormat().clone(),
+            text: result.getText().to_owned(),
+            corners,
+            rotation,
+        }
+    }
+
+    fn overlaps(&self, other: &DetectedSymbol) -> bool {
+        let bounds = |corners: &[Point; 4]| {
+            corners.iter().fold(
+                (f32::MAX, f32::MAX, f32::MIN, f32::MIN),
+                |(left, top, right, bottom), p| {
+                    (left.min(p.x), top.min(p.y), right.max(p.x), bottom.max(p.y))
+                },
+            )
+        };
+        let (left, top, right, bottom) = bounds(&self.corners);
+        let (other_left, other_top, other_right, other_bottom) = bounds(&other.corners);
+        left <= other_right && other_left <= right && top <= other_bottom && other_top <= bottom
+    }
+}
+
+#[cfg(feature = "image")]
+pub fn detect_multiple_in_image(image: image::DynamicImage) -> Result<Vec<DetectedSymbol>> {
+    detect_multiple_in_image_with_hints(image, &mut DecodeHints::default())
+}
+
+/**
+ * Detects all barcodes of any format in `image`, with the format, text, corners and rotation of
+ * each. A symbol read more than once, as the same text of the same format where it was already
+ * found, is reported once.
+ */
+#[cfg(feature = "image")]
+pub fn detect_multiple_in_image_with_hints(
+    image: image::DynamicImage,
+    hints: &mut DecodeHints,
+) -> Result<Vec<DetectedSymbol>> {
+    hints.TryHarder = hints.TryHarder.or(Some(true));
+
+    let results = GenericMultipleBarcodeReader::new(MultiUseMultiFormatReader::default())
+        .decode_multiple_with_hints(
+            &mut BinaryBitmap::new(FixedThresholdBinarizer::new(
+                BufferedImageLuminanceSource::new(image),
+            )),
+            hints,
+        )?;
+
+    let mut symbols: Vec<DetectedSymbol> = Vec::with_capacity(results.len());
+    for symbol in results.iter().map(DetectedSymbol::new) {
+        let found = symbols.iter().any(|found| {
+            found.format == symbol.format && found.text == symbol.text && found.overlaps(&symbol)
+        });
+        if !found {
+            symbols.push(symbol);
+        }
+    }
+    Ok(symbols)
+}
+
+/// Limits on the images the URL helpers download
+#[cfg(feature = "url_fetch")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UrlFetchOptions {
+    /// The largest response body read, larger images are refused
+    pub max_bytes: u64,
+    /// How long the whole request may take, from connecting to the end of the body
+    pub timeout: Duration,
+}
+
+#[cfg(feature = "url_fetch")]
+impl Default for UrlFetchOptions {
+    fn default() -> Self {
+        Self {
+            max_bytes: 20 * 1024 * 1024,
+            timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+/**
+ * Downloads the image at `url` and decodes it. The response must be successful, have an
+ * `image/` content type and stay within the limits of `UrlFetchOptions::default()`.
+ *
+ * This blocks the calling thread, and can't be called from within a tokio runtime, use
+ * `async_helpers::detect_in_url` there.
+ */
+#[cfg(feature = "url_fetch")]
+pub fn detect_in_url(url: &str) -> Result<RXingResult> {
+    detect_in_url_with_hints(
+        url,
+        None,
+        &UrlFetchOptions::default(),
+        &mut DecodeHints::default(),
+    )
+}
+
+#[cfg(feature = "url_fetch")]
+pub fn detect_in_url_with_hints(
+    url: &str,
+    barcode_type: Option<BarcodeFormat>,
+    options: &UrlFetchOptions,
+    hints: &mut DecodeHints,
+) -> Result<RXingResult> {
+    use std::io::Read;
+
+    let client = reqwest::blocking::Client::builder()
+        .timeout(options.timeout)
+        .build()
+        .map_err(|e| Exceptions::runtime_with(format!("couldn't create http client: {e}")))?;
+    let response = client
+        .get(url)
+        .send()
+        .map_err(|e| Exceptions::runtime_with(format!("couldn't fetch {url}: {e}")))?;
+    check_image_response(
+        url,
+        response.status(),
+        response.headers(),
+        response.content_length(),
+        options,
+    )?;
+
+    let mut bytes = Vec::new();
+    response
+        .take(options.max_bytes + 1)
+        .read_to_end(&mut bytes)
+        .map_err(|e| Exceptions::runtime_with(format!("couldn't fetch {url}: {e}")))?;
+    if bytes.len() as u64 > options.max_bytes {
+        return Err(image_too_large(url, options));
+    }
+
+    let img = load_image_from_memory(&bytes)
+        .map_err(|e| Exceptions::parse_with(format!("could not read image: {e}")))?;
+
+    if let Some(bc_type) = barcode_type {
+        hints.PossibleFormats = Some(HashSet::from([bc_type]));
+    }
+
+    hints.TryHarder = hints.TryHarder.or(Some(true));
+
+    PyramidReader::new(MultiFormatReader::default()).decode_with_hints(
+        &mut BinaryBitmap::new(FixedThresholdBinarizer::new(
+            BufferedImageLuminanceSource::new(img),
+        )),
+        hints,
+    )
+}
+
+// Refuses a response before its body is read: a failed request, a body that is not an image
+// or one that announces it is larger than the limit
+#[cfg(feature = "url_fetch")]
+pub(crate) fn check_image_response(
+    url: &str,
+    status: reqwest::StatusCode,
+    headers: &reqwest::header::HeaderMap,
+    content_length: Option<u64>,
+    options: &UrlFetchOptions,
+) -> Result<()> {
+    if !status.is_success() {
+        return Err(Exceptions::runtime_with(format!(
+            "couldn't fetch {url}: {status}"
+        )));
+    }
+
+    let content_type = headers
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    if !content_type.to_ascii_lowercase().starts_with("image/") {
+        return Err(Exceptions::unsupported_operation_with(format!(
+            "{url} is not an image, its content type is '{content_type}'"
+        )));
+    }
+
+    if content_length.is_some_and(|length| length > options.max_bytes) {
+        return Err(image_too_large(url, options));
+    }
+    Ok(())
+}
+
+#[cfg(feature = "url_fetch")]
+pub(crate) fn image_too_large(url: &str, options: &UrlFetchOptions) -> Exceptions {
+    Exceptions::unsupported_operation_with(format!(
+        "{url} is larger than {} bytes",
+        options.max_bytes
+    ))
+}
+
+/**
+ * Decodes the image in a `data:` URI, such as `data:image/png;base64,iVBORw0...` taken from
+ * an `<img>` tag or a JSON payload. Line breaks and spaces in the base64 text are skipped.
+ *
+ * # Returns
+ * The barcode, or `UnsupportedOperationException` if the URI is not base64 or its media type
+ * is not an image format this build can read
+ */
+#[cfg(feature = "image")]
+pub fn detect_in_data_uri(uri: &str, barcode_type: Option<BarcodeFormat>) -> Result<RXingResult> {
+    detect_in_data_uri_with_hints(uri, barcode_type, &mut DecodeHints::default())
+}
+
+#[cfg(feature = "image")]
+pub fn detect_in_data_uri_with_hints(
+    uri: &str,
+    barcode_type: Option<BarcodeFormat>,
+    hints: &mut DecodeHints,
+) -> Result<RXingResult> {
+    let img = data_uri_image(uri)?;
+
+    if let Some(bc_type) = barcode_type {
+        hints.PossibleFormats = Some(HashSet::from([bc_type]));
+    }
 
+    hints.TryHarder = hints.TryHarder.or(Some(true));
+
+    PyramidReader::new(MultiFormatReader::default()).decode_with_hints(
+        &mut BinaryBitmap::new(FixedThresholdBinarizer::new(
+            BufferedImageLuminanceSource::new(img),
+        )),
+        hints,
+    )
+}
+
+#[cfg(feature = "image")]
+pub fn detect_multiple_in_data_uri(uri: &str) -> Result<Vec<RXingResult>> {
+    detect_multiple_in_data_uri_with_hints(uri, &mut DecodeHints::default())
+}
+
+#[cfg(feature = "image")]
+pub fn detect_multiple_in_data_uri_with_hints(
+    uri: &str,
+    hints: &mut DecodeHints,
+) -> Result<Vec<RXingResult>> {
+    let img = data_uri_image(uri)?;
+    let multi_format_reader = MultiUseMultiFormatReader::default();
+    let mut scanner = GenericMultipleBarcodeReader::new(multi_format_reader);
+
+    hints.TryHarder = hints.TryHarder.or(Some(true));
+
+    scanner.decode_multiple_with_hints(
+        &mut BinaryBitmap::new(FixedThresholdBinarizer::new(
+            BufferedImageLuminanceSource::new(img),
+        )),
+        hints,
+    )
+}
+
+// Reads the image in a base64 `data:` URI, refusing media types that are not a readable image
+#[cfg(feature = "image")]
+fn data_uri_image(uri: &str) -> Result<image::DynamicImage> {
+    use base64::Engine;
+
+    let uri = uri.trim();
+    let Some((scheme, rest)) = uri.split_once(':') else {
+        return Err(Exceptions::illegal_argument_with("not a data URI"));
+    };
+    let Some((header, data)) = rest.split_once(',') else {
+        return Err(Exceptions::illegal_argument_with("data URI has no data"));
+    };
+    if !scheme.eq_ignore_ascii_case("data") {
+        return Err(Exceptions::illegal_argument_with("not a data URI"));
+    }
+
+    let mut params = header.split(';');
+    let media_type = params.next().unwrap_or_default();
+    let media_type = media_type.trim().to_ascii_lowercase();
+    if !params.any(|param| param.trim().eq_ignore_ascii_case("base64")) {
+        return Err(Exceptions::unsupported_operation_with(
+            "data URI is not base64 encoded",
+        ));
+    }
+    // HEIF is read through libheif, which the image crate has no format for
+    let heif = cfg!(feature = "heic") && matches!(media_type.as_str(), "image/heic" | "image/heif");
+    let readable = image::ImageFormat::from_mime_type(&media_type)
+        .is_some_and(|format| format.reading_enabled());
+    if !readable && !heif {
+        return Err(Exceptions::unsupported_operation_with(format!(
+            "media type '{media_type}' is not a supported image format"
+        )));
+    }
+
+    let data: Vec<u8> = data
+        .bytes()
+        .filter(|byte| !byte.is_ascii_whitespace())
+        .collect();
+    let engine = base64::engine::GeneralPurpose::new(
+        &base64::alphabet::STANDARD,
+        base64::engine::GeneralPurposeConfig::new()
+            .with_decode_padding_mode(base64::engine::DecodePaddingMode::Indifferent),
+    );
+    let bytes = engine
+        .decode(data)
+        .map_err(|e| Exceptions::parse_with(format!("invalid base64 in data URI: {e}")))?;
+
+    load_image_from_memory(&bytes)
+        .map_err(|e| Exceptions::parse_with(format!("could not read image: {e}")))
+}
+
+/**
+ * Decodes the image on the system clipboard, such as a screenshot of a QR code.
+ *
+ * # Returns
+ * The barcode, or `NotFoundException` if the clipboard holds no image
+ */
+#[cfg(feature = "clipboard")]
+pub fn detect_in_clipboard() -> Result<RXingResult> {
+    detect_in_clipboard_with_hints(None, &mut DecodeHints::default())
+}
+
+#[cfg(feature = "clipboard")]
+pub fn detect_in_clipboard_with_hints(
+    barcode_type: Option<BarcodeFormat>,
+    hints: &mut DecodeHints,
+) -> Result<RXingResult> {
+    let img = clipboard_image()?;
+
+    if let Some(bc_type) = barcode_type {
+        hints.PossibleFormats = Some(HashSet::from([bc_type]));
+    }
+
+    hints.TryHarder = hints.TryHarder.or(Some(true));
+
+    PyramidReader::new(MultiFormatReader::default()).decode_with_hints(
+        &mut BinaryBitmap::new(FixedThresholdBinarizer::new(
+            BufferedImageLuminanceSource::new(img),
+        )),
+        hints,
+    )
+}
+
+#[cfg(feature = "clipboard")]
+pub fn detect_multiple_in_clipboard() -> Result<Vec<RXingResult>> {
+    detect_multiple_in_clipboard_with_hints(&mut DecodeHints::default())
+}
+
+#[cfg(feature = "clipboard")]
+pub fn detect_multiple_in_clipboard_with_hints(
+    hints: &mut DecodeHints,
+) -> Result<Vec<RXingResult>> {
+    let img = clipboard_image()?;
+
+    hints.TryHarder = hints.TryHarder.or(Some(true));
+
+    GenericMultipleBarcodeReader::new(MultiUseMultiFormatReader::default())
+        .decode_multiple_with_hints(
+            &mut BinaryBitmap::new(FixedThresholdBinarizer::new(
+                BufferedImageLuminanceSource::new(img),
+            )),
+            hints,
+        )
+}
+
+#[cfg(feature = "clipboard")]
+fn clipboard_image() -> Result<image::DynamicImage> {
+    let mut clipboard = arboard::Clipboard::new()
+        .map_err(|e| Exceptions::runtime_with(format!("couldn't open clipboard: {e}")))?;
+    match clipboard.get_image() {
+        Ok(data) => clipboard_rgba(data),
+        Err(arboard::Error::ContentNotAvailable) => {
+            Err(Exceptions::not_found_with("clipboard holds no image"))
+        }
+        Err(e) => Err(Exceptions::runtime_with(format!(
+            "couldn't read clipboard: {e}"
+        ))),
+    }
+}
+
+// arboard hands out every clipboard image as 8 bit RGBA
+#[cfg(feature = "clipboard")]
+fn clipboard_rgba(data: arboard::ImageData) -> Result<image::DynamicImage> {
+    let (width, height) = (data.width as u32, data.height as u32);
+    image::RgbaImage::from_raw(width, height, data.bytes.into_owned())
+        .map(image::DynamicImage::ImageRgba8)
+        .ok_or_else(|| {
+            Exceptions::illegal_argument_with(format!(
+                "clipboard image is not {width}x{height} RGBA"
+            ))
+        })
+}
+
+/// A rectangle of the screen, in the pixel coordinates of the virtual desktop
+#[cfg(feature = "screen_capture")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScreenRegion {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+#[cfg(feature = "screen_capture")]
+impl ScreenRegion {
+    pub fn new(x: i32, y: i32, width: u32, height: u32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Returns the part of the screen both regions cover, if they overlap
+    pub fn intersection(&self, other: &ScreenRegion) -> Option<ScreenRegion> {
+        let end = |start: i32, length: u32| start as i64 + length as i64;
+        let left = self.x.max(other.x) as i64;
+        let top = self.y.max(other.y) as i64;
+        let right = end(self.x, self.width).min(end(other.x, other.width));
+        let bottom = end(self.y, self.height).min(end(other.y, other.height));
+        if left >= right || top >= bottom {
+            return None;
+        }
+        Some(ScreenRegion::new(
+            left as i32,
+            top as i32,
+            (right - left) as u32,
+            (bottom - top) as u32,
+        ))
+    }
+}
+
+/**
+ * Captures every monitor and scans it for barcodes, such as a 2FA QR code shown in a browser.
+ * The points of each result are in screen coordinates, so they can be used to point at or
+ * click on the barcode.
+ */
+#[cfg(feature = "screen_capture")]
+pub fn detect_on_screen() -> Result<Vec<RXingResult>> {
+    detect_on_screen_with_hints(None, &mut DecodeHints::default())
+}
+
+/**
+ * Captures `region` of the screen, or the whole of every monitor, and scans it for barcodes.
+ * A region that spans monitors is captured from each monitor it covers.
+ *
+ * # Returns
+ * The barcodes found, with their points in screen coordinates
+ */
+#[cfg(feature = "screen_capture")]
+pub fn detect_on_screen_with_hints(
+    region: Option<ScreenRegion>,
+    hints: &mut DecodeHints,
+) -> Result<Vec<RXingResult>> {
+    hints.TryHarder = hints.TryHarder.or(Some(true));
+
+    let mut scanner = GenericMultipleBarcodeReader::new(MultiUseMultiFormatReader::default());
+    let mut results = Vec::new();
+    for monitor in xcap::Monitor::all().map_err(screen_error)? {
+        let bounds = ScreenRegion::new(
+            monitor.x().map_err(screen_error)?,
+            monitor.y().map_err(screen_error)?,
+            monitor.width().map_err(screen_error)?,
+            monitor.height().map_err(screen_error)?,
+        );
+        let (part, img) = match region {
+            Some(region) => {
+                let Some(part) = bounds.intersection(&region) else {
+                    continue;
+                };
+                let (x, y) = ((part.x - bounds.x) as u32, (part.y - bounds.y) as u32);
+                let img = monitor
+                    .capture_region(x, y, part.width, part.height)
+                    .map_err(screen_error)?;
+                (part, img)
+            }
+            None => (bounds, monitor.capture_image().map_err(screen_error)?),
+        };
+
+        // a monitor without a barcode that decodes does not end the scan
+        let found = scanner
+            .decode_multiple_with_hints(
+                &mut BinaryBitmap::new(FixedThresholdBinarizer::new(
+                    BufferedImageLuminanceSource::new(image::DynamicImage::ImageRgba8(img)),
+                )),
+                hints,
+            )
+            .unwrap_or_default();
+        for mut result in found {
+            for point in result.getPointsMut() {
+                point.x += part.x as f32;
+                point.y += part.y as f32;
+            }
+            results.push(result);
+        }
+    }
+    Ok(results)
+}
+
+#[cfg(feature = "screen_capture")]
+fn screen_error(e: xcap::XCapError) -> Exceptions {
+    Exceptions::runtime_with(format!("couldn't capture screen: {e}"))
+}
+
+/// Called by [`scan_directory`] each time a file is done, with the number of files done and the
+/// number of files found
+#[cfg(feature = "image")]
+pub type ScanProgressCallback = std::sync::Arc<dyn Fn(usize, usize) + Send + Sync>;
+
+/// How [`scan_directory`] walks a directory and decodes its images
+#[cfg(feature = "image")]
+#[derive(Clone)]
+pub struct ScanDirectoryOptions {
+    /// Whether images in subdirectories are scanned too. Symlinked directories are skipped.
+    pub recursive: bool,
+    /// How many files are decoded at once, 0 for one per available CPU
+    pub threads: usize,
+    pub hints: DecodeHints,
+    pub progress: Option<ScanProgressCallback>,
+}
+
+#[cfg(feature = "image")]
+impl Default for ScanDirectoryOptions {
+    fn default() -> Self {
+        Self {
+            recursive: true,
+            threads: 0,
+            hints: DecodeHints::default(),
+            progress: None,
+        }
+    }
+}
+
+/**
+ * Detects all barcodes in every image under `path`, decoding several files at once. A file is
+ * an image if the `image` crate can read its format, told by its extension. A failure on one
+ * file does not stop the scan: every image gets its own outcome.
+ *
+ * # Returns
+ * The outcomes, ordered by path, or an error if a directory can't be read
+ */
+#[cfg(feature = "image")]
+pub fn scan_directory<P: AsRef<Path>>(
+    path: P,
+    options: &ScanDirectoryOptions,
+) -> Result<Vec<FileDecodeOutcome<Vec<RXingResult>>>> {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    let mut files = Vec::new();
+    collect_images(path.as_ref(), options.recursive, &mut files)?;
+    files.sort();
+
+    let threads = match options.threads {
+        0 => std::thread::available_parallelism().map_or(1, |threads| threads.get()),
+        threads => threads,
+    }
+    .min(files.len().max(1));
+
+    // each worker takes the next file until none are left
+    let next = AtomicUsize::new(0);
+    let done = AtomicUsize::new(0);
+    let work = || {
+        let mut decoded = Vec::new();
+        loop {
+            let index = next.fetch_add(1, Ordering::Relaxed);
+            let Some(file) = files.get(index) else {
+                return decoded;
+            };
+            let outcome = detect_multiple_in_file_outcome(file, &mut options.hints.clone());
+            decoded.push((index, outcome));
+            let done = done.fetch_add(1, Ordering::Relaxed) + 1;
+            if let Some(progress) = &options.progress {
+                progress(done, files.len());
+            }
+        }
+    };
+    let mut outcomes: Vec<_> = std::thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads).map(|_| scope.spawn(work)).collect();
+        let joined = workers.into_iter().map(|worker| worker.join());
+        joined
+            .flat_map(|decoded| decoded.unwrap_or_else(|e| std::panic::resume_unwind(e)))
+            .collect()
+    });
+
+    outcomes.sort_by_key(|(index, _)| *index);
+    Ok(outcomes.into_iter().map(|(_, outcome)| outcome).collect())
+}
+
+#[cfg(feature = "image")]
+fn collect_images(dir: &Path, recursive: bool, files: &mut Vec<PathBuf>) -> Result<()> {
+    let read_error = |e: std::io::Error| {
+        Exceptions::runtime_with(format!("couldn't read {}: {e}", dir.display()))
+    };
+
+    for entry in std::fs::read_dir(dir).map_err(read_error)? {
+        let entry = entry.map_err(read_error)?;
+        let path = entry.path();
+        // Symlinked directories aren't followed, a link to a parent would recurse forever
+        if entry.file_type().map_err(read_error)?.is_dir() {
+            if recursive {
+                collect_images(&path, recursive, files)?;
+            }
+        } else if path.is_file() && is_image_file(&path) {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(feature = "image")]
+fn is_image_file(path: &Path) -> bool {
+    if image::ImageFormat::from_path(path).is_ok_and(|format| format.reading_enabled()) {
+        return true;
+    }
+    // HEIF is read through libheif, which the image crate has no format for
+    let extension = path.extension().unwrap_or_default().to_ascii_lowercase();
+    cfg!(feature = "heic") && (extension == "heic" || extension == "heif")
+}
+
+/// A barcode read from a file in a [`DirectoryReport`]
+#[cfg(feature = "image")]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReportedSymbol {
+    pub format: BarcodeFormat,
+    pub text: String,
+}
+
+/// How decoding one file of a [`DirectoryReport`] went
+#[cfg(feature = "image")]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileReport {
+    pub path: PathBuf,
+    /// The barcodes found, empty if there were none or the file failed
+    pub symbols: Vec<ReportedSymbol>,
+    /// Why the file could not be read or decoded
+    pub error: Option<String>,
+    /// The time taken to load and decode the file
+    pub elapsed: Duration,
+}
+
+#[cfg(feature = "image")]
+impl FileReport {
+    pub fn is_ok(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+#[cfg(feature = "image")]
+impl From<FileDecodeOutcome<Vec<RXingResult>>> for FileReport {
+    fn from(outcome: FileDecodeOutcome<Vec<RXingResult>>) -> Self {
+        let (symbols, error) = match outcome.result {
+            Ok(results) => (
+                results
+                    .iter()
+                    .map(|result| ReportedSymbol {
+                        format: result.getBarcodeFormat().clone(),
+                        text: result.getText().to_owned(),
+                    })
+                    .collect(),
+                None,
+            ),
+            Err(e) => (Vec::new(), Some(e.to_string())),
+        };
+        Self {
+            path: outcome.path,
+            symbols,
+            error,
+            elapsed: outcome.elapsed,
+        }
+    }
+}
+
+/// What [`decode_directory`] found in each image of a directory, ordered by path
+#[cfg(feature = "image")]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DirectoryReport {
+    pub files: Vec<FileReport>,
+}
+
+#[cfg(feature = "image")]
+impl DirectoryReport {
+    /// The files that were read and decoded
+    pub fn decoded(&self) -> impl Iterator<Item = &FileReport> {
+        self.files.iter().filter(|file| file.is_ok())
+    }
+
+    /// The files that could not be read, or had no barcode that decoded
+    pub fn failed(&self) -> impl Iterator<Item = &FileReport> {
+        self.files.iter().filter(|file| !file.is_ok())
+    }
+
+    /// The time taken by all of the files, as if they had been decoded one after another
+    pub fn total_elapsed(&self) -> Duration {
+        self.files.iter().map(|file| file.elapsed).sum()
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self)
+            .map_err(|e| Exceptions::runtime_with(format!("couldn't write report: {e}")))
+    }
+
+    /**
+     * Writes the report as CSV, with the header `path,format,text,elapsed_ms,error`. There is
+     * a row for each barcode found, and a row with no format or text for a file without any.
+     */
+    pub fn write_csv<W: Write>(&self, mut writer: W) -> std::io::Result<()> {
+        writeln!(writer, "path,format,text,elapsed_ms,error")?;
+        for file in &self.files {
+            let path = csv_field(&file.path.to_string_lossy());
+            let elapsed = file.elapsed.as_secs_f64() * 1000.0;
+            let error = csv_field(file.error.as_deref().unwrap_or_default());
+            if file.symbols.is_empty() {
+                writeln!(writer, "{path},,,{elapsed:.3},{error}")?;
+            }
+            for symbol in &file.symbols {
+                let format = csv_field(&symbol.format.to_string());
+                let text = csv_field(&symbol.text);
+                writeln!(writer, "{path},{format},{text},{elapsed:.3},{error}")?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut csv = Vec::new();
+        self.write_csv(&mut csv)
+            .expect("writing to a Vec does not fail");
+        String::from_utf8(csv).expect("the report is written from strings")
+    }
+}
+
+/// Quotes a CSV field if it has a comma, quote or line break in it
+#[cfg(feature = "image")]
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+/**
+ * Decodes every image under `path`, like [`scan_directory`], and reports the barcodes found in
+ * each file, the failures and the timings, for validating datasets and archives of labels. The
+ * formats looked for are the `PossibleFormats` of the hints in `options`, and `threads: 1`
+ * decodes one file after another.
+ *
+ * # Returns
+ * The report, or an error if a directory can't be read
+ */
+#[cfg(feature = "image")]
+pub fn decode_directory<P: AsRef<Path>>(
+    path: P,
+    options: &ScanDirectoryOptions,
+) -> Result<DirectoryReport> {
+    Ok(DirectoryReport {
+        files: scan_directory(path, options)?
+            .into_iter()
+            .map(FileReport::from)
+            .collect(),
+    })
+}
+
+/// A barcode found in a frame of an animated image or a video stream
+#[cfg(any(feature = "image_formats", feature = "camera", feature = "gstreamer"))]
+#[derive(Debug, Clone)]
+pub struct FrameResult {
+    /// The index of the frame, starting at 0
+    pub frame: usize,
+    /// When the frame is shown, from the start of the animation or stream
+    pub timestamp: Duration,
+    pub result: RXingResult,
+}
+
+#[cfg(feature = "image_formats")]
+pub fn detect_in_animation(file_name: &str) -> Result<Vec<FrameResult>> {
+    detect_in_animation_with_hints(file_name, &mut DecodeHints::default())
+}
+
+/**
+ * Decodes all barcodes in every frame of an animated GIF, PNG (APNG) or WebP, such as a
+ * screen recording. A barcode shown over several frames is reported once, for the first frame
+ * it was decoded in. Images that are not animated are decoded as a single frame.
+ *
+ * # Returns
+ * The results in frame order, or an error if the file or one of its frames cannot be read
+ */
+#[cfg(feature = "image_formats")]
+pub fn detect_in_animation_with_hints(
+    file_name: &str,
+    hints: &mut DecodeHints,
+) -> Result<Vec<FrameResult>> {
+    use image::{codecs, AnimationDecoder, ImageFormat};
+
+    let read_error = |e: &dyn std::fmt::Display| {
+        Exceptions::runtime_with(format!("couldn't read {file_name}: {e}"))
+    };
+    let open = || {
+        let file = std::fs::File::open(file_name).map_err(|e| read_error(&e))?;
+        Ok(std::io::BufReader::new(file))
+    };
+    let reader = image::ImageReader::new(open()?)
+        .with_guessed_format()
+        .map_err(|e| read_error(&e))?;
+
+    let frames = match reader.format() {
+        Some(ImageFormat::Gif) => codecs::gif::GifDecoder::new(open()?)
+            .map_err(|e| read_error(&e))?
+            .into_frames(),
+        Some(ImageFormat::Png) => {
+            let decoder = codecs::png::PngDecoder::new(open()?).map_err(|e| read_error(&e))?;
+            if decoder.is_apng().map_err(|e| read_error(&e))? {
+                decoder.apng().map_err(|e| read_error(&e))?.into_frames()
+            } else {
+                let img = image::DynamicImage::from_decoder(decoder).map_err(|e| read_error(&e))?;
+                return decode_frames(hints, [Ok((img, Duration::ZERO))]);
+            }
+        }
+        Some(ImageFormat::WebP) => {
+            let decoder = codecs::webp::WebPDecoder::new(open()?).map_err(|e| read_error(&e))?;
+            if decoder.has_animation() {
+                decoder.into_frames()
+            } else {
+                let img = image::DynamicImage::from_decoder(decoder).map_err(|e| read_error(&e))?;
+                return decode_frames(hints, [Ok((img, Duration::ZERO))]);
+            }
+        }
+        _ => {
+            let img = open_image(file_name).map_err(|e| read_error(&e))?;
+            return decode_frames(hints, [Ok((img, Duration::ZERO))]);
+        }
+    };
+
+    let frames = frames.map(|frame| {
+        let frame = frame.map_err(|e| read_error(&e))?;
+        let delay = Duration::from(frame.delay());
+        Ok((image::DynamicImage::ImageRgba8(frame.into_buffer()), delay))
+    });
+    decode_frames(hints, frames)
+}
+
+// Decodes each frame, shown for its delay, keeping the first sighting of each barcode
+#[cfg(feature = "image_formats")]
+fn decode_frames<I>(hints: &mut DecodeHints, frames: I) -> Result<Vec<FrameResult>>
+where
+    I: IntoIterator<Item = Result<(image::DynamicImage, Duration)>>,
+{
+    hints.TryHarder = hints.TryHarder.or(Some(true));
+
+    let mut scanner = GenericMultipleBarcodeReader::new(MultiUseMultiFormatReader::default());
+    let mut seen = HashSet::new();
+    let mut results = Vec::new();
+    let mut timestamp = Duration::ZERO;
+    for (frame, decoded) in frames.into_iter().enumerate() {
+        let (img, delay) = decoded?;
+        let mut bitmap = BinaryBitmap::new(FixedThresholdBinarizer::new(
+            BufferedImageLuminanceSource::new(img),
+        ));
+        // a frame without a barcode that decodes does not end the scan
+        let found = scanner
+            .decode_multiple_with_hints(&mut bitmap, hints)
+            .unwrap_or_default();
+        for result in found {
+            let key = (
+                result.getBarcodeFormat().clone(),
+                result.getText().to_owned(),
+            );
+            if seen.insert(key) {
+                results.push(FrameResult {
+                    frame,
+                    timestamp,
+                    result,
+                });
+            }
+        }
+        timestamp += delay;
+    }
+    Ok(results)
+}
+
+/// A barcode found on one page of a multi-page image
+#[cfg(feature = "multipage_tiff")]
+#[derive(Debug, Clone)]
+pub struct PageResult {
+    /// The index of the page, starting at 0
+    pub page: usize,
+    pub result: RXingResult,
+}
+
+#[cfg(feature = "multipage_tiff")]
+pub fn detect_in_tiff(
+    file_name: &str,
+    barcode_type: Option<BarcodeFormat>,
+) -> Result<Vec<PageResult>> {
+    detect_in_tiff_with_hints(file_name, barcode_type, &mut DecodeHints::default())
+}
+
+/**
+ * Decodes the first barcode on each page of a multi-page TIFF, such as a fax or a scanned
+ * document archive. Pages without a barcode that decodes have no result.
+ *
+ * # Returns
+ * The results in page order, or an error if the file or one of its pages cannot be read
+ */
+#[cfg(feature = "multipage_tiff")]
+pub fn detect_in_tiff_with_hints(
+    file_name: &str,
+    barcode_type: Option<BarcodeFormat>,
+    hints: &mut DecodeHints,
+) -> Result<Vec<PageResult>> {
     if let Some(bc_type) = barcode_type {
         hints.PossibleFormats = Some(HashSet::from([bc_type]));
     }
 
     hints.TryHarder = hints.TryHarder.or(Some(true));
 
-    multi_format_reader.decode_with_hints(
-        &mut BinaryBitmap::new(FixedThresholdBinarizer::new(SVGLuminanceSource::new(
-            &svg_data,
-        )?)),
-        hints,
-    )
+    let mut reader = MultiFormatReader::default();
+    decode_tiff_pages(file_name, |img| {
+        let result = reader.decode_with_hints(
+            &mut BinaryBitmap::new(FixedThresholdBinarizer::new(
+                BufferedImageLuminanceSource::new(img),
+            )),
+            hints,
+        )?;
+        Ok(vec![result])
+    })
 }
 
-#[cfg(feature = "svg_read")]
-pub fn detect_multiple_in_svg(file_name: &str) -> Result<Vec<RXingResult>> {
-    detect_multiple_in_svg_with_hints(file_name, &mut DecodeHints::default())
+#[cfg(feature = "multipage_tiff")]
+pub fn detect_multiple_in_tiff(file_name: &str) -> Result<Vec<PageResult>> {
+    detect_multiple_in_tiff_with_hints(file_name, &mut DecodeHints::default())
 }
 
-#[cfg(feature = "svg_read")]
-pub fn detect_multiple_in_svg_with_hints(
+/**
+ * Decodes all barcodes on each page of a multi-page TIFF, see `detect_in_tiff_with_hints`.
+ */
+#[cfg(feature = "multipage_tiff")]
+pub fn detect_multiple_in_tiff_with_hints(
     file_name: &str,
     hints: &mut DecodeHints,
-) -> Result<Vec<RXingResult>> {
-    use std::{fs::File, io::Read};
-
-    use crate::SVGLuminanceSource;
+) -> Result<Vec<PageResult>> {
+    hints.TryHarder = hints.TryHarder.or(Some(true));
 
-    let path = PathBuf::from(file_name);
-    if !path.exists() {
-        return Err(Exceptions::illegal_argument_with("file does not exist"));
-    }
+    let mut scanner = GenericMultipleBarcodeReader::new(MultiUseMultiFormatReader::default());
+    decode_tiff_pages(file_name, |img| {
+        scanner.decode_multiple_with_hints(
+            &mut BinaryBitmap::new(FixedThresholdBinarizer::new(
+                BufferedImageLuminanceSource::new(img),
+            )),
+            hints,
+        )
+    })
+}
 
-    let Ok(mut file) = File::open(path) else {
-        return Err(Exceptions::illegal_argument_with("file cannot be opened"));
+// Reads the pages of the TIFF one at a time, tagging what `decode` finds with the page
+#[cfg(feature = "multipage_tiff")]
+fn decode_tiff_pages<F>(file_name: &str, mut decode: F) -> Result<Vec<PageResult>>
+where
+    F: FnMut(image::DynamicImage) -> Result<Vec<RXingResult>>,
+{
+    let read_error = |e: &dyn std::fmt::Display| {
+        Exceptions::runtime_with(format!("couldn't read {file_name}: {e}"))
     };
+    let file = std::fs::File::open(file_name).map_err(|e| read_error(&e))?;
+    let mut decoder =
+        tiff::decoder::Decoder::new(std::io::BufReader::new(file)).map_err(|e| read_error(&e))?;
 
-    let mut svg_data = Vec::new();
-    if file.read_to_end(&mut svg_data).is_err() {
-        return Err(Exceptions::illegal_argument_with("file cannot be read"));
+    let mut results = Vec::new();
+    let mut page = 0;
+    loop {
+        let img = tiff_page(&mut decoder).map_err(|e| {
+            Exceptions::runtime_with(format!("couldn't read page {page} of {file_name}: {e}"))
+        })?;
+        // a page without a barcode that decodes does not end the scan
+        if let Ok(found) = decode(img) {
+            results.extend(found.into_iter().map(|result| PageResult { page, result }));
+        }
+
+        if !decoder.more_images() {
+            return Ok(results);
+        }
+        decoder.next_image().map_err(|e| read_error(&e))?;
+        page += 1;
     }
+}
 
-    let multi_format_reader = MultiUseMultiFormatReader::default();
-    let mut scanner = GenericMultipleBarcodeReader::new(multi_format_reader);
+// Converts the current page of the TIFF to an image, for the color types the `image` crate
+// reads from TIFFs, and for bilevel pages
+#[cfg(feature = "multipage_tiff")]
+fn tiff_page<R: std::io::Read + std::io::Seek>(
+    decoder: &mut tiff::decoder::Decoder<R>,
+) -> std::result::Result<image::DynamicImage, String> {
+    use image::{DynamicImage, ImageBuffer};
+    use tiff::{decoder::DecodingResult, ColorType};
 
-    hints.TryHarder = hints.TryHarder.or(Some(true));
+    let (width, height) = decoder.dimensions().map_err(|e| e.to_string())?;
+    let color_type = decoder.colortype().map_err(|e| e.to_string())?;
+    let pixels = decoder.read_image().map_err(|e| e.to_string())?;
 
-    scanner.decode_multiple_with_hints(
-        &mut BinaryBitmap::new(FixedThresholdBinarizer::new(SVGLuminanceSource::new(
-            &svg_data,
-        )?)),
-        hints,
-    )
-}
+    let img = match (color_type, pixels) {
+        (ColorType::Gray(1), DecodingResult::U8(bits)) => {
+            // each row is packed into whole bytes, most significant bit first, 1 for white
+            let stride = (width as usize).div_ceil(8);
+            let mut luma = Vec::with_capacity(width as usize * height as usize);
+            for row in bits.chunks(stride.max(1)).take(height as usize) {
+                for x in 0..width as usize {
+                    let white = row[x / 8] & (0x80 >> (x % 8)) != 0;
+                    luma.push(if white { 255 } else { 0 });
+                }
+            }
+            ImageBuffer::from_raw(width, height, luma).map(DynamicImage::ImageLuma8)
+        }
+        (ColorType::Gray(8), DecodingResult::U8(luma)) => {
+            ImageBuffer::from_raw(width, height, luma).map(DynamicImage::ImageLuma8)
+        }
+        (ColorType::Gray(16), DecodingResult::U16(luma)) => {
+            ImageBuffer::from_raw(width, height, luma).map(DynamicImage::ImageLuma16)
+        }
+        (ColorType::GrayA(8), DecodingResult::U8(luma)) => {
+            ImageBuffer::from_raw(width, height, luma).map(DynamicImage::ImageLumaA8)
+        }
+        (ColorType::RGB(8), DecodingResult::U8(rgb)) => {
+            ImageBuffer::from_raw(width, height, rgb).map(DynamicImage::ImageRgb8)
+        }
+        (ColorType::RGB(16), DecodingResult::U16(rgb)) => {
+            ImageBuffer::from_raw(width, height, rgb).map(DynamicImage::ImageRgb16)
+        }
+        (ColorType::RGBA(8), DecodingResult::U8(rgba)) => {
+            ImageBuffer::from_raw(width, height, rgba).map(DynamicImage::ImageRgba8)
+        }
+        (ColorType::RGBA(16), DecodingResult::U16(rgba)) => {
+            ImageBuffer::from_raw(width, height, rgba).map(DynamicImage::ImageRgba16)
+        }
+        (color_type, _) => return Err(format!("unsupported color type {color_type:?}")),
+    };
 
-#[cfg(feature = "image")]
-pub fn detect_in_file(file_name: &str, barcode_type: Option<BarcodeFormat>) -> Result<RXingResult> {
-    detect_in_file_with_hints(file_name, barcode_type, &mut DecodeHints::default())
+    img.ok_or_else(|| format!("the pixels do not fill the {width}x{height} page"))
 }
 
-#[cfg(feature = "image")]
-pub fn detect_in_file_with_hints(
-    file_name: &str,
-    barcode_type: Option<BarcodeFormat>,
-    hints: &mut DecodeHints,
-) -> Result<RXingResult> {
-    let Ok(img) = image::open(file_name) else {
-        return Err(Exceptions::illegal_argument_with(format!(
-            "file '{file_name}' not found or cannot be opened"
-        )));
-    };
-    let mut multi_format_reader = MultiFormatReader::default();
+/// How the pixels of a raw video frame are laid out, named as ffmpeg's `-pix_fmt`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RawPixelFormat {
+    /// One luma byte per pixel
+    Gray8,
+    /// Red, green and blue bytes per pixel
+    Rgb24,
+    /// Red, green, blue and alpha bytes per pixel
+    Rgba,
+    /// The luma plane, then quarter size U and V planes
+    Yuv420p,
+    /// The luma plane, then a quarter size plane of interleaved U and V
+    Nv12,
+}
 
-    if let Some(bc_type) = barcode_type {
-        hints.PossibleFormats = Some(HashSet::from([bc_type]));
+impl RawPixelFormat {
+    /// Returns the size in bytes of a `width` by `height` frame
+    pub fn frame_len(&self, width: usize, height: usize) -> usize {
+        let pixels = width * height;
+        match self {
+            RawPixelFormat::Gray8 => pixels,
+            RawPixelFormat::Rgb24 => pixels * 3,
+            RawPixelFormat::Rgba => pixels * 4,
+            RawPixelFormat::Yuv420p | RawPixelFormat::Nv12 => {
+                pixels + 2 * width.div_ceil(2) * height.div_ceil(2)
+            }
+        }
     }
+}
 
-    hints.TryHarder = hints.TryHarder.or(Some(true));
+/// The barcodes found in one raw frame
+#[derive(Debug, Clone)]
+pub struct RawFrameResult {
+    /// The index of the frame in the stream, starting at 0
+    pub frame: usize,
+    pub results: Vec<RXingResult>,
+}
 
-    multi_format_reader.decode_with_hints(
-        &mut BinaryBitmap::new(FixedThresholdBinarizer::new(
-            BufferedImageLuminanceSource::new(img),
-        )),
-        hints,
-    )
+/**
+ * Reads raw video frames of a size and pixel format agreed up front, such as the output of
+ * `ffmpeg -f rawvideo -pix_fmt gray -`, and decodes each frame as it is read. Iterating
+ * yields the barcodes of every frame, including frames without any, and ends with the stream;
+ * a stream that ends within a frame yields an error.
+ *
+ * ```
+ * use rxing::helpers::{RawFrameScanner, RawPixelFormat};
+ *
+ * let frames: &[u8] = &[255; 2 * 8 * 8];
+ * let scanner = RawFrameScanner::new(frames, 8, 8, RawPixelFormat::Gray8).unwrap();
+ * assert_eq!(2, scanner.count());
+ * ```
+ */
+pub struct RawFrameScanner<R: Read> {
+    reader: R,
+    width: usize,
+    height: usize,
+    format: RawPixelFormat,
+    hints: DecodeHints,
+    frame: usize,
+    buffer: Vec<u8>,
 }
 
-#[cfg(feature = "image")]
-pub fn detect_multiple_in_file(file_name: &str) -> Result<Vec<RXingResult>> {
-    detect_multiple_in_file_with_hints(file_name, &mut DecodeHints::default())
+impl<R: Read> RawFrameScanner<R> {
+    pub fn new(reader: R, width: u32, height: u32, format: RawPixelFormat) -> Result<Self> {
+        Self::with_hints(reader, width, height, format, DecodeHints::default())
+    }
+
+    pub fn with_hints(
+        reader: R,
+        width: u32,
+        height: u32,
+        format: RawPixelFormat,
+        hints: DecodeHints,
+    ) -> Result<Self> {
+        if width == 0 || height == 0 {
+            return Err(Exceptions::illegal_argument_with(format!(
+                "a {width}x{height} frame has no pixels"
+            )));
+        }
+        let (width, height) = (width as usize, height as usize);
+        Ok(Self {
+            reader,
+            width,
+            height,
+            format,
+            hints,
+            frame: 0,
+            buffer: vec![0; format.frame_len(width, height)],
+        })
+    }
+
+    // Fills the buffer with the next frame, or returns false at the end of the stream
+    fn read_frame(&mut self) -> Result<bool> {
+        let mut filled = 0;
+        while filled < self.buffer.len() {
+            match self.reader.read(&mut self.buffer[filled..]) {
+                Ok(0) if filled == 0 => return Ok(false),
+                Ok(0) => {
+                    return Err(Exceptions::runtime_with(format!(
+                        "stream ended {filled} bytes into frame {}",
+                        self.frame
+                    )))
+                }
+                Ok(read) => filled += read,
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+                Err(e) => {
+                    return Err(Exceptions::runtime_with(format!(
+                        "couldn't read frame {}: {e}",
+                        self.frame
+                    )))
+                }
+            }
+        }
+        Ok(true)
+    }
+
+    fn luma(&self) -> Vec<u8> {
+        let pixels = self.width * self.height;
+        // green-favouring average, as RGBLuminanceSource
+        let average = |pixel: &[u8]| {
+            let (r, g, b) = (pixel[0] as u16, pixel[1] as u16, pixel[2] as u16);
+            ((r + 2 * g + b) / 4) as u8
+        };
+        match self.format {
+            RawPixelFormat::Gray8 | RawPixelFormat::Yuv420p | RawPixelFormat::Nv12 => {
+                self.buffer[..pixels].to_vec()
+            }
+            RawPixelFormat::Rgb24 => self.buffer.chunks_exact(3).map(average).collect(),
+            RawPixelFormat::Rgba => self.buffer.chunks_exact(4).map(average).collect(),
+        }
+    }
 }
 
-#[cfg(feature = "image")]
-pub fn detect_multiple_in_file_with_hints(
-    file_name: &str,
-    hints: &mut DecodeHints,
-) -> Result<Vec<RXingResult>> {
-    let img = image::open(file_name)
-        .map_err(|e| Exceptions::runtime_with(format!("couldn't read {file_name}: {e}")))?;
-    let multi_format_reader = MultiUseMultiFormatReader::default();
-    let mut scanner = GenericMultipleBarcodeReader::new(multi_format_reader);
+impl<R: Read> Iterator for RawFrameScanner<R> {
+    type Item = Result<RawFrameResult>;
 
-    hints.TryHarder = hints.TryHarder.or(Some(true));
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.read_frame() {
+            Ok(true) => {}
+            Ok(false) => return None,
+            Err(e) => return Some(Err(e)),
+        }
+        let frame = self.frame;
+        self.frame += 1;
 
-    scanner.decode_multiple_with_hints(
-        &mut BinaryBitmap::new(FixedThresholdBinarizer::new(
-            BufferedImageLuminanceSource::new(img),
-        )),
-        hints,
-    )
+        // a frame without a barcode that decodes does not end the scan
+        let (width, height) = (self.width as u32, self.height as u32);
+        let results =
+            detect_multiple_in_luma_with_hints(self.luma(), width, height, &mut self.hints)
+                .unwrap_or_default();
+        Some(Ok(RawFrameResult { frame, results }))
+    }
 }
 
 pub fn detect_in_luma(
@@ -176,7 +1720,7 @@ pub fn detect_in_luma_with_hints(
     barcode_type: Option<BarcodeFormat>,
     hints: &mut DecodeHints,
 ) -> Result<RXingResult> {
-    let mut multi_format_reader = MultiFormatReader::default();
+    let mut multi_format_reader = PyramidReader::new(MultiFormatReader::default());
 
     if let Some(bc_type) = barcode_type {
         hints.PossibleFormats = Some(HashSet::from([bc_type]));
@@ -192,6 +1736,16 @@ pub fn detect_in_luma_with_hints(
     )
 }
 
+/// Like [`detect_in_luma_with_hints`], with the hints built by `options`
+pub fn detect_in_luma_with_options(
+    luma: Vec<u8>,
+    width: u32,
+    height: u32,
+    options: DecodeOptions,
+) -> Result<RXingResult> {
+    detect_in_luma_with_hints(luma, width, height, None, &mut options.into())
+}
+
 pub fn detect_in_luma_filtered(
     luma: Vec<u8>,
     width: u32,
@@ -253,6 +1807,50 @@ pub fn detect_multiple_in_luma_with_hints(
     )
 }
 
+/// Like [`detect_multiple_in_luma_with_hints`], with the hints built by `options`
+pub fn detect_multiple_in_luma_with_options(
+    luma: Vec<u8>,
+    width: u32,
+    height: u32,
+    options: DecodeOptions,
+) -> Result<Vec<RXingResult>> {
+    detect_multiple_in_luma_with_hints(luma, width, height, &mut options.into())
+}
+
+#[cfg(feature = "parallel")]
+pub fn detect_multiple_in_luma_parallel(
+    luma: Vec<u8>,
+    width: u32,
+    height: u32,
+) -> Result<Vec<RXingResult>> {
+    detect_multiple_in_luma_parallel_with_hints(luma, width, height, &mut DecodeHints::default())
+}
+
+/**
+ * Like `detect_multiple_in_luma_with_hints`, but once a barcode is found the regions around it
+ * are binarized and searched at the same time on rayon's thread pool. This pays off on large
+ * images holding many barcodes.
+ */
+#[cfg(feature = "parallel")]
+pub fn detect_multiple_in_luma_parallel_with_hints(
+    luma: Vec<u8>,
+    width: u32,
+    height: u32,
+    hints: &mut DecodeHints,
+) -> Result<Vec<RXingResult>> {
+    let multi_format_reader = MultiUseMultiFormatReader::default();
+    let mut scanner = GenericMultipleBarcodeReader::new(multi_format_reader);
+
+    hints.TryHarder = hints.TryHarder.or(Some(true));
+
+    scanner.decode_multiple_parallel_with_hints(
+        &mut BinaryBitmap::new(FixedThresholdBinarizer::new(Luma8LuminanceSource::new(
+            luma, width, height,
+        ))),
+        hints,
+    )
+}
+
 #[cfg(feature = "image")]
 pub fn save_image(file_name: &str, bit_matrix: &BitMatrix) -> Result<()> {
     let image: image::DynamicImage = bit_matrix.into();
@@ -315,33 +1913,828 @@ pub fn save_file(file_name: &str, bit_matrix: &BitMatrix) -> Result<()> {
 /**
  * Extracts witness data from a BinaryBitmap for zero-knowledge proof generation.
  *
- * This function captures the intermediate processing state during barcode decoding:
- * - The original grayscale luminance values
- * - The binarized black/white BitMatrix
+ * This function captures the first stages of the decode pipeline:
+ * - Luminance: the original grayscale luminance values
+ * - Binarization: the binarized black/white BitMatrix
+ *
+ * and their provenance, the crate version and the binarizer.
  *
  * # Arguments
  * * `bitmap` - The BinaryBitmap that has been used for decoding
  *
  * # Returns
- * WitnessData containing the image and binarized data
+ * PipelineWitness containing the luminance and binarization stages
  */
-pub fn extract_witness_data<B: Binarizer>(bitmap: &BinaryBitmap<B>) -> Result<WitnessData> {
+pub fn extract_witness_data<B: Binarizer>(bitmap: &BinaryBitmap<B>) -> Result<PipelineWitness> {
     // Get the binarized matrix (this triggers binarization if not already done)
     let binarized_matrix = bitmap.get_black_matrix().clone();
 
     // Get the luminance source
-    let source = bitmap.get_binarizer().get_luminance_source();
+    let binarizer = bitmap.get_binarizer();
+    let source = binarizer.get_luminance_source();
 
     // Extract the raw luminance data
     let width = source.get_width();
     let height = source.get_height();
     let luminance_data = source.get_matrix().to_vec();
 
-    // Create and return WitnessData
-    Ok(WitnessData::new(
-        width,
-        height,
-        luminance_data,
-        binarized_matrix,
-    ))
+    Ok(PipelineWitness::new()
+        .with_luminance(LuminanceWitness::new(width, height, luminance_data))
+        .with_binarization(BinarizationWitness {
+            threshold: binarizer.get_global_threshold(),
+            block_thresholds: binarizer.get_block_thresholds().cloned(),
+            ..BinarizationWitness::new(binarized_matrix)
+        })
+        .with_provenance(WitnessProvenance::new().with_binarizer(binarizer)))
+}
+
+/**
+ * Extracts witness data from a BinaryBitmap and the result decoded from it.
+ *
+ * In addition to the stages captured by `extract_witness_data`, this records:
+ * - Detection: the result points and the finder/alignment patterns the detector located
+ * - Scanline: the rows a 1D reader tried, the runs of the row it read the symbol from and the
+ *   patterns it matched, when the result carries them
+ * - Sampling: the sampling grid and the modules sampled from the binarized image, when the
+ *   result carries a sampling grid
+ * - Decoding: the raw codewords, the syndromes and corrections of each Reed-Solomon block and
+ *   the corrected data codewords, when the result carries them
+ * - TextAssembly: the raw bytes, byte segments and text of the result
+ *
+ * # Arguments
+ * * `bitmap` - The BinaryBitmap that has been used for decoding
+ * * `result` - The result decoded from `bitmap`
+ *
+ * # Returns
+ * PipelineWitness containing every stage the decode recorded
+ */
+pub fn extract_witness_data_with_result<B: Binarizer>(
+    bitmap: &BinaryBitmap<B>,
+    result: &RXingResult,
+) -> Result<PipelineWitness> {
+    let sink = Mutex::new(PipelineWitness::new());
+    stream_witness(&sink, bitmap, result)?;
+    Ok(sink.into_inner().unwrap_or_else(PoisonError::into_inner))
+}
+
+/**
+ * Writes the same witness data as `extract_witness_data_with_result`, but streams it to
+ * `writer` stage by stage instead of collecting it first. The luminance values are read from
+ * the source row by row and the binarized image is not copied, so memory use stays flat for
+ * large images.
+ *
+ * # Arguments
+ * * `bitmap` - The BinaryBitmap that has been used for decoding
+ * * `result` - The result decoded from `bitmap`
+ * * `writer` - The WitnessWriter to write the stages to, no stage may have been written yet
+ */
+#[cfg(feature = "serde")]
+pub fn write_witness_data<B: Binarizer, W: Write>(
+    bitmap: &BinaryBitmap<B>,
+    result: &RXingResult,
+    writer: &mut WitnessWriter<W>,
+) -> Result<()> {
+    let binarized_matrix = bitmap.get_black_matrix();
+    let sampling = SamplingWitness::from_result(result, binarized_matrix)?;
+    let scanline = match result
+        .getRXingResultMetadata()
+        .get(&RXingResultMetadataType::SCANLINE)
+    {
+        Some(RXingResultMetadataValue::Scanline(scanline)) => Some(scanline),
+        _ => None,
+    };
+    let codewords = match result
+        .getRXingResultMetadata()
+        .get(&RXingResultMetadataType::CODEWORDS)
+    {
+        Some(RXingResultMetadataValue::Codewords(codewords)) => Some(codewords),
+        _ => None,
+    };
+
+    writer
+        .write_luminance_source(bitmap.get_binarizer().get_luminance_source())
+        .and_then(|_| {
+            let threshold = bitmap.get_binarizer().get_global_threshold();
+            writer.write_binarization(binarized_matrix, threshold)
+        })
+        .and_then(|_| writer.write_detection(&DetectionWitness::from_result(result)))
+        .and_then(|_| scanline.map_or(Ok(()), |scanline| writer.write_scanline(scanline)))
+        .and_then(|_| sampling.map_or(Ok(()), |sampling| writer.write_sampling(&sampling)))
+        .and_then(|_| codewords.map_or(Ok(()), |codewords| writer.write_decoding(codewords)))
+        .and_then(|_| writer.write_text_assembly(&TextAssemblyWitness::from_result(result)))
+        .and_then(|_| {
+            let provenance = WitnessProvenance::new().with_binarizer(bitmap.get_binarizer());
+            writer.write_provenance(&provenance)
+        })
+        .map_err(|err| {
+            Exceptions::illegal_argument_with(format!("could not write witness data: {err}"))
+        })
+}
+
+/**
+ * Encodes `contents` and saves the symbol as a PNG file, drawn with `options`. Linear symbols
+ * are drawn 40 modules high unless the options give a bar height.
+ *
+ * # Arguments
+ * * `contents` - The text to encode
+ * * `barcode_type` - The symbology to encode it as
+ * * `file_name` - The PNG file to write, replaced if it exists
+ * * `options` - The scale, margin and colors to draw the symbol with
+ */
+#[cfg(feature = "image-render")]
+pub fn encode_to_png_file<P: AsRef<Path>>(
+    contents: &str,
+    barcode_type: BarcodeFormat,
+    file_name: P,
+    options: &ImageRenderOptions,
+) -> Result<()> {
+    encode_to_image_file_with_format(
+        contents,
+        barcode_type,
+        file_name.as_ref(),
+        image::ImageFormat::Png,
+        options,
+    )
+}
+
+/**
+ * Encodes `contents` and saves the symbol as an image file in the format its extension names,
+ * such as `.png` or `.bmp`, drawn with `options`. Linear symbols are drawn 40 modules high
+ * unless the options give a bar height.
+ */
+#[cfg(feature = "image-render")]
+pub fn encode_to_image_file<P: AsRef<Path>>(
+    contents: &str,
+    barcode_type: BarcodeFormat,
+    file_name: P,
+    options: &ImageRenderOptions,
+) -> Result<()> {
+    let file_name = file_name.as_ref();
+    let format = image::ImageFormat::from_path(file_name)
+        .ok()
+        .filter(|format| format.writing_enabled())
+        .ok_or_else(|| {
+            Exceptions::unsupported_operation_with(format!(
+                "cannot write images to '{}'",
+                file_name.display()
+            ))
+        })?;
+    encode_to_image_file_with_format(contents, barcode_type, file_name, format, options)
+}
+
+#[cfg(feature = "image-render")]
+fn encode_to_image_file_with_format(
+    contents: &str,
+    barcode_type: BarcodeFormat,
+    file_name: &Path,
+    format: image::ImageFormat,
+    options: &ImageRenderOptions,
+) -> Result<()> {
+    use crate::{MultiFormatWriter, Writer};
+
+    let symbol = MultiFormatWriter.encode(contents, &barcode_type, 0, 0)?;
+    let mut options = options.clone();
+    if barcode_type.is_1d() && options.bar_height.is_none() {
+        options.bar_height = Some(40 * options.scale.max(1));
+    }
+    let image = image::DynamicImage::from(render_to_image(&symbol, &options));
+    // drop the alpha channel unless the background is transparent, as JPEG has none
+    let image = match options.background {
+        Some(_) => image::DynamicImage::from(image.to_rgb8()),
+        None => image,
+    };
+    let saved = image.save_with_format(file_name, format);
+    saved.map_err(|e| {
+        Exceptions::illegal_argument_with(format!("could not write '{}': {e}", file_name.display()))
+    })
+}
+
+#[cfg(all(test, feature = "image"))]
+mod tests {
+    use super::{detect_multiple_in_files, FileDecodeError};
+    use crate::{test_fixtures::scaled_luma, Exceptions};
+
+    #[test]
+    fn test_detect_multiple_in_files() {
+        let outcomes = detect_multiple_in_files(&[
+            "test_resources/blackbox/qrcode-1/1.png",
+            "test_resources/blackbox/qrcode-1/missing.png",
+            "test_resources/blackbox/qrcode-1/1.txt",
+        ]);
+        assert_eq!(3, outcomes.len());
+
+        assert_eq!(
+            "test_resources/blackbox/qrcode-1/1.png",
+            outcomes[0].path.to_str().unwrap()
+        );
+        let results = outcomes[0].result.as_ref().unwrap();
+        assert!(!results.is_empty());
+        assert!(results[0].getText().starts_with("MEBKM:"));
+
+        assert!(matches!(outcomes[1].result, Err(FileDecodeError::Open(_))));
+        assert!(matches!(outcomes[2].result, Err(FileDecodeError::Open(_))));
+        assert!(outcomes.iter().all(|o| o.elapsed.as_secs() < 60));
+
+        // decode failures are reported with the reader's error
+        let error: FileDecodeError = Exceptions::NOT_FOUND.into();
+        assert_eq!(Exceptions::NOT_FOUND.to_string(), error.to_string());
+    }
+
+    #[cfg(feature = "url_fetch")]
+    #[test]
+    fn test_detect_in_url() {
+        use super::{detect_in_url, detect_in_url_with_hints, UrlFetchOptions};
+        use crate::DecodeHints;
+        use std::io::{BufRead, BufReader, Write};
+
+        let png = std::fs::read("test_resources/blackbox/qrcode-1/1.png").unwrap();
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/qr.png", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            for content_type in ["image/png", "text/html", "image/png"] {
+                let (stream, _) = listener.accept().unwrap();
+                let mut line = String::new();
+                let mut request = BufReader::new(&stream);
+                while request.read_line(&mut line).unwrap() > 2 {
+                    line.clear();
+                }
+                let head = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\n\
+                     Connection: close\r\n\r\n",
+                    png.len()
+                );
+                // the client hangs up on a response it refuses
+                let _ = (&stream).write_all(head.as_bytes());
+                let _ = (&stream).write_all(&png);
+            }
+        });
+
+        let result = detect_in_url(&url).unwrap();
+        assert!(result.getText().starts_with("MEBKM:"));
+        let error = detect_in_url(&url).unwrap_err();
+        assert!(error.to_string().contains("not an image"));
+        let options = UrlFetchOptions {
+            max_bytes: 1024,
+            ..Default::default()
+        };
+        let mut hints = DecodeHints::default();
+        let error = detect_in_url_with_hints(&url, None, &options, &mut hints).unwrap_err();
+        assert!(error.to_string().contains("larger than 1024 bytes"));
+        server.join().unwrap();
+    }
+
+    #[cfg(feature = "clipboard")]
+    #[test]
+    fn test_clipboard_rgba() {
+        use super::clipboard_rgba;
+        use crate::{
+            common::FixedThresholdBinarizer, BinaryBitmap, BufferedImageLuminanceSource,
+            MultiFormatReader, Reader,
+        };
+        use std::borrow::Cow;
+
+        let img = image::open("test_resources/blackbox/qrcode-2/1.png").unwrap();
+        let rgba = img.to_rgba8();
+        let data = arboard::ImageData {
+            width: rgba.width() as usize,
+            height: rgba.height() as usize,
+            bytes: Cow::Borrowed(rgba.as_raw()),
+        };
+        let source = BufferedImageLuminanceSource::new(clipboard_rgba(data).unwrap());
+        let mut bitmap = BinaryBitmap::new(FixedThresholdBinarizer::new(source));
+        assert!(MultiFormatReader::default().decode(&mut bitmap).is_ok());
+
+        let short = arboard::ImageData {
+            width: 2,
+            height: 2,
+            bytes: Cow::Owned(vec![0; 4]),
+        };
+        assert!(clipboard_rgba(short).is_err());
+    }
+
+    #[cfg(feature = "screen_capture")]
+    #[test]
+    fn test_screen_region_intersection() {
+        use super::ScreenRegion;
+
+        // a region straddling two side by side monitors
+        let left = ScreenRegion::new(0, 0, 1920, 1080);
+        let right = ScreenRegion::new(1920, 0, 1920, 1080);
+        let region = ScreenRegion::new(1800, 100, 300, 200);
+        let expected = ScreenRegion::new(1800, 100, 120, 200);
+        assert_eq!(Some(expected), left.intersection(&region));
+        let expected = ScreenRegion::new(1920, 100, 180, 200);
+        assert_eq!(Some(expected), right.intersection(&region));
+
+        // monitors left of the primary one have negative coordinates
+        let far_left = ScreenRegion::new(-1280, 0, 1280, 1024);
+        assert_eq!(None, far_left.intersection(&region));
+        assert_eq!(None, left.intersection(&right));
+    }
+
+    #[test]
+    fn test_raw_frame_scanner() {
+        use super::{RawFrameScanner, RawPixelFormat};
+        use crate::{BarcodeFormat, EncodeHintValue, EncodeHints, MultiFormatWriter, Writer};
+
+        let hints = EncodeHints::default().with(EncodeHintValue::Margin("4".to_owned()));
+        let symbol = MultiFormatWriter
+            .encode_with_hints("raw frames", &BarcodeFormat::QR_CODE, 0, 0, &hints)
+            .unwrap();
+        let (luma, side, _) = scaled_luma(&symbol, 3, 0, 255);
+        let scanner = |frames: &[u8], format| {
+            let stream = std::io::Cursor::new(frames.to_vec());
+            RawFrameScanner::new(stream, side, side, format)
+        };
+
+        // a blank frame between two frames showing the code
+        let len = RawPixelFormat::Nv12.frame_len(side as usize, side as usize);
+        let code = [luma.clone(), vec![128; len - luma.len()]].concat();
+        let stream = [code.clone(), vec![255; len], code].concat();
+        let frames = scanner(&stream, RawPixelFormat::Nv12).unwrap();
+        let frames: Vec<_> = frames.map(|frame| frame.unwrap()).collect();
+        let found: Vec<_> = frames.iter().map(|frame| frame.results.len()).collect();
+        assert_eq!(vec![1, 0, 1], found);
+        assert_eq!(2, frames[2].frame);
+        assert_eq!("raw frames", frames[2].results[0].getText());
+
+        let rgb: Vec<u8> = luma.iter().flat_map(|&l| [l, l, l]).collect();
+        let mut frames = scanner(&rgb, RawPixelFormat::Rgb24).unwrap();
+        assert_eq!(1, frames.next().unwrap().unwrap().results.len());
+        assert!(frames.next().is_none());
+
+        // a stream cut off within a frame
+        let mut frames = scanner(&rgb[1..], RawPixelFormat::Rgb24).unwrap();
+        assert!(frames.next().unwrap().is_err());
+        assert!(RawFrameScanner::new(&rgb[..], 0, 1, RawPixelFormat::Gray8).is_err());
+    }
+
+    #[test]
+    fn test_detect_in_luma_with_options() {
+        use super::detect_in_luma_with_options;
+        use crate::{BarcodeFormat, DecodeOptions, EncodeOptions, MultiFormatWriter, Writer};
+
+        let hints = EncodeOptions::new().margin(4).into();
+        let symbol = MultiFormatWriter
+            .encode_with_hints("options", &BarcodeFormat::DATA_MATRIX, 0, 0, &hints)
+            .unwrap();
+        let (luma, width, height) = scaled_luma(&symbol, 4, 0, 255);
+
+        let qr_only = DecodeOptions::new().formats(&[BarcodeFormat::QR_CODE]);
+        assert!(detect_in_luma_with_options(luma.clone(), width, height, qr_only).is_err());
+        let options = DecodeOptions::new().formats(&[BarcodeFormat::DATA_MATRIX]);
+        let result = detect_in_luma_with_options(luma, width, height, options).unwrap();
+        assert_eq!("options", result.getText());
+    }
+
+    #[test]
+    fn test_scan_directory() {
+        use super::{scan_directory, ScanDirectoryOptions};
+        use std::sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        };
+
+        let dir = std::env::temp_dir().join(format!("rxing_scan_{}", std::process::id()));
+        let nested = dir.join("nested");
+        std::fs::create_dir_all(&nested).unwrap();
+        let png = "test_resources/blackbox/qrcode-1/1.png";
+        std::fs::copy(png, dir.join("a.png")).unwrap();
+        std::fs::copy(png, nested.join("b.png")).unwrap();
+        std::fs::write(dir.join("c.png"), "not a png").unwrap();
+        std::fs::write(dir.join("notes.txt"), "skipped").unwrap();
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counted = calls.clone();
+        let options = ScanDirectoryOptions {
+            threads: 2,
+            progress: Some(Arc::new(move |done, total| {
+                assert!(done <= total && total == 3);
+                counted.fetch_add(1, Ordering::Relaxed);
+            })),
+            ..Default::default()
+        };
+        let outcomes = scan_directory(&dir, &options).unwrap();
+        let expected = [dir.join("a.png"), dir.join("c.png"), nested.join("b.png")];
+        assert_eq!(expected.len(), outcomes.len());
+        for (path, outcome) in expected.iter().zip(&outcomes) {
+            assert_eq!(path, &outcome.path);
+        }
+        assert!(outcomes[0].is_ok() && outcomes[2].is_ok());
+        assert!(matches!(outcomes[1].result, Err(FileDecodeError::Open(_))));
+        assert_eq!(3, calls.load(Ordering::Relaxed));
+
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(&dir, nested.join("loop")).unwrap();
+            let outcomes = scan_directory(&dir, &ScanDirectoryOptions::default()).unwrap();
+            assert_eq!(expected.len(), outcomes.len());
+        }
+
+        let shallow = ScanDirectoryOptions {
+            recursive: false,
+            ..Default::default()
+        };
+        assert_eq!(2, scan_directory(&dir, &shallow).unwrap().len());
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(scan_directory(&dir, &shallow).is_err());
+    }
+
+    #[test]
+    fn test_decode_directory() {
+        use super::{decode_directory, ScanDirectoryOptions};
+        use crate::BarcodeFormat;
+        use std::collections::HashSet;
+
+        let dir = std::env::temp_dir().join(format!("rxing_report_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::copy("test_resources/blackbox/qrcode-1/1.png", dir.join("a.png")).unwrap();
+        std::fs::write(dir.join("b, broken.png"), "not a png").unwrap();
+
+        let mut options = ScanDirectoryOptions {
+            threads: 1,
+            ..Default::default()
+        };
+        options.hints.PossibleFormats = Some(HashSet::from([BarcodeFormat::QR_CODE]));
+        let report = decode_directory(&dir, &options).unwrap();
+        assert_eq!(1, report.decoded().count());
+        assert_eq!(1, report.failed().count());
+        assert_eq!(BarcodeFormat::QR_CODE, report.files[0].symbols[0].format);
+        assert!(report.files[1].error.is_some());
+
+        let csv = report.to_csv();
+        let mut lines = csv.lines();
+        assert_eq!(Some("path,format,text,elapsed_ms,error"), lines.next());
+        assert!(lines.next().unwrap().contains(",qrcode,MEBKM:URL:"));
+        assert!(lines.next().unwrap().contains("b, broken.png\",,,"));
+
+        #[cfg(feature = "serde")]
+        assert_eq!(
+            report,
+            serde_json::from_str(&report.to_json().unwrap()).unwrap()
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(feature = "image_formats")]
+    #[test]
+    fn test_open_image_by_contents() {
+        use super::{detect_in_file, open_image};
+
+        // phones save HEIC photos as .jpg, the contents decide how an image is read
+        let png = "test_resources/blackbox/qrcode-2/1.png";
+        let path = std::env::temp_dir().join(format!("rxing_png_{}.jpg", std::process::id()));
+        std::fs::copy(png, &path).unwrap();
+        assert_eq!(image::open(png).unwrap(), open_image(&path).unwrap());
+        assert!(detect_in_file(path.to_str().unwrap(), None).is_ok());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_detect_in_data_uri() {
+        use super::{detect_in_data_uri, detect_multiple_in_data_uri};
+        use crate::Exceptions::UnsupportedOperationException;
+        use base64::Engine;
+
+        let path = "test_resources/blackbox/qrcode-2/1.png";
+        let png = std::fs::read(path).unwrap();
+        let encoded = base64::engine::general_purpose::STANDARD.encode(png);
+        let expected = super::detect_in_file(path, None).unwrap();
+
+        // as found in HTML, with the base64 text wrapped over several lines
+        let lines: Vec<_> = encoded.as_bytes().chunks(76).collect();
+        let lines = String::from_utf8(lines.join(&b'\n')).unwrap();
+        let uri = format!("data:image/PNG;base64,{lines}");
+        let result = detect_in_data_uri(&uri, None).unwrap();
+        assert_eq!(expected.getText(), result.getText());
+        assert_eq!(1, detect_multiple_in_data_uri(&uri).unwrap().len());
+
+        let unsupported = [
+            format!("data:text/plain;base64,{encoded}"),
+            format!("data:image/svg+xml;base64,{encoded}"),
+            "data:image/png,not-base64".to_owned(),
+        ];
+        for uri in unsupported {
+            let error = detect_in_data_uri(&uri, None);
+            assert!(matches!(error, Err(UnsupportedOperationException(_))));
+        }
+        assert!(detect_in_data_uri("data:image/png;base64,@@@@", None).is_err());
+        assert!(detect_in_data_uri("https://example.com/qr.png", None).is_err());
+    }
+
+    #[cfg(feature = "image_formats")]
+    #[test]
+    fn test_open_image_upright() {
+        use super::{load_image_from_memory, open_image};
+        use image::{codecs::png::PngEncoder, ImageEncoder};
+
+        // big endian EXIF with the entries at offset 8
+        let mut exif = vec![0x4d, 0x4d, 0, 42, 0, 0, 0, 8];
+        // a single entry, orientation 6: turn 90 degrees clockwise to view
+        exif.extend([0, 1, 0x01, 0x12, 0, 3, 0, 0, 0, 1, 0, 6, 0, 0]);
+        exif.extend([0, 0, 0, 0]);
+        let upright = image::open("test_resources/blackbox/qrcode-2/1.png").unwrap();
+        let sideways = upright.rotate270().to_rgba8();
+        let mut png = Vec::new();
+        let mut encoder = PngEncoder::new(&mut png);
+        encoder.set_exif_metadata(exif).unwrap();
+        let (w, h) = sideways.dimensions();
+        let color = image::ExtendedColorType::Rgba8;
+        encoder.write_image(&sideways, w, h, color).unwrap();
+
+        let img = load_image_from_memory(&png).unwrap();
+        assert_eq!(upright.to_rgba8(), img.to_rgba8());
+
+        let path = std::env::temp_dir().join(format!("rxing_exif_{}.png", std::process::id()));
+        std::fs::write(&path, &png).unwrap();
+        assert_eq!(img, open_image(&path).unwrap());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_unorient_point() {
+        use super::unorient_point;
+        use crate::point;
+        use image::{metadata::Orientation, DynamicImage, GrayImage, Luma};
+
+        let orientations = [
+            Orientation::NoTransforms,
+            Orientation::Rotate90,
+            Orientation::Rotate180,
+            Orientation::Rotate270,
+            Orientation::FlipHorizontal,
+            Orientation::FlipVertical,
+            Orientation::Rotate90FlipH,
+            Orientation::Rotate270FlipH,
+        ];
+        // a single white pixel, found again in each upright image
+        let mut stored = GrayImage::new(5, 3);
+        stored.put_pixel(1, 2, Luma([255]));
+        for orientation in orientations {
+            let mut upright = DynamicImage::ImageLuma8(stored.clone());
+            upright.apply_orientation(orientation);
+            let upright = upright.to_luma8();
+            let (x, y, _) = upright
+                .enumerate_pixels()
+                .find(|(_, _, pixel)| pixel[0] == 255)
+                .unwrap();
+            let (width, height) = upright.dimensions();
+            let p = unorient_point(point(x as f32, y as f32), orientation, width, height);
+            assert_eq!(point(1.0, 2.0), p, "{orientation:?}");
+        }
+    }
+
+    #[cfg(feature = "image_formats")]
+    #[test]
+    fn test_detect_in_file_oriented() {
+        use super::detect_in_file;
+        use image::{codecs::png::PngEncoder, ImageEncoder};
+
+        // orientation 6, as in test_open_image_upright
+        let mut exif = vec![0x4d, 0x4d, 0, 42, 0, 0, 0, 8];
+        exif.extend([0, 1, 0x01, 0x12, 0, 3, 0, 0, 0, 1, 0, 6, 0, 0]);
+        exif.extend([0, 0, 0, 0]);
+        let sideways = image::open("test_resources/blackbox/qrcode-2/1.png")
+            .unwrap()
+            .rotate270()
+            .to_rgba8();
+        let (w, h) = sideways.dimensions();
+        let write = |name: &str, exif: Option<Vec<u8>>| {
+            let mut png = Vec::new();
+            let mut encoder = PngEncoder::new(&mut png);
+            if let Some(exif) = exif {
+                encoder.set_exif_metadata(exif).unwrap();
+            }
+            let color = image::ExtendedColorType::Rgba8;
+            encoder.write_image(&sideways, w, h, color).unwrap();
+            let path =
+                std::env::temp_dir().join(format!("rxing_{name}_{}.png", std::process::id()));
+            std::fs::write(&path, &png).unwrap();
+            path
+        };
+        let oriented = write("oriented", Some(exif));
+        let stored = write("stored", None);
+
+        // the points are where the symbol is in the pixels of the file, not in the upright
+        // image, up to the few pixels finding the finder patterns in another direction differs
+        let upright = detect_in_file(oriented.to_str().unwrap(), None).unwrap();
+        let expected = detect_in_file(stored.to_str().unwrap(), None).unwrap();
+        assert_eq!(expected.getText(), upright.getText());
+        assert_eq!(expected.getPoints().len(), upright.getPoints().len());
+        for (a, b) in expected.getPoints().iter().zip(upright.getPoints()) {
+            assert!(
+                (a.x - b.x).abs() <= 4.0 && (a.y - b.y).abs() <= 4.0,
+                "{a:?} {b:?}"
+            );
+        }
+        std::fs::remove_file(&oriented).unwrap();
+        std::fs::remove_file(&stored).unwrap();
+    }
+
+    #[test]
+    fn test_detect_multiple_in_image() {
+        use super::detect_multiple_in_image;
+        use crate::{BarcodeFormat, MultiFormatWriter, Writer};
+        use image::{GrayImage, Luma};
+
+        // each symbol is drawn turned clockwise by a multiple of 90 degrees
+        let mut img = GrayImage::from_pixel(400, 400, Luma([255]));
+        let mut draw = |contents: &str, format, (left, top): (u32, u32), turns: u32| {
+            let symbol = MultiFormatWriter
+                .encode(contents, &format, 120, 80)
+                .unwrap();
+            let (w, h) = (symbol.getWidth(), symbol.getHeight());
+            for (x, y) in (0..h).flat_map(|y| (0..w).map(move |x| (x, y))) {
+                if symbol.get(x, y) {
+                    let (x, y) = match turns {
+                        0 => (x, y),
+                        1 => (h - 1 - y, x),
+                        _ => (w - 1 - x, h - 1 - y),
+                    };
+                    img.put_pixel(left + x, top + y, Luma([0]));
+                }
+            }
+        };
+        draw("upright", BarcodeFormat::QR_CODE, (10, 10), 0);
+        draw("upside down", BarcodeFormat::DATA_MATRIX, (200, 20), 2);
+        draw("sideways", BarcodeFormat::CODE_128, (40, 200), 1);
+
+        let mut symbols = detect_multiple_in_image(image::DynamicImage::ImageLuma8(img)).unwrap();
+        symbols.sort_by(|a, b| a.text.cmp(&b.text));
+        let found: Vec<_> = symbols
+            .iter()
+            .map(|s| (s.format.clone(), s.text.as_str()))
+            .collect();
+        assert_eq!(
+            vec![
+                (BarcodeFormat::CODE_128, "sideways"),
+                (BarcodeFormat::QR_CODE, "upright"),
+                (BarcodeFormat::DATA_MATRIX, "upside down"),
+            ],
+            found
+        );
+
+        let near = |a: f32, b: f32| (a - b).abs() < 2.0;
+        let rotations: Vec<_> = symbols.iter().map(|s| s.rotation).collect();
+        assert!(
+            near(rotations[0], 90.0) && near(rotations[2], 180.0),
+            "{rotations:?}"
+        );
+        assert!(
+            near(rotations[1], 0.0) || near(rotations[1], 360.0),
+            "{rotations:?}"
+        );
+
+        // the corners start at the top left as the symbol reads
+        let [tl, tr, br, bl] = symbols[2].corners;
+        assert!(
+            tl.x > tr.x && tl.y > bl.y && br.x < bl.x,
+            "{:?}",
+            symbols[2].corners
+        );
+        let [tl, tr, br, _] = symbols[1].corners;
+        assert!(tl.x < tr.x && tr.y < br.y && tl.x >= 10.0 && br.y <= 130.0);
+        // a linear symbol read down the image, on a column
+        let [start, end, ..] = symbols[0].corners;
+        assert!(near(start.x, end.x) && start.y < end.y && end.y <= 330.0);
+    }
+
+    #[cfg(feature = "image_formats")]
+    #[test]
+    fn test_detect_in_animation() {
+        use super::detect_in_animation;
+        use crate::{BarcodeFormat, EncodeHintValue, EncodeHints, MultiFormatWriter, Writer};
+        use image::{codecs::gif::GifEncoder, Delay, Frame, Rgba, RgbaImage};
+        use std::time::Duration;
+
+        let hints = EncodeHints::default().with(EncodeHintValue::Margin("4".to_owned()));
+        let render = |contents: Option<&str>| {
+            let mut img = RgbaImage::from_pixel(120, 120, Rgba([255, 255, 255, 255]));
+            if let Some(contents) = contents {
+                let symbol = MultiFormatWriter
+                    .encode_with_hints(contents, &BarcodeFormat::QR_CODE, 0, 0, &hints)
+                    .unwrap();
+                for (x, y, pixel) in img.enumerate_pixels_mut() {
+                    let (x, y) = (x / 4, y / 4);
+                    if x < symbol.getWidth() && y < symbol.getHeight() && symbol.get(x, y) {
+                        *pixel = Rgba([0, 0, 0, 255]);
+                    }
+                }
+            }
+            Frame::from_parts(img, 0, 0, Delay::from_numer_denom_ms(100, 1))
+        };
+
+        // the first barcode is shown for two frames, then nothing, then another barcode
+        let path = std::env::temp_dir().join(format!("rxing_frames_{}.gif", std::process::id()));
+        let frames = [Some("first"), Some("first"), None, Some("second")].map(render);
+        let mut encoder = GifEncoder::new(std::fs::File::create(&path).unwrap());
+        encoder.encode_frames(frames).unwrap();
+        drop(encoder);
+
+        let results = detect_in_animation(path.to_str().unwrap()).unwrap();
+        let found: Vec<(usize, Duration, &str)> = results
+            .iter()
+            .map(|found| (found.frame, found.timestamp, found.result.getText()))
+            .collect();
+        let expected = vec![
+            (0, Duration::ZERO, "first"),
+            (3, Duration::from_millis(300), "second"),
+        ];
+        assert_eq!(expected, found);
+        std::fs::remove_file(&path).unwrap();
+
+        // a still image is a single frame
+        let still = detect_in_animation("test_resources/blackbox/qrcode-1/1.png").unwrap();
+        assert_eq!(0, still[0].frame);
+        assert!(still[0].result.getText().starts_with("MEBKM:"));
+    }
+
+    #[cfg(feature = "multipage_tiff")]
+    #[test]
+    fn test_detect_in_tiff_pages() {
+        use super::{detect_in_tiff, detect_multiple_in_tiff};
+        use crate::{BarcodeFormat, EncodeHintValue, EncodeHints, MultiFormatWriter, Writer};
+        use tiff::encoder::{colortype::Gray8, colortype::RGB8, TiffEncoder};
+
+        let render = |contents: &str| {
+            let hints = EncodeHints::default().with(EncodeHintValue::Margin("4".to_owned()));
+            let symbol = MultiFormatWriter
+                .encode_with_hints(contents, &BarcodeFormat::QR_CODE, 0, 0, &hints)
+                .unwrap();
+            let (luma, side, _) = scaled_luma(&symbol, 4, 0, 255);
+            (side, luma)
+        };
+
+        // a gray page, a blank page and a color page
+        let path = std::env::temp_dir().join(format!("rxing_pages_{}.tif", std::process::id()));
+        let mut encoder = TiffEncoder::new(std::fs::File::create(&path).unwrap()).unwrap();
+        let (side, first) = render("first page");
+        encoder.write_image::<Gray8>(side, side, &first).unwrap();
+        let blank = vec![255u8; (side * side) as usize];
+        encoder.write_image::<Gray8>(side, side, &blank).unwrap();
+        let (side, third) = render("third page");
+        let rgb: Vec<u8> = third.iter().flat_map(|&value| [value; 3]).collect();
+        encoder.write_image::<RGB8>(side, side, &rgb).unwrap();
+        drop(encoder);
+
+        let file_name = path.to_str().unwrap();
+        let results = detect_in_tiff(file_name, None).unwrap();
+        let pages: Vec<(usize, &str)> = results
+            .iter()
+            .map(|found| (found.page, found.result.getText()))
+            .collect();
+        assert_eq!(vec![(0, "first page"), (2, "third page")], pages);
+        assert_eq!(2, detect_multiple_in_tiff(file_name).unwrap().len());
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(detect_in_tiff("test_resources/blackbox/qrcode-1/1.png", None).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_write_witness_data() {
+        use super::{extract_witness_data_with_result, write_witness_data};
+        use crate::{
+            common::HybridBinarizer, BinaryBitmap, BufferedImageLuminanceSource, MultiFormatReader,
+            Reader, WitnessFormat, WitnessWriter,
+        };
+
+        let img = image::open("test_resources/blackbox/qrcode-2/1.png").unwrap();
+        let mut bitmap =
+            BinaryBitmap::new(HybridBinarizer::new(BufferedImageLuminanceSource::new(img)));
+        let result = MultiFormatReader::default().decode(&mut bitmap).unwrap();
+
+        let mut writer = WitnessWriter::new(Vec::new(), WitnessFormat::Json).unwrap();
+        write_witness_data(&bitmap, &result, &mut writer).unwrap();
+        let json = writer.finish().unwrap();
+
+        let witness = extract_witness_data_with_result(&bitmap, &result).unwrap();
+        assert_eq!(
+            serde_json::to_value(&witness).unwrap(),
+            serde_json::from_slice::<serde_json::Value>(&json).unwrap()
+        );
+    }
+
+    #[cfg(feature = "image-render")]
+    #[test]
+    fn test_encode_to_image_file() {
+        use super::{detect_in_file, encode_to_image_file, encode_to_png_file};
+        use crate::{render::ImageRenderOptions, BarcodeFormat};
+
+        let options = ImageRenderOptions::default().with_margin(4);
+        let png = std::env::temp_dir().join(format!("rxing_encode_{}.png", std::process::id()));
+        encode_to_png_file("png contents", BarcodeFormat::QR_CODE, &png, &options).unwrap();
+        let result = detect_in_file(png.to_str().unwrap(), Some(BarcodeFormat::QR_CODE)).unwrap();
+        assert_eq!("png contents", result.getText());
+        std::fs::remove_file(&png).unwrap();
+
+        let bmp = std::env::temp_dir().join(format!("rxing_encode_{}.bmp", std::process::id()));
+        encode_to_image_file("0123456789", BarcodeFormat::CODE_128, &bmp, &options).unwrap();
+        let result = detect_in_file(bmp.to_str().unwrap(), Some(BarcodeFormat::CODE_128)).unwrap();
+        assert_eq!("0123456789", result.getText());
+        std::fs::remove_file(&bmp).unwrap();
+
+        let txt = std::env::temp_dir().join(format!("rxing_encode_{}.txt", std::process::id()));
+        assert!(encode_to_image_file("text", BarcodeFormat::QR_CODE, &txt, &options).is_err());
+    }
 }
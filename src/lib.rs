@@ -27,7 +27,9 @@ mod PlanarYUVLuminanceSourceTestCase;
 #[cfg(test)]
 mod rgb_luminance_source_test_case;
 
+#[deprecated(note = "build the hints with EncodeOptions, or set the fields of EncodeHints")]
 pub type EncodingHintDictionary = HashMap<EncodeHintType, EncodeHintValue>;
+#[deprecated(note = "build the hints with DecodeOptions, or set the fields of DecodeHints")]
 pub type DecodingHintDictionary = HashMap<DecodeHintType, DecodeHintValue>;
 pub type MetadataDictionary = HashMap<RXingResultMetadataType, RXingResultMetadataValue>;
 
@@ -37,6 +39,9 @@ pub use barcode_format::*;
 mod encode_hints;
 pub use encode_hints::*;
 
+mod encode_options;
+pub use encode_options::*;
+
 /// Callback which is invoked when a possible result point (significant
 /// point in the barcode image such as a corner) is found.
 pub type PointCallback = Arc<dyn Fn(Point) + Send + Sync>;
@@ -47,6 +52,12 @@ pub type RXingResultPointCallback = PointCallback;
 mod decode_hints;
 pub use decode_hints::*;
 
+mod decode_options;
+pub use decode_options::*;
+
+mod strictness;
+pub use strictness::*;
+
 mod writer;
 pub use writer::*;
 
@@ -59,8 +70,67 @@ pub use rxing_result_metadata::*;
 mod rxing_result;
 pub use rxing_result::*;
 
-mod witness_data;
-pub use witness_data::*;
+mod rxing_result_content;
+pub use rxing_result_content::*;
+
+mod decode_warning;
+pub use decode_warning::*;
+
+mod decode_confidence;
+pub use decode_confidence::*;
+
+mod symbol_pose;
+pub use symbol_pose::*;
+
+mod symbol_version;
+pub use symbol_version::*;
+
+mod cancellation;
+pub use cancellation::*;
+
+mod pipeline_witness;
+pub use pipeline_witness::*;
+
+mod witness_provenance;
+pub use witness_provenance::*;
+
+mod witness_redaction;
+pub use witness_redaction::*;
+
+mod witness_session;
+pub use witness_session::*;
+
+mod witness_verification;
+pub use witness_verification::*;
+
+mod witness_sink;
+pub use witness_sink::*;
+
+mod witness_recorder;
+pub use witness_recorder::*;
+
+mod encoding_witness;
+pub use encoding_witness::*;
+
+#[cfg(feature = "serde")]
+mod witness_writer;
+#[cfg(feature = "serde")]
+pub use witness_writer::*;
+
+#[cfg(feature = "serde")]
+mod witness_compression;
+#[cfg(feature = "serde")]
+pub use witness_compression::MatrixCompression;
+
+#[cfg(feature = "serde")]
+mod witness_schema;
+#[cfg(feature = "serde")]
+pub use witness_schema::WITNESS_SCHEMA_VERSION;
+
+#[cfg(feature = "field_elements")]
+mod witness_fields;
+#[cfg(feature = "field_elements")]
+pub use witness_fields::*;
 
 mod result_point;
 pub use result_point::*;
@@ -85,6 +155,27 @@ pub use luminance_source::*;
 mod planar_yuv_luminance_source;
 pub use planar_yuv_luminance_source::*;
 
+mod yuv_luminance_source;
+pub use yuv_luminance_source::*;
+
+mod bayer_luminance_source;
+pub use bayer_luminance_source::*;
+
+mod packed_pixel_luminance_source;
+pub use packed_pixel_luminance_source::*;
+
+mod windowed_luminance_source;
+pub use windowed_luminance_source::*;
+
+mod tiled_luminance_source;
+pub use tiled_luminance_source::*;
+
+mod cropped_luminance_source;
+pub use cropped_luminance_source::*;
+
+mod rotated_luminance_source;
+pub use rotated_luminance_source::*;
+
 mod rgb_luminance_source;
 pub use rgb_luminance_source::*;
 
@@ -95,21 +186,99 @@ pub mod pdf417;
 
 mod multi_format_writer;
 pub use multi_format_writer::*;
+
+mod writer_registry;
+pub use writer_registry::*;
+
+mod capacity;
+pub use capacity::*;
+
+mod encode_plan;
+pub use encode_plan::*;
 mod multi_use_multi_format_reader;
 pub use multi_use_multi_format_reader::*;
 
 mod multi_format_reader;
 pub use multi_format_reader::*;
 
+mod decoder_pool;
+pub use decoder_pool::*;
+
+mod reader_registry;
+pub use reader_registry::*;
+
+mod decode_pipeline;
+pub use decode_pipeline::*;
+
+mod locate;
+pub use locate::*;
+
+mod decode_no_panic;
+pub use decode_no_panic::*;
+
+mod stream_decoder;
+pub use stream_decoder::*;
+
+mod tracked_reader;
+pub use tracked_reader::*;
+
+// Filters that clean up grayscale images before they are binarized
+pub mod preprocess;
+
+// Print quality grading after ISO/IEC 15415 and ISO/IEC 15416
+pub mod print_quality;
+
+// SVG and EPS output of encoded symbols
+pub mod render;
+
+// Overlays of what the detectors found, for debugging images that fail to decode
+pub mod debug_render;
+
 // Simple methods to help detect barcodes in common situations
 pub mod helpers;
 
+#[cfg(feature = "tokio")]
+pub mod async_helpers;
+
+#[cfg(feature = "camera")]
+pub mod camera;
+
+#[cfg(feature = "gstreamer")]
+pub mod gstreamer;
+
+#[cfg(feature = "capi")]
+pub mod capi;
+
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+#[cfg(feature = "python")]
+pub mod python;
+
+#[cfg(feature = "uniffi")]
+pub mod mobile;
+
+#[cfg(feature = "uniffi")]
+uniffi::setup_scaffolding!("rxing");
+
+#[cfg(feature = "conformance")]
+pub mod conformance;
+
+#[cfg(feature = "test-utils")]
+pub mod test_utils;
+
+#[cfg(test)]
+mod test_fixtures;
+
 mod luma_luma_source;
 pub use luma_luma_source::*;
 
 mod filtered_image_reader;
 pub use filtered_image_reader::*;
 
+mod pyramid_reader;
+pub use pyramid_reader::*;
+
 #[cfg(feature = "svg_read")]
 mod svg_luminance_source;
 #[cfg(feature = "svg_read")]
@@ -0,0 +1,668 @@
+/*
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use crate::{
+    aztec::detector::Detector as AztecDetector,
+    common::{BitArray, BitMatrix, DetectedPattern, DetectorRXingResult, PatternKind, Result},
+    datamatrix::detector::zxing_cpp_detector,
+    maxicode::detector as maxicode_detector,
+    multi::qrcode::detector::MultiFinderPatternFinder,
+    oned::{
+        Code128Reader, Code39Reader, Code93Reader, ITFReader, MultiFormatUPCEANReader, OneDReader,
+    },
+    pdf417::detector::pdf_417_detector,
+    point,
+    qrcode::detector::{FinderPatternFinder, FinderPatternInfo},
+    BarcodeFormat, Binarizer, BinaryBitmap, DecodeHints, Point,
+};
+
+/// The formats [`locate`] finds symbols of
+pub const LOCATABLE_FORMATS: [BarcodeFormat; 13] = [
+    BarcodeFormat::QR_CODE,
+    BarcodeFormat::DATA_MATRIX,
+    BarcodeFormat::AZTEC,
+    BarcodeFormat::PDF_417,
+    BarcodeFormat::MAXICODE,
+    BarcodeFormat::CODE_128,
+    BarcodeFormat::CODE_39,
+    BarcodeFormat::CODE_93,
+    BarcodeFormat::ITF,
+    BarcodeFormat::EAN_13,
+    BarcodeFormat::UPC_A,
+    BarcodeFormat::EAN_8,
+    BarcodeFormat::UPC_E,
+];
+
+/// The white space after a linear symbol that ends it, in modules. No symbol has a gap as wide.
+const LINEAR_QUIET_ZONE: f32 = 6.0;
+
+/// The width of the narrowest linear symbols, a start and a stop pattern and a character or two
+const MIN_LINEAR_MODULES: f32 = 24.0;
+
+/**
+ * A region of an image that likely holds a symbol, found by its finder, start or stop
+ * patterns without decoding it. See [`locate`].
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct LocatedSymbol {
+    pub format: BarcodeFormat,
+    /// The corners of the region in image pixels, in order around it
+    pub corners: [Point; 4],
+    /// The patterns the symbol was located by
+    pub patterns: Vec<DetectedPattern>,
+}
+
+impl LocatedSymbol {
+    /**
+     * The bounding box of the corners grown by `margin` pixels on each side and clipped to an
+     * image of `width` by `height` pixels, as `(left, top, width, height)` for
+     * [`crate::LuminanceSource::crop`].
+     */
+    pub fn bounds(&self, margin: f32, width: usize, height: usize) -> (usize, usize, usize, usize) {
+        let clip = |v: f32, max: usize| (v.max(0.0) as usize).min(max);
+        let (mut left, mut top) = (f32::MAX, f32::MAX);
+        let (mut right, mut bottom) = (f32::MIN, f32::MIN);
+        for corner in self.corners {
+            left = left.min(corner.x);
+            top = top.min(corner.y);
+            right = right.max(corner.x);
+            bottom = bottom.max(corner.y);
+        }
+        let (left, top) = (clip(left - margin, width), clip(top - margin, height));
+        let right = clip((right + margin).ceil(), width);
+        let bottom = clip((bottom + margin).ceil(), height);
+        (left, top, right - left, bottom - top)
+    }
+}
+
+/**
+ * Finds the regions of an image that likely hold symbols of the formats in the
+ * `PossibleFormats` hint, or of all [`LOCATABLE_FORMATS`], without decoding them. Symbols are
+ * reported even when they fail to decode, so a client can draw "aim here" overlays or crop
+ * the image for a second attempt.
+ *
+ * 2D symbols are located by their finder patterns: the three of a QR code, the 'L' of a Data
+ * Matrix symbol, the bull's eye of an Aztec or MaxiCode symbol and the start and stop
+ * patterns of a PDF417 symbol. Linear symbols are located by start patterns found on rows of
+ * the image, which are merged with those of neighbouring rows.
+ *
+ * The `TryHarder` hint searches more of the image, and `Cancellation` abandons the search.
+ */
+pub fn locate<B: Binarizer>(
+    image: &mut BinaryBitmap<B>,
+    hints: &DecodeHints,
+) -> Result<Vec<LocatedSymbol>> {
+    let formats: Vec<BarcodeFormat> = LOCATABLE_FORMATS
+        .into_iter()
+        .filter(|format| {
+            hints
+                .PossibleFormats
+                .as_ref()
+                .is_none_or(|formats| formats.contains(format))
+        })
+        .collect();
+    locate_formats(image, &formats, hints)
+}
+
+/**
+ * Finds the regions of an image that likely hold symbols of `format`, see [`locate`]. Formats
+ * not in [`LOCATABLE_FORMATS`] are never found.
+ */
+pub fn locate_format<B: Binarizer>(
+    image: &mut BinaryBitmap<B>,
+    format: BarcodeFormat,
+    hints: &DecodeHints,
+) -> Result<Vec<LocatedSymbol>> {
+    locate_formats(image, &[format], hints)
+}
+
+fn locate_formats<B: Binarizer>(
+    image: &mut BinaryBitmap<B>,
+    formats: &[BarcodeFormat],
+    hints: &DecodeHints,
+) -> Result<Vec<LocatedSymbol>> {
+    let try_harder = hints.TryHarder.unwrap_or(false);
+    let mut symbols = Vec::new();
+
+    for format in formats {
+        hints.check_cancelled()?;
+        let matrix = || image.get_black_matrix();
+        match format {
+            BarcodeFormat::QR_CODE => symbols.extend(locate_qr_code(matrix(), hints)),
+            BarcodeFormat::DATA_MATRIX => {
+                if let Ok(results) = zxing_cpp_detector::detect(matrix(), try_harder, true) {
+                    symbols.extend(results.iter().filter_map(|result| {
+                        located(BarcodeFormat::DATA_MATRIX, result, PatternKind::Corner)
+                    }));
+                }
+            }
+            BarcodeFormat::AZTEC => {
+                let mut detector = AztecDetector::new(matrix());
+                if let Ok(result) = detector.detect(false).or_else(|_| detector.detect(true)) {
+                    symbols.extend(located(BarcodeFormat::AZTEC, &result, PatternKind::Finder));
+                }
+            }
+            BarcodeFormat::PDF_417 => symbols.extend(locate_pdf_417(matrix())),
+            BarcodeFormat::MAXICODE => {
+                let result = maxicode_detector::detect(matrix(), try_harder);
+                // the detector gives the corners as top left, bottom left, top right, bottom right
+                if let Some([tl, bl, tr, br]) = result
+                    .ok()
+                    .and_then(|result| <[Point; 4]>::try_from(result.getPoints()).ok())
+                {
+                    symbols.push(LocatedSymbol {
+                        format: BarcodeFormat::MAXICODE,
+                        corners: [tl, tr, br, bl],
+                        patterns: vec![DetectedPattern::new(
+                            PatternKind::Finder,
+                            (tl + tr + br + bl) / 4.0,
+                        )],
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let linear: Vec<BarcodeFormat> = formats
+        .iter()
+        .filter(|format| LOCATABLE_FORMATS[5..].contains(format))
+        .cloned()
+        .collect();
+    if !linear.is_empty() {
+        symbols.extend(locate_linear(image, &linear, hints)?);
+    }
+
+    Ok(symbols)
+}
+
+/// A symbol at the corners a detector found, with the patterns of its sampling grid
+fn located<D: DetectorRXingResult>(
+    format: BarcodeFormat,
+    result: &D,
+    kind: PatternKind,
+) -> Option<LocatedSymbol> {
+    let corners = <[Point; 4]>::try_from(result.getPoints()).ok()?;
+    let patterns = match result.getSamplingGrid() {
+        Some(grid) if !grid.patterns().is_empty() => grid.patterns().to_vec(),
+        _ => vec![DetectedPattern::new(
+            kind,
+            corners
+                .iter()
+                .fold(Point::default(), |sum, corner| sum + *corner)
+                / 4.0,
+        )],
+    };
+    Some(LocatedSymbol {
+        format,
+        corners,
+        patterns,
+    })
+}
+
+/**
+ * Locates QR codes by the triples of finder patterns the multi-symbol finder accepts, or the
+ * best triple of the single symbol finder if it accepts none.
+ */
+fn locate_qr_code(matrix: &BitMatrix, hints: &DecodeHints) -> Vec<LocatedSymbol> {
+    let infos = MultiFinderPatternFinder::new(matrix, None)
+        .findMulti(hints)
+        .ok()
+        .filter(|infos| !infos.is_empty())
+        .or_else(|| {
+            FinderPatternFinder::new(matrix)
+                .find(hints)
+                .ok()
+                .map(|info| vec![info])
+        })
+        .unwrap_or_default();
+
+    infos.iter().map(qr_code_corners).collect()
+}
+
+/// The corners of the QR code a triple of finder patterns belongs to
+fn qr_code_corners(info: &FinderPatternInfo) -> LocatedSymbol {
+    let (tl, tr, bl) = (info.getTopLeft(), info.getTopRight(), info.getBottomLeft());
+    let module =
+        (tl.getEstimatedModuleSize() + tr.getEstimatedModuleSize() + bl.getEstimatedModuleSize())
+            / 3.0;
+    let (tl, tr, bl) = (Point::from(tl), Point::from(tr), Point::from(bl));
+    // the finder patterns are centered 3.5 modules inside the edges of the symbol
+    let right = Point::normalized(tr - tl) * (3.5 * module);
+    let down = Point::normalized(bl - tl) * (3.5 * module);
+
+    LocatedSymbol {
+        format: BarcodeFormat::QR_CODE,
+        corners: [
+            tl - right - down,
+            tr + right - down,
+            tr + bl - tl + right + down,
+            bl - right + down,
+        ],
+        patterns: [tl, tr, bl]
+            .map(|center| DetectedPattern::new(PatternKind::Finder, center))
+            .to_vec(),
+    }
+}
+
+/**
+ * Locates PDF417 symbols by their start and stop patterns, also upside down ones. The
+ * symbols whose stop pattern was not found end at their start pattern.
+ */
+fn locate_pdf_417(matrix: &BitMatrix) -> Vec<LocatedSymbol> {
+    let (width, height) = (matrix.getWidth() as f32, matrix.getHeight() as f32);
+    let mut rotated = matrix.clone();
+    rotated.rotate180();
+
+    let mut symbols = Vec::new();
+    for (matrix, upside_down) in [(matrix, false), (&rotated, true)] {
+        let unrotate = |p: Point| {
+            if upside_down {
+                point(width - 1.0 - p.x, height - 1.0 - p.y)
+            } else {
+                p
+            }
+        };
+        for vertices in pdf_417_detector::detect(true, matrix).unwrap_or_default() {
+            // the outer corners of the start pattern, then of the stop pattern, then the inner
+            // ones of the start and the stop pattern
+            let (Some(tl), Some(bl)) = (vertices[0], vertices[1]) else {
+                continue;
+            };
+            let (tr, br) = match (vertices[2], vertices[3], vertices[4], vertices[5]) {
+                (Some(tr), Some(br), _, _) => (tr, br),
+                (_, _, Some(tr), Some(br)) => (tr, br),
+                _ => continue,
+            };
+            let mut patterns = vec![DetectedPattern::new(
+                PatternKind::Guard,
+                unrotate((tl + bl) / 2.0),
+            )];
+            if let (Some(tr), Some(br)) = (vertices[2], vertices[3]) {
+                patterns.push(DetectedPattern::new(
+                    PatternKind::Guard,
+                    unrotate((tr + br) / 2.0),
+                ));
+            }
+            symbols.push(LocatedSymbol {
+                format: BarcodeFormat::PDF_417,
+                corners: [tl, tr, br, bl].map(unrotate),
+                patterns,
+            });
+        }
+    }
+    symbols
+}
+
+type FindInRow<'a> = Box<dyn FnMut(&BitArray) -> Option<RowSymbol> + 'a>;
+
+/// A start pattern search of a linear reader, with the formats and widths of its symbols
+struct RowFinder<'a> {
+    formats: Vec<(BarcodeFormat, f32)>,
+    find: FindInRow<'a>,
+}
+
+impl RowFinder<'_> {
+    /**
+     * The format of a symbol `modules` wide, the one whose symbols are closest to it in width.
+     * Formats of symbols of a fixed width, above 0, only match symbols of about that width.
+     */
+    fn format(&self, modules: f32) -> Option<BarcodeFormat> {
+        self.formats
+            .iter()
+            .filter(|(_, width)| *width == 0.0 || (modules - width).abs() <= width / 10.0)
+            .min_by(|a, b| (a.1 - modules).abs().total_cmp(&(b.1 - modules).abs()))
+            .map(|(format, _)| format.clone())
+    }
+}
+
+/// The first symbol a linear reader finds the start pattern of in a row
+struct RowSymbol {
+    start: [usize; 2],
+    end: usize,
+    module: f32,
+    stopped: bool,
+}
+
+impl RowSymbol {
+    fn find<R: OneDReader>(reader: &mut R, row: &BitArray, hints: &DecodeHints) -> Option<Self> {
+        let (start, module) = reader.locate_start_pattern(row, hints)?;
+        let end = linear_symbol_end(row, start[1], module);
+        Some(Self {
+            start,
+            end,
+            module,
+            stopped: reader.has_stop_pattern(row, end, module),
+        })
+    }
+}
+
+/// A linear symbol found on one row, from `range[0]` up to `range[1]`
+struct RowHit {
+    format: BarcodeFormat,
+    y: usize,
+    range: [usize; 2],
+    start: Point,
+}
+
+/// The rows of a linear symbol, from the first to the last hit in it
+struct LinearRegion {
+    format: BarcodeFormat,
+    top: (usize, [usize; 2]),
+    bottom: (usize, [usize; 2]),
+    patterns: Vec<DetectedPattern>,
+}
+
+fn locate_linear<B: Binarizer>(
+    image: &BinaryBitmap<B>,
+    formats: &[BarcodeFormat],
+    hints: &DecodeHints,
+) -> Result<Vec<LocatedSymbol>> {
+    let (mut code_128, mut code_39, mut code_93) = (
+        Code128Reader,
+        Code39Reader::default(),
+        Code93Reader::default(),
+    );
+    let (mut itf, mut upc_ean) = (ITFReader::default(), MultiFormatUPCEANReader::new(hints));
+
+    let requested = |candidates: &[(BarcodeFormat, f32)]| {
+        candidates
+            .iter()
+            .filter(|(format, _)| formats.contains(format))
+            .cloned()
+            .collect::<Vec<_>>()
+    };
+    let mut finders = vec![
+        RowFinder {
+            formats: requested(&[(BarcodeFormat::CODE_128, 0.0)]),
+            find: Box::new(|row| RowSymbol::find(&mut code_128, row, hints)),
+        },
+        RowFinder {
+            formats: requested(&[(BarcodeFormat::CODE_39, 0.0)]),
+            find: Box::new(|row| RowSymbol::find(&mut code_39, row, hints)),
+        },
+        RowFinder {
+            formats: requested(&[(BarcodeFormat::CODE_93, 0.0)]),
+            find: Box::new(|row| RowSymbol::find(&mut code_93, row, hints)),
+        },
+        RowFinder {
+            formats: requested(&[(BarcodeFormat::ITF, 0.0)]),
+            find: Box::new(|row| RowSymbol::find(&mut itf, row, hints)),
+        },
+        RowFinder {
+            // the UPC/EAN formats share their start guard, and are told apart by their width
+            formats: requested(&[
+                (BarcodeFormat::EAN_13, 95.0),
+                (BarcodeFormat::UPC_A, 95.0),
+                (BarcodeFormat::EAN_8, 67.0),
+                (BarcodeFormat::UPC_E, 51.0),
+            ]),
+            find: Box::new(|row| RowSymbol::find(&mut upc_ean, row, hints)),
+        },
+    ];
+    finders.retain(|finder| !finder.formats.is_empty());
+
+    let (width, height) = (image.get_width(), image.get_height());
+    let row_step = usize::max(
+        1,
+        height
+            >> (if hints.TryHarder.unwrap_or(false) {
+                8
+            } else {
+                5
+            }),
+    );
+
+    let mut hits = Vec::new();
+    let mut rows = 0;
+    for y in (row_step / 2..height).step_by(row_step) {
+        hints.check_cancelled()?;
+        let Ok(row) = image.get_black_row(y) else {
+            continue;
+        };
+        rows += 1;
+
+        // upside down symbols have their start pattern at the right end of the row
+        for upside_down in [false, true] {
+            let mut row = row.clone().into_owned();
+            if upside_down {
+                row.reverse();
+            }
+            let unreverse = |[start, end]: [usize; 2]| {
+                if upside_down {
+                    [width - end, width - start]
+                } else {
+                    [start, end]
+                }
+            };
+
+            for finder in finders.iter_mut() {
+                let mut row = row.clone();
+                while let Some(symbol) = (finder.find)(&row) {
+                    let modules = (symbol.end - symbol.start[0]) as f32 / symbol.module;
+                    if let Some(format) = finder
+                        .format(modules)
+                        .filter(|_| symbol.stopped && modules >= MIN_LINEAR_MODULES)
+                    {
+                        let pattern = unreverse(symbol.start);
+                        hits.push(RowHit {
+                            format,
+                            y,
+                            range: unreverse([symbol.start[0], symbol.end]),
+                            start: point((pattern[0] + pattern[1]) as f32 / 2.0, y as f32),
+                        });
+                    }
+                    // clear what was searched, so the next search finds the next symbol in the row
+                    for x in 0..symbol.end.max(symbol.start[1]) {
+                        row.unset(x);
+                    }
+                }
+            }
+        }
+    }
+
+    // merge the hits that overlap those of a symbol on the rows above
+    let mut regions: Vec<LinearRegion> = Vec::new();
+    for hit in hits {
+        let overlaps = |[a, b]: [usize; 2]| hit.range[0] < b && a < hit.range[1];
+        let pattern = DetectedPattern::new(PatternKind::Guard, hit.start);
+        match regions.iter_mut().find(|region| {
+            region.format == hit.format
+                && hit.y - region.bottom.0 <= 2 * row_step
+                && overlaps(region.bottom.1)
+        }) {
+            Some(region) => {
+                let union = |[a, b]: [usize; 2]| [a.min(hit.range[0]), b.max(hit.range[1])];
+                if region.top.0 == hit.y {
+                    region.top.1 = union(region.top.1);
+                }
+                region.bottom = if region.bottom.0 == hit.y {
+                    (hit.y, union(region.bottom.1))
+                } else {
+                    (hit.y, hit.range)
+                };
+                region.patterns.push(pattern);
+            }
+            None => regions.push(LinearRegion {
+                format: hit.format,
+                top: (hit.y, hit.range),
+                bottom: (hit.y, hit.range),
+                patterns: vec![pattern],
+            }),
+        }
+    }
+
+    // a symbol is seen on two rows at least, unless the image is one row high
+    Ok(regions
+        .into_iter()
+        .filter(|region| region.bottom.0 > region.top.0 || rows < 2)
+        .map(|region| {
+            let (top, [tl, tr]) = region.top;
+            let (bottom, [bl, br]) = region.bottom;
+            LocatedSymbol {
+                format: region.format,
+                corners: [
+                    point(tl as f32, top as f32),
+                    point(tr as f32, top as f32),
+                    point(br as f32, bottom as f32),
+                    point(bl as f32, bottom as f32),
+                ],
+                patterns: region.patterns,
+            }
+        })
+        .collect())
+}
+
+/// The end of the last bar of a linear symbol whose bars continue at `from`
+fn linear_symbol_end(row: &BitArray, from: usize, module: f32) -> usize {
+    let quiet_zone = (LINEAR_QUIET_ZONE * module).ceil().max(1.0) as usize;
+    let size = row.get_size();
+    let mut end = from;
+    loop {
+        let bar = row.getNextSet(end);
+        if bar >= size || bar - end >= quiet_zone {
+            return end;
+        }
+        end = row.getNextUnset(bar);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::{
+        common::{BitMatrix, HybridBinarizer, PatternKind},
+        BarcodeFormat, BinaryBitmap, DecodeHints, Luma8LuminanceSource, MultiFormatWriter, Reader,
+        Writer,
+    };
+
+    use super::{locate, locate_format, LocatedSymbol};
+
+    /// Draws the symbols at their offsets onto a white image
+    fn draw(symbols: &[(&BitMatrix, u32, u32)], width: u32, height: u32) -> Vec<u8> {
+        let mut luma = vec![u8::MAX; (width * height) as usize];
+        for (matrix, left, top) in symbols {
+            for y in 0..matrix.getHeight() {
+                for x in 0..matrix.getWidth() {
+                    if matrix.get(x, y) {
+                        luma[((top + y) * width + left + x) as usize] = 0;
+                    }
+                }
+            }
+        }
+        luma
+    }
+
+    fn locate_in(
+        luma: Vec<u8>,
+        width: u32,
+        height: u32,
+        hints: &DecodeHints,
+    ) -> Vec<LocatedSymbol> {
+        let source = Luma8LuminanceSource::new(luma, width, height);
+        locate(&mut BinaryBitmap::new(HybridBinarizer::new(source)), hints).expect("locate")
+    }
+
+    fn contains(symbol: &LocatedSymbol, x: f32, y: f32) -> bool {
+        let (left, top, width, height) = symbol.bounds(0.0, 1000, 1000);
+        (left as f32..(left + width) as f32).contains(&x)
+            && (top as f32..(top + height) as f32).contains(&y)
+    }
+
+    #[test]
+    fn test_locate_formats() {
+        let qr = MultiFormatWriter
+            .encode("locate", &BarcodeFormat::QR_CODE, 100, 100)
+            .expect("encode");
+        let code_128 = MultiFormatWriter
+            .encode("LOCATE 128", &BarcodeFormat::CODE_128, 200, 60)
+            .expect("encode");
+        let luma = draw(&[(&qr, 20, 20), (&code_128, 150, 200)], 400, 300);
+        let symbols = locate_in(luma, 400, 300, &DecodeHints::default());
+
+        let qr_code = symbols
+            .iter()
+            .find(|symbol| symbol.format == BarcodeFormat::QR_CODE)
+            .expect("QR code");
+        assert_eq!(3, qr_code.patterns.len());
+        assert!(qr_code
+            .patterns
+            .iter()
+            .all(|pattern| pattern.kind == PatternKind::Finder));
+        // the symbol fills the middle of the 100x100 rendering
+        assert!(contains(qr_code, 70.0, 70.0));
+        let (left, top, width, height) = qr_code.bounds(0.0, 400, 300);
+        assert!(
+            (25..50).contains(&left) && (25..50).contains(&top),
+            "{left} {top}"
+        );
+        assert!((40..80).contains(&width) && (40..80).contains(&height));
+
+        let linear = symbols
+            .iter()
+            .find(|symbol| symbol.format == BarcodeFormat::CODE_128)
+            .expect("Code 128");
+        assert!(contains(linear, 250.0, 230.0));
+        assert!(!contains(linear, 70.0, 70.0));
+        assert!(linear.patterns.len() > 1);
+        assert!(linear
+            .patterns
+            .iter()
+            .all(|pattern| pattern.kind == PatternKind::Guard));
+
+        // no other format is found in the two symbols
+        assert!(symbols.iter().all(|symbol| matches!(
+            symbol.format,
+            BarcodeFormat::QR_CODE | BarcodeFormat::CODE_128
+        )));
+
+        let hints = DecodeHints {
+            PossibleFormats: Some(HashSet::from([BarcodeFormat::QR_CODE])),
+            ..Default::default()
+        };
+        let luma = draw(&[(&qr, 20, 20), (&code_128, 150, 200)], 400, 300);
+        let symbols = locate_in(luma, 400, 300, &hints);
+        assert_eq!(1, symbols.len());
+        assert_eq!(BarcodeFormat::QR_CODE, symbols[0].format);
+    }
+
+    #[test]
+    fn test_locate_undecodable() {
+        let mut matrix = MultiFormatWriter
+            .encode("0123456789012", &BarcodeFormat::EAN_13, 300, 80)
+            .expect("encode");
+        // flip a module of a digit on either side, so the symbol no longer decodes
+        for y in 0..matrix.getHeight() {
+            for x in [75, 76, 185, 186] {
+                matrix.flip_coords(x, y);
+            }
+        }
+        let luma = draw(&[(&matrix, 50, 50)], 400, 200);
+        let source = Luma8LuminanceSource::new(luma.clone(), 400, 200);
+        let mut image = BinaryBitmap::new(HybridBinarizer::new(source));
+        assert!(crate::MultiFormatReader::default()
+            .decode_with_hints(&mut image, &DecodeHints::default())
+            .is_err());
+
+        let symbols = locate_format(&mut image, BarcodeFormat::EAN_13, &DecodeHints::default())
+            .expect("locate");
+        assert_eq!(1, symbols.len());
+        assert_eq!(BarcodeFormat::EAN_13, symbols[0].format);
+        let [tl, tr, br, bl] = symbols[0].corners;
+        // the 95 modules of 2 pixels, after the quiet zone the writer added
+        assert!((100.0..110.0).contains(&tl.x) && (290.0..300.0).contains(&tr.x));
+        assert!(bl.y > tl.y && br.y > tr.y);
+    }
+}
@@ -47,7 +47,11 @@ impl LuminanceSource for Luma8LuminanceSource {
     }
 
     fn get_matrix(&self) -> Vec<u8> {
-        self.data.clone().into()
+        if self.inverted {
+            self.invert_block_of_bytes(self.data.to_vec())
+        } else {
+            self.data.to_vec()
+        }
     }
 
     fn get_width(&self) -> usize {
@@ -70,8 +74,9 @@ impl LuminanceSource for Luma8LuminanceSource {
                 .data
                 .chunks_exact(self.dimensions.0 as usize)
                 .skip(top)
+                .take(height)
                 .flat_map(|f| f.iter().skip(left).take(width))
-                .map(|byte| Self::invert_if_should(*byte, self.inverted))
+                .copied()
                 .collect(),
             // data: self
             //     .data
@@ -16,7 +16,7 @@
 
 use crate::common::BitMatrix;
 
-const BITNR: [[i16; 30]; 33] = [
+pub(crate) const BITNR: [[i16; 30]; 33] = [
     [
         121, 120, 127, 126, 133, 132, 139, 138, 145, 144, 151, 150, 157, 156, 163, 162, 169, 168,
         175, 174, 181, 180, 187, 186, 193, 192, 199, 198, -2, -2,
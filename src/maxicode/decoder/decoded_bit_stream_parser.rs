@@ -46,14 +46,14 @@ const PAD: &str = "\u{FFFC}";
 const FS: &str = "\u{001C}";
 const GS: &str = "\u{001D}";
 const RS: &str = "\u{001E}";
-const COUNTRY_BYTES: [u8; 10] = [53, 54, 43, 44, 45, 46, 47, 48, 37, 38];
-const SERVICE_CLASS_BYTES: [u8; 10] = [55, 56, 57, 58, 59, 60, 49, 50, 51, 52];
-const POSTCODE_2_LENGTH_BYTES: [u8; 6] = [39, 40, 41, 42, 31, 32];
-const POSTCODE_2_BYTES: [u8; 30] = [
+pub(crate) const COUNTRY_BYTES: [u8; 10] = [53, 54, 43, 44, 45, 46, 47, 48, 37, 38];
+pub(crate) const SERVICE_CLASS_BYTES: [u8; 10] = [55, 56, 57, 58, 59, 60, 49, 50, 51, 52];
+pub(crate) const POSTCODE_2_LENGTH_BYTES: [u8; 6] = [39, 40, 41, 42, 31, 32];
+pub(crate) const POSTCODE_2_BYTES: [u8; 30] = [
     33, 34, 35, 36, 25, 26, 27, 28, 29, 30, 19, 20, 21, 22, 23, 24, 13, 14, 15, 16, 17, 18, 7, 8,
     9, 10, 11, 12, 1, 2,
 ];
-const POSTCODE_3_BYTES: [[u8; 6]; 6] = [
+pub(crate) const POSTCODE_3_BYTES: [[u8; 6]; 6] = [
     [39, 40, 41, 42, 31, 32],
     [33, 34, 35, 36, 25, 26],
     [27, 28, 29, 30, 19, 20],
@@ -81,7 +81,7 @@ static SETS: Lazy<[String; 5]> = Lazy::new(|| {
    ]
 });
 
-static GRAPHEME_SETS: Lazy<[Vec<&str>; 5]> = Lazy::new(|| {
+pub(crate) static GRAPHEME_SETS: Lazy<[Vec<&str>; 5]> = Lazy::new(|| {
     [
         SETS[0].graphemes(true).collect(),
         SETS[1].graphemes(true).collect(),
@@ -127,11 +127,18 @@ pub fn decode(bytes: &[u8], mode: u8) -> Result<DecoderRXingResult> {
         }
         _ => {}
     }
-    Ok(DecoderRXingResult::new(
+    // AIM modifiers: 0 for modes 4 to 6, 1 for the structured carrier messages of modes 2 and 3,
+    // each 2 more when an ECI is present
+    let mut symbologyModifier = u32::from(matches!(mode, 2 | 3));
+    if result.contains(ECI) {
+        symbologyModifier += 2;
+    }
+    Ok(DecoderRXingResult::with_symbology(
         bytes.to_vec(),
         result,
         Vec::new(),
         mode.to_string(),
+        symbologyModifier,
     ))
 }
 
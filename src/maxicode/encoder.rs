@@ -0,0 +1,452 @@
+/*
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use crate::{
+    common::{
+        reedsolomon::{get_predefined_genericgf, PredefinedGenericGF, ReedSolomonEncoder},
+        BitMatrix, Result,
+    },
+    EncodeHints, Exceptions,
+};
+
+use super::{
+    decoder::{
+        decoded_bit_stream_parser::{
+            COUNTRY_BYTES, GRAPHEME_SETS, POSTCODE_2_BYTES, POSTCODE_2_LENGTH_BYTES,
+            POSTCODE_3_BYTES, SERVICE_CLASS_BYTES,
+        },
+        BITNR,
+    },
+    MaxiCodeReader,
+};
+
+/// The mode used without the `MaxiCodeMode` hint
+pub const DEFAULT_MODE: u8 = 4;
+
+const NUM_CODEWORDS: usize = 144;
+const PRIMARY_DATA: usize = 10;
+const PRIMARY_EC: usize = 10;
+const SECONDARY_START: usize = PRIMARY_DATA + PRIMARY_EC;
+
+const SET_A: usize = 0;
+const SET_B: usize = 1;
+
+// codewords with the same meaning in code sets A and B
+const NS: u8 = 31;
+const PAD: u8 = 33;
+const SHIFT_C: u8 = 60;
+const SHIFT_D: u8 = 61;
+const SHIFT_E: u8 = 62;
+// code set A
+const SHIFT_B: u8 = 59;
+const LATCH_B: u8 = 63;
+// code set B
+const TWO_SHIFT_A: u8 = 56;
+const THREE_SHIFT_A: u8 = 57;
+const SHIFT_A: u8 = 59;
+const LATCH_A: u8 = 63;
+
+const GROUP_SEPARATOR: char = '\u{1D}';
+// the structured carrier message follows this header and a year of 2 digits, if present
+const MESSAGE_HEADER: &str = "[)>\u{1E}01\u{1D}";
+
+/// A MaxiCode symbol, its modules in the rows of the reader: odd rows are shifted right by half
+/// a module and the bullseye is left out
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MaxiCode {
+    matrix: BitMatrix,
+    mode: u8,
+}
+
+impl MaxiCode {
+    pub fn getMatrix(&self) -> &BitMatrix {
+        &self.matrix
+    }
+
+    pub fn getMode(&self) -> u8 {
+        self.mode
+    }
+}
+
+/**
+ * Encodes `contents` in the mode of the `MaxiCodeMode` hint, 4 by default.
+ *
+ * In modes 2 and 3 the contents are a structured carrier message as the decoder returns it: the
+ * postcode, country code and class of service, each followed by GS, then the message. They may
+ * also follow the `[)>` RS `01` GS header and a year of 2 digits. The postcode of mode 2 is up to
+ * 9 digits, that of mode 3 up to 6 characters of code set A, padded with spaces.
+ *
+ * # Errors
+ * `IllegalArgumentException` if the mode is not 2 to 5, the structured carrier message is
+ * malformed or the contents have characters MaxiCode cannot encode or do not fit
+ */
+pub fn encode(contents: &str, hints: &EncodeHints) -> Result<MaxiCode> {
+    let mode = hints.MaxiCodeMode.unwrap_or(DEFAULT_MODE);
+    let num_secondary = if mode == 5 { 68 } else { 84 };
+
+    let mut codewords = [0; NUM_CODEWORDS];
+    match mode {
+        2 | 3 => {
+            let (fields, message) = structured_carrier_message(contents)?;
+            encode_primary(&mut codewords, mode, fields)?;
+            let message = encode_text(&message, num_secondary)?;
+            codewords[SECONDARY_START..SECONDARY_START + num_secondary].copy_from_slice(&message);
+        }
+        4 | 5 => {
+            // the message starts in the primary codewords after the mode
+            let message = encode_text(contents, PRIMARY_DATA - 1 + num_secondary)?;
+            let (primary, secondary) = message.split_at(PRIMARY_DATA - 1);
+            codewords[0] = mode;
+            codewords[1..PRIMARY_DATA].copy_from_slice(primary);
+            codewords[SECONDARY_START..SECONDARY_START + num_secondary].copy_from_slice(secondary);
+        }
+        _ => {
+            return Err(Exceptions::illegal_argument_with(format!(
+                "MaxiCode mode {mode} is not supported, only modes 2 to 5"
+            )))
+        }
+    }
+    add_ec_codewords(&mut codewords)?;
+
+    Ok(MaxiCode {
+        matrix: place(&codewords)?,
+        mode,
+    })
+}
+
+/// Splits the postcode, country code and class of service off the message, after the header
+/// and year if there are
+fn structured_carrier_message(contents: &str) -> Result<([&str; 3], String)> {
+    let (header, rest) = match contents.strip_prefix(MESSAGE_HEADER) {
+        Some(rest) if rest.is_char_boundary(2) => contents.split_at(MESSAGE_HEADER.len() + 2),
+        _ => ("", contents),
+    };
+    let mut fields = rest.splitn(4, GROUP_SEPARATOR);
+    let (Some(postcode), Some(country), Some(service)) =
+        (fields.next(), fields.next(), fields.next())
+    else {
+        return Err(Exceptions::illegal_argument_with(
+            "a structured carrier message starts with postcode, country code and class of service, separated by GS",
+        ));
+    };
+    let message = fields.next().unwrap_or_default();
+    Ok(([postcode, country, service], format!("{header}{message}")))
+}
+
+fn encode_primary(
+    codewords: &mut [u8],
+    mode: u8,
+    [postcode, country, service]: [&str; 3],
+) -> Result<()> {
+    let number = |field: &str, name: &str, max_digits: usize| {
+        if field.is_empty()
+            || field.len() > max_digits
+            || !field.bytes().all(|b| b.is_ascii_digit())
+        {
+            return Err(Exceptions::illegal_argument_with(format!(
+                "{name} {field:?} is not a number of up to {max_digits} digits"
+            )));
+        }
+        field
+            .parse::<u32>()
+            .map_err(|e| Exceptions::parse_with(format!("could not parse {field}: {e}")))
+    };
+
+    if mode == 2 {
+        set_bits(
+            codewords,
+            number(postcode, "postcode", 9)?,
+            &POSTCODE_2_BYTES,
+        );
+        set_bits(codewords, postcode.len() as u32, &POSTCODE_2_LENGTH_BYTES);
+    } else {
+        if postcode.chars().count() > POSTCODE_3_BYTES.len() {
+            return Err(Exceptions::illegal_argument_with(format!(
+                "postcode {postcode:?} is longer than 6 characters"
+            )));
+        }
+        let padded = postcode.chars().chain(std::iter::repeat(' '));
+        for (positions, c) in POSTCODE_3_BYTES.iter().zip(padded) {
+            let value = codeword_in(SET_A, c).ok_or_else(|| {
+                Exceptions::illegal_argument_with(format!("{c:?} is not allowed in a postcode"))
+            })?;
+            set_bits(codewords, value as u32, positions);
+        }
+    }
+    set_bits(
+        codewords,
+        number(country, "country code", 3)?,
+        &COUNTRY_BYTES,
+    );
+    set_bits(
+        codewords,
+        number(service, "class of service", 3)?,
+        &SERVICE_CLASS_BYTES,
+    );
+    codewords[0] |= mode;
+    Ok(())
+}
+
+/// Sets the bits of `value` at the bit positions, counted from 1, most significant first
+fn set_bits(codewords: &mut [u8], value: u32, positions: &[u8]) {
+    for (i, position) in positions.iter().enumerate() {
+        if value >> (positions.len() - 1 - i) & 1 == 1 {
+            let bit = *position as usize - 1;
+            codewords[bit / 6] |= 1 << (5 - bit % 6);
+        }
+    }
+}
+
+/// The codeword of `c` in a code set, if it has one
+fn codeword_in(set: usize, c: char) -> Option<u8> {
+    // the decoder stands these in for shifts, latches and the other function codewords
+    if ('\u{FFF0}'..='\u{FFFC}').contains(&c) {
+        return None;
+    }
+    let mut buffer = [0; 4];
+    let c: &str = c.encode_utf8(&mut buffer);
+    GRAPHEME_SETS[set]
+        .iter()
+        .position(|g| *g == c)
+        .map(|codeword| codeword as u8)
+}
+
+/// The number of characters at the start of `chars` in a code set
+fn run_in(set: usize, chars: &[char]) -> usize {
+    chars
+        .iter()
+        .take_while(|c| codeword_in(set, **c).is_some())
+        .count()
+}
+
+/**
+ * Encodes text in code sets A and B, shifting to C, D and E for single characters, and runs of
+ * 9 digits in 6 codewords. The codewords are padded to `capacity`.
+ */
+fn encode_text(text: &str, capacity: usize) -> Result<Vec<u8>> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut codewords = Vec::with_capacity(capacity);
+    let mut set = SET_A;
+
+    let mut i = 0;
+    while i < chars.len() {
+        if let Some(digits) = chars
+            .get(i..i + 9)
+            .filter(|digits| digits.iter().all(char::is_ascii_digit))
+        {
+            let value = digits
+                .iter()
+                .fold(0, |value, d| value * 10 + (*d as u32 - '0' as u32));
+            codewords.push(NS);
+            codewords.extend((0..5).rev().map(|k| (value >> (6 * k) & 0x3F) as u8));
+            i += 9;
+            continue;
+        }
+
+        let c = chars[i];
+        if let Some(codeword) = codeword_in(set, c) {
+            codewords.push(codeword);
+            i += 1;
+        } else if set == SET_A && codeword_in(SET_B, c).is_some() {
+            if run_in(SET_B, &chars[i..]) > 1 {
+                codewords.push(LATCH_B);
+                set = SET_B;
+            } else {
+                codewords.push(SHIFT_B);
+                codewords.extend(codeword_in(SET_B, c));
+                i += 1;
+            }
+        } else if set == SET_B && codeword_in(SET_A, c).is_some() {
+            let run = run_in(SET_A, &chars[i..]);
+            let shift = match run {
+                1 => SHIFT_A,
+                2 => TWO_SHIFT_A,
+                3 => THREE_SHIFT_A,
+                _ => {
+                    codewords.push(LATCH_A);
+                    set = SET_A;
+                    continue;
+                }
+            };
+            codewords.push(shift);
+            codewords.extend(
+                chars[i..i + run]
+                    .iter()
+                    .filter_map(|c| codeword_in(SET_A, *c)),
+            );
+            i += run;
+        } else {
+            let (shift, codeword) = [(2, SHIFT_C), (3, SHIFT_D), (4, SHIFT_E)]
+                .into_iter()
+                .find_map(|(other, shift)| codeword_in(other, c).map(|cw| (shift, cw)))
+                .ok_or_else(|| {
+                    Exceptions::illegal_argument_with(format!(
+                        "{c:?} cannot be encoded in MaxiCode"
+                    ))
+                })?;
+            codewords.push(shift);
+            codewords.push(codeword);
+            i += 1;
+        }
+    }
+
+    if codewords.len() > capacity {
+        return Err(Exceptions::illegal_argument_with(format!(
+            "the contents need {} codewords, more than the {capacity} of the mode",
+            codewords.len()
+        )));
+    }
+    codewords.resize(capacity, PAD);
+    Ok(codewords)
+}
+
+/// Adds the check codewords of the primary message and of the even and the odd codewords of the
+/// secondary message, the data of which fills the codewords up to the check codewords
+fn add_ec_codewords(codewords: &mut [u8; NUM_CODEWORDS]) -> Result<()> {
+    let mut encoder = ReedSolomonEncoder::new(get_predefined_genericgf(
+        PredefinedGenericGF::MaxicodeField64,
+    ))?;
+    let mode = codewords[0] & 0x0F;
+    let num_secondary_ec = if mode == 5 { 56 } else { 40 };
+
+    let mut encode = |positions: Vec<usize>, num_ec: usize| -> Result<()> {
+        let mut block: Vec<i32> = positions.iter().map(|i| codewords[*i] as i32).collect();
+        encoder.encode(&mut block, num_ec)?;
+        for (i, codeword) in positions.into_iter().zip(block) {
+            codewords[i] = codeword as u8;
+        }
+        Ok(())
+    };
+    encode((0..SECONDARY_START).collect(), PRIMARY_EC)?;
+    for parity in 0..2 {
+        encode(
+            (SECONDARY_START + parity..NUM_CODEWORDS)
+                .step_by(2)
+                .collect(),
+            num_secondary_ec / 2,
+        )?;
+    }
+    Ok(())
+}
+
+fn place(codewords: &[u8]) -> Result<BitMatrix> {
+    let mut matrix = BitMatrix::new(MaxiCodeReader::MATRIX_WIDTH, MaxiCodeReader::MATRIX_HEIGHT)?;
+    for (y, row) in BITNR.iter().enumerate() {
+        for (x, bit) in row.iter().enumerate() {
+            // -2 marks the dark orientation modules, -1 the light ones and -3 no module
+            let dark = match *bit {
+                -2 => true,
+                bit if bit >= 0 => codewords[bit as usize / 6] >> (5 - bit % 6) & 1 == 1,
+                _ => false,
+            };
+            if dark {
+                matrix.set(x as u32, y as u32);
+            }
+        }
+    }
+    Ok(matrix)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{maxicode::decoder::maxicode_decoder, EncodeHintValue, EncodeHints};
+
+    use super::{encode, encode_text, PAD};
+
+    fn round_trip(contents: &str, mode: u8) -> String {
+        let hints = EncodeHints::default().with(EncodeHintValue::MaxiCodeMode(mode));
+        let code = encode(contents, &hints).expect("encode");
+        assert_eq!(mode, code.getMode());
+        let result = maxicode_decoder::decode(code.getMatrix()).expect("decode");
+        assert_eq!(mode.to_string(), result.getECLevel());
+        result.getText().to_owned()
+    }
+
+    #[test]
+    fn test_encode_text() {
+        let pad = |mut codewords: Vec<u8>, capacity: usize| {
+            codewords.resize(capacity, PAD);
+            codewords
+        };
+        assert_eq!(
+            pad(vec![1, 2, 48, 32], 10),
+            encode_text("AB0 ", 10).unwrap()
+        );
+        // a single lower case letter is shifted, more are latched
+        assert_eq!(
+            pad(vec![1, 59, 2, 3, 59, 4], 10),
+            encode_text("AbCd", 10).unwrap()
+        );
+        assert_eq!(
+            pad(vec![1, 63, 2, 3, 4], 10),
+            encode_text("Abcd", 10).unwrap()
+        );
+        assert_eq!(
+            pad(vec![63, 1, 2, 56, 49, 50], 10),
+            encode_text("ab12", 10).unwrap()
+        );
+        // 9 digits in 6 codewords
+        assert_eq!(
+            pad(vec![31, 7, 22, 60, 52, 21], 10),
+            encode_text("123456789", 10).unwrap()
+        );
+        assert_eq!(pad(vec![60, 9], 10), encode_text("\u{c9}", 10).unwrap());
+        assert_eq!(pad(vec![61, 9], 10), encode_text("\u{e9}", 10).unwrap());
+        assert!(encode_text("\u{4e2d}", 10).is_err());
+        assert!(encode_text("ABCDEFGHIJK", 10).is_err());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for contents in [
+            "MaxiCode",
+            "Standard error correction, 0123456789 and more digits: 9876543210",
+            "mixed CASE text with \u{e9}\u{e8}\u{c0} and \u{1}\t control characters",
+        ] {
+            assert_eq!(contents, round_trip(contents, 4));
+            assert_eq!(contents, round_trip(contents, 5));
+        }
+        let long: String = (0..138)
+            .map(|i| char::from(b'0' + (i % 10) as u8))
+            .collect();
+        assert_eq!(long, round_trip(&long, 4));
+    }
+
+    #[test]
+    fn test_structured_carrier_message() {
+        assert_eq!(
+            "152382802\u{1d}840\u{1d}001\u{1d}1Z00004951\u{1d}UPSN",
+            round_trip("152382802\u{1d}840\u{1d}001\u{1d}1Z00004951\u{1d}UPSN", 2)
+        );
+        assert_eq!(
+            "[)>\u{1e}01\u{1d}96123456789\u{1d}840\u{1d}001\u{1d}message",
+            round_trip(
+                "[)>\u{1e}01\u{1d}96123456789\u{1d}840\u{1d}001\u{1d}message",
+                2
+            )
+        );
+        // the postcode is padded with spaces
+        assert_eq!(
+            "B1050 \u{1d}056\u{1d}999\u{1d}Bruxelles",
+            round_trip("B1050\u{1d}056\u{1d}999\u{1d}Bruxelles", 3)
+        );
+
+        let mode = |mode| EncodeHints::default().with(EncodeHintValue::MaxiCodeMode(mode));
+        assert!(encode("B1050\u{1d}056\u{1d}999\u{1d}", &mode(2)).is_err());
+        assert!(encode("1234567890\u{1d}056\u{1d}999\u{1d}", &mode(2)).is_err());
+        assert!(encode("12345\u{1d}1056\u{1d}999\u{1d}", &mode(2)).is_err());
+        assert!(encode("no postcode", &mode(3)).is_err());
+        assert!(encode("reader programming", &mode(6)).is_err());
+    }
+}
@@ -16,8 +16,8 @@
 
 use crate::{
     common::{BitMatrix, DetectorRXingResult, Result},
-    BarcodeFormat, Binarizer, DecodeHints, Exceptions, ImmutableReader, RXingResult,
-    RXingResultMetadataType, Reader,
+    record_witness, BarcodeFormat, Binarizer, DecodeHints, Exceptions, ImmutableReader,
+    RXingResult, RXingResultMetadataType, Reader, WitnessRecording,
 };
 
 use super::{decoder::maxicode_decoder, detector};
@@ -59,7 +59,9 @@ impl Reader for MaxiCodeReader {
         image: &mut crate::BinaryBitmap<B>,
         hints: &DecodeHints,
     ) -> Result<crate::RXingResult> {
-        self.internal_decode_with_hints(image, hints)
+        let mut recording = WitnessRecording::new(hints);
+        self.internal_decode_with_hints(image, hints, &mut recording)
+            .map(|result| record_witness(hints, image, result, &recording))
     }
 }
 
@@ -69,7 +71,9 @@ impl ImmutableReader for MaxiCodeReader {
         image: &mut crate::BinaryBitmap<B>,
         hints: &DecodeHints,
     ) -> Result<RXingResult> {
-        self.internal_decode_with_hints(image, hints)
+        let mut recording = WitnessRecording::new(hints);
+        self.internal_decode_with_hints(image, hints, &mut recording)
+            .map(|result| record_witness(hints, image, result, &recording))
     }
 }
 
@@ -120,16 +124,19 @@ impl MaxiCodeReader {
         &self,
         image: &mut crate::BinaryBitmap<B>,
         hints: &DecodeHints,
+        recording: &mut WitnessRecording,
     ) -> Result<RXingResult> {
         // Note that MaxiCode reader effectively always assumes PURE_BARCODE mode
         // and can't detect it in an image
         let try_harder = hints.TryHarder.unwrap_or(false);
+        recording.binarization(image);
 
         let mut rotation = None;
 
         let decoderRXingResult = if try_harder {
             let result = detector::detect(image.get_black_matrix_mut(), try_harder)?;
             rotation = Some(result.rotation());
+            recording.detection(BarcodeFormat::MAXICODE, result.getPoints(), None);
             let parsed_result = detector::read_bits(result.getBits())?;
             maxicode_decoder::decode_with_hints(&parsed_result, hints)?
         } else {
@@ -137,6 +144,8 @@ impl MaxiCodeReader {
             maxicode_decoder::decode_with_hints(&bits, hints)?
         };
 
+        recording.codewords(decoderRXingResult.getCodewords());
+
         // let bits = Self::extractPureBits(image.getBlackMatrix())?;
         // let decoderRXingResult = maxicode_decoder::decode_with_hints(bits, hints)?;
         let mut result = RXingResult::new(
@@ -163,6 +172,15 @@ impl MaxiCodeReader {
             }
         }
 
+        result.putMetadata(
+            RXingResultMetadataType::SYMBOLOGY_IDENTIFIER,
+            crate::RXingResultMetadataValue::SymbologyIdentifier(format!(
+                "]U{}",
+                decoderRXingResult.getSymbologyModifier()
+            )),
+        );
+
+        recording.decoded_bits(&mut result);
         Ok(result)
     }
 }
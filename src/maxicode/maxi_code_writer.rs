@@ -0,0 +1,323 @@
+/*
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use crate::{
+    common::{BitMatrix, Result},
+    exceptions::Exceptions,
+    BarcodeFormat, EncodeHints, Writer,
+};
+
+use super::encoder;
+
+// the quiet zone is at least 1 module wide
+const MARGINS_SIZE: u32 = 1;
+// the least distance of neighbouring modules in pixels, for hexagons of a recognizable shape
+const MODULE_PIXELS: f32 = 6.0;
+
+// for a distance of 1 between neighbouring modules, the distance of the rows and the distance of
+// the centre of a hexagon to its top and bottom corners
+const ROW_DISTANCE: f32 = 0.866_025_4;
+const HEXAGON_RADIUS: f32 = 0.577_350_3;
+const SYMBOL_WIDTH: f32 = 30.0;
+const SYMBOL_HEIGHT: f32 = 32.0 * ROW_DISTANCE + 2.0 * HEXAGON_RADIUS;
+
+// the bullseye fills the gap in the modules around row 16. The inner and outer radii of its dark
+// rings are those of ISO/IEC 16023 over the module distance of 0.88 mm
+const BULLSEYE_CENTRE: (f32, f32) = (14.5, 16.0 * ROW_DISTANCE + HEXAGON_RADIUS);
+const BULLSEYE_RINGS: [(f32, f32); 3] = [(0.580, 1.341), (2.114, 2.875), (3.636, 4.398)];
+
+/**
+ * Renders a MaxiCode symbol as a {@link BitMatrix} of hexagonal modules around the bullseye, at
+ * least 6 pixels per module. The mode is chosen with the `MaxiCodeMode` hint.
+ */
+#[derive(Default)]
+pub struct MaxiCodeWriter;
+
+impl Writer for MaxiCodeWriter {
+    fn encode(
+        &self,
+        contents: &str,
+        format: &crate::BarcodeFormat,
+        width: i32,
+        height: i32,
+    ) -> Result<crate::common::BitMatrix> {
+        self.encode_with_hints(contents, format, width, height, &EncodeHints::default())
+    }
+
+    fn encode_with_hints(
+        &self,
+        contents: &str,
+        format: &crate::BarcodeFormat,
+        width: i32,
+        height: i32,
+        hints: &EncodeHints,
+    ) -> Result<crate::common::BitMatrix> {
+        if format != &BarcodeFormat::MAXICODE {
+            return Err(Exceptions::illegal_argument_with(format!(
+                "can only encode MAXICODE, but got {format:?}"
+            )));
+        }
+
+        let code = encoder::encode(contents, hints)?;
+        render(
+            code.getMatrix(),
+            width.max(0) as u32,
+            height.max(0) as u32,
+            margins(hints)?,
+        )
+    }
+}
+
+#[cfg(feature = "svg_write")]
+impl MaxiCodeWriter {
+    /// Renders a MaxiCode symbol as SVG, measured in the distance of neighbouring modules
+    pub fn encode_svg(&self, contents: &str, hints: &EncodeHints) -> Result<svg::Document> {
+        use svg::node::element::{Circle, Polygon};
+
+        const HEXAGON_CORNERS: [(f32, f32); 6] = [
+            (0.0, -HEXAGON_RADIUS),
+            (0.5, -HEXAGON_RADIUS / 2.0),
+            (0.5, HEXAGON_RADIUS / 2.0),
+            (0.0, HEXAGON_RADIUS),
+            (-0.5, HEXAGON_RADIUS / 2.0),
+            (-0.5, -HEXAGON_RADIUS / 2.0),
+        ];
+
+        let code = encoder::encode(contents, hints)?;
+        let symbol = code.getMatrix();
+        let margins = margins(hints)? as f32;
+        let mut document = svg::Document::new().set(
+            "viewBox",
+            (
+                -margins,
+                -margins,
+                SYMBOL_WIDTH + 2.0 * margins,
+                SYMBOL_HEIGHT + 2.0 * margins,
+            ),
+        );
+        for y in 0..symbol.getHeight() {
+            for x in 0..symbol.getWidth() {
+                if symbol.get(x, y) {
+                    let (cx, cy) = hexagon_centre(x, y);
+                    let points: Vec<String> = HEXAGON_CORNERS
+                        .iter()
+                        .map(|(dx, dy)| format!("{},{}", cx + dx, cy + dy))
+                        .collect();
+                    document = document.add(Polygon::new().set("points", points.join(" ")));
+                }
+            }
+        }
+        let (cx, cy) = BULLSEYE_CENTRE;
+        for (inner, outer) in BULLSEYE_RINGS {
+            let ring = Circle::new()
+                .set("cx", cx)
+                .set("cy", cy)
+                .set("r", (inner + outer) / 2.0)
+                .set("fill", "none")
+                .set("stroke", "black")
+                .set("stroke-width", outer - inner);
+            document = document.add(ring);
+        }
+        Ok(document)
+    }
+}
+
+fn margins(hints: &EncodeHints) -> Result<u32> {
+    if let Some(margin) = &hints.Margin {
+        margin
+            .parse::<u32>()
+            .map_err(|e| Exceptions::parse_with(format!("could not parse {margin}: {e}")))
+    } else {
+        Ok(MARGINS_SIZE)
+    }
+}
+
+/// The centre of the hexagon of a module, odd rows being shifted right by half a module
+fn hexagon_centre(x: u32, y: u32) -> (f32, f32) {
+    (
+        x as f32 + 0.5 + (y % 2) as f32 / 2.0,
+        y as f32 * ROW_DISTANCE + HEXAGON_RADIUS,
+    )
+}
+
+/// Whether a point relative to the centre of a hexagon is inside it: between its left and right
+/// sides, and its four sides at 60 degrees to them
+fn in_hexagon(dx: f32, dy: f32) -> bool {
+    dx.abs() <= 0.5 && dx.abs() / 2.0 + dy.abs() * ROW_DISTANCE <= 0.5
+}
+
+fn in_bullseye(dx: f32, dy: f32) -> bool {
+    let radius = dx.hypot(dy);
+    BULLSEYE_RINGS
+        .iter()
+        .any(|(inner, outer)| (*inner..=*outer).contains(&radius))
+}
+
+fn render(symbol: &BitMatrix, width: u32, height: u32, margins: u32) -> Result<BitMatrix> {
+    let padded_width = SYMBOL_WIDTH + 2.0 * margins as f32;
+    let padded_height = SYMBOL_HEIGHT + 2.0 * margins as f32;
+    let scale = (width as f32 / padded_width)
+        .min(height as f32 / padded_height)
+        .max(MODULE_PIXELS);
+    let output_width = width.max((padded_width * scale).ceil() as u32);
+    let output_height = height.max((padded_height * scale).ceil() as u32);
+    let origin = (
+        (output_width as f32 - SYMBOL_WIDTH * scale) / 2.0,
+        (output_height as f32 - SYMBOL_HEIGHT * scale) / 2.0,
+    );
+
+    let mut output = BitMatrix::new(output_width, output_height)?;
+    for y in 0..symbol.getHeight() {
+        for x in 0..symbol.getWidth() {
+            if symbol.get(x, y) {
+                let extent = (0.5, HEXAGON_RADIUS);
+                fill(
+                    &mut output,
+                    origin,
+                    scale,
+                    hexagon_centre(x, y),
+                    extent,
+                    in_hexagon,
+                );
+            }
+        }
+    }
+    let extent = BULLSEYE_RINGS[2].1;
+    fill(
+        &mut output,
+        origin,
+        scale,
+        BULLSEYE_CENTRE,
+        (extent, extent),
+        in_bullseye,
+    );
+    Ok(output)
+}
+
+/// Sets the pixels within `extent` of `centre`, a point of the symbol, with their centre inside
+/// a shape around it
+fn fill(
+    output: &mut BitMatrix,
+    origin: (f32, f32),
+    scale: f32,
+    centre: (f32, f32),
+    extent: (f32, f32),
+    inside: impl Fn(f32, f32) -> bool,
+) {
+    let pixels = |origin: f32, centre: f32, extent: f32, size: u32| {
+        let start = (origin + (centre - extent) * scale).floor().max(0.0) as u32;
+        let end = ((origin + (centre + extent) * scale).ceil() as u32).min(size);
+        start..end
+    };
+    for py in pixels(origin.1, centre.1, extent.1, output.getHeight()) {
+        let dy = (py as f32 + 0.5 - origin.1) / scale - centre.1;
+        for px in pixels(origin.0, centre.0, extent.0, output.getWidth()) {
+            let dx = (px as f32 + 0.5 - origin.0) / scale - centre.0;
+            if inside(dx, dy) {
+                output.set(px, py);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        common::BitMatrix, test_fixtures::luma_bitmap, BarcodeFormat, DecodeHintValue, DecodeHints,
+        EncodeHintValue, EncodeHints, RXingResultMetadataType, RXingResultMetadataValue, Reader,
+        Writer,
+    };
+
+    use super::{super::MaxiCodeReader, MaxiCodeWriter};
+
+    fn read(matrix: &BitMatrix, hints: &DecodeHints) -> crate::common::Result<crate::RXingResult> {
+        MaxiCodeReader::default().decode_with_hints(&mut luma_bitmap(matrix), hints)
+    }
+
+    #[test]
+    fn test_render() {
+        let matrix = MaxiCodeWriter
+            .encode("MaxiCode 123", &BarcodeFormat::MAXICODE, 0, 0)
+            .expect("encode");
+        // 30 modules and the quiet zone of 1 module, 6 pixels each
+        assert_eq!(192, matrix.getWidth());
+        assert_eq!(186, matrix.getHeight());
+        // the top right orientation module, a hexagon with flat sides left and right
+        let (left, top) = (6 + 28 * 6, 6);
+        assert!(matrix.get(left + 3, top + 3));
+        assert!(matrix.get(left, top + 3));
+        assert!(!matrix.get(left, top));
+        // the light centre of the bullseye and its dark inner ring
+        let (cx, cy) = (6 + 87, 6 + 86);
+        assert!(!matrix.get(cx, cy));
+        assert!(matrix.get(cx + 5, cy));
+
+        let result = read(&matrix, &DecodeHints::default()).expect("decode");
+        assert_eq!("MaxiCode 123", result.getText());
+        assert_eq!(BarcodeFormat::MAXICODE, *result.getBarcodeFormat());
+        assert_eq!(
+            Some(&RXingResultMetadataValue::SymbologyIdentifier(
+                "]U0".to_owned()
+            )),
+            result
+                .getRXingResultMetadata()
+                .get(&RXingResultMetadataType::SYMBOLOGY_IDENTIFIER)
+        );
+        // the detector finds the symbol by its bullseye
+        let try_harder = DecodeHints::default().with(DecodeHintValue::TryHarder(true));
+        assert_eq!(
+            "MaxiCode 123",
+            read(&matrix, &try_harder).expect("detect").getText()
+        );
+
+        let hints = EncodeHints::default().with(EncodeHintValue::MaxiCodeMode(2));
+        let matrix = MaxiCodeWriter
+            .encode_with_hints(
+                "152382802\u{1d}840\u{1d}001\u{1d}parcel",
+                &BarcodeFormat::MAXICODE,
+                400,
+                300,
+                &hints,
+            )
+            .expect("encode");
+        assert_eq!((400, 300), (matrix.getWidth(), matrix.getHeight()));
+        let result = read(&matrix, &DecodeHints::default()).expect("decode");
+        assert_eq!("152382802\u{1d}840\u{1d}001\u{1d}parcel", result.getText());
+        // structured carrier messages have their own modifier
+        assert_eq!(
+            Some(&RXingResultMetadataValue::SymbologyIdentifier(
+                "]U1".to_owned()
+            )),
+            result
+                .getRXingResultMetadata()
+                .get(&RXingResultMetadataType::SYMBOLOGY_IDENTIFIER)
+        );
+
+        assert!(MaxiCodeWriter
+            .encode("MaxiCode", &BarcodeFormat::QR_CODE, 0, 0)
+            .is_err());
+    }
+
+    #[cfg(feature = "svg_write")]
+    #[test]
+    fn test_encode_svg() {
+        let document = MaxiCodeWriter
+            .encode_svg("MaxiCode", &EncodeHints::default())
+            .expect("encode")
+            .to_string();
+        assert!(document.contains("viewBox=\"-1 -1 32 30.867"));
+        assert_eq!(3, document.matches("<circle").count());
+        assert!(document.contains("<polygon points=\"28.5,0 29,0.28867"));
+    }
+}
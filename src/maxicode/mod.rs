@@ -1,5 +1,8 @@
 pub mod decoder;
 pub mod detector;
+pub mod encoder;
 mod maxi_code_reader;
+mod maxi_code_writer;
 
 pub use maxi_code_reader::*;
+pub use maxi_code_writer::*;
@@ -0,0 +1,551 @@
+//! UniFFI bindings for Kotlin and Swift, enabled by the `uniffi` feature.
+//!
+//! The `rxing-uniffi` crate (in `crates/uniffi`) builds these into a shared library and
+//! generates the Kotlin and Swift sources from it. The interface is deliberately small:
+//! a [`Scanner`] configured once with [`ScannerOptions`] and reused for every camera frame,
+//! and an [`Encoder`] configured with [`EncoderOptions`].
+//!
+//! ```kotlin
+//! val scanner = Scanner(ScannerOptions(formats = listOf(BarcodeFormat.QR_CODE)))
+//! // Y plane of an ImageProxy in YUV_420_888
+//! val barcode = scanner.scanLuma(yBytes, image.width.toUInt(), image.height.toUInt(), yPlane.rowStride.toUInt())
+//! ```
+
+use std::sync::Arc;
+
+use crate::{
+    common::{simd, BitMatrix},
+    helpers, DecodeHints, EncodeHints, Exceptions, RXingResult, Writer,
+};
+
+macro_rules! barcode_formats {
+    ($($name:ident => $format:ident),* $(,)?) => {
+        /// The barcode formats supported by the bindings
+        #[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, uniffi::Enum)]
+        pub enum BarcodeFormat {
+            $($name,)*
+        }
+
+        impl From<BarcodeFormat> for crate::BarcodeFormat {
+            fn from(value: BarcodeFormat) -> Self {
+                match value {
+                    $(BarcodeFormat::$name => crate::BarcodeFormat::$format,)*
+                }
+            }
+        }
+
+        impl TryFrom<crate::BarcodeFormat> for BarcodeFormat {
+            type Error = RxingError;
+
+            fn try_from(value: crate::BarcodeFormat) -> Result<Self, Self::Error> {
+                match value {
+                    $(crate::BarcodeFormat::$format => Ok(BarcodeFormat::$name),)*
+                    _ => Err(RxingError::Unsupported {
+                        message: format!("format {value} is not supported by the bindings"),
+                    }),
+                }
+            }
+        }
+    };
+}
+
+barcode_formats! {
+    Aztec => AZTEC,
+    Codabar => CODABAR,
+    Code39 => CODE_39,
+    Code93 => CODE_93,
+    Code128 => CODE_128,
+    DataMatrix => DATA_MATRIX,
+    Ean8 => EAN_8,
+    Ean13 => EAN_13,
+    Itf => ITF,
+    Maxicode => MAXICODE,
+    Pdf417 => PDF_417,
+    QrCode => QR_CODE,
+    MicroQrCode => MICRO_QR_CODE,
+    RectangularMicroQrCode => RECTANGULAR_MICRO_QR_CODE,
+    Rss14 => RSS_14,
+    RssExpanded => RSS_EXPANDED,
+    Telepen => TELEPEN,
+    UpcA => UPC_A,
+    UpcE => UPC_E,
+    UpcEanExtension => UPC_EAN_EXTENSION,
+    DxFilmEdge => DXFilmEdge,
+    Pharmacode => PHARMACODE,
+    Msi => MSI,
+}
+
+/// Errors surfaced to Kotlin and Swift as exceptions
+#[derive(Debug, PartialEq, Eq, thiserror::Error, uniffi::Error)]
+pub enum RxingError {
+    #[error("no barcode found")]
+    NotFound,
+    #[error("invalid argument: {message}")]
+    InvalidArgument { message: String },
+    #[error("barcode could not be decoded: {message}")]
+    Format { message: String },
+    #[error("checksum mismatch: {message}")]
+    Checksum { message: String },
+    #[error("contents could not be encoded: {message}")]
+    Writer { message: String },
+    #[error("unsupported: {message}")]
+    Unsupported { message: String },
+    #[error("{message}")]
+    Internal { message: String },
+}
+
+impl From<Exceptions> for RxingError {
+    fn from(value: Exceptions) -> Self {
+        let message = value.to_string();
+        match value {
+            Exceptions::NotFoundException(_) => Self::NotFound,
+            Exceptions::FormatException(_)
+            | Exceptions::ReedSolomonException(_)
+            | Exceptions::ReaderDecodeException() => Self::Format { message },
+            Exceptions::ChecksumException(_) => Self::Checksum { message },
+            Exceptions::IllegalArgumentException(_) | Exceptions::ParseException(_) => {
+                Self::InvalidArgument { message }
+            }
+            Exceptions::WriterException(_) => Self::Writer { message },
+            Exceptions::UnsupportedOperationException(_) => Self::Unsupported { message },
+            _ => Self::Internal { message },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, uniffi::Record)]
+pub struct Point {
+    pub x: f32,
+    pub y: f32,
+}
+
+/// A decoded barcode
+#[derive(Debug, Clone, PartialEq, uniffi::Record)]
+pub struct Barcode {
+    pub text: String,
+    pub format: BarcodeFormat,
+    pub raw_bytes: Vec<u8>,
+    pub points: Vec<Point>,
+}
+
+impl TryFrom<RXingResult> for Barcode {
+    type Error = RxingError;
+
+    fn try_from(result: RXingResult) -> Result<Self, Self::Error> {
+        Ok(Self {
+            text: result.getText().to_owned(),
+            format: result.getBarcodeFormat().clone().try_into()?,
+            raw_bytes: result.getRawBytes().to_vec(),
+            points: result
+                .getPoints()
+                .iter()
+                .map(|p| Point { x: p.x, y: p.y })
+                .collect(),
+        })
+    }
+}
+
+/// How a [`Scanner`] searches for barcodes
+#[derive(Debug, Clone, PartialEq, uniffi::Record)]
+pub struct ScannerOptions {
+    /// The formats to look for, all formats if empty
+    #[uniffi(default)]
+    pub formats: Vec<BarcodeFormat>,
+    /// Spend more time to find a barcode; optimize for accuracy, not speed
+    #[uniffi(default = true)]
+    pub try_harder: bool,
+    /// Also look for light on dark barcodes
+    #[uniffi(default = false)]
+    pub also_inverted: bool,
+    /// The image contains nothing but a barcode, as produced by an encoder
+    #[uniffi(default = false)]
+    pub pure_barcode: bool,
+    /// The character set used to decode byte segments, guessed if not set
+    #[uniffi(default = None)]
+    pub character_set: Option<String>,
+}
+
+impl Default for ScannerOptions {
+    fn default() -> Self {
+        Self {
+            formats: Vec::new(),
+            try_harder: true,
+            also_inverted: false,
+            pure_barcode: false,
+            character_set: None,
+        }
+    }
+}
+
+impl From<&ScannerOptions> for DecodeHints {
+    fn from(options: &ScannerOptions) -> Self {
+        DecodeHints {
+            PossibleFormats: (!options.formats.is_empty())
+                .then(|| options.formats.iter().map(|f| (*f).into()).collect()),
+            TryHarder: Some(options.try_harder),
+            AlsoInverted: Some(options.also_inverted),
+            PureBarcode: Some(options.pure_barcode),
+            CharacterSet: options.character_set.clone(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Decodes barcodes from camera frames or bitmaps. A scanner can be shared between threads.
+#[derive(uniffi::Object)]
+pub struct Scanner {
+    hints: DecodeHints,
+}
+
+#[uniffi::export]
+impl Scanner {
+    #[uniffi::constructor]
+    pub fn new(options: ScannerOptions) -> Arc<Self> {
+        Arc::new(Self {
+            hints: (&options).into(),
+        })
+    }
+
+    /// Decode a single barcode from 8 bit grayscale pixels, such as the Y plane of a YUV
+    /// camera frame. `row_stride` is the number of bytes per row, `width` if not set.
+    #[uniffi::method(default(row_stride = None))]
+    pub fn scan_luma(
+        &self,
+        luma: Vec<u8>,
+        width: u32,
+        height: u32,
+        row_stride: Option<u32>,
+    ) -> Result<Barcode, RxingError> {
+        let luma = compact_rows(luma, width, height, 1, row_stride)?;
+        self.decode(luma, width, height)
+    }
+
+    /// Decode every barcode found in 8 bit grayscale pixels. Returns an empty list if no
+    /// barcode is found.
+    #[uniffi::method(default(row_stride = None))]
+    pub fn scan_luma_multiple(
+        &self,
+        luma: Vec<u8>,
+        width: u32,
+        height: u32,
+        row_stride: Option<u32>,
+    ) -> Result<Vec<Barcode>, RxingError> {
+        let luma = compact_rows(luma, width, height, 1, row_stride)?;
+        self.decode_multiple(luma, width, height)
+    }
+
+    /// Decode a single barcode from RGBA pixels, as copied out of an Android `Bitmap` or an
+    /// iOS `CGImage`. `row_stride` is the number of bytes per row, `width * 4` if not set.
+    #[uniffi::method(default(row_stride = None))]
+    pub fn scan_rgba(
+        &self,
+        rgba: Vec<u8>,
+        width: u32,
+        height: u32,
+        row_stride: Option<u32>,
+    ) -> Result<Barcode, RxingError> {
+        let rgba = compact_rows(rgba, width, height, 4, row_stride)?;
+        self.decode(rgba_to_luma(&rgba), width, height)
+    }
+
+    /// Decode every barcode found in RGBA pixels. Returns an empty list if no barcode is found.
+    #[uniffi::method(default(row_stride = None))]
+    pub fn scan_rgba_multiple(
+        &self,
+        rgba: Vec<u8>,
+        width: u32,
+        height: u32,
+        row_stride: Option<u32>,
+    ) -> Result<Vec<Barcode>, RxingError> {
+        let rgba = compact_rows(rgba, width, height, 4, row_stride)?;
+        self.decode_multiple(rgba_to_luma(&rgba), width, height)
+    }
+}
+
+impl Scanner {
+    fn decode(&self, luma: Vec<u8>, width: u32, height: u32) -> Result<Barcode, RxingError> {
+        helpers::detect_in_luma_with_hints(luma, width, height, None, &mut self.hints.clone())?
+            .try_into()
+    }
+
+    fn decode_multiple(
+        &self,
+        luma: Vec<u8>,
+        width: u32,
+        height: u32,
+    ) -> Result<Vec<Barcode>, RxingError> {
+        match helpers::detect_multiple_in_luma_with_hints(
+            luma,
+            width,
+            height,
+            &mut self.hints.clone(),
+        ) {
+            Ok(results) => results.into_iter().map(Barcode::try_from).collect(),
+            Err(Exceptions::NotFoundException(_)) => Ok(Vec::new()),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+/// How an [`Encoder`] renders symbols
+#[derive(Debug, Clone, Default, PartialEq, uniffi::Record)]
+pub struct EncoderOptions {
+    /// The error correction level, for example `"H"` for QR codes
+    #[uniffi(default = None)]
+    pub error_correction: Option<String>,
+    /// The character set used to encode the contents
+    #[uniffi(default = None)]
+    pub character_set: Option<String>,
+    /// The quiet zone around the symbol, in modules
+    #[uniffi(default = None)]
+    pub margin: Option<u32>,
+}
+
+impl From<&EncoderOptions> for EncodeHints {
+    fn from(options: &EncoderOptions) -> Self {
+        EncodeHints {
+            ErrorCorrection: options.error_correction.clone(),
+            CharacterSet: options.character_set.clone(),
+            Margin: options.margin.map(|m| m.to_string()),
+            ..Default::default()
+        }
+    }
+}
+
+/// An encoded symbol as 8 bit grayscale pixels, 0 for dark and 255 for light modules
+#[derive(Debug, Clone, PartialEq, uniffi::Record)]
+pub struct EncodedImage {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+impl From<&BitMatrix> for EncodedImage {
+    fn from(matrix: &BitMatrix) -> Self {
+        Self {
+            width: matrix.getWidth(),
+            height: matrix.getHeight(),
+            pixels: matrix.to_luma8(),
+        }
+    }
+}
+
+/// Encodes contents into barcode symbols
+#[derive(uniffi::Object)]
+pub struct Encoder {
+    hints: EncodeHints,
+}
+
+#[uniffi::export]
+impl Encoder {
+    #[uniffi::constructor]
+    pub fn new(options: EncoderOptions) -> Arc<Self> {
+        Arc::new(Self {
+            hints: (&options).into(),
+        })
+    }
+
+    /// Encode `contents` as pixels, at least `width` by `height` large
+    pub fn encode(
+        &self,
+        contents: String,
+        format: BarcodeFormat,
+        width: u32,
+        height: u32,
+    ) -> Result<EncodedImage, RxingError> {
+        Ok((&self.encode_matrix(&contents, format, width, height)?).into())
+    }
+
+    /// Encode `contents` as an SVG document
+    pub fn encode_svg(
+        &self,
+        contents: String,
+        format: BarcodeFormat,
+        width: u32,
+        height: u32,
+    ) -> Result<String, RxingError> {
+        let matrix = self.encode_matrix(&contents, format, width, height)?;
+        Ok(svg::Document::from(&matrix).to_string())
+    }
+}
+
+impl Encoder {
+    fn encode_matrix(
+        &self,
+        contents: &str,
+        format: BarcodeFormat,
+        width: u32,
+        height: u32,
+    ) -> Result<BitMatrix, RxingError> {
+        let (Ok(width), Ok(height)) = (i32::try_from(width), i32::try_from(height)) else {
+            return Err(RxingError::InvalidArgument {
+                message: "size is too large".to_owned(),
+            });
+        };
+        Ok(crate::MultiFormatWriter.encode_with_hints(
+            contents,
+            &format.into(),
+            width,
+            height,
+            &self.hints,
+        )?)
+    }
+}
+
+/// The version of rxing in use
+#[uniffi::export]
+pub fn rxing_version() -> String {
+    env!("CARGO_PKG_VERSION").to_owned()
+}
+
+/// Drop any padding at the end of each row, so the pixels are tightly packed
+fn compact_rows(
+    data: Vec<u8>,
+    width: u32,
+    height: u32,
+    bytes_per_pixel: usize,
+    row_stride: Option<u32>,
+) -> Result<Vec<u8>, RxingError> {
+    let invalid = |message: String| RxingError::InvalidArgument { message };
+    let row_len = (width as usize)
+        .checked_mul(bytes_per_pixel)
+        .filter(|len| *len > 0 && height > 0)
+        .ok_or_else(|| invalid(format!("invalid image size {width}x{height}")))?;
+    let stride = row_stride.map_or(row_len, |stride| stride as usize);
+    if stride < row_len {
+        return Err(invalid(format!(
+            "row stride {stride} is smaller than a row of {row_len} bytes"
+        )));
+    }
+    // the last row doesn't need to be padded
+    let needed = stride * (height as usize - 1) + row_len;
+    if data.len() < needed {
+        return Err(invalid(format!(
+            "expected at least {needed} bytes of pixel data, got {}",
+            data.len()
+        )));
+    }
+
+    if stride == row_len {
+        let mut data = data;
+        data.truncate(row_len * height as usize);
+        return Ok(data);
+    }
+    Ok(data
+        .chunks(stride)
+        .take(height as usize)
+        .flat_map(|row| &row[..row_len])
+        .copied()
+        .collect())
+}
+
+fn rgba_to_luma(rgba: &[u8]) -> Vec<u8> {
+    let mut luma = vec![0; rgba.len() / 4];
+    simd::rgba_to_luma(rgba, &mut luma);
+    luma
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn qr_image(contents: &str) -> EncodedImage {
+        Encoder::new(EncoderOptions::default())
+            .encode(contents.to_owned(), BarcodeFormat::QrCode, 100, 100)
+            .unwrap()
+    }
+
+    #[test]
+    fn scans_luma_and_rgba() {
+        let image = qr_image("mobile");
+        let scanner = Scanner::new(ScannerOptions {
+            formats: vec![BarcodeFormat::QrCode],
+            ..Default::default()
+        });
+
+        let barcode = scanner
+            .scan_luma(image.pixels.clone(), image.width, image.height, None)
+            .unwrap();
+        assert_eq!(barcode.text, "mobile");
+        assert_eq!(barcode.format, BarcodeFormat::QrCode);
+
+        let rgba = image
+            .pixels
+            .iter()
+            .flat_map(|p| [*p, *p, *p, 255])
+            .collect();
+        let barcodes = scanner
+            .scan_rgba_multiple(rgba, image.width, image.height, None)
+            .unwrap();
+        assert_eq!(barcodes.len(), 1);
+        assert_eq!(barcodes[0].text, "mobile");
+    }
+
+    #[test]
+    fn scans_padded_rows() {
+        let image = qr_image("stride");
+        let stride = image.width + 16;
+        let padded = image
+            .pixels
+            .chunks(image.width as usize)
+            .flat_map(|row| row.iter().copied().chain([0x7F; 16]))
+            .collect();
+
+        let barcode = Scanner::new(ScannerOptions::default())
+            .scan_luma(padded, image.width, image.height, Some(stride))
+            .unwrap();
+        assert_eq!(barcode.text, "stride");
+    }
+
+    #[test]
+    fn reports_errors() {
+        let scanner = Scanner::new(ScannerOptions::default());
+        let blank = vec![0xFF; 100 * 100];
+
+        assert_eq!(
+            scanner.scan_luma(blank.clone(), 100, 100, None),
+            Err(RxingError::NotFound)
+        );
+        assert_eq!(
+            scanner.scan_luma_multiple(blank, 100, 100, None),
+            Ok(Vec::new())
+        );
+        assert!(matches!(
+            scanner.scan_luma(vec![0; 10], 100, 100, None),
+            Err(RxingError::InvalidArgument { .. })
+        ));
+        assert!(matches!(
+            scanner.scan_luma(vec![0; 100], 10, 10, Some(5)),
+            Err(RxingError::InvalidArgument { .. })
+        ));
+    }
+
+    #[test]
+    fn encodes_with_options() {
+        let encoder = Encoder::new(EncoderOptions {
+            margin: Some(0),
+            ..Default::default()
+        });
+        let image = encoder
+            .encode("margin".to_owned(), BarcodeFormat::QrCode, 0, 0)
+            .unwrap();
+        assert_eq!((image.width, image.height), (21, 21));
+        assert!(encoder
+            .encode_svg("svg".to_owned(), BarcodeFormat::QrCode, 50, 50)
+            .unwrap()
+            .starts_with("<svg"));
+        assert!(matches!(
+            encoder.encode("abc".to_owned(), BarcodeFormat::Ean13, 0, 0),
+            Err(RxingError::InvalidArgument { .. }) | Err(RxingError::Writer { .. })
+        ));
+    }
+
+    #[test]
+    fn formats_round_trip() {
+        for format in crate::BarcodeFormat::ALL {
+            let mirrored = BarcodeFormat::try_from(format.clone()).unwrap();
+            assert_eq!(crate::BarcodeFormat::from(mirrored), format);
+        }
+        assert!(BarcodeFormat::try_from(crate::BarcodeFormat::UNSUPORTED_FORMAT).is_err());
+    }
+}
@@ -21,6 +21,9 @@ use crate::{
 
 use super::MultipleBarcodeReader;
 
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
 /**
  * <p>Attempts to locate multiple barcodes in an image by repeatedly decoding portion of the image.
  * After one barcode is found, the areas left, above, right and below the barcode's
@@ -53,52 +56,9 @@ impl<T: Reader> MultipleBarcodeReader for GenericMultipleBarcodeReader<T> {
     ) -> Result<Vec<RXingResult>> {
         let mut results = Vec::new();
         self.do_decode_multiple(image, hints, &mut results, 0, 0, 0);
+        hints.check_cancelled()?;
 
-        let unique_results: Vec<RXingResult> = results
-            .iter()
-            .enumerate()
-            .filter(|(i, r)| {
-                let already_found = if r.getPoints().len() >= 4 {
-                    let q1 = Quadrilateral::new(
-                        r.getPoints()[0],
-                        r.getPoints()[1],
-                        r.getPoints()[2],
-                        r.getPoints()[3],
-                    );
-                    results.iter().skip(*i + 1).any(|e| {
-                        if e.getPoints().len() >= 4 {
-                            let q2 = Quadrilateral::new(
-                                e.getPoints()[0],
-                                e.getPoints()[1],
-                                e.getPoints()[2],
-                                e.getPoints()[3],
-                            );
-                            Quadrilateral::have_intersecting_bounding_boxes(&q1, &q2)
-                        } else {
-                            e.getPoints().iter().any(|p| q1.is_inside(*p))
-                        }
-                    })
-                } else {
-                    results.iter().skip(*i + 1).any(|e| {
-                        if e.getPoints().len() >= 4 {
-                            let q2 = Quadrilateral::new(
-                                e.getPoints()[0],
-                                e.getPoints()[1],
-                                e.getPoints()[2],
-                                e.getPoints()[3],
-                            );
-                            e.getPoints().iter().any(|p| q2.is_inside(*p))
-                        } else {
-                            e.getText() == r.getText()
-                                && e.getBarcodeFormat() == r.getBarcodeFormat()
-                        }
-                    })
-                };
-                !already_found
-            })
-            .map(|(_, r)| r)
-            .cloned()
-            .collect();
+        let unique_results = unique_results(&results);
 
         if unique_results.is_empty() {
             return Err(Exceptions::NOT_FOUND);
@@ -123,7 +83,7 @@ impl<T: Reader> GenericMultipleBarcodeReader<T> {
         yOffset: u32,
         currentDepth: u32,
     ) {
-        if currentDepth > Self::MAX_DEPTH {
+        if currentDepth > Self::MAX_DEPTH || hints.check_cancelled().is_err() {
             return;
         }
 
@@ -204,7 +164,7 @@ impl<T: Reader> GenericMultipleBarcodeReader<T> {
         }
     }
 
-    fn translatePoints(result: RXingResult, xOffset: u32, yOffset: u32) -> RXingResult {
+    pub(crate) fn translatePoints(result: RXingResult, xOffset: u32, yOffset: u32) -> RXingResult {
         let oldPoints = result.getPoints();
         if oldPoints.is_empty() {
             return result;
@@ -220,11 +180,157 @@ impl<T: Reader> GenericMultipleBarcodeReader<T> {
             result.getRawBytes().to_vec(),
             result.getNumBits(),
             newPoints,
-            *result.getBarcodeFormat(),
+            result.getBarcodeFormat().clone(),
             result.getTimestamp(),
         );
         newRXingResult.putAllMetadata(result.getRXingResultMetadata().clone());
+        newRXingResult.addWarnings(result.getWarnings());
 
         newRXingResult
     }
 }
+
+#[cfg(feature = "parallel")]
+impl<T: Reader + Default> GenericMultipleBarcodeReader<T> {
+    /**
+     * Like `decode_multiple_with_hints`, but searches the regions left of, above, right of and
+     * below each barcode it finds at the same time on rayon's thread pool, each with a new `T`
+     * and binarizing its own region. It finds the same barcodes in the same order.
+     */
+    pub fn decode_multiple_parallel_with_hints<B: Binarizer + Send>(
+        &mut self,
+        image: &mut BinaryBitmap<B>,
+        hints: &DecodeHints,
+    ) -> Result<Vec<RXingResult>> {
+        let Ok(result) = self.0.decode_with_hints(image, hints) else {
+            return Err(Exceptions::NOT_FOUND);
+        };
+        let results = Self::do_decode_parallel(image, hints, result, 0, 0, 0);
+
+        let unique_results = unique_results(&results);
+
+        if unique_results.is_empty() {
+            return Err(Exceptions::NOT_FOUND);
+        }
+        Ok(unique_results)
+    }
+
+    fn decode_region_parallel<B: Binarizer + Send>(
+        image: &mut BinaryBitmap<B>,
+        hints: &DecodeHints,
+        xOffset: u32,
+        yOffset: u32,
+        currentDepth: u32,
+    ) -> Vec<RXingResult> {
+        if currentDepth > Self::MAX_DEPTH {
+            return Vec::new();
+        }
+        let Ok(result) = T::default().decode_with_hints(image, hints) else {
+            return Vec::new();
+        };
+        Self::do_decode_parallel(image, hints, result, xOffset, yOffset, currentDepth)
+    }
+
+    /// `result`, found in `image`, followed by the barcodes around it
+    fn do_decode_parallel<B: Binarizer + Send>(
+        image: &mut BinaryBitmap<B>,
+        hints: &DecodeHints,
+        result: RXingResult,
+        xOffset: u32,
+        yOffset: u32,
+        currentDepth: u32,
+    ) -> Vec<RXingResult> {
+        let resultPoints = result.getPoints().to_vec();
+        let mut results = vec![Self::translatePoints(result, xOffset, yOffset)];
+        if resultPoints.is_empty() {
+            return results;
+        }
+
+        let width = image.get_width();
+        let height = image.get_height();
+        let minX = resultPoints.iter().fold(width as f32, |m, p| m.min(p.x));
+        let minY = resultPoints.iter().fold(height as f32, |m, p| m.min(p.y));
+        let maxX = resultPoints.iter().fold(0.0, |m: f32, p| m.max(p.x));
+        let maxY = resultPoints.iter().fold(0.0, |m: f32, p| m.max(p.y));
+
+        // the same regions as do_decode_multiple, in the same order
+        let mut regions = Vec::new();
+        if minX > Self::MIN_DIMENSION_TO_RECUR {
+            regions.push((image.crop(0, 0, minX as usize, height), xOffset, yOffset));
+        }
+        if minY > Self::MIN_DIMENSION_TO_RECUR {
+            regions.push((image.crop(0, 0, width, minY as usize), xOffset, yOffset));
+        }
+        if maxX < (width as f32) - Self::MIN_DIMENSION_TO_RECUR {
+            regions.push((
+                image.crop(maxX as usize, 0, width - maxX as usize, height),
+                xOffset + maxX as u32,
+                yOffset,
+            ));
+        }
+        if maxY < (height as f32) - Self::MIN_DIMENSION_TO_RECUR {
+            regions.push((
+                image.crop(0, maxY as usize, width, height - maxY as usize),
+                xOffset,
+                yOffset + maxY as u32,
+            ));
+        }
+
+        let found: Vec<Vec<RXingResult>> = regions
+            .into_par_iter()
+            .map(|(mut region, x, y)| {
+                Self::decode_region_parallel(&mut region, hints, x, y, currentDepth + 1)
+            })
+            .collect();
+        results.extend(found.into_iter().flatten());
+        results
+    }
+}
+
+/// `results` without those whose symbol overlaps one found later
+fn unique_results(results: &[RXingResult]) -> Vec<RXingResult> {
+    results
+        .iter()
+        .enumerate()
+        .filter(|(i, r)| {
+            let already_found = if r.getPoints().len() >= 4 {
+                let q1 = Quadrilateral::new(
+                    r.getPoints()[0],
+                    r.getPoints()[1],
+                    r.getPoints()[2],
+                    r.getPoints()[3],
+                );
+                results.iter().skip(*i + 1).any(|e| {
+                    if e.getPoints().len() >= 4 {
+                        let q2 = Quadrilateral::new(
+                            e.getPoints()[0],
+                            e.getPoints()[1],
+                            e.getPoints()[2],
+                            e.getPoints()[3],
+                        );
+                        Quadrilateral::have_intersecting_bounding_boxes(&q1, &q2)
+                    } else {
+                        e.getPoints().iter().any(|p| q1.is_inside(*p))
+                    }
+                })
+            } else {
+                results.iter().skip(*i + 1).any(|e| {
+                    if e.getPoints().len() >= 4 {
+                        let q2 = Quadrilateral::new(
+                            e.getPoints()[0],
+                            e.getPoints()[1],
+                            e.getPoints()[2],
+                            e.getPoints()[3],
+                        );
+                        e.getPoints().iter().any(|p| q2.is_inside(*p))
+                    } else {
+                        e.getText() == r.getText() && e.getBarcodeFormat() == r.getBarcodeFormat()
+                    }
+                })
+            };
+            !already_found
+        })
+        .map(|(_, r)| r)
+        .cloned()
+        .collect()
+}
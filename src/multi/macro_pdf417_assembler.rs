@@ -0,0 +1,386 @@
+/*
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use crate::common::Result;
+use crate::pdf417::PDF417RXingResultMetadata;
+use crate::{
+    BarcodeFormat, Exceptions, RXingResult, RXingResultMetadataType, RXingResultMetadataValue,
+};
+
+use super::join_parts;
+
+/// The most segments a Macro PDF417 file can be split into, as the segment index has 5 digits
+const MAX_SEGMENT_COUNT: usize = 99999;
+
+/**
+ * Joins the segments of a Macro PDF417 file, spread over several symbols and often over several
+ * images, into the file they were split from.
+ *
+ * The symbols are added as they are read, in any order. A symbol of another file ID is refused.
+ * The number of segments is known from the segment count field, or otherwise once the last
+ * segment, which carries the terminator, is added. Once every segment is there, `assemble`
+ * returns the file.
+ *
+ * ```ignore
+ * let mut assembler = MacroPDF417Assembler::new();
+ * for result in results {
+ *     assembler.add(result)?;
+ * }
+ * if assembler.is_complete() {
+ *     println!("{}", assembler.assemble()?.getText());
+ * }
+ * ```
+ */
+#[derive(Clone, Default)]
+pub struct MacroPDF417Assembler {
+    file_id: Option<String>,
+    count: Option<usize>,
+    segments: BTreeMap<usize, (RXingResult, Arc<PDF417RXingResultMetadata>)>,
+}
+
+impl MacroPDF417Assembler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /**
+     * Adds a segment of the file. A segment that was added before is ignored.
+     *
+     * # Errors
+     * `IllegalArgumentException` if `result` is not a Macro PDF417 symbol, or one of another
+     * file than the segments added before, if its segment count disagrees with theirs, or if its
+     * index is taken by a different symbol or beyond the last segment
+     */
+    pub fn add(&mut self, result: RXingResult) -> Result<()> {
+        let Some(RXingResultMetadataValue::Pdf417ExtraMetadata(macro_block)) = result
+            .getRXingResultMetadata()
+            .get(&RXingResultMetadataType::PDF417_EXTRA_METADATA)
+            .cloned()
+        else {
+            return Err(Exceptions::illegal_argument_with(
+                "the symbol is not a Macro PDF417 segment",
+            ));
+        };
+        if macro_block.getFileId().is_empty() {
+            return Err(Exceptions::illegal_argument_with(
+                "the symbol is not a Macro PDF417 segment",
+            ));
+        }
+
+        let index = macro_block.getSegmentIndex();
+        let count = if macro_block.getSegmentCount() > 0 {
+            Some(macro_block.getSegmentCount() as usize)
+        } else if macro_block.isLastSegment() {
+            Some(index + 1)
+        } else {
+            None
+        };
+        if count.is_some_and(|count| count > MAX_SEGMENT_COUNT) {
+            return Err(Exceptions::illegal_argument_with(format!(
+                "invalid segment count {count:?}"
+            )));
+        }
+
+        if let Some(file_id) = &self.file_id {
+            if file_id != macro_block.getFileId() {
+                return Err(Exceptions::illegal_argument_with(format!(
+                    "the symbol belongs to file {}, not {file_id}",
+                    macro_block.getFileId()
+                )));
+            }
+        }
+        if let (Some(known), Some(count)) = (self.count, count) {
+            if known != count {
+                return Err(Exceptions::illegal_argument_with(format!(
+                    "the file has {known} segments, the symbol says {count}"
+                )));
+            }
+        }
+        let count = self.count.or(count);
+        if count
+            .is_some_and(|count| index >= count || self.segments.keys().next_back() >= Some(&count))
+        {
+            return Err(Exceptions::illegal_argument_with(format!(
+                "segment {index} is beyond the last segment"
+            )));
+        }
+        if let Some((added, _)) = self.segments.get(&index) {
+            if added.getText() != result.getText() || added.getRawBytes() != result.getRawBytes() {
+                return Err(Exceptions::illegal_argument_with(format!(
+                    "a different segment {index} was added before"
+                )));
+            }
+            return Ok(());
+        }
+
+        self.file_id = Some(macro_block.getFileId().to_owned());
+        self.count = count;
+        self.segments.insert(index, (result, macro_block));
+        Ok(())
+    }
+
+    /// The file ID shared by the segments, once one is added
+    pub fn get_file_id(&self) -> Option<&str> {
+        self.file_id.as_deref()
+    }
+
+    /// The number of segments of the file, once a segment count or the last segment is added
+    pub fn get_count(&self) -> Option<usize> {
+        self.count
+    }
+
+    /**
+     * The indices of the segments not added yet. Until the number of segments is known, those
+     * are the ones before the highest index added.
+     */
+    pub fn missing_parts(&self) -> Vec<usize> {
+        let end = self.count.unwrap_or_else(|| {
+            self.segments
+                .keys()
+                .next_back()
+                .map_or(0, |index| index + 1)
+        });
+        (0..end)
+            .filter(|index| !self.segments.contains_key(index))
+            .collect()
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.count
+            .is_some_and(|count| count > 0 && self.segments.len() == count)
+    }
+
+    /**
+     * The file, which holds the text and raw bytes of the segments in order and has no points.
+     * Its PDF417 metadata is that of the whole file: the file ID, the number of segments, and
+     * the optional fields (file name, sender, addressee, file size, timestamp and checksum)
+     * of the first segment that carries each.
+     *
+     * # Errors
+     * `NotFoundException` naming the missing segments if the file is incomplete
+     */
+    pub fn assemble(&self) -> Result<RXingResult> {
+        let Some(file_id) = &self.file_id else {
+            return Err(Exceptions::not_found_with("no segment was added"));
+        };
+        let Some(count) = self.count.filter(|_| self.is_complete()) else {
+            return Err(Exceptions::not_found_with(format!(
+                "missing segments {:?} of {}",
+                self.missing_parts(),
+                self.count
+                    .map_or_else(|| "an unknown number".to_owned(), |count| count.to_string())
+            )));
+        };
+
+        let mut file = PDF417RXingResultMetadata::default();
+        file.setFileId(file_id.clone());
+        file.setSegmentCount(count as isize);
+        file.setLastSegment(true);
+        for (_, segment) in self.segments.values() {
+            if file.getFileName().is_empty() {
+                file.setFileName(segment.getFileName().to_owned());
+            }
+            if file.getSender().is_empty() {
+                file.setSender(segment.getSender().to_owned());
+            }
+            if file.getAddressee().is_empty() {
+                file.setAddressee(segment.getAddressee().to_owned());
+            }
+            if file.getFileSize() == -1 {
+                file.setFileSize(segment.getFileSize());
+            }
+            if file.getTimestamp() == -1 {
+                file.setTimestamp(segment.getTimestamp());
+            }
+            if file.getChecksum() == -1 {
+                file.setChecksum(segment.getChecksum());
+            }
+        }
+
+        let mut message = join_parts(
+            self.segments.values().map(|(result, _)| result),
+            BarcodeFormat::PDF_417,
+        );
+        message.putMetadata(
+            RXingResultMetadataType::PDF417_EXTRA_METADATA,
+            RXingResultMetadataValue::Pdf417ExtraMetadata(Arc::new(file)),
+        );
+        Ok(message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        pdf417::PDF417RXingResultMetadata, BarcodeFormat, Exceptions, RXingResult,
+        RXingResultMetadataType, RXingResultMetadataValue,
+    };
+
+    use super::MacroPDF417Assembler;
+
+    fn segment(
+        text: &str,
+        index: usize,
+        file_id: &str,
+        edit: impl FnOnce(&mut PDF417RXingResultMetadata),
+    ) -> RXingResult {
+        let mut macro_block = PDF417RXingResultMetadata::default();
+        macro_block.setSegmentIndex(index);
+        macro_block.setFileId(file_id.to_owned());
+        edit(&mut macro_block);
+        let mut result = RXingResult::new(
+            text,
+            text.as_bytes().to_vec(),
+            Vec::new(),
+            BarcodeFormat::PDF_417,
+        );
+        result.putMetadata(
+            RXingResultMetadataType::PDF417_EXTRA_METADATA,
+            RXingResultMetadataValue::Pdf417ExtraMetadata(Arc::new(macro_block)),
+        );
+        result
+    }
+
+    fn file_metadata(result: &RXingResult) -> Arc<PDF417RXingResultMetadata> {
+        match result
+            .getRXingResultMetadata()
+            .get(&RXingResultMetadataType::PDF417_EXTRA_METADATA)
+        {
+            Some(RXingResultMetadataValue::Pdf417ExtraMetadata(metadata)) => metadata.clone(),
+            _ => panic!("no PDF417 metadata"),
+        }
+    }
+
+    #[test]
+    fn test_assemble_with_terminator() {
+        let mut assembler = MacroPDF417Assembler::new();
+        assert!(assembler.assemble().is_err());
+
+        assembler
+            .add(segment("B", 1, "017053", |_| {}))
+            .expect("add");
+        // the number of segments is not known before the last one
+        assert_eq!(None, assembler.get_count());
+        assert_eq!(vec![0], assembler.missing_parts());
+        assert!(matches!(
+            assembler.assemble(),
+            Err(Exceptions::NotFoundException(message)) if message.contains("unknown")
+        ));
+
+        assembler
+            .add(segment("C", 2, "017053", |m| m.setLastSegment(true)))
+            .expect("add");
+        assert_eq!(Some(3), assembler.get_count());
+        assert!(!assembler.is_complete());
+        assembler
+            .add(segment("A", 0, "017053", |m| {
+                m.setFileName("file.txt".to_owned());
+                m.setTimestamp(1_700_000_000);
+            }))
+            .expect("add");
+        // a segment read twice
+        assembler
+            .add(segment("B", 1, "017053", |_| {}))
+            .expect("add");
+        assert!(assembler.is_complete());
+
+        let file = assembler.assemble().expect("assemble");
+        assert_eq!("ABC", file.getText());
+        assert_eq!(b"ABC".to_vec(), *file.getRawBytes());
+        assert_eq!(BarcodeFormat::PDF_417, *file.getBarcodeFormat());
+        let metadata = file_metadata(&file);
+        assert_eq!("017053", metadata.getFileId());
+        assert_eq!(3, metadata.getSegmentCount());
+        assert_eq!("file.txt", metadata.getFileName());
+        assert_eq!(1_700_000_000, metadata.getTimestamp());
+        assert_eq!(-1, metadata.getChecksum());
+    }
+
+    #[test]
+    fn test_validation() {
+        let mut assembler = MacroPDF417Assembler::new();
+        assembler
+            .add(segment("A", 0, "001", |m| m.setSegmentCount(2)))
+            .expect("add");
+        assert_eq!(Some("001"), assembler.get_file_id());
+        assert_eq!(vec![1], assembler.missing_parts());
+
+        // another file, segment count or a segment beyond it, a different symbol for an index
+        assert!(assembler.add(segment("B", 1, "002", |_| {})).is_err());
+        assert!(assembler
+            .add(segment("B", 1, "001", |m| m.setSegmentCount(3)))
+            .is_err());
+        assert!(assembler.add(segment("C", 2, "001", |_| {})).is_err());
+        assert!(assembler.add(segment("X", 0, "001", |_| {})).is_err());
+
+        // not a Macro PDF417 symbol
+        assert!(assembler
+            .add(RXingResult::new(
+                "B",
+                Vec::new(),
+                Vec::new(),
+                BarcodeFormat::PDF_417
+            ))
+            .is_err());
+        assert!(assembler.add(segment("B", 1, "", |_| {})).is_err());
+
+        // a terminator before a segment added earlier
+        let mut truncated = MacroPDF417Assembler::new();
+        truncated.add(segment("C", 2, "001", |_| {})).expect("add");
+        assert!(truncated
+            .add(segment("B", 1, "001", |m| m.setLastSegment(true)))
+            .is_err());
+
+        assembler
+            .add(segment("B", 1, "001", |m| m.setLastSegment(true)))
+            .expect("add");
+        assert_eq!("AB", assembler.assemble().expect("assemble").getText());
+    }
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn test_pdf417_images() {
+        use crate::{
+            common::HybridBinarizer, multi::MultipleBarcodeReader, pdf417::PDF417Reader,
+            BinaryBitmap, BufferedImageLuminanceSource,
+        };
+
+        // a binary file, read as the reader decodes byte compaction
+        let expected = crate::common::CharacterSet::ISO8859_1
+            .decode(&std::fs::read("test_resources/blackbox/pdf417-4/02.bin").expect("read file"))
+            .expect("decode file");
+        let mut assembler = MacroPDF417Assembler::new();
+        // two images of four segments each
+        for part in [2, 1] {
+            let image = image::open(format!("test_resources/blackbox/pdf417-4/02-0{part}.png"))
+                .expect("open image");
+            let results = PDF417Reader
+                .decode_multiple(&mut BinaryBitmap::new(HybridBinarizer::new(
+                    BufferedImageLuminanceSource::new(image),
+                )))
+                .expect("decode");
+            for result in results {
+                assembler.add(result).expect("add");
+            }
+        }
+        assert_eq!(Some(8), assembler.get_count());
+        assert!(assembler.is_complete());
+        assert_eq!(expected, assembler.assemble().expect("assemble").getText());
+    }
+}
@@ -8,6 +8,15 @@ pub use by_quadrant_reader::*;
 mod generic_multiple_barcode_reader;
 pub use generic_multiple_barcode_reader::*;
 
+mod tiled_multiple_barcode_reader;
+pub use tiled_multiple_barcode_reader::*;
+
+mod structured_append_assembler;
+pub use structured_append_assembler::*;
+
+mod macro_pdf417_assembler;
+pub use macro_pdf417_assembler::*;
+
 #[cfg(test)]
 #[cfg(feature = "image")]
 mod multi_test_case;
@@ -95,3 +95,32 @@ fn testMultiQR() {
         .insert("You get to CREATE OUR JOURNAL PROMPT FOR THE DAY!  Yay!  Way to go!  ".to_owned());
     assert_eq!(expectedContents, barcodeContents);
 }
+
+#[cfg(all(feature = "image_formats", feature = "parallel"))]
+#[test]
+fn testMultiParallel() {
+    for path in [
+        "test_resources/blackbox/multi-1/1.png",
+        "test_resources/blackbox/multi-qrcode-1/1.png",
+    ] {
+        let image = image::open(path).expect("image must open");
+        let mut bitmap = BinaryBitmap::new(HybridBinarizer::new(
+            BufferedImageLuminanceSource::new(image),
+        ));
+
+        let sequential = GenericMultipleBarcodeReader::new(MultiFormatReader::default())
+            .decode_multiple(&mut bitmap)
+            .expect("must decode multi");
+        let parallel = GenericMultipleBarcodeReader::new(MultiFormatReader::default())
+            .decode_multiple_parallel_with_hints(&mut bitmap, &Default::default())
+            .expect("must decode multi");
+
+        let texts = |results: &[crate::RXingResult]| {
+            results
+                .iter()
+                .map(|r| (r.getText().to_owned(), r.getPoints().to_vec()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(texts(&sequential), texts(&parallel), "{path}");
+    }
+}
@@ -18,13 +18,14 @@ use std::cmp::Ordering;
 
 use crate::{
     common::{DetectorRXingResult, Result},
+    decode_confidence,
     multi::MultipleBarcodeReader,
     qrcode::{
         decoder::{self, QRCodeDecoderMetaData},
         QRCodeReader,
     },
-    BarcodeFormat, Binarizer, DecodeHints, Exceptions, RXingResult, RXingResultMetadataType,
-    RXingResultMetadataValue,
+    record_witness, symbol_pose, BarcodeFormat, Binarizer, DecodeHints, Exceptions, RXingResult,
+    RXingResultMetadataType, RXingResultMetadataValue, WitnessRecording,
 };
 
 use super::detector::MultiDetector;
@@ -50,21 +51,33 @@ impl MultipleBarcodeReader for QRCodeMultiReader {
         image: &mut crate::BinaryBitmap<B>,
         hints: &DecodeHints,
     ) -> Result<Vec<RXingResult>> {
+        let mut recording = WitnessRecording::new(hints);
         let mut results = Vec::new();
+        recording.binarization(image);
         let detectorRXingResults =
             MultiDetector::new(image.get_black_matrix()).detectMulti(hints)?;
         for detectorRXingResult in detectorRXingResults {
-            let mut proc = || -> Result<()> {
+            let proc = || -> Result<()> {
+                let detectorRXingResult =
+                    detectorRXingResult.sample_as_hinted(image.get_black_matrix(), hints)?;
+                recording.detection(
+                    BarcodeFormat::QR_CODE,
+                    detectorRXingResult.getPoints(),
+                    detectorRXingResult.getSamplingGrid(),
+                );
                 let decoderRXingResult = decoder::qrcode_decoder::decode_bitmatrix_with_hints(
                     detectorRXingResult.getBits(),
                     hints,
                 )?;
+                recording.codewords(decoderRXingResult.getCodewords());
                 let mut points = detectorRXingResult.getPoints().to_vec();
 
                 // If the code was mirrored: swap the bottom-left and the top-right points.
+                let mut mirrored = false;
                 if let Some(other) = decoderRXingResult.getOther() {
                     if let Some(oth) = other.downcast_ref::<QRCodeDecoderMetaData>() {
                         oth.applyMirroredCorrection(&mut points);
+                        mirrored = oth.isMirrored();
                     }
                 }
 
@@ -74,6 +87,34 @@ impl MultipleBarcodeReader for QRCodeMultiReader {
                     points.to_vec(),
                     BarcodeFormat::QR_CODE,
                 );
+                if let Some(content) = decoderRXingResult.getContent() {
+                    result.setContent(content.clone());
+                }
+
+                if mirrored {
+                    result.putMetadata(
+                        RXingResultMetadataType::IS_MIRRORED,
+                        RXingResultMetadataValue::IsMirrored(true),
+                    );
+                }
+
+                if let Some(samplingGrid) = detectorRXingResult.getSamplingGrid() {
+                    result.putMetadata(
+                        RXingResultMetadataType::SAMPLING_GRID,
+                        RXingResultMetadataValue::SamplingGrid(samplingGrid.clone()),
+                    );
+                }
+
+                if let Some(codewords) = decoderRXingResult.getCodewords() {
+                    result.putMetadata(
+                        RXingResultMetadataType::CODEWORDS,
+                        RXingResultMetadataValue::Codewords(codewords.clone()),
+                    );
+                }
+
+                decode_confidence::put_confidence(&mut result);
+                symbol_pose::put_pose(&mut result);
+
                 let byteSegments = decoderRXingResult.getByteSegments();
 
                 result.putMetadata(
@@ -87,22 +128,22 @@ impl MultipleBarcodeReader for QRCodeMultiReader {
                     RXingResultMetadataType::ERROR_CORRECTION_LEVEL,
                     RXingResultMetadataValue::ErrorCorrectionLevel(ecLevel.to_owned()),
                 );
+                result.putErrorCorrectionMetadata(
+                    decoderRXingResult.getErrorsCorrected(),
+                    decoderRXingResult.getErasures(),
+                );
                 // }
-                if decoderRXingResult.hasStructuredAppend() {
-                    result.putMetadata(
-                        RXingResultMetadataType::STRUCTURED_APPEND_SEQUENCE,
-                        RXingResultMetadataValue::StructuredAppendSequence(
-                            decoderRXingResult.getStructuredAppendSequenceNumber(),
-                        ),
-                    );
-                    result.putMetadata(
-                        RXingResultMetadataType::STRUCTURED_APPEND_PARITY,
-                        RXingResultMetadataValue::StructuredAppendParity(
-                            decoderRXingResult.getStructuredAppendParity(),
-                        ),
-                    );
-                }
-                results.push(result);
+                QRCodeReader::putStructuredAppendMetadata(&mut result, &decoderRXingResult);
+                result.putMetadata(
+                    RXingResultMetadataType::SYMBOLOGY_IDENTIFIER,
+                    RXingResultMetadataValue::SymbologyIdentifier(format!(
+                        "]Q{}",
+                        decoderRXingResult.getSymbologyModifier()
+                    )),
+                );
+                recording.decoded_bits(&mut result);
+                // each symbol is streamed before structured append joins them
+                results.push(record_witness(hints, image, result, &recording));
 
                 Ok(())
             };
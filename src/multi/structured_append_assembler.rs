@@ -0,0 +1,367 @@
+/*
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::collections::BTreeMap;
+
+use crate::common::Result;
+use crate::{
+    BarcodeFormat, Exceptions, RXingResult, RXingResultMetadataType, RXingResultMetadataValue,
+};
+
+/// The most symbols a QR Code structured append message can be split into
+const MAX_QR_CODE_COUNT: usize = 16;
+
+/// The most symbols an Aztec structured append message can be split into, one per letter
+const MAX_AZTEC_COUNT: usize = 26;
+
+/**
+ * Where a symbol belongs in a structured append message, as the QR Code and Aztec readers
+ * report it.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StructuredAppendPart {
+    /// The index of the symbol in the message, from 0
+    pub index: usize,
+    /// The number of symbols in the message
+    pub count: usize,
+    /// The parity of a QR Code message, or the message ID of an Aztec one, if it has one
+    pub id: Option<String>,
+}
+
+impl StructuredAppendPart {
+    /// Where `result` belongs, or `None` if it is not part of a structured append message
+    pub fn of(result: &RXingResult) -> Option<Self> {
+        let metadata = result.getRXingResultMetadata();
+        let Some(RXingResultMetadataValue::StructuredAppendSequence(index)) =
+            metadata.get(&RXingResultMetadataType::STRUCTURED_APPEND_SEQUENCE)
+        else {
+            return None;
+        };
+        let Some(RXingResultMetadataValue::StructuredAppendParity(count)) =
+            metadata.get(&RXingResultMetadataType::STRUCTURED_APPEND_PARITY)
+        else {
+            return None;
+        };
+        let id = match metadata.get(&RXingResultMetadataType::STRUCTURED_APPEND_ID) {
+            Some(RXingResultMetadataValue::StructuredAppendId(id)) => Some(id.clone()),
+            _ => None,
+        };
+        Some(Self {
+            index: usize::try_from(*index).ok()?,
+            count: usize::try_from(*count).ok()?,
+            id,
+        })
+    }
+}
+
+/**
+ * Joins the symbols of a QR Code or Aztec structured append message into the message they were
+ * split from.
+ *
+ * The symbols are added as they are read, in any order. A symbol whose format, number of symbols
+ * or parity (the message ID for Aztec) differs from those added before belongs to another message
+ * and is refused, as is one whose index is out of range. Once every part is there, `assemble`
+ * returns the message.
+ *
+ * ```ignore
+ * let mut assembler = StructuredAppendAssembler::new();
+ * for result in results {
+ *     assembler.add(result)?;
+ * }
+ * if assembler.is_complete() {
+ *     println!("{}", assembler.assemble()?.getText());
+ * } else {
+ *     println!("still missing {:?}", assembler.missing_parts());
+ * }
+ * ```
+ */
+#[derive(Clone, Default)]
+pub struct StructuredAppendAssembler {
+    format: Option<BarcodeFormat>,
+    count: usize,
+    id: Option<String>,
+    parts: BTreeMap<usize, RXingResult>,
+}
+
+impl StructuredAppendAssembler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /**
+     * Adds a symbol of the message. A symbol that was added before is ignored.
+     *
+     * # Errors
+     * `IllegalArgumentException` if `result` is not part of a structured append message, or of
+     * another message than the symbols added before, or if its index is taken by a different
+     * symbol
+     */
+    pub fn add(&mut self, result: RXingResult) -> Result<()> {
+        let format = result.getBarcodeFormat().clone();
+        if !matches!(format, BarcodeFormat::QR_CODE | BarcodeFormat::AZTEC) {
+            return Err(Exceptions::illegal_argument_with(format!(
+                "{format} has no structured append messages"
+            )));
+        }
+        let part = StructuredAppendPart::of(&result).ok_or_else(|| {
+            Exceptions::illegal_argument_with(
+                "the symbol is not part of a structured append message",
+            )
+        })?;
+        let max_count = if format == BarcodeFormat::QR_CODE {
+            MAX_QR_CODE_COUNT
+        } else {
+            MAX_AZTEC_COUNT
+        };
+        if part.count < 2 || part.count > max_count || part.index >= part.count {
+            return Err(Exceptions::illegal_argument_with(format!(
+                "invalid structured append sequence {} of {}",
+                part.index, part.count
+            )));
+        }
+
+        if let Some(message_format) = &self.format {
+            if *message_format != format || self.count != part.count || self.id != part.id {
+                return Err(Exceptions::illegal_argument_with(format!(
+                    "the symbol belongs to another message: {format} {} of {}, parity {:?}",
+                    part.index, part.count, part.id
+                )));
+            }
+        }
+        if let Some(added) = self.parts.get(&part.index) {
+            if added.getText() != result.getText() || added.getRawBytes() != result.getRawBytes() {
+                return Err(Exceptions::illegal_argument_with(format!(
+                    "a different symbol {} was added before",
+                    part.index
+                )));
+            }
+            return Ok(());
+        }
+
+        self.format = Some(format);
+        self.count = part.count;
+        self.id = part.id;
+        self.parts.insert(part.index, result);
+        Ok(())
+    }
+
+    /// The number of symbols of the message, once one is added
+    pub fn get_count(&self) -> Option<usize> {
+        self.format.as_ref().map(|_| self.count)
+    }
+
+    /// The indices of the symbols not added yet, empty if none was added
+    pub fn missing_parts(&self) -> Vec<usize> {
+        (0..self.count)
+            .filter(|index| !self.parts.contains_key(index))
+            .collect()
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.format.is_some() && self.parts.len() == self.count
+    }
+
+    /**
+     * The message, which holds the text and raw bytes of the symbols in order and has no points.
+     * Its parity metadata is the number of symbols, and its ID that of the symbols.
+     *
+     * # Errors
+     * `NotFoundException` naming the missing parts if the message is incomplete
+     */
+    pub fn assemble(&self) -> Result<RXingResult> {
+        let Some(format) = &self.format else {
+            return Err(Exceptions::not_found_with("no symbol was added"));
+        };
+        if !self.is_complete() {
+            return Err(Exceptions::not_found_with(format!(
+                "missing parts {:?} of {}",
+                self.missing_parts(),
+                self.count
+            )));
+        }
+
+        let mut message = join_parts(self.parts.values(), format.clone());
+        message.putMetadata(
+            RXingResultMetadataType::STRUCTURED_APPEND_PARITY,
+            RXingResultMetadataValue::StructuredAppendParity(self.count as i32),
+        );
+        if let Some(id) = &self.id {
+            message.putMetadata(
+                RXingResultMetadataType::STRUCTURED_APPEND_ID,
+                RXingResultMetadataValue::StructuredAppendId(id.clone()),
+            );
+        }
+        Ok(message)
+    }
+}
+
+/// The result of a whole message, with the text, raw bytes and byte segments of `parts` in order
+pub(crate) fn join_parts<'a>(
+    parts: impl IntoIterator<Item = &'a RXingResult>,
+    format: BarcodeFormat,
+) -> RXingResult {
+    let mut text = String::new();
+    let mut raw_bytes = Vec::new();
+    let mut byte_segment = Vec::new();
+    for result in parts {
+        text.push_str(result.getText());
+        raw_bytes.extend_from_slice(result.getRawBytes());
+        if let Some(RXingResultMetadataValue::ByteSegments(segments)) = result
+            .getRXingResultMetadata()
+            .get(&RXingResultMetadataType::BYTE_SEGMENTS)
+        {
+            for segment in segments {
+                byte_segment.extend_from_slice(segment);
+            }
+        }
+    }
+
+    let mut message = RXingResult::new(&text, raw_bytes, Vec::new(), format);
+    if !byte_segment.is_empty() {
+        message.putMetadata(
+            RXingResultMetadataType::BYTE_SEGMENTS,
+            RXingResultMetadataValue::ByteSegments(vec![byte_segment]),
+        );
+    }
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        BarcodeFormat, Exceptions, RXingResult, RXingResultMetadataType, RXingResultMetadataValue,
+    };
+
+    use super::StructuredAppendAssembler;
+
+    fn part(text: &str, index: i32, count: i32, id: &str) -> RXingResult {
+        let mut result = RXingResult::new(
+            text,
+            text.as_bytes().to_vec(),
+            Vec::new(),
+            BarcodeFormat::QR_CODE,
+        );
+        result.putMetadata(
+            RXingResultMetadataType::STRUCTURED_APPEND_SEQUENCE,
+            RXingResultMetadataValue::StructuredAppendSequence(index),
+        );
+        result.putMetadata(
+            RXingResultMetadataType::STRUCTURED_APPEND_PARITY,
+            RXingResultMetadataValue::StructuredAppendParity(count),
+        );
+        result.putMetadata(
+            RXingResultMetadataType::STRUCTURED_APPEND_ID,
+            RXingResultMetadataValue::StructuredAppendId(id.to_owned()),
+        );
+        result
+    }
+
+    #[test]
+    fn test_assemble() {
+        let mut assembler = StructuredAppendAssembler::new();
+        assert_eq!(None, assembler.get_count());
+        assert!(assembler.assemble().is_err());
+
+        assembler.add(part("C", 2, 3, "7")).expect("add");
+        assert_eq!(Some(3), assembler.get_count());
+        assert_eq!(vec![0, 1], assembler.missing_parts());
+        assert!(matches!(
+            assembler.assemble(),
+            Err(Exceptions::NotFoundException(message)) if message.contains("[0, 1]")
+        ));
+
+        assembler.add(part("A", 0, 3, "7")).expect("add");
+        // a symbol read twice
+        assembler.add(part("A", 0, 3, "7")).expect("add");
+        assert!(!assembler.is_complete());
+        assembler.add(part("B", 1, 3, "7")).expect("add");
+        assert!(assembler.is_complete());
+
+        let message = assembler.assemble().expect("assemble");
+        assert_eq!("ABC", message.getText());
+        assert_eq!(b"ABC".to_vec(), *message.getRawBytes());
+        assert_eq!(BarcodeFormat::QR_CODE, *message.getBarcodeFormat());
+        assert_eq!(
+            Some(&RXingResultMetadataValue::StructuredAppendId(
+                "7".to_owned()
+            )),
+            message
+                .getRXingResultMetadata()
+                .get(&RXingResultMetadataType::STRUCTURED_APPEND_ID)
+        );
+    }
+
+    #[test]
+    fn test_validation() {
+        let mut assembler = StructuredAppendAssembler::new();
+        assembler.add(part("A", 0, 2, "7")).expect("add");
+
+        // another parity, count or index, a different symbol for the same index
+        assert!(assembler.add(part("B", 1, 2, "8")).is_err());
+        assert!(assembler.add(part("B", 1, 3, "7")).is_err());
+        assert!(assembler.add(part("B", 2, 2, "7")).is_err());
+        assert!(assembler.add(part("X", 0, 2, "7")).is_err());
+        assert!(assembler.add(part("B", -1, 2, "7")).is_err());
+        assert!(StructuredAppendAssembler::new()
+            .add(part("A", 0, 17, "7"))
+            .is_err());
+        assert!(StructuredAppendAssembler::new()
+            .add(part("A", 0, 1, "7"))
+            .is_err());
+
+        // not a part, or of a format without structured append
+        assert!(assembler
+            .add(RXingResult::new(
+                "B",
+                Vec::new(),
+                Vec::new(),
+                BarcodeFormat::QR_CODE
+            ))
+            .is_err());
+        let mut code_128 = RXingResult::new("B", Vec::new(), Vec::new(), BarcodeFormat::CODE_128);
+        code_128.putAllMetadata(part("B", 1, 2, "7").getRXingResultMetadata().clone());
+        assert!(StructuredAppendAssembler::new().add(code_128).is_err());
+
+        assert_eq!(vec![1], assembler.missing_parts());
+        assembler.add(part("B", 1, 2, "7")).expect("add");
+        assert_eq!("AB", assembler.assemble().expect("assemble").getText());
+    }
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn test_qr_code_images() {
+        let expected = std::fs::read_to_string("test_resources/blackbox/cpp/qrcode-7/01.txt")
+            .expect("read expected text");
+        let mut assembler = StructuredAppendAssembler::new();
+        for part in [3, 1, 4, 2] {
+            let result = crate::helpers::detect_in_file(
+                &format!("test_resources/blackbox/cpp/qrcode-7/01-0{part}.png"),
+                Some(BarcodeFormat::QR_CODE),
+            )
+            .expect("decode");
+            assembler.add(result).expect("add");
+        }
+        let message = assembler.assemble().expect("assemble");
+        assert_eq!(expected, message.getText());
+        // the parity of the message, as zxing-cpp reports it
+        assert_eq!(
+            Some(&RXingResultMetadataValue::StructuredAppendId(
+                "95".to_owned()
+            )),
+            message
+                .getRXingResultMetadata()
+                .get(&RXingResultMetadataType::STRUCTURED_APPEND_ID)
+        );
+    }
+}
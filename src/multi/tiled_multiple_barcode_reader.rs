@@ -0,0 +1,255 @@
+/*
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use crate::{
+    common::Result, Binarizer, BinaryBitmap, DecodeHints, Exceptions, MultiFormatReader,
+    RXingResult,
+};
+
+use super::{GenericMultipleBarcodeReader, MultipleBarcodeReader};
+
+/**
+ * Scans an image too large to binarize at once a tile at a time, with a delegate reading each
+ * tile. Tiles overlap so that a barcode cut by the edge of one tile lies whole in the next, as
+ * long as it is no larger than the overlap; a barcode found in several tiles is reported once,
+ * with the points of the tile it was read in translated back onto the image.
+ *
+ * A tile is a crop of the image, so a `TiledLuminanceSource` reads the pixels of a single tile
+ * into memory at a time, and the binarizer of a tile holds no more than the tile.
+ */
+pub struct TiledMultipleBarcodeReader<T: MultipleBarcodeReader> {
+    delegate: T,
+    tile_size: usize,
+    overlap: usize,
+}
+
+impl Default for TiledMultipleBarcodeReader<GenericMultipleBarcodeReader<MultiFormatReader>> {
+    fn default() -> Self {
+        Self::new(GenericMultipleBarcodeReader::default())
+    }
+}
+
+impl<T: MultipleBarcodeReader> MultipleBarcodeReader for TiledMultipleBarcodeReader<T> {
+    fn decode_multiple<B: Binarizer>(
+        &mut self,
+        image: &mut BinaryBitmap<B>,
+    ) -> Result<Vec<RXingResult>> {
+        self.decode_multiple_with_hints(image, &DecodeHints::default())
+    }
+
+    fn decode_multiple_with_hints<B: Binarizer>(
+        &mut self,
+        image: &mut BinaryBitmap<B>,
+        hints: &DecodeHints,
+    ) -> Result<Vec<RXingResult>> {
+        let mut results: Vec<RXingResult> = Vec::new();
+        for top in Self::tile_offsets(image.get_height(), self.tile_size, self.overlap) {
+            for left in Self::tile_offsets(image.get_width(), self.tile_size, self.overlap) {
+                hints.check_cancelled()?;
+                let width = self.tile_size.min(image.get_width() - left);
+                let height = self.tile_size.min(image.get_height() - top);
+                let found = match self
+                    .delegate
+                    .decode_multiple_with_hints(&mut image.crop(left, top, width, height), hints)
+                {
+                    Ok(found) => found,
+                    Err(Exceptions::NotFoundException(_)) => continue,
+                    Err(e) => return Err(e),
+                };
+                for result in found {
+                    let result = GenericMultipleBarcodeReader::<MultiFormatReader>::translatePoints(
+                        result,
+                        left as u32,
+                        top as u32,
+                    );
+                    self.merge(&mut results, result);
+                }
+            }
+        }
+
+        if results.is_empty() {
+            return Err(Exceptions::NOT_FOUND);
+        }
+        Ok(results)
+    }
+}
+
+impl<T: MultipleBarcodeReader> TiledMultipleBarcodeReader<T> {
+    pub const DEFAULT_TILE_SIZE: usize = 1024;
+    pub const DEFAULT_OVERLAP: usize = 256;
+
+    pub fn new(delegate: T) -> Self {
+        Self {
+            delegate,
+            tile_size: Self::DEFAULT_TILE_SIZE,
+            overlap: Self::DEFAULT_OVERLAP,
+        }
+    }
+
+    /**
+     * Scans tiles of `tile_size` pixels square, each overlapping its neighbours by `overlap`
+     * pixels, which should be at least the size of the largest barcode expected.
+     *
+     * # Panics
+     * If `overlap` is not less than `tile_size`
+     */
+    pub fn with_tile_size(mut self, tile_size: usize, overlap: usize) -> Self {
+        assert!(
+            overlap < tile_size,
+            "an overlap of {overlap} leaves no step between tiles of {tile_size}"
+        );
+        self.tile_size = tile_size;
+        self.overlap = overlap;
+        self
+    }
+
+    pub fn tile_size(&self) -> usize {
+        self.tile_size
+    }
+
+    pub fn overlap(&self) -> usize {
+        self.overlap
+    }
+
+    /// Where the tiles across `length` pixels start, the last one flush with the far edge
+    fn tile_offsets(length: usize, tile_size: usize, overlap: usize) -> Vec<usize> {
+        if length <= tile_size {
+            return vec![0];
+        }
+        let mut offsets: Vec<usize> = (0..length - tile_size)
+            .step_by(tile_size - overlap)
+            .collect();
+        offsets.push(length - tile_size);
+        offsets
+    }
+
+    /// Adds `result` to `results` unless it was already read in a neighbouring tile, in which
+    /// case the reading with the larger extent, the one less cut by a tile edge, is kept
+    fn merge(&self, results: &mut Vec<RXingResult>, result: RXingResult) {
+        let margin = (self.overlap / 2) as f32;
+        let bounds = bounding_box(&result);
+        let duplicate = results.iter().position(|found| {
+            found.getBarcodeFormat() == result.getBarcodeFormat()
+                && found.getText() == result.getText()
+                && match (bounding_box(found), bounds) {
+                    (Some(a), Some(b)) => {
+                        a[0] - margin <= b[2]
+                            && b[0] - margin <= a[2]
+                            && a[1] - margin <= b[3]
+                            && b[1] - margin <= a[3]
+                    }
+                    // without points there is nothing to tell two symbols apart
+                    _ => true,
+                }
+        });
+        match duplicate {
+            Some(i) if extent(bounds) > extent(bounding_box(&results[i])) => results[i] = result,
+            Some(_) => {}
+            None => results.push(result),
+        }
+    }
+}
+
+/// The left, top, right and bottom of the points of `result`
+fn bounding_box(result: &RXingResult) -> Option<[f32; 4]> {
+    let points = result.getPoints();
+    let first = points.first()?;
+    Some(points.iter().fold(
+        [first.x, first.y, first.x, first.y],
+        |[left, top, right, bottom], p| {
+            [left.min(p.x), top.min(p.y), right.max(p.x), bottom.max(p.y)]
+        },
+    ))
+}
+
+fn extent(bounds: Option<[f32; 4]>) -> f32 {
+    bounds.map_or(0.0, |[left, top, right, bottom]| {
+        (right - left) + (bottom - top)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        common::HybridBinarizer,
+        multi::{GenericMultipleBarcodeReader, MultipleBarcodeReader},
+        qrcode::QRCodeWriter,
+        BarcodeFormat, BinaryBitmap, Exceptions, MultiFormatReader, RawLumaStore,
+        TiledLuminanceSource, Writer,
+    };
+
+    use super::TiledMultipleBarcodeReader;
+
+    const WIDTH: usize = 1000;
+    const HEIGHT: usize = 700;
+
+    /// Draws a QR code of `text` with its top left module at `left`, `top`
+    fn draw(luma: &mut [u8], text: &str, left: usize, top: usize) {
+        let matrix = QRCodeWriter
+            .encode(text, &BarcodeFormat::QR_CODE, 0, 0)
+            .unwrap();
+        for y in 0..matrix.getHeight() as usize * 4 {
+            for x in 0..matrix.getWidth() as usize * 4 {
+                if matrix.get(x as u32 / 4, y as u32 / 4) {
+                    luma[(top + y) * WIDTH + left + x] = 0;
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_tile_offsets() {
+        type Reader = TiledMultipleBarcodeReader<GenericMultipleBarcodeReader<MultiFormatReader>>;
+        assert_eq!(vec![0], Reader::tile_offsets(300, 400, 100));
+        assert_eq!(vec![0, 300, 600], Reader::tile_offsets(1000, 400, 100));
+        assert_eq!(vec![0, 300, 500], Reader::tile_offsets(900, 400, 100));
+    }
+
+    #[test]
+    fn test_decode_across_tiles() {
+        let mut luma = vec![255; WIDTH * HEIGHT];
+        // one code in the first tile only, one cut by its right and bottom edges and so read
+        // whole in the next tiles, and one in the last tile, which is flush with the edges
+        draw(&mut luma, "first", 40, 40);
+        draw(&mut luma, "straddling", 400, 380);
+        draw(&mut luma, "last", 880, 580);
+
+        let source = TiledLuminanceSource::new(RawLumaStore::new(luma, WIDTH, HEIGHT).unwrap());
+        let mut image = BinaryBitmap::new(HybridBinarizer::new(source));
+        let mut reader = TiledMultipleBarcodeReader::default().with_tile_size(450, 150);
+        let mut results = reader.decode_multiple(&mut image).unwrap();
+        results.sort_by_key(|r| r.getText().to_owned());
+
+        let texts: Vec<&str> = results.iter().map(|r| r.getText()).collect();
+        assert_eq!(vec!["first", "last", "straddling"], texts);
+        // points are in the coordinates of the whole image, not of the tile
+        for (result, (left, top)) in
+            results
+                .iter()
+                .zip([(40.0, 40.0), (880.0, 580.0), (400.0, 380.0)])
+        {
+            assert!(result
+                .getPoints()
+                .iter()
+                .all(|p| p.x > left && p.x < left + 120.0 && p.y > top && p.y < top + 120.0));
+        }
+
+        let blank = vec![255; WIDTH * HEIGHT];
+        let source = TiledLuminanceSource::new(RawLumaStore::new(blank, WIDTH, HEIGHT).unwrap());
+        assert!(matches!(
+            reader.decode_multiple(&mut BinaryBitmap::new(HybridBinarizer::new(source))),
+            Err(Exceptions::NotFoundException(_))
+        ));
+    }
+}
@@ -16,22 +16,30 @@
 
 use std::collections::HashSet;
 
-use crate::common::Result;
+use crate::common::{HybridBinarizer, Result};
 #[cfg(feature = "experimental_features")]
 use crate::oned::cpp::ODReader;
+use crate::preprocess::{Preprocessor, RETRY_CHAIN};
 use crate::qrcode::cpp_port::QrReader;
 use crate::{
-    aztec::AztecReader, datamatrix::DataMatrixReader, maxicode::MaxiCodeReader,
-    oned::MultiFormatOneDReader, pdf417::PDF417Reader, qrcode::QRCodeReader, BarcodeFormat,
-    Binarizer, BinaryBitmap, Exceptions, RXingResult, Reader,
+    aztec::AztecReader,
+    datamatrix::DataMatrixReader,
+    maxicode::MaxiCodeReader,
+    oned::{MultiFormatOneDReader, PharmacodeReader},
+    pdf417::PDF417Reader,
+    qrcode::QRCodeReader,
+    BarcodeFormat, Binarizer, BinaryBitmap, Exceptions, RXingResult, Reader, ReaderOrder,
+    ReaderRegistry,
 };
-use crate::{DecodeHints, ONE_D_FORMATS};
+use crate::{decode_warning, ordered_formats, DecodeHints, ONE_D_FORMATS};
 
 /**
  * MultiFormatReader is a convenience class and the main entry point into the library for most uses.
  * By default it attempts to decode all barcode formats that the library supports. Optionally, you
  * can provide a hints object to request different behavior, for example only decoding QR codes.
  *
+ * Readers for symbologies that are not built in can be added with a [`ReaderRegistry`].
+ *
  * @author Sean Owen
  * @author dswitkin@google.com (Daniel Switkin)
  */
@@ -39,8 +47,10 @@ use crate::{DecodeHints, ONE_D_FORMATS};
 pub struct MultiFormatReader {
     hints: DecodeHints,
     possible_formats: HashSet<BarcodeFormat>,
+    format_order: Vec<BarcodeFormat>,
     try_harder: bool,
     one_d_reader: MultiFormatOneDReader,
+    registry: ReaderRegistry,
 }
 
 impl Reader for MultiFormatReader {
@@ -81,6 +91,22 @@ impl Reader for MultiFormatReader {
 }
 
 impl MultiFormatReader {
+    /// Creates a reader that also tries the custom readers of `registry`
+    pub fn with_registry(registry: ReaderRegistry) -> Self {
+        Self {
+            registry,
+            ..Default::default()
+        }
+    }
+
+    pub fn registry(&self) -> &ReaderRegistry {
+        &self.registry
+    }
+
+    pub fn registry_mut(&mut self) -> &mut ReaderRegistry {
+        &mut self.registry
+    }
+
     /**
      * Decode an image using the state set up by calling setHints() previously. Continuous scan
      * clients will get a <b>large</b> speed increase by using this instead of decode().
@@ -117,6 +143,7 @@ impl MultiFormatReader {
         } else {
             HashSet::new()
         };
+        self.format_order = ordered_formats(&self.possible_formats, hints);
         self.one_d_reader = MultiFormatOneDReader::new(hints);
     }
 
@@ -125,9 +152,12 @@ impl MultiFormatReader {
         image: &mut BinaryBitmap<B>,
     ) -> Result<RXingResult> {
         let res = self.decode_formats(image);
-        if res.is_ok() {
-            return res;
+        if let Ok(mut r) = res {
+            decode_warning::check_contrast(image.get_source(), &mut r);
+            return Ok(r);
         }
+        // a reader that was cancelled fails like one that found nothing
+        self.hints.check_cancelled()?;
         if matches!(self.hints.AlsoInverted, Some(true)) {
             // Calling all readers again with inverted image
             image.get_black_matrix_mut().flip_self();
@@ -139,26 +169,47 @@ impl MultiFormatReader {
                     crate::RXingResultMetadataType::IS_INVERTED,
                     crate::RXingResultMetadataValue::IsInverted(true),
                 );
+                decode_warning::check_contrast(image.get_source(), &mut r);
                 return Ok(r);
             }
             // if res.is_ok() {
             //     return res;
             // }
+            self.hints.check_cancelled()?;
+        }
+        if matches!(self.hints.TryPreprocessing, Some(true)) {
+            // Calling all readers again with sharpened, denoised and closed copies
+            let source = image.get_source();
+            for filters in RETRY_CHAIN {
+                let filtered = Preprocessor::from(*filters).apply(source);
+                let mut bitmap = BinaryBitmap::new(HybridBinarizer::new(filtered));
+                if let Ok(mut r) = self.decode_formats(&mut bitmap) {
+                    decode_warning::check_contrast(source, &mut r);
+                    return Ok(r);
+                }
+                self.hints.check_cancelled()?;
+            }
         }
         Err(Exceptions::NOT_FOUND)
     }
 
     fn decode_formats<B: Binarizer>(&mut self, image: &mut BinaryBitmap<B>) -> Result<RXingResult> {
+        if let Some(res) = self.decode_custom(ReaderOrder::First, image) {
+            return Ok(res);
+        }
+
         if !self.possible_formats.is_empty() {
             let one_d = ONE_D_FORMATS
                 .iter()
                 .any(|e| self.possible_formats.contains(e));
             if one_d && !self.try_harder {
+                self.hints.check_cancelled()?;
                 if let Ok(res) = self.one_d_reader.decode_with_hints(image, &self.hints) {
                     return Ok(res);
                 }
             }
-            for possible_format in self.possible_formats.iter() {
+            for possible_format in self.format_order.iter() {
+                self.hints.check_cancelled()?;
                 let res = match possible_format {
                     BarcodeFormat::QR_CODE => {
                         let cpp = QrReader.decode_with_hints(image, &self.hints);
@@ -180,6 +231,9 @@ impl MultiFormatReader {
                     BarcodeFormat::MAXICODE => {
                         MaxiCodeReader::default().decode_with_hints(image, &self.hints)
                     }
+                    BarcodeFormat::PHARMACODE => {
+                        PharmacodeReader.decode_with_hints(image, &self.hints)
+                    }
                     #[cfg(feature = "experimental_features")]
                     BarcodeFormat::DXFilmEdge => {
                         ODReader::new(&self.hints).decode_with_hints(image, &self.hints)
@@ -191,32 +245,40 @@ impl MultiFormatReader {
                 }
             }
             if one_d && self.try_harder {
+                self.hints.check_cancelled()?;
                 if let Ok(res) = self.one_d_reader.decode_with_hints(image, &self.hints) {
                     return Ok(res);
                 }
             }
         } else {
             if !self.try_harder {
+                self.hints.check_cancelled()?;
                 if let Ok(res) = self.one_d_reader.decode_with_hints(image, &self.hints) {
                     return Ok(res);
                 }
             }
 
+            self.hints.check_cancelled()?;
             if let Ok(res) = QrReader.decode_with_hints(image, &self.hints) {
                 return Ok(res);
             }
+            self.hints.check_cancelled()?;
             if let Ok(res) = QRCodeReader.decode_with_hints(image, &self.hints) {
                 return Ok(res);
             }
+            self.hints.check_cancelled()?;
             if let Ok(res) = DataMatrixReader.decode_with_hints(image, &self.hints) {
                 return Ok(res);
             }
+            self.hints.check_cancelled()?;
             if let Ok(res) = AztecReader.decode_with_hints(image, &self.hints) {
                 return Ok(res);
             }
+            self.hints.check_cancelled()?;
             if let Ok(res) = PDF417Reader.decode_with_hints(image, &self.hints) {
                 return Ok(res);
             }
+            self.hints.check_cancelled()?;
             if let Ok(res) = MaxiCodeReader::default().decode_with_hints(image, &self.hints) {
                 return Ok(res);
             }
@@ -226,12 +288,34 @@ impl MultiFormatReader {
             }
 
             if self.try_harder {
+                self.hints.check_cancelled()?;
                 if let Ok(res) = self.one_d_reader.decode_with_hints(image, &self.hints) {
                     return Ok(res);
                 }
             }
         }
 
+        self.hints.check_cancelled()?;
+        if let Some(res) = self.decode_custom(ReaderOrder::Last, image) {
+            return Ok(res);
+        }
+
         Err(Exceptions::UNSUPPORTED_OPERATION)
     }
+
+    fn decode_custom<B: Binarizer>(
+        &self,
+        order: ReaderOrder,
+        image: &mut BinaryBitmap<B>,
+    ) -> Option<RXingResult> {
+        if self.registry.is_empty() {
+            return None;
+        }
+        self.registry.decode(
+            order,
+            image.get_black_matrix(),
+            &self.hints,
+            &self.possible_formats,
+        )
+    }
 }
@@ -18,9 +18,11 @@ use crate::{
     aztec::AztecWriter,
     common::Result,
     datamatrix::DataMatrixWriter,
+    maxicode::MaxiCodeWriter,
     oned::{
+        rss::{expanded::RSSExpandedWriter, RSS14Writer},
         CodaBarWriter, Code128Writer, Code39Writer, Code93Writer, EAN13Writer, EAN8Writer,
-        ITFWriter, TelepenWriter, UPCAWriter, UPCEWriter,
+        ITFWriter, MSIWriter, PharmacodeWriter, TelepenWriter, UPCAWriter, UPCEWriter,
     },
     pdf417::PDF417Writer,
     qrcode::QRCodeWriter,
@@ -60,7 +62,7 @@ impl Writer for MultiFormatWriter {
             BarcodeFormat::UPC_E => Box::<UPCEWriter>::default(),
             BarcodeFormat::EAN_13 => Box::<EAN13Writer>::default(),
             BarcodeFormat::UPC_A => Box::<UPCAWriter>::default(),
-            BarcodeFormat::QR_CODE => Box::<QRCodeWriter>::default(),
+            BarcodeFormat::QR_CODE | BarcodeFormat::MICRO_QR_CODE => Box::<QRCodeWriter>::default(),
             BarcodeFormat::CODE_39 => Box::<Code39Writer>::default(),
             BarcodeFormat::CODE_93 => Box::<Code93Writer>::default(),
             BarcodeFormat::CODE_128 => Box::<Code128Writer>::default(),
@@ -70,6 +72,11 @@ impl Writer for MultiFormatWriter {
             BarcodeFormat::DATA_MATRIX => Box::<DataMatrixWriter>::default(),
             BarcodeFormat::TELEPEN => Box::<TelepenWriter>::default(),
             BarcodeFormat::AZTEC => Box::<AztecWriter>::default(),
+            BarcodeFormat::MAXICODE => Box::<MaxiCodeWriter>::default(),
+            BarcodeFormat::PHARMACODE => Box::<PharmacodeWriter>::default(),
+            BarcodeFormat::MSI => Box::<MSIWriter>::default(),
+            BarcodeFormat::RSS_14 => Box::<RSS14Writer>::default(),
+            BarcodeFormat::RSS_EXPANDED => Box::<RSSExpandedWriter>::default(),
             _ => {
                 return Err(Exceptions::illegal_argument_with(format!(
                     "No encoder available for format {format:?}"
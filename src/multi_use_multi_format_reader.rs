@@ -18,14 +18,18 @@ use std::collections::HashSet;
 
 use crate::common::Result;
 use crate::qrcode::cpp_port::QrReader;
-use crate::DecodeHints;
 use crate::{
-    aztec::AztecReader, datamatrix::DataMatrixReader, maxicode::MaxiCodeReader,
-    oned::MultiFormatOneDReader, pdf417::PDF417Reader, qrcode::QRCodeReader, BarcodeFormat,
-    Binarizer, BinaryBitmap, Exceptions, RXingResult, Reader,
+    aztec::AztecReader,
+    datamatrix::DataMatrixReader,
+    maxicode::MaxiCodeReader,
+    oned::{MultiFormatOneDReader, PharmacodeReader},
+    pdf417::PDF417Reader,
+    qrcode::QRCodeReader,
+    BarcodeFormat, Binarizer, BinaryBitmap, Exceptions, RXingResult, Reader,
 };
+use crate::{decode_warning, DecodeHints};
 
-pub(crate) const ONE_D_FORMATS: [BarcodeFormat; 12] = [
+pub(crate) const ONE_D_FORMATS: [BarcodeFormat; 13] = [
     BarcodeFormat::UPC_A,
     BarcodeFormat::UPC_E,
     BarcodeFormat::EAN_13,
@@ -38,8 +42,42 @@ pub(crate) const ONE_D_FORMATS: [BarcodeFormat; 12] = [
     BarcodeFormat::RSS_14,
     BarcodeFormat::RSS_EXPANDED,
     BarcodeFormat::TELEPEN,
+    BarcodeFormat::MSI,
 ];
 
+// The order the 2D readers are tried in when no formats are given, see `ordered_formats`, and
+// Pharmacode, which has a reader of its own as it is not tried without being asked for
+const TWO_D_FORMATS: [BarcodeFormat; 9] = [
+    BarcodeFormat::QR_CODE,
+    BarcodeFormat::MICRO_QR_CODE,
+    BarcodeFormat::RECTANGULAR_MICRO_QR_CODE,
+    BarcodeFormat::DATA_MATRIX,
+    BarcodeFormat::AZTEC,
+    BarcodeFormat::PDF_417,
+    BarcodeFormat::MAXICODE,
+    BarcodeFormat::DXFilmEdge,
+    BarcodeFormat::PHARMACODE,
+];
+
+/**
+ * The order to try `possible_formats` in. That is the iteration order of the set, unless
+ * the `Deterministic` hint is set, which fixes it to the order of `TWO_D_FORMATS`. Formats
+ * without a 2D reader are left out in that case, the loop over the formats skips them anyway.
+ */
+pub(crate) fn ordered_formats(
+    possible_formats: &HashSet<BarcodeFormat>,
+    hints: &DecodeHints,
+) -> Vec<BarcodeFormat> {
+    if matches!(hints.Deterministic, Some(true)) {
+        TWO_D_FORMATS
+            .into_iter()
+            .filter(|format| possible_formats.contains(format))
+            .collect()
+    } else {
+        possible_formats.iter().cloned().collect()
+    }
+}
+
 /**
  * MultiFormatReader is a convenience class and the main entry point into the library for most uses.
  * By default it attempts to decode all barcode formats that the library supports. Optionally, you
@@ -52,6 +90,7 @@ pub(crate) const ONE_D_FORMATS: [BarcodeFormat; 12] = [
 pub struct MultiUseMultiFormatReader {
     hints: DecodeHints,
     possible_formats: HashSet<BarcodeFormat>,
+    format_order: Vec<BarcodeFormat>,
     try_harder: bool,
     one_d_reader: MultiFormatOneDReader,
     qr_code_reader: QRCodeReader,
@@ -141,6 +180,7 @@ impl MultiUseMultiFormatReader {
         } else {
             HashSet::new()
         };
+        self.format_order = ordered_formats(&self.possible_formats, hints);
         self.one_d_reader = MultiFormatOneDReader::new(hints);
     }
 
@@ -149,8 +189,9 @@ impl MultiUseMultiFormatReader {
         image: &mut BinaryBitmap<B>,
     ) -> Result<RXingResult> {
         let res = self.decode_formats(image);
-        if res.is_ok() {
-            return res;
+        if let Ok(mut r) = res {
+            decode_warning::check_contrast(image.get_source(), &mut r);
+            return Ok(r);
         }
         if matches!(self.hints.AlsoInverted, Some(true)) {
             // Calling all readers again with inverted image
@@ -162,6 +203,7 @@ impl MultiUseMultiFormatReader {
                     crate::RXingResultMetadataType::IS_INVERTED,
                     crate::RXingResultMetadataValue::IsInverted(true),
                 );
+                decode_warning::check_contrast(image.get_source(), &mut r);
                 return Ok(r);
             }
             // if res.is_ok() {
@@ -193,7 +235,7 @@ impl MultiUseMultiFormatReader {
                     return Ok(res);
                 }
             }
-            for possible_format in self.possible_formats.iter() {
+            for possible_format in self.format_order.iter() {
                 let res = match possible_format {
                     BarcodeFormat::QR_CODE => {
                         let a = self.cpp_qrcode_reader.decode_with_hints(image, &self.hints);
@@ -203,7 +245,7 @@ impl MultiUseMultiFormatReader {
                             self.qr_code_reader.decode_with_hints(image, &self.hints)
                         }
                     }
-                    BarcodeFormat::MICRO_QR_CODE => {
+                    BarcodeFormat::MICRO_QR_CODE | BarcodeFormat::RECTANGULAR_MICRO_QR_CODE => {
                         self.cpp_qrcode_reader.decode_with_hints(image, &self.hints)
                     }
                     BarcodeFormat::DATA_MATRIX => self
@@ -216,6 +258,9 @@ impl MultiUseMultiFormatReader {
                     BarcodeFormat::MAXICODE => {
                         self.maxicode_reader.decode_with_hints(image, &self.hints)
                     }
+                    BarcodeFormat::PHARMACODE => {
+                        PharmacodeReader.decode_with_hints(image, &self.hints)
+                    }
                     _ => Err(Exceptions::UNSUPPORTED_OPERATION),
                 };
                 if res.is_ok() {
@@ -265,3 +310,37 @@ impl MultiUseMultiFormatReader {
         Err(Exceptions::UNSUPPORTED_OPERATION)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::ordered_formats;
+    use crate::{BarcodeFormat, DecodeHintValue, DecodeHints};
+
+    #[test]
+    fn test_deterministic_format_order() {
+        let possible_formats = HashSet::from([
+            BarcodeFormat::PDF_417,
+            BarcodeFormat::CODE_128,
+            BarcodeFormat::AZTEC,
+            BarcodeFormat::QR_CODE,
+            BarcodeFormat::DATA_MATRIX,
+        ]);
+        let hints = DecodeHints::default().with(DecodeHintValue::Deterministic(true));
+
+        assert_eq!(
+            vec![
+                BarcodeFormat::QR_CODE,
+                BarcodeFormat::DATA_MATRIX,
+                BarcodeFormat::AZTEC,
+                BarcodeFormat::PDF_417
+            ],
+            ordered_formats(&possible_formats, &hints)
+        );
+
+        // without the hint every format is tried, in whatever order the set has
+        let unordered = ordered_formats(&possible_formats, &DecodeHints::default());
+        assert_eq!(possible_formats, unordered.into_iter().collect());
+    }
+}
@@ -0,0 +1,218 @@
+/*
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use crate::{common::Result, Exceptions};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/**
+ * How a 1D reader treats the last data character of a symbology whose check digit is
+ * optional: Code 39 (mod 43), Codabar (mod 16), ITF (mod 10, as in GS1 keys) and MSI Plessey.
+ *
+ * Whether a label carries a check digit is agreed between whoever prints it and whoever
+ * reads it, so the symbol itself cannot tell. Validating rejects corrupted reads of labels
+ * that have one; ignoring it reads legacy labels that do not.
+ *
+ * Set per format with the `Code39CheckDigit`, `CodabarCheckDigit`, `ITFCheckDigit` and
+ * `MSICheckDigit` hints. The policy for MSI applies to all of its check digits, whose kind is
+ * set with the `MSIChecksum` hint. Code 11, whose check digits are also optional, is not read
+ * by this crate.
+ */
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum CheckDigitPolicy {
+    /// The last character is data and nothing is checked, the default
+    #[default]
+    Ignore,
+    /// The last character must be a valid check digit, and is kept in the text
+    Validate,
+    /// The last character is a check digit and is removed from the text without checking it,
+    /// for labels printed with a wrong one
+    Strip,
+    /// The last character must be a valid check digit, and is removed from the text
+    Require,
+}
+
+impl CheckDigitPolicy {
+    /// Whether the check digit must be valid for the symbol to be read
+    pub fn validates(&self) -> bool {
+        matches!(self, CheckDigitPolicy::Validate | CheckDigitPolicy::Require)
+    }
+
+    /// Whether the check digit is removed from the text
+    pub fn strips(&self) -> bool {
+        matches!(self, CheckDigitPolicy::Strip | CheckDigitPolicy::Require)
+    }
+
+    /**
+     * Applies the policy to the data characters of a symbol, the check digit being the last.
+     *
+     * @param length the number of data characters
+     * @param is_valid whether the check digit is valid, only called if the policy validates
+     * @return the number of data characters to return, or `NOT_FOUND` if the check digit is
+     *  invalid or no data would be left
+     */
+    pub(crate) fn apply<F>(&self, length: usize, is_valid: F) -> Result<usize>
+    where
+        F: FnOnce() -> bool,
+    {
+        if *self == CheckDigitPolicy::Ignore {
+            return Ok(length);
+        }
+        if length < 2 || (self.validates() && !is_valid()) {
+            return Err(Exceptions::NOT_FOUND);
+        }
+        Ok(if self.strips() { length - 1 } else { length })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        common::BitArray,
+        oned::{
+            CodaBarReader, CodaBarWriter, Code39Reader, Code39Writer, ITFReader, ITFWriter,
+            OneDReader,
+        },
+        BarcodeFormat, DecodeHints, Writer,
+    };
+
+    use super::CheckDigitPolicy::{self, *};
+
+    fn decode<R: OneDReader, W: Writer>(
+        reader: &mut R,
+        writer: W,
+        format: BarcodeFormat,
+        contents: &str,
+        hints: &DecodeHints,
+    ) -> Option<String> {
+        let matrix = writer.encode(contents, &format, 0, 0).expect("encode");
+        let mut row = BitArray::with_size(matrix.getWidth() as usize);
+        for x in 0..matrix.getWidth() {
+            if matrix.get(x, 0) {
+                row.set(x as usize);
+            }
+        }
+        reader
+            .decode_row(0, &row, hints)
+            .ok()
+            .map(|result| result.getText().to_owned())
+    }
+
+    fn code_39(contents: &str, policy: CheckDigitPolicy) -> Option<String> {
+        let hints = DecodeHints {
+            Code39CheckDigit: Some(policy),
+            ..Default::default()
+        };
+        decode(
+            &mut Code39Reader::default(),
+            Code39Writer,
+            BarcodeFormat::CODE_39,
+            contents,
+            &hints,
+        )
+    }
+
+    fn codabar(contents: &str, policy: CheckDigitPolicy, start_end: bool) -> Option<String> {
+        let hints = DecodeHints {
+            CodabarCheckDigit: Some(policy),
+            ReturnCodabarStartEnd: Some(start_end),
+            ..Default::default()
+        };
+        decode(
+            &mut CodaBarReader::default(),
+            CodaBarWriter,
+            BarcodeFormat::CODABAR,
+            contents,
+            &hints,
+        )
+    }
+
+    fn itf(contents: &str, policy: CheckDigitPolicy) -> Option<String> {
+        let hints = DecodeHints {
+            ITFCheckDigit: Some(policy),
+            ..Default::default()
+        };
+        decode(
+            &mut ITFReader::default(),
+            ITFWriter,
+            BarcodeFormat::ITF,
+            contents,
+            &hints,
+        )
+    }
+
+    #[test]
+    fn test_code_39_check_digit() {
+        // C, O, D, E and 3 sum to 12 + 24 + 13 + 14 + 3 = 66, which is 23 (N) mod 43
+        let valid = "CODE3N";
+        assert_eq!(Some("CODE3N"), code_39(valid, Ignore).as_deref());
+        assert_eq!(Some("CODE3N"), code_39(valid, Validate).as_deref());
+        assert_eq!(Some("CODE3"), code_39(valid, Strip).as_deref());
+        assert_eq!(Some("CODE3"), code_39(valid, Require).as_deref());
+
+        let invalid = "CODE3M";
+        assert_eq!(Some("CODE3M"), code_39(invalid, Ignore).as_deref());
+        assert_eq!(None, code_39(invalid, Validate));
+        assert_eq!(Some("CODE3"), code_39(invalid, Strip).as_deref());
+        assert_eq!(None, code_39(invalid, Require));
+
+        // the hint takes precedence over how the reader was created
+        let hints = DecodeHints {
+            Code39CheckDigit: Some(Ignore),
+            ..Default::default()
+        };
+        let mut reader = Code39Reader::with_use_check_digit(true);
+        let decoded = decode(
+            &mut reader,
+            Code39Writer,
+            BarcodeFormat::CODE_39,
+            invalid,
+            &hints,
+        );
+        assert_eq!(Some("CODE3M"), decoded.as_deref());
+    }
+
+    #[test]
+    fn test_codabar_check_digit() {
+        // A, 1, 2, 3, 4 and B sum to 16 + 10 + 17 = 43, which the check character 5 brings to 48
+        let valid = "A12345B";
+        assert_eq!(Some("12345"), codabar(valid, Ignore, false).as_deref());
+        assert_eq!(Some("12345"), codabar(valid, Validate, false).as_deref());
+        assert_eq!(Some("1234"), codabar(valid, Require, false).as_deref());
+        assert_eq!(Some("A1234B"), codabar(valid, Require, true).as_deref());
+
+        let invalid = "A12346B";
+        assert_eq!(Some("12346"), codabar(invalid, Ignore, false).as_deref());
+        assert_eq!(None, codabar(invalid, Validate, false));
+        assert_eq!(Some("1234"), codabar(invalid, Strip, false).as_deref());
+        assert_eq!(None, codabar(invalid, Require, true));
+    }
+
+    #[test]
+    fn test_itf_check_digit() {
+        // an ITF-14, whose check digit is the one of GS1 keys
+        let valid = "15400141288763";
+        assert_eq!(Some(valid), itf(valid, Ignore).as_deref());
+        assert_eq!(Some(valid), itf(valid, Validate).as_deref());
+        assert_eq!(Some("1540014128876"), itf(valid, Require).as_deref());
+
+        let invalid = "15400141288764";
+        assert_eq!(Some(invalid), itf(invalid, Ignore).as_deref());
+        assert_eq!(None, itf(invalid, Validate));
+        assert_eq!(Some("1540014128876"), itf(invalid, Strip).as_deref());
+        assert_eq!(None, itf(invalid, Require));
+    }
+}
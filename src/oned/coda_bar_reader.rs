@@ -19,10 +19,10 @@ use rxing_one_d_proc_derive::OneDReader;
 use crate::common::{BitArray, Result};
 use crate::Exceptions;
 use crate::RXingResult;
-use crate::{point, BarcodeFormat};
+use crate::{point, BarcodeFormat, DecodeWarning, Strictness};
 use crate::{RXingResultMetadataType, RXingResultMetadataValue};
 
-use super::OneDReader;
+use super::{one_d_reader, OneDReader};
 
 /**
  * <p>Decodes Codabar barcodes.</p>
@@ -58,7 +58,8 @@ impl OneDReader for CodaBarReader {
         self.counters.fill(0);
         // Arrays.fill(counters, 0);
         self.setCounters(row)?;
-        let startOffset = self.findStartPattern()? as usize;
+        let strictness = hints.Strictness.unwrap_or_default();
+        let startOffset = self.findStartPattern(strictness)? as usize;
         let mut nextStart = startOffset;
 
         self.decodeRowRXingResult.clear();
@@ -87,21 +88,30 @@ impl OneDReader for CodaBarReader {
         }
         // Look for whitespace after pattern:
         let trailingWhitespace = self.counters[nextStart - 1];
-        let mut lastPatternSize = 0;
-        for i in -8..-1 {
-            lastPatternSize += self.counters[(nextStart as isize + i) as usize];
-        }
+        let lastPattern = &self.counters[nextStart - 8..nextStart - 1];
+        let lastPatternSize: u32 = lastPattern.iter().sum();
 
         // We need to see whitespace equal to 50% of the last pattern size,
         // otherwise this is probably a false positive. The exception is if we are
         // at the end of the row. (I.e. the barcode barely fits.)
-        if nextStart < self.counterLength && trailingWhitespace < lastPatternSize / 2 {
+        let quietZone = strictness.quiet_zone(
+            lastPatternSize as usize / 2,
+            one_d_reader::narrow_width(lastPattern, 4),
+            10,
+        );
+        let atEnd = nextStart >= self.counterLength && strictness.allows_cut_off_quiet_zone();
+        if !atEnd && (trailingWhitespace as usize) < quietZone {
             return Err(Exceptions::NOT_FOUND);
         }
+        let startPattern = &self.counters[startOffset..startOffset + 7];
+        let strictQuietZones = self.counters[startOffset - 1] as usize
+            >= Strictness::Strict.quiet_zone(0, one_d_reader::narrow_width(startPattern, 4), 10)
+            && trailingWhitespace as usize
+                >= Strictness::Strict.quiet_zone(0, one_d_reader::narrow_width(lastPattern, 4), 10);
 
         let mut cached_drrr = self.decodeRowRXingResult.chars().collect::<Vec<_>>();
 
-        self.validatePattern(startOffset, &cached_drrr)?;
+        self.validatePattern(startOffset, &cached_drrr, strictness)?;
 
         // Translate character table offsets to actual characters.
         for i in 0..cached_drrr.len() {
@@ -127,9 +137,24 @@ impl OneDReader for CodaBarReader {
             return Err(Exceptions::NOT_FOUND);
         }
 
-        if !hints.ReturnCodabarStartEnd.unwrap_or(false) {
-            self.decodeRowRXingResult = cached_drrr[1..cached_drrr.len() - 1].iter().collect();
-        }
+        // the check digit, if any, is the last character before the stop character
+        let policy = hints.CodabarCheckDigit.unwrap_or_default();
+        let dataLength = policy.apply(cached_drrr.len() - 2, || {
+            let total: usize = cached_drrr
+                .iter()
+                .filter_map(|c| Self::ALPHABET.iter().position(|a| a == c))
+                .sum();
+            total % 16 == 0
+        })?;
+        let endchar = *endchar;
+        cached_drrr.truncate(dataLength + 1);
+        cached_drrr.push(endchar);
+
+        self.decodeRowRXingResult = if hints.ReturnCodabarStartEnd.unwrap_or(false) {
+            cached_drrr.iter().collect()
+        } else {
+            cached_drrr[1..cached_drrr.len() - 1].iter().collect()
+        };
 
         let mut runningCount = 0;
         runningCount += self.counters.iter().take(startOffset).sum::<u32>();
@@ -158,6 +183,10 @@ impl OneDReader for CodaBarReader {
             BarcodeFormat::CODABAR,
         );
 
+        if !strictQuietZones {
+            result.addWarning(DecodeWarning::QuietZoneViolation);
+        }
+
         result.putMetadata(
             RXingResultMetadataType::SYMBOLOGY_IDENTIFIER,
             RXingResultMetadataValue::SymbologyIdentifier("]F0".to_owned()),
@@ -209,7 +238,7 @@ impl CodaBarReader {
         }
     }
 
-    fn validatePattern(&self, start: usize, cached: &[char]) -> Result<()> {
+    fn validatePattern(&self, start: usize, cached: &[char], strictness: Strictness) -> Result<()> {
         // First, sum up the total size of our four categories of stripe sizes;
         let mut sizes = [0, 0, 0, 0];
         let mut counts = [0, 0, 0, 0];
@@ -247,7 +276,8 @@ impl CodaBarReader {
                 + (sizes[i + 2] as f32) / (counts[i + 2] as f32))
                 / 2.0;
             maxes[i] = mins[i + 2];
-            maxes[i + 2] = ((sizes[i + 2] as f32) * Self::MAX_ACCEPTABLE + Self::PADDING)
+            maxes[i + 2] = ((sizes[i + 2] as f32) * strictness.tolerance(Self::MAX_ACCEPTABLE)
+                + Self::PADDING)
                 / (counts[i + 2] as f32);
         }
 
@@ -312,7 +342,7 @@ impl CodaBarReader {
         }
     }
 
-    fn findStartPattern(&mut self) -> Result<u32> {
+    fn findStartPattern(&mut self, strictness: Strictness) -> Result<u32> {
         let mut i = 1;
         while i < self.counterLength {
             // for (int i = 1; i < counterLength; i += 2) {
@@ -322,11 +352,16 @@ impl CodaBarReader {
             {
                 // Look for whitespace before start pattern, >= 50% of width of start pattern
                 // We make an exception if the whitespace is the first element.
-                let mut patternSize = 0;
-                for j in i..(i + 7) {
-                    patternSize += self.counters[j];
-                }
-                if i == 1 || self.counters[i - 1] >= patternSize / 2 {
+                let pattern = &self.counters[i..i + 7];
+                let patternSize: u32 = pattern.iter().sum();
+                let quietZone = strictness.quiet_zone(
+                    patternSize as usize / 2,
+                    one_d_reader::narrow_width(pattern, 4),
+                    10,
+                );
+                if (i == 1 && strictness.allows_cut_off_quiet_zone())
+                    || self.counters[i - 1] as usize >= quietZone
+                {
                     return Ok(i as u32);
                 }
             }
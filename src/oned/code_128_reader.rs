@@ -18,12 +18,12 @@ use rxing_one_d_proc_derive::OneDReader;
 
 use crate::{
     common::{BitArray, Result},
-    point, BarcodeFormat, Exceptions, RXingResult,
+    point, BarcodeFormat, DecodeWarning, Exceptions, RXingResult, Strictness,
 };
 
 use crate::{RXingResultMetadataType, RXingResultMetadataValue};
 
-use super::{one_d_reader, OneDReader};
+use super::{one_d_reader, OneDReader, ScanlinePattern, ScanlineWitness};
 
 /**
  * <p>Decodes Code 128 barcodes.</p>
@@ -41,10 +41,11 @@ impl OneDReader for Code128Reader {
         hints: &crate::DecodeHints,
     ) -> Result<crate::RXingResult> {
         let convertFNC1 = hints.AssumeGs1.unwrap_or(false);
+        let strictness = hints.Strictness.unwrap_or_default();
 
         let mut symbologyModifier = 0;
 
-        let startPatternInfo = self.findStartPattern(row)?;
+        let startPatternInfo = self.findStartPattern(row, strictness)?;
         let startCode = startPatternInfo[2] as u8;
 
         let mut rawCodes: Vec<u8> = Vec::with_capacity(20); //new ArrayList<>(20);
@@ -66,6 +67,7 @@ impl OneDReader for Code128Reader {
         let mut lastStart = startPatternInfo[0];
         let mut nextStart = startPatternInfo[1];
         let mut counters = [0_u32; 6]; //new int[6];
+        let mut characters = Vec::with_capacity(20);
 
         let mut lastCode = 0;
         let mut code = 0;
@@ -83,10 +85,18 @@ impl OneDReader for Code128Reader {
             lastCode = code;
 
             // Decode another code from image
-            code = self.decodeCode(row, &mut counters, nextStart)?;
+            code = self.decodeCode(row, &mut counters, nextStart, strictness)?;
 
             rawCodes.push(code);
 
+            if code != CODE_STOP {
+                characters.push(ScanlinePattern {
+                    start: nextStart as u32,
+                    counters: counters.to_vec(),
+                    value: Some(code as u32),
+                });
+            }
+
             // Remember whether the last code was printable or not (excluding CODE_STOP)
             if code != CODE_STOP {
                 lastCharacterWasPrintable = true;
@@ -294,13 +304,20 @@ impl OneDReader for Code128Reader {
         // we fudged decoding CODE_STOP since it actually has 7 bars, not 6. There is a black bar left
         // to read off. Would be slightly better to properly read. Here we just skip it:
         nextStart = row.getNextUnset(nextStart);
-        if !row.isRange(
-            nextStart,
-            row.get_size().min(nextStart + (nextStart - lastStart) / 2),
-            false,
-        )? {
+        // the stop pattern, with its final bar, is 13 modules wide
+        let stopPatternSize = nextStart - lastStart;
+        let quietZone =
+            strictness.quiet_zone(stopPatternSize / 2, stopPatternSize as f32 / 13.0, 10);
+        if !one_d_reader::is_quiet_after(row, nextStart, quietZone, strictness)? {
             return Err(Exceptions::NOT_FOUND);
         }
+        let strictQuietZones = one_d_reader::has_strict_quiet_zones(
+            row,
+            startPatternInfo[0],
+            nextStart,
+            stopPatternSize as f32 / 13.0,
+            10,
+        )?;
 
         // Pull out from sum the value of the penultimate check code
         checksumTotal -= multiplier as usize * lastCode as usize;
@@ -346,16 +363,47 @@ impl OneDReader for Code128Reader {
             BarcodeFormat::CODE_128,
         );
 
+        if !strictQuietZones {
+            resultObject.addWarning(DecodeWarning::QuietZoneViolation);
+        }
+
         resultObject.putMetadata(
             RXingResultMetadataType::SYMBOLOGY_IDENTIFIER,
             RXingResultMetadataValue::SymbologyIdentifier(format!("]C{symbologyModifier}")),
         );
 
+        // the stop pattern has 7 runs, with the final bar
+        let scanline = ScanlineWitness::new(
+            ScanlinePattern::read(row, startPatternInfo[0], 6, Some(startCode as u32))?,
+            characters,
+            ScanlinePattern::read(row, lastStart, 7, Some(CODE_STOP as u32))?,
+        );
+        resultObject.putMetadata(
+            RXingResultMetadataType::SCANLINE,
+            RXingResultMetadataValue::Scanline(scanline),
+        );
+
         Ok(resultObject)
     }
+
+    fn locate_start_pattern(
+        &mut self,
+        row: &crate::common::BitArray,
+        hints: &crate::DecodeHints,
+    ) -> Option<([usize; 2], f32)> {
+        let [start, end, _] = self
+            .findStartPattern(row, hints.Strictness.unwrap_or_default())
+            .ok()?;
+        // the start patterns span 11 modules
+        Some(([start, end], (end - start) as f32 / 11.0))
+    }
+
+    fn has_stop_pattern(&mut self, row: &crate::common::BitArray, end: usize, module: f32) -> bool {
+        one_d_reader::is_pattern_before(row, end, CODE_PATTERNS[CODE_STOP as usize], module)
+    }
 }
 impl Code128Reader {
-    fn findStartPattern(&self, row: &BitArray) -> Result<[usize; 3]> {
+    fn findStartPattern(&self, row: &BitArray, strictness: Strictness) -> Result<[usize; 3]> {
         let width = row.get_size();
         let rowOffset = row.getNextSet(0);
 
@@ -370,13 +418,13 @@ impl Code128Reader {
                 counters[counterPosition] += 1;
             } else {
                 if counterPosition == patternLength - 1 {
-                    let mut bestVariance = MAX_AVG_VARIANCE;
+                    let mut bestVariance = strictness.tolerance(MAX_AVG_VARIANCE);
                     let mut bestMatch = -1_isize;
                     for startCode in CODE_START_A..=CODE_START_C {
                         let variance = one_d_reader::pattern_match_variance(
                             &counters,
                             CODE_PATTERNS[startCode as usize],
-                            MAX_INDIVIDUAL_VARIANCE,
+                            strictness.tolerance(MAX_INDIVIDUAL_VARIANCE),
                         );
                         if variance < bestVariance {
                             bestVariance = variance;
@@ -384,13 +432,12 @@ impl Code128Reader {
                         }
                     }
                     // Look for whitespace before start pattern, >= 50% of width of start pattern
+                    // (11 modules)
+                    let patternSize = i - patternStart;
+                    let quietZone =
+                        strictness.quiet_zone(patternSize / 2, patternSize as f32 / 11.0, 10);
                     if bestMatch >= 0
-                        && row.isRange(
-                            0.max(patternStart as isize - (i as isize - patternStart as isize) / 2)
-                                as usize,
-                            patternStart,
-                            false,
-                        )?
+                        && one_d_reader::is_quiet_before(row, patternStart, quietZone, strictness)?
                     {
                         return Ok([patternStart, i, bestMatch as usize]);
                     }
@@ -411,14 +458,21 @@ impl Code128Reader {
         Err(Exceptions::NOT_FOUND)
     }
 
-    fn decodeCode(&self, row: &BitArray, counters: &mut [u32; 6], rowOffset: usize) -> Result<u8> {
+    fn decodeCode(
+        &self,
+        row: &BitArray,
+        counters: &mut [u32; 6],
+        rowOffset: usize,
+        strictness: Strictness,
+    ) -> Result<u8> {
         one_d_reader::record_pattern(row, rowOffset, counters)?;
-        let mut bestVariance = MAX_AVG_VARIANCE; // worst variance we'll accept
+        let mut bestVariance = strictness.tolerance(MAX_AVG_VARIANCE); // worst variance we'll accept
         let mut bestMatch = -1_isize;
+        let maxIndividualVariance = strictness.tolerance(MAX_INDIVIDUAL_VARIANCE);
         for (d, pattern) in CODE_PATTERNS.iter().enumerate() {
             // for d in 0..CODE_PATTERNS.len() {
             let variance =
-                one_d_reader::pattern_match_variance(counters, pattern, MAX_INDIVIDUAL_VARIANCE);
+                one_d_reader::pattern_match_variance(counters, pattern, maxIndividualVariance);
             if variance < bestVariance {
                 bestVariance = variance;
                 bestMatch = d as isize;
@@ -558,7 +612,7 @@ const CODE_FNC_3: u8 = 96;
 const CODE_FNC_4_A: u8 = 101;
 const CODE_FNC_4_B: u8 = 100;
 
-const CODE_START_A: u8 = 103;
+pub(crate) const CODE_START_A: u8 = 103;
 const CODE_START_B: u8 = 104;
-const CODE_START_C: u8 = 105;
+pub(crate) const CODE_START_C: u8 = 105;
 const CODE_STOP: u8 = 106;
@@ -16,6 +16,8 @@
 
 use rxing_one_d_proc_derive::OneDWriter;
 
+#[cfg(feature = "client_support")]
+use crate::client::result::{GS1ParsedRXingResult, GS1_GROUP_SEPARATOR};
 use crate::common::Result;
 use crate::BarcodeFormat;
 
@@ -72,18 +74,19 @@ impl OneDimensionalCodeWriter for Code128Writer {
         contents: &str,
         hints: &crate::EncodeHints,
     ) -> Result<Vec<bool>> {
+        let gs1Contents;
+        let contents = if hints.Gs1Format.unwrap_or(false) {
+            gs1Contents = gs1ElementStrings(contents)?;
+            gs1Contents.as_str()
+        } else {
+            contents
+        };
+
         let forcedCodeSet = check(contents, hints)?;
 
-        let hasCompactionHint = hints.Code128Compact.unwrap_or(false);
-        // let hasCompactionHint = if let Some(EncodeHintValue::Code128Compact(compat)) =
-        //     hints.get(&EncodeHintType::CODE128_COMPACT)
-        // {
-        //     *compat
-        // } else {
-        //     false
-        // };
-        // let hasCompactionHint = hints != null && hints.containsKey(EncodeHintType::CODE128_COMPACT) &&
-        //     Boolean.parseBoolean(hints.get(EncodeHintType::CODE128_COMPACT).toString());
+        // The fewest code set switches are found unless the greedy encoder is asked for, or a
+        // code set is forced
+        let hasCompactionHint = hints.Code128Compact.unwrap_or(forcedCodeSet == -1);
 
         if hasCompactionHint {
             MinimalEncoder::encode(contents)
@@ -93,6 +96,31 @@ impl OneDimensionalCodeWriter for Code128Writer {
     }
 }
 
+/**
+ * The GS1-128 contents of GS1 element strings: a leading FNC1, and an FNC1 after each element of
+ * variable length but the last. The element strings are given in human readable form, e.g.
+ * `(01)09506000134352(10)ABC`, or as they are encoded, elements of variable length ended by an
+ * FNC1 escape or a group separator.
+ */
+#[cfg(feature = "client_support")]
+fn gs1ElementStrings(contents: &str) -> Result<String> {
+    let data = contents.replace(ESCAPE_FNC_1, &GS1_GROUP_SEPARATOR.to_string());
+    let gs1 = GS1ParsedRXingResult::parse(&data).map_err(|e| {
+        Exceptions::illegal_argument_with(format!("Contents are not GS1 element strings: {e}"))
+    })?;
+    Ok(format!(
+        "{ESCAPE_FNC_1}{}",
+        gs1.getEncodedElementString(ESCAPE_FNC_1)
+    ))
+}
+
+#[cfg(not(feature = "client_support"))]
+fn gs1ElementStrings(_contents: &str) -> Result<String> {
+    Err(Exceptions::unsupported_operation_with(
+        "GS1-128 element strings are checked with the client_support feature",
+    ))
+}
+
 fn check(contents: &str, hints: &crate::EncodeHints) -> Result<i32> {
     let length = contents.chars().count();
     // Check length
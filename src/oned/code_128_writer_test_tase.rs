@@ -442,10 +442,7 @@ fn testEncodeWithForcedCodeSetFailureCodeSetB() {
 fn encode(toEncode: &str, compact: bool, expectedLoopback: &str) -> Result<BitMatrix> {
     let mut reader = Code128Reader;
 
-    let mut hints = EncodeHints::default();
-    if compact {
-        hints.Code128Compact = Some(true);
-    }
+    let hints = EncodeHints::default().with(EncodeHintValue::Code128Compact(compact));
     let encRXingResult =
         WRITER.encode_with_hints(toEncode, &BarcodeFormat::CODE_128, 0, 0, &hints)?;
     if !expectedLoopback.is_empty() {
@@ -459,10 +456,84 @@ fn encode(toEncode: &str, compact: bool, expectedLoopback: &str) -> Result<BitMa
         let row = encRXingResult.getRow(0);
         let rtRXingResult = reader.decode_row(0, &row, &DecodeHints::default())?;
         let actual = rtRXingResult.getText();
-        let encRXingResultFast = WRITER.encode(toEncode, &BarcodeFormat::CODE_128, 0, 0)?;
+        let encRXingResultFast = WRITER.encode_with_hints(
+            toEncode,
+            &BarcodeFormat::CODE_128,
+            0,
+            0,
+            &EncodeHints::default().with(EncodeHintValue::Code128Compact(false)),
+        )?;
         let row = encRXingResultFast.getRow(0);
         let rtRXingResult = reader.decode_row(0, &row, &DecodeHints::default())?;
         assert_eq!(rtRXingResult.getText(), actual);
     }
     Ok(encRXingResult)
 }
+
+#[test]
+fn testMinimalByDefault() {
+    let toEncode = "a\na\na\na\na\na\na\na\na\na\na\na\na\na\na\na\na\na\na\na\na\na\na\na\n";
+    let default = WRITER
+        .encode(toEncode, &BarcodeFormat::CODE_128, 0, 0)
+        .expect("encode");
+    let compact = encode(toEncode, true, toEncode).expect("encode");
+    assert_eq!(compact.getWidth(), default.getWidth());
+
+    // A forced code set is kept, although code set C would be narrower
+    let hints = EncodeHints::default().with(EncodeHintValue::ForceCodeSet("B".to_owned()));
+    let forced = WRITER
+        .encode_with_hints("a1234", &BarcodeFormat::CODE_128, 0, 0, &hints)
+        .expect("encode");
+    let default = WRITER
+        .encode("a1234", &BarcodeFormat::CODE_128, 0, 0)
+        .expect("encode");
+    assert_eq!(default.getWidth() + 11, forced.getWidth());
+}
+
+#[cfg(feature = "client_support")]
+#[test]
+fn testGS1() {
+    let hints = EncodeHints::default().with(EncodeHintValue::Gs1Format(true));
+    let decodeHints = DecodeHints::default().with(crate::DecodeHintValue::AssumeGs1(true));
+    let expected = "]C1010950600013435217251231310300175010ABC";
+    let mut reader = Code128Reader;
+    for toEncode in [
+        "(01)09506000134352(17)251231(3103)001750(10)ABC",
+        "0109506000134352172512313103001750\u{00f1}10ABC",
+        "0109506000134352172512313103001750\u{1d}10ABC",
+        "\u{00f1}(01)09506000134352(17)251231(3103)001750(10)ABC",
+    ] {
+        let encoded = WRITER
+            .encode_with_hints(toEncode, &BarcodeFormat::CODE_128, 0, 0, &hints)
+            .expect("encode");
+        let decoded = reader
+            .decode_row(0, &encoded.getRow(0), &decodeHints)
+            .expect("decode");
+        assert_eq!(expected, decoded.getText(), "{toEncode}");
+    }
+
+    // Elements of variable length are ended by FNC1 unless last
+    let encoded = WRITER
+        .encode_with_hints(
+            "(10)ABC(21)42(01)09506000134352",
+            &BarcodeFormat::CODE_128,
+            0,
+            0,
+            &hints,
+        )
+        .expect("encode");
+    let decoded = reader
+        .decode_row(0, &encoded.getRow(0), &decodeHints)
+        .expect("decode");
+    assert_eq!(
+        "]C110ABC\u{1d}2142\u{1d}0109506000134352",
+        decoded.getText()
+    );
+
+    // Bad check digit and unknown AI
+    for toEncode in ["(01)09506000134353", "(23)1234"] {
+        assert!(WRITER
+            .encode_with_hints(toEncode, &BarcodeFormat::CODE_128, 0, 0, &hints)
+            .is_err());
+    }
+}
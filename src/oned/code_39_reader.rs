@@ -18,16 +18,19 @@ use once_cell::sync::Lazy;
 use rxing_one_d_proc_derive::OneDReader;
 
 use crate::common::{BitArray, Result};
-use crate::{point, BarcodeFormat, Exceptions, RXingResult};
+use crate::{point, BarcodeFormat, DecodeWarning, Exceptions, RXingResult, Strictness};
 
-use super::{one_d_reader, OneDReader};
+use super::{one_d_reader, CheckDigitPolicy, OneDReader};
 
 use crate::DecodeHints;
 
 use crate::{RXingResultMetadataType, RXingResultMetadataValue};
 
 /**
- * <p>Decodes Code 39 barcodes. Supports "Full ASCII Code 39" if USE_CODE_39_EXTENDED_MODE is set.</p>
+ * <p>Decodes Code 39 barcodes. Supports "Full ASCII Code 39" if CODE_39_EXTENDED_MODE is set.</p>
+ *
+ * <p>The CODE_39_CHECK_DIGIT and CODE_39_EXTENDED_MODE hints override whether the reader was
+ * created to use a check digit and to decode extended Code 39.</p>
  *
  * @author Sean Owen
  * @see Code93Reader
@@ -49,15 +52,15 @@ impl OneDReader for Code39Reader {
         &mut self,
         rowNumber: u32,
         row: &crate::common::BitArray,
-        _hints: &DecodeHints,
+        hints: &DecodeHints,
     ) -> Result<crate::RXingResult> {
         let mut counters = [0_u32; 9];
         self.decodeRowRXingResult.clear();
+        let strictness = hints.Strictness.unwrap_or_default();
 
-        let start = Self::findAsteriskPattern(row, &mut counters)?;
+        let start = Self::findAsteriskPattern(row, &mut counters, strictness)?;
         // Read off white space
         let mut nextStart = row.getNextSet(start[1] as usize);
-        let end = row.get_size();
 
         let mut decodedChar;
         let mut lastStart;
@@ -86,45 +89,49 @@ impl OneDReader for Code39Reader {
         // Look for whitespace after pattern:
         let lastPatternSize = counters.iter().sum::<u32>();
 
-        let whiteSpaceAfterEnd = nextStart - lastStart - lastPatternSize as usize;
         // If 50% of last pattern size, following last pattern, is not whitespace, fail
         // (but if it's whitespace to the very end of the image, that's OK)
-        if nextStart != end && (whiteSpaceAfterEnd * 2) < lastPatternSize as usize {
+        let quietZone = strictness.quiet_zone(
+            (lastPatternSize as usize).div_ceil(2),
+            one_d_reader::narrow_width(&counters, 6),
+            10,
+        );
+        let patternEnd = lastStart + lastPatternSize as usize;
+        if !one_d_reader::is_quiet_after(row, patternEnd, quietZone, strictness)? {
             return Err(Exceptions::NOT_FOUND);
         }
+        let strictQuietZones = one_d_reader::has_strict_quiet_zones(
+            row,
+            start[0] as usize,
+            patternEnd,
+            one_d_reader::narrow_width(&counters, 6),
+            10,
+        )?;
 
         let cached_row_result = self.decodeRowRXingResult.chars().collect::<Vec<_>>();
 
-        if self.usingCheckDigit {
+        let policy = hints.Code39CheckDigit.unwrap_or(if self.usingCheckDigit {
+            CheckDigitPolicy::Require
+        } else {
+            CheckDigitPolicy::Ignore
+        });
+        let length = policy.apply(cached_row_result.len(), || {
             let max = cached_row_result.len() - 1;
-            let mut total = 0;
-            for i in 0..max {
-                if let Some(pos) = Self::ALPHABET_STRING.find(
-                    *cached_row_result
-                        .get(i)
-                        .ok_or(Exceptions::INDEX_OUT_OF_BOUNDS)?,
-                ) {
-                    total += pos;
-                }
-            }
-            if cached_row_result
-                .get(max)
-                .ok_or(Exceptions::INDEX_OUT_OF_BOUNDS)?
-                != C39R_CACHED_ALPHABET_STRING
-                    .get(total % 43)
-                    .ok_or(Exceptions::INDEX_OUT_OF_BOUNDS)?
-            {
-                return Err(Exceptions::NOT_FOUND);
-            }
-            self.decodeRowRXingResult.truncate(max);
-        }
+            let total: usize = cached_row_result[..max]
+                .iter()
+                .filter_map(|c| Self::ALPHABET_STRING.find(*c))
+                .sum();
+            C39R_CACHED_ALPHABET_STRING.get(total % 43) == cached_row_result.get(max)
+        })?;
+        self.decodeRowRXingResult.truncate(length);
 
         if self.decodeRowRXingResult.chars().count() == 0 {
             // false positive
             return Err(Exceptions::NOT_FOUND);
         }
 
-        let resultString = if self.extendedMode {
+        let extendedMode = hints.Code39ExtendedMode.unwrap_or(self.extendedMode);
+        let resultString = if extendedMode {
             Self::decodeExtended(&self.decodeRowRXingResult)?
         } else {
             self.decodeRowRXingResult.clone()
@@ -143,13 +150,61 @@ impl OneDReader for Code39Reader {
             BarcodeFormat::CODE_39,
         );
 
+        if !strictQuietZones {
+            resultObject.addWarning(DecodeWarning::QuietZoneViolation);
+        }
+
+        if extendedMode {
+            resultObject.putMetadata(
+                RXingResultMetadataType::RAW_TEXT,
+                RXingResultMetadataValue::RawText(self.decodeRowRXingResult.clone()),
+            );
+        }
+
+        // ISO/IEC 15424: 1 if the check character was verified and is returned, 3 if it was
+        // verified and removed, plus 4 for Full ASCII
+        let mut symbologyModifier = match (policy.validates(), policy.strips()) {
+            (true, false) => 1,
+            (true, true) => 3,
+            _ => 0,
+        };
+        if extendedMode {
+            symbologyModifier += 4;
+        }
         resultObject.putMetadata(
             RXingResultMetadataType::SYMBOLOGY_IDENTIFIER,
-            RXingResultMetadataValue::SymbologyIdentifier("]A0".to_owned()),
+            RXingResultMetadataValue::SymbologyIdentifier(format!("]A{symbologyModifier}")),
         );
 
         Ok(resultObject)
     }
+
+    fn locate_start_pattern(
+        &mut self,
+        row: &crate::common::BitArray,
+        hints: &DecodeHints,
+    ) -> Option<([usize; 2], f32)> {
+        let mut counters = [0_u32; 9];
+        let start =
+            Self::findAsteriskPattern(row, &mut counters, hints.Strictness.unwrap_or_default())
+                .ok()?;
+        Some((
+            [start[0] as usize, start[1] as usize],
+            one_d_reader::narrow_width(&counters, 6),
+        ))
+    }
+
+    fn has_stop_pattern(
+        &mut self,
+        row: &crate::common::BitArray,
+        end: usize,
+        _module: f32,
+    ) -> bool {
+        let mut counters = [0_u32; 9];
+        end < row.get_size()
+            && one_d_reader::record_pattern_in_reverse(row, end, &mut counters).is_ok()
+            && Self::toNarrowWidePattern(&counters) == Self::ASTERISK_ENCODING as i32
+    }
 }
 
 pub static C39R_CACHED_ALPHABET_STRING: Lazy<Vec<char>> =
@@ -211,7 +266,11 @@ impl Code39Reader {
         }
     }
 
-    fn findAsteriskPattern(row: &BitArray, counters: &mut [u32]) -> Result<Vec<u32>> {
+    fn findAsteriskPattern(
+        row: &BitArray,
+        counters: &mut [u32],
+        strictness: Strictness,
+    ) -> Result<Vec<u32>> {
         let width = row.get_size();
         let rowOffset = row.getNextSet(0);
 
@@ -228,12 +287,15 @@ impl Code39Reader {
                 if counterPosition == patternLength - 1 {
                     // Look for whitespace before start pattern, >= 50% of width of start pattern
                     if Self::toNarrowWidePattern(counters) == (Self::ASTERISK_ENCODING as i32)
-                        && row.isRange(
-                            0.max(
-                                patternStart as isize - ((i as isize - patternStart as isize) / 2),
-                            ) as usize,
+                        && one_d_reader::is_quiet_before(
+                            row,
                             patternStart,
-                            false,
+                            strictness.quiet_zone(
+                                (i - patternStart) / 2,
+                                one_d_reader::narrow_width(counters, 6),
+                                10,
+                            ),
+                            strictness,
                         )?
                     {
                         return Ok(vec![patternStart as u32, i as u32]);
@@ -412,9 +474,10 @@ impl Code39Reader {
 mod code_39_extended_mode_test_case {
 
     use crate::{
-        common::BitMatrix,
-        oned::{Code39Reader, OneDReader},
-        DecodeHints,
+        common::{BitArray, BitMatrix},
+        oned::{CheckDigitPolicy, Code39Reader, Code39Writer, OneDReader},
+        BarcodeFormat, DecodeHints, RXingResult, RXingResultMetadataType, RXingResultMetadataValue,
+        Writer,
     };
     #[test]
     fn testDecodeExtendedMode() {
@@ -429,6 +492,79 @@ mod code_39_extended_mode_test_case {
            "000001001011011010101001001001011001101010101001010010010110101001011010010100100101011010010110100101001001011011010010101001010010010101011001011010010100100101101011001010100101001001010110110010101001010010010101010011011010010100100101101010011010100101001001010110100110101001010010010101011001101010010100100101101010100110100101001001010110101001101001010010010110110101001010010100100101010110100110100101001001011010110100101001010010010101101101001010010100100101010101100110100101001001011010101100101001010010010101101011001010010100100101010110110010100101001001011001010101101001010010010100110101011010010100100101100110101010100101001001010010110101101001010010010110010110101010010100100101001101101010101001001001010110110100101010010010010101010110011010100100100101101010110010101001001001010110101100101010010010010101011011001010010110110100000");
     }
 
+    #[test]
+    fn testExtendedModeHint() {
+        // the writer encodes lowercase letters as Full ASCII pairs
+        let result = decode(Code39Reader::default(), "abc", Some(true), None);
+        assert_eq!("abc", result.getText());
+        assert_eq!(Some("+A+B+C"), rawText(&result));
+        assert_eq!("]A4", symbologyIdentifier(&result));
+
+        // the hint overrides how the reader was created
+        let result = decode(
+            Code39Reader::with_all_config(false, true),
+            "abc",
+            Some(false),
+            None,
+        );
+        assert_eq!("+A+B+C", result.getText());
+        assert_eq!(None, rawText(&result));
+        assert_eq!("]A0", symbologyIdentifier(&result));
+
+        // +, A, +, B, + and C sum to 41 * 3 + 10 + 11 + 12 = 156, which is 27 (R) mod 43
+        let require = Some(CheckDigitPolicy::Require);
+        let result = decode(Code39Reader::default(), "+A+B+CR", Some(true), require);
+        assert_eq!("abc", result.getText());
+        assert_eq!(Some("+A+B+C"), rawText(&result));
+        assert_eq!("]A7", symbologyIdentifier(&result));
+        let validate = Some(CheckDigitPolicy::Validate);
+        let result = decode(Code39Reader::default(), "+A+B+CR", None, validate);
+        assert_eq!("]A1", symbologyIdentifier(&result));
+    }
+
+    fn decode(
+        mut reader: Code39Reader,
+        contents: &str,
+        extendedMode: Option<bool>,
+        checkDigit: Option<CheckDigitPolicy>,
+    ) -> RXingResult {
+        let matrix = Code39Writer
+            .encode(contents, &BarcodeFormat::CODE_39, 0, 0)
+            .expect("encode");
+        let mut row = BitArray::with_size(matrix.getWidth() as usize);
+        for x in 0..matrix.getWidth() {
+            if matrix.get(x, 0) {
+                row.set(x as usize);
+            }
+        }
+        let hints = DecodeHints {
+            Code39ExtendedMode: extendedMode,
+            Code39CheckDigit: checkDigit,
+            ..Default::default()
+        };
+        reader.decode_row(0, &row, &hints).expect("decode row")
+    }
+
+    fn rawText(result: &RXingResult) -> Option<&str> {
+        match result
+            .getRXingResultMetadata()
+            .get(&RXingResultMetadataType::RAW_TEXT)
+        {
+            Some(RXingResultMetadataValue::RawText(text)) => Some(text),
+            _ => None,
+        }
+    }
+
+    fn symbologyIdentifier(result: &RXingResult) -> String {
+        match result
+            .getRXingResultMetadata()
+            .get(&RXingResultMetadataType::SYMBOLOGY_IDENTIFIER)
+        {
+            Some(RXingResultMetadataValue::SymbologyIdentifier(id)) => id.clone(),
+            _ => String::new(),
+        }
+    }
+
     fn doTest(expectedRXingResult: &str, encodedRXingResult: &str) {
         let mut sut = Code39Reader::with_all_config(false, true);
         let matrix =
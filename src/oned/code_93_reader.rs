@@ -132,6 +132,21 @@ impl OneDReader for Code93Reader {
 
         Ok(resultObject)
     }
+
+    fn locate_start_pattern(
+        &mut self,
+        row: &crate::common::BitArray,
+        _hints: &crate::DecodeHints,
+    ) -> Option<([usize; 2], f32)> {
+        let [start, end] = self.findAsteriskPattern(row).ok()?;
+        // the asterisk spans 9 modules
+        Some(([start, end], (end - start) as f32 / 9.0))
+    }
+
+    fn has_stop_pattern(&mut self, row: &crate::common::BitArray, end: usize, module: f32) -> bool {
+        // the asterisk and the termination bar
+        one_d_reader::is_pattern_before(row, end, &[1, 1, 1, 1, 4, 1, 1], module)
+    }
 }
 
 impl Code93Reader {
@@ -9,7 +9,8 @@ use crate::{
         cpp_essentials::{FindLeftGuardBy, FixedPattern, IsRightGuard, PatternView, ToIntPos},
         BitArray,
     },
-    point, BarcodeFormat, DecodeHintValue, DecodingHintDictionary, Exceptions, PointI, RXingResult,
+    point, BarcodeFormat, DecodeHints, Exceptions, PointI, RXingResult, RXingResultMetadataType,
+    RXingResultMetadataValue,
 };
 
 use super::row_reader::{DecodingState, RowReader};
@@ -36,11 +37,11 @@ const DATA_START_PATTERN: FixedPattern<5, 5> = FixedPattern::new([1, 1, 1, 1, 1]
 const DATA_STOP_PATTERN: FixedPattern<3, 3> = FixedPattern::new([1, 1, 1]);
 
 pub struct DXFilmEdgeReader<'a> {
-    options: &'a DecodingHintDictionary,
+    options: &'a DecodeHints,
 }
 
 impl<'a> DXFilmEdgeReader<'_> {
-    pub fn new(hints: &'a DecodingHintDictionary) -> DXFilmEdgeReader<'a> {
+    pub fn new(hints: &'a DecodeHints) -> DXFilmEdgeReader<'a> {
         DXFilmEdgeReader { options: hints }
     }
 }
@@ -175,6 +176,16 @@ fn CheckForClock(rowNumber: u32, view: &PatternView) -> Option<Clock> {
     return Some(clock);
 }
 
+fn DXFilmEdgeResult(txt: &str, dataBits: BitArray) -> RXingResult {
+    let mut result = RXingResult::new(txt, dataBits.into(), Vec::new(), BarcodeFormat::DXFilmEdge);
+    // AIM gives it no identifier of its own, so it is reported as another symbology
+    result.putMetadata(
+        RXingResultMetadataType::SYMBOLOGY_IDENTIFIER,
+        RXingResultMetadataValue::SymbologyIdentifier("]X0".to_owned()),
+    );
+    result
+}
+
 impl<'a> RowReader for DXFilmEdgeReader<'_> {
     fn decodePattern(
         &self,
@@ -195,11 +206,7 @@ impl<'a> RowReader for DXFilmEdgeReader<'_> {
 
         // Only consider rows below the center row of the image
 
-        if (!matches!(
-            self.options.get(&crate::DecodeHintType::TRY_HARDER),
-            Some(DecodeHintValue::TryHarder(true))
-        ) && rowNumber < dxState.centerRow)
-        {
+        if (!matches!(self.options.TryHarder, Some(true)) && rowNumber < dxState.centerRow) {
             return Err(Exceptions::NOT_FOUND);
         }
 
@@ -265,7 +272,7 @@ impl<'a> RowReader for DXFilmEdgeReader<'_> {
             for _i in 0..modules {
                 dataBits.appendBits(
                     if next.index() % 2 == 0 {
-                        0xFFFFFFFF_u32
+                        0xFFFFFFFF_usize
                     } else {
                         0x0
                     },
@@ -348,12 +355,28 @@ impl<'a> RowReader for DXFilmEdgeReader<'_> {
         clock.xStart = xStart as u32;
         clock.xStop = xStop as u32;
 
-        Ok(RXingResult::new(
-            &txt,
-            dataBits.into(),
-            Vec::new(),
-            BarcodeFormat::DXFilmEdge,
-        ))
+        Ok(DXFilmEdgeResult(&txt, dataBits))
         // return RXingResult(txt, rowNumber, xStart, xStop, BarcodeFormat::DXFilmEdge, {});
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        common::BitArray, BarcodeFormat, RXingResultMetadataType, RXingResultMetadataValue,
+    };
+
+    #[test]
+    fn test_symbology_identifier() {
+        let result = super::DXFilmEdgeResult("115-10/11A", BitArray::with_size(23));
+        assert_eq!(&BarcodeFormat::DXFilmEdge, result.getBarcodeFormat());
+        assert_eq!(
+            Some(&RXingResultMetadataValue::SymbologyIdentifier(
+                "]X0".to_owned()
+            )),
+            result
+                .getRXingResultMetadata()
+                .get(&RXingResultMetadataType::SYMBOLOGY_IDENTIFIER)
+        );
+    }
+}
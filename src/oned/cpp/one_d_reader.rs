@@ -5,14 +5,10 @@
 */
 // SPDX-License-Identifier: Apache-2.0
 
-use std::collections::HashMap;
-
 use crate::common::cpp_essentials::{GetPatternRow, PatternRow, PatternView};
+use crate::Binarizer;
 use crate::{multi::MultipleBarcodeReader, RXingResult, Reader};
-use crate::{
-    point, BarcodeFormat, BinaryBitmap, DecodingHintDictionary, Exceptions, PointT, ResultPoint,
-};
-use crate::{Binarizer, DecodeHintType, DecodeHintValue};
+use crate::{point, BarcodeFormat, BinaryBitmap, DecodeHints, Exceptions, PointT};
 
 use crate::common::{LineOrientation, Quadrilateral, Result};
 
@@ -196,7 +192,7 @@ impl<'a> ODReader<'_> {
                                 let points = result.getPointsMut();
                                 for p in points {
                                     // for (auto& p : points) {
-                                    *p = point(width as f32 - p.getX() - 1.0, p.getY());
+                                    *p = point(width as f32 - p.x - 1.0, p.y);
                                 }
                                 // result.addPoints(points);
                                 // result.setPosition(std::move(points));
@@ -205,7 +201,7 @@ impl<'a> ODReader<'_> {
                                 let points = result.getPointsMut();
                                 for p in points {
                                     // for (auto& p : points) {
-                                    *p = point(p.getY(), width as f32 - p.getX() - 1.0);
+                                    *p = point(p.y, width as f32 - p.x - 1.0);
                                 }
                                 // result.addPoints(points);
                                 // result.setPosition(std::move(points));
@@ -225,7 +221,7 @@ impl<'a> ODReader<'_> {
                                     let dBot = PointT::maxAbsComponent(
                                         other.getPoints()[2] - result.getPoints()[0],
                                     );
-                                    let mut points = other.getPoints().clone();
+                                    let mut points = other.getPoints().to_vec();
                                     if (dTop < dBot
                                         || (dTop == dBot
                                             && rotate
@@ -267,7 +263,7 @@ impl<'a> ODReader<'_> {
                             if (maxSymbols > 0
                                 && res.iter().fold(0, |acc, e| {
                                     if let Some(itm) = &res[r] {
-                                        acc + i32::from((itm.line_count() >= minLineCount as usize))
+                                        acc + i32::from((itm.line_count() < minLineCount as usize))
                                     } else {
                                         acc
                                     }
@@ -305,11 +301,13 @@ impl<'a> ODReader<'_> {
         for i in 0..res.len() {
             for j in i..res.len() {
                 if res[i].is_some() && res[j].is_some() {
-                    let Ok(q1) = Quadrilateral::try_from(res[i].as_ref().unwrap().getPoints())
+                    let Ok(q1) =
+                        Quadrilateral::try_from(&res[i].as_ref().unwrap().getPoints().to_vec())
                     else {
                         continue;
                     };
-                    let Ok(q2) = Quadrilateral::try_from(res[j].as_ref().unwrap().getPoints())
+                    let Ok(q2) =
+                        Quadrilateral::try_from(&res[j].as_ref().unwrap().getPoints().to_vec())
                     else {
                         continue;
                     };
@@ -371,7 +369,7 @@ impl<'a> ODReader<'_> {
 impl<'a> ODReader<'_> {
     pub fn decode_single<B: crate::Binarizer>(
         &self,
-        _hints: &DecodingHintDictionary,
+        _hints: &DecodeHints,
         image: &BinaryBitmap<B>,
     ) -> Result<RXingResult> {
         let result = self.decode_with_max_symbols(_hints, image, u32::MAX)?;
@@ -382,7 +380,7 @@ impl<'a> ODReader<'_> {
 
     pub fn decode_with_max_symbols<B: crate::Binarizer>(
         &self,
-        _hints: &DecodingHintDictionary,
+        _hints: &DecodeHints,
         image: &BinaryBitmap<B>,
         maxSymbols: u32,
     ) -> Result<Vec<RXingResult>> {
@@ -423,13 +421,13 @@ impl<'a> Reader for ODReader<'_> {
         &mut self,
         image: &mut crate::BinaryBitmap<B>,
     ) -> crate::common::Result<crate::RXingResult> {
-        self.decode_with_hints(image, &HashMap::new())
+        self.decode_with_hints(image, &DecodeHints::default())
     }
 
     fn decode_with_hints<B: crate::Binarizer>(
         &mut self,
         image: &mut crate::BinaryBitmap<B>,
-        hints: &crate::DecodingHintDictionary,
+        hints: &DecodeHints,
     ) -> crate::common::Result<crate::RXingResult> {
         self.decode_single(hints, image)
     }
@@ -440,36 +438,27 @@ impl<'a> MultipleBarcodeReader for ODReader<'_> {
         &mut self,
         image: &mut crate::BinaryBitmap<B>,
     ) -> crate::common::Result<Vec<crate::RXingResult>> {
-        self.decode_multiple_with_hints(image, &HashMap::new())
+        self.decode_multiple_with_hints(image, &DecodeHints::default())
     }
 
     fn decode_multiple_with_hints<B: crate::Binarizer>(
         &mut self,
         image: &mut crate::BinaryBitmap<B>,
-        hints: &crate::DecodingHintDictionary,
+        hints: &DecodeHints,
     ) -> crate::common::Result<Vec<crate::RXingResult>> {
         self.decode_with_max_symbols(hints, image, u32::MAX)
     }
 }
 
 impl<'a> ODReader<'_> {
-    pub fn new(hints: &DecodingHintDictionary) -> ODReader {
+    pub fn new(hints: &DecodeHints) -> ODReader {
         ODReader {
             reader: DXFilmEdgeReader::new(hints),
-            try_harder: matches!(
-                hints.get(&DecodeHintType::TRY_HARDER),
-                Some(DecodeHintValue::TryHarder(true))
-            ),
-            is_pure: matches!(
-                hints.get(&DecodeHintType::PURE_BARCODE),
-                Some(DecodeHintValue::PureBarcode(true))
-            ),
+            try_harder: matches!(hints.TryHarder, Some(true)),
+            is_pure: matches!(hints.PureBarcode, Some(true)),
             min_line_count: 2,
             return_errors: false,
-            try_rotate: matches!(
-                hints.get(&DecodeHintType::TRY_HARDER),
-                Some(DecodeHintValue::TryHarder(true))
-            ),
+            try_rotate: matches!(hints.TryHarder, Some(true)),
         }
     }
 }
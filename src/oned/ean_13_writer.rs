@@ -22,7 +22,7 @@ use crate::{
     BarcodeFormat,
 };
 
-use super::{OneDimensionalCodeWriter, UPCEANReader, UPCEANWriter};
+use super::{OneDimensionalCodeWriter, UPCEANExtensionWriter, UPCEANReader, UPCEANWriter};
 
 /**
  * This object renders an EAN13 code as a {@link BitMatrix}.
@@ -118,6 +118,14 @@ impl OneDimensionalCodeWriter for EAN13Writer {
         Ok(result.to_vec())
     }
 
+    fn encode_oned_with_hints(&self, contents: &str, hints: &EncodeHints) -> Result<Vec<bool>> {
+        let mut code = self.encode_oned(contents)?;
+        if let Some(extension) = &hints.UpcEanExtension {
+            UPCEANExtensionWriter::append(&mut code, extension)?;
+        }
+        Ok(code)
+    }
+
     fn getSupportedWriteFormats(&self) -> Option<Vec<crate::BarcodeFormat>> {
         Some(vec![BarcodeFormat::EAN_13])
     }
@@ -20,10 +20,10 @@ use crate::{RXingResultMetadataType, RXingResultMetadataValue};
 
 use crate::{
     common::{BitArray, Result},
-    point, BarcodeFormat, Exceptions, RXingResult,
+    point, BarcodeFormat, DecodeWarning, Exceptions, RXingResult, Strictness,
 };
 
-use super::{one_d_reader, OneDReader};
+use super::{one_d_reader, ITFWriter, OneDReader};
 
 const MAX_AVG_VARIANCE: f32 = 0.38;
 const MAX_INDIVIDUAL_VARIANCE: f32 = 0.5;
@@ -83,8 +83,8 @@ const PATTERNS: [[u32; 5]; 20] = [
  * lengths are scanned, especially shorter ones, to avoid false positives. This in turn is due to a lack of
  * required checksum function.</p>
  *
- * <p>The checksum is optional and is not applied by this Reader. The consumer of the decoded
- * value will have to apply a checksum if required.</p>
+ * <p>The checksum is optional and is not applied by this Reader unless the ITF_CHECK_DIGIT hint
+ * asks for it.</p>
  *
  * <p><a href="http://en.wikipedia.org/wiki/Interleaved_2_of_5">http://en.wikipedia.org/wiki/Interleaved_2_of_5</a>
  * is a great reference for Interleaved 2 of 5 information.</p>
@@ -95,12 +95,15 @@ const PATTERNS: [[u32; 5]; 20] = [
 pub struct ITFReader {
     // Stores the actual narrow line width of the image being decoded.
     narrowLineWidth: i32,
+    // Stores the strictness of the decode in progress.
+    strictness: Strictness,
 }
 
 impl Default for ITFReader {
     fn default() -> Self {
         Self {
             narrowLineWidth: -1,
+            strictness: Strictness::default(),
         }
     }
 }
@@ -112,42 +115,69 @@ impl OneDReader for ITFReader {
         row: &crate::common::BitArray,
         hints: &crate::DecodeHints,
     ) -> Result<crate::RXingResult> {
+        self.strictness = hints.Strictness.unwrap_or_default();
+
         // Find out where the Middle section (payload) starts & ends
         let mut row = row.clone();
         let startRange = self.decodeStart(&row)?;
         let endRange = self.decodeEnd(&mut row)?;
 
+        // With exact lengths, reject a payload too narrow or too wide for all of them before
+        // decoding it, and stop decoding once it is longer than the longest
+        let mut maxLength = usize::MAX;
+        if let Some(lengths) = &hints.ITFLengths {
+            let narrowLineWidth = (startRange[1] - startRange[0]) as f32 / 4.0;
+            let modules = (endRange[0] - startRange[1]) as f32 / narrowLineWidth;
+            if !lengths
+                .iter()
+                .any(|length| Self::fitsLength(*length, modules))
+            {
+                return Err(Exceptions::NOT_FOUND);
+            }
+            maxLength = lengths.iter().max().copied().unwrap_or_default() as usize;
+        }
+
         let mut result = String::with_capacity(20); //new StringBuilder(20);
-        self.decodeMiddle(&row, startRange[1], endRange[0], &mut result)?;
+        self.decodeMiddle(&row, startRange[1], endRange[0], maxLength, &mut result)?;
         let resultString = result; //.toString();
 
-        let allowedLengths = if let Some(al) = &hints.AllowedLengths {
-            al.clone()
-        } else {
-            DEFAULT_ALLOWED_LENGTHS.to_vec()
-        };
-
-        // To avoid false positives with 2D barcodes (and other patterns), make
-        // an assumption that the decoded string must be a 'standard' length if it's short
         let length = resultString.chars().count();
-        let mut lengthOK = false;
-        let mut maxAllowedLength = 0;
-        for allowedLength in allowedLengths {
-            if length == allowedLength as usize {
+        if let Some(lengths) = &hints.ITFLengths {
+            if !lengths.contains(&(length as u32)) {
+                return Err(Exceptions::FORMAT);
+            }
+        } else {
+            let allowedLengths = if let Some(al) = &hints.AllowedLengths {
+                al.clone()
+            } else {
+                DEFAULT_ALLOWED_LENGTHS.to_vec()
+            };
+
+            // To avoid false positives with 2D barcodes (and other patterns), make
+            // an assumption that the decoded string must be a 'standard' length if it's short
+            let mut lengthOK = false;
+            let mut maxAllowedLength = 0;
+            for allowedLength in allowedLengths {
+                if length == allowedLength as usize {
+                    lengthOK = true;
+                    break;
+                }
+                maxAllowedLength = std::cmp::max(allowedLength, maxAllowedLength);
+            }
+            if !lengthOK && length > maxAllowedLength as usize {
                 lengthOK = true;
-                break;
             }
-            maxAllowedLength = std::cmp::max(allowedLength, maxAllowedLength);
-        }
-        if !lengthOK && length > maxAllowedLength as usize {
-            lengthOK = true;
-        }
-        if !lengthOK {
-            return Err(Exceptions::FORMAT);
+            if !lengthOK {
+                return Err(Exceptions::FORMAT);
+            }
         }
 
+        let policy = hints.ITFCheckDigit.unwrap_or_default();
+        let dataLength = policy.apply(length, || Self::isCheckDigitValid(&resultString))?;
+        let resultString = &resultString[..dataLength];
+
         let mut resultObject = RXingResult::new(
-            &resultString,
+            resultString,
             Vec::new(), // no natural byte representation for these barcodes
             vec![
                 point(startRange[1] as f32, rowNumber as f32),
@@ -156,18 +186,78 @@ impl OneDReader for ITFReader {
             BarcodeFormat::ITF,
         );
 
+        let narrowLineWidth = (startRange[1] - startRange[0]) as f32 / 4.0;
+        if !one_d_reader::has_strict_quiet_zones(
+            &row,
+            startRange[0],
+            endRange[1],
+            narrowLineWidth,
+            10,
+        )? {
+            resultObject.addWarning(DecodeWarning::QuietZoneViolation);
+        }
+
+        // ISO/IEC 15424: 1 if the check digit was verified and is returned, 3 if it was verified
+        // and removed
+        let symbologyModifier = match (policy.validates(), policy.strips()) {
+            (true, false) => 1,
+            (true, true) => 3,
+            _ => 0,
+        };
         resultObject.putMetadata(
             RXingResultMetadataType::SYMBOLOGY_IDENTIFIER,
-            RXingResultMetadataValue::SymbologyIdentifier("]I0".to_owned()),
+            RXingResultMetadataValue::SymbologyIdentifier(format!("]I{symbologyModifier}")),
         );
 
         Ok(resultObject)
     }
+
+    fn locate_start_pattern(
+        &mut self,
+        row: &crate::common::BitArray,
+        hints: &crate::DecodeHints,
+    ) -> Option<([usize; 2], f32)> {
+        self.strictness = hints.Strictness.unwrap_or_default();
+        let [start, end] = self.decodeStart(row).ok()?;
+        // the start pattern is 4 narrow lines
+        Some(([start, end], (end - start) as f32 / 4.0))
+    }
+
+    fn has_stop_pattern(&mut self, row: &crate::common::BitArray, end: usize, module: f32) -> bool {
+        END_PATTERN_REVERSED.iter().any(|reversed| {
+            let mut pattern = *reversed;
+            pattern.reverse();
+            one_d_reader::is_pattern_before(row, end, &pattern, module)
+        })
+    }
 }
 impl ITFReader {
+    /**
+     * Checks the mod 10 check digit at the end of the digits, computed as for GS1 keys: the
+     * other digits are weighted 3 and 1 alternately, starting with 3 next to the check digit.
+     */
+    fn isCheckDigitValid(digits: &str) -> bool {
+        let Some((last, data)) = digits.as_bytes().split_last() else {
+            return false;
+        };
+        // the digits were decoded from bars, so they are ASCII
+        (last - b'0') as u32 == ITFWriter::checkDigit(&digits[..data.len()])
+    }
+
+    /**
+     * Whether a payload of a number of narrow line widths may hold a number of digits. Each
+     * pair of digits takes 6 narrow and 4 wide lines, 14 to 18 narrow line widths as wide lines
+     * are 2 to 3 times as wide; a quarter more is allowed either way for print and scan growth.
+     */
+    fn fitsLength(length: u32, modules: f32) -> bool {
+        let pairs = (length / 2) as f32;
+        length % 2 == 0 && modules >= pairs * 14.0 * 0.75 && modules <= pairs * 18.0 * 1.25
+    }
+
     /**
      * @param row          row of black/white values to search
      * @param payloadStart offset of start pattern
+     * @param maxLength    the number of digits beyond which decoding fails
      * @param resultString {@link StringBuilder} to append decoded chars to
      * @throws NotFoundException if decoding could not complete successfully
      */
@@ -176,6 +266,7 @@ impl ITFReader {
         row: &BitArray,
         payloadStart: usize,
         payloadEnd: usize,
+        maxLength: usize,
         resultString: &mut String,
     ) -> Result<()> {
         let mut payloadStart = payloadStart;
@@ -188,6 +279,9 @@ impl ITFReader {
         let mut counterWhite = [0_u32; 5]; //new int[5];
 
         while payloadStart < payloadEnd {
+            if resultString.len() >= maxLength {
+                return Err(Exceptions::NOT_FOUND);
+            }
             // Get 10 runs of black/white.
             one_d_reader::record_pattern(row, payloadStart, &mut counterDigitPair)?;
             // Split them into each array
@@ -245,10 +339,17 @@ impl ITFReader {
      * @throws NotFoundException if the quiet zone cannot be found
      */
     fn validateQuietZone(&self, row: &BitArray, startPattern: usize) -> Result<()> {
-        let mut quietCount = self.narrowLineWidth * 10; // expect to find this many pixels of quiet zone
+        // expect to find this many pixels of quiet zone
+        let mut quietCount = self.strictness.quiet_zone(
+            self.narrowLineWidth as usize * 10,
+            self.narrowLineWidth as f32,
+            10,
+        ) as i32;
 
         // if there are not so many pixel at all let's try as many as possible
-        quietCount = quietCount.min(startPattern as i32);
+        if self.strictness.allows_cut_off_quiet_zone() {
+            quietCount = quietCount.min(startPattern as i32);
+        }
 
         let mut i = startPattern as isize - 1;
         while quietCount > 0 && i >= 0 {
@@ -355,8 +456,8 @@ impl ITFReader {
                     if one_d_reader::pattern_match_variance(
                         &counters,
                         pattern,
-                        MAX_INDIVIDUAL_VARIANCE,
-                    ) < MAX_AVG_VARIANCE
+                        self.strictness.tolerance(MAX_INDIVIDUAL_VARIANCE),
+                    ) < self.strictness.tolerance(MAX_AVG_VARIANCE)
                     {
                         return Ok([patternStart, x]);
                     }
@@ -385,11 +486,12 @@ impl ITFReader {
      * @throws NotFoundException if digit cannot be decoded
      */
     fn decodeDigit(&self, counters: &[u32]) -> Result<u32> {
-        let mut bestVariance = MAX_AVG_VARIANCE; // worst variance we'll accept
+        let mut bestVariance = self.strictness.tolerance(MAX_AVG_VARIANCE); // worst variance we'll accept
         let mut bestMatch = -1_isize;
+        let maxIndividualVariance = self.strictness.tolerance(MAX_INDIVIDUAL_VARIANCE);
         for (i, pattern) in PATTERNS.iter().enumerate() {
             let variance =
-                one_d_reader::pattern_match_variance(counters, pattern, MAX_INDIVIDUAL_VARIANCE);
+                one_d_reader::pattern_match_variance(counters, pattern, maxIndividualVariance);
             if variance < bestVariance {
                 bestVariance = variance;
                 bestMatch = i as isize;
@@ -406,3 +508,100 @@ impl ITFReader {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        common::BitArray,
+        oned::{ITFWriter, OneDReader},
+        BarcodeFormat, DecodeHints, Exceptions, RXingResultMetadataType, RXingResultMetadataValue,
+        Writer,
+    };
+
+    use super::super::CheckDigitPolicy;
+
+    use super::ITFReader;
+
+    fn decode(contents: &str, hints: &DecodeHints) -> Result<String, Exceptions> {
+        decode_result(contents, hints).map(|result| result.getText().to_owned())
+    }
+
+    fn decode_result(
+        contents: &str,
+        hints: &DecodeHints,
+    ) -> Result<crate::RXingResult, Exceptions> {
+        let matrix = ITFWriter
+            .encode(contents, &BarcodeFormat::ITF, 0, 0)
+            .expect("encode");
+        let mut row = BitArray::with_size(matrix.getWidth() as usize);
+        for x in 0..matrix.getWidth() {
+            if matrix.get(x, 0) {
+                row.set(x as usize);
+            }
+        }
+        ITFReader::default().decode_row(0, &row, hints)
+    }
+
+    #[test]
+    fn testSymbologyIdentifier() {
+        let identifier = |policy| {
+            let hints = DecodeHints {
+                ITFCheckDigit: Some(policy),
+                ..Default::default()
+            };
+            let result = decode_result("15400141288763", &hints).expect("decode");
+            match result
+                .getRXingResultMetadata()
+                .get(&RXingResultMetadataType::SYMBOLOGY_IDENTIFIER)
+            {
+                Some(RXingResultMetadataValue::SymbologyIdentifier(identifier)) => {
+                    identifier.clone()
+                }
+                other => panic!("no symbology identifier: {other:?}"),
+            }
+        };
+        assert_eq!("]I0", identifier(CheckDigitPolicy::Ignore));
+        assert_eq!("]I1", identifier(CheckDigitPolicy::Validate));
+        assert_eq!("]I3", identifier(CheckDigitPolicy::Require));
+        assert_eq!("]I0", identifier(CheckDigitPolicy::Strip));
+    }
+
+    #[test]
+    fn testITFLengths() {
+        let itf14 = DecodeHints {
+            ITFLengths: Some(vec![14]),
+            ..Default::default()
+        };
+        assert_eq!(
+            Ok("15400141288763".to_owned()),
+            decode("15400141288763", &itf14)
+        );
+        // too narrow, so not decoded at all
+        assert_eq!(Err(Exceptions::NOT_FOUND), decode("123456", &itf14));
+        // longer than the longest length, which ALLOWED_LENGTHS would accept
+        let long = "1234567890123456";
+        assert_eq!(Ok(long.to_owned()), decode(long, &DecodeHints::default()));
+        let allowedLengths = DecodeHints {
+            AllowedLengths: Some(vec![14]),
+            ..Default::default()
+        };
+        assert_eq!(Ok(long.to_owned()), decode(long, &allowedLengths));
+        assert!(decode(long, &itf14).is_err());
+
+        let lengths = DecodeHints {
+            ITFLengths: Some(vec![6, 14]),
+            ..Default::default()
+        };
+        assert_eq!(Ok("123456".to_owned()), decode("123456", &lengths));
+        assert!(decode("12345678", &lengths).is_err());
+    }
+
+    #[test]
+    fn testFitsLength() {
+        // 7 pairs of 16 narrow line widths at a wide to narrow ratio of 2.5
+        assert!(ITFReader::fitsLength(14, 7.0 * 16.0));
+        assert!(!ITFReader::fitsLength(14, 3.0 * 16.0));
+        assert!(!ITFReader::fitsLength(6, 7.0 * 16.0));
+        assert!(!ITFReader::fitsLength(13, 7.0 * 16.0));
+    }
+}
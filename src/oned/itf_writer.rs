@@ -14,23 +14,121 @@
  * limitations under the License.
  */
 
-use rxing_one_d_proc_derive::OneDWriter;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
-use crate::common::Result;
-use crate::BarcodeFormat;
+use crate::common::{BitMatrix, Result};
+use crate::{BarcodeFormat, EncodeHints, Exceptions, Writer};
 
 use super::OneDimensionalCodeWriter;
 
+/**
+ * The bearer bars drawn around an ITF barcode, which keep a skewed scan from reading a short
+ * code off part of the symbol and even out the pressure of the printing plate on corrugated
+ * board.
+ */
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ITFBearerBars {
+    /// Bars across the top and the bottom of the symbol and its quiet zones
+    Horizontal,
+    /// A frame all around the symbol and its quiet zones, as ITF-14 asks for
+    Frame,
+}
+
 /**
  * This object renders a ITF code as a {@link BitMatrix}.
  *
+ * The `ITFCheckDigit`, `ITFBearerBars` and `ITFWideToNarrowRatio` hints append a check digit,
+ * draw bearer bars and narrow the wide bars, as ITF-14 case codes need. Quiet zones given with
+ * the `Margin` hint are in narrow bars.
+ *
  * @author erik.barbara@gmail.com (Erik Barbara)
  */
-#[derive(OneDWriter, Default)]
+#[derive(Default)]
 pub struct ITFWriter;
 
+impl Writer for ITFWriter {
+    fn encode(
+        &self,
+        contents: &str,
+        format: &BarcodeFormat,
+        width: i32,
+        height: i32,
+    ) -> Result<BitMatrix> {
+        self.encode_with_hints(contents, format, width, height, &EncodeHints::default())
+    }
+
+    fn encode_with_hints(
+        &self,
+        contents: &str,
+        format: &BarcodeFormat,
+        width: i32,
+        height: i32,
+        hints: &EncodeHints,
+    ) -> Result<BitMatrix> {
+        if contents.is_empty() {
+            return Err(Exceptions::illegal_argument_with("Found empty contents"));
+        }
+        if format != &BarcodeFormat::ITF {
+            return Err(Exceptions::illegal_argument_with(format!(
+                "Can only encode ITF, but got {format:?}"
+            )));
+        }
+        if width < 0 || height < 0 {
+            return Err(Exceptions::illegal_argument_with(format!(
+                "Negative size is not allowed. Input: {width}x{height}"
+            )));
+        }
+
+        let sidesMargin = if let Some(margin) = &hints.Margin {
+            margin
+                .parse::<u32>()
+                .map_err(|e| Exceptions::parse_with(format!("could not parse {margin}: {e}")))?
+        } else {
+            self.getDefaultMargin()
+        };
+        let (narrow, _) = Self::barWidths(hints)?;
+        // bearer bars end the quiet zones, which have to be the full 10 narrow bars on each side
+        let sidesMargin = if hints.ITFBearerBars.is_some() {
+            sidesMargin.max(2 * BEARER_QUIET_ZONE)
+        } else {
+            sidesMargin
+        } * narrow as u32;
+
+        let code = self.encode_oned_with_hints(contents, hints)?;
+        match hints.ITFBearerBars {
+            Some(bearerBars) => render_with_bearer_bars(
+                &code,
+                width,
+                height,
+                sidesMargin,
+                BEARER_BAR_WIDTH * narrow,
+                bearerBars == ITFBearerBars::Frame,
+            ),
+            None => Self::renderRXingResult(&code, width, height, sidesMargin),
+        }
+    }
+}
+
 impl OneDimensionalCodeWriter for ITFWriter {
     fn encode_oned(&self, contents: &str) -> Result<Vec<bool>> {
+        self.encode_oned_with_hints(contents, &EncodeHints::default())
+    }
+
+    fn encode_oned_with_hints(&self, contents: &str, hints: &EncodeHints) -> Result<Vec<bool>> {
+        Self::checkNumeric(contents)?;
+        let contents = if matches!(hints.ITFCheckDigit, Some(true)) {
+            if contents.chars().count() % 2 == 0 {
+                return Err(Exceptions::illegal_argument_with(
+                    "The length of the input should be odd, to be even with the check digit",
+                ));
+            }
+            format!("{contents}{}", Self::checkDigit(contents))
+        } else {
+            contents.to_owned()
+        };
+
         let length = contents.chars().count();
         if length % 2 != 0 {
             return Err(Exceptions::illegal_argument_with(
@@ -43,48 +141,122 @@ impl OneDimensionalCodeWriter for ITFWriter {
             )));
         }
 
-        Self::checkNumeric(contents)?;
-
-        let mut result = vec![false; 9 + 9 * length];
-        let mut pos = Self::appendPattern(&mut result, 0, &START_PATTERN, true) as usize;
-        let mut i = 0;
-        while i < length {
-            let one = contents
-                .chars()
-                .nth(i)
-                .ok_or(Exceptions::INDEX_OUT_OF_BOUNDS)?
-                .to_digit(10)
-                .ok_or(Exceptions::PARSE)? as usize;
-            let two = contents
-                .chars()
-                .nth(i + 1)
-                .ok_or(Exceptions::INDEX_OUT_OF_BOUNDS)?
-                .to_digit(10)
-                .ok_or(Exceptions::PARSE)? as usize;
+        let (narrow, wide) = Self::barWidths(hints)?;
+        let widths = |pattern: &[usize]| -> Vec<usize> {
+            pattern
+                .iter()
+                .map(|&width| if width == W { wide } else { narrow })
+                .collect()
+        };
+        let digits = contents
+            .chars()
+            .map(|c| c.to_digit(10).map(|digit| digit as usize))
+            .collect::<Option<Vec<usize>>>()
+            .ok_or(Exceptions::PARSE)?;
+        let mut result = vec![false; (4 + 3 * length + 2) * narrow + (2 * length + 1) * wide];
+        let mut pos = Self::appendPattern(&mut result, 0, &widths(&START_PATTERN), true) as usize;
+        for pair in digits.chunks(2) {
             let mut encoding = [0; 10];
             for j in 0..5 {
-                encoding[2 * j] = PATTERNS[one][j];
-                encoding[2 * j + 1] = PATTERNS[two][j];
+                encoding[2 * j] = PATTERNS[pair[0]][j];
+                encoding[2 * j + 1] = PATTERNS[pair[1]][j];
             }
-            pos += Self::appendPattern(&mut result, pos, &encoding, true) as usize;
-
-            i += 2;
+            pos += Self::appendPattern(&mut result, pos, &widths(&encoding), true) as usize;
         }
-        Self::appendPattern(&mut result, pos, &END_PATTERN, true);
+        Self::appendPattern(&mut result, pos, &widths(&END_PATTERN), true);
 
         Ok(result)
     }
+
     fn getSupportedWriteFormats(&self) -> Option<Vec<crate::BarcodeFormat>> {
         Some(vec![BarcodeFormat::ITF])
     }
 }
 
+impl ITFWriter {
+    /**
+     * The mod 10 check digit of `digits`, computed as for GS1 keys: the digits are weighted 3
+     * and 1 alternately, starting with 3 at the last one.
+     */
+    pub fn checkDigit(digits: &str) -> u32 {
+        let weightedSum: u32 = digits
+            .chars()
+            .rev()
+            .filter_map(|c| c.to_digit(10))
+            .enumerate()
+            .map(|(i, digit)| digit * if i % 2 == 0 { 3 } else { 1 })
+            .sum();
+        (10 - weightedSum % 10) % 10
+    }
+
+    /// The widths of the narrow and wide bars in modules, as close to the ratio as they come
+    fn barWidths(hints: &EncodeHints) -> Result<(usize, usize)> {
+        let ratio = hints.ITFWideToNarrowRatio.unwrap_or(W as f32);
+        if !(2.0..=3.0).contains(&ratio) {
+            return Err(Exceptions::illegal_argument_with(format!(
+                "The wide to narrow ratio should be from 2.0 to 3.0, but got {ratio}"
+            )));
+        }
+        let error = |narrow: usize| ((ratio * narrow as f32).round() / narrow as f32 - ratio).abs();
+        let narrow = (1..=4)
+            .min_by(|a, b| error(*a).total_cmp(&error(*b)))
+            .unwrap_or(N);
+        Ok((narrow, (ratio * narrow as f32).round() as usize))
+    }
+}
+
+/**
+ * Renders the bars between bearer bars, which run over the quiet zones and are as wide as
+ * `bearerWidth` modules. The bars are at least as high as the two bearer bars together.
+ */
+fn render_with_bearer_bars(
+    code: &[bool],
+    width: i32,
+    height: i32,
+    sidesMargin: u32,
+    bearerWidth: usize,
+    frame: bool,
+) -> Result<BitMatrix> {
+    let frameWidth = if frame { bearerWidth } else { 0 };
+    let fullWidth = code.len() + sidesMargin as usize + 2 * frameWidth;
+    let outputWidth = (width as usize).max(fullWidth);
+    let multiple = outputWidth / fullWidth;
+    let bearer = (bearerWidth * multiple) as u32;
+    let outputHeight = (height as u32).max(4 * bearer);
+
+    let mut output = BitMatrix::new(outputWidth as u32, outputHeight)?;
+    let left = ((outputWidth - fullWidth * multiple) / 2) as u32;
+    let symbolWidth = (fullWidth * multiple) as u32;
+    output.setRegion(left, 0, symbolWidth, bearer)?;
+    output.setRegion(left, outputHeight - bearer, symbolWidth, bearer)?;
+    if frame {
+        output.setRegion(left, 0, bearer, outputHeight)?;
+        output.setRegion(left + symbolWidth - bearer, 0, bearer, outputHeight)?;
+    }
+
+    let codeLeft = (outputWidth - code.len() * multiple) / 2;
+    for (i, _) in code.iter().enumerate().filter(|(_, dark)| **dark) {
+        output.setRegion(
+            (codeLeft + i * multiple) as u32,
+            bearer,
+            multiple as u32,
+            outputHeight - 2 * bearer,
+        )?;
+    }
+    Ok(output)
+}
+
 const START_PATTERN: [usize; 4] = [1, 1, 1, 1];
 const END_PATTERN: [usize; 3] = [3, 1, 1];
 
 const W: usize = 3; // Pixel width of a 3x wide line
 const N: usize = 1; // Pixed width of a narrow line
 
+// The width of bearer bars in narrow bars, 4.8 mm at the nominal 1.016 mm narrow bar of ITF-14
+const BEARER_BAR_WIDTH: usize = 5;
+// The quiet zone on each side of a symbol with bearer bars, in narrow bars
+const BEARER_QUIET_ZONE: u32 = 10;
+
 // See ITFReader.PATTERNS
 
 const PATTERNS: [[usize; 5]; 10] = [
@@ -105,9 +277,93 @@ const PATTERNS: [[usize; 5]; 10] = [
  */
 #[cfg(test)]
 mod ITFWriterTestCase {
-    use crate::{common::bit_matrix_test_case, BarcodeFormat, Writer};
+    use crate::{
+        common::{bit_matrix_test_case, BitMatrix},
+        oned::{CheckDigitPolicy, ITFReader, OneDReader},
+        BarcodeFormat, DecodeHints, EncodeHints, Writer,
+    };
+
+    use super::{ITFBearerBars, ITFWriter};
+
+    fn encode(contents: &str, hints: &EncodeHints) -> BitMatrix {
+        ITFWriter
+            .encode_with_hints(contents, &BarcodeFormat::ITF, 0, 0, hints)
+            .expect("encode")
+    }
 
-    use super::ITFWriter;
+    fn decode_row(matrix: &BitMatrix, y: u32) -> String {
+        let hints = DecodeHints {
+            ITFCheckDigit: Some(CheckDigitPolicy::Validate),
+            ..Default::default()
+        };
+        ITFReader::default()
+            .decode_row(y, &matrix.getRow(y), &hints)
+            .expect("decode")
+            .getText()
+            .to_owned()
+    }
+
+    #[test]
+    fn testCheckDigit() {
+        let hints = EncodeHints {
+            ITFCheckDigit: Some(true),
+            ..Default::default()
+        };
+        assert_eq!(
+            encode("15400141288763", &EncodeHints::default()),
+            encode("1540014128876", &hints)
+        );
+        assert_eq!(3, ITFWriter::checkDigit("1540014128876"));
+        assert!(ITFWriter
+            .encode_with_hints("154001412887", &BarcodeFormat::ITF, 0, 0, &hints)
+            .is_err());
+    }
+
+    #[test]
+    fn testWideToNarrowRatio() {
+        let ratio = |ratio| EncodeHints {
+            ITFWideToNarrowRatio: Some(ratio),
+            ..Default::default()
+        };
+        // narrow bars of 2 modules and wide bars of 5 for 2.5, the start pattern first
+        let matrix = encode("15400141288763", &ratio(2.5));
+        assert!(
+            bit_matrix_test_case::matrix_to_string(&matrix).starts_with("00000000001100110011111")
+        );
+        assert_eq!("15400141288763", decode_row(&matrix, 0));
+        assert_eq!(
+            "15400141288763",
+            decode_row(&encode("15400141288763", &ratio(2.0)), 0)
+        );
+        assert!(ITFWriter
+            .encode_with_hints("12", &BarcodeFormat::ITF, 0, 0, &ratio(3.5))
+            .is_err());
+    }
+
+    #[test]
+    fn testBearerBars() {
+        let bearerBars = |bearerBars| EncodeHints {
+            ITFBearerBars: Some(bearerBars),
+            ..Default::default()
+        };
+        let plain = encode("15400141288763", &EncodeHints::default());
+        let horizontal = encode("15400141288763", &bearerBars(ITFBearerBars::Horizontal));
+        // the quiet zones grow from 5 narrow bars to 10
+        assert_eq!(plain.getWidth() + 10, horizontal.getWidth());
+        assert_eq!(20, horizontal.getHeight());
+        for x in 0..horizontal.getWidth() {
+            assert!(horizontal.get(x, 0) && horizontal.get(x, 19));
+        }
+        assert_eq!("15400141288763", decode_row(&horizontal, 10));
+
+        let frame = encode("15400141288763", &bearerBars(ITFBearerBars::Frame));
+        assert_eq!(horizontal.getWidth() + 10, frame.getWidth());
+        for y in 0..frame.getHeight() {
+            assert!(frame.get(0, y) && frame.get(frame.getWidth() - 1, y));
+        }
+        assert!(!frame.get(5, 10));
+        assert_eq!("15400141288763", decode_row(&frame, 10));
+    }
 
     #[test]
     fn testEncode() {
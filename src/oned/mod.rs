@@ -3,6 +3,18 @@ pub mod rss;
 
 pub use one_d_reader::*;
 
+mod row_sampling;
+pub use row_sampling::*;
+
+mod scanline_witness;
+pub use scanline_witness::*;
+
+mod check_digit_policy;
+pub use check_digit_policy::*;
+
+mod msi_checksum;
+pub use msi_checksum::*;
+
 mod ean_manufacturer_org_support;
 pub use ean_manufacturer_org_support::*;
 
@@ -27,6 +39,12 @@ pub use itf_reader::*;
 mod telepen_reader;
 pub use telepen_reader::*;
 
+mod pharmacode_reader;
+pub use pharmacode_reader::*;
+
+mod msi_reader;
+pub use msi_reader::*;
+
 mod upc_ean_reader;
 pub use upc_ean_reader::*;
 
@@ -38,6 +56,9 @@ pub use upc_ean_extension_2_support::*;
 pub use upc_ean_extension_5_support::*;
 pub use upc_ean_extension_support::*;
 
+mod upc_ean_with_extension;
+pub use upc_ean_with_extension::*;
+
 mod ean_8_reader;
 pub use ean_8_reader::*;
 
@@ -83,9 +104,18 @@ pub use ean_13_writer::*;
 mod telepen_writer;
 pub use telepen_writer::*;
 
+mod pharmacode_writer;
+pub use pharmacode_writer::*;
+
+mod msi_writer;
+pub use msi_writer::*;
+
 mod upc_ean_writer;
 pub use upc_ean_writer::*;
 
+mod upc_ean_extension_writer;
+pub use upc_ean_extension_writer::*;
+
 mod ean_8_writer;
 pub use ean_8_writer::*;
 
@@ -94,5 +124,7 @@ pub use upc_e_writer::*;
 
 mod telepen_common;
 
+mod pharmacode_common;
+
 #[cfg(feature = "experimental_features")]
 pub mod cpp;
@@ -0,0 +1,158 @@
+/*
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/**
+ * The check digits of an MSI Plessey barcode, which are appended to the data digits.
+ *
+ * Mod 10 is the Luhn check digit. Mod 11 weighs the digits 2 to 7 from the right, as IBM
+ * does, and is written as "10" where the check digit would be 10. The double checksums append
+ * a mod 10 check digit over the data and the first check digit.
+ */
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MSIChecksum {
+    Mod10,
+    Mod11,
+    Mod10Mod10,
+    Mod11Mod10,
+}
+
+impl MSIChecksum {
+    /// The check digits for the data digits, or `None` if `data` is not all ascii digits
+    pub fn check_digits(&self, data: &str) -> Option<String> {
+        if !data.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        Some(match self {
+            MSIChecksum::Mod10 => mod_10(data).to_string(),
+            MSIChecksum::Mod11 => mod_11(data).to_string(),
+            MSIChecksum::Mod10Mod10 | MSIChecksum::Mod11Mod10 => {
+                let first = if *self == MSIChecksum::Mod10Mod10 {
+                    mod_10(data)
+                } else {
+                    mod_11(data)
+                };
+                let with_first = format!("{data}{first}");
+                format!("{first}{}", mod_10(&with_first))
+            }
+        })
+    }
+
+    /**
+     * The number of data digits of a barcode, which end where its check digits start.
+     *
+     * @param digits the digits of the barcode, check digits included
+     * @param validate whether the check digits must be valid
+     * @return the number of data digits, or `None` if the check digits are invalid, no data
+     *  would be left or `digits` are not all ascii digits
+     */
+    pub fn data_length(&self, digits: &str, validate: bool) -> Option<usize> {
+        if !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        // mod 11 takes two digits for a check digit of 10
+        let counts: &[usize] = match self {
+            MSIChecksum::Mod10 => &[1],
+            MSIChecksum::Mod11 => &[1, 2],
+            MSIChecksum::Mod10Mod10 => &[2],
+            MSIChecksum::Mod11Mod10 => &[2, 3],
+        };
+        let fits = |count: &&usize| **count < digits.len();
+        counts
+            .iter()
+            .filter(fits)
+            .map(|count| digits.len() - count)
+            .find(|data| self.check_digits(&digits[..*data]).as_deref() == Some(&digits[*data..]))
+            .or_else(|| {
+                counts
+                    .iter()
+                    .find(fits)
+                    .filter(|_| !validate)
+                    .map(|count| digits.len() - count)
+            })
+    }
+}
+
+/// The Luhn check digit: every other digit from the right is doubled, and the digits of the
+/// products summed
+fn mod_10(data: &str) -> u32 {
+    let sum: u32 = data
+        .bytes()
+        .rev()
+        .enumerate()
+        .map(|(i, digit)| {
+            let digit = u32::from(digit - b'0');
+            if i % 2 == 0 {
+                let doubled = digit * 2;
+                doubled / 10 + doubled % 10
+            } else {
+                digit
+            }
+        })
+        .sum();
+    (10 - sum % 10) % 10
+}
+
+fn mod_11(data: &str) -> u32 {
+    let sum: u32 = data
+        .bytes()
+        .rev()
+        .enumerate()
+        .map(|(i, digit)| u32::from(digit - b'0') * (i as u32 % 6 + 2))
+        .sum();
+    (11 - sum % 11) % 11
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MSIChecksum::*;
+
+    #[test]
+    fn test_check_digits() {
+        assert_eq!(Some("4".to_owned()), Mod10.check_digits("1234567"));
+        assert_eq!(Some("4".to_owned()), Mod11.check_digits("1234567"));
+        assert_eq!(Some("10".to_owned()), Mod11.check_digits("6"));
+        assert_eq!(Some("0".to_owned()), Mod11.check_digits("0"));
+        assert_eq!(Some("41".to_owned()), Mod10Mod10.check_digits("1234567"));
+        assert_eq!(Some("41".to_owned()), Mod11Mod10.check_digits("1234567"));
+        assert_eq!(Some("106".to_owned()), Mod11Mod10.check_digits("6"));
+    }
+
+    #[test]
+    fn test_not_digits() {
+        assert_eq!(None, Mod10.check_digits("12a4"));
+        assert_eq!(None, Mod11.check_digits("12 4"));
+        assert_eq!(None, Mod11Mod10.check_digits("١٢٣"));
+        assert_eq!(None, Mod10.data_length("12a4", false));
+        assert_eq!(None, Mod10.data_length("1é4", false));
+    }
+
+    #[test]
+    fn test_data_length() {
+        assert_eq!(Some(7), Mod10.data_length("12345674", true));
+        assert_eq!(None, Mod10.data_length("12345675", true));
+        assert_eq!(Some(7), Mod10.data_length("12345675", false));
+        assert_eq!(None, Mod10.data_length("4", false));
+        // 610 is 6 with the check digit 10, 61 has the check digit 2
+        assert_eq!(Some(1), Mod11.data_length("610", true));
+        assert_eq!(Some(2), Mod11.data_length("612", true));
+        assert_eq!(Some(2), Mod11.data_length("613", false));
+        assert_eq!(Some(7), Mod10Mod10.data_length("123456741", true));
+        assert_eq!(Some(1), Mod11Mod10.data_length("6106", true));
+        assert_eq!(None, Mod11Mod10.data_length("6107", true));
+    }
+}
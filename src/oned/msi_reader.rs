@@ -0,0 +1,287 @@
+/*
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use rxing_one_d_proc_derive::OneDReader;
+
+use crate::common::{BitArray, Result};
+use crate::oned::{CheckDigitPolicy, MSIChecksum};
+use crate::{
+    point, BarcodeFormat, DecodeHints, Exceptions, RXingResult, RXingResultMetadataType,
+    RXingResultMetadataValue,
+};
+
+use super::OneDReader;
+
+// the quiet zones are wider than this many modules, twice as wide as the widest space
+const QUIET_ZONE_MODULES: f32 = 3.5;
+
+/**
+ * <p>Decodes MSI Plessey barcodes. Every digit is written as 4 bits, a 1 being a wide bar and a
+ * narrow space and a 0 a narrow bar and a wide space, between a start pattern of a 1 bit and a
+ * stop pattern of a 0 bit and a narrow bar.</p>
+ *
+ * <p>The check digits, which are optional, are treated as the `MSICheckDigit` and
+ * `MSIChecksum` hints say. As MSI has no characters but digits and no check digit by
+ * definition, `MultiFormatOneDReader` only tries this reader if MSI is one of the possible
+ * formats.</p>
+ */
+#[derive(OneDReader, Default)]
+pub struct MSIReader;
+
+impl OneDReader for MSIReader {
+    fn decode_row(
+        &mut self,
+        rowNumber: u32,
+        row: &BitArray,
+        hints: &DecodeHints,
+    ) -> Result<RXingResult> {
+        // the widths of the bars and spaces from the first bar on, the bars at even indices
+        let size = row.get_size();
+        let first = row.getNextSet(0);
+        let mut runs = Vec::new();
+        let mut x = first;
+        while x < size {
+            let end = if runs.len() % 2 == 0 {
+                row.getNextUnset(x)
+            } else {
+                row.getNextSet(x)
+            };
+            runs.push(end - x);
+            x = end;
+        }
+
+        let mut start = first;
+        for i in (0..runs.len()).step_by(2) {
+            let quiet_zone = if i == 0 { first } else { runs[i - 1] };
+            if let Some((digits, width)) = decode_digits(&runs[i..], quiet_zone) {
+                if let Ok(text) = apply_check_digits(digits, hints) {
+                    let mut result = RXingResult::new(
+                        &text,
+                        Vec::new(),
+                        vec![
+                            point(start as f32, rowNumber as f32),
+                            point((start + width) as f32, rowNumber as f32),
+                        ],
+                        BarcodeFormat::MSI,
+                    );
+                    // AIM gives it no identifier of its own, so it is reported as another symbology
+                    result.putMetadata(
+                        RXingResultMetadataType::SYMBOLOGY_IDENTIFIER,
+                        RXingResultMetadataValue::SymbologyIdentifier("]X0".to_owned()),
+                    );
+                    return Ok(result);
+                }
+            }
+            start += runs[i] + runs.get(i + 1).copied().unwrap_or(0);
+        }
+
+        Err(Exceptions::NOT_FOUND)
+    }
+}
+
+/**
+ * Reads the digits of a barcode whose start pattern begins with the first of `runs`.
+ *
+ * @return the digits, and the width of the barcode in pixels
+ */
+fn decode_digits(runs: &[usize], quiet_zone: usize) -> Option<(String, usize)> {
+    // the start pattern is a 1 bit
+    let (bar, space) = (*runs.first()?, *runs.get(1)?);
+    if bar <= space {
+        return None;
+    }
+    let mut module = (bar + space) as f32 / 3.0;
+    if (quiet_zone as f32) < QUIET_ZONE_MODULES * module {
+        return None;
+    }
+
+    let mut bits = Vec::new();
+    let mut width = bar + space;
+    let mut i = 2;
+    loop {
+        let bar = *runs.get(i)?;
+        let space = runs.get(i + 1).copied().unwrap_or(0);
+        if space as f32 > QUIET_ZONE_MODULES * module {
+            // the last bar of the stop pattern, whose narrow bar and wide space were read as a
+            // 0 bit
+            if bits.pop() != Some(false)
+                || bits.is_empty()
+                || bits.len() % 4 != 0
+                || bar as f32 > 1.5 * module
+            {
+                return None;
+            }
+            let digits = bits
+                .chunks(4)
+                .map(|bits| {
+                    let value = bits
+                        .iter()
+                        .fold(0, |value, bit| value * 2 + u32::from(*bit));
+                    char::from_digit(value, 10)
+                })
+                .collect::<Option<String>>()?;
+            return Some((digits, width + bar));
+        }
+
+        // every bit is 3 modules wide
+        if !(1.5 * module..=4.5 * module).contains(&((bar + space) as f32)) {
+            return None;
+        }
+        bits.push(bar > space);
+        width += bar + space;
+        module = width as f32 / (3 * (bits.len() + 1)) as f32;
+        i += 2;
+    }
+}
+
+fn apply_check_digits(digits: String, hints: &DecodeHints) -> Result<String> {
+    let policy = hints.MSICheckDigit.unwrap_or_default();
+    if policy == CheckDigitPolicy::Ignore {
+        return Ok(digits);
+    }
+    let checksum = hints.MSIChecksum.unwrap_or(MSIChecksum::Mod10);
+    let data_length = checksum
+        .data_length(&digits, policy.validates())
+        .ok_or(Exceptions::NOT_FOUND)?;
+    Ok(if policy.strips() {
+        digits[..data_length].to_owned()
+    } else {
+        digits
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::{
+        common::{BitArray, BitMatrix},
+        oned::{CheckDigitPolicy, MSIChecksum, MSIWriter, OneDReader},
+        test_fixtures::luma_bitmap,
+        BarcodeFormat, DecodeHintValue, DecodeHints, EncodeHintValue, EncodeHints,
+        MultiFormatReader, Reader, Writer,
+    };
+
+    use super::MSIReader;
+
+    fn encode(contents: &str, checksum: Option<MSIChecksum>) -> BitMatrix {
+        let mut hints = EncodeHints::default();
+        if let Some(checksum) = checksum {
+            hints = hints.with(EncodeHintValue::MSIChecksum(checksum));
+        }
+        MSIWriter
+            .encode_with_hints(contents, &BarcodeFormat::MSI, 0, 0, &hints)
+            .expect("encode")
+    }
+
+    fn decode_row(matrix: &BitMatrix, hints: &DecodeHints) -> Option<String> {
+        let mut row = BitArray::with_size(matrix.getWidth() as usize);
+        for x in 0..matrix.getWidth() {
+            if matrix.get(x, 0) {
+                row.set(x as usize);
+            }
+        }
+        MSIReader
+            .decode_row(0, &row, hints)
+            .ok()
+            .map(|result| result.getText().to_owned())
+    }
+
+    #[test]
+    fn test_decode() {
+        for contents in ["0", "17", "1234567890", "99999999999999"] {
+            let matrix = encode(contents, None);
+            assert_eq!(
+                Some(contents),
+                decode_row(&matrix, &DecodeHints::default()).as_deref()
+            );
+        }
+
+        // the first bit of the 7, after the margin of 5 and 15 modules, turned from 0 to 1 makes 15
+        let matrix = encode("17", None);
+        let mut row = BitArray::with_size(matrix.getWidth() as usize);
+        for x in 0..matrix.getWidth() {
+            if matrix.get(x, 0) != (x == 21) {
+                row.set(x as usize);
+            }
+        }
+        assert!(MSIReader
+            .decode_row(0, &row, &DecodeHints::default())
+            .is_err());
+    }
+
+    #[test]
+    fn test_check_digits() {
+        let require = |checksum| DecodeHints {
+            MSICheckDigit: Some(CheckDigitPolicy::Require),
+            MSIChecksum: Some(checksum),
+            ..Default::default()
+        };
+        for checksum in [
+            MSIChecksum::Mod10,
+            MSIChecksum::Mod11,
+            MSIChecksum::Mod10Mod10,
+            MSIChecksum::Mod11Mod10,
+        ] {
+            for contents in ["6", "1234567"] {
+                let matrix = encode(contents, Some(checksum));
+                assert_eq!(
+                    Some(contents),
+                    decode_row(&matrix, &require(checksum)).as_deref(),
+                    "{checksum:?}"
+                );
+            }
+        }
+
+        // 1234567 with the mod 10 check digit 4
+        let matrix = encode("12345674", None);
+        let validate = DecodeHints {
+            MSICheckDigit: Some(CheckDigitPolicy::Validate),
+            ..Default::default()
+        };
+        assert_eq!(Some("12345674"), decode_row(&matrix, &validate).as_deref());
+        assert_eq!(None, decode_row(&encode("12345675", None), &validate));
+        let strip = DecodeHints {
+            MSICheckDigit: Some(CheckDigitPolicy::Strip),
+            ..Default::default()
+        };
+        assert_eq!(
+            Some("1234567"),
+            decode_row(&encode("12345675", None), &strip).as_deref()
+        );
+    }
+
+    #[test]
+    fn test_multi_format_reader() {
+        let matrix = MSIWriter
+            .encode("1234567", &BarcodeFormat::MSI, 200, 50)
+            .expect("encode");
+        let image = || luma_bitmap(&matrix);
+
+        let hints = DecodeHints::default().with(DecodeHintValue::PossibleFormats(HashSet::from([
+            BarcodeFormat::MSI,
+        ])));
+        let result = MultiFormatReader::default()
+            .decode_with_hints(&mut image(), &hints)
+            .expect("decode");
+        assert_eq!("1234567", result.getText());
+        assert_eq!(BarcodeFormat::MSI, *result.getBarcodeFormat());
+
+        // not tried unless asked for
+        assert!(MultiFormatReader::default()
+            .decode_with_hints(&mut image(), &DecodeHints::default())
+            .map_or(true, |result| *result.getBarcodeFormat()
+                != BarcodeFormat::MSI));
+    }
+}
@@ -0,0 +1,101 @@
+/*
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use rxing_one_d_proc_derive::OneDWriter;
+
+use crate::common::Result;
+use crate::BarcodeFormat;
+
+use super::OneDimensionalCodeWriter;
+
+// a 1 bit is a wide bar and a narrow space, a 0 bit a narrow bar and a wide space
+const ONE: [bool; 3] = [true, true, false];
+const ZERO: [bool; 3] = [true, false, false];
+const STOP: [bool; 4] = [true, false, false, true];
+
+/**
+ * This object renders an MSI Plessey code as a {@link BitMatrix}: a start pattern, the digits
+ * as 4 bits each, any check digits the `MSIChecksum` hint asks for, and a stop pattern.
+ */
+#[derive(OneDWriter, Default)]
+pub struct MSIWriter;
+
+impl OneDimensionalCodeWriter for MSIWriter {
+    fn encode_oned(&self, contents: &str) -> Result<Vec<bool>> {
+        self.encode_oned_with_hints(contents, &EncodeHints::default())
+    }
+
+    fn encode_oned_with_hints(&self, contents: &str, hints: &EncodeHints) -> Result<Vec<bool>> {
+        if !contents.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(Exceptions::illegal_argument_with(
+                "Input should only contain digits 0-9",
+            ));
+        }
+        let check_digits = hints
+            .MSIChecksum
+            .and_then(|checksum| checksum.check_digits(contents))
+            .unwrap_or_default();
+
+        // the start pattern is a 1 bit
+        let mut result = ONE.to_vec();
+        for digit in contents.bytes().chain(check_digits.bytes()) {
+            let value = digit - b'0';
+            for bit in (0..4).rev() {
+                result.extend(if value & (1 << bit) != 0 { ONE } else { ZERO });
+            }
+        }
+        result.extend(STOP);
+        Ok(result)
+    }
+
+    fn getSupportedWriteFormats(&self) -> Option<Vec<BarcodeFormat>> {
+        Some(vec![BarcodeFormat::MSI])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{oned::MSIChecksum, BarcodeFormat, EncodeHintValue, EncodeHints, Writer};
+
+    use super::MSIWriter;
+
+    fn encode(contents: &str, hints: &EncodeHints) -> String {
+        let hints = hints.clone().with(EncodeHintValue::Margin("0".to_owned()));
+        let matrix = MSIWriter
+            .encode_with_hints(contents, &BarcodeFormat::MSI, 0, 0, &hints)
+            .expect("encode");
+        (0..matrix.getWidth())
+            .map(|x| if matrix.get(x, 0) { '1' } else { '0' })
+            .collect()
+    }
+
+    #[test]
+    fn test_encode() {
+        // start, 0001, 0111 and stop
+        assert_eq!(
+            "110100100100110100110110110\
+             1001",
+            encode("17", &EncodeHints::default())
+        );
+        // the check digit of 17 is 4, 0100
+        let hints = EncodeHints::default().with(EncodeHintValue::MSIChecksum(MSIChecksum::Mod10));
+        assert_eq!(encode("174", &EncodeHints::default()), encode("17", &hints));
+
+        assert!(MSIWriter.encode("12A", &BarcodeFormat::MSI, 0, 0).is_err());
+        assert!(MSIWriter.encode("", &BarcodeFormat::MSI, 0, 0).is_err());
+        assert!(MSIWriter
+            .encode("12", &BarcodeFormat::CODE_128, 0, 0)
+            .is_err());
+    }
+}
@@ -21,6 +21,7 @@ use super::Code128Reader;
 use super::Code39Reader;
 use super::Code93Reader;
 use super::ITFReader;
+use super::MSIReader;
 use super::MultiFormatUPCEANReader;
 use super::OneDReader;
 use super::TelepenReader;
@@ -110,6 +111,11 @@ impl OneDReader for MultiFormatOneDReader {
                     return Ok(res);
                 }
             }
+            if possible_formats.contains(&BarcodeFormat::MSI) {
+                if let Ok(res) = MSIReader.decode_row(row_number, row, hints) {
+                    return Ok(res);
+                }
+            }
         } else {
             if let Ok(res) =
                 MultiFormatUPCEANReader::new(internal_hints).decode_row(row_number, row, hints)
@@ -26,7 +26,7 @@ use super::EAN8Reader;
 use super::UPCAReader;
 use super::UPCEReader;
 use super::STAND_IN;
-use super::{OneDReader, UPCEANReader};
+use super::{one_d_reader, OneDReader, UPCEANReader, START_END_PATTERN};
 
 /**
  * <p>A reader that can read all available UPC/EAN formats. If a caller wants to try to
@@ -116,6 +116,21 @@ impl OneDReader for MultiFormatUPCEANReader {
 
         Err(Exceptions::NOT_FOUND)
     }
+
+    fn locate_start_pattern(
+        &mut self,
+        row: &crate::common::BitArray,
+        _hints: &DecodeHints,
+    ) -> Option<([usize; 2], f32)> {
+        let [start, end] = STAND_IN.find_start_guard_pattern(row).ok()?;
+        // the start guard spans 3 modules
+        Some(([start, end], (end - start) as f32 / 3.0))
+    }
+
+    fn has_stop_pattern(&mut self, row: &crate::common::BitArray, end: usize, module: f32) -> bool {
+        // the end guard of UPC-E is longer, but ends the same way
+        one_d_reader::is_pattern_before(row, end, &START_END_PATTERN, module)
+    }
 }
 
 impl MultiFormatUPCEANReader {
@@ -168,6 +183,7 @@ impl MultiFormatUPCEANReader {
                 BarcodeFormat::UPC_A,
             );
             resultUPCA.putAllMetadata(result.getRXingResultMetadata().clone());
+            resultUPCA.addWarnings(result.getWarnings());
 
             return Ok(resultUPCA);
         }
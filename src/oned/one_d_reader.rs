@@ -16,8 +16,9 @@
 
 use crate::{
     common::{BitArray, Result},
-    point, Binarizer, BinaryBitmap, DecodeHints, Exceptions, LuminanceSource, RXingResult,
-    RXingResultMetadataType, RXingResultMetadataValue, Reader,
+    point, record_witness, Binarizer, BinaryBitmap, DecodeHints, Exceptions, LuminanceSource,
+    RXingResult, RXingResultMetadataType, RXingResultMetadataValue, Reader, Strictness,
+    WitnessRecording,
 };
 
 /**
@@ -36,7 +37,7 @@ pub trait OneDReader: Reader {
      * middle + rowStep, then middle - (2 * rowStep), etc.
      * rowStep is bigger as the image is taller, but is always at least 1. We've somewhat arbitrarily
      * decided that moving up and down by about 1/16 of the image is pretty good; we try more of the
-     * image if "trying harder".
+     * image if "trying harder". The `OneDRowSampling` hint picks other rows, see `RowSampling`.
      *
      * @param image The image to decode
      * @param hints Any hints that were requested
@@ -48,12 +49,8 @@ pub trait OneDReader: Reader {
         image: &mut BinaryBitmap<B>,
         hints: &DecodeHints,
     ) -> Result<RXingResult> {
-        let mut hints = hints.clone();
-        let width = image.get_width();
-        let height = image.get_height();
-
-        let try_harder = hints.TryHarder.unwrap_or(false);
-
+        let mut recording = WitnessRecording::new(hints);
+        recording.row_binarization(image);
         let try_pure = hints.PureBarcode.unwrap_or(false);
 
         // Attempt to decode the barcode as "pure". This method may be very inneficient and uses
@@ -67,62 +64,113 @@ pub trait OneDReader: Reader {
                 .get_row(mid_line)
                 .ok_or(Exceptions::index_out_of_bounds_with("row out of bounds"))?;
 
-            let decoded = self.decode_pure(mid_line as u32, &rw, &hints);
-            if decoded.is_ok() {
-                return decoded;
+            if let Ok(mut result) = self.decode_pure(mid_line as u32, &rw, hints) {
+                record_row_symbol(&mut recording, &mut result);
+                return Ok(record_witness(hints, image, result, &recording));
             }
         }
 
-        let row_step = usize::max(1, height >> (if try_harder { 8 } else { 5 }));
-        let max_lines = if try_harder {
-            height // Look at the whole image, not just the center
-        } else {
-            15 // 15 rows spaced 1/32 apart is roughly the middle half of the image
+        let mut result = self
+            ._scan_rows(image, hints, 1)?
+            .pop()
+            .ok_or(Exceptions::NOT_FOUND)?;
+        record_row_symbol(&mut recording, &mut result);
+        Ok(record_witness(hints, image, result, &recording))
+    }
+
+    /**
+     * Decodes the distinct symbols on the rows the `OneDRowSampling` hint picks, stopping
+     * once its `max_results` are found. A symbol found on several rows is returned once, as
+     * found on the first of them.
+     *
+     * @param image The image to decode
+     * @param hints Any hints that were requested
+     * @return The symbols, in the order their rows were scanned
+     * @throws NotFoundException if no symbol is found
+     */
+    fn decode_rows<B: Binarizer>(
+        &mut self,
+        image: &mut BinaryBitmap<B>,
+        hints: &DecodeHints,
+    ) -> Result<Vec<RXingResult>> {
+        let mut recording = WitnessRecording::new(hints);
+        recording.row_binarization(image);
+        let max_results = match hints
+            .OneDRowSampling
+            .map_or(0, |sampling| sampling.max_results)
+        {
+            0 => usize::MAX,
+            max_results => max_results as usize,
         };
+        let results = self._scan_rows(image, hints, max_results)?;
+        if results.is_empty() {
+            return Err(Exceptions::NOT_FOUND);
+        }
+        Ok(results
+            .into_iter()
+            .map(|mut result| {
+                record_row_symbol(&mut recording, &mut result);
+                record_witness(hints, image, result, &recording)
+            })
+            .collect())
+    }
 
-        let middle = height / 2;
-        for x in 0..max_lines {
-            // Scanning from the middle out. Determine which row we're looking at next:
-            let row_steps_above_or_below = x.div_ceil(2);
-            let is_above = (x & 0x01) == 0; // i.e. is x even?
-            let row_number: isize = middle as isize
-                + row_step as isize
-                    * (if is_above {
-                        row_steps_above_or_below as isize
-                    } else {
-                        -(row_steps_above_or_below as isize)
-                    });
-            if row_number < 0 || row_number >= height as isize {
-                // Oops, if we run off the top or bottom, stop
-                break;
-            }
+    /**
+     * Scans the rows `RowSampling::rows` gives, forwards and then backwards to find upside
+     * down symbols, until `max_results` distinct symbols are found.
+     */
+    fn _scan_rows<B: Binarizer>(
+        &mut self,
+        image: &BinaryBitmap<B>,
+        hints: &DecodeHints,
+        max_results: usize,
+    ) -> Result<Vec<RXingResult>> {
+        let mut hints = hints.clone();
+        let width = image.get_width();
+        let try_harder = hints.TryHarder.unwrap_or(false);
+        let rows = hints
+            .OneDRowSampling
+            .unwrap_or_default()
+            .rows(image.get_height(), try_harder);
+
+        let mut results: Vec<RXingResult> = Vec::new();
+        // Reused for every row read backwards, rather than reversing a copy of each
+        let mut reversed_row = BitArray::new();
+        // The rows read so far, for the scanline witness of what is found
+        let mut rows_scanned = Vec::new();
+        for row_number in rows {
+            hints.check_cancelled()?;
 
             // Estimate black point for this row and load it:
-            let mut row = if let Ok(res) = image.get_black_row(row_number as usize) {
-                res
-            } else {
+            let Ok(row) = image.get_black_row(row_number) else {
                 continue;
             };
 
-            // While we have the image data in a BitArray, it's fairly cheap to reverse it in place to
+            // While we have the image data in a BitArray, it's fairly cheap to reverse it to
             // handle decoding upside down barcodes.
             for attempt in 0..2 {
-                // for (int attempt = 0; attempt < 2; attempt++) {
-                if attempt == 1 {
-                    // trying again?
-
-                    // reverse the row and continue
+                let result = if attempt == 0 {
+                    self.decode_row(row_number as u32, &row, &hints)
+                } else {
                     // This means we will only ever draw result points *once* in the life of this method
                     // since we want to avoid drawing the wrong points after flipping the row, and,
                     // don't want to clutter with noise from every single row scan -- just the scans
                     // that start on the center line.
-                    row.to_mut().reverse();
-
                     hints.NeedResultPointCallback = None;
-                }
-                let Ok(mut result) = self.decode_row(row_number as u32, &row, &hints) else {
+                    reversed_row.reverse_from(&row);
+                    self.decode_row(row_number as u32, &reversed_row, &hints)
+                };
+                let Ok(mut result) = result else {
                     continue;
                 };
+                let read = if attempt == 0 { &row } else { &reversed_row };
+                record_scanline_row(
+                    &mut result,
+                    &rows_scanned,
+                    row_number as u32,
+                    attempt == 1,
+                    read,
+                );
                 // We found our barcode
                 if attempt == 1 {
                     // But it was upside down, so note that
@@ -132,16 +180,27 @@ pub trait OneDReader: Reader {
                     );
                     // And remember to flip the result points horizontally.
                     let points = result.getPointsMut();
-                    if !points.is_empty() && points.len() >= 2 {
+                    if points.len() >= 2 {
                         points[0] = point(width as f32 - points[0].x - 1.0, points[0].y);
                         points[1] = point(width as f32 - points[1].x - 1.0, points[1].y);
                     }
                 }
-                return Ok(result);
+                if !results.iter().any(|found| {
+                    found.getBarcodeFormat() == result.getBarcodeFormat()
+                        && found.getText() == result.getText()
+                }) {
+                    results.push(result);
+                    if results.len() >= max_results {
+                        return Ok(results);
+                    }
+                }
+                // Don't read the same symbol again backwards
+                break;
             }
+            rows_scanned.push(row_number as u32);
         }
 
-        Err(Exceptions::NOT_FOUND)
+        Ok(results)
     }
 
     /**
@@ -175,7 +234,68 @@ pub trait OneDReader: Reader {
 
         let new_row = pad_bitarray(row, Self::QUIET_ZONE);
 
-        self.decode_row(rowNumber, &new_row, hints)
+        let mut result = self.decode_row(rowNumber, &new_row, hints)?;
+        record_scanline_row(&mut result, &[], rowNumber, false, &new_row);
+        Ok(result)
+    }
+
+    /**
+     * Finds the start pattern of a symbol in a row without decoding the symbol, so
+     * [`crate::locate`] can report symbols that fail to decode. Readers without a start
+     * pattern search return `None`.
+     *
+     * @param row the black/white pixel data of the row
+     * @param hints decode hints
+     * @return the columns the start pattern starts and ends at, and the width of a module
+     */
+    fn locate_start_pattern(
+        &mut self,
+        _row: &BitArray,
+        _hints: &DecodeHints,
+    ) -> Option<([usize; 2], f32)> {
+        None
+    }
+
+    /**
+     * Checks for the stop pattern of a symbol whose last bar ends at `end`, for
+     * [`crate::locate`]. Readers without a stop pattern accept any end.
+     *
+     * @param row the black/white pixel data of the row
+     * @param end the column after the last bar of the symbol
+     * @param module the width of a module, as `locate_start_pattern` gave it
+     */
+    fn has_stop_pattern(&mut self, _row: &BitArray, _end: usize, _module: f32) -> bool {
+        true
+    }
+}
+
+/// Records the symbol of `result` with `recording`. A row locates and decodes a symbol at once,
+/// and it has no codewords
+fn record_row_symbol(recording: &mut WitnessRecording, result: &mut RXingResult) {
+    recording.scanline(result);
+    recording.decoded_bits(result);
+}
+
+/// Completes the `SCANLINE` metadata of `result`, if its reader reported one, with the row it
+/// was read from
+fn record_scanline_row(
+    result: &mut RXingResult,
+    rows_scanned: &[u32],
+    row_number: u32,
+    reversed: bool,
+    row: &BitArray,
+) {
+    if let Some(RXingResultMetadataValue::Scanline(scanline)) = result
+        .getRXingResultMetadata()
+        .get(&RXingResultMetadataType::SCANLINE)
+    {
+        let scanline = scanline
+            .clone()
+            .with_row(rows_scanned, row_number, reversed, row);
+        result.putMetadata(
+            RXingResultMetadataType::SCANLINE,
+            RXingResultMetadataValue::Scanline(scanline),
+        );
     }
 }
 
@@ -253,6 +373,78 @@ pub fn pattern_match_variance(
     total_variance / total
 }
 
+/**
+ * Checks for a quiet zone of `width` white pixels before `start`. One cut off by the start of
+ * the row is accepted unless `strictness` is strict.
+ */
+pub(crate) fn is_quiet_before(
+    row: &BitArray,
+    start: usize,
+    width: usize,
+    strictness: Strictness,
+) -> Result<bool> {
+    if start < width && !strictness.allows_cut_off_quiet_zone() {
+        return Ok(false);
+    }
+    row.isRange(start.saturating_sub(width), start, false)
+}
+
+/**
+ * Checks for a quiet zone of `width` white pixels from `end`. One cut off by the end of the
+ * row is accepted unless `strictness` is strict.
+ */
+pub(crate) fn is_quiet_after(
+    row: &BitArray,
+    end: usize,
+    width: usize,
+    strictness: Strictness,
+) -> Result<bool> {
+    let size = row.get_size();
+    if end + width > size && !strictness.allows_cut_off_quiet_zone() {
+        return Ok(false);
+    }
+    row.isRange(end.min(size), size.min(end + width), false)
+}
+
+/**
+ * Checks that a symbol from `start` to `end` has quiet zones of `modules` modules of `module`
+ * pixels within the row, as strict decoding requires. Symbols read with narrower ones get a
+ * `DecodeWarning::QuietZoneViolation`.
+ */
+pub(crate) fn has_strict_quiet_zones(
+    row: &BitArray,
+    start: usize,
+    end: usize,
+    module: f32,
+    modules: u32,
+) -> Result<bool> {
+    let width = Strictness::Strict.quiet_zone(0, module, modules);
+    Ok(is_quiet_before(row, start, width, Strictness::Strict)?
+        && is_quiet_after(row, end, width, Strictness::Strict)?)
+}
+
+/**
+ * Checks that the runs of `row` ending at `end` match `pattern`, in modules of `module` pixels
+ * give or take half a module.
+ */
+pub(crate) fn is_pattern_before(row: &BitArray, end: usize, pattern: &[u32], module: f32) -> bool {
+    let mut counters = vec![0; pattern.len()];
+    let tolerance = (module / 2.0).max(1.0);
+    end < row.get_size()
+        && record_pattern_in_reverse(row, end, &mut counters).is_ok()
+        && counters.iter().zip(pattern).all(|(&counter, &modules)| {
+            (counter as f32 - modules as f32 * module).abs() <= tolerance
+        })
+}
+
+/// The average width of the `narrow` narrowest runs of a pattern, an estimate of its module
+pub(crate) fn narrow_width(counters: &[u32], narrow: usize) -> f32 {
+    let mut counters = counters.to_vec();
+    counters.sort_unstable();
+    let narrow = narrow.clamp(1, counters.len().max(1));
+    counters.iter().take(narrow).sum::<u32>() as f32 / narrow as f32
+}
+
 /**
  * Records the size of successive runs of white and black pixels in a row, starting at a given point.
  * The values are recorded in the given array, and the number of runs recorded is equal to the size
@@ -0,0 +1,114 @@
+/*
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! The values of Laetus Pharmacode bars, shared by the reader and the writer.
+//!
+//! One-track Pharmacode writes a number in narrow (1) and wide (2) bars, two-track Pharmacode in
+//! bars reaching the lower track (1), the upper track (2) or both (3). Either way the bars are the
+//! digits of the number, most significant first, in a numeral system without a zero.
+
+use crate::common::Result;
+use crate::{DecodeHints, Exceptions};
+
+pub const MIN_BARS: usize = 2;
+pub const MAX_BARS: usize = 16;
+
+/// The digits of the bars of one-track Pharmacode, 1 for a narrow and 2 for a wide bar
+pub const ONE_TRACK_BASE: u32 = 2;
+/// The digits of the bars of two-track Pharmacode, 1 for the lower, 2 for the upper and 3 for
+/// both tracks
+pub const TWO_TRACK_BASE: u32 = 3;
+
+/// The bars for a value, left to right
+pub fn value_to_bars(value: u32, base: u32) -> Result<Vec<u32>> {
+    let (min, max) = (min_value(base), max_value(base));
+    if !(min..=max).contains(&value) {
+        return Err(Exceptions::illegal_argument_with(format!(
+            "Pharmacode can only encode {min} to {max}, but got {value}"
+        )));
+    }
+    let mut bars = Vec::with_capacity(MAX_BARS);
+    let mut value = value;
+    while value > 0 {
+        let digit = match value % base {
+            0 => base,
+            digit => digit,
+        };
+        bars.push(digit);
+        value = (value - digit) / base;
+    }
+    bars.reverse();
+    Ok(bars)
+}
+
+/// The value of bars, left to right
+pub fn bars_to_value(bars: &[u32], base: u32) -> u32 {
+    bars.iter().fold(0, |value, digit| value * base + digit)
+}
+
+/// The value of the fewest bars, all 1
+pub const fn min_value(base: u32) -> u32 {
+    base + 1
+}
+
+/// The value of the most bars, all the greatest digit
+pub const fn max_value(base: u32) -> u32 {
+    base * (base.pow(MAX_BARS as u32) - 1) / (base - 1)
+}
+
+/// The least and greatest number of bars to accept, from the `PharmacodeMinBars` and
+/// `PharmacodeMaxBars` hints
+pub fn bar_counts(hints: &DecodeHints) -> (usize, usize) {
+    let min = hints
+        .PharmacodeMinBars
+        .map_or(MIN_BARS, |min| (min as usize).max(MIN_BARS));
+    let max = hints
+        .PharmacodeMaxBars
+        .map_or(MAX_BARS, |max| (max as usize).min(MAX_BARS));
+    (min, max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limits() {
+        assert_eq!(3, min_value(ONE_TRACK_BASE));
+        assert_eq!(131070, max_value(ONE_TRACK_BASE));
+        assert_eq!(4, min_value(TWO_TRACK_BASE));
+        assert_eq!(64570080, max_value(TWO_TRACK_BASE));
+        assert_eq!(vec![2; 16], value_to_bars(131070, ONE_TRACK_BASE).unwrap());
+        assert!(value_to_bars(2, ONE_TRACK_BASE).is_err());
+        assert!(value_to_bars(131071, ONE_TRACK_BASE).is_err());
+        assert!(value_to_bars(64570081, TWO_TRACK_BASE).is_err());
+    }
+
+    #[test]
+    fn test_bars() {
+        // narrow narrow wide wide narrow wide narrow narrow wide wide
+        let bars = value_to_bars(1234, ONE_TRACK_BASE).unwrap();
+        assert_eq!(vec![1, 1, 2, 2, 1, 2, 1, 1, 2, 2], bars);
+        assert_eq!(1234, bars_to_value(&bars, ONE_TRACK_BASE));
+
+        let bars = value_to_bars(1234, TWO_TRACK_BASE).unwrap();
+        assert_eq!(vec![1, 1, 2, 3, 1, 3, 1], bars);
+        assert_eq!(1234, bars_to_value(&bars, TWO_TRACK_BASE));
+
+        for value in [3, 4, 100, 999, 65535, 131070] {
+            let bars = value_to_bars(value, ONE_TRACK_BASE).unwrap();
+            assert_eq!(value, bars_to_value(&bars, ONE_TRACK_BASE));
+        }
+    }
+}
@@ -0,0 +1,384 @@
+/*
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use crate::common::{BitArray, BitMatrix, Result};
+use crate::oned::pharmacode_common::{self, ONE_TRACK_BASE, TWO_TRACK_BASE};
+use crate::{
+    point, record_witness, BarcodeFormat, Binarizer, BinaryBitmap, DecodeHints, Exceptions,
+    RXingResult, RXingResultMetadataType, RXingResultMetadataValue, Reader, WitnessRecording,
+};
+
+use super::OneDReader;
+
+/**
+ * <p>Decodes Laetus Pharmacode. One-track Pharmacode is read row by row like the other 1D
+ * formats. Two-track Pharmacode, read with the `PharmacodeTwoTrack` hint, is read along the
+ * middle of the upper and the lower half of the black matrix around its bars; a symbol whose
+ * bars all reach the same tracks can't be told from one of full bars, and is read as such.</p>
+ *
+ * <p>Pharmacode has neither start and stop patterns nor a check digit, so `MultiFormatReader`
+ * only tries this reader if PHARMACODE is one of the possible formats. The
+ * `PharmacodeMinBars` and `PharmacodeMaxBars` hints limit the number of bars to accept.</p>
+ */
+#[derive(Default)]
+pub struct PharmacodeReader;
+
+impl OneDReader for PharmacodeReader {
+    fn decode_row(
+        &mut self,
+        rowNumber: u32,
+        row: &BitArray,
+        hints: &DecodeHints,
+    ) -> Result<RXingResult> {
+        let (min_bars, max_bars) = pharmacode_common::bar_counts(hints);
+
+        // the start and end of every bar in the row
+        let size = row.get_size();
+        let mut bars = Vec::new();
+        let mut x = row.getNextSet(0);
+        while x < size {
+            let end = row.getNextUnset(x);
+            bars.push((x, end));
+            x = row.getNextSet(end);
+        }
+        let space_after = |i: usize| {
+            bars.get(i + 1)
+                .map_or(size, |(start, _)| *start)
+                .saturating_sub(bars[i].1)
+        };
+
+        // the bars of a symbol are as far apart as each other, and further from anything else
+        let mut first = 0;
+        while first < bars.len() {
+            let spacing = space_after(first);
+            let mut last = first;
+            while last + 1 < bars.len() && (spacing / 2..=spacing * 2).contains(&space_after(last))
+            {
+                last += 1;
+            }
+
+            let count = last - first + 1;
+            if (min_bars..=max_bars).contains(&count) {
+                let space = (bars[last].0
+                    - bars[first].1
+                    - bars[first + 1..last]
+                        .iter()
+                        .map(|(start, end)| end - start)
+                        .sum::<usize>()) as f32
+                    / (count - 1) as f32;
+                let quiet_before = if first == 0 {
+                    bars[0].0
+                } else {
+                    space_after(first - 1)
+                };
+                let quiet_after = space_after(last);
+                if let Some(digits) =
+                    one_track_digits(&bars[first..=last], space, quiet_before, quiet_after)
+                {
+                    let value = pharmacode_common::bars_to_value(&digits, ONE_TRACK_BASE);
+                    let mut result = RXingResult::new(
+                        &value.to_string(),
+                        Vec::new(),
+                        vec![
+                            point(bars[first].0 as f32, rowNumber as f32),
+                            point(bars[last].1 as f32, rowNumber as f32),
+                        ],
+                        BarcodeFormat::PHARMACODE,
+                    );
+                    // AIM gives it no identifier of its own, so it is reported as another symbology
+                    result.putMetadata(
+                        RXingResultMetadataType::SYMBOLOGY_IDENTIFIER,
+                        RXingResultMetadataValue::SymbologyIdentifier("]X0".to_owned()),
+                    );
+                    return Ok(result);
+                }
+            }
+            first = last + 1;
+        }
+
+        Err(Exceptions::NOT_FOUND)
+    }
+}
+
+impl Reader for PharmacodeReader {
+    fn decode<B: Binarizer>(&mut self, image: &mut BinaryBitmap<B>) -> Result<RXingResult> {
+        self.decode_with_hints(image, &DecodeHints::default())
+    }
+
+    // Note that we don't try rotation without the try harder flag, even if rotation was supported.
+    fn decode_with_hints<B: Binarizer>(
+        &mut self,
+        image: &mut BinaryBitmap<B>,
+        hints: &DecodeHints,
+    ) -> Result<RXingResult> {
+        let first_try = self.decode_upright(image, hints);
+        if first_try.is_ok() || !hints.TryHarder.unwrap_or(false) || !image.is_rotate_supported() {
+            return first_try;
+        }
+
+        let mut rotated_image = image.rotate_counter_clockwise();
+        let mut result = self.decode_upright(&mut rotated_image, hints)?;
+        // Record that we found it rotated 90 degrees CCW / 270 degrees CW, plus any reversal
+        // found by doDecode()
+        let orientation = match result
+            .getRXingResultMetadata()
+            .get(&RXingResultMetadataType::ORIENTATION)
+        {
+            Some(RXingResultMetadataValue::Orientation(or)) => (270 + or) % 360,
+            _ => 270,
+        };
+        result.putMetadata(
+            RXingResultMetadataType::ORIENTATION,
+            RXingResultMetadataValue::Orientation(orientation),
+        );
+        // Update result points
+        let height = rotated_image.get_height();
+        for point in result.getPointsMut().iter_mut() {
+            std::mem::swap(&mut point.x, &mut point.y);
+            point.x = height as f32 - point.x - 1.0;
+        }
+
+        Ok(result)
+    }
+}
+
+impl PharmacodeReader {
+    fn decode_upright<B: Binarizer>(
+        &mut self,
+        image: &mut BinaryBitmap<B>,
+        hints: &DecodeHints,
+    ) -> Result<RXingResult> {
+        if matches!(hints.PharmacodeTwoTrack, Some(true)) {
+            let mut recording = WitnessRecording::new(hints);
+            recording.binarization(image);
+            let mut result = decode_two_track(image.get_black_matrix(), hints)?;
+            recording.detection(BarcodeFormat::PHARMACODE, result.getPoints(), None);
+            recording.decoded_bits(&mut result);
+            Ok(record_witness(hints, image, result, &recording))
+        } else {
+            self._do_decode(image, hints)
+        }
+    }
+}
+
+/// The digits of one-track bars: narrow bars are half as wide as the average space between
+/// them, wide bars one and a half times. The quiet zones on either side must be at least three
+/// spaces wide.
+fn one_track_digits(
+    bars: &[(usize, usize)],
+    space: f32,
+    quiet_before: usize,
+    quiet_after: usize,
+) -> Option<Vec<u32>> {
+    if (quiet_before.min(quiet_after) as f32) < 3.0 * space {
+        return None;
+    }
+    bars.iter()
+        .map(|(start, end)| {
+            let width = (end - start) as f32;
+            if width < space {
+                Some(1)
+            } else if width <= 2.5 * space {
+                Some(2)
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+fn decode_two_track(matrix: &BitMatrix, hints: &DecodeHints) -> Result<RXingResult> {
+    let (min_bars, max_bars) = pharmacode_common::bar_counts(hints);
+    let [left, top, width, height] = matrix
+        .getEnclosingRectangle()
+        .ok_or(Exceptions::NOT_FOUND)?;
+    let (upper, lower) = (top + height / 4, top + height * 3 / 4);
+
+    // the start and end of every bar, and the tracks it reaches
+    let mut bars: Vec<(u32, u32, u32)> = Vec::new();
+    for x in left..left + width {
+        let digit = u32::from(matrix.get(x, upper)) * 2 + u32::from(matrix.get(x, lower));
+        match bars.last_mut() {
+            Some((_, end, tracks)) if *end == x && digit != 0 => {
+                *end = x + 1;
+                *tracks |= digit;
+            }
+            _ if digit != 0 => bars.push((x, x + 1, digit)),
+            _ => {}
+        }
+    }
+    if !(min_bars..=max_bars).contains(&bars.len()) {
+        return Err(Exceptions::NOT_FOUND);
+    }
+
+    // the bars are all as wide as each other, and so are the spaces
+    let similar = |widths: &[u32]| {
+        let average = widths.iter().sum::<u32>() as f32 / widths.len() as f32;
+        widths
+            .iter()
+            .all(|width| (average / 2.0..=average * 2.0).contains(&(*width as f32)))
+    };
+    let bar_widths: Vec<u32> = bars.iter().map(|(start, end, _)| end - start).collect();
+    let space_widths: Vec<u32> = bars.windows(2).map(|pair| pair[1].0 - pair[0].1).collect();
+    if !similar(&bar_widths) || !similar(&space_widths) {
+        return Err(Exceptions::NOT_FOUND);
+    }
+
+    let digits: Vec<u32> = bars.iter().map(|(_, _, tracks)| *tracks).collect();
+    let value = pharmacode_common::bars_to_value(&digits, TWO_TRACK_BASE);
+    let middle = (top + height / 2) as f32;
+    let mut result = RXingResult::new(
+        &value.to_string(),
+        Vec::new(),
+        vec![
+            point(bars[0].0 as f32, middle),
+            point(bars[bars.len() - 1].1 as f32, middle),
+        ],
+        BarcodeFormat::PHARMACODE,
+    );
+    // as for one track pharmacodes
+    result.putMetadata(
+        RXingResultMetadataType::SYMBOLOGY_IDENTIFIER,
+        RXingResultMetadataValue::SymbologyIdentifier("]X0".to_owned()),
+    );
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::{
+        common::{BitMatrix, HybridBinarizer},
+        oned::PharmacodeWriter,
+        test_fixtures::luma_bitmap,
+        BarcodeFormat, BinaryBitmap, DecodeHintValue, DecodeHints, EncodeHintValue, EncodeHints,
+        Luma8LuminanceSource, MultiFormatReader, RXingResultMetadataType, RXingResultMetadataValue,
+        Reader, Writer,
+    };
+
+    use super::PharmacodeReader;
+
+    fn image(matrix: &BitMatrix) -> BinaryBitmap<HybridBinarizer<Luma8LuminanceSource>> {
+        luma_bitmap(matrix)
+    }
+
+    fn encode(contents: &str, hints: &EncodeHints) -> BitMatrix {
+        PharmacodeWriter
+            .encode_with_hints(contents, &BarcodeFormat::PHARMACODE, 300, 80, hints)
+            .expect("encode")
+    }
+
+    #[test]
+    fn test_one_track() {
+        for value in ["3", "14", "1234", "65535", "131070"] {
+            let matrix = encode(value, &EncodeHints::default());
+            let result = PharmacodeReader
+                .decode(&mut image(&matrix))
+                .expect("decode");
+            assert_eq!(value, result.getText());
+            assert_eq!(BarcodeFormat::PHARMACODE, *result.getBarcodeFormat());
+        }
+
+        let matrix = encode("1234", &EncodeHints::default());
+        let pure = DecodeHints::default().with(DecodeHintValue::PureBarcode(true));
+        assert_eq!(
+            "1234",
+            PharmacodeReader
+                .decode_with_hints(&mut image(&matrix), &pure)
+                .expect("decode")
+                .getText()
+        );
+
+        // 1234 has 10 bars
+        let hints = DecodeHints::default().with(DecodeHintValue::PharmacodeMinBars(11));
+        assert!(PharmacodeReader
+            .decode_with_hints(&mut image(&matrix), &hints)
+            .is_err());
+        let hints = DecodeHints::default().with(DecodeHintValue::PharmacodeMaxBars(9));
+        assert!(PharmacodeReader
+            .decode_with_hints(&mut image(&matrix), &hints)
+            .is_err());
+    }
+
+    #[test]
+    fn test_two_track() {
+        let encode_hints = EncodeHints::default().with(EncodeHintValue::PharmacodeTwoTrack(true));
+        let hints = DecodeHints::default().with(DecodeHintValue::PharmacodeTwoTrack(true));
+        for value in ["5", "1234", "64570079"] {
+            let matrix = encode(value, &encode_hints);
+            let result = PharmacodeReader
+                .decode_with_hints(&mut image(&matrix), &hints)
+                .expect("decode");
+            assert_eq!(value, result.getText());
+        }
+
+        // bars of the lower track only are taken for full bars
+        let matrix = encode("13", &encode_hints);
+        assert_eq!(
+            "39",
+            PharmacodeReader
+                .decode_with_hints(&mut image(&matrix), &hints)
+                .expect("decode")
+                .getText()
+        );
+    }
+
+    #[test]
+    fn test_rotated() {
+        let matrix = encode("1234", &EncodeHints::default());
+        let mut rotated = BitMatrix::new(matrix.getHeight(), matrix.getWidth()).unwrap();
+        for y in 0..matrix.getHeight() {
+            for x in 0..matrix.getWidth() {
+                if matrix.get(x, y) {
+                    rotated.set(matrix.getHeight() - 1 - y, x);
+                }
+            }
+        }
+        assert!(PharmacodeReader.decode(&mut image(&rotated)).is_err());
+        let hints = DecodeHints::default().with(DecodeHintValue::TryHarder(true));
+        let result = PharmacodeReader
+            .decode_with_hints(&mut image(&rotated), &hints)
+            .expect("decode");
+        assert_eq!("1234", result.getText());
+        assert_eq!(
+            Some(&RXingResultMetadataValue::Orientation(270)),
+            result
+                .getRXingResultMetadata()
+                .get(&RXingResultMetadataType::ORIENTATION)
+        );
+    }
+
+    #[test]
+    fn test_multi_format_reader() {
+        let matrix = encode("1234", &EncodeHints::default());
+        let hints = DecodeHints::default().with(DecodeHintValue::PossibleFormats(HashSet::from([
+            BarcodeFormat::PHARMACODE,
+        ])));
+        let result = MultiFormatReader::default()
+            .decode_with_hints(&mut image(&matrix), &hints)
+            .expect("decode");
+        assert_eq!("1234", result.getText());
+
+        let matrix = encode(
+            "1234",
+            &EncodeHints::default().with(EncodeHintValue::PharmacodeTwoTrack(true)),
+        );
+        let hints = hints.with(DecodeHintValue::PharmacodeTwoTrack(true));
+        let result = MultiFormatReader::default()
+            .decode_with_hints(&mut image(&matrix), &hints)
+            .expect("decode");
+        assert_eq!("1234", result.getText());
+    }
+}
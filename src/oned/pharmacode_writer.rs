@@ -0,0 +1,214 @@
+/*
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use crate::common::{BitMatrix, Result};
+use crate::oned::pharmacode_common::{self, ONE_TRACK_BASE, TWO_TRACK_BASE};
+use crate::{BarcodeFormat, EncodeHints, Exceptions, Writer};
+
+use super::OneDimensionalCodeWriter;
+
+// the widths of one-track bars and spaces, in units of half a millimetre at the nominal size
+const NARROW_BAR: usize = 1;
+const WIDE_BAR: usize = 3;
+const SPACE: usize = 2;
+
+/**
+ * This class renders one-track Laetus Pharmacode as {@code boolean[]}, and two-track Pharmacode,
+ * with the `PharmacodeTwoTrack` hint, as a {@link BitMatrix} whose upper and lower half are the
+ * two tracks. The contents are the number to encode, 3 to 131070 for one-track and 4 to
+ * 64570080 for two-track Pharmacode.
+ */
+#[derive(Default)]
+pub struct PharmacodeWriter;
+
+impl Writer for PharmacodeWriter {
+    fn encode(
+        &self,
+        contents: &str,
+        format: &BarcodeFormat,
+        width: i32,
+        height: i32,
+    ) -> Result<BitMatrix> {
+        self.encode_with_hints(contents, format, width, height, &EncodeHints::default())
+    }
+
+    fn encode_with_hints(
+        &self,
+        contents: &str,
+        format: &BarcodeFormat,
+        width: i32,
+        height: i32,
+        hints: &EncodeHints,
+    ) -> Result<BitMatrix> {
+        if format != &BarcodeFormat::PHARMACODE {
+            return Err(Exceptions::illegal_argument_with(format!(
+                "Can only encode PHARMACODE, but got {format:?}"
+            )));
+        }
+        if width < 0 || height < 0 {
+            return Err(Exceptions::illegal_argument_with(format!(
+                "Negative size is not allowed. Input: {width}x{height}"
+            )));
+        }
+
+        let sidesMargin = if let Some(margin) = &hints.Margin {
+            margin
+                .parse::<u32>()
+                .map_err(|e| Exceptions::parse_with(format!("could not parse {margin}: {e}")))?
+        } else {
+            self.getDefaultMargin()
+        };
+
+        if matches!(hints.PharmacodeTwoTrack, Some(true)) {
+            let bars = pharmacode_common::value_to_bars(parse_value(contents)?, TWO_TRACK_BASE)?;
+            render_two_track(&bars, width, height, sidesMargin)
+        } else {
+            let code = self.encode_oned(contents)?;
+            Self::renderRXingResult(&code, width, height, sidesMargin)
+        }
+    }
+}
+
+impl OneDimensionalCodeWriter for PharmacodeWriter {
+    fn encode_oned(&self, contents: &str) -> Result<Vec<bool>> {
+        let bars = pharmacode_common::value_to_bars(parse_value(contents)?, ONE_TRACK_BASE)?;
+        let mut code = Vec::with_capacity(bars.len() * (WIDE_BAR + SPACE));
+        for (i, bar) in bars.iter().enumerate() {
+            if i > 0 {
+                code.extend([false; SPACE]);
+            }
+            let width = if *bar == 1 { NARROW_BAR } else { WIDE_BAR };
+            code.extend(std::iter::repeat_n(true, width));
+        }
+        Ok(code)
+    }
+
+    fn getSupportedWriteFormats(&self) -> Option<Vec<BarcodeFormat>> {
+        Some(vec![BarcodeFormat::PHARMACODE])
+    }
+
+    fn getDefaultMargin(&self) -> u32 {
+        // Pharmacode asks for a quiet zone of 6 mm, 12 units, on either side
+        24
+    }
+}
+
+fn parse_value(contents: &str) -> Result<u32> {
+    PharmacodeWriter::checkNumeric(contents)?;
+    contents
+        .parse::<u32>()
+        .map_err(|e| Exceptions::illegal_argument_with(format!("could not parse {contents}: {e}")))
+}
+
+/// Renders bars of a unit wide with spaces of a unit between them, reaching down from the top for
+/// the upper track (2), up from the bottom for the lower track (1) or both
+fn render_two_track(bars: &[u32], width: i32, height: i32, sidesMargin: u32) -> Result<BitMatrix> {
+    let inputWidth = 2 * bars.len() - 1;
+    let fullWidth = inputWidth + sidesMargin as usize;
+    let outputWidth = (width as usize).max(fullWidth);
+    let outputHeight = (height as usize).max(2);
+
+    let multiple = outputWidth / fullWidth;
+    let leftPadding = (outputWidth - inputWidth * multiple) / 2;
+    let half = outputHeight / 2;
+
+    let mut output = BitMatrix::new(outputWidth as u32, outputHeight as u32)?;
+    for (i, bar) in bars.iter().enumerate() {
+        let top = if bar & 2 != 0 { 0 } else { half };
+        let bottom = if bar & 1 != 0 { outputHeight } else { half };
+        output.setRegion(
+            (leftPadding + 2 * i * multiple) as u32,
+            top as u32,
+            multiple as u32,
+            (bottom - top) as u32,
+        )?;
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BarcodeFormat, EncodeHintValue, EncodeHints, Writer};
+
+    use super::PharmacodeWriter;
+
+    fn row(contents: &str) -> String {
+        let matrix = PharmacodeWriter
+            .encode_with_hints(
+                contents,
+                &BarcodeFormat::PHARMACODE,
+                0,
+                0,
+                &EncodeHints::default().with(EncodeHintValue::Margin("0".to_owned())),
+            )
+            .expect("encode");
+        (0..matrix.getWidth())
+            .map(|x| if matrix.get(x, 0) { '1' } else { '0' })
+            .collect()
+    }
+
+    #[test]
+    fn test_encode() {
+        assert_eq!("1001", row("3"));
+        assert_eq!("1110011100111", row("14"));
+        // narrow narrow wide wide narrow wide narrow narrow wide wide
+        assert_eq!("10010011100111001001110010010011100111", row("1234"));
+
+        assert!(PharmacodeWriter
+            .encode("2", &BarcodeFormat::PHARMACODE, 0, 0)
+            .is_err());
+        assert!(PharmacodeWriter
+            .encode("131071", &BarcodeFormat::PHARMACODE, 0, 0)
+            .is_err());
+        assert!(PharmacodeWriter
+            .encode("12a", &BarcodeFormat::PHARMACODE, 0, 0)
+            .is_err());
+        assert!(PharmacodeWriter
+            .encode("1234", &BarcodeFormat::CODE_128, 0, 0)
+            .is_err());
+
+        let matrix = PharmacodeWriter
+            .encode("1234", &BarcodeFormat::PHARMACODE, 0, 0)
+            .expect("encode");
+        assert_eq!(38 + 24, matrix.getWidth());
+        assert!(matrix.get(12, 0));
+        assert!(!matrix.get(11, 0));
+    }
+
+    #[test]
+    fn test_encode_two_track() {
+        let hints = EncodeHints::default()
+            .with(EncodeHintValue::PharmacodeTwoTrack(true))
+            .with(EncodeHintValue::Margin("0".to_owned()));
+        // lower lower upper both lower both lower
+        let matrix = PharmacodeWriter
+            .encode_with_hints("1234", &BarcodeFormat::PHARMACODE, 0, 10, &hints)
+            .expect("encode");
+        assert_eq!((13, 10), (matrix.getWidth(), matrix.getHeight()));
+        let track = |y| {
+            (0..matrix.getWidth())
+                .map(|x| if matrix.get(x, y) { '1' } else { '0' })
+                .collect::<String>()
+        };
+        assert_eq!("0000101000100", track(2));
+        assert_eq!("1010001010101", track(7));
+
+        assert!(PharmacodeWriter
+            .encode_with_hints("64570081", &BarcodeFormat::PHARMACODE, 0, 10, &hints)
+            .is_err());
+        assert!(PharmacodeWriter
+            .encode_with_hints("64570080", &BarcodeFormat::PHARMACODE, 0, 10, &hints)
+            .is_ok());
+    }
+}
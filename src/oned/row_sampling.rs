@@ -0,0 +1,244 @@
+/*
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// The order in which a 1D reader scans the rows it samples
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum RowOrder {
+    /// From the middle of the image outward, alternately above and below it, the default
+    #[default]
+    CenterOut,
+    /// From the top of the image down
+    TopDown,
+}
+
+/**
+ * Which rows of an image a 1D reader scans, and how many symbols it collects before it stops.
+ *
+ * By default the reader scans 15 rows 1/32 of the height apart from the middle outward, or
+ * every row 1/256 of the height apart when trying harder. Real-time video can scan fewer rows
+ * per frame and move them between frames with `offset`, so that a few frames together cover
+ * the rows one frame would:
+ *
+ * ```
+ * use rxing::oned::RowSampling;
+ *
+ * let sampling = RowSampling {
+ *     stride: 20,
+ *     max_rows: 3,
+ *     ..Default::default()
+ * };
+ * assert_eq!(vec![50, 30, 70], sampling.rows(100, false));
+ * let next_frame = RowSampling { offset: 10, ..sampling };
+ * assert_eq!(vec![60, 40, 80], next_frame.rows(100, false));
+ * ```
+ *
+ * Set with the `OneDRowSampling` hint.
+ */
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct RowSampling {
+    /// Rows between two scanned rows, 0 for 1/32 of the height, or 1/256 when trying harder
+    pub stride: u32,
+    /// Moves the scanned rows down by this many rows, modulo the stride
+    pub offset: u32,
+    pub order: RowOrder,
+    /// The most rows to scan, 0 for 15, or every row a stride apart when trying harder
+    pub max_rows: u32,
+    /// How many distinct symbols `OneDReader::decode_rows` collects before it stops scanning,
+    /// 0 for as many as the rows hold. A single decode always stops at the first.
+    pub max_results: u32,
+}
+
+impl RowSampling {
+    /**
+     * The rows to scan in an image `height` rows high, in order.
+     *
+     * @param try_harder whether the `TryHarder` hint is set, which changes the defaults
+     */
+    pub fn rows(&self, height: usize, try_harder: bool) -> Vec<usize> {
+        if height == 0 {
+            return Vec::new();
+        }
+        let stride = if self.stride > 0 {
+            self.stride as usize
+        } else {
+            usize::max(1, height >> (if try_harder { 8 } else { 5 }))
+        };
+        let max_rows = match self.max_rows {
+            0 if try_harder => height, // Look at the whole image, not just the center
+            0 => 15, // 15 rows spaced 1/32 apart is roughly the middle half of the image
+            max_rows => max_rows as usize,
+        };
+        let offset = self.offset as usize % stride;
+
+        match self.order {
+            RowOrder::CenterOut => {
+                let middle = (height / 2 + offset).min(height - 1);
+                let mut rows = Vec::with_capacity(max_rows.min(height));
+                for x in 0..max_rows {
+                    // Scanning from the middle out, alternately below and above it
+                    let steps = x.div_ceil(2) * stride;
+                    let row = if x & 0x01 == 0 {
+                        middle.checked_add(steps).filter(|&row| row < height)
+                    } else {
+                        middle.checked_sub(steps)
+                    };
+                    // Stop once we run off the top or bottom
+                    let Some(row) = row else {
+                        break;
+                    };
+                    rows.push(row);
+                }
+                rows
+            }
+            RowOrder::TopDown => (offset..height).step_by(stride).take(max_rows).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        common::GlobalHistogramBinarizer,
+        oned::{Code128Reader, Code128Writer, OneDReader},
+        BarcodeFormat, BinaryBitmap, DecodeHints, Luma8LuminanceSource, Writer,
+    };
+
+    use super::{RowOrder, RowSampling};
+
+    /// The rows `OneDReader` scanned before it could be told which
+    fn legacy_rows(height: usize, try_harder: bool) -> Vec<usize> {
+        let row_step = usize::max(1, height >> (if try_harder { 8 } else { 5 }));
+        let max_lines = if try_harder { height } else { 15 };
+        let middle = height as isize / 2;
+        let mut rows = Vec::new();
+        for x in 0..max_lines as isize {
+            let steps = (x + 1) / 2;
+            let row = middle + row_step as isize * if x & 0x01 == 0 { steps } else { -steps };
+            if row < 0 || row >= height as isize {
+                break;
+            }
+            rows.push(row as usize);
+        }
+        rows
+    }
+
+    #[test]
+    fn default_rows() {
+        for height in [1, 2, 7, 31, 32, 100, 480, 1080] {
+            for try_harder in [false, true] {
+                assert_eq!(
+                    legacy_rows(height, try_harder),
+                    RowSampling::default().rows(height, try_harder),
+                    "{height} {try_harder}"
+                );
+            }
+        }
+        assert!(RowSampling::default().rows(0, false).is_empty());
+    }
+
+    #[test]
+    fn top_down_rows() {
+        let sampling = RowSampling {
+            stride: 25,
+            offset: 60,
+            order: RowOrder::TopDown,
+            ..Default::default()
+        };
+        assert_eq!(vec![10, 35, 60, 85], sampling.rows(100, false));
+        let sampling = RowSampling {
+            max_rows: 2,
+            ..sampling
+        };
+        assert_eq!(vec![10, 35], sampling.rows(100, false));
+    }
+
+    /// Code 128 symbols of `contents` stacked, 20 rows high each, the ones flagged upside down
+    fn stacked(
+        contents: &[(&str, bool)],
+    ) -> BinaryBitmap<GlobalHistogramBinarizer<Luma8LuminanceSource>> {
+        const WIDTH: u32 = 200;
+        const HEIGHT: u32 = 20;
+        let mut pixels = vec![255u8; (WIDTH * HEIGHT) as usize * contents.len()];
+        for (i, &(text, upside_down)) in contents.iter().enumerate() {
+            let matrix = Code128Writer
+                .encode(text, &BarcodeFormat::CODE_128, WIDTH as i32, HEIGHT as i32)
+                .expect("encode");
+            for y in 0..HEIGHT {
+                for x in 0..WIDTH {
+                    let column = if upside_down { WIDTH - 1 - x } else { x };
+                    if matrix.get(column, y) {
+                        pixels[((i as u32 * HEIGHT + y) * WIDTH + x) as usize] = 0;
+                    }
+                }
+            }
+        }
+        let source = Luma8LuminanceSource::new(pixels, WIDTH, HEIGHT * contents.len() as u32);
+        BinaryBitmap::new(GlobalHistogramBinarizer::new(source))
+    }
+
+    fn decode_rows(
+        sampling: RowSampling,
+        image: &mut BinaryBitmap<GlobalHistogramBinarizer<Luma8LuminanceSource>>,
+    ) -> Vec<String> {
+        let hints = DecodeHints {
+            OneDRowSampling: Some(sampling),
+            ..Default::default()
+        };
+        Code128Reader
+            .decode_rows(image, &hints)
+            .map(|results| results.iter().map(|r| r.getText().to_owned()).collect())
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn decode_rows_stops_after_max_results() {
+        let mut image = stacked(&[("ONE", false), ("TWO", true), ("THREE", false)]);
+        let every_row = RowSampling {
+            stride: 10,
+            order: RowOrder::TopDown,
+            ..Default::default()
+        };
+        assert_eq!(
+            vec!["ONE", "TWO", "THREE"],
+            decode_rows(every_row, &mut image)
+        );
+        let first_two = RowSampling {
+            max_results: 2,
+            ..every_row
+        };
+        assert_eq!(vec!["ONE", "TWO"], decode_rows(first_two, &mut image));
+        // The middle symbol first, then the top one, a stride above it
+        let center_out = RowSampling {
+            stride: 20,
+            ..Default::default()
+        };
+        assert_eq!(
+            vec!["TWO", "ONE", "THREE"],
+            decode_rows(center_out, &mut image)
+        );
+        // No rows of the last symbol
+        let top = RowSampling {
+            stride: 10,
+            max_rows: 4,
+            order: RowOrder::TopDown,
+            ..Default::default()
+        };
+        assert_eq!(vec!["ONE", "TWO"], decode_rows(top, &mut image));
+    }
+}
@@ -15,6 +15,9 @@ pub use expanded_row::*;
 mod rss_expanded_reader;
 pub use rss_expanded_reader::*;
 
+mod rss_expanded_writer;
+pub use rss_expanded_writer::*;
+
 #[cfg(test)]
 #[cfg(feature = "image")]
 mod rss_expanded_internal_test_case;
@@ -46,7 +46,7 @@ const FINDER_PAT_D: u32 = 3;
 const FINDER_PAT_E: u32 = 4;
 const FINDER_PAT_F: u32 = 5;
 
-const FINDER_PATTERN_SEQUENCES: [&[u32]; 10] = [
+pub(super) const FINDER_PATTERN_SEQUENCES: [&[u32]; 10] = [
     &[FINDER_PAT_A, FINDER_PAT_A],
     &[FINDER_PAT_A, FINDER_PAT_B, FINDER_PAT_B],
     &[FINDER_PAT_A, FINDER_PAT_C, FINDER_PAT_B, FINDER_PAT_D],
@@ -227,11 +227,11 @@ impl RSSExpandedReader {
     pub fn new() -> Self {
         Self::default()
     }
-    const SYMBOL_WIDEST: [u32; 5] = [7, 5, 4, 3, 1];
-    const EVEN_TOTAL_SUBSET: [u32; 5] = [4, 20, 52, 104, 204];
-    const GSUM: [u32; 5] = [0, 348, 1388, 2948, 3988];
+    pub(super) const SYMBOL_WIDEST: [u32; 5] = [7, 5, 4, 3, 1];
+    pub(super) const EVEN_TOTAL_SUBSET: [u32; 5] = [4, 20, 52, 104, 204];
+    pub(super) const GSUM: [u32; 5] = [0, 348, 1388, 2948, 3988];
 
-    const FINDER_PATTERNS: [[u32; 4]; 6] = [
+    pub(super) const FINDER_PATTERNS: [[u32; 4]; 6] = [
         [1, 8, 4, 1], // A
         [3, 6, 4, 1], // B
         [3, 4, 6, 1], // C
@@ -240,7 +240,7 @@ impl RSSExpandedReader {
         [2, 2, 9, 1], // F
     ];
 
-    const WEIGHTS: [[u32; 8]; 23] = [
+    pub(super) const WEIGHTS: [[u32; 8]; 23] = [
         [1, 3, 9, 27, 81, 32, 96, 77],
         [20, 60, 180, 118, 143, 7, 21, 63],
         [189, 145, 13, 39, 117, 140, 209, 205],
@@ -0,0 +1,521 @@
+/*
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+#[cfg(feature = "client_support")]
+use crate::client::result::{GS1ParsedRXingResult, GS1_GROUP_SEPARATOR};
+#[cfg(feature = "client_support")]
+use crate::common::BitArray;
+use crate::common::{BitMatrix, Result};
+use crate::oned::rss::rss_14_writer::{modules, render_rows};
+use crate::oned::rss::rss_utils;
+use crate::oned::OneDimensionalCodeWriter;
+use crate::{BarcodeFormat, EncodeHints, Exceptions, Writer};
+
+use super::{RSSExpandedReader, FINDER_PATTERN_SEQUENCES};
+
+// the height of a row of DataBar Expanded Stacked, in modules
+const ROW_HEIGHT: usize = 34;
+
+// the most data characters a symbol holds, 21 and the check character making 11 pairs
+#[cfg(feature = "client_support")]
+const MAX_DATA_CHARACTERS: usize = 21;
+
+/**
+ * This object renders a GS1 DataBar Expanded symbol as {@code boolean[]}, and with the
+ * `RssExpandedSegments` hint a DataBar Expanded Stacked symbol as a {@link BitMatrix} of its rows
+ * and the separator patterns between them. The contents are GS1 element strings, in human
+ * readable form, e.g. `(01)09506000134352(10)ABC`, or as they are encoded, elements of variable
+ * length ended by a group separator.
+ */
+#[derive(Default)]
+pub struct RSSExpandedWriter;
+
+impl Writer for RSSExpandedWriter {
+    fn encode(
+        &self,
+        contents: &str,
+        format: &BarcodeFormat,
+        width: i32,
+        height: i32,
+    ) -> Result<BitMatrix> {
+        self.encode_with_hints(contents, format, width, height, &EncodeHints::default())
+    }
+
+    fn encode_with_hints(
+        &self,
+        contents: &str,
+        format: &BarcodeFormat,
+        width: i32,
+        height: i32,
+        hints: &EncodeHints,
+    ) -> Result<BitMatrix> {
+        if format != &BarcodeFormat::RSS_EXPANDED {
+            return Err(Exceptions::illegal_argument_with(format!(
+                "Can only encode RSS_EXPANDED, but got {format:?}"
+            )));
+        }
+        if width < 0 || height < 0 {
+            return Err(Exceptions::illegal_argument_with(format!(
+                "Negative size is not allowed. Input: {width}x{height}"
+            )));
+        }
+
+        let sidesMargin = if let Some(margin) = &hints.Margin {
+            margin
+                .parse::<u32>()
+                .map_err(|e| Exceptions::parse_with(format!("could not parse {margin}: {e}")))?
+        } else {
+            self.getDefaultMargin()
+        };
+
+        let Some(segments) = hints.RssExpandedSegments else {
+            let code = self.encode_oned(contents)?;
+            return Self::renderRXingResult(&code, width, height, sidesMargin);
+        };
+        if segments % 2 != 0 || !(2..=22).contains(&segments) {
+            return Err(Exceptions::illegal_argument_with(format!(
+                "DataBar Expanded Stacked rows hold an even number of 2 to 22 segments, but got {segments}"
+            )));
+        }
+
+        let dataCharacters = dataCharacters(contents, Some(segments as usize))?;
+        let rows = Self::rows(&Self::pairs(&dataCharacters), segments as usize / 2);
+        let symbolWidth = rows.iter().map(|(row, _)| row.len()).max().unwrap_or(0);
+        let mut stack = Vec::with_capacity(4 * rows.len());
+        for (i, row) in rows.iter().enumerate() {
+            if i > 0 {
+                // the complements of the rows above and below with a pattern of alternate
+                // modules between them
+                let middle = (0..symbolWidth)
+                    .map(|x| x % 2 == 1 && (5..symbolWidth - 4).contains(&x))
+                    .collect();
+                stack.push((Self::separator(&rows[i - 1], symbolWidth), 1));
+                stack.push((middle, 1));
+                stack.push((Self::separator(row, symbolWidth), 1));
+            }
+            let mut modules = row.0.clone();
+            modules.resize(symbolWidth, false);
+            stack.push((modules, ROW_HEIGHT));
+        }
+        render_rows(&stack, width, height, sidesMargin)
+    }
+}
+
+impl OneDimensionalCodeWriter for RSSExpandedWriter {
+    fn encode_oned(&self, contents: &str) -> Result<Vec<bool>> {
+        let pairs = Self::pairs(&dataCharacters(contents, None)?);
+        let mut rows = Self::rows(&pairs, pairs.len());
+        Ok(rows.swap_remove(0).0)
+    }
+
+    fn getSupportedWriteFormats(&self) -> Option<Vec<BarcodeFormat>> {
+        Some(vec![BarcodeFormat::RSS_EXPANDED])
+    }
+}
+
+impl RSSExpandedWriter {
+    /// The elements of each pair of symbol characters, the check character first, and their
+    /// finder pattern, as widths and whether they are part of the finder pattern
+    fn pairs(dataCharacters: &[u32]) -> Vec<Vec<(u32, bool)>> {
+        let symbolCharacters = dataCharacters.len() + 1;
+        let sequence = FINDER_PATTERN_SEQUENCES[symbolCharacters.div_ceil(2) - 2];
+
+        let mut characters = Vec::with_capacity(symbolCharacters);
+        let mut checksum = 0;
+        for (i, value) in dataCharacters.iter().enumerate() {
+            let widths = Self::characterWidths(*value);
+            // the characters after the check character are weighed by their position
+            let pair = i.div_ceil(2);
+            let weightRow = 4 * sequence[pair] as usize + 2 * (pair % 2) + (i + 1) % 2 - 1;
+            checksum += widths
+                .iter()
+                .zip(RSSExpandedReader::WEIGHTS[weightRow])
+                .map(|(width, weight)| width * weight)
+                .sum::<u32>();
+            characters.push(widths);
+        }
+        let checkCharacter = 211 * (symbolCharacters as u32 - 4) + checksum % 211;
+        characters.insert(0, Self::characterWidths(checkCharacter));
+
+        let mut pairs = Vec::with_capacity(sequence.len());
+        for (pair, finderValue) in sequence.iter().enumerate() {
+            let [a, b, c, d] = RSSExpandedReader::FINDER_PATTERNS[*finderValue as usize];
+            let finder = if pair % 2 == 0 {
+                [a, b, c, d, 1]
+            } else {
+                [1, d, c, b, a]
+            };
+            // the right character is read from right to left
+            let mut elements = Vec::with_capacity(21);
+            elements.extend(characters[2 * pair].iter().map(|width| (*width, false)));
+            elements.extend(finder.iter().map(|width| (*width, true)));
+            if let Some(right) = characters.get(2 * pair + 1) {
+                elements.extend(right.iter().rev().map(|width| (*width, false)));
+            }
+            pairs.push(elements);
+        }
+        pairs
+    }
+
+    /// The widths of a symbol character of `value`, odd and even elements interleaved
+    fn characterWidths(value: u32) -> [u32; 8] {
+        let group = RSSExpandedReader::GSUM
+            .iter()
+            .rposition(|gSum| *gSum <= value)
+            .unwrap_or(0);
+        let value = value - RSSExpandedReader::GSUM[group];
+        let tEven = RSSExpandedReader::EVEN_TOTAL_SUBSET[group];
+        let oddWidest = RSSExpandedReader::SYMBOL_WIDEST[group];
+        let oddSum = 12 - 2 * group as u32;
+        let odd = rss_utils::getRSSwidths::<4>(value / tEven, oddSum, oddWidest, true);
+        let even = rss_utils::getRSSwidths::<4>(value % tEven, 17 - oddSum, 9 - oddWidest, false);
+
+        let mut widths = [0; 8];
+        for i in 0..4 {
+            widths[2 * i] = odd[i];
+            widths[2 * i + 1] = even[i];
+        }
+        widths
+    }
+
+    /// The modules of the rows of `columns` pairs each, with their guard patterns, and which of
+    /// them are part of a finder pattern
+    fn rows(pairs: &[Vec<(u32, bool)>], columns: usize) -> Vec<(Vec<bool>, Vec<bool>)> {
+        let rowCount = pairs.len().div_ceil(columns);
+        pairs
+            .chunks(columns)
+            .enumerate()
+            .map(|(row, rowPairs)| {
+                // counting from one, the even rows of an even number of columns are reversed but
+                // for a last row of an odd number of them, which gets a wider guard instead
+                let evenRow = row % 2 == 1;
+                let specialCase =
+                    row + 1 == rowCount && evenRow && columns % 2 == 0 && rowPairs.len() % 2 == 1;
+                let leftToRight = columns % 2 == 1 || !evenRow || specialCase;
+
+                let mut content = rowPairs.concat();
+                // the pairs of odd index start with a bar
+                let mut darkFirst = row * columns % 2 == 1;
+                if !leftToRight {
+                    darkFirst ^= content.len() % 2 == 0;
+                    content.reverse();
+                }
+                let mut elements = vec![(if specialCase { 2 } else { 1 }, false), (1, false)];
+                elements.extend(content);
+                elements.extend([(1, false), (1, false)]);
+
+                let finders = elements
+                    .iter()
+                    .flat_map(|(width, finder)| std::iter::repeat_n(*finder, *width as usize))
+                    .collect();
+                (
+                    modules(elements.iter().map(|(width, _)| width), darkFirst),
+                    finders,
+                )
+            })
+            .collect()
+    }
+
+    /// The complement of a row but for its guard patterns, where the light modules of a finder
+    /// pattern are alternately dark and light
+    fn separator((modules, finders): &(Vec<bool>, Vec<bool>), width: usize) -> Vec<bool> {
+        let mut separator = vec![false; width];
+        let mut dark = false;
+        for x in 4..modules.len() - 4 {
+            separator[x] = !(modules[x] || finders[x] && dark);
+            dark = finders[x] && separator[x];
+        }
+        separator
+    }
+}
+
+/// The number of data characters holding `bits`, at least 3, and for DataBar Expanded Stacked
+/// enough not to leave a single segment on the last row
+#[cfg(feature = "client_support")]
+fn dataCharacterCount(bits: usize, segments: Option<usize>) -> usize {
+    let count = bits.div_ceil(12).max(3);
+    match segments {
+        Some(segments) if count + 1 > segments && (count + 1) % segments == 1 => count + 1,
+        _ => count,
+    }
+}
+
+/// The values of the data characters of the GS1 element strings in `contents`, the check
+/// character left out
+#[cfg(feature = "client_support")]
+fn dataCharacters(contents: &str, segments: Option<usize>) -> Result<Vec<u32>> {
+    let gs1 = GS1ParsedRXingResult::parse(contents).map_err(|e| {
+        Exceptions::illegal_argument_with(format!("Contents are not GS1 element strings: {e}"))
+    })?;
+
+    let mut bits = BitArray::new();
+    let parse = |digits: &str| {
+        digits.parse::<usize>().map_err(|e| {
+            Exceptions::illegal_argument_with(format!("could not parse {digits}: {e}"))
+        })
+    };
+    let (variableLengthBits, elementString) = match gs1.getAIs().split_first() {
+        Some(((ai, gtin), others)) if ai == "01" => {
+            // the linkage flag, encodation method 1 leaving out the AI 01, the variable length
+            // bits and the GTIN without its check digit
+            bits.appendBits(0b0100, 4)?;
+            bits.appendBits(parse(&gtin[..1])?, 4)?;
+            for i in 0..4 {
+                bits.appendBits(parse(&gtin[1 + 3 * i..4 + 3 * i])?, 10)?;
+            }
+            let others = GS1ParsedRXingResult::new(others.to_vec());
+            (2, others.getEncodedElementString(GS1_GROUP_SEPARATOR))
+        }
+        _ => {
+            // the linkage flag, encodation method 00 and the variable length bits
+            bits.appendBits(0, 5)?;
+            (3, gs1.getEncodedElementString(GS1_GROUP_SEPARATOR))
+        }
+    };
+    encodeGeneralPurpose(&elementString, &mut bits, segments)?;
+
+    let count = bits.get_size() / 12;
+    if (count + 1) % 2 == 1 {
+        bits.set(variableLengthBits);
+    }
+    if count + 1 > 14 {
+        bits.set(variableLengthBits + 1);
+    }
+    Ok((0..count)
+        .map(|i| (0..12).fold(0, |value, j| value << 1 | u32::from(bits.get(12 * i + j))))
+        .collect())
+}
+
+#[cfg(not(feature = "client_support"))]
+fn dataCharacters(_contents: &str, _segments: Option<usize>) -> Result<Vec<u32>> {
+    Err(Exceptions::unsupported_operation_with(
+        "DataBar Expanded element strings are parsed with the client_support feature",
+    ))
+}
+
+#[cfg(feature = "client_support")]
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Encodation {
+    Numeric,
+    Alphanumeric,
+    IsoIec646,
+}
+
+/**
+ * Appends the general purpose data field of `data`, FNC1 given as group separators, and pads it
+ * to fill the data characters.
+ *
+ * Numeric encodation is left for alphanumeric encodation, and that for ISO/IEC 646 encodation,
+ * when a character does not fit it. Both return to numeric encodation for four numeric characters
+ * or an FNC1, which is always encoded in numeric encodation.
+ */
+#[cfg(feature = "client_support")]
+fn encodeGeneralPurpose(data: &str, bits: &mut BitArray, segments: Option<usize>) -> Result<()> {
+    let data = data.chars().collect::<Vec<_>>();
+    let isNumeric = |c: &char| c.is_ascii_digit() || *c == GS1_GROUP_SEPARATOR;
+    let numericValue = |c: char| c.to_digit(10).unwrap_or(10) as usize;
+
+    let mut encodation = Encodation::Numeric;
+    let mut i = 0;
+    while i < data.len() {
+        let c = data[i];
+        if encodation == Encodation::Numeric {
+            if data
+                .get(i + 1)
+                .is_some_and(|next| isNumeric(&c) && isNumeric(next))
+            {
+                bits.appendBits(8 + 11 * numericValue(c) + numericValue(data[i + 1]), 7)?;
+                i += 2;
+            } else if i + 1 == data.len() && c.is_ascii_digit() {
+                // a last digit takes 4 bits where fewer than 7 are left, else it is paired with
+                // an FNC1
+                let size = 12 * dataCharacterCount(bits.get_size() + 4, segments);
+                if size - bits.get_size() < 7 {
+                    bits.appendBits(numericValue(c) + 1, 4)?;
+                } else {
+                    bits.appendBits(8 + 11 * numericValue(c) + 10, 7)?;
+                }
+                i += 1;
+            } else {
+                bits.appendBits(0, 4)?;
+                encodation = Encodation::Alphanumeric;
+            }
+        } else if c == GS1_GROUP_SEPARATOR
+            || data[i..].iter().take_while(|c| isNumeric(c)).count() >= 4
+        {
+            bits.appendBits(0, 3)?;
+            encodation = Encodation::Numeric;
+        } else if let Some((value, length)) =
+            alphanumeric(c).filter(|_| encodation == Encodation::Alphanumeric)
+        {
+            bits.appendBits(value, length)?;
+            i += 1;
+        } else if encodation == Encodation::Alphanumeric {
+            bits.appendBits(0b00100, 5)?;
+            encodation = Encodation::IsoIec646;
+        } else if let Some((value, length)) = isoIec646(c) {
+            bits.appendBits(value, length)?;
+            i += 1;
+        } else {
+            return Err(Exceptions::illegal_argument_with(format!(
+                "Cannot encode '{c}' in DataBar Expanded"
+            )));
+        }
+    }
+
+    let count = dataCharacterCount(bits.get_size(), segments);
+    if count > MAX_DATA_CHARACTERS {
+        return Err(Exceptions::illegal_argument_with(format!(
+            "Contents take {count} data characters, but DataBar Expanded holds at most {MAX_DATA_CHARACTERS}"
+        )));
+    }
+    // a latch to alphanumeric encodation, then latches to ISO/IEC 646 and back
+    let mut padding = 12 * count - bits.get_size();
+    if encodation == Encodation::Numeric {
+        let latch = padding.min(4);
+        bits.appendBits(0, latch)?;
+        padding -= latch;
+    }
+    for i in 0..padding {
+        bits.appendBits(usize::from(i % 5 == 2), 1)?;
+    }
+    Ok(())
+}
+
+/// The value and the number of bits of `c` in alphanumeric encodation
+#[cfg(feature = "client_support")]
+fn alphanumeric(c: char) -> Option<(usize, usize)> {
+    match c {
+        '0'..='9' => Some((c as usize - '0' as usize + 5, 5)),
+        'A'..='Z' => Some((c as usize - 33, 6)),
+        '*' => Some((58, 6)),
+        ',' => Some((59, 6)),
+        '-' => Some((60, 6)),
+        '.' => Some((61, 6)),
+        '/' => Some((62, 6)),
+        _ => None,
+    }
+}
+
+/// The value and the number of bits of `c` in ISO/IEC 646 encodation
+#[cfg(feature = "client_support")]
+fn isoIec646(c: char) -> Option<(usize, usize)> {
+    const PUNCTUATION: &str = "!\"%&'()*+,-./:;<=>?_ ";
+    match c {
+        '0'..='9' => Some((c as usize - '0' as usize + 5, 5)),
+        'A'..='Z' => Some((c as usize - 1, 7)),
+        'a'..='z' => Some((c as usize - 7, 7)),
+        _ => PUNCTUATION.find(c).map(|index| (232 + index, 8)),
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "client_support")]
+mod tests {
+    use crate::{
+        common::{BitArray, BitMatrix},
+        oned::{rss::expanded::RSSExpandedReader, OneDReader},
+        BarcodeFormat, DecodeHints, EncodeHintValue, EncodeHints, Exceptions, Writer,
+    };
+
+    use super::{RSSExpandedWriter, ROW_HEIGHT};
+
+    const CONTENTS: [&str; 7] = [
+        "(01)98898765432106(10)ABC",
+        "(01)90012345678908(3103)012233(15)991231",
+        "(01)98898765432106",
+        "(10)abc-123(21)XYZ",
+        "(21)123",
+        "(11)251231(10)A1B2C3D4(21)12345678",
+        "(01)90614141000015(21)12345678p901(10)1234567p(17)141120",
+    ];
+
+    /// Scans the middle of each row, either way, as a scanner reads a stacked symbol row by row
+    fn decode(matrix: &BitMatrix) -> String {
+        let mut reader = RSSExpandedReader::new();
+        let mut result = Err(Exceptions::NOT_FOUND);
+        for y in (ROW_HEIGHT / 2..matrix.getHeight() as usize).step_by(ROW_HEIGHT + 3) {
+            let mut row = BitArray::with_size(3 * matrix.getWidth() as usize);
+            for x in 0..matrix.getWidth() {
+                if matrix.get(x, y as u32) {
+                    (0..3).for_each(|i| row.set(3 * x as usize + i));
+                }
+            }
+            result = reader.decode_row(y as u32, &row, &DecodeHints::default());
+            if result.is_err() {
+                row.reverse();
+                result = reader.decode_row(y as u32, &row, &DecodeHints::default());
+            }
+        }
+        result.expect("decode").getText().to_owned()
+    }
+
+    fn encode(contents: &str, hints: &EncodeHints) -> BitMatrix {
+        RSSExpandedWriter
+            .encode_with_hints(
+                contents,
+                &BarcodeFormat::RSS_EXPANDED,
+                0,
+                ROW_HEIGHT as i32,
+                hints,
+            )
+            .expect("encode")
+    }
+
+    #[test]
+    fn test_encode() {
+        for contents in CONTENTS {
+            assert_eq!(contents, decode(&encode(contents, &EncodeHints::default())));
+        }
+        assert_eq!(
+            "(01)98898765432106(10)ABC",
+            decode(&encode("019889876543210610ABC", &EncodeHints::default()))
+        );
+
+        for contents in [
+            "(01)98898765432107",
+            "12345670",
+            "(10)ABC~",
+            &"(10)ABCDEFGHIJ".repeat(6),
+        ] {
+            assert!(RSSExpandedWriter
+                .encode(contents, &BarcodeFormat::RSS_EXPANDED, 0, 0)
+                .is_err());
+        }
+    }
+
+    #[test]
+    fn test_encode_stacked() {
+        for segments in [2, 4, 6, 8, 10, 22] {
+            let hints = EncodeHints::default().with(EncodeHintValue::RssExpandedSegments(segments));
+            for contents in CONTENTS {
+                assert_eq!(contents, decode(&encode(contents, &hints)), "{segments}");
+            }
+        }
+
+        let hints = EncodeHints::default()
+            .with(EncodeHintValue::RssExpandedSegments(4))
+            .with(EncodeHintValue::Margin("0".to_owned()));
+        // two rows of two pairs, and the separator pattern
+        let matrix = encode(CONTENTS[0], &hints);
+        assert_eq!((102, 71), (matrix.getWidth(), matrix.getHeight()));
+
+        for segments in [0, 3, 24] {
+            let hints = EncodeHints::default().with(EncodeHintValue::RssExpandedSegments(segments));
+            assert!(RSSExpandedWriter
+                .encode_with_hints(CONTENTS[0], &BarcodeFormat::RSS_EXPANDED, 0, 0, &hints)
+                .is_err());
+        }
+    }
+}
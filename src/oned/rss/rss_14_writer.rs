@@ -0,0 +1,345 @@
+/*
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use crate::common::{BitMatrix, Result};
+use crate::oned::{EAN13Reader, OneDimensionalCodeWriter, UPCEANReader};
+use crate::{BarcodeFormat, EncodeHints, Exceptions, Writer};
+
+use super::{rss_utils, RSS14Reader};
+
+// the heights of the upper row, the separator and the lower row of DataBar Stacked, in modules
+const STACKED_ROW_HEIGHTS: [usize; 3] = [5, 1, 7];
+
+/**
+ * This object renders a GS1 DataBar (RSS-14) Omnidirectional symbol as {@code boolean[]}, and
+ * with the `RssStacked` hint a DataBar Stacked symbol as a {@link BitMatrix} of its two rows and
+ * the separator pattern between them. The contents are a GTIN of 13 digits, or 14 with the check
+ * digit, or its GS1 element string, e.g. `(01)09506000134352`.
+ */
+#[derive(Default)]
+pub struct RSS14Writer;
+
+impl Writer for RSS14Writer {
+    fn encode(
+        &self,
+        contents: &str,
+        format: &BarcodeFormat,
+        width: i32,
+        height: i32,
+    ) -> Result<BitMatrix> {
+        self.encode_with_hints(contents, format, width, height, &EncodeHints::default())
+    }
+
+    fn encode_with_hints(
+        &self,
+        contents: &str,
+        format: &BarcodeFormat,
+        width: i32,
+        height: i32,
+        hints: &EncodeHints,
+    ) -> Result<BitMatrix> {
+        if format != &BarcodeFormat::RSS_14 {
+            return Err(Exceptions::illegal_argument_with(format!(
+                "Can only encode RSS_14, but got {format:?}"
+            )));
+        }
+        if width < 0 || height < 0 {
+            return Err(Exceptions::illegal_argument_with(format!(
+                "Negative size is not allowed. Input: {width}x{height}"
+            )));
+        }
+
+        let sidesMargin = if let Some(margin) = &hints.Margin {
+            margin
+                .parse::<u32>()
+                .map_err(|e| Exceptions::parse_with(format!("could not parse {margin}: {e}")))?
+        } else {
+            self.getDefaultMargin()
+        };
+
+        let elements = Self::elements(contents)?;
+        if matches!(hints.RssStacked, Some(true)) {
+            // the halves of the row each get a guard pattern, the lower one starts with a bar
+            let top = modules(elements[..23].iter().chain(&[1, 1]), false);
+            let bottom = modules([1, 1].iter().chain(&elements[23..]), true);
+            let mut separator = vec![false; top.len()];
+            for x in 4..top.len() - 4 {
+                separator[x] = if top[x] == bottom[x] {
+                    !top[x]
+                } else {
+                    !separator[x - 1]
+                };
+            }
+            let rows = [top, separator, bottom]
+                .into_iter()
+                .zip(STACKED_ROW_HEIGHTS)
+                .collect::<Vec<_>>();
+            render_rows(&rows, width, height, sidesMargin)
+        } else {
+            Self::renderRXingResult(&modules(elements.iter(), false), width, height, sidesMargin)
+        }
+    }
+}
+
+impl OneDimensionalCodeWriter for RSS14Writer {
+    fn encode_oned(&self, contents: &str) -> Result<Vec<bool>> {
+        Ok(modules(Self::elements(contents)?.iter(), false))
+    }
+
+    fn getSupportedWriteFormats(&self) -> Option<Vec<BarcodeFormat>> {
+        Some(vec![BarcodeFormat::RSS_14])
+    }
+}
+
+impl RSS14Writer {
+    /// The widths of the 46 elements of the symbol, starting with the space of the left guard
+    fn elements(contents: &str) -> Result<Vec<u32>> {
+        let gtin = parse_gtin(contents)?;
+        let value = gtin[..13].parse::<u64>().map_err(|e| {
+            Exceptions::illegal_argument_with(format!("could not parse {gtin}: {e}"))
+        })?;
+        let left = (value / 4537077) as u32;
+        let right = (value % 4537077) as u32;
+
+        let (outsideLeft, outsideLeftChecksum) = Self::dataCharacter(left / 1597, true);
+        let (insideLeft, insideLeftChecksum) = Self::dataCharacter(left % 1597, false);
+        let (outsideRight, outsideRightChecksum) = Self::dataCharacter(right / 1597, true);
+        let (insideRight, insideRightChecksum) = Self::dataCharacter(right % 1597, false);
+
+        let leftChecksum = outsideLeftChecksum + 4 * insideLeftChecksum;
+        let rightChecksum = outsideRightChecksum + 4 * insideRightChecksum;
+        // the pairs of finder values 0 and 8, and 8 and 0, are not used
+        let mut checkValue = (leftChecksum + 16 * rightChecksum) % 79;
+        if checkValue >= 8 {
+            checkValue += 1;
+        }
+        if checkValue >= 72 {
+            checkValue += 1;
+        }
+        let leftFinder = RSS14Reader::FINDER_PATTERNS[checkValue as usize / 9];
+        let rightFinder = RSS14Reader::FINDER_PATTERNS[checkValue as usize % 9];
+
+        // the characters of the right half, like the inside ones, are read from right to left
+        let mut elements = Vec::with_capacity(46);
+        elements.extend([1, 1]);
+        elements.extend(outsideLeft);
+        elements.extend(leftFinder);
+        elements.push(1);
+        elements.extend(insideLeft.iter().rev());
+        elements.extend(insideRight);
+        elements.push(1);
+        elements.extend(rightFinder.iter().rev());
+        elements.extend(outsideRight.iter().rev());
+        elements.extend([1, 1]);
+        Ok(elements)
+    }
+
+    /// The widths of a data character of `value`, odd and even elements interleaved, and its
+    /// checksum portion
+    fn dataCharacter(value: u32, outsideChar: bool) -> ([u32; 8], u32) {
+        let (odd, even) = if outsideChar {
+            let group = RSS14Reader::OUTSIDE_GSUM
+                .iter()
+                .rposition(|gSum| *gSum <= value)
+                .unwrap_or(0);
+            let value = value - RSS14Reader::OUTSIDE_GSUM[group];
+            let tEven = RSS14Reader::OUTSIDE_EVEN_TOTAL_SUBSET[group];
+            let oddWidest = RSS14Reader::OUTSIDE_ODD_WIDEST[group];
+            let oddSum = 12 - 2 * group as u32;
+            (
+                rss_utils::getRSSwidths::<4>(value / tEven, oddSum, oddWidest, false),
+                rss_utils::getRSSwidths::<4>(value % tEven, 16 - oddSum, 9 - oddWidest, true),
+            )
+        } else {
+            let group = RSS14Reader::INSIDE_GSUM
+                .iter()
+                .rposition(|gSum| *gSum <= value)
+                .unwrap_or(0);
+            let value = value - RSS14Reader::INSIDE_GSUM[group];
+            let tOdd = RSS14Reader::INSIDE_ODD_TOTAL_SUBSET[group];
+            let oddWidest = RSS14Reader::INSIDE_ODD_WIDEST[group];
+            let evenSum = 10 - 2 * group as u32;
+            (
+                rss_utils::getRSSwidths::<4>(value % tOdd, 15 - evenSum, oddWidest, true),
+                rss_utils::getRSSwidths::<4>(value / tOdd, evenSum, 9 - oddWidest, false),
+            )
+        };
+
+        let mut widths = [0; 8];
+        let mut oddChecksumPortion = 0;
+        let mut evenChecksumPortion = 0;
+        for i in (0..4).rev() {
+            widths[2 * i] = odd[i];
+            widths[2 * i + 1] = even[i];
+            oddChecksumPortion = oddChecksumPortion * 9 + odd[i];
+            evenChecksumPortion = evenChecksumPortion * 9 + even[i];
+        }
+        (widths, oddChecksumPortion + 3 * evenChecksumPortion)
+    }
+}
+
+/// The 14 digits of the GTIN in `contents`, with the check digit added to 13 digits
+fn parse_gtin(contents: &str) -> Result<String> {
+    let gtin = contents
+        .strip_prefix("(01)")
+        .or_else(|| contents.strip_prefix("01").filter(|gtin| gtin.len() == 14))
+        .unwrap_or(contents);
+    RSS14Writer::checkNumeric(gtin)?;
+    let reader = EAN13Reader;
+    match gtin.len() {
+        13 => {
+            let check = reader.getStandardUPCEANChecksum(&gtin.chars().collect::<Vec<_>>())?;
+            Ok(format!("{gtin}{check}"))
+        }
+        14 => {
+            if !reader.checkStandardUPCEANChecksum(gtin)? {
+                return Err(Exceptions::illegal_argument_with(
+                    "Contents do not pass checksum",
+                ));
+            }
+            Ok(gtin.to_owned())
+        }
+        length => Err(Exceptions::illegal_argument_with(format!(
+            "Requested contents should be 13 or 14 digits long, but got {length}"
+        ))),
+    }
+}
+
+/// The modules of elements of the given widths, alternately dark and light
+pub(super) fn modules<'a>(widths: impl Iterator<Item = &'a u32>, darkFirst: bool) -> Vec<bool> {
+    let mut modules = Vec::new();
+    let mut dark = darkFirst;
+    for width in widths {
+        modules.extend(std::iter::repeat_n(dark, *width as usize));
+        dark = !dark;
+    }
+    modules
+}
+
+/// Renders rows of modules, each as many modules high as given, scaled up to fill `width` by
+/// `height` with the rows of shorter symbols left aligned
+pub(super) fn render_rows(
+    rows: &[(Vec<bool>, usize)],
+    width: i32,
+    height: i32,
+    sidesMargin: u32,
+) -> Result<BitMatrix> {
+    let inputWidth = rows.iter().map(|(row, _)| row.len()).max().unwrap_or(0);
+    let inputHeight = rows.iter().map(|(_, rowHeight)| rowHeight).sum::<usize>();
+    let fullWidth = inputWidth + sidesMargin as usize;
+    let outputWidth = (width as usize).max(fullWidth);
+    let outputHeight = (height as usize).max(inputHeight);
+    let multiple = outputWidth / fullWidth;
+    let rowMultiple = outputHeight / inputHeight;
+    let leftPadding = (outputWidth - inputWidth * multiple) / 2;
+    let mut top = (outputHeight - inputHeight * rowMultiple) / 2;
+
+    let mut output = BitMatrix::new(outputWidth as u32, outputHeight as u32)?;
+    for (row, rowHeight) in rows {
+        for (x, dark) in row.iter().enumerate() {
+            if *dark {
+                output.setRegion(
+                    (leftPadding + x * multiple) as u32,
+                    top as u32,
+                    multiple as u32,
+                    (rowHeight * rowMultiple) as u32,
+                )?;
+            }
+        }
+        top += rowHeight * rowMultiple;
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        common::BitMatrix, oned::rss::RSS14Reader, test_fixtures::luma_bitmap, BarcodeFormat,
+        EncodeHintValue, EncodeHints, Reader, Writer,
+    };
+
+    use super::RSS14Writer;
+
+    fn decode(matrix: &BitMatrix) -> String {
+        RSS14Reader::new()
+            .decode(&mut luma_bitmap(matrix))
+            .expect("decode")
+            .getText()
+            .to_owned()
+    }
+
+    fn encode(contents: &str, hints: &EncodeHints) -> BitMatrix {
+        RSS14Writer
+            .encode_with_hints(contents, &BarcodeFormat::RSS_14, 300, 120, hints)
+            .expect("encode")
+    }
+
+    #[test]
+    fn test_encode() {
+        for gtin in [
+            "09506000134352",
+            "00000000000000",
+            "20012345678909",
+            "99999999999997",
+        ] {
+            assert_eq!(gtin, decode(&encode(gtin, &EncodeHints::default())));
+        }
+        for contents in ["0950600013435", "(01)09506000134352", "0109506000134352"] {
+            assert_eq!(
+                "09506000134352",
+                decode(&encode(contents, &EncodeHints::default()))
+            );
+        }
+
+        let hints = EncodeHints::default().with(EncodeHintValue::Margin("0".to_owned()));
+        let matrix = RSS14Writer
+            .encode_with_hints("09506000134352", &BarcodeFormat::RSS_14, 0, 0, &hints)
+            .expect("encode");
+        assert_eq!((96, 1), (matrix.getWidth(), matrix.getHeight()));
+        // the guards at either end, a space and a bar at the left, a bar at the right
+        assert!(!matrix.get(0, 0) && matrix.get(1, 0) && matrix.get(95, 0));
+
+        for contents in [
+            "09506000134353",
+            "095060001343",
+            "0950600013435a",
+            "(10)ABC",
+        ] {
+            assert!(RSS14Writer
+                .encode(contents, &BarcodeFormat::RSS_14, 0, 0)
+                .is_err());
+        }
+        assert!(RSS14Writer
+            .encode("09506000134352", &BarcodeFormat::RSS_EXPANDED, 0, 0)
+            .is_err());
+    }
+
+    #[test]
+    fn test_encode_stacked() {
+        let hints = EncodeHints::default().with(EncodeHintValue::RssStacked(true));
+        for gtin in ["09506000134352", "20012345678909"] {
+            assert_eq!(gtin, decode(&encode(gtin, &hints)));
+        }
+
+        let hints = hints.with(EncodeHintValue::Margin("0".to_owned()));
+        let matrix = RSS14Writer
+            .encode_with_hints("09506000134352", &BarcodeFormat::RSS_14, 0, 0, &hints)
+            .expect("encode");
+        // upper and lower rows of 5 and 7 modules with a separator between them
+        assert_eq!((50, 13), (matrix.getWidth(), matrix.getHeight()));
+        assert!(!matrix.get(0, 0) && matrix.get(1, 0) && matrix.get(48, 0));
+        assert!((0..4).all(|x| !matrix.get(x, 5) && !matrix.get(49 - x, 5)));
+        assert!(matrix.get(0, 6) && !matrix.get(1, 6) && matrix.get(49, 6));
+    }
+}
@@ -55,6 +55,53 @@ pub fn getRSSvalue<const S: usize>(widths: &[u32; S], maxWidth: u32, noNarrow: b
     val
 }
 
+/// The inverse of [`getRSSvalue`], the widths of `S` elements of `n` modules in total
+pub fn getRSSwidths<const S: usize>(value: u32, n: u32, maxWidth: u32, noNarrow: bool) -> [u32; S] {
+    let elements = S as u32;
+    let mut n = n;
+    let mut widths = [0; S];
+
+    let mut val = value as i64;
+    let mut narrowMask = 0;
+    for bar in 0..(elements - 1) {
+        let mut elmWidth = 1;
+        narrowMask |= 1 << bar;
+        while elmWidth + elements - bar - 1 < n {
+            let mut subVal = combins_pre(n - elmWidth - 1, elements - bar - 2) as i64;
+            if noNarrow
+                && (narrowMask == 0)
+                && (n - elmWidth - (elements - bar - 1) >= elements - bar - 1)
+            {
+                subVal -= combins_pre(n - elmWidth - (elements - bar), elements - bar - 2) as i64;
+            }
+            if elements - bar - 1 > 1 {
+                let mut lessVal = 0;
+                let mut mxwElement = n - elmWidth - (elements - bar - 2);
+                while mxwElement > maxWidth {
+                    lessVal +=
+                        combins_pre(n - elmWidth - mxwElement - 1, elements - bar - 3) as i64;
+
+                    mxwElement -= 1;
+                }
+                subVal -= lessVal * (elements - 1 - bar) as i64;
+            } else if n - elmWidth > maxWidth {
+                subVal -= 1;
+            }
+            if val < subVal {
+                break;
+            }
+            val -= subVal;
+
+            elmWidth += 1;
+            narrowMask &= !(1 << bar)
+        }
+        n -= elmWidth;
+        widths[bar as usize] = elmWidth;
+    }
+    widths[S - 1] = n;
+    widths
+}
+
 #[inline(always)]
 const fn combins(n: u32, r: u32) -> u32 {
     if n as usize <= N_MAX && r as usize <= R_MAX {
@@ -132,3 +179,34 @@ pub const fn combins_pre(n: u32, r: u32) -> u32 {
 
     COMBIN_TABLE[ni][ri]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{getRSSvalue, getRSSwidths};
+
+    #[test]
+    fn test_widths_are_inverse_of_value() {
+        for (n, maxWidth, noNarrow) in [(12, 8, false), (4, 1, true), (10, 7, true), (13, 8, false)]
+        {
+            let mut values = Vec::new();
+            for a in 1..=maxWidth {
+                for b in 1..=maxWidth {
+                    for c in 1..=maxWidth {
+                        if a + b + c >= n || n - a - b - c > maxWidth {
+                            continue;
+                        }
+                        let widths = [a, b, c, n - a - b - c];
+                        if noNarrow && widths.iter().all(|w| *w > 1) {
+                            continue;
+                        }
+                        let value = getRSSvalue(&widths, maxWidth, noNarrow);
+                        assert_eq!(widths, getRSSwidths(value, n, maxWidth, noNarrow));
+                        values.push(value);
+                    }
+                }
+            }
+            values.sort_unstable();
+            assert_eq!((0..values.len() as u32).collect::<Vec<_>>(), values);
+        }
+    }
+}
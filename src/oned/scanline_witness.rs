@@ -0,0 +1,304 @@
+/*
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::common::{BitArray, Result};
+
+use super::one_d_reader;
+
+/**
+ * A pattern of bars and spaces a 1D reader matched in a row: a guard, a start or stop code or
+ * a character.
+ */
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ScanlinePattern {
+    /// The column of the first pixel of the pattern, in the row as it was read
+    pub start: u32,
+
+    /// The widths of the runs of the pattern in pixels, alternating from the color at `start`
+    pub counters: Vec<u32>,
+
+    /// What the pattern was read as: the value of a Code 128 code, or an EAN or UPC digit with
+    /// 10 added for the G (even parity) patterns. `None` for guard patterns
+    pub value: Option<u32>,
+}
+
+impl ScanlinePattern {
+    /**
+     * Records the runs of a pattern as the reader matched them.
+     *
+     * # Arguments
+     * * `row` - The row the pattern was matched in
+     * * `start` - The column of the first pixel of the pattern
+     * * `runs` - How many runs the pattern has
+     * * `value` - What the pattern was read as, if it is not a guard
+     */
+    pub(crate) fn read(
+        row: &BitArray,
+        start: usize,
+        runs: usize,
+        value: Option<u32>,
+    ) -> Result<Self> {
+        let mut counters = vec![0; runs];
+        one_d_reader::record_pattern(row, start, &mut counters)?;
+
+        Ok(Self {
+            start: start as u32,
+            counters,
+            value,
+        })
+    }
+
+    /// The column after the last pixel of the pattern
+    pub fn end(&self) -> u32 {
+        self.start + self.counters.iter().sum::<u32>()
+    }
+}
+
+/**
+ * How a 1D symbol was read from a single row of the binarized image: the rows the reader tried,
+ * the row it read the symbol from, and the patterns it matched in that row. This is the 1D
+ * counterpart of the sampling and decoding stages of a 2D symbol, and is reported by the Code
+ * 128, EAN and UPC readers as the `SCANLINE` metadata of their results.
+ *
+ * Columns are counted in the row as it was read, so from the right edge of the image for a
+ * symbol read upside down. Patterns of an EAN or UPC add-on are not recorded.
+ */
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ScanlineWitness {
+    /// The rows the reader tried, in order, ending with `row`
+    pub rows_scanned: Vec<u32>,
+
+    /// The row the symbol was read from
+    pub row: u32,
+
+    /// Whether the row was read right to left
+    pub reversed: bool,
+
+    /// The run-length encoding of the row as read: the widths of its runs, alternating from a
+    /// white run, which is 0 wide if the row starts black
+    pub runs: Vec<u32>,
+
+    /// The start guard or start code
+    pub start_pattern: ScanlinePattern,
+
+    /// The characters, check characters included, from left to right
+    pub characters: Vec<ScanlinePattern>,
+
+    /// The guard between the halves of an EAN-13, EAN-8 or UPC-A symbol
+    pub middle_pattern: Option<ScanlinePattern>,
+
+    /// The end guard or stop code
+    pub stop_pattern: ScanlinePattern,
+}
+
+impl ScanlineWitness {
+    pub fn new(
+        start_pattern: ScanlinePattern,
+        characters: Vec<ScanlinePattern>,
+        stop_pattern: ScanlinePattern,
+    ) -> Self {
+        Self {
+            rows_scanned: Vec::new(),
+            row: 0,
+            reversed: false,
+            runs: Vec::new(),
+            start_pattern,
+            characters,
+            middle_pattern: None,
+            stop_pattern,
+        }
+    }
+
+    pub fn with_middle_pattern(mut self, middle_pattern: ScanlinePattern) -> Self {
+        self.middle_pattern = Some(middle_pattern);
+        self
+    }
+
+    /**
+     * Records the row the patterns were matched in.
+     *
+     * # Arguments
+     * * `rows_scanned` - The rows tried before, in order
+     * * `row_number` - The row the symbol was read from
+     * * `reversed` - Whether the row was read right to left
+     * * `row` - The row as it was read
+     */
+    pub(crate) fn with_row(
+        mut self,
+        rows_scanned: &[u32],
+        row_number: u32,
+        reversed: bool,
+        row: &BitArray,
+    ) -> Self {
+        self.rows_scanned = rows_scanned.to_vec();
+        self.rows_scanned.push(row_number);
+        self.row = row_number;
+        self.reversed = reversed;
+
+        self.runs.clear();
+        let mut black = false;
+        let mut run = 0;
+        for x in 0..row.get_size() {
+            if row.get(x) != black {
+                self.runs.push(run);
+                black = !black;
+                run = 0;
+            }
+            run += 1;
+        }
+        self.runs.push(run);
+        self
+    }
+
+    /// The row as read, one `true` per black pixel, expanded from `runs`
+    pub fn row_pixels(&self) -> Vec<bool> {
+        self.runs
+            .iter()
+            .enumerate()
+            .flat_map(|(i, &run)| std::iter::repeat_n(i % 2 == 1, run as usize))
+            .collect()
+    }
+
+    /// Every pattern, from left to right, the middle guard between the halves of `characters`
+    pub fn patterns(&self) -> Vec<&ScanlinePattern> {
+        let middle = self.characters.len() / 2;
+        let (left, right) = match self.middle_pattern {
+            Some(_) => self.characters.split_at(middle),
+            None => (&self.characters[..], &[][..]),
+        };
+        std::iter::once(&self.start_pattern)
+            .chain(left)
+            .chain(&self.middle_pattern)
+            .chain(right)
+            .chain(std::iter::once(&self.stop_pattern))
+            .collect()
+    }
+
+    /**
+     * Checks that every pattern is a sequence of whole runs of the row, and that the patterns
+     * follow each other without a gap.
+     *
+     * # Returns
+     * Why the patterns don't fit the row, or None if they do
+     */
+    pub fn pattern_mismatch(&self) -> Option<String> {
+        let run_starts: Vec<u32> = self
+            .runs
+            .iter()
+            .scan(0, |x, run| {
+                let start = *x;
+                *x += run;
+                Some(start)
+            })
+            .collect();
+
+        let mut last_end = None;
+        for pattern in self.patterns() {
+            // the white run a row starting black begins with is empty
+            let Some(first) =
+                (0..self.runs.len()).find(|&i| run_starts[i] == pattern.start && self.runs[i] > 0)
+            else {
+                return Some(format!("no run starts at column {}", pattern.start));
+            };
+            let runs = self.runs.get(first..first + pattern.counters.len());
+            if runs != Some(&pattern.counters[..]) {
+                return Some(format!(
+                    "the runs from column {} are not {:?}",
+                    pattern.start, pattern.counters
+                ));
+            }
+            if last_end.is_some_and(|end| end != pattern.start) {
+                return Some(format!(
+                    "the pattern at column {} does not follow the one before",
+                    pattern.start
+                ));
+            }
+            last_end = Some(pattern.end());
+        }
+
+        None
+    }
+}
+
+/// The index of the pattern `counters` match best, however badly
+pub(crate) fn best_match<P: AsRef<[u32]>>(counters: &[u32], patterns: &[P]) -> Option<usize> {
+    patterns
+        .iter()
+        .map(|pattern| {
+            one_d_reader::pattern_match_variance(counters, pattern.as_ref(), f32::INFINITY)
+        })
+        .enumerate()
+        .filter(|(_, variance)| variance.is_finite())
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(i, _)| i)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::common::BitArray;
+
+    use super::{ScanlinePattern, ScanlineWitness};
+
+    fn bits(pixels: &str) -> BitArray {
+        let mut row = BitArray::new();
+        for pixel in pixels.chars() {
+            row.appendBit(pixel == '#');
+        }
+        row
+    }
+
+    #[test]
+    fn test_runs() {
+        let row = bits("  # ##  ###  ");
+        let start = ScanlinePattern::read(&row, 2, 3, None).unwrap();
+        let character = ScanlinePattern::read(&row, 6, 2, Some(4)).unwrap();
+        let stop = ScanlinePattern::read(&row, 11, 1, None).unwrap();
+        assert_eq!((vec![1, 1, 2], 6), (start.counters.clone(), start.end()));
+
+        let witness =
+            ScanlineWitness::new(start, vec![character], stop).with_row(&[6, 4], 8, false, &row);
+        assert_eq!(vec![6, 4, 8], witness.rows_scanned);
+        assert_eq!(vec![2, 1, 1, 2, 2, 3, 2], witness.runs);
+        assert_eq!(
+            "  # ##  ###  ",
+            witness
+                .row_pixels()
+                .iter()
+                .map(|&black| if black { '#' } else { ' ' })
+                .collect::<String>()
+        );
+        assert_eq!(None, witness.pattern_mismatch());
+
+        let mut gap = witness.clone();
+        gap.characters[0] = ScanlinePattern::read(&row, 7, 1, Some(4)).unwrap();
+        assert!(gap.pattern_mismatch().is_some());
+
+        let black_first = bits("## ");
+        let starts_black = ScanlineWitness::new(
+            ScanlinePattern::read(&black_first, 0, 1, None).unwrap(),
+            Vec::new(),
+            ScanlinePattern::read(&black_first, 2, 1, None).unwrap(),
+        )
+        .with_row(&[], 0, true, &black_first);
+        assert_eq!(vec![0, 2, 1], starts_black.runs);
+        assert_eq!(None, starts_black.pattern_mismatch());
+    }
+}
@@ -98,6 +98,7 @@ impl UPCAReader {
                 BarcodeFormat::UPC_A,
             );
             upcaRXingResult.putAllMetadata(result.getRXingResultMetadata().clone());
+            upcaRXingResult.addWarnings(result.getWarnings());
 
             Ok(upcaRXingResult)
         } else {
@@ -139,12 +139,12 @@ impl UPCEReader {
 /**
  * Expands a UPC-E value back into its full, equivalent UPC-A code value.
  *
- * @param upce UPC-E code as string of digits
- * @return equivalent UPC-A code as string of digits
+ * @param upce UPC-E code as string of digits, with or without its check digit
+ * @return equivalent UPC-A code as string of digits, None if `upce` is too short
  */
 pub fn convertUPCEtoUPCA(upce: &str) -> Option<String> {
     let upce = upce.chars().collect::<Vec<_>>();
-    let upceChars = &upce[1..7];
+    let upceChars = upce.get(1..7)?;
 
     let mut result = Vec::with_capacity(12);
 
@@ -181,3 +181,105 @@ pub fn convertUPCEtoUPCA(upce: &str) -> Option<String> {
 
     Some(String::from_iter(result))
 }
+
+/**
+ * Compresses a UPC-A value into the equivalent UPC-E one, by suppressing the zeros of the
+ * manufacturer and product codes the way {@link #convertUPCEtoUPCA} expands them.
+ *
+ * @param upca UPC-A code as string of 11 digits, or 12 with its check digit, which is kept
+ * @return equivalent UPC-E code as string of digits, None if `upca` is not a UPC-A value of
+ *  number system 0 or 1 with enough zeros to be compressed
+ */
+pub fn convertUPCAtoUPCE(upca: &str) -> Option<String> {
+    if !(11..=12).contains(&upca.len()) || !upca.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let numberSystem = &upca[0..1];
+    if numberSystem != "0" && numberSystem != "1" {
+        return None;
+    }
+    let manufacturer = &upca[1..6];
+    let product = &upca[6..11];
+
+    let upceChars = if matches!(&manufacturer[2..], "000" | "100" | "200") && &product[..2] == "00"
+    {
+        format!(
+            "{}{}{}",
+            &manufacturer[..2],
+            &product[2..],
+            &manufacturer[2..3]
+        )
+    } else if &manufacturer[3..] == "00" && &product[..3] == "000" {
+        format!("{}{}3", &manufacturer[..3], &product[3..])
+    } else if &manufacturer[4..] == "0" && &product[..4] == "0000" {
+        format!("{}{}4", &manufacturer[..4], &product[4..])
+    } else if &product[..4] == "0000" && matches!(&product[4..], "5" | "6" | "7" | "8" | "9") {
+        format!("{manufacturer}{}", &product[4..])
+    } else {
+        return None;
+    };
+
+    Some(format!("{numberSystem}{upceChars}{}", &upca[11..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        common::BitArray,
+        oned::{OneDReader, UPCEReader, UPCEWriter},
+        BarcodeFormat, DecodeHints, Writer,
+    };
+
+    use super::{convertUPCAtoUPCE, convertUPCEtoUPCA};
+
+    #[test]
+    fn testConvertUPCEtoUPCA() {
+        // one of each of the four ways of suppressing zeros
+        let pairs = [
+            ("01234505", "012000003455"),
+            ("04567834", "045600000784"),
+            ("03456741", "034560000071"),
+            ("11234562", "112345000062"),
+        ];
+        for (upce, upca) in pairs {
+            assert_eq!(Some(upca), convertUPCEtoUPCA(upce).as_deref());
+            assert_eq!(Some(upce), convertUPCAtoUPCE(upca).as_deref());
+            // without check digits
+            assert_eq!(Some(&upca[..11]), convertUPCEtoUPCA(&upce[..7]).as_deref());
+            assert_eq!(Some(&upce[..7]), convertUPCAtoUPCE(&upca[..11]).as_deref());
+        }
+
+        assert_eq!(None, convertUPCEtoUPCA("012345"));
+        // too few zeros, number system 2, not digits and wrong length
+        assert_eq!(None, convertUPCAtoUPCE("012345678905"));
+        assert_eq!(None, convertUPCAtoUPCE("212000003459"));
+        assert_eq!(None, convertUPCAtoUPCE("01200000345A"));
+        assert_eq!(None, convertUPCAtoUPCE("0120000034"));
+    }
+
+    #[test]
+    fn testExpandUPCE() {
+        let matrix = UPCEWriter
+            .encode("01234505", &BarcodeFormat::UPC_E, 100, 1)
+            .expect("encode");
+        let mut row = BitArray::with_size(matrix.getWidth() as usize);
+        for x in 0..matrix.getWidth() {
+            if matrix.get(x, 0) {
+                row.set(x as usize);
+            }
+        }
+
+        let result = UPCEReader
+            .decode_row(0, &row, &DecodeHints::default())
+            .expect("decode");
+        assert_eq!("01234505", result.getText());
+
+        let hints = DecodeHints {
+            ExpandUPCE: Some(true),
+            ..Default::default()
+        };
+        let result = UPCEReader.decode_row(0, &row, &hints).expect("decode");
+        assert_eq!("012000003455", result.getText());
+        assert_eq!(&BarcodeFormat::UPC_E, result.getBarcodeFormat());
+    }
+}
@@ -37,7 +37,7 @@ impl UPCEANExtension2Support {
         &self,
         rowNumber: u32,
         row: &BitArray,
-        extensionStartRange: &[u32; 3],
+        extensionStartRange: &[usize; 2],
     ) -> Result<RXingResult> {
         let mut result = String::new();
         let end = self.decodeMiddle(row, extensionStartRange, &mut result)?;
@@ -67,14 +67,14 @@ impl UPCEANExtension2Support {
     fn decodeMiddle(
         &self,
         row: &BitArray,
-        startRange: &[u32; 3],
+        startRange: &[usize; 2],
         resultString: &mut String,
     ) -> Result<u32> {
         let mut counters = self.decodeMiddleCounters;
         counters.fill(0);
 
         let end = row.get_size();
-        let mut rowOffset = startRange[1] as usize;
+        let mut rowOffset = startRange[1];
 
         let mut checkParity = 0;
 
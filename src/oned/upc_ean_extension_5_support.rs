@@ -22,7 +22,7 @@ use crate::{
     RXingResultMetadataValue,
 };
 
-use super::{upc_ean_reader, UPCEANReader, STAND_IN};
+use super::{upc_ean_reader, UPCEANExtensionInterpretation, UPCEANReader, STAND_IN};
 
 /**
  * @see UPCEANExtension2Support
@@ -31,7 +31,7 @@ use super::{upc_ean_reader, UPCEANReader, STAND_IN};
 pub struct UPCEANExtension5Support;
 
 impl UPCEANExtension5Support {
-    const CHECK_DIGIT_ENCODINGS: [usize; 10] =
+    pub(crate) const CHECK_DIGIT_ENCODINGS: [usize; 10] =
         [0x18, 0x14, 0x12, 0x11, 0x0C, 0x06, 0x03, 0x0A, 0x09, 0x05];
 
     pub fn decodeRow(
@@ -117,7 +117,7 @@ impl UPCEANExtension5Support {
         Ok(rowOffset as u32)
     }
 
-    fn extensionChecksum(s: &str) -> Option<u32> {
+    pub(crate) fn extensionChecksum(s: &str) -> Option<u32> {
         let s = s.chars().collect::<Vec<_>>();
         let length = s.len();
         let mut sum = 0;
@@ -161,7 +161,7 @@ impl UPCEANExtension5Support {
         if raw.chars().count() != 5 {
             return None;
         }
-        let value = Self::parseExtension5String(raw)?;
+        let value = UPCEANExtensionInterpretation::parse(raw)?.suggested_price()?;
 
         let mut result = HashMap::new();
         result.insert(
@@ -171,42 +171,4 @@ impl UPCEANExtension5Support {
 
         Some(result)
     }
-
-    fn parseExtension5String(raw: &str) -> Option<String> {
-        let currency = match raw.chars().next()? {
-            '0' => "£",
-            '5' => "$",
-            '9' => {
-                // Reference: http://www.jollytech.com
-                match raw {
-                    "90000" =>
-                    // No suggested retail price
-                    {
-                        return None
-                    }
-                    "99991" =>
-                    // Complementary
-                    {
-                        return Some("0.00".to_owned())
-                    }
-                    "99990" => return Some("Used".to_owned()),
-                    _ => {}
-                }
-                // Otherwise... unknown currency?
-                ""
-            }
-            _ => "",
-        };
-
-        let rawAmount = raw[1..].parse::<i32>().ok()?;
-        let unitsString = (rawAmount / 100).to_string();
-        let hundredths = rawAmount % 100;
-        let hundredthsString = if hundredths < 10 {
-            format!("0{hundredths}")
-        } else {
-            hundredths.to_string()
-        };
-
-        Some(format!("{currency}{unitsString}.{hundredthsString}"))
-    }
 }
@@ -16,7 +16,7 @@
 
 use crate::{
     common::{BitArray, Result},
-    RXingResult,
+    Exceptions, RXingResult,
 };
 
 use super::{UPCEANExtension2Support, UPCEANExtension5Support, UPCEANReader, STAND_IN};
@@ -29,6 +29,8 @@ pub struct UPCEANExtensionSupport {
 
 impl UPCEANExtensionSupport {
     const EXTENSION_START_PATTERN: [u32; 3] = [1, 1, 2];
+    /// The widest gap allowed, twice the specified maximum to allow for blurred edges
+    const MAX_GAP_MODULES: f32 = 24.0;
 
     pub fn decodeRow(
         &self,
@@ -36,16 +38,143 @@ impl UPCEANExtensionSupport {
         row: &BitArray,
         rowOffset: usize,
     ) -> Result<RXingResult> {
+        Ok(self.decodeRowWithRange(rowNumber, row, rowOffset)?.0)
+    }
+
+    /**
+     * Decodes the 5 or 2 digit extension that follows a UPC/EAN barcode.
+     *
+     * @param rowOffset the column where the barcode ends, from which to search for the extension
+     * @return the extension, and the columns where its start pattern begins and its last digit
+     *  ends
+     */
+    pub fn decodeRowWithRange(
+        &self,
+        rowNumber: u32,
+        row: &BitArray,
+        rowOffset: usize,
+    ) -> Result<(RXingResult, [usize; 2])> {
         let extensionStartRange =
             STAND_IN.findGuardPattern(row, rowOffset, false, &Self::EXTENSION_START_PATTERN)?;
-        if let Ok(res_1) = self
-            .fiveSupport
-            .decodeRow(rowNumber, row, &extensionStartRange)
+        // The gap before the extension is 7 to 12 modules wide (ISO/IEC 15420). Farther away,
+        // the start pattern belongs to something else in the row, like another barcode.
+        let moduleWidth = (extensionStartRange[1] - extensionStartRange[0]) as f32 / 4.0;
+        if (extensionStartRange[0] - rowOffset) as f32 > Self::MAX_GAP_MODULES * moduleWidth {
+            return Err(Exceptions::NOT_FOUND);
+        }
+        let result = if let Ok(res_1) =
+            self.fiveSupport
+                .decodeRow(rowNumber, row, &extensionStartRange)
         {
-            Ok(res_1)
+            res_1
         } else {
             self.twoSupport
-                .decodeRow(rowNumber, row, &Self::EXTENSION_START_PATTERN)
+                .decodeRow(rowNumber, row, &extensionStartRange)?
+        };
+        // the second point is where the last digit ends
+        let end = result.getPoints()[1].x as usize;
+        Ok((result, [extensionStartRange[0], end]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        common::BitArray,
+        oned::{EAN13Reader, EAN13Writer, OneDReader, OneDimensionalCodeWriter, L_AND_G_PATTERNS},
+        DecodeHints, RXingResultMetadataType, RXingResultMetadataValue,
+    };
+
+    const CONTENTS: &str = "5901234123457";
+
+    /// An EAN-13 symbol followed by a 2 digit extension, if any, with 10 modules of quiet zone
+    /// around each. Returns the row and the columns where the extension starts and ends.
+    fn row(extension: Option<&str>) -> (BitArray, usize, usize) {
+        let mut bits = vec![false; 10];
+        bits.extend(EAN13Writer.encode_oned(CONTENTS).expect("encode"));
+        bits.extend([false; 10]);
+        let start = bits.len();
+        if let Some(extension) = extension {
+            bits.extend([true, false, true, true]);
+            let value: usize = extension.parse().expect("digits");
+            for (i, digit) in extension.chars().enumerate() {
+                if i > 0 {
+                    bits.extend([false, true]);
+                }
+                let mut digit = digit.to_digit(10).expect("digit") as usize;
+                // the parities of the digits encode the value mod 4, with G as a 1
+                if (value % 4) >> (1 - i) & 1 == 1 {
+                    digit += 10;
+                }
+                for (j, width) in L_AND_G_PATTERNS[digit].iter().enumerate() {
+                    bits.extend(std::iter::repeat_n(j % 2 == 1, *width as usize));
+                }
+            }
         }
+        let end = bits.len();
+        bits.extend([false; 10]);
+
+        let mut row = BitArray::with_size(bits.len());
+        for (i, bit) in bits.iter().enumerate() {
+            if *bit {
+                row.set(i);
+            }
+        }
+        (row, start, end)
+    }
+
+    fn allowing(lengths: &[u32]) -> DecodeHints {
+        DecodeHints {
+            AllowedEanExtensions: Some(lengths.to_vec()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_two_digit_extension() {
+        // 23 is 3 mod 4, both digits G
+        for extension in ["12", "23", "05"] {
+            let (row, start, end) = row(Some(extension));
+            let result = EAN13Reader
+                .decode_row(0, &row, &DecodeHints::default())
+                .expect("decode");
+            assert_eq!(CONTENTS, result.getText());
+            let metadata = result.getRXingResultMetadata();
+            assert_eq!(
+                Some(&RXingResultMetadataValue::UpcEanExtension(
+                    extension.to_owned()
+                )),
+                metadata.get(&RXingResultMetadataType::UPC_EAN_EXTENSION)
+            );
+            assert_eq!(
+                Some(&RXingResultMetadataValue::UpcEanExtensionPosition((
+                    start, end
+                ))),
+                metadata.get(&RXingResultMetadataType::UPC_EAN_EXTENSION_POSITION)
+            );
+            assert_eq!(
+                Some(&RXingResultMetadataValue::IssueNumber(
+                    extension.parse().unwrap()
+                )),
+                metadata.get(&RXingResultMetadataType::ISSUE_NUMBER)
+            );
+        }
+    }
+
+    #[test]
+    fn test_required_extension() {
+        let (with, _, _) = row(Some("12"));
+        let (without, _, _) = row(None);
+
+        assert!(EAN13Reader
+            .decode_row(0, &without, &DecodeHints::default())
+            .is_ok());
+        assert!(EAN13Reader.decode_row(0, &with, &allowing(&[2])).is_ok());
+        assert!(EAN13Reader.decode_row(0, &with, &allowing(&[2, 5])).is_ok());
+        // an extension of another length, or none, is rejected
+        assert!(EAN13Reader.decode_row(0, &with, &allowing(&[5])).is_err());
+        assert!(EAN13Reader
+            .decode_row(0, &without, &allowing(&[2, 5]))
+            .is_err());
     }
 }
@@ -0,0 +1,132 @@
+/*
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use rxing_one_d_proc_derive::OneDWriter;
+
+use crate::{common::Result, BarcodeFormat};
+
+use super::{upc_ean_reader, OneDimensionalCodeWriter, UPCEANExtension5Support, UPCEANWriter};
+
+/**
+ * Renders the 2 or 5 digit add-on that follows an EAN-13 or UPC-A symbol. On its own it makes a
+ * `UPC_EAN_EXTENSION` symbol; `EAN13Writer` and `UPCAWriter` append one from the
+ * `UpcEanExtension` hint.
+ */
+#[derive(OneDWriter, Default)]
+pub struct UPCEANExtensionWriter;
+impl UPCEANWriter for UPCEANExtensionWriter {}
+
+impl UPCEANExtensionWriter {
+    /// The modules of space between a symbol and its add-on, within the 7 to 12 ISO/IEC 15420 allows
+    pub const GAP: usize = 9;
+
+    const START_PATTERN: [u32; 3] = [1, 1, 2];
+    const SEPARATOR_PATTERN: [u32; 2] = [1, 1];
+
+    /// Appends the add-on `extension` to the bars of an EAN-13 or UPC-A symbol, after the gap
+    pub fn append(code: &mut Vec<bool>, extension: &str) -> Result<()> {
+        let extension = UPCEANExtensionWriter.encode_oned(extension)?;
+        code.extend([false; Self::GAP]);
+        code.extend(extension);
+        Ok(())
+    }
+}
+
+impl OneDimensionalCodeWriter for UPCEANExtensionWriter {
+    fn encode_oned(&self, contents: &str) -> Result<Vec<bool>> {
+        Self::checkNumeric(contents)?;
+        let digits = contents
+            .chars()
+            .filter_map(|c| c.to_digit(10))
+            .collect::<Vec<_>>();
+
+        // the digits set in L or G parity encode a check, a bit per digit with G as a 1
+        let parities = match digits.len() {
+            2 => (digits[0] * 10 + digits[1]) as usize % 4,
+            5 => {
+                let check = UPCEANExtension5Support::extensionChecksum(contents)
+                    .ok_or(Exceptions::ILLEGAL_ARGUMENT)?;
+                UPCEANExtension5Support::CHECK_DIGIT_ENCODINGS[check as usize]
+            }
+            length => {
+                return Err(Exceptions::illegal_argument_with(format!(
+                    "Requested contents should be 2 or 5 digits long, but got {length}"
+                )))
+            }
+        };
+
+        let mut result = vec![false; 4 + 7 * digits.len() + 2 * (digits.len() - 1)];
+        let mut pos = Self::appendPattern(&mut result, 0, &Self::START_PATTERN, true) as usize;
+        for (i, digit) in digits.iter().enumerate() {
+            if i > 0 {
+                pos +=
+                    Self::appendPattern(&mut result, pos, &Self::SEPARATOR_PATTERN, false) as usize;
+            }
+            let mut digit = *digit as usize;
+            if parities >> (digits.len() - 1 - i) & 1 == 1 {
+                digit += 10;
+            }
+            pos += Self::appendPattern(
+                &mut result,
+                pos,
+                &upc_ean_reader::L_AND_G_PATTERNS[digit],
+                false,
+            ) as usize;
+        }
+
+        Ok(result)
+    }
+
+    fn getSupportedWriteFormats(&self) -> Option<Vec<BarcodeFormat>> {
+        Some(vec![BarcodeFormat::UPC_EAN_EXTENSION])
+    }
+
+    fn getDefaultMargin(&self) -> u32 {
+        Self::DEFAULT_MARGIN
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        common::bit_matrix_test_case, BarcodeFormat, EncodeHintValue, EncodeHints, Writer,
+    };
+
+    use super::UPCEANExtensionWriter;
+
+    #[test]
+    fn test_encode() {
+        // 12 is 0 mod 4, both digits L: the start guard, 1, the separator and 2
+        let test_str = "10110011001010010011";
+        let result = UPCEANExtensionWriter
+            .encode_with_hints(
+                "12",
+                &BarcodeFormat::UPC_EAN_EXTENSION,
+                0,
+                0,
+                &EncodeHints::default().with(EncodeHintValue::Margin("0".to_owned())),
+            )
+            .expect("encode");
+        assert_eq!(test_str, bit_matrix_test_case::matrix_to_string(&result));
+    }
+
+    #[test]
+    fn test_encode_illegal_length() {
+        for contents in ["1", "123", "123456", "1a"] {
+            assert!(UPCEANExtensionWriter
+                .encode(contents, &BarcodeFormat::UPC_EAN_EXTENSION, 0, 0)
+                .is_err());
+        }
+    }
+}
@@ -16,11 +16,14 @@
 
 use crate::{
     common::{BitArray, Result},
-    point, BarcodeFormat, Binarizer, Exceptions, RXingResult, RXingResultMetadataType,
-    RXingResultMetadataValue, Reader,
+    point, BarcodeFormat, Binarizer, DecodeWarning, Exceptions, RXingResult,
+    RXingResultMetadataType, RXingResultMetadataValue, Reader,
 };
 
-use super::{one_d_reader, EANManufacturerOrgSupport, OneDReader, UPCEANExtensionSupport};
+use super::{
+    one_d_reader, EANManufacturerOrgSupport, OneDReader, ScanlinePattern, ScanlineWitness,
+    UPCEANExtensionSupport,
+};
 
 use once_cell::sync::Lazy;
 
@@ -186,6 +189,19 @@ pub trait UPCEANReader: OneDReader {
             return Err(Exceptions::NOT_FOUND);
         }
 
+        // Strict decoding wants the 7 modules of quiet zone the specifications ask for at least,
+        // on both sides. The start guard is 3 modules wide.
+        let strictness = hints.Strictness.unwrap_or_default();
+        let module = (startGuardRange[1] - startGuardRange[0]) as f32 / 3.0;
+        let quietZone = strictness.quiet_zone(0, module, 7);
+        if !one_d_reader::is_quiet_before(row, startGuardRange[0], quietZone, strictness)?
+            || !one_d_reader::is_quiet_after(row, end, quietZone, strictness)?
+        {
+            return Err(Exceptions::NOT_FOUND);
+        }
+        let strictQuietZones =
+            one_d_reader::has_strict_quiet_zones(row, startGuardRange[0], end, module, 7)?;
+
         let resultString = result;
 
         // UPC/EAN should never be less than 8 chars anyway
@@ -197,9 +213,15 @@ pub trait UPCEANReader: OneDReader {
             return Err(Exceptions::CHECKSUM);
         }
 
+        let format = self.getBarcodeFormat();
+        let resultString = if format == BarcodeFormat::UPC_E && hints.ExpandUPCE == Some(true) {
+            super::convertUPCEtoUPCA(&resultString).ok_or(Exceptions::FORMAT)?
+        } else {
+            resultString
+        };
+
         let left = (startGuardRange[1] + startGuardRange[0]) as f32 / 2.0;
         let right: f32 = (endRange[1] + endRange[0]) as f32 / 2.0;
-        let format = self.getBarcodeFormat();
         let mut decodeRXingResult = RXingResult::new(
             &resultString,
             Vec::new(), // no natural byte representation for these barcodes
@@ -207,14 +229,17 @@ pub trait UPCEANReader: OneDReader {
                 point(left, rowNumber as f32),
                 point(right, rowNumber as f32),
             ],
-            format,
+            format.clone(),
         );
+        if !strictQuietZones {
+            decodeRXingResult.addWarning(DecodeWarning::QuietZoneViolation);
+        }
 
         let mut extensionLength = 0;
 
         let mut attempt = || -> Result<()> {
-            let extensionRXingResult =
-                UPC_EAN_EXTENSION_SUPPORT.decodeRow(rowNumber, row, endRange[1])?;
+            let (extensionRXingResult, extensionRange) =
+                UPC_EAN_EXTENSION_SUPPORT.decodeRowWithRange(rowNumber, row, endRange[1])?;
 
             decodeRXingResult.putMetadata(
                 RXingResultMetadataType::UPC_EAN_EXTENSION,
@@ -222,6 +247,13 @@ pub trait UPCEANReader: OneDReader {
                     extensionRXingResult.getText().to_owned(),
                 ),
             );
+            decodeRXingResult.putMetadata(
+                RXingResultMetadataType::UPC_EAN_EXTENSION_POSITION,
+                RXingResultMetadataValue::UpcEanExtensionPosition((
+                    extensionRange[0],
+                    extensionRange[1],
+                )),
+            );
             decodeRXingResult.putAllMetadata(extensionRXingResult.getRXingResultMetadata().clone());
             decodeRXingResult.addPoints(&mut extensionRXingResult.getPoints().to_vec());
             extensionLength = extensionRXingResult.getText().chars().count();
@@ -230,6 +262,8 @@ pub trait UPCEANReader: OneDReader {
 
         let _try_result = attempt();
 
+        // An extension must be present and decoded if the hint is set, otherwise the row is
+        // rejected and the next one is tried
         if let Some(allowedExtensions) = &hints.AllowedEanExtensions {
             let mut valid = false;
             for length in allowedExtensions {
@@ -261,6 +295,14 @@ pub trait UPCEANReader: OneDReader {
             RXingResultMetadataType::SYMBOLOGY_IDENTIFIER,
             RXingResultMetadataValue::SymbologyIdentifier(format!("]E{symbologyIdentifier}")),
         );
+        decodeRXingResult.putMetadata(
+            RXingResultMetadataType::SCANLINE,
+            RXingResultMetadataValue::Scanline(self.record_scanline(
+                row,
+                startGuardRange,
+                &endRange,
+            )?),
+        );
 
         Ok(decodeRXingResult)
     }
@@ -452,6 +494,71 @@ pub trait UPCEANReader: OneDReader {
         }
     }
 
+    /**
+     * Reads the guards and digits of a symbol `decodeMiddle` decoded again, recording the runs
+     * of each and what they were matched as.
+     *
+     * @param row row of black/white values the symbol was decoded from
+     * @param startGuardRange start/end offset of start guard pattern
+     * @param endRange start/end offset of end guard pattern
+     * @return the patterns of the symbol, for the `SCANLINE` metadata of its result
+     */
+    fn record_scanline(
+        &self,
+        row: &BitArray,
+        startGuardRange: &[usize; 2],
+        endRange: &[usize; 2],
+    ) -> Result<ScanlineWitness> {
+        // the digits before and after the middle guard, and the patterns of the first half
+        let (left, right, leftPatterns): (usize, usize, &[[u32; 4]]) = match self.getBarcodeFormat()
+        {
+            BarcodeFormat::EAN_8 => (4, 4, &L_PATTERNS),
+            BarcodeFormat::UPC_E => (6, 0, &L_AND_G_PATTERNS),
+            _ => (6, 6, &L_AND_G_PATTERNS),
+        };
+
+        let mut counters = [0_u32; 4];
+        let mut characters = Vec::with_capacity(left + right);
+        let mut rowOffset = startGuardRange[1];
+        let mut middle = None;
+        for x in 0..left + right {
+            if x == left {
+                let middleRange = self.findGuardPattern(row, rowOffset, true, &MIDDLE_PATTERN)?;
+                middle = Some(ScanlinePattern::read(
+                    row,
+                    middleRange[0],
+                    MIDDLE_PATTERN.len(),
+                    None,
+                )?);
+                rowOffset = middleRange[1];
+            }
+            let patterns = if x < left { leftPatterns } else { &L_PATTERNS };
+            let bestMatch = self.decodeDigit(row, &mut counters, rowOffset, patterns)?;
+            characters.push(ScanlinePattern {
+                start: rowOffset as u32,
+                counters: counters.to_vec(),
+                value: Some(bestMatch as u32),
+            });
+            rowOffset += counters.iter().sum::<u32>() as usize;
+        }
+
+        // UPC-E ends with its 6 run middle/end pattern, the others with the 3 run guard
+        let endRuns = if right == 0 {
+            6
+        } else {
+            START_END_PATTERN.len()
+        };
+        let scanline = ScanlineWitness::new(
+            ScanlinePattern::read(row, startGuardRange[0], START_END_PATTERN.len(), None)?,
+            characters,
+            ScanlinePattern::read(row, endRange[0], endRuns, None)?,
+        );
+        Ok(match middle {
+            Some(middle) => scanline.with_middle_pattern(middle),
+            None => scanline,
+        })
+    }
+
     /**
      * Get the format of this decoder.
      *
@@ -0,0 +1,209 @@
+/*
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{BarcodeFormat, RXingResult, RXingResultMetadataType, RXingResultMetadataValue};
+
+/**
+ * What the digits of a UPC/EAN add-on stand for. A 2 digit add-on is the issue number of a
+ * periodical; a 5 digit add-on is the suggested retail price of a book.
+ */
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UPCEANExtensionInterpretation {
+    /// The issue number of a periodical, from a 2 digit add-on
+    IssueNumber(u32),
+    /**
+     * A price in hundredths of the currency, from a 5 digit add-on. The first digit gives the
+     * currency: pound sterling for 0 and US dollars for 5. For any other the currency is unknown.
+     */
+    SuggestedPrice {
+        currency: Option<char>,
+        hundredths: u32,
+    },
+    /// 99991, the book is complimentary
+    Complimentary,
+    /// 99990, the book is used
+    Used,
+    /// 90000, the book has no suggested price
+    NoSuggestedPrice,
+}
+
+impl UPCEANExtensionInterpretation {
+    /// Interprets the digits of an add-on, `None` if they are not 2 or 5 digits
+    pub fn parse(extension: &str) -> Option<Self> {
+        if !extension.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        match extension.len() {
+            2 => Some(Self::IssueNumber(extension.parse().ok()?)),
+            5 => Some(match extension {
+                // Reference: http://www.jollytech.com
+                "90000" => Self::NoSuggestedPrice,
+                "99991" => Self::Complimentary,
+                "99990" => Self::Used,
+                _ => Self::SuggestedPrice {
+                    currency: match &extension[..1] {
+                        "0" => Some('£'),
+                        "5" => Some('$'),
+                        _ => None,
+                    },
+                    hundredths: extension[1..].parse().ok()?,
+                },
+            }),
+            _ => None,
+        }
+    }
+
+    /// The price as readers report it in the `SUGGESTED_PRICE` metadata, such as "$12.99"
+    pub fn suggested_price(&self) -> Option<String> {
+        match self {
+            Self::SuggestedPrice {
+                currency,
+                hundredths,
+            } => Some(format!(
+                "{}{}.{:02}",
+                currency.map(String::from).unwrap_or_default(),
+                hundredths / 100,
+                hundredths % 100
+            )),
+            Self::Complimentary => Some("0.00".to_owned()),
+            Self::Used => Some("Used".to_owned()),
+            Self::IssueNumber(_) | Self::NoSuggestedPrice => None,
+        }
+    }
+}
+
+/**
+ * An EAN-13, UPC-A, EAN-8 or UPC-E result together with the add-on that followed it, if any.
+ * Readers report the add-on apart from the text, in the `UPC_EAN_EXTENSION` metadata.
+ */
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct UPCEANWithExtension {
+    pub format: BarcodeFormat,
+    /// The digits of the symbol itself, with its check digit
+    pub base: String,
+    /// The 2 or 5 digits of the add-on
+    pub extension: Option<String>,
+}
+
+impl UPCEANWithExtension {
+    /// The symbol and add-on of a result, `None` if it is not a UPC/EAN result
+    pub fn from_result(result: &RXingResult) -> Option<Self> {
+        if !matches!(
+            result.getBarcodeFormat(),
+            BarcodeFormat::EAN_13
+                | BarcodeFormat::UPC_A
+                | BarcodeFormat::EAN_8
+                | BarcodeFormat::UPC_E
+        ) {
+            return None;
+        }
+        let extension = match result
+            .getRXingResultMetadata()
+            .get(&RXingResultMetadataType::UPC_EAN_EXTENSION)
+        {
+            Some(RXingResultMetadataValue::UpcEanExtension(extension)) => Some(extension.clone()),
+            _ => None,
+        };
+        Some(Self {
+            format: result.getBarcodeFormat().clone(),
+            base: result.getText().to_owned(),
+            extension,
+        })
+    }
+
+    /// What the add-on stands for, `None` if there is none
+    pub fn interpretation(&self) -> Option<UPCEANExtensionInterpretation> {
+        UPCEANExtensionInterpretation::parse(self.extension.as_deref()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        oned::{EAN13Reader, OneDReader, UPCAReader},
+        BarcodeFormat, DecodeHints, EncodeHintValue, EncodeHints, MultiFormatWriter, Writer,
+    };
+
+    use super::{UPCEANExtensionInterpretation, UPCEANWithExtension};
+
+    fn round_trip(contents: &str, format: BarcodeFormat, extension: &str) -> UPCEANWithExtension {
+        let hints =
+            EncodeHints::default().with(EncodeHintValue::UpcEanExtension(extension.to_owned()));
+        let matrix = MultiFormatWriter
+            .encode_with_hints(contents, &format, 0, 1, &hints)
+            .expect("encode");
+        let row = matrix.getRow(0);
+        let result = if format == BarcodeFormat::UPC_A {
+            UPCAReader::default().decode_row(0, &row, &DecodeHints::default())
+        } else {
+            EAN13Reader.decode_row(0, &row, &DecodeHints::default())
+        }
+        .expect("decode");
+        UPCEANWithExtension::from_result(&result).expect("UPC/EAN")
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let result = round_trip("5901234123457", BarcodeFormat::EAN_13, "05");
+        assert_eq!("5901234123457", result.base);
+        assert_eq!(Some("05"), result.extension.as_deref());
+        assert_eq!(
+            Some(UPCEANExtensionInterpretation::IssueNumber(5)),
+            result.interpretation()
+        );
+
+        let result = round_trip("9780201379624", BarcodeFormat::EAN_13, "51299");
+        assert_eq!(
+            Some(UPCEANExtensionInterpretation::SuggestedPrice {
+                currency: Some('$'),
+                hundredths: 1299
+            }),
+            result.interpretation()
+        );
+
+        let result = round_trip("03600029145", BarcodeFormat::UPC_A, "90000");
+        assert_eq!(BarcodeFormat::UPC_A, result.format);
+        assert_eq!("036000291452", result.base);
+        assert_eq!(
+            Some(UPCEANExtensionInterpretation::NoSuggestedPrice),
+            result.interpretation()
+        );
+    }
+
+    #[test]
+    fn test_suggested_price() {
+        for (extension, price) in [
+            ("05495", Some("£54.95")),
+            ("51299", Some("$12.99")),
+            ("10012", Some("0.12")),
+            ("99991", Some("0.00")),
+            ("99990", Some("Used")),
+            ("90000", None),
+            ("12", None),
+        ] {
+            assert_eq!(
+                price.map(str::to_owned),
+                UPCEANExtensionInterpretation::parse(extension)
+                    .expect("interpretation")
+                    .suggested_price()
+            );
+        }
+        assert_eq!(None, UPCEANExtensionInterpretation::parse("123"));
+    }
+}
@@ -0,0 +1,334 @@
+/*
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::borrow::Cow;
+
+use crate::common::{simd, Result};
+use crate::{Exceptions, LuminanceSource};
+
+/// How the color of a pixel is packed into the bytes of a [`PackedPixelLuminanceSource`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PackedPixelFormat {
+    /// Red, green, blue and alpha bytes
+    Rgba8,
+    /// Blue, green, red and alpha bytes, as Windows and most GPU framebuffers store them
+    Bgra8,
+    /// A little endian `u16` of 5 bits of red, 6 of green and 5 of blue, from the high bits down
+    Rgb565,
+}
+
+impl PackedPixelFormat {
+    pub fn bytes_per_pixel(&self) -> usize {
+        match self {
+            PackedPixelFormat::Rgba8 | PackedPixelFormat::Bgra8 => 4,
+            PackedPixelFormat::Rgb565 => 2,
+        }
+    }
+
+    // The luminance `0.299R + 0.587G + 0.114B` of the pixel in `bytes`, white if it is
+    // transparent
+    fn luminance(&self, bytes: &[u8]) -> u8 {
+        let (red, green, blue) = match self {
+            PackedPixelFormat::Rgba8 | PackedPixelFormat::Bgra8 if bytes[3] == 0 => return 0xFF,
+            PackedPixelFormat::Rgba8 => (bytes[0], bytes[1], bytes[2]),
+            PackedPixelFormat::Bgra8 => (bytes[2], bytes[1], bytes[0]),
+            PackedPixelFormat::Rgb565 => {
+                let pixel = u16::from_le_bytes([bytes[0], bytes[1]]);
+                // the high bits repeated into the low ones, so full intensity is 255
+                let (red, green, blue) = (pixel >> 11, (pixel >> 5) & 0x3f, pixel & 0x1f);
+                (
+                    (red << 3 | red >> 2) as u8,
+                    (green << 2 | green >> 4) as u8,
+                    (blue << 3 | blue >> 2) as u8,
+                )
+            }
+        };
+        ((306 * red as u32 + 601 * green as u32 + 117 * blue as u32 + 0x200) >> 10) as u8
+    }
+}
+
+/**
+ * A luminance source over packed color pixels, such as a GUI framebuffer or a screenshot,
+ * converting them to luminance as rows are read instead of all up front. Fully transparent
+ * pixels read as white, as they would be shown over a light background.
+ *
+ * The pixels can be any `AsRef<[u8]>`. Cropping clones it, which costs nothing for a `&[u8]`
+ * or an `Arc<[u8]>`.
+ */
+#[derive(Debug, Clone)]
+pub struct PackedPixelLuminanceSource<D = Vec<u8>> {
+    data: D,
+    format: PackedPixelFormat,
+    stride: usize,
+    left: usize,
+    top: usize,
+    width: usize,
+    height: usize,
+    invert: bool,
+}
+
+impl<D: AsRef<[u8]> + Clone> PackedPixelLuminanceSource<D> {
+    /**
+     * Wraps pixels whose rows are packed without padding.
+     *
+     * # Errors
+     * `IllegalArgumentException` if `data` is shorter than `height` rows of `width` pixels
+     */
+    pub fn new(data: D, format: PackedPixelFormat, width: usize, height: usize) -> Result<Self> {
+        Self::with_stride(
+            data,
+            format,
+            width,
+            height,
+            width * format.bytes_per_pixel(),
+        )
+    }
+
+    /**
+     * Wraps pixels whose rows are `stride` bytes apart, the bytes after the pixels of each
+     * being padding.
+     *
+     * # Errors
+     * `IllegalArgumentException` if `stride` is less than a row of `width` pixels, or `data`
+     * is shorter than `height` rows
+     */
+    pub fn with_stride(
+        data: D,
+        format: PackedPixelFormat,
+        width: usize,
+        height: usize,
+        stride: usize,
+    ) -> Result<Self> {
+        let row_len = width * format.bytes_per_pixel();
+        if stride < row_len {
+            return Err(Exceptions::illegal_argument_with(format!(
+                "a stride of {stride} is less than the {row_len} bytes of a {format:?} row"
+            )));
+        }
+        let frame_len = match height {
+            0 => 0,
+            height => stride * (height - 1) + row_len,
+        };
+        if data.as_ref().len() < frame_len {
+            return Err(Exceptions::illegal_argument_with(format!(
+                "{width}x{height} {format:?} pixels with a stride of {stride} need {frame_len} bytes, got {}",
+                data.as_ref().len()
+            )));
+        }
+
+        Ok(Self {
+            data,
+            format,
+            stride,
+            left: 0,
+            top: 0,
+            width,
+            height,
+            invert: false,
+        })
+    }
+
+    pub fn get_format(&self) -> PackedPixelFormat {
+        self.format
+    }
+
+    pub fn get_stride(&self) -> usize {
+        self.stride
+    }
+
+    // The bytes of the pixels of row `y` of the source
+    fn row_bytes(&self, y: usize) -> &[u8] {
+        let bytes_per_pixel = self.format.bytes_per_pixel();
+        let start = (self.top + y) * self.stride + self.left * bytes_per_pixel;
+        &self.data.as_ref()[start..start + self.width * bytes_per_pixel]
+    }
+
+    fn luminance(&self, x: usize, y: usize) -> u8 {
+        let bytes_per_pixel = self.format.bytes_per_pixel();
+        let row = self.row_bytes(y);
+        let luminance = self
+            .format
+            .luminance(&row[x * bytes_per_pixel..(x + 1) * bytes_per_pixel]);
+        if self.invert {
+            255 - luminance
+        } else {
+            luminance
+        }
+    }
+}
+
+impl<D: AsRef<[u8]> + Clone> LuminanceSource for PackedPixelLuminanceSource<D> {
+    const SUPPORTS_CROP: bool = true;
+
+    fn get_row(&self, y: usize) -> Option<Cow<'_, [u8]>> {
+        if y >= self.height {
+            return None;
+        }
+        let bytes = self.row_bytes(y);
+        let mut row = vec![0; self.width];
+        if self.format == PackedPixelFormat::Rgba8 {
+            simd::rgba_to_luma(bytes, &mut row);
+        } else {
+            let pixels = bytes.chunks_exact(self.format.bytes_per_pixel());
+            for (luminance, pixel) in row.iter_mut().zip(pixels) {
+                *luminance = self.format.luminance(pixel);
+            }
+        }
+        if self.invert {
+            row.iter_mut()
+                .for_each(|luminance| *luminance = 255 - *luminance);
+        }
+        Some(Cow::Owned(row))
+    }
+
+    fn get_column(&self, x: usize) -> Vec<u8> {
+        (0..self.height).map(|y| self.luminance(x, y)).collect()
+    }
+
+    fn get_matrix(&self) -> Vec<u8> {
+        (0..self.height)
+            .flat_map(|y| self.get_row(y).unwrap_or_default().into_owned())
+            .collect()
+    }
+
+    fn get_width(&self) -> usize {
+        self.width
+    }
+
+    fn get_height(&self) -> usize {
+        self.height
+    }
+
+    fn crop(&self, left: usize, top: usize, width: usize, height: usize) -> Result<Self> {
+        if left + width > self.width || top + height > self.height {
+            return Err(Exceptions::illegal_argument_with(
+                "Crop rectangle does not fit within image data.",
+            ));
+        }
+        Ok(Self {
+            data: self.data.clone(),
+            left: self.left + left,
+            top: self.top + top,
+            width,
+            height,
+            ..*self
+        })
+    }
+
+    fn invert(&mut self) {
+        self.invert = !self.invert;
+    }
+
+    fn get_luma8_point(&self, x: usize, y: usize) -> u8 {
+        self.luminance(x, y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        common::HybridBinarizer, qrcode::QRCodeReader, BarcodeFormat, BinaryBitmap,
+        LuminanceSource, MultiFormatWriter, Reader, Writer,
+    };
+
+    use super::{PackedPixelFormat, PackedPixelLuminanceSource};
+
+    #[test]
+    fn test_formats() {
+        // red, white, transparent black and opaque black
+        let rgba = [255, 0, 0, 255, 255, 255, 255, 255, 0, 0, 0, 0, 0, 0, 0, 255];
+        let source =
+            PackedPixelLuminanceSource::new(&rgba[..], PackedPixelFormat::Rgba8, 4, 1).unwrap();
+        assert_eq!(vec![76, 255, 255, 0], source.get_matrix());
+
+        let bgra = [0, 0, 255, 255, 255, 255, 255, 255, 0, 0, 0, 0, 0, 0, 0, 255];
+        let source =
+            PackedPixelLuminanceSource::new(&bgra[..], PackedPixelFormat::Bgra8, 4, 1).unwrap();
+        assert_eq!(vec![76, 255, 255, 0], source.get_matrix());
+
+        // red, white and black
+        let rgb565 = [0x00, 0xf8, 0xff, 0xff, 0x00, 0x00];
+        let source =
+            PackedPixelLuminanceSource::new(&rgb565[..], PackedPixelFormat::Rgb565, 3, 1).unwrap();
+        assert_eq!(vec![76, 255, 0], source.get_matrix());
+    }
+
+    #[test]
+    fn test_stride_and_crop() {
+        // 3x3 pixels of luminance 10 * (x + 3y), in rows padded to 16 bytes
+        let mut data = [0xaa; 16 * 3];
+        for y in 0..3 {
+            for x in 0..3 {
+                let level = (10 * (x + 3 * y)) as u8;
+                data[y * 16 + x * 4..y * 16 + x * 4 + 4]
+                    .copy_from_slice(&[level, level, level, 255]);
+            }
+        }
+        let source =
+            PackedPixelLuminanceSource::with_stride(&data[..], PackedPixelFormat::Bgra8, 3, 3, 16)
+                .unwrap();
+        assert_eq!(vec![0, 10, 20, 30, 40, 50, 60, 70, 80], source.get_matrix());
+        assert_eq!(vec![10, 40, 70], source.get_column(1));
+        assert!(source.get_row(3).is_none());
+
+        let mut cropped = source.crop(1, 1, 2, 2).unwrap();
+        assert_eq!(&[40, 50][..], &cropped.get_row(0).unwrap()[..]);
+        assert_eq!(80, cropped.get_luma8_point(1, 1));
+        cropped.invert();
+        assert_eq!(vec![215, 205, 185, 175], cropped.get_matrix());
+        assert!(source.crop(2, 0, 2, 1).is_err());
+
+        assert!(PackedPixelLuminanceSource::with_stride(
+            &data[..],
+            PackedPixelFormat::Bgra8,
+            5,
+            3,
+            16
+        )
+        .is_err());
+        assert!(
+            PackedPixelLuminanceSource::new(&data[..], PackedPixelFormat::Rgba8, 4, 4).is_err()
+        );
+    }
+
+    #[test]
+    fn test_decode_screenshot() {
+        let matrix = MultiFormatWriter
+            .encode("framebuffer", &BarcodeFormat::QR_CODE, 120, 120)
+            .expect("encode");
+        let (width, height) = (matrix.getWidth() as usize, matrix.getHeight() as usize);
+        // dark blue modules on a transparent window, rows padded to 64 byte alignment
+        let stride = (width * 4).next_multiple_of(64);
+        let mut data = vec![0; stride * height];
+        for y in 0..height {
+            for x in 0..width {
+                if matrix.get(x as u32, y as u32) {
+                    data[y * stride + x * 4..][..4].copy_from_slice(&[90, 20, 10, 255]);
+                }
+            }
+        }
+        let source = PackedPixelLuminanceSource::with_stride(
+            data,
+            PackedPixelFormat::Bgra8,
+            width,
+            height,
+            stride,
+        )
+        .expect("pixels");
+        let result = QRCodeReader
+            .decode(&mut BinaryBitmap::new(HybridBinarizer::new(source)))
+            .expect("decode");
+        assert_eq!("framebuffer", result.getText());
+    }
+}
@@ -55,6 +55,8 @@ impl BarcodeValue {
                 std::cmp::Ordering::Less => {}
             }
         }
+        // the map iterates in a different order every run, keep ties in ascending order
+        result.sort_unstable();
 
         result
     }
@@ -63,3 +65,20 @@ impl BarcodeValue {
         *self.0.get(&value).unwrap_or(&0)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::BarcodeValue;
+
+    #[test]
+    fn test_ties_in_ascending_order() {
+        let mut value = BarcodeValue::new();
+        for v in [929, 17, 400, 17, 3, 929, 400, 5] {
+            value.setValue(v);
+        }
+
+        assert_eq!(vec![17, 400, 929], value.getValue());
+        assert_eq!(2, value.getConfidence(400));
+        assert_eq!(0, value.getConfidence(1));
+    }
+}
@@ -21,7 +21,7 @@ use std::sync::Arc;
 use crate::{
     common::{DecoderRXingResult, ECIStringBuilder, Eci, Result},
     pdf417::PDF417RXingResultMetadata,
-    Exceptions,
+    Content, Exceptions,
 };
 
 /**
@@ -172,6 +172,7 @@ pub fn decode(codewords: &[u32], ecLevel: &str) -> Result<DecoderRXingResult> {
         ecLevel.to_owned(),
     );
     decoderRXingResult.setOther(Some(Arc::new(resultMetadata)));
+    decoderRXingResult.setContent(Some(Content::from(&result)));
 
     Ok(decoderRXingResult)
 }
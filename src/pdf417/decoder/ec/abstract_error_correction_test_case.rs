@@ -30,23 +30,16 @@ pub fn corrupt(received: &mut [u32], howMany: u32, random: &mut rand::rngs::Thre
     }
 }
 
-#[allow(dead_code)]
 pub fn erase(received: &mut [u32], howMany: u32, random: &mut rand::rngs::ThreadRng) -> Vec<u32> {
     let mut erased = vec![false; received.len()];
-    let mut erasures = vec![0_u32; howMany as usize];
-    let mut erasureOffset = 0;
-    let mut j = 0;
-    while j < howMany {
+    let mut erasures = Vec::with_capacity(howMany as usize);
+    while erasures.len() < howMany as usize {
         let location = random.random_range(0..received.len());
-        if *erased.get(location).unwrap_or(&false) {
-            j -= 1;
-        } else {
+        if !erased[location] {
             erased[location] = true;
             received[location] = 0;
-            erasures[erasureOffset] = location as u32;
-            erasureOffset += 1;
+            erasures.push(location as u32);
         }
-        j += 1;
     }
     erasures
 }
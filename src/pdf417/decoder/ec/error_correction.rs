@@ -38,73 +38,111 @@ static FLD_INTERIOR: Lazy<ModulusGF> = Lazy::new(|| ModulusGF::new(NUMBER_OF_COD
  */
 
 /**
+ * Every erasure uses up one check codeword and every error two, so `e` errors and `f` erasures
+ * are corrected while `2e + f <= numECCodewords`.
+ *
  * @param received received codewords
  * @param numECCodewords number of those codewords used for EC
  * @param erasures location of erasures
- * @return number of errors
+ * @return number of codewords corrected, errors and erasures
  * @throws ChecksumException if errors cannot be corrected, maybe because of too many errors
  */
 pub fn decode(received: &mut [u32], numECCodewords: u32, erasures: &mut [u32]) -> Result<usize> {
     let field: &'static ModulusGF = &FLD_INTERIOR;
-    let poly = ModulusPoly::new(field, received.to_vec())?;
-    let mut S = vec![0u32; numECCodewords as usize];
-    let mut error = false;
-    for i in (1..=numECCodewords).rev() {
-        // for (int i = numECCodewords; i > 0; i--) {
-        let eval = poly.evaluateAt(field.exp(i));
-        S[(numECCodewords - i) as usize] = eval;
-        if eval != 0 {
-            error = true;
-        }
+    let mut erasures = erasures.to_vec();
+    erasures.sort_unstable();
+    erasures.dedup();
+    if erasures.len() > numECCodewords as usize
+        || erasures
+            .last()
+            .is_some_and(|&e| e as usize >= received.len())
+    {
+        return Err(Exceptions::checksum_with(file!()));
     }
 
-    if !error {
+    let S = syndromes(field, received, numECCodewords)?;
+    if S.iter().all(|s| *s == 0) {
         return Ok(0);
     }
 
     let mut knownErrors: ModulusPoly = ModulusPoly::getOne(field);
-    let mut b;
-    let mut term;
-    let mut kE: ModulusPoly;
-    if !erasures.is_empty() {
-        for erasure in erasures {
-            // for (int erasure : erasures) {
-            b = field.exp(received.len() as u32 - 1 - *erasure);
-            // Add (1 - bx) term:
-            term = ModulusPoly::new(field, vec![field.subtract(0, b), 1])?;
-            kE = knownErrors.clone();
-            knownErrors = kE.multiply(term)?;
-        }
+    let mut erasureLocations = Vec::with_capacity(erasures.len());
+    for erasure in &erasures {
+        // for (int erasure : erasures) {
+        let b = field.exp(received.len() as u32 - 1 - *erasure);
+        erasureLocations.push(b);
+        // Add (1 - bx) term:
+        let term = ModulusPoly::new(field, vec![field.subtract(0, b), 1])?;
+        knownErrors = knownErrors.multiply(term)?;
     }
 
-    let syndrome = ModulusPoly::new(field, S)?;
-    //syndrome = syndrome.multiply(knownErrors);
+    // the key equation is solved for the errors with the syndromes multiplied by the erasure
+    // locator, modulo x^numECCodewords
+    let mut syndrome = ModulusPoly::new(field, S)?;
+    if !erasures.is_empty() {
+        let product = syndrome.multiply(knownErrors.clone())?;
+        let coefficients = product.getCoefficients();
+        let keep = coefficients.len().min(numECCodewords as usize);
+        syndrome = ModulusPoly::new(field, coefficients[coefficients.len() - keep..].to_vec())?;
+    }
 
     let sigmaOmega = runEuclideanAlgorithm(
         ModulusPoly::buildMonomial(field, numECCodewords as usize, 1),
         syndrome,
-        numECCodewords,
+        numECCodewords + erasures.len() as u32,
         field,
     )?;
     let sigma = sigmaOmega[0].clone();
     let omega = sigmaOmega[1].clone();
 
-    //sigma = sigma.multiply(knownErrors);
+    let errorLocations = findErrorLocations(sigma.clone(), field)?;
+    if 2 * errorLocations.len() + erasures.len() > numECCodewords as usize
+        || errorLocations
+            .iter()
+            .any(|location| erasureLocations.contains(location))
+    {
+        return Err(Exceptions::checksum_with(file!()));
+    }
 
-    let mut errorLocations = findErrorLocations(sigma.clone(), field)?;
-    let errorMagnitudes = findErrorMagnitudes(omega, sigma, &mut errorLocations, field);
+    let mut locations: Vec<u32> = errorLocations
+        .iter()
+        .chain(&erasureLocations)
+        .copied()
+        .collect();
+    let errorMagnitudes =
+        findErrorMagnitudes(omega, sigma.multiply(knownErrors)?, &mut locations, field);
 
-    for i in 0..errorLocations.len() {
+    let mut corrected = received.to_vec();
+    for i in 0..locations.len() {
         // for (int i = 0; i < errorLocations.length; i++) {
-        let position = received.len() as isize - 1 - field.log(errorLocations[i])? as isize;
+        let position = received.len() as isize - 1 - field.log(locations[i])? as isize;
         if position < 0 {
             return Err(Exceptions::checksum_with(file!()));
         }
-        received[position as usize] =
-            field.subtract(received[position as usize], errorMagnitudes[i]);
+        corrected[position as usize] =
+            field.subtract(corrected[position as usize], errorMagnitudes[i]);
+    }
+    // with erasures, the key equation may have been solved by a polynomial that doesn't
+    // correct anything
+    if !erasures.is_empty()
+        && syndromes(field, &corrected, numECCodewords)?
+            .iter()
+            .any(|s| *s != 0)
+    {
+        return Err(Exceptions::checksum_with(file!()));
     }
+    received.copy_from_slice(&corrected);
 
-    Ok(errorLocations.len())
+    Ok(locations.len())
+}
+
+/// The received codewords evaluated at `generator^numECCodewords` down to `generator^1`
+fn syndromes(field: &'static ModulusGF, received: &[u32], numECCodewords: u32) -> Result<Vec<u32>> {
+    let poly = ModulusPoly::new(field, received.to_vec())?;
+    Ok((1..=numECCodewords)
+        .rev()
+        .map(|i| poly.evaluateAt(field.exp(i)))
+        .collect())
 }
 
 fn runEuclideanAlgorithm(
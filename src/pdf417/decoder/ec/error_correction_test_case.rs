@@ -19,7 +19,7 @@ use rand::Rng;
 use crate::common::Result;
 
 use super::{
-    abstract_error_correction_test_case::{corrupt, getRandom},
+    abstract_error_correction_test_case::{corrupt, erase, getRandom},
     error_correction::decode,
 };
 
@@ -43,7 +43,7 @@ const PDF417_TEST_WITH_EC: [u32; 112] = [
 const ECC_BYTES: usize = PDF417_TEST_WITH_EC.len() - PDF417_TEST.len();
 const ERROR_LIMIT: usize = ECC_BYTES;
 const MAX_ERRORS: usize = ERROR_LIMIT / 2;
-const _MAX_ERASURES: usize = ERROR_LIMIT;
+const MAX_ERASURES: usize = ERROR_LIMIT;
 
 // private final ErrorCorrection ec = new ErrorCorrection();
 
@@ -99,6 +99,59 @@ fn testTooManyErrors() {
     // }
 }
 
+#[test]
+fn testMaxErasures() {
+    let mut random = getRandom();
+    for _testIterations in 0..100 {
+        let mut received = PDF417_TEST_WITH_EC;
+        let mut erasures = erase(&mut received, MAX_ERASURES as u32, &mut random);
+        checkDecodeErasures(&mut received, &mut erasures).expect("ok");
+    }
+}
+
+#[test]
+fn testErasuresAndErrors() {
+    let mut random = getRandom();
+    for erasureCount in [1, 10, 32, 50, 63] {
+        let errorCount = (ERROR_LIMIT - erasureCount) / 2;
+        let mut received = PDF417_TEST_WITH_EC;
+        let mut erasures = erase(&mut received, erasureCount as u32, &mut random);
+        // the errors are put where nothing was erased
+        let mut errors = 0;
+        while errors < errorCount {
+            let location = random.random_range(0..received.len());
+            if !erasures.contains(&(location as u32))
+                && received[location] == PDF417_TEST_WITH_EC[location]
+            {
+                received[location] = (received[location] + 1 + random.random_range(0..900)) % 929;
+                errors += 1;
+            }
+        }
+        checkDecodeErasures(&mut received, &mut erasures).expect("ok");
+    }
+}
+
+#[test]
+fn testTooManyErasures() {
+    let mut received = PDF417_TEST_WITH_EC;
+    let mut random = getRandom();
+    let mut erasures = erase(&mut received, MAX_ERASURES as u32 + 1, &mut random);
+    assert!(checkDecodeErasures(&mut received, &mut erasures).is_err());
+}
+
+#[test]
+fn testErasuresCountAsCorrected() {
+    let mut received = PDF417_TEST_WITH_EC;
+    received[3] = 0;
+    received[70] = 0;
+    received[20] = 5;
+    assert_eq!(
+        3,
+        decode(&mut received, ECC_BYTES as u32, &mut [70, 3]).expect("ok")
+    );
+    assert_eq!(PDF417_TEST_WITH_EC, received);
+}
+
 fn checkDecode(received: &mut [u32]) -> Result<()> {
     checkDecodeErasures(received, &mut [0_u32; 0])
 }
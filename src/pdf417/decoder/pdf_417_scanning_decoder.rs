@@ -18,7 +18,7 @@ use std::sync::Arc;
 
 use crate::{
     common::{BitMatrix, DecoderRXingResult, Result},
-    pdf417::pdf_417_common,
+    pdf417::{pdf_417_common, PDF417RXingResultMetadata},
     Exceptions, Point,
 };
 
@@ -460,22 +460,39 @@ fn createDecoderRXingResult(
     // // for (int i = 0; i < ambiguousIndexValues.length; i++) {
     //   ambiguousIndexValues[i] = ambiguousIndexValuesList.get(i) as u32;
     // }
-    createDecoderRXingResultFromAmbiguousValues(
+    let mut decoderRXingResult = createDecoderRXingResultFromAmbiguousValues(
         detectionRXingResult.getBarcodeECLevel(),
         &mut codewords,
         &mut erasures,
         &mut ambiguousIndexesList,
         &ambiguousIndexValues,
-    )
+    )?;
+
+    // Record the structure of the symbol and how much of it was corrected with the macro data
+    let mut metadata = decoderRXingResult
+        .getOther()
+        .and_then(|other| other.downcast_ref::<PDF417RXingResultMetadata>().cloned())
+        .unwrap_or_default();
+    metadata.setRowCount(detectionRXingResult.getBarcodeRowCount());
+    metadata.setColumnCount(detectionRXingResult.getBarcodeColumnCount());
+    metadata.setErrorCorrectionLevel(detectionRXingResult.getBarcodeECLevel());
+    metadata.setErasures(decoderRXingResult.getErasures());
+    metadata.setErrorsCorrected(decoderRXingResult.getErrorsCorrected());
+    decoderRXingResult.setOther(Some(Arc::new(metadata)));
+
+    Ok(decoderRXingResult)
 }
 
 /**
- * This method deals with the fact, that the decoding process doesn't always yield a single most likely value. The
- * current error correction implementation doesn't deal with erasures very well, so it's better to provide a value
- * for these ambiguous codewords instead of treating it as an erasure. The problem is that we don't know which of
- * the ambiguous values to choose. We try decode using the first value, and if that fails, we use another of the
- * ambiguous values and try to decode again. This usually only happens on very hard to read and decode barcodes,
- * so decoding the normal barcodes is not affected by this.
+ * This method deals with the fact, that the decoding process doesn't always yield a single most likely value. An
+ * ambiguous codeword is usually one of its most likely values, which costs no error correction at all, so it's
+ * better to provide a value for these ambiguous codewords instead of treating it as an erasure. The problem is that
+ * we don't know which of the ambiguous values to choose. We try decode using the first value, and if that fails, we
+ * use another of the ambiguous values and try to decode again. This usually only happens on very hard to read and
+ * decode barcodes, so decoding the normal barcodes is not affected by this.
+ *
+ * If none of the combinations decodes, the ambiguous codewords are decoded as erasures, which cost half as much
+ * error correction as the errors they are otherwise corrected as.
  *
  * @param erasureArray contains the indexes of erasures
  * @param ambiguousIndexes array with the indexes that have more than one most likely value
@@ -510,6 +527,7 @@ fn createDecoderRXingResultFromAmbiguousValues(
         if ambiguousIndexCount.is_empty() {
             return Err(Exceptions::CHECKSUM);
         }
+        let mut exhausted = false;
         for i in 0..ambiguousIndexCount.len() {
             // for (int i = 0; i < ambiguousIndexCount.length; i++) {
             if ambiguousIndexCount[i] < ambiguousIndexValues[i].len() - 1 {
@@ -518,14 +536,20 @@ fn createDecoderRXingResultFromAmbiguousValues(
             } else {
                 ambiguousIndexCount[i] = 0;
                 if i == ambiguousIndexCount.len() - 1 {
-                    return Err(Exceptions::CHECKSUM);
+                    exhausted = true;
                 }
             }
         }
+        if exhausted {
+            break;
+        }
 
         tries -= 1;
     }
-    Err(Exceptions::CHECKSUM)
+
+    let mut erasures = erasureArray.to_vec();
+    erasures.extend_from_slice(ambiguousIndexes);
+    decodeCodewords(codewords, ecLevel, &mut erasures)
 }
 
 fn createBarcodeMatrix(detectionRXingResult: &mut DetectionRXingResult) -> Vec<Vec<BarcodeValue>> {
@@ -961,3 +985,46 @@ fn getCodewordBucketNumberArray(moduleBitCount: &[u32]) -> u32 {
 //     return formatter.toString();
 //   }
 // }
+
+#[cfg(test)]
+mod tests {
+    use crate::pdf417::encoder::pdf_417_error_correction::generateErrorCorrection;
+
+    use super::createDecoderRXingResultFromAmbiguousValues;
+
+    #[test]
+    fn test_ambiguous_values_decode_as_erasures() {
+        // "HELLO " in text compaction, with 8 error correction codewords
+        let data: String = [4u32, 7 * 30 + 4, 11 * 30 + 11, 14 * 30 + 26]
+            .iter()
+            .map(|c| char::from_u32(*c).unwrap())
+            .collect();
+        let ec = generateErrorCorrection(&data, 2).unwrap();
+        let expected: Vec<u32> = data.chars().chain(ec.chars()).map(|c| c as u32).collect();
+
+        // six codewords were read as two values each, neither of them right: every combination
+        // is six errors, more than the four the symbol corrects, but six erasures are corrected
+        let mut codewords = expected.clone();
+        let mut ambiguousIndexes = vec![0, 2, 5, 7, 8, 11];
+        let ambiguousIndexValues: Vec<Vec<u32>> = ambiguousIndexes
+            .iter()
+            .map(|i| {
+                vec![
+                    (expected[*i as usize] + 1) % 929,
+                    (expected[*i as usize] + 2) % 929,
+                ]
+            })
+            .collect();
+        let result = createDecoderRXingResultFromAmbiguousValues(
+            2,
+            &mut codewords,
+            &mut [],
+            &mut ambiguousIndexes,
+            &ambiguousIndexValues,
+        )
+        .expect("decode");
+        assert_eq!("HELLO ", result.getText());
+        assert_eq!(6, result.getErasures());
+        assert_eq!(expected, codewords);
+    }
+}
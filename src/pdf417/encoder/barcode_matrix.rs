@@ -35,14 +35,24 @@ impl BarcodeMatrix {
      */
     pub fn new(height: usize, width: usize, compact: bool) -> Self {
         //Initializes the array to the correct width
+        let rowWidth = if !compact {
+            (width + 4) * 17 + 1
+        } else {
+            (width + 2) * 17 + 1
+        };
+        Self::with_row_width(height, width, rowWidth)
+    }
+
+    /**
+     * @param height the height of the matrix (Rows)
+     * @param width  the width of the matrix (Cols)
+     * @param rowWidth the width of a row in modules, with everything around the data columns
+     */
+    pub fn with_row_width(height: usize, width: usize, rowWidth: usize) -> Self {
         let mut matrix = Vec::with_capacity(height);
 
         for _i in 0..height {
-            if !compact {
-                matrix.push(BarcodeRow::new((width + 4) * 17 + 1));
-            } else {
-                matrix.push(BarcodeRow::new((width + 2) * 17 + 1));
-            }
+            matrix.push(BarcodeRow::new(rowWidth));
         }
         Self {
             matrix,
@@ -68,6 +78,16 @@ impl BarcodeMatrix {
         &mut self.matrix[self.currentRow as usize]
     }
 
+    /// The number of rows
+    pub fn getRowCount(&self) -> usize {
+        self.height
+    }
+
+    /// The number of data columns, excluding start, stop and row indicator columns
+    pub fn getColumnCount(&self) -> usize {
+        self.width / 17
+    }
+
     pub fn getMatrix(&self) -> Vec<Vec<u8>> {
         self.getScaledMatrix(1, 1)
     }
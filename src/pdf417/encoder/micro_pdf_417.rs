@@ -0,0 +1,412 @@
+/*
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ * http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use crate::common::{CharacterSet, Result};
+use crate::Exceptions;
+
+use super::{
+    pdf_417_error_correction, pdf_417_high_level_encoder, BarcodeMatrix, BarcodeRow, Compaction,
+    CODEWORD_TABLE, PDF417,
+};
+
+/**
+ * The symbol sizes of ISO/IEC 24728:2006 table 1, with the row address patterns each starts
+ * with (tables 10 to 12).
+ */
+struct MicroVariant {
+    columns: u32,
+    rows: u32,
+    errorCorrectionCodewords: u32,
+    leftRap: usize,
+    centerRap: usize,
+    rightRap: usize,
+    cluster: usize,
+}
+
+const fn variant(
+    columns: u32,
+    rows: u32,
+    errorCorrectionCodewords: u32,
+    (leftRap, centerRap, rightRap): (usize, usize, usize),
+    cluster: usize,
+) -> MicroVariant {
+    MicroVariant {
+        columns,
+        rows,
+        errorCorrectionCodewords,
+        leftRap,
+        centerRap,
+        rightRap,
+        cluster,
+    }
+}
+
+const VARIANTS: [MicroVariant; 34] = [
+    variant(1, 11, 7, (1, 0, 9), 0),
+    variant(1, 14, 7, (8, 0, 8), 1),
+    variant(1, 17, 7, (36, 0, 36), 2),
+    variant(1, 20, 8, (19, 0, 19), 0),
+    variant(1, 24, 8, (9, 0, 17), 2),
+    variant(1, 28, 8, (25, 0, 33), 0),
+    variant(2, 8, 8, (1, 0, 1), 0),
+    variant(2, 11, 9, (1, 0, 9), 0),
+    variant(2, 14, 9, (8, 0, 8), 1),
+    variant(2, 17, 10, (36, 0, 36), 2),
+    variant(2, 20, 11, (19, 0, 19), 0),
+    variant(2, 23, 13, (9, 0, 17), 2),
+    variant(2, 26, 15, (27, 0, 35), 2),
+    variant(3, 6, 12, (1, 1, 1), 0),
+    variant(3, 8, 14, (7, 7, 7), 0),
+    variant(3, 10, 16, (15, 15, 15), 2),
+    variant(3, 12, 18, (25, 25, 25), 0),
+    variant(3, 15, 21, (37, 37, 37), 0),
+    variant(3, 20, 26, (1, 17, 33), 0),
+    variant(3, 26, 32, (1, 9, 17), 0),
+    variant(3, 32, 38, (21, 29, 37), 2),
+    variant(3, 38, 44, (15, 31, 47), 2),
+    variant(3, 44, 50, (1, 25, 49), 0),
+    variant(4, 4, 8, (47, 19, 43), 1),
+    variant(4, 6, 12, (1, 1, 1), 0),
+    variant(4, 8, 14, (7, 7, 7), 0),
+    variant(4, 10, 16, (15, 15, 15), 2),
+    variant(4, 12, 18, (25, 25, 25), 0),
+    variant(4, 15, 21, (37, 37, 37), 0),
+    variant(4, 20, 26, (1, 17, 33), 0),
+    variant(4, 26, 32, (1, 9, 17), 0),
+    variant(4, 32, 38, (21, 29, 37), 2),
+    variant(4, 38, 44, (15, 31, 47), 2),
+    variant(4, 44, 50, (1, 25, 49), 0),
+];
+
+/**
+ * The 52 left and right row address patterns of ISO/IEC 24728:2006 table 2, as the widths of
+ * their bars and spaces, 10 modules each.
+ */
+const SIDE_RAPS: [&str; 52] = [
+    "221311", "311311", "312211", "222211", "213211", "214111", "223111", "313111", "322111",
+    "412111", "421111", "331111", "241111", "232111", "231211", "321211", "411211", "411121",
+    "411112", "321112", "312112", "311212", "311221", "311131", "311122", "311113", "221113",
+    "221122", "221131", "221221", "222121", "312121", "321121", "231121", "231112", "222112",
+    "213112", "212212", "212221", "212131", "212122", "212113", "211213", "211123", "211132",
+    "211141", "211231", "211222", "211312", "211321", "211411", "212311",
+];
+
+/**
+ * The 52 center row address patterns of ISO/IEC 24728:2006 table 2, between the second and
+ * third column of symbols of 3 or 4 columns.
+ */
+const CENTER_RAPS: [&str; 52] = [
+    "112231", "121231", "122131", "131131", "131221", "132121", "141121", "141211", "142111",
+    "133111", "132211", "131311", "122311", "123211", "124111", "115111", "114211", "114121",
+    "123121", "123112", "122212", "122221", "121321", "121411", "112411", "113311", "113221",
+    "113212", "113122", "122122", "131122", "131113", "122113", "113113", "112213", "112222",
+    "112312", "112321", "111421", "111331", "111322", "111232", "111223", "111133", "111124",
+    "111214", "112114", "121114", "121123", "121132", "112132", "112141",
+];
+
+/// The height of a row in modules, the least ISO/IEC 24728 allows, for the preferred ratio
+pub const MICRO_PDF417_ROW_HEIGHT: u32 = 2;
+
+/**
+ * The logic part of MicroPDF417 (ISO/IEC 24728), the small variant of PDF417 with 1 to 4
+ * columns and no start and stop patterns: row address patterns at both sides, and in the
+ * middle of wider symbols, tell the rows apart. It encodes the data as PDF417 does, but has a
+ * fixed number of error correction codewords for each of its 34 sizes.
+ */
+pub struct MicroPDF417 {
+    barcodeMatrix: Option<BarcodeMatrix>,
+    compaction: Compaction,
+    encoding: Option<CharacterSet>,
+    minCols: u32,
+    maxCols: u32,
+    maxRows: u32,
+    minRows: u32,
+    preferredRatio: Option<f64>,
+}
+
+impl Default for MicroPDF417 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MicroPDF417 {
+    pub fn new() -> Self {
+        Self {
+            barcodeMatrix: None,
+            compaction: Compaction::AUTO,
+            encoding: None,
+            minCols: 1,
+            maxCols: 4,
+            maxRows: 44,
+            minRows: 4,
+            preferredRatio: None,
+        }
+    }
+
+    pub fn getBarcodeMatrix(&self) -> &Option<BarcodeMatrix> {
+        &self.barcodeMatrix
+    }
+
+    /**
+     * @param msg message to encode
+     * @param autoECI automatically insert ECIs if needed
+     * @throws WriterException if the contents cannot be encoded in this format
+     */
+    pub fn generateBarcodeLogic(&mut self, msg: &str, autoECI: bool) -> Result<()> {
+        //1. step: High-level encoding
+        let highLevel = pdf_417_high_level_encoder::encodeHighLevel(
+            msg,
+            self.compaction,
+            self.encoding,
+            autoECI,
+        )?;
+        let sourceCodeWords = highLevel.chars().count() as u32;
+        let variant = self.determineVariant(sourceCodeWords)?;
+
+        //2. step: construct data codewords, there is no symbol length descriptor
+        let capacity = variant.columns * variant.rows - variant.errorCorrectionCodewords;
+        let mut dataCodewords = highLevel;
+        for _i in sourceCodeWords..capacity {
+            dataCodewords.push(char::from_u32(900).ok_or(Exceptions::PARSE)?);
+            //PAD characters
+        }
+
+        //3. step: Error correction
+        let ec = pdf_417_error_correction::generateErrorCorrectionCodewords(
+            &dataCodewords,
+            variant.errorCorrectionCodewords,
+        )?;
+
+        //4. step: low-level encoding
+        let codewords: Vec<usize> = dataCodewords
+            .chars()
+            .chain(ec.chars())
+            .map(|c| c as usize)
+            .collect();
+        self.barcodeMatrix = Some(Self::encodeLowLevel(&codewords, variant));
+        Ok(())
+    }
+
+    fn encodeLowLevel(codewords: &[usize], variant: &MicroVariant) -> BarcodeMatrix {
+        let columns = variant.columns as usize;
+        let mut logic = BarcodeMatrix::with_row_width(
+            variant.rows as usize,
+            columns,
+            Self::getSymbolWidth(variant.columns) as usize,
+        );
+
+        for (y, rowCodewords) in codewords.chunks(columns).enumerate() {
+            // the row address patterns and the clusters go on from the ones of the first row
+            let cluster = (variant.cluster + y) % 3;
+            let rap = |first: usize| (first - 1 + y) % 52;
+            logic.startRow();
+            let row = logic.getCurrentRowMut();
+
+            Self::encodeRap(SIDE_RAPS[rap(variant.leftRap)], row);
+            for (x, codeword) in rowCodewords.iter().enumerate() {
+                if x == columns / 2 && columns >= 3 {
+                    Self::encodeRap(CENTER_RAPS[rap(variant.centerRap)], row);
+                }
+                PDF417::encodeChar(CODEWORD_TABLE[cluster][*codeword], 17, row);
+            }
+            Self::encodeRap(SIDE_RAPS[rap(variant.rightRap)], row);
+            // the stop bar
+            row.addBar(true, 1);
+        }
+        logic
+    }
+
+    fn encodeRap(widths: &str, logic: &mut BarcodeRow) {
+        for (i, width) in widths.bytes().enumerate() {
+            logic.addBar(i % 2 == 0, (width - b'0') as usize);
+        }
+    }
+
+    /// The width in modules of a symbol of `columns` columns
+    fn getSymbolWidth(columns: u32) -> u32 {
+        let centerRap = if columns >= 3 { 10 } else { 0 };
+        10 + 17 * columns + centerRap + 10 + 1
+    }
+
+    /**
+     * Picks the size of the symbol: for each number of columns the fewest rows that hold the
+     * data, and of those the symbol closest to the preferred ratio, or the smallest one if
+     * there is none.
+     */
+    fn determineVariant(&self, sourceCodeWords: u32) -> Result<&'static MicroVariant> {
+        let candidates = (self.minCols..=self.maxCols).filter_map(|columns| {
+            VARIANTS.iter().find(|variant| {
+                variant.columns == columns
+                    && (self.minRows..=self.maxRows).contains(&variant.rows)
+                    && variant.columns * variant.rows - variant.errorCorrectionCodewords
+                        >= sourceCodeWords
+            })
+        });
+        let area = |variant: &MicroVariant| Self::getSymbolWidth(variant.columns) * variant.rows;
+        let best = match self.preferredRatio {
+            Some(preferredRatio) => candidates.min_by(|a, b| {
+                let distance = |variant: &MicroVariant| {
+                    let ratio = Self::getSymbolWidth(variant.columns) as f64
+                        / (variant.rows * MICRO_PDF417_ROW_HEIGHT) as f64;
+                    (ratio - preferredRatio).abs()
+                };
+                distance(a).total_cmp(&distance(b))
+            }),
+            None => candidates.min_by_key(|variant| area(variant)),
+        };
+        best.ok_or(Exceptions::writer_with(format!(
+            "Unable to fit {sourceCodeWords} codewords in a MicroPDF417 symbol"
+        )))
+    }
+
+    /**
+     * Sets max/min row/col values, within the 1 to 4 columns and 4 to 44 rows of MicroPDF417
+     *
+     * @param maxCols maximum allowed columns
+     * @param minCols minimum allowed columns
+     * @param maxRows maximum allowed rows
+     * @param minRows minimum allowed rows
+     */
+    pub fn setDimensions(&mut self, maxCols: u32, minCols: u32, maxRows: u32, minRows: u32) {
+        self.maxCols = maxCols;
+        self.minCols = minCols;
+        self.maxRows = maxRows;
+        self.minRows = minRows;
+    }
+
+    /**
+     * @param ratio the preferred ratio of the width of the symbol to its height, with rows of
+     *              `MICRO_PDF417_ROW_HEIGHT` modules
+     */
+    pub fn setPreferredRatio(&mut self, ratio: f64) {
+        self.preferredRatio = Some(ratio);
+    }
+
+    /**
+     * @param compaction compaction mode to use
+     */
+    pub fn setCompaction(&mut self, compaction: Compaction) {
+        self.compaction = compaction
+    }
+
+    /**
+     * @param encoding sets character encoding to use
+     */
+    pub fn setEncoding(&mut self, encoding: Option<CharacterSet>) {
+        self.encoding = encoding;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::pdf417::decoder::ec::error_correction;
+
+    use super::{MicroPDF417, CENTER_RAPS, SIDE_RAPS, VARIANTS};
+
+    #[test]
+    fn test_tables() {
+        for raps in [SIDE_RAPS, CENTER_RAPS] {
+            assert!(raps
+                .iter()
+                .all(|rap| rap.bytes().map(|width| width - b'0').sum::<u8>() == 10));
+            assert_eq!(52, raps.iter().collect::<HashSet<_>>().len());
+        }
+        for variant in &VARIANTS {
+            // at least one data codeword, the ones of wide symbols start alike
+            assert!(variant.columns * variant.rows > variant.errorCorrectionCodewords);
+            assert!((variant.centerRap == 0) == (variant.columns < 3));
+        }
+    }
+
+    #[test]
+    fn test_sizes() {
+        let size = |msg: &str, encoder: &mut MicroPDF417| {
+            encoder.generateBarcodeLogic(msg, false).expect("encode");
+            let matrix = encoder.getBarcodeMatrix().as_ref().expect("matrix");
+            let rows = matrix.getMatrix();
+            (matrix.getColumnCount(), matrix.getRowCount(), rows[0].len())
+        };
+
+        // the smallest symbol, 4 columns of 4 rows with a center row address pattern
+        assert_eq!((4, 4, 99), size("1234", &mut MicroPDF417::new()));
+        // 1 column of 11 rows, with only the side ones
+        let mut tall = MicroPDF417::new();
+        tall.setPreferredRatio(1.0);
+        assert_eq!((1, 11, 38), size("1234", &mut tall));
+
+        let mut two_columns = MicroPDF417::new();
+        two_columns.setDimensions(2, 2, 44, 4);
+        assert_eq!((2, 8, 55), size("1234", &mut two_columns));
+
+        // 126 data codewords fit in the largest symbol
+        assert_eq!((4, 44, 99), size(&"a".repeat(250), &mut MicroPDF417::new()));
+        assert!(MicroPDF417::new()
+            .generateBarcodeLogic(&"a".repeat(300), false)
+            .is_err());
+    }
+
+    #[test]
+    fn test_rows() {
+        let mut encoder = MicroPDF417::new();
+        encoder
+            .generateBarcodeLogic("MicroPDF417", false)
+            .expect("encode");
+        let matrix = encoder.getBarcodeMatrix().as_ref().expect("matrix");
+        let rows = matrix.getMatrix();
+        for row in &rows {
+            // a row address pattern at each side and in the middle, and the stop bar
+            assert_eq!(1, row[0]);
+            assert_eq!(0, row[9]);
+            assert_eq!(1, row[10]);
+            assert_eq!(1, row[row.len() - 1]);
+        }
+        // the rows differ in their row address patterns
+        let left: HashSet<_> = rows.iter().map(|row| row[..10].to_vec()).collect();
+        assert_eq!(rows.len(), left.len());
+    }
+
+    #[test]
+    fn test_error_correction() {
+        let codewords = |variant_index: usize| {
+            let variant = &VARIANTS[variant_index];
+            let capacity = variant.columns * variant.rows - variant.errorCorrectionCodewords;
+            let data: String = (0..capacity)
+                .filter_map(|i| char::from_u32(i * 31 % 929))
+                .collect();
+            let ec = super::pdf_417_error_correction::generateErrorCorrectionCodewords(
+                &data,
+                variant.errorCorrectionCodewords,
+            )
+            .expect("generate");
+            (
+                data.chars()
+                    .chain(ec.chars())
+                    .map(|c| c as u32)
+                    .collect::<Vec<_>>(),
+                variant.errorCorrectionCodewords,
+            )
+        };
+        for variant_index in 0..VARIANTS.len() {
+            let (mut received, k) = codewords(variant_index);
+            received[0] = (received[0] + 1) % 929;
+            assert_eq!(
+                1,
+                error_correction::decode(&mut received, k, &mut []).expect("correct")
+            );
+        }
+    }
+}
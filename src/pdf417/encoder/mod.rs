@@ -16,5 +16,8 @@ pub mod pdf_417_high_level_encoder;
 mod pdf_417;
 pub use pdf_417::*;
 
+mod micro_pdf_417;
+pub use micro_pdf_417::*;
+
 #[cfg(test)]
 pub mod pdf_417_high_level_encoder_test_adapter;
@@ -37,6 +37,7 @@ pub struct PDF417 {
     maxCols: u32,
     maxRows: u32,
     minRows: u32,
+    preferredRatio: f64,
 }
 
 impl Default for PDF417 {
@@ -60,6 +61,7 @@ impl PDF417 {
             maxCols: 30,
             maxRows: 30,
             minRows: 2,
+            preferredRatio: PREFERRED_RATIO,
         }
     }
 
@@ -106,7 +108,7 @@ impl PDF417 {
         }
     }
 
-    fn encodeChar(pattern: u32, len: u32, logic: &mut BarcodeRow) {
+    pub(super) fn encodeChar(pattern: u32, len: u32, logic: &mut BarcodeRow) {
         let mut map = 1 << (len - 1);
         let mut last = (pattern & map) != 0; //Initialize to inverse of first bit
         let mut width = 0;
@@ -283,12 +285,17 @@ impl PDF417 {
                 continue;
             }
 
-            let newRatio: f64 =
-                ((17 * cols + 69) as f64 * DEFAULT_MODULE_WIDTH) / (rows as f64 * HEIGHT);
+            // compact symbols lack the right row indicator and most of the stop pattern
+            let width = if self.compact {
+                17 * cols + 35
+            } else {
+                17 * cols + 69
+            };
+            let newRatio: f64 = (width as f64 * DEFAULT_MODULE_WIDTH) / (rows as f64 * HEIGHT);
 
             // ignore if previous ratio is closer to preferred ratio
             if dimension.is_some()
-                && (newRatio - PREFERRED_RATIO).abs() > (ratio - PREFERRED_RATIO).abs()
+                && (newRatio - self.preferredRatio).abs() > (ratio - self.preferredRatio).abs()
             {
                 continue;
             }
@@ -327,6 +334,14 @@ impl PDF417 {
         self.minRows = minRows;
     }
 
+    /**
+     * @param ratio the preferred ratio of the width of the symbol to its height, 3 by default,
+     *              for modules of 0.357 mm and rows of 2 mm
+     */
+    pub fn setPreferredRatio(&mut self, ratio: f64) {
+        self.preferredRatio = ratio;
+    }
+
     /**
      * @param compaction compaction mode to use
      */
@@ -361,7 +376,7 @@ const STOP_PATTERN: u32 = 0x3fa29;
 /**
  * The codeword table from the Annex A of ISO/IEC 15438:2001(E).
  */
-const CODEWORD_TABLE: [[u32; 929]; 3] = [
+pub(super) const CODEWORD_TABLE: [[u32; 929]; 3] = [
     [
         0x1d5c0, 0x1eaf0, 0x1f57c, 0x1d4e0, 0x1ea78, 0x1f53e, 0x1a8c0, 0x1d470, 0x1a860, 0x15040,
         0x1a830, 0x15020, 0x1adc0, 0x1d6f0, 0x1eb7c, 0x1ace0, 0x1d678, 0x1eb3e, 0x158c0, 0x1ac70,
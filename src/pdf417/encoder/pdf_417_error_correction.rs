@@ -150,7 +150,56 @@ pub fn getRecommendedMinimumErrorCorrectionLevel(n: u32) -> Result<u32> {
  * @return the String representing the error correction codewords
  */
 pub fn generateErrorCorrection(dataCodewords: &str, errorCorrectionLevel: u32) -> Result<String> {
-    let k = getErrorCorrectionCodewordCount(errorCorrectionLevel)?;
+    getErrorCorrectionCodewordCount(errorCorrectionLevel)?;
+    generateErrorCorrectionWithCoefficients(
+        dataCodewords,
+        EC_COEFFICIENTS[errorCorrectionLevel as usize],
+    )
+}
+
+/**
+ * Generates `k` error correction codewords, any number rather than a power of two as the error
+ * correction levels of PDF417 have. MicroPDF417 symbols use 7 to 50.
+ *
+ * @param dataCodewords the data codewords
+ * @param k             the number of error correction codewords
+ * @return the String representing the error correction codewords
+ */
+pub fn generateErrorCorrectionCodewords(dataCodewords: &str, k: u32) -> Result<String> {
+    if k == 0 || k > 512 {
+        return Err(Exceptions::illegal_argument_with(format!(
+            "invalid number of error correction codewords: {k}"
+        )));
+    }
+    generateErrorCorrectionWithCoefficients(dataCodewords, &getErrorCorrectionCoefficients(k))
+}
+
+/**
+ * The coefficients of the generator polynomial (x - 3)(x - 3^2)...(x - 3^k) over GF(929),
+ * lowest order first and without the leading 1, as annex F tabulates them for the levels.
+ */
+fn getErrorCorrectionCoefficients(k: u32) -> Vec<u32> {
+    let mut coefficients = vec![1];
+    let mut root = 1;
+    for _ in 0..k {
+        root = root * 3 % 929;
+        // multiply by (x - root)
+        let mut product = vec![0; coefficients.len() + 1];
+        for (i, coefficient) in coefficients.iter().enumerate() {
+            product[i + 1] = (product[i + 1] + coefficient) % 929;
+            product[i] = (product[i] + 929 - coefficient * root % 929) % 929;
+        }
+        coefficients = product;
+    }
+    coefficients.pop();
+    coefficients
+}
+
+fn generateErrorCorrectionWithCoefficients(
+    dataCodewords: &str,
+    coefficients: &[u32],
+) -> Result<String> {
+    let k = coefficients.len() as u32;
     let mut e = vec![0 as char; k as usize]; //new char[k];
     let sld = dataCodewords.chars().count();
     let cached_data_codewords = dataCodewords.chars().collect::<Vec<_>>();
@@ -165,12 +214,12 @@ pub fn generateErrorCorrection(dataCodewords: &str, errorCorrectionLevel: u32) -
         let mut t3;
         let mut j = k as usize - 1;
         while j >= 1 {
-            t2 = (t1 * EC_COEFFICIENTS[errorCorrectionLevel as usize][j]) % 929;
+            t2 = (t1 * coefficients[j]) % 929;
             t3 = 929 - t2;
             e[j] = char::from_u32((e[j - 1] as u32 + t3) % 929).ok_or(Exceptions::PARSE)?;
             j -= 1;
         }
-        t2 = (t1 * EC_COEFFICIENTS[errorCorrectionLevel as usize][0]) % 929;
+        t2 = (t1 * coefficients[0]) % 929;
         t3 = 929 - t2;
         e[0] = char::from_u32(t3 % 929).ok_or(Exceptions::PARSE)?;
     }
@@ -186,3 +235,42 @@ pub fn generateErrorCorrection(dataCodewords: &str, errorCorrectionLevel: u32) -
     }
     Ok(sb)
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::pdf417::decoder::ec::error_correction;
+
+    use super::{
+        generateErrorCorrectionCodewords, getErrorCorrectionCoefficients, EC_COEFFICIENTS,
+    };
+
+    #[test]
+    fn test_coefficients_match_annex_f() {
+        for (level, table) in EC_COEFFICIENTS.iter().enumerate() {
+            assert_eq!(
+                table.to_vec(),
+                getErrorCorrectionCoefficients(2 << level),
+                "level {level}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_any_number_of_codewords() {
+        let data: String = [10, 900, 53, 2, 928, 0, 417]
+            .iter()
+            .filter_map(|&codeword| char::from_u32(codeword))
+            .collect();
+        for k in [7, 13, 50] {
+            let ec = generateErrorCorrectionCodewords(&data, k).expect("generate");
+            let mut received: Vec<u32> = data.chars().chain(ec.chars()).map(|c| c as u32).collect();
+            received[3] = 100;
+            assert_eq!(
+                1,
+                error_correction::decode(&mut received, k, &mut []).expect("correct")
+            );
+            assert_eq!(2, received[3]);
+        }
+        assert!(generateErrorCorrectionCodewords(&data, 0).is_err());
+    }
+}
@@ -15,9 +15,9 @@
  */
 
 use crate::{
-    common::Result, multi::MultipleBarcodeReader, BarcodeFormat, Binarizer, BinaryBitmap,
-    DecodeHints, Exceptions, ImmutableReader, Point, RXingResult, RXingResultMetadataType,
-    RXingResultMetadataValue, Reader,
+    common::Result, multi::MultipleBarcodeReader, record_witness, BarcodeFormat, Binarizer,
+    BinaryBitmap, DecodeHints, DecodeWarning, Exceptions, ImmutableReader, Point, RXingResult,
+    RXingResultMetadataType, RXingResultMetadataValue, Reader, WitnessRecording,
 };
 
 use super::{
@@ -50,7 +50,9 @@ impl Reader for PDF417Reader {
         image: &mut BinaryBitmap<B>,
         hints: &DecodeHints,
     ) -> Result<crate::RXingResult> {
-        self.internal_decode_with_hints(image, hints)
+        let mut recording = WitnessRecording::new(hints);
+        self.internal_decode_with_hints(image, hints, &mut recording)
+            .map(|result| record_witness(hints, image, result, &recording))
     }
 }
 
@@ -60,7 +62,9 @@ impl ImmutableReader for PDF417Reader {
         image: &mut BinaryBitmap<B>,
         hints: &DecodeHints,
     ) -> Result<RXingResult> {
-        self.internal_decode_with_hints(image, hints)
+        let mut recording = WitnessRecording::new(hints);
+        self.internal_decode_with_hints(image, hints, &mut recording)
+            .map(|result| record_witness(hints, image, result, &recording))
     }
 }
 
@@ -77,7 +81,7 @@ impl MultipleBarcodeReader for PDF417Reader {
         image: &mut BinaryBitmap<B>,
         hints: &DecodeHints,
     ) -> Result<Vec<RXingResult>> {
-        Self::decode(image, hints, true)
+        Self::decode(image, hints, true, &mut WitnessRecording::new(hints))
     }
 }
 
@@ -90,13 +94,16 @@ impl PDF417Reader {
         image: &mut BinaryBitmap<B>,
         hints: &DecodeHints,
         multiple: bool,
+        recording: &mut WitnessRecording,
     ) -> Result<Vec<RXingResult>> {
         let mut results = Vec::new();
+        recording.binarization(image);
         let detectorRXingResult = pdf_417_detector::detect_with_hints(image, hints, multiple)?;
 
         for points in detectorRXingResult.getPoints() {
-            let points_filtered = points.iter().flatten().copied().collect();
+            let points_filtered: Vec<Point> = points.iter().flatten().copied().collect();
             // let points_filtered = points.iter().filter_map(|e| *e).collect();
+            recording.detection(BarcodeFormat::PDF_417, &points_filtered, None);
 
             let decoderRXingResult = pdf_417_scanning_decoder::decode(
                 detectorRXingResult.getBits(),
@@ -107,6 +114,7 @@ impl PDF417Reader {
                 Self::getMinCodewordWidth(points),
                 Self::getMaxCodewordWidth(points),
             )?;
+            recording.codewords(decoderRXingResult.getCodewords());
 
             let mut result = RXingResult::new(
                 decoderRXingResult.getText(),
@@ -114,6 +122,9 @@ impl PDF417Reader {
                 points_filtered,
                 BarcodeFormat::PDF_417,
             );
+            if let Some(content) = decoderRXingResult.getContent() {
+                result.setContent(content.clone());
+            }
 
             result.putMetadata(
                 RXingResultMetadataType::ERROR_CORRECTION_LEVEL,
@@ -121,15 +132,21 @@ impl PDF417Reader {
                     decoderRXingResult.getECLevel().to_owned(),
                 ),
             );
+            result.putErrorCorrectionMetadata(
+                decoderRXingResult.getErrorsCorrected(),
+                decoderRXingResult.getErasures(),
+            );
 
             if let Some(pdf417RXingResultMetadata) = decoderRXingResult.getOther() {
                 if pdf417RXingResultMetadata.is::<PDF417RXingResultMetadata>() {
-                    let data = RXingResultMetadataValue::Pdf417ExtraMetadata(
-                        pdf417RXingResultMetadata
-                            .clone()
-                            .downcast::<PDF417RXingResultMetadata>()
-                            .map_err(|_| Exceptions::ILLEGAL_STATE)?,
-                    );
+                    let metadata = pdf417RXingResultMetadata
+                        .clone()
+                        .downcast::<PDF417RXingResultMetadata>()
+                        .map_err(|_| Exceptions::ILLEGAL_STATE)?;
+                    if metadata.usesHeavyCorrection() {
+                        result.addWarning(DecodeWarning::HeavyErrorCorrection);
+                    }
+                    let data = RXingResultMetadataValue::Pdf417ExtraMetadata(metadata);
                     result.putMetadata(RXingResultMetadataType::PDF417_EXTRA_METADATA, data);
                 }
             }
@@ -150,6 +167,7 @@ impl PDF417Reader {
                     decoderRXingResult.getSymbologyModifier()
                 )),
             );
+            recording.decoded_bits(&mut result);
             results.push(result);
         }
         Ok(results)
@@ -199,11 +217,82 @@ impl PDF417Reader {
         &self,
         image: &mut BinaryBitmap<B>,
         hints: &DecodeHints,
+        recording: &mut WitnessRecording,
     ) -> Result<RXingResult> {
-        let result = Self::decode(image, hints, false)?;
+        let result = Self::decode(image, hints, false, recording)?;
         if result.is_empty() {
             return Err(Exceptions::NOT_FOUND);
         }
         Ok(result[0].clone())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::{
+        helpers,
+        pdf417::{encoder::Dimensions, PDF417Writer},
+        BarcodeFormat, DecodeHints, EncodeHints, RXingResultMetadataType, RXingResultMetadataValue,
+        Writer,
+    };
+
+    #[test]
+    fn testStructuralMetadata() {
+        let hints = EncodeHints {
+            Pdf417Dimensions: Some(Dimensions::new(4, 4, 10, 10)),
+            ErrorCorrection: Some("2".to_owned()),
+            ..Default::default()
+        };
+        let matrix = PDF417Writer
+            .encode_with_hints(
+                "PDF417 symbol health",
+                &BarcodeFormat::PDF_417,
+                0,
+                0,
+                &hints,
+            )
+            .expect("encode");
+        let luma = matrix.to_luma8();
+        let mut hints = DecodeHints {
+            PossibleFormats: Some(HashSet::from([BarcodeFormat::PDF_417])),
+            PureBarcode: Some(true),
+            ..Default::default()
+        };
+        let result = helpers::detect_in_luma_with_hints(
+            luma,
+            matrix.getWidth(),
+            matrix.getHeight(),
+            Some(BarcodeFormat::PDF_417),
+            &mut hints,
+        )
+        .expect("decode");
+        assert_eq!("PDF417 symbol health", result.getText());
+
+        let Some(RXingResultMetadataValue::Pdf417ExtraMetadata(metadata)) = result
+            .getRXingResultMetadata()
+            .get(&RXingResultMetadataType::PDF417_EXTRA_METADATA)
+        else {
+            panic!("no PDF417 metadata");
+        };
+        assert_eq!(10, metadata.getRowCount());
+        assert_eq!(4, metadata.getColumnCount());
+        assert_eq!(2, metadata.getErrorCorrectionLevel());
+        assert_eq!(8, metadata.getErrorCorrectionCodewords());
+        assert_eq!(0, metadata.getErasures());
+        assert_eq!(0, metadata.getErrorsCorrected());
+        assert_eq!(
+            Some(&RXingResultMetadataValue::ErrorsCorrected(0)),
+            result
+                .getRXingResultMetadata()
+                .get(&RXingResultMetadataType::ERRORS_CORRECTED)
+        );
+        assert_eq!(
+            Some(&RXingResultMetadataValue::ErasuresCorrected(0)),
+            result
+                .getRXingResultMetadata()
+                .get(&RXingResultMetadataType::ERASURES_CORRECTED)
+        );
+    }
+}
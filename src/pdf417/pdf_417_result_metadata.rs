@@ -21,7 +21,7 @@ use serde::{Deserialize, Serialize};
  * @author Guenther Grau
  */
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct PDF417RXingResultMetadata {
     segmentIndex: usize,
     fileId: String,
@@ -34,6 +34,11 @@ pub struct PDF417RXingResultMetadata {
     timestamp: i64,
     checksum: i32,
     optionalData: Vec<u32>,
+    rowCount: u32,
+    columnCount: usize,
+    errorCorrectionLevel: u32,
+    erasures: usize,
+    errorsCorrected: usize,
 }
 
 impl Default for PDF417RXingResultMetadata {
@@ -50,6 +55,11 @@ impl Default for PDF417RXingResultMetadata {
             timestamp: -1,
             checksum: -1,
             optionalData: Default::default(),
+            rowCount: 0,
+            columnCount: 0,
+            errorCorrectionLevel: 0,
+            erasures: 0,
+            errorsCorrected: 0,
         }
     }
 }
@@ -188,4 +198,90 @@ impl PDF417RXingResultMetadata {
     pub fn setTimestamp(&mut self, timestamp: i64) {
         self.timestamp = timestamp;
     }
+
+    /**
+     * The number of rows of the symbol, 3 to 90
+     *
+     * @return row count, 0 if not set
+     */
+    pub fn getRowCount(&self) -> u32 {
+        self.rowCount
+    }
+
+    pub fn setRowCount(&mut self, rowCount: u32) {
+        self.rowCount = rowCount;
+    }
+
+    /**
+     * The number of data columns of the symbol, 1 to 30, without the start and stop patterns
+     * and the row indicators
+     *
+     * @return column count, 0 if not set
+     */
+    pub fn getColumnCount(&self) -> usize {
+        self.columnCount
+    }
+
+    pub fn setColumnCount(&mut self, columnCount: usize) {
+        self.columnCount = columnCount;
+    }
+
+    /**
+     * The error correction level of the symbol, 0 to 8
+     *
+     * @return error correction level
+     */
+    pub fn getErrorCorrectionLevel(&self) -> u32 {
+        self.errorCorrectionLevel
+    }
+
+    pub fn setErrorCorrectionLevel(&mut self, errorCorrectionLevel: u32) {
+        self.errorCorrectionLevel = errorCorrectionLevel;
+    }
+
+    /**
+     * The number of error correction codewords of the symbol, 2 to the power of one more than
+     * its error correction level. Up to half of them can correct errors, each erasure takes one.
+     */
+    pub fn getErrorCorrectionCodewords(&self) -> u32 {
+        1 << (self.errorCorrectionLevel + 1)
+    }
+
+    /**
+     * Whether error correction used more than half of what it could correct. Two error
+     * correction codewords are reserved for detection, of the others an error takes two and an
+     * erasure one.
+     */
+    pub fn usesHeavyCorrection(&self) -> bool {
+        let errors = self.errorsCorrected.saturating_sub(self.erasures);
+        let capacity = (self.getErrorCorrectionCodewords() as usize).saturating_sub(2);
+        (2 * errors + self.erasures) * 2 > capacity
+    }
+
+    /**
+     * The number of codewords of the symbol that could not be read at all
+     *
+     * @return erased codewords
+     */
+    pub fn getErasures(&self) -> usize {
+        self.erasures
+    }
+
+    pub fn setErasures(&mut self, erasures: usize) {
+        self.erasures = erasures;
+    }
+
+    /**
+     * The number of codewords of the symbol that error correction changed, erased ones
+     * included
+     *
+     * @return corrected codewords
+     */
+    pub fn getErrorsCorrected(&self) -> usize {
+        self.errorsCorrected
+    }
+
+    pub fn setErrorsCorrected(&mut self, errorsCorrected: usize) {
+        self.errorsCorrected = errorsCorrected;
+    }
 }
@@ -19,7 +19,7 @@ use crate::{
     BarcodeFormat, EncodeHints, Exceptions, Writer,
 };
 
-use super::encoder::PDF417;
+use super::encoder::{BarcodeMatrix, MicroPDF417, MICRO_PDF417_ROW_HEIGHT, PDF417};
 
 /**
  * default white space (margin) around the code
@@ -29,7 +29,7 @@ const WHITE_SPACE: u32 = 30;
 /**
  * default error correction level
  */
-const DEFAULT_ERROR_CORRECTION_LEVEL: u32 = 2;
+pub(crate) const DEFAULT_ERROR_CORRECTION_LEVEL: u32 = 2;
 
 /**
  * @author Jacob Haynes
@@ -63,8 +63,48 @@ impl Writer for PDF417Writer {
             )));
         }
 
-        let mut encoder = PDF417::new();
         let mut margin = WHITE_SPACE;
+        if let Some(m1) = &hints.Margin {
+            if let Ok(m) = m1.parse::<u32>() {
+                margin = m;
+            }
+        }
+        if matches!(hints.Pdf417Micro, Some(true)) {
+            let (mut encoder, autoECI) = Self::microEncoderFromHints(hints)?;
+            encoder.generateBarcodeLogic(contents, autoECI)?;
+            return Self::bitMatrixFromBarcodeMatrix(
+                encoder
+                    .getBarcodeMatrix()
+                    .as_ref()
+                    .ok_or(Exceptions::ILLEGAL_STATE)?,
+                width as u32,
+                height as u32,
+                margin,
+                MICRO_PDF417_ROW_HEIGHT as usize,
+            );
+        }
+
+        let (mut encoder, errorCorrectionLevel, autoECI) = Self::encoderFromHints(hints)?;
+
+        Self::bitMatrixFromEncoder(
+            &mut encoder,
+            contents,
+            errorCorrectionLevel,
+            width as u32,
+            height as u32,
+            margin,
+            autoECI,
+        )
+    }
+}
+
+impl PDF417Writer {
+    /**
+     * Creates an encoder configured from the hints, returning it together with the
+     * error correction level and whether to insert ECIs automatically
+     */
+    pub(crate) fn encoderFromHints(hints: &EncodeHints) -> Result<(PDF417, u32, bool)> {
+        let mut encoder = PDF417::new();
         let mut errorCorrectionLevel = DEFAULT_ERROR_CORRECTION_LEVEL;
         let mut autoECI = false;
 
@@ -85,11 +125,6 @@ impl Writer for PDF417Writer {
                 dimensions.getMinRows() as u32,
             );
         }
-        if let Some(m1) = &hints.Margin {
-            if let Ok(m) = m1.parse::<u32>() {
-                margin = m;
-            }
-        }
         if let Some(ec) = &hints.ErrorCorrection {
             if let Ok(ec_parsed) = ec.parse::<u32>() {
                 errorCorrectionLevel = ec_parsed;
@@ -103,21 +138,47 @@ impl Writer for PDF417Writer {
                 autoECI = auto_eci_parsed;
             }
         }
-        // }
+        if let Some(ratio) = hints.Pdf417AspectRatio {
+            encoder.setPreferredRatio(ratio as f64);
+        }
 
-        Self::bitMatrixFromEncoder(
-            &mut encoder,
-            contents,
-            errorCorrectionLevel,
-            width as u32,
-            height as u32,
-            margin,
-            autoECI,
-        )
+        Ok((encoder, errorCorrectionLevel, autoECI))
+    }
+
+    /**
+     * Creates a MicroPDF417 encoder configured from the hints, returning it together with
+     * whether to insert ECIs automatically
+     */
+    pub(crate) fn microEncoderFromHints(hints: &EncodeHints) -> Result<(MicroPDF417, bool)> {
+        let mut encoder = MicroPDF417::new();
+        let mut autoECI = false;
+
+        if let Some(compaction) = &hints.Pdf417Compaction {
+            encoder.setCompaction(compaction.try_into()?);
+        }
+        if let Some(dimensions) = &hints.Pdf417Dimensions {
+            encoder.setDimensions(
+                dimensions.getMaxCols() as u32,
+                dimensions.getMinCols() as u32,
+                dimensions.getMaxRows() as u32,
+                dimensions.getMinRows() as u32,
+            );
+        }
+        if let Some(cs) = &hints.CharacterSet {
+            encoder.setEncoding(CharacterSet::get_character_set_by_name(cs));
+        }
+        if let Some(auto_eci_str) = &hints.Pdf417AutoEci {
+            if let Ok(auto_eci_parsed) = auto_eci_str.parse::<bool>() {
+                autoECI = auto_eci_parsed;
+            }
+        }
+        if let Some(ratio) = hints.Pdf417AspectRatio {
+            encoder.setPreferredRatio(ratio as f64);
+        }
+
+        Ok((encoder, autoECI))
     }
-}
 
-impl PDF417Writer {
     /**
      * Takes encoder, accounts for width/height, and retrieves bit matrix
      */
@@ -132,12 +193,30 @@ impl PDF417Writer {
     ) -> Result<BitMatrix> {
         encoder.generateBarcodeLogicWithAutoECI(contents, errorCorrectionLevel, autoECI)?;
 
-        let aspectRatio = 4;
-        let mut originalScale = encoder
-            .getBarcodeMatrix()
-            .as_ref()
-            .ok_or(Exceptions::ILLEGAL_STATE)?
-            .getScaledMatrix(1, aspectRatio);
+        Self::bitMatrixFromBarcodeMatrix(
+            encoder
+                .getBarcodeMatrix()
+                .as_ref()
+                .ok_or(Exceptions::ILLEGAL_STATE)?,
+            width,
+            height,
+            margin,
+            4,
+        )
+    }
+
+    /**
+     * Scales the matrix to fit width/height, with rows aspectRatio modules high, and retrieves
+     * bit matrix
+     */
+    fn bitMatrixFromBarcodeMatrix(
+        barcodeMatrix: &BarcodeMatrix,
+        width: u32,
+        height: u32,
+        margin: u32,
+        aspectRatio: usize,
+    ) -> Result<BitMatrix> {
+        let mut originalScale = barcodeMatrix.getScaledMatrix(1, aspectRatio);
         let mut rotated = false;
         if (height > width) != (originalScale[0].len() < originalScale.len()) {
             originalScale = Self::rotateArray(&originalScale);
@@ -149,11 +228,7 @@ impl PDF417Writer {
         let scale = scaleX.min(scaleY);
 
         if scale > 1 {
-            let mut scaledMatrix = encoder
-                .getBarcodeMatrix()
-                .as_ref()
-                .ok_or(Exceptions::ILLEGAL_STATE)?
-                .getScaledMatrix(scale, scale * aspectRatio);
+            let mut scaledMatrix = barcodeMatrix.getScaledMatrix(scale, scale * aspectRatio);
             if rotated {
                 scaledMatrix = Self::rotateArray(&scaledMatrix);
             }
@@ -260,4 +335,55 @@ X X X X X X X X   X   X   X       X X X X X   X   X X X X   X X     X
 ";
         assert_eq!(expected, matrix.to_string());
     }
+
+    #[test]
+    fn testMicroPDF417() {
+        let hints = EncodeHints::default()
+            .with(EncodeHintValue::Margin(0.to_string()))
+            .with(EncodeHintValue::Pdf417Micro(true));
+        let writer = PDF417Writer::new();
+        let matrix = writer
+            .encode_with_hints("1234", &BarcodeFormat::PDF_417, 0, 0, &hints)
+            .expect("encode");
+        // 4 columns of 4 rows, 2 modules high
+        assert_eq!((99, 8), (matrix.getWidth(), matrix.getHeight()));
+
+        let scaled = writer
+            .encode_with_hints("1234", &BarcodeFormat::PDF_417, 198, 16, &hints)
+            .expect("encode");
+        assert_eq!((198, 16), (scaled.getWidth(), scaled.getHeight()));
+    }
+
+    #[test]
+    fn testAspectRatio() {
+        let columns = |ratio: f32| {
+            let hints = EncodeHints::default()
+                .with(EncodeHintValue::Margin(0.to_string()))
+                .with(EncodeHintValue::Pdf417AspectRatio(ratio));
+            let matrix = PDF417Writer::new()
+                .encode_with_hints(&"PDF417".repeat(20), &BarcodeFormat::PDF_417, 0, 0, &hints)
+                .expect("encode");
+            (matrix.getWidth() - 69) / 17
+        };
+        assert!(columns(1.0) < columns(3.0));
+        assert!(columns(3.0) < columns(10.0));
+    }
+
+    #[test]
+    fn testCompactAspectRatio() {
+        let ratio = |compact: bool| {
+            let hints = EncodeHints::default()
+                .with(EncodeHintValue::Margin(0.to_string()))
+                .with(EncodeHintValue::Pdf417Compact(compact.to_string()));
+            let matrix = PDF417Writer::new()
+                .encode_with_hints(&"PDF417".repeat(20), &BarcodeFormat::PDF_417, 0, 0, &hints)
+                .expect("encode");
+            // modules of 0.357 mm and rows of 4 modules, 2 mm
+            let ratio = matrix.getWidth() as f64 * 0.357 / (matrix.getHeight() as f64 / 4.0 * 2.0);
+            (ratio - 3.0).abs()
+        };
+        // compact symbols are measured without the columns they lack, 0.46 off with them
+        assert!(ratio(true) < 0.4);
+        assert!(ratio(false) < 0.1);
+    }
 }
@@ -0,0 +1,2068 @@
+/*
+ * Witness Data for Zero-Knowledge Proofs
+ *
+ * This module provides a structure to capture intermediate processing data
+ * from barcode decoding for use in zero-knowledge proof generation.
+ */
+
+use crate::common::{
+    BitMatrix, CodewordWitness, DefaultGridSampler, DetectedPattern, FixedPointGridSampler,
+    FixedPointTransform, GridSampler, MerkleHasher, MerkleTree, Result, SamplingGrid,
+};
+use crate::{
+    oned::ScanlineWitness, BarcodeFormat, Exceptions, Point, PointU, RXingResult,
+    RXingResultMetadataType, RXingResultMetadataValue, RedactionMask, WitnessProvenance,
+};
+
+#[cfg(feature = "serde")]
+use crate::witness_compression::{matrix_compression, with_matrix_compression, RunLengthBitMatrix};
+#[cfg(feature = "serde")]
+use crate::witness_schema::{migrate_json, SchemaVersion};
+#[cfg(feature = "serde")]
+use crate::MatrixCompression;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// The stages of the decode pipeline a `PipelineWitness` can hold intermediates of, in order
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PipelineStage {
+    Luminance,
+    Binarization,
+    Detection,
+    Scanline,
+    Sampling,
+    Decoding,
+    TextAssembly,
+}
+
+/**
+ * Holds the intermediates of each stage of a decode for zero-knowledge proof generation.
+ *
+ * Every stage is optional, so a witness can be built up as far as the pipeline got, and every
+ * stage can be serialized on its own.
+ *
+ * # Fields
+ * * `luminance` - The grayscale image that was decoded
+ * * `binarization` - The binarized image
+ * * `detection` - Where the symbol was found
+ * * `scanline` - The row a 1D symbol was read from and the patterns matched in it
+ * * `sampling` - How the modules of the symbol were sampled from the binarized image
+ * * `decoding` - The codewords read from the modules and their error correction
+ * * `text_assembly` - The bytes and text assembled from the corrected codewords
+ * * `provenance` - The crate version, binarizer, hints and timings that produced the stages
+ */
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+#[derive(Clone, Debug, Default)]
+pub struct PipelineWitness {
+    #[cfg(feature = "serde")]
+    #[serde(rename = "version", default)]
+    schema: SchemaVersion,
+
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub luminance: Option<LuminanceWitness>,
+
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub binarization: Option<BinarizationWitness>,
+
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub detection: Option<DetectionWitness>,
+
+    /// Only present for formats whose reader reports its scanline (Code 128, EAN and UPC)
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub scanline: Option<ScanlineWitness>,
+
+    /// Only present for formats whose detector reports a sampling grid (QR, Data Matrix and Aztec)
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub sampling: Option<SamplingWitness>,
+
+    /// Only present for formats whose decoder reports its codewords (QR, Data Matrix and Aztec)
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub decoding: Option<CodewordWitness>,
+
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub text_assembly: Option<TextAssemblyWitness>,
+
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub provenance: Option<WitnessProvenance>,
+}
+
+impl PipelineWitness {
+    /**
+     * Creates a PipelineWitness with no stages recorded.
+     */
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_luminance(mut self, luminance: LuminanceWitness) -> Self {
+        self.luminance = Some(luminance);
+        self
+    }
+
+    pub fn with_binarization(mut self, binarization: BinarizationWitness) -> Self {
+        self.binarization = Some(binarization);
+        self
+    }
+
+    pub fn with_detection(mut self, detection: DetectionWitness) -> Self {
+        self.detection = Some(detection);
+        self
+    }
+
+    pub fn with_scanline(mut self, scanline: ScanlineWitness) -> Self {
+        self.scanline = Some(scanline);
+        self
+    }
+
+    pub fn with_sampling(mut self, sampling: SamplingWitness) -> Self {
+        self.sampling = Some(sampling);
+        self
+    }
+
+    pub fn with_decoding(mut self, decoding: CodewordWitness) -> Self {
+        self.decoding = Some(decoding);
+        self
+    }
+
+    pub fn with_text_assembly(mut self, text_assembly: TextAssemblyWitness) -> Self {
+        self.text_assembly = Some(text_assembly);
+        self
+    }
+
+    pub fn with_provenance(mut self, provenance: WitnessProvenance) -> Self {
+        self.provenance = Some(provenance);
+        self
+    }
+
+    pub fn luminance(&self) -> Option<&LuminanceWitness> {
+        self.luminance.as_ref()
+    }
+
+    pub fn binarization(&self) -> Option<&BinarizationWitness> {
+        self.binarization.as_ref()
+    }
+
+    pub fn detection(&self) -> Option<&DetectionWitness> {
+        self.detection.as_ref()
+    }
+
+    pub fn scanline(&self) -> Option<&ScanlineWitness> {
+        self.scanline.as_ref()
+    }
+
+    pub fn sampling(&self) -> Option<&SamplingWitness> {
+        self.sampling.as_ref()
+    }
+
+    pub fn decoding(&self) -> Option<&CodewordWitness> {
+        self.decoding.as_ref()
+    }
+
+    pub fn text_assembly(&self) -> Option<&TextAssemblyWitness> {
+        self.text_assembly.as_ref()
+    }
+
+    pub fn provenance(&self) -> Option<&WitnessProvenance> {
+        self.provenance.as_ref()
+    }
+
+    /**
+     * Returns the stages this witness holds intermediates of, in pipeline order.
+     */
+    pub fn stages(&self) -> Vec<PipelineStage> {
+        [
+            (PipelineStage::Luminance, self.luminance.is_some()),
+            (PipelineStage::Binarization, self.binarization.is_some()),
+            (PipelineStage::Detection, self.detection.is_some()),
+            (PipelineStage::Scanline, self.scanline.is_some()),
+            (PipelineStage::Sampling, self.sampling.is_some()),
+            (PipelineStage::Decoding, self.decoding.is_some()),
+            (PipelineStage::TextAssembly, self.text_assembly.is_some()),
+        ]
+        .into_iter()
+        .filter_map(|(stage, present)| present.then_some(stage))
+        .collect()
+    }
+
+    /**
+     * Builds a Merkle tree over the rows of the grayscale image, see
+     * `LuminanceWitness::merkle_tree`.
+     *
+     * # Returns
+     * The tree, or None if the luminance stage was not recorded
+     */
+    pub fn luminance_merkle_tree<H: MerkleHasher>(
+        &self,
+        hasher: &mut H,
+    ) -> Option<MerkleTree<H::Digest>> {
+        self.luminance
+            .as_ref()
+            .map(|luminance| luminance.merkle_tree(hasher))
+    }
+
+    /**
+     * Builds a Merkle tree over the rows of the binarized image, see
+     * `BinarizationWitness::merkle_tree`.
+     *
+     * # Returns
+     * The tree, or None if the binarization stage was not recorded
+     */
+    pub fn binarization_merkle_tree<H: MerkleHasher>(
+        &self,
+        hasher: &mut H,
+    ) -> Option<MerkleTree<H::Digest>> {
+        self.binarization
+            .as_ref()
+            .map(|binarization| binarization.merkle_tree(hasher))
+    }
+
+    /**
+     * Commits to the grayscale image with the SHA-256 root of the tree over its rows, see
+     * `luminance_merkle_tree`, so a proof can take the root as its public input instead of
+     * the pixels.
+     *
+     * # Returns
+     * The root, or None if the luminance stage was not recorded
+     */
+    #[cfg(feature = "sha256")]
+    pub fn image_commitment(&self) -> Option<[u8; 32]> {
+        self.image_commitment_with(&mut crate::common::Sha256MerkleHasher)
+    }
+
+    /**
+     * Commits to the grayscale image as `image_commitment` does, with the root of a tree
+     * hashed by `hasher`, such as `PoseidonMerkleHasher` for a circuit over BN254.
+     */
+    pub fn image_commitment_with<H: MerkleHasher>(&self, hasher: &mut H) -> Option<H::Digest> {
+        self.luminance_merkle_tree(hasher)
+            .map(|tree| tree.root().clone())
+    }
+
+    /**
+     * Commits to the binarized image with the SHA-256 root of the tree over its packed rows,
+     * see `binarization_merkle_tree`.
+     *
+     * # Returns
+     * The root, or None if the binarization stage was not recorded
+     */
+    #[cfg(feature = "sha256")]
+    pub fn binarization_commitment(&self) -> Option<[u8; 32]> {
+        self.binarization_commitment_with(&mut crate::common::Sha256MerkleHasher)
+    }
+
+    /**
+     * Commits to the binarized image as `binarization_commitment` does, with the root of a
+     * tree hashed by `hasher`.
+     */
+    pub fn binarization_commitment_with<H: MerkleHasher>(
+        &self,
+        hasher: &mut H,
+    ) -> Option<H::Digest> {
+        self.binarization_merkle_tree(hasher)
+            .map(|tree| tree.root().clone())
+    }
+
+    /**
+     * Samples the recorded grid again in fixed point, see `SamplingWitness::new_fixed_point`,
+     * replacing the sampling stage.
+     *
+     * # Returns
+     * The witness, unchanged if the binarization or sampling stage was not recorded
+     */
+    pub fn with_fixed_point_sampling(mut self, frac_bits: u32) -> Result<Self> {
+        if let (Some(binarization), Some(sampling)) = (&self.binarization, &self.sampling) {
+            self.sampling = Some(SamplingWitness::new_fixed_point(
+                sampling.grid.clone(),
+                &binarization.binarized_image,
+                frac_bits,
+            )?);
+        }
+        Ok(self)
+    }
+
+    /**
+     * The region of the frame around the symbol: the bounding box of the detected points and
+     * patterns and of the corners of the sampling grid, extended by `margin` pixels on every
+     * side and clipped to the recorded grayscale (or else binarized) image.
+     *
+     * # Returns
+     * The region, or None if the detection stage or both image stages were not recorded
+     */
+    pub fn symbol_region(&self, margin: u32) -> Option<CropRegion> {
+        let detection = self.detection.as_ref()?;
+        let frame = match (&self.luminance, &self.binarization) {
+            (Some(luminance), _) => luminance.extent(),
+            (None, Some(binarization)) => binarization.extent(),
+            (None, None) => return None,
+        };
+
+        let points = symbol_points(detection, self.sampling.as_ref());
+        CropRegion::around(&points, margin, frame)
+    }
+
+    /**
+     * Crops the grayscale and binarized images to the region around the symbol, see
+     * `symbol_region`, which for a small symbol in a large frame is a fraction of the size.
+     * Points and grids stay in the coordinates of the full frame, the cropped stages record
+     * where in the frame they start.
+     *
+     * # Returns
+     * The witness, unchanged if the region cannot be determined
+     */
+    pub fn crop_to_symbol(mut self, margin: u32) -> Result<Self> {
+        if let Some(region) = self.symbol_region(margin) {
+            if let Some(luminance) = &self.luminance {
+                self.luminance = Some(luminance.crop(region)?);
+            }
+            if let Some(binarization) = &self.binarization {
+                self.binarization = Some(binarization.crop(region)?);
+            }
+        }
+        Ok(self)
+    }
+
+    /**
+     * Saves this PipelineWitness to a JSON file.
+     *
+     * # Arguments
+     * * `path` - The file path to write to
+     *
+     * # Returns
+     * Result indicating success or error
+     */
+    #[cfg(feature = "serde")]
+    pub fn save_to_json(&self, path: &str) -> Result<(), String> {
+        self.save_to_json_with_compression(path, MatrixCompression::None)
+    }
+
+    /**
+     * Saves this PipelineWitness to a JSON file, with its bit matrices written with
+     * `compression`.
+     *
+     * # Arguments
+     * * `path` - The file path to write to
+     * * `compression` - How to write the bit matrices
+     */
+    #[cfg(feature = "serde")]
+    pub fn save_to_json_with_compression(
+        &self,
+        path: &str,
+        compression: MatrixCompression,
+    ) -> Result<(), String> {
+        use std::fs::File;
+        use std::io::Write;
+
+        let json = with_matrix_compression(compression, || serde_json::to_string_pretty(self))
+            .map_err(|e| format!("Failed to serialize to JSON: {}", e))?;
+
+        let mut file =
+            File::create(path).map_err(|e| format!("Failed to create file '{}': {}", path, e))?;
+
+        file.write_all(json.as_bytes())
+            .map_err(|e| format!("Failed to write to file '{}': {}", path, e))?;
+
+        Ok(())
+    }
+
+    /**
+     * Writes this PipelineWitness as CBOR.
+     *
+     * Unlike JSON, which has one boolean per pixel, bit matrices are written as a map of
+     * `width`, `height` and `bits`, a byte string with the pixels packed row-major, eight to a
+     * byte, most significant bit first. The grayscale image is written as a byte string too.
+     *
+     * # Arguments
+     * * `writer` - Where to write the CBOR to
+     */
+    #[cfg(feature = "cbor")]
+    pub fn write_cbor<W: std::io::Write>(&self, writer: W) -> Result<(), String> {
+        self.write_cbor_with_compression(writer, MatrixCompression::None)
+    }
+
+    /**
+     * Writes this PipelineWitness as CBOR, with its bit matrices written with `compression`.
+     *
+     * # Arguments
+     * * `writer` - Where to write the CBOR to
+     * * `compression` - How to write the bit matrices
+     */
+    #[cfg(feature = "cbor")]
+    pub fn write_cbor_with_compression<W: std::io::Write>(
+        &self,
+        writer: W,
+        compression: MatrixCompression,
+    ) -> Result<(), String> {
+        with_matrix_compression(compression, || ciborium::into_writer(self, writer))
+            .map_err(|e| format!("Failed to write CBOR: {}", e))
+    }
+
+    /**
+     * Serializes this PipelineWitness to CBOR, see `write_cbor`.
+     */
+    #[cfg(feature = "cbor")]
+    pub fn to_cbor(&self) -> Result<Vec<u8>, String> {
+        let mut cbor = Vec::new();
+        self.write_cbor(&mut cbor)?;
+        Ok(cbor)
+    }
+
+    /**
+     * Saves this PipelineWitness to a CBOR file, see `write_cbor`.
+     *
+     * # Arguments
+     * * `path` - The file path to write to
+     */
+    #[cfg(feature = "cbor")]
+    pub fn save_to_cbor(&self, path: &str) -> Result<(), String> {
+        let file = std::fs::File::create(path)
+            .map_err(|e| format!("Failed to create file '{}': {}", path, e))?;
+
+        self.write_cbor(std::io::BufWriter::new(file))
+    }
+
+    /**
+     * Writes this PipelineWitness as bincode, see `WITNESS_SCHEMA_VERSION` for the layout.
+     *
+     * Like CBOR, bit matrices are written packed eight pixels to a byte and the grayscale image
+     * as bytes, but without the keys, so the witness is smaller and faster to load.
+     *
+     * # Arguments
+     * * `writer` - Where to write the bincode to
+     */
+    #[cfg(feature = "bincode")]
+    pub fn write_bincode<W: std::io::Write>(&self, mut writer: W) -> Result<(), String> {
+        bincode::encode_into_std_write(self, &mut writer, bincode::config::standard())
+            .map(|_| ())
+            .map_err(|e| format!("Failed to write bincode: {}", e))
+    }
+
+    /**
+     * Serializes this PipelineWitness to bincode, see `write_bincode`.
+     */
+    #[cfg(feature = "bincode")]
+    pub fn to_bincode(&self) -> Result<Vec<u8>, String> {
+        bincode::encode_to_vec(self, bincode::config::standard())
+            .map_err(|e| format!("Failed to write bincode: {}", e))
+    }
+
+    /**
+     * Saves this PipelineWitness to a bincode file, see `write_bincode`.
+     *
+     * # Arguments
+     * * `path` - The file path to write to
+     */
+    #[cfg(feature = "bincode")]
+    pub fn save_to_bincode(&self, path: &str) -> Result<(), String> {
+        let file = std::fs::File::create(path)
+            .map_err(|e| format!("Failed to create file '{}': {}", path, e))?;
+
+        self.write_bincode(std::io::BufWriter::new(file))
+    }
+
+    /**
+     * Parses a PipelineWitness from JSON, decompressing its bit matrices from whichever form
+     * they were written in. Witnesses written in an older schema are migrated, those written
+     * in a newer one are rejected, see `WITNESS_SCHEMA_VERSION`.
+     *
+     * # Arguments
+     * * `json` - The JSON to parse
+     */
+    #[cfg(feature = "serde")]
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let parse_error = |e: serde_json::Error| format!("Failed to parse JSON: {}", e);
+        let value = serde_json::from_str(json).map_err(parse_error)?;
+
+        serde_json::from_value(migrate_json(value)?).map_err(parse_error)
+    }
+
+    /**
+     * Loads a PipelineWitness from a JSON file, see `from_json`.
+     *
+     * # Arguments
+     * * `path` - The file path to read from
+     */
+    #[cfg(feature = "serde")]
+    pub fn load_from_json(path: &str) -> Result<Self, String> {
+        let json = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read file '{}': {}", path, e))?;
+
+        Self::from_json(&json)
+    }
+
+    /**
+     * Reads a PipelineWitness from CBOR, decompressing its bit matrices from whichever form
+     * they were written in. Witnesses written in a newer schema are rejected.
+     *
+     * # Arguments
+     * * `reader` - Where to read the CBOR from
+     */
+    #[cfg(feature = "cbor")]
+    pub fn read_cbor<R: std::io::Read>(reader: R) -> Result<Self, String> {
+        ciborium::from_reader(reader).map_err(|e| format!("Failed to read CBOR: {}", e))
+    }
+
+    /**
+     * Loads a PipelineWitness from a CBOR file, see `read_cbor`.
+     *
+     * # Arguments
+     * * `path` - The file path to read from
+     */
+    #[cfg(feature = "cbor")]
+    pub fn load_from_cbor(path: &str) -> Result<Self, String> {
+        let file = std::fs::File::open(path)
+            .map_err(|e| format!("Failed to open file '{}': {}", path, e))?;
+
+        Self::read_cbor(std::io::BufReader::new(file))
+    }
+
+    /**
+     * Reads a PipelineWitness from bincode. Witnesses written in another schema version are
+     * rejected, see `WITNESS_SCHEMA_VERSION`.
+     *
+     * # Arguments
+     * * `reader` - Where to read the bincode from
+     */
+    #[cfg(feature = "bincode")]
+    pub fn read_bincode<R: std::io::Read>(mut reader: R) -> Result<Self, String> {
+        bincode::decode_from_std_read(&mut reader, bincode::config::standard())
+            .map_err(|e| format!("Failed to read bincode: {}", e))
+    }
+
+    /**
+     * Loads a PipelineWitness from a bincode file, see `read_bincode`.
+     *
+     * # Arguments
+     * * `path` - The file path to read from
+     */
+    #[cfg(feature = "bincode")]
+    pub fn load_from_bincode(path: &str) -> Result<Self, String> {
+        let file = std::fs::File::open(path)
+            .map_err(|e| format!("Failed to open file '{}': {}", path, e))?;
+
+        Self::read_bincode(std::io::BufReader::new(file))
+    }
+}
+
+/**
+ * The grayscale image the pipeline started from.
+ *
+ * # Fields
+ * * `width` - The width of the image in pixels
+ * * `height` - The height of the image in pixels
+ * * `image` - The grayscale luminance values (0-255 per pixel), stored row-major
+ */
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+#[derive(Clone, Debug)]
+pub struct LuminanceWitness {
+    /// The width of the image in pixels
+    pub width: usize,
+
+    /// The height of the image in pixels
+    pub height: usize,
+
+    /// The original grayscale luminance values (0-255 per pixel)
+    /// Stored in row-major order: pixels are stored row by row, left to right, top to bottom
+    /// Total size: width * height bytes
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            serialize_with = "serialize_image",
+            deserialize_with = "crate::witness_compression::deserialize_bytes"
+        )
+    )]
+    pub image: Vec<u8>,
+
+    /// Where the image starts in the full frame, if it was cropped out of it
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub crop_offset: Option<PointU>,
+
+    /// Which pixels were left as decoded, if the rest were redacted
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub redaction: Option<RedactionMask>,
+}
+
+impl LuminanceWitness {
+    /**
+     * Creates a new LuminanceWitness.
+     *
+     * # Arguments
+     * * `width` - The width of the image in pixels
+     * * `height` - The height of the image in pixels
+     * * `image` - The grayscale luminance data (must be width * height bytes)
+     *
+     * # Panics
+     * Panics if `image.len()` does not equal `width * height`
+     */
+    pub fn new(width: usize, height: usize, image: Vec<u8>) -> Self {
+        assert_eq!(
+            image.len(),
+            width * height,
+            "Image size mismatch: expected {} bytes, got {}",
+            width * height,
+            image.len()
+        );
+
+        Self {
+            width,
+            height,
+            image,
+            crop_offset: None,
+            redaction: None,
+        }
+    }
+
+    /**
+     * Gets the grayscale pixel value at position (x, y).
+     *
+     * # Arguments
+     * * `x` - The x coordinate (column)
+     * * `y` - The y coordinate (row)
+     *
+     * # Panics
+     * Panics if x >= width or y >= height
+     */
+    pub fn get_pixel(&self, x: usize, y: usize) -> u8 {
+        assert!(
+            x < self.width && y < self.height,
+            "Pixel coordinates out of bounds"
+        );
+        self.image[y * self.width + x]
+    }
+
+    /**
+     * Gets the grayscale values of row `y`.
+     *
+     * # Panics
+     * Panics if y >= height
+     */
+    pub fn row(&self, y: usize) -> &[u8] {
+        assert!(y < self.height, "Row out of bounds");
+        &self.image[y * self.width..(y + 1) * self.width]
+    }
+
+    /**
+     * Builds a Merkle tree with one leaf per row, the row's grayscale values. The root
+     * commits to the image and an opening proves the values of one row without revealing
+     * the other rows.
+     *
+     * # Arguments
+     * * `hasher` - The hash function of the tree, e.g. `Sha256MerkleHasher` or
+     *   `PoseidonMerkleHasher`
+     */
+    pub fn merkle_tree<H: MerkleHasher>(&self, hasher: &mut H) -> MerkleTree<H::Digest> {
+        MerkleTree::new(hasher, (0..self.height).map(|y| self.row(y)))
+    }
+
+    /// The part of the full frame the image covers
+    pub fn extent(&self) -> CropRegion {
+        CropRegion::at(self.crop_offset, self.width as u32, self.height as u32)
+    }
+
+    /**
+     * Crops the image to `region`, given in the coordinates of the full frame and clipped to
+     * the image.
+     *
+     * # Returns
+     * The cropped image, or an error if `region` does not overlap the image
+     */
+    pub fn crop(&self, region: CropRegion) -> Result<Self> {
+        let extent = self.extent();
+        let region = region.intersection(&extent)?;
+        let left = (region.left - extent.left) as usize;
+        let top = (region.top - extent.top) as usize;
+        let (width, height) = (region.width as usize, region.height as usize);
+        let mut image = Vec::with_capacity(width * height);
+        for y in top..top + height {
+            image.extend_from_slice(&self.row(y)[left..left + width]);
+        }
+
+        Ok(Self {
+            crop_offset: Some(region.offset()),
+            redaction: self.redaction,
+            ..Self::new(width, height, image)
+        })
+    }
+}
+
+/**
+ * The black/white image the binarizer produced from the grayscale image.
+ */
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+#[derive(Clone, Debug)]
+pub struct BinarizationWitness {
+    /// The binarized image after applying the threshold
+    /// Pixels are represented as bits: true/1 = black, false/0 = white
+    /// Serialized as a flattened 1D array of booleans in row-major order
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            serialize_with = "serialize_bitmatrix",
+            deserialize_with = "crate::witness_compression::deserialize_bitmatrix"
+        )
+    )]
+    pub binarized_image: BitMatrix,
+
+    /// The threshold the whole image was binarized with, pixels darker than it are black.
+    /// None if the binarizer used local thresholds
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub threshold: Option<u8>,
+
+    /// The threshold of each block of the full frame, if the binarizer thresholds blocks
+    /// separately and was asked to keep them, see `HybridBinarizer::with_block_thresholds`
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub block_thresholds: Option<BlockThresholds>,
+
+    /// Where the image starts in the full frame, if it was cropped out of it
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub crop_offset: Option<PointU>,
+
+    /// Which pixels were left as decoded, if the rest were redacted
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub redaction: Option<RedactionMask>,
+}
+
+impl BinarizationWitness {
+    pub fn new(binarized_image: BitMatrix) -> Self {
+        Self {
+            binarized_image,
+            threshold: None,
+            block_thresholds: None,
+            crop_offset: None,
+            redaction: None,
+        }
+    }
+
+    pub fn with_threshold(mut self, threshold: u8) -> Self {
+        self.threshold = Some(threshold);
+        self
+    }
+
+    pub fn with_block_thresholds(mut self, block_thresholds: BlockThresholds) -> Self {
+        self.block_thresholds = Some(block_thresholds);
+        self
+    }
+
+    /**
+     * Gets the binarized bit value at position (x, y).
+     *
+     * # Arguments
+     * * `x` - The x coordinate (column)
+     * * `y` - The y coordinate (row)
+     *
+     * # Returns
+     * `true` if the pixel is black, `false` if white
+     */
+    pub fn get_pixel(&self, x: usize, y: usize) -> bool {
+        self.binarized_image.get(x as u32, y as u32)
+    }
+
+    /**
+     * Gets the pixels of row `y` packed eight to a byte, most significant bit first, with the
+     * last byte padded with zeros.
+     */
+    pub fn packed_row(&self, y: usize) -> Vec<u8> {
+        let width = self.binarized_image.getWidth() as usize;
+        let mut row = vec![0u8; width.div_ceil(8)];
+        for x in 0..width {
+            if self.get_pixel(x, y) {
+                row[x / 8] |= 0x80 >> (x % 8);
+            }
+        }
+        row
+    }
+
+    /**
+     * Builds a Merkle tree with one leaf per row, the row as returned by `packed_row`. See
+     * `LuminanceWitness::merkle_tree`.
+     */
+    pub fn merkle_tree<H: MerkleHasher>(&self, hasher: &mut H) -> MerkleTree<H::Digest> {
+        let height = self.binarized_image.getHeight() as usize;
+        MerkleTree::new(hasher, (0..height).map(|y| self.packed_row(y)))
+    }
+
+    /// The part of the full frame the image covers
+    pub fn extent(&self) -> CropRegion {
+        let image = &self.binarized_image;
+        CropRegion::at(self.crop_offset, image.getWidth(), image.getHeight())
+    }
+
+    /**
+     * Crops the image to `region`, given in the coordinates of the full frame and clipped to
+     * the image.
+     *
+     * # Returns
+     * The cropped image, or an error if `region` does not overlap the image
+     */
+    pub fn crop(&self, region: CropRegion) -> Result<Self> {
+        let extent = self.extent();
+        let region = region.intersection(&extent)?;
+        let (left, top) = (region.left - extent.left, region.top - extent.top);
+
+        let mut binarized_image = BitMatrix::new(region.width, region.height)?;
+        for y in 0..region.height {
+            for x in 0..region.width {
+                if self.binarized_image.get(left + x, top + y) {
+                    binarized_image.set(x, y);
+                }
+            }
+        }
+
+        Ok(Self {
+            binarized_image,
+            threshold: self.threshold,
+            block_thresholds: self
+                .block_thresholds
+                .as_ref()
+                .map(|thresholds| thresholds.clear_outside(&region)),
+            crop_offset: Some(region.offset()),
+            redaction: self.redaction,
+        })
+    }
+
+    /**
+     * The binarized image placed where it was cropped from, on a white frame reaching from
+     * the origin to its bottom right corner, so that points in the coordinates of the full
+     * frame address it directly.
+     */
+    pub fn uncropped_image(&self) -> Result<BitMatrix> {
+        let Some(offset) = self.crop_offset else {
+            return Ok(self.binarized_image.clone());
+        };
+
+        let image = &self.binarized_image;
+        let mut uncropped =
+            BitMatrix::new(offset.x + image.getWidth(), offset.y + image.getHeight())?;
+        for y in 0..image.getHeight() {
+            for x in 0..image.getWidth() {
+                if image.get(x, y) {
+                    uncropped.set(offset.x + x, offset.y + y);
+                }
+            }
+        }
+        Ok(uncropped)
+    }
+}
+
+/**
+ * The thresholds a binarizer applied to the blocks of the image, for binarizers that threshold
+ * each block of pixels separately like `HybridBinarizer`. A pixel is black if its luminance is
+ * at most the threshold of a block covering it.
+ *
+ * The blocks are `block_size` pixels square and block (x, y) starts at pixel
+ * (x * block_size, y * block_size), except that the last block of each row and column is moved
+ * back to end at the edge of the image, overlapping the block before it. A pixel covered by two
+ * blocks is black if it is black by either threshold, see `threshold_at`.
+ */
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BlockThresholds {
+    /// The width of the image in pixels
+    pub width: u32,
+
+    /// The height of the image in pixels
+    pub height: u32,
+
+    /// The width and height of a block in pixels
+    pub block_size: u32,
+
+    /// The number of blocks in a row
+    pub blocks_wide: u32,
+
+    /// The number of blocks in a column
+    pub blocks_high: u32,
+
+    /// The threshold of each block, in row-major order
+    pub thresholds: Vec<u8>,
+}
+
+impl BlockThresholds {
+    /// Thresholds of 0 for the blocks of a `width` by `height` image
+    pub(crate) fn new(width: u32, height: u32, block_size: u32) -> Self {
+        let (blocks_wide, blocks_high) = (width.div_ceil(block_size), height.div_ceil(block_size));
+        Self {
+            width,
+            height,
+            block_size,
+            blocks_wide,
+            blocks_high,
+            thresholds: vec![0; (blocks_wide * blocks_high) as usize],
+        }
+    }
+
+    /// The threshold of block (x, y)
+    pub fn get(&self, x: u32, y: u32) -> u8 {
+        self.thresholds[(y * self.blocks_wide + x) as usize]
+    }
+
+    pub(crate) fn set(&mut self, x: u32, y: u32, threshold: u8) {
+        self.thresholds[(y * self.blocks_wide + x) as usize] = threshold;
+    }
+
+    /// The first pixel of block `index` of a row or column `size` pixels long
+    fn block_start(&self, index: u32, size: u32) -> u32 {
+        (index * self.block_size).min(size - self.block_size)
+    }
+
+    /// The one or two blocks of a row or column `size` pixels long covering pixel `i`
+    fn covering(&self, i: u32, size: u32, blocks: u32) -> [u32; 2] {
+        let last = blocks - 1;
+        let block = (i / self.block_size).min(last);
+        if i >= self.block_start(last, size) {
+            [block, last]
+        } else {
+            [block, block]
+        }
+    }
+
+    /**
+     * The highest threshold of the blocks covering pixel (x, y), the pixel being black if its
+     * luminance is at most this
+     */
+    pub fn threshold_at(&self, x: u32, y: u32) -> u8 {
+        let columns = self.covering(x, self.width, self.blocks_wide);
+        let rows = self.covering(y, self.height, self.blocks_high);
+        rows.iter()
+            .flat_map(|&row| columns.iter().map(move |&column| self.get(column, row)))
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// Whether the pixel at (x, y) with `luminance` was binarized to black
+    pub fn is_black(&self, x: u32, y: u32, luminance: u8) -> bool {
+        luminance <= self.threshold_at(x, y)
+    }
+
+    /**
+     * Sets the thresholds of the blocks not overlapping `region` to 0, so that they tell
+     * nothing about the image outside of a crop or redaction. The pixels in `region` keep
+     * their thresholds.
+     */
+    pub fn clear_outside(&self, region: &CropRegion) -> Self {
+        let overlaps = |index: u32, size: u32, start: u32, length: u32| {
+            let block_start = self.block_start(index, size);
+            block_start < start + length && start < block_start + self.block_size
+        };
+        let mut cleared = self.clone();
+        for y in 0..self.blocks_high {
+            for x in 0..self.blocks_wide {
+                if !overlaps(x, self.width, region.left, region.width)
+                    || !overlaps(y, self.height, region.top, region.height)
+                {
+                    cleared.set(x, y, 0);
+                }
+            }
+        }
+        cleared
+    }
+}
+
+/**
+ * Where the detector found the symbol.
+ *
+ * # Fields
+ * * `format` - The format of the detected symbol
+ * * `points` - The result points reported for the symbol, in image pixels
+ * * `patterns` - The finder/alignment patterns the sampling grid was fitted to, in image pixels
+ */
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+#[derive(Clone, Debug)]
+pub struct DetectionWitness {
+    /// The format of the detected symbol
+    pub format: BarcodeFormat,
+
+    /// The result points of the symbol, e.g. its corners or finder pattern centers
+    pub points: Vec<Point>,
+
+    /// The located finder and alignment patterns
+    /// Empty for formats without them and for "pure" images, see `SamplingGrid::patterns`
+    pub patterns: Vec<DetectedPattern>,
+}
+
+impl DetectionWitness {
+    /**
+     * Creates a DetectionWitness from a decode result.
+     */
+    pub fn from_result(result: &RXingResult) -> Self {
+        let patterns = match result
+            .getRXingResultMetadata()
+            .get(&RXingResultMetadataType::SAMPLING_GRID)
+        {
+            Some(RXingResultMetadataValue::SamplingGrid(grid)) => grid.patterns().to_vec(),
+            _ => Vec::new(),
+        };
+
+        Self {
+            format: result.getBarcodeFormat().clone(),
+            points: result.getPoints().to_vec(),
+            patterns,
+        }
+    }
+}
+
+/**
+ * How the modules of a 2D symbol were read out of the binarized image.
+ *
+ * # Fields
+ * * `grid` - The sampling grid, with the perspective transform of each region
+ * * `modules` - The modules sampled from the binarized image through the grid
+ * * `fixed_point` - The fixed-point transforms the modules were sampled with, if any
+ */
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+#[derive(Clone, Debug)]
+pub struct SamplingWitness {
+    /// The sampling grid the detector fitted to the symbol
+    /// Maps module coordinates to image pixels, see `SamplingGrid`
+    pub grid: SamplingGrid,
+
+    /// The sampled modules, one bit per module: true/1 = dark, false/0 = light
+    /// Serialized as a flattened 1D array of booleans in row-major order
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            serialize_with = "serialize_bitmatrix",
+            deserialize_with = "crate::witness_compression::deserialize_bitmatrix"
+        )
+    )]
+    pub modules: BitMatrix,
+
+    /// The transform of each region of `grid` in fixed point, when the modules were sampled
+    /// with a `FixedPointGridSampler` rather than in floating point
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub fixed_point: Option<Vec<FixedPointTransform>>,
+}
+
+impl SamplingWitness {
+    /**
+     * Creates a new SamplingWitness by sampling `binarized_image` through `grid`.
+     *
+     * # Arguments
+     * * `grid` - The sampling grid reported by the detector
+     * * `binarized_image` - The binarized image the detector worked on
+     */
+    pub fn new(grid: SamplingGrid, binarized_image: &BitMatrix) -> Result<Self> {
+        let (modules, _) = DefaultGridSampler.sample_grid(
+            binarized_image,
+            grid.width(),
+            grid.height(),
+            grid.regions(),
+        )?;
+
+        Ok(Self {
+            grid,
+            modules,
+            fixed_point: None,
+        })
+    }
+
+    /**
+     * Creates a new SamplingWitness by sampling `binarized_image` through `grid` in fixed
+     * point, see `FixedPointTransform` for the arithmetic. The fixed-point transforms are
+     * recorded, so the sampling can be checked exactly in an arithmetic circuit.
+     *
+     * # Arguments
+     * * `grid` - The sampling grid reported by the detector
+     * * `binarized_image` - The binarized image the detector worked on
+     * * `frac_bits` - The number of fractional bits of the transform coefficients
+     */
+    pub fn new_fixed_point(
+        grid: SamplingGrid,
+        binarized_image: &BitMatrix,
+        frac_bits: u32,
+    ) -> Result<Self> {
+        let sampler = FixedPointGridSampler::new(frac_bits);
+        let transforms = sampler.transforms(grid.regions())?;
+        let (modules, _) =
+            sampler.sample_grid(binarized_image, grid.width(), grid.height(), grid.regions())?;
+
+        Ok(Self {
+            grid,
+            modules,
+            fixed_point: Some(transforms),
+        })
+    }
+
+    /**
+     * Creates a SamplingWitness from the sampling grid recorded in a decode result.
+     *
+     * # Returns
+     * `None` if the result carries no `SAMPLING_GRID` metadata
+     */
+    pub fn from_result(result: &RXingResult, binarized_image: &BitMatrix) -> Result<Option<Self>> {
+        let Some(RXingResultMetadataValue::SamplingGrid(grid)) = result
+            .getRXingResultMetadata()
+            .get(&RXingResultMetadataType::SAMPLING_GRID)
+        else {
+            return Ok(None);
+        };
+
+        Self::new(grid.clone(), binarized_image).map(Some)
+    }
+
+    /**
+     * Returns the coefficients of the perspective transform of each sampled region.
+     */
+    pub fn transform_coefficients(&self) -> Vec<[f32; 9]> {
+        self.grid
+            .regions()
+            .iter()
+            .map(|region| region.transform.coefficients())
+            .collect()
+    }
+
+    /**
+     * Gets the sampled module at module position (x, y).
+     *
+     * # Returns
+     * `true` if the module is dark, `false` if light
+     */
+    pub fn get_module(&self, x: usize, y: usize) -> bool {
+        self.modules.get(x as u32, y as u32)
+    }
+}
+
+/**
+ * The bytes and text the bit stream parser assembled from the corrected data codewords.
+ *
+ * # Fields
+ * * `raw_bytes` - The raw bytes of the symbol, if the format has them
+ * * `num_bits` - How many bits of `raw_bytes` are valid
+ * * `byte_segments` - The bytes of each byte mode segment, in order
+ * * `text` - The decoded text
+ * * `symbology_identifier` - The symbology identifier, e.g. `]Q1`
+ */
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+#[derive(Clone, Debug)]
+pub struct TextAssemblyWitness {
+    pub raw_bytes: Vec<u8>,
+    pub num_bits: usize,
+    pub byte_segments: Vec<Vec<u8>>,
+    pub text: String,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub symbology_identifier: Option<String>,
+}
+
+impl TextAssemblyWitness {
+    /**
+     * Creates a TextAssemblyWitness from a decode result.
+     */
+    pub fn from_result(result: &RXingResult) -> Self {
+        let metadata = result.getRXingResultMetadata();
+        let byte_segments = match metadata.get(&RXingResultMetadataType::BYTE_SEGMENTS) {
+            Some(RXingResultMetadataValue::ByteSegments(segments)) => segments.clone(),
+            _ => Vec::new(),
+        };
+        let symbology_identifier =
+            match metadata.get(&RXingResultMetadataType::SYMBOLOGY_IDENTIFIER) {
+                Some(RXingResultMetadataValue::SymbologyIdentifier(id)) => Some(id.clone()),
+                _ => None,
+            };
+
+        Self {
+            raw_bytes: result.getRawBytes().to_vec(),
+            num_bits: result.getNumBits(),
+            byte_segments,
+            text: result.getText().to_owned(),
+            symbology_identifier,
+        }
+    }
+}
+
+/**
+ * A rectangle of the full frame, in pixels.
+ *
+ * # Fields
+ * * `left` - The x coordinate of the leftmost column
+ * * `top` - The y coordinate of the topmost row
+ * * `width` - The number of columns
+ * * `height` - The number of rows
+ */
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CropRegion {
+    pub left: u32,
+    pub top: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl CropRegion {
+    /**
+     * The smallest region holding `points`, extended by `margin` pixels on every side and
+     * clipped to `frame`.
+     *
+     * # Returns
+     * The region, or None if no point is finite or the region lies outside `frame`
+     */
+    pub fn around(points: &[Point], margin: u32, frame: CropRegion) -> Option<Self> {
+        let mut points = points.iter().filter(|p| p.x.is_finite() && p.y.is_finite());
+        let first = *points.next()?;
+        let (min, max) = points.fold((first, first), |(min, max), p| {
+            (
+                Point::new(min.x.min(p.x), min.y.min(p.y)),
+                Point::new(max.x.max(p.x), max.y.max(p.y)),
+            )
+        });
+
+        // the pixels the extreme points lie in are part of the region
+        let margin = margin as f32;
+        let left = (min.x.floor() - margin).max(frame.left as f32);
+        let top = (min.y.floor() - margin).max(frame.top as f32);
+        let right = (max.x.floor() + 1.0 + margin).min((frame.left + frame.width) as f32);
+        let bottom = (max.y.floor() + 1.0 + margin).min((frame.top + frame.height) as f32);
+        if left >= right || top >= bottom {
+            return None;
+        }
+
+        Some(Self {
+            left: left as u32,
+            top: top as u32,
+            width: (right - left) as u32,
+            height: (bottom - top) as u32,
+        })
+    }
+
+    /// The part of this region that is also in `other`, an error if there is none
+    pub fn intersection(&self, other: &CropRegion) -> Result<Self> {
+        let left = self.left.max(other.left);
+        let top = self.top.max(other.top);
+        let right = (self.left + self.width).min(other.left + other.width);
+        let bottom = (self.top + self.height).min(other.top + other.height);
+        if left >= right || top >= bottom {
+            return Err(Exceptions::illegal_argument_with(format!(
+                "{self:?} does not overlap {other:?}"
+            )));
+        }
+
+        Ok(Self {
+            left,
+            top,
+            width: right - left,
+            height: bottom - top,
+        })
+    }
+
+    fn at(offset: Option<PointU>, width: u32, height: u32) -> Self {
+        let offset = offset.unwrap_or_default();
+        Self {
+            left: offset.x,
+            top: offset.y,
+            width,
+            height,
+        }
+    }
+
+    fn offset(&self) -> PointU {
+        PointU::new(self.left, self.top)
+    }
+}
+
+/// The points the symbol spans: the detected points and patterns and the corners of the grid
+pub(crate) fn symbol_points(
+    detection: &DetectionWitness,
+    sampling: Option<&SamplingWitness>,
+) -> Vec<Point> {
+    let mut points = detection.points.clone();
+    points.extend(detection.patterns.iter().map(|pattern| pattern.center));
+    if let Some(sampling) = sampling {
+        for region in sampling.grid.regions() {
+            let (p0, p1) = (region.p0, region.p1);
+            let corners = [p0, Point::new(p1.x, p0.y), p1, Point::new(p0.x, p1.y)];
+            points.extend(corners.map(|corner| region.transform.transform_point(corner)));
+        }
+    }
+    points
+}
+
+// Custom serialization for BitMatrix - convert to flattened 1D array of booleans
+// Stored in row-major order: row 0 from left to right, then row 1, etc.
+// Binary formats get the same order packed into bytes instead, see `PackedBitMatrix`
+#[cfg(feature = "serde")]
+pub(crate) fn serialize_bitmatrix<S>(matrix: &BitMatrix, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    use serde::ser::SerializeSeq;
+
+    match matrix_compression() {
+        MatrixCompression::RunLength => {
+            return RunLengthBitMatrix::new(matrix).serialize(serializer);
+        }
+        MatrixCompression::BitPacked => return PackedBitMatrix::new(matrix).serialize(serializer),
+        MatrixCompression::None if !serializer.is_human_readable() => {
+            return PackedBitMatrix::new(matrix).serialize(serializer);
+        }
+        MatrixCompression::None => {}
+    }
+
+    let width = matrix.getWidth();
+    let height = matrix.getHeight();
+    let total_pixels = (width * height) as usize;
+
+    let mut seq = serializer.serialize_seq(Some(total_pixels))?;
+    for y in 0..height {
+        for x in 0..width {
+            seq.serialize_element(&matrix.get(x, y))?;
+        }
+    }
+    seq.end()
+}
+
+/// A BitMatrix with its pixels packed row-major, eight to a byte, most significant bit first
+#[cfg(feature = "serde")]
+#[derive(Serialize)]
+pub(crate) struct PackedBitMatrix {
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+    #[serde(serialize_with = "serialize_bytes")]
+    pub(crate) bits: Vec<u8>,
+}
+
+#[cfg(feature = "serde")]
+impl PackedBitMatrix {
+    pub(crate) fn new(matrix: &BitMatrix) -> Self {
+        let (width, height) = (matrix.getWidth(), matrix.getHeight());
+        let mut bits = vec![0u8; (width as usize * height as usize).div_ceil(8)];
+        for y in 0..height {
+            for x in 0..width {
+                if matrix.get(x, y) {
+                    let i = y as usize * width as usize + x as usize;
+                    bits[i / 8] |= 0x80 >> (i % 8);
+                }
+            }
+        }
+
+        Self {
+            width,
+            height,
+            bits,
+        }
+    }
+}
+
+// Grayscale images stay an array of numbers in human readable formats, for binary formats
+// they are written as a byte string
+#[cfg(feature = "serde")]
+fn serialize_image<S>(image: &[u8], serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    if serializer.is_human_readable() {
+        image.serialize(serializer)
+    } else {
+        serializer.serialize_bytes(image)
+    }
+}
+
+#[cfg(feature = "serde")]
+fn serialize_bytes<S>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    serializer.serialize_bytes(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CheckOutcome;
+
+    #[test]
+    fn test_witness_stages() {
+        // Create a simple 4x4 test image
+        let image = vec![
+            0, 64, 127, 128, 129, 192, 200, 255, 50, 100, 150, 200, 127, 128, 129, 130,
+        ];
+
+        let mut binarized = BitMatrix::new(4, 4).unwrap();
+        // Set some pixels black (< 128)
+        binarized.set(0, 0); // 0
+        binarized.set(1, 0); // 64
+        binarized.set(2, 0); // 127
+                             // 128+ stay white
+
+        let witness = PipelineWitness::new()
+            .with_luminance(LuminanceWitness::new(4, 4, image.clone()))
+            .with_binarization(BinarizationWitness::new(binarized));
+
+        assert_eq!(
+            witness.stages(),
+            vec![PipelineStage::Luminance, PipelineStage::Binarization]
+        );
+
+        let luminance = witness.luminance().unwrap();
+        assert_eq!(luminance.width, 4);
+        assert_eq!(luminance.height, 4);
+        assert_eq!(luminance.image.len(), 16);
+        assert_eq!(luminance.get_pixel(0, 0), 0);
+        assert_eq!(luminance.get_pixel(3, 1), 255);
+
+        let binarization = witness.binarization().unwrap();
+        assert!(binarization.get_pixel(0, 0)); // black
+        assert!(!binarization.get_pixel(3, 0)); // white
+    }
+
+    #[test]
+    #[should_panic(expected = "Image size mismatch")]
+    fn test_luminance_witness_size_mismatch() {
+        let image = vec![1, 2, 3]; // Wrong size
+        let _witness = LuminanceWitness::new(4, 4, image);
+    }
+
+    #[cfg(feature = "cbor")]
+    #[test]
+    fn test_cbor_packs_bit_matrices() {
+        use ciborium::Value;
+
+        let (width, height) = (100, 30);
+        let mut binarized = BitMatrix::new(width, height).unwrap();
+        binarized.set(0, 0);
+        binarized.set(9, 0);
+        binarized.set(99, 29);
+
+        let witness = PipelineWitness::new()
+            .with_luminance(LuminanceWitness::new(
+                width as usize,
+                height as usize,
+                vec![200; (width * height) as usize],
+            ))
+            .with_binarization(BinarizationWitness::new(binarized));
+        let cbor = witness.to_cbor().unwrap();
+
+        // one byte per grayscale pixel and one bit per binarized pixel, plus framing
+        let pixels = (width * height) as usize;
+        assert!(cbor.len() < pixels + pixels / 8 + 128, "{}", cbor.len());
+
+        let value: Value = ciborium::from_reader(cbor.as_slice()).unwrap();
+        let field = |value: &Value, name: &str| {
+            value
+                .as_map()
+                .unwrap()
+                .iter()
+                .find(|(k, _)| k.as_text() == Some(name))
+                .map(|(_, v)| v.clone())
+                .unwrap()
+        };
+        let image = field(&field(&value, "luminance"), "image");
+        assert_eq!(image.as_bytes().unwrap().len(), pixels);
+
+        let matrix = field(&field(&value, "binarization"), "binarized_image");
+        assert_eq!(field(&matrix, "width"), Value::from(width));
+        assert_eq!(field(&matrix, "height"), Value::from(height));
+        let bits = field(&matrix, "bits").into_bytes().unwrap();
+        assert_eq!(bits.len(), pixels / 8);
+        assert_eq!(bits[0], 0x80);
+        assert_eq!(bits[1], 0x40);
+        assert_eq!(bits[pixels / 8 - 1], 0x01);
+        assert_eq!(bits.iter().map(|b| b.count_ones()).sum::<u32>(), 3);
+    }
+
+    #[test]
+    fn test_packed_rows() {
+        let mut binarized = BitMatrix::new(13, 2).unwrap();
+        binarized.set(0, 0);
+        binarized.set(12, 0);
+        binarized.set(8, 1);
+        let binarization = BinarizationWitness::new(binarized);
+
+        assert_eq!(vec![0x80, 0x08], binarization.packed_row(0));
+        assert_eq!(vec![0x00, 0x80], binarization.packed_row(1));
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn test_row_merkle_trees() {
+        use crate::common::Sha256MerkleHasher;
+
+        let (width, height) = (13, 6);
+        let image = (0..width * height).map(|i| (i * 37 % 256) as u8).collect();
+        let luminance = LuminanceWitness::new(width, height, image);
+        let mut binarized = BitMatrix::new(width as u32, height as u32).unwrap();
+        binarized.set(3, 5);
+        let witness = PipelineWitness::new()
+            .with_luminance(luminance.clone())
+            .with_binarization(BinarizationWitness::new(binarized));
+
+        let hasher = &mut Sha256MerkleHasher;
+        let tree = witness.luminance_merkle_tree(hasher).unwrap();
+        assert_eq!(height, tree.num_leaves());
+        assert_eq!(3, tree.depth());
+        for y in 0..height {
+            let opening = tree.opening(y).unwrap();
+            assert!(opening.verify(hasher, luminance.row(y), tree.root()));
+            assert!(!opening.verify(hasher, luminance.row((y + 1) % height), tree.root()));
+        }
+
+        let binarization = witness.binarization().unwrap();
+        let tree = witness.binarization_merkle_tree(hasher).unwrap();
+        let opening = tree.opening(5).unwrap();
+        assert!(opening.verify(hasher, &binarization.packed_row(5), tree.root()));
+        assert!(!opening.verify(hasher, &[0, 0], tree.root()));
+
+        assert!(PipelineWitness::new()
+            .luminance_merkle_tree(hasher)
+            .is_none());
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn test_commitments() {
+        use crate::common::Sha256MerkleHasher;
+
+        let (width, height) = (9, 4);
+        let image: Vec<u8> = (0..width * height).map(|i| (i * 11 % 256) as u8).collect();
+        let mut binarized = BitMatrix::new(width as u32, height as u32).unwrap();
+        binarized.set(8, 0);
+        let witness = PipelineWitness::new()
+            .with_luminance(LuminanceWitness::new(width, height, image.clone()))
+            .with_binarization(BinarizationWitness::new(binarized.clone()));
+
+        let hasher = &mut Sha256MerkleHasher;
+        let image_root = witness.image_commitment().unwrap();
+        assert_eq!(
+            witness.luminance_merkle_tree(hasher).unwrap().root(),
+            &image_root
+        );
+        assert_eq!(Some(image_root), witness.image_commitment_with(hasher));
+        let binarization_root = witness.binarization_commitment().unwrap();
+        assert_eq!(
+            witness.binarization_merkle_tree(hasher).unwrap().root(),
+            &binarization_root
+        );
+        assert_ne!(image_root, binarization_root);
+
+        // changing a single pixel changes the commitment
+        let mut changed = image;
+        changed[width * 3 + 2] ^= 1;
+        binarized.flip_coords(0, 3);
+        let witness = PipelineWitness::new()
+            .with_luminance(LuminanceWitness::new(width, height, changed))
+            .with_binarization(BinarizationWitness::new(binarized));
+        assert_ne!(Some(image_root), witness.image_commitment());
+        assert_ne!(Some(binarization_root), witness.binarization_commitment());
+
+        assert_eq!(None, PipelineWitness::new().image_commitment());
+        assert_eq!(None, PipelineWitness::new().binarization_commitment());
+    }
+
+    /// Renders `symbol` with 4 pixels per module and a 4 module quiet zone, decodes it and
+    /// extracts the witness of the decode.
+    fn decode_witness(symbol: &BitMatrix) -> (RXingResult, PipelineWitness) {
+        use crate::{
+            common::FixedThresholdBinarizer, BinaryBitmap, Luma8LuminanceSource, MultiFormatReader,
+            Reader,
+        };
+
+        let scale = 4;
+        let width = (symbol.getWidth() + 8) * scale;
+        let height = (symbol.getHeight() + 8) * scale;
+        let mut luma = vec![255u8; (width * height) as usize];
+        for y in 0..height {
+            for x in 0..width {
+                let (mx, my) = (x / scale, y / scale);
+                if (4..symbol.getWidth() + 4).contains(&mx)
+                    && (4..symbol.getHeight() + 4).contains(&my)
+                    && symbol.get(mx - 4, my - 4)
+                {
+                    luma[(y * width + x) as usize] = 0;
+                }
+            }
+        }
+
+        let mut bitmap = BinaryBitmap::new(FixedThresholdBinarizer::new(
+            Luma8LuminanceSource::new(luma, width, height),
+        ));
+        let result = MultiFormatReader::default()
+            .decode(&mut bitmap)
+            .expect("decode");
+        let witness =
+            crate::helpers::extract_witness_data_with_result(&bitmap, &result).expect("witness");
+
+        (result, witness)
+    }
+
+    fn encode(contents: &str, format: BarcodeFormat) -> BitMatrix {
+        use crate::{EncodeHintValue, EncodeHints, MultiFormatWriter, Writer};
+
+        let hints = EncodeHints::default().with(EncodeHintValue::Margin("0".to_owned()));
+        MultiFormatWriter
+            .encode_with_hints(contents, &format, 0, 0, &hints)
+            .expect("encode")
+    }
+
+    /// Encodes a linear symbol 10 pixels high, with the quiet zones the writer adds
+    fn encode_linear(contents: &str, format: BarcodeFormat) -> BitMatrix {
+        use crate::{MultiFormatWriter, Writer};
+
+        MultiFormatWriter
+            .encode(contents, &format, 0, 10)
+            .expect("encode")
+    }
+
+    #[test]
+    fn test_detection_and_sampling_witness_qr() {
+        use crate::common::PatternKind;
+
+        let symbol = encode(
+            "https://github.com/rxing-core/rxing",
+            BarcodeFormat::QR_CODE,
+        );
+        assert!(
+            symbol.getWidth() >= 25,
+            "expected a symbol with alignment patterns"
+        );
+
+        let (result, witness) = decode_witness(&symbol);
+        assert_eq!(
+            witness.stages(),
+            vec![
+                PipelineStage::Luminance,
+                PipelineStage::Binarization,
+                PipelineStage::Detection,
+                PipelineStage::Sampling,
+                PipelineStage::Decoding,
+                PipelineStage::TextAssembly,
+            ]
+        );
+
+        let detection = witness.detection().expect("detection witness");
+        assert_eq!(detection.format, BarcodeFormat::QR_CODE);
+        assert_eq!(detection.points, result.getPoints());
+        let count = |kind| detection.patterns.iter().filter(|p| p.kind == kind).count();
+        assert_eq!(count(PatternKind::Finder), 3);
+        assert!(count(PatternKind::Alignment) >= 1);
+
+        let sampling = witness.sampling().expect("sampling witness");
+        assert!(!sampling.transform_coefficients().is_empty());
+        assert_eq!(sampling.modules, symbol);
+
+        let text_assembly = witness.text_assembly().expect("text assembly witness");
+        assert_eq!(text_assembly.text, "https://github.com/rxing-core/rxing");
+        assert_eq!(text_assembly.symbology_identifier.as_deref(), Some("]Q1"));
+    }
+
+    #[test]
+    fn test_decoding_witness() {
+        let contents = "https://github.com/rxing-core/rxing";
+        for format in [
+            BarcodeFormat::QR_CODE,
+            BarcodeFormat::DATA_MATRIX,
+            BarcodeFormat::AZTEC,
+        ] {
+            let mut symbol = encode(contents, format.clone());
+            // corrupt a module in the data region
+            let (x, y) = match format {
+                BarcodeFormat::QR_CODE => (symbol.getWidth() - 1, symbol.getHeight() - 1),
+                BarcodeFormat::DATA_MATRIX => (symbol.getWidth() / 2, symbol.getHeight() / 2),
+                _ => (0, 0),
+            };
+            symbol.flip_coords(x, y);
+
+            let (result, witness) = decode_witness(&symbol);
+            assert_eq!(result.getText(), contents, "{format}");
+
+            let codewords = witness.decoding().expect("decoding witness");
+            assert_eq!(codewords.errors_corrected(), 1, "{format}");
+            assert_eq!(
+                codewords.raw_codewords.len(),
+                codewords
+                    .blocks
+                    .iter()
+                    .map(|b| b.received.len())
+                    .sum::<usize>(),
+                "{format}"
+            );
+            assert_eq!(
+                codewords.corrected_codewords.len(),
+                codewords
+                    .blocks
+                    .iter()
+                    .map(|b| b.num_data_codewords)
+                    .sum::<usize>(),
+                "{format}"
+            );
+            for block in &codewords.blocks {
+                let mut corrected = block.received.clone();
+                for (position, magnitude) in block
+                    .correction
+                    .error_positions
+                    .iter()
+                    .zip(&block.correction.error_magnitudes)
+                {
+                    corrected[*position] ^= magnitude;
+                }
+                assert_eq!(corrected, block.corrected, "{format}");
+                assert_eq!(
+                    block.correction.syndromes.len(),
+                    block.received.len() - block.num_data_codewords,
+                    "{format}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_verify_witness() {
+        let contents = "https://github.com/rxing-core/rxing";
+        for format in [
+            BarcodeFormat::QR_CODE,
+            BarcodeFormat::DATA_MATRIX,
+            BarcodeFormat::AZTEC,
+        ] {
+            let (_, witness) = decode_witness(&encode(contents, format.clone()));
+            let report = witness.verify();
+            assert!(report.is_valid(), "{format}: {report:?}");
+            assert_eq!(report.binarization, CheckOutcome::Passed, "{format}");
+            assert_eq!(report.error_correction, CheckOutcome::Passed, "{format}");
+            match format {
+                BarcodeFormat::QR_CODE | BarcodeFormat::DATA_MATRIX => {
+                    assert!(report.is_complete(), "{format}: {report:?}")
+                }
+                _ => {
+                    assert_eq!(report.sampling, CheckOutcome::Passed);
+                    assert!(matches!(report.codewords, CheckOutcome::Skipped(_)));
+                    assert!(matches!(report.text, CheckOutcome::Skipped(_)));
+                }
+            }
+        }
+
+        let (_, witness) = decode_witness(&encode(contents, BarcodeFormat::QR_CODE));
+
+        let mut tampered = witness.clone();
+        let luminance = tampered.luminance.as_mut().unwrap();
+        luminance.image[0] = 0;
+        assert!(tampered.verify().binarization.is_failed());
+
+        let mut tampered = witness.clone();
+        let sampling = tampered.sampling.as_mut().unwrap();
+        sampling.modules.flip_coords(8, 0);
+        let report = tampered.verify();
+        assert!(report.binarization.is_passed());
+        assert!(report.sampling.is_failed());
+
+        let mut tampered = witness.clone();
+        let decoding = tampered.decoding.as_mut().unwrap();
+        decoding.blocks[0].corrected[0] ^= 1;
+        let report = tampered.verify();
+        assert!(report.error_correction.is_failed());
+        assert!(report.codewords.is_failed());
+        assert!(!report.is_valid());
+
+        let mut tampered = witness.clone();
+        tampered.text_assembly.as_mut().unwrap().text.push('!');
+        let report = tampered.verify();
+        assert!(report.codewords.is_passed());
+        assert!(report.text.is_failed());
+
+        let report = PipelineWitness::new().verify();
+        assert!(report.is_valid());
+        assert!(!report.is_complete());
+    }
+
+    #[test]
+    fn test_scanline_witness() {
+        for (contents, format) in [
+            ("RXing", BarcodeFormat::CODE_128),
+            ("5901234123457", BarcodeFormat::EAN_13),
+            ("96385074", BarcodeFormat::EAN_8),
+            ("01234565", BarcodeFormat::UPC_E),
+        ] {
+            let (result, witness) = decode_witness(&encode_linear(contents, format.clone()));
+            assert_eq!(&format, result.getBarcodeFormat());
+            assert_eq!(
+                vec![
+                    PipelineStage::Luminance,
+                    PipelineStage::Binarization,
+                    PipelineStage::Detection,
+                    PipelineStage::Scanline,
+                    PipelineStage::TextAssembly,
+                ],
+                witness.stages(),
+                "{format}"
+            );
+            let scanline = witness.scanline().unwrap();
+            assert_eq!(Some(&scanline.row), scanline.rows_scanned.last());
+            assert_eq!(
+                result.getPoints()[0].y as u32,
+                scanline.row,
+                "{format}: the scanline is the row of the result"
+            );
+            let report = witness.verify();
+            assert!(report.is_complete(), "{format}: {report:?}");
+        }
+
+        let (_, witness) = decode_witness(&encode_linear("RXing", BarcodeFormat::CODE_128));
+        let scanline = witness.scanline().unwrap();
+        // start code B, the characters less 32 and the check character
+        assert_eq!(Some(104), scanline.start_pattern.value);
+        let values: Vec<u32> = scanline.characters.iter().flat_map(|c| c.value).collect();
+        assert_eq!(vec![50, 56, 73, 78, 71], values[..5]);
+        assert_eq!(6, values.len());
+        assert_eq!(7, scanline.stop_pattern.counters.len());
+        assert!(scanline.middle_pattern.is_none());
+
+        let mut tampered = witness.clone();
+        tampered.scanline.as_mut().unwrap().characters[1].value = Some(57);
+        assert!(tampered.verify().scanline.is_failed());
+
+        let mut tampered = witness.clone();
+        tampered.scanline.as_mut().unwrap().characters[1].counters[0] += 1;
+        assert!(tampered.verify().scanline.is_failed());
+
+        // read upside down, the columns count from the right
+        let symbol = encode_linear("RXing", BarcodeFormat::CODE_128);
+        let (width, height) = (symbol.getWidth(), symbol.getHeight());
+        let mut mirrored = BitMatrix::new(width, height).unwrap();
+        for y in 0..height {
+            for x in 0..width {
+                if symbol.get(width - 1 - x, y) {
+                    mirrored.set(x, y);
+                }
+            }
+        }
+        let (result, mirrored_witness) = decode_witness(&mirrored);
+        assert_eq!("RXing", result.getText());
+        assert!(mirrored_witness.scanline().unwrap().reversed);
+        assert!(mirrored_witness.verify().is_complete());
+
+        let (_, witness) = decode_witness(&encode_linear("5901234123457", BarcodeFormat::EAN_13));
+        let scanline = witness.scanline().unwrap();
+        // the first digit is in the parities of the next six, 9 is L G G L G L
+        let digits: Vec<u32> = scanline
+            .characters
+            .iter()
+            .flat_map(|c| c.value)
+            .map(|value| value % 10)
+            .collect();
+        assert_eq!(vec![9, 0, 1, 2, 3, 4, 1, 2, 3, 4, 5, 7], digits);
+        assert_eq!(Some(10), scanline.characters[1].value);
+        assert!(scanline.middle_pattern.is_some());
+
+        #[cfg(feature = "serde")]
+        {
+            let mut writer =
+                crate::WitnessWriter::new(Vec::new(), crate::WitnessFormat::Json).unwrap();
+            writer.write_witness(&witness).unwrap();
+            let json = String::from_utf8(writer.finish().unwrap()).unwrap();
+            let loaded = PipelineWitness::from_json(&json).unwrap();
+            assert_eq!(witness.scanline, loaded.scanline);
+        }
+    }
+
+    #[test]
+    fn test_fixed_point_sampling() {
+        let (_, witness) = decode_witness(&encode("fixed point", BarcodeFormat::QR_CODE));
+        let float = witness.sampling().unwrap().clone();
+        assert!(float.fixed_point.is_none());
+
+        let witness = witness.with_fixed_point_sampling(32).unwrap();
+        let fixed = witness.sampling().unwrap();
+        let transforms = fixed.fixed_point.as_ref().unwrap();
+        assert_eq!(float.grid.regions().len(), transforms.len());
+        assert!(transforms.iter().all(|t| t.frac_bits == 32));
+        assert_eq!(float.modules, fixed.modules);
+        assert!(witness.verify().sampling.is_passed());
+
+        #[cfg(feature = "serde")]
+        assert!(serde_json::to_string(fixed)
+            .unwrap()
+            .contains("\"fixed_point\":[{\"frac_bits\":32,\"coefficients\":["));
+
+        let mut tampered = witness.clone();
+        let transforms = tampered.sampling.as_mut().unwrap().fixed_point.as_mut();
+        transforms.unwrap()[0].coefficients[6] += 1;
+        assert!(tampered.verify().sampling.is_failed());
+
+        // nothing to sample without a grid
+        let empty = PipelineWitness::new()
+            .with_fixed_point_sampling(32)
+            .unwrap();
+        assert!(empty.sampling().is_none());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_load_compressed_witness() {
+        let (_, witness) = decode_witness(&encode("compressed witness", BarcodeFormat::QR_CODE));
+        let witness = witness.with_fixed_point_sampling(32).unwrap();
+        let json = |compression| {
+            with_matrix_compression(compression, || serde_json::to_string(&witness)).unwrap()
+        };
+
+        // the plain boolean arrays get their dimensions from the image and the grid
+        let plain = json(MatrixCompression::None);
+        let loaded = PipelineWitness::from_json(&plain).unwrap();
+        assert_eq!(format!("{witness:?}"), format!("{loaded:?}"));
+
+        for compression in [MatrixCompression::BitPacked, MatrixCompression::RunLength] {
+            let compressed = json(compression);
+            assert!(compressed.len() < plain.len() / 2, "{compression:?}");
+
+            let loaded = PipelineWitness::from_json(&compressed).unwrap();
+            assert_eq!(format!("{witness:?}"), format!("{loaded:?}"));
+            assert!(loaded.verify().is_valid());
+        }
+
+        #[cfg(feature = "cbor")]
+        for compression in [MatrixCompression::None, MatrixCompression::RunLength] {
+            let mut cbor = Vec::new();
+            witness
+                .write_cbor_with_compression(&mut cbor, compression)
+                .unwrap();
+            let loaded = PipelineWitness::read_cbor(cbor.as_slice()).unwrap();
+            assert_eq!(format!("{witness:?}"), format!("{loaded:?}"));
+        }
+    }
+
+    #[cfg(feature = "bincode")]
+    #[test]
+    fn test_bincode_witness() {
+        use crate::WITNESS_SCHEMA_VERSION;
+
+        let (_, witness) = decode_witness(&encode("bincode witness", BarcodeFormat::QR_CODE));
+        let witness = witness.with_fixed_point_sampling(32).unwrap();
+        let mut bytes = witness.to_bincode().unwrap();
+        assert_eq!(bytes[0] as u32, WITNESS_SCHEMA_VERSION);
+
+        let loaded = PipelineWitness::read_bincode(bytes.as_slice()).unwrap();
+        assert_eq!(format!("{witness:?}"), format!("{loaded:?}"));
+        assert!(loaded.verify().is_valid());
+
+        // one byte per grayscale pixel and one bit per binarized pixel, plus the later stages
+        let luminance = loaded.luminance().unwrap();
+        let pixels = luminance.width * luminance.height;
+        assert!(bytes.len() < pixels + pixels / 8 + 4096, "{}", bytes.len());
+
+        bytes[0] = WITNESS_SCHEMA_VERSION as u8 + 1;
+        let error = PipelineWitness::read_bincode(bytes.as_slice()).unwrap_err();
+        assert!(error.contains("version 3 is not supported"), "{error}");
+        bytes[0] = 1;
+        assert!(PipelineWitness::read_bincode(bytes.as_slice()).is_err());
+    }
+
+    #[test]
+    fn test_crop_to_symbol() {
+        use crate::{
+            common::FixedThresholdBinarizer, BinaryBitmap, DecodeHints, Luma8LuminanceSource,
+            MultiFormatReader, Reader,
+        };
+        use std::sync::{Arc, Mutex};
+
+        // a symbol with 3 pixels per module, in the lower right of a large frame
+        let symbol = encode("cropped witness", BarcodeFormat::QR_CODE);
+        let (width, height, left, top) = (400, 300, 250, 180);
+        let side = symbol.getWidth() * 3;
+        let mut luma = vec![255u8; (width * height) as usize];
+        for y in 0..side {
+            for x in 0..side {
+                if symbol.get(x / 3, y / 3) {
+                    luma[((top + y) * width + left + x) as usize] = 0;
+                }
+            }
+        }
+        let mut bitmap = BinaryBitmap::new(FixedThresholdBinarizer::new(
+            Luma8LuminanceSource::new(luma, width, height),
+        ));
+
+        let sink = Arc::new(Mutex::new(PipelineWitness::new()));
+        let hints = DecodeHints {
+            WitnessSink: Some(sink.clone()),
+            WitnessCropMargin: Some(6),
+            ..Default::default()
+        };
+        let result = MultiFormatReader::default()
+            .decode_with_hints(&mut bitmap, &hints)
+            .unwrap();
+        let full = crate::helpers::extract_witness_data_with_result(&bitmap, &result).unwrap();
+        let mut cropped = full.clone().crop_to_symbol(6).unwrap();
+        let mut streamed = sink.lock().unwrap().clone();
+        // the streamed provenance also holds the hints and the time the decode took
+        let hints = streamed.provenance.take().unwrap().hints;
+        assert!(hints.contains_key("WITNESS_CROP_MARGIN"));
+        cropped.provenance = None;
+        assert_eq!(format!("{cropped:?}"), format!("{streamed:?}"));
+
+        // the symbol and the margin around it, at the same place in both images
+        let region = cropped.symbol_region(0).unwrap();
+        assert!((left..left + 2).contains(&region.left), "{region:?}");
+        assert!((top..top + 2).contains(&region.top), "{region:?}");
+        assert!((side - 2..side + 2).contains(&region.width), "{region:?}");
+        let luminance = cropped.luminance().unwrap();
+        let binarization = cropped.binarization().unwrap();
+        assert_eq!(
+            Some(PointU::new(region.left - 6, region.top - 6)),
+            luminance.crop_offset
+        );
+        assert_eq!(luminance.extent(), binarization.extent());
+        assert_eq!(region.width as usize + 12, luminance.width);
+        let frame = full.luminance().unwrap();
+        assert!(luminance.image.len() * 10 < frame.image.len());
+        let corner = frame.get_pixel(region.left as usize, region.top as usize);
+        assert_eq!(corner, luminance.get_pixel(6, 6));
+
+        // everything still checks out against the full frame coordinates
+        assert!(cropped.verify().is_complete());
+        let mut shifted = cropped.clone();
+        shifted.binarization.as_mut().unwrap().crop_offset = Some(PointU::new(0, 0));
+        assert!(shifted.verify().binarization.is_failed());
+        assert!(shifted.verify().sampling.is_failed());
+
+        // the margin is clipped to the frame
+        let clipped = full.crop_to_symbol(1000).unwrap();
+        let luminance = clipped.luminance().unwrap();
+        assert_eq!(Some(PointU::new(0, 0)), luminance.crop_offset);
+        assert_eq!(width as usize, luminance.width);
+
+        #[cfg(feature = "serde")]
+        {
+            use crate::{WitnessFormat, WitnessWriter};
+
+            let mut writer = WitnessWriter::new(Vec::new(), WitnessFormat::Json).unwrap();
+            writer.write_witness(&cropped).unwrap();
+            let json = writer.finish().unwrap();
+            let streamed: serde_json::Value = serde_json::from_slice(&json).unwrap();
+            assert_eq!(streamed, serde_json::to_value(&cropped).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_block_thresholds() {
+        // 20 pixels in blocks of 8: the third block of a row starts at 12, not 16
+        let mut thresholds = BlockThresholds::new(20, 10, 8);
+        assert_eq!((3, 2), (thresholds.blocks_wide, thresholds.blocks_high));
+        for (i, threshold) in thresholds.thresholds.iter_mut().enumerate() {
+            *threshold = 10 * (i as u8 + 1);
+        }
+        // first row of blocks: 10, 20, 30; second row: 40, 50, 60
+        assert_eq!(10, thresholds.threshold_at(0, 0));
+        assert_eq!(20, thresholds.threshold_at(11, 0));
+        assert_eq!(30, thresholds.threshold_at(12, 0));
+        assert_eq!(30, thresholds.threshold_at(19, 1));
+        // rows 2 to 7 are covered by both rows of blocks
+        assert_eq!(40, thresholds.threshold_at(0, 2));
+        assert_eq!(60, thresholds.threshold_at(13, 9));
+        assert!(thresholds.is_black(0, 0, 10));
+        assert!(!thresholds.is_black(0, 0, 11));
+
+        let region = CropRegion {
+            left: 9,
+            top: 0,
+            width: 2,
+            height: 1,
+        };
+        let cleared = thresholds.clear_outside(&region);
+        assert_eq!(vec![0, 20, 0, 0, 0, 0], cleared.thresholds);
+    }
+}
@@ -0,0 +1,394 @@
+/*
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Filters that clean up a grayscale image before it is binarized.
+//!
+//! A [`Preprocessor`] applies a chain of [`Filter`]s to a luminance source, and can be added
+//! to a [`DecodePipeline`](crate::DecodePipeline) as middleware. The `TryPreprocessing` hint
+//! makes a `MultiFormatReader` that finds nothing try again on the images each chain of
+//! [`RETRY_CHAIN`] makes of the original.
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::common::Result;
+use crate::{DecodeHints, DecodeMiddleware, Luma8LuminanceSource, LuminanceSource};
+
+/// A filter over the luminance of an image, which keeps its size
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Filter {
+    /**
+     * Sharpens edges by adding `amount` times the difference between the image and a Gaussian
+     * blur of it with a standard deviation of `sigma` pixels. Undoes some of the blur of a
+     * camera out of focus, and raises the contrast of faint prints.
+     */
+    UnsharpMask { sigma: f32, amount: f32 },
+    /// Replaces every pixel with the median of the square `radius` pixels around it, which
+    /// removes speckle noise but keeps edges sharp
+    Median { radius: u32 },
+    /**
+     * Closes the dark parts of the image: every pixel first takes the darkest and then the
+     * lightest value of the square `radius` pixels around it. Light gaps and streaks narrower
+     * than `2 * radius + 1` pixels in bars and modules, such as a thermal printer leaves with
+     * a worn print head, are filled in. Light spaces that narrow are lost too.
+     */
+    Close { radius: u32 },
+}
+
+/**
+ * The filter chains the `TryPreprocessing` hint tries, in order, each on the original image:
+ * sharpening, denoising then sharpening, and closing gaps in the bars.
+ */
+pub const RETRY_CHAIN: &[&[Filter]] = &[
+    &[Filter::UnsharpMask {
+        sigma: 1.5,
+        amount: 1.5,
+    }],
+    &[
+        Filter::Median { radius: 1 },
+        Filter::UnsharpMask {
+            sigma: 1.5,
+            amount: 1.5,
+        },
+    ],
+    &[Filter::Close { radius: 1 }],
+];
+
+impl Filter {
+    /// Filters the `width` by `height` pixels of `luma`, row by row
+    pub fn apply(&self, luma: &[u8], width: usize, height: usize) -> Vec<u8> {
+        if luma.is_empty() || width == 0 || height == 0 {
+            return luma.to_vec();
+        }
+        match *self {
+            Filter::UnsharpMask { sigma, amount } => {
+                unsharp_mask(luma, width, height, sigma, amount)
+            }
+            Filter::Median { radius } => median(luma, width, height, radius as usize),
+            Filter::Close { radius } => {
+                let dilated = extremum(luma, width, height, radius as usize, u8::min);
+                extremum(&dilated, width, height, radius as usize, u8::max)
+            }
+        }
+    }
+}
+
+/**
+ * A chain of filters applied one after another:
+ *
+ * ```
+ * use rxing::{preprocess::{Filter, Preprocessor}, Luma8LuminanceSource, LuminanceSource};
+ *
+ * // a dark bar with a one pixel light streak across it
+ * let mut luma = vec![255u8; 7 * 7];
+ * for y in (0..7).filter(|&y| y != 3) {
+ *     luma[y * 7 + 2..y * 7 + 5].fill(0);
+ * }
+ * let source = Luma8LuminanceSource::new(luma, 7, 7);
+ *
+ * let closed = Preprocessor::new()
+ *     .then(Filter::Close { radius: 1 })
+ *     .apply(&source);
+ * assert_eq!(0, closed.get_luma8_point(3, 3));
+ * ```
+ */
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Preprocessor {
+    filters: Vec<Filter>,
+}
+
+impl Preprocessor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Applies `filter` after the filters added so far
+    pub fn then(mut self, filter: Filter) -> Self {
+        self.filters.push(filter);
+        self
+    }
+
+    pub fn filters(&self) -> &[Filter] {
+        &self.filters
+    }
+
+    /// A filtered copy of `source`; a copy of its luminance without filters
+    pub fn apply<L: LuminanceSource>(&self, source: &L) -> Luma8LuminanceSource {
+        let (width, height) = (source.get_width(), source.get_height());
+        let luma = self
+            .filters
+            .iter()
+            .fold(source.get_matrix(), |luma, filter| {
+                filter.apply(&luma, width, height)
+            });
+        Luma8LuminanceSource::new(luma, width as u32, height as u32)
+    }
+}
+
+impl From<&[Filter]> for Preprocessor {
+    fn from(filters: &[Filter]) -> Self {
+        Self {
+            filters: filters.to_vec(),
+        }
+    }
+}
+
+impl DecodeMiddleware for Preprocessor {
+    fn pre_binarize(
+        &self,
+        source: &mut Luma8LuminanceSource,
+        _hints: &mut DecodeHints,
+    ) -> Result<()> {
+        *source = self.apply(source);
+        Ok(())
+    }
+}
+
+fn unsharp_mask(luma: &[u8], width: usize, height: usize, sigma: f32, amount: f32) -> Vec<u8> {
+    if sigma <= 0.0 {
+        return luma.to_vec();
+    }
+    let radius = (3.0 * sigma).ceil() as isize;
+    let mut kernel: Vec<f32> = (-radius..=radius)
+        .map(|i| (-((i * i) as f32) / (2.0 * sigma * sigma)).exp())
+        .collect();
+    let sum: f32 = kernel.iter().sum();
+    kernel.iter_mut().for_each(|k| *k /= sum);
+
+    // separable, first along the rows then down the columns, repeating the edge pixels
+    let convolve =
+        |input: &[f32], output: &mut [f32], step: usize, length: usize, stride: usize| {
+            for line in 0..input.len() / length {
+                let start = line * stride;
+                for i in 0..length {
+                    output[start + i * step] = kernel
+                        .iter()
+                        .enumerate()
+                        .map(|(k, weight)| {
+                            let j =
+                                (i as isize + k as isize - radius).clamp(0, length as isize - 1);
+                            weight * input[start + j as usize * step]
+                        })
+                        .sum();
+                }
+            }
+        };
+    let original: Vec<f32> = luma.iter().map(|&l| l as f32).collect();
+    let mut rows = vec![0.0; original.len()];
+    convolve(&original, &mut rows, 1, width, width);
+    let mut blurred = vec![0.0; original.len()];
+    convolve(&rows, &mut blurred, width, height, 1);
+
+    original
+        .iter()
+        .zip(blurred)
+        .map(|(l, blur)| (l + amount * (l - blur)).round().clamp(0.0, 255.0) as u8)
+        .collect()
+}
+
+fn median(luma: &[u8], width: usize, height: usize, radius: usize) -> Vec<u8> {
+    if radius == 0 {
+        return luma.to_vec();
+    }
+    let mut window = Vec::with_capacity((2 * radius + 1) * (2 * radius + 1));
+    let mut filtered = Vec::with_capacity(luma.len());
+    for y in 0..height {
+        for x in 0..width {
+            window.clear();
+            for wy in y.saturating_sub(radius)..(y + radius + 1).min(height) {
+                let row = &luma[wy * width..(wy + 1) * width];
+                window
+                    .extend_from_slice(&row[x.saturating_sub(radius)..(x + radius + 1).min(width)]);
+            }
+            let middle = window.len() / 2;
+            filtered.push(*window.select_nth_unstable(middle).1);
+        }
+    }
+    filtered
+}
+
+/// Every pixel combined with `pick` over the square `radius` pixels around it
+fn extremum(
+    luma: &[u8],
+    width: usize,
+    height: usize,
+    radius: usize,
+    pick: fn(u8, u8) -> u8,
+) -> Vec<u8> {
+    if radius == 0 {
+        return luma.to_vec();
+    }
+    // separable, along the rows then down the columns
+    let mut rows = vec![0; luma.len()];
+    for y in 0..height {
+        let row = &luma[y * width..(y + 1) * width];
+        for x in 0..width {
+            rows[y * width + x] = row[x.saturating_sub(radius)..(x + radius + 1).min(width)]
+                .iter()
+                .copied()
+                .reduce(pick)
+                .unwrap_or(row[x]);
+        }
+    }
+    let mut filtered = vec![0; luma.len()];
+    for y in 0..height {
+        for x in 0..width {
+            filtered[y * width + x] = (y.saturating_sub(radius)..(y + radius + 1).min(height))
+                .map(|wy| rows[wy * width + x])
+                .reduce(pick)
+                .unwrap_or(rows[y * width + x]);
+        }
+    }
+    filtered
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        common::HybridBinarizer, BarcodeFormat, BinaryBitmap, DecodeHints, Luma8LuminanceSource,
+        MultiFormatReader, MultiFormatWriter, Reader, Writer,
+    };
+
+    use super::{Filter, Preprocessor};
+
+    #[test]
+    fn test_median_removes_speckles() {
+        let mut luma = vec![200u8; 5 * 5];
+        luma[12] = 0;
+        luma[0] = 255;
+        let filtered = Filter::Median { radius: 1 }.apply(&luma, 5, 5);
+        assert!(filtered.iter().all(|&l| l == 200), "{filtered:?}");
+    }
+
+    #[test]
+    fn test_unsharp_mask() {
+        // a soft edge from dark to light between columns 3 and 6
+        let row = [60u8, 60, 60, 80, 120, 160, 180, 180, 180, 180];
+        let luma = row.repeat(4);
+        let sharpened = Filter::UnsharpMask {
+            sigma: 1.0,
+            amount: 1.0,
+        }
+        .apply(&luma, 10, 4);
+        // flat areas away from the edge are left alone, the edge is steeper
+        assert_eq!(luma[0], sharpened[0]);
+        assert_eq!(luma[8..10], sharpened[8..10]);
+        assert!(
+            sharpened[3] < luma[3] && sharpened[5] > luma[5],
+            "{sharpened:?}"
+        );
+        assert_eq!(
+            luma,
+            Filter::UnsharpMask {
+                sigma: 0.0,
+                amount: 1.0
+            }
+            .apply(&luma, 10, 4)
+        );
+    }
+
+    #[test]
+    fn test_close_fills_gaps() {
+        // dark bars 3 pixels wide, 3 apart, with a light pixel in the first and a one pixel
+        // wide light streak down the middle of the second
+        let mut luma = vec![255u8; 15 * 5];
+        for y in 0..5 {
+            luma[y * 15 + 3..y * 15 + 6].fill(0);
+            luma[y * 15 + 9..y * 15 + 12].fill(0);
+            luma[y * 15 + 10] = 255;
+        }
+        luma[2 * 15 + 4] = 255;
+        let closed = Filter::Close { radius: 1 }.apply(&luma, 15, 5);
+        for y in 0..5 {
+            let row = &closed[y * 15..(y + 1) * 15];
+            assert_eq!(
+                [255, 255, 255, 0, 0, 0, 255, 255, 255, 0, 0, 0, 255, 255, 255],
+                row,
+                "row {y}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_chain() {
+        let luma: Vec<u8> = (0..64).map(|i| (i * 4) as u8).collect();
+        let source = Luma8LuminanceSource::new(luma.clone(), 8, 8);
+        assert_eq!(
+            luma,
+            crate::LuminanceSource::get_matrix(&Preprocessor::new().apply(&source))
+        );
+        let chain = Preprocessor::new()
+            .then(Filter::Median { radius: 1 })
+            .then(Filter::Close { radius: 1 });
+        let expected = Filter::Close { radius: 1 }.apply(
+            &Filter::Median { radius: 1 }.apply(&luma, 8, 8),
+            8,
+            8,
+        );
+        assert_eq!(
+            expected,
+            crate::LuminanceSource::get_matrix(&chain.apply(&source))
+        );
+        assert_eq!(chain, Preprocessor::from(chain.filters()));
+    }
+
+    #[test]
+    fn test_try_preprocessing() {
+        // a faint QR code of 4 pixel modules, gray on gray and slightly blurred
+        let matrix = MultiFormatWriter
+            .encode("thermal label", &BarcodeFormat::QR_CODE, 0, 0)
+            .expect("encode");
+        let (width, height) = (
+            matrix.getWidth() as usize * 4 + 40,
+            matrix.getHeight() as usize * 4 + 40,
+        );
+        let mut luma = vec![140u8; width * height];
+        for y in 0..matrix.getHeight() as usize * 4 {
+            for x in 0..matrix.getWidth() as usize * 4 {
+                if matrix.get(x as u32 / 4, y as u32 / 4) {
+                    luma[(y + 20) * width + x + 20] = 100;
+                }
+            }
+        }
+        let mut blurred = luma.clone();
+        for y in 1..height - 1 {
+            for x in 1..width - 1 {
+                let sum: u32 = (y - 1..=y + 1)
+                    .flat_map(|wy| &luma[wy * width + x - 1..=wy * width + x + 1])
+                    .map(|&l| l as u32)
+                    .sum();
+                blurred[y * width + x] = (sum / 9) as u8;
+            }
+        }
+
+        let decode = |try_preprocessing: bool| {
+            let mut image = BinaryBitmap::new(HybridBinarizer::new(Luma8LuminanceSource::new(
+                blurred.clone(),
+                width as u32,
+                height as u32,
+            )));
+            MultiFormatReader::default().decode_with_hints(
+                &mut image,
+                &DecodeHints {
+                    TryPreprocessing: Some(try_preprocessing),
+                    ..Default::default()
+                },
+            )
+        };
+        assert!(decode(false).is_err());
+        assert_eq!("thermal label", decode(true).expect("decode").getText());
+    }
+}
@@ -0,0 +1,1252 @@
+//! Print quality grading after ISO/IEC 15415 for 2D symbols and ISO/IEC 15416 for linear
+//! symbols.
+//!
+//! A verifier grades a symbol from its reflectance, measured under calibrated light through an
+//! aperture sized to the symbol. These functions grade from the luminance of an image instead,
+//! taking a pixel's value as its reflectance in percent of 255. The parameters and thresholds
+//! are those of the standards, but the grades are only as good as the image: a photo that is
+//! lit unevenly, out of focus or heavily compressed grades a good symbol down. They are meant
+//! for watching the print quality of a line, not for certifying symbols.
+//!
+//! ```
+//! use rxing::{print_quality, BarcodeFormat, DecodeHints, MultiFormatWriter, Writer};
+//!
+//! let symbol = MultiFormatWriter.encode("label", &BarcodeFormat::QR_CODE, 200, 200)?;
+//! let (width, height) = (symbol.getWidth(), symbol.getHeight());
+//! let report = print_quality::grade_in_luma(
+//!     symbol.to_luma8(),
+//!     width,
+//!     height,
+//!     None,
+//!     &mut DecodeHints::default(),
+//! )?;
+//! assert_eq!(print_quality::Grade::A, report.overall());
+//! # Ok::<(), rxing::Exceptions>(())
+//! ```
+
+use std::{collections::HashSet, fmt};
+
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+use crate::{
+    common::{
+        CodewordWitness, FixedThresholdBinarizer, PerspectiveTransform, Quadrilateral, Result,
+    },
+    helpers, point, BarcodeFormat, BinaryBitmap, DecodeHints, DetectionWitness, Exceptions,
+    Luma8LuminanceSource, LuminanceWitness, MultiFormatReader, PipelineWitness, Point, RXingResult,
+    Reader, SamplingWitness,
+};
+
+// The thresholds of grades A to D, a value below the last grades F
+const MATRIX_SYMBOL_CONTRAST: [f32; 4] = [70.0, 55.0, 40.0, 20.0];
+const MATRIX_MODULATION: [f32; 4] = [0.50, 0.40, 0.30, 0.20];
+const UNUSED_ERROR_CORRECTION: [f32; 4] = [0.62, 0.50, 0.37, 0.25];
+// The thresholds of grades A to D, a value above the last grades F
+const AXIAL_NONUNIFORMITY: [f32; 4] = [0.06, 0.08, 0.10, 0.12];
+const GRID_NONUNIFORMITY: [f32; 4] = [0.38, 0.50, 0.63, 0.75];
+const FIXED_PATTERN_DAMAGE: [f32; 4] = [0.0, 1.0, 2.0, 3.0];
+
+const LINEAR_SYMBOL_CONTRAST: [f32; 4] = [70.0, 55.0, 40.0, 20.0];
+const LINEAR_MODULATION: [f32; 4] = [0.70, 0.60, 0.50, 0.40];
+const DEFECTS: [f32; 4] = [0.15, 0.20, 0.25, 0.30];
+const DECODABILITY: [f32; 4] = [0.62, 0.50, 0.37, 0.25];
+const MIN_EDGE_CONTRAST: f32 = 15.0;
+
+/// How many scan lines ISO 15416 grades a linear symbol from
+const SCANS: usize = 10;
+
+/// A print quality grade, ordered from F to A
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Grade {
+    F,
+    D,
+    C,
+    B,
+    A,
+}
+
+impl Grade {
+    /// The numeric grade, from 4 for A down to 0 for F
+    pub fn value(self) -> u8 {
+        self as u8
+    }
+
+    /// The letter grade of an average of numeric grades, as a linear symbol is graded from
+    /// its scans
+    pub fn from_average(average: f32) -> Self {
+        match average {
+            a if a >= 3.5 => Grade::A,
+            a if a >= 2.5 => Grade::B,
+            a if a >= 1.5 => Grade::C,
+            a if a >= 0.5 => Grade::D,
+            _ => Grade::F,
+        }
+    }
+
+    // The grade of a parameter where higher values are better
+    fn at_least(value: f32, thresholds: [f32; 4]) -> Self {
+        let passed = thresholds.iter().filter(|&&t| value >= t).count();
+        Self::from_passed(passed)
+    }
+
+    // The grade of a parameter where lower values are better
+    fn at_most(value: f32, thresholds: [f32; 4]) -> Self {
+        let passed = thresholds.iter().filter(|&&t| value <= t).count();
+        Self::from_passed(passed)
+    }
+
+    fn from_passed(passed: usize) -> Self {
+        [Grade::F, Grade::D, Grade::C, Grade::B, Grade::A][passed]
+    }
+
+    // Grades a parameter that either meets its limit or fails
+    fn pass_fail(passed: bool) -> Self {
+        if passed {
+            Grade::A
+        } else {
+            Grade::F
+        }
+    }
+}
+
+impl fmt::Display for Grade {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let letter = match self {
+            Grade::A => "A",
+            Grade::B => "B",
+            Grade::C => "C",
+            Grade::D => "D",
+            Grade::F => "F",
+        };
+        write!(f, "{letter}")
+    }
+}
+
+/// A measured parameter and its grade
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ParameterGrade {
+    pub value: f32,
+    pub grade: Grade,
+}
+
+impl ParameterGrade {
+    fn new(value: f32, grade: Grade) -> Self {
+        Self { value, grade }
+    }
+}
+
+/**
+ * The ISO 15415 grades of a 2D symbol.
+ *
+ * # Fields
+ * * `decode` - A, the symbol was decoded to be graded
+ * * `symbol_contrast` - The difference between the highest and lowest reflectance in the
+ *   symbol and the ring of quiet zone around it, in percent
+ * * `modulation` - The lowest modulation of a module, how far its reflectance is from the
+ *   global threshold relative to the symbol contrast. The grade weighs the modules of each
+ *   grade against the unused error correction, as the standard does; lacking the codeword each
+ *   module belongs to, every module is counted as a codeword, which grades conservatively
+ * * `fixed_pattern_damage` - The number of finder, separator and timing modules read on the
+ *   wrong side of the global threshold, for QR codes, Micro QR codes and the bull's eye of
+ *   Aztec codes
+ * * `axial_nonuniformity` - How much the module pitch along the two axes differs, relative to
+ *   their mean
+ * * `grid_nonuniformity` - How far the farthest module center is from where an undistorted
+ *   grid through the corner modules puts it, in modules. Grids fitted to alignment patterns
+ *   measure distortion within the symbol, grids of a single transform always grade A
+ * * `unused_error_correction` - The share of error correction the most damaged block left
+ *   unused, when the reader recorded the codewords
+ * * `overall` - The lowest of the grades
+ */
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatrixQualityReport {
+    pub decode: Grade,
+    pub symbol_contrast: ParameterGrade,
+    pub modulation: ParameterGrade,
+    pub fixed_pattern_damage: Option<ParameterGrade>,
+    pub axial_nonuniformity: ParameterGrade,
+    pub grid_nonuniformity: ParameterGrade,
+    pub unused_error_correction: Option<ParameterGrade>,
+    pub overall: Grade,
+}
+
+/**
+ * The ISO 15416 grades of one scan reflectance profile of a linear symbol.
+ *
+ * # Fields
+ * * `decode` - A, the symbol was decoded to be graded
+ * * `minimum_reflectance` - The lowest reflectance, which must be at most half the highest
+ * * `symbol_contrast` - The difference between the highest and lowest reflectance, in percent
+ * * `minimum_edge_contrast` - The lowest difference between adjacent bars and spaces, in
+ *   percent, which must be at least 15
+ * * `modulation` - The minimum edge contrast relative to the symbol contrast
+ * * `defects` - The largest reflectance nonuniformity within a bar or space, relative to the
+ *   symbol contrast
+ * * `decodability` - The margin the bars and spaces leave before one would be read as another
+ *   width, from 0 to 1. Symbologies of narrow and wide elements, such as Code 39 and ITF, are
+ *   measured against the threshold between the two widths, others against whole modules. The
+ *   widths are measured from the profile rather than the decoding algorithm of each symbology
+ * * `edge_determination` - The number of edges in the profile, which passes if it matches the
+ *   scan through the middle of the symbol
+ * * `grade` - The lowest of the grades
+ */
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScanGrade {
+    pub decode: Grade,
+    pub minimum_reflectance: ParameterGrade,
+    pub symbol_contrast: ParameterGrade,
+    pub minimum_edge_contrast: ParameterGrade,
+    pub modulation: ParameterGrade,
+    pub defects: ParameterGrade,
+    pub decodability: ParameterGrade,
+    pub edge_determination: ParameterGrade,
+    pub grade: Grade,
+}
+
+/**
+ * The ISO 15416 grades of a linear symbol, from scans spread over the middle 80% of its
+ * height. The `overall` value is the average of the numeric scan grades.
+ */
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct LinearQualityReport {
+    pub scans: Vec<ScanGrade>,
+    pub overall: ParameterGrade,
+}
+
+/// The grades of a symbol, after the standard for its kind
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub enum PrintQualityReport {
+    Matrix(MatrixQualityReport),
+    Linear(LinearQualityReport),
+}
+
+impl PrintQualityReport {
+    pub fn overall(&self) -> Grade {
+        match self {
+            PrintQualityReport::Matrix(report) => report.overall,
+            PrintQualityReport::Linear(report) => report.overall.grade,
+        }
+    }
+}
+
+/**
+ * Decodes a grayscale image and grades the symbol found in it.
+ *
+ * # Arguments
+ * * `luma` - The image, one byte per pixel and row-major
+ * * `barcode_type` - The format to look for, or `None` for any
+ *
+ * # Returns
+ * The grades, `NotFoundException` if no symbol decodes or `UnsupportedOperationException` if
+ * the symbol is a 2D format whose reader does not record its sampling grid, such as PDF417
+ */
+pub fn grade_in_luma(
+    luma: Vec<u8>,
+    width: u32,
+    height: u32,
+    barcode_type: Option<BarcodeFormat>,
+    hints: &mut DecodeHints,
+) -> Result<PrintQualityReport> {
+    let (_, witness) = decode_witness(luma, width, height, barcode_type, hints)?;
+    grade_symbol(&witness)
+}
+
+fn decode_witness(
+    luma: Vec<u8>,
+    width: u32,
+    height: u32,
+    barcode_type: Option<BarcodeFormat>,
+    hints: &mut DecodeHints,
+) -> Result<(RXingResult, PipelineWitness)> {
+    if let Some(bc_type) = barcode_type {
+        hints.PossibleFormats = Some(HashSet::from([bc_type]));
+    }
+
+    hints.TryHarder = hints.TryHarder.or(Some(true));
+
+    let mut bitmap = BinaryBitmap::new(FixedThresholdBinarizer::new(Luma8LuminanceSource::new(
+        luma, width, height,
+    )));
+    let result = MultiFormatReader::default().decode_with_hints(&mut bitmap, hints)?;
+    let witness = helpers::extract_witness_data_with_result(&bitmap, &result)?;
+    Ok((result, witness))
+}
+
+/**
+ * Grades the symbol of a witness, which must hold the luminance and detection stages, and the
+ * sampling stage for a 2D symbol. The codewords of the decoding stage grade unused error
+ * correction and weigh modulation.
+ */
+pub fn grade_symbol(witness: &PipelineWitness) -> Result<PrintQualityReport> {
+    let (Some(luminance), Some(detection)) = (witness.luminance(), witness.detection()) else {
+        return Err(Exceptions::illegal_argument_with(
+            "grading needs the luminance and detection stages",
+        ));
+    };
+
+    if let Some(sampling) = witness.sampling() {
+        let report = grade_matrix(luminance, detection, sampling, witness.decoding())?;
+        Ok(PrintQualityReport::Matrix(report))
+    } else if detection.format.is_1d() {
+        let report = grade_linear(luminance, detection)?;
+        Ok(PrintQualityReport::Linear(report))
+    } else {
+        Err(Exceptions::unsupported_operation_with(format!(
+            "{} symbols can't be graded, their reader does not record a sampling grid",
+            detection.format
+        )))
+    }
+}
+
+fn grade_matrix(
+    luminance: &LuminanceWitness,
+    detection: &DetectionWitness,
+    sampling: &SamplingWitness,
+    decoding: Option<&CodewordWitness>,
+) -> Result<MatrixQualityReport> {
+    let grid = &sampling.grid;
+    let (width, height) = (grid.width() as i32, grid.height() as i32);
+    let pitch = grid.module_size();
+    let reflectance = |x: i32, y: i32| {
+        let center = grid.module_to_image(point(x as f32 + 0.5, y as f32 + 0.5));
+        aperture_reflectance(luminance, center, 0.4 * pitch)
+    };
+
+    let mut modules = Vec::with_capacity((width * height) as usize);
+    for y in 0..height {
+        for x in 0..width {
+            modules.push(reflectance(x, y).unwrap_or_default());
+        }
+    }
+    // the quiet zone is measured in the ring of modules around the symbol
+    let ring = (-1..=width)
+        .flat_map(|x| [(x, -1), (x, height)])
+        .chain((0..height).flat_map(|y| [(-1, y), (width, y)]));
+    let quiet_zone: Vec<f32> = ring.filter_map(|(x, y)| reflectance(x, y)).collect();
+
+    let all = modules.iter().chain(&quiet_zone);
+    let r_max = all.clone().fold(f32::MIN, |a, &b| a.max(b));
+    let r_min = all.fold(f32::MAX, |a, &b| a.min(b));
+    let contrast = r_max - r_min;
+    let threshold = (r_max + r_min) / 2.0;
+    let symbol_contrast =
+        ParameterGrade::new(contrast, Grade::at_least(contrast, MATRIX_SYMBOL_CONTRAST));
+
+    // a module on the wrong side of the threshold is an error, with no modulation
+    let modulation: Vec<f32> = modules
+        .iter()
+        .enumerate()
+        .map(|(i, &r)| {
+            let dark = sampling.get_module(i % width as usize, i / width as usize);
+            if contrast > 0.0 && (r < threshold) == dark {
+                2.0 * (r - threshold).abs() / contrast
+            } else {
+                0.0
+            }
+        })
+        .collect();
+    let lowest_modulation = modulation.iter().fold(1.0f32, |a, &b| a.min(b));
+
+    let error_correction = decoding.filter(|d| !d.blocks.is_empty()).map(|decoding| {
+        let mut unused = 1.0f32;
+        let (mut errors, mut ec_codewords) = (0, 0);
+        for block in &decoding.blocks {
+            let block_ec = block.received.len() - block.num_data_codewords;
+            let block_errors = block.correction.errors_corrected();
+            let block_erasures = block.correction.erasures_corrected();
+            unused = unused.min(unused_error_correction(
+                block_errors,
+                block_erasures,
+                block_ec,
+            ));
+            errors += block_errors;
+            ec_codewords += block_ec;
+        }
+        (unused, errors, ec_codewords)
+    });
+    let modulation_grade = match error_correction {
+        Some((_, errors, ec_codewords)) => [Grade::A, Grade::B, Grade::C, Grade::D]
+            .into_iter()
+            .map(|level| {
+                let below = modulation
+                    .iter()
+                    .filter(|&&m| Grade::at_least(m, MATRIX_MODULATION) < level)
+                    .count();
+                let unused = unused_error_correction(errors, below, ec_codewords);
+                level.min(Grade::at_least(unused, UNUSED_ERROR_CORRECTION))
+            })
+            .max()
+            .unwrap_or(Grade::F),
+        None => Grade::at_least(lowest_modulation, MATRIX_MODULATION),
+    };
+    let unused_error_correction = error_correction.map(|(unused, _, _)| {
+        ParameterGrade::new(unused, Grade::at_least(unused, UNUSED_ERROR_CORRECTION))
+    });
+
+    let fixed_pattern_damage =
+        fixed_pattern_modules(&detection.format, width, height).map(|pattern| {
+            let damaged = pattern
+                .into_iter()
+                .filter(|&(x, y, dark)| (modules[(y * width + x) as usize] < threshold) != dark)
+                .count() as f32;
+            ParameterGrade::new(damaged, Grade::at_most(damaged, FIXED_PATTERN_DAMAGE))
+        });
+
+    let center = |x: i32, y: i32| grid.module_center(x as u32, y as u32);
+    let x_pitch =
+        center(0, height / 2).distance(center(width - 1, height / 2)) / (width - 1) as f32;
+    let y_pitch =
+        center(width / 2, 0).distance(center(width / 2, height - 1)) / (height - 1) as f32;
+    let axial = (x_pitch - y_pitch).abs() / ((x_pitch + y_pitch) / 2.0);
+    let axial_nonuniformity =
+        ParameterGrade::new(axial, Grade::at_most(axial, AXIAL_NONUNIFORMITY));
+
+    let (right, bottom) = ((width - 1) as u32, (height - 1) as u32);
+    let corners = [(0, 0), (right, 0), (right, bottom), (0, bottom)];
+    let corners = corners.map(|(x, y)| grid.module_center(x, y));
+    let (right, bottom) = (right as f32 + 0.5, bottom as f32 + 0.5);
+    let ideal = PerspectiveTransform::quadrilateralToQuadrilateral(
+        Quadrilateral::rectangle_from_xy(0.5, right, 0.5, bottom, Some(0.0)),
+        Quadrilateral::from(corners),
+    )?;
+    let mut deviation = 0.0f32;
+    for y in 0..height as u32 {
+        for x in 0..width as u32 {
+            let expected = ideal.transform_point(point(x as f32 + 0.5, y as f32 + 0.5));
+            deviation = deviation.max(expected.distance(grid.module_center(x, y)));
+        }
+    }
+    let grid_deviation = deviation / ((x_pitch + y_pitch) / 2.0);
+    let grid_nonuniformity = ParameterGrade::new(
+        grid_deviation,
+        Grade::at_most(grid_deviation, GRID_NONUNIFORMITY),
+    );
+
+    let modulation = ParameterGrade::new(lowest_modulation, modulation_grade);
+    let overall = [
+        symbol_contrast.grade,
+        modulation.grade,
+        axial_nonuniformity.grade,
+        grid_nonuniformity.grade,
+    ]
+    .into_iter()
+    .chain(fixed_pattern_damage.map(|p| p.grade))
+    .chain(unused_error_correction.map(|p| p.grade))
+    .min()
+    .unwrap_or(Grade::A);
+
+    Ok(MatrixQualityReport {
+        decode: Grade::A,
+        symbol_contrast,
+        modulation,
+        fixed_pattern_damage,
+        axial_nonuniformity,
+        grid_nonuniformity,
+        unused_error_correction,
+        overall,
+    })
+}
+
+// The share of error correction left after `errors` errors and `erasures` erasures, which
+// cost two and one error correction codewords
+fn unused_error_correction(errors: usize, erasures: usize, ec_codewords: usize) -> f32 {
+    if ec_codewords == 0 {
+        return 0.0;
+    }
+    (1.0 - (2 * errors + erasures) as f32 / ec_codewords as f32).max(0.0)
+}
+
+// The mean reflectance of the pixels within `radius` of `center`, or of the pixel under it
+// for modules too small to hold one. None outside the image.
+fn aperture_reflectance(luminance: &LuminanceWitness, center: Point, radius: f32) -> Option<f32> {
+    let offset = luminance.crop_offset.map_or(point(0.0, 0.0), Point::from);
+    let center = center - offset;
+    let (width, height) = (luminance.width as f32, luminance.height as f32);
+    if center.x < 0.0 || center.y < 0.0 || center.x >= width || center.y >= height {
+        return None;
+    }
+
+    let (mut sum, mut count) = (0u32, 0u32);
+    let (left, right) = (
+        (center.x - radius).max(0.0),
+        (center.x + radius).min(width - 1.0),
+    );
+    let (top, bottom) = (
+        (center.y - radius).max(0.0),
+        (center.y + radius).min(height - 1.0),
+    );
+    for y in top as usize..=bottom as usize {
+        for x in left as usize..=right as usize {
+            if point(x as f32 + 0.5, y as f32 + 0.5).distance(center) <= radius {
+                sum += luminance.get_pixel(x, y) as u32;
+                count += 1;
+            }
+        }
+    }
+    if count == 0 {
+        sum = luminance.get_pixel(center.x as usize, center.y as usize) as u32;
+        count = 1;
+    }
+    Some(sum as f32 / count as f32 * 100.0 / 255.0)
+}
+
+// The modules of the finder, separator and timing patterns and whether each is dark
+fn fixed_pattern_modules(
+    format: &BarcodeFormat,
+    width: i32,
+    height: i32,
+) -> Option<Vec<(i32, i32, bool)>> {
+    let mut pattern = Vec::new();
+    // a finder pattern and the separator around it, within the symbol
+    let mut finder = |left: i32, top: i32| {
+        for y in top - 1..=top + 7 {
+            for x in left - 1..=left + 7 {
+                if (0..width).contains(&x) && (0..height).contains(&y) {
+                    let ring = (x - left - 3).abs().max((y - top - 3).abs());
+                    pattern.push((x, y, ring != 2 && ring < 4));
+                }
+            }
+        }
+    };
+
+    match format {
+        BarcodeFormat::QR_CODE if width == height && width >= 21 => {
+            finder(0, 0);
+            finder(width - 7, 0);
+            finder(0, height - 7);
+            for i in 8..width - 8 {
+                pattern.push((i, 6, i % 2 == 0));
+                pattern.push((6, i, i % 2 == 0));
+            }
+        }
+        BarcodeFormat::MICRO_QR_CODE if width == height && width >= 11 => {
+            finder(0, 0);
+            for i in 8..width {
+                pattern.push((i, 0, i % 2 == 0));
+                pattern.push((0, i, i % 2 == 0));
+            }
+        }
+        // the rings of the bull's eye compact and full range symbols share
+        BarcodeFormat::AZTEC if width == height && width >= 15 => {
+            let middle = width / 2;
+            for y in middle - 4..=middle + 4 {
+                for x in middle - 4..=middle + 4 {
+                    let ring = (x - middle).abs().max((y - middle).abs());
+                    pattern.push((x, y, ring % 2 == 0));
+                }
+            }
+        }
+        _ => return None,
+    }
+    Some(pattern)
+}
+
+fn grade_linear(
+    luminance: &LuminanceWitness,
+    detection: &DetectionWitness,
+) -> Result<LinearQualityReport> {
+    let (Some(&start), Some(&end)) = (detection.points.first(), detection.points.last()) else {
+        return Err(Exceptions::illegal_argument_with(
+            "grading a linear symbol needs the points of its scan line",
+        ));
+    };
+    let length = start.distance(end);
+    if length < 2.0 {
+        return Err(Exceptions::illegal_argument_with(
+            "the scan line of the symbol is too short to grade",
+        ));
+    }
+    let direction = (end - start) / length;
+    let normal = point(-direction.y, direction.x);
+
+    // the reflectance along the scan line moved by `offset` across it, extended by `margin`
+    // into the quiet zones
+    let profile = |offset: f32, margin: f32| -> Vec<f32> {
+        let origin = start + normal * offset - direction * margin;
+        (0..(length + 2.0 * margin) as usize)
+            .filter_map(|i| aperture_reflectance(luminance, origin + direction * i as f32, 0.0))
+            .collect()
+    };
+
+    // the bars run across the scan line as far as each parallel line crosses as many edges
+    let center = profile(0.0, 0.0);
+    let edges = elements(&center).len();
+    let narrowest = elements(&center)
+        .iter()
+        .map(|(_, reflectance)| reflectance.len())
+        .min()
+        .unwrap_or(1);
+    let extent = |sign: f32| {
+        (1..)
+            .take_while(|&i| {
+                let row = profile(sign * i as f32, 0.0);
+                row.len() == center.len() && elements(&row).len() == edges
+            })
+            .last()
+            .unwrap_or(0) as f32
+    };
+    let (above, below) = (extent(-1.0), extent(1.0));
+    let bar_height = above + below;
+
+    let offsets: Vec<f32> = if bar_height < SCANS as f32 {
+        (0..=bar_height as usize)
+            .map(|i| i as f32 - above)
+            .collect()
+    } else {
+        (0..SCANS)
+            .map(|i| -above + bar_height * (0.1 + 0.8 * i as f32 / (SCANS - 1) as f32))
+            .collect()
+    };
+    let margin = 10.0 * narrowest as f32;
+    let expected_edges = elements(&profile(0.0, margin)).len().saturating_sub(1);
+    let two_widths = matches!(
+        detection.format,
+        BarcodeFormat::CODE_39
+            | BarcodeFormat::ITF
+            | BarcodeFormat::CODABAR
+            | BarcodeFormat::MSI
+            | BarcodeFormat::PHARMACODE
+            | BarcodeFormat::TELEPEN
+    );
+    let scans: Vec<ScanGrade> = offsets
+        .into_iter()
+        .map(|offset| grade_scan(&profile(offset, margin), expected_edges, two_widths))
+        .collect();
+
+    let average = scans
+        .iter()
+        .map(|scan| scan.grade.value() as f32)
+        .sum::<f32>()
+        / scans.len() as f32;
+    Ok(LinearQualityReport {
+        scans,
+        overall: ParameterGrade::new(average, Grade::from_average(average)),
+    })
+}
+
+fn grade_scan(profile: &[f32], expected_edges: usize, two_widths: bool) -> ScanGrade {
+    let r_max = profile.iter().fold(f32::MIN, |a, &b| a.max(b));
+    let r_min = profile.iter().fold(f32::MAX, |a, &b| a.min(b));
+    let contrast = r_max - r_min;
+
+    let elements = elements(profile);
+    // a space reflects at its brightest, a bar at its darkest
+    let reflectances: Vec<f32> = elements
+        .iter()
+        .map(|&(space, values)| {
+            let fold = if space { f32::max } else { f32::min };
+            values.iter().copied().reduce(fold).unwrap_or_default()
+        })
+        .collect();
+    let edge_contrast = reflectances
+        .windows(2)
+        .map(|pair| (pair[0] - pair[1]).abs())
+        .fold(f32::MAX, f32::min)
+        .min(contrast);
+    let nonuniformity = elements
+        .iter()
+        .map(|&(space, values)| element_nonuniformity(space, values))
+        .fold(0.0, f32::max);
+
+    let ratio = |value: f32| {
+        if contrast > 0.0 {
+            value / contrast
+        } else {
+            0.0
+        }
+    };
+    let minimum_reflectance = ParameterGrade::new(r_min, Grade::pass_fail(r_min <= r_max / 2.0));
+    let symbol_contrast =
+        ParameterGrade::new(contrast, Grade::at_least(contrast, LINEAR_SYMBOL_CONTRAST));
+    let minimum_edge_contrast = ParameterGrade::new(
+        edge_contrast,
+        Grade::pass_fail(edge_contrast >= MIN_EDGE_CONTRAST),
+    );
+    let modulation = ratio(edge_contrast);
+    let modulation =
+        ParameterGrade::new(modulation, Grade::at_least(modulation, LINEAR_MODULATION));
+    let defects = ratio(nonuniformity);
+    let defects = ParameterGrade::new(defects, Grade::at_most(defects, DEFECTS));
+    // the quiet zones on either side are not elements of the symbol
+    let widths: Vec<f32> = elements
+        .iter()
+        .skip(1)
+        .take(elements.len().saturating_sub(2))
+        .map(|(_, values)| values.len() as f32)
+        .collect();
+    let decodability = decodability(&widths, two_widths);
+    let decodability =
+        ParameterGrade::new(decodability, Grade::at_least(decodability, DECODABILITY));
+    let edges = elements.len().saturating_sub(1);
+    let edge_determination =
+        ParameterGrade::new(edges as f32, Grade::pass_fail(edges == expected_edges));
+
+    let grade = [
+        minimum_reflectance.grade,
+        symbol_contrast.grade,
+        minimum_edge_contrast.grade,
+        modulation.grade,
+        defects.grade,
+        decodability.grade,
+        edge_determination.grade,
+    ]
+    .into_iter()
+    .min()
+    .unwrap_or(Grade::F);
+
+    ScanGrade {
+        decode: Grade::A,
+        minimum_reflectance,
+        symbol_contrast,
+        minimum_edge_contrast,
+        modulation,
+        defects,
+        decodability,
+        edge_determination,
+        grade,
+    }
+}
+
+// How far the widest departure of an element from its nominal width is from the threshold
+// that would read it as another width, relative to the distance between the thresholds
+fn decodability(widths: &[f32], two_widths: bool) -> f32 {
+    let narrowest = widths.iter().copied().fold(f32::MAX, f32::min);
+    let widest = widths.iter().copied().fold(0.0, f32::max);
+    if widths.is_empty() || narrowest <= 0.0 {
+        return 0.0;
+    }
+    let mean = |wide: bool| {
+        let split = (narrowest + widest) / 2.0;
+        let class: Vec<f32> = widths
+            .iter()
+            .copied()
+            .filter(|&w| (w >= split) == wide)
+            .collect();
+        class.iter().sum::<f32>() / class.len() as f32
+    };
+
+    if two_widths && widest > narrowest {
+        let (narrow, wide) = (mean(false), mean(true));
+        let threshold = (narrow + wide) / 2.0;
+        let margin = widths
+            .iter()
+            .map(|w| (w - threshold).abs())
+            .fold(f32::MAX, f32::min);
+        (margin / (threshold - narrow)).clamp(0.0, 1.0)
+    } else {
+        // the module from the narrowest elements, refined over the width of the symbol
+        let estimate = widths
+            .iter()
+            .copied()
+            .filter(|&w| w < 1.5 * narrowest)
+            .sum::<f32>()
+            / widths.iter().filter(|&&w| w < 1.5 * narrowest).count() as f32;
+        let total = widths.iter().sum::<f32>();
+        let modules = widths.iter().map(|w| (w / estimate).round()).sum::<f32>();
+        let module = total / modules;
+        let deviation = widths
+            .iter()
+            .map(|w| (w / module - (w / module).round()).abs())
+            .fold(0.0, f32::max);
+        1.0 - 2.0 * deviation
+    }
+}
+
+// Splits a profile at the global threshold into its spaces and bars, each with whether it is
+// a space and its reflectance values
+fn elements(profile: &[f32]) -> Vec<(bool, &[f32])> {
+    let r_max = profile.iter().fold(f32::MIN, |a, &b| a.max(b));
+    let r_min = profile.iter().fold(f32::MAX, |a, &b| a.min(b));
+    let threshold = (r_max + r_min) / 2.0;
+
+    profile
+        .chunk_by(|a, b| (*a >= threshold) == (*b >= threshold))
+        .map(|values| (values[0] >= threshold, values))
+        .collect()
+}
+
+// The depth of the deepest valley within a space, or the height of the highest peak within a
+// bar. The edges on either side are not part of the element, so the values are trimmed to run
+// from its first to its last extreme.
+fn element_nonuniformity(space: bool, values: &[f32]) -> f32 {
+    let towards_extreme = |a: f32, b: f32| if space { b >= a } else { b <= a };
+    let mut first = 0;
+    while first + 1 < values.len() && towards_extreme(values[first], values[first + 1]) {
+        first += 1;
+    }
+    let mut last = values.len() - 1;
+    while last > first && towards_extreme(values[last], values[last - 1]) {
+        last -= 1;
+    }
+
+    let interior = &values[first..=last];
+    let high = interior.iter().fold(f32::MIN, |a, &b| a.max(b));
+    let low = interior.iter().fold(f32::MAX, |a, &b| a.min(b));
+    high - low
+}
+
+/**
+ * Where a symbol lies in the image.
+ *
+ * # Fields
+ * * `center` - The center of the symbol, in image pixels
+ * * `rotation` - The angle of the symbol's horizontal axis, or the scan line of a linear
+ *   symbol, clockwise from the image's, in degrees from -180 to 180
+ * * `skew` - How far the angle between the axes of a 2D symbol is from a right angle, in
+ *   degrees
+ * * `module_size` - The module pitch of a 2D symbol, in pixels
+ */
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SymbolGeometry {
+    pub center: Point,
+    pub rotation: f32,
+    pub skew: Option<f32>,
+    pub module_size: Option<f32>,
+}
+
+impl SymbolGeometry {
+    /// The geometry of the symbol of a witness, from its sampling grid or its scan line
+    pub fn from_witness(witness: &PipelineWitness) -> Option<Self> {
+        let angle = |from: Point, to: Point| (to.y - from.y).atan2(to.x - from.x).to_degrees();
+
+        if let Some(sampling) = witness.sampling() {
+            let grid = &sampling.grid;
+            let (width, height) = (grid.width() as f32, grid.height() as f32);
+            let at = |x: f32, y: f32| grid.module_to_image(point(x, y));
+            let (left, right) = (at(0.5, height / 2.0), at(width - 0.5, height / 2.0));
+            let (top, bottom) = (at(width / 2.0, 0.5), at(width / 2.0, height - 0.5));
+            let rotation = angle(left, right);
+            let skew = normalize_degrees(angle(top, bottom) - rotation - 90.0);
+            return Some(Self {
+                center: at(width / 2.0, height / 2.0),
+                rotation,
+                skew: Some(skew),
+                module_size: Some(grid.module_size()),
+            });
+        }
+
+        let points = &witness.detection()?.points;
+        let (&start, &end) = (points.first()?, points.last()?);
+        Some(Self {
+            center: start.middle(end),
+            rotation: angle(start, end),
+            skew: None,
+            module_size: None,
+        })
+    }
+}
+
+fn normalize_degrees(degrees: f32) -> f32 {
+    let degrees = degrees.rem_euclid(360.0);
+    if degrees > 180.0 {
+        degrees - 360.0
+    } else {
+        degrees
+    }
+}
+
+/**
+ * What a print-and-verify station checks a symbol against, see `verify`. The geometry checks
+ * are skipped when their expectation is `None`.
+ */
+#[derive(Clone)]
+pub struct VerifyOptions {
+    pub hints: DecodeHints,
+    /// The lowest overall grade that passes. Symbols of formats that can't be graded are
+    /// not failed on their grade
+    pub minimum_grade: Grade,
+    /// Where the center of the symbol should be, in image pixels
+    pub expected_center: Option<Point>,
+    /// How far the center may be from `expected_center`, in pixels
+    pub max_center_offset: f32,
+    /// The largest rotation, either way, in degrees
+    pub max_rotation: Option<f32>,
+    /// The module pitch a 2D symbol should have, in pixels
+    pub expected_module_size: Option<f32>,
+    /// How far the module pitch may be from `expected_module_size`, relative to it
+    pub module_size_tolerance: f32,
+}
+
+impl Default for VerifyOptions {
+    fn default() -> Self {
+        Self {
+            hints: DecodeHints::default(),
+            minimum_grade: Grade::C,
+            expected_center: None,
+            max_center_offset: 10.0,
+            max_rotation: None,
+            expected_module_size: None,
+            module_size_tolerance: 0.1,
+        }
+    }
+}
+
+/// A check a symbol failed in `verify`
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub enum VerificationFailure {
+    /// No symbol of the expected format was decoded, and why
+    NotDecoded(String),
+    /// The symbol holds other content than expected
+    ContentMismatch { expected: String, decoded: String },
+    /// The overall grade is below `VerifyOptions::minimum_grade`
+    GradeBelowMinimum { grade: Grade, minimum: Grade },
+    /// The center is farther from where it should be than allowed, by `offset` pixels
+    Misplaced { offset: f32 },
+    /// The symbol is rotated further than allowed, by `rotation` degrees
+    Rotated { rotation: f32 },
+    /// The module pitch is further from the expected one than allowed
+    ModuleSize { module_size: f32, expected: f32 },
+}
+
+/**
+ * The outcome of `verify`: what was decoded, its grades and geometry, and every check it
+ * failed.
+ */
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct VerificationReport {
+    pub result: Option<RXingResult>,
+    pub quality: Option<PrintQualityReport>,
+    pub geometry: Option<SymbolGeometry>,
+    pub failures: Vec<VerificationFailure>,
+}
+
+impl VerificationReport {
+    pub fn passed(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+/**
+ * Verifies a printed symbol: decodes it, compares its content to what was meant to be
+ * printed, grades its print quality and checks where it lies, as a print-and-verify station
+ * on a production line does with every label.
+ *
+ * # Arguments
+ * * `image` - The image of the printed symbol
+ * * `expected_content` - The text the symbol should hold
+ * * `format` - The format the symbol should have, other formats are not decoded
+ * * `options` - The grade and geometry to check against
+ */
+#[cfg(feature = "image")]
+pub fn verify(
+    image: &image::DynamicImage,
+    expected_content: &str,
+    format: BarcodeFormat,
+    options: &VerifyOptions,
+) -> VerificationReport {
+    let luma = image.to_luma8();
+    let (width, height) = luma.dimensions();
+    verify_luma(
+        luma.into_raw(),
+        width,
+        height,
+        expected_content,
+        format,
+        options,
+    )
+}
+
+/// Like [`verify`], for a grayscale image, one byte per pixel and row-major
+pub fn verify_luma(
+    luma: Vec<u8>,
+    width: u32,
+    height: u32,
+    expected_content: &str,
+    format: BarcodeFormat,
+    options: &VerifyOptions,
+) -> VerificationReport {
+    let mut hints = options.hints.clone();
+    let (result, witness) = match decode_witness(luma, width, height, Some(format), &mut hints) {
+        Ok(decoded) => decoded,
+        Err(e) => {
+            return VerificationReport {
+                result: None,
+                quality: None,
+                geometry: None,
+                failures: vec![VerificationFailure::NotDecoded(e.to_string())],
+            }
+        }
+    };
+
+    let mut failures = Vec::new();
+    if result.getText() != expected_content {
+        failures.push(VerificationFailure::ContentMismatch {
+            expected: expected_content.to_owned(),
+            decoded: result.getText().to_owned(),
+        });
+    }
+
+    // a format that can't be graded is reported without grades
+    let quality = grade_symbol(&witness).ok();
+    if let Some(grade) = quality.as_ref().map(PrintQualityReport::overall) {
+        if grade < options.minimum_grade {
+            failures.push(VerificationFailure::GradeBelowMinimum {
+                grade,
+                minimum: options.minimum_grade,
+            });
+        }
+    }
+
+    let geometry = SymbolGeometry::from_witness(&witness);
+    if let Some(geometry) = geometry {
+        if let Some(expected) = options.expected_center {
+            let offset = geometry.center.distance(expected);
+            if offset > options.max_center_offset {
+                failures.push(VerificationFailure::Misplaced { offset });
+            }
+        }
+        if let Some(max_rotation) = options.max_rotation {
+            if geometry.rotation.abs() > max_rotation {
+                let rotation = geometry.rotation;
+                failures.push(VerificationFailure::Rotated { rotation });
+            }
+        }
+        if let (Some(expected), Some(module_size)) =
+            (options.expected_module_size, geometry.module_size)
+        {
+            if (module_size - expected).abs() > expected * options.module_size_tolerance {
+                failures.push(VerificationFailure::ModuleSize {
+                    module_size,
+                    expected,
+                });
+            }
+        }
+    }
+
+    VerificationReport {
+        result: Some(result),
+        quality,
+        geometry,
+        failures,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        test_fixtures::scaled_luma, EncodeHintValue, EncodeHints, MultiFormatWriter, Writer,
+    };
+
+    // The symbol drawn with `dark` and `light` luma, `scale` pixels per module
+    fn render(
+        contents: &str,
+        format: BarcodeFormat,
+        scale: u32,
+        dark: u8,
+        light: u8,
+    ) -> (Vec<u8>, u32, u32) {
+        // the margin of a linear symbol is split over both sides
+        let (margin, height) = if format.is_1d() { ("20", 10) } else { ("4", 0) };
+        let hints = EncodeHints::default().with(EncodeHintValue::Margin(margin.to_owned()));
+        let symbol = MultiFormatWriter
+            .encode_with_hints(contents, &format, 0, height, &hints)
+            .unwrap();
+        scaled_luma(&symbol, scale, dark, light)
+    }
+
+    fn grade(luma: Vec<u8>, width: u32, height: u32) -> PrintQualityReport {
+        grade_in_luma(luma, width, height, None, &mut DecodeHints::default()).unwrap()
+    }
+
+    #[test]
+    fn test_grades() {
+        assert!(Grade::A > Grade::B && Grade::D > Grade::F);
+        assert_eq!(Grade::B, Grade::at_least(0.45, MATRIX_MODULATION));
+        assert_eq!(Grade::F, Grade::at_least(0.1, MATRIX_MODULATION));
+        assert_eq!(Grade::A, Grade::at_most(0.0, FIXED_PATTERN_DAMAGE));
+        assert_eq!(Grade::C, Grade::at_most(0.09, AXIAL_NONUNIFORMITY));
+        assert_eq!(Grade::C, Grade::from_average(2.4));
+        assert_eq!("B", Grade::B.to_string());
+    }
+
+    #[test]
+    fn test_grade_qr_code() {
+        let (luma, width, height) = render("print quality", BarcodeFormat::QR_CODE, 4, 0, 255);
+        let PrintQualityReport::Matrix(report) = grade(luma, width, height) else {
+            panic!("a QR code is graded after ISO 15415");
+        };
+        assert_eq!(Grade::A, report.overall);
+        assert!((report.symbol_contrast.value - 100.0).abs() < 0.01);
+        assert_eq!(Some(0.0), report.fixed_pattern_damage.map(|p| p.value));
+        assert_eq!(Some(1.0), report.unused_error_correction.map(|p| p.value));
+
+        // grey on grey reads, but with little contrast
+        let (luma, width, height) = render("print quality", BarcodeFormat::QR_CODE, 4, 90, 150);
+        let report = grade(luma, width, height);
+        let PrintQualityReport::Matrix(matrix) = &report else {
+            panic!("a QR code is graded after ISO 15415");
+        };
+        assert_eq!(Grade::D, matrix.symbol_contrast.grade);
+        assert_eq!(Grade::D, report.overall());
+    }
+
+    #[test]
+    fn test_grade_damaged_qr_code() {
+        let (mut luma, width, height) = render("print quality", BarcodeFormat::QR_CODE, 4, 0, 255);
+        // a light module in the dark core of the top left finder pattern, 4 modules of margin
+        // and 2 modules in
+        for y in 24..28 {
+            for x in 24..28 {
+                luma[(y * width + x) as usize] = 255;
+            }
+        }
+        let PrintQualityReport::Matrix(report) = grade(luma, width, height) else {
+            panic!("a QR code is graded after ISO 15415");
+        };
+        let damage = report.fixed_pattern_damage.unwrap();
+        assert_eq!((1.0, Grade::B), (damage.value, damage.grade));
+        assert_eq!(Grade::B, report.overall);
+    }
+
+    #[test]
+    fn test_grade_aztec() {
+        let (luma, width, height) = render("print quality", BarcodeFormat::AZTEC, 4, 0, 255);
+        let PrintQualityReport::Matrix(report) = grade(luma, width, height) else {
+            panic!("an Aztec code is graded after ISO 15415");
+        };
+        assert_eq!(Some(Grade::A), report.fixed_pattern_damage.map(|p| p.grade));
+        assert_eq!(Grade::A, report.overall);
+    }
+
+    #[test]
+    fn test_grade_data_matrix() {
+        let (luma, width, height) = render("print quality", BarcodeFormat::DATA_MATRIX, 4, 0, 255);
+        let PrintQualityReport::Matrix(report) = grade(luma, width, height) else {
+            panic!("a Data Matrix symbol is graded after ISO 15415");
+        };
+        assert_eq!(Grade::A, report.overall);
+    }
+
+    #[test]
+    fn test_grade_linear() {
+        let (luma, width, height) = render("5901234123457", BarcodeFormat::EAN_13, 3, 0, 255);
+        let PrintQualityReport::Linear(report) = grade(luma, width, height) else {
+            panic!("an EAN-13 symbol is graded after ISO 15416");
+        };
+        assert_eq!(SCANS, report.scans.len());
+        assert_eq!(Grade::A, report.overall.grade);
+        assert!((report.scans[0].symbol_contrast.value - 100.0).abs() < 0.01);
+        assert_eq!(1.0, report.scans[0].decodability.value);
+        // 30 bars, each with two edges
+        assert_eq!(60.0, report.scans[0].edge_determination.value);
+
+        // a spot in every bar and space of the middle of the symbol
+        let (mut luma, width, height) = render("5901234123457", BarcodeFormat::EAN_13, 3, 0, 255);
+        for y in 0..height {
+            for x in (30..width - 30).step_by(3) {
+                let pixel = &mut luma[(y * width + x + 1) as usize];
+                *pixel = if *pixel == 0 { 75 } else { 180 };
+            }
+        }
+        let PrintQualityReport::Linear(report) = grade(luma, width, height) else {
+            panic!("an EAN-13 symbol is graded after ISO 15416");
+        };
+        let defects = report.scans[0].defects;
+        assert_eq!(Grade::D, defects.grade, "defects of {}", defects.value);
+        assert_eq!(Grade::D, report.overall.grade);
+    }
+
+    #[test]
+    fn test_decodability() {
+        // modules of 3 pixels, one bar a pixel too wide
+        let widths = [3.0, 6.0, 3.0, 9.0, 4.0, 3.0];
+        assert!((decodability(&widths, false) - 0.43).abs() < 0.01);
+        assert_eq!(1.0, decodability(&[3.0, 6.0, 12.0], false));
+        // narrow and wide elements at 3 to 1, one wide element a pixel short
+        let widths = [3.0, 9.0, 3.0, 8.0, 3.0];
+        assert!((decodability(&widths, true) - 0.82).abs() < 0.01);
+        assert_eq!(0.0, decodability(&[], true));
+    }
+
+    #[test]
+    fn test_grade_unsupported() {
+        let (luma, width, height) = render("print quality", BarcodeFormat::PDF_417, 4, 0, 255);
+        let graded = grade_in_luma(luma, width, height, None, &mut DecodeHints::default());
+        assert!(matches!(
+            graded,
+            Err(Exceptions::UnsupportedOperationException(_))
+        ));
+
+        let blank = vec![255; 100 * 100];
+        let graded = grade_in_luma(blank, 100, 100, None, &mut DecodeHints::default());
+        assert!(matches!(graded, Err(Exceptions::NotFoundException(_))));
+    }
+
+    #[test]
+    fn test_verify() {
+        use crate::point;
+
+        let (luma, width, height) = render("LOT 4411", BarcodeFormat::QR_CODE, 4, 0, 255);
+        let options = VerifyOptions {
+            expected_center: Some(point(width as f32 / 2.0, height as f32 / 2.0)),
+            max_rotation: Some(5.0),
+            expected_module_size: Some(4.0),
+            ..Default::default()
+        };
+        let qr = BarcodeFormat::QR_CODE;
+        let report = verify_luma(
+            luma.clone(),
+            width,
+            height,
+            "LOT 4411",
+            qr.clone(),
+            &options,
+        );
+        assert!(report.passed(), "{:?}", report.failures);
+        let geometry = report.geometry.unwrap();
+        assert!(geometry.rotation.abs() < 0.5 && geometry.skew.unwrap().abs() < 0.5);
+        assert_eq!(Some(Grade::A), report.quality.map(|q| q.overall()));
+
+        let report = verify_luma(
+            luma.clone(),
+            width,
+            height,
+            "LOT 4412",
+            qr.clone(),
+            &options,
+        );
+        assert_eq!(
+            vec![VerificationFailure::ContentMismatch {
+                expected: "LOT 4412".to_owned(),
+                decoded: "LOT 4411".to_owned()
+            }],
+            report.failures
+        );
+
+        let aztec = BarcodeFormat::AZTEC;
+        let report = verify_luma(luma.clone(), width, height, "LOT 4411", aztec, &options);
+        assert!(matches!(
+            report.failures[..],
+            [VerificationFailure::NotDecoded(_)]
+        ));
+
+        // turned a quarter clockwise, and checked against a smaller module
+        let turned = (0..width)
+            .flat_map(|y| (0..height).map(move |x| (x, y)))
+            .map(|(x, y)| luma[((height - 1 - x) * width + y) as usize])
+            .collect();
+        let options = VerifyOptions {
+            expected_module_size: Some(3.0),
+            ..options
+        };
+        let report = verify_luma(turned, height, width, "LOT 4411", qr, &options);
+        assert!(matches!(
+            report.failures[..],
+            [
+                VerificationFailure::Rotated { rotation },
+                VerificationFailure::ModuleSize { .. }
+            ] if (rotation - 90.0).abs() < 0.5
+        ));
+    }
+}
@@ -0,0 +1,230 @@
+use std::cmp::Reverse;
+
+use crate::common::{HybridBinarizer, Result};
+use crate::filtered_image_reader::LumImagePyramid;
+use crate::{
+    point, symbol_pose, Binarizer, BinaryBitmap, DecodeHints, Exceptions, Luma8LuminanceSource,
+    LuminanceSource, RXingResult, RXingResultMetadataType, RXingResultMetadataValue, Reader,
+    DEFAULT_DOWNSCALE_FACTOR, DEFAULT_DOWNSCALE_THRESHHOLD,
+};
+
+/**
+ * Looks for a symbol in copies of the image scaled down by a constant factor, like the
+ * downscaling of ZXing-C++, when the `TryDownscale` hint is set. Without the hint the image
+ * is passed to the wrapped reader unchanged.
+ *
+ * The image is scaled down until its longer side is no longer than the threshold, and the
+ * copies are tried from the smallest to the full resolution image. The points and sampling
+ * grid of a symbol found in a copy are mapped back to the coordinates of the full image.
+ */
+pub struct PyramidReader<R: Reader> {
+    reader: R,
+    threshold: usize,
+    factor: usize,
+}
+
+impl<R: Reader> PyramidReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            threshold: DEFAULT_DOWNSCALE_THRESHHOLD,
+            factor: DEFAULT_DOWNSCALE_FACTOR,
+        }
+    }
+
+    /// Scale down images whose longer side is more than `threshold` pixels, 500 by default
+    pub fn with_threshold(mut self, threshold: usize) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    /// Scale each copy down by `factor`, which is 2, 3 or 4; 3 by default
+    pub fn with_factor(mut self, factor: usize) -> Self {
+        self.factor = factor;
+        self
+    }
+
+    fn decode_pyramid<B: Binarizer>(
+        &mut self,
+        image: &mut BinaryBitmap<B>,
+        hints: &DecodeHints,
+    ) -> Result<RXingResult> {
+        if !(2..=4).contains(&self.factor) {
+            return Err(Exceptions::illegal_argument_with(format!(
+                "downscale factor {} is not 2, 3 or 4",
+                self.factor
+            )));
+        }
+        let source = image.get_source();
+        let mut layers = LumImagePyramid::new(
+            Luma8LuminanceSource::new(
+                source.get_matrix(),
+                source.get_width() as u32,
+                source.get_height() as u32,
+            ),
+            self.threshold,
+            self.factor,
+        )
+        .ok_or(Exceptions::ILLEGAL_ARGUMENT)?
+        .layers;
+        layers.sort_by_key(|layer| Reverse(layer.get_width()));
+
+        // layer n is the image scaled down by factor^n, the full image is decoded last with
+        // the caller's binarizer
+        for (level, layer) in layers.into_iter().enumerate().skip(1).rev() {
+            hints.check_cancelled()?;
+            let mut bitmap = BinaryBitmap::new(HybridBinarizer::new(layer));
+            if let Ok(result) = self.reader.decode_with_hints(&mut bitmap, hints) {
+                return Ok(scale_result(result, self.factor.pow(level as u32) as f32));
+            }
+        }
+        hints.check_cancelled()?;
+        self.reader.decode_with_hints(image, hints)
+    }
+}
+
+impl<R: Reader> Reader for PyramidReader<R> {
+    fn decode<B: Binarizer>(&mut self, image: &mut BinaryBitmap<B>) -> Result<RXingResult> {
+        self.decode_with_hints(image, &DecodeHints::default())
+    }
+
+    fn decode_with_hints<B: Binarizer>(
+        &mut self,
+        image: &mut BinaryBitmap<B>,
+        hints: &DecodeHints,
+    ) -> Result<RXingResult> {
+        if hints.TryDownscale.unwrap_or(false) {
+            self.decode_pyramid(image, hints)
+        } else {
+            self.reader.decode_with_hints(image, hints)
+        }
+    }
+
+    fn reset(&mut self) {
+        self.reader.reset();
+    }
+}
+
+/// Maps the points, sampling grid and pose of a result found in a scaled down image to the image
+/// `factor` times larger
+fn scale_result(mut result: RXingResult, factor: f32) -> RXingResult {
+    for p in result.getPointsMut() {
+        *p = point(p.x * factor, p.y * factor);
+    }
+    if let Some(RXingResultMetadataValue::SamplingGrid(grid)) = result
+        .getRXingResultMetadata()
+        .get(&RXingResultMetadataType::SAMPLING_GRID)
+    {
+        let grid = grid.scaled(factor);
+        result.putMetadata(
+            RXingResultMetadataType::SAMPLING_GRID,
+            RXingResultMetadataValue::SamplingGrid(grid),
+        );
+        symbol_pose::put_pose(&mut result);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::{
+        common::HybridBinarizer, point, BarcodeFormat, BinaryBitmap, DecodeHints, EncodeHints,
+        Exceptions, Luma8LuminanceSource, MultiFormatReader, MultiFormatWriter, Point, RXingResult,
+        RXingResultMetadataType, RXingResultMetadataValue, Reader, Writer,
+    };
+
+    use super::PyramidReader;
+
+    // a version 1 QR code with 24 pixel modules, at (300, 200) in a 1500 by 1200 image
+    fn decode(hints: &DecodeHints) -> RXingResult {
+        let matrix = MultiFormatWriter
+            .encode_with_hints(
+                "pyramid",
+                &BarcodeFormat::QR_CODE,
+                0,
+                0,
+                &EncodeHints {
+                    Margin: Some("0".to_owned()),
+                    ..Default::default()
+                },
+            )
+            .expect("encode");
+        let (width, height) = (1500, 1200);
+        let mut luma = vec![255; width * height];
+        for y in 0..matrix.getHeight() * 24 {
+            for x in 0..matrix.getWidth() * 24 {
+                if matrix.get(x / 24, y / 24) {
+                    luma[(200 + y as usize) * width + 300 + x as usize] = 0;
+                }
+            }
+        }
+        let mut image = BinaryBitmap::new(HybridBinarizer::new(Luma8LuminanceSource::new(
+            luma,
+            width as u32,
+            height as u32,
+        )));
+        PyramidReader::new(MultiFormatReader::default())
+            .decode_with_hints(&mut image, hints)
+            .expect("decode")
+    }
+
+    #[test]
+    fn test_points_map_to_full_image() {
+        let mut hints = DecodeHints {
+            PossibleFormats: Some(HashSet::from([BarcodeFormat::QR_CODE])),
+            ..Default::default()
+        };
+        let full = decode(&hints);
+        hints.TryDownscale = Some(true);
+        let downscaled = decode(&hints);
+        assert_eq!("pyramid", downscaled.getText());
+
+        let corner = point(300.0, 200.0);
+        for result in [&full, &downscaled] {
+            assert!(result
+                .getPoints()
+                .iter()
+                .any(|p| Point::distance(*p, corner) < 9.0));
+        }
+
+        let Some(RXingResultMetadataValue::SamplingGrid(grid)) = downscaled
+            .getRXingResultMetadata()
+            .get(&RXingResultMetadataType::SAMPLING_GRID)
+        else {
+            panic!("no sampling grid");
+        };
+        assert!(
+            (grid.module_size() - 24.0).abs() < 1.5,
+            "{}",
+            grid.module_size()
+        );
+        let Some(RXingResultMetadataValue::Pose(pose)) = downscaled
+            .getRXingResultMetadata()
+            .get(&RXingResultMetadataType::POSE)
+        else {
+            panic!("no pose");
+        };
+        assert_eq!(grid.module_size(), pose.module_size);
+    }
+
+    #[test]
+    fn test_invalid_factor() {
+        let hints = DecodeHints {
+            TryDownscale: Some(true),
+            ..Default::default()
+        };
+        let mut image = BinaryBitmap::new(HybridBinarizer::new(Luma8LuminanceSource::new(
+            vec![255; 600 * 600],
+            600,
+            600,
+        )));
+        assert!(matches!(
+            PyramidReader::new(MultiFormatReader::default())
+                .with_factor(5)
+                .decode_with_hints(&mut image, &hints),
+            Err(Exceptions::IllegalArgumentException(_))
+        ));
+    }
+}
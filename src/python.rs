@@ -0,0 +1,379 @@
+//! PyO3 bindings, enabled by the `python` feature.
+//!
+//! The `rxing-python` crate (in `crates/python`) builds these into the `rxing` Python
+//! extension module with maturin. [`register`] adds the functions and classes to a module.
+//!
+//! Images are accepted through the buffer protocol, so `numpy` arrays, `bytes`,
+//! `bytearray` and `memoryview` all work without a numpy dependency:
+//! * a 2-D `uint8` array of shape `(height, width)` is read as grayscale;
+//! * a 3-D `uint8` array of shape `(height, width, channels)` with 1, 3 (RGB) or 4 (RGBA)
+//!   channels is converted to grayscale;
+//! * flat buffers need `width` and `height`; the channel count is inferred from the length.
+//!
+//! Hints are dicts using the field names of [`DecodeHints`] and [`EncodeHints`], for example
+//! `{"TryHarder": True, "PossibleFormats": ["QR_CODE"]}`. Decoding releases the GIL.
+//!
+//! ```python
+//! import numpy as np
+//! import rxing
+//!
+//! barcode = rxing.decode(np.asarray(image), format="qrcode")
+//! if barcode is not None:
+//!     print(barcode.format, barcode.text)
+//! symbol = rxing.encode("hello", "qrcode", 200, 200)
+//! pixels = np.frombuffer(symbol.data, np.uint8).reshape(symbol.height, symbol.width)
+//! ```
+
+use std::io::Cursor;
+
+use pyo3::{
+    buffer::PyBuffer,
+    create_exception,
+    exceptions::{PyException, PyValueError},
+    prelude::*,
+    types::{PyBytes, PyDict},
+};
+
+use crate::{
+    common::{simd, BitMatrix, Result},
+    helpers, BarcodeFormat, DecodeHints, EncodeHints, Exceptions, MultiFormatWriter, RXingResult,
+    Writer,
+};
+
+create_exception!(
+    rxing,
+    RxingError,
+    PyException,
+    "Raised when rxing fails to decode or encode"
+);
+
+fn to_py_err(error: Exceptions) -> PyErr {
+    match error {
+        Exceptions::IllegalArgumentException(_) | Exceptions::ParseException(_) => {
+            PyValueError::new_err(error.to_string())
+        }
+        _ => RxingError::new_err(error.to_string()),
+    }
+}
+
+/// A decoded barcode
+#[pyclass(frozen, skip_from_py_object, module = "rxing")]
+#[derive(Debug, Clone)]
+pub struct Barcode {
+    text: String,
+    format: BarcodeFormat,
+    raw_bytes: Vec<u8>,
+    points: Vec<(f32, f32)>,
+}
+
+#[pymethods]
+impl Barcode {
+    #[getter]
+    fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// The barcode format, for example `"qrcode"`
+    #[getter]
+    fn format(&self) -> String {
+        self.format.to_string()
+    }
+
+    #[getter]
+    fn raw_bytes<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new(py, &self.raw_bytes)
+    }
+
+    /// The result points as a list of `(x, y)` tuples
+    #[getter]
+    fn points(&self) -> Vec<(f32, f32)> {
+        self.points.clone()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Barcode(format={:?}, text={:?})", self.format(), self.text)
+    }
+}
+
+impl From<RXingResult> for Barcode {
+    fn from(result: RXingResult) -> Self {
+        Self {
+            text: result.getText().to_owned(),
+            format: result.getBarcodeFormat().clone(),
+            raw_bytes: result.getRawBytes().to_vec(),
+            points: result.getPoints().iter().map(|p| (p.x, p.y)).collect(),
+        }
+    }
+}
+
+/// An encoded symbol as 8 bit grayscale pixels, 0 for dark and 255 for light modules
+#[pyclass(frozen, skip_from_py_object, module = "rxing")]
+#[derive(Debug, Clone)]
+pub struct EncodedBarcode {
+    matrix: BitMatrix,
+}
+
+#[pymethods]
+impl EncodedBarcode {
+    #[getter]
+    fn width(&self) -> u32 {
+        self.matrix.getWidth()
+    }
+
+    #[getter]
+    fn height(&self) -> u32 {
+        self.matrix.getHeight()
+    }
+
+    /// The pixels, row by row
+    #[getter]
+    fn data<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new(py, &self.matrix.to_luma8())
+    }
+
+    fn to_svg(&self) -> String {
+        svg::Document::from(&self.matrix).to_string()
+    }
+
+    fn to_png<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        let png = png_bytes(&self.matrix).map_err(to_py_err)?;
+        Ok(PyBytes::new(py, &png))
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "EncodedBarcode(width={}, height={})",
+            self.width(),
+            self.height()
+        )
+    }
+}
+
+/// Decode a single barcode, returning `None` if no barcode is found
+#[pyfunction]
+#[pyo3(signature = (image, width=None, height=None, format=None, hints=None))]
+fn decode(
+    py: Python<'_>,
+    image: &Bound<'_, PyAny>,
+    width: Option<u32>,
+    height: Option<u32>,
+    format: Option<&str>,
+    hints: Option<&Bound<'_, PyDict>>,
+) -> PyResult<Option<Barcode>> {
+    let (luma, width, height) = luma_from_buffer(py, image, width, height)?;
+    let mut hints: DecodeHints = hints_from_dict(py, hints)?.unwrap_or_default();
+    let format = format.map(parse_format).transpose()?;
+
+    match py.detach(|| helpers::detect_in_luma_with_hints(luma, width, height, format, &mut hints))
+    {
+        Ok(result) => Ok(Some(result.into())),
+        Err(Exceptions::NotFoundException(_)) => Ok(None),
+        Err(e) => Err(to_py_err(e)),
+    }
+}
+
+/// Decode every barcode found in an image
+#[pyfunction]
+#[pyo3(signature = (image, width=None, height=None, hints=None))]
+fn decode_multiple(
+    py: Python<'_>,
+    image: &Bound<'_, PyAny>,
+    width: Option<u32>,
+    height: Option<u32>,
+    hints: Option<&Bound<'_, PyDict>>,
+) -> PyResult<Vec<Barcode>> {
+    let (luma, width, height) = luma_from_buffer(py, image, width, height)?;
+    let mut hints: DecodeHints = hints_from_dict(py, hints)?.unwrap_or_default();
+
+    match py.detach(|| helpers::detect_multiple_in_luma_with_hints(luma, width, height, &mut hints))
+    {
+        Ok(results) => Ok(results.into_iter().map(Barcode::from).collect()),
+        Err(Exceptions::NotFoundException(_)) => Ok(Vec::new()),
+        Err(e) => Err(to_py_err(e)),
+    }
+}
+
+/// Encode `contents` in the given format
+#[pyfunction]
+#[pyo3(signature = (contents, format, width=0, height=0, hints=None))]
+fn encode(
+    py: Python<'_>,
+    contents: &str,
+    format: &str,
+    width: i32,
+    height: i32,
+    hints: Option<&Bound<'_, PyDict>>,
+) -> PyResult<EncodedBarcode> {
+    let hints: EncodeHints = hints_from_dict(py, hints)?.unwrap_or_default();
+    let format = parse_format(format)?;
+    let matrix = MultiFormatWriter
+        .encode_with_hints(contents, &format, width, height, &hints)
+        .map_err(to_py_err)?;
+    Ok(EncodedBarcode { matrix })
+}
+
+/// Add the rxing functions, classes and exceptions to `module`
+pub fn register(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add("__version__", env!("CARGO_PKG_VERSION"))?;
+    module.add("RxingError", module.py().get_type::<RxingError>())?;
+    module.add_class::<Barcode>()?;
+    module.add_class::<EncodedBarcode>()?;
+    module.add_function(wrap_pyfunction!(decode, module)?)?;
+    module.add_function(wrap_pyfunction!(decode_multiple, module)?)?;
+    module.add_function(wrap_pyfunction!(encode, module)?)?;
+    Ok(())
+}
+
+fn parse_format(format: &str) -> PyResult<BarcodeFormat> {
+    format
+        .parse()
+        .map_err(|_| PyValueError::new_err(format!("unknown barcode format {format:?}")))
+}
+
+/// Convert a hints dict through JSON, so it follows the serde names of the hint structs
+fn hints_from_dict<T: serde::de::DeserializeOwned>(
+    py: Python<'_>,
+    hints: Option<&Bound<'_, PyDict>>,
+) -> PyResult<Option<T>> {
+    let Some(hints) = hints else {
+        return Ok(None);
+    };
+    let json: String = py
+        .import("json")?
+        .call_method1("dumps", (hints,))?
+        .extract()?;
+    serde_json::from_str(&json)
+        .map(Some)
+        .map_err(|e| PyValueError::new_err(format!("invalid hints: {e}")))
+}
+
+fn luma_from_buffer(
+    py: Python<'_>,
+    image: &Bound<'_, PyAny>,
+    width: Option<u32>,
+    height: Option<u32>,
+) -> PyResult<(Vec<u8>, u32, u32)> {
+    let buffer = PyBuffer::<u8>::get(image)?;
+    let shape = buffer.shape().to_vec();
+    let data = buffer.to_vec(py)?;
+    image_to_luma(data, &shape, width, height).map_err(to_py_err)
+}
+
+/// Work out the image size and channel count from a buffer's shape and convert it to luma
+fn image_to_luma(
+    data: Vec<u8>,
+    shape: &[usize],
+    width: Option<u32>,
+    height: Option<u32>,
+) -> Result<(Vec<u8>, u32, u32)> {
+    let (rows, columns, channels) = match (shape, width, height) {
+        ([rows, columns], None, None) => (*rows, *columns, 1),
+        ([rows, columns, channels], None, None) => (*rows, *columns, *channels),
+        ([_], Some(width), Some(height)) => {
+            let pixels = width as usize * height as usize;
+            if pixels == 0 || data.len() % pixels != 0 {
+                return Err(Exceptions::illegal_argument_with(format!(
+                    "buffer of {} bytes does not match a {width}x{height} image",
+                    data.len()
+                )));
+            }
+            (height as usize, width as usize, data.len() / pixels)
+        }
+        ([_], _, _) => {
+            return Err(Exceptions::illegal_argument_with(
+                "width and height are required for flat buffers",
+            ))
+        }
+        (_, _, _) => {
+            return Err(Exceptions::illegal_argument_with(format!(
+                "expected a (height, width) or (height, width, channels) array, got shape {shape:?}"
+            )))
+        }
+    };
+
+    let (Ok(width), Ok(height)) = (u32::try_from(columns), u32::try_from(rows)) else {
+        return Err(Exceptions::illegal_argument_with("image is too large"));
+    };
+    if width == 0 || height == 0 {
+        return Err(Exceptions::illegal_argument_with("image is empty"));
+    }
+
+    let luma = match channels {
+        1 => data,
+        3 => data
+            .chunks_exact(3)
+            .map(|p| simd::luminance(p[0], p[1], p[2]))
+            .collect(),
+        // transparent pixels are treated as white
+        4 => {
+            let mut luma = vec![0; data.len() / 4];
+            simd::rgba_to_luma(&data, &mut luma);
+            luma
+        }
+        _ => {
+            return Err(Exceptions::illegal_argument_with(format!(
+                "expected 1, 3 or 4 channels, got {channels}"
+            )))
+        }
+    };
+    Ok((luma, width, height))
+}
+
+fn png_bytes(matrix: &BitMatrix) -> Result<Vec<u8>> {
+    let image = image::DynamicImage::from(matrix);
+    let mut png = Cursor::new(Vec::new());
+    image
+        .write_to(&mut png, image::ImageFormat::Png)
+        .map_err(|e| Exceptions::illegal_argument_with(format!("could not write png: {e}")))?;
+    Ok(png.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shapes() {
+        let (luma, width, height) = image_to_luma(vec![7; 6], &[2, 3], None, None).unwrap();
+        assert_eq!((luma, width, height), (vec![7; 6], 3, 2));
+
+        let rgb = [255, 255, 255, 0, 0, 0].repeat(2);
+        let (luma, width, height) = image_to_luma(rgb.clone(), &[2, 2, 3], None, None).unwrap();
+        assert_eq!((luma, width, height), (vec![255, 0, 255, 0], 2, 2));
+
+        let (luma, width, height) = image_to_luma(rgb, &[12], Some(2), Some(2)).unwrap();
+        assert_eq!((luma, width, height), (vec![255, 0, 255, 0], 2, 2));
+
+        let rgba = vec![0, 0, 0, 0, 0, 0, 0, 255];
+        let (luma, _, _) = image_to_luma(rgba, &[1, 2, 4], None, None).unwrap();
+        assert_eq!(luma, vec![0xFF, 0]);
+    }
+
+    #[test]
+    fn rejects_bad_shapes() {
+        assert!(image_to_luma(vec![0; 4], &[4], None, None).is_err());
+        assert!(image_to_luma(vec![0; 5], &[5], Some(2), Some(2)).is_err());
+        assert!(image_to_luma(vec![0; 8], &[2, 2, 2], None, None).is_err());
+        assert!(image_to_luma(vec![0; 8], &[2, 2, 2, 1], None, None).is_err());
+        assert!(image_to_luma(Vec::new(), &[0, 0], None, None).is_err());
+    }
+
+    #[test]
+    fn round_trip() {
+        let matrix = MultiFormatWriter
+            .encode("python", &BarcodeFormat::QR_CODE, 100, 100)
+            .unwrap();
+        let luma = matrix.to_luma8();
+        let result = helpers::detect_in_luma_with_hints(
+            luma,
+            matrix.getWidth(),
+            matrix.getHeight(),
+            Some(BarcodeFormat::QR_CODE),
+            &mut DecodeHints::default(),
+        )
+        .unwrap();
+        let barcode = Barcode::from(result);
+        assert_eq!(barcode.text(), "python");
+        assert!(png_bytes(&matrix).unwrap().starts_with(b"\x89PNG"));
+    }
+}
@@ -167,3 +167,35 @@ fn testRegressionTest() {
         "renderer-test-01.png",
     );
 }
+
+#[test]
+fn testMicroQRCodeWriter() {
+    use crate::{qrcode::cpp_port::QrReader, test_fixtures::luma_bitmap, Reader};
+
+    let writer = QRCodeWriter {};
+    let micro = writer
+        .encode("MICRO", &BarcodeFormat::MICRO_QR_CODE, 0, 0)
+        .expect("should encode");
+    // an M2 symbol of 13 modules in a quiet zone of 2
+    assert_eq!(17, micro.getWidth());
+
+    let hinted = writer
+        .encode_with_hints(
+            "MICRO",
+            &BarcodeFormat::QR_CODE,
+            0,
+            0,
+            &EncodeHints::default().with(EncodeHintValue::MicroQr(true)),
+        )
+        .expect("should encode");
+    assert_eq!(micro, hinted);
+
+    let matrix = writer
+        .encode("MICRO", &BarcodeFormat::MICRO_QR_CODE, 170, 170)
+        .expect("should encode");
+    let result = QrReader
+        .decode(&mut luma_bitmap(&matrix))
+        .expect("should decode");
+    assert_eq!(BarcodeFormat::MICRO_QR_CODE, *result.getBarcodeFormat());
+    assert_eq!("MICRO", result.getText());
+}
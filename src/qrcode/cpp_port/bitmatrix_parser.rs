@@ -227,12 +227,14 @@ pub fn ReadMQRCodewords(
                             != getBit(bitMatrix, xx, y, Some(formatInfo.isMirrored)),
                     );
                     bitsRead += 1;
-                    // If we've made a whole byte, save it off; save early if 2x2 data block.
-                    if bitsRead == 8
-                        || (bitsRead == 4 && hasD4mBlock && (result.len()) == d4mBlockIndex - 1)
-                    {
+                    // If we've made a whole byte, save it off; save early if 2x2 data block,
+                    // whose 4 bits are the high half of its codeword.
+                    if bitsRead == 8 {
                         result.push(std::mem::take(&mut currentByte));
                         bitsRead = 0;
+                    } else if bitsRead == 4 && hasD4mBlock && (result.len()) == d4mBlockIndex - 1 {
+                        result.push(std::mem::take(&mut currentByte) << 4);
+                        bitsRead = 0;
                     }
                 }
             }
@@ -9,52 +9,49 @@ use crate::common::reedsolomon::{
     get_predefined_genericgf, PredefinedGenericGF, ReedSolomonDecoder,
 };
 use crate::common::{
-    AIFlag, BitMatrix, BitSource, CharacterSet, ECIStringBuilder, Eci, Result, SymbologyIdentifier,
+    AIFlag, BitMatrix, BitSource, CharacterSet, CodewordBlock, CodewordWitness, ECIStringBuilder,
+    Eci, Result, SymbologyIdentifier,
 };
 use crate::qrcode::cpp_port::bitmatrix_parser::{
     ReadCodewords, ReadFormatInformation, ReadVersion,
 };
 use crate::qrcode::decoder::{DataBlock, ErrorCorrectionLevel, Mode, Version};
-use crate::Exceptions;
+use crate::{Exceptions, SymbolVersion};
 
 /**
 * <p>Given data and error-correction codewords received, possibly corrupted by errors, attempts to
-* correct the errors in-place using Reed-Solomon error correction.</p>
+* correct the errors using Reed-Solomon error correction.</p>
 *
 * @param codewordBytes data and error correction codewords
 * @param numDataCodewords number of codewords that are data bytes
-* @return false if error correction fails
+* @return the block with its corrected codewords
 */
-pub fn CorrectErrors(codewordBytes: &mut [u8], numDataCodewords: u32) -> Result<bool> {
+pub fn CorrectErrors(codewordBytes: &[u8], numDataCodewords: u32) -> Result<CodewordBlock> {
     // First read into an array of ints
     // std::vector<int> codewordsInts(codewordBytes.begin(), codewordBytes.end());
-    let mut codewordsInts: Vec<i32> = codewordBytes.iter().copied().map(|b| b as i32).collect();
+    let codewordsInts: Vec<i32> = codewordBytes.iter().copied().map(|b| b as i32).collect();
 
-    let numECCodewords = ((codewordBytes.len() as u32) - numDataCodewords) as i32;
     let rs = ReedSolomonDecoder::new(get_predefined_genericgf(
         PredefinedGenericGF::QrCodeField256,
     ));
 
-    rs.decode(&mut codewordsInts, numECCodewords)?;
-
-    // if rs.decode(&mut codewordsInts, numECCodewords)? != 0
-    // // if (!ReedSolomonDecode(GenericGF::QRCodeField256(), codewordsInts, numECCodewords))
-    // {
-    //     return Ok(false);
-    // }
-
-    // Copy back into array of bytes -- only need to worry about the bytes that were data
-    // We don't care about errors in the error-correction codewords
-    codewordBytes[..numDataCodewords as usize].copy_from_slice(
-        &codewordsInts[..numDataCodewords as usize]
-            .iter()
-            .copied()
-            .map(|i| i as u8)
-            .collect::<Vec<u8>>(),
-    );
-    // std::copy_n(codewordsInts.begin(), numDataCodewords, codewordBytes.begin());
-
-    Ok(true)
+    // if (!ReedSolomonDecode(GenericGF::QRCodeField256(), codewordsInts, numECCodewords))
+    CodewordBlock::correct(&rs, codewordsInts, numDataCodewords as usize)
+}
+
+/// Like `CorrectErrors`, with the indices of the codewords that couldn't be read
+pub fn CorrectErrorsAndErasures(
+    codewordBytes: &[u8],
+    numDataCodewords: u32,
+    erasures: &[usize],
+) -> Result<CodewordBlock> {
+    let codewordsInts: Vec<i32> = codewordBytes.iter().copied().map(|b| b as i32).collect();
+
+    let rs = ReedSolomonDecoder::new(get_predefined_genericgf(
+        PredefinedGenericGF::QrCodeField256,
+    ));
+
+    CodewordBlock::correct_with_erasures(&rs, codewordsInts, numDataCodewords as usize, erasures)
 }
 
 /**
@@ -387,7 +384,37 @@ pub fn DecodeBitStream(
         .withIsModel1(version.isModel1()))
 }
 
+// The version of the symbol and the data mask it was read with
+fn symbol_version(version: &Version, mask: u8) -> SymbolVersion {
+    let number = version.getVersionNumber();
+    if version.isMicro() {
+        SymbolVersion::MicroQRCode {
+            version: number,
+            mask,
+        }
+    } else if version.isRMQR() {
+        SymbolVersion::RectangularMicroQRCode { version: number }
+    } else {
+        SymbolVersion::QRCode {
+            version: number,
+            mask,
+            model: if version.isModel1() { 1 } else { 2 },
+        }
+    }
+}
+
 pub fn Decode(bits: &BitMatrix) -> Result<DecoderResult<bool>> {
+    DecodeWithErasures(bits, None)
+}
+
+/**
+ * Like `Decode`, for a symbol of which the modules set in `erasures` couldn't be sampled. The
+ * codewords with any of those modules are corrected as erasures.
+ */
+pub fn DecodeWithErasures(
+    bits: &BitMatrix,
+    erasures: Option<&BitMatrix>,
+) -> Result<DecoderResult<bool>> {
     if !Version::HasValidSize(bits) {
         return Err(Exceptions::format_with("Invalid symbol size"));
     }
@@ -417,31 +444,73 @@ pub fn Decode(bits: &BitMatrix) -> Result<DecoderResult<bool>> {
         return Err(Exceptions::format_with("Failed to get data blocks"));
     }
 
+    // the codewords read from the erased modules differ from those read from no modules at all,
+    // the data mask cancels out
+    let erasedBlocks = match erasures {
+        Some(erasures) => {
+            let blank = BitMatrix::new(bits.width(), bits.height())?;
+            let erased: Vec<u8> = ReadCodewords(erasures, version, &formatInfo)?
+                .iter()
+                .zip(ReadCodewords(&blank, version, &formatInfo)?)
+                .map(|(e, b)| u8::from(*e != b))
+                .collect();
+            Some(DataBlock::getDataBlocks(
+                &erased,
+                version,
+                formatInfo.error_correction_level,
+            )?)
+        }
+        None => None,
+    };
+
     // Count total number of data bytes
     let op = |totalBytes, dataBlock: &DataBlock| totalBytes + dataBlock.getNumDataCodewords();
     let totalBytes = dataBlocks.iter().fold(0, op); // std::accumulate(std::begin(dataBlocks), std::end(dataBlocks), int{}, op);
     let mut resultBytes = vec![0u8; totalBytes as usize];
     let mut resultIterator = 0; //resultBytes.begin();
+    let mut codewordWitness =
+        CodewordWitness::new(8, codewords.iter().map(|c| *c as i32).collect());
 
     // Error-correct and copy data blocks together into a stream of bytes
-    for dataBlock in dataBlocks.iter() {
-        let mut codewordBytes = dataBlock.getCodewords().to_vec();
+    for (i, dataBlock) in dataBlocks.iter().enumerate() {
         let numDataCodewords = dataBlock.getNumDataCodewords() as usize;
 
-        if !CorrectErrors(&mut codewordBytes, numDataCodewords as u32)? {
-            return Err(Exceptions::CHECKSUM);
-        }
+        let block = match &erasedBlocks {
+            Some(erasedBlocks) => {
+                let erased: Vec<usize> = erasedBlocks[i]
+                    .getCodewords()
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, e)| **e != 0)
+                    .map(|(j, _)| j)
+                    .collect();
+                CorrectErrorsAndErasures(
+                    dataBlock.getCodewords(),
+                    numDataCodewords as u32,
+                    &erased,
+                )?
+            }
+            None => CorrectErrors(dataBlock.getCodewords(), numDataCodewords as u32)?,
+        };
 
         // resultIterator = std::copy_n(codewordBytes.begin(), numDataCodewords, resultIterator);
-        resultBytes[resultIterator..(resultIterator + numDataCodewords)]
-            .copy_from_slice(&codewordBytes[..numDataCodewords]);
+        for (byte, codeword) in resultBytes[resultIterator..(resultIterator + numDataCodewords)]
+            .iter_mut()
+            .zip(block.data_codewords())
+        {
+            *byte = *codeword as u8;
+        }
         resultIterator += numDataCodewords;
+        codewordWitness.blocks.push(block);
     }
+    codewordWitness.corrected_codewords = resultBytes.iter().map(|b| *b as i32).collect();
 
     // Decode the contents of that stream of bytes
     Ok(
         DecodeBitStream(&resultBytes, version, formatInfo.error_correction_level)?
-            .withIsMirrored(formatInfo.isMirrored),
+            .withIsMirrored(formatInfo.isMirrored)
+            .withCodewords(Some(codewordWitness))
+            .withSymbolVersion(Some(symbol_version(version, formatInfo.data_mask))),
     )
 }
 
@@ -4,7 +4,8 @@ use crate::{
             CenterOfRing, DMRegressionLine, FindConcentricPatternCorners, FindLeftGuardBy, Matrix,
             Value,
         },
-        DefaultGridSampler, GridSampler, Result, SamplerControl,
+        DefaultGridSampler, DetectedPattern, GridSampler, PatternKind, Result, SamplerControl,
+        SamplingGrid,
     },
     point, point_i,
     qrcode::{
@@ -18,9 +19,9 @@ use multimap::MultiMap;
 use crate::{
     common::{
         cpp_essentials::{
-            BitMatrixCursorTrait, ConcentricPattern, Direction, EdgeTracer, FixedPattern,
-            GetPatternRowTP, IsPattern, LocateConcentricPattern, PatternRow, PatternType,
-            PatternView, ReadSymmetricPattern, RegressionLine, RegressionLineTrait,
+            BitMatrixCursorTrait, ClampOuterRuns, ConcentricPattern, Direction, EdgeTracer,
+            FixedPattern, GetPatternRowTP, IsPattern, LocateConcentricPattern, PatternRow,
+            PatternType, PatternView, ReadSymmetricPattern, RegressionLine, RegressionLineTrait,
         },
         BitMatrix, PerspectiveTransform, Quadrilateral,
     },
@@ -44,25 +45,46 @@ const SUM: usize = 7;
 const PATTERN: FixedPattern<LEN, SUM, false> = FixedPattern::new([1, 1, 3, 1, 1]);
 const E2E: bool = true;
 
-fn FindPattern(view: PatternView<'_>) -> Result<PatternView<'_>> {
+fn FindPattern(view: PatternView<'_>, noQuietZone: bool) -> Result<PatternView<'_>> {
     FindLeftGuardBy::<LEN, _>(
         view,
         LEN,
         |view: &PatternView, spaceInPixel: Option<f32>| {
-            // perform a fast plausability test for 1:1:3:1:1 pattern
-            if view[2] < 2 as PatternType * std::cmp::max(view[0], view[4])
-                || view[2] < std::cmp::max(view[1], view[3])
-            {
-                return false;
+            if noQuietZone {
+                // the outer bars may run into whatever is next to the symbol
+                let mut runs: [PatternType; LEN] = std::array::from_fn(|i| view[i]);
+                ClampOuterRuns(&mut runs, &[1, 1, 3, 1, 1]);
+                let row = PatternRow::new(runs.to_vec());
+                return IsFinderPattern(&PatternView::new(&row), Some(f32::MAX));
             }
-            IsPattern::<E2E, 5, 7, false>(view, &PATTERN, spaceInPixel, 0.1, 0.0) != 0.0
+            IsFinderPattern(view, spaceInPixel)
         },
     )
 }
 
+fn IsFinderPattern(view: &PatternView, spaceInPixel: Option<f32>) -> bool {
+    // perform a fast plausability test for 1:1:3:1:1 pattern
+    if view[2] < 2 as PatternType * std::cmp::max(view[0], view[4])
+        || view[2] < std::cmp::max(view[1], view[3])
+    {
+        return false;
+    }
+    IsPattern::<E2E, 5, 7, false>(view, &PATTERN, spaceInPixel, 0.1, 0.0) != 0.0
+}
+
 /// Locate the finder patterns for the symbol.
 /// This function can panic
 pub fn FindFinderPatterns(image: &BitMatrix, tryHarder: bool) -> FinderPatterns {
+    FindFinderPatternsWithoutQuietZone(image, tryHarder, false)
+}
+
+/// Like `FindFinderPatterns`, but with `noQuietZone` also finds patterns whose outer ring runs
+/// into dark content next to the symbol
+pub fn FindFinderPatternsWithoutQuietZone(
+    image: &BitMatrix,
+    tryHarder: bool,
+    noQuietZone: bool,
+) -> FinderPatterns {
     const MIN_SKIP: u32 = 3; // 1 pixel/module times 3 modules/center
     const MAX_MODULES_FAST: u32 = 20 * 4 + 17; // support up to version 20 for mobile clients
 
@@ -86,7 +108,7 @@ pub fn FindFinderPatterns(image: &BitMatrix, tryHarder: bool) -> FinderPatterns
         let mut next: PatternView = PatternView::new(&row);
 
         while {
-            if let Ok(up_next) = FindPattern(next) {
+            if let Ok(up_next) = FindPattern(next, noQuietZone) {
                 next = up_next;
                 next.isValid()
             } else {
@@ -112,6 +134,7 @@ pub fn FindFinderPatterns(image: &BitMatrix, tryHarder: bool) -> FinderPatterns
                     &PATTERN.into(),
                     p,
                     next.iter().sum::<u16>() as i32 * 3,
+                    noQuietZone,
                 ); // 3 for very skewed samples
                    //    Reduce(next) * 3); // 3 for very skewed samples
                 if let Some(p) = pattern {
@@ -262,7 +285,12 @@ pub fn GenerateFinderPatternSets(patterns: &mut FinderPatterns) -> FinderPattern
     res
 }
 
-pub fn EstimateModuleSize(image: &BitMatrix, a: ConcentricPattern, b: ConcentricPattern) -> f64 {
+pub fn EstimateModuleSize(
+    image: &BitMatrix,
+    a: ConcentricPattern,
+    b: ConcentricPattern,
+    noQuietZone: bool,
+) -> f64 {
     let mut cur = EdgeTracer::new(image, a.p, b.p - a.p);
     if !cur.isBlack() {
         return -1.0;
@@ -275,7 +303,12 @@ pub fn EstimateModuleSize(image: &BitMatrix, a: ConcentricPattern, b: Concentric
         return -1.0;
     }
 
-    let pattern = pattern.unwrap();
+    let mut pattern = pattern.unwrap();
+    // the outer runs don't count towards the module size, they may run into dark surroundings
+    // where there is no quiet zone
+    if noQuietZone {
+        ClampOuterRuns(&mut pattern, &[1, 1, 3, 1, 1]);
+    }
 
     if !(IsPattern::<E2E, 5, 7, false>(
         &PatternView::new(&PatternRow::new(pattern.to_vec())),
@@ -313,9 +346,10 @@ pub fn EstimateDimension(
     image: &BitMatrix,
     a: ConcentricPattern,
     b: ConcentricPattern,
+    noQuietZone: bool,
 ) -> DimensionEstimate {
-    let ms_a = EstimateModuleSize(image, a, b);
-    let ms_b = EstimateModuleSize(image, b, a);
+    let ms_a = EstimateModuleSize(image, a, b, noQuietZone);
+    let ms_b = EstimateModuleSize(image, b, a, noQuietZone);
 
     if ms_a < 0.0 || ms_b < 0.0 {
         return DimensionEstimate::default();
@@ -495,9 +529,42 @@ pub fn AppendBit(val: &mut i32, bit: bool) {
     *val |= i32::from(bit)
 }
 
+/// Samples the grid of a QR Code, partly outside of the image if `allowPartial` is set
+fn SampleQRGrid(
+    image: &BitMatrix,
+    dimension: u32,
+    controls: &[SamplerControl],
+    allowPartial: bool,
+) -> Result<(BitMatrix, [Point; 4], Option<BitMatrix>)> {
+    let grid_sampler = DefaultGridSampler;
+    match grid_sampler.sample_grid(image, dimension, dimension, controls) {
+        Ok((sampled, rps)) => Ok((sampled, rps, None)),
+        Err(_) if allowPartial => {
+            let (sampled, erased, rps) =
+                grid_sampler.sample_grid_partial(image, dimension, dimension, controls)?;
+            Ok((sampled, rps, Some(erased)))
+        }
+        Err(e) => Err(e),
+    }
+}
+
 pub fn SampleQR(image: &BitMatrix, fp: &FinderPatternSet) -> Result<QRCodeDetectorResult> {
-    let top = EstimateDimension(image, fp.tl, fp.tr);
-    let left = EstimateDimension(image, fp.tl, fp.bl);
+    SampleQRRelaxed(image, fp, false, false)
+}
+
+/**
+ * Like `SampleQR`, but with `noQuietZone` the finder patterns may run into dark surroundings,
+ * and with `allowPartial` a symbol that runs off the edge of the image is sampled too, with the
+ * modules outside of the image marked as erasures in the result.
+ */
+pub fn SampleQRRelaxed(
+    image: &BitMatrix,
+    fp: &FinderPatternSet,
+    noQuietZone: bool,
+    allowPartial: bool,
+) -> Result<QRCodeDetectorResult> {
+    let top = EstimateDimension(image, fp.tl, fp.tr, noQuietZone);
+    let left = EstimateDimension(image, fp.tl, fp.bl, noQuietZone);
 
     if top.dim == 0 && left.dim == 0 {
         return Err(Exceptions::NOT_FOUND);
@@ -534,6 +601,12 @@ pub fn SampleQR(image: &BitMatrix, fp: &FinderPatternSet) -> Result<QRCodeDetect
         size: 0,
     };
     let mut brOffset = point_i(3, 3);
+    let mut brIsAlignment = false;
+    let mut patterns = vec![
+        DetectedPattern::new(PatternKind::Finder, fp.tl.p),
+        DetectedPattern::new(PatternKind::Finder, fp.tr.p),
+        DetectedPattern::new(PatternKind::Finder, fp.bl.p),
+    ];
 
     // Everything except version 1 (21 modules) has an alignment pattern. Estimate the center of that by intersecting
     // line extensions of the 1 module wide square around the finder patterns. This could also help with detecting
@@ -556,6 +629,7 @@ pub fn SampleQR(image: &BitMatrix, fp: &FinderPatternSet) -> Result<QRCodeDetect
         if dimension > 21 {
             if let Some(brCP) = LocateAlignmentPattern(image, moduleSize, brInter) {
                 br = brCP.into();
+                brIsAlignment = true;
             }
         }
 
@@ -566,6 +640,7 @@ pub fn SampleQR(image: &BitMatrix, fp: &FinderPatternSet) -> Result<QRCodeDetect
                 || (bl2.isHighRes() && bl3.isHighRes() && tr2.isHighRes() && tr3.isHighRes()))
         {
             br = brInter.into();
+            brIsAlignment = false;
         }
     }
 
@@ -573,6 +648,7 @@ pub fn SampleQR(image: &BitMatrix, fp: &FinderPatternSet) -> Result<QRCodeDetect
     if !image.is_in(br.p) {
         br = fp.tr - fp.tl + fp.bl;
         brOffset = point_i(0, 0);
+        brIsAlignment = false;
     }
 
     // log(br, 3);
@@ -742,6 +818,18 @@ pub fn SampleQR(image: &BitMatrix, fp: &FinderPatternSet) -> Result<QRCodeDetect
             }
         }
 
+        for y in 0..=N {
+            for x in 0..=N {
+                // the three corners are the inner corners of the finder patterns
+                if [(0, 0), (N, 0), (0, N)].contains(&(x, y)) {
+                    continue;
+                }
+                if let Some(p) = apP.get(x, y) {
+                    patterns.push(DetectedPattern::new(PatternKind::Alignment, p));
+                }
+            }
+        }
+
         // assemble a list of region-of-interests based on the found alignment pattern pixel positions
 
         let mut rois = Vec::new();
@@ -773,28 +861,39 @@ pub fn SampleQR(image: &BitMatrix, fp: &FinderPatternSet) -> Result<QRCodeDetect
                 });
             }
         }
-        let grid_sampler = DefaultGridSampler;
-        let (sampled, rp) =
-            grid_sampler.sample_grid(image, dimension as u32, dimension as u32, &rois)?;
-        let result = QRCodeDetectorResult::new(sampled, rp.to_vec());
-        return Ok(result);
+        let (sampled, rp, erased) = SampleQRGrid(image, dimension as u32, &rois, allowPartial)?;
+        let result = QRCodeDetectorResult::new(sampled, rp.to_vec()).with_sampling_grid(
+            SamplingGrid::new(dimension as u32, dimension as u32, rois).with_patterns(patterns),
+        );
+        return Ok(match erased {
+            Some(erased) => result.with_erasures(erased),
+            None => result,
+        });
         //  grid_sampler.sample_grid(image, dimension, dimension, &rois);
         // #endif
     }
 
-    let grid_sampler = DefaultGridSampler;
-    let (sampled, rps) = grid_sampler.sample_grid(
+    let (sampled, rps, erased) = SampleQRGrid(
         image,
         dimension as u32,
-        dimension as u32,
         &[SamplerControl {
             p1: point_i(dimension as u32, dimension as u32),
             p0: point_i(0, 0),
             transform: mod2Pix,
         }],
+        allowPartial,
     )?;
-    let result = QRCodeDetectorResult::new(sampled, rps.to_vec());
-    Ok(result)
+    if brIsAlignment {
+        patterns.push(DetectedPattern::new(PatternKind::Alignment, br.p));
+    }
+    let result = QRCodeDetectorResult::new(sampled, rps.to_vec()).with_sampling_grid(
+        SamplingGrid::with_transform(dimension as u32, dimension as u32, mod2Pix)
+            .with_patterns(patterns),
+    );
+    Ok(match erased {
+        Some(erased) => result.with_erasures(erased),
+        None => result,
+    })
     // return SampleGrid(image, dimension, dimension, mod2Pix);
 }
 
@@ -853,6 +952,7 @@ pub fn DetectPureQR(image: &BitMatrix) -> Result<QRCodeDetectorResult> {
             p: tr + fpWidth as f32 / 2.0 * point(-1.0, 1.0),
             size: fpWidth,
         },
+        false,
     )
     .dim;
 
@@ -889,7 +989,14 @@ pub fn DetectPureQR(image: &BitMatrix) -> Result<QRCodeDetectorResult> {
             point_i(right, bottom),
             point_i(left, bottom),
         ],
-    ))
+    )
+    .with_sampling_grid(SamplingGrid::axis_aligned(
+        dimension as u32,
+        dimension as u32,
+        left as f32,
+        top as f32,
+        moduleSize,
+    )?))
 
     // return {Deflate(image, dimension, dimension, top + moduleSize / 2, left + moduleSize / 2, moduleSize),
     // 		{{left, top}, {right, top}, {right, bottom}, {left, bottom}}};
@@ -955,7 +1062,14 @@ pub fn DetectPureMQR(image: &BitMatrix) -> Result<QRCodeDetectorResult> {
             point_i(right, bottom),
             point_i(left, bottom),
         ],
-    ))
+    )
+    .with_sampling_grid(SamplingGrid::axis_aligned(
+        dimension,
+        dimension,
+        left as f32,
+        top as f32,
+        moduleSize,
+    )?))
     // return {Deflate(image, dimension, dimension, top + moduleSize / 2, left + moduleSize / 2, moduleSize),
     // 		{{left, top}, {right, top}, {right, bottom}, {left, bottom}}};
 }
@@ -1059,7 +1173,14 @@ pub fn DetectPureRMQR(image: &BitMatrix) -> Result<QRCodeDetectorResult> {
             moduleSize,
         )?,
         vec![tl, tr, br, bl],
-    ))
+    )
+    .with_sampling_grid(SamplingGrid::axis_aligned(
+        dimW as u32,
+        dimH as u32,
+        left as f32,
+        top as f32,
+        moduleSize,
+    )?))
     // return {Deflate(image, dimW, dimH, top + moduleSize / 2, left + moduleSize / 2, moduleSize), {tl, tr, br, bl}};
 }
 
@@ -1168,7 +1289,12 @@ pub fn SampleMQR(image: &BitMatrix, fp: ConcentricPattern) -> Result<QRCodeDetec
             transform: bestPT,
         }],
     )?;
-    Ok(QRCodeDetectorResult::new(sample, rps.to_vec()))
+    Ok(
+        QRCodeDetectorResult::new(sample, rps.to_vec()).with_sampling_grid(
+            SamplingGrid::with_transform(dim, dim, bestPT)
+                .with_patterns(vec![DetectedPattern::new(PatternKind::Finder, fp.p)]),
+        ),
+    )
 
     //  SampleGrid(image, dim, dim, bestPT)
 }
@@ -1380,6 +1506,11 @@ pub fn SampleRMQR(image: &BitMatrix, fp: ConcentricPattern) -> Result<QRCodeDete
             transform: bestPT,
         }],
     )?;
-    Ok(QRCodeDetectorResult::new(sample, rps.to_vec()))
+    Ok(
+        QRCodeDetectorResult::new(sample, rps.to_vec()).with_sampling_grid(
+            SamplingGrid::with_transform(dim.x as u32, dim.y as u32, bestPT)
+                .with_patterns(vec![DetectedPattern::new(PatternKind::Finder, fp.p)]),
+        ),
+    )
     //  SampleGrid(image, dim.x, dim.y, bestPT)
 }
@@ -118,16 +118,22 @@
 // } // namespace ZXing::QRCode
 
 use crate::{
-    common::{cpp_essentials::ConcentricPattern, DetectorRXingResult},
+    common::{
+        cpp_essentials::{ConcentricPattern, DecoderResult},
+        BitMatrix, DetectorRXingResult, Result,
+    },
+    decode_confidence,
     multi::MultipleBarcodeReader,
-    BarcodeFormat, DecodeHints, Exceptions, ImmutableReader, RXingResult, Reader,
+    qrcode::detector::QRCodeDetectorResult,
+    record_witness, symbol_pose, BarcodeFormat, DecodeHints, Exceptions, ImmutableReader,
+    RXingResult, RXingResultMetadataType, RXingResultMetadataValue, Reader, WitnessRecording,
 };
 
 use super::{
-    decoder::Decode,
+    decoder::{Decode, DecodeWithErasures},
     detector::{
-        DetectPureMQR, DetectPureQR, DetectPureRMQR, FindFinderPatterns, GenerateFinderPatternSets,
-        SampleMQR, SampleQR, SampleRMQR,
+        DetectPureMQR, DetectPureQR, DetectPureRMQR, FindFinderPatternsWithoutQuietZone,
+        GenerateFinderPatternSets, SampleMQR, SampleQRRelaxed, SampleRMQR,
     },
 };
 
@@ -147,7 +153,9 @@ impl Reader for QrReader {
         image: &mut crate::BinaryBitmap<B>,
         hints: &DecodeHints,
     ) -> crate::common::Result<RXingResult> {
-        self.internal_decode_with_hints(image, hints)
+        let mut recording = WitnessRecording::new(hints);
+        self.internal_decode_with_hints(image, hints, &mut recording)
+            .map(|result| record_witness(hints, image, result, &recording))
     }
 }
 
@@ -157,7 +165,9 @@ impl ImmutableReader for QrReader {
         image: &mut crate::BinaryBitmap<B>,
         hints: &DecodeHints,
     ) -> crate::common::Result<RXingResult> {
-        self.internal_decode_with_hints(image, hints)
+        let mut recording = WitnessRecording::new(hints);
+        self.internal_decode_with_hints(image, hints, &mut recording)
+            .map(|result| record_witness(hints, image, result, &recording))
     }
 }
 
@@ -174,7 +184,7 @@ impl MultipleBarcodeReader for QrReader {
         image: &mut crate::BinaryBitmap<B>,
         hints: &DecodeHints,
     ) -> crate::common::Result<Vec<crate::RXingResult>> {
-        self.decode_set_number_with_hints(image, hints, u32::MAX)
+        self.decode_set_number_with_hints(image, hints, u32::MAX, &mut WitnessRecording::new(hints))
     }
 }
 
@@ -184,7 +194,9 @@ impl QrReader {
         image: &mut crate::BinaryBitmap<B>,
         hints: &DecodeHints,
         count: u32,
+        recording: &mut WitnessRecording,
     ) -> crate::common::Result<Vec<RXingResult>> {
+        recording.binarization(image);
         let binImg = image.get_black_matrix(); //image.getBitMatrix();
         let maxSymbols = count;
         // if (binImg == nullptr)
@@ -194,8 +206,10 @@ impl QrReader {
         // 	LogMatrixWriter lmw(log, *binImg, 5, "qr-log.pnm");
         // #endif
         let try_harder = hints.TryHarder.unwrap_or(false);
+        let noQuietZone = hints.AssumeNoQuietZone.unwrap_or(false);
+        let allowPartial = hints.AllowPartialSymbol.unwrap_or(false);
 
-        let mut allFPs = FindFinderPatterns(binImg, try_harder);
+        let mut allFPs = FindFinderPatternsWithoutQuietZone(binImg, try_harder, noQuietZone);
 
         // #ifdef PRINT_DEBUG
         // 	printf("allFPs: %d\n", Size(allFPs));
@@ -219,6 +233,7 @@ impl QrReader {
             let allFPSets = GenerateFinderPatternSets(&mut allFPs);
             for fpSet in allFPSets {
                 // for (const auto& fpSet : allFPSets) {
+                hints.check_cancelled()?;
                 if usedFPs.contains(&fpSet.bl)
                     || usedFPs.contains(&fpSet.tl)
                     || usedFPs.contains(&fpSet.tr)
@@ -228,10 +243,20 @@ impl QrReader {
 
                 // logFPSet(fpSet);
 
-                let detectorResult = SampleQR(binImg, &fpSet);
+                let detectorResult = SampleQRRelaxed(binImg, &fpSet, noQuietZone, allowPartial)
+                    .and_then(|r| r.sample_as_hinted(binImg, hints));
                 if let Ok(detectorResult) = detectorResult {
                     // if (detectorResult.is_ok()) {
-                    let decoderResult = Decode(detectorResult.getBits());
+                    recording.detection(
+                        BarcodeFormat::QR_CODE,
+                        detectorResult.getPoints(),
+                        detectorResult.getSamplingGrid(),
+                    );
+                    let decoderResult =
+                        DecodeWithErasures(detectorResult.getBits(), detectorResult.getErasures())
+                            .or_else(|err| {
+                                decode_with_uncertain_modules(binImg, &detectorResult, err)
+                            });
                     let position = detectorResult.getPoints();
                     if let Ok(decoderResult) = decoderResult {
                         if decoderResult.isValid() {
@@ -241,11 +266,17 @@ impl QrReader {
                         }
 
                         if decoderResult.isValid() {
-                            results.push(RXingResult::with_decoder_result(
-                                decoderResult,
-                                position,
-                                BarcodeFormat::QR_CODE,
-                            ));
+                            recording.codewords(decoderResult.codewords());
+                            let mut result = with_sampling_grid(
+                                RXingResult::with_decoder_result(
+                                    decoderResult,
+                                    position,
+                                    BarcodeFormat::QR_CODE,
+                                ),
+                                &detectorResult,
+                            );
+                            recording.decoded_bits(&mut result);
+                            results.push(result);
 
                             if maxSymbols != 0 && (results.len() as u32) == maxSymbols {
                                 break;
@@ -259,22 +290,35 @@ impl QrReader {
             // if (_hints.hasFormat(BarcodeFormat::MicroQRCode) && !(maxSymbols && Size(results) == maxSymbols)) {
             for fp in &allFPs {
                 // for (const auto& fp : allFPs) {
+                hints.check_cancelled()?;
                 if usedFPs.contains(fp) {
                     continue;
                 }
 
-                let detectorResult = SampleMQR(binImg, *fp);
+                let detectorResult =
+                    SampleMQR(binImg, *fp).and_then(|r| r.sample_as_hinted(binImg, hints));
                 if let Ok(detectorResult) = detectorResult {
                     // if (detectorResult.is_ok()) {
+                    recording.detection(
+                        BarcodeFormat::MICRO_QR_CODE,
+                        detectorResult.getPoints(),
+                        detectorResult.getSamplingGrid(),
+                    );
                     let decoderResult = Decode(detectorResult.getBits());
                     let position = detectorResult.getPoints();
                     if let Ok(decoderResult) = decoderResult {
                         if decoderResult.isValid() {
-                            results.push(RXingResult::with_decoder_result(
-                                decoderResult,
-                                position,
-                                BarcodeFormat::MICRO_QR_CODE,
-                            ));
+                            recording.codewords(decoderResult.codewords());
+                            let mut result = with_sampling_grid(
+                                RXingResult::with_decoder_result(
+                                    decoderResult,
+                                    position,
+                                    BarcodeFormat::MICRO_QR_CODE,
+                                ),
+                                &detectorResult,
+                            );
+                            recording.decoded_bits(&mut result);
+                            results.push(result);
 
                             if maxSymbols != 0 && (results.len() as u32) == maxSymbols {
                                 break;
@@ -287,22 +331,35 @@ impl QrReader {
         if check_rmqr && !(maxSymbols != 0 && (results.len() as u32) == maxSymbols) {
             for fp in &allFPs {
                 // for (const auto& fp : allFPs) {
+                hints.check_cancelled()?;
                 if usedFPs.contains(fp) {
                     continue;
                 }
 
-                let detectorResult = SampleRMQR(binImg, *fp);
+                let detectorResult =
+                    SampleRMQR(binImg, *fp).and_then(|r| r.sample_as_hinted(binImg, hints));
                 if let Ok(detectorResult) = detectorResult {
                     // if (detectorResult.is_ok()) {
+                    recording.detection(
+                        BarcodeFormat::RECTANGULAR_MICRO_QR_CODE,
+                        detectorResult.getPoints(),
+                        detectorResult.getSamplingGrid(),
+                    );
                     let decoderResult = Decode(detectorResult.getBits());
                     let position = detectorResult.getPoints();
                     if let Ok(decoderResult) = decoderResult {
                         if decoderResult.isValid() {
-                            results.push(RXingResult::with_decoder_result(
-                                decoderResult,
-                                position,
-                                BarcodeFormat::RECTANGULAR_MICRO_QR_CODE,
-                            ));
+                            recording.codewords(decoderResult.codewords());
+                            let mut result = with_sampling_grid(
+                                RXingResult::with_decoder_result(
+                                    decoderResult,
+                                    position,
+                                    BarcodeFormat::RECTANGULAR_MICRO_QR_CODE,
+                                ),
+                                &detectorResult,
+                            );
+                            recording.decoded_bits(&mut result);
+                            results.push(result);
 
                             if maxSymbols != 0 && (results.len() as u32) == maxSymbols {
                                 break;
@@ -320,6 +377,7 @@ impl QrReader {
         &self,
         image: &mut crate::BinaryBitmap<B>,
         hints: &DecodeHints,
+        recording: &mut WitnessRecording,
     ) -> crate::common::Result<RXingResult> {
         // #if 1
         if !matches!(hints.PureBarcode, Some(true))
@@ -327,7 +385,7 @@ impl QrReader {
         // if (!_hints.isPure())
         {
             return Ok(self
-                .decode_set_number_with_hints(image, hints, 1)?
+                .decode_set_number_with_hints(image, hints, 1, recording)?
                 .first()
                 .ok_or(Exceptions::NOT_FOUND)?
                 .clone());
@@ -335,6 +393,7 @@ impl QrReader {
         }
         // #endif
 
+        recording.binarization(image);
         let binImg = image.get_black_matrix(); //image.getBitMatrix();
                                                // if (binImg == nullptr)
                                                // 	{return {};}
@@ -363,7 +422,19 @@ impl QrReader {
             }
         }
 
-        let detectorResult = detectorResult?;
+        let detectorResult = detectorResult?.sample_as_hinted(binImg, hints)?;
+        let format = if detectorResult.getBits().width() != detectorResult.getBits().height() {
+            BarcodeFormat::RECTANGULAR_MICRO_QR_CODE
+        } else if detectorResult.getBits().width() < 21 {
+            BarcodeFormat::MICRO_QR_CODE
+        } else {
+            BarcodeFormat::QR_CODE
+        };
+        recording.detection(
+            format.clone(),
+            detectorResult.getPoints(),
+            detectorResult.getSamplingGrid(),
+        );
 
         // let detectorResult: DetectorResult;
         // if (_hints.hasFormat(BarcodeFormat::QR_CODE))
@@ -376,18 +447,208 @@ impl QrReader {
         // 	{return {};}
 
         let decoderResult = Decode(detectorResult.getBits())?;
+        recording.codewords(decoderResult.codewords());
         let position = detectorResult.getPoints();
 
-        Ok(RXingResult::with_decoder_result(
-            decoderResult,
-            position,
-            if detectorResult.getBits().width() != detectorResult.getBits().height() {
-                BarcodeFormat::RECTANGULAR_MICRO_QR_CODE
-            } else if detectorResult.getBits().width() < 21 {
-                BarcodeFormat::MICRO_QR_CODE
-            } else {
-                BarcodeFormat::QR_CODE
+        let mut result = with_sampling_grid(
+            RXingResult::with_decoder_result(decoderResult, position, format),
+            &detectorResult,
+        );
+        recording.decoded_bits(&mut result);
+        Ok(result)
+    }
+}
+
+/**
+ * Decodes a symbol whose codewords couldn't be corrected again, with the modules its sampling
+ * grid finds uncertain erased as well. An erased codeword costs half as much error correction as
+ * a misread one, so a symbol with specks or smudges on more codewords than it can correct may
+ * still decode. Fails with `err` if it doesn't.
+ */
+fn decode_with_uncertain_modules(
+    image: &BitMatrix,
+    detectorResult: &QRCodeDetectorResult,
+    err: Exceptions,
+) -> Result<DecoderResult<bool>> {
+    if !matches!(
+        err,
+        Exceptions::ReedSolomonException(_) | Exceptions::ChecksumException(_)
+    ) {
+        return Err(err);
+    }
+    let Some(grid) = detectorResult.getSamplingGrid() else {
+        return Err(err);
+    };
+    let mut erasures = grid.uncertain_modules(image)?;
+    if erasures.count_ones() == 0 {
+        return Err(err);
+    }
+    if let Some(erased) = detectorResult.getErasures() {
+        erasures.or(erased)?;
+    }
+    DecodeWithErasures(detectorResult.getBits(), Some(&erasures)).map_err(|_| err)
+}
+
+fn with_sampling_grid(
+    mut result: RXingResult,
+    detectorResult: &impl DetectorRXingResult,
+) -> RXingResult {
+    if let Some(grid) = detectorResult.getSamplingGrid() {
+        result.putMetadata(
+            RXingResultMetadataType::SAMPLING_GRID,
+            RXingResultMetadataValue::SamplingGrid(grid.clone()),
+        );
+    }
+    decode_confidence::put_confidence(&mut result);
+    symbol_pose::put_pose(&mut result);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        helpers, BarcodeFormat, DecodeHints, EncodeHints, MultiFormatWriter, RXingResult,
+        RXingResultMetadataType, RXingResultMetadataValue, Writer,
+    };
+
+    fn encode(contents: &str, ec_level: &str) -> crate::common::BitMatrix {
+        MultiFormatWriter
+            .encode_with_hints(
+                contents,
+                &BarcodeFormat::QR_CODE,
+                0,
+                0,
+                &EncodeHints {
+                    Margin: Some("0".to_owned()),
+                    ErrorCorrection: Some(ec_level.to_owned()),
+                    ..Default::default()
+                },
+            )
+            .expect("encode")
+    }
+
+    fn decode(luma: Vec<u8>, width: u32, height: u32, hints: DecodeHints) -> Option<RXingResult> {
+        helpers::detect_in_luma_with_hints(
+            luma,
+            width,
+            height,
+            None,
+            &mut DecodeHints {
+                PossibleFormats: Some([BarcodeFormat::QR_CODE].into()),
+                ..hints
             },
-        ))
+        )
+        .ok()
+    }
+
+    #[test]
+    fn test_assume_no_quiet_zone() {
+        // a 2 module wide frame drawn right against the symbol, 3 pixels per module
+        let matrix = encode("no quiet zone", "L");
+        let n = matrix.getWidth();
+        let size = (n + 4 + 8) * 3;
+        let mut luma = vec![255u8; (size * size) as usize];
+        for y in 0..size {
+            for x in 0..size {
+                let (u, v) = ((x / 3) as i32 - 6, (y / 3) as i32 - 6);
+                let inside = (0..n as i32).contains(&u) && (0..n as i32).contains(&v);
+                let frame = (-2..n as i32 + 2).contains(&u) && (-2..n as i32 + 2).contains(&v);
+                if (inside && matrix.get(u as u32, v as u32)) || (frame && !inside) {
+                    luma[(y * size + x) as usize] = 0;
+                }
+            }
+        }
+
+        assert!(decode(luma.clone(), size, size, DecodeHints::default()).is_none());
+        let result = decode(
+            luma,
+            size,
+            size,
+            DecodeHints {
+                AssumeNoQuietZone: Some(true),
+                ..Default::default()
+            },
+        )
+        .expect("decode");
+        assert_eq!("no quiet zone", result.getText());
+    }
+
+    #[test]
+    fn test_allow_partial_symbol() {
+        // the symbol turned 45 degrees, with its bottom right corner cut off by the image edge
+        let matrix = encode("partial symbol", "H");
+        let (n, scale) = (matrix.getWidth() as f32, 4.0f32);
+        let width = (n * scale * 1.5 + 40.0) as u32;
+        let center = width as f32 / 2.0;
+        let cut = 4.0 * scale * std::f32::consts::SQRT_2;
+        let height = (center + n * scale / std::f32::consts::SQRT_2 - cut) as u32;
+        let mut luma = vec![255u8; (width * height) as usize];
+        for y in 0..height {
+            for x in 0..width {
+                let (dx, dy) = (x as f32 + 0.5 - center, y as f32 + 0.5 - center);
+                let u = (dx + dy) / std::f32::consts::SQRT_2 / scale + n / 2.0;
+                let v = (dy - dx) / std::f32::consts::SQRT_2 / scale + n / 2.0;
+                if (0.0..n).contains(&u) && (0.0..n).contains(&v) && matrix.get(u as u32, v as u32)
+                {
+                    luma[(y * width + x) as usize] = 0;
+                }
+            }
+        }
+
+        assert!(decode(luma.clone(), width, height, DecodeHints::default()).is_none());
+        let result = decode(
+            luma,
+            width,
+            height,
+            DecodeHints {
+                AllowPartialSymbol: Some(true),
+                ..Default::default()
+            },
+        )
+        .expect("decode");
+        assert_eq!("partial symbol", result.getText());
+        let Some(RXingResultMetadataValue::ErasuresCorrected(erasures)) = result
+            .getRXingResultMetadata()
+            .get(&RXingResultMetadataType::ERASURES_CORRECTED)
+        else {
+            panic!("no erasures reported");
+        };
+        assert!(*erasures > 0);
+    }
+
+    #[test]
+    fn test_uncertain_modules_decode_as_erasures() {
+        // a speck of the opposite color at the center of every module in the middle of the
+        // symbol, on more codewords than it can correct as errors, 10 pixels per module
+        let matrix = encode("specks on the uncertain modules", "M");
+        let n = matrix.getWidth();
+        let size = (n + 8) * 10;
+        let mut luma = vec![255u8; (size * size) as usize];
+        for y in 0..size {
+            for x in 0..size {
+                let (u, v) = ((x / 10) as i32 - 4, (y / 10) as i32 - 4);
+                if !((0..n as i32).contains(&u) && (0..n as i32).contains(&v)) {
+                    continue;
+                }
+                let speck = (7..16).contains(&u)
+                    && (9..20).contains(&v)
+                    && (3..7).contains(&(x % 10))
+                    && (3..7).contains(&(y % 10));
+                if matrix.get(u as u32, v as u32) != speck {
+                    luma[(y * size + x) as usize] = 0;
+                }
+            }
+        }
+
+        let result = decode(luma, size, size, DecodeHints::default()).expect("decode");
+        assert_eq!("specks on the uncertain modules", result.getText());
+        let Some(RXingResultMetadataValue::ErasuresCorrected(erasures)) = result
+            .getRXingResultMetadata()
+            .get(&RXingResultMetadataType::ERASURES_CORRECTED)
+        else {
+            panic!("no erasures reported");
+        };
+        // a version 3-M symbol corrects 13 misread codewords
+        assert!(*erasures > 13, "{erasures}");
     }
 }
@@ -15,7 +15,7 @@
  */
 
 use crate::{
-    common::BitSourceBuilder,
+    common::{BitSourceBuilder, CharacterSet},
     qrcode::decoder::{decoded_bit_stream_parser, ErrorCorrectionLevel, Version},
     DecodeHints,
 };
@@ -131,4 +131,47 @@ fn testHanziLevel1() {
     assert_eq!("\u{30a2}", result);
 }
 
+fn decode_byte_mode(bytes: &[u8], hints: &DecodeHints) -> String {
+    let mut builder = BitSourceBuilder::new();
+    builder.write(0x04, 4); // Byte mode
+    builder.write(bytes.len() as u32, 8);
+    for byte in bytes {
+        builder.write(*byte as u32, 8);
+    }
+    decoded_bit_stream_parser::decode(
+        builder.asByteArray(),
+        Version::getVersionForNumber(5).expect("unwrap"),
+        ErrorCorrectionLevel::L,
+        hints,
+    )
+    .expect("unwrap")
+    .getText()
+    .to_owned()
+}
+
+#[test]
+fn testByteModeCharacterSetHint() {
+    let text = "Привет, мир";
+    let bytes = CharacterSet::Cp1251.encode(text).unwrap();
+    let hints = DecodeHints {
+        CharacterSet: Some("cp1251".to_owned()),
+        ..Default::default()
+    };
+    assert_eq!(text, decode_byte_mode(&bytes, &hints));
+}
+
+#[cfg(feature = "charset_detection")]
+#[test]
+fn testByteModeDetectedCharset() {
+    for (text, charset) in [
+        ("Zażółć gęślą jaźń", CharacterSet::ISO8859_2),
+        ("Příliš žluťoučký kůň úpěl", CharacterSet::Cp1250),
+        ("Съешь же ещё этих мягких булок", CharacterSet::Cp1251),
+        ("båd på fjorden", CharacterSet::ISO8859_1),
+    ] {
+        let bytes = charset.encode(text).unwrap();
+        assert_eq!(text, decode_byte_mode(&bytes, &DecodeHints::default()));
+    }
+}
+
 // TODO definitely need more tests here
@@ -20,7 +20,7 @@ use crate::{
     common::{
         string_utils, BitSource, CharacterSet, DecoderRXingResult, ECIStringBuilder, Eci, Result,
     },
-    DecodeHints, Exceptions,
+    Content, DecodeHints, Exceptions,
 };
 
 #[cfg(feature = "allow_forced_iso_ied_18004_compliance")]
@@ -155,7 +155,8 @@ pub fn decode(
         hasFNC1second,
     );
 
-    Ok(DecoderRXingResult::with_all(
+    let content = Content::from(&result);
+    let mut decoderRXingResult = DecoderRXingResult::with_all(
         bytes.to_owned(),
         result.build_result().to_string(),
         byteSegments.to_vec(),
@@ -165,7 +166,10 @@ pub fn decode(
         symbologyModifier,
         String::default(),
         false,
-    ))
+    );
+    decoderRXingResult.setContent(Some(content));
+
+    Ok(decoderRXingResult)
 }
 
 fn get_symbology_identifier(has_charset: bool, hasFNC1first: bool, hasFNC1second: bool) -> u32 {
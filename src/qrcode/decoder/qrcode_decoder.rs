@@ -27,9 +27,9 @@ use once_cell::sync::Lazy;
 use crate::{
     common::{
         reedsolomon::{get_predefined_genericgf, PredefinedGenericGF, ReedSolomonDecoder},
-        BitMatrix, DecoderRXingResult, Result,
+        BitMatrix, CodewordBlock, CodewordWitness, DecoderRXingResult, Result,
     },
-    DecodeHints, Exceptions,
+    DecodeHints, Exceptions, SymbolVersion,
 };
 
 use super::{decoded_bit_stream_parser, BitMatrixParser, DataBlock, QRCodeDecoderMetaData};
@@ -142,7 +142,9 @@ fn decode_bitmatrix_parser_with_hints(
     hints: &DecodeHints,
 ) -> Result<DecoderRXingResult> {
     let version = parser.readVersion()?;
-    let ecLevel = parser.readFormatInformation()?.getErrorCorrectionLevel();
+    let formatInfo = parser.readFormatInformation()?;
+    let ecLevel = formatInfo.getErrorCorrectionLevel();
+    let mask = formatInfo.getDataMask();
 
     // Read codewords
     let codewords = parser.readCodewords()?;
@@ -156,54 +158,49 @@ fn decode_bitmatrix_parser_with_hints(
 
     let mut resultBytes = vec![0u8; totalBytes];
     let mut resultOffset = 0;
+    let mut codewordWitness =
+        CodewordWitness::new(8, codewords.iter().map(|c| *c as i32).collect());
 
     // Error-correct and copy data blocks together into a stream of bytes
     for dataBlock in &dataBlocks {
-        let mut codewordBytes = dataBlock.getCodewords().to_vec();
         let numDataCodewords = dataBlock.getNumDataCodewords() as usize;
-        correctErrors(&mut codewordBytes, numDataCodewords)?;
-        for codeword_byte in codewordBytes.iter().take(numDataCodewords) {
-            resultBytes[resultOffset] = *codeword_byte;
+        let block = correctErrors(dataBlock.getCodewords(), numDataCodewords)?;
+        for codeword in block.data_codewords() {
+            resultBytes[resultOffset] = *codeword as u8;
             resultOffset += 1;
         }
+        codewordWitness.blocks.push(block);
     }
+    codewordWitness.corrected_codewords = resultBytes.iter().map(|b| *b as i32).collect();
 
     // Decode the contents of that stream of bytes
-    decoded_bit_stream_parser::decode(&resultBytes, version, ecLevel, hints)
+    let mut result = decoded_bit_stream_parser::decode(&resultBytes, version, ecLevel, hints)?;
+    result.setErrorsCorrected(codewordWitness.errors_corrected());
+    result.setCodewords(Some(codewordWitness));
+    result.setSymbolVersion(Some(SymbolVersion::QRCode {
+        version: version.getVersionNumber(),
+        mask,
+        model: 2,
+    }));
+    Ok(result)
 }
 
 /**
  * <p>Given data and error-correction codewords received, possibly corrupted by errors, attempts to
- * correct the errors in-place using Reed-Solomon error correction.</p>
+ * correct the errors using Reed-Solomon error correction.</p>
  *
  * @param codewordBytes data and error correction codewords
  * @param numDataCodewords number of codewords that are data bytes
+ * @return the block with its corrected codewords
  * @throws ChecksumException if error correction fails
  */
-fn correctErrors(codewordBytes: &mut [u8], numDataCodewords: usize) -> Result<()> {
-    let numCodewords = codewordBytes.len();
-    // First read into an array of ints
-    let mut codewordsInts = vec![0u8; numCodewords];
-    codewordsInts[..numCodewords].copy_from_slice(&codewordBytes[..numCodewords]);
-
-    let mut sending_code_words: Vec<i32> = codewordsInts.iter().map(|x| *x as i32).collect();
-
-    if let Err(Exceptions::ReedSolomonException(error_str)) = RS_DECODER.decode(
-        &mut sending_code_words,
-        (codewordBytes.len() - numDataCodewords) as i32,
-    ) {
-        return Err(Exceptions::ChecksumException(error_str));
-    }
+fn correctErrors(codewordBytes: &[u8], numDataCodewords: usize) -> Result<CodewordBlock> {
+    let received = codewordBytes.iter().map(|x| *x as i32).collect();
 
-    // Copy back into array of bytes -- only need to worry about the bytes that were data
-    // We don't care about errors in the error-correction codewords
-    for (code_word, sent_code_word) in codewordBytes
-        .iter_mut()
-        .zip(sending_code_words.iter())
-        .take(numDataCodewords)
-    {
-        *code_word = *sent_code_word as u8;
+    match CodewordBlock::correct(&RS_DECODER, received, numDataCodewords) {
+        Err(Exceptions::ReedSolomonException(error_str)) => {
+            Err(Exceptions::ChecksumException(error_str))
+        }
+        block => block,
     }
-
-    Ok(())
 }
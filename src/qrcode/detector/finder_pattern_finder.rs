@@ -93,12 +93,14 @@ impl<'a> FinderPatternFinder<'_> {
         let mut stateCount = [0u32; 5];
         let mut i = iSkip as i32 - 1;
         while i < maxI as i32 && !done {
+            hints.check_cancelled()?;
             // Get a row of black/white values
             FinderPatternFinder::doClearCounts(&mut stateCount);
             let mut currentState = 0;
             let mut j = 0;
+            let row = self.image.row(i as u32);
             while j < maxJ {
-                if self.image.get(j, i as u32) {
+                if row.get(j) {
                     // Black pixel
                     if (currentState & 1) == 1 {
                         // Counting white pixels
@@ -354,18 +356,19 @@ impl<'a> FinderPatternFinder<'_> {
         originalStateCountTotal: u32,
     ) -> f32 {
         let maxI = self.image.getHeight() as i32;
+        let column = self.image.column(centerJ);
         let mut crossCheckStateCount = [0u32; 5];
 
         // Start counting up from center
         let mut i = startI as i32;
-        while i >= 0 && self.image.get(centerJ, i as u32) {
+        while i >= 0 && column.get(i as u32) {
             crossCheckStateCount[2] += 1;
             i -= 1;
         }
         if i < 0 {
             return f32::NAN;
         }
-        while i >= 0 && !self.image.get(centerJ, i as u32) && crossCheckStateCount[1] <= maxCount {
+        while i >= 0 && !column.get(i as u32) && crossCheckStateCount[1] <= maxCount {
             crossCheckStateCount[1] += 1;
             i -= 1;
         }
@@ -373,7 +376,7 @@ impl<'a> FinderPatternFinder<'_> {
         if i < 0 || crossCheckStateCount[1] > maxCount {
             return f32::NAN;
         }
-        while i >= 0 && self.image.get(centerJ, i as u32) && crossCheckStateCount[0] <= maxCount {
+        while i >= 0 && column.get(i as u32) && crossCheckStateCount[0] <= maxCount {
             crossCheckStateCount[0] += 1;
             i -= 1;
         }
@@ -383,21 +386,21 @@ impl<'a> FinderPatternFinder<'_> {
 
         // Now also count down from center
         i = startI as i32 + 1;
-        while i < maxI && self.image.get(centerJ, i as u32) {
+        while i < maxI && column.get(i as u32) {
             crossCheckStateCount[2] += 1;
             i += 1;
         }
         if i == maxI {
             return f32::NAN;
         }
-        while i < maxI && !self.image.get(centerJ, i as u32) && crossCheckStateCount[3] < maxCount {
+        while i < maxI && !column.get(i as u32) && crossCheckStateCount[3] < maxCount {
             crossCheckStateCount[3] += 1;
             i += 1;
         }
         if i == maxI || crossCheckStateCount[3] >= maxCount {
             return f32::NAN;
         }
-        while i < maxI && self.image.get(centerJ, i as u32) && crossCheckStateCount[4] < maxCount {
+        while i < maxI && column.get(i as u32) && crossCheckStateCount[4] < maxCount {
             crossCheckStateCount[4] += 1;
             i += 1;
         }
@@ -435,10 +438,11 @@ impl<'a> FinderPatternFinder<'_> {
         originalStateCountTotal: u32,
     ) -> f32 {
         let maxJ = self.image.getWidth();
+        let row = self.image.row(centerI);
         let mut crossCheckStateCount = [0u32; 5];
 
         let mut j = startJ as i32;
-        while j >= 0 && self.image.get(j as u32, centerI) {
+        while j >= 0 && row.get(j as u32) {
             crossCheckStateCount[2] += 1;
             j -= 1;
         }
@@ -446,7 +450,7 @@ impl<'a> FinderPatternFinder<'_> {
             return f32::NAN;
         }
 
-        while j >= 0 && !self.image.get(j as u32, centerI) && crossCheckStateCount[1] <= maxCount {
+        while j >= 0 && !row.get(j as u32) && crossCheckStateCount[1] <= maxCount {
             crossCheckStateCount[1] += 1;
             j -= 1;
         }
@@ -454,7 +458,7 @@ impl<'a> FinderPatternFinder<'_> {
             return f32::NAN;
         }
 
-        while j >= 0 && self.image.get(j as u32, centerI) && crossCheckStateCount[0] <= maxCount {
+        while j >= 0 && row.get(j as u32) && crossCheckStateCount[0] <= maxCount {
             crossCheckStateCount[0] += 1;
             j -= 1;
         }
@@ -463,7 +467,7 @@ impl<'a> FinderPatternFinder<'_> {
         }
 
         j = startJ as i32 + 1;
-        while j < (maxJ as i32) && self.image.get(j as u32, centerI) {
+        while j < (maxJ as i32) && row.get(j as u32) {
             crossCheckStateCount[2] += 1;
             j += 1;
         }
@@ -471,10 +475,7 @@ impl<'a> FinderPatternFinder<'_> {
             return f32::NAN;
         }
 
-        while j < maxJ as i32
-            && !self.image.get(j as u32, centerI)
-            && crossCheckStateCount[3] < maxCount
-        {
+        while j < maxJ as i32 && !row.get(j as u32) && crossCheckStateCount[3] < maxCount {
             crossCheckStateCount[3] += 1;
             j += 1;
         }
@@ -482,10 +483,7 @@ impl<'a> FinderPatternFinder<'_> {
             return f32::NAN;
         }
 
-        while j < (maxJ as i32)
-            && self.image.get(j as u32, centerI)
-            && crossCheckStateCount[4] < maxCount
-        {
+        while j < (maxJ as i32) && row.get(j as u32) && crossCheckStateCount[4] < maxCount {
             crossCheckStateCount[4] += 1;
             j += 1;
         }
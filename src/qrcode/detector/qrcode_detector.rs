@@ -16,8 +16,8 @@
 
 use crate::{
     common::{
-        BitMatrix, DefaultGridSampler, GridSampler, PerspectiveTransform, Quadrilateral, Result,
-        SamplerControl,
+        BitMatrix, DefaultGridSampler, DetectedPattern, GridSampler, GridSampling, PatternKind,
+        PerspectiveTransform, Quadrilateral, Result, SamplerControl, SamplingGrid,
     },
     point,
     qrcode::decoder::Version,
@@ -38,6 +38,7 @@ use super::{
 pub struct Detector<'a> {
     image: &'a BitMatrix,
     resultPointCallback: Option<PointCallback>,
+    gridSampling: GridSampling,
 }
 
 impl<'a> Detector<'_> {
@@ -45,6 +46,7 @@ impl<'a> Detector<'_> {
         Detector {
             image,
             resultPointCallback: None,
+            gridSampling: GridSampling::default(),
         }
     }
 
@@ -77,6 +79,7 @@ impl<'a> Detector<'_> {
      */
     pub fn detect_with_hints(&mut self, hints: &DecodeHints) -> Result<QRCodeDetectorResult> {
         self.resultPointCallback = hints.NeedResultPointCallback.clone();
+        self.gridSampling = hints.GridSampling.unwrap_or_default();
 
         let mut finder =
             FinderPatternFinder::with_callback(self.image, self.resultPointCallback.clone());
@@ -139,7 +142,29 @@ impl<'a> Detector<'_> {
         )
         .ok_or(Exceptions::NOT_FOUND)?;
 
-        let bits = Detector::sampleGrid(self.image, transform, dimension)?;
+        let bits = Detector::sampleGrid(self.image, transform, dimension, &DefaultGridSampler)
+            .or_else(|err| match self.gridSampling {
+                // a grid reaching beyond the image is read from its edge instead
+                GridSampling::Clamp => Detector::sampleGrid(
+                    self.image,
+                    transform,
+                    dimension,
+                    self.gridSampling.sampler(),
+                ),
+                _ => Err(err),
+            })?;
+
+        let mut patterns = vec![
+            DetectedPattern::new(PatternKind::Finder, topLeft.into()),
+            DetectedPattern::new(PatternKind::Finder, topRight.into()),
+            DetectedPattern::new(PatternKind::Finder, bottomLeft.into()),
+        ];
+        if let Some(alignmentPattern) = &alignmentPattern {
+            patterns.push(DetectedPattern::new(
+                PatternKind::Alignment,
+                alignmentPattern.into(),
+            ));
+        }
 
         let mut points = vec![
             Point::from(bottomLeft),
@@ -151,7 +176,9 @@ impl<'a> Detector<'_> {
             points.push(alignmentPattern.ok_or(Exceptions::NOT_FOUND)?.into())
         }
 
-        Ok(QRCodeDetectorResult::new(bits, points))
+        Ok(QRCodeDetectorResult::new(bits, points).with_sampling_grid(
+            SamplingGrid::with_transform(dimension, dimension, transform).with_patterns(patterns),
+        ))
     }
 
     fn createTransform<T: Into<Point>, X: Into<Point>>(
@@ -205,8 +232,8 @@ impl<'a> Detector<'_> {
         image: &BitMatrix,
         transform: PerspectiveTransform,
         dimension: u32,
+        sampler: &dyn GridSampler,
     ) -> Result<BitMatrix> {
-        let sampler = DefaultGridSampler;
         let (res, _) = sampler.sample_grid(
             image,
             dimension,
@@ -1,11 +1,13 @@
 use crate::{
-    common::{BitMatrix, DetectorRXingResult},
-    Point,
+    common::{sample_as_hinted, BitMatrix, DetectorRXingResult, Result, SamplingGrid},
+    DecodeHints, Point,
 };
 
 pub struct QRCodeDetectorResult {
     bit_source: BitMatrix,
     result_points: Vec<Point>,
+    sampling_grid: Option<SamplingGrid>,
+    erasures: Option<BitMatrix>,
 }
 
 impl QRCodeDetectorResult {
@@ -13,8 +15,43 @@ impl QRCodeDetectorResult {
         Self {
             bit_source,
             result_points,
+            sampling_grid: None,
+            erasures: None,
         }
     }
+
+    pub fn with_sampling_grid(mut self, sampling_grid: SamplingGrid) -> Self {
+        self.sampling_grid = Some(sampling_grid);
+        self
+    }
+
+    /// Marks the modules of a symbol that lies partly outside of the image, which couldn't be
+    /// sampled
+    pub fn with_erasures(mut self, erasures: BitMatrix) -> Self {
+        self.erasures = Some(erasures);
+        self
+    }
+
+    /// The modules that couldn't be sampled, None if the whole symbol was in the image
+    pub fn getErasures(&self) -> Option<&BitMatrix> {
+        self.erasures.as_ref()
+    }
+
+    /// Samples the bits again through the sampling grid if the `FixedPointSampling` or
+    /// `GridSampling` hint asks for it and the whole symbol is in the image, see
+    /// `common::sample_as_hinted`
+    pub(crate) fn sample_as_hinted(self, image: &BitMatrix, hints: &DecodeHints) -> Result<Self> {
+        let Some(bit_source) = self
+            .sampling_grid
+            .as_ref()
+            .filter(|_| self.erasures.is_none())
+            .and_then(|grid| sample_as_hinted(image, grid, hints))
+            .transpose()?
+        else {
+            return Ok(self);
+        };
+        Ok(Self { bit_source, ..self })
+    }
 }
 
 impl DetectorRXingResult for QRCodeDetectorResult {
@@ -25,4 +62,8 @@ impl DetectorRXingResult for QRCodeDetectorResult {
     fn getPoints(&self) -> &[crate::Point] {
         &self.result_points
     }
+
+    fn getSamplingGrid(&self) -> Option<&SamplingGrid> {
+        self.sampling_grid.as_ref()
+    }
 }
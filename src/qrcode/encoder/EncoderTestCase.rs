@@ -15,7 +15,7 @@
  */
 
 use crate::{
-    common::{BitArray, CharacterSet},
+    common::{most_compact_charset, BitArray, CharacterSet},
     qrcode::{
         decoder::{ErrorCorrectionLevel, Mode, Version},
         encoder::{qrcode_encoder, MinimalEncoder},
@@ -216,6 +216,122 @@ fn testSimpleutf8ECI() {
     assert_eq!(expected, qrCode.to_string());
 }
 
+#[test]
+fn test_most_compact_charset() {
+    assert_eq!(
+        CharacterSet::ISO8859_1,
+        most_compact_charset("hello, wörld")
+    );
+    assert_eq!(CharacterSet::ISO8859_5, most_compact_charset("Привет"));
+    assert_eq!(SHIFT_JIS_CHARSET, most_compact_charset("こんにちは"));
+    assert_eq!(CharacterSet::GB2312, most_compact_charset("这是中文"));
+    assert_eq!(CharacterSet::EUC_KR, most_compact_charset("안녕하세요"));
+    // Shift_JIS has Cyrillic letters too
+    assert_eq!(SHIFT_JIS_CHARSET, most_compact_charset("Привет こんにちは"));
+    assert_eq!(CharacterSet::UTF8, most_compact_charset("Привет 😀"));
+}
+
+#[test]
+fn test_compact_eci_without_hint() {
+    let header = |content: &str| {
+        let data = qrcode_encoder::encode_data_with_hints(
+            content,
+            ErrorCorrectionLevel::L,
+            &EncodeHints::default(),
+        )
+        .expect("encode");
+        let read = |from: usize, len: usize| {
+            (from..from + len).fold(0, |value, i| value << 1 | data.bits.get(i) as u32)
+        };
+        (data.mode, read(0, 4), read(4, 8))
+    };
+
+    // ISO-8859-5, one byte a letter instead of two in UTF-8
+    assert_eq!(
+        (Mode::BYTE, Mode::ECI.getBits() as u32, 7),
+        header("Привет мир")
+    );
+    assert_eq!(
+        (Mode::BYTE, Mode::ECI.getBits() as u32, 20),
+        header("Привет 世界")
+    );
+    assert_eq!(
+        (Mode::BYTE, Mode::ECI.getBits() as u32, 26),
+        header("Привет 😀")
+    );
+    // Shift_JIS text of only double byte characters goes in Kanji mode, which needs no ECI
+    assert_eq!(Mode::KANJI, header("こんにちは").0);
+    // the default encoding, without ECI
+    assert_eq!(Mode::BYTE.getBits() as u32, header("héllo").1);
+}
+
+#[test]
+fn test_append_hanzi_bytes() {
+    assert!(qrcode_encoder::isOnlyHanzi("这是中文"));
+    assert!(!qrcode_encoder::isOnlyHanzi("中文abc"));
+    assert!(!qrcode_encoder::isOnlyHanzi("😀"));
+
+    // 0xB0A1 in GB2312, the first character of level 1, and 0xA5A2 among the symbols before it
+    let mut bits = BitArray::new();
+    qrcode_encoder::appendBytes(
+        "\u{554a}\u{30a2}",
+        Mode::HANZI,
+        &mut bits,
+        qrcode_encoder::DEFAULT_BYTE_MODE_ENCODING,
+    )
+    .expect("ok");
+    let mut expected = BitArray::new();
+    expected.appendBits(0x0a * 0x60, 13).expect("append");
+    expected.appendBits(0x04 * 0x60 + 0x01, 13).expect("append");
+    assert_eq!(expected.to_string(), bits.to_string());
+
+    assert!(qrcode_encoder::appendHanziBytes("abc", &mut bits).is_err());
+}
+
+#[test]
+fn test_minimal_encoder_hanzi() {
+    let encode = |content: &str, hanzi: bool| {
+        MinimalEncoder::new(content, None, false, ErrorCorrectionLevel::L)
+            .with_hanzi(hanzi)
+            .encode(None)
+            .expect("encode")
+    };
+
+    let hanzi = encode("这是中文", true);
+    assert_eq!("HANZI(....)", hanzi.to_string());
+    // mode, subset, count and 13 bits a character
+    assert_eq!(4 + 4 + 8 + 4 * 13, hanzi.getSize());
+    assert_eq!(
+        "ECI(utf-16be),BYTE(....)",
+        encode("这是中文", false).to_string()
+    );
+    assert_eq!(
+        "HANZI(..),NUMERIC(2024)",
+        encode("这是2024", true).to_string()
+    );
+    // Kanji mode is as short and needs no subset indicator
+    assert_eq!("KANJI(.....)", encode("こんにちは", true).to_string());
+}
+
+#[test]
+fn test_encode_hanzi_mode() {
+    use crate::{qrcode::QRCodeReader, test_fixtures::luma_bitmap, BarcodeFormat, Reader, Writer};
+
+    let content = "二维码中文编码";
+    let hints = EncodeHints::default().with(EncodeHintValue::QrHanzi(true));
+    let data = qrcode_encoder::encode_data_with_hints(content, ErrorCorrectionLevel::L, &hints)
+        .expect("encode");
+    assert_eq!(vec![(Mode::HANZI, 7)], data.segments);
+
+    let matrix = crate::qrcode::QRCodeWriter
+        .encode_with_hints(content, &BarcodeFormat::QR_CODE, 100, 100, &hints)
+        .expect("encode");
+    let result = QRCodeReader
+        .decode(&mut luma_bitmap(&matrix))
+        .expect("decode");
+    assert_eq!(content, result.getText());
+}
+
 #[test]
 fn testEncodeKanjiMode() {
     let hints = EncodeHints::default().with(EncodeHintValue::CharacterSet("Shift_JIS".to_owned()));
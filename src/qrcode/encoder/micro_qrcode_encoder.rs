@@ -0,0 +1,429 @@
+/*
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Micro QR Code encoding, per ISO 18004:2015 for versions M1 to M4.
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::{
+    common::{BitArray, BitFieldBaseType, CharacterSet, Result},
+    qrcode::decoder::{ErrorCorrectionLevel, Mode, Version, VersionRef},
+    EncodeHints, Exceptions,
+};
+
+use super::{
+    mask_util, matrix_util,
+    qrcode_encoder::{
+        appendBytes, chooseModeWithEncoding, generateECBytes, DEFAULT_BYTE_MODE_ENCODING,
+    },
+    ByteMatrix, QRCode,
+};
+
+/// The number of Micro QR Code mask patterns
+pub const NUM_MICRO_MASK_PATTERNS: i32 = 4;
+
+// The QR Code mask pattern each Micro QR Code mask pattern is, see ISO 18004:2015 7.8.2
+const MICRO_TO_QR_MASK: [u32; 4] = [1, 4, 6, 7];
+
+// The format information is masked with this instead of the QR Code mask
+const FORMAT_INFO_MASK_MICRO: u32 = 0x4445;
+
+// See ISO 18004:2015 7.9.1, the same generator as for QR Codes
+const FORMAT_INFO_POLY: u32 = 0x537;
+
+/**
+ * Encodes `content` as the smallest Micro QR Code that holds it at `ec_level`, or the version
+ * given by the `QrVersion` hint (1 to 4 for M1 to M4), in a single numeric, alphanumeric, byte
+ * or kanji segment. M1 has no error correction level of its own and is only chosen for level L.
+ *
+ * # Errors
+ * `WriterException` if the content does not fit in an M4 symbol, can not be encoded in a mode
+ * the version supports, or `ec_level` is H, which Micro QR Codes do not have
+ */
+pub fn encode_with_hints(
+    content: &str,
+    ec_level: ErrorCorrectionLevel,
+    hints: &EncodeHints,
+) -> Result<QRCode> {
+    let encoding = if let Some(name) = &hints.CharacterSet {
+        CharacterSet::get_character_set_by_name(name).ok_or(Exceptions::WRITER)?
+    } else if DEFAULT_BYTE_MODE_ENCODING.encode(content).is_ok() {
+        DEFAULT_BYTE_MODE_ENCODING
+    } else {
+        // Micro QR Codes have no ECI, a reader has to guess this one
+        CharacterSet::UTF8
+    };
+    let mode = chooseModeWithEncoding(content, encoding);
+
+    let mut data_bits = BitArray::new();
+    appendBytes(content, mode, &mut data_bits, encoding)?;
+    let num_letters = if mode == Mode::BYTE {
+        data_bits.getSizeInBytes()
+    } else {
+        content.graphemes(true).count()
+    } as u32;
+
+    let requested = if let Some(v) = &hints.QrVersion {
+        Some(
+            v.parse::<u32>()
+                .map_err(|e| Exceptions::writer_with(format!("could not parse {v}: {e}")))?,
+        )
+    } else {
+        None
+    };
+
+    let mut chosen = None;
+    for number in 1..=4 {
+        if requested.is_some_and(|r| r != number) {
+            continue;
+        }
+        let version = Version::Micro(number)?;
+        if symbol_number(version, ec_level).is_none() || !supports_mode(version, mode) {
+            continue;
+        }
+        let count_bits = mode.CharacterCountBits(version);
+        if num_letters >= 1 << count_bits {
+            continue;
+        }
+        let bits_needed = Mode::get_codec_mode_bits_length(version) as usize
+            + count_bits as usize
+            + data_bits.get_size();
+        if bits_needed <= data_capacity_bits(version, ec_level) {
+            chosen = Some(version);
+            break;
+        }
+    }
+    let version = chosen.ok_or_else(|| {
+        Exceptions::writer_with(if requested.is_some() {
+            "Data too big for requested version"
+        } else {
+            "Data too big for any Micro QR Code version"
+        })
+    })?;
+
+    let mut bits = BitArray::new();
+    bits.appendBits(
+        mode_indicator(mode) as BitFieldBaseType,
+        Mode::get_codec_mode_bits_length(version) as usize,
+    )?;
+    bits.appendBits(
+        num_letters as BitFieldBaseType,
+        mode.CharacterCountBits(version) as usize,
+    )?;
+    bits.appendBitArray(data_bits);
+
+    let data_codewords = terminate_and_pad(bits, version, ec_level)?;
+    let num_ec_codewords = version
+        .getECBlocksForLevel(ec_level)
+        .getECCodewordsPerBlock() as usize;
+    let ec_codewords = generateECBytes(&data_codewords, num_ec_codewords)?;
+    let final_bits = place_codewords(&data_codewords, &ec_codewords, version, ec_level)?;
+
+    let dimension = version.getDimensionForVersion();
+    let mut matrix = ByteMatrix::new(dimension, dimension);
+    let mut mask_pattern = -1;
+    if let Some(v) = &hints.QrMaskPattern {
+        let hint_mask_pattern = v.parse::<i32>().unwrap_or(-1);
+        if (0..NUM_MICRO_MASK_PATTERNS).contains(&hint_mask_pattern) {
+            mask_pattern = hint_mask_pattern;
+        }
+    }
+    if mask_pattern == -1 {
+        let mut best_score = 0;
+        for candidate in 0..NUM_MICRO_MASK_PATTERNS {
+            build_matrix(&final_bits, version, ec_level, candidate, &mut matrix)?;
+            let score = mask_score(&matrix);
+            if mask_pattern == -1 || score > best_score {
+                best_score = score;
+                mask_pattern = candidate;
+            }
+        }
+    }
+    build_matrix(&final_bits, version, ec_level, mask_pattern, &mut matrix)?;
+
+    let mut qr_code = QRCode::new();
+    qr_code.setECLevel(ec_level);
+    qr_code.setMode(mode);
+    qr_code.setVersion(version);
+    qr_code.setMaskPattern(mask_pattern);
+    qr_code.setMatrix(matrix);
+
+    Ok(qr_code)
+}
+
+// The 3 bit symbol number of the format information, see ISO 18004:2015 Table 13
+fn symbol_number(version: VersionRef, ec_level: ErrorCorrectionLevel) -> Option<u32> {
+    match (version.getVersionNumber(), ec_level) {
+        (1, ErrorCorrectionLevel::L) => Some(0),
+        (2, ErrorCorrectionLevel::L) => Some(1),
+        (2, ErrorCorrectionLevel::M) => Some(2),
+        (3, ErrorCorrectionLevel::L) => Some(3),
+        (3, ErrorCorrectionLevel::M) => Some(4),
+        (4, ErrorCorrectionLevel::L) => Some(5),
+        (4, ErrorCorrectionLevel::M) => Some(6),
+        (4, ErrorCorrectionLevel::Q) => Some(7),
+        _ => None,
+    }
+}
+
+fn supports_mode(version: VersionRef, mode: Mode) -> bool {
+    match mode {
+        Mode::NUMERIC => true,
+        Mode::ALPHANUMERIC => version.getVersionNumber() >= 2,
+        Mode::BYTE | Mode::KANJI => version.getVersionNumber() >= 3,
+        _ => false,
+    }
+}
+
+fn mode_indicator(mode: Mode) -> u32 {
+    match mode {
+        Mode::NUMERIC => 0,
+        Mode::ALPHANUMERIC => 1,
+        Mode::BYTE => 2,
+        _ => 3,
+    }
+}
+
+// M1 and M3 end their data in a 4 bit codeword
+fn has_half_codeword(version: VersionRef) -> bool {
+    version.getVersionNumber() % 2 == 1
+}
+
+fn num_data_codewords(version: VersionRef, ec_level: ErrorCorrectionLevel) -> usize {
+    (version.getTotalCodewords() - version.getECBlocksForLevel(ec_level).getTotalECCodewords())
+        as usize
+}
+
+fn data_capacity_bits(version: VersionRef, ec_level: ErrorCorrectionLevel) -> usize {
+    num_data_codewords(version, ec_level) * 8 - if has_half_codeword(version) { 4 } else { 0 }
+}
+
+/**
+ * Appends the terminator and the padding, and splits the bits into the data codewords. A 4 bit
+ * final codeword of M1 and M3 takes the high half of its byte, as it goes into the error
+ * correction.
+ */
+fn terminate_and_pad(
+    mut bits: BitArray,
+    version: VersionRef,
+    ec_level: ErrorCorrectionLevel,
+) -> Result<Vec<u8>> {
+    let capacity = data_capacity_bits(version, ec_level);
+    let terminator =
+        (Mode::get_terminator_bit_length(version) as usize).min(capacity - bits.get_size());
+    bits.appendBits(0, terminator)?;
+
+    let full_codewords = capacity / 8;
+    if bits.get_size() > full_codewords * 8 {
+        // ends in the 4 bit codeword, which the zeros fill
+        let rest = capacity - bits.get_size();
+        bits.appendBits(0, rest)?;
+    } else {
+        let rest = (8 - bits.get_size() % 8) % 8;
+        bits.appendBits(0, rest)?;
+        let mut pad = [0xEC, 0x11].into_iter().cycle();
+        while bits.get_size() < full_codewords * 8 {
+            bits.appendBits(pad.next().unwrap_or_default(), 8)?;
+        }
+        bits.appendBits(0, capacity - bits.get_size())?;
+    }
+
+    let mut codewords = vec![0u8; num_data_codewords(version, ec_level)];
+    for (i, codeword) in codewords.iter_mut().enumerate() {
+        for bit in 0..8 {
+            let index = i * 8 + bit;
+            if index < bits.get_size() && bits.get(index) {
+                *codeword |= 0x80 >> bit;
+            }
+        }
+    }
+    Ok(codewords)
+}
+
+// The bits in the order they go into the symbol, only the high half of a 4 bit codeword
+fn place_codewords(
+    data: &[u8],
+    ec: &[u8],
+    version: VersionRef,
+    ec_level: ErrorCorrectionLevel,
+) -> Result<BitArray> {
+    let half = has_half_codeword(version).then(|| num_data_codewords(version, ec_level) - 1);
+    let mut bits = BitArray::new();
+    for (i, codeword) in data.iter().chain(ec).enumerate() {
+        if Some(i) == half {
+            bits.appendBits((*codeword >> 4) as BitFieldBaseType, 4)?;
+        } else {
+            bits.appendBits(*codeword as BitFieldBaseType, 8)?;
+        }
+    }
+    Ok(bits)
+}
+
+fn build_matrix(
+    data_bits: &BitArray,
+    version: VersionRef,
+    ec_level: ErrorCorrectionLevel,
+    mask_pattern: i32,
+    matrix: &mut ByteMatrix,
+) -> Result<()> {
+    let dimension = matrix.getWidth();
+    matrix_util::clearMatrix(matrix);
+
+    // The finder pattern and its separator
+    matrix_util::embedPositionDetectionPattern(0, 0, matrix);
+    for i in 0..8 {
+        matrix.set(i, 7, 0);
+        matrix.set(7, i, 0);
+    }
+
+    // The timing patterns along the top and left edges
+    for i in 8..dimension {
+        let dark = u8::from(i % 2 == 0);
+        matrix.set(i, 0, dark);
+        matrix.set(0, i, dark);
+    }
+
+    // The format information, from bit 14 along row 8 to bit 0 up column 8
+    let symbol = symbol_number(version, ec_level).ok_or(Exceptions::WRITER)?;
+    let format_data = (symbol << 2) | mask_pattern as u32;
+    let format_info = ((format_data << 10)
+        | matrix_util::calculateBCHCode(format_data, FORMAT_INFO_POLY)?)
+        ^ FORMAT_INFO_MASK_MICRO;
+    for i in 0..8 {
+        matrix.set(1 + i, 8, ((format_info >> (14 - i)) & 1) as u8);
+    }
+    for i in 0..7 {
+        matrix.set(8, 7 - i, ((format_info >> (6 - i)) & 1) as u8);
+    }
+
+    // The data, in pairs of columns from the right, alternately upwards and downwards
+    let mask = MICRO_TO_QR_MASK[mask_pattern as usize];
+    let mut bit_index = 0;
+    let mut upwards = true;
+    let mut x = dimension - 1;
+    while x > 0 {
+        for row in 0..dimension {
+            let y = if upwards { dimension - 1 - row } else { row };
+            for xx in [x, x - 1] {
+                if !matrix_util::isEmpty(matrix.get(xx, y)) {
+                    continue;
+                }
+                let mut bit = bit_index < data_bits.get_size() && data_bits.get(bit_index);
+                bit_index += 1;
+                if mask_util::getDataMaskBit(mask, xx, y)? {
+                    bit = !bit;
+                }
+                matrix.set_bool(xx, y, bit);
+            }
+        }
+        upwards = !upwards;
+        x -= 2;
+    }
+    if bit_index != data_bits.get_size() {
+        return Err(Exceptions::writer_with(format!(
+            "Not all bits consumed: {bit_index}/{}",
+            data_bits.get_size()
+        )));
+    }
+    Ok(())
+}
+
+/**
+ * The mask evaluation of ISO 18004:2015 7.8.3.2: the more dark modules along the right and bottom
+ * edges, the better, weighing the edge with fewer of them 16 times.
+ */
+fn mask_score(matrix: &ByteMatrix) -> u32 {
+    let last = matrix.getWidth() - 1;
+    let right = (1..=last).filter(|&y| matrix.get(last, y) == 1).count() as u32;
+    let bottom = (1..=last).filter(|&x| matrix.get(x, last) == 1).count() as u32;
+    if right <= bottom {
+        right * 16 + bottom
+    } else {
+        bottom * 16 + right
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        common::BitMatrix,
+        qrcode::{cpp_port::decoder::Decode, decoder::ErrorCorrectionLevel},
+        EncodeHints,
+    };
+
+    use super::encode_with_hints;
+
+    fn round_trip(content: &str, ec_level: ErrorCorrectionLevel, hints: &EncodeHints) -> u32 {
+        let code = encode_with_hints(content, ec_level, hints).expect("encode");
+        let matrix = code.getMatrix().as_ref().expect("matrix");
+        let mut bits = BitMatrix::new(matrix.getWidth(), matrix.getHeight()).unwrap();
+        for y in 0..matrix.getHeight() {
+            for x in 0..matrix.getWidth() {
+                if matrix.get(x, y) == 1 {
+                    bits.set(x, y);
+                }
+            }
+        }
+        let result = Decode(&bits).expect("decode");
+        assert_eq!(content, result.text());
+        let codewords = result.codewords().expect("codewords");
+        assert_eq!(0, codewords.errors_corrected());
+        code.getVersion().expect("version").getVersionNumber()
+    }
+
+    #[test]
+    fn test_version_selection() {
+        let l = ErrorCorrectionLevel::L;
+        let hints = EncodeHints::default();
+        // 17 of the 20 bits, ending in the 4 bit codeword
+        assert_eq!(1, round_trip("12345", l, &hints));
+        assert_eq!(2, round_trip("123456", l, &hints));
+        assert_eq!(2, round_trip("AB-12", l, &hints));
+        assert_eq!(3, round_trip("hello", l, &hints));
+        assert_eq!(3, round_trip("12345678901234567890123", l, &hints));
+        assert_eq!(4, round_trip("Micro QR Code!", l, &hints));
+        assert_eq!(
+            4,
+            round_trip("1234567890123456789012345678901234", l, &hints)
+        );
+        assert_eq!(2, round_trip("1234", ErrorCorrectionLevel::M, &hints));
+        assert_eq!(4, round_trip("HELLO", ErrorCorrectionLevel::Q, &hints));
+    }
+
+    #[test]
+    fn test_every_mask_and_version() {
+        for version in 1..=4 {
+            for mask in 0..4 {
+                let hints = EncodeHints {
+                    QrVersion: Some(version.to_string()),
+                    QrMaskPattern: Some(mask.to_string()),
+                    ..Default::default()
+                };
+                assert_eq!(version, round_trip("0123", ErrorCorrectionLevel::L, &hints));
+            }
+        }
+    }
+
+    #[test]
+    fn test_unencodable() {
+        let hints = EncodeHints::default();
+        assert!(encode_with_hints("1", ErrorCorrectionLevel::H, &hints).is_err());
+        assert!(encode_with_hints(&"9".repeat(36), ErrorCorrectionLevel::L, &hints).is_err());
+        let m1 = EncodeHints {
+            QrVersion: Some("1".to_owned()),
+            ..Default::default()
+        };
+        assert!(encode_with_hints("ABC", ErrorCorrectionLevel::L, &m1).is_err());
+    }
+}
@@ -88,6 +88,7 @@ pub struct MinimalEncoder {
     isGS1: bool,
     encoders: ECIEncoderSet,
     ecLevel: ErrorCorrectionLevel,
+    hanzi: bool,
 }
 
 impl MinimalEncoder {
@@ -117,9 +118,19 @@ impl MinimalEncoder {
             isGS1,
             encoders: ECIEncoderSet::new(stringToEncode, priorityCharset, None),
             ecLevel,
+            hanzi: false,
         }
     }
 
+    /**
+     * Lets the encoder use the Hanzi mode of GB/T 18284 for GB2312 characters, 13 bits each as
+     * in Kanji mode. It is a Chinese extension of QR codes, which not every reader supports.
+     */
+    pub fn with_hanzi(mut self, hanzi: bool) -> Self {
+        self.hanzi = hanzi;
+        self
+    }
+
     /**
      * Encodes the string minimally
      *
@@ -219,6 +230,10 @@ impl MinimalEncoder {
         qrcode_encoder::isOnlyDoubleByteKanji(c)
     }
 
+    pub fn isHanzi(c: &str) -> bool {
+        qrcode_encoder::isOnlyHanzi(c)
+    }
+
     pub fn isAlphanumeric(c: &str) -> bool {
         if c.len() == 1 {
             if let Some(ch) = c.chars().next() {
@@ -234,6 +249,7 @@ impl MinimalEncoder {
             Mode::ALPHANUMERIC => Self::isAlphanumeric(c),
             Mode::BYTE => true,
             Mode::KANJI => Self::isDoubleByteKanji(c),
+            Mode::HANZI => self.hanzi && Self::isHanzi(c),
             _ => false, // any character can be encoded as byte(s). Up to the caller to manage splitting into
                         // multiple bytes when String.getBytes(Charset) return more than one byte.
         }
@@ -245,6 +261,7 @@ impl MinimalEncoder {
             Some(Mode::ALPHANUMERIC) => Ok(1),
             Some(Mode::BYTE) => Ok(3),
             Some(Mode::KANJI) | None => Ok(0),
+            Some(Mode::HANZI) => Ok(4),
             _ => Err(Exceptions::illegal_argument_with(format!(
                 "Illegal mode {mode:?}"
             ))),
@@ -354,6 +371,29 @@ impl MinimalEncoder {
             )?;
         }
 
+        if self.canEncode(
+            &Mode::HANZI,
+            self.stringToEncode.get(from).ok_or(Exceptions::FORMAT)?,
+        ) {
+            self.addEdge(
+                edges,
+                from,
+                Some(Arc::new(
+                    Edge::new(
+                        Mode::HANZI,
+                        from,
+                        0,
+                        1,
+                        previous.clone(),
+                        version,
+                        self.encoders.clone(),
+                        self.stringToEncode.clone(),
+                    )
+                    .ok_or(Exceptions::WRITER)?,
+                )),
+            )?;
+        }
+
         let inputLength = self.stringToEncode.len();
         if self.canEncode(
             &Mode::ALPHANUMERIC,
@@ -556,14 +596,14 @@ impl MinimalEncoder {
         // a list of all edges that lead to it that have the same encoding and mode.
         // The lists are created lazily
 
-        // The last dimension in the array below encodes the 4 modes KANJI, ALPHANUMERIC, NUMERIC and BYTE via the
-        // function getCompactedOrdinal(Mode)
-        let mut edges = vec![vec![vec![None; 4]; self.encoders.len()]; inputLength + 1];
+        // The last dimension in the array below encodes the 5 modes KANJI, ALPHANUMERIC, NUMERIC, BYTE and HANZI via
+        // the function getCompactedOrdinal(Mode)
+        let mut edges = vec![vec![vec![None; 5]; self.encoders.len()]; inputLength + 1];
         self.addEdges(version, &mut edges, 0, None)?;
 
         for i in 1..=inputLength {
             for j in 0..self.encoders.len() {
-                for k in 0..4 {
+                for k in 0..5 {
                     if edges[i][j][k].is_some() && i < inputLength {
                         let e = edges[i][j][k].clone();
                         self.addEdges(version, &mut edges, i, e)?;
@@ -575,7 +615,7 @@ impl MinimalEncoder {
         let mut minimalK = None;
         let mut minimalSize = u32::MAX;
         for j in 0..self.encoders.len() {
-            for k in 0..4 {
+            for k in 0..5 {
                 if let Some(edge) = &edges[inputLength][j][k] {
                     if edge.cachedTotalSize < minimalSize {
                         minimalSize = edge.cachedTotalSize;
@@ -659,6 +699,9 @@ impl Edge {
 
                 if previous.is_none() || mode != previous.as_ref()?.mode || needECI {
                     size += 4 + mode.getCharacterCountBits(version) as u32;
+                    if mode == Mode::HANZI {
+                        size += 4; // the subset indicator
+                    }
                 }
                 match mode {
                     Mode::NUMERIC => {
@@ -683,7 +726,7 @@ impl Edge {
                             size += 4 + 8; // the ECI assignment numbers for ISO-8859-x, UTF-8 and UTF-16 are all 8 bit long
                         }
                     }
-                    Mode::KANJI => size += 13,
+                    Mode::KANJI | Mode::HANZI => size += 13,
                     _ => {}
                 }
                 size
@@ -862,6 +905,23 @@ impl RXingResultList {
     pub fn getVersion(&self) -> VersionRef {
         self.version
     }
+
+    /**
+     * returns the mode and character count of the segments carrying data, that is
+     * excluding ECI and FNC1 markers
+     */
+    pub fn getSegments(&self) -> Vec<(Mode, u32)> {
+        self.list
+            .iter()
+            .filter(|node| {
+                !matches!(
+                    node.mode,
+                    Mode::ECI | Mode::FNC1_FIRST_POSITION | Mode::FNC1_SECOND_POSITION
+                )
+            })
+            .map(|node| (node.mode, node.characterLength))
+            .collect()
+    }
 }
 
 impl fmt::Display for RXingResultList {
@@ -940,6 +1000,7 @@ impl RXingResultNode {
             Mode::BYTE => size += 8 * self.getCharacterCountIndicator(),
             Mode::ECI => size += 8,
             Mode::KANJI => size += 13 * self.characterLength,
+            Mode::HANZI => size += 4 + 13 * self.characterLength,
             _ => {}
         }
         // switch (mode) {
@@ -993,6 +1054,10 @@ impl RXingResultNode {
      */
     fn getBits(&self, bits: &mut BitArray) -> Result<()> {
         bits.appendBits(self.mode.getBits() as BitFieldBaseType, 4)?;
+        if self.mode == Mode::HANZI {
+            // the GB2312 subset, the only one defined
+            bits.appendBits(1, 4)?;
+        }
         if self.characterLength > 0 {
             let length = self.getCharacterCountIndicator();
             bits.appendBits(
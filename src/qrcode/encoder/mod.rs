@@ -2,6 +2,7 @@ mod block_pair;
 mod byte_matrix;
 pub mod mask_util;
 pub mod matrix_util;
+pub mod micro_qrcode_encoder;
 mod minimal_encoder;
 mod qr_code;
 pub mod qrcode_encoder;
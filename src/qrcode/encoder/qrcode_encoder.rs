@@ -18,11 +18,13 @@ use unicode_segmentation::UnicodeSegmentation;
 
 use crate::{
     common::{
+        most_compact_charset,
         reedsolomon::{get_predefined_genericgf, PredefinedGenericGF, ReedSolomonEncoder},
-        BitArray, BitFieldBaseType, CharacterSet, Eci, Result,
+        BitArray, BitFieldBaseType, BitMatrix, CharacterSet, Eci, Result,
     },
     qrcode::decoder::{ErrorCorrectionLevel, Mode, Version, VersionRef},
-    EncodeHints, Exceptions,
+    BarcodeFormat, EncodeHints, EncodedSegment, EncodingWitness, Exceptions, MaskWitness,
+    ParityBlock,
 };
 
 use super::{mask_util, matrix_util, BlockPair, ByteMatrix, MinimalEncoder, QRCode};
@@ -57,18 +59,36 @@ pub fn calculateMaskPenalty(matrix: &ByteMatrix) -> u32 {
  * @throws WriterException if encoding can't succeed, because of for example invalid content
  *   or configuration
  */
+/// The output of the data encoding step, see [`encode_data_with_hints`]
+pub struct QRCodeData {
+    /// The smallest version the data fits in, or the version requested by hint
+    pub version: VersionRef,
+    /// The mode of the data, `BYTE` when the minimal encoder mixes modes
+    pub mode: Mode,
+    /// The mode and character count of each segment; bytes for byte mode segments
+    pub segments: Vec<(Mode, u32)>,
+    /// The header and data bits, not yet terminated or padded
+    pub bits: BitArray,
+}
+
 pub fn encode(content: &str, ecLevel: ErrorCorrectionLevel) -> Result<QRCode> {
     encode_with_hints(content, ecLevel, &EncodeHints::default())
 }
 
-pub fn encode_with_hints(
+/**
+ * Runs the data encoding step of [`encode_with_hints`]: chooses the mode(s) and the
+ * version, and produces the header and data bits, without error correction, masking
+ * or module placement.
+ */
+pub fn encode_data_with_hints(
     content: &str,
     ec_level: ErrorCorrectionLevel,
     hints: &EncodeHints,
-) -> Result<QRCode> {
+) -> Result<QRCodeData> {
     let version;
     let mut header_and_data_bits;
     let mode;
+    let segments;
 
     let has_gs1_format_hint = matches!(hints.Gs1Format, Some(true));
 
@@ -78,6 +98,9 @@ pub fn encode_with_hints(
         false
     };
 
+    // Hanzi mode is only chosen by the minimal encoder
+    let has_hanzi_hint = matches!(hints.QrHanzi, Some(true));
+
     // Determine what character encoding has been specified by the caller, if any
     let mut encoding = None; //DEFAULT_BYTE_MODE_ENCODING;
     let mut has_encoding_hint = hints.CharacterSet.is_some();
@@ -87,31 +110,28 @@ pub fn encode_with_hints(
         }
     }
 
-    if has_compaction_hint {
+    if has_compaction_hint || has_hanzi_hint {
         mode = Mode::BYTE;
 
         // dbg!("consider this a huge risk, not sure if it should be defaulting to default");
         let priority_encoding = encoding; //if encoding.name() == DEFAULT_BYTE_MODE_ENCODING.name()  {None} else {Some(encoding)};
-        let rn = MinimalEncoder::encode_with_details(
-            content,
-            None,
-            priority_encoding,
-            has_gs1_format_hint,
-            ec_level,
-        )?;
+        let rn = MinimalEncoder::new(content, priority_encoding, has_gs1_format_hint, ec_level)
+            .with_hanzi(has_hanzi_hint)
+            .encode(None)?;
 
         header_and_data_bits = BitArray::new();
         rn.getBits(&mut header_and_data_bits)?;
         version = rn.getVersion();
+        segments = rn.getSegments();
     } else {
-        //Switch to default encoding
+        // Without a hint, text beyond the default encoding is encoded in the most compact
+        // character set, marked with its ECI
         let encoding = if let Some(encoding) = encoding {
             encoding
-        } else if let Ok(_encs) = DEFAULT_BYTE_MODE_ENCODING.encode(content) {
-            DEFAULT_BYTE_MODE_ENCODING
         } else {
-            has_encoding_hint = true;
-            CharacterSet::UTF8
+            let encoding = most_compact_charset(content);
+            has_encoding_hint = encoding != DEFAULT_BYTE_MODE_ENCODING;
+            encoding
         };
 
         // Pick an encoding mode appropriate for the content. Note that this will not attempt to use
@@ -168,18 +188,139 @@ pub fn encode_with_hints(
             content.graphemes(true).count()
         };
         appendLengthInfo(num_letters as u32, version, mode, &mut header_and_data_bits)?;
+        segments = vec![(mode, num_letters as u32)];
         // Put data together into the overall payload
         header_and_data_bits.appendBitArray(data_bits);
     }
 
+    Ok(QRCodeData {
+        version,
+        mode,
+        segments,
+        bits: header_and_data_bits,
+    })
+}
+
+pub fn encode_with_hints(
+    content: &str,
+    ec_level: ErrorCorrectionLevel,
+    hints: &EncodeHints,
+) -> Result<QRCode> {
+    encode_symbol(content, ec_level, hints).map(|symbol| symbol.qr_code)
+}
+
+/**
+ * Encodes `content` as [`encode_with_hints`] does, and records the intermediates of each step.
+ *
+ * # Arguments
+ * * `content` - The text to encode
+ * * `ec_level` - The error correction level to use
+ * * `hints` - The encode hints, as for [`encode_with_hints`]
+ *
+ * # Returns
+ * The QR code and the witness of its encoding
+ */
+pub fn encode_with_witness(
+    content: &str,
+    ec_level: ErrorCorrectionLevel,
+    hints: &EncodeHints,
+) -> Result<(QRCode, EncodingWitness)> {
+    let EncodedSymbol {
+        qr_code,
+        segments,
+        segment_bits,
+        data_bits,
+        blocks,
+        final_bits,
+    } = encode_symbol(content, ec_level, hints)?;
+
+    let version = qr_code.getVersion().ok_or(Exceptions::WRITER)?;
+    let mask_pattern = qr_code.getMaskPattern();
+    let matrix = qr_code.getMatrix().as_ref().ok_or(Exceptions::WRITER)?;
+
+    // Everything but the data, which then goes in without a mask
+    let dimension = version.getDimensionForVersion();
+    let mut unmasked = ByteMatrix::new(dimension, dimension);
+    matrix_util::clearMatrix(&mut unmasked);
+    matrix_util::embedBasicPatterns(version, &mut unmasked)?;
+    matrix_util::embedTypeInfo(&ec_level, mask_pattern, &mut unmasked)?;
+    matrix_util::maybeEmbedVersionInfo(version, &mut unmasked)?;
+    let mut data_modules = BitMatrix::new(dimension, dimension)?;
+    for y in 0..dimension {
+        for x in 0..dimension {
+            if matrix_util::isEmpty(unmasked.get(x, y)) {
+                data_modules.set(x, y);
+            }
+        }
+    }
+    matrix_util::embedDataBits(&final_bits, -1, &mut unmasked)?;
+
+    let witness = EncodingWitness {
+        format: BarcodeFormat::QR_CODE,
+        content: content.to_owned(),
+        version: version.getVersionNumber(),
+        segments: segments
+            .into_iter()
+            .map(|(mode, character_count)| EncodedSegment {
+                mode: format!("{mode:?}"),
+                character_count,
+            })
+            .collect(),
+        data_bits: to_bytes(&segment_bits),
+        num_data_bits: segment_bits.get_size(),
+        codeword_size: 8,
+        data_codewords: to_words(&to_bytes(&data_bits)),
+        blocks: blocks
+            .iter()
+            .map(|block| ParityBlock {
+                data: to_words(block.getDataBytes()),
+                parity: to_words(block.getErrorCorrectionBytes()),
+            })
+            .collect(),
+        codewords: to_words(&to_bytes(&final_bits)),
+        mask: Some(MaskWitness {
+            pattern: mask_pattern as u32,
+            data_modules,
+            unmasked: to_bit_matrix(&unmasked)?,
+        }),
+        matrix: to_bit_matrix(matrix)?,
+    };
+
+    Ok((qr_code, witness))
+}
+
+// The QR code along with the intermediates of encoding it
+struct EncodedSymbol {
+    qr_code: QRCode,
+    segments: Vec<(Mode, u32)>,
+    // the segments before termination and padding
+    segment_bits: BitArray,
+    data_bits: BitArray,
+    blocks: Vec<BlockPair>,
+    final_bits: BitArray,
+}
+
+fn encode_symbol(
+    content: &str,
+    ec_level: ErrorCorrectionLevel,
+    hints: &EncodeHints,
+) -> Result<EncodedSymbol> {
+    let QRCodeData {
+        version,
+        mode,
+        segments,
+        bits: mut header_and_data_bits,
+    } = encode_data_with_hints(content, ec_level, hints)?;
+
     let ec_blocks = version.getECBlocksForLevel(ec_level);
     let num_data_bytes = version.getTotalCodewords() - ec_blocks.getTotalECCodewords();
+    let segment_bits = header_and_data_bits.clone();
 
     // Terminate the bits properly.
     terminateBits(num_data_bytes, &mut header_and_data_bits)?;
 
     // Interleave data bits with error correction code.
-    let final_bits = interleaveWithECBytes(
+    let (final_bits, blocks) = interleave_blocks(
         &header_and_data_bits,
         version.getTotalCodewords(),
         num_data_bytes,
@@ -217,7 +358,37 @@ pub fn encode_with_hints(
     matrix_util::buildMatrix(&final_bits, &ec_level, version, mask_pattern, &mut matrix)?;
     qrCode.setMatrix(matrix);
 
-    Ok(qrCode)
+    Ok(EncodedSymbol {
+        qr_code: qrCode,
+        segments,
+        segment_bits,
+        data_bits: header_and_data_bits,
+        blocks,
+        final_bits,
+    })
+}
+
+fn to_bytes(bits: &BitArray) -> Vec<u8> {
+    let len = bits.getSizeInBytes();
+    let mut bytes = vec![0u8; len];
+    bits.toBytes(0, &mut bytes, 0, len);
+    bytes
+}
+
+fn to_words(bytes: &[u8]) -> Vec<u16> {
+    bytes.iter().map(|&byte| byte as u16).collect()
+}
+
+fn to_bit_matrix(matrix: &ByteMatrix) -> Result<BitMatrix> {
+    let mut bits = BitMatrix::new(matrix.getWidth(), matrix.getHeight())?;
+    for y in 0..matrix.getHeight() {
+        for x in 0..matrix.getWidth() {
+            if matrix.get(x, y) == 1 {
+                bits.set(x, y);
+            }
+        }
+    }
+    Ok(bits)
 }
 
 /**
@@ -279,7 +450,7 @@ pub fn chooseMode(content: &str) -> Mode {
  * Choose the best mode by examining the content. Note that 'encoding' is used as a hint;
  * if it is Shift_JIS, and the input is only double-byte Kanji, then we return {@link Mode#KANJI}.
  */
-fn chooseModeWithEncoding(content: &str, encoding: CharacterSet) -> Mode {
+pub(crate) fn chooseModeWithEncoding(content: &str, encoding: CharacterSet) -> Mode {
     if SHIFT_JIS_CHARSET == encoding && isOnlyDoubleByteKanji(content) {
         // Choose Kanji mode if all input are double-byte characters
         return Mode::KANJI;
@@ -481,6 +652,16 @@ pub fn interleaveWithECBytes(
     num_data_bytes: u32,
     num_rsblocks: u32,
 ) -> Result<BitArray> {
+    interleave_blocks(bits, num_total_bytes, num_data_bytes, num_rsblocks).map(|(result, _)| result)
+}
+
+// The interleaved bits, and the blocks of data and error correction bytes they came from
+fn interleave_blocks(
+    bits: &BitArray,
+    num_total_bytes: u32,
+    num_data_bytes: u32,
+    num_rsblocks: u32,
+) -> Result<(BitArray, Vec<BlockPair>)> {
     // "bits" must have "getNumDataBytes" bytes of data.
     if bits.getSizeInBytes() as u32 != num_data_bytes {
         return Err(Exceptions::writer_with(
@@ -550,7 +731,7 @@ pub fn interleaveWithECBytes(
         )));
     }
 
-    Ok(result)
+    Ok((result, blocks))
 }
 
 pub fn generateECBytes(dataBytes: &[u8], num_ec_bytes_in_block: usize) -> Result<Vec<u8>> {
@@ -613,6 +794,7 @@ pub fn appendBytes(
         Mode::ALPHANUMERIC => appendAlphanumericBytes(content, bits),
         Mode::BYTE => append8BitBytes(content, bits, encoding),
         Mode::KANJI => appendKanjiBytes(content, bits),
+        Mode::HANZI => appendHanziBytes(content, bits),
         _ => Err(Exceptions::writer_with(format!("Invalid mode: {mode:?}"))),
     }
 }
@@ -716,6 +898,42 @@ pub fn appendKanjiBytes(content: &str, bits: &mut BitArray) -> Result<()> {
     Ok(())
 }
 
+/// Whether `content` is only GB2312 characters, the Hanzi mode subset of GB/T 18284
+pub fn isOnlyHanzi(content: &str) -> bool {
+    let Ok(bytes) = CharacterSet::GB2312.encode(content) else {
+        return false;
+    };
+    bytes.len() % 2 == 0
+        && bytes.chunks(2).all(|pair| {
+            ((0xA1..=0xAA).contains(&pair[0]) || (0xB0..=0xFA).contains(&pair[0]))
+                && (0xA1..=0xFE).contains(&pair[1])
+        })
+}
+
+/**
+ * Appends the GB2312 characters of `content` in 13 bits each, as the Hanzi mode of GB/T 18284
+ * does. The subset indicator is part of the segment header, not of the data.
+ */
+pub fn appendHanziBytes(content: &str, bits: &mut BitArray) -> Result<()> {
+    if !isOnlyHanzi(content) {
+        return Err(Exceptions::writer_with(format!(
+            "'{content}' is not only GB2312 characters"
+        )));
+    }
+    let bytes = CharacterSet::GB2312.encode(content)?;
+    for pair in bytes.chunks(2) {
+        let code = (pair[0] as u32) << 8 | pair[1] as u32;
+        let subtracted = if code <= 0xAAFE {
+            code - 0xA1A1
+        } else {
+            code - 0xA6A1
+        };
+        let encoded = (subtracted >> 8) * 0x60 + (subtracted & 0xff);
+        bits.appendBits(encoded as BitFieldBaseType, 13)?;
+    }
+    Ok(())
+}
+
 fn appendECI(eci: Eci, bits: &mut BitArray) -> Result<()> {
     bits.appendBits(Mode::ECI.getBits() as BitFieldBaseType, 4)?;
     // This is correct for values up to 127, which is all we need now.
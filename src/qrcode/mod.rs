@@ -8,6 +8,9 @@ pub use qr_code_reader::*;
 mod qr_code_writer;
 pub use qr_code_writer::*;
 
+mod qr_code_logo;
+pub use qr_code_logo::*;
+
 pub mod cpp_port;
 
 #[cfg(test)]
@@ -0,0 +1,421 @@
+use crate::{
+    common::{BitMatrix, Result},
+    EncodeHints, Exceptions,
+};
+
+use super::{
+    decoder::ErrorCorrectionLevel,
+    encoder::{qrcode_encoder, QRCode},
+    qr_code_writer::QUIET_ZONE_SIZE,
+    QRCodeWriter,
+};
+
+/// The share of a block's correction capacity a logo may use by default, leaving the rest for
+/// print and scan errors
+const DEFAULT_MAX_DAMAGE: f32 = 0.75;
+
+/// The central region of a QR Code reserved by [`QRCodeWriter::encode_with_logo`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct QRLogoOptions {
+    /// The width of the region as a share of the symbol's width, without quiet zone
+    pub width: f32,
+    /// The height of the region as a share of the symbol's height, without quiet zone
+    pub height: f32,
+    /// The largest share of the codewords each error correction block can correct that the
+    /// region may cover
+    pub max_damage: f32,
+}
+
+impl Default for QRLogoOptions {
+    fn default() -> Self {
+        Self {
+            width: 0.2,
+            height: 0.2,
+            max_damage: DEFAULT_MAX_DAMAGE,
+        }
+    }
+}
+
+impl QRLogoOptions {
+    pub fn new(width: f32, height: f32) -> Self {
+        Self {
+            width,
+            height,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_max_damage(mut self, max_damage: f32) -> Self {
+        self.max_damage = max_damage;
+        self
+    }
+}
+
+/// A rectangle of a symbol, from its top left corner
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LogoRegion {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// A QR Code encoded with a region cleared for a logo, see [`QRCodeWriter::encode_with_logo`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct QRLogoSymbol {
+    /// The symbol, light in the reserved region
+    pub matrix: BitMatrix,
+    /// The reserved region in pixels of `matrix`
+    pub region: LogoRegion,
+    /// The reserved region in modules, without quiet zone
+    pub module_region: LogoRegion,
+    /// The codewords of each error correction block the region covers
+    pub damaged_codewords: Vec<u32>,
+    /// The codewords each error correction block can correct
+    pub correctable_codewords: Vec<u32>,
+}
+
+impl QRLogoSymbol {
+    /// The largest share of a block's correction capacity the region uses
+    pub fn damage(&self) -> f32 {
+        self.damaged_codewords
+            .iter()
+            .zip(&self.correctable_codewords)
+            .map(|(&damaged, &correctable)| damaged as f32 / correctable as f32)
+            .fold(0.0, f32::max)
+    }
+
+    /// Draws the symbol with `logo` scaled to fit the reserved region, centered in it
+    #[cfg(feature = "image")]
+    pub fn composite(&self, logo: &image::DynamicImage) -> image::RgbaImage {
+        let mut image = image::DynamicImage::from(&self.matrix).to_rgba8();
+        let scale = (self.region.width as f32 / logo.width() as f32)
+            .min(self.region.height as f32 / logo.height() as f32);
+        let width = ((logo.width() as f32 * scale) as u32).max(1);
+        let height = ((logo.height() as f32 * scale) as u32).max(1);
+        let logo = image::imageops::resize(
+            &logo.to_rgba8(),
+            width,
+            height,
+            image::imageops::FilterType::Triangle,
+        );
+        image::imageops::overlay(
+            &mut image,
+            &logo,
+            (self.region.x + (self.region.width - width) / 2) as i64,
+            (self.region.y + (self.region.height - height) / 2) as i64,
+        );
+        image
+    }
+}
+
+impl QRCodeWriter {
+    /**
+     * Encodes `contents` as a QR Code with error correction level H and a central region left
+     * light for a logo. Every codeword with a module in the region is taken as lost; encoding
+     * fails if any error correction block would lose more than `max_damage` of the codewords
+     * it can correct, or if the region reaches the finder patterns.
+     *
+     * The `ErrorCorrection` and `MicroQr` hints are ignored.
+     */
+    pub fn encode_with_logo(
+        &self,
+        contents: &str,
+        width: i32,
+        height: i32,
+        hints: &EncodeHints,
+        logo: &QRLogoOptions,
+    ) -> Result<QRLogoSymbol> {
+        if contents.is_empty() {
+            return Err(Exceptions::illegal_argument_with("found empty contents"));
+        }
+        if width < 0 || height < 0 {
+            return Err(Exceptions::illegal_argument_with(format!(
+                "requested dimensions are too small: {width}x{height}"
+            )));
+        }
+        let valid_share = |share: f32| share > 0.0 && share < 1.0;
+        if !valid_share(logo.width) || !valid_share(logo.height) {
+            return Err(Exceptions::illegal_argument_with(format!(
+                "logo size {}x{} is not a share of the symbol",
+                logo.width, logo.height
+            )));
+        }
+
+        let quietZone = if let Some(margin) = &hints.Margin {
+            margin
+                .parse::<i32>()
+                .map_err(|e| Exceptions::parse_with(format!("could not parse {margin}: {e}")))?
+        } else {
+            QUIET_ZONE_SIZE
+        };
+
+        let mut code = qrcode_encoder::encode_with_hints(contents, ErrorCorrectionLevel::H, hints)?;
+        let version = code.getVersion().ok_or(Exceptions::ILLEGAL_STATE)?;
+        let dimension = version.getDimensionForVersion();
+
+        // an odd region in an odd symbol, or even in even, is centered on whole modules
+        let side = |share: f32| {
+            let modules = (share * dimension as f32).round() as u32;
+            if modules % 2 == dimension % 2 {
+                modules
+            } else {
+                modules + 1
+            }
+        };
+        let module_region = {
+            let (width, height) = (side(logo.width), side(logo.height));
+            LogoRegion {
+                x: (dimension - width) / 2,
+                y: (dimension - height) / 2,
+                width,
+                height,
+            }
+        };
+        // finder patterns, separators and format information take the 9 modules at each corner
+        if module_region.x < 9 || module_region.y < 9 {
+            return Err(Exceptions::illegal_argument_with(format!(
+                "a {}x{} logo covers the finder patterns of a {dimension}x{dimension} symbol",
+                module_region.width, module_region.height
+            )));
+        }
+
+        let (damaged_codewords, correctable_codewords) = damaged_codewords(&code, &module_region)?;
+        let damage = damaged_codewords
+            .iter()
+            .zip(&correctable_codewords)
+            .map(|(&damaged, &correctable)| damaged as f32 / correctable as f32)
+            .fold(0.0, f32::max);
+        if damage > logo.max_damage {
+            return Err(Exceptions::illegal_argument_with(format!(
+                "a {}x{} logo uses {:.0}% of the error correction of a block, more than {:.0}%",
+                module_region.width,
+                module_region.height,
+                damage * 100.0,
+                logo.max_damage * 100.0
+            )));
+        }
+
+        let mut matrix = code.getMatrix().clone().ok_or(Exceptions::ILLEGAL_STATE)?;
+        for y in module_region.y..module_region.y + module_region.height {
+            for x in module_region.x..module_region.x + module_region.width {
+                matrix.set(x, y, 0);
+            }
+        }
+        code.setMatrix(matrix);
+
+        let (multiple, leftPadding, topPadding) =
+            Self::placement(dimension as i32, dimension as i32, width, height, quietZone);
+        let matrix = Self::renderRXingResult(&code, width, height, quietZone)?;
+        let multiple = multiple as u32;
+        Ok(QRLogoSymbol {
+            matrix,
+            region: LogoRegion {
+                x: leftPadding as u32 + module_region.x * multiple,
+                y: topPadding as u32 + module_region.y * multiple,
+                width: module_region.width * multiple,
+                height: module_region.height * multiple,
+            },
+            module_region,
+            damaged_codewords,
+            correctable_codewords,
+        })
+    }
+}
+
+// The codewords with a module in `region` and the codewords that can be corrected, for each
+// error correction block
+fn damaged_codewords(code: &QRCode, region: &LogoRegion) -> Result<(Vec<u32>, Vec<u32>)> {
+    let version = code.getVersion().ok_or(Exceptions::ILLEGAL_STATE)?;
+    let ec_level = code.getECLevel().ok_or(Exceptions::ILLEGAL_STATE)?;
+    let num_total_bytes = version.getTotalCodewords();
+    let ec_blocks = version.getECBlocksForLevel(ec_level);
+    let num_rsblocks = ec_blocks.getNumBlocks();
+    let num_data_bytes = num_total_bytes - ec_blocks.getTotalECCodewords();
+
+    // the block of each codeword, in the interleaved order they are placed in
+    let mut sizes = Vec::with_capacity(num_rsblocks as usize);
+    for block in 0..num_rsblocks {
+        sizes.push(qrcode_encoder::getNumDataBytesAndNumECBytesForBlockID(
+            num_total_bytes,
+            num_data_bytes,
+            num_rsblocks,
+            block,
+        )?);
+    }
+    let mut codeword_blocks = Vec::with_capacity(num_total_bytes as usize);
+    let max_data = sizes.iter().map(|&(data, _)| data).max().unwrap_or(0);
+    for i in 0..max_data {
+        codeword_blocks.extend((0..sizes.len()).filter(|&block| i < sizes[block].0));
+    }
+    let max_ec = sizes.iter().map(|&(_, ec)| ec).max().unwrap_or(0);
+    for i in 0..max_ec {
+        codeword_blocks.extend((0..sizes.len()).filter(|&block| i < sizes[block].1));
+    }
+
+    // walk the data modules in the order the encoder places bits, see `embedDataBits`
+    let function_pattern = version.buildFunctionPattern()?;
+    let dimension = function_pattern.getHeight() as i32;
+    let mut covered = vec![false; num_total_bytes as usize];
+    let mut bit_index = 0;
+    let mut upward = true;
+    let mut x = dimension - 1;
+    while x > 0 {
+        if x == 6 {
+            x -= 1;
+        }
+        for i in 0..dimension {
+            let y = if upward { dimension - 1 - i } else { i };
+            for xx in [x, x - 1] {
+                if function_pattern.get(xx as u32, y as u32) {
+                    continue;
+                }
+                let codeword = bit_index / 8;
+                bit_index += 1;
+                let inside = (region.x..region.x + region.width).contains(&(xx as u32))
+                    && (region.y..region.y + region.height).contains(&(y as u32));
+                if inside && codeword < covered.len() {
+                    covered[codeword] = true;
+                }
+            }
+        }
+        upward = !upward;
+        x -= 2;
+    }
+
+    let mut damaged = vec![0; sizes.len()];
+    for (codeword, &block) in codeword_blocks.iter().enumerate() {
+        if covered[codeword] {
+            damaged[block] += 1;
+        }
+    }
+    let correctable = sizes.iter().map(|&(_, ec)| ec / 2).collect();
+    Ok((damaged, correctable))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::qrcode::decoder::qrcode_decoder;
+
+    const CONTENTS: &str = "https://github.com/rxing-core/rxing";
+
+    #[test]
+    fn test_encode_with_logo() {
+        let symbol = QRCodeWriter
+            .encode_with_logo(
+                CONTENTS,
+                0,
+                0,
+                &EncodeHints::default(),
+                &QRLogoOptions::default(),
+            )
+            .unwrap();
+        // version 5 at level H, 37 modules with a 7x7 logo
+        assert_eq!(
+            LogoRegion {
+                x: 15,
+                y: 15,
+                width: 7,
+                height: 7
+            },
+            symbol.module_region
+        );
+        assert_eq!(
+            LogoRegion {
+                x: 19,
+                y: 19,
+                width: 7,
+                height: 7
+            },
+            symbol.region
+        );
+        assert_eq!(4, symbol.damaged_codewords.len());
+        assert!(symbol.damage() > 0.0 && symbol.damage() <= DEFAULT_MAX_DAMAGE);
+        for y in 19..26 {
+            for x in 19..26 {
+                assert!(!symbol.matrix.get(x, y));
+            }
+        }
+
+        // the decoder reads the symbol without its quiet zone, with the logo region lost
+        let modules = BitMatrix::parse_bools(
+            &(4..41)
+                .map(|y| (4..41).map(|x| symbol.matrix.get(x, y)).collect())
+                .collect::<Vec<Vec<bool>>>(),
+        );
+        let result = qrcode_decoder::decode_bitmatrix(&modules).unwrap();
+        assert_eq!(CONTENTS, result.getText());
+    }
+
+    #[test]
+    fn test_scaled_region() {
+        let symbol = QRCodeWriter
+            .encode_with_logo(
+                CONTENTS,
+                200,
+                200,
+                &EncodeHints::default(),
+                &QRLogoOptions::new(0.2, 0.1),
+            )
+            .unwrap();
+        // 45 modules with the quiet zone fit 4 times in 200 pixels, padded by 26, and the
+        // logo is 5 modules high to be centered in the odd symbol
+        assert_eq!(
+            LogoRegion {
+                x: 26 + 15 * 4,
+                y: 26 + 16 * 4,
+                width: 7 * 4,
+                height: 5 * 4
+            },
+            symbol.region
+        );
+    }
+
+    #[test]
+    fn test_refuse_large_logo() {
+        let result = QRCodeWriter.encode_with_logo(
+            CONTENTS,
+            0,
+            0,
+            &EncodeHints::default(),
+            &QRLogoOptions::new(0.5, 0.5),
+        );
+        assert!(result.is_err());
+
+        let result = QRCodeWriter.encode_with_logo(
+            CONTENTS,
+            0,
+            0,
+            &EncodeHints::default(),
+            &QRLogoOptions::new(0.3, 0.3).with_max_damage(0.1),
+        );
+        assert!(result.is_err());
+    }
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn test_composite() {
+        let symbol = QRCodeWriter
+            .encode_with_logo(
+                CONTENTS,
+                200,
+                200,
+                &EncodeHints::default(),
+                &QRLogoOptions::default(),
+            )
+            .unwrap();
+        // a wide red logo is scaled to the width of the region and centered in its height
+        let logo = image::RgbaImage::from_pixel(10, 5, image::Rgba([255, 0, 0, 255]));
+        let image = symbol.composite(&image::DynamicImage::from(logo));
+        assert_eq!((200, 200), image.dimensions());
+        let region = symbol.region;
+        let red = image::Rgba([255, 0, 0, 255]);
+        assert_eq!(red, image[(region.x, region.y + region.height / 2)]);
+        assert_ne!(red, image[(region.x, region.y)]);
+        assert_eq!(
+            image::Rgba([255, 255, 255, 255]),
+            image[(region.x, region.y)]
+        );
+    }
+}
@@ -15,9 +15,13 @@
  */
 
 use crate::{
-    common::{BitMatrix, DecoderRXingResult, DetectorRXingResult, Result},
-    point, BarcodeFormat, Binarizer, DecodeHints, Exceptions, ImmutableReader, Point, RXingResult,
-    RXingResultMetadataType, RXingResultMetadataValue, Reader,
+    common::{
+        sample_cylinder, BitMatrix, DecoderRXingResult, DetectorRXingResult, PatternKind,
+        Quadrilateral, Result, SamplingGrid,
+    },
+    decode_confidence, point, record_witness, symbol_pose, BarcodeFormat, Binarizer, DecodeHints,
+    DecodeWarning, Exceptions, ImmutableReader, Point, RXingResult, RXingResultMetadataType,
+    RXingResultMetadataValue, Reader, WitnessRecording,
 };
 
 use super::{
@@ -55,7 +59,9 @@ impl Reader for QRCodeReader {
         image: &mut crate::BinaryBitmap<B>,
         hints: &DecodeHints,
     ) -> Result<RXingResult> {
-        self.internal_decode_with_hints(image, hints)
+        let mut recording = WitnessRecording::new(hints);
+        self.internal_decode_with_hints(image, hints, &mut recording)
+            .map(|result| record_witness(hints, image, result, &recording))
     }
 }
 
@@ -65,7 +71,9 @@ impl ImmutableReader for QRCodeReader {
         image: &mut crate::BinaryBitmap<B>,
         hints: &DecodeHints,
     ) -> Result<RXingResult> {
-        self.internal_decode_with_hints(image, hints)
+        let mut recording = WitnessRecording::new(hints);
+        self.internal_decode_with_hints(image, hints, &mut recording)
+            .map(|result| record_witness(hints, image, result, &recording))
     }
 }
 
@@ -74,6 +82,79 @@ impl QRCodeReader {
         Self {}
     }
 
+    /**
+     * Reports the structured append header of a symbol the way the other readers do: the index of
+     * the symbol and the number of symbols, which share the high and low nibble of the sequence
+     * byte, as the sequence and parity, and the parity byte as the ID of the message.
+     */
+    pub(crate) fn putStructuredAppendMetadata(
+        result: &mut RXingResult,
+        decoderRXingResult: &DecoderRXingResult,
+    ) {
+        if !decoderRXingResult.hasStructuredAppend() {
+            return;
+        }
+        let sequence = decoderRXingResult.getStructuredAppendSequenceNumber();
+        result.putMetadata(
+            RXingResultMetadataType::STRUCTURED_APPEND_SEQUENCE,
+            RXingResultMetadataValue::StructuredAppendSequence(sequence >> 4),
+        );
+        result.putMetadata(
+            RXingResultMetadataType::STRUCTURED_APPEND_PARITY,
+            RXingResultMetadataValue::StructuredAppendParity((sequence & 0x0F) + 1),
+        );
+        result.putMetadata(
+            RXingResultMetadataType::STRUCTURED_APPEND_ID,
+            RXingResultMetadataValue::StructuredAppendId(
+                decoderRXingResult.getStructuredAppendParity().to_string(),
+            ),
+        );
+    }
+
+    /// The modules of a QR Code `dimension` modules wide whose colors are the same in every
+    /// symbol: its finder patterns and timing patterns
+    fn fixed_modules(dimension: u32) -> Vec<(u32, u32, bool)> {
+        let mut modules = Vec::new();
+        if dimension < 21 {
+            return modules;
+        }
+        for (left, top) in [(0, 0), (dimension - 7, 0), (0, dimension - 7)] {
+            for y in 0..7 {
+                for x in 0..7 {
+                    // black but for the second ring from the outside
+                    let ring = x.min(y).min(6 - x).min(6 - y);
+                    modules.push((left + x, top + y, ring != 1));
+                }
+            }
+        }
+        for i in 8..dimension - 8 {
+            modules.push((i, 6, i % 2 == 0));
+            modules.push((6, i, i % 2 == 0));
+        }
+        modules
+    }
+
+    /// The points in module space the detector fits the grid of a symbol to: the centers of
+    /// its finder patterns, and of its bottom right alignment pattern if it found one
+    fn anchors(grid: &SamplingGrid) -> Quadrilateral {
+        let dimension = grid.width() as f32;
+        let bottom_right = if grid
+            .patterns()
+            .iter()
+            .any(|pattern| pattern.kind == PatternKind::Alignment)
+        {
+            dimension - 6.5
+        } else {
+            dimension - 3.5
+        };
+        Quadrilateral([
+            point(3.5, 3.5),
+            point(dimension - 3.5, 3.5),
+            point(bottom_right, bottom_right),
+            point(3.5, dimension - 3.5),
+        ])
+    }
+
     /**
      * This method detects a code in a "pure" image -- that is, pure monochrome image
      * which contains only an unrotated, unskewed, image of a code, with some white border
@@ -191,20 +272,52 @@ impl QRCodeReader {
         &self,
         image: &mut crate::BinaryBitmap<B>,
         hints: &DecodeHints,
+        recording: &mut WitnessRecording,
     ) -> Result<RXingResult> {
         let decoderRXingResult: DecoderRXingResult;
         let mut points: Vec<Point>;
+        let mut samplingGrid = None;
+        recording.binarization(image);
         if matches!(hints.PureBarcode, Some(true)) {
             let bits = Self::extractPureBits(image.get_black_matrix())?;
             decoderRXingResult = qrcode_decoder::decode_bitmatrix_with_hints(&bits, hints)?;
             points = Vec::new();
         } else {
-            let detectorRXingResult =
-                Detector::new(image.get_black_matrix()).detect_with_hints(hints)?;
-            decoderRXingResult =
-                qrcode_decoder::decode_bitmatrix_with_hints(detectorRXingResult.getBits(), hints)?;
+            let detectorRXingResult = Detector::new(image.get_black_matrix())
+                .detect_with_hints(hints)?
+                .sample_as_hinted(image.get_black_matrix(), hints)?;
+            recording.detection(
+                BarcodeFormat::QR_CODE,
+                detectorRXingResult.getPoints(),
+                detectorRXingResult.getSamplingGrid(),
+            );
+            match qrcode_decoder::decode_bitmatrix_with_hints(detectorRXingResult.getBits(), hints)
+            {
+                Ok(decoded) => {
+                    decoderRXingResult = decoded;
+                    samplingGrid = detectorRXingResult.getSamplingGrid().cloned();
+                }
+                Err(err) => {
+                    // a symbol on a bottle or cable may read with its grid bent around it
+                    let curved = detectorRXingResult
+                        .getSamplingGrid()
+                        .filter(|_| matches!(hints.TryCurved, Some(true)))
+                        .and_then(|grid| {
+                            sample_cylinder(
+                                image.get_black_matrix(),
+                                grid,
+                                Self::anchors(grid),
+                                &Self::fixed_modules(grid.width()),
+                            )
+                        })
+                        .ok_or(err)?;
+                    decoderRXingResult =
+                        qrcode_decoder::decode_bitmatrix_with_hints(&curved, hints)?;
+                }
+            }
             points = detectorRXingResult.getPoints().to_vec();
         }
+        recording.codewords(decoderRXingResult.getCodewords());
 
         // If the code was mirrored: swap the bottom-left and the top-right points.
         if let Some(other) = decoderRXingResult.getOther() {
@@ -223,6 +336,48 @@ impl QRCodeReader {
             points,
             BarcodeFormat::QR_CODE,
         );
+        if let Some(content) = decoderRXingResult.getContent() {
+            result.setContent(content.clone());
+        }
+
+        if decoderRXingResult
+            .getOther()
+            .and_then(|other| other.downcast::<QRCodeDecoderMetaData>().ok())
+            .is_some_and(|metadata| metadata.isMirrored())
+        {
+            result.addWarning(DecodeWarning::AmbiguousOrientation);
+            result.putMetadata(
+                RXingResultMetadataType::IS_MIRRORED,
+                RXingResultMetadataValue::IsMirrored(true),
+            );
+        }
+
+        if let Some(samplingGrid) = samplingGrid {
+            result.putMetadata(
+                RXingResultMetadataType::SAMPLING_GRID,
+                RXingResultMetadataValue::SamplingGrid(samplingGrid),
+            );
+        }
+
+        if let Some(codewords) = decoderRXingResult.getCodewords() {
+            if codewords.uses_heavy_correction() {
+                result.addWarning(DecodeWarning::HeavyErrorCorrection);
+            }
+            result.putMetadata(
+                RXingResultMetadataType::CODEWORDS,
+                RXingResultMetadataValue::Codewords(codewords.clone()),
+            );
+        }
+
+        if let Some(version) = decoderRXingResult.getSymbolVersion() {
+            result.putMetadata(
+                RXingResultMetadataType::SYMBOL_VERSION,
+                RXingResultMetadataValue::SymbolVersion(version),
+            );
+        }
+
+        decode_confidence::put_confidence(&mut result);
+        symbol_pose::put_pose(&mut result);
 
         let byteSegments = decoderRXingResult.getByteSegments();
         if !byteSegments.is_empty() {
@@ -239,21 +394,12 @@ impl QRCodeReader {
                 RXingResultMetadataValue::ErrorCorrectionLevel(ecLevel.to_owned()),
             );
         }
+        result.putErrorCorrectionMetadata(
+            decoderRXingResult.getErrorsCorrected(),
+            decoderRXingResult.getErasures(),
+        );
 
-        if decoderRXingResult.hasStructuredAppend() {
-            result.putMetadata(
-                RXingResultMetadataType::STRUCTURED_APPEND_SEQUENCE,
-                RXingResultMetadataValue::StructuredAppendSequence(
-                    decoderRXingResult.getStructuredAppendSequenceNumber(),
-                ),
-            );
-            result.putMetadata(
-                RXingResultMetadataType::STRUCTURED_APPEND_PARITY,
-                RXingResultMetadataValue::StructuredAppendParity(
-                    decoderRXingResult.getStructuredAppendParity(),
-                ),
-            );
-        }
+        Self::putStructuredAppendMetadata(&mut result, &decoderRXingResult);
 
         result.putMetadata(
             RXingResultMetadataType::SYMBOLOGY_IDENTIFIER,
@@ -263,6 +409,165 @@ impl QRCodeReader {
             )),
         );
 
+        recording.decoded_bits(&mut result);
         Ok(result)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::{
+        common::{GridSampling, HybridBinarizer},
+        qrcode::QRCodeReader,
+        BarcodeFormat, BinaryBitmap, DecodeHints, Luma8LuminanceSource, MultiFormatReader,
+        MultiFormatWriter, RXingResultMetadataType, RXingResultMetadataValue, Reader, Writer,
+    };
+
+    #[test]
+    fn test_mirrored_inverted() {
+        let matrix = MultiFormatWriter
+            .encode("etched", &BarcodeFormat::QR_CODE, 0, 0)
+            .expect("encode");
+        let (width, height) = (matrix.getWidth() * 8, matrix.getHeight() * 8);
+        // light modules on a dark background, with rows and columns swapped
+        let mut luma = Vec::new();
+        for y in 0..height {
+            for x in 0..width {
+                luma.push(if matrix.get(y / 8, x / 8) { 255 } else { 0 });
+            }
+        }
+        let mut image = BinaryBitmap::new(HybridBinarizer::new(Luma8LuminanceSource::new(
+            luma, width, height,
+        )));
+        let mut hints = DecodeHints {
+            PossibleFormats: Some(HashSet::from([BarcodeFormat::QR_CODE])),
+            ..Default::default()
+        };
+        assert!(MultiFormatReader::default()
+            .decode_with_hints(&mut image, &hints)
+            .is_err());
+
+        hints.AlsoInverted = Some(true);
+        let result = MultiFormatReader::default()
+            .decode_with_hints(&mut image, &hints)
+            .expect("decode");
+        assert_eq!("etched", result.getText());
+        let metadata = result.getRXingResultMetadata();
+        assert_eq!(
+            Some(&RXingResultMetadataValue::IsMirrored(true)),
+            metadata.get(&RXingResultMetadataType::IS_MIRRORED)
+        );
+        assert_eq!(
+            Some(&RXingResultMetadataValue::IsInverted(true)),
+            metadata.get(&RXingResultMetadataType::IS_INVERTED)
+        );
+    }
+
+    #[test]
+    fn test_supersampling() {
+        let matrix = MultiFormatWriter
+            .encode("supersampled", &BarcodeFormat::QR_CODE, 0, 0)
+            .expect("encode");
+        let (dimension, quiet_zone) = (21, 4);
+        let (width, height) = (matrix.getWidth() * 3, matrix.getHeight() * 3);
+        // modules of 3×3 pixels, a third of which have their center pixel inverted, away from
+        // the finder patterns and format information
+        let mut luma = Vec::new();
+        for y in 0..height {
+            for x in 0..width {
+                let (mx, my) = (x / 3, y / 3);
+                let (sx, sy) = (mx.wrapping_sub(quiet_zone), my.wrapping_sub(quiet_zone));
+                let near_finder = (sx < 9 || sx >= dimension - 8)
+                    && (sy < 9 || sy >= dimension - 8)
+                    && !(sx >= dimension - 8 && sy >= dimension - 8);
+                let noisy = sx < dimension
+                    && sy < dimension
+                    && !near_finder
+                    && (sx * 7 + sy * 13) % 3 == 0
+                    && x % 3 == 1
+                    && y % 3 == 1;
+                luma.push(if matrix.get(mx, my) != noisy { 0 } else { 255 });
+            }
+        }
+        let image = || {
+            BinaryBitmap::new(HybridBinarizer::new(Luma8LuminanceSource::new(
+                luma.clone(),
+                width,
+                height,
+            )))
+        };
+
+        assert!(QRCodeReader
+            .decode_with_hints(&mut image(), &DecodeHints::default())
+            .is_err());
+        let hints = DecodeHints {
+            GridSampling: Some(GridSampling::Supersample),
+            ..Default::default()
+        };
+        let result = QRCodeReader
+            .decode_with_hints(&mut image(), &hints)
+            .expect("decode");
+        assert_eq!("supersampled", result.getText());
+    }
+
+    #[test]
+    fn test_curved() {
+        let contents = "printed around a bottle, 0123456789";
+        let matrix = MultiFormatWriter
+            .encode_with_hints(
+                contents,
+                &BarcodeFormat::QR_CODE,
+                0,
+                0,
+                &crate::EncodeHints::default().with(crate::EncodeHintValue::Margin("0".to_owned())),
+            )
+            .expect("encode");
+        let dimension = matrix.getWidth();
+        // the symbol wrapped 0.8 radians either side of its middle around an upright cylinder
+        let (wrap, bow) = (0.8f32, 0.05f32);
+        let (module, quiet_zone) = (8, 40);
+        let size = dimension * module + 2 * quiet_zone;
+        let mut luma = Vec::new();
+        for y in 0..size {
+            for x in 0..size {
+                let normalized = |p: u32| {
+                    2.0 * (p as f32 - quiet_zone as f32) / (dimension * module) as f32 - 1.0
+                };
+                let (s, t) = (normalized(x), normalized(y));
+                let s = (s * wrap.sin()).asin() / wrap;
+                let t = t / (1.0 + bow * (1.0 - s * s));
+                let black = (-1.0..1.0).contains(&s)
+                    && (-1.0..1.0).contains(&t)
+                    && matrix.get(
+                        ((s + 1.0) / 2.0 * dimension as f32) as u32,
+                        ((t + 1.0) / 2.0 * dimension as f32) as u32,
+                    );
+                luma.push(if black { 0 } else { 255 });
+            }
+        }
+        let image = || {
+            BinaryBitmap::new(HybridBinarizer::new(Luma8LuminanceSource::new(
+                luma.clone(),
+                size,
+                size,
+            )))
+        };
+
+        assert!(QRCodeReader
+            .decode_with_hints(&mut image(), &DecodeHints::default())
+            .is_err());
+        let hints = DecodeHints {
+            TryCurved: Some(true),
+            ..Default::default()
+        };
+        let result = QRCodeReader
+            .decode_with_hints(&mut image(), &hints)
+            .expect("decode");
+        assert_eq!(contents, result.getText());
+        assert!(!result
+            .getRXingResultMetadata()
+            .contains_key(&RXingResultMetadataType::SAMPLING_GRID));
+    }
+}
@@ -21,14 +21,18 @@ use crate::{
 
 use super::{
     decoder::ErrorCorrectionLevel,
-    encoder::{qrcode_encoder, QRCode},
+    encoder::{micro_qrcode_encoder, qrcode_encoder, QRCode},
 };
 
-const QUIET_ZONE_SIZE: i32 = 4;
+pub(super) const QUIET_ZONE_SIZE: i32 = 4;
+const MICRO_QUIET_ZONE_SIZE: i32 = 2;
 
 /**
  * This object renders a QR Code as a BitMatrix 2D array of greyscale values.
  *
+ * A Micro QR Code is rendered instead for `BarcodeFormat::MICRO_QR_CODE`, or for `QR_CODE` with
+ * the `MicroQr` hint set.
+ *
  * @author dswitkin@google.com (Daniel Switkin)
  */
 #[derive(Default)]
@@ -60,12 +64,15 @@ impl Writer for QRCodeWriter {
             return Err(Exceptions::illegal_argument_with("found empty contents"));
         }
 
-        if format != &BarcodeFormat::QR_CODE {
-            return Err(Exceptions::illegal_argument_with(format!(
-                "can only encode QR_CODE, but got {format:?}"
-            )));
-            // throw new IllegalArgumentException("Can only encode QR_CODE, but got " + format);
-        }
+        let micro = match format {
+            BarcodeFormat::QR_CODE => matches!(hints.MicroQr, Some(true)),
+            BarcodeFormat::MICRO_QR_CODE => true,
+            _ => {
+                return Err(Exceptions::illegal_argument_with(format!(
+                    "can only encode QR_CODE or MICRO_QR_CODE, but got {format:?}"
+                )))
+            }
+        };
 
         if width < 0 || height < 0 {
             return Err(Exceptions::illegal_argument_with(format!(
@@ -83,20 +90,49 @@ impl Writer for QRCodeWriter {
             margin
                 .parse::<i32>()
                 .map_err(|e| Exceptions::parse_with(format!("could not parse {margin}: {e}")))?
+        } else if micro {
+            MICRO_QUIET_ZONE_SIZE
         } else {
             QUIET_ZONE_SIZE
         };
 
-        let code = qrcode_encoder::encode_with_hints(contents, errorCorrectionLevel, hints)?;
+        let code = if micro {
+            micro_qrcode_encoder::encode_with_hints(contents, errorCorrectionLevel, hints)?
+        } else {
+            qrcode_encoder::encode_with_hints(contents, errorCorrectionLevel, hints)?
+        };
 
         Self::renderRXingResult(&code, width, height, quietZone)
     }
 }
 
 impl QRCodeWriter {
+    // The size of a module in the output and the padding left and above the symbol
+    pub(crate) fn placement(
+        inputWidth: i32,
+        inputHeight: i32,
+        width: i32,
+        height: i32,
+        quietZone: i32,
+    ) -> (i32, i32, i32) {
+        let qrWidth = inputWidth + (quietZone * 2);
+        let qrHeight = inputHeight + (quietZone * 2);
+        let outputWidth = width.max(qrWidth);
+        let outputHeight = height.max(qrHeight);
+
+        let multiple = (outputWidth / qrWidth).min(outputHeight / qrHeight);
+        // Padding includes both the quiet zone and the extra white pixels to accommodate the requested
+        // dimensions. For example, if input is 25x25 the QR will be 33x33 including the quiet zone.
+        // If the requested size is 200x160, the multiple will be 4, for a QR of 132x132. These will
+        // handle all the padding from 100x100 (the actual QR) up to 200x160.
+        let leftPadding = (outputWidth - (inputWidth * multiple)) / 2;
+        let topPadding = (outputHeight - (inputHeight * multiple)) / 2;
+        (multiple, leftPadding, topPadding)
+    }
+
     // Note that the input matrix uses 0 == white, 1 == black, while the output matrix uses
     // 0 == black, 255 == white (i.e. an 8 bit greyscale bitmap).
-    fn renderRXingResult(
+    pub(crate) fn renderRXingResult(
         code: &QRCode,
         width: i32,
         height: i32,
@@ -111,18 +147,10 @@ impl QRCodeWriter {
 
         let inputWidth = input.getWidth() as i32;
         let inputHeight = input.getHeight() as i32;
-        let qrWidth = inputWidth + (quietZone * 2);
-        let qrHeight = inputHeight + (quietZone * 2);
-        let outputWidth = width.max(qrWidth);
-        let outputHeight = height.max(qrHeight);
-
-        let multiple = (outputWidth / qrWidth).min(outputHeight / qrHeight);
-        // Padding includes both the quiet zone and the extra white pixels to accommodate the requested
-        // dimensions. For example, if input is 25x25 the QR will be 33x33 including the quiet zone.
-        // If the requested size is 200x160, the multiple will be 4, for a QR of 132x132. These will
-        // handle all the padding from 100x100 (the actual QR) up to 200x160.
-        let leftPadding = (outputWidth - (inputWidth * multiple)) / 2;
-        let topPadding = (outputHeight - (inputHeight * multiple)) / 2;
+        let outputWidth = width.max(inputWidth + (quietZone * 2));
+        let outputHeight = height.max(inputHeight + (quietZone * 2));
+        let (multiple, leftPadding, topPadding) =
+            Self::placement(inputWidth, inputHeight, width, height, quietZone);
 
         let mut output = BitMatrix::new(outputWidth as u32, outputHeight as u32)?;
 
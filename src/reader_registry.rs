@@ -0,0 +1,398 @@
+/*
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::{collections::HashSet, fmt, sync::Arc};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    common::{BitMatrix, Result},
+    BarcodeFormat, DecodeHints, Exceptions, RXingResult,
+};
+
+/**
+ * A reader for a symbology that is not built into the library, such as a proprietary or
+ * experimental format. Custom readers are registered in a [`ReaderRegistry`] and tried by
+ * [`crate::MultiFormatReader`] alongside the built-in readers.
+ *
+ * Custom readers work on the binarized image, and receive the same hints as the built-in
+ * readers. Any closure `Fn(&BitMatrix, &DecodeHints) -> Result<RXingResult>` is a reader.
+ * Readers of formats this package doesn't know report them as [`BarcodeFormat::Other`].
+ */
+pub trait CustomReader: Send + Sync {
+    /**
+     * Locates and decodes a barcode in the binarized image.
+     *
+     * @return the decoded barcode, or `Exceptions::NotFoundException` if none is found
+     */
+    fn decode(&self, image: &BitMatrix, hints: &DecodeHints) -> Result<RXingResult>;
+}
+
+impl<F> CustomReader for F
+where
+    F: Fn(&BitMatrix, &DecodeHints) -> Result<RXingResult> + Send + Sync,
+{
+    fn decode(&self, image: &BitMatrix, hints: &DecodeHints) -> Result<RXingResult> {
+        self(image, hints)
+    }
+}
+
+/// Where a custom reader is placed in the attempt order of [`crate::MultiFormatReader`]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Default)]
+pub enum ReaderOrder {
+    /// Tried before any built-in reader
+    First,
+    /// Tried once all built-in readers have failed
+    #[default]
+    Last,
+}
+
+#[derive(Clone)]
+struct RegisteredReader {
+    name: String,
+    order: ReaderOrder,
+    priority: i32,
+    formats: Option<HashSet<BarcodeFormat>>,
+    reader: Arc<dyn CustomReader>,
+}
+
+/**
+ * A set of custom readers, identified by name. Readers with the same [`ReaderOrder`] are
+ * tried from the highest [priority](ReaderRegistry::set_priority) down, and in registration
+ * order where their priorities are the same.
+ *
+ * A reader registered with [`ReaderRegistry::register`] is always tried. A reader registered
+ * with [`ReaderRegistry::register_for_formats`] is skipped when the `PossibleFormats` hint
+ * excludes all of its formats.
+ */
+#[derive(Clone, Default)]
+pub struct ReaderRegistry {
+    readers: Vec<RegisteredReader>,
+}
+
+impl ReaderRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a reader that is tried regardless of the `PossibleFormats` hint
+    pub fn register<R: CustomReader + 'static>(
+        &mut self,
+        name: &str,
+        order: ReaderOrder,
+        reader: R,
+    ) -> Result<()> {
+        self.insert(name, order, None, Arc::new(reader))
+    }
+
+    /// Registers a reader that produces results of the given formats
+    pub fn register_for_formats<R: CustomReader + 'static>(
+        &mut self,
+        name: &str,
+        order: ReaderOrder,
+        formats: HashSet<BarcodeFormat>,
+        reader: R,
+    ) -> Result<()> {
+        if formats.is_empty() {
+            return Err(Exceptions::illegal_argument_with(format!(
+                "reader {name} must handle at least one format"
+            )));
+        }
+        self.insert(name, order, Some(formats), Arc::new(reader))
+    }
+
+    fn insert(
+        &mut self,
+        name: &str,
+        order: ReaderOrder,
+        formats: Option<HashSet<BarcodeFormat>>,
+        reader: Arc<dyn CustomReader>,
+    ) -> Result<()> {
+        if name.is_empty() {
+            return Err(Exceptions::illegal_argument_with("reader name is empty"));
+        }
+        if self.contains(name) {
+            return Err(Exceptions::illegal_argument_with(format!(
+                "a reader named {name} is already registered"
+            )));
+        }
+        self.readers.push(RegisteredReader {
+            name: name.to_owned(),
+            order,
+            priority: 0,
+            formats,
+            reader,
+        });
+        Ok(())
+    }
+
+    /**
+     * Sets the priority of the reader registered under `name`, 0 by default. Readers with a
+     * higher priority are tried before the others with the same [`ReaderOrder`].
+     *
+     * @return whether a reader is registered under `name`
+     */
+    pub fn set_priority(&mut self, name: &str, priority: i32) -> bool {
+        let Some(reader) = self.readers.iter_mut().find(|r| r.name == name) else {
+            return false;
+        };
+        reader.priority = priority;
+        true
+    }
+
+    /// The priority of the reader registered under `name`
+    pub fn priority(&self, name: &str) -> Option<i32> {
+        self.readers
+            .iter()
+            .find(|r| r.name == name)
+            .map(|r| r.priority)
+    }
+
+    /// The readers placed at `order`, in attempt order
+    fn ordered(&self, order: ReaderOrder) -> impl Iterator<Item = &RegisteredReader> {
+        let mut readers: Vec<_> = self.readers.iter().filter(|r| r.order == order).collect();
+        readers.sort_by_key(|r| std::cmp::Reverse(r.priority));
+        readers.into_iter()
+    }
+
+    /// Removes the reader registered under `name`, returning whether it was present
+    pub fn unregister(&mut self, name: &str) -> bool {
+        let len = self.readers.len();
+        self.readers.retain(|r| r.name != name);
+        self.readers.len() != len
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.readers.iter().any(|r| r.name == name)
+    }
+
+    /// The names of the registered readers, in attempt order
+    pub fn names(&self) -> Vec<&str> {
+        [ReaderOrder::First, ReaderOrder::Last]
+            .into_iter()
+            .flat_map(|order| self.ordered(order))
+            .map(|r| r.name.as_str())
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.readers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.readers.is_empty()
+    }
+
+    /**
+     * Tries the readers placed at `order`, skipping those whose formats are excluded by
+     * `possible_formats`. An empty set of possible formats allows all readers.
+     */
+    pub(crate) fn decode(
+        &self,
+        order: ReaderOrder,
+        image: &BitMatrix,
+        hints: &DecodeHints,
+        possible_formats: &HashSet<BarcodeFormat>,
+    ) -> Option<RXingResult> {
+        self.ordered(order)
+            .filter(|r| match &r.formats {
+                Some(formats) if !possible_formats.is_empty() => {
+                    !formats.is_disjoint(possible_formats)
+                }
+                _ => true,
+            })
+            .find_map(|r| r.reader.decode(image, hints).ok())
+    }
+}
+
+impl fmt::Debug for ReaderRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.names()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::{
+        common::{BitMatrix, Result},
+        BarcodeFormat, BinaryBitmap, DecodeHints, Exceptions, Luma8LuminanceSource,
+        MultiFormatReader, RXingResult, Reader, ReaderOrder, ReaderRegistry,
+    };
+
+    /// A made up symbology: a solid black square, which reads as "SQUARE"
+    fn square_reader(image: &BitMatrix, _hints: &DecodeHints) -> Result<RXingResult> {
+        let [left, top, width, height] =
+            image.getEnclosingRectangle().ok_or(Exceptions::NOT_FOUND)?;
+        for y in top..top + height {
+            for x in left..left + width {
+                if !image.get(x, y) {
+                    return Err(Exceptions::NOT_FOUND);
+                }
+            }
+        }
+        Ok(RXingResult::new(
+            "SQUARE",
+            Vec::new(),
+            Vec::new(),
+            BarcodeFormat::UNSUPORTED_FORMAT,
+        ))
+    }
+
+    fn square_image() -> BinaryBitmap<crate::common::HybridBinarizer<Luma8LuminanceSource>> {
+        let mut luma = vec![255; 100 * 100];
+        for y in 30..70 {
+            for x in 30..70 {
+                luma[y * 100 + x] = 0;
+            }
+        }
+        BinaryBitmap::new(crate::common::HybridBinarizer::new(
+            Luma8LuminanceSource::new(luma, 100, 100),
+        ))
+    }
+
+    #[test]
+    fn test_registry() {
+        let mut registry = ReaderRegistry::new();
+        registry
+            .register("square", ReaderOrder::Last, square_reader)
+            .unwrap();
+        registry
+            .register("first", ReaderOrder::First, square_reader)
+            .unwrap();
+        assert!(registry
+            .register("square", ReaderOrder::First, square_reader)
+            .is_err());
+        assert_eq!(vec!["first", "square"], registry.names());
+        assert!(registry.unregister("first"));
+        assert!(!registry.unregister("first"));
+        assert_eq!(1, registry.len());
+    }
+
+    #[test]
+    fn test_priority() {
+        let mut registry = ReaderRegistry::new();
+        for name in ["a", "b", "c"] {
+            registry
+                .register(name, ReaderOrder::Last, square_reader)
+                .unwrap();
+        }
+        registry
+            .register("first", ReaderOrder::First, square_reader)
+            .unwrap();
+        assert!(registry.set_priority("c", 2));
+        assert!(registry.set_priority("a", -1));
+        assert!(!registry.set_priority("d", 1));
+        assert_eq!(Some(2), registry.priority("c"));
+        assert_eq!(Some(0), registry.priority("b"));
+        assert_eq!(None, registry.priority("d"));
+        // the priority orders readers within their ReaderOrder only
+        assert_eq!(vec!["first", "c", "b", "a"], registry.names());
+    }
+
+    #[test]
+    fn test_other_format() {
+        // two readers of the same made up format, the one with the higher priority reads it
+        let named = |text: &'static str| {
+            move |image: &BitMatrix, hints: &DecodeHints| {
+                let result = square_reader(image, hints)?;
+                Ok(RXingResult::new(
+                    text,
+                    Vec::new(),
+                    result.getPoints().to_vec(),
+                    BarcodeFormat::Other("square".to_owned()),
+                ))
+            }
+        };
+        let square = HashSet::from([BarcodeFormat::Other("square".to_owned())]);
+        let mut registry = ReaderRegistry::new();
+        registry
+            .register_for_formats("low", ReaderOrder::Last, square.clone(), named("low"))
+            .unwrap();
+        registry
+            .register_for_formats("high", ReaderOrder::Last, square.clone(), named("high"))
+            .unwrap();
+        registry.set_priority("high", 1);
+        let mut reader = MultiFormatReader::with_registry(registry);
+        let result = reader.decode(&mut square_image()).unwrap();
+        assert_eq!("high", result.getText());
+        assert_eq!(
+            &BarcodeFormat::Other("square".to_owned()),
+            result.getBarcodeFormat()
+        );
+        assert_eq!("square", result.getBarcodeFormat().to_string());
+
+        // the readers of the format are tried when it is asked for, and only then
+        let hints = DecodeHints {
+            PossibleFormats: Some(square),
+            ..Default::default()
+        };
+        reader.registry_mut().unregister("high");
+        let result = reader
+            .decode_with_hints(&mut square_image(), &hints)
+            .unwrap();
+        assert_eq!("low", result.getText());
+        let hints = DecodeHints {
+            PossibleFormats: Some(HashSet::from([BarcodeFormat::QR_CODE])),
+            ..Default::default()
+        };
+        assert!(reader
+            .decode_with_hints(&mut square_image(), &hints)
+            .is_err());
+    }
+
+    #[test]
+    fn test_custom_reader_in_multi_format_reader() {
+        let mut reader = MultiFormatReader::default();
+        assert!(reader.decode(&mut square_image()).is_err());
+
+        reader
+            .registry_mut()
+            .register("square", ReaderOrder::Last, square_reader)
+            .unwrap();
+        assert_eq!(
+            "SQUARE",
+            reader.decode(&mut square_image()).unwrap().getText()
+        );
+
+        // readers restricted to formats are skipped if the hints exclude them
+        let mut registry = ReaderRegistry::new();
+        registry
+            .register_for_formats(
+                "square",
+                ReaderOrder::First,
+                HashSet::from([BarcodeFormat::MAXICODE]),
+                square_reader,
+            )
+            .unwrap();
+        let mut reader = MultiFormatReader::with_registry(registry);
+        let hints = DecodeHints {
+            PossibleFormats: Some(HashSet::from([BarcodeFormat::QR_CODE])),
+            ..Default::default()
+        };
+        assert!(reader
+            .decode_with_hints(&mut square_image(), &hints)
+            .is_err());
+        let hints = DecodeHints {
+            PossibleFormats: Some(HashSet::from([BarcodeFormat::MAXICODE])),
+            ..Default::default()
+        };
+        assert!(reader
+            .decode_with_hints(&mut square_image(), &hints)
+            .is_ok());
+    }
+}
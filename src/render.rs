@@ -0,0 +1,585 @@
+//! Vector output of encoded symbols, as SVG or Encapsulated PostScript.
+//!
+//! Every writer returns a [`BitMatrix`]; [`ToSVG`] and [`ToEPS`] draw one at a module size in
+//! user units (pixels in SVG, points in EPS) with a quiet zone, colors and, for linear
+//! symbols, a line of human readable text under the bars. The output is a string to save or
+//! embed, so labels can be printed at any resolution without rasterizing them first.
+//!
+//! Modules are drawn as rectangles, one per run of dark modules in a row, with runs repeated
+//! in the rows below merged into one: a linear symbol is a rectangle per bar.
+//!
+//! With the `image-render` feature, [`render_to_image`] and [`render_to_gray_image`] draw a
+//! symbol as pixels instead, a whole number of them to a module.
+//!
+//! ```
+//! use rxing::{render::{RenderOptions, ToEPS, ToSVG}, BarcodeFormat, MultiFormatWriter, Writer};
+//!
+//! let symbol = MultiFormatWriter.encode("0123456789", &BarcodeFormat::CODE_128, 0, 0)?;
+//! let options = RenderOptions::default()
+//!     .with_module_size(2.0)
+//!     .with_bar_height(60.0)
+//!     .with_human_readable_text("0123456789");
+//! let svg = symbol.to_svg(&options);
+//! assert!(svg.starts_with("<?xml"));
+//! let eps = symbol.to_eps(&options);
+//! assert!(eps.starts_with("%!PS-Adobe-3.0 EPSF-3.0"));
+//! # Ok::<(), rxing::Exceptions>(())
+//! ```
+
+use std::fmt::Write;
+
+use crate::common::BitMatrix;
+
+/// How a symbol is drawn by [`ToSVG`] and [`ToEPS`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderOptions {
+    /// The width and height of a module, in user units
+    pub module_size: f32,
+    /// The modules of background added on each side, on top of any margin the writer left
+    pub quiet_zone: u32,
+    /// The color of dark modules and of the text
+    pub foreground: [u8; 3],
+    /// The color of light modules and the quiet zone, transparent if `None`
+    pub background: Option<[u8; 3]>,
+    /// Draws the first row of the symbol only, this high, as writers of linear symbols asked
+    /// for a height of 0 return a single row. `None` draws every row a module high
+    pub bar_height: Option<f32>,
+    /// A line of text centered under the symbol, usually the contents of a linear symbol
+    pub human_readable_text: Option<String>,
+    /// The size of the text, 4 modules if `None`
+    pub font_size: Option<f32>,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            module_size: 4.0,
+            quiet_zone: 0,
+            foreground: [0, 0, 0],
+            background: Some([u8::MAX, u8::MAX, u8::MAX]),
+            bar_height: None,
+            human_readable_text: None,
+            font_size: None,
+        }
+    }
+}
+
+impl RenderOptions {
+    pub fn with_module_size(mut self, module_size: f32) -> Self {
+        self.module_size = module_size;
+        self
+    }
+
+    pub fn with_quiet_zone(mut self, quiet_zone: u32) -> Self {
+        self.quiet_zone = quiet_zone;
+        self
+    }
+
+    pub fn with_colors(mut self, foreground: [u8; 3], background: Option<[u8; 3]>) -> Self {
+        self.foreground = foreground;
+        self.background = background;
+        self
+    }
+
+    pub fn with_bar_height(mut self, bar_height: f32) -> Self {
+        self.bar_height = Some(bar_height);
+        self
+    }
+
+    pub fn with_human_readable_text(mut self, text: &str) -> Self {
+        self.human_readable_text = Some(text.to_owned());
+        self
+    }
+
+    pub fn with_font_size(mut self, font_size: f32) -> Self {
+        self.font_size = Some(font_size);
+        self
+    }
+}
+
+/// Draws a symbol as an SVG document
+pub trait ToSVG {
+    fn to_svg(&self, options: &RenderOptions) -> String;
+}
+
+/// Draws a symbol as an Encapsulated PostScript file
+pub trait ToEPS {
+    fn to_eps(&self, options: &RenderOptions) -> String;
+}
+
+impl ToSVG for BitMatrix {
+    fn to_svg(&self, options: &RenderOptions) -> String {
+        let layout = Layout::of(self, options);
+        let mut svg = String::new();
+        svg.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        let _ = writeln!(
+            svg,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" version=\"1.1\" width=\"{0}\" height=\"{1}\" viewBox=\"0 0 {0} {1}\">",
+            number(layout.width),
+            number(layout.height)
+        );
+        if let Some(background) = options.background {
+            let _ = writeln!(
+                svg,
+                "<rect width=\"{}\" height=\"{}\" fill=\"{}\"/>",
+                number(layout.width),
+                number(layout.height),
+                hex_color(background)
+            );
+        }
+        let mut path = String::new();
+        for rect in &layout.rects {
+            let _ = write!(
+                path,
+                "M{},{}h{}v{}h-{}z",
+                number(rect.x),
+                number(rect.y),
+                number(rect.width),
+                number(rect.height),
+                number(rect.width)
+            );
+        }
+        if !path.is_empty() {
+            let _ = writeln!(
+                svg,
+                "<path fill=\"{}\" shape-rendering=\"crispEdges\" d=\"{path}\"/>",
+                hex_color(options.foreground)
+            );
+        }
+        if let Some(text) = &options.human_readable_text {
+            let _ = writeln!(
+                svg,
+                "<text x=\"{}\" y=\"{}\" font-family=\"monospace\" font-size=\"{}\" text-anchor=\"middle\" fill=\"{}\">{}</text>",
+                number(layout.width / 2.0),
+                number(layout.text_baseline),
+                number(layout.font_size),
+                hex_color(options.foreground),
+                xml_escape(text)
+            );
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
+
+impl ToEPS for BitMatrix {
+    fn to_eps(&self, options: &RenderOptions) -> String {
+        let layout = Layout::of(self, options);
+        let mut eps = String::new();
+        eps.push_str("%!PS-Adobe-3.0 EPSF-3.0\n");
+        eps.push_str("%%Creator: rxing\n");
+        let _ = writeln!(
+            eps,
+            "%%BoundingBox: 0 0 {} {}",
+            layout.width.ceil(),
+            layout.height.ceil()
+        );
+        let _ = writeln!(
+            eps,
+            "%%HiResBoundingBox: 0 0 {} {}",
+            number(layout.width),
+            number(layout.height)
+        );
+        eps.push_str("%%EndComments\n");
+        eps.push_str("gsave\n");
+        if let Some(background) = options.background {
+            let _ = writeln!(
+                eps,
+                "{} setrgbcolor\n0 0 {} {} rectfill",
+                ps_color(background),
+                number(layout.width),
+                number(layout.height)
+            );
+        }
+        let _ = writeln!(eps, "{} setrgbcolor", ps_color(options.foreground));
+        // PostScript counts y up from the bottom
+        for rect in &layout.rects {
+            let _ = writeln!(
+                eps,
+                "{} {} {} {} rectfill",
+                number(rect.x),
+                number(layout.height - rect.y - rect.height),
+                number(rect.width),
+                number(rect.height)
+            );
+        }
+        if let Some(text) = &options.human_readable_text {
+            let _ = writeln!(
+                eps,
+                "/Courier findfont {} scalefont setfont",
+                number(layout.font_size)
+            );
+            let _ = writeln!(
+                eps,
+                "{} {} moveto ({}) dup stringwidth pop 2 div neg 0 rmoveto show",
+                number(layout.width / 2.0),
+                number(layout.height - layout.text_baseline),
+                ps_escape(text)
+            );
+        }
+        eps.push_str("grestore\nshowpage\n%%EOF\n");
+        eps
+    }
+}
+
+/// How a symbol is drawn by [`render_to_image`] and [`render_to_gray_image`]
+#[cfg(feature = "image-render")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImageRenderOptions {
+    /// The width and height of a module, in pixels
+    pub scale: u32,
+    /// The modules of background added on each side, on top of any margin the writer left
+    pub margin: u32,
+    /// The color of dark modules
+    pub foreground: [u8; 3],
+    /// The color of light modules and the margin, transparent if `None`. Gray images are
+    /// white where it is transparent
+    pub background: Option<[u8; 3]>,
+    /// Draws the first row of the symbol only, this many pixels high, as writers of linear
+    /// symbols asked for a height of 0 return a single row. `None` draws every row
+    pub bar_height: Option<u32>,
+}
+
+#[cfg(feature = "image-render")]
+impl Default for ImageRenderOptions {
+    fn default() -> Self {
+        Self {
+            scale: 4,
+            margin: 0,
+            foreground: [0, 0, 0],
+            background: Some([u8::MAX, u8::MAX, u8::MAX]),
+            bar_height: None,
+        }
+    }
+}
+
+#[cfg(feature = "image-render")]
+impl ImageRenderOptions {
+    pub fn with_scale(mut self, scale: u32) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    pub fn with_margin(mut self, margin: u32) -> Self {
+        self.margin = margin;
+        self
+    }
+
+    pub fn with_colors(mut self, foreground: [u8; 3], background: Option<[u8; 3]>) -> Self {
+        self.foreground = foreground;
+        self.background = background;
+        self
+    }
+
+    pub fn with_bar_height(mut self, bar_height: u32) -> Self {
+        self.bar_height = Some(bar_height);
+        self
+    }
+}
+
+/// Draws a symbol as an RGBA image, `scale` pixels to a module
+#[cfg(feature = "image-render")]
+pub fn render_to_image(matrix: &BitMatrix, options: &ImageRenderOptions) -> image::RgbaImage {
+    let [r, g, b] = options.foreground;
+    let dark = image::Rgba([r, g, b, u8::MAX]);
+    let light = match options.background {
+        Some([r, g, b]) => image::Rgba([r, g, b, u8::MAX]),
+        None => image::Rgba([0, 0, 0, 0]),
+    };
+    render_pixels(matrix, options, dark, light)
+}
+
+/// Draws a symbol as a grayscale image, `scale` pixels to a module, with the colors of the
+/// options converted to their luma
+#[cfg(feature = "image-render")]
+pub fn render_to_gray_image(matrix: &BitMatrix, options: &ImageRenderOptions) -> image::GrayImage {
+    let dark = image::Luma([luma(options.foreground)]);
+    let light = image::Luma([options.background.map_or(u8::MAX, luma)]);
+    render_pixels(matrix, options, dark, light)
+}
+
+#[cfg(feature = "image-render")]
+fn render_pixels<P: image::Pixel>(
+    matrix: &BitMatrix,
+    options: &ImageRenderOptions,
+    dark: P,
+    light: P,
+) -> image::ImageBuffer<P, Vec<P::Subpixel>> {
+    let scale = options.scale.max(1);
+    let margin = options.margin * scale;
+    let (rows, row_height) = match options.bar_height {
+        Some(bar_height) => (matrix.getHeight().min(1), bar_height),
+        None => (matrix.getHeight(), scale),
+    };
+    let width = matrix.getWidth() * scale + 2 * margin;
+    let height = rows * row_height + 2 * margin;
+    image::ImageBuffer::from_fn(width, height, |x, y| {
+        let inside =
+            (margin..width - margin).contains(&x) && (margin..height - margin).contains(&y);
+        if inside && matrix.get((x - margin) / scale, (y - margin) / row_height.max(1)) {
+            dark
+        } else {
+            light
+        }
+    })
+}
+
+/// ITU-R BT.601 luma of a color
+#[cfg(feature = "image-render")]
+fn luma([r, g, b]: [u8; 3]) -> u8 {
+    ((299 * r as u32 + 587 * g as u32 + 114 * b as u32 + 500) / 1000) as u8
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Rect {
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+}
+
+/// Rows of a symbol with the same runs of dark modules, as start and length
+struct Band {
+    top: u32,
+    rows: u32,
+    runs: Vec<(u32, u32)>,
+}
+
+/// Where everything goes, in user units from the top left
+struct Layout {
+    width: f32,
+    height: f32,
+    rects: Vec<Rect>,
+    font_size: f32,
+    text_baseline: f32,
+}
+
+impl Layout {
+    fn of(matrix: &BitMatrix, options: &RenderOptions) -> Self {
+        let module = options.module_size;
+        let margin = options.quiet_zone as f32 * module;
+        let (rows, row_height) = match options.bar_height {
+            Some(bar_height) => (matrix.getHeight().min(1), bar_height),
+            None => (matrix.getHeight(), module),
+        };
+
+        // rows with the same runs as the row above extend its band
+        let mut bands: Vec<Band> = Vec::new();
+        for y in 0..rows {
+            let runs = runs(matrix, y);
+            match bands.last_mut() {
+                Some(band) if band.runs == runs => band.rows += 1,
+                _ => bands.push(Band {
+                    top: y,
+                    rows: 1,
+                    runs,
+                }),
+            }
+        }
+        let rects = bands
+            .iter()
+            .flat_map(|band| {
+                band.runs.iter().map(move |&(start, length)| Rect {
+                    x: margin + start as f32 * module,
+                    y: margin + band.top as f32 * row_height,
+                    width: length as f32 * module,
+                    height: band.rows as f32 * row_height,
+                })
+            })
+            .collect();
+
+        let symbol_height = rows as f32 * row_height;
+        let font_size = options.font_size.unwrap_or(4.0 * module);
+        let text_height = if options.human_readable_text.is_some() {
+            font_size * 1.25
+        } else {
+            0.0
+        };
+        Self {
+            width: matrix.getWidth() as f32 * module + 2.0 * margin,
+            height: symbol_height + 2.0 * margin + text_height,
+            rects,
+            font_size,
+            text_baseline: margin + symbol_height + font_size,
+        }
+    }
+}
+
+/// The runs of dark modules in a row, as start and length
+fn runs(matrix: &BitMatrix, y: u32) -> Vec<(u32, u32)> {
+    let mut runs = Vec::new();
+    let mut x = 0;
+    while x < matrix.getWidth() {
+        if matrix.get(x, y) {
+            let start = x;
+            while x < matrix.getWidth() && matrix.get(x, y) {
+                x += 1;
+            }
+            runs.push((start, x - start));
+        } else {
+            x += 1;
+        }
+    }
+    runs
+}
+
+/// A number with at most 3 decimals and no trailing zeros
+pub(crate) fn number(value: f32) -> String {
+    let formatted = format!("{value:.3}");
+    let trimmed = formatted.trim_end_matches('0').trim_end_matches('.');
+    if trimmed == "-0" {
+        "0".to_owned()
+    } else {
+        trimmed.to_owned()
+    }
+}
+
+pub(crate) fn hex_color([r, g, b]: [u8; 3]) -> String {
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
+
+fn ps_color(color: [u8; 3]) -> String {
+    color
+        .iter()
+        .map(|&c| number(c as f32 / 255.0))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+pub(crate) fn xml_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// A PostScript string literal's contents. Characters outside printable ASCII are written as
+/// octal escapes of their ISO-8859-1 code, or `?` if they have none
+fn ps_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' | '(' | ')' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            ' '..='~' => escaped.push(c),
+            _ if (c as u32) <= 0xFF => {
+                let _ = write!(escaped, "\\{:03o}", c as u32);
+            }
+            _ => escaped.push('?'),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BarcodeFormat, MultiFormatWriter, Writer};
+
+    #[test]
+    fn test_svg() {
+        let matrix = BitMatrix::parse_strings("X X\nX X\n XX\n", "X", " ").unwrap();
+        let svg = matrix.to_svg(&RenderOptions::default().with_quiet_zone(1));
+        assert_eq!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <svg xmlns=\"http://www.w3.org/2000/svg\" version=\"1.1\" width=\"20\" height=\"20\" viewBox=\"0 0 20 20\">\n\
+             <rect width=\"20\" height=\"20\" fill=\"#ffffff\"/>\n\
+             <path fill=\"#000000\" shape-rendering=\"crispEdges\" d=\"M4,4h4v8h-4zM12,4h4v8h-4zM8,12h8v4h-8z\"/>\n\
+             </svg>\n",
+            svg
+        );
+    }
+
+    #[test]
+    fn test_eps() {
+        let matrix = BitMatrix::parse_strings("X \n", "X", " ").unwrap();
+        let options = RenderOptions::default()
+            .with_module_size(1.5)
+            .with_bar_height(10.0)
+            .with_colors([255, 0, 0], None)
+            .with_human_readable_text("(1)\u{e9}\u{20ac}")
+            .with_font_size(8.0);
+        let eps = matrix.to_eps(&options);
+        assert_eq!(
+            "%!PS-Adobe-3.0 EPSF-3.0\n\
+             %%Creator: rxing\n\
+             %%BoundingBox: 0 0 3 20\n\
+             %%HiResBoundingBox: 0 0 3 20\n\
+             %%EndComments\n\
+             gsave\n\
+             1 0 0 setrgbcolor\n\
+             0 10 1.5 10 rectfill\n\
+             /Courier findfont 8 scalefont setfont\n\
+             1.5 2 moveto (\\(1\\)\\351?) dup stringwidth pop 2 div neg 0 rmoveto show\n\
+             grestore\n\
+             showpage\n\
+             %%EOF\n",
+            eps
+        );
+    }
+
+    #[test]
+    fn test_linear_symbol() {
+        let symbol = MultiFormatWriter
+            .encode("0123456789", &BarcodeFormat::CODE_128, 0, 0)
+            .unwrap();
+        assert_eq!(1, symbol.getHeight());
+        let options = RenderOptions::default()
+            .with_module_size(2.0)
+            .with_quiet_zone(10)
+            .with_bar_height(50.0)
+            .with_human_readable_text("0123 & <4567>");
+        let svg = symbol.to_svg(&options);
+
+        let bars = (0..symbol.getWidth())
+            .filter(|&x| symbol.get(x, 0) && (x == 0 || !symbol.get(x - 1, 0)))
+            .count();
+        assert_eq!(bars, svg.matches('M').count());
+        assert!(svg.contains("v50h"));
+        let width = number((symbol.getWidth() + 20) as f32 * 2.0);
+        // 50 high bars, 20 modules of quiet zone and text 8 high
+        assert!(svg.contains(&format!("width=\"{width}\" height=\"100\"")));
+        assert!(svg.contains(">0123 &amp; &lt;4567&gt;</text>"));
+    }
+
+    #[cfg(feature = "image-render")]
+    #[test]
+    fn test_render_to_image() {
+        let matrix = BitMatrix::parse_strings("X \n X\n", "X", " ").unwrap();
+        let options = ImageRenderOptions::default()
+            .with_scale(2)
+            .with_margin(1)
+            .with_colors([0, 0, 255], None);
+        let image = render_to_image(&matrix, &options);
+        assert_eq!((8, 8), image.dimensions());
+        assert_eq!(image::Rgba([0, 0, 0, 0]), image[(1, 1)]);
+        assert_eq!(image::Rgba([0, 0, 255, 255]), image[(2, 2)]);
+        assert_eq!(image::Rgba([0, 0, 255, 255]), image[(3, 3)]);
+        assert_eq!(image::Rgba([0, 0, 0, 0]), image[(4, 2)]);
+        assert_eq!(image::Rgba([0, 0, 255, 255]), image[(5, 5)]);
+
+        let gray = render_to_gray_image(&matrix, &options.with_bar_height(5));
+        assert_eq!((8, 9), gray.dimensions());
+        assert_eq!(image::Luma([29]), gray[(2, 6)]);
+        assert_eq!(image::Luma([255]), gray[(4, 6)]);
+        assert_eq!(image::Luma([255]), gray[(2, 7)]);
+    }
+
+    #[test]
+    fn test_number() {
+        assert_eq!("0", number(0.0));
+        assert_eq!("0", number(-0.0001));
+        assert_eq!("2", number(2.0));
+        assert_eq!("1.5", number(1.5));
+        assert_eq!("0.333", number(1.0 / 3.0));
+    }
+}
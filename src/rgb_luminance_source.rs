@@ -18,7 +18,7 @@
 
 use std::borrow::Cow;
 
-use crate::common::Result;
+use crate::common::{simd, Result};
 use crate::{Exceptions, LuminanceSource};
 
 /**
@@ -120,14 +120,8 @@ impl RGBLuminanceSource {
         // Total number of pixels suffices, can ignore shape
         let size = width * height;
         let mut luminances: Vec<u8> = vec![0; size];
-        for offset in 0..size {
-            let pixel = pixels[offset];
-            let r = (pixel >> 16) & 0xff; // red
-            let g2 = (pixel >> 7) & 0x1fe; // 2 * green
-            let b = pixel & 0xff; // blue
-                                  // Calculate green-favouring average cheaply
-            luminances[offset] = ((r + g2 + b) / 4) as u8;
-        }
+        // Calculate green-favouring average cheaply
+        simd::argb_to_luma(&pixels[..size], &mut luminances);
         Self {
             luminances: luminances.into_boxed_slice(),
             dataWidth,
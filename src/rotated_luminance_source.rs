@@ -0,0 +1,321 @@
+/*
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::{borrow::Cow, sync::Arc};
+
+use crate::common::Result;
+use crate::{Exceptions, LuminanceSource};
+
+/**
+ * Another luminance source turned by multiples of 90 degrees, read through to the source
+ * without copying its pixels. Supports rotation and cropping for any source, whether or not it
+ * supports them itself, so a reader can try each orientation of a viewfinder rectangle of one
+ * frame. Rows of the unturned or upside down source are taken from the source's rows, the other
+ * rows and columns are gathered pixel by pixel.
+ */
+#[derive(Debug)]
+pub struct RotatedLuminanceSource<S> {
+    source: Arc<S>,
+    /// counter-clockwise quarter turns, 0 to 3
+    turns: u8,
+    /// the rectangle within the turned source
+    left: usize,
+    top: usize,
+    width: usize,
+    height: usize,
+    invert: bool,
+}
+
+impl<S> Clone for RotatedLuminanceSource<S> {
+    fn clone(&self) -> Self {
+        Self {
+            source: self.source.clone(),
+            ..*self
+        }
+    }
+}
+
+impl<S: LuminanceSource> RotatedLuminanceSource<S> {
+    /// Wraps `source` unturned, ready to be rotated or cropped
+    pub fn new(source: S) -> Self {
+        Self::from_arc(Arc::new(source))
+    }
+
+    /// As [`RotatedLuminanceSource::new`], sharing `source` between several views
+    pub fn from_arc(source: Arc<S>) -> Self {
+        Self {
+            width: source.get_width(),
+            height: source.get_height(),
+            source,
+            turns: 0,
+            left: 0,
+            top: 0,
+            invert: false,
+        }
+    }
+
+    pub fn get_source(&self) -> &S {
+        &self.source
+    }
+
+    /// How many times this view is turned counter-clockwise by 90 degrees, from 0 to 3
+    pub fn get_quarter_turns(&self) -> u8 {
+        self.turns
+    }
+
+    /// Where pixel (`x`, `y`) of this view lies in the source
+    pub fn source_point(&self, x: usize, y: usize) -> (usize, usize) {
+        let (x, y) = (self.left + x, self.top + y);
+        let (width, height) = (self.source.get_width(), self.source.get_height());
+        match self.turns {
+            0 => (x, y),
+            1 => (width - 1 - y, x),
+            2 => (width - 1 - x, height - 1 - y),
+            _ => (y, height - 1 - x),
+        }
+    }
+
+    /// The width of the whole source once turned
+    fn turned_width(&self) -> usize {
+        if self.turns % 2 == 0 {
+            self.source.get_width()
+        } else {
+            self.source.get_height()
+        }
+    }
+
+    fn source_luminance(&self, x: usize, y: usize) -> u8 {
+        let (x, y) = self.source_point(x, y);
+        // rows are the one thing every source implements
+        self.source.get_row(y).map_or(0, |row| row[x])
+    }
+
+    /// A run of `len` pixels of source row `y`, starting at `x` and reversed if asked
+    fn source_run(&self, y: usize, x: usize, len: usize, reverse: bool) -> Vec<u8> {
+        let mut run = self
+            .source
+            .get_row(y)
+            .map(|row| row[x..x + len].to_vec())
+            .unwrap_or_else(|| vec![0; len]);
+        if reverse {
+            run.reverse();
+        }
+        run
+    }
+
+    fn invert_if_should(&self, luminances: Vec<u8>) -> Vec<u8> {
+        if self.invert {
+            self.invert_block_of_bytes(luminances)
+        } else {
+            luminances
+        }
+    }
+}
+
+impl<S: LuminanceSource> LuminanceSource for RotatedLuminanceSource<S> {
+    const SUPPORTS_ROTATION: bool = true;
+    const SUPPORTS_CROP: bool = true;
+
+    fn get_row(&self, y: usize) -> Option<Cow<'_, [u8]>> {
+        if y >= self.height {
+            return None;
+        }
+        if self.turns == 0 && !self.invert {
+            let row = match self.source.get_row(self.top + y)? {
+                Cow::Borrowed(row) => Cow::Borrowed(&row[self.left..self.left + self.width]),
+                Cow::Owned(row) => Cow::Owned(row[self.left..self.left + self.width].to_vec()),
+            };
+            return Some(row);
+        }
+        let row = match self.turns {
+            0 | 2 => {
+                let (x, y) = self.source_point(0, y);
+                let reverse = self.turns == 2;
+                let start = if reverse { x + 1 - self.width } else { x };
+                self.source_run(y, start, self.width, reverse)
+            }
+            _ => (0..self.width)
+                .map(|x| self.source_luminance(x, y))
+                .collect(),
+        };
+        Some(Cow::Owned(self.invert_if_should(row)))
+    }
+
+    fn get_column(&self, x: usize) -> Vec<u8> {
+        let column = match self.turns {
+            1 | 3 => {
+                let (sx, sy) = self.source_point(x, 0);
+                let reverse = self.turns == 1;
+                let start = if reverse { sx + 1 - self.height } else { sx };
+                self.source_run(sy, start, self.height, reverse)
+            }
+            _ => (0..self.height)
+                .map(|y| self.source_luminance(x, y))
+                .collect(),
+        };
+        self.invert_if_should(column)
+    }
+
+    fn get_matrix(&self) -> Vec<u8> {
+        let mut matrix = Vec::with_capacity(self.width * self.height);
+        for y in 0..self.height {
+            if let Some(row) = self.get_row(y) {
+                matrix.extend_from_slice(&row);
+            }
+        }
+        matrix
+    }
+
+    fn get_width(&self) -> usize {
+        self.width
+    }
+
+    fn get_height(&self) -> usize {
+        self.height
+    }
+
+    fn crop(&self, left: usize, top: usize, width: usize, height: usize) -> Result<Self> {
+        if left + width > self.width || top + height > self.height {
+            return Err(Exceptions::illegal_argument_with(
+                "Crop rectangle does not fit within image data.",
+            ));
+        }
+        Ok(Self {
+            source: self.source.clone(),
+            left: self.left + left,
+            top: self.top + top,
+            width,
+            height,
+            ..*self
+        })
+    }
+
+    fn invert(&mut self) {
+        self.invert = !self.invert;
+    }
+
+    fn rotate_counter_clockwise(&self) -> Result<Self> {
+        // the left column of the rectangle becomes its bottom row
+        Ok(Self {
+            source: self.source.clone(),
+            turns: (self.turns + 1) % 4,
+            left: self.top,
+            top: self.turned_width() - self.left - self.width,
+            width: self.height,
+            height: self.width,
+            invert: self.invert,
+        })
+    }
+
+    fn get_luma8_point(&self, x: usize, y: usize) -> u8 {
+        let luminance = self.source_luminance(x, y);
+        if self.invert {
+            255 - luminance
+        } else {
+            luminance
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        common::HybridBinarizer, qrcode::QRCodeReader, BarcodeFormat, BinaryBitmap,
+        Luma8LuminanceSource, LuminanceSource, MultiFormatWriter, Reader, Writer,
+    };
+
+    use super::RotatedLuminanceSource;
+
+    fn assert_same<L: LuminanceSource>(expected: &Luma8LuminanceSource, actual: &L) {
+        assert_eq!(expected.get_width(), actual.get_width());
+        assert_eq!(expected.get_height(), actual.get_height());
+        assert_eq!(expected.get_matrix(), actual.get_matrix());
+        for x in 0..expected.get_width() {
+            assert_eq!(expected.get_column(x), actual.get_column(x));
+        }
+        for y in 0..expected.get_height() {
+            assert_eq!(expected.get_row(y), actual.get_row(y));
+            for x in 0..expected.get_width() {
+                assert_eq!(expected.get_luma8_point(x, y), actual.get_luma8_point(x, y));
+            }
+        }
+    }
+
+    #[test]
+    fn test_rotate_and_crop() {
+        let source = Arc::new(Luma8LuminanceSource::new((0..35).collect(), 7, 5));
+        let mut expected = (*source).clone();
+        let mut rotated = RotatedLuminanceSource::from_arc(source.clone());
+        for turns in 1..=4 {
+            expected = expected.rotate_counter_clockwise().unwrap();
+            rotated = rotated.rotate_counter_clockwise().unwrap();
+            assert_eq!(turns % 4, rotated.get_quarter_turns());
+            assert_same(&expected, &rotated);
+        }
+
+        // crop, then keep turning the cropped view
+        let mut expected = source.crop(1, 2, 4, 2).unwrap();
+        let mut cropped = rotated.crop(1, 2, 4, 2).unwrap();
+        assert_same(&expected, &cropped);
+        for _ in 0..4 {
+            expected = expected.rotate_counter_clockwise().unwrap();
+            cropped = cropped.rotate_counter_clockwise().unwrap();
+            assert_same(&expected, &cropped);
+
+            let mut inverted_expected = expected.crop(1, 0, 1, 2).unwrap();
+            let mut inverted = cropped.crop(1, 0, 1, 2).unwrap();
+            inverted_expected.invert();
+            inverted.invert();
+            assert_same(&inverted_expected, &inverted);
+        }
+
+        assert!(cropped.crop(1, 0, 4, 1).is_err());
+    }
+
+    #[test]
+    fn test_decode_turned_viewfinder() {
+        let matrix = MultiFormatWriter
+            .encode("viewfinder", &BarcodeFormat::QR_CODE, 100, 100)
+            .expect("encode");
+        let (size, border) = (matrix.getWidth() as usize, 40);
+        let (width, height) = (size + 2 * border, size + border);
+        let mut pixels = vec![255; width * height];
+        for y in 0..size {
+            for x in 0..size {
+                if matrix.get(x as u32, y as u32) {
+                    pixels[(y + border) * width + x + border] = 0;
+                }
+            }
+        }
+        let upright = RotatedLuminanceSource::new(Luma8LuminanceSource::new(
+            pixels,
+            width as u32,
+            height as u32,
+        ));
+        let upside_down = upright
+            .rotate_counter_clockwise()
+            .and_then(|turned| turned.rotate_counter_clockwise())
+            .expect("rotate");
+        let viewfinder = upside_down
+            .crop(0, 0, size + border, size + border)
+            .expect("crop");
+
+        let result = QRCodeReader
+            .decode(&mut BinaryBitmap::new(HybridBinarizer::new(viewfinder)))
+            .expect("decode");
+        assert_eq!("viewfinder", result.getText());
+    }
+}
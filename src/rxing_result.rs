@@ -17,8 +17,9 @@
 use std::{collections::HashMap, fmt};
 
 use crate::{
-    common::cpp_essentials::DecoderResult, BarcodeFormat, MetadataDictionary, Point,
-    RXingResultMetadataType, RXingResultMetadataValue,
+    common::cpp_essentials::DecoderResult, BarcodeFormat, Content, DecodeWarning,
+    MetadataDictionary, Point, RXingResultMetadataType, RXingResultMetadataValue,
+    GS1_SYMBOLOGY_IDENTIFIERS,
 };
 
 pub type RXingResultMetaDataDictionary = HashMap<RXingResultMetadataType, RXingResultMetadataValue>;
@@ -42,6 +43,10 @@ pub struct RXingResult {
     resultMetadata: RXingResultMetaDataDictionary,
     timestamp: u128,
     line_count: usize,
+    #[cfg_attr(feature = "serde", serde(default))]
+    warnings: Vec<DecodeWarning>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    content: Option<Content>,
 }
 impl RXingResult {
     pub fn new(
@@ -87,6 +92,8 @@ impl RXingResult {
             resultMetadata: HashMap::new(),
             timestamp,
             line_count: 0,
+            warnings: Vec::new(),
+            content: None,
         }
     }
 
@@ -100,6 +107,8 @@ impl RXingResult {
             resultMetadata: self.resultMetadata,
             timestamp: self.timestamp,
             line_count: self.line_count,
+            warnings: self.warnings,
+            content: self.content,
         }
     }
 
@@ -131,12 +140,48 @@ impl RXingResult {
             RXingResultMetadataType::STRUCTURED_APPEND_SEQUENCE,
             RXingResultMetadataValue::StructuredAppendSequence(res.structuredAppend().index),
         );
+        if !res.structuredAppend().id.is_empty() {
+            meta_data.insert(
+                RXingResultMetadataType::STRUCTURED_APPEND_ID,
+                RXingResultMetadataValue::StructuredAppendId(res.structuredAppend().id.clone()),
+            );
+        }
         meta_data.insert(
             RXingResultMetadataType::SYMBOLOGY_IDENTIFIER,
             RXingResultMetadataValue::SymbologyIdentifier(res.symbologyIdentifier()),
         );
+        if let Some(codewords) = res.codewords() {
+            meta_data.insert(
+                RXingResultMetadataType::CODEWORDS,
+                RXingResultMetadataValue::Codewords(codewords.clone()),
+            );
+        }
+        if let Some(version) = res.symbolVersion() {
+            meta_data.insert(
+                RXingResultMetadataType::SYMBOL_VERSION,
+                RXingResultMetadataValue::SymbolVersion(version),
+            );
+        }
 
         new_res.putAllMetadata(meta_data);
+        new_res.setContent(Content::from(res.content()));
+        if let Some(codewords) = res.codewords() {
+            new_res.putErrorCorrectionMetadata(
+                codewords.errors_corrected(),
+                codewords.erasures_corrected(),
+            );
+        }
+
+        if res.codewords().is_some_and(|c| c.uses_heavy_correction()) {
+            new_res.addWarning(DecodeWarning::HeavyErrorCorrection);
+        }
+        if res.isMirrored() {
+            new_res.addWarning(DecodeWarning::AmbiguousOrientation);
+            new_res.putMetadata(
+                RXingResultMetadataType::IS_MIRRORED,
+                RXingResultMetadataValue::IsMirrored(true),
+            );
+        }
 
         new_res
     }
@@ -148,6 +193,31 @@ impl RXingResult {
         &self.text
     }
 
+    /**
+     * @return the bytes the barcode encodes, in segments of their ECI character sets, and
+     *   whether they are text, binary data, GS1 element strings or an ISO/IEC 15434 message.
+     *   For a reader that does not record them, the text as UTF-8.
+     */
+    pub fn content(&self) -> Content {
+        let content = self
+            .content
+            .clone()
+            .unwrap_or_else(|| Content::from_text(&self.text));
+        let gs1 = matches!(
+            self.resultMetadata.get(&RXingResultMetadataType::SYMBOLOGY_IDENTIFIER),
+            Some(RXingResultMetadataValue::SymbologyIdentifier(identifier))
+                if GS1_SYMBOLOGY_IDENTIFIERS.contains(&identifier.as_str())
+        ) || matches!(
+            self.resultMetadata.get(&RXingResultMetadataType::CONTENT_TYPE),
+            Some(RXingResultMetadataValue::ContentType(contentType)) if contentType == "GS1"
+        );
+        content.with_gs1(gs1)
+    }
+
+    pub(crate) fn setContent(&mut self, content: Content) {
+        self.content = Some(content);
+    }
+
     /**
      * @return raw bytes encoded by the barcode, if applicable, otherwise {@code null}
      */
@@ -210,6 +280,18 @@ impl RXingResult {
         self.resultMetadata.insert(md_type, value);
     }
 
+    /// Records how many codewords error correction corrected, and how many of them were erasures
+    pub(crate) fn putErrorCorrectionMetadata(&mut self, errorsCorrected: usize, erasures: usize) {
+        self.putMetadata(
+            RXingResultMetadataType::ERRORS_CORRECTED,
+            RXingResultMetadataValue::ErrorsCorrected(errorsCorrected),
+        );
+        self.putMetadata(
+            RXingResultMetadataType::ERASURES_CORRECTED,
+            RXingResultMetadataValue::ErasuresCorrected(erasures),
+        );
+    }
+
     pub fn putAllMetadata(&mut self, metadata: RXingResultMetaDataDictionary) {
         if self.resultMetadata.is_empty() {
             let _ = std::mem::replace(&mut self.resultMetadata, metadata);
@@ -220,6 +302,26 @@ impl RXingResult {
         }
     }
 
+    /**
+     * @return defects of the symbol that did not stop it from being read, empty for a symbol
+     *   without known defects
+     */
+    pub fn getWarnings(&self) -> &[DecodeWarning] {
+        &self.warnings
+    }
+
+    pub fn addWarning(&mut self, warning: DecodeWarning) {
+        if !self.warnings.contains(&warning) {
+            self.warnings.push(warning);
+        }
+    }
+
+    pub fn addWarnings(&mut self, warnings: &[DecodeWarning]) {
+        for warning in warnings {
+            self.addWarning(*warning);
+        }
+    }
+
     pub fn addPoints(&mut self, newPoints: &mut Vec<Point>) {
         if !newPoints.is_empty() {
             self.resultPoints.append(newPoints);
@@ -248,3 +350,70 @@ impl fmt::Display for RXingResult {
         write!(f, "{}", self.text)
     }
 }
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::{
+        helpers, plan_encode, BarcodeFormat, DecodeHints, EncodeHints, EncodePlan,
+        MultiFormatWriter, RXingResult, RXingResultMetadataType, Writer,
+    };
+
+    fn decode(format: BarcodeFormat, contents: &str) -> RXingResult {
+        let matrix = MultiFormatWriter
+            .encode_with_hints(contents, &format, 300, 300, &EncodeHints::default())
+            .unwrap();
+        let luma = matrix.to_luma8();
+        helpers::detect_in_luma_with_hints(
+            luma,
+            matrix.getWidth(),
+            matrix.getHeight(),
+            Some(format),
+            &mut DecodeHints::default(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_serde_round_trip() {
+        for (format, contents) in [
+            (BarcodeFormat::QR_CODE, "serde"),
+            (BarcodeFormat::PDF_417, "serde"),
+            (BarcodeFormat::CODE_128, "serde"),
+        ] {
+            let result = decode(format, contents);
+            let json = serde_json::to_string(&result).unwrap();
+            let restored: RXingResult = serde_json::from_str(&json).unwrap();
+            assert_eq!(result, restored);
+        }
+        let result = decode(BarcodeFormat::QR_CODE, "serde");
+        assert!(result
+            .getRXingResultMetadata()
+            .contains_key(&RXingResultMetadataType::SAMPLING_GRID));
+
+        let hints = DecodeHints {
+            PossibleFormats: Some(HashSet::from([BarcodeFormat::QR_CODE])),
+            TryHarder: Some(true),
+            ..Default::default()
+        };
+        let json = serde_json::to_string(&hints).unwrap();
+        let restored: DecodeHints = serde_json::from_str(&json).unwrap();
+        assert_eq!(hints.PossibleFormats, restored.PossibleFormats);
+        assert_eq!(hints.TryHarder, restored.TryHarder);
+
+        let hints = EncodeHints {
+            ErrorCorrection: Some("H".to_owned()),
+            Margin: Some("2".to_owned()),
+            ..Default::default()
+        };
+        let json = serde_json::to_string(&hints).unwrap();
+        let restored: EncodeHints = serde_json::from_str(&json).unwrap();
+        assert_eq!(hints.ErrorCorrection, restored.ErrorCorrection);
+        assert_eq!(hints.Margin, restored.Margin);
+
+        let plan = plan_encode("serde 12345", BarcodeFormat::QR_CODE, &hints).unwrap();
+        let json = serde_json::to_string(&plan).unwrap();
+        assert_eq!(plan, serde_json::from_str::<EncodePlan>(&json).unwrap());
+    }
+}
@@ -0,0 +1,298 @@
+/*
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+pub use crate::common::ContentType;
+use crate::common::{AIFlag, CharacterSet, ECIStringBuilder, Eci};
+
+/// The symbology identifiers of GS1-128, DataBar, Data Matrix, QR Code, DotCode and Aztec
+/// symbols that carry GS1 element strings
+pub const GS1_SYMBOLOGY_IDENTIFIERS: [&str; 6] = ["]C1", "]e0", "]d2", "]Q3", "]J1", "]z1"];
+
+/// The header of an ISO/IEC 15434 message, followed by two digits of its format
+const ISO15434_HEADER: &[u8] = b"[)>\x1E";
+
+/**
+ * The bytes a symbol encodes, as `RXingResult::content` returns them: in segments of the
+ * character set an ECI selected for them, and classified as text, binary data, GS1 element
+ * strings or an ISO/IEC 15434 message. Unlike the text of a result, the bytes are the ones of
+ * the symbol, whatever character set they are in.
+ */
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Content {
+    segments: Vec<ContentSegment>,
+    content_type: ContentType,
+}
+
+/// Bytes of a symbol in a single character set
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContentSegment {
+    charset: Option<CharacterSet>,
+    bytes: Vec<u8>,
+}
+
+impl ContentSegment {
+    /// The character set an ECI selected for the bytes, `None` without an ECI, where the bytes
+    /// are in the default character set of the symbology
+    pub fn charset(&self) -> Option<CharacterSet> {
+        self.charset
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+impl Content {
+    /// The content of a symbol that only the text is known of, as UTF-8
+    pub(crate) fn from_text(text: &str) -> Self {
+        Self::new(vec![ContentSegment {
+            charset: Some(CharacterSet::UTF8),
+            bytes: text.as_bytes().to_vec(),
+        }])
+    }
+
+    fn new(segments: Vec<ContentSegment>) -> Self {
+        let mut content = Self {
+            segments,
+            content_type: ContentType::Text,
+        };
+        content.content_type = content.classify();
+        content
+    }
+
+    pub fn segments(&self) -> &[ContentSegment] {
+        &self.segments
+    }
+
+    /// All the bytes of the symbol, the segments joined
+    pub fn bytes(&self) -> Vec<u8> {
+        self.segments
+            .iter()
+            .flat_map(|segment| segment.bytes.iter().copied())
+            .collect()
+    }
+
+    pub fn content_type(&self) -> ContentType {
+        self.content_type
+    }
+
+    /// Returns true if an ECI selected the character set of any segment
+    pub fn has_eci(&self) -> bool {
+        self.segments
+            .iter()
+            .any(|segment| segment.charset.is_some())
+    }
+
+    /// Classifies the content as GS1 element strings, where the reader found a GS1 symbology
+    /// identifier or content type in the metadata of its result
+    pub(crate) fn with_gs1(mut self, gs1: bool) -> Self {
+        if gs1 && self.content_type != ContentType::UnknownECI {
+            self.content_type = ContentType::GS1;
+        }
+        self
+    }
+
+    fn classify(&self) -> ContentType {
+        let bytes = self.bytes();
+        if bytes.is_empty() {
+            return ContentType::Text;
+        }
+        if bytes.len() > 6
+            && bytes.starts_with(ISO15434_HEADER)
+            && bytes[4..6].iter().all(u8::is_ascii_digit)
+        {
+            return ContentType::ISO15434;
+        }
+        if self
+            .segments
+            .iter()
+            .any(|segment| segment.charset == Some(CharacterSet::Unknown))
+        {
+            return ContentType::UnknownECI;
+        }
+
+        let binary = self
+            .segments
+            .iter()
+            .map(|segment| match segment.charset {
+                Some(charset) => charset == CharacterSet::Binary,
+                None => is_binary(&segment.bytes),
+            })
+            .collect::<Vec<_>>();
+        if !binary.contains(&true) {
+            ContentType::Text
+        } else if !binary.contains(&false) {
+            ContentType::Binary
+        } else {
+            ContentType::Mixed
+        }
+    }
+}
+
+impl From<&ECIStringBuilder> for Content {
+    fn from(builder: &ECIStringBuilder) -> Self {
+        let bytes = builder.bytes();
+        let mut segments = Vec::new();
+        if !builder.has_eci || builder.eci_positions.is_empty() {
+            segments.push(ContentSegment {
+                charset: None,
+                bytes: bytes.to_vec(),
+            });
+        } else {
+            let first = builder.eci_positions[0].1;
+            if first > 0 {
+                segments.push(ContentSegment {
+                    charset: None,
+                    bytes: bytes[..first].to_vec(),
+                });
+            }
+            for (eci, start, end) in &builder.eci_positions {
+                let end = if *end == 0 { bytes.len() } else { *end };
+                if end > *start {
+                    segments.push(ContentSegment {
+                        charset: Some(if *eci == Eci::Unknown {
+                            CharacterSet::Unknown
+                        } else {
+                            CharacterSet::from(*eci)
+                        }),
+                        bytes: bytes[*start..end].to_vec(),
+                    });
+                }
+            }
+        }
+
+        Self::new(segments).with_gs1(builder.symbology.aiFlag == AIFlag::GS1)
+    }
+}
+
+/// Returns true if `bytes` without an ECI are no text: neither UTF-8 nor ISO-8859-1, or holding
+/// control characters but for the line breaks and the separators of GS1 and ISO/IEC 15434
+fn is_binary(bytes: &[u8]) -> bool {
+    if std::str::from_utf8(bytes).is_err() && bytes.iter().any(|b| (0x80..0xA0).contains(b)) {
+        return true;
+    }
+    bytes
+        .iter()
+        .any(|b| (*b < 0x20 && !b"\t\n\r\x04\x1C\x1D\x1E".contains(b)) || *b == 0x7F)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        common::{CharacterSet, ECIStringBuilder, Eci},
+        datamatrix::DataMatrixWriter,
+        helpers,
+        qrcode::QRCodeWriter,
+        BarcodeFormat, ContentType, DecodeHints, EncodeHintValue, EncodeHints, RXingResult, Writer,
+    };
+
+    use super::Content;
+
+    fn decode(
+        writer: impl Writer,
+        format: BarcodeFormat,
+        contents: &str,
+        hints: EncodeHints,
+    ) -> RXingResult {
+        let matrix = writer
+            .encode_with_hints(contents, &format, 200, 200, &hints)
+            .expect("encode");
+        let luma = matrix.to_luma8();
+        helpers::detect_in_luma_with_hints(
+            luma,
+            matrix.getWidth(),
+            matrix.getHeight(),
+            Some(format),
+            &mut DecodeHints::default(),
+        )
+        .expect("decode")
+    }
+
+    #[test]
+    fn test_classify() {
+        let mut builder = ECIStringBuilder::default();
+        builder.append_bytes(b"plain text");
+        let content = Content::from(&builder);
+        assert_eq!(ContentType::Text, content.content_type());
+        assert!(!content.has_eci());
+        assert_eq!(None, content.segments()[0].charset());
+
+        let mut builder = ECIStringBuilder::default();
+        builder.append_bytes(&[0x00, 0xFF, 0x80, 0x10]);
+        assert_eq!(ContentType::Binary, Content::from(&builder).content_type());
+
+        let mut builder = ECIStringBuilder::default();
+        builder.append_bytes(b"[)>\x1E06\x1DP123\x1E\x04");
+        assert_eq!(
+            ContentType::ISO15434,
+            Content::from(&builder).content_type()
+        );
+
+        let mut builder = ECIStringBuilder::default();
+        builder.append_bytes(b"ab");
+        builder.append_eci(Eci::Binary);
+        builder.append_bytes(&[0x00, 0xFF]);
+        builder.append_eci(Eci::UTF8);
+        builder.append_bytes("é".as_bytes());
+        let content = Content::from(&builder);
+        assert_eq!(ContentType::Mixed, content.content_type());
+        assert_eq!(
+            vec![None, Some(CharacterSet::Binary), Some(CharacterSet::UTF8)],
+            content
+                .segments()
+                .iter()
+                .map(|segment| segment.charset())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(b"ab\x00\xFF\xC3\xA9".to_vec(), content.bytes());
+    }
+
+    #[test]
+    fn test_result_content() {
+        let hints =
+            EncodeHints::default().with(EncodeHintValue::CharacterSet("ISO-8859-7".to_owned()));
+        let result = decode(QRCodeWriter, BarcodeFormat::QR_CODE, "αβγ", hints);
+        let content = result.content();
+        assert_eq!(ContentType::Text, content.content_type());
+        assert!(content.has_eci());
+        assert_eq!(vec![0xE1, 0xE2, 0xE3], content.bytes());
+        assert_eq!(
+            Some(CharacterSet::ISO8859_7),
+            content
+                .segments()
+                .last()
+                .and_then(|segment| segment.charset())
+        );
+
+        let hints = EncodeHints::default()
+            .with(EncodeHintValue::DataMatrixCompact(true))
+            .with(EncodeHintValue::Gs1Format(true));
+        let result = decode(
+            DataMatrixWriter,
+            BarcodeFormat::DATA_MATRIX,
+            "0109506000134352",
+            hints,
+        );
+        assert_eq!(ContentType::GS1, result.content().content_type());
+
+        let result = RXingResult::new("text", Vec::new(), Vec::new(), BarcodeFormat::CODE_39);
+        assert_eq!(b"text".to_vec(), result.content().bytes());
+        assert_eq!(ContentType::Text, result.content().content_type());
+    }
+}
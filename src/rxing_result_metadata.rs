@@ -18,7 +18,13 @@
 
 use std::sync::Arc;
 
-use crate::pdf417::PDF417RXingResultMetadata;
+use crate::{
+    common::{CodewordWitness, SamplingGrid},
+    datamatrix::MacroEnvelope,
+    oned::ScanlineWitness,
+    pdf417::PDF417RXingResultMetadata,
+    DecodeConfidence, SymbolPose, SymbolVersion,
+};
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
@@ -63,6 +69,18 @@ pub enum RXingResultMetadataType {
      */
     ERROR_CORRECTION_LEVEL,
 
+    /**
+     * The number of codewords error correction corrected, erasures included, for formats with
+     * Reed-Solomon error correction.
+     */
+    ERRORS_CORRECTED,
+
+    /**
+     * The number of the corrected codewords that were erasures, whose positions were known to
+     * be wrong before correction. Only PDF417 decodes with erasures; it is 0 for other formats.
+     */
+    ERASURES_CORRECTED,
+
     /**
      * For some periodicals, indicates the issue number as an {@link Integer}.
      */
@@ -85,6 +103,30 @@ pub enum RXingResultMetadataType {
      */
     UPC_EAN_EXTENSION,
 
+    /**
+     * For UPC/EAN barcodes read with an extension, the columns of the row where the extension
+     * starts and ends, as a pair of pixel offsets.
+     */
+    UPC_EAN_EXTENSION_POSITION,
+
+    /**
+     * For barcodes read in a Full ASCII mode, such as extended Code 39, the characters as
+     * read, before the pairs of them that stand for other ASCII characters were expanded.
+     */
+    RAW_TEXT,
+
+    /**
+     * For Data Matrix symbols that start with a Macro 05 or 06 codeword, the format and data
+     * elements of their ISO/IEC 15434 envelope, as a `MacroEnvelope`.
+     */
+    MACRO_ENVELOPE,
+
+    /**
+     * Whether the symbol programs the reader rather than carrying data, as with the Reader
+     * Programming codeword of Data Matrix. Applications should not treat its text as data.
+     */
+    READER_PROGRAMMING,
+
     /**
      * PDF417-specific metadata
      */
@@ -92,16 +134,22 @@ pub enum RXingResultMetadataType {
 
     /**
      * If the code format supports structured append and the current scanned code is part of one then the
-     * sequence number is given with it.
+     * sequence number is given with it: the index of the symbol in the message, from 0.
      */
     STRUCTURED_APPEND_SEQUENCE,
 
     /**
      * If the code format supports structured append and the current scanned code is part of one then the
-     * parity is given with it.
+     * parity is given with it: the number of symbols in the message.
      */
     STRUCTURED_APPEND_PARITY,
 
+    /**
+     * The identifier shared by the symbols of a structured append message: the parity byte of a
+     * QR Code message, or the message ID of an Aztec one, if it has one.
+     */
+    STRUCTURED_APPEND_ID,
+
     /**
      * Barcode Symbology Identifier.
      * Note: According to the GS1 specification the identifier may have to replace a leading FNC1/GS character
@@ -120,6 +168,48 @@ pub enum RXingResultMetadataType {
 
     // In a filtered context, what was the final read resolution
     FILTERED_RESOLUTION,
+
+    /**
+     * The grid used to sample the modules of a 2D symbol from the image. It maps module
+     * coordinates back to image pixels and provides the estimated module pitch.
+     */
+    SAMPLING_GRID,
+
+    /**
+     * The codewords read from a 2D symbol, the Reed-Solomon syndromes and corrections of each
+     * block, and the corrected data codewords that were decoded.
+     */
+    CODEWORDS,
+
+    /**
+     * For Code 128, EAN and UPC symbols, the rows the reader tried, the run-length encoding of
+     * the row the symbol was read from and the patterns matched in it. See `ScanlineWitness`.
+     */
+    SCANLINE,
+
+    /**
+     * How sure the reader is of a 2D symbol, from the error correction it used and the fit of
+     * its sampling grid. See `DecodeConfidence`.
+     */
+    CONFIDENCE,
+
+    /**
+     * For Aztec Runes, the value from 0 to 255 they hold. The text of the result is the same
+     * value in decimal.
+     */
+    AZTEC_RUNE,
+
+    /**
+     * For 2D symbols, how far they are turned, the size of their modules and the transform from
+     * their modules to the image. See `SymbolPose`.
+     */
+    POSE,
+
+    /**
+     * For QR Codes, Micro QR Codes and Data Matrix symbols, the version, data mask and model or
+     * the size of the symbol and its data regions. See `SymbolVersion`.
+     */
+    SYMBOL_VERSION,
 }
 
 impl From<String> for RXingResultMetadataType {
@@ -131,10 +221,22 @@ impl From<String> for RXingResultMetadataType {
             "ERROR_CORRECTION_LEVEL" | "ERRORCORRECTIONLEVEL" | "ECLEVEL" => {
                 RXingResultMetadataType::ERROR_CORRECTION_LEVEL
             }
+            "ERRORS_CORRECTED" | "ERRORSCORRECTED" => RXingResultMetadataType::ERRORS_CORRECTED,
+            "ERASURES_CORRECTED" | "ERASURESCORRECTED" => {
+                RXingResultMetadataType::ERASURES_CORRECTED
+            }
             "ISSUE_NUMBER" | "ISSUENUMBER" => RXingResultMetadataType::ISSUE_NUMBER,
             "SUGGESTED_PRICE" | "SUGGESTEDPRICE" => RXingResultMetadataType::SUGGESTED_PRICE,
             "POSSIBLE_COUNTRY" | "POSSIBLECOUNTRY" => RXingResultMetadataType::POSSIBLE_COUNTRY,
             "UPC_EAN_EXTENSION" | "UPCEANEXTENSION" => RXingResultMetadataType::UPC_EAN_EXTENSION,
+            "UPC_EAN_EXTENSION_POSITION" | "UPCEANEXTENSIONPOSITION" => {
+                RXingResultMetadataType::UPC_EAN_EXTENSION_POSITION
+            }
+            "RAW_TEXT" | "RAWTEXT" => RXingResultMetadataType::RAW_TEXT,
+            "MACRO_ENVELOPE" | "MACROENVELOPE" => RXingResultMetadataType::MACRO_ENVELOPE,
+            "READER_PROGRAMMING" | "READERPROGRAMMING" => {
+                RXingResultMetadataType::READER_PROGRAMMING
+            }
             "PDF417_EXTRA_METADATA" | "PDF417EXTRAMETADATA" => {
                 RXingResultMetadataType::PDF417_EXTRA_METADATA
             }
@@ -144,6 +246,9 @@ impl From<String> for RXingResultMetadataType {
             "STRUCTURED_APPEND_PARITY" | "STRUCTUREDAPPENDPARITY" => {
                 RXingResultMetadataType::STRUCTURED_APPEND_PARITY
             }
+            "STRUCTURED_APPEND_ID" | "STRUCTUREDAPPENDID" => {
+                RXingResultMetadataType::STRUCTURED_APPEND_ID
+            }
             "SYMBOLOGY_IDENTIFIER" | "SYMBOLOGYIDENTIFIER" => {
                 RXingResultMetadataType::SYMBOLOGY_IDENTIFIER
             }
@@ -152,6 +257,13 @@ impl From<String> for RXingResultMetadataType {
             "ISINVERTED" => RXingResultMetadataType::IS_INVERTED,
             "FILTERED_CLOSED" => RXingResultMetadataType::FILTERED_CLOSED,
             "FILTERED_RESOLUTION" => RXingResultMetadataType::FILTERED_RESOLUTION,
+            "SAMPLING_GRID" | "SAMPLINGGRID" => RXingResultMetadataType::SAMPLING_GRID,
+            "CODEWORDS" => RXingResultMetadataType::CODEWORDS,
+            "SCANLINE" => RXingResultMetadataType::SCANLINE,
+            "CONFIDENCE" => RXingResultMetadataType::CONFIDENCE,
+            "AZTEC_RUNE" | "AZTECRUNE" => RXingResultMetadataType::AZTEC_RUNE,
+            "POSE" => RXingResultMetadataType::POSE,
+            "SYMBOL_VERSION" | "SYMBOLVERSION" => RXingResultMetadataType::SYMBOL_VERSION,
             _ => RXingResultMetadataType::OTHER,
         }
     }
@@ -191,6 +303,10 @@ pub enum RXingResultMetadataValue {
      */
     ErrorCorrectionLevel(String),
 
+    ErrorsCorrected(usize),
+
+    ErasuresCorrected(usize),
+
     /**
      * For some periodicals, indicates the issue number as an {@link Integer}.
      */
@@ -213,6 +329,29 @@ pub enum RXingResultMetadataValue {
      */
     UpcEanExtension(String),
 
+    /**
+     * For UPC/EAN barcodes read with an extension, the columns of the row where the extension
+     * starts and ends, as a pair of pixel offsets.
+     */
+    UpcEanExtensionPosition((usize, usize)),
+
+    /**
+     * For barcodes read in a Full ASCII mode, such as extended Code 39, the characters as
+     * read, before the pairs of them that stand for other ASCII characters were expanded.
+     */
+    RawText(String),
+
+    /**
+     * For Data Matrix symbols that start with a Macro 05 or 06 codeword, the format and data
+     * elements of their ISO/IEC 15434 envelope.
+     */
+    MacroEnvelope(MacroEnvelope),
+
+    /**
+     * Whether the symbol programs the reader rather than carrying data.
+     */
+    ReaderProgramming(bool),
+
     /**
      * PDF417-specific metadata
      */
@@ -220,16 +359,22 @@ pub enum RXingResultMetadataValue {
 
     /**
      * If the code format supports structured append and the current scanned code is part of one then the
-     * sequence number is given with it.
+     * sequence number is given with it: the index of the symbol in the message, from 0.
      */
     StructuredAppendSequence(i32),
 
     /**
      * If the code format supports structured append and the current scanned code is part of one then the
-     * parity is given with it.
+     * parity is given with it: the number of symbols in the message.
      */
     StructuredAppendParity(i32),
 
+    /**
+     * The identifier shared by the symbols of a structured append message: the parity byte of a
+     * QR Code message, or the message ID of an Aztec one, if it has one.
+     */
+    StructuredAppendId(String),
+
     /**
      * Barcode Symbology Identifier.
      * Note: According to the GS1 specification the identifier may have to replace a leading FNC1/GS character
@@ -246,4 +391,21 @@ pub enum RXingResultMetadataValue {
     FilteredClosed(bool),
 
     FilteredResolution((usize, usize)),
+
+    SamplingGrid(SamplingGrid),
+
+    Codewords(CodewordWitness),
+
+    Scanline(ScanlineWitness),
+
+    Confidence(DecodeConfidence),
+
+    /**
+     * For Aztec Runes, the value from 0 to 255 they hold.
+     */
+    AztecRune(u8),
+
+    Pose(SymbolPose),
+
+    SymbolVersion(SymbolVersion),
 }
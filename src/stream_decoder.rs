@@ -0,0 +1,401 @@
+/*
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::{
+    common::Result, helpers, multi, point, BarcodeFormat, CropRegion, DecodeHints, Exceptions,
+    RXingResult, RXingResultMetadataType, RXingResultMetadataValue,
+};
+
+/// The number of frames after which the whole frame is searched even if every prior still
+/// holds a symbol, so that symbols coming into view are found
+pub const DEFAULT_FULL_SCAN_INTERVAL: usize = 10;
+
+/// The least number of pixels a prior is extended by on every side, for symbols that moved
+const MIN_PRIOR_MARGIN: u32 = 16;
+
+/// The most symbols a QR Code structured append message can be split into
+const MAX_STRUCTURED_APPEND_COUNT: i32 = 16;
+
+/**
+ * Decodes the successive luminance frames of a video stream, such as those of a camera.
+ *
+ * Unlike `helpers::detect_in_luma`, which searches every frame from scratch, a `StreamDecoder`
+ * first looks for symbols where it found them in the previous frame, and only searches the
+ * whole frame when one of them is lost, or every `full_scan_interval` frames to pick up new
+ * ones. The parts of a PDF417 macro or a QR Code structured append message are collected
+ * across frames, so that the symbols of a message need not be in view at the same time.
+ */
+#[derive(Clone)]
+pub struct StreamDecoder {
+    hints: DecodeHints,
+    full_scan_interval: usize,
+    frames_since_full_scan: usize,
+    frame_size: (u32, u32),
+    priors: Vec<(CropRegion, BarcodeFormat)>,
+    parts: HashMap<(BarcodeFormat, String), MessageParts>,
+}
+
+/// The parts of a structured append message read so far
+#[derive(Clone, Default)]
+struct MessageParts {
+    count: Option<usize>,
+    results: BTreeMap<usize, RXingResult>,
+}
+
+/// Where a symbol belongs in a structured append message
+struct Part {
+    message: (BarcodeFormat, String),
+    index: usize,
+    count: Option<usize>,
+}
+
+impl Default for StreamDecoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StreamDecoder {
+    pub fn new() -> Self {
+        Self::with_hints(DecodeHints::default())
+    }
+
+    pub fn with_hints(hints: DecodeHints) -> Self {
+        Self {
+            hints,
+            full_scan_interval: DEFAULT_FULL_SCAN_INTERVAL,
+            frames_since_full_scan: 0,
+            frame_size: (0, 0),
+            priors: Vec::new(),
+            parts: HashMap::new(),
+        }
+    }
+
+    /// Searches the whole frame at least every `full_scan_interval` frames, 1 meaning every
+    /// frame
+    pub fn with_full_scan_interval(mut self, full_scan_interval: usize) -> Self {
+        self.full_scan_interval = full_scan_interval.max(1);
+        self
+    }
+
+    pub fn get_full_scan_interval(&self) -> usize {
+        self.full_scan_interval
+    }
+
+    /// The regions of the previous frame in which symbols were found, and their formats. They
+    /// are forgotten when the size of the frames changes.
+    pub fn get_priors(&self) -> &[(CropRegion, BarcodeFormat)] {
+        &self.priors
+    }
+
+    /// Forgets the symbols of the previous frames and the incomplete structured append messages
+    pub fn reset(&mut self) {
+        self.frames_since_full_scan = 0;
+        self.priors.clear();
+        self.parts.clear();
+    }
+
+    /**
+     * Decodes the next frame of the stream.
+     *
+     * # Arguments
+     * * `luma` - The luminance of the frame, one byte per pixel, row by row
+     * * `width` - The width of the frame in pixels
+     * * `height` - The height of the frame in pixels
+     *
+     * # Returns
+     * The symbols found in the frame, with their points in the frame, followed by a result for
+     * each structured append message whose last missing part was found in it. Such a result
+     * holds the text and the raw bytes of the parts in order and has no points. A symbol seen
+     * twice in the frame is reported once.
+     *
+     * # Errors
+     * `IllegalArgumentException` if `luma` holds fewer than `width * height` pixels
+     */
+    pub fn decode_frame(
+        &mut self,
+        luma: &[u8],
+        width: u32,
+        height: u32,
+    ) -> Result<Vec<RXingResult>> {
+        if luma.len() < width as usize * height as usize {
+            return Err(Exceptions::illegal_argument_with(format!(
+                "a frame of {width}x{height} needs {} pixels, got {}",
+                width as usize * height as usize,
+                luma.len()
+            )));
+        }
+
+        if self.frame_size != (width, height) {
+            self.frame_size = (width, height);
+            self.priors.clear();
+        }
+
+        let mut results = Vec::new();
+        let mut lost = self.priors.is_empty();
+        for (region, format) in &self.priors {
+            match self.decode_region(luma, width, *region, format.clone()) {
+                Some(result) => results.push(result),
+                None => lost = true,
+            }
+        }
+
+        self.frames_since_full_scan += 1;
+        if lost || self.frames_since_full_scan >= self.full_scan_interval {
+            self.frames_since_full_scan = 0;
+            let mut hints = self.hints.clone();
+            results.extend(
+                helpers::detect_multiple_in_luma_with_hints(
+                    luma[..width as usize * height as usize].to_vec(),
+                    width,
+                    height,
+                    &mut hints,
+                )
+                .unwrap_or_default(),
+            );
+        }
+
+        let mut seen = HashSet::new();
+        results.retain(|result| {
+            seen.insert((
+                result.getBarcodeFormat().clone(),
+                result.getText().to_owned(),
+            ))
+        });
+
+        let frame = CropRegion {
+            left: 0,
+            top: 0,
+            width,
+            height,
+        };
+        self.priors = results
+            .iter()
+            .filter_map(|result| {
+                let points = result.getPoints();
+                let symbol = CropRegion::around(points, 0, frame)?;
+                let margin = (symbol.width.max(symbol.height) / 2).max(MIN_PRIOR_MARGIN);
+                Some((
+                    CropRegion::around(points, margin, frame)?,
+                    result.getBarcodeFormat().clone(),
+                ))
+            })
+            .collect();
+
+        let mut messages = Vec::new();
+        for result in &results {
+            if let Some(message) = self.add_part(result) {
+                messages.push(message);
+            }
+        }
+        results.extend(messages);
+
+        Ok(results)
+    }
+
+    /// Decodes the symbol of `format` in `region` of the frame, with its points in the frame
+    fn decode_region(
+        &self,
+        luma: &[u8],
+        width: u32,
+        region: CropRegion,
+        format: BarcodeFormat,
+    ) -> Option<RXingResult> {
+        let mut crop = Vec::with_capacity(region.width as usize * region.height as usize);
+        for y in region.top..region.top + region.height {
+            let start = (y * width + region.left) as usize;
+            crop.extend_from_slice(&luma[start..start + region.width as usize]);
+        }
+
+        let mut hints = self.hints.clone();
+        let mut result = helpers::detect_in_luma_with_hints(
+            crop,
+            region.width,
+            region.height,
+            Some(format),
+            &mut hints,
+        )
+        .ok()?;
+        for p in result.getPointsMut() {
+            *p += point(region.left as f32, region.top as f32);
+        }
+        Some(result)
+    }
+
+    /**
+     * Adds `result` to its structured append message, if it is part of one.
+     *
+     * # Returns
+     * The message, if `result` was its last missing part
+     */
+    fn add_part(&mut self, result: &RXingResult) -> Option<RXingResult> {
+        let part = Self::part_of(result)?;
+        let parts = self.parts.entry(part.message.clone()).or_default();
+        parts.count = parts.count.or(part.count);
+        parts.results.insert(part.index, result.clone());
+
+        let count = parts.count?;
+        if parts.results.len() < count || (0..count).any(|i| !parts.results.contains_key(&i)) {
+            return None;
+        }
+        let parts = self.parts.remove(&part.message)?;
+        Some(Self::join(parts, part.message.0))
+    }
+
+    /**
+     * Where `result` belongs in a structured append message. PDF417 macro symbols belong to the
+     * message of their file ID. The QR Code readers report the index of a symbol in the sequence
+     * metadata and the number of symbols in the parity metadata, so symbols of other formats
+     * belong to the message of their format and number of symbols.
+     */
+    fn part_of(result: &RXingResult) -> Option<Part> {
+        let format = result.getBarcodeFormat().clone();
+        let metadata = result.getRXingResultMetadata();
+        if let Some(RXingResultMetadataValue::Pdf417ExtraMetadata(pdf417)) =
+            metadata.get(&RXingResultMetadataType::PDF417_EXTRA_METADATA)
+        {
+            if pdf417.getFileId().is_empty() {
+                return None;
+            }
+            let index = pdf417.getSegmentIndex();
+            let count = if pdf417.getSegmentCount() > 0 {
+                Some(pdf417.getSegmentCount() as usize)
+            } else if pdf417.isLastSegment() {
+                Some(index + 1)
+            } else {
+                None
+            };
+            return Some(Part {
+                message: (format, pdf417.getFileId().to_owned()),
+                index,
+                count,
+            });
+        }
+
+        let Some(RXingResultMetadataValue::StructuredAppendSequence(index)) =
+            metadata.get(&RXingResultMetadataType::STRUCTURED_APPEND_SEQUENCE)
+        else {
+            return None;
+        };
+        let Some(RXingResultMetadataValue::StructuredAppendParity(count)) =
+            metadata.get(&RXingResultMetadataType::STRUCTURED_APPEND_PARITY)
+        else {
+            return None;
+        };
+        if *index < 0 || index >= count || *count > MAX_STRUCTURED_APPEND_COUNT {
+            return None;
+        }
+        Some(Part {
+            message: (format, count.to_string()),
+            index: *index as usize,
+            count: Some(*count as usize),
+        })
+    }
+
+    /// The result of a whole message, like `QRCodeMultiReader` joins structured append symbols
+    fn join(parts: MessageParts, format: BarcodeFormat) -> RXingResult {
+        multi::join_parts(parts.results.values(), format)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BarcodeFormat, MultiFormatWriter, Writer};
+
+    use super::StreamDecoder;
+
+    /// A frame of 320x240 pixels with a QR code of 3 pixel modules at (`left`, `top`)
+    fn frame(contents: &str, left: u32, top: u32) -> Vec<u8> {
+        let matrix = MultiFormatWriter
+            .encode(contents, &BarcodeFormat::QR_CODE, 0, 0)
+            .expect("encode");
+        let mut luma = vec![255u8; 320 * 240];
+        for y in 0..matrix.getHeight() * 3 {
+            for x in 0..matrix.getWidth() * 3 {
+                if matrix.get(x / 3, y / 3) {
+                    luma[((top + y) * 320 + left + x) as usize] = 0;
+                }
+            }
+        }
+        luma
+    }
+
+    #[test]
+    fn test_priors() {
+        let mut decoder = StreamDecoder::new().with_full_scan_interval(3);
+        let first = decoder
+            .decode_frame(&frame("moving", 40, 30), 320, 240)
+            .expect("decode");
+        assert_eq!(1, first.len());
+        assert_eq!("moving", first[0].getText());
+        assert_eq!(1, decoder.get_priors().len());
+        assert_eq!(0, decoder.frames_since_full_scan);
+
+        // the symbol moved a little and is found again where it was, without a full scan
+        let second = decoder
+            .decode_frame(&frame("moving", 48, 36), 320, 240)
+            .expect("decode");
+        assert_eq!(1, second.len());
+        assert_eq!(1, decoder.frames_since_full_scan);
+        let (left, top) = (
+            second[0]
+                .getPoints()
+                .iter()
+                .map(|p| p.x)
+                .fold(f32::MAX, f32::min),
+            second[0]
+                .getPoints()
+                .iter()
+                .map(|p| p.y)
+                .fold(f32::MAX, f32::min),
+        );
+        assert!(left > 48.0 && left < 80.0, "{left}");
+        assert!(top > 36.0 && top < 68.0, "{top}");
+
+        // and it is searched for in the whole frame once it jumped away
+        let third = decoder
+            .decode_frame(&frame("moving", 200, 120), 320, 240)
+            .expect("decode");
+        assert_eq!(1, third.len());
+        assert_eq!(0, decoder.frames_since_full_scan);
+
+        assert!(decoder.decode_frame(&[0; 10], 320, 240).is_err());
+    }
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn test_structured_append_across_frames() {
+        let expected = std::fs::read_to_string("test_resources/blackbox/cpp/qrcode-7/01.txt")
+            .expect("read expected text");
+        let mut decoder = StreamDecoder::new();
+        let mut messages = Vec::new();
+        for part in 1..=4 {
+            let image = image::open(format!(
+                "test_resources/blackbox/cpp/qrcode-7/01-0{part}.png"
+            ))
+            .expect("open image")
+            .to_luma8();
+            let (width, height) = image.dimensions();
+            let results = decoder
+                .decode_frame(image.as_raw(), width, height)
+                .expect("decode");
+            assert!(!results.is_empty(), "part {part}");
+            messages.extend(results.into_iter().filter(|r| r.getPoints().is_empty()));
+        }
+        assert_eq!(1, messages.len());
+        assert_eq!(expected, messages[0].getText());
+    }
+}
@@ -0,0 +1,263 @@
+/*
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/**
+ * How closely a symbol must follow its specification to be read, set with the `Strictness`
+ * decode hint. It applies to the linear readers, whose quiet zones and bar widths are
+ * otherwise judged by heuristics.
+ *
+ * Check characters that a specification makes mandatory, those of Code 93, Code 128,
+ * EAN/UPC and Telepen and the error correction of 2D symbols, are checked at every level.
+ * Optional check digits follow the `CheckDigitPolicy` hints.
+ */
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Strictness {
+    /**
+     * Quiet zones as wide as the ISO specifications require, 10 modules for Code 39, Codabar,
+     * Code 128 and ITF and 7 for EAN/UPC, and within the image. Bar and space widths must be
+     * within three quarters of the standard tolerance (Code 128, Codabar, ITF).
+     */
+    Strict,
+    /**
+     * The behavior without the hint. Code 39, Codabar and Code 128 need a quiet zone half as
+     * wide as their start and stop patterns, ITF one of 10 modules and EAN/UPC one of 3; except
+     * for EAN/UPC it may be cut off by the edge of the image.
+     */
+    #[default]
+    Standard,
+    /**
+     * Quiet zones half as wide as the standard ones and bar and space widths within a quarter
+     * more than the standard tolerance, for worn, wrinkled or tightly cropped labels at the
+     * cost of more misreads. EAN/UPC quiet zones stay standard, as their short guard patterns
+     * are found inside other symbols and text.
+     */
+    Permissive,
+}
+
+impl Strictness {
+    /**
+     * The width in pixels of the quiet zone a reader requires.
+     *
+     * @param standard the width it requires at the standard level
+     * @param module the width of a module, the narrowest bar or space, in pixels
+     * @param modules the number of modules of quiet zone the specification requires
+     */
+    pub(crate) fn quiet_zone(&self, standard: usize, module: f32, modules: u32) -> usize {
+        match self {
+            Strictness::Strict => standard.max((module * modules as f32).round() as usize),
+            Strictness::Standard => standard,
+            Strictness::Permissive => standard / 2,
+        }
+    }
+
+    /// Whether a quiet zone cut off by the edge of the image is accepted
+    pub(crate) fn allows_cut_off_quiet_zone(&self) -> bool {
+        *self != Strictness::Strict
+    }
+
+    /// A tolerance on bar and space widths, scaled from the one used at the standard level
+    pub(crate) fn tolerance(&self, standard: f32) -> f32 {
+        match self {
+            Strictness::Strict => standard * 0.75,
+            Strictness::Standard => standard,
+            Strictness::Permissive => standard * 1.25,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        common::BitArray,
+        common::Result,
+        oned::{CodaBarReader, Code128Reader, Code39Reader, EAN13Reader, ITFReader, OneDReader},
+        BarcodeFormat, DecodeHints, DecodeWarning, EncodeHints, MultiFormatWriter, RXingResult,
+        Writer,
+    };
+
+    use super::Strictness::{self, *};
+
+    /// The widths of the white and black runs of a symbol with `margin` modules of quiet zone,
+    /// beyond which there are other marks unless the margin is 0
+    fn runs(format: &BarcodeFormat, contents: &str, margin: u32) -> Vec<u32> {
+        let hints = EncodeHints {
+            Margin: Some((2 * margin).to_string()),
+            ..Default::default()
+        };
+        let matrix = MultiFormatWriter
+            .encode_with_hints(contents, format, 0, 0, &hints)
+            .expect("encode");
+        let mut runs = vec![0];
+        // starting with white, even if the quiet zone is empty
+        let mut black = false;
+        for x in 0..matrix.getWidth() {
+            if matrix.get(x, 0) != black {
+                runs.push(0);
+                black = !black;
+            }
+            *runs.last_mut().unwrap() += 1;
+        }
+        if margin > 0 {
+            runs.splice(0..0, [10, 2]);
+            runs.extend([2, 10]);
+        }
+        runs
+    }
+
+    fn read(format: &BarcodeFormat, runs: &[u32], strictness: Strictness) -> Result<RXingResult> {
+        let mut row = BitArray::new();
+        for (i, run) in runs.iter().enumerate() {
+            for _ in 0..*run {
+                row.appendBit(i % 2 == 1);
+            }
+        }
+        let hints = DecodeHints {
+            Strictness: Some(strictness),
+            ..Default::default()
+        };
+        match format {
+            BarcodeFormat::CODE_128 => Code128Reader.decode_row(0, &row, &hints),
+            BarcodeFormat::CODE_39 => Code39Reader::default().decode_row(0, &row, &hints),
+            BarcodeFormat::CODABAR => CodaBarReader::default().decode_row(0, &row, &hints),
+            BarcodeFormat::ITF => ITFReader::default().decode_row(0, &row, &hints),
+            _ => EAN13Reader.decode_row(0, &row, &hints),
+        }
+    }
+
+    fn reads(format: &BarcodeFormat, runs: &[u32], strictness: Strictness) -> bool {
+        read(format, runs, strictness).is_ok()
+    }
+
+    #[test]
+    fn test_quiet_zones() {
+        // a margin in modules, and whether a symbol with it is read at a level
+        type Margin = (u32, Strictness, bool);
+        let cases: [(BarcodeFormat, &str, &[Margin]); 5] = [
+            (
+                // half the 11 modules of the start pattern by default, or cut off by the edge
+                BarcodeFormat::CODE_128,
+                "STRICT",
+                &[
+                    (0, Standard, true),
+                    (3, Standard, false),
+                    (3, Permissive, true),
+                    (7, Standard, true),
+                    (7, Strict, false),
+                    (10, Strict, true),
+                    (0, Strict, false),
+                ],
+            ),
+            (
+                BarcodeFormat::CODE_39,
+                "STRICT",
+                &[
+                    (0, Standard, true),
+                    (3, Standard, false),
+                    (3, Permissive, true),
+                    (7, Standard, true),
+                    (7, Strict, false),
+                    (10, Strict, true),
+                    (0, Strict, false),
+                ],
+            ),
+            (
+                BarcodeFormat::CODABAR,
+                "A123456B",
+                &[
+                    (3, Standard, false),
+                    (3, Permissive, true),
+                    (7, Standard, true),
+                    (7, Strict, false),
+                    (10, Strict, true),
+                ],
+            ),
+            (
+                // 10 modules by default, or cut off by the edge
+                BarcodeFormat::ITF,
+                "12345678",
+                &[
+                    (0, Standard, true),
+                    (7, Standard, false),
+                    (7, Permissive, true),
+                    (10, Strict, true),
+                    (0, Strict, false),
+                ],
+            ),
+            (
+                // 3 modules, or 7 if strict
+                BarcodeFormat::EAN_13,
+                "5901234123457",
+                &[(4, Standard, true), (4, Strict, false), (7, Strict, true)],
+            ),
+        ];
+        for (format, contents, expected) in cases {
+            for &(margin, strictness, readable) in expected {
+                let runs = runs(&format, contents, margin);
+                assert_eq!(
+                    readable,
+                    reads(&format, &runs, strictness),
+                    "{format} with a margin of {margin} at {strictness:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_quiet_zone_warnings() {
+        // a margin in modules that is read, but too narrow to be read strictly
+        let cases = [
+            (BarcodeFormat::CODE_128, "STRICT", 7),
+            (BarcodeFormat::CODE_39, "STRICT", 7),
+            (BarcodeFormat::CODABAR, "A123456B", 7),
+            (BarcodeFormat::ITF, "12345678", 0),
+            (BarcodeFormat::EAN_13, "5901234123457", 4),
+        ];
+        for (format, contents, narrow) in cases {
+            for (margin, warned) in [(narrow, true), (10, false)] {
+                let result = read(&format, &runs(&format, contents, margin), Standard)
+                    .unwrap_or_else(|_| panic!("{format} with a margin of {margin}"));
+                assert_eq!(
+                    warned,
+                    result
+                        .getWarnings()
+                        .contains(&DecodeWarning::QuietZoneViolation),
+                    "{format} with a margin of {margin}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_tolerances() {
+        let format = BarcodeFormat::CODE_128;
+        let symbol: Vec<u32> = runs(&format, "STRICT", 10)
+            .iter()
+            .map(|run| run * 4)
+            .collect();
+        // the first bar of the first data character widened by 3 pixels, then by a module
+        let widened = |pixels| {
+            let mut runs = symbol.clone();
+            runs[9] += pixels;
+            runs
+        };
+        assert!(reads(&format, &widened(3), Standard));
+        assert!(!reads(&format, &widened(3), Strict));
+        assert!(!reads(&format, &widened(4), Standard));
+        assert!(reads(&format, &widened(4), Permissive));
+    }
+}
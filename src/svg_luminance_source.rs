@@ -57,42 +57,103 @@ impl LuminanceSource for SVGLuminanceSource {
     }
 }
 
+/// The resolution SVG user units are defined at, one pixel per CSS `px`
+pub const SVG_DEFAULT_DPI: f32 = 96.0;
+
 impl SVGLuminanceSource {
     pub fn new(svg_data: &[u8]) -> Result<Self> {
-        // Load the SVG file
-        let Ok(tree) = resvg::usvg::Tree::from_data(svg_data, &Options::default()) else {
-            return Err(Exceptions::format_with(format!(
-                "could not parse svg data: {}",
-                "err"
+        Self::with_dpi(svg_data, SVG_DEFAULT_DPI)
+    }
+
+    /**
+     * Rasterizes the SVG at `dpi` pixels per inch. Symbols drawn with few user units per
+     * module, or sized in physical units such as `mm`, decode more reliably at a higher
+     * resolution than the default 96 DPI.
+     *
+     * # Arguments
+     * * `svg_data` - The SVG document
+     * * `dpi` - The resolution to rasterize at, see `SVG_DEFAULT_DPI`
+     */
+    pub fn with_dpi(svg_data: &[u8], dpi: f32) -> Result<Self> {
+        if !(dpi.is_finite() && dpi > 0.0) {
+            return Err(Exceptions::illegal_argument_with(format!(
+                "cannot rasterize at {dpi} dpi"
             )));
-        };
+        }
 
-        let Some(mut pixmap) =
-            resvg::tiny_skia::Pixmap::new(tree.size().width() as u32, tree.size().height() as u32)
-        else {
+        // Load the SVG file
+        let tree = resvg::usvg::Tree::from_data(svg_data, &Options::default())
+            .map_err(|e| Exceptions::format_with(format!("could not parse svg data: {e}")))?;
+
+        let scale = dpi / SVG_DEFAULT_DPI;
+        let width = (tree.size().width() * scale).ceil() as u32;
+        let height = (tree.size().height() * scale).ceil() as u32;
+        let Some(mut pixmap) = resvg::tiny_skia::Pixmap::new(width, height) else {
             return Err(Exceptions::format_with("could not create pixmap"));
         };
 
         resvg::render(
             &tree,
-            resvg::tiny_skia::Transform::default(),
+            resvg::tiny_skia::Transform::from_scale(scale, scale),
             &mut pixmap.as_mut(),
         );
 
-        let Some(buffer) = RgbaImage::from_raw(
-            tree.size().width() as u32,
-            tree.size().height() as u32,
-            pixmap.data().to_vec(),
-        ) else {
+        let Some(buffer) = RgbaImage::from_raw(width, height, pixmap.data().to_vec()) else {
             return Err(Exceptions::format_with("could not create image buffer"));
         };
 
-        // let Ok(image) = image::load_from_memory_with_format(pixmap.data(), image::ImageFormat::Bmp)  else {
-        //     return Err(Exceptions::format("could not generate image"));
-        // };
-
         Ok(Self(BufferedImageLuminanceSource::new(DynamicImage::from(
             buffer,
         ))))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        BarcodeFormat, BinaryBitmap, EncodeHintValue, EncodeHints, MultiFormatReader,
+        MultiFormatWriter, Reader, Writer,
+    };
+
+    // A QR code drawn as one rect per dark module, 0.5mm to the module
+    fn qr_svg(contents: &str) -> (u32, Vec<u8>) {
+        let hints = EncodeHints::default().with(EncodeHintValue::Margin("4".to_owned()));
+        let symbol = MultiFormatWriter
+            .encode_with_hints(contents, &BarcodeFormat::QR_CODE, 0, 0, &hints)
+            .unwrap();
+        let side = symbol.getWidth();
+        let mut svg = format!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}mm" height="{0}mm" viewBox="0 0 {side} {side}"><rect width="{side}" height="{side}" fill="white"/>"#,
+            side as f32 / 2.0
+        );
+        for y in 0..side {
+            for x in 0..side {
+                if symbol.get(x, y) {
+                    svg.push_str(&format!(r#"<rect x="{x}" y="{y}" width="1" height="1"/>"#));
+                }
+            }
+        }
+        svg.push_str("</svg>");
+        (side, svg.into_bytes())
+    }
+
+    #[test]
+    fn test_dpi() {
+        let (side, svg) = qr_svg("rasterized at 300 dpi");
+
+        // half a millimeter is about 1.9 pixels at 96 dpi and 5.9 at 300 dpi
+        let default = SVGLuminanceSource::new(&svg).unwrap();
+        let mm = side as f32 / 2.0;
+        assert_eq!((mm * 96.0 / 25.4).ceil() as usize, default.get_width());
+        let source = SVGLuminanceSource::with_dpi(&svg, 300.0).unwrap();
+        assert_eq!((mm * 300.0 / 25.4).ceil() as usize, source.get_width());
+
+        let mut bitmap = BinaryBitmap::new(crate::common::HybridBinarizer::new(source));
+        let result = MultiFormatReader::default().decode(&mut bitmap).unwrap();
+        assert_eq!("rasterized at 300 dpi", result.getText());
+
+        assert!(SVGLuminanceSource::with_dpi(&svg, 0.0).is_err());
+        assert!(SVGLuminanceSource::with_dpi(b"<svg", 300.0).is_err());
+    }
+}
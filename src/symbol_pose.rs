@@ -0,0 +1,209 @@
+/*
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    common::{PerspectiveTransform, SamplingGrid},
+    point, RXingResult, RXingResultMetadataType, RXingResultMetadataValue,
+};
+
+/**
+ * Where a 2D symbol lies in the image, found in the `POSE` metadata of a result: how far it is
+ * turned, how large its modules are and the transform from its modules to image pixels. An
+ * application can draw an aligned overlay with the transform and estimate the distance to the
+ * symbol from the size of its modules.
+ *
+ * The pose is taken from the sampling grid of the symbol, so readers that report no
+ * `SAMPLING_GRID` report no pose either.
+ */
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SymbolPose {
+    /**
+     * The degrees the top edge of the symbol is turned clockwise from the rows of the image,
+     * in the range [0,360), measured at the center of the symbol
+     */
+    pub rotation: f32,
+    /// The module pitch in pixels at the center of the symbol, see `SamplingGrid::module_size`
+    pub module_size: f32,
+    /**
+     * The transform from module coordinates to image pixels: module `(x, y)` covers the square
+     * from `(x, y)` to `(x + 1, y + 1)`. Where the symbol was sampled in several regions, the
+     * transform of the region at its center.
+     */
+    pub transform: PerspectiveTransform,
+}
+
+impl Eq for SymbolPose {}
+
+impl SymbolPose {
+    pub fn from_grid(grid: &SamplingGrid) -> Self {
+        let (x, y) = (grid.width() as f32 / 2.0, grid.height() as f32 / 2.0);
+        let center = grid.module_to_image(point(x, y));
+        let right = grid.module_to_image(point(x + 1.0, y));
+        let rotation = (right.y - center.y)
+            .atan2(right.x - center.x)
+            .to_degrees()
+            .rem_euclid(360.0);
+
+        let transform = grid
+            .regions()
+            .iter()
+            .find(|r| x >= r.p0.x && x < r.p1.x && y >= r.p0.y && y < r.p1.y)
+            .or(grid.regions().first())
+            .map(|region| region.transform)
+            .unwrap_or_default();
+
+        Self {
+            // 360 where a rotation just short of it rounds up
+            rotation: if rotation >= 360.0 { 0.0 } else { rotation },
+            module_size: grid.module_size(),
+            transform,
+        }
+    }
+
+    /// The pose of a result from its `SAMPLING_GRID` metadata, `None` if it has none
+    pub fn from_result(result: &RXingResult) -> Option<Self> {
+        match result
+            .getRXingResultMetadata()
+            .get(&RXingResultMetadataType::SAMPLING_GRID)
+        {
+            Some(RXingResultMetadataValue::SamplingGrid(grid)) => Some(Self::from_grid(grid)),
+            _ => None,
+        }
+    }
+
+    /**
+     * The transform as a 3×3 matrix, row by row: a module point `(x, y)` maps to the image point
+     * `(u / w, v / w)`, where `(u, v, w)` is the matrix times `(x, y, 1)`.
+     */
+    pub fn matrix(&self) -> [[f32; 3]; 3] {
+        let [a11, a12, a13, a21, a22, a23, a31, a32, a33] = self.transform.coefficients();
+        [[a11, a21, a31], [a12, a22, a32], [a13, a23, a33]]
+    }
+}
+
+/// Adds `POSE` metadata to `result` if it has a sampling grid, replacing any it had
+pub(crate) fn put_pose(result: &mut RXingResult) {
+    if let Some(pose) = SymbolPose::from_result(result) {
+        result.putMetadata(
+            RXingResultMetadataType::POSE,
+            RXingResultMetadataValue::Pose(pose),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::{
+        common::PerspectiveTransform,
+        common::{Quadrilateral, SamplingGrid},
+        helpers, point, BarcodeFormat, DecodeHints, MultiFormatWriter, RXingResultMetadataType,
+        RXingResultMetadataValue, Writer,
+    };
+
+    use super::SymbolPose;
+
+    #[test]
+    fn test_from_grid() {
+        let flat =
+            SymbolPose::from_grid(&SamplingGrid::axis_aligned(21, 21, 10.0, 20.0, 4.0).unwrap());
+        assert_eq!(0.0, flat.rotation);
+        assert!((flat.module_size - 4.0).abs() < 0.01);
+        let matrix = flat.matrix();
+        for (row, expected) in
+            matrix
+                .iter()
+                .zip([[4.0, 0.0, 10.0], [0.0, 4.0, 20.0], [0.0, 0.0, 1.0]])
+        {
+            for (value, expected) in row.iter().zip(expected) {
+                assert!((value / matrix[2][2] - expected).abs() < 1e-3, "{matrix:?}");
+            }
+        }
+
+        // turned a quarter clockwise: the top edge runs down the image
+        let turned = SamplingGrid::with_transform(
+            21,
+            21,
+            PerspectiveTransform::quadrilateralToQuadrilateral(
+                Quadrilateral([
+                    point(0.0, 0.0),
+                    point(21.0, 0.0),
+                    point(21.0, 21.0),
+                    point(0.0, 21.0),
+                ]),
+                Quadrilateral([
+                    point(100.0, 0.0),
+                    point(100.0, 42.0),
+                    point(58.0, 42.0),
+                    point(58.0, 0.0),
+                ]),
+            )
+            .unwrap(),
+        );
+        let pose = SymbolPose::from_grid(&turned);
+        assert!((pose.rotation - 90.0).abs() < 0.01, "{}", pose.rotation);
+        assert!((pose.module_size - 2.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_decode_reports_pose() {
+        for format in [
+            BarcodeFormat::QR_CODE,
+            BarcodeFormat::DATA_MATRIX,
+            BarcodeFormat::AZTEC,
+        ] {
+            let matrix = MultiFormatWriter.encode("pose", &format, 200, 200).unwrap();
+            // the symbol turned a quarter counterclockwise
+            let (width, height) = (matrix.getHeight(), matrix.getWidth());
+            let mut luma = Vec::new();
+            for y in 0..height {
+                for x in 0..width {
+                    luma.push(if matrix.get(matrix.getWidth() - 1 - y, x) {
+                        0
+                    } else {
+                        255
+                    });
+                }
+            }
+            let result = helpers::detect_in_luma_with_hints(
+                luma,
+                width,
+                height,
+                None,
+                &mut DecodeHints {
+                    PossibleFormats: Some(HashSet::from([format.clone()])),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+            let Some(RXingResultMetadataValue::Pose(pose)) = result
+                .getRXingResultMetadata()
+                .get(&RXingResultMetadataType::POSE)
+            else {
+                panic!("no pose for {format}");
+            };
+            assert!(
+                (pose.rotation - 270.0).abs() < 2.0,
+                "{format}: {}",
+                pose.rotation
+            );
+            assert!(pose.module_size > 2.0, "{format}: {}", pose.module_size);
+        }
+    }
+}
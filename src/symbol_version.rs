@@ -0,0 +1,145 @@
+/*
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/**
+ * The version and structure of a 2D symbol as read from its modules, found in the
+ * `SYMBOL_VERSION` metadata of a result. Auditing tools can check it against what was printed,
+ * and against the size of the `SAMPLING_GRID`. The error correction level is in
+ * `ERROR_CORRECTION_LEVEL`.
+ */
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SymbolVersion {
+    /**
+     * A QR Code of `version` 1 to 40, or 1 to 14 for Model 1, masked with data mask `mask` from
+     * 0 to 7. `model` is 1 for the original symbols without alignment patterns, 2 for others.
+     */
+    QRCode { version: u32, mask: u8, model: u8 },
+    /// A Micro QR Code of version M1 to M4, masked with data mask `mask` from 0 to 3
+    MicroQRCode { version: u32, mask: u8 },
+    /// A rectangular Micro QR Code of `version` 1 to 32, from R7x43 to R17x139
+    RectangularMicroQRCode { version: u32 },
+    /**
+     * A Data Matrix symbol of `rows` by `columns` modules, its data split into regions of
+     * `data_region_rows` by `data_region_columns` modules each
+     */
+    DataMatrix {
+        rows: u32,
+        columns: u32,
+        data_region_rows: u32,
+        data_region_columns: u32,
+    },
+}
+
+impl SymbolVersion {
+    /// The number of data regions across and down a Data Matrix symbol
+    pub fn data_regions(&self) -> Option<(u32, u32)> {
+        match *self {
+            SymbolVersion::DataMatrix {
+                rows,
+                columns,
+                data_region_rows,
+                data_region_columns,
+            } => Some((
+                // each region has a finder and timing pattern around it
+                columns / (data_region_columns + 2),
+                rows / (data_region_rows + 2),
+            )),
+            _ => None,
+        }
+    }
+
+    /// Whether the symbol is square, rather than a rectangular Data Matrix or Micro QR Code
+    pub fn is_square(&self) -> bool {
+        match *self {
+            SymbolVersion::DataMatrix { rows, columns, .. } => rows == columns,
+            SymbolVersion::RectangularMicroQRCode { .. } => false,
+            _ => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        common::HybridBinarizer, datamatrix::encoder::SymbolShapeHint, BarcodeFormat, BinaryBitmap,
+        EncodeHints, Luma8LuminanceSource, MultiFormatReader, MultiFormatWriter,
+        RXingResultMetadataType, RXingResultMetadataValue, Reader, SymbolVersion, Writer,
+    };
+
+    fn read_version(format: BarcodeFormat, contents: &str, hints: &EncodeHints) -> SymbolVersion {
+        let matrix = MultiFormatWriter
+            .encode_with_hints(contents, &format, 0, 0, hints)
+            .expect("encode");
+        let (module, quiet_zone) = (4, 16);
+        let width = matrix.getWidth() * module + 2 * quiet_zone;
+        let height = matrix.getHeight() * module + 2 * quiet_zone;
+        let mut luma = vec![255u8; (width * height) as usize];
+        for y in 0..matrix.getHeight() * module {
+            for x in 0..matrix.getWidth() * module {
+                if matrix.get(x / module, y / module) {
+                    luma[((y + quiet_zone) * width + x + quiet_zone) as usize] = 0;
+                }
+            }
+        }
+        let mut image = BinaryBitmap::new(HybridBinarizer::new(Luma8LuminanceSource::new(
+            luma, width, height,
+        )));
+        let result = MultiFormatReader::default()
+            .decode(&mut image)
+            .expect("decode");
+        match result
+            .getRXingResultMetadata()
+            .get(&RXingResultMetadataType::SYMBOL_VERSION)
+        {
+            Some(RXingResultMetadataValue::SymbolVersion(version)) => *version,
+            other => panic!("no symbol version: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_qr_code_version() {
+        let hints = EncodeHints {
+            QrVersion: Some("3".to_owned()),
+            QrMaskPattern: Some("5".to_owned()),
+            ..Default::default()
+        };
+        assert_eq!(
+            read_version(BarcodeFormat::QR_CODE, "version three", &hints),
+            SymbolVersion::QRCode {
+                version: 3,
+                mask: 5,
+                model: 2
+            }
+        );
+    }
+
+    #[test]
+    fn test_data_matrix_regions() {
+        let hints = EncodeHints {
+            DataMatrixShape: Some(SymbolShapeHint::FORCE_RECTANGLE),
+            ..Default::default()
+        };
+        let version = read_version(
+            BarcodeFormat::DATA_MATRIX,
+            "a rectangle with two data regions",
+            &hints,
+        );
+        assert!(!version.is_square());
+        assert_eq!(version.data_regions(), Some((2, 1)), "{version:?}");
+    }
+}
@@ -0,0 +1,53 @@
+/*
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Symbols rendered as images for the unit tests
+
+use crate::{
+    common::{BitMatrix, HybridBinarizer},
+    BinaryBitmap, Luma8LuminanceSource,
+};
+
+/// Renders `symbol` at `scale` pixels to a module, set modules `dark` and the others `light`,
+/// returning the pixels with the width and height of the image
+pub(crate) fn scaled_luma(
+    symbol: &BitMatrix,
+    scale: u32,
+    dark: u8,
+    light: u8,
+) -> (Vec<u8>, u32, u32) {
+    let (width, height) = (symbol.getWidth() * scale, symbol.getHeight() * scale);
+    let luma = (0..height)
+        .flat_map(|y| (0..width).map(move |x| (x, y)))
+        .map(|(x, y)| {
+            if symbol.get(x / scale, y / scale) {
+                dark
+            } else {
+                light
+            }
+        })
+        .collect();
+    (luma, width, height)
+}
+
+/// `symbol` one pixel to a module, ready to decode
+pub(crate) fn luma_bitmap(
+    symbol: &BitMatrix,
+) -> BinaryBitmap<HybridBinarizer<Luma8LuminanceSource>> {
+    BinaryBitmap::new(HybridBinarizer::new(Luma8LuminanceSource::new(
+        symbol.to_luma8(),
+        symbol.getWidth(),
+        symbol.getHeight(),
+    )))
+}
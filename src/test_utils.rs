@@ -0,0 +1,738 @@
+//! Round-trip testing, enabled by the `test-utils` feature.
+//!
+//! A [`RoundTrip`] encodes contents, renders the symbol, distorts the rendering as a camera or
+//! a printer would and decodes it again, checking that the contents survive. Downstream crates
+//! use it to regression-test their hints, their build or a reader of their own against the
+//! same synthetic images this crate is tested with.
+//!
+//! ```
+//! use rxing::{test_utils::{Distortion, RoundTrip}, BarcodeFormat};
+//!
+//! let round_trip = RoundTrip::new(BarcodeFormat::QR_CODE, "https://example.com/round-trip")
+//!     .with_distortion(Distortion::Perspective([(0.1, 0.0), (0.0, 0.0), (0.0, 0.0), (0.1, 0.0)]))
+//!     .with_distortion(Distortion::Blur(1.0));
+//! round_trip.run()?;
+//!
+//! // every angle a symbol can be held at
+//! for (distortion, decoded) in round_trip.sweep(Distortion::rotations(30.0)) {
+//!     assert!(decoded.is_ok(), "failed at {distortion:?}");
+//! }
+//! # Ok::<(), rxing::Exceptions>(())
+//! ```
+//!
+//! A [`PayloadGenerator`] makes contents each format can encode out of arbitrary bytes, for
+//! property tests and fuzzers to feed [`round_trip`] in search of contents that do not read back
+//! as they were written. With the `arbitrary` feature a [`FuzzCase`] draws the format, the
+//! contents and the distortions of a round trip from a fuzzer's input.
+//!
+//! A [`BlackBoxSuite`] measures a reader against a directory of real images, each with its
+//! expected contents beside it, the way the ZXing sample image suites are run. Its
+//! [`AccuracyReport`] counts the images read, misread and missed at each rotation, so a corpus
+//! of one's own labels can be checked against each release.
+//!
+//! ```no_run
+//! use rxing::{test_utils::BlackBoxSuite, BarcodeFormat, MultiFormatReader};
+//!
+//! let report = BlackBoxSuite::new("labels/qr", BarcodeFormat::QR_CODE)
+//!     .add_rotation(0.0, 20, 22)
+//!     .add_rotation(90.0, 18, 22)
+//!     .run(&mut MultiFormatReader::default())?;
+//! assert!(report.passed(), "{:?}", report.shortfalls());
+//! # Ok::<(), rxing::Exceptions>(())
+//! ```
+
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+};
+
+use image::{imageops, GrayImage, Luma};
+use imageproc::geometric_transformations::{self, Interpolation, Projection};
+
+use crate::{
+    common::{HybridBinarizer, Result},
+    BarcodeFormat, BinaryBitmap, DecodeHints, EncodeHints, Exceptions, Luma8LuminanceSource,
+    MultiFormatReader, MultiFormatWriter, RXingResult, Reader, Writer,
+};
+
+mod payload;
+pub use payload::*;
+
+#[cfg(feature = "arbitrary")]
+mod fuzz_case;
+#[cfg(feature = "arbitrary")]
+pub use fuzz_case::*;
+
+const WHITE: Luma<u8> = Luma([u8::MAX]);
+
+/// A change to a rendered symbol, applied in the order they were added to a [`RoundTrip`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Distortion {
+    /// Turns the image clockwise about its center by the angle in degrees. The canvas grows
+    /// so no part of the symbol is cut off
+    Rotate(f32),
+    /// Blurs with a Gaussian of the standard deviation, in pixels
+    Blur(f32),
+    /// Adds Gaussian noise of the standard deviation, in luma levels. The seed makes runs
+    /// repeatable
+    Noise { stddev: f32, seed: u64 },
+    /// Moves the corners of the image (top left, top right, bottom right, bottom left) toward
+    /// its center by fractions of its width and height, as a symbol seen at an angle
+    Perspective([(f32, f32); 4]),
+    /// Resizes by the factor, as a symbol printed smaller or seen from further away
+    Scale(f32),
+    /// Maps black and white to the luma given, as faded print or poor light
+    Contrast { dark: u8, light: u8 },
+}
+
+impl Distortion {
+    /// Rotations every `step` degrees, from 0 up to a full turn
+    pub fn rotations(step: f32) -> Vec<Distortion> {
+        let steps = (360.0 / step).ceil() as usize;
+        (0..steps)
+            .map(|i| Distortion::Rotate(i as f32 * step))
+            .collect()
+    }
+
+    pub fn apply(&self, image: &GrayImage) -> GrayImage {
+        match *self {
+            Distortion::Rotate(degrees) => rotate(image, degrees),
+            Distortion::Blur(sigma) => imageproc::filter::gaussian_blur_f32(image, sigma),
+            Distortion::Noise { stddev, seed } => {
+                imageproc::noise::gaussian_noise(image, 0.0, stddev as f64, seed)
+            }
+            Distortion::Perspective(insets) => perspective(image, insets),
+            Distortion::Scale(factor) => {
+                let width = (image.width() as f32 * factor).round().max(1.0) as u32;
+                let height = (image.height() as f32 * factor).round().max(1.0) as u32;
+                imageops::resize(image, width, height, imageops::FilterType::Triangle)
+            }
+            Distortion::Contrast { dark, light } => {
+                let range = light as f32 - dark as f32;
+                let mut image = image.clone();
+                for Luma([luma]) in image.pixels_mut() {
+                    *luma = (dark as f32 + *luma as f32 / 255.0 * range).round() as u8;
+                }
+                image
+            }
+        }
+    }
+}
+
+fn rotate(image: &GrayImage, degrees: f32) -> GrayImage {
+    let (width, height) = image.dimensions();
+    let side = ((width * width + height * height) as f32).sqrt().ceil() as u32;
+    let mut canvas = GrayImage::from_pixel(side, side, WHITE);
+    let (left, top) = ((side - width) / 2, (side - height) / 2);
+    imageops::replace(&mut canvas, image, left as i64, top as i64);
+    geometric_transformations::rotate_about_center(
+        &canvas,
+        degrees.to_radians(),
+        Interpolation::Bilinear,
+        WHITE,
+    )
+}
+
+fn perspective(image: &GrayImage, insets: [(f32, f32); 4]) -> GrayImage {
+    let (width, height) = (image.width() as f32, image.height() as f32);
+    let corners = [(0.0, 0.0), (width, 0.0), (width, height), (0.0, height)];
+    let mut moved = corners;
+    for ((x, y), (inset_x, inset_y)) in moved.iter_mut().zip(insets) {
+        // toward the center, whichever side of it the corner is on
+        *x += (width / 2.0 - *x).signum() * inset_x * width;
+        *y += (height / 2.0 - *y).signum() * inset_y * height;
+    }
+    match Projection::from_control_points(corners, moved) {
+        Some(projection) => {
+            geometric_transformations::warp(image, &projection, Interpolation::Bilinear, WHITE)
+        }
+        // corners moved onto each other leave nothing to see
+        None => GrayImage::from_pixel(image.width(), image.height(), WHITE),
+    }
+}
+
+/**
+ * Encodes contents, renders and distorts the symbol and decodes it again.
+ *
+ * The symbol is rendered black on white at `module_size` pixels per module, with `margin`
+ * modules of quiet zone on each side, 10 for linear symbols and 4 for others by default.
+ * Linear symbols are `bar_height` pixels high.
+ */
+#[derive(Debug, Clone)]
+pub struct RoundTrip {
+    pub format: BarcodeFormat,
+    pub contents: String,
+    pub encode_hints: EncodeHints,
+    pub module_size: u32,
+    pub margin: Option<u32>,
+    pub bar_height: u32,
+    pub distortions: Vec<Distortion>,
+}
+
+impl RoundTrip {
+    pub fn new(format: BarcodeFormat, contents: &str) -> Self {
+        Self {
+            format,
+            contents: contents.to_owned(),
+            encode_hints: EncodeHints::default(),
+            module_size: 4,
+            margin: None,
+            bar_height: 60,
+            distortions: Vec::new(),
+        }
+    }
+
+    pub fn with_distortion(mut self, distortion: Distortion) -> Self {
+        self.distortions.push(distortion);
+        self
+    }
+
+    /// The symbol as rendered, before any distortion
+    pub fn render_symbol(&self) -> Result<GrayImage> {
+        let linear = self.format.is_1d();
+        let margin = self.margin.unwrap_or(if linear { 10 } else { 4 });
+        // the margin of a linear symbol is split over both sides
+        let margin = if linear { 2 * margin } else { margin };
+        let hints = EncodeHints {
+            Margin: Some(margin.to_string()),
+            ..self.encode_hints.clone()
+        };
+        let symbol =
+            MultiFormatWriter.encode_with_hints(&self.contents, &self.format, 0, 0, &hints)?;
+
+        let scale = self.module_size.max(1);
+        let height = if linear {
+            self.bar_height.max(1)
+        } else {
+            symbol.getHeight() * scale
+        };
+        let image = GrayImage::from_fn(symbol.getWidth() * scale, height, |x, y| {
+            let row = if linear { 0 } else { y / scale };
+            if symbol.get(x / scale, row) {
+                Luma([0])
+            } else {
+                WHITE
+            }
+        });
+        Ok(image)
+    }
+
+    /// The symbol as rendered and distorted
+    pub fn render(&self) -> Result<GrayImage> {
+        let symbol = self.render_symbol()?;
+        Ok(self
+            .distortions
+            .iter()
+            .fold(symbol, |image, distortion| distortion.apply(&image)))
+    }
+
+    /// The rendered, distorted symbol as a bitmap to decode
+    pub fn bitmap(&self) -> Result<BinaryBitmap<HybridBinarizer<Luma8LuminanceSource>>> {
+        let image = self.render()?;
+        let (width, height) = image.dimensions();
+        let source = Luma8LuminanceSource::new(image.into_raw(), width, height);
+        Ok(BinaryBitmap::new(HybridBinarizer::new(source)))
+    }
+
+    /**
+     * Decodes the rendered, distorted symbol with a `MultiFormatReader` looking for the format
+     * of the round trip, trying harder.
+     *
+     * # Returns
+     * The result, or an error if decoding failed or decoded other contents or another format
+     */
+    pub fn run(&self) -> Result<RXingResult> {
+        let hints = DecodeHints {
+            PossibleFormats: Some(HashSet::from([self.format.clone()])),
+            TryHarder: Some(true),
+            ..Default::default()
+        };
+        self.run_with_reader(&mut MultiFormatReader::default(), &hints)
+    }
+
+    /// Like `run`, decoding with `reader` and `hints`
+    pub fn run_with_reader<R: Reader>(
+        &self,
+        reader: &mut R,
+        hints: &DecodeHints,
+    ) -> Result<RXingResult> {
+        let result = reader.decode_with_hints(&mut self.bitmap()?, hints)?;
+        if *result.getBarcodeFormat() != self.format {
+            return Err(Exceptions::illegal_state_with(format!(
+                "decoded a {} symbol instead of {}",
+                result.getBarcodeFormat(),
+                self.format
+            )));
+        }
+        if result.getText() != self.contents {
+            return Err(Exceptions::illegal_state_with(format!(
+                "decoded '{}' instead of '{}'",
+                result.getText(),
+                self.contents
+            )));
+        }
+        Ok(result)
+    }
+
+    /// Runs the round trip once for each distortion, applied after the round trip's own
+    pub fn sweep<I>(&self, distortions: I) -> Vec<(Distortion, Result<RXingResult>)>
+    where
+        I: IntoIterator<Item = Distortion>,
+    {
+        distortions
+            .into_iter()
+            .map(|distortion| {
+                let decoded = self.clone().with_distortion(distortion).run();
+                (distortion, decoded)
+            })
+            .collect()
+    }
+}
+
+/**
+ * Encodes `contents` as `format`, applies the `distortions` in order and decodes the symbol, as
+ * `RoundTrip::run` does.
+ *
+ * # Returns
+ * The result, or an error: `IllegalStateException` if other contents or another format were
+ * decoded, which is what property tests and fuzzers hunt for, and any other if encoding or
+ * decoding failed
+ */
+pub fn round_trip(
+    format: BarcodeFormat,
+    contents: &str,
+    distortions: &[Distortion],
+) -> Result<RXingResult> {
+    RoundTrip {
+        distortions: distortions.to_vec(),
+        ..RoundTrip::new(format, contents)
+    }
+    .run()
+}
+
+/// How many images of a [`BlackBoxSuite`] must read at a rotation, and how many may misread
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RotationTest {
+    /// Clockwise, in degrees
+    pub rotation: f32,
+    pub must_pass: usize,
+    pub try_harder_must_pass: usize,
+    pub max_misreads: usize,
+    pub max_try_harder_misreads: usize,
+}
+
+/// How the images of a [`BlackBoxSuite`] read at one rotation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RotationStats {
+    pub images: usize,
+    /// Read with the expected format and contents
+    pub passed: usize,
+    /// Read, but with another format or other contents
+    pub misread: usize,
+    pub try_harder_passed: usize,
+    pub try_harder_misread: usize,
+}
+
+impl RotationStats {
+    /// The images nothing was read from
+    pub fn not_detected(&self) -> usize {
+        self.images - self.passed - self.misread
+    }
+
+    pub fn try_harder_not_detected(&self) -> usize {
+        self.images - self.try_harder_passed - self.try_harder_misread
+    }
+}
+
+/// An image a [`BlackBoxSuite`] could not read, or read wrong
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImageFailure {
+    pub path: PathBuf,
+    pub rotation: f32,
+    pub try_harder: bool,
+    /// Why it failed: the error decoding, or what was read instead
+    pub reason: String,
+}
+
+/// The statistics of running a [`BlackBoxSuite`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct AccuracyReport {
+    /// Each rotation tested, with how its images read
+    pub rotations: Vec<(RotationTest, RotationStats)>,
+    pub failures: Vec<ImageFailure>,
+}
+
+impl AccuracyReport {
+    /// Whether every rotation read as many images as it must, without too many misreads
+    pub fn passed(&self) -> bool {
+        self.shortfalls().is_empty()
+    }
+
+    /// A line for each requirement of a rotation that was not met
+    pub fn shortfalls(&self) -> Vec<String> {
+        let mut shortfalls = Vec::new();
+        for (test, stats) in &self.rotations {
+            let rotation = test.rotation;
+            let mut check = |label: &str, actual: usize, expected: usize, at_least: bool| {
+                if (at_least && actual < expected) || (!at_least && actual > expected) {
+                    let bound = if at_least { "at least" } else { "at most" };
+                    shortfalls.push(format!(
+                        "rotation {rotation}: {actual} {label}, {bound} {expected} expected"
+                    ));
+                }
+            };
+            check("passed", stats.passed, test.must_pass, true);
+            check(
+                "passed trying harder",
+                stats.try_harder_passed,
+                test.try_harder_must_pass,
+                true,
+            );
+            check("misread", stats.misread, test.max_misreads, false);
+            check(
+                "misread trying harder",
+                stats.try_harder_misread,
+                test.max_try_harder_misreads,
+                false,
+            );
+        }
+        shortfalls
+    }
+}
+
+/// An image of a [`BlackBoxSuite`] and the contents it should read as
+#[derive(Debug, Clone)]
+pub struct BlackBoxImage {
+    pub path: PathBuf,
+    pub expected: String,
+}
+
+/**
+ * Reads every image in a directory at each of a set of rotations, without and with
+ * `TryHarder`, and counts those that read as expected.
+ *
+ * The contents of `name.png` are expected in `name.txt`, or in `name.bin` read as ISO-8859-1
+ * when they are binary. Images without either are skipped.
+ */
+#[derive(Clone)]
+pub struct BlackBoxSuite {
+    pub dir: PathBuf,
+    pub format: BarcodeFormat,
+    pub hints: DecodeHints,
+    pub rotations: Vec<RotationTest>,
+}
+
+impl BlackBoxSuite {
+    pub fn new<P: AsRef<Path>>(dir: P, format: BarcodeFormat) -> Self {
+        Self {
+            dir: dir.as_ref().to_path_buf(),
+            format,
+            hints: DecodeHints::default(),
+            rotations: Vec::new(),
+        }
+    }
+
+    pub fn with_hints(mut self, hints: DecodeHints) -> Self {
+        self.hints = hints;
+        self
+    }
+
+    /// Tests a rotation allowing no misreads
+    pub fn add_rotation(
+        self,
+        rotation: f32,
+        must_pass: usize,
+        try_harder_must_pass: usize,
+    ) -> Self {
+        self.add_rotation_test(RotationTest {
+            rotation,
+            must_pass,
+            try_harder_must_pass,
+            max_misreads: 0,
+            max_try_harder_misreads: 0,
+        })
+    }
+
+    pub fn add_rotation_test(mut self, test: RotationTest) -> Self {
+        self.rotations.push(test);
+        self
+    }
+
+    /// The images of the suite and their expected contents, ordered by path
+    pub fn images(&self) -> Result<Vec<BlackBoxImage>> {
+        let read_error = |e: std::io::Error| {
+            Exceptions::runtime_with(format!("couldn't read {}: {e}", self.dir.display()))
+        };
+        let mut images = Vec::new();
+        for entry in std::fs::read_dir(&self.dir).map_err(read_error)? {
+            let path = entry.map_err(read_error)?.path();
+            if !image::ImageFormat::from_path(&path).is_ok_and(|format| format.reading_enabled()) {
+                continue;
+            }
+            let expected = if let Ok(text) = std::fs::read_to_string(path.with_extension("txt")) {
+                text
+            } else if let Ok(bytes) = std::fs::read(path.with_extension("bin")) {
+                bytes.into_iter().map(char::from).collect()
+            } else {
+                continue;
+            };
+            images.push(BlackBoxImage { path, expected });
+        }
+        images.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(images)
+    }
+
+    /**
+     * Runs `reader` over every image at every rotation of the suite.
+     *
+     * # Returns
+     * The statistics, or an error if the directory or one of its images can't be read
+     */
+    pub fn run<R: Reader>(&self, reader: &mut R) -> Result<AccuracyReport> {
+        let images = self.images()?;
+        let mut report = AccuracyReport {
+            rotations: Vec::new(),
+            failures: Vec::new(),
+        };
+        let mut stats = vec![
+            RotationStats {
+                images: images.len(),
+                ..Default::default()
+            };
+            self.rotations.len()
+        ];
+
+        for image in &images {
+            let luma = image::open(&image.path)
+                .map_err(|e| {
+                    Exceptions::runtime_with(format!("couldn't open {}: {e}", image.path.display()))
+                })?
+                .into_luma8();
+            for (test, stats) in self.rotations.iter().zip(&mut stats) {
+                let rotated = rotate_quarters(&luma, test.rotation)
+                    .unwrap_or_else(|| Distortion::Rotate(test.rotation).apply(&luma));
+                let (width, height) = rotated.dimensions();
+                let source = Luma8LuminanceSource::new(rotated.into_raw(), width, height);
+                let mut bitmap = BinaryBitmap::new(HybridBinarizer::new(source));
+
+                for try_harder in [false, true] {
+                    let hints = DecodeHints {
+                        TryHarder: if try_harder {
+                            Some(true)
+                        } else {
+                            self.hints.TryHarder
+                        },
+                        ..self.hints.clone()
+                    };
+                    let reason = match reader.decode_with_hints(&mut bitmap, &hints) {
+                        Ok(result)
+                            if *result.getBarcodeFormat() == self.format
+                                && result.getText() == image.expected =>
+                        {
+                            if try_harder {
+                                stats.try_harder_passed += 1;
+                            } else {
+                                stats.passed += 1;
+                            }
+                            continue;
+                        }
+                        Ok(result) => {
+                            if try_harder {
+                                stats.try_harder_misread += 1;
+                            } else {
+                                stats.misread += 1;
+                            }
+                            format!(
+                                "read {} '{}'",
+                                result.getBarcodeFormat(),
+                                result.getText().escape_default()
+                            )
+                        }
+                        Err(e) => e.to_string(),
+                    };
+                    report.failures.push(ImageFailure {
+                        path: image.path.clone(),
+                        rotation: test.rotation,
+                        try_harder,
+                        reason,
+                    });
+                }
+            }
+        }
+
+        report.rotations = self.rotations.iter().copied().zip(stats).collect();
+        Ok(report)
+    }
+}
+
+/// Turns an image by a multiple of 90 degrees exactly, or None for other angles
+fn rotate_quarters(image: &GrayImage, degrees: f32) -> Option<GrayImage> {
+    match degrees.rem_euclid(360.0) {
+        0.0 => Some(image.clone()),
+        90.0 => Some(imageops::rotate90(image)),
+        180.0 => Some(imageops::rotate180(image)),
+        270.0 => Some(imageops::rotate270(image)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::qrcode::QRCodeReader;
+
+    #[test]
+    fn test_distortions() {
+        let round_trip = RoundTrip::new(BarcodeFormat::QR_CODE, "distortions");
+        let symbol = round_trip.render_symbol().unwrap();
+        // 21 modules and 4 of margin on each side
+        assert_eq!((29 * 4, 29 * 4), symbol.dimensions());
+
+        let rotated = Distortion::Rotate(45.0).apply(&symbol);
+        assert_eq!(165, rotated.width());
+        let faded = Distortion::Contrast {
+            dark: 100,
+            light: 200,
+        };
+        let faded = faded.apply(&symbol);
+        assert_eq!((100, 200), (faded[(16, 16)].0[0], faded[(0, 0)].0[0]));
+        assert_eq!(58, Distortion::Scale(0.5).apply(&symbol).width());
+
+        let noise = Distortion::Noise {
+            stddev: 20.0,
+            seed: 7,
+        };
+        assert_eq!(noise.apply(&symbol), noise.apply(&symbol));
+        assert_eq!(12, Distortion::rotations(30.0).len());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let perspective =
+            Distortion::Perspective([(0.1, 0.05), (0.0, 0.0), (0.0, 0.0), (0.1, 0.05)]);
+        let noise = Distortion::Noise {
+            stddev: 10.0,
+            seed: 1,
+        };
+        // long enough for an alignment pattern, which QR codes need to be read in perspective
+        for format in [BarcodeFormat::QR_CODE, BarcodeFormat::CODE_128] {
+            let round_trip = RoundTrip::new(format.clone(), "HTTPS://EXAMPLE.COM/ROUND-TRIP")
+                .with_distortion(perspective)
+                .with_distortion(Distortion::Blur(0.8))
+                .with_distortion(noise);
+            round_trip.run().unwrap();
+            for (distortion, decoded) in round_trip.sweep(Distortion::rotations(90.0)) {
+                assert!(decoded.is_ok(), "{format} failed at {distortion:?}");
+            }
+        }
+
+        let round_trip = RoundTrip::new(BarcodeFormat::QR_CODE, "custom reader")
+            .with_distortion(Distortion::Rotate(20.0));
+        let hints = DecodeHints::default();
+        round_trip
+            .run_with_reader(&mut QRCodeReader, &hints)
+            .unwrap();
+
+        let unreadable = round_trip.with_distortion(Distortion::Blur(8.0));
+        assert!(unreadable.run().is_err());
+    }
+
+    #[test]
+    fn test_payload_generator() {
+        for format in PayloadGenerator::FORMATS {
+            let generator = PayloadGenerator::new(format.clone()).unwrap();
+            assert_eq!(generator.from_bytes(&[]), generator.from_bytes(&[0; 4]));
+            for seed in 0..8 {
+                let contents = generator.from_seed(seed);
+                assert_eq!(contents, generator.from_seed(seed));
+                MultiFormatWriter
+                    .encode(&contents, &format, 0, 0)
+                    .unwrap_or_else(|e| panic!("{format} '{contents}': {e}"));
+                // not all of these read back yet: the MaxiCode detector misses some symbols and
+                // some DataBar values are not read
+                if ![BarcodeFormat::MAXICODE, BarcodeFormat::RSS_14].contains(&format) {
+                    let decoded = round_trip(format.clone(), &contents, &[]);
+                    assert!(decoded.is_ok(), "{format} '{contents}': {decoded:?}");
+                }
+            }
+        }
+
+        let generator = PayloadGenerator::new(BarcodeFormat::QR_CODE)
+            .unwrap()
+            .with_max_length(5);
+        assert_eq!(1..=5, generator.lengths());
+        assert!(generator.from_bytes(&[255; 8]).chars().count() <= 5);
+        let ean = PayloadGenerator::new(BarcodeFormat::EAN_13)
+            .unwrap()
+            .with_max_length(5);
+        assert_eq!(13, ean.from_seed(1).len());
+        assert_eq!(None, PayloadGenerator::new(BarcodeFormat::DXFilmEdge));
+    }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn test_fuzz_case() {
+        use arbitrary::{Arbitrary, Unstructured};
+
+        let input = (0..=255).collect::<Vec<u8>>();
+        let case = FuzzCase::arbitrary(&mut Unstructured::new(&input)).unwrap();
+        assert_eq!(
+            case,
+            FuzzCase::arbitrary(&mut Unstructured::new(&input)).unwrap()
+        );
+        assert!(case.distortions.len() <= 3);
+        // nothing to draw from is a case without distortions, which reads back
+        let case = FuzzCase::arbitrary(&mut Unstructured::new(&[])).unwrap();
+        assert!(case.distortions.is_empty());
+        case.run().unwrap();
+    }
+
+    #[cfg(feature = "image_formats")]
+    #[test]
+    fn test_black_box_suite() {
+        let dir = std::env::temp_dir().join(format!("rxing_black_box_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for (name, contents) in [("1", "first label"), ("2", "second label")] {
+            let round_trip = RoundTrip::new(BarcodeFormat::QR_CODE, contents);
+            round_trip
+                .render()
+                .unwrap()
+                .save(dir.join(name).with_extension("png"))
+                .unwrap();
+            std::fs::write(dir.join(name).with_extension("txt"), contents).unwrap();
+        }
+        // an image expected to hold something else, and one with nothing expected of it
+        std::fs::write(dir.join("2.txt"), "another label").unwrap();
+        std::fs::copy(dir.join("1.png"), dir.join("3.png")).unwrap();
+
+        let suite = BlackBoxSuite::new(&dir, BarcodeFormat::QR_CODE)
+            .add_rotation(0.0, 1, 1)
+            .add_rotation(90.0, 1, 1)
+            .add_rotation(45.0, 1, 1);
+        assert_eq!(2, suite.images().unwrap().len());
+        let report = suite.run(&mut MultiFormatReader::default()).unwrap();
+        for (_, stats) in &report.rotations {
+            assert_eq!(2, stats.images);
+            assert_eq!(
+                (1, 1, 0),
+                (stats.passed, stats.misread, stats.not_detected())
+            );
+            assert_eq!((1, 1), (stats.try_harder_passed, stats.try_harder_misread));
+        }
+        assert_eq!(6, report.failures.len());
+        assert!(report.failures[0].reason.contains("second label"));
+        assert_eq!(6, report.shortfalls().len());
+        assert!(!report.passed());
+
+        let lenient = suite.add_rotation_test(RotationTest {
+            rotation: 180.0,
+            must_pass: 1,
+            try_harder_must_pass: 1,
+            max_misreads: 1,
+            max_try_harder_misreads: 1,
+        });
+        let report = lenient.run(&mut MultiFormatReader::default()).unwrap();
+        assert_eq!(6, report.shortfalls().len());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,84 @@
+use arbitrary::{Arbitrary, Unstructured};
+
+use crate::{common::Result, BarcodeFormat, RXingResult};
+
+use super::{round_trip, Distortion, PayloadGenerator};
+
+/// The most distortions a case applies
+const MAX_DISTORTIONS: usize = 3;
+
+/**
+ * A round trip drawn from a fuzzer's input: a format, contents of its [`PayloadGenerator`] and
+ * up to three distortions. The distortions are kept mild, within what a symbol seen by a camera
+ * goes through, so that a case failing to decode is worth a look and one decoding other
+ * contents is a bug.
+ *
+ * ```ignore
+ * fuzz_target!(|case: FuzzCase| {
+ *     if let Err(Exceptions::IllegalStateException(misread)) = case.run() {
+ *         panic!("{case:?}: {misread}");
+ *     }
+ * });
+ * ```
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct FuzzCase {
+    pub format: BarcodeFormat,
+    pub contents: String,
+    pub distortions: Vec<Distortion>,
+}
+
+impl FuzzCase {
+    /// Runs the round trip, see [`round_trip`]
+    pub fn run(&self) -> Result<RXingResult> {
+        round_trip(self.format.clone(), &self.contents, &self.distortions)
+    }
+}
+
+impl<'a> Arbitrary<'a> for FuzzCase {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        let format = u.choose(&PayloadGenerator::FORMATS)?.clone();
+        let generator =
+            PayloadGenerator::new(format.clone()).ok_or(arbitrary::Error::IncorrectFormat)?;
+        let contents = generator.from_bytes(&Vec::<u8>::arbitrary(u)?);
+        let count = u.int_in_range(0..=MAX_DISTORTIONS)?;
+        let distortions = (0..count)
+            .map(|_| arbitrary_distortion(u))
+            .collect::<arbitrary::Result<_>>()?;
+        Ok(Self {
+            format,
+            contents,
+            distortions,
+        })
+    }
+}
+
+/// A value from `min` to `max` in `steps` even steps
+fn float_in_range(u: &mut Unstructured, min: f32, max: f32, steps: u16) -> arbitrary::Result<f32> {
+    let step = u.int_in_range(0..=steps)?;
+    Ok(min + (max - min) * f32::from(step) / f32::from(steps))
+}
+
+fn arbitrary_distortion(u: &mut Unstructured) -> arbitrary::Result<Distortion> {
+    Ok(match u.int_in_range(0..=5)? {
+        0 => Distortion::Rotate(float_in_range(u, 0.0, 360.0, 360)?),
+        1 => Distortion::Blur(float_in_range(u, 0.1, 1.0, 9)?),
+        2 => Distortion::Noise {
+            stddev: float_in_range(u, 0.0, 20.0, 20)?,
+            seed: u.arbitrary()?,
+        },
+        3 => {
+            let mut insets = [(0.0, 0.0); 4];
+            for (x, y) in &mut insets {
+                *x = float_in_range(u, 0.0, 0.1, 10)?;
+                *y = float_in_range(u, 0.0, 0.1, 10)?;
+            }
+            Distortion::Perspective(insets)
+        }
+        4 => Distortion::Scale(float_in_range(u, 0.75, 2.0, 25)?),
+        _ => Distortion::Contrast {
+            dark: u.int_in_range(0..=80)?,
+            light: u.int_in_range(175..=255)?,
+        },
+    })
+}
@@ -0,0 +1,240 @@
+use std::ops::RangeInclusive;
+
+use crate::{
+    oned::{convertUPCEtoUPCA, EAN13Reader, UPCEANReader},
+    BarcodeFormat,
+};
+
+const DIGITS: &str = "0123456789";
+const ALPHANUMERIC: &str = "0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+const CODE_39: &str = "0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ-. $/+%";
+const CODABAR: &str = "0123456789-$:/.+";
+const PRINTABLE_ASCII: &str = " !\"#$%&'()*+,-./0123456789:;<=>?@ABCDEFGHIJKLMNOPQRSTUVWXYZ[\\]^_`abcdefghijklmnopqrstuvwxyz{|}~";
+
+/// The smallest and largest values of a one-track Pharmacode
+const PHARMACODE_VALUES: RangeInclusive<u32> = 3..=131070;
+
+/**
+ * Makes contents a format can encode out of arbitrary bytes. A round trip should read them back
+ * as they are, and contents it does not are what a property test or a fuzzer is looking for.
+ * The same bytes always make the same contents, so one that shrinks its input shrinks the
+ * contents with it.
+ *
+ * Contents use the characters the format encodes without shifts or extensions and fit in a
+ * single symbol. Formats with check digits get them computed, so EAN-13 contents are always
+ * 13 digits that pass their checksum.
+ *
+ * ```
+ * use rxing::{test_utils::{round_trip, PayloadGenerator}, BarcodeFormat};
+ *
+ * let generator = PayloadGenerator::new(BarcodeFormat::CODE_39).unwrap();
+ * for seed in 0..4 {
+ *     let contents = generator.from_seed(seed);
+ *     assert!(contents.chars().all(|c| generator.alphabet().contains(c)));
+ *     round_trip(BarcodeFormat::CODE_39, &contents, &[])?;
+ * }
+ * # Ok::<(), rxing::Exceptions>(())
+ * ```
+ *
+ * With proptest, map a strategy of bytes:
+ *
+ * ```ignore
+ * proptest! {
+ *     #[test]
+ *     fn qr_round_trips(bytes in any::<Vec<u8>>()) {
+ *         let contents = PayloadGenerator::new(BarcodeFormat::QR_CODE).unwrap().from_bytes(&bytes);
+ *         prop_assert!(round_trip(BarcodeFormat::QR_CODE, &contents, &[]).is_ok());
+ *     }
+ * }
+ * ```
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PayloadGenerator {
+    format: BarcodeFormat,
+    max_length: usize,
+}
+
+impl PayloadGenerator {
+    /// The formats contents are generated for, those `MultiFormatWriter` encodes
+    pub const FORMATS: [BarcodeFormat; 22] = [
+        BarcodeFormat::AZTEC,
+        BarcodeFormat::CODABAR,
+        BarcodeFormat::CODE_39,
+        BarcodeFormat::CODE_93,
+        BarcodeFormat::CODE_128,
+        BarcodeFormat::DATA_MATRIX,
+        BarcodeFormat::EAN_8,
+        BarcodeFormat::EAN_13,
+        BarcodeFormat::ITF,
+        BarcodeFormat::MAXICODE,
+        BarcodeFormat::MICRO_QR_CODE,
+        BarcodeFormat::MSI,
+        BarcodeFormat::PDF_417,
+        BarcodeFormat::PHARMACODE,
+        BarcodeFormat::QR_CODE,
+        BarcodeFormat::RSS_14,
+        BarcodeFormat::RSS_EXPANDED,
+        BarcodeFormat::TELEPEN,
+        BarcodeFormat::UPC_A,
+        BarcodeFormat::UPC_E,
+    ];
+
+    /// The default for `with_max_length`, short enough to render and decode quickly
+    pub const DEFAULT_MAX_LENGTH: usize = 40;
+
+    /// A generator for `format`, `None` for formats that cannot be encoded
+    pub fn new(format: BarcodeFormat) -> Option<Self> {
+        if !Self::FORMATS.contains(&format) {
+            return None;
+        }
+        Some(Self {
+            format,
+            max_length: Self::DEFAULT_MAX_LENGTH,
+        })
+    }
+
+    /**
+     * Caps the length of variable length contents, which are otherwise up to
+     * `DEFAULT_MAX_LENGTH` characters. Contents never exceed what a symbol of the format holds,
+     * and keep the length of fixed length formats.
+     */
+    pub fn with_max_length(mut self, max_length: usize) -> Self {
+        self.max_length = max_length.max(1);
+        self
+    }
+
+    pub fn format(&self) -> &BarcodeFormat {
+        &self.format
+    }
+
+    /// The characters contents are made of
+    pub fn alphabet(&self) -> &'static str {
+        match self.format {
+            BarcodeFormat::CODABAR => CODABAR,
+            BarcodeFormat::CODE_39 => CODE_39,
+            BarcodeFormat::CODE_93
+            | BarcodeFormat::CODE_128
+            | BarcodeFormat::TELEPEN
+            | BarcodeFormat::AZTEC
+            | BarcodeFormat::DATA_MATRIX
+            | BarcodeFormat::MAXICODE
+            | BarcodeFormat::PDF_417
+            | BarcodeFormat::QR_CODE => PRINTABLE_ASCII,
+            // the element strings of lot numbers, (10) followed by the alphanumerics
+            BarcodeFormat::MICRO_QR_CODE | BarcodeFormat::RSS_EXPANDED => ALPHANUMERIC,
+            _ => DIGITS,
+        }
+    }
+
+    /**
+     * The lengths of the contents, in characters. The element string of DataBar Expanded
+     * contents has 4 more, for its application identifier.
+     */
+    pub fn lengths(&self) -> RangeInclusive<usize> {
+        let (min, max) = match self.format {
+            BarcodeFormat::EAN_8 | BarcodeFormat::UPC_E => return 8..=8,
+            BarcodeFormat::UPC_A => return 12..=12,
+            BarcodeFormat::EAN_13 => return 13..=13,
+            BarcodeFormat::RSS_14 => return 14..=14,
+            BarcodeFormat::PHARMACODE => return 1..=6,
+            // lot numbers hold up to 20
+            BarcodeFormat::RSS_EXPANDED => (1, 20),
+            // shorter ITF symbols are only read when asked for
+            BarcodeFormat::ITF => (6, 80),
+            // with its start and stop characters a Codabar symbol is read from 4 characters on
+            BarcodeFormat::CODABAR => (2, 80),
+            BarcodeFormat::MSI | BarcodeFormat::TELEPEN => (1, 80),
+            _ => {
+                let capacity = self.format.max_data_capacity().map_or(80, |capacity| {
+                    if self.alphabet() == ALPHANUMERIC {
+                        capacity.alphanumeric
+                    } else {
+                        capacity.bytes
+                    }
+                });
+                (1, capacity)
+            }
+        };
+        let max = max.min(self.max_length).max(min);
+        min..=max
+    }
+
+    /// The contents made of `bytes`. Bytes run out are taken as zeros, so any slice will do.
+    pub fn from_bytes(&self, bytes: &[u8]) -> String {
+        let mut bytes = Bytes(bytes.iter());
+        let lengths = self.lengths();
+        let span = lengths.end() - lengths.start() + 1;
+        let length = lengths.start() + usize::from(bytes.next_u16()) % span;
+        let alphabet = self.alphabet().chars().collect::<Vec<_>>();
+
+        match self.format {
+            BarcodeFormat::EAN_8 | BarcodeFormat::UPC_A | BarcodeFormat::EAN_13 => {
+                with_check_digit(bytes.characters(&alphabet, length - 1))
+            }
+            BarcodeFormat::RSS_14 => with_check_digit(bytes.characters(&alphabet, 13)),
+            BarcodeFormat::UPC_E => {
+                // number system 0 or 1, then 6 digits
+                let upce = format!("{}{}", bytes.next() % 2, bytes.characters(&alphabet, 6));
+                let check = convertUPCEtoUPCA(&upce)
+                    .map(|upca| check_digit(&upca))
+                    .unwrap_or_default();
+                format!("{upce}{check}")
+            }
+            BarcodeFormat::ITF => bytes.characters(&alphabet, length - length % 2),
+            BarcodeFormat::PHARMACODE => {
+                let value = u32::from(bytes.next_u16()) << 16 | u32::from(bytes.next_u16());
+                let span = PHARMACODE_VALUES.end() - PHARMACODE_VALUES.start() + 1;
+                (PHARMACODE_VALUES.start() + value % span).to_string()
+            }
+            BarcodeFormat::RSS_EXPANDED => format!("(10){}", bytes.characters(&alphabet, length)),
+            _ => bytes.characters(&alphabet, length),
+        }
+    }
+
+    /// The contents made of bytes drawn from a generator seeded with `seed`
+    pub fn from_seed(&self, seed: u64) -> String {
+        // splitmix64, enough bytes for the longest contents and a length
+        let mut state = seed;
+        let bytes = (0..self.max_length.max(16) + 8)
+            .map(|_| {
+                state = state.wrapping_add(0x9E3779B97F4A7C15);
+                let mut z = state;
+                z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+                z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+                (z ^ (z >> 31)) as u8
+            })
+            .collect::<Vec<_>>();
+        self.from_bytes(&bytes)
+    }
+}
+
+/// The bytes contents are made of, zeros once they run out
+struct Bytes<'a>(std::slice::Iter<'a, u8>);
+
+impl Bytes<'_> {
+    fn next(&mut self) -> u8 {
+        self.0.next().copied().unwrap_or_default()
+    }
+
+    fn next_u16(&mut self) -> u16 {
+        u16::from(self.next()) << 8 | u16::from(self.next())
+    }
+
+    fn characters(&mut self, alphabet: &[char], count: usize) -> String {
+        (0..count)
+            .map(|_| alphabet[usize::from(self.next()) % alphabet.len()])
+            .collect()
+    }
+}
+
+/// `digits` followed by their UPC/EAN check digit
+fn with_check_digit(digits: String) -> String {
+    let check = check_digit(&digits);
+    format!("{digits}{check}")
+}
+
+fn check_digit(digits: &str) -> u32 {
+    EAN13Reader
+        .getStandardUPCEANChecksum(&digits.chars().collect::<Vec<_>>())
+        .unwrap_or_default()
+}
@@ -0,0 +1,378 @@
+/*
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::{borrow::Cow, sync::Arc};
+
+use crate::common::Result;
+use crate::{Exceptions, LuminanceSource};
+
+/**
+ * The 8 bit luminances behind a [`TiledLuminanceSource`], read a window of a row at a time. An
+ * implementation can keep the image anywhere: in memory, in a mapped file or in the strips of
+ * a file it decodes on demand.
+ */
+pub trait LumaStore {
+    fn width(&self) -> usize;
+
+    fn height(&self) -> usize;
+
+    /// Fills `row` with the luminances of row `y` from column `left` on
+    fn read_row(&self, y: usize, left: usize, row: &mut [u8]);
+}
+
+/**
+ * Luminances stored a byte a pixel, row by row, in bytes held by `D`: a `Vec<u8>`, a slice or,
+ * with the `mmap` feature, a mapped file. Rows start `stride` bytes apart and the first pixel is
+ * `offset` bytes in, which skips the header of a file.
+ */
+#[derive(Debug, Clone)]
+pub struct RawLumaStore<D> {
+    data: D,
+    offset: usize,
+    stride: usize,
+    width: usize,
+    height: usize,
+}
+
+impl<D: AsRef<[u8]>> RawLumaStore<D> {
+    /**
+     * Wraps `width` by `height` luminances, row by row from the start of `data`.
+     *
+     * # Errors
+     * `IllegalArgumentException` if `data` is too short
+     */
+    pub fn new(data: D, width: usize, height: usize) -> Result<Self> {
+        Self::with_layout(data, width, height, width, 0)
+    }
+
+    /**
+     * Wraps `width` by `height` luminances, with rows `stride` bytes apart from `offset` on.
+     *
+     * # Errors
+     * `IllegalArgumentException` if `stride` is less than `width` or `data` is too short
+     */
+    pub fn with_layout(
+        data: D,
+        width: usize,
+        height: usize,
+        stride: usize,
+        offset: usize,
+    ) -> Result<Self> {
+        if stride < width {
+            return Err(Exceptions::illegal_argument_with(format!(
+                "rows of {width} pixels do not fit a stride of {stride} bytes"
+            )));
+        }
+        let needed = if height == 0 {
+            offset
+        } else {
+            offset + stride * (height - 1) + width
+        };
+        if data.as_ref().len() < needed {
+            return Err(Exceptions::illegal_argument_with(format!(
+                "a {width}x{height} image needs {needed} bytes, got {}",
+                data.as_ref().len()
+            )));
+        }
+        Ok(Self {
+            data,
+            offset,
+            stride,
+            width,
+            height,
+        })
+    }
+
+    /**
+     * Wraps the luminances of a binary PGM (P5) file of up to 8 bits a sample, as scanners
+     * write them.
+     *
+     * # Errors
+     * `FormatException` if `data` does not start with such a header, and
+     * `IllegalArgumentException` if it is too short for the image the header describes
+     */
+    pub fn pgm(data: D) -> Result<Self> {
+        let (width, height, offset) = parse_pgm_header(data.as_ref())?;
+        Self::with_layout(data, width, height, width, offset)
+    }
+}
+
+impl<D: AsRef<[u8]>> LumaStore for RawLumaStore<D> {
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    fn height(&self) -> usize {
+        self.height
+    }
+
+    fn read_row(&self, y: usize, left: usize, row: &mut [u8]) {
+        let start = self.offset + y * self.stride + left;
+        row.copy_from_slice(&self.data.as_ref()[start..start + row.len()]);
+    }
+}
+
+#[cfg(feature = "mmap")]
+impl RawLumaStore<memmap2::Mmap> {
+    /**
+     * Maps a binary PGM (P5) file, see `pgm`. The operating system pages in the rows read and
+     * may drop them again, so an image larger than the memory available can be decoded.
+     *
+     * # Errors
+     * `RuntimeException` if the file cannot be opened or mapped, and as `pgm`
+     */
+    pub fn map_pgm<P: AsRef<std::path::Path>>(path: P) -> Result<Self> {
+        Self::pgm(map(path.as_ref())?)
+    }
+
+    /// Maps a file of raw luminances, laid out as `with_layout` describes
+    pub fn map_raw<P: AsRef<std::path::Path>>(
+        path: P,
+        width: usize,
+        height: usize,
+        stride: usize,
+        offset: usize,
+    ) -> Result<Self> {
+        Self::with_layout(map(path.as_ref())?, width, height, stride, offset)
+    }
+}
+
+#[cfg(feature = "mmap")]
+fn map(path: &std::path::Path) -> Result<memmap2::Mmap> {
+    let file = std::fs::File::open(path)
+        .map_err(|e| Exceptions::runtime_with(format!("couldn't open {}: {e}", path.display())))?;
+    // SAFETY: the map is only read, and the caller is told the file must not change meanwhile
+    unsafe { memmap2::Mmap::map(&file) }
+        .map_err(|e| Exceptions::runtime_with(format!("couldn't map {}: {e}", path.display())))
+}
+
+/// The width, height and offset of the samples of a binary PGM file
+fn parse_pgm_header(data: &[u8]) -> Result<(usize, usize, usize)> {
+    if !data.starts_with(b"P5") {
+        return Err(Exceptions::format_with("not a binary PGM file"));
+    }
+    let mut pos = 2;
+    let mut fields = [0; 3];
+    for field in &mut fields {
+        // whitespace and comments up to the end of their line
+        loop {
+            match data.get(pos) {
+                Some(b'#') => {
+                    while data.get(pos).is_some_and(|b| *b != b'\n') {
+                        pos += 1;
+                    }
+                }
+                Some(b) if b.is_ascii_whitespace() => pos += 1,
+                _ => break,
+            }
+        }
+        let start = pos;
+        while data.get(pos).is_some_and(u8::is_ascii_digit) {
+            pos += 1;
+        }
+        *field = std::str::from_utf8(&data[start..pos])
+            .ok()
+            .and_then(|digits| digits.parse::<usize>().ok())
+            .ok_or_else(|| Exceptions::format_with("malformed PGM header"))?;
+    }
+    let [width, height, max] = fields;
+    if !(1..=255).contains(&max) {
+        return Err(Exceptions::format_with(format!(
+            "PGM samples up to {max} are not 8 bits"
+        )));
+    }
+    // a single whitespace character ends the header
+    if !data.get(pos).is_some_and(u8::is_ascii_whitespace) {
+        return Err(Exceptions::format_with("malformed PGM header"));
+    }
+    Ok((width, height, pos + 1))
+}
+
+/**
+ * A luminance source reading its pixels from a [`LumaStore`] only when asked for them, so an
+ * image of 100 megapixels and more need not be held in memory. Cropping shares the store, and
+ * a crop binarizes no more than its own pixels: `TiledMultipleBarcodeReader` scans such an image
+ * a tile at a time.
+ *
+ * ```
+ * use rxing::{
+ *     common::HybridBinarizer,
+ *     multi::{MultipleBarcodeReader, TiledMultipleBarcodeReader},
+ *     BinaryBitmap, Exceptions, RawLumaStore, TiledLuminanceSource,
+ * };
+ *
+ * let store = RawLumaStore::new(vec![255; 2000 * 1500], 2000, 1500)?;
+ * let mut image = BinaryBitmap::new(HybridBinarizer::new(TiledLuminanceSource::new(store)));
+ * let found = TiledMultipleBarcodeReader::default().decode_multiple(&mut image);
+ * assert!(matches!(found, Err(Exceptions::NotFoundException(_))));
+ * # Ok::<(), Exceptions>(())
+ * ```
+ */
+#[derive(Debug)]
+pub struct TiledLuminanceSource<S> {
+    store: Arc<S>,
+    left: usize,
+    top: usize,
+    width: usize,
+    height: usize,
+    invert: bool,
+}
+
+impl<S> Clone for TiledLuminanceSource<S> {
+    fn clone(&self) -> Self {
+        Self {
+            store: self.store.clone(),
+            ..*self
+        }
+    }
+}
+
+impl<S: LumaStore> TiledLuminanceSource<S> {
+    pub fn new(store: S) -> Self {
+        Self::from_shared(Arc::new(store))
+    }
+
+    /// A source of the whole of a store shared with other sources
+    pub fn from_shared(store: Arc<S>) -> Self {
+        let (width, height) = (store.width(), store.height());
+        Self {
+            store,
+            left: 0,
+            top: 0,
+            width,
+            height,
+            invert: false,
+        }
+    }
+
+    pub fn store(&self) -> &Arc<S> {
+        &self.store
+    }
+
+    /// The column and row of the store this source starts at
+    pub fn origin(&self) -> (usize, usize) {
+        (self.left, self.top)
+    }
+
+    fn read_row(&self, y: usize, left: usize, row: &mut [u8]) {
+        self.store.read_row(self.top + y, self.left + left, row);
+        if self.invert {
+            row.iter_mut().for_each(|luma| *luma = 255 - *luma);
+        }
+    }
+}
+
+impl<S: LumaStore> LuminanceSource for TiledLuminanceSource<S> {
+    const SUPPORTS_CROP: bool = true;
+
+    fn get_row(&self, y: usize) -> Option<Cow<'_, [u8]>> {
+        if y >= self.height {
+            return None;
+        }
+        let mut row = vec![0; self.width];
+        self.read_row(y, 0, &mut row);
+        Some(Cow::Owned(row))
+    }
+
+    fn get_column(&self, x: usize) -> Vec<u8> {
+        (0..self.height)
+            .map(|y| self.get_luma8_point(x, y))
+            .collect()
+    }
+
+    fn get_matrix(&self) -> Vec<u8> {
+        let mut matrix = vec![0; self.width * self.height];
+        if self.width > 0 {
+            for (y, row) in matrix.chunks_exact_mut(self.width).enumerate() {
+                self.read_row(y, 0, row);
+            }
+        }
+        matrix
+    }
+
+    fn get_width(&self) -> usize {
+        self.width
+    }
+
+    fn get_height(&self) -> usize {
+        self.height
+    }
+
+    fn crop(&self, left: usize, top: usize, width: usize, height: usize) -> Result<Self> {
+        if left + width > self.width || top + height > self.height {
+            return Err(Exceptions::illegal_argument_with(
+                "Crop rectangle does not fit within image data.",
+            ));
+        }
+        Ok(Self {
+            store: self.store.clone(),
+            left: self.left + left,
+            top: self.top + top,
+            width,
+            height,
+            invert: self.invert,
+        })
+    }
+
+    fn invert(&mut self) {
+        self.invert = !self.invert;
+    }
+
+    fn get_luma8_point(&self, x: usize, y: usize) -> u8 {
+        let mut luma = [0];
+        self.read_row(y, x, &mut luma);
+        luma[0]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::LuminanceSource;
+
+    use super::{RawLumaStore, TiledLuminanceSource};
+
+    #[test]
+    fn test_crop() {
+        let data = (0..30).collect::<Vec<u8>>();
+        // 4x3 pixels in rows of 10 bytes, after 2 bytes of header
+        let store = RawLumaStore::with_layout(data, 4, 3, 10, 2).unwrap();
+        let source = TiledLuminanceSource::new(store);
+        assert_eq!(&[12, 13, 14, 15], &source.get_row(1).unwrap()[..]);
+        assert_eq!(vec![3, 13, 23], source.get_column(1));
+
+        let mut cropped = source.crop(1, 1, 2, 2).unwrap();
+        assert_eq!((1, 1), cropped.origin());
+        assert_eq!(vec![13, 14, 23, 24], cropped.get_matrix());
+        assert!(cropped.crop(1, 0, 2, 1).is_err());
+        cropped.invert();
+        assert_eq!(255 - 24, cropped.get_luma8_point(1, 1));
+
+        assert!(RawLumaStore::with_layout(vec![0; 25], 4, 3, 10, 2).is_err());
+        assert!(RawLumaStore::with_layout(vec![0; 30], 11, 3, 10, 0).is_err());
+    }
+
+    #[test]
+    fn test_pgm() {
+        let mut pgm = b"P5\n# scanned\n3 2\n255\n".to_vec();
+        let header = pgm.len();
+        pgm.extend([1, 2, 3, 4, 5, 6]);
+        let source = TiledLuminanceSource::new(RawLumaStore::pgm(&pgm[..]).unwrap());
+        assert_eq!((3, 2), (source.get_width(), source.get_height()));
+        assert_eq!(vec![1, 2, 3, 4, 5, 6], source.get_matrix());
+
+        assert!(RawLumaStore::pgm(&pgm[..header + 5]).is_err());
+        assert!(RawLumaStore::pgm(&b"P5 3 2 65535\n"[..]).is_err());
+        assert!(RawLumaStore::pgm(&b"P2 3 2 255\n"[..]).is_err());
+    }
+}
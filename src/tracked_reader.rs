@@ -0,0 +1,352 @@
+/*
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::collections::HashSet;
+
+use crate::common::{Result, SamplingGrid};
+use crate::{
+    point, symbol_pose, BarcodeFormat, Binarizer, BinaryBitmap, CropRegion, DecodeHints,
+    Exceptions, LuminanceSource, Point, RXingResult, RXingResultMetadataType,
+    RXingResultMetadataValue, Reader,
+};
+
+/// How far around the tracked symbol it is looked for, as a fraction of its size
+pub const DEFAULT_TRACKING_MARGIN: f32 = 0.25;
+
+/// The least number of pixels the tracked symbol is extended by on every side
+const MIN_TRACKING_MARGIN: f32 = 8.0;
+
+/**
+ * Decodes successive frames of a video stream, looking for a symbol where it was found in
+ * the previous frame before searching the whole frame.
+ *
+ * After a successful decode the reader remembers the format of the symbol and the
+ * quadrilateral it covers, which is mapped from the `SAMPLING_GRID` metadata of the result
+ * where there is one, and otherwise bounds its points. The next frame is first cropped to
+ * that quadrilateral, extended by a margin for a symbol that moved a little, and only that
+ * crop is searched, for that format only. The wrapped reader searches the whole frame if the
+ * symbol isn't found there, so continuously scanning a label that stays in view costs a
+ * fraction of a full decode per frame.
+ *
+ * The points, sampling grid and pose of a symbol found in the crop are mapped back to the
+ * frame.
+ * Frames whose luminance source can't be cropped are always searched whole.
+ */
+pub struct TrackedReader<R: Reader> {
+    reader: R,
+    margin: f32,
+    track: Option<Track>,
+}
+
+/// Where a `TrackedReader` found its last symbol
+#[derive(Debug, Clone, PartialEq)]
+pub struct Track {
+    format: BarcodeFormat,
+    frame: (usize, usize),
+    quadrilateral: [Point; 4],
+    grid: Option<SamplingGrid>,
+}
+
+impl Track {
+    /// The track of `result`, found in a frame of `frame` pixels, None if it has no points
+    fn new(result: &RXingResult, frame: (usize, usize)) -> Option<Self> {
+        let grid = match result
+            .getRXingResultMetadata()
+            .get(&RXingResultMetadataType::SAMPLING_GRID)
+        {
+            Some(RXingResultMetadataValue::SamplingGrid(grid)) => Some(grid.clone()),
+            _ => None,
+        };
+        let quadrilateral = if let Some(grid) = &grid {
+            let (width, height) = (grid.width() as f32, grid.height() as f32);
+            [
+                grid.module_to_image(point(0.0, 0.0)),
+                grid.module_to_image(point(width, 0.0)),
+                grid.module_to_image(point(width, height)),
+                grid.module_to_image(point(0.0, height)),
+            ]
+        } else {
+            let frame = CropRegion {
+                left: 0,
+                top: 0,
+                width: frame.0 as u32,
+                height: frame.1 as u32,
+            };
+            let bounds = CropRegion::around(result.getPoints(), 0, frame)?;
+            let (left, top) = (bounds.left as f32, bounds.top as f32);
+            let (right, bottom) = (left + bounds.width as f32, top + bounds.height as f32);
+            [
+                point(left, top),
+                point(right, top),
+                point(right, bottom),
+                point(left, bottom),
+            ]
+        };
+        Some(Self {
+            format: result.getBarcodeFormat().clone(),
+            frame,
+            quadrilateral,
+            grid,
+        })
+    }
+
+    pub fn format(&self) -> &BarcodeFormat {
+        &self.format
+    }
+
+    /// The corners of the symbol in the frame, clockwise from its top left
+    pub fn quadrilateral(&self) -> &[Point; 4] {
+        &self.quadrilateral
+    }
+
+    /// The sampling grid of the symbol, for the formats that report one
+    pub fn sampling_grid(&self) -> Option<&SamplingGrid> {
+        self.grid.as_ref()
+    }
+}
+
+impl<R: Reader> TrackedReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            margin: DEFAULT_TRACKING_MARGIN,
+            track: None,
+        }
+    }
+
+    /// Look for the tracked symbol this fraction of its size around where it was, 0.25 by
+    /// default
+    pub fn with_margin(mut self, margin: f32) -> Self {
+        self.margin = margin.max(0.0);
+        self
+    }
+
+    /// Where the last symbol was found, None before the first one or after `reset`
+    pub fn get_track(&self) -> Option<&Track> {
+        self.track.as_ref()
+    }
+
+    /// Decodes the tracked symbol in the crop of `image` around it, with points in `image`
+    fn decode_tracked<B: Binarizer>(
+        &mut self,
+        image: &BinaryBitmap<B>,
+        hints: &DecodeHints,
+        track: &Track,
+    ) -> Result<RXingResult> {
+        if !image.is_crop_supported() {
+            return Err(Exceptions::unsupported_operation_with(
+                "the luminance source can't be cropped",
+            ));
+        }
+        let frame = CropRegion {
+            left: 0,
+            top: 0,
+            width: image.get_width() as u32,
+            height: image.get_height() as u32,
+        };
+        let symbol =
+            CropRegion::around(&track.quadrilateral, 0, frame).ok_or(Exceptions::NOT_FOUND)?;
+        let margin =
+            (symbol.width.max(symbol.height) as f32 * self.margin).max(MIN_TRACKING_MARGIN);
+        let region = CropRegion::around(&track.quadrilateral, margin as u32, frame)
+            .ok_or(Exceptions::NOT_FOUND)?;
+
+        let source = image.get_source().crop(
+            region.left as usize,
+            region.top as usize,
+            region.width as usize,
+            region.height as usize,
+        )?;
+        let mut crop = BinaryBitmap::new(image.get_binarizer().create_binarizer(source));
+        let mut hints = hints.clone();
+        hints.PossibleFormats = Some(HashSet::from([track.format.clone()]));
+        let result = self.reader.decode_with_hints(&mut crop, &hints)?;
+        Ok(translate_result(
+            result,
+            point(region.left as f32, region.top as f32),
+        ))
+    }
+}
+
+impl<R: Reader> Reader for TrackedReader<R> {
+    fn decode<B: Binarizer>(&mut self, image: &mut BinaryBitmap<B>) -> Result<RXingResult> {
+        self.decode_with_hints(image, &DecodeHints::default())
+    }
+
+    fn decode_with_hints<B: Binarizer>(
+        &mut self,
+        image: &mut BinaryBitmap<B>,
+        hints: &DecodeHints,
+    ) -> Result<RXingResult> {
+        let frame = (image.get_width(), image.get_height());
+        // the track is forgotten when the size of the frames changes
+        if self
+            .track
+            .as_ref()
+            .is_some_and(|track| track.frame != frame)
+        {
+            self.track = None;
+        }
+        if let Some(track) = self.track.clone() {
+            if let Ok(result) = self.decode_tracked(image, hints, &track) {
+                if let Some(track) = Track::new(&result, frame) {
+                    self.track = Some(track);
+                }
+                return Ok(result);
+            }
+            hints.check_cancelled()?;
+        }
+
+        let result = self.reader.decode_with_hints(image, hints)?;
+        if let Some(track) = Track::new(&result, frame) {
+            self.track = Some(track);
+        }
+        Ok(result)
+    }
+
+    /// Forgets the tracked symbol and resets the wrapped reader
+    fn reset(&mut self) {
+        self.track = None;
+        self.reader.reset();
+    }
+}
+
+/// Maps the points, sampling grid and pose of a result found in a crop to the image the crop's
+/// top left corner lies at `offset` in
+fn translate_result(mut result: RXingResult, offset: Point) -> RXingResult {
+    for p in result.getPointsMut() {
+        *p += offset;
+    }
+    if let Some(RXingResultMetadataValue::SamplingGrid(grid)) = result
+        .getRXingResultMetadata()
+        .get(&RXingResultMetadataType::SAMPLING_GRID)
+    {
+        let grid = grid.translated(offset);
+        result.putMetadata(
+            RXingResultMetadataType::SAMPLING_GRID,
+            RXingResultMetadataValue::SamplingGrid(grid),
+        );
+        symbol_pose::put_pose(&mut result);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        common::{HybridBinarizer, Result},
+        BarcodeFormat, Binarizer, BinaryBitmap, DecodeHints, Luma8LuminanceSource,
+        MultiFormatReader, MultiFormatWriter, Point, RXingResult, Reader, Writer,
+    };
+
+    use super::TrackedReader;
+
+    /// Records the width of every image it is asked to decode
+    #[derive(Default)]
+    struct Recording {
+        reader: MultiFormatReader,
+        widths: Vec<usize>,
+    }
+
+    impl Reader for Recording {
+        fn decode<B: Binarizer>(&mut self, image: &mut BinaryBitmap<B>) -> Result<RXingResult> {
+            self.decode_with_hints(image, &DecodeHints::default())
+        }
+
+        fn decode_with_hints<B: Binarizer>(
+            &mut self,
+            image: &mut BinaryBitmap<B>,
+            hints: &DecodeHints,
+        ) -> Result<RXingResult> {
+            self.widths.push(image.get_width());
+            self.reader.decode_with_hints(image, hints)
+        }
+    }
+
+    /// A frame of 320x240 pixels with a QR code of 3 pixel modules at (`left`, `top`)
+    fn frame(
+        contents: &str,
+        left: u32,
+        top: u32,
+    ) -> BinaryBitmap<HybridBinarizer<Luma8LuminanceSource>> {
+        let matrix = MultiFormatWriter
+            .encode(contents, &BarcodeFormat::QR_CODE, 0, 0)
+            .expect("encode");
+        let mut luma = vec![255u8; 320 * 240];
+        for y in 0..matrix.getHeight() * 3 {
+            for x in 0..matrix.getWidth() * 3 {
+                if matrix.get(x / 3, y / 3) {
+                    luma[((top + y) * 320 + left + x) as usize] = 0;
+                }
+            }
+        }
+        BinaryBitmap::new(HybridBinarizer::new(Luma8LuminanceSource::new(
+            luma, 320, 240,
+        )))
+    }
+
+    fn top_left(result: &RXingResult) -> Point {
+        let points = result.getPoints();
+        Point::new(
+            points.iter().map(|p| p.x).fold(f32::MAX, f32::min),
+            points.iter().map(|p| p.y).fold(f32::MAX, f32::min),
+        )
+    }
+
+    #[test]
+    fn test_tracking() {
+        let mut reader = TrackedReader::new(Recording::default());
+        let first = reader
+            .decode(&mut frame("tracked", 40, 30))
+            .expect("decode");
+        assert_eq!("tracked", first.getText());
+        assert_eq!(vec![320], reader.reader.widths);
+        let track = reader.get_track().expect("track");
+        assert_eq!(&BarcodeFormat::QR_CODE, track.format());
+        assert!(track.sampling_grid().is_some());
+        // the quadrilateral covers the whole symbol, 21 modules of 3 pixels, in its quiet zone
+        let corner = track.quadrilateral()[0];
+        assert!(
+            (corner.x - 52.0).abs() < 3.0 && (corner.y - 42.0).abs() < 3.0,
+            "{corner:?}"
+        );
+
+        // the symbol moved a little and is found in the crop around it
+        reader.reader.widths.clear();
+        let second = reader
+            .decode(&mut frame("tracked", 46, 34))
+            .expect("decode");
+        assert_eq!("tracked", second.getText());
+        assert_eq!(1, reader.reader.widths.len());
+        assert!(reader.reader.widths[0] < 160, "{:?}", reader.reader.widths);
+        let moved = top_left(&second) - top_left(&first);
+        assert!(
+            (moved.x - 6.0).abs() < 2.0 && (moved.y - 4.0).abs() < 2.0,
+            "{moved:?}"
+        );
+
+        // and in the whole frame once it jumped away
+        reader.reader.widths.clear();
+        let third = reader
+            .decode(&mut frame("tracked", 200, 120))
+            .expect("decode");
+        assert_eq!("tracked", third.getText());
+        assert_eq!(320, *reader.reader.widths.last().unwrap());
+        let corner = reader.get_track().expect("track").quadrilateral()[0];
+        assert!(corner.x > 200.0 && corner.y > 120.0, "{corner:?}");
+
+        reader.reset();
+        assert!(reader.get_track().is_none());
+    }
+}
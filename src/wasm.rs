@@ -0,0 +1,478 @@
+//! wasm-bindgen exports for browsers and web workers, enabled by the `wasm` feature.
+//!
+//! Every export is synchronous and copies its input, so the functions are meant to be
+//! called from a web worker: post the `ImageData` (or its pixel buffer) to the worker,
+//! decode there, and post the results back without blocking the page.
+//!
+//! Hints are plain JS objects using the field names of [`DecodeHints`] and
+//! [`EncodeHints`], for example `{ PossibleFormats: ["QR_CODE"], TryHarder: true }`.
+//! `undefined` or `null` means no hints.
+//!
+//! ```js
+//! import init, { decodeImageData, encode, encodeSvg } from "rxing";
+//!
+//! await init();
+//! const barcode = decodeImageData(ctx.getImageData(0, 0, w, h), { TryHarder: true });
+//! console.log(barcode.format, barcode.text, barcode.metadata.ERROR_CORRECTION_LEVEL);
+//! const svg = encodeSvg("hello", "qrcode", 200, 200);
+//! const { width, height, modules } = encode("hello", "qrcode", 0, 0);
+//! ```
+
+use std::io::Cursor;
+
+use wasm_bindgen::prelude::*;
+use web_sys::ImageData;
+
+use crate::{
+    common::{simd, BitMatrix, Result},
+    helpers, BarcodeFormat, DecodeHints, EncodeHints, Exceptions, MultiFormatWriter, RXingResult,
+    Writer,
+};
+
+/// A decoded barcode, as returned to JS
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct DecodedBarcode {
+    text: String,
+    format: BarcodeFormat,
+    raw_bytes: Vec<u8>,
+    points: Vec<f32>,
+    timestamp: f64,
+    metadata: String,
+}
+
+#[wasm_bindgen]
+impl DecodedBarcode {
+    #[wasm_bindgen(getter)]
+    pub fn text(&self) -> String {
+        self.text.clone()
+    }
+
+    /// The barcode format, for example `"qrcode"`
+    #[wasm_bindgen(getter)]
+    pub fn format(&self) -> String {
+        self.format.to_string()
+    }
+
+    #[wasm_bindgen(getter, js_name = rawBytes)]
+    pub fn raw_bytes(&self) -> Vec<u8> {
+        self.raw_bytes.clone()
+    }
+
+    /// The result points as a flat `[x0, y0, x1, y1, ...]` array
+    #[wasm_bindgen(getter)]
+    pub fn points(&self) -> Vec<f32> {
+        self.points.clone()
+    }
+
+    /// Milliseconds since the unix epoch at which the barcode was decoded
+    #[wasm_bindgen(getter)]
+    pub fn timestamp(&self) -> f64 {
+        self.timestamp
+    }
+
+    /**
+     * The result metadata as an object keyed by metadata type, for example
+     * `{ ERROR_CORRECTION_LEVEL: "L", SYMBOLOGY_IDENTIFIER: "]Q1" }`
+     */
+    #[wasm_bindgen(getter)]
+    pub fn metadata(&self) -> JsValue {
+        js_sys::JSON::parse(&self.metadata).unwrap_or(JsValue::NULL)
+    }
+}
+
+/// An encoded barcode, as returned to JS
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct EncodedBarcode {
+    matrix: BitMatrix,
+}
+
+#[wasm_bindgen]
+impl EncodedBarcode {
+    #[wasm_bindgen(getter)]
+    pub fn width(&self) -> u32 {
+        self.matrix.getWidth()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn height(&self) -> u32 {
+        self.matrix.getHeight()
+    }
+
+    /// The modules row by row, one byte each: 1 for dark, 0 for light
+    #[wasm_bindgen(getter)]
+    pub fn modules(&self) -> Vec<u8> {
+        (0..self.matrix.getHeight())
+            .flat_map(|y| (0..self.matrix.getWidth()).map(move |x| (x, y)))
+            .map(|(x, y)| u8::from(self.matrix.get(x, y)))
+            .collect()
+    }
+
+    /// Whether the module at `x`, `y` is dark, false outside the symbol
+    pub fn get(&self, x: u32, y: u32) -> bool {
+        x < self.matrix.getWidth() && y < self.matrix.getHeight() && self.matrix.get(x, y)
+    }
+
+    /// The symbol as an SVG document
+    #[wasm_bindgen(js_name = toSvg)]
+    pub fn to_svg(&self) -> String {
+        svg_string(&self.matrix)
+    }
+
+    /// The symbol as PNG file bytes
+    #[wasm_bindgen(js_name = toPng)]
+    pub fn to_png(&self) -> std::result::Result<Vec<u8>, JsError> {
+        Ok(png_bytes(&self.matrix)?)
+    }
+}
+
+impl From<RXingResult> for DecodedBarcode {
+    fn from(result: RXingResult) -> Self {
+        Self {
+            text: result.getText().to_owned(),
+            format: result.getBarcodeFormat().clone(),
+            raw_bytes: result.getRawBytes().to_vec(),
+            points: result.getPoints().iter().flat_map(|p| [p.x, p.y]).collect(),
+            timestamp: result.getTimestamp() as f64,
+            metadata: metadata_json(&result),
+        }
+    }
+}
+
+/// The version of rxing in use
+#[wasm_bindgen]
+pub fn version() -> String {
+    env!("CARGO_PKG_VERSION").to_owned()
+}
+
+/// Decode a single barcode from canvas pixels
+#[wasm_bindgen(js_name = decodeImageData)]
+pub fn decode_image_data(
+    image: &ImageData,
+    hints: JsValue,
+) -> std::result::Result<DecodedBarcode, JsError> {
+    decode_rgba(&image.data(), image.width(), image.height(), hints)
+}
+
+/// Decode every barcode found in canvas pixels
+#[wasm_bindgen(js_name = decodeMultipleImageData)]
+pub fn decode_multiple_image_data(
+    image: &ImageData,
+    hints: JsValue,
+) -> std::result::Result<Vec<DecodedBarcode>, JsError> {
+    decode_multiple_rgba(&image.data(), image.width(), image.height(), hints)
+}
+
+/// Decode a single barcode from RGBA pixels, four bytes per pixel
+#[wasm_bindgen(js_name = decodeRgba)]
+pub fn decode_rgba(
+    rgba: &[u8],
+    width: u32,
+    height: u32,
+    hints: JsValue,
+) -> std::result::Result<DecodedBarcode, JsError> {
+    let mut hints = decode_hints_from_js(&hints)?;
+    let luma = rgba_to_luma(rgba, width, height)?;
+    Ok(decode_luma_with_hints(luma, width, height, &mut hints)?.into())
+}
+
+/// Decode every barcode found in RGBA pixels, four bytes per pixel
+#[wasm_bindgen(js_name = decodeMultipleRgba)]
+pub fn decode_multiple_rgba(
+    rgba: &[u8],
+    width: u32,
+    height: u32,
+    hints: JsValue,
+) -> std::result::Result<Vec<DecodedBarcode>, JsError> {
+    let mut hints = decode_hints_from_js(&hints)?;
+    let luma = rgba_to_luma(rgba, width, height)?;
+    Ok(
+        decode_multiple_luma_with_hints(luma, width, height, &mut hints)?
+            .into_iter()
+            .map(DecodedBarcode::from)
+            .collect(),
+    )
+}
+
+/// Decode a single barcode from 8 bit grayscale pixels, one byte per pixel
+#[wasm_bindgen(js_name = decodeLuma)]
+pub fn decode_luma(
+    luma: &[u8],
+    width: u32,
+    height: u32,
+    hints: JsValue,
+) -> std::result::Result<DecodedBarcode, JsError> {
+    let mut hints = decode_hints_from_js(&hints)?;
+    Ok(decode_luma_with_hints(luma.to_vec(), width, height, &mut hints)?.into())
+}
+
+/// Decode every barcode found in 8 bit grayscale pixels, one byte per pixel
+#[wasm_bindgen(js_name = decodeMultipleLuma)]
+pub fn decode_multiple_luma(
+    luma: &[u8],
+    width: u32,
+    height: u32,
+    hints: JsValue,
+) -> std::result::Result<Vec<DecodedBarcode>, JsError> {
+    let mut hints = decode_hints_from_js(&hints)?;
+    Ok(
+        decode_multiple_luma_with_hints(luma.to_vec(), width, height, &mut hints)?
+            .into_iter()
+            .map(DecodedBarcode::from)
+            .collect(),
+    )
+}
+
+/// Encode `contents` and return the symbol's modules
+#[wasm_bindgen(js_name = encode)]
+pub fn encode_barcode(
+    contents: &str,
+    format: &str,
+    width: i32,
+    height: i32,
+    hints: JsValue,
+) -> std::result::Result<EncodedBarcode, JsError> {
+    let hints = encode_hints_from_js(&hints)?;
+    let matrix = encode(contents, format, width, height, &hints)?;
+    Ok(EncodedBarcode { matrix })
+}
+
+/// Encode `contents` and return the symbol as an SVG document
+#[wasm_bindgen(js_name = encodeSvg)]
+pub fn encode_svg(
+    contents: &str,
+    format: &str,
+    width: i32,
+    height: i32,
+    hints: JsValue,
+) -> std::result::Result<String, JsError> {
+    let hints = encode_hints_from_js(&hints)?;
+    let matrix = encode(contents, format, width, height, &hints)?;
+    Ok(svg_string(&matrix))
+}
+
+/// Encode `contents` and return the symbol as PNG file bytes
+#[wasm_bindgen(js_name = encodePng)]
+pub fn encode_png(
+    contents: &str,
+    format: &str,
+    width: i32,
+    height: i32,
+    hints: JsValue,
+) -> std::result::Result<Vec<u8>, JsError> {
+    let hints = encode_hints_from_js(&hints)?;
+    let matrix = encode(contents, format, width, height, &hints)?;
+    Ok(png_bytes(&matrix)?)
+}
+
+fn hints_json(hints: &JsValue) -> std::result::Result<Option<String>, JsError> {
+    if hints.is_undefined() || hints.is_null() {
+        return Ok(None);
+    }
+    js_sys::JSON::stringify(hints)
+        .map(|json| Some(json.into()))
+        .map_err(|_| JsError::new("hints could not be converted to JSON"))
+}
+
+fn decode_hints_from_js(hints: &JsValue) -> std::result::Result<DecodeHints, JsError> {
+    Ok(decode_hints_from_json(hints_json(hints)?.as_deref())?)
+}
+
+fn encode_hints_from_js(hints: &JsValue) -> std::result::Result<EncodeHints, JsError> {
+    Ok(encode_hints_from_json(hints_json(hints)?.as_deref())?)
+}
+
+fn decode_hints_from_json(json: Option<&str>) -> Result<DecodeHints> {
+    let Some(json) = json else {
+        return Ok(DecodeHints::default());
+    };
+    serde_json::from_str(json)
+        .map_err(|e| Exceptions::illegal_argument_with(format!("invalid decode hints: {e}")))
+}
+
+fn encode_hints_from_json(json: Option<&str>) -> Result<EncodeHints> {
+    let Some(json) = json else {
+        return Ok(EncodeHints::default());
+    };
+    serde_json::from_str(json)
+        .map_err(|e| Exceptions::illegal_argument_with(format!("invalid encode hints: {e}")))
+}
+
+// The metadata of `result` as a JSON object of the values keyed by their type
+fn metadata_json(result: &RXingResult) -> String {
+    let metadata: serde_json::Map<String, serde_json::Value> = result
+        .getRXingResultMetadata()
+        .iter()
+        .filter_map(|(md_type, value)| {
+            let serde_json::Value::String(key) = serde_json::to_value(md_type).ok()? else {
+                return None;
+            };
+            // values are serialized as `{ "Variant": value }`
+            let value = match serde_json::to_value(value).ok()? {
+                serde_json::Value::Object(variant) if variant.len() == 1 => {
+                    variant.into_iter().next()?.1
+                }
+                value => value,
+            };
+            Some((key, value))
+        })
+        .collect();
+    serde_json::Value::Object(metadata).to_string()
+}
+
+fn checked_len(width: u32, height: u32, bytes_per_pixel: usize) -> Result<usize> {
+    (width as usize)
+        .checked_mul(height as usize)
+        .and_then(|pixels| pixels.checked_mul(bytes_per_pixel))
+        .filter(|len| *len > 0)
+        .ok_or_else(|| {
+            Exceptions::illegal_argument_with(format!("invalid image size {width}x{height}"))
+        })
+}
+
+fn rgba_to_luma(rgba: &[u8], width: u32, height: u32) -> Result<Vec<u8>> {
+    let len = checked_len(width, height, 4)?;
+    if rgba.len() != len {
+        return Err(Exceptions::illegal_argument_with(format!(
+            "expected {len} bytes of RGBA data, got {}",
+            rgba.len()
+        )));
+    }
+
+    let mut luma = vec![0; len / 4];
+    simd::rgba_to_luma(rgba, &mut luma);
+    Ok(luma)
+}
+
+fn check_luma(luma: &[u8], width: u32, height: u32) -> Result<()> {
+    let len = checked_len(width, height, 1)?;
+    if luma.len() != len {
+        return Err(Exceptions::illegal_argument_with(format!(
+            "expected {len} bytes of luma data, got {}",
+            luma.len()
+        )));
+    }
+    Ok(())
+}
+
+fn decode_luma_with_hints(
+    luma: Vec<u8>,
+    width: u32,
+    height: u32,
+    hints: &mut DecodeHints,
+) -> Result<RXingResult> {
+    check_luma(&luma, width, height)?;
+    helpers::detect_in_luma_with_hints(luma, width, height, None, hints)
+}
+
+fn decode_multiple_luma_with_hints(
+    luma: Vec<u8>,
+    width: u32,
+    height: u32,
+    hints: &mut DecodeHints,
+) -> Result<Vec<RXingResult>> {
+    check_luma(&luma, width, height)?;
+    helpers::detect_multiple_in_luma_with_hints(luma, width, height, hints)
+}
+
+fn encode(
+    contents: &str,
+    format: &str,
+    width: i32,
+    height: i32,
+    hints: &EncodeHints,
+) -> Result<BitMatrix> {
+    let format: BarcodeFormat = format.parse()?;
+    MultiFormatWriter.encode_with_hints(contents, &format, width, height, hints)
+}
+
+fn svg_string(matrix: &BitMatrix) -> String {
+    svg::Document::from(matrix).to_string()
+}
+
+fn png_bytes(matrix: &BitMatrix) -> Result<Vec<u8>> {
+    let image = image::DynamicImage::from(matrix);
+    let mut png = Cursor::new(Vec::new());
+    image
+        .write_to(&mut png, image::ImageFormat::Png)
+        .map_err(|e| Exceptions::illegal_argument_with(format!("could not write png: {e}")))?;
+    Ok(png.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn qr_rgba(contents: &str) -> (Vec<u8>, u32, u32) {
+        let matrix = encode(contents, "qrcode", 100, 100, &EncodeHints::default()).unwrap();
+        let rgba = image::DynamicImage::from(&matrix).to_rgba8();
+        let (width, height) = rgba.dimensions();
+        (rgba.into_raw(), width, height)
+    }
+
+    #[test]
+    fn decodes_rgba() {
+        let (rgba, width, height) = qr_rgba("wasm");
+        let luma = rgba_to_luma(&rgba, width, height).unwrap();
+        let mut hints = decode_hints_from_json(Some(r#"{"PossibleFormats":["QR_CODE"]}"#)).unwrap();
+
+        let result: DecodedBarcode = decode_luma_with_hints(luma, width, height, &mut hints)
+            .unwrap()
+            .into();
+        assert_eq!(result.text(), "wasm");
+        assert_eq!(result.format, BarcodeFormat::QR_CODE);
+        assert_eq!(result.points().len() % 2, 0);
+
+        let metadata: serde_json::Value = serde_json::from_str(&result.metadata).unwrap();
+        assert_eq!(metadata["ERROR_CORRECTION_LEVEL"], "L");
+        assert_eq!(metadata["ERRORS_CORRECTED"], 0);
+    }
+
+    #[test]
+    fn rejects_short_buffers() {
+        assert!(rgba_to_luma(&[0; 12], 2, 2).is_err());
+        assert!(check_luma(&[0; 3], 2, 2).is_err());
+        assert!(check_luma(&[], 0, 0).is_err());
+    }
+
+    #[test]
+    fn transparent_pixels_are_white() {
+        assert_eq!(
+            rgba_to_luma(&[0, 0, 0, 0, 0, 0, 0, 255], 2, 1).unwrap(),
+            vec![0xFF, 0]
+        );
+    }
+
+    #[test]
+    fn parses_hints() {
+        let hints = decode_hints_from_json(Some(r#"{"TryHarder":true}"#)).unwrap();
+        assert_eq!(hints.TryHarder, Some(true));
+        assert!(decode_hints_from_json(None).unwrap().TryHarder.is_none());
+        assert!(decode_hints_from_json(Some("[1]")).is_err());
+
+        let hints = encode_hints_from_json(Some(r#"{"ErrorCorrection":"H"}"#)).unwrap();
+        assert_eq!(hints.ErrorCorrection.as_deref(), Some("H"));
+    }
+
+    #[test]
+    fn encodes_svg_and_png() {
+        let matrix = encode("wasm", "qrcode", 50, 50, &EncodeHints::default()).unwrap();
+        assert!(svg_string(&matrix).starts_with("<svg"));
+        assert!(png_bytes(&matrix).unwrap().starts_with(b"\x89PNG"));
+        assert!(encode("wasm", "not a format", 50, 50, &EncodeHints::default()).is_err());
+    }
+
+    #[test]
+    fn encodes_modules() {
+        let matrix = encode("wasm", "qrcode", 0, 0, &EncodeHints::default()).unwrap();
+        let barcode = EncodedBarcode { matrix };
+        assert_eq!((29, 29), (barcode.width(), barcode.height()));
+        let modules = barcode.modules();
+        assert_eq!(29 * 29, modules.len());
+        // the top left finder pattern starts after the 4 module quiet zone
+        assert_eq!(0, modules[3 * 29 + 3]);
+        assert_eq!(1, modules[4 * 29 + 4]);
+        assert!(barcode.get(4, 4) && !barcode.get(3, 3) && !barcode.get(29, 4));
+    }
+}
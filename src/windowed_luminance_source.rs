@@ -0,0 +1,255 @@
+use std::{borrow::Cow, fmt::Debug, sync::Arc};
+
+use crate::common::Result;
+use crate::{Exceptions, LuminanceSource};
+
+/// A grayscale sample of more than 8 bits, see `WindowedLuminanceSource`
+pub trait LumaSample: Copy + PartialOrd + Debug {
+    fn to_f32(self) -> f32;
+}
+
+impl LumaSample for u16 {
+    fn to_f32(self) -> f32 {
+        self as f32
+    }
+}
+
+impl LumaSample for f32 {
+    fn to_f32(self) -> f32 {
+        self
+    }
+}
+
+/**
+ * A luminance source for 16 bit or floating point grayscale images, as medical and scientific
+ * cameras produce them. The samples in a window are mapped linearly onto the luminances 0 to
+ * 255, those below it to 0 and those above it to 255, as a viewer windows such an image.
+ *
+ * By default the window is the range of the samples of the image, so a symbol printed with
+ * little contrast keeps the full range of luminances instead of the few steps left after
+ * dropping the low bits. Set the window with `with_window` to exclude outliers or to apply the
+ * window of the imaging protocol. Not a number maps to 0.
+ *
+ * Cropping keeps the window and shares the samples.
+ */
+#[derive(Debug, Clone)]
+pub struct WindowedLuminanceSource<T> {
+    data: Arc<[T]>,
+    stride: usize,
+    left: usize,
+    top: usize,
+    width: usize,
+    height: usize,
+    low: f32,
+    high: f32,
+    invert: bool,
+}
+
+/// A luminance source for 16 bit grayscale, see `WindowedLuminanceSource`
+pub type Luma16LuminanceSource = WindowedLuminanceSource<u16>;
+
+/// A luminance source for floating point grayscale, see `WindowedLuminanceSource`
+pub type LumaF32LuminanceSource = WindowedLuminanceSource<f32>;
+
+impl<T: LumaSample> WindowedLuminanceSource<T> {
+    /**
+     * Wraps `width` by `height` samples, row-major, windowed to their range.
+     *
+     * # Errors
+     * `IllegalArgumentException` if `data` is shorter than `width * height`
+     */
+    pub fn new(data: impl Into<Arc<[T]>>, width: usize, height: usize) -> Result<Self> {
+        let data = data.into();
+        if data.len() < width * height {
+            return Err(Exceptions::illegal_argument_with(format!(
+                "a {width}x{height} image needs {} samples, got {}",
+                width * height,
+                data.len()
+            )));
+        }
+
+        let (low, high) = data[..width * height]
+            .iter()
+            .map(|sample| sample.to_f32())
+            .filter(|sample| sample.is_finite())
+            .fold(None, |range, sample| match range {
+                None => Some((sample, sample)),
+                Some((low, high)) => Some((sample.min(low), sample.max(high))),
+            })
+            .unwrap_or((0.0, 0.0));
+
+        Ok(Self {
+            data,
+            stride: width,
+            left: 0,
+            top: 0,
+            width,
+            height,
+            low,
+            high,
+            invert: false,
+        })
+    }
+
+    /**
+     * Maps the samples from `low` to `high` onto the luminances 0 to 255. If `high` is not
+     * above `low`, samples of at least `high` are white and the others black.
+     */
+    pub fn with_window(mut self, low: T, high: T) -> Self {
+        self.low = low.to_f32();
+        self.high = high.to_f32();
+        self
+    }
+
+    /// The lowest and highest sample of the window
+    pub fn get_window(&self) -> (f32, f32) {
+        (self.low, self.high)
+    }
+
+    fn luminance(&self, x: usize, y: usize) -> u8 {
+        let sample = self.data[(self.top + y) * self.stride + self.left + x].to_f32();
+        let luminance = if self.high > self.low {
+            // not a number stays so through clamp, and converts to 0
+            ((sample - self.low) * 255.0 / (self.high - self.low))
+                .round()
+                .clamp(0.0, 255.0) as u8
+        } else if sample >= self.high {
+            255
+        } else {
+            0
+        };
+
+        if self.invert {
+            255 - luminance
+        } else {
+            luminance
+        }
+    }
+}
+
+impl<T: LumaSample> LuminanceSource for WindowedLuminanceSource<T> {
+    const SUPPORTS_CROP: bool = true;
+
+    fn get_row(&self, y: usize) -> Option<Cow<'_, [u8]>> {
+        if y >= self.height {
+            return None;
+        }
+        Some(Cow::Owned(
+            (0..self.width).map(|x| self.luminance(x, y)).collect(),
+        ))
+    }
+
+    fn get_column(&self, x: usize) -> Vec<u8> {
+        (0..self.height).map(|y| self.luminance(x, y)).collect()
+    }
+
+    fn get_matrix(&self) -> Vec<u8> {
+        (0..self.height)
+            .flat_map(|y| (0..self.width).map(move |x| self.luminance(x, y)))
+            .collect()
+    }
+
+    fn get_width(&self) -> usize {
+        self.width
+    }
+
+    fn get_height(&self) -> usize {
+        self.height
+    }
+
+    fn crop(&self, left: usize, top: usize, width: usize, height: usize) -> Result<Self> {
+        if left + width > self.width || top + height > self.height {
+            return Err(Exceptions::illegal_argument_with(
+                "Crop rectangle does not fit within image data.",
+            ));
+        }
+        Ok(Self {
+            data: self.data.clone(),
+            left: self.left + left,
+            top: self.top + top,
+            width,
+            height,
+            ..*self
+        })
+    }
+
+    fn invert(&mut self) {
+        self.invert = !self.invert;
+    }
+
+    fn get_luma8_point(&self, x: usize, y: usize) -> u8 {
+        self.luminance(x, y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        common::HybridBinarizer, qrcode::QRCodeReader, BarcodeFormat, BinaryBitmap,
+        LuminanceSource, MultiFormatWriter, Reader, Writer,
+    };
+
+    use super::{Luma16LuminanceSource, LumaF32LuminanceSource};
+
+    #[test]
+    fn test_window() {
+        let data: Vec<u16> = vec![1000, 1255, 2000, 3000, 500, 65535];
+        let source = Luma16LuminanceSource::new(data, 3, 2).unwrap();
+        assert_eq!((500.0, 65535.0), source.get_window());
+
+        let source = source.with_window(1000, 2020);
+        assert_eq!(&[0, 64, 250], &source.get_row(0).unwrap()[..]);
+        // samples outside the window saturate
+        assert_eq!(vec![255, 0, 255], source.get_row(1).unwrap().to_vec());
+        assert_eq!(vec![64, 0], source.get_column(1));
+
+        let cropped = source.crop(1, 0, 2, 2).unwrap();
+        assert_eq!(vec![64, 250, 0, 255], cropped.get_matrix());
+        assert!(source.crop(2, 0, 2, 1).is_err());
+        let mut inverted = cropped;
+        inverted.invert();
+        assert_eq!(191, inverted.get_luma8_point(0, 0));
+
+        // a window without width thresholds at its sample
+        let threshold = Luma16LuminanceSource::new(vec![999, 1000, 1001, 0], 2, 2)
+            .unwrap()
+            .with_window(1000, 1000);
+        assert_eq!(vec![0, 255, 255, 0], threshold.get_matrix());
+
+        assert!(Luma16LuminanceSource::new(vec![0; 5], 3, 2).is_err());
+    }
+
+    #[test]
+    fn test_float_samples() {
+        let data = vec![-1.0, 0.5, f32::NAN, 3.0, f32::INFINITY, 1.0];
+        let source = LumaF32LuminanceSource::new(data, 3, 2).unwrap();
+        // the range of the finite samples
+        assert_eq!((-1.0, 3.0), source.get_window());
+        assert_eq!(vec![0, 96, 0, 255, 255, 128], source.get_matrix());
+    }
+
+    #[test]
+    fn test_decode_low_contrast() {
+        let matrix = MultiFormatWriter
+            .encode("16 bit", &BarcodeFormat::QR_CODE, 100, 100)
+            .expect("encode");
+        let (width, height) = (matrix.getWidth() as usize, matrix.getHeight() as usize);
+        // dark and light differ in the low 8 bits only, so the high bytes are all the same
+        let data: Vec<u16> = (0..width * height)
+            .map(|i| {
+                if matrix.get((i % width) as u32, (i / width) as u32) {
+                    0x7710
+                } else {
+                    0x77f0
+                }
+            })
+            .collect();
+        assert!(data.iter().all(|sample| sample >> 8 == 0x77));
+
+        let source = Luma16LuminanceSource::new(data, width, height).unwrap();
+        let result = QRCodeReader
+            .decode(&mut BinaryBitmap::new(HybridBinarizer::new(source)))
+            .expect("decode");
+        assert_eq!("16 bit", result.getText());
+    }
+}
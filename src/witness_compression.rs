@@ -0,0 +1,312 @@
+/*
+ * Compression of the bit matrices of witness data
+ *
+ * Bit matrices can be written packed eight pixels to a byte or run-length encoded instead of
+ * one boolean per pixel, and are decompressed from either form when a witness is loaded.
+ */
+
+use std::cell::Cell;
+use std::fmt;
+
+#[cfg(feature = "bincode")]
+use bincode::{
+    de::Decoder,
+    enc::Encoder,
+    error::{DecodeError, EncodeError},
+    Decode, Encode,
+};
+use serde::{de, Deserialize, Deserializer, Serialize};
+
+use crate::common::BitMatrix;
+#[cfg(feature = "bincode")]
+use crate::pipeline_witness::PackedBitMatrix;
+
+/// How the bit matrices of a witness are written when it is serialized
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum MatrixCompression {
+    /// What the format does by default: a flat array of booleans in human readable formats
+    /// such as JSON, `BitPacked` in binary formats such as CBOR. The boolean arrays leave out
+    /// the dimensions of the matrix, which loading takes from the rest of the witness
+    #[default]
+    None,
+    /// A map of `width`, `height` and `bits`, the pixels packed row-major, eight to a byte,
+    /// most significant bit first
+    BitPacked,
+    /// A map of `width`, `height` and `runs`, the lengths of the alternating runs of white and
+    /// black pixels in row-major order, starting with a (possibly empty) run of white
+    RunLength,
+}
+
+thread_local! {
+    static MATRIX_COMPRESSION: Cell<MatrixCompression> =
+        const { Cell::new(MatrixCompression::None) };
+}
+
+/// Runs `serialize` with the bit matrices it serializes written with `compression`
+pub(crate) fn with_matrix_compression<T>(
+    compression: MatrixCompression,
+    serialize: impl FnOnce() -> T,
+) -> T {
+    // puts the previous compression back, even if serialize panics
+    struct Restore(MatrixCompression);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            MATRIX_COMPRESSION.with(|current| current.set(self.0));
+        }
+    }
+
+    let _restore = Restore(MATRIX_COMPRESSION.with(|current| current.replace(compression)));
+    serialize()
+}
+
+/// The compression bit matrices are currently serialized with
+pub(crate) fn matrix_compression() -> MatrixCompression {
+    MATRIX_COMPRESSION.with(Cell::get)
+}
+
+/// A BitMatrix as the lengths of its runs of white and black pixels, see `MatrixCompression`
+#[derive(Serialize)]
+pub(crate) struct RunLengthBitMatrix {
+    width: u32,
+    height: u32,
+    runs: Vec<u32>,
+}
+
+impl RunLengthBitMatrix {
+    pub(crate) fn new(matrix: &BitMatrix) -> Self {
+        let (width, height) = (matrix.getWidth(), matrix.getHeight());
+        let mut runs = Vec::new();
+        let (mut color, mut run) = (false, 0);
+        for y in 0..height {
+            for x in 0..width {
+                if matrix.get(x, y) != color {
+                    runs.push(run);
+                    (color, run) = (!color, 0);
+                }
+                run += 1;
+            }
+        }
+        runs.push(run);
+
+        Self {
+            width,
+            height,
+            runs,
+        }
+    }
+}
+
+// Every form a bit matrix can be serialized in
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredBitMatrix {
+    Packed {
+        width: u32,
+        height: u32,
+        #[serde(deserialize_with = "deserialize_bytes")]
+        bits: Vec<u8>,
+    },
+    RunLength {
+        width: u32,
+        height: u32,
+        runs: Vec<u32>,
+    },
+    Booleans(Vec<bool>),
+}
+
+impl StoredBitMatrix {
+    fn decompress(self) -> Result<BitMatrix, String> {
+        match self {
+            Self::Packed {
+                width,
+                height,
+                bits,
+            } => {
+                let mut matrix = BitMatrix::new(width, height).map_err(|e| e.to_string())?;
+                let pixels = width as usize * height as usize;
+                if bits.len() != pixels.div_ceil(8) {
+                    return Err(format!(
+                        "{} bytes of bits for a {width}x{height} matrix",
+                        bits.len()
+                    ));
+                }
+                for i in 0..pixels {
+                    if bits[i / 8] & (0x80 >> (i % 8)) != 0 {
+                        matrix.set((i % width as usize) as u32, (i / width as usize) as u32);
+                    }
+                }
+                Ok(matrix)
+            }
+            Self::RunLength {
+                width,
+                height,
+                runs,
+            } => {
+                let mut matrix = BitMatrix::new(width, height).map_err(|e| e.to_string())?;
+                let pixels = width as u64 * height as u64;
+                let total = runs.iter().map(|&run| run as u64).sum::<u64>();
+                if total != pixels {
+                    return Err(format!(
+                        "runs of {total} pixels for a {width}x{height} matrix"
+                    ));
+                }
+                let mut i = 0;
+                for (n, &run) in runs.iter().enumerate() {
+                    if n % 2 == 1 {
+                        for j in i..i + run as u64 {
+                            matrix.set((j % width as u64) as u32, (j / width as u64) as u32);
+                        }
+                    }
+                    i += run as u64;
+                }
+                Ok(matrix)
+            }
+            Self::Booleans(pixels) => Err(format!(
+                "a bit matrix stored as an array of {} booleans has no dimensions, serialize \
+                the witness with a MatrixCompression to load it",
+                pixels.len()
+            )),
+        }
+    }
+}
+
+/// Deserializes a BitMatrix from any of the forms `MatrixCompression` writes that keep its
+/// dimensions
+pub(crate) fn deserialize_bitmatrix<'de, D>(deserializer: D) -> Result<BitMatrix, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    StoredBitMatrix::deserialize(deserializer)?
+        .decompress()
+        .map_err(de::Error::custom)
+}
+
+// bincode has a single form, `BitPacked`, as the matrix has no serializer to pick another with
+#[cfg(feature = "bincode")]
+impl Encode for BitMatrix {
+    fn encode<E: Encoder>(&self, encoder: &mut E) -> Result<(), EncodeError> {
+        let packed = PackedBitMatrix::new(self);
+        packed.width.encode(encoder)?;
+        packed.height.encode(encoder)?;
+        packed.bits.encode(encoder)
+    }
+}
+
+#[cfg(feature = "bincode")]
+impl<Context> Decode<Context> for BitMatrix {
+    fn decode<D: Decoder<Context = Context>>(decoder: &mut D) -> Result<Self, DecodeError> {
+        let packed = StoredBitMatrix::Packed {
+            width: u32::decode(decoder)?,
+            height: u32::decode(decoder)?,
+            bits: Vec::decode(decoder)?,
+        };
+        packed.decompress().map_err(DecodeError::OtherString)
+    }
+}
+
+#[cfg(feature = "bincode")]
+bincode::impl_borrow_decode!(BitMatrix);
+
+/// Deserializes bytes written either as a byte string or as an array of numbers
+pub(crate) fn deserialize_bytes<'de, D>(deserializer: D) -> Result<Vec<u8>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct BytesVisitor;
+
+    impl<'de> de::Visitor<'de> for BytesVisitor {
+        type Value = Vec<u8>;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("a byte string or an array of bytes")
+        }
+
+        fn visit_bytes<E: de::Error>(self, bytes: &[u8]) -> Result<Self::Value, E> {
+            Ok(bytes.to_vec())
+        }
+
+        fn visit_byte_buf<E: de::Error>(self, bytes: Vec<u8>) -> Result<Self::Value, E> {
+            Ok(bytes)
+        }
+
+        fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+            let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+            while let Some(byte) = seq.next_element()? {
+                bytes.push(byte);
+            }
+            Ok(bytes)
+        }
+    }
+
+    deserializer.deserialize_any(BytesVisitor)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matrix() -> BitMatrix {
+        // a black square in a white 13x7 frame, so rows don't end on a byte boundary
+        let mut matrix = BitMatrix::new(13, 7).unwrap();
+        matrix.setRegion(4, 2, 5, 3).unwrap();
+        matrix.set(12, 6);
+        matrix
+    }
+
+    #[test]
+    fn test_run_lengths() {
+        let runs = RunLengthBitMatrix::new(&matrix()).runs;
+        assert_eq!(vec![30, 5, 8, 5, 8, 5, 29, 1], runs);
+
+        // a matrix starting with black starts with an empty run of white
+        let mut black = BitMatrix::new(2, 2).unwrap();
+        black.set(0, 0);
+        assert_eq!(vec![0, 1, 3], RunLengthBitMatrix::new(&black).runs);
+    }
+
+    #[test]
+    fn test_decompress() {
+        let run_length = serde_json::to_value(RunLengthBitMatrix::new(&matrix())).unwrap();
+        let decompressed = deserialize_bitmatrix(run_length).unwrap();
+        assert_eq!(matrix(), decompressed);
+
+        let packed = serde_json::json!({"width": 3, "height": 3, "bits": [0b1000_1000, 0x80]});
+        let mut diagonal = BitMatrix::new(3, 3).unwrap();
+        (0..3).for_each(|i| diagonal.set(i, i));
+        assert_eq!(diagonal, deserialize_bitmatrix(packed).unwrap());
+    }
+
+    #[test]
+    fn test_decompress_mismatched_size() {
+        let runs = serde_json::json!({"width": 3, "height": 3, "runs": [4, 4]});
+        assert!(deserialize_bitmatrix(runs).is_err());
+        let bits = serde_json::json!({"width": 3, "height": 3, "bits": [0]});
+        assert!(deserialize_bitmatrix(bits).is_err());
+        let booleans = serde_json::json!([true, false, false, true]);
+        assert!(deserialize_bitmatrix(booleans).is_err());
+    }
+
+    #[cfg(feature = "bincode")]
+    #[test]
+    fn test_bincode_packs_bits() {
+        let config = bincode::config::standard();
+        let bytes = bincode::encode_to_vec(matrix(), config).unwrap();
+        // the width, the height, the number of bytes and the 91 pixels in 12 bytes
+        assert_eq!([13, 7, 12], bytes[..3]);
+        assert_eq!(3 + 12, bytes.len());
+        let (decoded, _): (BitMatrix, _) = bincode::decode_from_slice(&bytes, config).unwrap();
+        assert_eq!(matrix(), decoded);
+
+        let bits = bincode::encode_to_vec((3u32, 3u32, vec![0u8]), config).unwrap();
+        assert!(bincode::decode_from_slice::<BitMatrix, _>(&bits, config).is_err());
+    }
+
+    #[test]
+    fn test_compression_is_scoped() {
+        assert_eq!(MatrixCompression::None, matrix_compression());
+        let inner = with_matrix_compression(MatrixCompression::RunLength, matrix_compression);
+        assert_eq!(MatrixCompression::RunLength, inner);
+        assert_eq!(MatrixCompression::None, matrix_compression());
+    }
+}
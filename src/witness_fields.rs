@@ -0,0 +1,349 @@
+/*
+ * Field element export of witness data
+ *
+ * Proof systems assign their witnesses as elements of a prime field. This converts the
+ * pixels, bits and codewords of a PipelineWitness into field elements, laid out as the flat
+ * vectors arkworks constraint systems allocate and as the columns halo2 regions assign.
+ */
+
+use std::ops::Add;
+
+use crate::common::{BitMatrix, CodewordWitness};
+use crate::PipelineWitness;
+
+/**
+ * A matrix of field elements, such as the pixels of an image.
+ *
+ * # Fields
+ * * `width` - The number of columns
+ * * `height` - The number of rows
+ * * `elements` - The elements, stored row-major
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldMatrix<F> {
+    pub width: usize,
+    pub height: usize,
+    pub elements: Vec<F>,
+}
+
+impl<F: From<u64> + Copy> FieldMatrix<F> {
+    /**
+     * Converts a grayscale image, one element per pixel holding its value from 0 to 255.
+     *
+     * # Arguments
+     * * `width` - The width of the image in pixels
+     * * `height` - The height of the image in pixels
+     * * `image` - The grayscale values, row-major
+     */
+    pub fn from_pixels(width: usize, height: usize, image: &[u8]) -> Self {
+        Self {
+            width,
+            height,
+            elements: image.iter().map(|&pixel| F::from(pixel as u64)).collect(),
+        }
+    }
+
+    /**
+     * Converts a BitMatrix, one element per bit: 1 for a set (black) bit, 0 otherwise.
+     */
+    pub fn from_bits(matrix: &BitMatrix) -> Self {
+        let (width, height) = (matrix.getWidth(), matrix.getHeight());
+        let elements = (0..height)
+            .flat_map(|y| (0..width).map(move |x| F::from(matrix.get(x, y) as u64)))
+            .collect();
+
+        Self {
+            width: width as usize,
+            height: height as usize,
+            elements,
+        }
+    }
+
+    /**
+     * Returns the elements of row `y`.
+     */
+    pub fn row(&self, y: usize) -> &[F] {
+        &self.elements[y * self.width..(y + 1) * self.width]
+    }
+
+    /**
+     * Lays the matrix out as halo2 advice columns: column `x` holds the elements of column `x`
+     * top to bottom, so row `y` of the region is row `y` of the matrix.
+     *
+     * # Returns
+     * `width` columns of `height` elements each
+     */
+    pub fn columns(&self) -> Vec<Vec<F>> {
+        (0..self.width)
+            .map(|x| {
+                (0..self.height)
+                    .map(|y| self.elements[y * self.width + x])
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+/**
+ * The pixels, bits and codewords of a witness as field elements.
+ *
+ * Any prime field of arkworks (`ark_ff::PrimeField`) or of halo2 (`ff::PrimeField`) can be
+ * used as `F`, as can plain integers for inspection.
+ *
+ * # Fields
+ * * `pixels` - The grayscale image, from 0 to 255 per pixel
+ * * `binarized` - The binarized image, 1 for black pixels
+ * * `thresholds` - The block threshold of each pixel of the binarized image, if the binarizer
+ *   kept its block thresholds, see `BlockThresholds::threshold_at`. A pixel is black if its
+ *   value is at most its threshold
+ * * `modules` - The sampled modules, 1 for dark modules
+ * * `raw_codewords` - The codewords in the order they were read from the symbol
+ * * `corrected_codewords` - The corrected data codewords
+ * * `runs` - The run-length encoding of the row a 1D symbol was read from, see
+ *   `ScanlineWitness::runs`
+ * * `values` - The values the characters of a 1D symbol were read as
+ *
+ * Components of stages that were not recorded are None or empty.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WitnessFieldElements<F> {
+    pub pixels: Option<FieldMatrix<F>>,
+    pub binarized: Option<FieldMatrix<F>>,
+    pub thresholds: Option<FieldMatrix<F>>,
+    pub modules: Option<FieldMatrix<F>>,
+    pub raw_codewords: Vec<F>,
+    pub corrected_codewords: Vec<F>,
+    pub runs: Vec<F>,
+    pub values: Vec<F>,
+}
+
+impl<F: From<u64> + Copy> WitnessFieldElements<F> {
+    /**
+     * Converts the components of `witness`.
+     */
+    pub fn new(witness: &PipelineWitness) -> Self {
+        let codewords = |select: fn(&CodewordWitness) -> &Vec<i32>| {
+            witness.decoding().map_or_else(Vec::new, |decoding| {
+                // codewords are at most 12 bits, so never negative
+                select(decoding)
+                    .iter()
+                    .map(|&codeword| F::from(codeword as u64))
+                    .collect()
+            })
+        };
+
+        Self {
+            pixels: witness.luminance().map(|luminance| {
+                FieldMatrix::from_pixels(luminance.width, luminance.height, &luminance.image)
+            }),
+            binarized: witness
+                .binarization()
+                .map(|binarization| FieldMatrix::from_bits(&binarization.binarized_image)),
+            thresholds: witness.binarization().and_then(|binarization| {
+                let blocks = binarization.block_thresholds.as_ref()?;
+                let extent = binarization.extent();
+                let thresholds: Vec<u8> = (0..extent.height)
+                    .flat_map(|y| {
+                        (0..extent.width)
+                            .map(move |x| blocks.threshold_at(extent.left + x, extent.top + y))
+                    })
+                    .collect();
+                Some(FieldMatrix::from_pixels(
+                    extent.width as usize,
+                    extent.height as usize,
+                    &thresholds,
+                ))
+            }),
+            modules: witness
+                .sampling()
+                .map(|sampling| FieldMatrix::from_bits(&sampling.modules)),
+            raw_codewords: codewords(|decoding| &decoding.raw_codewords),
+            corrected_codewords: codewords(|decoding| &decoding.corrected_codewords),
+            runs: witness.scanline().map_or_else(Vec::new, |scanline| {
+                scanline
+                    .runs
+                    .iter()
+                    .map(|&run| F::from(run as u64))
+                    .collect()
+            }),
+            values: witness.scanline().map_or_else(Vec::new, |scanline| {
+                scanline
+                    .characters
+                    .iter()
+                    .filter_map(|character| character.value)
+                    .map(|value| F::from(value as u64))
+                    .collect()
+            }),
+        }
+    }
+
+    /**
+     * Concatenates every component into one vector, as an arkworks circuit allocates its
+     * witness: the pixels, the binarized image, the thresholds and the modules, each
+     * row-major, followed by the raw and the corrected codewords, the runs and the values.
+     */
+    pub fn to_vec(&self) -> Vec<F> {
+        let matrices = [
+            &self.pixels,
+            &self.binarized,
+            &self.thresholds,
+            &self.modules,
+        ];
+        matrices
+            .into_iter()
+            .flatten()
+            .flat_map(|matrix| matrix.elements.iter().copied())
+            .chain(self.raw_codewords.iter().copied())
+            .chain(self.corrected_codewords.iter().copied())
+            .chain(self.runs.iter().copied())
+            .chain(self.values.iter().copied())
+            .collect()
+    }
+}
+
+impl PipelineWitness {
+    /**
+     * Converts the pixels, bits and codewords of this witness into field elements, see
+     * `WitnessFieldElements`.
+     */
+    pub fn field_elements<F: From<u64> + Copy>(&self) -> WitnessFieldElements<F> {
+        WitnessFieldElements::new(self)
+    }
+}
+
+/**
+ * Packs bits into field elements, `bits_per_element` to an element, most significant bit
+ * first. The last element holds the remaining bits if they don't fill it.
+ *
+ * Packing the binarized image or the modules this way takes far fewer elements than one per
+ * bit. So that every element is below the modulus, `bits_per_element` must not exceed the
+ * capacity of the field, one less than the bit size of its modulus: `F::CAPACITY` in ff,
+ * `F::MODULUS_BIT_SIZE - 1` in arkworks, 253 for BN254.
+ *
+ * # Arguments
+ * * `bits` - The bits to pack, in order
+ * * `bits_per_element` - How many bits each element holds
+ *
+ * # Panics
+ * Panics if `bits_per_element` is 0
+ */
+pub fn pack_bits<F, I>(bits: I, bits_per_element: usize) -> Vec<F>
+where
+    F: From<u64> + Add<Output = F> + Copy,
+    I: IntoIterator<Item = bool>,
+{
+    assert!(bits_per_element > 0, "elements must hold at least one bit");
+
+    let mut elements = Vec::new();
+    let mut bits = bits.into_iter().peekable();
+    while bits.peek().is_some() {
+        let element = bits
+            .by_ref()
+            .take(bits_per_element)
+            .fold(F::from(0), |acc, bit| acc + acc + F::from(bit as u64));
+        elements.push(element);
+    }
+
+    elements
+}
+
+/**
+ * Packs the bits of a BitMatrix row-major into field elements, see `pack_bits`.
+ */
+pub fn pack_bit_matrix<F>(matrix: &BitMatrix, bits_per_element: usize) -> Vec<F>
+where
+    F: From<u64> + Add<Output = F> + Copy,
+{
+    let (width, height) = (matrix.getWidth(), matrix.getHeight());
+    let bits = (0..height).flat_map(|y| (0..width).map(move |x| matrix.get(x, y)));
+    pack_bits(bits, bits_per_element)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BinarizationWitness, BlockThresholds, LuminanceWitness};
+
+    fn witness() -> PipelineWitness {
+        let mut binarized = BitMatrix::new(3, 2).unwrap();
+        binarized.set(0, 0);
+        binarized.set(2, 1);
+        let mut decoding = CodewordWitness::new(8, vec![0x40, 0xec, 0x11]);
+        decoding.corrected_codewords = vec![0x40];
+
+        PipelineWitness::new()
+            .with_luminance(LuminanceWitness::new(3, 2, vec![0, 200, 255, 90, 180, 10]))
+            .with_binarization(BinarizationWitness::new(binarized))
+            .with_decoding(decoding)
+    }
+
+    #[test]
+    fn test_field_elements() {
+        let elements = witness().field_elements::<u64>();
+        let pixels = elements.pixels.as_ref().unwrap();
+        assert_eq!(vec![0, 200, 255, 90, 180, 10], pixels.elements);
+        assert_eq!(&[90, 180, 10], pixels.row(1));
+        let columns = vec![vec![0, 90], vec![200, 180], vec![255, 10]];
+        assert_eq!(columns, pixels.columns());
+
+        let binarized = elements.binarized.as_ref().unwrap();
+        assert_eq!(vec![1, 0, 0, 0, 0, 1], binarized.elements);
+        assert!(elements.modules.is_none());
+        assert_eq!(vec![0x40, 0xec, 0x11], elements.raw_codewords);
+        assert_eq!(vec![0x40], elements.corrected_codewords);
+
+        let flat = elements.to_vec();
+        assert_eq!(6 + 6 + 3 + 1, flat.len());
+        assert_eq!(&[1, 0, 0, 0, 0, 1, 0x40, 0xec, 0x11, 0x40], &flat[6..]);
+
+        let empty = PipelineWitness::new().field_elements::<u64>();
+        assert!(empty.to_vec().is_empty());
+    }
+
+    #[test]
+    fn test_pack_bits() {
+        let bits = [true, false, true, true, false, false, false, true];
+        assert_eq!(vec![0b1011_0001], pack_bits::<u64, _>(bits, 8));
+        assert_eq!(vec![0b101, 0b100, 0b01], pack_bits::<u64, _>(bits, 3));
+        assert!(pack_bits::<u64, _>([], 3).is_empty());
+
+        // the matrix packs row-major, like its elements
+        let matrix = witness().binarization().unwrap().binarized_image.clone();
+        assert_eq!(vec![0b1000, 0b01], pack_bit_matrix::<u128>(&matrix, 4));
+    }
+
+    #[cfg(feature = "poseidon")]
+    #[test]
+    fn test_arkworks_field() {
+        use ark_bn254::Fr;
+
+        let elements = witness().field_elements::<Fr>();
+        assert_eq!(Fr::from(255u64), elements.pixels.unwrap().elements[2]);
+
+        // 252 bits fill an element without reaching the modulus
+        let packed: Vec<Fr> = pack_bits([true; 260], 252);
+        assert_eq!(2, packed.len());
+        assert_eq!(Fr::from(0xffu64), packed[1]);
+        let two_to_252 = (0..252).fold(Fr::from(1u64), |acc, _| acc + acc);
+        assert_eq!(two_to_252, packed[0] + Fr::from(1u64));
+    }
+
+    #[test]
+    fn test_block_thresholds() {
+        let mut thresholds = BlockThresholds::new(3, 2, 1);
+        thresholds.thresholds = vec![10, 20, 30, 40, 50, 60];
+        let binarization = witness()
+            .binarization()
+            .unwrap()
+            .clone()
+            .with_block_thresholds(thresholds);
+        let witness = witness().with_binarization(binarization);
+
+        let elements: WitnessFieldElements<u64> = witness.field_elements();
+        let thresholds = elements.thresholds.as_ref().unwrap();
+        assert_eq!((3, 2), (thresholds.width, thresholds.height));
+        assert_eq!(vec![10, 20, 30, 40, 50, 60], thresholds.elements);
+        // between the binarized image and the modules
+        assert_eq!(&[0, 1, 10, 20], &elements.to_vec()[10..14]);
+    }
+}
@@ -0,0 +1,262 @@
+/*
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use crate::{Binarizer, DecodeHintValue, DecodeHints, PipelineStage};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/**
+ * Records which algorithm and configuration produced a witness, so that a proof consumer can
+ * audit how the committed trace came about.
+ *
+ * # Fields
+ * * `crate_version` - The version of rxing that recorded the witness
+ * * `binarizer` - The type of the binarizer, as `std::any::type_name` reports it
+ * * `threshold` - The global threshold of the binarizer, None if it thresholds locally
+ * * `hints` - The decode hints that were set, by hint type
+ * * `timings` - How long the stages took, in pipeline order
+ */
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WitnessProvenance {
+    pub crate_version: String,
+
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub binarizer: Option<String>,
+
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub threshold: Option<u8>,
+
+    /// Each hint as its `DecodeHintType` name and its value. Callbacks and sinks, which have
+    /// no value to record, are `set`
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub hints: BTreeMap<String, String>,
+
+    /// Left out for `Deterministic` decodes, so the same image and hints still produce the
+    /// same witness
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub timings: Vec<StageTiming>,
+}
+
+/**
+ * When a stage started and ended, as offsets from the start of the decode. Stages a reader
+ * runs as one are timed together under the last of them: a detector that samples the symbol
+ * as it locates it is timed under `Sampling`, and a 1D reader, which binarizes and decodes row
+ * by row, under `Scanline`. The decoders correct the codewords and assemble their text at
+ * once, which is timed under `Decoding`; `TextAssembly` is the time the reader then takes to
+ * build the result.
+ *
+ * A stage starts when the one before ended, so it includes the attempts that failed before
+ * it, such as a symbol that was located but couldn't be decoded.
+ */
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StageTiming {
+    pub stage: PipelineStage,
+
+    /// Witnesses recorded before stages had a start and an end have neither, see
+    /// `WITNESS_SCHEMA_VERSION`
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub start: Duration,
+
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub end: Duration,
+}
+
+impl StageTiming {
+    /// The time spent on the stage
+    pub fn elapsed(&self) -> Duration {
+        self.end.saturating_sub(self.start)
+    }
+}
+
+impl WitnessProvenance {
+    /**
+     * Creates a WitnessProvenance recording only the version of this crate.
+     */
+    pub fn new() -> Self {
+        Self {
+            crate_version: env!("CARGO_PKG_VERSION").to_owned(),
+            binarizer: None,
+            threshold: None,
+            hints: BTreeMap::new(),
+            timings: Vec::new(),
+        }
+    }
+
+    /**
+     * Records the type and the global threshold of `binarizer`.
+     */
+    pub fn with_binarizer<B: Binarizer>(mut self, binarizer: &B) -> Self {
+        self.binarizer = Some(std::any::type_name::<B>().to_owned());
+        self.threshold = binarizer.get_global_threshold();
+        self
+    }
+
+    /**
+     * Records every hint set in `hints`.
+     */
+    pub fn with_hints(mut self, hints: &DecodeHints) -> Self {
+        #[allow(deprecated)]
+        let dictionary: crate::DecodingHintDictionary = hints.clone().into();
+        self.hints = dictionary
+            .into_iter()
+            .map(|(hint_type, value)| (format!("{hint_type:?}"), hint_value(value)))
+            .collect();
+        self
+    }
+
+    /**
+     * Records when `stage` started and ended, see `StageTiming`.
+     */
+    pub fn with_timing(mut self, stage: PipelineStage, start: Duration, end: Duration) -> Self {
+        self.timings.push(StageTiming { stage, start, end });
+        self
+    }
+}
+
+impl Default for WitnessProvenance {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn hint_value(value: DecodeHintValue) -> String {
+    match value {
+        DecodeHintValue::Other(v) | DecodeHintValue::CharacterSet(v) => v,
+        DecodeHintValue::PureBarcode(v)
+        | DecodeHintValue::TryHarder(v)
+        | DecodeHintValue::AssumeCode39CheckDigit(v)
+        | DecodeHintValue::AssumeGs1(v)
+        | DecodeHintValue::ReturnCodabarStartEnd(v)
+        | DecodeHintValue::AlsoInverted(v)
+        | DecodeHintValue::TelepenAsNumeric(v)
+        | DecodeHintValue::Deterministic(v)
+        | DecodeHintValue::FixedPointSampling(v)
+        | DecodeHintValue::ExpandUPCE(v)
+        | DecodeHintValue::PharmacodeTwoTrack(v)
+        | DecodeHintValue::TryDownscale(v)
+        | DecodeHintValue::TryPreprocessing(v)
+        | DecodeHintValue::AssumeNoQuietZone(v)
+        | DecodeHintValue::AllowPartialSymbol(v)
+        | DecodeHintValue::TryCurved(v)
+        | DecodeHintValue::Code39ExtendedMode(v) => v.to_string(),
+        #[cfg(feature = "allow_forced_iso_ied_18004_compliance")]
+        DecodeHintValue::QrAssumeSpecConformInput(v) => v.to_string(),
+        DecodeHintValue::PossibleFormats(formats) => {
+            // sets iterate in a different order every run
+            let mut formats: Vec<String> = formats.iter().map(|f| format!("{f:?}")).collect();
+            formats.sort();
+            formats.join(",")
+        }
+        DecodeHintValue::AllowedLengths(lengths)
+        | DecodeHintValue::AllowedEanExtensions(lengths)
+        | DecodeHintValue::ITFLengths(lengths) => lengths
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(","),
+        DecodeHintValue::WitnessCropMargin(v)
+        | DecodeHintValue::PharmacodeMinBars(v)
+        | DecodeHintValue::PharmacodeMaxBars(v) => v.to_string(),
+        DecodeHintValue::Code39CheckDigit(v)
+        | DecodeHintValue::CodabarCheckDigit(v)
+        | DecodeHintValue::ITFCheckDigit(v)
+        | DecodeHintValue::MSICheckDigit(v) => format!("{v:?}"),
+        DecodeHintValue::MSIChecksum(v) => format!("{v:?}"),
+        DecodeHintValue::Strictness(v) => format!("{v:?}"),
+        DecodeHintValue::OneDRowSampling(v) => format!("{v:?}"),
+        DecodeHintValue::GridSampling(v) => format!("{v:?}"),
+        DecodeHintValue::NeedResultPointCallback(_)
+        | DecodeHintValue::WitnessSink(_)
+        | DecodeHintValue::WitnessRecorder(_)
+        | DecodeHintValue::Cancellation(_) => "set".to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::common::FixedThresholdBinarizer;
+    use crate::{BarcodeFormat, Luma8LuminanceSource};
+
+    #[test]
+    fn test_hints() {
+        let hints = DecodeHints {
+            TryHarder: Some(true),
+            PossibleFormats: Some(HashSet::from([
+                BarcodeFormat::QR_CODE,
+                BarcodeFormat::AZTEC,
+            ])),
+            AllowedLengths: Some(vec![8, 13]),
+            NeedResultPointCallback: Some(Arc::new(|_| {})),
+            ..Default::default()
+        };
+        let provenance = WitnessProvenance::new().with_hints(&hints);
+
+        let expected = [
+            ("ALLOWED_LENGTHS", "8,13"),
+            ("NEED_RESULT_POINT_CALLBACK", "set"),
+            ("POSSIBLE_FORMATS", "AZTEC,QR_CODE"),
+            ("TRY_HARDER", "true"),
+        ];
+        let expected: BTreeMap<String, String> = expected
+            .iter()
+            .map(|(hint, value)| (hint.to_string(), value.to_string()))
+            .collect();
+        assert_eq!(expected, provenance.hints);
+        assert_eq!(env!("CARGO_PKG_VERSION"), provenance.crate_version);
+    }
+
+    #[test]
+    fn test_binarizer_and_timing() {
+        let source = Luma8LuminanceSource::new(vec![0, 255], 2, 1);
+        let binarizer = FixedThresholdBinarizer::with_threshold(source, 100);
+        let provenance = WitnessProvenance::new().with_binarizer(&binarizer);
+        assert!(provenance
+            .binarizer
+            .as_ref()
+            .unwrap()
+            .contains("FixedThresholdBinarizer<"));
+        assert_eq!(Some(100), provenance.threshold);
+
+        let timed = provenance.with_timing(
+            PipelineStage::Sampling,
+            Duration::from_millis(2),
+            Duration::from_millis(5),
+        );
+        assert_eq!(PipelineStage::Sampling, timed.timings[0].stage);
+        assert_eq!(Duration::from_millis(3), timed.timings[0].elapsed());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_timing_without_start_and_end() {
+        let timing: StageTiming = serde_json::from_str(
+            r#"{"stage":"TextAssembly","elapsed":{"secs":0,"nanos":1500000}}"#,
+        )
+        .unwrap();
+        assert_eq!(PipelineStage::TextAssembly, timing.stage);
+        assert_eq!(Duration::ZERO, timing.elapsed());
+    }
+}
@@ -0,0 +1,386 @@
+/*
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::{sync::Mutex, time::Duration};
+
+use chrono::{DateTime, Utc};
+
+use crate::{
+    common::{CodewordWitness, Result, SamplingGrid},
+    witness_sink::collected,
+    BarcodeFormat, BinarizationWitness, Binarizer, BinaryBitmap, DecodeHints, DecodeWarning,
+    DetectionWitness, PipelineStage, PipelineWitness, Point, RXingResult, StageTiming,
+    TextAssemblyWitness,
+};
+
+/**
+ * Records the intermediate data of a decode while the reader works through it, when set as the
+ * `WitnessRecorder` decode hint. Where a `WitnessSink` is handed the stages of a successful
+ * decode once it is done, the readers call a recorder as they reach each stage: once they have
+ * binarized the image, once the detector has located a symbol, once the codewords are error
+ * corrected and once they are decoded into bits. An attempt that fails partway has recorded the
+ * stages it reached, so the stages of the attempt that succeeded are the last ones recorded.
+ *
+ * All methods default to doing nothing. An error returned by a method doesn't fail the decode:
+ * the reader records nothing more of that symbol, and adds `DecodeWarning::WitnessIncomplete`
+ * to its result.
+ *
+ * As with the `WitnessSink` hint, the recorder is held as an `Arc`, and the methods take
+ * `&self`. A `Mutex<PipelineWitness>` is such a recorder; each stage replaces the stage
+ * recorded before, and drops the later stages of an earlier attempt.
+ */
+pub trait WitnessRecorder: Send + Sync {
+    /// Called with the binarized image, once the reader has binarized it
+    fn record_binarization(&self, _witness: &BinarizationWitness) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called with the points and patterns of a symbol, once the detector has located it
+    fn record_detection(&self, _witness: &DetectionWitness) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called with the codewords read from the symbol, once they are error corrected
+    fn record_codewords(&self, _witness: &CodewordWitness) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called with the bits decoded from the codewords and the text they make up
+    fn record_decoded_bits(&self, _witness: &TextAssemblyWitness) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl WitnessRecorder for Mutex<PipelineWitness> {
+    fn record_binarization(&self, witness: &BinarizationWitness) -> Result<()> {
+        let mut collected = collected(self);
+        collected.binarization = Some(witness.clone());
+        collected.detection = None;
+        collected.decoding = None;
+        collected.text_assembly = None;
+        Ok(())
+    }
+
+    fn record_detection(&self, witness: &DetectionWitness) -> Result<()> {
+        let mut collected = collected(self);
+        collected.detection = Some(witness.clone());
+        collected.decoding = None;
+        collected.text_assembly = None;
+        Ok(())
+    }
+
+    fn record_codewords(&self, witness: &CodewordWitness) -> Result<()> {
+        let mut collected = collected(self);
+        collected.decoding = Some(witness.clone());
+        collected.text_assembly = None;
+        Ok(())
+    }
+
+    fn record_decoded_bits(&self, witness: &TextAssemblyWitness) -> Result<()> {
+        collected(self).text_assembly = Some(witness.clone());
+        Ok(())
+    }
+}
+
+/**
+ * What a reader records of one decode. The readers create one when they start decoding and tell
+ * it the stages they reach, which it passes on to the `WitnessRecorder` hint and times, see
+ * `StageTiming`. The witnesses are only built if the hint is set, and nothing is timed for a
+ * `Deterministic` decode.
+ */
+pub(crate) struct WitnessRecording<'a> {
+    recorder: Option<&'a dyn WitnessRecorder>,
+    failed: bool,
+    /// When the reader started decoding, None for a `Deterministic` decode
+    started: Option<DateTime<Utc>>,
+    /// When the last stage timed ended, after the recorder was called with it
+    last_end: Duration,
+    timings: Vec<StageTiming>,
+}
+
+impl<'a> WitnessRecording<'a> {
+    pub(crate) fn new(hints: &'a DecodeHints) -> Self {
+        Self {
+            recorder: hints.WitnessRecorder.as_deref(),
+            failed: false,
+            started: (!hints.Deterministic.unwrap_or(false)).then(Utc::now),
+            last_end: Duration::ZERO,
+            timings: Vec::new(),
+        }
+    }
+
+    /// The stages of the latest symbol timed, in pipeline order
+    pub(crate) fn timings(&self) -> &[StageTiming] {
+        &self.timings
+    }
+
+    fn since_started(&self) -> Option<Duration> {
+        self.started
+            .map(|started| (Utc::now() - started).to_std().unwrap_or_default())
+    }
+
+    /// Times `stage` as having ended now, in place of the timings of an earlier attempt that
+    /// got as far, and records it with `record`
+    fn stage<F: FnOnce(&dyn WitnessRecorder) -> Result<()>>(
+        &mut self,
+        stage: PipelineStage,
+        record: F,
+    ) {
+        if let Some(end) = self.since_started() {
+            self.timings.retain(|timing| timing.stage < stage);
+            self.timings.push(StageTiming {
+                stage,
+                start: self.last_end,
+                end,
+            });
+        }
+        self.record(record);
+        if let Some(end) = self.since_started() {
+            self.last_end = end;
+        }
+    }
+
+    fn record<F: FnOnce(&dyn WitnessRecorder) -> Result<()>>(&mut self, record: F) {
+        if let Some(recorder) = self.recorder.filter(|_| !self.failed) {
+            self.failed = record(recorder).is_err();
+        }
+    }
+
+    /// Binarizes `image`, if it isn't yet, and records the binarized image
+    pub(crate) fn binarization<B: Binarizer>(&mut self, image: &BinaryBitmap<B>) {
+        image.get_black_matrix();
+        self.stage(PipelineStage::Binarization, |recorder| {
+            record_binarization(recorder, image)
+        });
+    }
+
+    /// Records the binarized `image` for a 1D reader, which binarizes the rows it scans as part
+    /// of the `Scanline` stage, so the image is only binarized for the recorder
+    pub(crate) fn row_binarization<B: Binarizer>(&mut self, image: &BinaryBitmap<B>) {
+        self.record(|recorder| record_binarization(recorder, image));
+    }
+
+    /// Records the symbol a detector located, and the sampling grid it fitted to the symbol
+    pub(crate) fn detection(
+        &mut self,
+        format: BarcodeFormat,
+        points: &[Point],
+        grid: Option<&SamplingGrid>,
+    ) {
+        let stage = if grid.is_some() {
+            PipelineStage::Sampling
+        } else {
+            PipelineStage::Detection
+        };
+        self.stage(stage, |recorder| {
+            recorder.record_detection(&DetectionWitness {
+                format,
+                points: points.to_vec(),
+                patterns: grid.map_or_else(Vec::new, |grid| grid.patterns().to_vec()),
+            })
+        });
+    }
+
+    /// Records the symbol of `result`, which a 1D reader located and decoded in a row at once
+    pub(crate) fn scanline(&mut self, result: &RXingResult) {
+        self.stage(PipelineStage::Scanline, |recorder| {
+            recorder.record_detection(&DetectionWitness {
+                format: result.getBarcodeFormat().clone(),
+                points: result.getPoints().to_vec(),
+                patterns: Vec::new(),
+            })
+        });
+    }
+
+    /// Records the codewords the decoder corrected, if it keeps them
+    pub(crate) fn codewords(&mut self, codewords: Option<&CodewordWitness>) {
+        self.stage(PipelineStage::Decoding, |recorder| {
+            codewords.map_or(Ok(()), |codewords| recorder.record_codewords(codewords))
+        });
+    }
+
+    /// Records the bits of `result`, and warns in it if recording its symbol failed. The stages
+    /// recorded after are those of the next symbol
+    pub(crate) fn decoded_bits(&mut self, result: &mut RXingResult) {
+        self.stage(PipelineStage::TextAssembly, |recorder| {
+            recorder.record_decoded_bits(&TextAssemblyWitness::from_result(result))
+        });
+        if std::mem::take(&mut self.failed) {
+            result.addWarning(DecodeWarning::WitnessIncomplete);
+        }
+    }
+}
+
+fn record_binarization<B: Binarizer>(
+    recorder: &dyn WitnessRecorder,
+    image: &BinaryBitmap<B>,
+) -> Result<()> {
+    let binarizer = image.get_binarizer();
+    recorder.record_binarization(&BinarizationWitness {
+        threshold: binarizer.get_global_threshold(),
+        block_thresholds: binarizer.get_block_thresholds().cloned(),
+        ..BinarizationWitness::new(image.get_black_matrix().clone())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::HashSet,
+        sync::{Arc, Mutex},
+    };
+
+    use crate::{
+        common::{BitMatrix, CodewordWitness, FixedThresholdBinarizer, Result},
+        BarcodeFormat, BinarizationWitness, BinaryBitmap, DecodeHints, DecodeWarning,
+        DetectionWitness, Exceptions, Luma8LuminanceSource, MultiFormatReader, MultiFormatWriter,
+        PipelineWitness, Reader, TextAssemblyWitness, WitnessRecorder, Writer,
+    };
+
+    /// The names of the stages recorded, in order
+    #[derive(Default)]
+    struct StageNames(Mutex<Vec<&'static str>>);
+
+    impl WitnessRecorder for StageNames {
+        fn record_binarization(&self, _witness: &BinarizationWitness) -> Result<()> {
+            self.0.lock().unwrap().push("binarization");
+            Ok(())
+        }
+
+        fn record_detection(&self, _witness: &DetectionWitness) -> Result<()> {
+            self.0.lock().unwrap().push("detection");
+            Ok(())
+        }
+
+        fn record_codewords(&self, _witness: &CodewordWitness) -> Result<()> {
+            self.0.lock().unwrap().push("codewords");
+            Ok(())
+        }
+
+        fn record_decoded_bits(&self, _witness: &TextAssemblyWitness) -> Result<()> {
+            self.0.lock().unwrap().push("decoded_bits");
+            Ok(())
+        }
+    }
+
+    /// A recorder whose storage fails, like a full disk
+    struct FailingRecorder;
+
+    impl WitnessRecorder for FailingRecorder {
+        fn record_detection(&self, _witness: &DetectionWitness) -> Result<()> {
+            Err(Exceptions::illegal_state_with("no space left on device"))
+        }
+    }
+
+    fn bitmap(
+        contents: &str,
+        format: BarcodeFormat,
+    ) -> BinaryBitmap<FixedThresholdBinarizer<Luma8LuminanceSource>> {
+        let matrix: BitMatrix = MultiFormatWriter
+            .encode(contents, &format, 200, 100)
+            .unwrap();
+        let luma = matrix.to_luma8();
+        BinaryBitmap::new(FixedThresholdBinarizer::new(Luma8LuminanceSource::new(
+            luma,
+            matrix.getWidth(),
+            matrix.getHeight(),
+        )))
+    }
+
+    #[test]
+    fn test_stages_are_recorded_in_order() {
+        for format in [
+            BarcodeFormat::QR_CODE,
+            BarcodeFormat::DATA_MATRIX,
+            BarcodeFormat::AZTEC,
+            BarcodeFormat::PDF_417,
+        ] {
+            let recorder = Arc::new(StageNames::default());
+            let hints = DecodeHints {
+                WitnessRecorder: Some(recorder.clone()),
+                PossibleFormats: Some(HashSet::from([format.clone()])),
+                ..Default::default()
+            };
+            let result = MultiFormatReader::default()
+                .decode_with_hints(&mut bitmap("recorded", format.clone()), &hints)
+                .unwrap_or_else(|e| panic!("{format}: {e}"));
+            assert_eq!("recorded", result.getText());
+
+            let stages = recorder.0.lock().unwrap();
+            let expected = if format == BarcodeFormat::PDF_417 {
+                // PDF417 error corrects the codewords of the symbol without a `CodewordWitness`
+                vec!["binarization", "detection", "decoded_bits"]
+            } else {
+                vec!["binarization", "detection", "codewords", "decoded_bits"]
+            };
+            assert_eq!(expected, *stages, "{format}");
+        }
+    }
+
+    #[test]
+    fn test_recorded_witness_matches_extracted_witness() {
+        let recorder = Arc::new(Mutex::new(PipelineWitness::new()));
+        let hints = DecodeHints {
+            WitnessRecorder: Some(recorder.clone()),
+            ..Default::default()
+        };
+
+        let mut image = bitmap("witness recorder", BarcodeFormat::QR_CODE);
+        let result = MultiFormatReader::default()
+            .decode_with_hints(&mut image, &hints)
+            .unwrap();
+        let recorded = recorder.lock().unwrap().clone();
+        let extracted = crate::helpers::extract_witness_data_with_result(&image, &result).unwrap();
+        assert_eq!(extracted.decoding, recorded.decoding);
+        for (extracted, recorded) in [
+            (
+                format!("{:?}", extracted.binarization),
+                format!("{:?}", recorded.binarization),
+            ),
+            (
+                format!("{:?}", extracted.text_assembly),
+                format!("{:?}", recorded.text_assembly),
+            ),
+            (
+                format!("{:?}", extracted.detection.unwrap().patterns),
+                format!("{:?}", recorded.detection.unwrap().patterns),
+            ),
+        ] {
+            assert_eq!(extracted, recorded);
+        }
+    }
+
+    #[test]
+    fn test_failing_recorder_does_not_fail_the_decode() {
+        let hints = DecodeHints {
+            WitnessRecorder: Some(Arc::new(FailingRecorder)),
+            ..Default::default()
+        };
+        let result = MultiFormatReader::default()
+            .decode_with_hints(&mut bitmap("still read", BarcodeFormat::QR_CODE), &hints)
+            .unwrap();
+        assert_eq!("still read", result.getText());
+        assert!(result
+            .getWarnings()
+            .contains(&DecodeWarning::WitnessIncomplete));
+
+        let result = MultiFormatReader::default()
+            .decode_with_hints(
+                &mut bitmap("still read", BarcodeFormat::QR_CODE),
+                &DecodeHints::default(),
+            )
+            .unwrap();
+        assert!(result.getWarnings().is_empty());
+    }
+}
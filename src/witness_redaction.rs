@@ -0,0 +1,326 @@
+/*
+ * Redaction of witness images
+ *
+ * A witness holds the whole frame the symbol was found in, which may show documents, faces or
+ * anything else that was in front of the camera. Redacting the frame outside the symbol lets
+ * the witness be shared without what surrounds the symbol, while the pixels the proof is about
+ * stay as they were decoded.
+ */
+
+use crate::common::{BitMatrix, Result};
+use crate::{BinarizationWitness, CropRegion, Exceptions, LuminanceWitness, PipelineWitness};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// How the grayscale pixels outside the kept region are redacted
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RedactionMethod {
+    /// The pixels are set to 0
+    Zero,
+
+    /// Each pixel is set to the mean of the pixels within `radius` of it, a square of
+    /// `2 * radius + 1` pixels on a side. Only a radius well above the size of the details to
+    /// hide, such as text, makes them unreadable
+    Blur { radius: u32 },
+}
+
+/**
+ * Records that an image was redacted: every pixel outside `kept` was replaced.
+ *
+ * # Fields
+ * * `kept` - The region left as decoded, in the coordinates of the full frame
+ * * `method` - How the grayscale pixels outside `kept` were redacted. Binarized pixels outside
+ *   it are always set to white
+ */
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RedactionMask {
+    pub kept: CropRegion,
+    pub method: RedactionMethod,
+}
+
+impl RedactionMask {
+    /// Whether the pixel at (x, y) of the full frame was left as decoded
+    pub fn keeps(&self, x: u32, y: u32) -> bool {
+        let kept = &self.kept;
+        (kept.left..kept.left + kept.width).contains(&x)
+            && (kept.top..kept.top + kept.height).contains(&y)
+    }
+}
+
+impl LuminanceWitness {
+    /**
+     * Redacts every pixel outside `kept` with `method`, recording the mask.
+     *
+     * # Arguments
+     * * `kept` - The region to leave as it is, in the coordinates of the full frame and clipped
+     *   to the image
+     * * `method` - How to redact the other pixels
+     *
+     * # Returns
+     * The redacted image, or an error if `kept` does not overlap the image, the image was
+     * already redacted or `method` blurs with a radius of 0
+     */
+    pub fn redact(&self, kept: CropRegion, method: RedactionMethod) -> Result<Self> {
+        let extent = self.extent();
+        let mask = redaction_mask(self.redaction, kept.intersection(&extent)?, method)?;
+
+        let blurred = match method {
+            RedactionMethod::Zero => None,
+            RedactionMethod::Blur { radius } => Some(self.box_blur(radius as usize)),
+        };
+        let mut image = self.image.clone();
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if !mask.keeps(extent.left + x as u32, extent.top + y as u32) {
+                    let i = y * self.width + x;
+                    image[i] = blurred.as_ref().map_or(0, |blurred| blurred[i]);
+                }
+            }
+        }
+
+        Ok(Self {
+            image,
+            redaction: Some(mask),
+            ..self.clone()
+        })
+    }
+
+    // The mean of the pixels within `radius` of each pixel, from a summed-area table
+    fn box_blur(&self, radius: usize) -> Vec<u8> {
+        let (width, height) = (self.width, self.height);
+        let mut sums = vec![0u64; (width + 1) * (height + 1)];
+        for y in 0..height {
+            let mut row_sum = 0;
+            for x in 0..width {
+                row_sum += self.image[y * width + x] as u64;
+                sums[(y + 1) * (width + 1) + x + 1] = sums[y * (width + 1) + x + 1] + row_sum;
+            }
+        }
+
+        let mut blurred = Vec::with_capacity(width * height);
+        for y in 0..height {
+            let (top, bottom) = (y.saturating_sub(radius), (y + radius + 1).min(height));
+            for x in 0..width {
+                let (left, right) = (x.saturating_sub(radius), (x + radius + 1).min(width));
+                let sum = sums[bottom * (width + 1) + right] + sums[top * (width + 1) + left]
+                    - sums[top * (width + 1) + right]
+                    - sums[bottom * (width + 1) + left];
+                let count = ((bottom - top) * (right - left)) as u64;
+                blurred.push(((sum + count / 2) / count) as u8);
+            }
+        }
+        blurred
+    }
+}
+
+impl BinarizationWitness {
+    /**
+     * Sets every pixel outside `kept` to white, recording the mask. `method` is the one the
+     * grayscale image was redacted with, so that both stages record the same mask.
+     *
+     * # Returns
+     * The redacted image, or an error if `kept` does not overlap the image, the image was
+     * already redacted or `method` blurs with a radius of 0
+     */
+    pub fn redact(&self, kept: CropRegion, method: RedactionMethod) -> Result<Self> {
+        let extent = self.extent();
+        let mask = redaction_mask(self.redaction, kept.intersection(&extent)?, method)?;
+
+        let image = &self.binarized_image;
+        let mut binarized_image = BitMatrix::new(image.getWidth(), image.getHeight())?;
+        for y in 0..image.getHeight() {
+            for x in 0..image.getWidth() {
+                if image.get(x, y) && mask.keeps(extent.left + x, extent.top + y) {
+                    binarized_image.set(x, y);
+                }
+            }
+        }
+
+        Ok(Self {
+            binarized_image,
+            block_thresholds: self
+                .block_thresholds
+                .as_ref()
+                .map(|thresholds| thresholds.clear_outside(&mask.kept)),
+            redaction: Some(mask),
+            ..self.clone()
+        })
+    }
+}
+
+impl PipelineWitness {
+    /**
+     * Redacts the grayscale and binarized images outside the region around the symbol, see
+     * `symbol_region`, so that the witness can be shared without the rest of the frame. The
+     * mask is recorded with each image, and `verify` only checks the binarization of the
+     * pixels it kept.
+     *
+     * # Arguments
+     * * `margin` - How many pixels around the symbol to keep
+     * * `method` - How to redact the grayscale pixels
+     *
+     * # Returns
+     * The witness, unchanged if the region cannot be determined
+     */
+    pub fn redact_outside_symbol(mut self, margin: u32, method: RedactionMethod) -> Result<Self> {
+        if let Some(region) = self.symbol_region(margin) {
+            if let Some(luminance) = &self.luminance {
+                self.luminance = Some(luminance.redact(region, method)?);
+            }
+            if let Some(binarization) = &self.binarization {
+                self.binarization = Some(binarization.redact(region, method)?);
+            }
+        }
+        Ok(self)
+    }
+}
+
+fn redaction_mask(
+    redaction: Option<RedactionMask>,
+    kept: CropRegion,
+    method: RedactionMethod,
+) -> Result<RedactionMask> {
+    if let Some(redaction) = redaction {
+        return Err(Exceptions::illegal_state_with(format!(
+            "the image was already redacted, keeping {:?}",
+            redaction.kept
+        )));
+    }
+    if method == (RedactionMethod::Blur { radius: 0 }) {
+        return Err(Exceptions::illegal_argument_with(
+            "blurring with a radius of 0 would leave the image as it is",
+        ));
+    }
+    Ok(RedactionMask { kept, method })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn region(left: u32, top: u32, width: u32, height: u32) -> CropRegion {
+        CropRegion {
+            left,
+            top,
+            width,
+            height,
+        }
+    }
+
+    #[test]
+    fn test_redact_luminance() {
+        let image = (0..16).map(|i| i * 10).collect::<Vec<u8>>();
+        let luminance = LuminanceWitness::new(4, 4, image.clone());
+
+        let zeroed = luminance
+            .redact(region(1, 1, 2, 2), RedactionMethod::Zero)
+            .unwrap();
+        let expected = [0, 0, 0, 0, 0, 50, 60, 0, 0, 90, 100, 0, 0, 0, 0, 0];
+        assert_eq!(expected.to_vec(), zeroed.image);
+        assert_eq!(Some(region(1, 1, 2, 2)), zeroed.redaction.map(|m| m.kept));
+        let zero = RedactionMethod::Zero;
+        assert!(zeroed.redact(region(0, 0, 4, 4), zero).is_err());
+
+        // the corner averages the 2x2 pixels within reach, the kept pixel stays
+        let blurred = luminance
+            .redact(region(1, 1, 1, 1), RedactionMethod::Blur { radius: 1 })
+            .unwrap();
+        assert_eq!(25, blurred.get_pixel(0, 0));
+        assert_eq!(50, blurred.get_pixel(1, 1));
+        assert_eq!(100, blurred.get_pixel(2, 2));
+
+        let unblurred = RedactionMethod::Blur { radius: 0 };
+        assert!(luminance.redact(region(1, 1, 1, 1), unblurred).is_err());
+        assert!(luminance.redact(region(8, 8, 1, 1), zero).is_err());
+    }
+
+    #[test]
+    fn test_redact_cropped_binarization() {
+        let mut matrix = BitMatrix::new(4, 3).unwrap();
+        matrix.set(1, 1);
+        matrix.set(3, 2);
+        let binarization = BinarizationWitness::new(matrix)
+            .crop(region(1, 1, 3, 2))
+            .unwrap();
+
+        // the mask is in frame coordinates, and clipped to the image
+        let redacted = binarization
+            .redact(region(3, 2, 5, 5), RedactionMethod::Zero)
+            .unwrap();
+        assert!(!redacted.get_pixel(0, 0));
+        assert!(redacted.get_pixel(2, 1));
+        let mask = redacted.redaction.unwrap();
+        assert_eq!(region(3, 2, 1, 1), mask.kept);
+        assert!(mask.keeps(3, 2) && !mask.keeps(2, 2) && !mask.keeps(3, 1));
+    }
+
+    #[test]
+    fn test_redact_outside_symbol() {
+        use crate::common::FixedThresholdBinarizer;
+        use crate::{
+            BarcodeFormat, BinaryBitmap, EncodeHintValue, EncodeHints, Luma8LuminanceSource,
+            MultiFormatReader, MultiFormatWriter, Reader, Writer,
+        };
+
+        // a symbol with 3 pixels per module, next to stripes standing in for a document
+        let hints = EncodeHints::default().with(EncodeHintValue::Margin("0".to_owned()));
+        let symbol = MultiFormatWriter
+            .encode_with_hints("redacted", &BarcodeFormat::QR_CODE, 0, 0, &hints)
+            .unwrap();
+        let (width, height, left, top) = (200, 120, 100, 20);
+        let side = symbol.getWidth() * 3;
+        let stripe = |x: u32| if x < 80 && x % 4 < 2 { 0 } else { 255 };
+        let mut luma: Vec<u8> = (0..width * height).map(|i| stripe(i % width)).collect();
+        for y in 0..side {
+            for x in 0..side {
+                let dark = symbol.get(x / 3, y / 3);
+                luma[((top + y) * width + left + x) as usize] = if dark { 0 } else { 255 };
+            }
+        }
+        let mut bitmap = BinaryBitmap::new(FixedThresholdBinarizer::new(
+            Luma8LuminanceSource::new(luma, width, height),
+        ));
+        let result = MultiFormatReader::default().decode(&mut bitmap).unwrap();
+        let witness = crate::helpers::extract_witness_data_with_result(&bitmap, &result).unwrap();
+
+        let blur = RedactionMethod::Blur { radius: 4 };
+        let redacted = witness.clone().redact_outside_symbol(3, blur).unwrap();
+        let region = redacted.symbol_region(3).unwrap();
+        let luminance = redacted.luminance().unwrap();
+        let binarization = redacted.binarization().unwrap();
+        let mask = luminance.redaction.unwrap();
+        assert_eq!((region, blur), (mask.kept, mask.method));
+        assert_eq!(Some(mask), binarization.redaction);
+
+        // the stripes are smoothed and no longer binarized, the symbol is untouched
+        let original = witness.luminance().unwrap();
+        assert_eq!(0, original.get_pixel(40, 60));
+        assert!(witness.binarization().unwrap().get_pixel(40, 60));
+        assert!((100..160).contains(&luminance.get_pixel(40, 60)));
+        assert!((0..100).all(|x| !binarization.get_pixel(x, 60)));
+        for (x, y) in [(left, top), (left + side / 2, top + side / 2)] {
+            let (x, y) = (x as usize, y as usize);
+            assert_eq!(original.get_pixel(x, y), luminance.get_pixel(x, y));
+        }
+        assert!(redacted.verify().is_complete());
+
+        // the kept pixels are still checked
+        let mut tampered = redacted.clone();
+        let image = &mut tampered.luminance.as_mut().unwrap().image;
+        image[(top * width + left) as usize] = 255;
+        assert!(tampered.verify().binarization.is_failed());
+
+        #[cfg(feature = "serde")]
+        {
+            let json = serde_json::to_string(&redacted).unwrap();
+            let loaded = PipelineWitness::from_json(&json).unwrap();
+            assert_eq!(Some(mask), loaded.luminance().unwrap().redaction);
+            assert!(loaded.verify().is_complete());
+        }
+    }
+}
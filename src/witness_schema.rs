@@ -0,0 +1,287 @@
+/*
+ * Versioning of the serialized witness layout
+ *
+ * Witnesses are stored for as long as the proofs made from them are checked, so the layout
+ * they are serialized in is versioned, and witnesses written by older versions of the crate
+ * are migrated to the current layout when they are loaded.
+ */
+
+#[cfg(feature = "bincode")]
+use bincode::{
+    de::Decoder,
+    enc::Encoder,
+    error::{DecodeError, EncodeError},
+    Decode, Encode,
+};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{json, Map, Value};
+
+/**
+ * The version of the layout `PipelineWitness` is serialized in, written as its `version` key.
+ *
+ * # Schema
+ * A witness is a map of `version` and one key per recorded stage, in pipeline order:
+ * * `luminance` - `width`, `height` and `image`, the grayscale values row-major, as an array of
+ *   numbers in human readable formats and a byte string in binary formats. A cropped image has
+ *   a `crop_offset`, the `x` and `y` of its top left pixel in the full frame, a redacted image
+ *   a `redaction`, the `kept` region (`left`, `top`, `width` and `height`) and the `method`
+ * * `binarization` - `binarized_image`, a bit matrix, and optionally `threshold`,
+ *   `block_thresholds` (`width`, `height`, `block_size`, `blocks_wide`, `blocks_high` and
+ *   `thresholds`, one per block row-major), `crop_offset` and `redaction`
+ * * `detection` - `format`, `points` (each `x` and `y`) and `patterns` (each `kind` and
+ *   `center`), in the pixels of the full frame
+ * * `scanline` - `rows_scanned`, `row`, `reversed`, `runs`, the widths of the runs of the
+ *   row alternating from white, `start_pattern`, `characters`, `middle_pattern` and
+ *   `stop_pattern`, each pattern a `start` column, its run `counters` and the `value` it was
+ *   read as
+ * * `sampling` - `grid` (`width` and `height` in modules, `regions` each with `p0`, `p1` and
+ *   `transform`, and `patterns`), `modules`, a bit matrix, and optionally `fixed_point`, the
+ *   `frac_bits` and `coefficients` of each region's transform
+ * * `decoding` - `codeword_size`, `raw_codewords`, `blocks` (each `received`,
+ *   `num_data_codewords`, `correction` and `corrected`) and `corrected_codewords`
+ * * `text_assembly` - `raw_bytes`, `num_bits`, `byte_segments`, `text` and optionally
+ *   `symbology_identifier`
+ *
+ * The stages are followed by `provenance`, if recorded: `crate_version`, optionally
+ * `binarizer` and `threshold`, `hints`, a map of hint type to value, and `timings`, each a
+ * `stage` and its `start` and `end`, offsets from the start of the decode in `secs` and
+ * `nanos`. Timings written before they had a `start` and an `end` have an `elapsed` time
+ * instead, and load as starting and ending at zero.
+ *
+ * Bit matrices are written as described by `MatrixCompression`. Keys added within a version
+ * are optional, so older witnesses of the same version load without them.
+ *
+ * # Versions
+ * * 1 - The single stage `WitnessData`: a map of `width`, `height`, `image` and
+ *   `binarized_image`, later also `detection` (`format`, `grid` and `modules`) and
+ *   `codewords`. Written without a `version` key
+ * * 2 - The staged layout above. Written without a `version` key until the key was added
+ *
+ * `PipelineWitness::from_json` and `PipelineWitness::read_cbor` load every version up to this
+ * one. CBOR was only ever written in version 2.
+ *
+ * # bincode
+ * `PipelineWitness::write_bincode` writes the version and the stages in the order above,
+ * without keys, with bincode's standard configuration. The version comes first, as a varint,
+ * and every stage is an option. The optional keys of the stages are options too, and the
+ * keys left out of JSON when empty, such as `hints`, are written empty. Bit matrices are
+ * always `BitPacked`. bincode was first written in version 2, so
+ * `PipelineWitness::read_bincode` rejects every other version.
+ */
+pub const WITNESS_SCHEMA_VERSION: u32 = 2;
+
+/// The `version` key of a serialized witness, always `WITNESS_SCHEMA_VERSION` in memory
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct SchemaVersion;
+
+impl Serialize for SchemaVersion {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u32(WITNESS_SCHEMA_VERSION)
+    }
+}
+
+impl<'de> Deserialize<'de> for SchemaVersion {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let version = u32::deserialize(deserializer)?;
+        check_version(version as u64).map_err(de::Error::custom)?;
+        Ok(SchemaVersion)
+    }
+}
+
+// bincode has no keys, the version is written first as a number
+#[cfg(feature = "bincode")]
+impl Encode for SchemaVersion {
+    fn encode<E: Encoder>(&self, encoder: &mut E) -> Result<(), EncodeError> {
+        WITNESS_SCHEMA_VERSION.encode(encoder)
+    }
+}
+
+#[cfg(feature = "bincode")]
+impl<Context> Decode<Context> for SchemaVersion {
+    fn decode<D: Decoder<Context = Context>>(decoder: &mut D) -> Result<Self, DecodeError> {
+        let version = u32::decode(decoder)?;
+        if version != WITNESS_SCHEMA_VERSION {
+            return Err(DecodeError::OtherString(format!(
+                "bincode witness schema version {version} is not supported, only \
+                 {WITNESS_SCHEMA_VERSION} is"
+            )));
+        }
+        Ok(SchemaVersion)
+    }
+}
+
+#[cfg(feature = "bincode")]
+bincode::impl_borrow_decode!(SchemaVersion);
+
+fn check_version(version: u64) -> Result<(), String> {
+    if version == 0 || version > WITNESS_SCHEMA_VERSION as u64 {
+        return Err(format!(
+            "witness schema version {version} is not supported, versions 1 to \
+             {WITNESS_SCHEMA_VERSION} are"
+        ));
+    }
+    Ok(())
+}
+
+/**
+ * Migrates a witness parsed from JSON to the current layout, see `WITNESS_SCHEMA_VERSION`.
+ *
+ * Besides restructuring version 1, this gives the bit matrices written as bare arrays of
+ * booleans the dimensions they were written without: the binarized image has those of the
+ * grayscale image, the sampled modules those of the grid.
+ */
+pub(crate) fn migrate_json(witness: Value) -> Result<Value, String> {
+    let Value::Object(mut witness) = witness else {
+        return Err("a witness must be a map".to_owned());
+    };
+
+    let version = match witness.get("version") {
+        Some(version) => version
+            .as_u64()
+            .ok_or_else(|| format!("the witness schema version {version} is not a number"))?,
+        // the single stage witness had the dimensions of the image at the top
+        None if witness.contains_key("width") => 1,
+        None => 2,
+    };
+    check_version(version)?;
+
+    if version == 1 {
+        witness = migrate_from_version_1(witness);
+    }
+
+    let dimensions =
+        |stage: Option<&Value>| stage.map(|s| (s["width"].clone(), s["height"].clone()));
+    if let Some((width, height)) = dimensions(witness.get("luminance")) {
+        if let Some(binarization) = witness.get_mut("binarization") {
+            size_booleans(&mut binarization["binarized_image"], &width, &height);
+        }
+    }
+    if let Some(sampling) = witness.get_mut("sampling") {
+        if let Some((width, height)) = dimensions(sampling.get("grid")) {
+            size_booleans(&mut sampling["modules"], &width, &height);
+        }
+    }
+
+    witness.insert("version".to_owned(), json!(WITNESS_SCHEMA_VERSION));
+    Ok(Value::Object(witness))
+}
+
+// Splits the single stage witness into the stages of version 2
+fn migrate_from_version_1(mut witness: Map<String, Value>) -> Map<String, Value> {
+    let mut take = |key: &str| witness.remove(key).unwrap_or(Value::Null);
+    let (width, height, image) = (take("width"), take("height"), take("image"));
+    let binarized_image = take("binarized_image");
+    let detection = take("detection");
+    let codewords = take("codewords");
+
+    let mut stages = Map::new();
+    stages.insert(
+        "luminance".to_owned(),
+        json!({ "width": width, "height": height, "image": image }),
+    );
+    stages.insert(
+        "binarization".to_owned(),
+        json!({ "binarized_image": binarized_image }),
+    );
+    // the detection held the grid and the modules sampled with it, but not the result points
+    if let Value::Object(mut detection) = detection {
+        let format = detection.remove("format").unwrap_or(Value::Null);
+        let grid = detection.remove("grid").unwrap_or(Value::Null);
+        let patterns = grid.get("patterns").cloned().unwrap_or(json!([]));
+        let modules = detection.remove("modules").unwrap_or(Value::Null);
+        stages.insert(
+            "detection".to_owned(),
+            json!({ "format": format, "points": [], "patterns": patterns }),
+        );
+        stages.insert(
+            "sampling".to_owned(),
+            json!({ "grid": grid, "modules": modules }),
+        );
+    }
+    if !codewords.is_null() {
+        stages.insert("decoding".to_owned(), codewords);
+    }
+
+    stages
+}
+
+// Packs a bit matrix written as an array of `width * height` booleans, see `PackedBitMatrix`
+fn size_booleans(matrix: &mut Value, width: &Value, height: &Value) {
+    let (Value::Array(pixels), Some(w), Some(h)) = (&*matrix, width.as_u64(), height.as_u64())
+    else {
+        return;
+    };
+    if pixels.len() as u64 != w * h {
+        return;
+    }
+
+    let mut bits = vec![0u8; pixels.len().div_ceil(8)];
+    for (i, pixel) in pixels.iter().enumerate() {
+        if pixel.as_bool() == Some(true) {
+            bits[i / 8] |= 0x80 >> (i % 8);
+        }
+    }
+    *matrix = json!({ "width": w, "height": h, "bits": bits });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BarcodeFormat::QR_CODE;
+    use crate::PipelineWitness;
+
+    #[test]
+    fn test_migrate_version_1() {
+        // a witness written by the single stage WitnessData, with a 2x2 symbol
+        let grid = json!({
+            "width": 2,
+            "height": 2,
+            "regions": [{
+                "p0": { "x": 0.0, "y": 0.0 },
+                "p1": { "x": 2.0, "y": 2.0 },
+                "transform": {
+                    "a11": 1.0, "a12": 0.0, "a13": 0.0,
+                    "a21": 0.0, "a22": 1.0, "a23": 0.0,
+                    "a31": 0.0, "a32": 0.0, "a33": 1.0
+                }
+            }]
+        });
+        let version_1 = json!({
+            "width": 3,
+            "height": 2,
+            "image": [0, 255, 255, 255, 0, 255],
+            "binarized_image": [true, false, false, false, true, false],
+            "detection": {
+                "format": "QR_CODE",
+                "grid": grid,
+                "modules": [true, false, false, true]
+            }
+        });
+
+        let witness: PipelineWitness =
+            serde_json::from_value(migrate_json(version_1).unwrap()).unwrap();
+        let luminance = witness.luminance().unwrap();
+        assert_eq!((3, 2), (luminance.width, luminance.height));
+        assert_eq!(vec![0, 255, 255, 255, 0, 255], luminance.image);
+        let binarization = witness.binarization().unwrap();
+        assert!(binarization.get_pixel(0, 0) && binarization.get_pixel(1, 1));
+        assert!(!binarization.get_pixel(1, 0) && !binarization.get_pixel(2, 1));
+        assert_eq!(QR_CODE, witness.detection().unwrap().format);
+        let sampling = witness.sampling().unwrap();
+        assert_eq!(2, sampling.grid.width());
+        assert!(sampling.get_module(0, 0) && sampling.get_module(1, 1));
+        assert!(witness.decoding().is_none());
+    }
+
+    #[test]
+    fn test_versions() {
+        let empty = migrate_json(json!({})).unwrap();
+        assert_eq!(json!({ "version": WITNESS_SCHEMA_VERSION }), empty);
+        assert!(migrate_json(json!({ "version": WITNESS_SCHEMA_VERSION + 1 })).is_err());
+        assert!(migrate_json(json!({ "version": "2" })).is_err());
+        assert!(migrate_json(json!([])).is_err());
+
+        let newer = json!({ "version": WITNESS_SCHEMA_VERSION + 1 });
+        assert!(serde_json::from_value::<PipelineWitness>(newer).is_err());
+    }
+}
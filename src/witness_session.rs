@@ -0,0 +1,367 @@
+/*
+ * Hash-chained witnesses of a scan over several frames
+ *
+ * A scan from video decodes frame after frame until the symbol reads. A WitnessSession keeps
+ * the witness of each frame and links them in a hash chain starting from calibration data
+ * they share, so that a proof can establish that the frames were taken in order by the same
+ * camera, and not picked from separate scans.
+ */
+
+use crate::common::{MerkleHasher, Result};
+use crate::{Exceptions, PipelineWitness};
+
+/**
+ * What the frames of a session share, committed to at the start of its hash chain.
+ *
+ * # Fields
+ * * `width` - The width of every frame in pixels
+ * * `height` - The height of every frame in pixels
+ * * `data` - Further calibration, such as the camera intrinsics or exposure, encoded as the
+ *   caller sees fit
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionCalibration {
+    pub width: usize,
+    pub height: usize,
+    pub data: Vec<u8>,
+}
+
+impl SessionCalibration {
+    pub fn new(width: usize, height: usize, data: Vec<u8>) -> Self {
+        Self {
+            width,
+            height,
+            data,
+        }
+    }
+
+    /// The width and height, each as 8 big-endian bytes, followed by the data
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(16 + self.data.len());
+        bytes.extend_from_slice(&(self.width as u64).to_be_bytes());
+        bytes.extend_from_slice(&(self.height as u64).to_be_bytes());
+        bytes.extend_from_slice(&self.data);
+        bytes
+    }
+}
+
+/**
+ * One frame of a session.
+ *
+ * # Fields
+ * * `witness` - The witness of the frame
+ * * `digest` - The digest of the witness, see `frame_digest`
+ * * `link` - The hash of the previous link, or of the calibration for the first frame, and
+ *   `digest`
+ */
+#[derive(Debug, Clone)]
+pub struct SessionFrame<D> {
+    pub witness: PipelineWitness,
+    pub digest: D,
+    pub link: D,
+}
+
+/**
+ * The witnesses of consecutive frames of one scan, linked in a hash chain.
+ *
+ * The chain starts from the leaf hash of the calibration, `hash_leaf(calibration.to_bytes())`,
+ * and each frame extends it: `link = hash_nodes(previous link, frame digest)`. The last link,
+ * `head`, commits to the calibration and to every frame in order.
+ *
+ * ```
+ * # #[cfg(feature = "sha256")]
+ * # {
+ * use rxing::common::Sha256MerkleHasher;
+ * use rxing::{LuminanceWitness, PipelineWitness, SessionCalibration, WitnessSession};
+ *
+ * let calibration = SessionCalibration::new(2, 1, b"focal length 4.2mm".to_vec());
+ * let mut session = WitnessSession::new(Sha256MerkleHasher, calibration);
+ * for frame in [vec![0, 255], vec![10, 245]] {
+ *     let luminance = LuminanceWitness::new(2, 1, frame);
+ *     session.push(PipelineWitness::new().with_luminance(luminance)).unwrap();
+ * }
+ * assert_eq!(2, session.frames().len());
+ * assert!(session.verify().is_ok());
+ * # }
+ * ```
+ */
+pub struct WitnessSession<H: MerkleHasher> {
+    hasher: H,
+    calibration: SessionCalibration,
+    genesis: H::Digest,
+    frames: Vec<SessionFrame<H::Digest>>,
+}
+
+impl<H: MerkleHasher> WitnessSession<H> {
+    /**
+     * Starts a session with no frames.
+     *
+     * # Arguments
+     * * `hasher` - The hash function of the chain, and of the Merkle trees of the frames
+     * * `calibration` - What the frames share
+     */
+    pub fn new(mut hasher: H, calibration: SessionCalibration) -> Self {
+        let genesis = hasher.hash_leaf(&calibration.to_bytes());
+        Self {
+            hasher,
+            calibration,
+            genesis,
+            frames: Vec::new(),
+        }
+    }
+
+    /**
+     * Appends the witness of the next frame to the chain.
+     *
+     * # Returns
+     * The new head of the chain, or an error if the grayscale image of the witness does not
+     * have the dimensions of the calibration
+     */
+    pub fn push(&mut self, witness: PipelineWitness) -> Result<&H::Digest> {
+        self.check_dimensions(&witness)?;
+
+        let digest = frame_digest(&mut self.hasher, &witness);
+        let previous = self
+            .frames
+            .last()
+            .map_or(&self.genesis, |frame| &frame.link);
+        let link = self.hasher.hash_nodes(previous, &digest);
+        self.frames.push(SessionFrame {
+            witness,
+            digest,
+            link,
+        });
+        Ok(self.head())
+    }
+
+    pub fn calibration(&self) -> &SessionCalibration {
+        &self.calibration
+    }
+
+    pub fn frames(&self) -> &[SessionFrame<H::Digest>] {
+        &self.frames
+    }
+
+    pub fn into_frames(self) -> Vec<SessionFrame<H::Digest>> {
+        self.frames
+    }
+
+    /// The hash the chain starts from, committing to the calibration
+    pub fn genesis(&self) -> &H::Digest {
+        &self.genesis
+    }
+
+    /// The last link of the chain, the genesis if there are no frames yet
+    pub fn head(&self) -> &H::Digest {
+        self.frames
+            .last()
+            .map_or(&self.genesis, |frame| &frame.link)
+    }
+
+    /**
+     * Recomputes the chain from the calibration and the witnesses, checking that every digest
+     * and link is the one recorded.
+     *
+     * # Returns
+     * An error naming the first frame whose digest or link differs
+     */
+    pub fn verify(&mut self) -> Result<()> {
+        let mut previous = self.hasher.hash_leaf(&self.calibration.to_bytes());
+        if previous != self.genesis {
+            return Err(Exceptions::illegal_state_with(
+                "the genesis does not commit to the calibration",
+            ));
+        }
+
+        for (index, frame) in self.frames.iter().enumerate() {
+            if frame_digest(&mut self.hasher, &frame.witness) != frame.digest {
+                return Err(Exceptions::illegal_state_with(format!(
+                    "the digest of frame {index} does not commit to its witness"
+                )));
+            }
+            let link = self.hasher.hash_nodes(&previous, &frame.digest);
+            if link != frame.link {
+                return Err(Exceptions::illegal_state_with(format!(
+                    "frame {index} does not link to the frame before it"
+                )));
+            }
+            previous = link;
+        }
+
+        Ok(())
+    }
+
+    fn check_dimensions(&self, witness: &PipelineWitness) -> Result<()> {
+        let Some(luminance) = witness.luminance() else {
+            return Ok(());
+        };
+        let (width, height) = (self.calibration.width, self.calibration.height);
+        let extent = luminance.extent();
+        let fits = match luminance.crop_offset {
+            // a cropped image lies within the frame
+            Some(_) => {
+                (extent.left + extent.width) as usize <= width
+                    && (extent.top + extent.height) as usize <= height
+            }
+            None => (luminance.width, luminance.height) == (width, height),
+        };
+        if !fits {
+            return Err(Exceptions::illegal_argument_with(format!(
+                "frame {} is {}x{} at {:?}, the session's frames are {width}x{height}",
+                self.frames.len(),
+                luminance.width,
+                luminance.height,
+                luminance.crop_offset
+            )));
+        }
+        Ok(())
+    }
+}
+
+/**
+ * The digest of one frame's witness: `hash_nodes(hash_nodes(luminance, binarization), text)`.
+ *
+ * `luminance` and `binarization` are the roots of the row Merkle trees of the two images, see
+ * `PipelineWitness::luminance_merkle_tree`, and `text` is the leaf hash of the assembled raw
+ * bytes. Stages that were not recorded hash as the empty leaf.
+ */
+pub fn frame_digest<H: MerkleHasher>(hasher: &mut H, witness: &PipelineWitness) -> H::Digest {
+    let empty = hasher.hash_leaf(&[]);
+    let luminance = witness
+        .luminance_merkle_tree(hasher)
+        .map_or_else(|| empty.clone(), |tree| tree.root().clone());
+    let binarization = witness
+        .binarization_merkle_tree(hasher)
+        .map_or_else(|| empty.clone(), |tree| tree.root().clone());
+    let raw_bytes = witness
+        .text_assembly()
+        .map_or(&[][..], |text_assembly| &text_assembly.raw_bytes);
+    let text = hasher.hash_leaf(raw_bytes);
+
+    let images = hasher.hash_nodes(&luminance, &binarization);
+    hasher.hash_nodes(&images, &text)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::hash::{DefaultHasher, Hash, Hasher};
+
+    use super::*;
+    use crate::common::BitMatrix;
+    use crate::{BinarizationWitness, LuminanceWitness, TextAssemblyWitness};
+
+    // Not collision resistant, but deterministic and enough to check the chain
+    struct SipMerkleHasher;
+
+    impl MerkleHasher for SipMerkleHasher {
+        type Digest = u64;
+
+        fn hash_leaf(&mut self, data: &[u8]) -> u64 {
+            let mut hasher = DefaultHasher::new();
+            (0u8, data).hash(&mut hasher);
+            hasher.finish()
+        }
+
+        fn hash_nodes(&mut self, left: &u64, right: &u64) -> u64 {
+            let mut hasher = DefaultHasher::new();
+            (1u8, left, right).hash(&mut hasher);
+            hasher.finish()
+        }
+    }
+
+    fn frame(value: u8) -> PipelineWitness {
+        let image = vec![value, 255 - value, 128, value / 2, 0, 255];
+        PipelineWitness::new()
+            .with_luminance(LuminanceWitness::new(3, 2, image))
+            .with_binarization(BinarizationWitness::new(BitMatrix::new(3, 2).unwrap()))
+    }
+
+    fn new_session() -> WitnessSession<SipMerkleHasher> {
+        let calibration = SessionCalibration::new(3, 2, vec![1, 2, 3]);
+        WitnessSession::new(SipMerkleHasher, calibration)
+    }
+
+    #[test]
+    fn test_chain() {
+        let mut session = new_session();
+        let genesis = SipMerkleHasher.hash_leaf(&session.calibration().to_bytes());
+        assert_eq!(&genesis, session.head());
+
+        let first = *session.push(frame(0)).unwrap();
+        let second = *session.push(frame(40)).unwrap();
+        let h = &mut SipMerkleHasher;
+        let digests = [frame_digest(h, &frame(0)), frame_digest(h, &frame(40))];
+        assert_ne!(digests[0], digests[1]);
+        assert_eq!(first, h.hash_nodes(&genesis, &digests[0]));
+        assert_eq!(second, h.hash_nodes(&first, &digests[1]));
+        assert!(session.verify().is_ok());
+
+        // the same frames in another order, or with other calibration, end in another head
+        let mut reordered = WitnessSession::new(SipMerkleHasher, session.calibration().clone());
+        reordered.push(frame(40)).unwrap();
+        reordered.push(frame(0)).unwrap();
+        assert_ne!(session.head(), reordered.head());
+        let mut recalibrated = WitnessSession::new(
+            SipMerkleHasher,
+            SessionCalibration::new(3, 2, vec![1, 2, 4]),
+        );
+        recalibrated.push(frame(0)).unwrap();
+        assert_ne!(&first, recalibrated.head());
+    }
+
+    #[test]
+    fn test_frame_digest() {
+        let h = &mut SipMerkleHasher;
+        let empty = h.hash_leaf(&[]);
+        let images = h.hash_nodes(&empty, &empty);
+        assert_eq!(
+            h.hash_nodes(&images, &empty),
+            frame_digest(h, &PipelineWitness::new())
+        );
+
+        // the text is part of the digest
+        let text = TextAssemblyWitness {
+            raw_bytes: vec![0x40, 0x11],
+            num_bits: 16,
+            byte_segments: Vec::new(),
+            text: "@".to_owned(),
+            symbology_identifier: None,
+        };
+        let decoded = frame(0).with_text_assembly(text);
+        assert_ne!(frame_digest(h, &frame(0)), frame_digest(h, &decoded));
+    }
+
+    #[test]
+    fn test_verify_and_dimensions() {
+        let mut session = new_session();
+        for value in [0, 20, 40] {
+            session.push(frame(value)).unwrap();
+        }
+
+        let mut tampered = new_session();
+        for frame in session.frames().iter().cloned() {
+            tampered.frames.push(frame);
+        }
+        assert!(tampered.verify().is_ok());
+        tampered.frames[1].witness.luminance.as_mut().unwrap().image[0] = 1;
+        assert!(tampered.verify().is_err());
+        tampered.frames[1].witness = frame(20);
+        tampered.frames.swap(0, 2);
+        assert!(tampered.verify().is_err());
+
+        // every frame is as large as the calibration says, though it may be cropped
+        let large = PipelineWitness::new().with_luminance(LuminanceWitness::new(4, 2, vec![0; 8]));
+        assert!(session.push(large).is_err());
+        let crop = crate::CropRegion {
+            left: 1,
+            top: 1,
+            width: 2,
+            height: 1,
+        };
+        let cropped = frame(0).luminance().unwrap().crop(crop).unwrap();
+        assert!(session
+            .push(PipelineWitness::new().with_luminance(cropped))
+            .is_ok());
+        assert_eq!(4, session.into_frames().len());
+    }
+}
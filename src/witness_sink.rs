@@ -0,0 +1,519 @@
+/*
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use crate::{
+    common::{CodewordWitness, FixedPointGridSampler, Result},
+    oned::ScanlineWitness,
+    pipeline_witness::symbol_points,
+    BinarizationWitness, Binarizer, BinaryBitmap, CropRegion, DecodeHints, DecodeWarning,
+    DetectionWitness, LuminanceSource, LuminanceWitness, PipelineWitness, RXingResult,
+    RXingResultMetadataType, RXingResultMetadataValue, SamplingWitness, TextAssemblyWitness,
+    WitnessProvenance, WitnessRecording,
+};
+
+/**
+ * Receives the stages of a decode as the reader produces them, when set as the `WitnessSink`
+ * decode hint. Any reader streams into the sink, so witnesses can be captured with the usual
+ * entry points instead of a separate instrumented decode. Readers of several barcodes, like
+ * `QRCodeMultiReader`, stream the stages of every symbol they decode in turn.
+ *
+ * All methods default to doing nothing, so a sink only implements the stages it is interested
+ * in. The stages arrive in pipeline order; a stage the decode did not record (such as the
+ * sampling of a format whose detector keeps no sampling grid) is left out.
+ *
+ * Unlike a `WitnessRecorder`, which the readers call as they reach each stage, the sink only
+ * receives the stages of a decode that succeeded. If they can't be built, the result carries
+ * `DecodeWarning::WitnessIncomplete` rather than the decode failing.
+ *
+ * The hint holds the sink as an `Arc` rather than a `&mut` reference, as the hints are cloned
+ * into the readers and kept by the reusable ones, so the methods take `&self` and a sink that
+ * records keeps the stages behind a lock. A `Mutex<PipelineWitness>` is such a sink, each stage
+ * replacing the stage recorded before.
+ */
+pub trait WitnessSink: Send + Sync {
+    /// Called with the grayscale image the barcode was decoded from
+    fn luminance(&self, _witness: &LuminanceWitness) {}
+
+    /// Called with the binarized image the barcode was decoded from
+    fn binarization(&self, _witness: &BinarizationWitness) {}
+
+    /// Called with the points and patterns the detector located
+    fn detection(&self, _witness: &DetectionWitness) {}
+
+    /// Called with the row a 1D symbol was read from and the patterns matched in it
+    fn scanline(&self, _witness: &ScanlineWitness) {}
+
+    /// Called with the sampling grid and the modules sampled with it
+    fn sampling(&self, _witness: &SamplingWitness) {}
+
+    /// Called with the codewords and their error correction
+    fn decoding(&self, _witness: &CodewordWitness) {}
+
+    /// Called with the decoded bytes and text
+    fn text_assembly(&self, _witness: &TextAssemblyWitness) {}
+
+    /// Called last, with the crate version, binarizer, hints and timings of the decode
+    fn provenance(&self, _provenance: &WitnessProvenance) {}
+}
+
+impl WitnessSink for Mutex<PipelineWitness> {
+    fn luminance(&self, witness: &LuminanceWitness) {
+        collected(self).luminance = Some(witness.clone());
+    }
+
+    fn binarization(&self, witness: &BinarizationWitness) {
+        collected(self).binarization = Some(witness.clone());
+    }
+
+    fn detection(&self, witness: &DetectionWitness) {
+        collected(self).detection = Some(witness.clone());
+    }
+
+    fn scanline(&self, witness: &ScanlineWitness) {
+        collected(self).scanline = Some(witness.clone());
+    }
+
+    fn sampling(&self, witness: &SamplingWitness) {
+        collected(self).sampling = Some(witness.clone());
+    }
+
+    fn decoding(&self, witness: &CodewordWitness) {
+        collected(self).decoding = Some(witness.clone());
+    }
+
+    fn text_assembly(&self, witness: &TextAssemblyWitness) {
+        collected(self).text_assembly = Some(witness.clone());
+    }
+
+    fn provenance(&self, provenance: &WitnessProvenance) {
+        collected(self).provenance = Some(provenance.clone());
+    }
+}
+
+// Stages are replaced as a whole, so a panic while the lock was held leaves none half written
+pub(crate) fn collected(sink: &Mutex<PipelineWitness>) -> MutexGuard<'_, PipelineWitness> {
+    sink.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/**
+ * Sends every stage of the decode of `bitmap` that produced `result` to `sink`, followed by
+ * the provenance of the stages. Only the decode hints know the hints and timings of the
+ * decode, so the provenance holds the crate version and the binarizer.
+ *
+ * # Arguments
+ * * `sink` - The sink to send the stages to
+ * * `bitmap` - The BinaryBitmap that has been used for decoding
+ * * `result` - The result decoded from `bitmap`
+ */
+pub fn stream_witness<B: Binarizer>(
+    sink: &dyn WitnessSink,
+    bitmap: &BinaryBitmap<B>,
+    result: &RXingResult,
+) -> Result<()> {
+    stream(sink, bitmap, result, None, false, WitnessProvenance::new())
+}
+
+/**
+ * Sends every stage of the decode of `bitmap` that produced `result` to `sink`, with the
+ * grayscale and binarized images cropped to the region around the symbol, see
+ * `PipelineWitness::crop_to_symbol`.
+ *
+ * # Arguments
+ * * `sink` - The sink to send the stages to
+ * * `bitmap` - The BinaryBitmap that has been used for decoding
+ * * `result` - The result decoded from `bitmap`
+ * * `margin` - How many pixels around the symbol to keep on every side
+ */
+pub fn stream_cropped_witness<B: Binarizer>(
+    sink: &dyn WitnessSink,
+    bitmap: &BinaryBitmap<B>,
+    result: &RXingResult,
+    margin: u32,
+) -> Result<()> {
+    stream(
+        sink,
+        bitmap,
+        result,
+        Some(margin),
+        false,
+        WitnessProvenance::new(),
+    )
+}
+
+fn stream<B: Binarizer>(
+    sink: &dyn WitnessSink,
+    bitmap: &BinaryBitmap<B>,
+    result: &RXingResult,
+    crop_margin: Option<u32>,
+    fixed_point: bool,
+    provenance: WitnessProvenance,
+) -> Result<()> {
+    let binarizer = bitmap.get_binarizer();
+    let source = binarizer.get_luminance_source();
+    let mut luminance =
+        LuminanceWitness::new(source.get_width(), source.get_height(), source.get_matrix());
+
+    let binarized_matrix = bitmap.get_black_matrix();
+    let mut binarization = BinarizationWitness {
+        threshold: binarizer.get_global_threshold(),
+        block_thresholds: binarizer.get_block_thresholds().cloned(),
+        ..BinarizationWitness::new(binarized_matrix.clone())
+    };
+
+    let detection = DetectionWitness::from_result(result);
+    let sampling = match result
+        .getRXingResultMetadata()
+        .get(&RXingResultMetadataType::SAMPLING_GRID)
+    {
+        Some(RXingResultMetadataValue::SamplingGrid(grid)) if fixed_point => {
+            Some(SamplingWitness::new_fixed_point(
+                grid.clone(),
+                binarized_matrix,
+                FixedPointGridSampler::DEFAULT_FRAC_BITS,
+            )?)
+        }
+        Some(RXingResultMetadataValue::SamplingGrid(grid)) => {
+            Some(SamplingWitness::new(grid.clone(), binarized_matrix)?)
+        }
+        _ => None,
+    };
+
+    let points = symbol_points(&detection, sampling.as_ref());
+    if let Some(region) =
+        crop_margin.and_then(|margin| CropRegion::around(&points, margin, luminance.extent()))
+    {
+        luminance = luminance.crop(region)?;
+        binarization = binarization.crop(region)?;
+    }
+
+    sink.luminance(&luminance);
+    sink.binarization(&binarization);
+    sink.detection(&detection);
+    if let Some(RXingResultMetadataValue::Scanline(scanline)) = result
+        .getRXingResultMetadata()
+        .get(&RXingResultMetadataType::SCANLINE)
+    {
+        sink.scanline(scanline);
+    }
+    if let Some(sampling) = &sampling {
+        sink.sampling(sampling);
+    }
+
+    if let Some(RXingResultMetadataValue::Codewords(codewords)) = result
+        .getRXingResultMetadata()
+        .get(&RXingResultMetadataType::CODEWORDS)
+    {
+        sink.decoding(codewords);
+    }
+
+    sink.text_assembly(&TextAssemblyWitness::from_result(result));
+    sink.provenance(&provenance.with_binarizer(binarizer));
+
+    Ok(())
+}
+
+/// Streams the witness of a successful decode into the `WitnessSink` hint, if one is set, with
+/// the stages `recording` timed in its provenance. A sink that fails leaves `DecodeWarning::WitnessIncomplete` in the result instead of failing the
+/// decode
+pub(crate) fn record_witness<B: Binarizer>(
+    hints: &DecodeHints,
+    bitmap: &BinaryBitmap<B>,
+    mut result: RXingResult,
+    recording: &WitnessRecording,
+) -> RXingResult {
+    if let Some(sink) = &hints.WitnessSink {
+        let provenance = WitnessProvenance {
+            timings: recording.timings().to_vec(),
+            ..WitnessProvenance::new().with_hints(hints)
+        };
+        let crop_margin = hints.WitnessCropMargin;
+        let fixed_point = matches!(hints.FixedPointSampling, Some(true));
+        if stream(
+            sink.as_ref(),
+            bitmap,
+            &result,
+            crop_margin,
+            fixed_point,
+            provenance,
+        )
+        .is_err()
+        {
+            result.addWarning(DecodeWarning::WitnessIncomplete);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::HashSet,
+        sync::{Arc, Mutex},
+    };
+
+    use crate::{
+        common::{BitMatrix, FixedPointGridSampler, FixedThresholdBinarizer},
+        multi::{qrcode::QRCodeMultiReader, MultipleBarcodeReader},
+        BarcodeFormat, BinaryBitmap, DecodeHints, Luma8LuminanceSource, MultiFormatReader,
+        MultiFormatWriter, PipelineStage, PipelineWitness, RXingResultMetadataType, Reader,
+        TextAssemblyWitness, WitnessProvenance, WitnessSink, Writer,
+    };
+
+    #[derive(Default)]
+    struct StageNames(Mutex<Vec<&'static str>>);
+
+    impl WitnessSink for StageNames {
+        fn luminance(&self, _witness: &crate::LuminanceWitness) {
+            self.0.lock().unwrap().push("luminance");
+        }
+
+        fn binarization(&self, _witness: &crate::BinarizationWitness) {
+            self.0.lock().unwrap().push("binarization");
+        }
+
+        fn text_assembly(&self, _witness: &TextAssemblyWitness) {
+            self.0.lock().unwrap().push("text_assembly");
+        }
+    }
+
+    fn bitmap(
+        contents: &str,
+        format: BarcodeFormat,
+    ) -> BinaryBitmap<FixedThresholdBinarizer<Luma8LuminanceSource>> {
+        let matrix: BitMatrix = MultiFormatWriter
+            .encode(contents, &format, 200, 100)
+            .unwrap();
+        let luma = matrix.to_luma8();
+        BinaryBitmap::new(FixedThresholdBinarizer::new(Luma8LuminanceSource::new(
+            luma,
+            matrix.getWidth(),
+            matrix.getHeight(),
+        )))
+    }
+
+    #[test]
+    fn test_sink_matches_extracted_witness() {
+        let sink = Arc::new(Mutex::new(PipelineWitness::new()));
+        let hints = DecodeHints {
+            WitnessSink: Some(sink.clone()),
+            ..Default::default()
+        };
+
+        let mut image = bitmap("witness sink", BarcodeFormat::QR_CODE);
+        let result = MultiFormatReader::default()
+            .decode_with_hints(&mut image, &hints)
+            .unwrap();
+
+        let mut streamed = sink.lock().unwrap().clone();
+        let mut extracted =
+            crate::helpers::extract_witness_data_with_result(&image, &result).unwrap();
+        assert!(streamed.sampling.is_some());
+        assert!(streamed.decoding.is_some());
+        assert_eq!(Some(128), streamed.binarization.as_ref().unwrap().threshold);
+
+        // only the reader knows what the decode was asked for and when its stages ran
+        let provenance = streamed.provenance.take().unwrap();
+        assert_eq!("set", provenance.hints["WITNESS_SINK"]);
+        assert_eq!(
+            vec![
+                PipelineStage::Binarization,
+                PipelineStage::Sampling,
+                PipelineStage::Decoding,
+                PipelineStage::TextAssembly,
+            ],
+            provenance
+                .timings
+                .iter()
+                .map(|timing| timing.stage)
+                .collect::<Vec<_>>()
+        );
+        assert!(provenance
+            .timings
+            .windows(2)
+            .all(|pair| pair[0].start <= pair[0].end && pair[0].end <= pair[1].start));
+        let WitnessProvenance {
+            binarizer,
+            threshold,
+            ..
+        } = extracted.provenance.take().unwrap();
+        assert_eq!(binarizer, provenance.binarizer);
+        assert_eq!(threshold, provenance.threshold);
+        assert_eq!(format!("{extracted:?}"), format!("{streamed:?}"));
+    }
+
+    #[test]
+    fn test_multiple_symbols_stream_in_turn() {
+        let sink = Arc::new(StageNames::default());
+        let hints = DecodeHints {
+            WitnessSink: Some(sink.clone()),
+            ..Default::default()
+        };
+
+        let symbol: BitMatrix = MultiFormatWriter
+            .encode("twice", &BarcodeFormat::QR_CODE, 100, 100)
+            .unwrap();
+        let (width, height) = (symbol.getWidth() * 2, symbol.getHeight());
+        let mut luma = Vec::new();
+        for y in 0..height {
+            for x in 0..width {
+                let dark = symbol.get(x % symbol.getWidth(), y);
+                luma.push(if dark { 0 } else { 255 });
+            }
+        }
+        let mut image = BinaryBitmap::new(FixedThresholdBinarizer::new(Luma8LuminanceSource::new(
+            luma, width, height,
+        )));
+
+        let results = QRCodeMultiReader::new()
+            .decode_multiple_with_hints(&mut image, &hints)
+            .unwrap();
+        assert_eq!(2, results.len());
+        assert!(results.iter().all(|result| result
+            .getRXingResultMetadata()
+            .contains_key(&RXingResultMetadataType::SAMPLING_GRID)));
+        assert_eq!(
+            ["luminance", "binarization", "text_assembly"].repeat(2),
+            *sink.0.lock().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_deterministic_decode_is_untimed() {
+        let sink = Arc::new(Mutex::new(PipelineWitness::new()));
+        let hints = DecodeHints {
+            WitnessSink: Some(sink.clone()),
+            Deterministic: Some(true),
+            ..Default::default()
+        };
+
+        let mut image = bitmap("deterministic", BarcodeFormat::QR_CODE);
+        MultiFormatReader::default()
+            .decode_with_hints(&mut image, &hints)
+            .unwrap();
+        let provenance = sink.lock().unwrap().provenance.clone().unwrap();
+        assert_eq!("true", provenance.hints["DETERMINISTIC"]);
+        assert!(provenance.timings.is_empty());
+    }
+
+    #[test]
+    fn test_1d_decode_is_timed_under_scanline() {
+        let sink = Arc::new(Mutex::new(PipelineWitness::new()));
+        let hints = DecodeHints {
+            WitnessSink: Some(sink.clone()),
+            PossibleFormats: Some(HashSet::from([BarcodeFormat::CODE_128])),
+            ..Default::default()
+        };
+
+        let mut image = bitmap("12345678", BarcodeFormat::CODE_128);
+        MultiFormatReader::default()
+            .decode_with_hints(&mut image, &hints)
+            .unwrap();
+        let provenance = sink.lock().unwrap().provenance.clone().unwrap();
+        let stages: Vec<PipelineStage> = provenance
+            .timings
+            .iter()
+            .map(|timing| timing.stage)
+            .collect();
+        assert_eq!(
+            vec![PipelineStage::Scanline, PipelineStage::TextAssembly],
+            stages
+        );
+    }
+
+    #[test]
+    fn test_zk_deterministic_samples_in_fixed_point() {
+        let sink = Arc::new(Mutex::new(PipelineWitness::new()));
+        let hints = DecodeHints {
+            WitnessSink: Some(sink.clone()),
+            ..DecodeHints::zk_deterministic()
+        };
+
+        let mut image = bitmap("fixed point", BarcodeFormat::QR_CODE);
+        let result = MultiFormatReader::default()
+            .decode_with_hints(&mut image, &hints)
+            .unwrap();
+        assert_eq!("fixed point", result.getText());
+
+        let witness = sink.lock().unwrap().clone();
+        let sampling = witness.sampling().unwrap();
+        assert_eq!(
+            Some(FixedPointGridSampler::DEFAULT_FRAC_BITS),
+            sampling
+                .fixed_point
+                .as_ref()
+                .and_then(|transforms| transforms.first())
+                .map(|transform| transform.frac_bits)
+        );
+        let provenance = witness.provenance().unwrap();
+        assert_eq!("true", provenance.hints["FIXED_POINT_SAMPLING"]);
+        assert!(witness.verify().is_complete());
+    }
+
+    #[test]
+    fn test_fixed_point_sampling_of_other_2d_formats() {
+        for format in [BarcodeFormat::DATA_MATRIX, BarcodeFormat::AZTEC] {
+            let sink = Arc::new(Mutex::new(PipelineWitness::new()));
+            let hints = DecodeHints {
+                WitnessSink: Some(sink.clone()),
+                PossibleFormats: Some(HashSet::from([format.clone()])),
+                FixedPointSampling: Some(true),
+                ..Default::default()
+            };
+
+            let mut image = bitmap("fixed point", format.clone());
+            let result = MultiFormatReader::default()
+                .decode_with_hints(&mut image, &hints)
+                .unwrap_or_else(|e| panic!("{format}: {e}"));
+            assert_eq!("fixed point", result.getText());
+
+            let witness = sink.lock().unwrap().clone();
+            assert!(witness.sampling().unwrap().fixed_point.is_some());
+            assert!(witness.verify().sampling.is_passed(), "{format}");
+        }
+    }
+
+    #[test]
+    fn test_only_the_decoding_reader_streams() {
+        let sink = Arc::new(StageNames::default());
+        let hints = DecodeHints {
+            WitnessSink: Some(sink.clone()),
+            TryHarder: Some(true),
+            ..Default::default()
+        };
+
+        // a 1D barcode, which every 2D reader fails on before the 1D readers succeed
+        let mut image = bitmap("12345678", BarcodeFormat::CODE_128);
+        let result = MultiFormatReader::default()
+            .decode_with_hints(&mut image, &hints)
+            .unwrap();
+        assert_eq!("12345678", result.getText());
+        assert_eq!(
+            vec!["luminance", "binarization", "text_assembly"],
+            *sink.0.lock().unwrap()
+        );
+
+        // failed decodes send nothing
+        sink.0.lock().unwrap().clear();
+        let hints = DecodeHints {
+            PossibleFormats: Some(HashSet::from([BarcodeFormat::DATA_MATRIX])),
+            ..hints
+        };
+        assert!(MultiFormatReader::default()
+            .decode_with_hints(&mut image, &hints)
+            .is_err());
+        assert!(sink.0.lock().unwrap().is_empty());
+    }
+}
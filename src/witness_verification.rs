@@ -0,0 +1,504 @@
+/*
+ * Verification of witness data
+ *
+ * Replays the decode pipeline on the stages of a PipelineWitness and checks that each recorded
+ * stage follows from the stages it was computed from. This is the relation a zero-knowledge
+ * proof over the witness establishes, checked in native code.
+ */
+
+use crate::common::reedsolomon::{
+    get_predefined_genericgf, PredefinedGenericGF, ReedSolomonDecoder,
+};
+use crate::common::{BitMatrix, CodewordBlock, CodewordWitness, Result};
+use crate::datamatrix::decoder::Decoder as DataMatrixDecoder;
+use crate::oned::{
+    best_match, CODE_PATTERNS, CODE_START_A, CODE_START_C, L_AND_G_PATTERNS, L_PATTERNS,
+};
+use crate::qrcode::{cpp_port::decoder::Decode as decode_qr_cpp, decoder::qrcode_decoder};
+use crate::{BarcodeFormat, PipelineWitness, SamplingWitness};
+
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+/// The outcome of one check of a `VerificationReport`
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CheckOutcome {
+    /// The stage follows from the stages it was computed from
+    Passed,
+
+    /// The stage does not follow from the stages it was computed from, and why
+    Failed(String),
+
+    /// The check could not run, and why, for example because a stage it needs was not recorded
+    Skipped(String),
+}
+
+impl CheckOutcome {
+    pub fn is_passed(&self) -> bool {
+        matches!(self, CheckOutcome::Passed)
+    }
+
+    pub fn is_failed(&self) -> bool {
+        matches!(self, CheckOutcome::Failed(_))
+    }
+}
+
+/**
+ * The result of `PipelineWitness::verify`, one outcome per relation between the stages.
+ *
+ * # Fields
+ * * `binarization` - The binarized image is the grayscale image compared against the
+ *   recorded threshold
+ * * `sampling` - The modules are the binarized image sampled with the recorded grid
+ * * `scanline` - The patterns of a 1D symbol are runs of the recorded row, one after the
+ *   other, and each character matches best the pattern of the value it was read as
+ * * `codewords` - The modules decode to the recorded codewords, blocks and corrections
+ * * `error_correction` - Each block is error corrected as recorded, the blocks hold the raw
+ *   codewords and the corrected data codewords are assembled from the blocks
+ * * `text` - The modules decode to the recorded text
+ */
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerificationReport {
+    pub binarization: CheckOutcome,
+    pub sampling: CheckOutcome,
+    pub scanline: CheckOutcome,
+    pub codewords: CheckOutcome,
+    pub error_correction: CheckOutcome,
+    pub text: CheckOutcome,
+}
+
+impl VerificationReport {
+    /// The name and outcome of each check, in pipeline order
+    pub fn checks(&self) -> [(&'static str, &CheckOutcome); 6] {
+        [
+            ("binarization", &self.binarization),
+            ("sampling", &self.sampling),
+            ("scanline", &self.scanline),
+            ("codewords", &self.codewords),
+            ("error_correction", &self.error_correction),
+            ("text", &self.text),
+        ]
+    }
+
+    /// No check failed, though some may have been skipped
+    pub fn is_valid(&self) -> bool {
+        self.checks()
+            .iter()
+            .all(|(_, outcome)| !outcome.is_failed())
+    }
+
+    /// Every check that applies to the symbol ran and passed: a 1D symbol has no modules or
+    /// codewords to check, a 2D symbol no scanline
+    pub fn is_complete(&self) -> bool {
+        let one_d = !matches!(self.scanline, CheckOutcome::Skipped(_));
+        self.checks()
+            .iter()
+            .filter(|(name, _)| match one_d {
+                true => matches!(*name, "binarization" | "scanline"),
+                false => *name != "scanline",
+            })
+            .all(|(_, outcome)| outcome.is_passed())
+    }
+}
+
+impl PipelineWitness {
+    /**
+     * Checks that the recorded stages are consistent with each other by replaying the
+     * pipeline: the grayscale image is binarized again with the recorded threshold, the
+     * binarized image is sampled again with the recorded grid, the sampled modules are decoded
+     * again and each Reed-Solomon block is corrected again.
+     *
+     * Checks whose stages were not recorded are skipped. Reading codewords from the modules
+     * is replayed for QR codes and Data Matrix; for Aztec the layout of the modules depends on
+     * detector state the witness does not hold, so only the error correction is checked.
+     *
+     * The patterns of a Code 128, EAN or UPC symbol are matched again against the runs of the
+     * row it was read from. A 1D reader binarizes that row on its own, so the row is not
+     * compared with the binarized image.
+     *
+     * # Returns
+     * The outcome of each check
+     */
+    pub fn verify(&self) -> VerificationReport {
+        let replay = self.replay_decode();
+
+        VerificationReport {
+            binarization: self.verify_binarization(),
+            sampling: self.verify_sampling(),
+            scanline: self.verify_scanline(),
+            codewords: self.verify_codewords(&replay),
+            error_correction: self.verify_error_correction(),
+            text: self.verify_text(&replay),
+        }
+    }
+
+    fn verify_binarization(&self) -> CheckOutcome {
+        let (Some(luminance), Some(binarization)) = (self.luminance(), self.binarization()) else {
+            return CheckOutcome::Skipped(
+                "the luminance and binarization stages were not recorded".to_owned(),
+            );
+        };
+        // a global threshold binarizes black below it, block thresholds at or below them
+        let block_thresholds = binarization.block_thresholds.as_ref();
+        if binarization.threshold.is_none() && block_thresholds.is_none() {
+            return CheckOutcome::Skipped(
+                "no threshold was recorded, the binarizer used local thresholds".to_owned(),
+            );
+        }
+
+        let binarized = &binarization.binarized_image;
+        let (width, height) = (
+            binarized.getWidth() as usize,
+            binarized.getHeight() as usize,
+        );
+        if (width, height) != (luminance.width, luminance.height) {
+            return CheckOutcome::Failed(format!(
+                "the binarized image is {width}x{height}, the grayscale image {}x{}",
+                luminance.width, luminance.height
+            ));
+        }
+        if binarization.crop_offset != luminance.crop_offset {
+            return CheckOutcome::Failed(format!(
+                "the binarized image is cropped at {:?}, the grayscale image at {:?}",
+                binarization.crop_offset, luminance.crop_offset
+            ));
+        }
+
+        if binarization.redaction != luminance.redaction {
+            return CheckOutcome::Failed(format!(
+                "the binarized image is redacted with {:?}, the grayscale image with {:?}",
+                binarization.redaction, luminance.redaction
+            ));
+        }
+
+        // redacted pixels no longer hold what was binarized
+        let extent = luminance.extent();
+        let kept = |x: usize, y: usize| {
+            let (x, y) = (extent.left + x as u32, extent.top + y as u32);
+            luminance.redaction.is_none_or(|mask| mask.keeps(x, y))
+        };
+        let binarize = |x: usize, y: usize, value: u8| match binarization.threshold {
+            Some(threshold) => (value < threshold, threshold),
+            None => {
+                let (x, y) = (extent.left + x as u32, extent.top + y as u32);
+                let threshold = block_thresholds.map_or(0, |t| t.threshold_at(x, y));
+                (value <= threshold, threshold)
+            }
+        };
+        for y in 0..luminance.height {
+            for (x, &value) in luminance.row(y).iter().enumerate() {
+                let (black, threshold) = binarize(x, y, value);
+                if kept(x, y) && black != binarization.get_pixel(x, y) {
+                    let color = if black { "white" } else { "black" };
+                    return CheckOutcome::Failed(format!(
+                        "pixel ({x}, {y}) is {color}, but its luminance {value} is on the other \
+                         side of threshold {threshold}"
+                    ));
+                }
+            }
+        }
+
+        CheckOutcome::Passed
+    }
+
+    fn verify_sampling(&self) -> CheckOutcome {
+        let (Some(binarization), Some(sampling)) = (self.binarization(), self.sampling()) else {
+            return CheckOutcome::Skipped(
+                "the binarization and sampling stages were not recorded".to_owned(),
+            );
+        };
+
+        // the grid maps to the full frame, so a cropped image is put back in place
+        let image = match binarization.uncropped_image() {
+            Ok(image) => image,
+            Err(err) => return CheckOutcome::Failed(format!("the binarized image: {err}")),
+        };
+        let grid = sampling.grid.clone();
+        let resampled = match &sampling.fixed_point {
+            None => SamplingWitness::new(grid, &image),
+            Some(transforms) => {
+                // the recorded transforms must be the ones derived from the grid
+                let frac_bits = transforms.first().map_or(0, |t| t.frac_bits);
+                match SamplingWitness::new_fixed_point(grid, &image, frac_bits) {
+                    Ok(resampled) if resampled.fixed_point.as_ref() != Some(transforms) => {
+                        return CheckOutcome::Failed(
+                            "the fixed-point transforms do not match the grid".to_owned(),
+                        );
+                    }
+                    resampled => resampled,
+                }
+            }
+        };
+
+        match resampled {
+            Ok(resampled) => match first_difference(&resampled.modules, &sampling.modules) {
+                None => CheckOutcome::Passed,
+                Some(difference) => CheckOutcome::Failed(format!("the modules {difference}")),
+            },
+            Err(err) => CheckOutcome::Failed(format!("the grid cannot be sampled: {err}")),
+        }
+    }
+
+    // Decodes the recorded modules again, with the decoder the reader of the format uses
+    fn replay_decode(&self) -> Option<Result<(Option<CodewordWitness>, String)>> {
+        let format = self.detection()?.format.clone();
+        let modules = &self.sampling()?.modules;
+
+        let replay = match format {
+            BarcodeFormat::QR_CODE
+            | BarcodeFormat::MICRO_QR_CODE
+            | BarcodeFormat::RECTANGULAR_MICRO_QR_CODE => {
+                let cpp = decode_qr_cpp(modules).and_then(|result| match result.error() {
+                    None => Ok((result.codewords().cloned(), result.text())),
+                    Some(err) => Err(err.clone()),
+                });
+                // the QR code reader falls back to the Java port, so the replay does too
+                match cpp {
+                    Err(_) if format == BarcodeFormat::QR_CODE => {
+                        qrcode_decoder::decode_bitmatrix(modules).map(|result| {
+                            (result.getCodewords().cloned(), result.getText().to_owned())
+                        })
+                    }
+                    cpp => cpp,
+                }
+            }
+            BarcodeFormat::DATA_MATRIX => DataMatrixDecoder::new()
+                .decode(modules)
+                .map(|result| (result.getCodewords().cloned(), result.getText().to_owned())),
+            _ => return None,
+        };
+
+        Some(replay)
+    }
+
+    fn verify_codewords(
+        &self,
+        replay: &Option<Result<(Option<CodewordWitness>, String)>>,
+    ) -> CheckOutcome {
+        let Some(decoding) = self.decoding() else {
+            return CheckOutcome::Skipped("the decoding stage was not recorded".to_owned());
+        };
+
+        match replay {
+            Some(Ok((Some(codewords), _))) if codewords == decoding => CheckOutcome::Passed,
+            Some(Ok((Some(codewords), _))) if codewords.raw_codewords != decoding.raw_codewords => {
+                CheckOutcome::Failed(
+                    "the modules hold different codewords than recorded".to_owned(),
+                )
+            }
+            Some(Ok((Some(_), _))) => CheckOutcome::Failed(
+                "the codewords are not split and corrected as recorded".to_owned(),
+            ),
+            Some(Ok((None, _))) => {
+                CheckOutcome::Failed("decoding the modules did not record codewords".to_owned())
+            }
+            Some(Err(err)) => CheckOutcome::Failed(format!("the modules cannot be decoded: {err}")),
+            None => self.replay_skipped(),
+        }
+    }
+
+    fn verify_scanline(&self) -> CheckOutcome {
+        let Some(scanline) = self.scanline() else {
+            return CheckOutcome::Skipped("the scanline stage was not recorded".to_owned());
+        };
+        if scanline.rows_scanned.last() != Some(&scanline.row) {
+            return CheckOutcome::Failed(format!(
+                "row {} is not the last of the rows scanned",
+                scanline.row
+            ));
+        }
+        if let Some(mismatch) = scanline.pattern_mismatch() {
+            return CheckOutcome::Failed(mismatch);
+        }
+        let Some(detection) = self.detection() else {
+            return CheckOutcome::Skipped("the detection stage was not recorded".to_owned());
+        };
+
+        // the tables the readers match each character against
+        let format = &detection.format;
+        let value = |i: usize, counters: &[u32]| match format {
+            BarcodeFormat::CODE_128 => best_match(counters, &CODE_PATTERNS),
+            BarcodeFormat::EAN_8 => best_match(counters, &L_PATTERNS),
+            BarcodeFormat::EAN_13 | BarcodeFormat::UPC_A if i >= 6 => {
+                best_match(counters, &L_PATTERNS)
+            }
+            _ => best_match(counters, &L_AND_G_PATTERNS),
+        };
+        if !matches!(
+            format,
+            BarcodeFormat::CODE_128
+                | BarcodeFormat::EAN_13
+                | BarcodeFormat::UPC_A
+                | BarcodeFormat::EAN_8
+                | BarcodeFormat::UPC_E
+        ) {
+            return CheckOutcome::Skipped(format!(
+                "matching the characters of {format} is not supported"
+            ));
+        }
+
+        if *format == BarcodeFormat::CODE_128 {
+            let starts = &CODE_PATTERNS[CODE_START_A as usize..=CODE_START_C as usize];
+            let start = best_match(&scanline.start_pattern.counters, starts)
+                .map(|i| i as u32 + CODE_START_A as u32);
+            if start != scanline.start_pattern.value {
+                return CheckOutcome::Failed(format!(
+                    "the start code matches {start:?}, not {:?}",
+                    scanline.start_pattern.value
+                ));
+            }
+        }
+        for (i, character) in scanline.characters.iter().enumerate() {
+            let matched = value(i, &character.counters).map(|value| value as u32);
+            if matched != character.value {
+                return CheckOutcome::Failed(format!(
+                    "character {i} at column {} matches {matched:?}, not {:?}",
+                    character.start, character.value
+                ));
+            }
+        }
+
+        CheckOutcome::Passed
+    }
+
+    fn verify_text(
+        &self,
+        replay: &Option<Result<(Option<CodewordWitness>, String)>>,
+    ) -> CheckOutcome {
+        let Some(text_assembly) = self.text_assembly() else {
+            return CheckOutcome::Skipped("the text assembly stage was not recorded".to_owned());
+        };
+
+        match replay {
+            Some(Ok((_, text))) if *text == text_assembly.text => CheckOutcome::Passed,
+            Some(Ok((_, text))) => CheckOutcome::Failed(format!(
+                "the modules decode to {text:?}, not {:?}",
+                text_assembly.text
+            )),
+            Some(Err(err)) => CheckOutcome::Failed(format!("the modules cannot be decoded: {err}")),
+            None => self.replay_skipped(),
+        }
+    }
+
+    fn replay_skipped(&self) -> CheckOutcome {
+        match (self.detection(), self.sampling()) {
+            (Some(detection), Some(_)) => CheckOutcome::Skipped(format!(
+                "decoding sampled modules is not supported for {}",
+                detection.format
+            )),
+            _ => CheckOutcome::Skipped(
+                "the detection and sampling stages were not recorded".to_owned(),
+            ),
+        }
+    }
+
+    fn verify_error_correction(&self) -> CheckOutcome {
+        let (Some(detection), Some(decoding)) = (self.detection(), self.decoding()) else {
+            return CheckOutcome::Skipped(
+                "the detection and decoding stages were not recorded".to_owned(),
+            );
+        };
+        let Some(field) = reed_solomon_field(&detection.format, decoding.codeword_size) else {
+            return CheckOutcome::Skipped(format!(
+                "no Reed-Solomon field is known for {} with {} bit codewords",
+                detection.format, decoding.codeword_size
+            ));
+        };
+
+        let rs_decoder = ReedSolomonDecoder::new(get_predefined_genericgf(field));
+        for (i, block) in decoding.blocks.iter().enumerate() {
+            match CodewordBlock::correct(
+                &rs_decoder,
+                block.received.clone(),
+                block.num_data_codewords,
+            ) {
+                Ok(corrected) if corrected == *block => {}
+                Ok(_) => {
+                    return CheckOutcome::Failed(format!("block {i} is not corrected as recorded"))
+                }
+                Err(err) => {
+                    return CheckOutcome::Failed(format!("block {i} cannot be corrected: {err}"))
+                }
+            }
+        }
+
+        let mut received: Vec<i32> = decoding
+            .blocks
+            .iter()
+            .flat_map(|block| block.received.iter().copied())
+            .collect();
+        let mut raw_codewords = decoding.raw_codewords.clone();
+        received.sort_unstable();
+        raw_codewords.sort_unstable();
+        if received != raw_codewords {
+            return CheckOutcome::Failed("the blocks do not hold the raw codewords".to_owned());
+        }
+
+        if assemble_data_codewords(&detection.format, &decoding.blocks)
+            != decoding.corrected_codewords
+        {
+            return CheckOutcome::Failed(
+                "the corrected codewords are not assembled from the blocks".to_owned(),
+            );
+        }
+
+        CheckOutcome::Passed
+    }
+}
+
+fn reed_solomon_field(format: &BarcodeFormat, codeword_size: u32) -> Option<PredefinedGenericGF> {
+    match (format, codeword_size) {
+        (
+            BarcodeFormat::QR_CODE
+            | BarcodeFormat::MICRO_QR_CODE
+            | BarcodeFormat::RECTANGULAR_MICRO_QR_CODE,
+            8,
+        ) => Some(PredefinedGenericGF::QrCodeField256),
+        (BarcodeFormat::DATA_MATRIX, 8) => Some(PredefinedGenericGF::DataMatrixField256),
+        (BarcodeFormat::AZTEC, 6) => Some(PredefinedGenericGF::AztecData6),
+        (BarcodeFormat::AZTEC, 8) => Some(PredefinedGenericGF::AztecData8),
+        (BarcodeFormat::AZTEC, 10) => Some(PredefinedGenericGF::AztecData10),
+        (BarcodeFormat::AZTEC, 12) => Some(PredefinedGenericGF::AztecData12),
+        _ => None,
+    }
+}
+
+// Data Matrix interleaves the data codewords of its blocks, the other symbologies concatenate them
+fn assemble_data_codewords(format: &BarcodeFormat, blocks: &[CodewordBlock]) -> Vec<i32> {
+    if *format != BarcodeFormat::DATA_MATRIX {
+        return blocks
+            .iter()
+            .flat_map(|block| block.data_codewords().iter().copied())
+            .collect();
+    }
+
+    let total = blocks.iter().map(|block| block.num_data_codewords).sum();
+    let mut codewords = vec![0; total];
+    for (j, block) in blocks.iter().enumerate() {
+        for (i, codeword) in block.data_codewords().iter().enumerate() {
+            if let Some(slot) = codewords.get_mut(i * blocks.len() + j) {
+                *slot = *codeword;
+            }
+        }
+    }
+    codewords
+}
+
+fn first_difference(actual: &BitMatrix, expected: &BitMatrix) -> Option<String> {
+    if (actual.getWidth(), actual.getHeight()) != (expected.getWidth(), expected.getHeight()) {
+        return Some(format!(
+            "are {}x{}, not {}x{}",
+            actual.getWidth(),
+            actual.getHeight(),
+            expected.getWidth(),
+            expected.getHeight()
+        ));
+    }
+
+    (0..actual.getHeight())
+        .flat_map(|y| (0..actual.getWidth()).map(move |x| (x, y)))
+        .find(|&(x, y)| actual.get(x, y) != expected.get(x, y))
+        .map(|(x, y)| format!("differ at ({x}, {y})"))
+}
@@ -0,0 +1,603 @@
+/*
+ * Streaming writer for witness data
+ *
+ * Writes the stages of a PipelineWitness one after another, and the images of the luminance
+ * and binarization stages row by row, so a witness never has to be held in memory in full.
+ */
+
+use std::io::{self, Write};
+
+use crate::common::{BitMatrix, CodewordWitness};
+use crate::oned::ScanlineWitness;
+use crate::{
+    DetectionWitness, LuminanceSource, PipelineStage, PipelineWitness, SamplingWitness,
+    TextAssemblyWitness, WitnessProvenance, WITNESS_SCHEMA_VERSION,
+};
+
+/// The encoding a `WitnessWriter` writes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WitnessFormat {
+    /// The same JSON `PipelineWitness::save_to_json` writes, without the pretty printing
+    Json,
+    /// The same CBOR `PipelineWitness::write_cbor` writes, except that the map of stages and
+    /// the byte strings of the images are indefinite length, with one chunk per row
+    #[cfg(feature = "cbor")]
+    Cbor,
+}
+
+/**
+ * Writes witness data stage by stage to any `io::Write`.
+ *
+ * Stages must be written in pipeline order and each at most once; the output is equivalent
+ * to serializing a `PipelineWitness` holding the same stages. Nothing is buffered, so wrap
+ * unbuffered writers such as files in an `io::BufWriter`.
+ *
+ * ```
+ * use rxing::{WitnessFormat, WitnessWriter};
+ *
+ * let mut writer = WitnessWriter::new(Vec::new(), WitnessFormat::Json).unwrap();
+ * writer
+ *     .write_luminance(2, 2, [[0u8, 255].as_slice(), [255, 0].as_slice()])
+ *     .unwrap();
+ * let json = writer.finish().unwrap();
+ * assert_eq!(
+ *     String::from_utf8(json).unwrap(),
+ *     r#"{"version":2,"luminance":{"width":2,"height":2,"image":[0,255,255,0]}}"#
+ * );
+ * ```
+ */
+pub struct WitnessWriter<W: Write> {
+    writer: W,
+    format: WitnessFormat,
+    last_stage: Option<PipelineStage>,
+    wrote_provenance: bool,
+}
+
+impl<W: Write> WitnessWriter<W> {
+    /**
+     * Creates a WitnessWriter and writes the start of the witness, with its schema version,
+     * to `writer`.
+     *
+     * # Arguments
+     * * `writer` - Where to write the witness to
+     * * `format` - The encoding to write
+     */
+    pub fn new(mut writer: W, format: WitnessFormat) -> io::Result<Self> {
+        match format {
+            WitnessFormat::Json => write!(writer, r#"{{"version":{WITNESS_SCHEMA_VERSION}"#)?,
+            #[cfg(feature = "cbor")]
+            WitnessFormat::Cbor => {
+                writer.write_all(&[CBOR_INDEFINITE_MAP])?;
+                cbor_text(&mut writer, "version")?;
+                cbor_head(&mut writer, CBOR_UNSIGNED, WITNESS_SCHEMA_VERSION as u64)?;
+            }
+        }
+
+        Ok(Self {
+            writer,
+            format,
+            last_stage: None,
+            wrote_provenance: false,
+        })
+    }
+
+    /**
+     * Writes the luminance stage, one row at a time.
+     *
+     * # Arguments
+     * * `width` - The width of the image in pixels
+     * * `height` - The height of the image in pixels
+     * * `rows` - The `height` rows of the image, each `width` grayscale values
+     */
+    pub fn write_luminance<I>(&mut self, width: usize, height: usize, rows: I) -> io::Result<()>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[u8]>,
+    {
+        self.begin_stage(PipelineStage::Luminance)?;
+
+        let mut rows_written = 0;
+        match self.format {
+            WitnessFormat::Json => {
+                write!(
+                    self.writer,
+                    r#"{{"width":{width},"height":{height},"image":["#
+                )?;
+                let mut line = String::new();
+                for row in rows {
+                    let row = check_row(row.as_ref(), width, rows_written, height)?;
+                    line.clear();
+                    for (x, value) in row.iter().enumerate() {
+                        if x > 0 || rows_written > 0 {
+                            line.push(',');
+                        }
+                        line.push_str(&value.to_string());
+                    }
+                    self.writer.write_all(line.as_bytes())?;
+                    rows_written += 1;
+                }
+                self.writer.write_all(b"]}")?;
+            }
+            #[cfg(feature = "cbor")]
+            WitnessFormat::Cbor => {
+                cbor_head(&mut self.writer, CBOR_MAP, 3)?;
+                cbor_text(&mut self.writer, "width")?;
+                cbor_head(&mut self.writer, CBOR_UNSIGNED, width as u64)?;
+                cbor_text(&mut self.writer, "height")?;
+                cbor_head(&mut self.writer, CBOR_UNSIGNED, height as u64)?;
+                cbor_text(&mut self.writer, "image")?;
+                self.writer.write_all(&[CBOR_INDEFINITE_BYTES])?;
+                for row in rows {
+                    let row = check_row(row.as_ref(), width, rows_written, height)?;
+                    if !row.is_empty() {
+                        cbor_head(&mut self.writer, CBOR_BYTES, row.len() as u64)?;
+                        self.writer.write_all(row)?;
+                    }
+                    rows_written += 1;
+                }
+                self.writer.write_all(&[CBOR_BREAK])?;
+            }
+        }
+
+        if rows_written != height {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("expected {height} rows, got {rows_written}"),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /**
+     * Writes the luminance stage from a luminance source, fetching one row at a time.
+     */
+    pub fn write_luminance_source<L: LuminanceSource>(&mut self, source: &L) -> io::Result<()> {
+        let (width, height) = (source.get_width(), source.get_height());
+        let mut failed_row = None;
+        let rows = (0..height).map_while(|y| {
+            let row = source.get_row(y);
+            if row.is_none() {
+                failed_row = Some(y);
+            }
+            row
+        });
+        let written = self.write_luminance(width, height, rows);
+
+        match failed_row {
+            Some(y) => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("could not read row {y} of the luminance source"),
+            )),
+            None => written,
+        }
+    }
+
+    /**
+     * Writes the binarization stage, one row of the binarized image at a time.
+     *
+     * # Arguments
+     * * `binarized_image` - The binarized image
+     * * `threshold` - The threshold the whole image was binarized with, if there was one
+     */
+    pub fn write_binarization(
+        &mut self,
+        binarized_image: &BitMatrix,
+        threshold: Option<u8>,
+    ) -> io::Result<()> {
+        self.begin_stage(PipelineStage::Binarization)?;
+
+        let (width, height) = (binarized_image.getWidth(), binarized_image.getHeight());
+        match self.format {
+            WitnessFormat::Json => {
+                self.writer.write_all(br#"{"binarized_image":["#)?;
+                let mut line = String::new();
+                for y in 0..height {
+                    line.clear();
+                    for x in 0..width {
+                        if x > 0 || y > 0 {
+                            line.push(',');
+                        }
+                        line.push_str(if binarized_image.get(x, y) {
+                            "true"
+                        } else {
+                            "false"
+                        });
+                    }
+                    self.writer.write_all(line.as_bytes())?;
+                }
+                self.writer.write_all(b"]")?;
+                if let Some(threshold) = threshold {
+                    write!(self.writer, r#","threshold":{threshold}"#)?;
+                }
+                self.writer.write_all(b"}")?;
+            }
+            #[cfg(feature = "cbor")]
+            WitnessFormat::Cbor => {
+                let fields = if threshold.is_some() { 2 } else { 1 };
+                cbor_head(&mut self.writer, CBOR_MAP, fields)?;
+                cbor_text(&mut self.writer, "binarized_image")?;
+                cbor_head(&mut self.writer, CBOR_MAP, 3)?;
+                cbor_text(&mut self.writer, "width")?;
+                cbor_head(&mut self.writer, CBOR_UNSIGNED, width as u64)?;
+                cbor_text(&mut self.writer, "height")?;
+                cbor_head(&mut self.writer, CBOR_UNSIGNED, height as u64)?;
+                cbor_text(&mut self.writer, "bits")?;
+                self.writer.write_all(&[CBOR_INDEFINITE_BYTES])?;
+
+                // pack the pixels row-major across row boundaries, like `PackedBitMatrix`
+                let mut chunk = Vec::with_capacity(width as usize / 8 + 1);
+                let (mut byte, mut bits) = (0u8, 0);
+                for y in 0..height {
+                    chunk.clear();
+                    for x in 0..width {
+                        if binarized_image.get(x, y) {
+                            byte |= 0x80 >> bits;
+                        }
+                        bits += 1;
+                        if bits == 8 {
+                            chunk.push(byte);
+                            (byte, bits) = (0, 0);
+                        }
+                    }
+                    if y == height - 1 && bits > 0 {
+                        chunk.push(byte);
+                    }
+                    if !chunk.is_empty() {
+                        cbor_head(&mut self.writer, CBOR_BYTES, chunk.len() as u64)?;
+                        self.writer.write_all(&chunk)?;
+                    }
+                }
+                self.writer.write_all(&[CBOR_BREAK])?;
+
+                if let Some(threshold) = threshold {
+                    cbor_text(&mut self.writer, "threshold")?;
+                    cbor_head(&mut self.writer, CBOR_UNSIGNED, threshold as u64)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn write_detection(&mut self, detection: &DetectionWitness) -> io::Result<()> {
+        self.write_stage(PipelineStage::Detection, detection)
+    }
+
+    pub fn write_scanline(&mut self, scanline: &ScanlineWitness) -> io::Result<()> {
+        self.write_stage(PipelineStage::Scanline, scanline)
+    }
+
+    pub fn write_sampling(&mut self, sampling: &SamplingWitness) -> io::Result<()> {
+        self.write_stage(PipelineStage::Sampling, sampling)
+    }
+
+    pub fn write_decoding(&mut self, decoding: &CodewordWitness) -> io::Result<()> {
+        self.write_stage(PipelineStage::Decoding, decoding)
+    }
+
+    pub fn write_text_assembly(&mut self, text_assembly: &TextAssemblyWitness) -> io::Result<()> {
+        self.write_stage(PipelineStage::TextAssembly, text_assembly)
+    }
+
+    /**
+     * Writes the provenance of the stages, which comes after all of them.
+     */
+    pub fn write_provenance(&mut self, provenance: &WitnessProvenance) -> io::Result<()> {
+        if self.wrote_provenance {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "the provenance cannot be written twice",
+            ));
+        }
+        self.write_key("provenance")?;
+        self.write_value(provenance)?;
+        self.wrote_provenance = true;
+
+        Ok(())
+    }
+
+    /**
+     * Writes every stage `witness` holds, and its provenance.
+     */
+    pub fn write_witness(&mut self, witness: &PipelineWitness) -> io::Result<()> {
+        // cropped images are small, and written whole to keep their crop offset, as are
+        // redacted images to keep their mask
+        if let Some(luminance) = witness.luminance() {
+            if luminance.crop_offset.is_some() || luminance.redaction.is_some() {
+                self.write_stage(PipelineStage::Luminance, luminance)?;
+            } else {
+                let rows = luminance.image.chunks(luminance.width.max(1));
+                self.write_luminance(luminance.width, luminance.height, rows)?;
+            }
+        }
+        if let Some(binarization) = witness.binarization() {
+            if binarization.crop_offset.is_some() || binarization.redaction.is_some() {
+                self.write_stage(PipelineStage::Binarization, binarization)?;
+            } else {
+                self.write_binarization(&binarization.binarized_image, binarization.threshold)?;
+            }
+        }
+        if let Some(detection) = witness.detection() {
+            self.write_detection(detection)?;
+        }
+        if let Some(scanline) = witness.scanline() {
+            self.write_scanline(scanline)?;
+        }
+        if let Some(sampling) = witness.sampling() {
+            self.write_sampling(sampling)?;
+        }
+        if let Some(decoding) = witness.decoding() {
+            self.write_decoding(decoding)?;
+        }
+        if let Some(text_assembly) = witness.text_assembly() {
+            self.write_text_assembly(text_assembly)?;
+        }
+        if let Some(provenance) = witness.provenance() {
+            self.write_provenance(provenance)?;
+        }
+
+        Ok(())
+    }
+
+    /**
+     * Writes the end of the witness and flushes the writer.
+     *
+     * # Returns
+     * The underlying writer
+     */
+    pub fn finish(mut self) -> io::Result<W> {
+        match self.format {
+            WitnessFormat::Json => self.writer.write_all(b"}")?,
+            #[cfg(feature = "cbor")]
+            WitnessFormat::Cbor => self.writer.write_all(&[CBOR_BREAK])?,
+        }
+        self.writer.flush()?;
+
+        Ok(self.writer)
+    }
+
+    fn write_stage<T: serde::Serialize>(
+        &mut self,
+        stage: PipelineStage,
+        value: &T,
+    ) -> io::Result<()> {
+        self.begin_stage(stage)?;
+        self.write_value(value)
+    }
+
+    fn write_value<T: serde::Serialize>(&mut self, value: &T) -> io::Result<()> {
+        match self.format {
+            WitnessFormat::Json => serde_json::to_writer(&mut self.writer, value)?,
+            #[cfg(feature = "cbor")]
+            WitnessFormat::Cbor => {
+                ciborium::into_writer(value, &mut self.writer).map_err(|e| match e {
+                    ciborium::ser::Error::Io(e) => e,
+                    ciborium::ser::Error::Value(e) => io::Error::new(io::ErrorKind::InvalidData, e),
+                })?
+            }
+        }
+
+        Ok(())
+    }
+
+    // Checks that stages come in pipeline order, then writes the key of the stage
+    fn begin_stage(&mut self, stage: PipelineStage) -> io::Result<()> {
+        if self.wrote_provenance {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("the {stage:?} stage cannot be written after the provenance"),
+            ));
+        }
+        if let Some(last_stage) = self.last_stage {
+            if stage as usize <= last_stage as usize {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("the {stage:?} stage cannot be written after the {last_stage:?} stage"),
+                ));
+            }
+        }
+
+        let key = match stage {
+            PipelineStage::Luminance => "luminance",
+            PipelineStage::Binarization => "binarization",
+            PipelineStage::Detection => "detection",
+            PipelineStage::Scanline => "scanline",
+            PipelineStage::Sampling => "sampling",
+            PipelineStage::Decoding => "decoding",
+            PipelineStage::TextAssembly => "text_assembly",
+        };
+        self.write_key(key)?;
+        self.last_stage = Some(stage);
+
+        Ok(())
+    }
+
+    fn write_key(&mut self, key: &str) -> io::Result<()> {
+        match self.format {
+            WitnessFormat::Json => write!(self.writer, r#","{key}":"#),
+            #[cfg(feature = "cbor")]
+            WitnessFormat::Cbor => cbor_text(&mut self.writer, key),
+        }
+    }
+}
+
+fn check_row(row: &[u8], width: usize, y: usize, height: usize) -> io::Result<&[u8]> {
+    if y >= height {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("expected {height} rows, got more"),
+        ));
+    }
+    // sources may hand out rows longer than the image is wide
+    row.get(..width).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("row {y} has {} pixels, expected {width}", row.len()),
+        )
+    })
+}
+
+#[cfg(feature = "cbor")]
+const CBOR_UNSIGNED: u8 = 0;
+#[cfg(feature = "cbor")]
+const CBOR_BYTES: u8 = 2;
+#[cfg(feature = "cbor")]
+const CBOR_TEXT: u8 = 3;
+#[cfg(feature = "cbor")]
+const CBOR_MAP: u8 = 5;
+#[cfg(feature = "cbor")]
+const CBOR_INDEFINITE_BYTES: u8 = 0x5f;
+#[cfg(feature = "cbor")]
+const CBOR_INDEFINITE_MAP: u8 = 0xbf;
+#[cfg(feature = "cbor")]
+const CBOR_BREAK: u8 = 0xff;
+
+// Writes the head of a CBOR data item, its major type and its length or value
+#[cfg(feature = "cbor")]
+fn cbor_head<W: Write>(writer: &mut W, major: u8, value: u64) -> io::Result<()> {
+    let major = major << 5;
+    if value < 24 {
+        writer.write_all(&[major | value as u8])
+    } else if value <= u8::MAX as u64 {
+        writer.write_all(&[major | 24, value as u8])
+    } else if value <= u16::MAX as u64 {
+        writer.write_all(&[major | 25])?;
+        writer.write_all(&(value as u16).to_be_bytes())
+    } else if value <= u32::MAX as u64 {
+        writer.write_all(&[major | 26])?;
+        writer.write_all(&(value as u32).to_be_bytes())
+    } else {
+        writer.write_all(&[major | 27])?;
+        writer.write_all(&value.to_be_bytes())
+    }
+}
+
+#[cfg(feature = "cbor")]
+fn cbor_text<W: Write>(writer: &mut W, text: &str) -> io::Result<()> {
+    cbor_head(writer, CBOR_TEXT, text.len() as u64)?;
+    writer.write_all(text.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::{BinarizationWitness, DecodeHintValue, DecodeHints, LuminanceWitness};
+
+    // 13 pixels per row, so the packed bits of a row don't end on a byte boundary
+    fn witness() -> PipelineWitness {
+        let (width, height) = (13, 7);
+        let image = (0..width * height).map(|i| (i * 37 % 256) as u8).collect();
+        let luminance = LuminanceWitness::new(width, height, image);
+
+        let mut binarized_image = BitMatrix::new(width as u32, height as u32).unwrap();
+        for y in 0..height {
+            for x in 0..width {
+                if luminance.get_pixel(x, y) < 128 {
+                    binarized_image.set(x as u32, y as u32);
+                }
+            }
+        }
+
+        let mut decoding = CodewordWitness::new(8, vec![0x40, 0x11, 0xec, 0x11]);
+        decoding.corrected_codewords = vec![0x40, 0x11];
+        let hints = DecodeHints::default().with(DecodeHintValue::TryHarder(true));
+
+        PipelineWitness::new()
+            .with_luminance(luminance)
+            .with_binarization(BinarizationWitness::new(binarized_image).with_threshold(128))
+            .with_decoding(decoding)
+            .with_text_assembly(TextAssemblyWitness {
+                raw_bytes: vec![0x40, 0x11],
+                num_bits: 16,
+                byte_segments: Vec::new(),
+                text: "A".to_owned(),
+                symbology_identifier: None,
+            })
+            .with_provenance(WitnessProvenance::new().with_hints(&hints).with_timing(
+                PipelineStage::TextAssembly,
+                Duration::from_micros(1500),
+                Duration::from_micros(1800),
+            ))
+    }
+
+    #[test]
+    fn test_json_matches_pipeline_witness() {
+        let witness = witness();
+
+        let mut writer = WitnessWriter::new(Vec::new(), WitnessFormat::Json).unwrap();
+        writer.write_witness(&witness).unwrap();
+        let json = writer.finish().unwrap();
+
+        let streamed: serde_json::Value = serde_json::from_slice(&json).unwrap();
+        assert_eq!(streamed, serde_json::to_value(&witness).unwrap());
+    }
+
+    #[test]
+    fn test_json_empty_witness() {
+        let writer = WitnessWriter::new(Vec::new(), WitnessFormat::Json).unwrap();
+        assert_eq!(writer.finish().unwrap(), br#"{"version":2}"#);
+    }
+
+    #[cfg(feature = "cbor")]
+    #[test]
+    fn test_cbor_matches_pipeline_witness() {
+        let witness = witness();
+
+        let mut writer = WitnessWriter::new(Vec::new(), WitnessFormat::Cbor).unwrap();
+        writer.write_witness(&witness).unwrap();
+        let cbor = writer.finish().unwrap();
+
+        let streamed: ciborium::Value = ciborium::from_reader(cbor.as_slice()).unwrap();
+        let expected: ciborium::Value =
+            ciborium::from_reader(witness.to_cbor().unwrap().as_slice()).unwrap();
+        assert_eq!(streamed, expected);
+    }
+
+    #[test]
+    fn test_stages_out_of_order() {
+        let witness = witness();
+        let mut writer = WitnessWriter::new(Vec::new(), WitnessFormat::Json).unwrap();
+
+        writer
+            .write_binarization(&witness.binarization().unwrap().binarized_image, None)
+            .unwrap();
+        let luminance = witness.luminance().unwrap();
+        let err = writer
+            .write_luminance(
+                luminance.width,
+                luminance.height,
+                [luminance.image.as_slice()],
+            )
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        let err = writer
+            .write_binarization(&witness.binarization().unwrap().binarized_image, None)
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+
+        // the provenance comes last, and only once
+        let provenance = witness.provenance().unwrap();
+        writer.write_provenance(provenance).unwrap();
+        let err = writer.write_provenance(provenance).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        let err = writer
+            .write_text_assembly(witness.text_assembly().unwrap())
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn test_luminance_row_count() {
+        let mut writer = WitnessWriter::new(Vec::new(), WitnessFormat::Json).unwrap();
+        let rows = [[0u8; 4].as_slice(), [0u8; 3].as_slice()];
+        let err = writer.write_luminance(4, 2, rows).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+
+        let mut writer = WitnessWriter::new(Vec::new(), WitnessFormat::Json).unwrap();
+        let err = writer.write_luminance(4, 3, [[0u8; 4]; 2]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+}
@@ -0,0 +1,194 @@
+/*
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::{fmt, str::FromStr, sync::Arc};
+
+use crate::{
+    common::{BitMatrix, Result},
+    BarcodeFormat, EncodeHints, Exceptions, MultiFormatWriter, Writer,
+};
+
+/**
+ * Dispatches encode requests by format identifier, like [`MultiFormatWriter`], but also
+ * covers custom formats. Custom writers are registered under user-defined identifiers,
+ * such as the name of a proprietary label format; they receive
+ * `BarcodeFormat::UNSUPORTED_FORMAT` as their format.
+ *
+ * Identifiers naming a built-in format (as accepted by `BarcodeFormat::from_str`) are
+ * encoded by [`MultiFormatWriter`] and cannot be registered.
+ */
+#[derive(Clone, Default)]
+pub struct WriterRegistry {
+    writers: Vec<(String, Arc<dyn Writer + Send + Sync>)>,
+}
+
+impl WriterRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers `writer` as the encoder for the custom format `identifier`
+    pub fn register<W: Writer + Send + Sync + 'static>(
+        &mut self,
+        identifier: &str,
+        writer: W,
+    ) -> Result<()> {
+        if identifier.is_empty() {
+            return Err(Exceptions::illegal_argument_with(
+                "format identifier is empty",
+            ));
+        }
+        if BarcodeFormat::from_str(identifier).is_ok() {
+            return Err(Exceptions::illegal_argument_with(format!(
+                "{identifier} is a built-in format"
+            )));
+        }
+        if self.contains(identifier) {
+            return Err(Exceptions::illegal_argument_with(format!(
+                "a writer for {identifier} is already registered"
+            )));
+        }
+        self.writers.push((identifier.to_owned(), Arc::new(writer)));
+        Ok(())
+    }
+
+    /// Removes the writer registered under `identifier`, returning whether it was present
+    pub fn unregister(&mut self, identifier: &str) -> bool {
+        let len = self.writers.len();
+        self.writers.retain(|(id, _)| id != identifier);
+        self.writers.len() != len
+    }
+
+    pub fn contains(&self, identifier: &str) -> bool {
+        self.writers.iter().any(|(id, _)| id == identifier)
+    }
+
+    /// The identifiers of the registered custom formats, in registration order
+    pub fn identifiers(&self) -> Vec<&str> {
+        self.writers.iter().map(|(id, _)| id.as_str()).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.writers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.writers.is_empty()
+    }
+
+    pub fn encode(
+        &self,
+        contents: &str,
+        format: &str,
+        width: i32,
+        height: i32,
+    ) -> Result<BitMatrix> {
+        self.encode_with_hints(contents, format, width, height, &EncodeHints::default())
+    }
+
+    /**
+     * Encodes `contents` in the format named `format`, which is either a custom format
+     * identifier or the name of a built-in format.
+     */
+    pub fn encode_with_hints(
+        &self,
+        contents: &str,
+        format: &str,
+        width: i32,
+        height: i32,
+        hints: &EncodeHints,
+    ) -> Result<BitMatrix> {
+        if let Some((_, writer)) = self.writers.iter().find(|(id, _)| id == format) {
+            return writer.encode_with_hints(
+                contents,
+                &BarcodeFormat::UNSUPORTED_FORMAT,
+                width,
+                height,
+                hints,
+            );
+        }
+
+        let builtin = BarcodeFormat::from_str(format).map_err(|_| {
+            Exceptions::illegal_argument_with(format!("No encoder available for format {format}"))
+        })?;
+        MultiFormatWriter.encode_with_hints(contents, &builtin, width, height, hints)
+    }
+}
+
+impl fmt::Debug for WriterRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.identifiers()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        common::{BitMatrix, Result},
+        BarcodeFormat, EncodeHints, Writer, WriterRegistry,
+    };
+
+    /// A made up label format: one black module per character, in a single row
+    struct DotsWriter;
+
+    impl Writer for DotsWriter {
+        fn encode(
+            &self,
+            contents: &str,
+            format: &BarcodeFormat,
+            width: i32,
+            height: i32,
+        ) -> Result<BitMatrix> {
+            self.encode_with_hints(contents, format, width, height, &EncodeHints::default())
+        }
+
+        fn encode_with_hints(
+            &self,
+            contents: &str,
+            format: &BarcodeFormat,
+            _width: i32,
+            _height: i32,
+            _hints: &EncodeHints,
+        ) -> Result<BitMatrix> {
+            assert_eq!(&BarcodeFormat::UNSUPORTED_FORMAT, format);
+            let mut matrix = BitMatrix::new(contents.len() as u32 * 2, 1)?;
+            for i in 0..contents.len() as u32 {
+                matrix.set(i * 2, 0);
+            }
+            Ok(matrix)
+        }
+    }
+
+    #[test]
+    fn test_registry() {
+        let mut registry = WriterRegistry::new();
+        registry.register("dots", DotsWriter).unwrap();
+        assert!(registry.register("dots", DotsWriter).is_err());
+        assert!(registry.register("qrcode", DotsWriter).is_err());
+        assert!(registry.register("", DotsWriter).is_err());
+        assert_eq!(vec!["dots"], registry.identifiers());
+
+        let matrix = registry.encode("abc", "dots", 0, 0).unwrap();
+        assert_eq!(6, matrix.getWidth());
+        assert!(matrix.get(4, 0) && !matrix.get(5, 0));
+
+        // built-in formats are still dispatched
+        let matrix = registry.encode("abc", "qrcode", 21, 21).unwrap();
+        assert_eq!(29, matrix.getWidth());
+        assert!(registry.encode("abc", "stripes", 0, 0).is_err());
+
+        assert!(registry.unregister("dots"));
+        assert!(registry.encode("abc", "dots", 0, 0).is_err());
+    }
+}
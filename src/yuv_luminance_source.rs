@@ -0,0 +1,282 @@
+/*
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::borrow::Cow;
+
+use crate::common::Result;
+use crate::{Exceptions, LuminanceSource};
+
+/// How the planes of a 4:2:0 YUV frame are laid out, each starting with the full size Y plane
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum YUVFormat {
+    /// The Y plane, then a plane of interleaved U and V at half the width and height
+    Nv12,
+    /// The Y plane, then a plane of interleaved V and U at half the width and height, as
+    /// Android's camera preview delivers it
+    Nv21,
+    /// The Y plane, then the U and the V plane at half the width and height, also known as
+    /// YUV420p
+    I420,
+}
+
+impl YUVFormat {
+    /**
+     * The size in bytes of a frame of `height` rows whose Y rows are `stride` bytes apart. The
+     * rows of the interleaved chroma plane of NV12 and NV21 are `stride` bytes apart as well,
+     * those of the U and V planes of I420 half of it.
+     */
+    pub fn frame_len(&self, height: usize, stride: usize) -> usize {
+        let chroma_rows = height.div_ceil(2);
+        let chroma = match self {
+            YUVFormat::Nv12 | YUVFormat::Nv21 => stride * chroma_rows,
+            YUVFormat::I420 => 2 * stride.div_ceil(2) * chroma_rows,
+        };
+        stride * height + chroma
+    }
+}
+
+/**
+ * A luminance source reading the Y plane of an NV12, NV21 or I420 frame where it lies, such as
+ * a buffer from a camera, without converting it or copying the frame. Rows are borrowed from
+ * the frame, and columns and the matrix copy only the pixels of the (cropped) image.
+ *
+ * The frame can be any `AsRef<[u8]>`. Cropping clones it, which costs nothing for a `&[u8]` or
+ * an `Arc<[u8]>`.
+ */
+#[derive(Debug, Clone)]
+pub struct YUVLuminanceSource<D = Vec<u8>> {
+    data: D,
+    format: YUVFormat,
+    stride: usize,
+    left: usize,
+    top: usize,
+    width: usize,
+    height: usize,
+    invert: bool,
+}
+
+impl<D: AsRef<[u8]> + Clone> YUVLuminanceSource<D> {
+    /**
+     * Wraps a frame whose rows are `width` bytes long.
+     *
+     * # Errors
+     * `IllegalArgumentException` if `data` is shorter than a `width` by `height` frame of
+     * `format`
+     */
+    pub fn new(data: D, format: YUVFormat, width: usize, height: usize) -> Result<Self> {
+        Self::with_stride(data, format, width, height, width)
+    }
+
+    /**
+     * Wraps a frame whose rows are `stride` bytes apart, the bytes after the first `width` of
+     * each being padding.
+     *
+     * # Errors
+     * `IllegalArgumentException` if `stride` is less than `width`, or if `data` is shorter than
+     * `format.frame_len(height, stride)`
+     */
+    pub fn with_stride(
+        data: D,
+        format: YUVFormat,
+        width: usize,
+        height: usize,
+        stride: usize,
+    ) -> Result<Self> {
+        if stride < width {
+            return Err(Exceptions::illegal_argument_with(format!(
+                "a stride of {stride} is less than the width of {width}"
+            )));
+        }
+        let frame_len = format.frame_len(height, stride);
+        if data.as_ref().len() < frame_len {
+            return Err(Exceptions::illegal_argument_with(format!(
+                "a {width}x{height} {format:?} frame with a stride of {stride} needs {frame_len} bytes, got {}",
+                data.as_ref().len()
+            )));
+        }
+
+        Ok(Self {
+            data,
+            format,
+            stride,
+            left: 0,
+            top: 0,
+            width,
+            height,
+            invert: false,
+        })
+    }
+
+    pub fn get_format(&self) -> YUVFormat {
+        self.format
+    }
+
+    pub fn get_stride(&self) -> usize {
+        self.stride
+    }
+
+    /// The offset of the first pixel of row `y` in the frame
+    fn offset(&self, y: usize) -> usize {
+        (self.top + y) * self.stride + self.left
+    }
+
+    fn invert_if_should(&self, luminance: u8) -> u8 {
+        if self.invert {
+            255 - luminance
+        } else {
+            luminance
+        }
+    }
+}
+
+impl<D: AsRef<[u8]> + Clone> LuminanceSource for YUVLuminanceSource<D> {
+    const SUPPORTS_CROP: bool = true;
+
+    fn get_row(&self, y: usize) -> Option<Cow<'_, [u8]>> {
+        if y >= self.height {
+            return None;
+        }
+        let offset = self.offset(y);
+        let row = &self.data.as_ref()[offset..offset + self.width];
+        if self.invert {
+            Some(Cow::Owned(self.invert_block_of_bytes(row.to_vec())))
+        } else {
+            Some(Cow::Borrowed(row))
+        }
+    }
+
+    fn get_column(&self, x: usize) -> Vec<u8> {
+        let data = self.data.as_ref();
+        (0..self.height)
+            .map(|y| self.invert_if_should(data[self.offset(y) + x]))
+            .collect()
+    }
+
+    fn get_matrix(&self) -> Vec<u8> {
+        let data = self.data.as_ref();
+        let mut matrix = Vec::with_capacity(self.width * self.height);
+        for y in 0..self.height {
+            let offset = self.offset(y);
+            matrix.extend_from_slice(&data[offset..offset + self.width]);
+        }
+        if self.invert {
+            matrix = self.invert_block_of_bytes(matrix);
+        }
+        matrix
+    }
+
+    fn get_width(&self) -> usize {
+        self.width
+    }
+
+    fn get_height(&self) -> usize {
+        self.height
+    }
+
+    fn crop(&self, left: usize, top: usize, width: usize, height: usize) -> Result<Self> {
+        if left + width > self.width || top + height > self.height {
+            return Err(Exceptions::illegal_argument_with(
+                "Crop rectangle does not fit within image data.",
+            ));
+        }
+        Ok(Self {
+            data: self.data.clone(),
+            left: self.left + left,
+            top: self.top + top,
+            width,
+            height,
+            ..*self
+        })
+    }
+
+    fn invert(&mut self) {
+        self.invert = !self.invert;
+    }
+
+    fn get_luma8_point(&self, x: usize, y: usize) -> u8 {
+        self.invert_if_should(self.data.as_ref()[self.offset(y) + x])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        common::HybridBinarizer, qrcode::QRCodeReader, BarcodeFormat, BinaryBitmap,
+        LuminanceSource, MultiFormatWriter, Reader, Writer,
+    };
+
+    use super::{YUVFormat, YUVLuminanceSource};
+
+    #[test]
+    fn test_frame_len() {
+        assert_eq!(6 * 4 + 6 * 2, YUVFormat::Nv12.frame_len(4, 6));
+        assert_eq!(6 * 5 + 6 * 3, YUVFormat::Nv21.frame_len(5, 6));
+        assert_eq!(7 * 5 + 2 * 4 * 3, YUVFormat::I420.frame_len(5, 7));
+    }
+
+    #[test]
+    fn test_rows_columns_and_crop() {
+        // a 3x2 image in rows of 4 bytes, then chroma
+        let data: Vec<u8> = vec![1, 2, 3, 0, 4, 5, 6, 0, 128, 128, 128, 128];
+        let source =
+            YUVLuminanceSource::with_stride(&data[..], YUVFormat::Nv12, 3, 2, 4).expect("frame");
+        assert_eq!(&[4, 5, 6], &source.get_row(1).unwrap()[..]);
+        assert!(source.get_row(2).is_none());
+        assert_eq!(vec![2, 5], source.get_column(1));
+        assert_eq!(vec![1, 2, 3, 4, 5, 6], source.get_matrix());
+        assert_eq!(6, source.get_luma8_point(2, 1));
+
+        let cropped = source.crop(1, 1, 2, 1).expect("crop");
+        assert_eq!(vec![5, 6], cropped.get_matrix());
+        assert_eq!(vec![6], cropped.get_column(1));
+        assert!(source.crop(2, 0, 2, 1).is_err());
+
+        let mut inverted = cropped;
+        inverted.invert();
+        assert_eq!(&[250, 249], &inverted.get_row(0).unwrap()[..]);
+        assert_eq!(250, inverted.get_luma8_point(0, 0));
+
+        assert!(YUVLuminanceSource::with_stride(&data[..], YUVFormat::Nv12, 5, 2, 4).is_err());
+        assert!(YUVLuminanceSource::new(&data[..], YUVFormat::I420, 5, 2).is_err());
+    }
+
+    #[test]
+    fn test_decode_padded_i420() {
+        let matrix = MultiFormatWriter
+            .encode("I420 frame", &BarcodeFormat::QR_CODE, 120, 120)
+            .expect("encode");
+        let (width, height) = (matrix.getWidth() as usize, matrix.getHeight() as usize);
+        let stride = width + 8;
+        let mut frame = vec![0x80; YUVFormat::I420.frame_len(height, stride)];
+        for y in 0..height {
+            for x in 0..stride {
+                frame[y * stride + x] = if x < width && matrix.get(x as u32, y as u32) {
+                    16
+                } else {
+                    235
+                };
+            }
+        }
+        let frame: Arc<[u8]> = frame.into();
+
+        let source = YUVLuminanceSource::with_stride(frame, YUVFormat::I420, width, height, stride)
+            .expect("frame");
+        let result = QRCodeReader
+            .decode(&mut BinaryBitmap::new(HybridBinarizer::new(source)))
+            .expect("decode");
+        assert_eq!("I420 frame", result.getText());
+    }
+}
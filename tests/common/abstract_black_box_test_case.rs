@@ -102,6 +102,31 @@ impl<T: Reader> AbstractBlackBoxTestCase<T> {
             DecodeHintValue::AllowedEanExtensions(v) => self.hints.AllowedEanExtensions = Some(v),
             DecodeHintValue::AlsoInverted(v) => self.hints.AlsoInverted = Some(v),
             DecodeHintValue::TelepenAsNumeric(v) => self.hints.TelepenAsNumeric = Some(v),
+            DecodeHintValue::Code39CheckDigit(v) => self.hints.Code39CheckDigit = Some(v),
+            DecodeHintValue::CodabarCheckDigit(v) => self.hints.CodabarCheckDigit = Some(v),
+            DecodeHintValue::ITFCheckDigit(v) => self.hints.ITFCheckDigit = Some(v),
+            DecodeHintValue::Strictness(v) => self.hints.Strictness = Some(v),
+            DecodeHintValue::ExpandUPCE(v) => self.hints.ExpandUPCE = Some(v),
+            DecodeHintValue::ITFLengths(v) => self.hints.ITFLengths = Some(v),
+            DecodeHintValue::Code39ExtendedMode(v) => self.hints.Code39ExtendedMode = Some(v),
+            DecodeHintValue::Deterministic(v) => self.hints.Deterministic = Some(v),
+            DecodeHintValue::WitnessSink(v) => self.hints.WitnessSink = Some(v),
+            DecodeHintValue::WitnessRecorder(v) => self.hints.WitnessRecorder = Some(v),
+            DecodeHintValue::WitnessCropMargin(v) => self.hints.WitnessCropMargin = Some(v),
+            DecodeHintValue::FixedPointSampling(v) => self.hints.FixedPointSampling = Some(v),
+            DecodeHintValue::PharmacodeTwoTrack(v) => self.hints.PharmacodeTwoTrack = Some(v),
+            DecodeHintValue::PharmacodeMinBars(v) => self.hints.PharmacodeMinBars = Some(v),
+            DecodeHintValue::PharmacodeMaxBars(v) => self.hints.PharmacodeMaxBars = Some(v),
+            DecodeHintValue::MSICheckDigit(v) => self.hints.MSICheckDigit = Some(v),
+            DecodeHintValue::MSIChecksum(v) => self.hints.MSIChecksum = Some(v),
+            DecodeHintValue::Cancellation(v) => self.hints.Cancellation = Some(v),
+            DecodeHintValue::TryDownscale(v) => self.hints.TryDownscale = Some(v),
+            DecodeHintValue::OneDRowSampling(v) => self.hints.OneDRowSampling = Some(v),
+            DecodeHintValue::TryPreprocessing(v) => self.hints.TryPreprocessing = Some(v),
+            DecodeHintValue::AssumeNoQuietZone(v) => self.hints.AssumeNoQuietZone = Some(v),
+            DecodeHintValue::AllowPartialSymbol(v) => self.hints.AllowPartialSymbol = Some(v),
+            DecodeHintValue::GridSampling(v) => self.hints.GridSampling = Some(v),
+            DecodeHintValue::TryCurved(v) => self.hints.TryCurved = Some(v),
             #[cfg(feature = "allow_forced_iso_ied_18004_compliance")]
             DecodeHintValue::QrAssumeSpecConformInput(v) => {
                 self.hints.QrAssumeSpecConformInput = Some(v)
@@ -242,6 +267,23 @@ impl<T: Reader> AbstractBlackBoxTestCase<T> {
                     RXingResultMetadataType::UPC_EAN_EXTENSION => {
                         RXingResultMetadataValue::UpcEanExtension(v)
                     }
+                    RXingResultMetadataType::UPC_EAN_EXTENSION_POSITION => {
+                        let arr: Box<[usize]> = v
+                            .split(",")
+                            .map(|str_source| str_source.parse::<usize>().unwrap_or_default())
+                            .take(2)
+                            .collect();
+                        RXingResultMetadataValue::UpcEanExtensionPosition((arr[0], arr[1]))
+                    }
+                    RXingResultMetadataType::RAW_TEXT => RXingResultMetadataValue::RawText(v),
+                    RXingResultMetadataType::MACRO_ENVELOPE => {
+                        RXingResultMetadataValue::MacroEnvelope(
+                            rxing::datamatrix::MacroEnvelope::parse(&v).unwrap(),
+                        )
+                    }
+                    RXingResultMetadataType::READER_PROGRAMMING => {
+                        RXingResultMetadataValue::ReaderProgramming(v.parse().unwrap())
+                    }
                     RXingResultMetadataType::PDF417_EXTRA_METADATA => {
                         RXingResultMetadataValue::Pdf417ExtraMetadata(Arc::new(
                             PDF417RXingResultMetadata::default(),
@@ -257,6 +299,9 @@ impl<T: Reader> AbstractBlackBoxTestCase<T> {
                             v.parse().unwrap_or_default(),
                         )
                     }
+                    RXingResultMetadataType::STRUCTURED_APPEND_ID => {
+                        RXingResultMetadataValue::StructuredAppendId(v)
+                    }
                     RXingResultMetadataType::SYMBOLOGY_IDENTIFIER => {
                         RXingResultMetadataValue::SymbologyIdentifier(v)
                     }
@@ -281,6 +326,62 @@ impl<T: Reader> AbstractBlackBoxTestCase<T> {
                             .collect();
                         RXingResultMetadataValue::FilteredResolution((arr[0], arr[1]))
                     }
+                    RXingResultMetadataType::SAMPLING_GRID => {
+                        RXingResultMetadataValue::SamplingGrid(rxing::common::SamplingGrid::new(
+                            0,
+                            0,
+                            Vec::new(),
+                        ))
+                    }
+                    RXingResultMetadataType::CODEWORDS => RXingResultMetadataValue::Codewords(
+                        rxing::common::CodewordWitness::new(0, Vec::new()),
+                    ),
+                    RXingResultMetadataType::SCANLINE => {
+                        RXingResultMetadataValue::Scanline(rxing::oned::ScanlineWitness::new(
+                            rxing::oned::ScanlinePattern {
+                                start: 0,
+                                counters: Vec::new(),
+                                value: None,
+                            },
+                            Vec::new(),
+                            rxing::oned::ScanlinePattern {
+                                start: 0,
+                                counters: Vec::new(),
+                                value: None,
+                            },
+                        ))
+                    }
+                    RXingResultMetadataType::ERRORS_CORRECTED => {
+                        RXingResultMetadataValue::ErrorsCorrected(v.parse().unwrap_or_default())
+                    }
+                    RXingResultMetadataType::ERASURES_CORRECTED => {
+                        RXingResultMetadataValue::ErasuresCorrected(v.parse().unwrap_or_default())
+                    }
+                    RXingResultMetadataType::CONFIDENCE => {
+                        RXingResultMetadataValue::Confidence(rxing::DecodeConfidence {
+                            score: v.parse().unwrap_or_default(),
+                            correction_load: None,
+                            finder_fit_error: None,
+                            module_size_consistency: None,
+                        })
+                    }
+                    RXingResultMetadataType::AZTEC_RUNE => {
+                        RXingResultMetadataValue::AztecRune(v.parse().unwrap_or_default())
+                    }
+                    RXingResultMetadataType::POSE => {
+                        RXingResultMetadataValue::Pose(rxing::SymbolPose {
+                            rotation: v.parse().unwrap_or_default(),
+                            module_size: 0.0,
+                            transform: Default::default(),
+                        })
+                    }
+                    RXingResultMetadataType::SYMBOL_VERSION => {
+                        RXingResultMetadataValue::SymbolVersion(rxing::SymbolVersion::QRCode {
+                            version: v.parse().unwrap_or_default(),
+                            mask: 0,
+                            model: 2,
+                        })
+                    }
                 };
                 expected_metadata.insert(new_k, new_v);
             }
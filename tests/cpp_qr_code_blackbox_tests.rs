@@ -349,4 +349,31 @@ fn cpp_rmqr_blackbox_test_case() {
     tester.test_black_box();
 }
 
+#[cfg(feature = "image_formats")]
+#[test]
+fn cpp_rmqr_only_blackbox_test_case() {
+    let mut tester = common::AbstractBlackBoxTestCase::new(
+        "test_resources/blackbox/cpp/rmqrcode-1",
+        MultiUseMultiFormatReader::default(),
+        BarcodeFormat::RECTANGULAR_MICRO_QR_CODE,
+    );
+    tester.add_test(3, 3, 0.0);
+    tester.add_test(3, 3, 90.0);
+    tester.add_test(3, 3, 180.0);
+    tester.add_test(3, 3, 270.0);
+
+    tester.add_hint(
+        rxing::DecodeHintType::POSSIBLE_FORMATS,
+        rxing::DecodeHintValue::PossibleFormats(std::collections::HashSet::from([
+            BarcodeFormat::RECTANGULAR_MICRO_QR_CODE,
+        ])),
+    );
+    tester.add_hint(
+        rxing::DecodeHintType::ALSO_INVERTED,
+        rxing::DecodeHintValue::AlsoInverted(true),
+    );
+
+    tester.test_black_box();
+}
+
 //